@@ -0,0 +1,234 @@
+//! API key authentication and dataset visibility
+//!
+//! Keys are configured in a TOML file (`GEORAG_AUTH_FILE`):
+//!
+//! ```toml
+//! [[api_keys]]
+//! name = "partner-portal"
+//! key = "..."
+//! allowed_tags = ["public"]
+//!
+//! [[api_keys]]
+//! name = "city-team"
+//! key = "..."
+//! workspaces = ["city"]
+//!
+//! [[api_keys]]
+//! name = "internal"
+//! key = "..."
+//! ```
+//!
+//! A key without `allowed_tags` sees every dataset. A key with `allowed_tags`
+//! sees untagged datasets and datasets whose tags are all allowed. A key with
+//! `workspaces` may only use the workspaces listed by name or ID; without it
+//! every workspace. Without an auth file the API is open and every caller is
+//! unrestricted.
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::{normalize_tags, TagVisibility, WorkspaceMeta};
+use serde::Deserialize;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Header accepted as an alternative to `Authorization: Bearer <key>`
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// A configured API key
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKey {
+    /// Name used in logs
+    pub name: String,
+
+    /// Secret sent by the client
+    pub key: String,
+
+    /// Dataset tags this key may see; absent means unrestricted
+    #[serde(default)]
+    pub allowed_tags: Option<Vec<String>>,
+
+    /// Names or IDs of the workspaces this key may use; absent means all
+    #[serde(default)]
+    pub workspaces: Option<Vec<String>>,
+}
+
+impl ApiKey {
+    /// Visibility granted to requests made with this key
+    pub fn visibility(&self) -> TagVisibility {
+        match &self.allowed_tags {
+            Some(tags) => TagVisibility::only(normalize_tags(tags)),
+            None => TagVisibility::All,
+        }
+    }
+}
+
+/// API key configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+}
+
+impl AuthConfig {
+    /// Load API keys from a TOML file
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let config: AuthConfig =
+            toml::from_str(&content).map_err(|e| GeoragError::ConfigInvalid {
+                key: "api_keys".to_string(),
+                reason: format!("Failed to parse TOML: {}", e),
+            })?;
+
+        if let Some(key) = config.api_keys.iter().find(|k| k.key.trim().is_empty()) {
+            return Err(GeoragError::ConfigInvalid {
+                key: "api_keys".to_string(),
+                reason: format!("API key '{}' has an empty key", key.name),
+            });
+        }
+
+        if let Some(key) = config
+            .api_keys
+            .iter()
+            .find(|k| k.workspaces.as_ref().is_some_and(|w| w.iter().all(|w| w.trim().is_empty())))
+        {
+            return Err(GeoragError::ConfigInvalid {
+                key: "api_keys".to_string(),
+                reason: format!("API key '{}' is bound to no workspace", key.name),
+            });
+        }
+
+        Ok(config)
+    }
+
+    /// Check if authentication is enforced
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty()
+    }
+
+    /// Find the key matching a presented secret
+    pub fn find(&self, presented: &str) -> Option<&ApiKey> {
+        self.api_keys.iter().find(|k| k.key == presented)
+    }
+}
+
+/// The authenticated caller of a request
+#[derive(Debug, Clone)]
+pub struct Caller {
+    /// Name of the API key, if authentication is enabled
+    pub key_name: Option<String>,
+
+    /// Datasets the caller may see
+    pub visibility: TagVisibility,
+
+    /// Names or IDs of the workspaces the caller may use; `None` means all
+    pub workspaces: Option<Vec<String>>,
+}
+
+impl Caller {
+    /// Caller used when authentication is disabled
+    pub fn unrestricted() -> Self {
+        Self {
+            key_name: None,
+            visibility: TagVisibility::All,
+            workspaces: None,
+        }
+    }
+
+    /// Check if the caller may use a workspace
+    pub fn allows_workspace(&self, workspace: &WorkspaceMeta) -> bool {
+        match &self.workspaces {
+            Some(allowed) => allowed
+                .iter()
+                .map(|w| w.trim())
+                .any(|w| w == workspace.name || w == workspace.id.to_string()),
+            None => true,
+        }
+    }
+
+    /// Reject callers that may not use a workspace
+    pub fn require_workspace(
+        &self,
+        workspace: &WorkspaceMeta,
+    ) -> std::result::Result<(), ApiError> {
+        if !self.allows_workspace(workspace) {
+            return Err(ApiError::forbidden(format!(
+                "API key is not allowed to use workspace '{}'",
+                workspace.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reject callers bound to some workspaces
+    ///
+    /// Used by endpoints acting across workspaces.
+    pub fn require_all_workspaces(&self) -> std::result::Result<(), ApiError> {
+        if self.workspaces.is_some() {
+            return Err(ApiError::forbidden("API key is bound to specific workspaces"));
+        }
+        Ok(())
+    }
+
+    /// Reject callers that cannot see every dataset
+    ///
+    /// Used by endpoints that change dataset visibility.
+    pub fn require_unrestricted(&self) -> std::result::Result<(), ApiError> {
+        if self.visibility.is_restricted() {
+            return Err(ApiError::forbidden("API key is restricted to tagged datasets"));
+        }
+        Ok(())
+    }
+}
+
+/// Resolve the caller from the request's API key and attach it as an extension
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let caller = if state.auth.is_enabled() {
+        let Some(presented) = presented_key(request.headers()) else {
+            return ApiError::unauthorized("Missing API key")
+                .with_details("Send the key as 'Authorization: Bearer <key>' or 'X-Api-Key'")
+                .into_response();
+        };
+
+        let Some(key) = state.auth.find(presented) else {
+            return ApiError::unauthorized("Invalid API key").into_response();
+        };
+
+        Caller {
+            key_name: Some(key.name.clone()),
+            visibility: key.visibility(),
+            workspaces: key.workspaces.clone(),
+        }
+    } else {
+        Caller::unrestricted()
+    };
+
+    request.extensions_mut().insert(caller);
+    next.run(request).await
+}
+
+/// Extract the API key from the Authorization or X-Api-Key header
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    bearer
+        .or_else(|| headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
@@ -1,19 +1,207 @@
+use georag_core::config::{
+    format_quota, mask_config_value, parse_axis_order, parse_bool, parse_confidence_cutoffs,
+    parse_default_radius, parse_dimension_check, parse_distance_unit, parse_ingest_batch_size,
+    parse_ingest_channel_capacity, parse_map_tile_url, parse_max_feature_errors,
+    parse_max_feature_vertices, parse_max_filter_vertices, parse_max_sample,
+    parse_max_source_bytes, parse_min_score, parse_oversized_features, parse_property_list,
+    parse_quota, parse_source_url_template, parse_spatial_predicate, parse_validity_mode,
+    parse_z_coordinates, ConfigSource,
+};
+use georag_core::error::{GeoragError, Result};
+use georag_core::formats::{IngestBuffers, ReadPolicy, DEFAULT_MAX_FEATURE_ERRORS};
+use georag_core::geo::{
+    FeatureLimits, GeometryLimits, PointQueryDefaults, ZCoordinates, DEFAULT_MAX_SAMPLE,
+};
+use georag_core::llm::factory::DEFAULT_OLLAMA_URL;
+use georag_core::llm::{
+    create_embedder, AnyEmbedder, DimensionCheck, DimensionCheckMode, EmbedderOptions,
+    OllamaGenerator, RequestPolicy,
+};
+use georag_core::models::{
+    AxisOrder, ConfidenceCutoffs, EventRetention, IndexState, SourceUrlTemplate, ValidityMode,
+    WorkspaceQuotas,
+};
+use georag_core::resources::{limit_variable, parse_resource_limit, ResourceKind, ResourceLimits};
+use georag_retrieval::rerank::{
+    DEFAULT_RERANK_CONCURRENCY, DEFAULT_RERANK_MODEL, DEFAULT_RERANK_POOL, MAX_RERANK_POOL,
+};
+use georag_retrieval::{Basemap, LlmReranker, RerankMode};
+use georag_service::remote::DEFAULT_MAX_DOWNLOAD_BYTES;
+use georag_service::{DownloadPolicy, SourcePolicy, DEFAULT_MAX_PAGE_SIZE, DEFAULT_PAGE_TTL};
+use std::collections::BTreeMap;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::health::HealthPolicy;
+use crate::state::DEFAULT_WORKSPACE;
 
 /// API server configuration loaded from environment variables
 #[derive(Debug, Clone)]
 pub struct ApiConfig {
     pub port: u16,
+    /// Origins allowed by CORS, comma-separated
     pub cors_origin: String,
+    /// TOML file read for values the environment does not set
+    pub config_file: Option<PathBuf>,
+    /// Workspace served by routes that do not select one
+    pub default_workspace: String,
     pub database_url: Option<String>,
     pub embedder: EmbedderConfig,
+    pub query: QueryConfig,
+    pub redaction_file: Option<PathBuf>,
+    /// TOML file with API keys and their allowed dataset tags
+    pub auth_file: Option<PathBuf>,
+    /// Offline bundle served read-only instead of the database
+    pub bundle_file: Option<PathBuf>,
+    /// Directory for the original files of uploads, instead of the storage backend
+    pub blob_dir: Option<PathBuf>,
+    /// Feature properties copied into chunk metadata when an index is built
+    pub chunk_properties: Vec<String>,
+    /// How uploads with unreadable features are handled
+    pub read_policy: ReadPolicy,
+    /// Axis order assumed for uploads that do not set one
+    pub axis_order: AxisOrder,
+    /// Which original upload files are kept for download
+    pub source_policy: SourcePolicy,
+    /// Limits of datasets ingested from URLs; a size limit of 0 refuses URLs
+    pub download_policy: DownloadPolicy,
+    /// Vertex limit for uploaded features and the handling of larger ones
+    pub feature_limits: FeatureLimits,
+    /// Whether the Z ordinates of uploaded features are kept
+    pub z_coordinates: ZCoordinates,
+    /// Batch size and channel capacity of the ingest pipeline
+    pub pipeline_buffers: IngestBuffers,
+    /// Quotas of workspaces whose settings leave them unset
+    pub quotas: WorkspaceQuotas,
+    /// Health checks of a PostgreSQL backend
+    pub health: HealthPolicy,
+    /// Bundle serving queries while the PostgreSQL backend is down
+    pub fallback_bundle: Option<PathBuf>,
+    /// Caps on concurrent ingests, builds, embedding batches and large exports
+    pub resource_limits: ResourceLimits,
+    /// How many change events are kept, and for how long
+    pub event_retention: EventRetention,
+    /// Where each value came from, keyed like `inspection_map`
+    pub sources: ConfigSources,
+}
+
+/// Sources of the values in an `ApiConfig`
+///
+/// A value counts as set from the environment or the configuration file only
+/// when it was present there and parsed; otherwise the default was used.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources {
+    sources: BTreeMap<String, ConfigSource>,
+    /// Tables of the configuration file, keyed by section
+    file: toml::Table,
+    /// Keys of the configuration file whose values failed to parse
+    invalid: Vec<String>,
+}
+
+impl ConfigSources {
+    /// Sources reading values missing from the environment from a TOML file
+    ///
+    /// The file has a table per section, e.g. `[quota]` with `max_datasets`.
+    fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let file = content.parse::<toml::Table>().map_err(|e| GeoragError::ConfigInvalid {
+            key: "server.config_file".to_string(),
+            reason: format!("Failed to parse {}: {}", path.display(), e),
+        })?;
+        Ok(Self { file, ..Default::default() })
+    }
+
+    /// Read and parse an environment variable, then the file's value, recording the source
+    fn read<T>(&mut self, key: &str, var: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+        if let Some(value) = env::var(var).ok().and_then(|v| parse(&v)) {
+            self.sources.insert(key.to_string(), ConfigSource::Environment);
+            return Some(value);
+        }
+
+        let value = self.file_value(key).map(|v| (parse(&v), v));
+        let (source, value) = match value {
+            Some((Some(value), _)) => (ConfigSource::File, Some(value)),
+            Some((None, _)) => {
+                self.invalid.push(key.to_string());
+                (ConfigSource::Default, None)
+            }
+            None => (ConfigSource::Default, None),
+        };
+        self.sources.insert(key.to_string(), source);
+        value
+    }
+
+    /// Value of a key in the configuration file, as an environment variable would hold it
+    ///
+    /// Arrays become comma-separated lists.
+    fn file_value(&self, key: &str) -> Option<String> {
+        let (section, name) = key.split_once('.')?;
+        let value = self.file.get(section)?.as_table()?.get(name)?;
+        Some(match value {
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            other => other.to_string(),
+        })
+    }
+
+    /// Check every value of the configuration file was known and parsed
+    fn validate_file(&self) -> Result<()> {
+        if let Some(key) = self.invalid.first() {
+            return Err(GeoragError::ConfigInvalid {
+                key: key.clone(),
+                reason: "Invalid value in the configuration file".to_string(),
+            });
+        }
+        for (section, table) in &self.file {
+            let Some(table) = table.as_table() else {
+                return Err(GeoragError::ConfigInvalid {
+                    key: section.clone(),
+                    reason: "Expected a table of settings".to_string(),
+                });
+            };
+            for name in table.keys() {
+                let key = format!("{}.{}", section, name);
+                if !self.sources.contains_key(&key) {
+                    return Err(GeoragError::ConfigInvalid {
+                        key,
+                        reason: "Unknown setting in the configuration file".to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Source of a value; unknown keys are defaults
+    pub fn get(&self, key: &str) -> ConfigSource {
+        self.sources.get(key).copied().unwrap_or(ConfigSource::Default)
+    }
 }
 
 /// Embedder configuration
 #[derive(Debug, Clone)]
 pub struct EmbedderConfig {
+    /// Embedder string, e.g. `nomic-embed-text` or `mock:768`
     pub model: String,
     pub dimensions: usize,
+    /// Pull the model through Ollama when it is not installed
+    pub auto_pull: bool,
+    /// Other models a query may select with `embedder_model`
+    pub allowed_models: Vec<AllowedEmbedder>,
+    /// Ollama base URL
+    pub ollama_url: String,
+    /// Timeout and retries of each Ollama request, also used by `llm` reranking
+    pub request_policy: RequestPolicy,
+    /// Whether disagreeing embedding dimensions stop the server
+    pub dimension_check: DimensionCheckMode,
 }
 
 impl Default for EmbedderConfig {
@@ -21,34 +209,735 @@ impl Default for EmbedderConfig {
         Self {
             model: "nomic-embed-text".to_string(),
             dimensions: 768,
+            auto_pull: false,
+            allowed_models: Vec::new(),
+            ollama_url: DEFAULT_OLLAMA_URL.to_string(),
+            request_policy: RequestPolicy::default(),
+            dimension_check: DimensionCheckMode::default(),
+        }
+    }
+}
+
+impl EmbedderConfig {
+    /// Look up a model a request may use; the configured model is always allowed
+    pub fn allowed(&self, model: &str) -> Option<AllowedEmbedder> {
+        if model == self.model {
+            return Some(AllowedEmbedder {
+                model: self.model.clone(),
+                dimensions: self.dimensions,
+            });
         }
+        self.allowed_models.iter().find(|allowed| allowed.model == model).cloned()
+    }
+
+    /// The configured model as an allowed embedder
+    pub fn configured(&self) -> AllowedEmbedder {
+        AllowedEmbedder {
+            model: self.model.clone(),
+            dimensions: self.dimensions,
+        }
+    }
+
+    /// Create the embedder for an allowed model
+    pub fn create(&self, model: &AllowedEmbedder) -> Result<AnyEmbedder> {
+        let options = EmbedderOptions::default()
+            .with_base_url(&self.ollama_url)
+            .with_auto_pull(self.auto_pull)
+            .with_dimensions(model.dimensions)
+            .with_request_policy(self.request_policy);
+        create_embedder(&model.model, &options)
+    }
+
+    /// Compare the configured dimensions with the model and its index
+    ///
+    /// The model is probed with one embedding, without pulling it; when it
+    /// cannot be probed only the index is compared. A mismatch is an error
+    /// unless the dimension check only warns, in which case it is logged.
+    pub async fn reconcile(&self, index: Option<&IndexState>) -> Result<DimensionCheck> {
+        let embedder = create_embedder(
+            &self.model,
+            &EmbedderOptions::default()
+                .with_base_url(&self.ollama_url)
+                .with_dimensions(self.dimensions)
+                .with_request_policy(self.request_policy),
+        )?;
+        let setting = "GEORAG_EMBEDDER_DIM";
+        let check = match DimensionCheck::run(&embedder, setting, index).await {
+            Ok(check) => check,
+            Err(e) => {
+                tracing::warn!(model = %self.model, "Embedding dimensions not probed: {}", e);
+                DimensionCheck::unprobed(&embedder, setting, index)
+            }
+        };
+        if let Some(warning) = check.enforce(self.dimension_check)? {
+            tracing::warn!(model = %self.model, "{}", warning);
+        }
+        Ok(check)
+    }
+
+    /// Names of every model a request may use, the configured one first
+    pub fn allowed_names(&self) -> Vec<String> {
+        std::iter::once(self.model.clone())
+            .chain(self.allowed_models.iter().map(|allowed| allowed.model.clone()))
+            .collect()
+    }
+}
+
+/// Embedding model a query may select instead of the configured one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowedEmbedder {
+    pub model: String,
+    pub dimensions: usize,
+}
+
+/// Configuration file key of the number of concurrent operations of `kind`
+fn limit_key(kind: ResourceKind) -> &'static str {
+    match kind {
+        ResourceKind::Ingest => "limits.max_ingests",
+        ResourceKind::Build => "limits.max_builds",
+        ResourceKind::EmbeddingBatch => "limits.max_embedding_batches",
+        ResourceKind::Export => "limits.max_exports",
+    }
+}
+
+/// Parse `GEORAG_DOWNLOAD_HOSTS`: comma-separated host names, or `*` for any
+fn parse_download_hosts(value: &str) -> Option<Vec<String>> {
+    let hosts: Vec<String> = value
+        .split(',')
+        .map(|host| host.trim().to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect();
+    (!hosts.iter().any(|host| host == "*")).then_some(hosts)
+}
+
+/// Parse `GEORAG_EMBEDDER_MODELS`: comma-separated `model` or `model=dimensions`
+///
+/// Models without dimensions use the configured embedder's.
+fn parse_allowed_models(value: &str, default_dimensions: usize) -> Option<Vec<AllowedEmbedder>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((model, dimensions)) => Some(AllowedEmbedder {
+                model: model.trim().to_string(),
+                dimensions: dimensions.trim().parse().ok().filter(|d| *d > 0)?,
+            }),
+            None => Some(AllowedEmbedder {
+                model: entry.to_string(),
+                dimensions: default_dimensions,
+            }),
+        })
+        .collect()
+}
+
+/// Default maximum query request body size (1 MiB)
+const DEFAULT_MAX_QUERY_BODY_BYTES: usize = 1024 * 1024;
+
+/// Query defaults applied when a request does not override them
+#[derive(Debug, Clone)]
+pub struct QueryConfig {
+    /// Minimum similarity score for returned sources
+    pub min_score: Option<f32>,
+    /// Vertex limit and simplification for filter geometries
+    pub geometry_limits: GeometryLimits,
+    /// Maximum size of a query request body in bytes
+    pub max_body_bytes: usize,
+    /// Maximum number of features returned by a dataset sample
+    pub max_sample: usize,
+    /// Reranking of queries that do not choose one
+    pub rerank: RerankMode,
+    /// Candidates reranked when a query does not set a pool size
+    pub rerank_pool: usize,
+    /// Ollama model rating candidates for `llm` reranking
+    pub rerank_model: String,
+    /// Candidates rated at the same time by `llm` reranking
+    pub rerank_concurrency: usize,
+    /// Tile source drawn under `png` results; none renders them offline
+    pub basemap: Option<Basemap>,
+    /// Predicate and radius of point queries that do not set them
+    pub point_defaults: PointQueryDefaults,
+    /// Template of the `source_url` of each result; none leaves it out
+    pub source_url_template: Option<SourceUrlTemplate>,
+    /// Percentiles of high and medium confidence sources
+    pub confidence_cutoffs: ConfidenceCutoffs,
+    /// Largest page a paged query may ask for
+    pub max_page_size: usize,
+    /// Time the results of a paged query are kept for its next pages
+    pub page_ttl: Duration,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            min_score: None,
+            geometry_limits: GeometryLimits::default(),
+            max_body_bytes: DEFAULT_MAX_QUERY_BODY_BYTES,
+            max_sample: DEFAULT_MAX_SAMPLE,
+            rerank: RerankMode::None,
+            rerank_pool: DEFAULT_RERANK_POOL,
+            rerank_model: DEFAULT_RERANK_MODEL.to_string(),
+            rerank_concurrency: DEFAULT_RERANK_CONCURRENCY,
+            basemap: None,
+            point_defaults: PointQueryDefaults::default(),
+            source_url_template: None,
+            confidence_cutoffs: ConfidenceCutoffs::default(),
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            page_ttl: DEFAULT_PAGE_TTL,
+        }
+    }
+}
+
+impl QueryConfig {
+    /// Reranker for `llm` reranking, served by the embedder's Ollama
+    pub fn llm_reranker(&self, embedder: &EmbedderConfig) -> LlmReranker {
+        let generator = OllamaGenerator::new(&embedder.ollama_url, &self.rerank_model)
+            .with_request_policy(embedder.request_policy);
+        LlmReranker::new(generator).with_concurrency(self.rerank_concurrency)
     }
 }
 
 impl ApiConfig {
     /// Load configuration from environment variables
     pub fn from_env() -> Self {
-        let port = env::var("GEORAG_PORT").ok().and_then(|p| p.parse().ok()).unwrap_or(3001);
+        Self::from_sources(ConfigSources::default())
+    }
+
+    /// Load configuration from environment variables and the file named by `GEORAG_CONFIG_FILE`
+    pub fn load() -> Result<Self> {
+        match env::var("GEORAG_CONFIG_FILE") {
+            Ok(path) if !path.trim().is_empty() => Self::load_from(Some(Path::new(path.trim()))),
+            _ => Self::load_from(None),
+        }
+    }
 
-        let cors_origin =
-            env::var("GEORAG_CORS_ORIGIN").unwrap_or_else(|_| "http://localhost:3000".to_string());
+    /// Load configuration from environment variables and a TOML file
+    ///
+    /// Environment variables take precedence over the file. Unknown keys and
+    /// values that fail to parse in the file are errors; the file is read
+    /// again on every configuration reload.
+    pub fn load_from(file: Option<&Path>) -> Result<Self> {
+        let Some(path) = file else {
+            return Ok(Self::from_env());
+        };
 
-        let database_url = env::var("DATABASE_URL").ok();
+        let mut sources = ConfigSources::from_file(path)?;
+        sources
+            .sources
+            .insert("server.config_file".to_string(), ConfigSource::Environment);
+        let mut config = Self::from_sources(sources);
+        config.sources.validate_file()?;
+        config.config_file = Some(path.to_path_buf());
+        Ok(config)
+    }
 
+    fn from_sources(mut sources: ConfigSources) -> Self {
+        let port = sources.read("server.port", "GEORAG_PORT", |p| p.parse().ok()).unwrap_or(3001);
+
+        let cors_origin = sources
+            .read("server.cors_origin", "GEORAG_CORS_ORIGIN", |o| Some(o.to_string()))
+            .unwrap_or_else(|| "http://localhost:3000".to_string());
+
+        let default_workspace = sources
+            .read("server.default_workspace", "GEORAG_DEFAULT_WORKSPACE", |w| {
+                Some(w.trim().to_string()).filter(|w| !w.is_empty())
+            })
+            .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+
+        let database_url =
+            sources.read("storage.database_url", "DATABASE_URL", |u| Some(u.to_string()));
+
+        let embedder_defaults = EmbedderConfig::default();
+        let dimensions = sources
+            .read("embedder.dimensions", "GEORAG_EMBEDDER_DIM", |d| d.parse().ok())
+            .unwrap_or(embedder_defaults.dimensions);
         let embedder = EmbedderConfig {
-            model: env::var("GEORAG_EMBEDDER_MODEL")
-                .unwrap_or_else(|_| "nomic-embed-text".to_string()),
-            dimensions: env::var("GEORAG_EMBEDDER_DIM")
-                .ok()
-                .and_then(|d| d.parse().ok())
-                .unwrap_or(768),
+            model: sources
+                .read("embedder.model", "GEORAG_EMBEDDER_MODEL", |m| Some(m.to_string()))
+                .unwrap_or(embedder_defaults.model),
+            dimensions,
+            auto_pull: sources
+                .read("embedder.auto_pull", "GEORAG_AUTO_PULL", parse_bool)
+                .unwrap_or(embedder_defaults.auto_pull),
+            allowed_models: sources
+                .read("embedder.allowed_models", "GEORAG_EMBEDDER_MODELS", |m| {
+                    parse_allowed_models(m, dimensions)
+                })
+                .unwrap_or_default(),
+            ollama_url: sources
+                .read("embedder.ollama_url", "OLLAMA_URL", |u| Some(u.to_string()))
+                .unwrap_or(embedder_defaults.ollama_url),
+            request_policy: RequestPolicy {
+                timeout: sources
+                    .read("embedder.timeout_secs", "GEORAG_EMBEDDER_TIMEOUT_SECS", |t| {
+                        t.parse().ok().filter(|t| *t > 0).map(Duration::from_secs)
+                    })
+                    .unwrap_or(embedder_defaults.request_policy.timeout),
+                retries: sources
+                    .read("embedder.retries", "GEORAG_EMBEDDER_RETRIES", |r| r.parse().ok())
+                    .unwrap_or(embedder_defaults.request_policy.retries),
+                ..embedder_defaults.request_policy
+            },
+            dimension_check: sources
+                .read("embedder.dimension_check", "GEORAG_EMBEDDER_DIM_CHECK", |c| {
+                    parse_dimension_check(c).ok()
+                })
+                .unwrap_or(embedder_defaults.dimension_check),
+        };
+
+        let defaults = QueryConfig::default();
+        let map_tile_url = sources
+            .read("query.map_tile_url", "GEORAG_MAP_TILE_URL", |u| parse_map_tile_url(u).ok());
+        let map_tile_attribution =
+            sources.read("query.map_tile_attribution", "GEORAG_MAP_TILE_ATTRIBUTION", |a| {
+                Some(a.trim().to_string()).filter(|a| !a.is_empty())
+            });
+        let query = QueryConfig {
+            min_score: sources
+                .read("query.min_score", "GEORAG_MIN_SCORE", |s| parse_min_score(s).ok()),
+            geometry_limits: GeometryLimits {
+                max_vertices: sources
+                    .read("query.max_filter_vertices", "GEORAG_MAX_FILTER_VERTICES", |v| {
+                        parse_max_filter_vertices(v).ok()
+                    })
+                    .unwrap_or(defaults.geometry_limits.max_vertices),
+                auto_simplify: sources
+                    .read("query.simplify_filters", "GEORAG_SIMPLIFY_FILTERS", parse_bool)
+                    .unwrap_or(defaults.geometry_limits.auto_simplify),
+            },
+            max_body_bytes: sources
+                .read("query.max_body_bytes", "GEORAG_MAX_QUERY_BODY_BYTES", |b| b.parse().ok())
+                .unwrap_or(defaults.max_body_bytes),
+            max_sample: sources
+                .read("query.max_sample", "GEORAG_MAX_SAMPLE", |n| parse_max_sample(n).ok())
+                .unwrap_or(defaults.max_sample),
+            rerank: sources
+                .read("query.rerank", "GEORAG_RERANK", |m| m.parse().ok())
+                .unwrap_or(defaults.rerank),
+            rerank_pool: sources
+                .read("query.rerank_pool", "GEORAG_RERANK_POOL", |n| {
+                    n.parse().ok().filter(|n| (1..=MAX_RERANK_POOL).contains(n))
+                })
+                .unwrap_or(defaults.rerank_pool),
+            rerank_model: sources
+                .read("query.rerank_model", "GEORAG_RERANK_MODEL", |m| {
+                    Some(m.trim().to_string()).filter(|m| !m.is_empty())
+                })
+                .unwrap_or(defaults.rerank_model),
+            rerank_concurrency: sources
+                .read("query.rerank_concurrency", "GEORAG_RERANK_CONCURRENCY", |n| {
+                    n.parse().ok().filter(|n| *n > 0)
+                })
+                .unwrap_or(defaults.rerank_concurrency),
+            basemap: map_tile_url.map(|url| match map_tile_attribution {
+                Some(attribution) => Basemap::new(url).with_attribution(attribution),
+                None => Basemap::new(url),
+            }),
+            point_defaults: PointQueryDefaults {
+                predicate: sources
+                    .read(
+                        "query.default_spatial_predicate",
+                        "GEORAG_DEFAULT_SPATIAL_PREDICATE",
+                        |p| parse_spatial_predicate(p).ok(),
+                    )
+                    .unwrap_or(defaults.point_defaults.predicate),
+                radius: sources.read("query.default_radius", "GEORAG_DEFAULT_RADIUS", |r| {
+                    parse_default_radius(r).ok()
+                }),
+                radius_unit: sources
+                    .read("query.default_radius_unit", "GEORAG_DEFAULT_RADIUS_UNIT", |u| {
+                        parse_distance_unit(u).ok()
+                    })
+                    .unwrap_or(defaults.point_defaults.radius_unit),
+            },
+            source_url_template: sources.read(
+                "query.source_url_template",
+                "GEORAG_SOURCE_URL_TEMPLATE",
+                |t| match parse_source_url_template(t) {
+                    Ok(template) => Some(template),
+                    Err(e) => {
+                        tracing::warn!("Ignoring GEORAG_SOURCE_URL_TEMPLATE: {}", e);
+                        None
+                    }
+                },
+            ),
+            confidence_cutoffs: sources
+                .read("query.confidence_cutoffs", "GEORAG_CONFIDENCE_CUTOFFS", |c| {
+                    parse_confidence_cutoffs(c).ok()
+                })
+                .unwrap_or(defaults.confidence_cutoffs),
+            max_page_size: sources
+                .read("query.max_page_size", "GEORAG_MAX_PAGE_SIZE", |n| {
+                    n.parse().ok().filter(|n| *n > 0)
+                })
+                .unwrap_or(defaults.max_page_size),
+            page_ttl: sources
+                .read("query.cursor_ttl_secs", "GEORAG_QUERY_CURSOR_TTL_SECS", |t| {
+                    t.parse().ok().filter(|t| *t > 0).map(Duration::from_secs)
+                })
+                .unwrap_or(defaults.page_ttl),
+        };
+
+        let path = |p: &str| Some(PathBuf::from(p));
+        let redaction_file = sources.read("redaction.file", "GEORAG_REDACTION_FILE", path);
+        let auth_file = sources.read("auth.file", "GEORAG_AUTH_FILE", path);
+        let bundle_file = sources.read("storage.bundle", "GEORAG_BUNDLE", path);
+        let blob_dir = sources.read("storage.blob_dir", "GEORAG_BLOB_DIR", path);
+        let chunk_properties = sources
+            .read("index.chunk_properties", "GEORAG_CHUNK_PROPERTIES", |v| {
+                Some(parse_property_list(v))
+            })
+            .unwrap_or_default();
+
+        let validity = sources
+            .read("ingest.geometry_validity", "GEORAG_GEOMETRY_VALIDITY", |v| {
+                parse_validity_mode(v).ok()
+            })
+            .unwrap_or(ValidityMode::Lenient);
+        let max_feature_errors = sources
+            .read("ingest.max_feature_errors", "GEORAG_MAX_FEATURE_ERRORS", |n| {
+                parse_max_feature_errors(n).ok()
+            })
+            .unwrap_or(DEFAULT_MAX_FEATURE_ERRORS);
+        let read_policy = ReadPolicy::for_validity(validity, max_feature_errors);
+        let axis_order = sources
+            .read("ingest.axis_order", "GEORAG_AXIS_ORDER", |v| parse_axis_order(v).ok())
+            .unwrap_or_default();
+        let source_defaults = SourcePolicy::default();
+        let source_policy = SourcePolicy {
+            max_bytes: sources
+                .read("ingest.max_source_bytes", "GEORAG_MAX_SOURCE_BYTES", |n| {
+                    parse_max_source_bytes(n).ok()
+                })
+                .unwrap_or(source_defaults.max_bytes),
+            hash: sources
+                .read("ingest.hash_sources", "GEORAG_HASH_SOURCES", parse_bool)
+                .unwrap_or(source_defaults.hash),
+        };
+        let download_policy = DownloadPolicy::default()
+            .with_max_bytes(
+                sources
+                    .read("ingest.max_download_bytes", "GEORAG_MAX_DOWNLOAD_BYTES", |n| {
+                        n.trim().parse::<u64>().ok()
+                    })
+                    .unwrap_or(DEFAULT_MAX_DOWNLOAD_BYTES),
+            )
+            .with_allowed_hosts(
+                sources
+                    .read("ingest.download_hosts", "GEORAG_DOWNLOAD_HOSTS", |h| {
+                        Some(parse_download_hosts(h))
+                    })
+                    .unwrap_or(Some(Vec::new())),
+            )
+            .with_private_addresses(
+                sources
+                    .read(
+                        "ingest.download_private_addresses",
+                        "GEORAG_DOWNLOAD_PRIVATE_ADDRESSES",
+                        parse_bool,
+                    )
+                    .unwrap_or(false),
+            );
+        let feature_defaults = FeatureLimits::default();
+        let feature_limits = FeatureLimits {
+            max_vertices: sources
+                .read("ingest.max_feature_vertices", "GEORAG_MAX_FEATURE_VERTICES", |n| {
+                    parse_max_feature_vertices(n).ok()
+                })
+                .unwrap_or(feature_defaults.max_vertices),
+            oversized: sources
+                .read("ingest.oversized_features", "GEORAG_OVERSIZED_FEATURES", |v| {
+                    parse_oversized_features(v).ok()
+                })
+                .unwrap_or(feature_defaults.oversized),
+        };
+        let z_coordinates = sources
+            .read("ingest.z_coordinates", "GEORAG_Z_COORDINATES", |v| parse_z_coordinates(v).ok())
+            .unwrap_or_default();
+        let buffer_defaults = IngestBuffers::default();
+        let pipeline_buffers = IngestBuffers::new(
+            sources
+                .read("ingest.batch_size", "GEORAG_INGEST_BATCH_SIZE", |n| {
+                    parse_ingest_batch_size(n).ok()
+                })
+                .unwrap_or(buffer_defaults.batch_size),
+            sources
+                .read("ingest.channel_capacity", "GEORAG_INGEST_CHANNEL_CAPACITY", |n| {
+                    parse_ingest_channel_capacity(n).ok()
+                })
+                .unwrap_or(buffer_defaults.channel_capacity),
+        );
+        let mut quota =
+            |key: &str, var: &str| sources.read(key, var, |n| parse_quota(key, n).ok()).flatten();
+        let quotas = WorkspaceQuotas {
+            max_datasets: quota("quota.max_datasets", "GEORAG_MAX_DATASETS"),
+            max_features: quota("quota.max_features", "GEORAG_MAX_FEATURES"),
+            max_chunks: quota("quota.max_chunks", "GEORAG_MAX_CHUNKS"),
+            max_blob_bytes: quota("quota.max_blob_bytes", "GEORAG_MAX_BLOB_BYTES"),
+        };
+
+        let health_defaults = HealthPolicy::default();
+        let health = HealthPolicy {
+            check_interval: sources
+                .read("health.check_interval_secs", "GEORAG_HEALTH_CHECK_INTERVAL_SECS", |t| {
+                    t.trim().parse().ok().filter(|t| *t > 0).map(Duration::from_secs)
+                })
+                .unwrap_or(health_defaults.check_interval),
+            down_after: sources
+                .read("health.down_after", "GEORAG_HEALTH_DOWN_AFTER", |n| {
+                    n.trim().parse().ok().filter(|n| *n > 0)
+                })
+                .unwrap_or(health_defaults.down_after),
+        };
+        let fallback_bundle =
+            sources.read("health.fallback_bundle", "GEORAG_FALLBACK_BUNDLE", path);
+
+        let mut resource_limits = ResourceLimits::default();
+        for kind in ResourceKind::ALL {
+            if let Some(limit) = sources
+                .read(limit_key(kind), limit_variable(kind), |n| parse_resource_limit(n).ok())
+            {
+                resource_limits = resource_limits.with_limit(kind, limit);
+            }
+        }
+        if let Some(max_queued) =
+            sources
+                .read("limits.max_queued", "GEORAG_MAX_QUEUED_REQUESTS", |n| n.trim().parse().ok())
+        {
+            resource_limits = resource_limits.with_max_queued(max_queued);
+        }
+        if let Some(retry_after) =
+            sources.read("limits.retry_after_secs", "GEORAG_RETRY_AFTER_SECS", |t| {
+                t.trim().parse().ok().filter(|t| *t > 0).map(Duration::from_secs)
+            })
+        {
+            resource_limits = resource_limits.with_retry_after(retry_after);
+        }
+        if let Some(bytes) =
+            sources.read("limits.large_export_bytes", "GEORAG_LARGE_EXPORT_BYTES", |n| {
+                n.trim().parse().ok()
+            })
+        {
+            resource_limits = resource_limits.with_large_export_bytes(bytes);
+        }
+
+        let retention_defaults = EventRetention::default();
+        let event_retention = EventRetention {
+            max_events: sources
+                .read("events.retention_events", "GEORAG_EVENT_RETENTION_EVENTS", |n| {
+                    n.trim().parse().ok().filter(|n| *n > 0)
+                })
+                .unwrap_or(retention_defaults.max_events),
+            // 0 keeps events regardless of age
+            max_age: sources
+                .read("events.retention_days", "GEORAG_EVENT_RETENTION_DAYS", |d| {
+                    d.trim()
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|d| (0..=36_500).contains(d))
+                        .map(|d| (d > 0).then(|| chrono::Duration::days(d)))
+                })
+                .unwrap_or(retention_defaults.max_age),
         };
 
         Self {
             port,
             cors_origin,
+            config_file: None,
+            default_workspace,
             database_url,
             embedder,
+            query,
+            redaction_file,
+            auth_file,
+            bundle_file,
+            blob_dir,
+            chunk_properties,
+            read_policy,
+            axis_order,
+            source_policy,
+            download_policy,
+            feature_limits,
+            z_coordinates,
+            pipeline_buffers,
+            quotas,
+            health,
+            fallback_bundle,
+            resource_limits,
+            event_retention,
+            sources,
+        }
+    }
+
+    /// Effective configuration with the source of each value, secrets masked
+    ///
+    /// Keys are grouped by section (`server.port`, `embedder.model`, ...).
+    pub fn inspection_map(&self) -> BTreeMap<String, (String, ConfigSource)> {
+        let none = || "none".to_string();
+        let path = |p: &Option<PathBuf>| p.as_ref().map(|p| p.display().to_string());
+
+        let values = [
+            ("server.port", self.port.to_string()),
+            ("server.cors_origin", self.cors_origin.clone()),
+            ("server.config_file", path(&self.config_file).unwrap_or_else(none)),
+            ("server.default_workspace", self.default_workspace.clone()),
+            ("storage.backend", self.storage_backend().to_string()),
+            ("storage.database_url", self.database_url.clone().unwrap_or_else(none)),
+            ("storage.bundle", path(&self.bundle_file).unwrap_or_else(none)),
+            ("storage.blob_dir", path(&self.blob_dir).unwrap_or_else(none)),
+            ("embedder.model", self.embedder.model.clone()),
+            ("embedder.dimensions", self.embedder.dimensions.to_string()),
+            ("embedder.auto_pull", self.embedder.auto_pull.to_string()),
+            ("embedder.ollama_url", self.embedder.ollama_url.clone()),
+            (
+                "embedder.timeout_secs",
+                self.embedder.request_policy.timeout.as_secs().to_string(),
+            ),
+            ("embedder.retries", self.embedder.request_policy.retries.to_string()),
+            ("embedder.dimension_check", self.embedder.dimension_check.to_string()),
+            (
+                "embedder.allowed_models",
+                if self.embedder.allowed_models.is_empty() {
+                    none()
+                } else {
+                    self.embedder
+                        .allowed_models
+                        .iter()
+                        .map(|allowed| format!("{}={}", allowed.model, allowed.dimensions))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                },
+            ),
+            (
+                "query.min_score",
+                self.query.min_score.map(|s| s.to_string()).unwrap_or_else(none),
+            ),
+            ("query.max_filter_vertices", self.query.geometry_limits.max_vertices.to_string()),
+            ("query.simplify_filters", self.query.geometry_limits.auto_simplify.to_string()),
+            ("query.max_body_bytes", self.query.max_body_bytes.to_string()),
+            ("query.max_sample", self.query.max_sample.to_string()),
+            ("query.rerank", self.query.rerank.to_string()),
+            ("query.rerank_pool", self.query.rerank_pool.to_string()),
+            ("query.rerank_model", self.query.rerank_model.clone()),
+            ("query.rerank_concurrency", self.query.rerank_concurrency.to_string()),
+            (
+                "query.map_tile_url",
+                self.query.basemap.as_ref().map(|b| b.url_template.clone()).unwrap_or_else(none),
+            ),
+            (
+                "query.map_tile_attribution",
+                self.query
+                    .basemap
+                    .as_ref()
+                    .and_then(|b| b.attribution.clone())
+                    .unwrap_or_else(none),
+            ),
+            (
+                "query.default_spatial_predicate",
+                format!("{:?}", self.query.point_defaults.predicate),
+            ),
+            (
+                "query.default_radius",
+                self.query.point_defaults.radius.map(|r| r.to_string()).unwrap_or_else(none),
+            ),
+            (
+                "query.default_radius_unit",
+                format!("{:?}", self.query.point_defaults.radius_unit),
+            ),
+            (
+                "query.source_url_template",
+                self.query
+                    .source_url_template
+                    .as_ref()
+                    .map(|t| t.as_str().to_string())
+                    .unwrap_or_else(none),
+            ),
+            ("query.confidence_cutoffs", self.query.confidence_cutoffs.to_string()),
+            ("query.max_page_size", self.query.max_page_size.to_string()),
+            ("query.cursor_ttl_secs", self.query.page_ttl.as_secs().to_string()),
+            ("ingest.geometry_validity", format!("{:?}", self.read_policy.validity)),
+            ("ingest.max_feature_errors", self.read_policy.max_errors.to_string()),
+            ("ingest.axis_order", self.axis_order.to_string()),
+            ("ingest.max_source_bytes", self.source_policy.max_bytes.to_string()),
+            ("ingest.hash_sources", self.source_policy.hash.to_string()),
+            ("ingest.max_download_bytes", self.download_policy.max_bytes.to_string()),
+            (
+                "ingest.download_hosts",
+                match &self.download_policy.allowed_hosts {
+                    Some(hosts) if hosts.is_empty() => none(),
+                    Some(hosts) => hosts.join(","),
+                    None => "*".to_string(),
+                },
+            ),
+            (
+                "ingest.download_private_addresses",
+                self.download_policy.private_addresses.to_string(),
+            ),
+            ("ingest.max_feature_vertices", self.feature_limits.max_vertices.to_string()),
+            ("ingest.oversized_features", self.feature_limits.oversized.to_string()),
+            ("ingest.z_coordinates", self.z_coordinates.to_string()),
+            ("ingest.batch_size", self.pipeline_buffers.batch_size.to_string()),
+            ("ingest.channel_capacity", self.pipeline_buffers.channel_capacity.to_string()),
+            ("quota.max_datasets", format_quota(self.quotas.max_datasets)),
+            ("quota.max_features", format_quota(self.quotas.max_features)),
+            ("quota.max_chunks", format_quota(self.quotas.max_chunks)),
+            ("quota.max_blob_bytes", format_quota(self.quotas.max_blob_bytes)),
+            ("health.check_interval_secs", self.health.check_interval.as_secs().to_string()),
+            ("health.down_after", self.health.down_after.to_string()),
+            ("health.fallback_bundle", path(&self.fallback_bundle).unwrap_or_else(none)),
+            (limit_key(ResourceKind::Ingest), self.resource_limits.ingests.to_string()),
+            (limit_key(ResourceKind::Build), self.resource_limits.builds.to_string()),
+            (
+                limit_key(ResourceKind::EmbeddingBatch),
+                self.resource_limits.embedding_batches.to_string(),
+            ),
+            (limit_key(ResourceKind::Export), self.resource_limits.exports.to_string()),
+            ("limits.max_queued", self.resource_limits.max_queued.to_string()),
+            (
+                "limits.retry_after_secs",
+                self.resource_limits.retry_after.as_secs().to_string(),
+            ),
+            ("limits.large_export_bytes", self.resource_limits.large_export_bytes.to_string()),
+            ("events.retention_events", self.event_retention.max_events.to_string()),
+            (
+                "events.retention_days",
+                self.event_retention.max_age.map_or_else(none, |age| age.num_days().to_string()),
+            ),
+            ("redaction.file", path(&self.redaction_file).unwrap_or_else(none)),
+            ("auth.file", path(&self.auth_file).unwrap_or_else(none)),
+            (
+                "index.chunk_properties",
+                if self.chunk_properties.is_empty() {
+                    none()
+                } else {
+                    self.chunk_properties.join(", ")
+                },
+            ),
+        ];
+
+        values
+            .into_iter()
+            .map(|(key, value)| {
+                let source = match key {
+                    // Chosen by GEORAG_BUNDLE or DATABASE_URL
+                    "storage.backend" if self.storage_backend() != "memory" => {
+                        ConfigSource::Environment
+                    }
+                    _ => self.sources.get(key),
+                };
+                (key.to_string(), (mask_config_value(key, &value), source))
+            })
+            .collect()
+    }
+
+    /// Storage backend selected by the configuration
+    pub fn storage_backend(&self) -> &'static str {
+        if self.bundle_file.is_some() {
+            "bundle"
+        } else if self.uses_postgres() {
+            "postgres"
+        } else {
+            "memory"
         }
     }
 
@@ -1,28 +1,308 @@
+use chrono::{DateTime, Utc};
+use georag_core::config::WorkspaceSettings;
+use georag_core::geo::SampleStrategy;
+use georag_core::models::{DatasetSort, SortOrder};
+use georag_retrieval::{PropertySelection, TimeGrouping};
 use serde::Deserialize;
 
 /// Query request body
 #[derive(Debug, Deserialize)]
 pub struct QueryRequest {
+    /// Query text; required except in a query by example, where it only
+    /// labels the query
+    #[serde(default)]
     pub text: String,
     pub bbox: Option<[f64; 4]>,
-    #[serde(default = "default_top_k")]
-    pub top_k: usize,
+    /// GeoJSON geometry filter (alternative to `bbox`)
+    pub geometry: Option<serde_json::Value>,
+    /// Saved area used as the filter geometry (alternative to `bbox` and `geometry`)
+    pub area: Option<String>,
+    /// `[lon, lat]` to search around (alternative to `bbox`, `geometry` and
+    /// `area`); the workspace defaults fill in an unset predicate and radius
+    pub point: Option<[f64; 2]>,
+    /// Radius of a dwithin `point` query (defaults to `default_radius`)
+    pub radius: Option<BufferRequest>,
+    /// Predicate applied with `geometry`, `area` or `point`: within, intersects,
+    /// contains, coveredby, touches, crosses, overlaps, bbox or dwithin (`point`
+    /// only; defaults to `default_spatial_predicate` there)
+    pub predicate: Option<String>,
+    /// Buffer applied to the filter geometry before the predicate is evaluated
+    pub buffer: Option<BufferRequest>,
+    /// GeoJSON geometries removed from the filter: sources intersecting any
+    /// of them are dropped
+    #[serde(default)]
+    pub exclude_geometries: Vec<serde_json::Value>,
+    /// Saved areas removed from the filter, like `exclude_geometries`
+    #[serde(default)]
+    pub exclude_areas: Vec<String>,
+    /// Number of results (defaults to 10)
+    pub top_k: Option<usize>,
+    /// Minimum similarity score (overrides the server default)
+    pub min_score: Option<f32>,
+    /// Include the score distribution in the response
+    pub explain: Option<bool>,
+    /// Bucket ranked sources by a timestamp property
+    #[serde(default)]
+    pub group_by_time: Option<TimeGrouping>,
+    /// Feature properties the results must have, e.g. `{"category": "school"}`
+    #[serde(default)]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+    /// Geometry returned per source: full, centroid, bbox, none or adaptive
+    /// (defaults to full)
+    pub geometry_detail: Option<String>,
+    /// Most vertices an adaptive geometry keeps (defaults to 1000)
+    pub vertex_budget: Option<usize>,
+    /// Decimal places kept in returned coordinates (untrimmed when absent)
+    pub coordinate_precision: Option<u32>,
+    /// Embedding model serving this query instead of the configured one
+    /// (must be listed in `GEORAG_EMBEDDER_MODELS` and have a built index)
+    pub embedder_model: Option<String>,
+    /// Second-pass reranking: none, lexical or llm (defaults to `GEORAG_RERANK`)
+    pub rerank: Option<String>,
+    /// Candidates reranked before the results are cut to `top_k`
+    /// (defaults to `GEORAG_RERANK_POOL`)
+    pub rerank_pool: Option<usize>,
+    /// Merge sources cut from overlapping stretches of the same text
+    pub merge_overlapping: Option<bool>,
+    /// Turn `near:`, `within:`, `dataset:` and `since:` operators in `text`
+    /// into filters
+    pub parse_operators: Option<bool>,
+    /// Feature property a `since:` operator applies to (defaults to the
+    /// `group_by_time` property)
+    pub time_property: Option<String>,
+    /// Property keys kept in the results; score, excerpt and document_path
+    /// are always kept
+    #[serde(default)]
+    pub properties: PropertySelection,
+    /// Return the results this many at a time, with a cursor to the next page
+    pub page_size: Option<usize>,
+    /// Workspace query preset filling in the options left unset here
+    /// (defaults to the workspace's `default_query_preset`)
+    pub preset: Option<String>,
 }
 
-fn default_top_k() -> usize {
-    10
+/// Query by example request body: a stored feature or an indexed chunk, and
+/// the filters and options of a query
+#[derive(Debug, Deserialize)]
+pub struct QueryByFeatureRequest {
+    /// Stored feature whose indexed chunks the results should resemble
+    pub feature: Option<FeatureRefRequest>,
+    /// Indexed chunk the results should resemble (alternative to `feature`)
+    pub chunk_id: Option<u64>,
+    /// How the chunks of a feature are searched with: average (the mean of
+    /// their embeddings) or individual (each one, keeping a result's best
+    /// score); defaults to average
+    pub seed_mode: Option<String>,
+    /// Filters and output options, as in a query request
+    #[serde(flatten)]
+    pub query: QueryRequest,
+}
+
+/// Next page request body
+#[derive(Debug, Deserialize)]
+pub struct QueryNextRequest {
+    /// `next_cursor` of the previous page
+    pub cursor: String,
+}
+
+/// Create area request body: a geometry or a feature to copy it from
+#[derive(Debug, Deserialize)]
+pub struct CreateAreaRequest {
+    pub name: String,
+    /// GeoJSON geometry, or a Feature holding one, in WGS 84
+    pub geometry: Option<serde_json::Value>,
+    /// Stored feature whose geometry becomes the area
+    pub from_feature: Option<FeatureRefRequest>,
+}
+
+/// Stored feature referenced by dataset and feature ID
+#[derive(Debug, Deserialize)]
+pub struct FeatureRefRequest {
+    pub dataset: String,
+    pub id: String,
+}
+
+/// Buffer distance or radius of a query filter
+#[derive(Debug, Deserialize)]
+pub struct BufferRequest {
+    pub distance: f64,
+    /// Distance unit: meters, kilometers, miles or feet (defaults to meters)
+    pub unit: Option<String>,
+}
+
+/// Query string parameters of the query endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct QueryFormatParams {
+    /// Result format (geojson, json, csv or png); overrides the Accept header
+    pub format: Option<String>,
+    /// Width in pixels of a `png` map
+    pub width: Option<u32>,
+    /// Height in pixels of a `png` map
+    pub height: Option<u32>,
+}
+
+/// Query parameters of the compaction endpoint
+#[derive(Debug, Deserialize)]
+pub struct CompactParams {
+    /// Delete the orphans found; without it the scan only reports them
+    #[serde(default)]
+    pub apply: bool,
+}
+
+/// Query parameters of the dataset listing endpoints
+#[derive(Debug, Default, Deserialize)]
+pub struct ListDatasetsParams {
+    /// Field to sort by: name, added or features (defaults to name)
+    #[serde(default)]
+    pub sort: DatasetSort,
+    /// asc or desc (defaults to asc)
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// Query parameters of the dataset sample endpoint
+#[derive(Debug, Deserialize)]
+pub struct SampleParams {
+    /// Number of features, capped at the configured maximum
+    #[serde(default = "default_sample_size")]
+    pub n: usize,
+    /// Sampling strategy: random or spatial (defaults to random)
+    #[serde(default)]
+    pub strategy: SampleStrategy,
+    /// Comma-separated feature properties to keep
+    pub include: Option<String>,
+    /// Comma-separated feature properties to drop
+    pub exclude: Option<String>,
+}
+
+fn default_sample_size() -> usize {
+    20
+}
+
+/// Update dataset tags request body
+#[derive(Debug, Deserialize)]
+pub struct UpdateDatasetTagsRequest {
+    /// Access tags replacing the current ones; empty makes the dataset public
+    pub tags: Vec<String>,
+}
+
+/// Bulk dataset deletion request body
+#[derive(Debug, Deserialize)]
+pub struct BulkDeleteRequest {
+    /// Which datasets to delete
+    pub filter: BulkDeleteFilter,
+    /// Archive the datasets instead of deleting them
+    #[serde(default)]
+    pub archive: bool,
+    /// Carry out the deletion; without it only a preview is returned
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Conditions a dataset must all meet to be bulk deleted; at least one is required
+#[derive(Debug, Default, Deserialize)]
+pub struct BulkDeleteFilter {
+    /// Names starting with this text
+    pub name_prefix: Option<String>,
+    /// Names containing a match of this regular expression
+    pub name_regex: Option<String>,
+    /// Datasets added before this time
+    pub added_before: Option<DateTime<Utc>>,
+    /// Datasets carrying this access tag
+    pub tag: Option<String>,
+}
+
+/// Path of a dataset route, with or without a workspace prefix
+#[derive(Debug, Deserialize)]
+pub struct DatasetPath {
+    pub dataset_id: String,
+}
+
+/// Path of a dataset feature route, with or without a workspace prefix
+#[derive(Debug, Deserialize)]
+pub struct DatasetFeaturePath {
+    pub dataset_id: String,
+    pub feature_id: String,
+}
+
+/// Query parameters of the dataset feature endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct FeatureParams {
+    /// Comma-separated feature properties to keep
+    pub include: Option<String>,
+    /// Comma-separated feature properties to drop
+    pub exclude: Option<String>,
+}
+
+/// Query parameters of the workspace timeline
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineParams {
+    /// Start of the range: an RFC 3339 time or a date (defaults to two weeks ago)
+    pub since: Option<String>,
+    /// Bucket width: hour, day or week (defaults to day)
+    pub granularity: Option<String>,
+}
+
+/// Query parameters of the change event feed
+#[derive(Debug, Deserialize)]
+pub struct EventParams {
+    /// Return events after this sequence number; defaults to the consumer's
+    /// cursor, or 0 without a consumer
+    pub after_seq: Option<u64>,
+    /// Most events to return (defaults to 100, at most 1000)
+    #[serde(default = "default_event_limit")]
+    pub limit: usize,
+    /// Seconds to wait for an event when there are none yet (defaults to 0, at most 60)
+    #[serde(default)]
+    pub wait: u64,
+    /// Name whose stored cursor `after_seq` defaults to
+    pub consumer: Option<String>,
+}
+
+fn default_event_limit() -> usize {
+    georag_core::models::DEFAULT_EVENT_PAGE
+}
+
+/// Path of a change event cursor route
+#[derive(Debug, Deserialize)]
+pub struct EventCursorPath {
+    pub consumer: String,
+}
+
+/// Acknowledge change events request body
+#[derive(Debug, Deserialize)]
+pub struct EventCursorRequest {
+    /// Sequence number of the last event the consumer applied
+    pub seq: u64,
+}
+
+/// Path of a saved area route, with or without a workspace prefix
+#[derive(Debug, Deserialize)]
+pub struct AreaPath {
+    pub name: String,
+}
+
+/// Path of a query preset route, with or without a workspace prefix
+#[derive(Debug, Deserialize)]
+pub struct PresetPath {
+    pub name: String,
 }
 
 /// Create workspace request body
 #[derive(Debug, Deserialize)]
 pub struct CreateWorkspaceRequest {
     pub name: String,
-    #[serde(default = "default_crs")]
-    pub crs: u32,
-    pub distance_unit: Option<String>,
-    pub geometry_validity: Option<String>,
+    /// Any workspace settings; CRS, distance unit and validity mode have defaults
+    #[serde(flatten)]
+    pub settings: WorkspaceSettings,
 }
 
-fn default_crs() -> u32 {
-    4326
+/// Clone workspace request body
+#[derive(Debug, Deserialize)]
+pub struct CloneWorkspaceRequest {
+    /// Name of the new workspace
+    pub name: String,
+    /// Copy the original files of datasets too
+    #[serde(default)]
+    pub include_blobs: bool,
 }
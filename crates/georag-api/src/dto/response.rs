@@ -1,6 +1,19 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
+use georag_core::config::{ConfigSource, WorkspaceSettings};
+use georag_core::formats::{FeatureError, FormatDescription, TransformErrors};
+use georag_core::geo::OversizedFeature;
+use georag_core::models::{
+    AxisOrderDecision, BuildDiff, ChangeEvent, DatasetIndexStats, DatasetPreview, QueryPreset,
+    RemoteSource, SavedArea, SourceFile, Timeline, UsageDelta, WorkspaceQuotas, WorkspaceUsage,
+};
+use georag_service::{CloneCounts, PipelineStats};
 use serde::Serialize;
 
+use crate::health::{CircuitState, StoreHealth};
+use crate::reload::ReloadOutcome;
+
 /// Dataset information response
 #[derive(Debug, Serialize)]
 pub struct DatasetInfo {
@@ -8,6 +21,8 @@ pub struct DatasetInfo {
     #[serde(rename = "type")]
     pub geometry_type: String,
     pub count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<DatasetPreview>,
 }
 
 /// Extended dataset information for workspace-scoped responses
@@ -20,6 +35,20 @@ pub struct DatasetResponse {
     pub feature_count: usize,
     pub crs: u32,
     pub added_at: DateTime<Utc>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Preview of the content; absent for datasets ingested before previews were kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<DatasetPreview>,
+}
+
+/// Supported formats and the reader options ingest accepts for each
+#[derive(Debug, Serialize)]
+pub struct FormatsResponse {
+    pub formats: Vec<FormatDescription>,
 }
 
 /// Ingest operation response
@@ -29,6 +58,73 @@ pub struct IngestResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dataset_id: Option<u64>,
     pub message: String,
+    /// Features skipped because they could not be read
+    pub features_skipped: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub feature_errors: Vec<FeatureError>,
+    /// Features over the vertex limit that were simplified or subdivided
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub oversized_features: Vec<OversizedFeature>,
+    /// Features each workspace transform failed on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transform_errors: Vec<TransformErrors>,
+    /// Axis order the coordinates were read with (GeoJSON only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_order: Option<AxisOrderDecision>,
+    /// Original file kept for `GET /api/v1/datasets/{id}/source`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceFile>,
+    /// URL the dataset was downloaded from, for `url` ingests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    /// How the upload moved through the ingest pipeline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<PipelineResponse>,
+}
+
+/// Ingest pipeline stats of an upload
+#[derive(Debug, Serialize)]
+pub struct PipelineResponse {
+    pub batch_size: usize,
+    pub channel_capacity: usize,
+    pub batches: usize,
+    pub peak_features_in_flight: usize,
+    pub elapsed_ms: u64,
+    /// Throughput of the parse, normalize and store stages
+    pub stages: Vec<StageResponse>,
+}
+
+/// Throughput of one ingest pipeline stage
+#[derive(Debug, Serialize)]
+pub struct StageResponse {
+    pub stage: &'static str,
+    pub features: usize,
+    pub busy_ms: u64,
+    pub waiting_ms: u64,
+    pub features_per_second: f64,
+}
+
+impl From<&PipelineStats> for PipelineResponse {
+    fn from(stats: &PipelineStats) -> Self {
+        Self {
+            batch_size: stats.buffers.batch_size,
+            channel_capacity: stats.buffers.channel_capacity,
+            batches: stats.batches,
+            peak_features_in_flight: stats.peak_features_in_flight,
+            elapsed_ms: stats.elapsed.as_millis() as u64,
+            stages: stats
+                .stages
+                .iter()
+                .map(|stage| StageResponse {
+                    stage: stage.stage,
+                    features: stage.features,
+                    busy_ms: stage.busy.as_millis() as u64,
+                    waiting_ms: stage.waiting.as_millis() as u64,
+                    features_per_second: stage.features_per_second(),
+                })
+                .collect(),
+        }
+    }
 }
 
 impl IngestResponse {
@@ -37,8 +133,62 @@ impl IngestResponse {
             success: true,
             dataset_id: Some(dataset_id),
             message: format!("Successfully ingested {} with {} features", filename, feature_count),
+            features_skipped: 0,
+            feature_errors: Vec::new(),
+            oversized_features: Vec::new(),
+            transform_errors: Vec::new(),
+            axis_order: None,
+            source: None,
+            remote: None,
+            pipeline: None,
         }
     }
+
+    /// Report the features over the vertex limit that were kept in a reduced form
+    pub fn with_oversized_features(mut self, oversized: Vec<OversizedFeature>) -> Self {
+        self.oversized_features = oversized;
+        self
+    }
+
+    /// Report the failures of each workspace transform
+    pub fn with_transform_errors(mut self, errors: Vec<TransformErrors>) -> Self {
+        self.transform_errors = errors;
+        self
+    }
+
+    /// Report the features the reader skipped
+    pub fn with_feature_errors(mut self, errors: Vec<FeatureError>) -> Self {
+        if !errors.is_empty() {
+            self.message.push_str(&format!(" ({} skipped)", errors.len()));
+        }
+        self.features_skipped = errors.len();
+        self.feature_errors = errors;
+        self
+    }
+
+    /// Report the axis order the upload was read with
+    pub fn with_axis_order(mut self, decision: Option<AxisOrderDecision>) -> Self {
+        self.axis_order = decision;
+        self
+    }
+
+    /// Report the original file kept for download
+    pub fn with_source(mut self, source: Option<SourceFile>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Report the URL the dataset was downloaded from
+    pub fn with_remote(mut self, remote: Option<RemoteSource>) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// Report the throughput of the ingest pipeline stages
+    pub fn with_pipeline(mut self, stats: Option<&PipelineStats>) -> Self {
+        self.pipeline = stats.map(PipelineResponse::from);
+        self
+    }
 }
 
 /// Index integrity response
@@ -49,6 +199,9 @@ pub struct IndexIntegrityResponse {
     pub embedder: String,
     pub chunk_count: usize,
     pub embedding_dim: usize,
+    /// What the last build changed compared with the build before it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_build_diff: Option<BuildDiff>,
 }
 
 /// Index verification response
@@ -62,16 +215,102 @@ pub struct VerifyResponse {
 /// Health check response
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
+    /// `ok`, or the circuit state of a storage backend that is not healthy
     pub status: &'static str,
     pub service: &'static str,
+    /// Health checks of the storage backend, when it is supervised
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<StoreHealth>,
 }
 
 impl Default for HealthResponse {
     fn default() -> Self {
-        Self { status: "ok", service: "georag-api" }
+        Self {
+            status: "ok",
+            service: "georag-api",
+            store: None,
+        }
     }
 }
 
+impl HealthResponse {
+    /// Health of a supervised storage backend
+    pub fn with_store(mut self, store: StoreHealth) -> Self {
+        if store.state != CircuitState::Healthy {
+            self.status = store.state.as_str();
+        }
+        self.store = Some(store);
+        self
+    }
+}
+
+/// A configuration value and where it came from
+#[derive(Debug, Serialize)]
+pub struct ConfigEntryResponse {
+    pub value: String,
+    pub source: ConfigSource,
+}
+
+/// Effective server configuration, secrets masked
+#[derive(Debug, Serialize)]
+pub struct ConfigResponse {
+    pub config: BTreeMap<String, ConfigEntryResponse>,
+    /// When the configuration was last reloaded without an error
+    pub last_reloaded: Option<DateTime<Utc>>,
+    /// Latest reloads, oldest first
+    pub reloads: Vec<ReloadOutcome>,
+}
+
+/// Result of scanning for, and optionally deleting, orphaned index data
+#[derive(Debug, Serialize)]
+pub struct CompactResponse {
+    pub embeddings_scanned: usize,
+    pub chunks_scanned: usize,
+    pub orphan_embeddings: usize,
+    pub dangling_chunks: usize,
+    pub degenerate_chunks: usize,
+    pub reclaimable_bytes: u64,
+    pub applied: bool,
+    pub embeddings_deleted: usize,
+    pub chunks_deleted: usize,
+    pub degenerate_deleted: usize,
+}
+
+/// Preview or outcome of a bulk dataset deletion
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteResponse {
+    /// `delete` or `archive`
+    pub action: &'static str,
+    /// Whether the datasets were changed; `false` for a preview
+    pub confirmed: bool,
+    pub datasets_scanned: usize,
+    pub matched: usize,
+    pub feature_count: usize,
+    pub source_bytes: u64,
+    /// Datasets changed, when confirmed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<usize>,
+    /// Datasets left unchanged because of an error, when confirmed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<usize>,
+    pub datasets: Vec<BulkDatasetStatus>,
+}
+
+/// One dataset of a bulk deletion
+#[derive(Debug, Serialize)]
+pub struct BulkDatasetStatus {
+    pub id: String,
+    pub name: String,
+    pub added_at: DateTime<Utc>,
+    pub feature_count: usize,
+    /// `planned` in a preview, else `deleted`, `archived` or `failed`
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks_removed: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Workspace response
 #[derive(Debug, Serialize)]
 pub struct WorkspaceResponse {
@@ -83,11 +322,184 @@ pub struct WorkspaceResponse {
     pub created_at: DateTime<Utc>,
 }
 
+/// A workspace with its stored settings and the configuration in effect
+#[derive(Debug, Serialize)]
+pub struct WorkspaceDetailResponse {
+    #[serde(flatten)]
+    pub workspace: WorkspaceResponse,
+    pub settings: WorkspaceSettings,
+    /// Every setting's value and where it came from
+    pub effective: BTreeMap<String, ConfigEntryResponse>,
+}
+
+/// A cloned workspace and what was copied into it
+#[derive(Debug, Serialize)]
+pub struct CloneWorkspaceResponse {
+    pub workspace: WorkspaceDetailResponse,
+    pub source_workspace_id: String,
+    /// Dataset IDs in the source and in the clone
+    pub datasets: Vec<ClonedDatasetResponse>,
+    /// Usage the clone takes up
+    pub usage: UsageDelta,
+    pub verification: CloneVerificationResponse,
+}
+
+/// A dataset's ID in the source workspace and in the clone
+#[derive(Debug, Serialize)]
+pub struct ClonedDatasetResponse {
+    pub source_id: String,
+    pub id: String,
+}
+
+/// Counts read from the source compared with those in the clone
+#[derive(Debug, Serialize)]
+pub struct CloneVerificationResponse {
+    pub source: CloneCountsResponse,
+    pub clone: CloneCountsResponse,
+}
+
+/// What one side of a clone holds
+#[derive(Debug, Serialize)]
+pub struct CloneCountsResponse {
+    pub datasets: usize,
+    pub features: usize,
+    pub chunks: usize,
+    pub embeddings: usize,
+    pub blobs: usize,
+}
+
+impl From<CloneCounts> for CloneCountsResponse {
+    fn from(counts: CloneCounts) -> Self {
+        Self {
+            datasets: counts.datasets,
+            features: counts.features,
+            chunks: counts.chunks,
+            embeddings: counts.embeddings,
+            blobs: counts.blobs,
+        }
+    }
+}
+
+/// Saved area response
+#[derive(Debug, Serialize)]
+pub struct AreaResponse {
+    pub name: String,
+    pub geometry: serde_json::Value,
+    pub vertices: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_feature: Option<AreaSourceResponse>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Feature a saved area was copied from
+#[derive(Debug, Serialize)]
+pub struct AreaSourceResponse {
+    pub dataset: String,
+    pub id: String,
+}
+
+impl From<SavedArea> for AreaResponse {
+    fn from(area: SavedArea) -> Self {
+        Self {
+            vertices: area.vertex_count(),
+            geometry: area.geometry.to_geojson(),
+            from_feature: area.source.map(|source| AreaSourceResponse {
+                dataset: source.dataset_id.0.to_string(),
+                id: source.feature_id.0.to_string(),
+            }),
+            name: area.name,
+            created_at: area.created_at,
+        }
+    }
+}
+
+/// Query preset response
+#[derive(Debug, Serialize)]
+pub struct PresetResponse {
+    pub name: String,
+    /// Whether queries naming no preset take this one
+    pub default: bool,
+    pub options: QueryPreset,
+}
+
+impl PresetResponse {
+    /// Preset `name` of the settings, if they have one of that name
+    pub fn from_settings(settings: &WorkspaceSettings, name: &str) -> Option<Self> {
+        let options = settings.query_presets.as_ref()?.get(name)?.clone();
+        Some(Self {
+            name: name.to_string(),
+            default: settings.default_query_preset.as_deref() == Some(name),
+            options,
+        })
+    }
+}
+
+/// Workspace settings response
+#[derive(Debug, Serialize)]
+pub struct WorkspaceSettingsResponse {
+    pub workspace_id: String,
+    /// Whether any settings were stored; unset settings fall back to server defaults
+    pub stored: bool,
+    pub settings: WorkspaceSettings,
+}
+
+/// Workspace usage response
+#[derive(Debug, Serialize)]
+pub struct WorkspaceUsageResponse {
+    pub workspace_id: String,
+    pub usage: WorkspaceUsage,
+    /// Effective limits; `null` means unlimited
+    pub quotas: WorkspaceQuotas,
+    /// Chunks, content bytes and embeddings of each dataset in the index
+    pub datasets: Vec<DatasetIndexUsage>,
+}
+
+/// Index counters of one dataset
+#[derive(Debug, Serialize)]
+pub struct DatasetIndexUsage {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub stats: DatasetIndexStats,
+}
+
+/// Events of a workspace counted per bucket, with its notable events
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    pub workspace_id: String,
+    #[serde(flatten)]
+    pub timeline: Timeline,
+}
+
+/// A page of the change event feed
+#[derive(Debug, Serialize)]
+pub struct EventsResponse {
+    /// Events in sequence order
+    pub events: Vec<ChangeEvent>,
+    /// Sequence number to pass as `after_seq` for the next page
+    pub next_after_seq: u64,
+    /// Newest sequence number handed out, 0 before the first event
+    pub latest_seq: u64,
+    /// Oldest event kept; readers behind it get 410 Gone
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub earliest_seq: Option<u64>,
+}
+
+/// Stored cursor of a change event consumer
+#[derive(Debug, Serialize)]
+pub struct EventCursorResponse {
+    pub consumer: String,
+    /// Last event the consumer acknowledged, 0 if it never did
+    pub seq: u64,
+}
+
 /// Index status response for workspace-scoped index operations
 #[derive(Debug, Serialize)]
 pub struct IndexStatusResponse {
     pub built: bool,
     pub rebuilding: bool,
+    /// Whether the rebuild waits for the storage backend to recover
+    pub paused: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1,9 +1,10 @@
 use axum::{
-    http::StatusCode,
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde::Serialize;
+use std::time::Duration;
 
 /// Unified API error type
 #[derive(Debug)]
@@ -11,6 +12,10 @@ pub struct ApiError {
     pub status: StatusCode,
     pub message: String,
     pub details: Option<String>,
+    /// Sent as `Retry-After`, in whole seconds
+    pub retry_after: Option<Duration>,
+    /// Stable identifier of the error for clients to branch on
+    pub code: Option<&'static str>,
 }
 
 impl ApiError {
@@ -19,6 +24,28 @@ impl ApiError {
             status: StatusCode::BAD_REQUEST,
             message: message.into(),
             details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
         }
     }
 
@@ -27,6 +54,78 @@ impl ApiError {
             status: StatusCode::NOT_FOUND,
             message: message.into(),
             details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn not_acceptable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_ACCEPTABLE,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::CONFLICT,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::PAYLOAD_TOO_LARGE,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn gone(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::GONE,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn too_many_requests(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn bad_gateway(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_GATEWAY,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
         }
     }
 
@@ -35,6 +134,28 @@ impl ApiError {
             status: StatusCode::INTERNAL_SERVER_ERROR,
             message: message.into(),
             details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn not_implemented(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_IMPLEMENTED,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
+        }
+    }
+
+    pub fn service_unavailable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+            code: None,
         }
     }
 
@@ -42,12 +163,26 @@ impl ApiError {
         self.details = Some(details.into());
         self
     }
+
+    /// Name the error with a code clients can branch on
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Tell the caller when to try again; rounded up to whole seconds
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
 }
 
 #[derive(Serialize)]
 struct ErrorBody {
     error: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<String>,
 }
 
@@ -55,9 +190,17 @@ impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
         let body = ErrorBody {
             error: self.message,
+            code: self.code,
             details: self.details,
         };
-        (self.status, Json(body)).into_response()
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(retry_after) = self.retry_after {
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+        }
+        response
     }
 }
 
@@ -67,7 +210,122 @@ impl From<georag_core::error::GeoragError> for ApiError {
             georag_core::error::GeoragError::IndexNotBuilt(_) => {
                 Self::not_found("Index not built").with_details(err.to_string())
             }
+            georag_core::error::GeoragError::DatasetNotFound { .. } => {
+                Self::not_found("Dataset not found").with_details(err.to_string())
+            }
+            georag_core::error::GeoragError::GeometryTooComplex { .. } => {
+                Self::unprocessable("Filter geometry is too complex").with_details(err.to_string())
+            }
+            // Stored bytes are a size limit; the other quotas count items
+            georag_core::error::GeoragError::QuotaExceeded { resource, .. } => match resource {
+                georag_core::models::QuotaResource::BlobBytes => {
+                    Self::payload_too_large("Workspace quota exceeded")
+                        .with_details(err.to_string())
+                }
+                _ => Self::unprocessable("Workspace quota exceeded").with_details(err.to_string()),
+            },
+            georag_core::error::GeoragError::ReadOnly { .. } => {
+                Self::forbidden("Store is read-only").with_details(err.to_string())
+            }
+            georag_core::error::GeoragError::IndexSchemaOutdated { .. }
+            | georag_core::error::GeoragError::IndexSchemaTooNew { .. } => {
+                Self::conflict("Index schema version is not supported")
+                    .with_details(err.to_string())
+            }
             _ => Self::internal("Internal error").with_details(err.to_string()),
         }
     }
 }
+
+impl From<georag_service::ServiceError> for ApiError {
+    fn from(err: georag_service::ServiceError) -> Self {
+        use georag_service::ServiceError;
+
+        match err {
+            ServiceError::UnsupportedFormat(e) => {
+                Self::bad_request("Unsupported file format").with_details(e.to_string())
+            }
+            ServiceError::InvalidOptions(e) => {
+                Self::bad_request("Invalid format options").with_details(e.to_string())
+            }
+            ServiceError::InvalidDataset(errors) => {
+                Self::bad_request("Invalid dataset").with_details(errors.join("; "))
+            }
+            ServiceError::Read(e) => {
+                Self::bad_request("Failed to parse file").with_details(e.to_string())
+            }
+            ServiceError::CrsMismatch { .. } => {
+                Self::bad_request("CRS mismatch").with_details(err.to_string())
+            }
+            ServiceError::InvalidQuery(message) | ServiceError::InvalidJoin(message) => {
+                Self::bad_request(message)
+            }
+            ServiceError::InvalidArea(message) => {
+                Self::bad_request("Invalid area").with_details(message)
+            }
+            ServiceError::AreaNotFound { .. }
+            | ServiceError::FeatureNotFound { .. }
+            | ServiceError::DatasetNotFound { .. }
+            | ServiceError::ChunkNotFound { .. } => Self::not_found(err.to_string()),
+            ServiceError::AreaExists { .. } => Self::conflict(err.to_string()),
+            ServiceError::InvalidPreset { name, reason } => {
+                Self::bad_request(format!("Invalid preset {}", name)).with_details(reason)
+            }
+            ServiceError::PresetNotFound { .. } => Self::not_found(err.to_string()),
+            ServiceError::InvalidUrl(url) => Self::bad_request("Invalid URL")
+                .with_details(format!("Expected an http or https URL, got {}", url)),
+            ServiceError::Download { .. } => {
+                Self::bad_gateway("Failed to download dataset").with_details(err.to_string())
+            }
+            ServiceError::DownloadRefused { .. } => {
+                Self::forbidden("Download refused").with_details(err.to_string())
+            }
+            ServiceError::DownloadTooLarge { .. } => {
+                Self::payload_too_large("Download too large").with_details(err.to_string())
+            }
+            ServiceError::InvalidFilter(message) => {
+                Self::bad_request("Invalid dataset filter").with_details(message)
+            }
+            ServiceError::InvalidTimeline(message) => {
+                Self::bad_request("Invalid timeline").with_details(message)
+            }
+            ServiceError::InvalidWorkspaceName(message) => {
+                Self::bad_request("Invalid workspace name").with_details(message)
+            }
+            ServiceError::WorkspaceExists { .. } => Self::conflict(err.to_string()),
+            ServiceError::WorkspaceNotFound { .. } => Self::not_found(err.to_string()),
+            ServiceError::InvalidSettings(e) => {
+                Self::bad_request("Invalid workspace settings").with_details(e.to_string())
+            }
+            ServiceError::SettingLocked { .. } => {
+                Self::conflict("Setting cannot change while the workspace holds data")
+                    .with_details(err.to_string())
+            }
+            ServiceError::InvalidCursor(message) => Self::bad_request("Invalid cursor")
+                .with_details(message)
+                .with_code("invalid_cursor"),
+            ServiceError::CursorExpired => Self::gone("Cursor expired")
+                .with_details("The results of this query are no longer kept; run the query again")
+                .with_code("cursor_expired"),
+            ServiceError::CursorInvalidated => Self::gone("Cursor invalidated")
+                .with_details("The index changed since the query ran; run the query again")
+                .with_code("cursor_invalidated"),
+            ServiceError::CursorNotFound => Self::not_found("Cursor not found")
+                .with_details("No query of this workspace has this cursor")
+                .with_code("cursor_not_found"),
+            ServiceError::ExampleNotIndexed { .. } => Self::unprocessable("Example not indexed")
+                .with_details(format!("{}; run 'georag build' and try again", err))
+                .with_code("example_not_indexed"),
+            ServiceError::InvalidEventRead(message) => {
+                Self::bad_request("Invalid event read").with_details(message)
+            }
+            ServiceError::EventsPruned { .. } => Self::gone("Events pruned")
+                .with_details(format!(
+                    "{}; start over from a fresh copy of the workspace and read after the newest event",
+                    err
+                ))
+                .with_code("events_pruned"),
+            ServiceError::Core(e) => e.into(),
+        }
+    }
+}
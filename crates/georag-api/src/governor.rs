@@ -0,0 +1,227 @@
+//! Instance-wide caps on concurrent expensive operations
+//!
+//! Ingests, index builds, embedding batches and large exports each take a
+//! slot of the [`ResourceGovernor`] while they run. Requests finding every
+//! slot taken wait in a queue of bounded depth; once the queue is full they
+//! are turned away with 429 and a `Retry-After`. With a shared [`SlotStore`]
+//! the slots are also taken there, so CLI runs against the same backend
+//! count towards the same limits.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::resources::{ResourceGate, ResourceGuard, ResourceKind, ResourceLimits};
+use georag_store::ports::SlotStore;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::error::ApiError;
+
+/// How often a slot of the shared store is tried again while all are held
+const SHARED_SLOT_POLL: Duration = Duration::from_millis(200);
+
+/// Slots and queue of one resource kind
+struct Pool {
+    limit: usize,
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// Caps concurrent ingests, builds, embedding batches and large exports
+pub struct ResourceGovernor {
+    limits: ResourceLimits,
+    pools: BTreeMap<ResourceKind, Arc<Pool>>,
+    shared: Option<Arc<dyn SlotStore>>,
+}
+
+/// A caller turned away because the queue of a resource is full
+#[derive(Debug, Clone)]
+pub struct Busy {
+    pub kind: ResourceKind,
+    pub limit: usize,
+    pub queued: usize,
+    pub retry_after: Duration,
+}
+
+impl From<Busy> for ApiError {
+    fn from(busy: Busy) -> Self {
+        ApiError::too_many_requests("Server busy")
+            .with_details(format!(
+                "All {} {} slots are taken and {} requests are already waiting",
+                busy.limit, busy.kind, busy.queued
+            ))
+            .with_retry_after(busy.retry_after)
+    }
+}
+
+/// Slot of a resource, freed when dropped
+pub struct ResourcePermit {
+    _local: OwnedSemaphorePermit,
+    _shared: Option<ResourceGuard>,
+}
+
+/// A place in the queue of a resource, left when admitted or dropped
+pub struct Ticket {
+    kind: ResourceKind,
+    pool: Arc<Pool>,
+    shared: Option<Arc<dyn SlotStore>>,
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        self.pool.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Ticket {
+    /// Wait for a slot, here and then in the shared store
+    ///
+    /// A shared store that fails is logged and skipped: its outage is the
+    /// store supervisor's to report, and the local cap still holds.
+    pub async fn admit(self) -> ResourcePermit {
+        // The semaphore is never closed
+        let local = self.pool.semaphore.clone().acquire_owned().await.expect("semaphore closed");
+        let shared = match &self.shared {
+            Some(store) => {
+                match store.acquire_slot(self.kind, self.pool.limit, SHARED_SLOT_POLL).await {
+                    Ok(guard) => Some(guard),
+                    Err(e) => {
+                        tracing::warn!(
+                            resource = %self.kind,
+                            error = %e,
+                            "Shared slot unavailable, relying on this instance's limit"
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        ResourcePermit { _local: local, _shared: shared }
+    }
+}
+
+/// Holders and queue of one resource, as reported by `/metrics`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceStats {
+    pub kind: ResourceKind,
+    pub limit: usize,
+    /// Operations holding a slot
+    pub holders: usize,
+    /// Requests waiting for a slot
+    pub queued: usize,
+    /// Requests turned away since startup
+    pub rejected: u64,
+}
+
+impl ResourceGovernor {
+    pub fn new(limits: ResourceLimits) -> Self {
+        let pools = ResourceKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let limit = limits.limit(kind);
+                let pool = Pool {
+                    limit,
+                    semaphore: Arc::new(Semaphore::new(limit)),
+                    queued: AtomicUsize::new(0),
+                    rejected: AtomicU64::new(0),
+                };
+                (kind, Arc::new(pool))
+            })
+            .collect();
+        Self { limits, pools, shared: None }
+    }
+
+    /// Also take slots from a store shared with other processes
+    pub fn with_shared_slots(mut self, store: Arc<dyn SlotStore>) -> Self {
+        self.shared = Some(store);
+        self
+    }
+
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    fn pool(&self, kind: ResourceKind) -> &Arc<Pool> {
+        &self.pools[&kind]
+    }
+
+    fn ticket(&self, kind: ResourceKind) -> Ticket {
+        Ticket {
+            kind,
+            pool: self.pool(kind).clone(),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Take a place in the queue of `kind`, or be turned away when it is full
+    ///
+    /// Requests that find a free slot always get a place; the queue depth
+    /// only counts those left waiting.
+    pub fn enqueue(&self, kind: ResourceKind) -> std::result::Result<Ticket, Busy> {
+        let pool = self.pool(kind);
+        let available = pool.semaphore.available_permits();
+        let max_queued = self.limits.max_queued;
+        let entered = pool.queued.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+            (queued < available + max_queued).then_some(queued + 1)
+        });
+
+        match entered {
+            Ok(_) => Ok(self.ticket(kind)),
+            Err(queued) => {
+                pool.rejected.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(resource = %kind, queued, "Turning away a request, queue full");
+                Err(Busy {
+                    kind,
+                    limit: pool.limit,
+                    queued: queued.saturating_sub(available),
+                    retry_after: self.limits.retry_after,
+                })
+            }
+        }
+    }
+
+    /// Wait for a slot of `kind`, or be turned away when the queue is full
+    pub async fn acquire(&self, kind: ResourceKind) -> std::result::Result<ResourcePermit, Busy> {
+        Ok(self.enqueue(kind)?.admit().await)
+    }
+
+    /// Holders and queue of every resource
+    pub fn stats(&self) -> Vec<ResourceStats> {
+        self.pools
+            .iter()
+            .map(|(&kind, pool)| {
+                let available = pool.semaphore.available_permits();
+                let queued = pool.queued.load(Ordering::SeqCst);
+                ResourceStats {
+                    kind,
+                    limit: pool.limit,
+                    holders: pool.limit - available,
+                    // Tickets holding a free slot they are about to take are not waiting
+                    queued: queued.saturating_sub(available),
+                    rejected: pool.rejected.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for ResourceGovernor {
+    fn default() -> Self {
+        Self::new(ResourceLimits::default())
+    }
+}
+
+/// Background work such as embedding batches of a build waits however long
+/// the queue is; it was accepted before it needed the slot
+#[async_trait]
+impl ResourceGate for ResourceGovernor {
+    async fn acquire(&self, kind: ResourceKind) -> Result<ResourceGuard> {
+        self.pool(kind).queued.fetch_add(1, Ordering::SeqCst);
+        Ok(ResourceGuard::new(self.ticket(kind).admit().await))
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+
+use crate::auth::Caller;
+use crate::dto::{CompactParams, CompactResponse, ConfigEntryResponse, ConfigResponse};
+use crate::error::ApiError;
+use crate::reload::{reload, ReloadOutcome, ReloadTrigger};
+use crate::state::AppState;
+
+/// Effective configuration of this instance, with secrets masked
+///
+/// Lists the latest configuration reloads too. Keys restricted to some dataset tags or bound to some workspaces are
+/// refused; with authentication disabled every caller is unrestricted.
+pub async fn get_config(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> Result<Json<ConfigResponse>, ApiError> {
+    if caller.visibility.is_restricted() {
+        return Err(ApiError::forbidden("The configuration requires an unrestricted API key"));
+    }
+    caller.require_all_workspaces()?;
+
+    let config = state
+        .live_config()
+        .effective_config
+        .iter()
+        .map(|(key, (value, source))| {
+            (key.clone(), ConfigEntryResponse { value: value.clone(), source: *source })
+        })
+        .collect();
+    let history = state.reload_history.lock().await;
+
+    Ok(Json(ConfigResponse {
+        config,
+        last_reloaded: history.last_reloaded(),
+        reloads: history.outcomes().cloned().collect(),
+    }))
+}
+
+/// Load the configuration again and apply what can change without a restart
+///
+/// Changed values that need a restart are listed as refused. A configuration
+/// that fails to load or validate is rejected with 422 and the running one
+/// is kept. Requires an unrestricted API key like the configuration endpoint.
+pub async fn reload_config(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+) -> Result<Json<ReloadOutcome>, ApiError> {
+    if caller.visibility.is_restricted() {
+        return Err(ApiError::forbidden("Reloading requires an unrestricted API key"));
+    }
+    caller.require_all_workspaces()?;
+
+    let outcome = reload(&state, ReloadTrigger::Request).await;
+    match outcome.error {
+        Some(error) => Err(ApiError::unprocessable("The configuration failed to load")
+            .with_details(format!("{}; the running configuration is kept", error))),
+        None => Ok(Json(outcome)),
+    }
+}
+
+/// Find orphaned embeddings and chunks, deleting them when `apply=true`
+///
+/// Refused with 409 while an index rebuild holds the build lock. Scans the
+/// stores of the default workspace.
+pub async fn compact(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<CompactParams>,
+) -> Result<Json<CompactResponse>, ApiError> {
+    if caller.visibility.is_restricted() {
+        return Err(ApiError::forbidden("Compaction requires an unrestricted API key"));
+    }
+    caller.require_all_workspaces()?;
+
+    let _build = state.build_lock.try_lock().map_err(|_| {
+        ApiError::conflict("An index rebuild is in progress")
+            .with_details("Retry once the rebuild has finished")
+    })?;
+
+    let service = state.compaction_service();
+    let plan = service.scan().await.map_err(|e| {
+        tracing::error!(error = %e, "Compaction scan failed");
+        ApiError::internal("Failed to scan the index stores").with_details(e.to_string())
+    })?;
+
+    let report = if params.apply && !plan.is_empty() {
+        let report = service
+            .apply(&plan, |progress| {
+                tracing::info!(
+                    deleted = progress.deleted,
+                    total = progress.total,
+                    "Compaction progress"
+                );
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!(error = %e, "Compaction failed");
+                ApiError::internal("Failed to delete orphaned data").with_details(e.to_string())
+            })?;
+        Some(report)
+    } else {
+        None
+    };
+
+    Ok(Json(CompactResponse {
+        embeddings_scanned: plan.embeddings_scanned,
+        chunks_scanned: plan.chunks_scanned,
+        orphan_embeddings: plan.orphan_embeddings.len(),
+        dangling_chunks: plan.dangling_chunks.len(),
+        degenerate_chunks: plan.degenerate_chunks.len(),
+        reclaimable_bytes: plan.reclaimable_bytes,
+        applied: report.is_some(),
+        embeddings_deleted: report.map_or(0, |r| r.embeddings_deleted),
+        chunks_deleted: report.map_or(0, |r| r.chunks_deleted),
+        degenerate_deleted: report.map_or(0, |r| r.degenerate_deleted),
+    }))
+}
@@ -0,0 +1,91 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use georag_core::models::{DatasetId, FeatureId};
+use georag_service::AreaService;
+
+use crate::auth::Caller;
+use crate::dto::{AreaPath, AreaResponse, CreateAreaRequest, DeleteResponse};
+use crate::error::ApiError;
+use crate::workspace::Workspace;
+
+/// Save a named area from a GeoJSON geometry or a stored feature
+pub async fn create_area(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<CreateAreaRequest>,
+) -> Result<(StatusCode, Json<AreaResponse>), ApiError> {
+    tracing::info!(name = %request.name, "Creating area");
+
+    let (state, workspace_id) = (&workspace.state, workspace.id);
+    let service = state.area_service();
+
+    let area = match (&request.geometry, &request.from_feature) {
+        (Some(geometry), None) => {
+            let geometry = AreaService::parse_geometry(geometry)?;
+            service.create(workspace_id, &request.name, &geometry).await?
+        }
+        (None, Some(feature)) => {
+            let dataset_id: u64 = feature
+                .dataset
+                .parse()
+                .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+            let feature_id: u64 = feature
+                .id
+                .parse()
+                .map_err(|_| ApiError::bad_request("Invalid feature ID format"))?;
+
+            // Features of datasets hidden from the caller cannot be copied
+            state
+                .spatial_store
+                .get_dataset(DatasetId(dataset_id))
+                .await
+                .map_err(|e| {
+                    ApiError::internal("Failed to load dataset").with_details(e.to_string())
+                })?
+                .filter(|dataset| caller.visibility.allows(&dataset.tags))
+                .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+
+            service
+                .create_from_feature(
+                    workspace_id,
+                    &request.name,
+                    DatasetId(dataset_id),
+                    FeatureId(feature_id),
+                )
+                .await?
+        }
+        _ => return Err(ApiError::bad_request("Specify either geometry or from_feature")),
+    };
+
+    Ok((StatusCode::CREATED, Json(area.into())))
+}
+
+/// List the saved areas of the workspace, sorted by name
+pub async fn list_areas(
+    Extension(workspace): Extension<Workspace>,
+) -> Result<Json<Vec<AreaResponse>>, ApiError> {
+    tracing::info!("Listing areas");
+
+    let areas = workspace.state.area_service().list(workspace.id).await?;
+
+    Ok(Json(areas.into_iter().map(AreaResponse::from).collect()))
+}
+
+pub async fn get_area(
+    Extension(workspace): Extension<Workspace>,
+    Path(AreaPath { name }): Path<AreaPath>,
+) -> Result<Json<AreaResponse>, ApiError> {
+    let area = workspace.state.area_service().resolve(workspace.id, &name).await?;
+
+    Ok(Json(area.into()))
+}
+
+pub async fn delete_area(
+    Extension(workspace): Extension<Workspace>,
+    Path(AreaPath { name }): Path<AreaPath>,
+) -> Result<Json<DeleteResponse>, ApiError> {
+    tracing::info!(name = %name, "Deleting area");
+
+    workspace.state.area_service().delete(workspace.id, &name).await?;
+
+    Ok(Json(DeleteResponse::success("area", &name)))
+}
@@ -1,101 +1,592 @@
-use std::sync::Arc;
-
 use axum::{
-    extract::{Path, State},
-    Json,
+    extract::{Path, Query},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use georag_core::models::{
+    normalize_tags, sort_datasets, AuditEvent, AuditEventKind, ChunkingSettings, DatasetId,
+    DatasetMeta, FeatureId, TagVisibility, UsageDelta,
 };
-use georag_core::models::{DatasetId, DatasetMeta};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::resources::ResourceKind;
+use georag_retrieval::PropertySelection;
+use georag_service::{BulkAction, BulkReport, DatasetFilter, NamePattern, SchemaService};
+use serde_json::{json, Value};
 
-use crate::dto::{DatasetInfo, DatasetResponse, DeleteResponse};
+use crate::auth::Caller;
+use crate::dto::{
+    BulkDatasetStatus, BulkDeleteFilter, BulkDeleteRequest, BulkDeleteResponse, DatasetFeaturePath,
+    DatasetInfo, DatasetPath, DatasetResponse, DeleteResponse, FeatureParams, ListDatasetsParams,
+    SampleParams, UpdateDatasetTagsRequest,
+};
 use crate::error::ApiError;
 use crate::state::AppState;
+use crate::workspace::Workspace;
 
-/// List all datasets (legacy endpoint)
+/// List all datasets of the workspace visible to the caller (legacy endpoint)
+///
+/// Sorted by name unless `sort` and `order` ask otherwise; ties are listed
+/// by name and ID so repeated calls return the same order.
 pub async fn list_datasets(
-    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<ListDatasetsParams>,
 ) -> Result<Json<Vec<DatasetInfo>>, ApiError> {
-    tracing::info!("Listing datasets");
+    tracing::info!(workspace = %workspace.name, "Listing datasets");
 
-    let datasets = state.spatial_store.list_datasets().await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to list datasets");
-        ApiError::internal("Failed to list datasets").with_details(e.to_string())
-    })?;
+    let visibility = caller.visibility;
+    let mut datasets = workspace
+        .state
+        .spatial_store
+        .list_visible_datasets(&visibility)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list datasets");
+            ApiError::internal("Failed to list datasets").with_details(e.to_string())
+        })?;
+
+    sort_datasets(&mut datasets, params.sort, params.order);
 
     let infos: Vec<DatasetInfo> = datasets.iter().map(dataset_meta_to_info).collect();
     Ok(Json(infos))
 }
 
-/// List datasets for a specific workspace
+/// List datasets for a specific workspace that are visible to the caller
+///
+/// Sorted like [`list_datasets`].
 pub async fn list_datasets_for_workspace(
-    State(state): State<Arc<AppState>>,
-    Path(workspace_id): Path<String>,
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<ListDatasetsParams>,
 ) -> Result<Json<Vec<DatasetResponse>>, ApiError> {
-    tracing::info!(workspace_id = %workspace_id, "Listing datasets for workspace");
-
-    let id = workspace_id
-        .parse()
-        .map_err(|_| ApiError::bad_request("Invalid workspace ID format"))?;
-
-    let workspace = state.workspace_store.get_workspace(id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to check workspace existence");
-        ApiError::internal("Failed to verify workspace").with_details(e.to_string())
-    })?;
-
-    if workspace.is_none() {
-        return Err(ApiError::not_found("Workspace not found"));
-    }
+    tracing::info!(workspace_id = %workspace.id, "Listing datasets for workspace");
 
-    let datasets = state.workspace_store.list_datasets_for_workspace(id).await.map_err(|e| {
+    let state = &workspace.state;
+    let datasets = state.workspace_datasets(workspace.id).await.map_err(|e| {
         tracing::error!(error = %e, "Failed to list datasets for workspace");
         ApiError::internal("Failed to list datasets").with_details(e.to_string())
     })?;
 
+    let mut datasets = visible_datasets(state, datasets, &caller.visibility).await?;
+    sort_datasets(&mut datasets, params.sort, params.order);
+
     let responses: Vec<DatasetResponse> =
         datasets.into_iter().map(dataset_meta_to_response).collect();
     Ok(Json(responses))
 }
 
-/// Delete a dataset within a workspace
-pub async fn delete_dataset(
-    State(state): State<Arc<AppState>>,
-    Path((workspace_id, dataset_id)): Path<(String, String)>,
-) -> Result<Json<DeleteResponse>, ApiError> {
-    tracing::info!(workspace_id = %workspace_id, dataset_id = %dataset_id, "Deleting dataset");
+/// Sample features of a dataset as a GeoJSON FeatureCollection
+///
+/// `strategy=spatial` spreads the sample across the dataset extent instead of
+/// drawing it uniformly, and `include` or `exclude` limit the properties
+/// returned. Datasets hidden from the caller are reported as not found.
+pub async fn sample_dataset(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetPath { dataset_id }): Path<DatasetPath>,
+    Query(params): Query<SampleParams>,
+) -> Result<Json<Value>, ApiError> {
+    let state = &workspace.state;
+    tracing::info!(dataset_id = %dataset_id, n = params.n, strategy = %params.strategy, "Sampling dataset");
 
-    let ws_id = workspace_id
+    let ds_id: u64 = dataset_id
         .parse()
-        .map_err(|_| ApiError::bad_request("Invalid workspace ID format"))?;
+        .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+    let selection = property_selection(params.include.as_deref(), params.exclude.as_deref())?;
+
+    let dataset = state
+        .spatial_store
+        .get_dataset(DatasetId(ds_id))
+        .await
+        .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+        .filter(|dataset| caller.visibility.allows(&dataset.tags))
+        .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+
+    let n = params.n.min(state.query_config.max_sample);
+    let features = state
+        .spatial_store
+        .sample_features(dataset.id, n, params.strategy)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to sample dataset");
+            ApiError::internal("Failed to sample dataset").with_details(e.to_string())
+        })?;
+
+    let features: Vec<Value> = features
+        .into_iter()
+        .map(|mut feature| {
+            selection.apply_to_feature(&mut feature.properties);
+            json!({
+                "type": "Feature",
+                "id": feature.id.0,
+                "geometry": feature.geometry_geojson(),
+                "properties": feature.properties,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "type": "FeatureCollection",
+        "dataset": dataset.name,
+        "strategy": params.strategy,
+        "features": features,
+    })))
+}
+
+/// JSON Schema of a dataset's features, inferred from the stored features
+///
+/// Lists each property's types, how many features carry it and example
+/// values, with the geometry types, CRS and chunking settings under
+/// `x-georag`. Datasets hidden from the caller are reported as not found.
+pub async fn get_dataset_schema(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetPath { dataset_id }): Path<DatasetPath>,
+) -> Result<Json<Value>, ApiError> {
+    let state = &workspace.state;
+    tracing::info!(dataset_id = %dataset_id, "Inferring dataset schema");
 
     let ds_id: u64 = dataset_id
         .parse()
         .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
 
-    let workspace = state.workspace_store.get_workspace(ws_id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to check workspace existence");
-        ApiError::internal("Failed to verify workspace").with_details(e.to_string())
-    })?;
+    let dataset = state
+        .spatial_store
+        .get_dataset(DatasetId(ds_id))
+        .await
+        .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+        .filter(|dataset| caller.visibility.allows(&dataset.tags))
+        .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+
+    let generator = ChunkGenerator::default().with_properties(state.chunk_properties.clone());
+    let schema = SchemaService::new(state.spatial_store.clone())
+        .with_chunking(ChunkingSettings::from(&generator))
+        .dataset_schema(dataset.id)
+        .await?;
+
+    Ok(Json(schema.to_json_schema()))
+}
+
+/// One feature of a dataset with its full geometry
+///
+/// Query results simplified to a vertex budget point here for the geometry
+/// as stored; `include` or `exclude` limit the properties returned. Features
+/// of datasets hidden from the caller are reported as not found.
+pub async fn get_dataset_feature(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetFeaturePath { dataset_id, feature_id }): Path<DatasetFeaturePath>,
+    Query(params): Query<FeatureParams>,
+) -> Result<Json<Value>, ApiError> {
+    let state = &workspace.state;
+    tracing::info!(dataset_id = %dataset_id, feature_id = %feature_id, "Fetching dataset feature");
+
+    let ds_id: u64 = dataset_id
+        .parse()
+        .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+    let feature_id: u64 = feature_id
+        .parse()
+        .map_err(|_| ApiError::bad_request("Invalid feature ID format"))?;
+    let selection = property_selection(params.include.as_deref(), params.exclude.as_deref())?;
+
+    let dataset = state
+        .spatial_store
+        .get_dataset(DatasetId(ds_id))
+        .await
+        .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+        .filter(|dataset| caller.visibility.allows(&dataset.tags))
+        .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+
+    // Feature IDs are store-wide, so look the feature up among the
+    // dataset's own features rather than by ID alone
+    let mut feature = state
+        .spatial_store
+        .get_features_for_dataset(dataset.id)
+        .await
+        .map_err(|e| ApiError::internal("Failed to load features").with_details(e.to_string()))?
+        .into_iter()
+        .find(|feature| feature.id == FeatureId(feature_id))
+        .ok_or_else(|| ApiError::not_found("Feature not found"))?;
+
+    selection.apply_to_feature(&mut feature.properties);
+    Ok(Json(json!({
+        "type": "Feature",
+        "id": feature.id.0,
+        "dataset": dataset.name,
+        "geometry": feature.geometry_geojson(),
+        "properties": feature.properties,
+    })))
+}
+
+/// Property selection from comma-separated `include` and `exclude` lists
+fn property_selection(
+    include: Option<&str>,
+    exclude: Option<&str>,
+) -> Result<PropertySelection, ApiError> {
+    let keys = |list: Option<&str>| -> Vec<String> {
+        list.unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let selection = PropertySelection {
+        include: keys(include),
+        exclude: keys(exclude),
+    };
+    selection
+        .validate()
+        .map_err(|e| ApiError::bad_request("Invalid property selection").with_details(e))?;
+    Ok(selection)
+}
+
+/// Download the original file of a dataset
+///
+/// Served with the content type recorded at ingest, as an attachment named
+/// after the ingested file, and with the SHA-256 as ETag when it was hashed.
+/// Datasets hidden from the caller, and datasets whose file was not kept
+/// (e.g. over the size limit), are reported as not found.
+pub async fn download_dataset_source(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetPath { dataset_id }): Path<DatasetPath>,
+) -> Result<Response, ApiError> {
+    let state = &workspace.state;
+    tracing::info!(dataset_id = %dataset_id, "Downloading dataset source");
+
+    let ds_id: u64 = dataset_id
+        .parse()
+        .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+
+    let dataset = state
+        .spatial_store
+        .get_dataset(DatasetId(ds_id))
+        .await
+        .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+        .filter(|dataset| caller.visibility.allows(&dataset.tags))
+        .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
 
-    if workspace.is_none() {
-        return Err(ApiError::not_found("Workspace not found"));
+    let source = dataset
+        .format
+        .source
+        .ok_or_else(|| ApiError::not_found("The original file of this dataset was not kept"))?;
+
+    let _slot = if source.size >= state.governor.limits().large_export_bytes {
+        Some(state.governor.acquire(ResourceKind::Export).await?)
+    } else {
+        None
+    };
+
+    let content = state
+        .blob_store
+        .get_blob(dataset.id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to load dataset source");
+            ApiError::internal("Failed to load original file").with_details(e.to_string())
+        })?
+        .ok_or_else(|| ApiError::not_found("Original file not found"))?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_str(&source.content_type)
+            .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+    );
+    headers.insert(header::CONTENT_DISPOSITION, content_disposition(&source.filename));
+    if let Some(sha256) = &source.sha256 {
+        if let Ok(etag) = HeaderValue::from_str(&format!("\"{}\"", sha256)) {
+            headers.insert(header::ETAG, etag);
+        }
     }
 
+    Ok((headers, content).into_response())
+}
+
+/// `Content-Disposition` for an attachment, with an ASCII fallback name
+/// and the exact name in RFC 5987 encoding
+fn content_disposition(filename: &str) -> HeaderValue {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+
+    HeaderValue::from_str(&format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    ))
+    .unwrap_or_else(|_| HeaderValue::from_static("attachment"))
+}
+
+/// Replace the access tags of a dataset
+///
+/// Only unrestricted callers may change tags, since a restricted key could
+/// otherwise untag a dataset to make it visible to itself.
+pub async fn update_dataset_tags(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetPath { dataset_id }): Path<DatasetPath>,
+    Json(request): Json<UpdateDatasetTagsRequest>,
+) -> Result<Json<DatasetResponse>, ApiError> {
+    tracing::info!(workspace_id = %workspace.id, dataset_id = %dataset_id, "Updating dataset tags");
+
+    caller.require_unrestricted()?;
+
+    let ds_id: u64 = dataset_id
+        .parse()
+        .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+
+    let state = &workspace.state;
+    let tags = normalize_tags(&request.tags);
     state
-        .workspace_store
-        .delete_dataset_in_workspace(ws_id, DatasetId(ds_id))
+        .spatial_store
+        .set_dataset_tags(DatasetId(ds_id), &tags)
         .await
         .map_err(|e| {
-            tracing::error!(error = %e, "Failed to delete dataset");
-            ApiError::internal("Failed to delete dataset").with_details(e.to_string())
+            tracing::error!(error = %e, "Failed to update dataset tags");
+            ApiError::from(e)
+        })?;
+
+    let dataset = state
+        .spatial_store
+        .get_dataset(DatasetId(ds_id))
+        .await
+        .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+        .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+    state.event_log_service().record_dataset_updated(workspace.id, &dataset).await;
+
+    Ok(Json(DatasetResponse {
+        id: dataset.id.0,
+        name: dataset.name,
+        geometry_type: format!("{:?}", dataset.geometry_type),
+        feature_count: dataset.feature_count,
+        crs: dataset.crs,
+        added_at: dataset.added_at,
+        tags: dataset.tags,
+        license: dataset.license,
+        attribution: dataset.attribution,
+        preview: dataset.format.preview,
+    }))
+}
+
+/// Keep the workspace datasets the caller may see
+///
+/// Tags are read from the spatial store, which holds the current tags of
+/// every ingested dataset.
+async fn visible_datasets(
+    state: &AppState,
+    datasets: Vec<DatasetMeta>,
+    visibility: &TagVisibility,
+) -> Result<Vec<DatasetMeta>, ApiError> {
+    if !visibility.is_restricted() {
+        return Ok(datasets);
+    }
+
+    let mut visible = Vec::with_capacity(datasets.len());
+    for mut meta in datasets {
+        let stored = state.spatial_store.get_dataset(meta.id).await.map_err(|e| {
+            ApiError::internal("Failed to load dataset").with_details(e.to_string())
         })?;
+        if let Some(dataset) = stored {
+            meta.tags = dataset.tags;
+        }
+        if visibility.allows(&meta.tags) {
+            visible.push(meta);
+        }
+    }
+    Ok(visible)
+}
+
+/// Delete a dataset within a workspace
+///
+/// Datasets hidden from the caller are reported as not found and kept.
+pub async fn delete_dataset(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Path(DatasetPath { dataset_id }): Path<DatasetPath>,
+) -> Result<Json<DeleteResponse>, ApiError> {
+    tracing::info!(workspace_id = %workspace.id, dataset_id = %dataset_id, "Deleting dataset");
+
+    let ds_id: u64 = dataset_id
+        .parse()
+        .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+
+    let (state, ws_id) = (&workspace.state, workspace.id);
+
+    // Read before deleting so the workspace's usage can be released
+    let in_workspace = state
+        .workspace_datasets(ws_id)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list datasets");
+            ApiError::internal("Failed to list datasets").with_details(e.to_string())
+        })?
+        .iter()
+        .any(|meta| meta.id == DatasetId(ds_id));
+    let dataset = if in_workspace {
+        state.spatial_store.get_dataset(DatasetId(ds_id)).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to get dataset");
+            ApiError::internal("Failed to get dataset").with_details(e.to_string())
+        })?
+    } else {
+        None
+    };
+    if dataset.as_ref().is_some_and(|dataset| !caller.visibility.allows(&dataset.tags)) {
+        return Err(ApiError::not_found("Dataset not found"));
+    }
+
+    state.delete_workspace_dataset(ws_id, DatasetId(ds_id)).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to delete dataset");
+        ApiError::internal("Failed to delete dataset").with_details(e.to_string())
+    })?;
+
+    // The dataset is gone either way; a leftover file is only logged
+    if let Err(e) = state.blob_store.delete_blob(DatasetId(ds_id)).await {
+        tracing::warn!(error = %e, "Failed to delete dataset source");
+    }
+
+    if let Some(dataset) = dataset {
+        let delta = UsageDelta::for_dataset(&dataset).negated();
+        if let Err(e) = state.workspace_store.adjust_workspace_usage(ws_id, &delta).await {
+            tracing::warn!(error = %e, "Failed to release workspace usage");
+        }
+        let event = AuditEvent::new(AuditEventKind::DatasetDeleted).with_details(&dataset.name);
+        state.audit_service().record(ws_id, event).await;
+        state.event_log_service().record_dataset_deleted(ws_id, dataset.id).await;
+    }
 
     Ok(Json(DeleteResponse::success("dataset", &dataset_id)))
 }
 
+/// Delete or archive the datasets a filter selects
+///
+/// Without `confirm` the matching datasets are only listed. Confirmed
+/// deletions are refused with 409 while an index rebuild holds the build
+/// lock; each dataset is deleted on its own and reported as deleted,
+/// archived or failed.
+pub async fn bulk_delete_datasets(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<BulkDeleteRequest>,
+) -> Result<Json<BulkDeleteResponse>, ApiError> {
+    let filter = dataset_filter(&request.filter)?;
+    let action = if request.archive {
+        BulkAction::Archive
+    } else {
+        BulkAction::Delete
+    };
+    tracing::info!(
+        workspace_id = %workspace.id,
+        action = action.as_str(),
+        confirm = request.confirm,
+        "Bulk deleting datasets"
+    );
+
+    let state = &workspace.state;
+    let service = state.bulk_dataset_service(workspace.id).await?;
+
+    // Planned under the lock too, so no build adds chunks between plan and apply
+    let _build = if request.confirm {
+        Some(state.build_lock.try_lock().map_err(|_| {
+            ApiError::conflict("An index rebuild is in progress")
+                .with_details("Retry once the rebuild has finished")
+        })?)
+    } else {
+        None
+    };
+
+    let plan = service.plan(&filter, action, &caller.visibility).await?;
+    let report = if request.confirm {
+        Some(
+            service
+                .apply(&plan, |progress| {
+                    tracing::info!(
+                        done = progress.done,
+                        total = progress.total,
+                        "Bulk delete progress"
+                    );
+                })
+                .await,
+        )
+    } else {
+        None
+    };
+
+    let datasets = plan
+        .datasets
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let result = report.as_ref().map(|report| &report.results[i]);
+            BulkDatasetStatus {
+                id: candidate.id.0.to_string(),
+                name: candidate.name.clone(),
+                added_at: candidate.added_at,
+                feature_count: candidate.feature_count,
+                status: match result {
+                    None => "planned",
+                    Some(result) if !result.succeeded() => "failed",
+                    Some(_) if action == BulkAction::Archive => "archived",
+                    Some(_) => "deleted",
+                },
+                chunks_removed: result.filter(|r| r.succeeded()).map(|r| r.chunks_removed),
+                error: result.and_then(|r| r.error.clone()),
+            }
+        })
+        .collect();
+
+    Ok(Json(BulkDeleteResponse {
+        action: action.as_str(),
+        confirmed: report.is_some(),
+        datasets_scanned: plan.datasets_scanned,
+        matched: plan.datasets.len(),
+        feature_count: plan.feature_count(),
+        source_bytes: plan.source_bytes(),
+        succeeded: report.as_ref().map(BulkReport::succeeded),
+        failed: report.as_ref().map(BulkReport::failed),
+        datasets,
+    }))
+}
+
+/// The service filter for a bulk deletion request
+fn dataset_filter(request: &BulkDeleteFilter) -> Result<DatasetFilter, ApiError> {
+    let mut filter = DatasetFilter::default();
+    match (&request.name_prefix, &request.name_regex) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::bad_request("Invalid dataset filter")
+                .with_details("Set name_prefix or name_regex, not both"))
+        }
+        (Some(prefix), None) => filter = filter.with_name(NamePattern::prefix(prefix)),
+        (None, Some(regex)) => filter = filter.with_name(NamePattern::regex(regex)?),
+        (None, None) => {}
+    }
+    if let Some(before) = request.added_before {
+        filter = filter.with_added_before(before);
+    }
+    if let Some(tag) = &request.tag {
+        filter = filter.with_tag(tag);
+    }
+    Ok(filter)
+}
+
 fn dataset_meta_to_info(meta: &DatasetMeta) -> DatasetInfo {
     DatasetInfo {
         id: meta.name.clone(),
         geometry_type: format!("{:?}", meta.geometry_type),
         count: meta.feature_count,
+        preview: meta.preview.clone(),
     }
 }
 
@@ -107,5 +598,9 @@ fn dataset_meta_to_response(meta: DatasetMeta) -> DatasetResponse {
         feature_count: meta.feature_count,
         crs: meta.crs,
         added_at: meta.added_at,
+        tags: meta.tags,
+        license: meta.license,
+        attribution: meta.attribution,
+        preview: meta.preview,
     }
 }
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+
+use crate::auth::Caller;
+use crate::dto::{
+    EventCursorPath, EventCursorRequest, EventCursorResponse, EventParams, EventsResponse,
+};
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Change events after a sequence number, across all workspaces
+///
+/// Waits up to `wait` seconds for an event when there are none yet. Without
+/// `after_seq`, reads after the stored cursor of `consumer`. Readers whose
+/// next events were pruned get 410 Gone and must start over from a fresh
+/// copy. Requires an unrestricted API key bound to no workspaces, since the
+/// feed carries the data of every workspace. Payloads are redacted with the
+/// rules of every workspace, see [`crate::redaction`].
+pub async fn list_events(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<EventParams>,
+) -> Result<Json<EventsResponse>, ApiError> {
+    require_feed_access(&caller)?;
+
+    let service = state.event_log_service();
+    let after_seq = match (params.after_seq, &params.consumer) {
+        (Some(after_seq), _) => after_seq,
+        (None, Some(consumer)) => service.cursor(consumer).await?,
+        (None, None) => 0,
+    };
+
+    let page = service.read(after_seq, params.limit, Duration::from_secs(params.wait)).await?;
+    Ok(Json(EventsResponse {
+        next_after_seq: page.next_after_seq(),
+        latest_seq: page.bounds.last,
+        earliest_seq: page.bounds.first,
+        events: page.events,
+    }))
+}
+
+/// Stored cursor of a change event consumer
+pub async fn get_event_cursor(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path(EventCursorPath { consumer }): Path<EventCursorPath>,
+) -> Result<Json<EventCursorResponse>, ApiError> {
+    require_feed_access(&caller)?;
+
+    let seq = state.event_log_service().cursor(&consumer).await?;
+    Ok(Json(EventCursorResponse { consumer, seq }))
+}
+
+/// Store the last event a consumer applied, so it can resume after it
+pub async fn put_event_cursor(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path(EventCursorPath { consumer }): Path<EventCursorPath>,
+    Json(request): Json<EventCursorRequest>,
+) -> Result<Json<EventCursorResponse>, ApiError> {
+    require_feed_access(&caller)?;
+
+    state.event_log_service().acknowledge(&consumer, request.seq).await?;
+    Ok(Json(EventCursorResponse { consumer, seq: request.seq }))
+}
+
+fn require_feed_access(caller: &Caller) -> Result<(), ApiError> {
+    if caller.visibility.is_restricted() {
+        return Err(ApiError::forbidden("The change event feed requires an unrestricted API key"));
+    }
+    caller.require_all_workspaces()
+}
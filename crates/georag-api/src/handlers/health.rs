@@ -1,7 +1,90 @@
-use axum::{response::IntoResponse, Json};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse, Json};
 
 use crate::dto::HealthResponse;
+use crate::governor::ResourceStats;
+use crate::health::CircuitState;
+use crate::state::AppState;
+
+/// Server health, with the circuit state of a supervised storage backend
+pub async fn health_check(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let response = HealthResponse::default();
+    Json(match &state.store_supervisor {
+        Some(supervisor) => response.with_store(supervisor.health()),
+        None => response,
+    })
+}
+
+/// Resource slots, feature cache lookups and storage backend health in the
+/// Prometheus text format
+///
+/// Storage health is left out unless the storage backend is supervised.
+pub async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut body = String::new();
+
+    // Writing to a String cannot fail
+    let stats = state.governor.stats();
+    let gauges: [(&str, fn(&ResourceStats) -> u64); 3] = [
+        ("georag_resource_limit", |s| s.limit as u64),
+        ("georag_resource_holders", |s| s.holders as u64),
+        ("georag_resource_queued", |s| s.queued as u64),
+    ];
+    for (name, value) in gauges {
+        let _ = writeln!(body, "# TYPE {} gauge", name);
+        for resource in &stats {
+            let _ =
+                writeln!(body, "{}{{resource=\"{}\"}} {}", name, resource.kind, value(resource));
+        }
+    }
+    let _ = writeln!(body, "# TYPE georag_resource_rejected_total counter");
+    for resource in &stats {
+        let _ = writeln!(
+            body,
+            "georag_resource_rejected_total{{resource=\"{}\"}} {}",
+            resource.kind, resource.rejected
+        );
+    }
+
+    let cache = state.feature_cache.stats();
+    for (name, value) in [
+        ("georag_feature_cache_hits_total", cache.hits),
+        ("georag_feature_cache_misses_total", cache.misses),
+    ] {
+        let _ = writeln!(body, "# TYPE {} counter", name);
+        let _ = writeln!(body, "{} {}", name, value);
+    }
+    let _ = writeln!(body, "# TYPE georag_feature_cache_hit_rate gauge");
+    let _ = writeln!(body, "georag_feature_cache_hit_rate {}", cache.hit_rate());
+    let _ = writeln!(body, "# TYPE georag_feature_cache_entries gauge");
+    let _ = writeln!(body, "georag_feature_cache_entries {}", state.feature_cache.len());
+
+    if let Some(supervisor) = &state.store_supervisor {
+        let health = supervisor.health();
+        let backend = &health.backend;
+        let counters = health.counters;
 
-pub async fn health_check() -> impl IntoResponse {
-    Json(HealthResponse::default())
+        let _ = writeln!(body, "# TYPE georag_store_circuit_state gauge");
+        for circuit in [CircuitState::Healthy, CircuitState::Degraded, CircuitState::Down] {
+            let _ = writeln!(
+                body,
+                "georag_store_circuit_state{{backend=\"{}\",state=\"{}\"}} {}",
+                backend,
+                circuit.as_str(),
+                u8::from(health.state == circuit)
+            );
+        }
+        for (name, value) in [
+            ("georag_store_health_checks_total", counters.checks),
+            ("georag_store_health_check_failures_total", counters.failed_checks),
+            ("georag_store_outages_total", counters.outages),
+            ("georag_store_rejected_writes_total", counters.rejected_writes),
+            ("georag_store_fallback_queries_total", counters.fallback_queries),
+        ] {
+            let _ = writeln!(body, "# TYPE {} counter", name);
+            let _ = writeln!(body, "{}{{backend=\"{}\"}} {}", name, backend, value);
+        }
+    }
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
 }
@@ -1,20 +1,17 @@
-use std::sync::Arc;
-
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
-};
+use axum::{http::StatusCode, Extension, Json};
+use georag_core::models::AuditEventKind;
+use georag_core::resources::ResourceKind;
 
 use crate::dto::{IndexIntegrityResponse, IndexStatusResponse, RebuildResponse, VerifyResponse};
 use crate::error::ApiError;
-use crate::state::AppState;
+use crate::health::CircuitState;
+use crate::workspace::Workspace;
 
-/// Get index integrity (legacy endpoint)
+/// Get index integrity
 pub async fn get_index_integrity(
-    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
 ) -> Result<Json<IndexIntegrityResponse>, ApiError> {
-    let index_state = state.get_index_state().await?;
+    let index_state = workspace.state.get_index_state().await?;
 
     Ok(Json(IndexIntegrityResponse {
         hash: index_state.hash,
@@ -22,13 +19,15 @@ pub async fn get_index_integrity(
         embedder: index_state.embedder,
         chunk_count: index_state.chunk_count,
         embedding_dim: index_state.embedding_dim,
+        last_build_diff: index_state.last_build_diff,
     }))
 }
 
-/// Verify index integrity (legacy endpoint)
+/// Verify index integrity
 pub async fn verify_index(
-    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
 ) -> Result<Json<VerifyResponse>, ApiError> {
+    let state = &workspace.state;
     let stored_state = state.get_index_state().await?;
     let computed_hash = state.compute_index_hash().await?;
 
@@ -41,23 +40,11 @@ pub async fn verify_index(
 
 /// Trigger index rebuild for a workspace (returns 202 Accepted)
 pub async fn rebuild_index(
-    State(state): State<Arc<AppState>>,
-    Path(workspace_id): Path<String>,
+    Extension(workspace): Extension<Workspace>,
 ) -> Result<(StatusCode, Json<RebuildResponse>), ApiError> {
-    tracing::info!(workspace_id = %workspace_id, "Triggering index rebuild");
-
-    let ws_id = workspace_id
-        .parse()
-        .map_err(|_| ApiError::bad_request("Invalid workspace ID format"))?;
+    tracing::info!(workspace_id = %workspace.id, "Triggering index rebuild");
 
-    let workspace = state.workspace_store.get_workspace(ws_id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to check workspace existence");
-        ApiError::internal("Failed to verify workspace").with_details(e.to_string())
-    })?;
-
-    if workspace.is_none() {
-        return Err(ApiError::not_found("Workspace not found"));
-    }
+    let (state, ws_id) = (workspace.state, workspace.id);
 
     // Check if a rebuild is already in progress
     if state.is_rebuilding(ws_id).await {
@@ -65,23 +52,47 @@ pub async fn rebuild_index(
             .with_details("Wait for the current rebuild to complete or check /index/status"));
     }
 
+    // Accepted only while there is room to wait for a build slot
+    let ticket = state.governor.enqueue(ResourceKind::Build)?;
+
     // Start background rebuild task
     state.start_rebuild(ws_id).await;
 
     let state_clone = state.clone();
     let ws_id_clone = ws_id;
     tokio::spawn(async move {
+        let _slot = ticket.admit().await;
         tracing::info!(workspace_id = %ws_id_clone, "Starting background index rebuild");
 
-        // Perform the rebuild
-        match state_clone.rebuild_index_for_workspace(ws_id_clone).await {
-            Ok(_) => {
-                tracing::info!(workspace_id = %ws_id_clone, "Index rebuild completed successfully");
-            }
-            Err(e) => {
-                tracing::error!(workspace_id = %ws_id_clone, error = %e, "Index rebuild failed");
-                state_clone.set_rebuild_error(ws_id_clone, e.to_string()).await;
+        // Perform the rebuild, pausing it while the storage backend is unhealthy
+        loop {
+            match state_clone.rebuild_index_for_workspace(ws_id_clone).await {
+                Ok(_) => {
+                    tracing::info!(workspace_id = %ws_id_clone, "Index rebuild completed successfully");
+                }
+                Err(e) => {
+                    if let Some(supervisor) = &state_clone.store_supervisor {
+                        if supervisor.check().await != CircuitState::Healthy {
+                            tracing::warn!(
+                                workspace_id = %ws_id_clone,
+                                error = %e,
+                                "Index rebuild paused until the storage backend recovers"
+                            );
+                            state_clone.pause_rebuild(ws_id_clone).await;
+                            supervisor.wait_until_healthy().await;
+                            state_clone.start_rebuild(ws_id_clone).await;
+                            continue;
+                        }
+                    }
+                    tracing::error!(workspace_id = %ws_id_clone, error = %e, "Index rebuild failed");
+                    state_clone
+                        .audit_service()
+                        .record_failure(ws_id_clone, Some(AuditEventKind::BuildFailed), &e)
+                        .await;
+                    state_clone.set_rebuild_error(ws_id_clone, e.to_string()).await;
+                }
             }
+            break;
         }
 
         state_clone.finish_rebuild(ws_id_clone).await;
@@ -92,31 +103,21 @@ pub async fn rebuild_index(
 
 /// Get index status for a workspace
 pub async fn get_workspace_index_status(
-    State(state): State<Arc<AppState>>,
-    Path(workspace_id): Path<String>,
+    Extension(workspace): Extension<Workspace>,
 ) -> Result<Json<IndexStatusResponse>, ApiError> {
-    tracing::info!(workspace_id = %workspace_id, "Getting index status");
-
-    let ws_id = workspace_id
-        .parse()
-        .map_err(|_| ApiError::bad_request("Invalid workspace ID format"))?;
+    tracing::info!(workspace_id = %workspace.id, "Getting index status");
 
-    let workspace = state.workspace_store.get_workspace(ws_id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to check workspace existence");
-        ApiError::internal("Failed to verify workspace").with_details(e.to_string())
-    })?;
-
-    if workspace.is_none() {
-        return Err(ApiError::not_found("Workspace not found"));
-    }
+    let (state, ws_id) = (&workspace.state, workspace.id);
 
     let rebuilding = state.is_rebuilding(ws_id).await;
+    let paused = state.is_rebuild_paused(ws_id).await;
     let index_state = state.get_workspace_index_state(ws_id).await;
 
     match index_state {
         Some(idx_state) => Ok(Json(IndexStatusResponse {
             built: true,
             rebuilding,
+            paused,
             hash: Some(idx_state.hash),
             built_at: Some(idx_state.built_at),
             chunk_count: Some(idx_state.chunk_count),
@@ -125,6 +126,7 @@ pub async fn get_workspace_index_status(
         None => Ok(Json(IndexStatusResponse {
             built: false,
             rebuilding,
+            paused,
             hash: None,
             built_at: None,
             chunk_count: None,
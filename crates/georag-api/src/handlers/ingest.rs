@@ -1,32 +1,200 @@
 use std::sync::Arc;
 
-use axum::{extract::Multipart, extract::State, Json};
+use axum::{extract::Multipart, extract::State, Extension, Json};
+use georag_core::config::parse_axis_order;
+use georag_core::models::{normalize_tags, AuditEvent, AuditEventKind, AxisOrder};
+use georag_core::resources::ResourceKind;
+use georag_service::remote::{self, Download};
+use georag_service::{IngestRequest, ServiceError};
+use std::collections::BTreeMap;
+use tempfile::TempDir;
 
-use crate::dto::IngestResponse;
+use crate::dto::{FormatsResponse, IngestResponse};
 use crate::error::ApiError;
-use crate::services::IngestService;
 use crate::state::AppState;
+use crate::workspace::Workspace;
+
+/// Supported formats with the options each accepts in the ingest `options` field
+pub async fn list_formats(State(state): State<Arc<AppState>>) -> Json<FormatsResponse> {
+    Json(FormatsResponse {
+        formats: state.format_registry.describe(),
+    })
+}
 
 pub async fn handle_ingest(
-    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
     mut multipart: Multipart,
 ) -> Result<Json<IngestResponse>, ApiError> {
-    tracing::info!("Processing ingest request");
+    tracing::info!(workspace = %workspace.name, "Processing ingest request");
+
+    let state = &workspace.state;
+    // Taken before the upload is read, so waiting uploads are not staged on disk
+    let _slot = state.governor.acquire(ResourceKind::Ingest).await?;
+    let upload = extract_upload(&mut multipart).await?;
+    // The temporary copy in `staged` lives until the handler returns
+    let staged = stage(upload.source, state).await?;
+    let filename = staged.filename.clone();
 
-    let (filename, data) = extract_file(&mut multipart).await?;
+    // Stored workspace settings fill in what the server's environment leaves unset
+    let settings = state.workspace_settings(workspace.id).await?;
+    let mut request = staged
+        .request
+        .with_tags(upload.tags)
+        .with_read_policy(state.ingest_read_policy(settings.as_ref()))
+        .with_axis_order(
+            upload.axis_order.unwrap_or_else(|| state.ingest_axis_order(settings.as_ref())),
+        )
+        .with_feature_limits(state.ingest_feature_limits(settings.as_ref()))
+        .with_z_coordinates(state.ingest_z_coordinates(settings.as_ref()))
+        .with_transforms(state.ingest_transforms(settings.as_ref()))
+        .with_buffers(state.ingest_buffers(settings.as_ref()));
+    if let Some(license) = &upload.license {
+        request = request.with_license(license);
+    }
+    if let Some(attribution) = &upload.attribution {
+        request = request.with_attribution(attribution);
+    }
+    for (key, value) in upload.options {
+        request = request.with_option(key, value);
+    }
+    let quota = state.workspace_quota(workspace.id).await?;
+    let service = state
+        .ingest_service(state.ingest_source_policy(settings.as_ref()))
+        .with_quota(quota);
+    let report = match service.ingest(&request).await {
+        Ok(report) => report,
+        Err(e) => {
+            tracing::warn!(error = %e, filename = %filename, "Ingest failed");
+            if let ServiceError::Core(error) = &e {
+                state.audit_service().record_failure(workspace.id, None, error).await;
+            }
+            return Err(e.into());
+        }
+    };
+    let event = AuditEvent::new(AuditEventKind::DatasetAdded).with_details(&report.dataset.name);
+    state.audit_service().record(workspace.id, event).await;
+    state
+        .event_log_service()
+        .record_dataset_added(workspace.id, state.spatial_store.as_ref(), report.dataset_id)
+        .await;
 
-    tracing::info!(filename = %filename, size = data.len(), "Received file for ingestion");
+    for warning in &report.warnings {
+        tracing::warn!(filename = %filename, "{}", warning);
+    }
 
-    let result = IngestService::ingest_file(&state, &filename, &data).await?;
+    if report.features_skipped() > 0 {
+        tracing::warn!(
+            filename = %filename,
+            skipped = report.features_skipped(),
+            "Skipped features that could not be read"
+        );
+    }
 
-    Ok(Json(IngestResponse::success(
-        result.dataset_id.0,
-        &filename,
-        result.feature_count,
-    )))
+    Ok(Json(
+        IngestResponse::success(report.dataset_id.0, &filename, report.features_stored)
+            .with_pipeline(report.pipeline.as_ref())
+            .with_feature_errors(report.feature_errors)
+            .with_oversized_features(report.oversized_features)
+            .with_transform_errors(report.transform_errors)
+            .with_axis_order(report.dataset.format.axis_order)
+            .with_source(report.dataset.format.source)
+            .with_remote(report.dataset.format.remote),
+    ))
 }
 
-async fn extract_file(multipart: &mut Multipart) -> Result<(String, Vec<u8>), ApiError> {
+/// What an ingest reads: an uploaded file or a URL to download
+enum UploadSource {
+    File { filename: String, data: Vec<u8> },
+    Url(String),
+}
+
+/// File being ingested, whose temporary copy is removed when dropped
+struct Staged {
+    request: IngestRequest,
+    filename: String,
+    _upload: Option<TempDir>,
+    _download: Option<Download>,
+}
+
+/// Write an upload to a temporary file or download a URL into one
+async fn stage(source: UploadSource, state: &AppState) -> Result<Staged, ApiError> {
+    match source {
+        UploadSource::File { filename, data } => {
+            tracing::info!(filename = %filename, size = data.len(), "Received file for ingestion");
+
+            let temp_dir = tempfile::tempdir().map_err(|e| {
+                ApiError::internal("Failed to create temp directory").with_details(e.to_string())
+            })?;
+            let temp_path = temp_dir.path().join(&filename);
+            std::fs::write(&temp_path, &data).map_err(|e| {
+                ApiError::internal("Failed to write temp file").with_details(e.to_string())
+            })?;
+
+            Ok(Staged {
+                // Uploads are read without validating them first, so an
+                // unreadable one answers 400 "Failed to parse file"
+                request: IngestRequest::new(&temp_path)
+                    .with_name(&filename)
+                    .with_source_name(&filename)
+                    .with_validation(false),
+                filename,
+                _upload: Some(temp_dir),
+                _download: None,
+            })
+        }
+        UploadSource::Url(url) => {
+            if !state.download_policy.allows_downloads() {
+                return Err(ApiError::forbidden("Ingest from URLs is disabled").with_details(
+                    "Set GEORAG_DOWNLOAD_HOSTS to the hosts to download from, or upload the \
+                     file as the 'file' field",
+                ));
+            }
+
+            let download = remote::fetch(&url, &state.format_registry, &state.download_policy)
+                .await
+                .map_err(|e| {
+                    tracing::warn!(error = %e, url = %url, "Download failed");
+                    ApiError::from(e)
+                })?;
+            tracing::info!(
+                url = %url,
+                size = download.source.size,
+                filename = download.file_name(),
+                "Downloaded file for ingestion"
+            );
+
+            Ok(Staged {
+                request: download.request(),
+                filename: download.file_name().to_string(),
+                _upload: None,
+                _download: Some(download),
+            })
+        }
+    }
+}
+
+/// Fields of an ingest upload
+struct Upload {
+    source: UploadSource,
+    tags: Vec<String>,
+    license: Option<String>,
+    attribution: Option<String>,
+    axis_order: Option<AxisOrder>,
+    options: BTreeMap<String, String>,
+}
+
+/// Read the `file` or `url` field and the optional `tags` (comma-separated),
+/// `license`, `attribution`, `axis_order` and `options` (a JSON object of
+/// reader options, see `GET /api/v1/formats`) fields
+async fn extract_upload(multipart: &mut Multipart) -> Result<Upload, ApiError> {
+    let mut file = None;
+    let mut url = None;
+    let mut tags = Vec::new();
+    let mut license = None;
+    let mut attribution = None;
+    let mut axis_order = None;
+    let mut options = BTreeMap::new();
+
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         ApiError::bad_request("Failed to parse multipart form").with_details(e.to_string())
     })? {
@@ -37,10 +205,64 @@ async fn extract_file(multipart: &mut Multipart) -> Result<(String, Vec<u8>), Ap
             let data = field.bytes().await.map_err(|e| {
                 ApiError::bad_request("Failed to read file data").with_details(e.to_string())
             })?;
-            return Ok((filename, data.to_vec()));
+            file = Some(UploadSource::File { filename, data: data.to_vec() });
+        } else if name == "url" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read url").with_details(e.to_string())
+            })?;
+            url = Some(UploadSource::Url(value.trim().to_string()));
+        } else if name == "tags" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read tags").with_details(e.to_string())
+            })?;
+            tags = normalize_tags(value.split(','));
+        } else if name == "license" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read license").with_details(e.to_string())
+            })?;
+            license = Some(value);
+        } else if name == "attribution" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read attribution").with_details(e.to_string())
+            })?;
+            attribution = Some(value);
+        } else if name == "axis_order" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read axis_order").with_details(e.to_string())
+            })?;
+            axis_order = Some(parse_axis_order(&value).map_err(|e| {
+                ApiError::bad_request("Invalid axis_order").with_details(e.to_string())
+            })?);
+        } else if name == "options" {
+            let value = field.text().await.map_err(|e| {
+                ApiError::bad_request("Failed to read options").with_details(e.to_string())
+            })?;
+            options = serde_json::from_str(&value).map_err(|e| {
+                ApiError::bad_request("Invalid options")
+                    .with_details(format!("Expected a JSON object of string values: {}", e))
+            })?;
         }
     }
 
-    Err(ApiError::bad_request("No file provided")
-        .with_details("Expected a 'file' field in the multipart form"))
+    let source = match (file, url) {
+        (Some(file), None) => file,
+        (None, Some(url)) => url,
+        (Some(_), Some(_)) => {
+            return Err(ApiError::bad_request("Both file and url provided")
+                .with_details("Send either a 'file' or a 'url' field, not both"))
+        }
+        (None, None) => {
+            return Err(ApiError::bad_request("No file provided")
+                .with_details("Expected a 'file' or 'url' field in the multipart form"))
+        }
+    };
+
+    Ok(Upload {
+        source,
+        tags,
+        license,
+        attribution,
+        axis_order,
+        options,
+    })
 }
@@ -1,13 +1,31 @@
+mod admin;
+mod areas;
 mod datasets;
+mod events;
 mod health;
 mod index;
 mod ingest;
+mod presets;
 mod query;
 mod workspaces;
 
-pub use datasets::{delete_dataset, list_datasets, list_datasets_for_workspace};
-pub use health::health_check;
+pub use admin::{compact, get_config, reload_config};
+pub use areas::{create_area, delete_area, get_area, list_areas};
+pub use datasets::{
+    bulk_delete_datasets, delete_dataset, download_dataset_source, get_dataset_feature,
+    get_dataset_schema, list_datasets, list_datasets_for_workspace, sample_dataset,
+    update_dataset_tags,
+};
+pub use events::{get_event_cursor, list_events, put_event_cursor};
+pub use health::{health_check, metrics};
 pub use index::{get_index_integrity, get_workspace_index_status, rebuild_index, verify_index};
-pub use ingest::handle_ingest;
-pub use query::handle_query;
-pub use workspaces::{create_workspace, delete_workspace, list_workspaces};
+pub use ingest::{handle_ingest, list_formats};
+pub use presets::{
+    clear_default_preset, delete_preset, get_preset, list_presets, put_preset, set_default_preset,
+};
+pub use query::{handle_query, handle_query_by_feature, handle_query_next, NEXT_CURSOR_HEADER};
+pub use workspaces::{
+    clone_workspace, create_workspace, delete_workspace, get_workspace_settings,
+    get_workspace_timeline, get_workspace_usage, list_workspaces, put_workspace_settings,
+    update_workspace,
+};
@@ -0,0 +1,115 @@
+use axum::{extract::Path, Extension, Json};
+use georag_core::config::WorkspaceSettings;
+use georag_core::models::{normalize_preset_name, QueryPreset};
+use georag_service::ServiceError;
+
+use crate::dto::{DeleteResponse, PresetPath, PresetResponse};
+use crate::error::ApiError;
+use crate::workspace::Workspace;
+
+/// List the query presets of the workspace, sorted by name
+pub async fn list_presets(
+    Extension(workspace): Extension<Workspace>,
+) -> Result<Json<Vec<PresetResponse>>, ApiError> {
+    tracing::info!("Listing query presets");
+
+    let settings = workspace.state.workspace_service().get(workspace.id).await?.settings;
+    let presets = settings
+        .query_presets
+        .iter()
+        .flatten()
+        .filter_map(|(name, _)| PresetResponse::from_settings(&settings, name))
+        .collect();
+
+    Ok(Json(presets))
+}
+
+pub async fn get_preset(
+    Extension(workspace): Extension<Workspace>,
+    Path(PresetPath { name }): Path<PresetPath>,
+) -> Result<Json<PresetResponse>, ApiError> {
+    let settings = workspace.state.workspace_service().get(workspace.id).await?.settings;
+
+    Ok(Json(preset_response(&settings, &name)?))
+}
+
+/// Save a query preset, replacing any preset of the same name
+///
+/// The options are checked as in a query request.
+pub async fn put_preset(
+    Extension(workspace): Extension<Workspace>,
+    Path(PresetPath { name }): Path<PresetPath>,
+    Json(preset): Json<QueryPreset>,
+) -> Result<Json<PresetResponse>, ApiError> {
+    tracing::info!(name = %name, "Saving query preset");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let index = state.get_index_state().await.ok();
+    let (name, view) = state
+        .workspace_service()
+        .save_preset(id, &name, &preset, index.as_ref())
+        .await?;
+    state.forget_workspace_settings(id).await;
+
+    Ok(Json(preset_response(&view.settings, &name)?))
+}
+
+/// Delete a query preset; deleting the default preset leaves no default
+pub async fn delete_preset(
+    Extension(workspace): Extension<Workspace>,
+    Path(PresetPath { name }): Path<PresetPath>,
+) -> Result<Json<DeleteResponse>, ApiError> {
+    tracing::info!(name = %name, "Deleting query preset");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let index = state.get_index_state().await.ok();
+    state.workspace_service().delete_preset(id, &name, index.as_ref()).await?;
+    state.forget_workspace_settings(id).await;
+
+    Ok(Json(DeleteResponse::success("preset", &name)))
+}
+
+/// Make a query preset the workspace default
+pub async fn set_default_preset(
+    Extension(workspace): Extension<Workspace>,
+    Path(PresetPath { name }): Path<PresetPath>,
+) -> Result<Json<PresetResponse>, ApiError> {
+    tracing::info!(name = %name, "Setting default query preset");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let index = state.get_index_state().await.ok();
+    let view = state
+        .workspace_service()
+        .set_default_preset(id, Some(&name), index.as_ref())
+        .await?;
+    state.forget_workspace_settings(id).await;
+
+    Ok(Json(preset_response(&view.settings, &name)?))
+}
+
+/// Stop a query preset being the workspace default; other defaults are kept
+pub async fn clear_default_preset(
+    Extension(workspace): Extension<Workspace>,
+    Path(PresetPath { name }): Path<PresetPath>,
+) -> Result<Json<PresetResponse>, ApiError> {
+    tracing::info!(name = %name, "Clearing default query preset");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let service = state.workspace_service();
+    let mut settings = service.get(id).await?.settings;
+    let response = preset_response(&settings, &name)?;
+    if response.default {
+        let index = state.get_index_state().await.ok();
+        settings = service.set_default_preset(id, None, index.as_ref()).await?.settings;
+        state.forget_workspace_settings(id).await;
+    }
+
+    Ok(Json(preset_response(&settings, &response.name)?))
+}
+
+/// Preset `name` of the settings, its name normalized
+fn preset_response(settings: &WorkspaceSettings, name: &str) -> Result<PresetResponse, ApiError> {
+    let name = normalize_preset_name(name).unwrap_or_else(|_| name.to_string());
+    PresetResponse::from_settings(settings, &name)
+        .ok_or_else(|| ServiceError::PresetNotFound { name }.into())
+}
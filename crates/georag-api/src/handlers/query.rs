@@ -1,25 +1,874 @@
-use std::sync::Arc;
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use georag_core::config::{parse_distance_unit, WorkspaceSettings};
+use georag_core::error::GeoragError;
+use georag_core::models::{
+    AuditEvent, AuditEventKind, ChunkId, Crs, DatasetId, Distance, DistanceUnit, FeatureId,
+    Geometry as CoreGeometry, SavedArea, SpatialFilter, SpatialPredicate, TagVisibility,
+};
+use georag_core::processing::chunk::property_text;
+use georag_retrieval::{
+    AppliedPreset, AttributeFilter, GeometryDetail, GeometryOutput, MapOptions, NamedAreaExpansion,
+    QueryPlan, QueryResult, ResultFormat, SeedMode,
+};
+use georag_service::{
+    apply_operators, parse_operators, parse_rerank, plan_hash, select_preset, ExampleSeed,
+    PagedQuery, QueryCursor, QueryService, ResultPage, ServiceError,
+};
+use serde_json::{Map, Value as JsonValue};
 
-use axum::{extract::State, Json};
-use geojson::FeatureCollection;
-
-use crate::dto::QueryRequest;
+use crate::auth::Caller;
+use crate::config::AllowedEmbedder;
+use crate::dto::{
+    BufferRequest, QueryByFeatureRequest, QueryFormatParams, QueryNextRequest, QueryRequest,
+};
 use crate::error::ApiError;
-use crate::services::QueryService;
 use crate::state::AppState;
+use crate::workspace::Workspace;
+
+/// Response header carrying the cursor of the next page of a paged query
+pub const NEXT_CURSOR_HEADER: HeaderName = HeaderName::from_static("x-georag-next-cursor");
 
 pub async fn handle_query(
-    State(state): State<Arc<AppState>>,
-    Json(request): Json<QueryRequest>,
-) -> Result<Json<FeatureCollection>, ApiError> {
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<QueryFormatParams>,
+    headers: HeaderMap,
+    Json(mut request): Json<QueryRequest>,
+) -> Result<Response, ApiError> {
+    let state = &workspace.state;
+    let format = negotiate_format(params.format.as_deref(), &headers)?;
+    let map_options = map_options(&params)?;
+    if request.text.trim().is_empty() {
+        return Err(ApiError::bad_request("text is required"));
+    }
+
+    let embedder_model = select_embedder(state, request.embedder_model.as_deref()).await?;
+    let settings = state.workspace_settings(workspace.id).await?;
+    let preset = apply_preset(&mut request, settings.as_ref())?;
+
     tracing::info!(
+        workspace = %workspace.name,
         query = %request.text,
-        top_k = request.top_k,
+        embedder_model = %embedder_model.model,
+        preset = preset.as_ref().map(|p| p.name.as_str()).unwrap_or("-"),
+        top_k = ?request.top_k,
         has_bbox = request.bbox.is_some(),
+        has_geometry = request.geometry.is_some(),
+        has_point = request.point.is_some(),
+        area = request.area.as_deref().unwrap_or("-"),
+        exclusions = request.exclude_geometries.len() + request.exclude_areas.len(),
+        parse_operators = ?request.parse_operators,
+        format = %format,
+        api_key = caller.key_name.as_deref().unwrap_or("-"),
         "Processing query request"
     );
 
-    let result = QueryService::execute(&state, &request, &state.embedder_config).await?;
+    let area = match &request.area {
+        Some(name) => Some(state.area_service().resolve(workspace.id, name).await?),
+        None => None,
+    };
+    let mut excluded_areas = Vec::with_capacity(request.exclude_areas.len());
+    for name in &request.exclude_areas {
+        excluded_areas.push(state.area_service().resolve(workspace.id, name).await?);
+    }
+    let plan = query_plan(
+        state,
+        &request,
+        area.as_ref(),
+        &excluded_areas,
+        caller.visibility,
+        settings.as_ref(),
+        preset,
+    )?;
+
+    // Operators in the text never fail the query; an unknown near: area is reported as ignored
+    let plan = if request.parse_operators.unwrap_or(false) {
+        let parsed = parse_operators(&request.text);
+        let place = match parsed.near().filter(|_| area.is_none()) {
+            Some(name) => match state.area_service().resolve(workspace.id, name).await {
+                Ok(place) => Some(place),
+                Err(ServiceError::AreaNotFound { .. } | ServiceError::InvalidArea(_)) => None,
+                Err(e) => return Err(e.into()),
+            },
+            None => None,
+        };
+        apply_operators(plan, parsed, place.as_ref(), request.time_property.as_deref())
+    } else {
+        plan
+    };
+    run_plan(
+        &workspace,
+        request,
+        plan,
+        embedder_model,
+        settings.as_ref(),
+        format,
+        map_options,
+    )
+    .await
+}
+
+/// Query by example: rank chunks by their similarity to a stored feature or chunk
+///
+/// The stored embeddings of the feature's chunks are searched with, so the
+/// query text is never embedded and the example's own chunks are never
+/// returned. Filters, paging and formats work as for `handle_query`.
+pub async fn handle_query_by_feature(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<QueryFormatParams>,
+    headers: HeaderMap,
+    Json(request): Json<QueryByFeatureRequest>,
+) -> Result<Response, ApiError> {
+    let state = &workspace.state;
+    let format = negotiate_format(params.format.as_deref(), &headers)?;
+    let map_options = map_options(&params)?;
+
+    let seed = match (&request.feature, request.chunk_id) {
+        (Some(feature), None) => {
+            let dataset_id: u64 = feature
+                .dataset
+                .parse()
+                .map_err(|_| ApiError::bad_request("Invalid dataset ID format"))?;
+            let feature_id: u64 = feature
+                .id
+                .parse()
+                .map_err(|_| ApiError::bad_request("Invalid feature ID format"))?;
+            ExampleSeed::Feature {
+                dataset_id: DatasetId(dataset_id),
+                feature_id: FeatureId(feature_id),
+            }
+        }
+        (None, Some(chunk_id)) => ExampleSeed::Chunk(ChunkId(chunk_id)),
+        _ => return Err(ApiError::bad_request("Specify either feature or chunk_id")),
+    };
+    let mode = match &request.seed_mode {
+        Some(mode) => mode
+            .parse::<SeedMode>()
+            .map_err(|e| ApiError::bad_request("Invalid seed_mode").with_details(e))?,
+        None => SeedMode::default(),
+    };
+
+    // Features and chunks of datasets hidden from the caller cannot be examples
+    let dataset_id = match seed {
+        ExampleSeed::Feature { dataset_id, .. } => Some(dataset_id),
+        ExampleSeed::Chunk(chunk_id) => {
+            state
+                .document_store
+                .get_chunk(chunk_id)
+                .await
+                .map_err(|e| {
+                    ApiError::internal("Failed to load chunk").with_details(e.to_string())
+                })?
+                .ok_or(ServiceError::ChunkNotFound { id: chunk_id })?
+                .metadata
+                .dataset_id
+        }
+    };
+    if let Some(dataset_id) = dataset_id {
+        state
+            .spatial_store
+            .get_dataset(dataset_id)
+            .await
+            .map_err(|e| ApiError::internal("Failed to load dataset").with_details(e.to_string()))?
+            .filter(|dataset| caller.visibility.allows(&dataset.tags))
+            .ok_or_else(|| ApiError::not_found("Dataset not found"))?;
+    }
+
+    let example = state.query_service().example(seed, mode).await?;
+    let embedder_model = select_embedder(state, request.query.embedder_model.as_deref()).await?;
+
+    tracing::info!(
+        workspace = %workspace.name,
+        example = %example.source,
+        seed_chunks = example.seed_chunks.len(),
+        seed_mode = %mode,
+        top_k = ?request.query.top_k,
+        format = %format,
+        api_key = caller.key_name.as_deref().unwrap_or("-"),
+        "Processing query by example request"
+    );
+
+    let mut query = request.query;
+    if query.text.trim().is_empty() {
+        query.text = format!("like {}", example.source);
+    }
+    let settings = state.workspace_settings(workspace.id).await?;
+    let preset = apply_preset(&mut query, settings.as_ref())?;
+    let area = match &query.area {
+        Some(name) => Some(state.area_service().resolve(workspace.id, name).await?),
+        None => None,
+    };
+    let mut excluded_areas = Vec::with_capacity(query.exclude_areas.len());
+    for name in &query.exclude_areas {
+        excluded_areas.push(state.area_service().resolve(workspace.id, name).await?);
+    }
+    let plan = query_plan(
+        state,
+        &query,
+        area.as_ref(),
+        &excluded_areas,
+        caller.visibility,
+        settings.as_ref(),
+        preset,
+    )?
+    .with_example(example);
+
+    run_plan(&workspace, query, plan, embedder_model, settings.as_ref(), format, map_options).await
+}
+
+/// Execute a plan built from `request` and render the results, a page at a
+/// time when the request asks for paging
+async fn run_plan(
+    workspace: &Workspace,
+    request: QueryRequest,
+    plan: QueryPlan,
+    embedder_model: AllowedEmbedder,
+    settings: Option<&WorkspaceSettings>,
+    format: ResultFormat,
+    map_options: MapOptions,
+) -> Result<Response, ApiError> {
+    let state = &workspace.state;
+    let geometry_output = geometry_output(&request)?;
+    request
+        .properties
+        .validate()
+        .map_err(|e| ApiError::bad_request("Invalid properties").with_details(e))?;
+    if let Some(page_size) = request.page_size {
+        state.query_pages.check_page_size(page_size)?;
+    }
+
+    let embedder = state.embedder_config.create(&embedder_model)?;
+
+    let mut service = state
+        .query_service()
+        .with_geometry_output(geometry_output)
+        .with_property_selection(request.properties.clone())
+        .with_confidence_cutoffs(state.query_confidence_cutoffs(settings));
+    if let Some(template) = state.query_source_url_template(settings) {
+        service = service.with_source_url_template(template);
+    }
+    if format == ResultFormat::Png {
+        if let Some(basemap) = state.query_basemap(settings) {
+            service = service.with_basemap(basemap);
+        }
+    }
+    // The stored index state describes the configured model's index
+    if embedder_model.model == state.embedder_config.model {
+        if let Ok(index_state) = state.get_index_state().await {
+            service = service.with_index_state(index_state);
+        }
+    }
+    // Read before ranking, so an index changing meanwhile invalidates the cursor
+    let generation = state.index_generation().await;
+    let result = service.execute(&plan, embedder).await.map_err(|e| match e {
+        ServiceError::InvalidQuery(_)
+        | ServiceError::ExampleNotIndexed { .. }
+        | ServiceError::Core(GeoragError::GeometryTooComplex { .. }) => ApiError::from(e),
+        _ => {
+            tracing::error!(error = %e, "Query execution failed");
+            ApiError::internal("Query execution failed").with_details(e.to_string())
+        }
+    })?;
+    state
+        .audit_service()
+        .record(workspace.id, AuditEvent::new(AuditEventKind::Query))
+        .await;
+
+    let scope = format!("{}/{}", workspace.id, embedder_model.model);
+    let hash = plan_hash(&plan, &scope);
+    let query = PagedQuery::new(plan, result, embedder_model.model)
+        .with_workspace(workspace.id)
+        .with_geometry_output(geometry_output)
+        .with_property_selection(request.properties);
+    match request.page_size {
+        Some(page_size) => {
+            let page = state.query_pages.first_page(hash, &generation, query, page_size);
+            render(&service, format, &page.query, &page.result, map_options, Some(&page)).await
+        }
+        None => render(&service, format, &query, &query.result, map_options, None).await,
+    }
+}
+
+/// Next page of a paged query
+///
+/// The page is cut from the results the query ranked, so the pipeline does
+/// not run again. Only the caller whose query it was can read its pages.
+pub async fn handle_query_next(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<QueryFormatParams>,
+    headers: HeaderMap,
+    Json(request): Json<QueryNextRequest>,
+) -> Result<Response, ApiError> {
+    let state = &workspace.state;
+    let format = negotiate_format(params.format.as_deref(), &headers)?;
+    let map_options = map_options(&params)?;
+
+    let cursor = QueryCursor::decode(&request.cursor)?;
+    let generation = state.index_generation().await;
+    let page = state.query_pages.page(&cursor, &generation, Some(workspace.id))?;
+    if page.query.plan.visibility != caller.visibility {
+        return Err(ServiceError::InvalidCursor(
+            "the cursor belongs to a query of another API key".to_string(),
+        )
+        .into());
+    }
+    tracing::info!(
+        workspace = %workspace.name,
+        offset = page.offset,
+        total = page.total,
+        format = %format,
+        api_key = caller.key_name.as_deref().unwrap_or("-"),
+        "Serving query page"
+    );
+
+    let mut service = state
+        .query_service()
+        .with_geometry_output(page.query.geometry_output)
+        .with_property_selection(page.query.properties.clone());
+    if format == ResultFormat::Png {
+        let settings = state.workspace_settings(workspace.id).await?;
+        if let Some(basemap) = state.query_basemap(settings.as_ref()) {
+            service = service.with_basemap(basemap);
+        }
+    }
+    render(&service, format, &page.query, &page.result, map_options, Some(&page)).await
+}
+
+/// Serialize `result`, the results of `query` or one page of them, in `format`
+///
+/// A page carries its position and the cursor of the next page: as foreign
+/// members of GeoJSON, and in the `X-Georag-Next-Cursor` header of every format.
+async fn render(
+    service: &QueryService,
+    format: ResultFormat,
+    query: &PagedQuery,
+    result: &QueryResult,
+    map_options: MapOptions,
+    page: Option<&ResultPage>,
+) -> Result<Response, ApiError> {
+    let content_type = [(header::CONTENT_TYPE, format.content_type())];
+    let mut response = match format {
+        ResultFormat::GeoJson => {
+            let mut collection = service.to_geojson(result).await;
+            collection.extend(summary_members(result));
+            collection.extend(geometry_members(&query.geometry_output));
+            if let Some(operators) = &query.plan.operators {
+                collection.insert(
+                    "operators".to_string(),
+                    serde_json::to_value(operators).unwrap_or(JsonValue::Null),
+                );
+            }
+            // Without an explanation the preset is reported without the effective plan
+            if let Some(preset) = &query.plan.preset {
+                collection
+                    .entry("preset")
+                    .or_insert_with(|| serde_json::to_value(preset).unwrap_or(JsonValue::Null));
+            }
+            collection.insert(
+                "embedder_model".to_string(),
+                JsonValue::from(query.embedder_model.clone()),
+            );
+            if let Some(page) = page {
+                collection.extend(page_members(page));
+            }
+            (content_type, Json(collection)).into_response()
+        }
+        ResultFormat::Json => {
+            let rows = service.to_rows(result).await;
+            (content_type, Json(rows)).into_response()
+        }
+        ResultFormat::Csv => (content_type, service.to_csv(result).await).into_response(),
+        ResultFormat::Png => {
+            let png = service.to_map_png(result, map_options).await.map_err(|e| {
+                tracing::error!(error = %e, "Map rendering failed");
+                ApiError::internal("Map rendering failed").with_details(e.to_string())
+            })?;
+            (content_type, png).into_response()
+        }
+    };
+
+    if let Some(next) = page.and_then(|page| page.next.as_ref()) {
+        if let Ok(cursor) = HeaderValue::from_str(&next.encode()) {
+            response.headers_mut().insert(NEXT_CURSOR_HEADER, cursor);
+        }
+    }
+    Ok(response)
+}
+
+/// Position of a page and the cursor of the next one, as GeoJSON foreign members
+fn page_members(page: &ResultPage) -> Map<String, JsonValue> {
+    let mut members = Map::new();
+    members.insert(
+        "page".to_string(),
+        serde_json::json!({
+            "offset": page.offset,
+            "size": page.result.sources.len(),
+            "total": page.total,
+        }),
+    );
+    members.insert(
+        "next_cursor".to_string(),
+        page.next
+            .as_ref()
+            .map(|next| JsonValue::from(next.encode()))
+            .unwrap_or(JsonValue::Null),
+    );
+    members
+}
+
+/// Resolve the embedder serving a query
+///
+/// Without an override the configured model is used. An override must be
+/// allow-listed and match the embedder the queried workspace's index was
+/// built with, since vectors from one model cannot be searched with another
+/// model's query embedding.
+async fn select_embedder(
+    state: &AppState,
+    requested: Option<&str>,
+) -> Result<AllowedEmbedder, ApiError> {
+    let config = &state.embedder_config;
+    let Some(model) = requested.filter(|model| *model != config.model) else {
+        return Ok(AllowedEmbedder {
+            model: config.model.clone(),
+            dimensions: config.dimensions,
+        });
+    };
+
+    let allowed = config.allowed(model).ok_or_else(|| {
+        ApiError::unprocessable(format!("Embedder model '{}' is not allowed", model))
+            .with_details(format!("Allowed models: {}", config.allowed_names().join(", ")))
+    })?;
+
+    // The state is scoped to the queried workspace, so this is its index only
+    let built = state.get_index_state().await.ok().map(|index| index.embedder);
+    if built.as_deref() != Some(allowed.model.as_str()) {
+        return Err(ApiError::unprocessable(format!(
+            "The index of this workspace was not built with embedder model '{}'",
+            model
+        ))
+        .with_details(format!(
+            "Model of the workspace's index: {}",
+            built.as_deref().unwrap_or("none")
+        )));
+    }
+
+    Ok(allowed)
+}
+
+/// Fill the options a request leaves unset from the preset it names, or else
+/// the workspace's default preset
+///
+/// Returns which options came from the preset, or `None` when no preset applies.
+fn apply_preset(
+    request: &mut QueryRequest,
+    settings: Option<&WorkspaceSettings>,
+) -> Result<Option<AppliedPreset>, ApiError> {
+    let Some(mut merge) = select_preset(
+        settings.and_then(|s| s.query_presets.as_ref()),
+        settings.and_then(|s| s.default_query_preset.as_deref()),
+        request.preset.as_deref(),
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let preset = merge.preset.clone();
+    merge.fill("top_k", &mut request.top_k, preset.top_k);
+    merge.fill("min_score", &mut request.min_score, preset.min_score);
+    merge.fill("rerank", &mut request.rerank, preset.rerank);
+    merge.fill("rerank_pool", &mut request.rerank_pool, preset.rerank_pool);
+    merge.fill("explain", &mut request.explain, preset.explain);
+    merge.fill("merge_overlapping", &mut request.merge_overlapping, preset.merge_overlapping);
+    merge.fill("parse_operators", &mut request.parse_operators, preset.parse_operators);
+    merge.fill("geometry_detail", &mut request.geometry_detail, preset.geometry_detail);
+    merge.fill("vertex_budget", &mut request.vertex_budget, preset.vertex_budget);
+    merge.fill(
+        "coordinate_precision",
+        &mut request.coordinate_precision,
+        preset.coordinate_precision,
+    );
+    Ok(Some(merge.finish()))
+}
+
+/// Build the query plan from the request and the server's query defaults
+///
+/// Only chunks from datasets allowed by `visibility` can be returned. Stored
+/// workspace settings fill in defaults the server's environment leaves unset.
+/// `area` is the saved area named by the request and `excluded_areas` those
+/// it excludes, already resolved. `preset` records the workspace preset
+/// already merged into the request.
+fn query_plan(
+    state: &AppState,
+    request: &QueryRequest,
+    area: Option<&SavedArea>,
+    excluded_areas: &[SavedArea],
+    visibility: TagVisibility,
+    settings: Option<&WorkspaceSettings>,
+    preset: Option<AppliedPreset>,
+) -> Result<QueryPlan, ApiError> {
+    let rerank = match &request.rerank {
+        Some(mode) => parse_rerank(mode)?,
+        None => state.query_config.rerank,
+    };
+
+    let mut plan = QueryPlan::new(&request.text)
+        .with_semantic_rerank(true)
+        .with_rerank(rerank)
+        .with_rerank_pool(request.rerank_pool.unwrap_or(state.query_config.rerank_pool))
+        .with_explain(request.explain.unwrap_or(false))
+        .with_merge_overlapping(request.merge_overlapping.unwrap_or(false))
+        .with_visibility(visibility);
+
+    if let Some(top_k) = request.top_k {
+        plan = plan.with_top_k(top_k);
+    }
+
+    if let Some(preset) = preset {
+        plan = plan.with_preset(preset);
+    }
+
+    if let Some(min_score) = request.min_score.or(state.query_min_score(settings)) {
+        plan = plan.with_min_score(min_score);
+    }
+
+    if let Some(grouping) = &request.group_by_time {
+        plan = plan.with_group_by_time(grouping.clone());
+    }
+
+    for (property, value) in &request.attributes {
+        let value = property_text(value).ok_or_else(|| {
+            ApiError::bad_request("Invalid attribute filter")
+                .with_details(format!("attributes.{} must not be null", property))
+        })?;
+        plan = plan.with_attribute_filter(AttributeFilter::new(property, value));
+    }
+
+    let spatial_sources = [
+        request.bbox.is_some(),
+        request.geometry.is_some(),
+        area.is_some(),
+        request.point.is_some(),
+    ];
+    let spatial_sources = spatial_sources.iter().filter(|set| **set).count();
+    if spatial_sources > 1 {
+        return Err(ApiError::bad_request("Specify only one of bbox, geometry, area or point"));
+    }
+
+    let buffer = request.buffer.as_ref().map(|b| parse_distance("buffer", b)).transpose()?;
+    if buffer.is_some() && spatial_sources == 0 {
+        return Err(ApiError::bad_request(
+            "buffer requires a bbox, geometry, area or point filter",
+        ));
+    }
+    if request.radius.is_some() && request.point.is_none() {
+        return Err(ApiError::bad_request("radius requires a point"));
+    }
+
+    let mut exclude = Vec::with_capacity(request.exclude_geometries.len());
+    for (i, geometry) in request.exclude_geometries.iter().enumerate() {
+        let geometry = CoreGeometry::from_geojson(geometry).ok_or_else(|| {
+            ApiError::bad_request(format!("exclude_geometries[{}] must be a GeoJSON geometry", i))
+        })?;
+        exclude.push(geometry);
+    }
+    exclude.extend(excluded_areas.iter().map(|area| area.geometry.clone()));
+    if !exclude.is_empty() && spatial_sources == 0 {
+        return Err(ApiError::bad_request(
+            "exclude_geometries and exclude_areas require a bbox, geometry, area or point filter",
+        ));
+    }
+
+    if let Some(bbox) = request.bbox {
+        plan = plan.with_spatial_filter(SpatialFilter {
+            predicate: SpatialPredicate::BoundingBox,
+            geometry: Some(bbox_to_polygon(&bbox)),
+            distance: None,
+            crs: Crs::wgs84(),
+            buffer,
+            exclude: Vec::new(),
+        });
+    }
+
+    if let Some(geometry) = &request.geometry {
+        let geometry = CoreGeometry::from_geojson(geometry)
+            .ok_or_else(|| ApiError::bad_request("geometry must be a GeoJSON geometry"))?;
+        let predicate = parse_shape_predicate(request.predicate.as_deref())?;
+        plan = plan.with_spatial_filter(SpatialFilter {
+            predicate,
+            geometry: Some(geometry),
+            distance: None,
+            crs: Crs::wgs84(),
+            buffer,
+            exclude: Vec::new(),
+        });
+    }
+
+    if let Some(area) = area {
+        let predicate = parse_shape_predicate(request.predicate.as_deref())?;
+        plan = plan
+            .with_spatial_filter(SpatialFilter {
+                predicate,
+                geometry: Some(area.geometry.clone()),
+                distance: None,
+                crs: Crs::wgs84(),
+                buffer,
+                exclude: Vec::new(),
+            })
+            .with_named_area(NamedAreaExpansion {
+                name: area.name.clone(),
+                vertices: area.vertex_count(),
+            });
+    }
+
+    // Explicit predicate and radius win; the workspace defaults fill in the rest
+    if let Some(point) = request.point {
+        let predicate = request.predicate.as_deref().map(parse_predicate).transpose()?;
+        let radius = request.radius.as_ref().map(|r| parse_distance("radius", r)).transpose()?;
+        let (filter, applied) = state
+            .query_point_defaults(settings)
+            .filter(point, predicate, radius)
+            .map_err(|e| ApiError::bad_request("Invalid point query").with_details(e))?;
+        plan = plan.with_spatial_filter(SpatialFilter { buffer, ..filter });
+        if !applied.is_empty() {
+            plan = plan.with_point_defaults(applied);
+        }
+    }
+
+    if let Some(filter) = &mut plan.spatial_filter {
+        filter.exclude = exclude;
+    }
+    for area in excluded_areas {
+        plan = plan.with_excluded_area(NamedAreaExpansion {
+            name: area.name.clone(),
+            vertices: area.vertex_count(),
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Geometry detail and coordinate precision requested for the response
+fn geometry_output(request: &QueryRequest) -> Result<GeometryOutput, ApiError> {
+    Ok(georag_service::geometry_output(
+        request.geometry_detail.as_deref(),
+        request.coordinate_precision,
+        request.vertex_budget,
+    )?)
+}
+
+/// Geometry output echoed as foreign members of the GeoJSON response
+fn geometry_members(output: &GeometryOutput) -> Map<String, JsonValue> {
+    let mut members = Map::new();
+    members.insert("geometry_detail".to_string(), JsonValue::from(output.detail.to_string()));
+    if let Some(precision) = output.precision {
+        members.insert("coordinate_precision".to_string(), JsonValue::from(precision));
+    }
+    if output.detail == GeometryDetail::Adaptive {
+        members.insert("vertex_budget".to_string(), JsonValue::from(output.vertex_budget));
+    }
+    members
+}
+
+/// Parse the buffer or radius distance in `field`, rejecting non-positive values
+fn parse_distance(field: &str, distance: &BufferRequest) -> Result<Distance, ApiError> {
+    let unit = match distance.unit.as_deref() {
+        Some(unit) => parse_distance_unit(unit).map_err(|e| {
+            ApiError::bad_request(format!("Invalid {} unit", field)).with_details(e.to_string())
+        })?,
+        None => DistanceUnit::Meters,
+    };
+
+    if !distance.distance.is_finite() || distance.distance <= 0.0 {
+        return Err(ApiError::bad_request(format!("{}.distance must be greater than zero", field)));
+    }
+
+    Ok(Distance::new(distance.distance, unit))
+}
+
+/// Query summary returned as foreign members of the GeoJSON response
+fn summary_members(result: &QueryResult) -> Map<String, JsonValue> {
+    let mut members = Map::new();
+    members.insert(
+        "filtered_by_threshold".to_string(),
+        JsonValue::from(result.filtered_by_threshold),
+    );
+    if result.all_below_threshold() {
+        members.insert("message".to_string(), JsonValue::from(result.answer.clone()));
+    }
+    if let Some(distribution) =
+        result.explanation.as_ref().and_then(|e| e.score_distribution.as_ref())
+    {
+        members.insert(
+            "score_distribution".to_string(),
+            serde_json::to_value(distribution).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(simplification) = result
+        .explanation
+        .as_ref()
+        .and_then(|e| e.spatial_phase.filter_simplification.as_ref())
+    {
+        members.insert(
+            "filter_simplification".to_string(),
+            serde_json::to_value(simplification).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(named_area) =
+        result.explanation.as_ref().and_then(|e| e.spatial_phase.named_area.as_ref())
+    {
+        members.insert(
+            "named_area".to_string(),
+            serde_json::to_value(named_area).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(point_defaults) = result
+        .explanation
+        .as_ref()
+        .and_then(|e| e.spatial_phase.point_defaults.as_ref())
+    {
+        members.insert(
+            "point_defaults".to_string(),
+            serde_json::to_value(point_defaults).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(exclusions) =
+        result.explanation.as_ref().and_then(|e| e.spatial_phase.exclusions.as_ref())
+    {
+        members.insert(
+            "exclusions".to_string(),
+            serde_json::to_value(exclusions).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(explanation) = &result.explanation {
+        members.insert(
+            "timings".to_string(),
+            serde_json::to_value(&explanation.timings).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(attributes) = result.explanation.as_ref().and_then(|e| e.attribute_phase.as_ref()) {
+        members.insert(
+            "attribute_phase".to_string(),
+            serde_json::to_value(attributes).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(rerank) = result.explanation.as_ref().and_then(|e| e.rerank_phase.as_ref()) {
+        members.insert(
+            "rerank_phase".to_string(),
+            serde_json::to_value(rerank).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(confidence) = result.explanation.as_ref().and_then(|e| e.confidence.as_ref()) {
+        members.insert(
+            "confidence".to_string(),
+            serde_json::to_value(confidence).unwrap_or(JsonValue::Null),
+        );
+    }
+    if let Some(preset) = result.explanation.as_ref().and_then(|e| e.preset.as_ref()) {
+        members
+            .insert("preset".to_string(), serde_json::to_value(preset).unwrap_or(JsonValue::Null));
+    }
+    if let Some(time_groups) = &result.time_groups {
+        members.insert(
+            "time_groups".to_string(),
+            serde_json::to_value(time_groups).unwrap_or(JsonValue::Null),
+        );
+    }
+    if !result.diagnostics.is_empty() {
+        members.insert(
+            "diagnostics".to_string(),
+            serde_json::to_value(&result.diagnostics).unwrap_or(JsonValue::Null),
+        );
+        members.insert(
+            "candidates".to_string(),
+            serde_json::to_value(result.candidates).unwrap_or(JsonValue::Null),
+        );
+    }
+    members
+}
+
+/// Size of a `png` result from the `width` and `height` parameters
+fn map_options(params: &QueryFormatParams) -> Result<MapOptions, ApiError> {
+    let defaults = MapOptions::default();
+    MapOptions::new(
+        params.width.unwrap_or(defaults.width),
+        params.height.unwrap_or(defaults.height),
+    )
+    .map_err(|e| ApiError::bad_request("Invalid map size").with_details(e))
+}
+
+/// Pick the result format from the `format` parameter, then the Accept header
+///
+/// Requests without either get GeoJSON.
+fn negotiate_format(
+    format_param: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<ResultFormat, ApiError> {
+    let not_acceptable = || {
+        ApiError::not_acceptable("Unsupported result format")
+            .with_details(format!("Supported formats: {}", ResultFormat::supported()))
+    };
+
+    if let Some(format) = format_param {
+        return format.parse().map_err(|_| not_acceptable());
+    }
+
+    match headers.get(header::ACCEPT) {
+        None => Ok(ResultFormat::default()),
+        Some(accept) => accept
+            .to_str()
+            .ok()
+            .and_then(ResultFormat::from_accept)
+            .ok_or_else(not_acceptable),
+    }
+}
+
+fn bbox_to_polygon(bbox: &[f64; 4]) -> CoreGeometry {
+    let [min_lng, min_lat, max_lng, max_lat] = *bbox;
+    CoreGeometry::polygon(vec![vec![
+        [min_lng, min_lat],
+        [max_lng, min_lat],
+        [max_lng, max_lat],
+        [min_lng, max_lat],
+        [min_lng, min_lat],
+    ]])
+}
+
+/// Parse the predicate applied with a request geometry or area (defaults to intersects)
+///
+/// `dwithin` measures from a point, so it is only accepted with `point`.
+fn parse_shape_predicate(predicate: Option<&str>) -> Result<SpatialPredicate, ApiError> {
+    match predicate.map(parse_predicate).transpose()? {
+        None => Ok(SpatialPredicate::Intersects),
+        Some(SpatialPredicate::DWithin) => {
+            Err(ApiError::bad_request("Predicate dwithin requires a point and radius"))
+        }
+        Some(predicate) => Ok(predicate),
+    }
+}
 
-    Ok(Json(result))
+/// Parse a request predicate
+fn parse_predicate(predicate: &str) -> Result<SpatialPredicate, ApiError> {
+    match predicate.to_lowercase().as_str() {
+        "intersects" => Ok(SpatialPredicate::Intersects),
+        "within" => Ok(SpatialPredicate::Within),
+        "contains" => Ok(SpatialPredicate::Contains),
+        "coveredby" | "covered_by" => Ok(SpatialPredicate::CoveredBy),
+        "touches" => Ok(SpatialPredicate::Touches),
+        "crosses" => Ok(SpatialPredicate::Crosses),
+        "overlaps" => Ok(SpatialPredicate::Overlaps),
+        "bbox" => Ok(SpatialPredicate::BoundingBox),
+        "dwithin" => Ok(SpatialPredicate::DWithin),
+        other => Err(ApiError::bad_request(format!(
+            "Invalid predicate '{}': expected within, intersects, contains, coveredby, touches, \
+            crosses, overlaps, bbox or dwithin",
+            other
+        ))),
+    }
 }
@@ -1,78 +1,210 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Query, State},
     http::StatusCode,
-    Json,
+    Extension, Json,
 };
-use georag_core::config::{parse_distance_unit, parse_validity_mode};
-use georag_core::models::WorkspaceConfig;
+use chrono::{DateTime, NaiveDate, Utc};
+use georag_core::config::{ConfigSource, WorkspaceSettings};
+use georag_core::models::{DatasetMeta, TimelineGranularity, WorkspaceId, WorkspaceMeta};
+use georag_core::resources::ResourceKind;
+use georag_service::{CloneReport, CloneVerification, WorkspaceCloneService, WorkspaceView};
 
-use crate::dto::{CreateWorkspaceRequest, DeleteResponse, WorkspaceResponse};
+use crate::auth::Caller;
+use crate::dto::{
+    CloneVerificationResponse, CloneWorkspaceRequest, CloneWorkspaceResponse,
+    ClonedDatasetResponse, ConfigEntryResponse, CreateWorkspaceRequest, DatasetIndexUsage,
+    DeleteResponse, TimelineParams, TimelineResponse, WorkspaceDetailResponse, WorkspaceResponse,
+    WorkspaceSettingsResponse, WorkspaceUsageResponse,
+};
 use crate::error::ApiError;
 use crate::state::AppState;
+use crate::workspace::Workspace;
 
+/// Create a workspace with any of its settings
+///
+/// Names must be unique, since routes select workspaces by name or ID. Keys
+/// bound to specific workspaces cannot create workspaces.
 pub async fn create_workspace(
     State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
     Json(request): Json<CreateWorkspaceRequest>,
-) -> Result<(StatusCode, Json<WorkspaceResponse>), ApiError> {
-    tracing::info!(name = %request.name, crs = request.crs, "Creating workspace");
-
-    let distance_unit = request
-        .distance_unit
-        .as_deref()
-        .map(parse_distance_unit)
-        .transpose()
-        .map_err(|e| ApiError::bad_request("Invalid distance unit").with_details(e.to_string()))?
-        .unwrap_or_default();
-
-    let geometry_validity = request
-        .geometry_validity
-        .as_deref()
-        .map(parse_validity_mode)
-        .transpose()
-        .map_err(|e| ApiError::bad_request("Invalid validity mode").with_details(e.to_string()))?
-        .unwrap_or_default();
-
-    let config = WorkspaceConfig {
-        crs: request.crs,
-        distance_unit,
-        geometry_validity,
-    };
+) -> Result<(StatusCode, Json<WorkspaceDetailResponse>), ApiError> {
+    tracing::info!(name = %request.name, crs = ?request.settings.crs, "Creating workspace");
 
-    let id = state
-        .workspace_store
-        .create_workspace(&request.name, &config)
-        .await
-        .map_err(|e| {
-            tracing::error!(error = %e, "Failed to create workspace");
-            ApiError::internal("Failed to create workspace").with_details(e.to_string())
-        })?;
-
-    let workspace = state
-        .workspace_store
-        .get_workspace(id)
-        .await
-        .map_err(|e| {
-            tracing::error!(error = %e, "Failed to get workspace after creation");
-            ApiError::internal("Failed to retrieve created workspace").with_details(e.to_string())
-        })?
-        .ok_or_else(|| ApiError::internal("Workspace not found after creation"))?;
-
-    let response = WorkspaceResponse {
-        id: workspace.id.to_string(),
-        name: workspace.name,
-        crs: workspace.crs,
-        distance_unit: format!("{:?}", workspace.distance_unit),
-        geometry_validity: format!("{:?}", workspace.geometry_validity),
-        created_at: workspace.created_at,
+    caller.require_all_workspaces()?;
+    if !state.can_serve_workspace(&request.name) {
+        return Err(ApiError::not_implemented(
+            "This storage backend serves the default workspace only",
+        )
+        .with_details(format!("Use the '{}' workspace", state.default_workspace)));
+    }
+
+    let view = state.workspace_service().create(&request.name, &request.settings).await?;
+
+    Ok((StatusCode::CREATED, Json(workspace_detail(&state, view))))
+}
+
+/// Copy a workspace's settings and data into a new workspace
+///
+/// The build lock is held throughout, so no rebuild or compaction changes
+/// chunks during the copy; datasets ingested meanwhile are left out. A clone
+/// that fails, or whose counts differ from the source's, is deleted again.
+pub async fn clone_workspace(
+    Extension(workspace): Extension<Workspace>,
+    Extension(caller): Extension<Caller>,
+    Json(request): Json<CloneWorkspaceRequest>,
+) -> Result<(StatusCode, Json<CloneWorkspaceResponse>), ApiError> {
+    tracing::info!(
+        workspace_id = %workspace.id,
+        name = %request.name,
+        include_blobs = request.include_blobs,
+        "Cloning workspace"
+    );
+
+    caller.require_all_workspaces()?;
+    caller.require_unrestricted()?;
+
+    let (state, id) = (&workspace.state, workspace.id);
+    if state.store_provider.is_none() {
+        return Err(ApiError::not_implemented(
+            "This storage backend serves the default workspace only",
+        )
+        .with_details("Cloning needs a backend that keeps each workspace's data apart"));
+    }
+
+    let _slot = state.governor.acquire(ResourceKind::Export).await?;
+    let _build = state.build_lock.try_lock().map_err(|_| {
+        ApiError::conflict("An index rebuild is in progress")
+            .with_details("Retry once the rebuild has finished")
+    })?;
+
+    let datasets = state.workspace_datasets(id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to list datasets");
+        ApiError::internal("Failed to list datasets").with_details(e.to_string())
+    })?;
+    let view = state.workspace_service().create_clone(id, &request.name).await?;
+    let clone_id = view.meta.id;
+
+    let report = match copy_workspace(state, &view.meta, &datasets, request.include_blobs).await {
+        Ok(report) => report,
+        Err(e) => {
+            // Leave no half-copied workspace behind
+            discard_workspace(state, clone_id).await;
+            return Err(e);
+        }
     };
 
-    Ok((StatusCode::CREATED, Json(response)))
+    // Same chunks and embeddings, so the same index
+    if let Ok(index) = state.get_index_state().await {
+        state.set_workspace_index_state(clone_id, index).await;
+    }
+
+    let verification = report.verification;
+    Ok((
+        StatusCode::CREATED,
+        Json(CloneWorkspaceResponse {
+            workspace: workspace_detail(state, view),
+            source_workspace_id: id.to_string(),
+            datasets: report
+                .datasets
+                .iter()
+                .map(|mapping| ClonedDatasetResponse {
+                    source_id: mapping.source.0.to_string(),
+                    id: mapping.destination.0.to_string(),
+                })
+                .collect(),
+            usage: report.copied,
+            verification: CloneVerificationResponse {
+                source: verification.source.into(),
+                clone: verification.destination.into(),
+            },
+        }),
+    ))
+}
+
+/// Copy the datasets of the source state's workspace into a new workspace
+async fn copy_workspace(
+    state: &AppState,
+    clone: &WorkspaceMeta,
+    datasets: &[DatasetMeta],
+    include_blobs: bool,
+) -> Result<CloneReport, ApiError> {
+    let destination = state.for_workspace(clone).await?;
+    let service = WorkspaceCloneService::new(
+        state.workspace_stores(),
+        destination.workspace_stores(),
+        destination.workspace_quota(clone.id).await?,
+    )
+    .with_blobs(include_blobs);
+
+    let plan = service.plan(datasets).await?;
+    let report = service
+        .run(&plan, |progress| {
+            tracing::info!(
+                phase = progress.phase.as_str(),
+                done = progress.done,
+                total = progress.total,
+                "Clone progress"
+            );
+        })
+        .await?;
+
+    if !report.verification.matches() {
+        let CloneVerification { source, destination } = report.verification;
+        return Err(ApiError::internal("Clone verification failed").with_details(format!(
+            "Read {} datasets, {} features, {} chunks, {} embeddings and {} files from the source; \
+             the clone holds {}, {}, {}, {} and {}",
+            source.datasets,
+            source.features,
+            source.chunks,
+            source.embeddings,
+            source.blobs,
+            destination.datasets,
+            destination.features,
+            destination.chunks,
+            destination.embeddings,
+            destination.blobs
+        )));
+    }
+    Ok(report)
+}
+
+/// Delete a workspace created for a clone that did not complete
+async fn discard_workspace(state: &AppState, id: WorkspaceId) {
+    if let Err(e) = state.workspace_store.delete_workspace(id).await {
+        tracing::warn!(workspace_id = %id, error = %e, "Failed to delete incomplete clone");
+    }
+    state.forget_workspace_settings(id).await;
+    if let Err(e) = state.forget_workspace_stores(id).await {
+        tracing::warn!(workspace_id = %id, error = ?e, "Failed to drop stores of incomplete clone");
+    }
 }
 
+/// Change some of a workspace's settings, keeping the others
+///
+/// The CRS cannot change once features are stored, nor the embedder's
+/// dimensions once an index is built.
+pub async fn update_workspace(
+    Extension(workspace): Extension<Workspace>,
+    Json(patch): Json<WorkspaceSettings>,
+) -> Result<Json<WorkspaceDetailResponse>, ApiError> {
+    tracing::info!(workspace_id = %workspace.id, "Updating workspace settings");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let index = state.get_index_state().await.ok();
+    let view = state.workspace_service().update(id, &patch, index.as_ref()).await?;
+    state.forget_workspace_settings(id).await;
+
+    Ok(Json(workspace_detail(state, view)))
+}
+
+/// List the workspaces the caller may use, sorted by name
 pub async fn list_workspaces(
     State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
 ) -> Result<Json<Vec<WorkspaceResponse>>, ApiError> {
     tracing::info!("Listing workspaces");
 
@@ -83,42 +215,172 @@ pub async fn list_workspaces(
 
     let responses: Vec<WorkspaceResponse> = workspaces
         .into_iter()
-        .map(|w| WorkspaceResponse {
-            id: w.id.to_string(),
-            name: w.name,
-            crs: w.crs,
-            distance_unit: format!("{:?}", w.distance_unit),
-            geometry_validity: format!("{:?}", w.geometry_validity),
-            created_at: w.created_at,
-        })
+        .filter(|w| caller.allows_workspace(w))
+        .map(workspace_response)
         .collect();
 
     Ok(Json(responses))
 }
 
 pub async fn delete_workspace(
-    State(state): State<Arc<AppState>>,
-    Path(workspace_id): Path<String>,
+    Extension(workspace): Extension<Workspace>,
 ) -> Result<Json<DeleteResponse>, ApiError> {
-    tracing::info!(workspace_id = %workspace_id, "Deleting workspace");
+    tracing::info!(workspace_id = %workspace.id, "Deleting workspace");
 
-    let id = workspace_id
-        .parse()
-        .map_err(|_| ApiError::bad_request("Invalid workspace ID format"))?;
+    let (state, id) = (&workspace.state, workspace.id);
+    state.workspace_store.delete_workspace(id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to delete workspace");
+        ApiError::internal("Failed to delete workspace").with_details(e.to_string())
+    })?;
+    state.forget_workspace_settings(id).await;
+    state.forget_workspace_stores(id).await?;
 
-    let workspace = state.workspace_store.get_workspace(id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to check workspace existence");
-        ApiError::internal("Failed to verify workspace").with_details(e.to_string())
+    Ok(Json(DeleteResponse::success("workspace", &id.to_string())))
+}
+
+pub async fn get_workspace_settings(
+    Extension(workspace): Extension<Workspace>,
+) -> Result<Json<WorkspaceSettingsResponse>, ApiError> {
+    let (state, id) = (&workspace.state, workspace.id);
+
+    let settings = state.workspace_store.get_workspace_settings(id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read workspace settings");
+        ApiError::internal("Failed to read workspace settings").with_details(e.to_string())
     })?;
 
-    if workspace.is_none() {
-        return Err(ApiError::not_found("Workspace not found"));
-    }
+    Ok(Json(WorkspaceSettingsResponse {
+        workspace_id: id.to_string(),
+        stored: settings.is_some(),
+        settings: settings.unwrap_or_default(),
+    }))
+}
 
-    state.workspace_store.delete_workspace(id).await.map_err(|e| {
-        tracing::error!(error = %e, "Failed to delete workspace");
-        ApiError::internal("Failed to delete workspace").with_details(e.to_string())
+pub async fn put_workspace_settings(
+    Extension(workspace): Extension<Workspace>,
+    Json(settings): Json<WorkspaceSettings>,
+) -> Result<Json<WorkspaceSettingsResponse>, ApiError> {
+    tracing::info!(workspace_id = %workspace.id, "Replacing workspace settings");
+
+    let (state, id) = (&workspace.state, workspace.id);
+    let index = state.get_index_state().await.ok();
+    let view = state.workspace_service().replace(id, &settings, index.as_ref()).await?;
+    state.forget_workspace_settings(id).await;
+
+    Ok(Json(WorkspaceSettingsResponse {
+        workspace_id: id.to_string(),
+        stored: true,
+        settings: view.settings,
+    }))
+}
+
+/// Current usage of a workspace and the quotas enforced on it
+pub async fn get_workspace_usage(
+    Extension(workspace): Extension<Workspace>,
+) -> Result<Json<WorkspaceUsageResponse>, ApiError> {
+    let quota = workspace.state.workspace_quota(workspace.id).await?;
+    let usage = quota.usage().await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to read workspace usage");
+        ApiError::internal("Failed to read workspace usage").with_details(e.to_string())
+    })?;
+
+    // Read from the stores' counters, so large indexes answer without a scan
+    let stats = workspace.state.index_stats_service().stats().await?;
+    let datasets = workspace.state.workspace_datasets(workspace.id).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to list datasets");
+        ApiError::internal("Failed to list datasets").with_details(e.to_string())
     })?;
+    let datasets = datasets
+        .into_iter()
+        .map(|meta| DatasetIndexUsage {
+            id: meta.id.0.to_string(),
+            stats: stats.get(&meta.id).copied().unwrap_or_default(),
+            name: meta.name,
+        })
+        .collect();
+
+    Ok(Json(WorkspaceUsageResponse {
+        workspace_id: workspace.id.to_string(),
+        usage,
+        quotas: quota.quotas(),
+        datasets,
+    }))
+}
+
+/// Events of a workspace counted per bucket since a recent point in time
+pub async fn get_workspace_timeline(
+    Extension(workspace): Extension<Workspace>,
+    Query(params): Query<TimelineParams>,
+) -> Result<Json<TimelineResponse>, ApiError> {
+    let since = params.since.as_deref().map(parse_since).transpose()?;
+    let granularity = match params.granularity.as_deref() {
+        Some(granularity) => granularity
+            .parse::<TimelineGranularity>()
+            .map_err(|e| ApiError::bad_request("Invalid granularity").with_details(e))?,
+        None => TimelineGranularity::default(),
+    };
 
-    Ok(Json(DeleteResponse::success("workspace", &workspace_id)))
+    let timeline = workspace
+        .state
+        .audit_service()
+        .timeline(workspace.id, since, granularity)
+        .await?;
+
+    Ok(Json(TimelineResponse {
+        workspace_id: workspace.id.to_string(),
+        timeline,
+    }))
+}
+
+/// Read `since` as an RFC 3339 time or a date, taken as midnight UTC
+fn parse_since(since: &str) -> Result<DateTime<Utc>, ApiError> {
+    DateTime::parse_from_rfc3339(since)
+        .map(|since| since.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                .map(|date| date.and_time(Default::default()).and_utc())
+        })
+        .map_err(|_| {
+            ApiError::bad_request("Invalid since").with_details(format!(
+                "Expected an RFC 3339 time or a YYYY-MM-DD date, got '{}'",
+                since
+            ))
+        })
+}
+
+fn workspace_response(workspace: WorkspaceMeta) -> WorkspaceResponse {
+    WorkspaceResponse {
+        id: workspace.id.to_string(),
+        name: workspace.name,
+        crs: workspace.crs,
+        distance_unit: format!("{:?}", workspace.distance_unit),
+        geometry_validity: format!("{:?}", workspace.geometry_validity),
+        created_at: workspace.created_at,
+    }
+}
+
+/// A workspace with its settings and the configuration in effect
+///
+/// The server's embedder applies unless the settings name one.
+fn workspace_detail(state: &AppState, view: WorkspaceView) -> WorkspaceDetailResponse {
+    let mut effective: BTreeMap<String, ConfigEntryResponse> = view
+        .effective()
+        .to_inspection_map()
+        .into_iter()
+        .map(|(key, (value, source))| (key, ConfigEntryResponse { value, source }))
+        .collect();
+    if view.settings.embedder.is_none() {
+        effective.insert(
+            "embedder".to_string(),
+            ConfigEntryResponse {
+                value: state.embedder_config.model.clone(),
+                source: ConfigSource::Default,
+            },
+        );
+    }
+
+    WorkspaceDetailResponse {
+        workspace: workspace_response(view.meta),
+        settings: view.settings,
+        effective,
+    }
 }
@@ -0,0 +1,433 @@
+//! Storage backend health supervision
+//!
+//! A [`StoreSupervisor`] checks the storage backend every `check_interval`
+//! and keeps a circuit state: `healthy` while checks pass, `degraded` after a
+//! failed check and `down` once `down_after` checks in a row have failed. The
+//! first check that passes again closes the circuit.
+//!
+//! While the circuit is not healthy, writes are rejected with 503 and index
+//! rebuilds that fail wait for the backend instead of failing. Once it is
+//! down, queries of the default workspace are served from the fallback
+//! bundle, when one is configured and was exported from the index the
+//! workspace is served with. Every outage is appended to `audit.log` when it
+//! ends, with the requests the fallback served.
+
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use georag_store::bundle::BundleStore;
+use georag_store::ports::HealthProbe;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Time between two health checks unless configured
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Failed checks in a row after which the backend counts as down, unless configured
+pub const DEFAULT_DOWN_AFTER: u32 = 3;
+
+/// Event name of outage entries in the audit log
+const STORE_OUTAGE_EVENT: &str = "store_outage";
+
+/// Requests served by the fallback listed per outage; later ones are only counted
+const MAX_LISTED_FALLBACK_REQUESTS: usize = 100;
+
+/// Outages reported by `/health`
+const RECENT_OUTAGES: usize = 10;
+
+/// Circuit state of a storage backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// The last health check passed
+    Healthy,
+
+    /// Health checks are failing, fewer than `down_after` in a row
+    Degraded,
+
+    /// At least `down_after` health checks in a row failed
+    Down,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Healthy => "healthy",
+            CircuitState::Degraded => "degraded",
+            CircuitState::Down => "down",
+        }
+    }
+}
+
+/// How often the backend is checked and when it counts as down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// Time between two checks, also the timeout of each check
+    pub check_interval: Duration,
+
+    /// Failed checks in a row after which the backend counts as down
+    pub down_after: u32,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            down_after: DEFAULT_DOWN_AFTER,
+        }
+    }
+}
+
+/// Counters since the server started
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct HealthCounters {
+    pub checks: u64,
+    pub failed_checks: u64,
+    pub outages: u64,
+    /// Writes rejected while the circuit was not healthy
+    pub rejected_writes: u64,
+    /// Queries served from the fallback bundle
+    pub fallback_queries: u64,
+}
+
+/// Request served from the fallback bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct FallbackRequest {
+    pub at: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub workspace: String,
+}
+
+/// Outage of a storage backend, from the first failed check to the next passing one
+#[derive(Debug, Clone, Serialize)]
+pub struct OutageRecord {
+    pub backend: String,
+    pub started_at: DateTime<Utc>,
+    /// None while the outage lasts
+    pub ended_at: Option<DateTime<Utc>>,
+    pub failed_checks: u64,
+    /// Error of the last failed check
+    pub last_error: Option<String>,
+    pub rejected_writes: u64,
+    pub fallback_queries: u64,
+    /// The first requests served from the fallback bundle
+    pub fallback_requests: Vec<FallbackRequest>,
+}
+
+/// Bundle serving queries while the backend is down
+#[derive(Debug, Clone, Serialize)]
+pub struct FallbackStatus {
+    /// Hash of the index the bundle was exported from
+    pub index_hash: Option<String>,
+    /// When that index was built
+    pub index_built_at: Option<DateTime<Utc>>,
+}
+
+/// Health of a storage backend, as reported by `/health`
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreHealth {
+    pub backend: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub last_checked: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<FallbackStatus>,
+    pub counters: HealthCounters,
+    /// Outage in progress, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outage: Option<OutageRecord>,
+    /// Latest ended outages, oldest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub recent_outages: Vec<OutageRecord>,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    last_checked: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    counters: HealthCounters,
+    outage: Option<OutageRecord>,
+    recent_outages: VecDeque<OutageRecord>,
+}
+
+/// Periodic health checks of a storage backend and its circuit state
+pub struct StoreSupervisor {
+    probe: Arc<dyn HealthProbe>,
+    policy: HealthPolicy,
+    fallback: Option<Arc<BundleStore>>,
+    audit_log: Option<PathBuf>,
+    circuit: Mutex<Circuit>,
+    state: watch::Sender<CircuitState>,
+}
+
+impl StoreSupervisor {
+    pub fn new(probe: Arc<dyn HealthProbe>, policy: HealthPolicy) -> Self {
+        Self {
+            probe,
+            policy,
+            fallback: None,
+            audit_log: None,
+            circuit: Mutex::new(Circuit::default()),
+            state: watch::Sender::new(CircuitState::Healthy),
+        }
+    }
+
+    /// Set the bundle serving queries while the backend is down
+    pub fn with_fallback(mut self, bundle: Arc<BundleStore>) -> Self {
+        self.fallback = Some(bundle);
+        self
+    }
+
+    /// Set the JSON Lines file ended outages are appended to
+    pub fn with_audit_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// Name of the supervised backend
+    pub fn backend(&self) -> &str {
+        self.probe.backend()
+    }
+
+    /// Current circuit state
+    pub fn state(&self) -> CircuitState {
+        *self.state.borrow()
+    }
+
+    /// Check the backend now and update the circuit
+    ///
+    /// A check taking longer than the check interval fails.
+    pub async fn check(&self) -> CircuitState {
+        let result =
+            match tokio::time::timeout(self.policy.check_interval, self.probe.health_check()).await
+            {
+                Ok(Ok(())) => Ok(()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => {
+                    Err(format!("no answer within {} ms", self.policy.check_interval.as_millis()))
+                }
+            };
+
+        let ended = self.record(result);
+        if let Some(outage) = ended {
+            tracing::warn!(
+                backend = %outage.backend,
+                started_at = %outage.started_at,
+                failed_checks = outage.failed_checks,
+                rejected_writes = outage.rejected_writes,
+                fallback_queries = outage.fallback_queries,
+                "Storage backend recovered"
+            );
+            if let Some(path) = &self.audit_log {
+                if let Err(e) = record_outage(path, &outage) {
+                    tracing::warn!("Failed to write storage outage audit entry: {}", e);
+                }
+            }
+        }
+        self.state()
+    }
+
+    /// Check the backend every check interval, forever
+    pub async fn run(self: Arc<Self>) {
+        let mut ticks = tokio::time::interval(self.policy.check_interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            self.check().await;
+        }
+    }
+
+    /// Wait until the circuit is healthy; returns at once when it is
+    pub async fn wait_until_healthy(&self) {
+        let mut state = self.state.subscribe();
+        // The sender lives as long as `self`, so waiting cannot fail
+        let _ = state.wait_for(|state| *state == CircuitState::Healthy).await;
+    }
+
+    /// Update the circuit with a check result, returning the outage it ended
+    fn record(&self, result: Result<(), String>) -> Option<OutageRecord> {
+        let mut circuit = self.circuit.lock().unwrap();
+        let now = Utc::now();
+        circuit.last_checked = Some(now);
+        circuit.counters.checks += 1;
+
+        let ended = match result {
+            Ok(()) => {
+                circuit.consecutive_failures = 0;
+                circuit.last_error = None;
+                circuit.outage.take().map(|mut outage| {
+                    outage.ended_at = Some(now);
+                    if circuit.recent_outages.len() == RECENT_OUTAGES {
+                        circuit.recent_outages.pop_front();
+                    }
+                    circuit.recent_outages.push_back(outage.clone());
+                    outage
+                })
+            }
+            Err(error) => {
+                if circuit.outage.is_none() {
+                    tracing::warn!(backend = %self.backend(), "Storage backend check failed: {}", error);
+                    circuit.counters.outages += 1;
+                    circuit.outage = Some(OutageRecord {
+                        backend: self.backend().to_string(),
+                        started_at: now,
+                        ended_at: None,
+                        failed_checks: 0,
+                        last_error: None,
+                        rejected_writes: 0,
+                        fallback_queries: 0,
+                        fallback_requests: Vec::new(),
+                    });
+                }
+                circuit.consecutive_failures += 1;
+                circuit.counters.failed_checks += 1;
+                circuit.last_error = Some(error.clone());
+                if let Some(outage) = &mut circuit.outage {
+                    outage.failed_checks += 1;
+                    outage.last_error = Some(error);
+                }
+                None
+            }
+        };
+
+        let state = match circuit.consecutive_failures {
+            0 => CircuitState::Healthy,
+            failures if failures < self.policy.down_after => CircuitState::Degraded,
+            _ => CircuitState::Down,
+        };
+        self.state.send_if_modified(|current| {
+            if *current == state {
+                return false;
+            }
+            if state == CircuitState::Down {
+                tracing::error!(backend = %self.backend(), "Storage backend is down");
+            }
+            *current = state;
+            true
+        });
+        ended
+    }
+
+    /// Fallback bundle for queries of an index, while the backend is down
+    ///
+    /// The bundle is used only when it was exported from the index with
+    /// `index_hash`; a bundle of an older or newer index would answer with
+    /// other data than the backend.
+    pub fn fallback_for(&self, index_hash: Option<&str>) -> Option<Arc<BundleStore>> {
+        if self.state() != CircuitState::Down {
+            return None;
+        }
+        let fallback = self.fallback.as_ref()?;
+        let exported = fallback.index_state().map(|state| state.hash.as_str());
+        (index_hash.is_some() && exported == index_hash).then(|| fallback.clone())
+    }
+
+    /// Count a request served from the fallback bundle
+    pub fn record_fallback(&self, request: FallbackRequest) {
+        let mut circuit = self.circuit.lock().unwrap();
+        circuit.counters.fallback_queries += 1;
+        if let Some(outage) = &mut circuit.outage {
+            outage.fallback_queries += 1;
+            if outage.fallback_requests.len() < MAX_LISTED_FALLBACK_REQUESTS {
+                outage.fallback_requests.push(request);
+            }
+        }
+    }
+
+    /// Count a write rejected while the circuit is not healthy
+    pub fn record_rejected_write(&self) {
+        let mut circuit = self.circuit.lock().unwrap();
+        circuit.counters.rejected_writes += 1;
+        if let Some(outage) = &mut circuit.outage {
+            outage.rejected_writes += 1;
+        }
+    }
+
+    /// Circuit state, counters and outages of the backend
+    pub fn health(&self) -> StoreHealth {
+        let circuit = self.circuit.lock().unwrap();
+        StoreHealth {
+            backend: self.backend().to_string(),
+            state: self.state(),
+            consecutive_failures: circuit.consecutive_failures,
+            last_checked: circuit.last_checked,
+            last_error: circuit.last_error.clone(),
+            fallback: self.fallback.as_ref().map(|bundle| FallbackStatus {
+                index_hash: bundle.index_state().map(|state| state.hash.clone()),
+                index_built_at: bundle.index_state().map(|state| state.built_at),
+            }),
+            counters: circuit.counters,
+            outage: circuit.outage.clone(),
+            recent_outages: circuit.recent_outages.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Append an ended outage to a JSON Lines audit log
+fn record_outage(audit_log: &Path, outage: &OutageRecord) -> std::io::Result<()> {
+    let mut entry = serde_json::to_value(outage)?;
+    entry["event"] = STORE_OUTAGE_EVENT.into();
+
+    let mut file = OpenOptions::new().create(true).append(true).open(audit_log)?;
+    writeln!(file, "{}", entry)
+}
+
+/// Reject writes with 503 while the storage backend is not healthy
+///
+/// Queries and other reads pass; the query handler's workspace decides
+/// whether they are served from the fallback bundle.
+pub async fn reject_writes(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(supervisor) = &state.store_supervisor else {
+        return next.run(request).await;
+    };
+    let circuit = supervisor.state();
+    if circuit == CircuitState::Healthy || !is_write(request.method(), request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    supervisor.record_rejected_write();
+    ApiError::service_unavailable("Storage backend unavailable")
+        .with_details(format!(
+            "The {} backend is {}; writes are refused until its health checks pass again",
+            supervisor.backend(),
+            circuit.as_str()
+        ))
+        .into_response()
+}
+
+/// Whether a request changes stored data
+///
+/// Queries, index verification and configuration reloads are POSTs that only read.
+pub fn is_write(method: &Method, path: &str) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return false;
+    }
+    !(is_query(method, path) || path.ends_with("/index/verify") || path == "/api/v1/admin/reload")
+}
+
+/// Whether a request is a query, which the fallback bundle can serve
+pub fn is_query(method: &Method, path: &str) -> bool {
+    *method == Method::POST && path.ends_with("/query")
+}
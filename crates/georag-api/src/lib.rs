@@ -1,11 +1,21 @@
+pub mod auth;
 pub mod config;
 pub mod dto;
 pub mod error;
+pub mod governor;
 pub mod handlers;
+pub mod health;
+pub mod redaction;
+pub mod reload;
 pub mod router;
-pub mod services;
 pub mod state;
+pub mod workspace;
 
-pub use config::{ApiConfig, EmbedderConfig};
-pub use router::create_router;
+pub use auth::{AuthConfig, Caller};
+pub use config::{AllowedEmbedder, ApiConfig, EmbedderConfig, QueryConfig};
+pub use governor::ResourceGovernor;
+pub use health::{HealthPolicy, StoreSupervisor};
+pub use reload::LiveConfig;
+pub use router::{cors_layer, create_router};
 pub use state::AppState;
+pub use workspace::Workspace;
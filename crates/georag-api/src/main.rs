@@ -1,21 +1,38 @@
+use std::path::Path;
 use std::sync::Arc;
 
-use axum::http::{header, HeaderValue, Method};
+use georag_core::config::{mask_url_credentials, ConfigSource};
+use georag_core::redaction::Redactor;
+use georag_store::bundle::BundleStore;
+use georag_store::filesystem::FilesystemBlobStore;
 use georag_store::memory::{
-    MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore, MemoryWorkspaceStore,
+    MemoryAreaStore, MemoryAuditStore, MemoryBlobStore, MemoryDocumentStore, MemoryEventLog,
+    MemorySpatialStore, MemoryStoreProvider, MemoryVectorStore, MemoryWorkspaceStore,
+};
+use georag_store::ports::{
+    AreaStore, AuditStore, BlobStore, DocumentStore, EventLogStore, HealthProbe, SpatialStore,
+    VectorStore, WorkspaceStore,
 };
-use georag_store::ports::{DocumentStore, SpatialStore, VectorStore, WorkspaceStore};
 use georag_store::postgres::{PostgresConfig, PostgresStore};
-use tower_http::cors::CorsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use georag_api::{create_router, ApiConfig, AppState};
+use georag_api::reload::{load_redactor, watch_files, CONFIG_POLL_INTERVAL};
+use georag_api::{
+    cors_layer, create_router, ApiConfig, AppState, AuthConfig, EmbedderConfig, LiveConfig,
+    ResourceGovernor, StoreSupervisor,
+};
 
 #[tokio::main]
 async fn main() {
     init_tracing();
 
-    let config = ApiConfig::from_env();
+    let config = match ApiConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     tracing::info!(
         port = config.port,
@@ -24,23 +41,169 @@ async fn main() {
         "Starting GeoRAG API server"
     );
 
-    let (spatial_store, vector_store, document_store, workspace_store) =
-        init_storage(&config).await;
+    let bundle = match &config.bundle_file {
+        Some(path) => Some(init_bundle(path).await),
+        None => None,
+    };
+    let (backends, postgres) = match &bundle {
+        Some(bundle) => (
+            Backends {
+                spatial: bundle.clone(),
+                vector: bundle.clone(),
+                document: bundle.clone(),
+                workspace: Arc::new(MemoryWorkspaceStore::new()),
+                blob: Arc::new(MemoryBlobStore::new()),
+                area: Arc::new(MemoryAreaStore::new()),
+                audit: Arc::new(MemoryAuditStore::new()),
+                event_log: Arc::new(MemoryEventLog::with_capacity(
+                    config.event_retention.max_events,
+                )),
+            },
+            None,
+        ),
+        None => init_storage(&config).await,
+    };
+    let blob_store = match &config.blob_dir {
+        Some(dir) => {
+            tracing::info!(dir = %dir.display(), "Keeping original files on disk");
+            Arc::new(FilesystemBlobStore::new(dir)) as Arc<dyn BlobStore>
+        }
+        None => backends.blob,
+    };
+
+    let redactor = init_redactor(&config);
+    let auth = init_auth(&config);
+
+    let mut effective_config = config.inspection_map();
+    let key_names: Vec<&str> = auth.api_keys.iter().map(|k| k.name.as_str()).collect();
+    effective_config.insert(
+        "auth.key_names".to_string(),
+        (
+            if key_names.is_empty() {
+                "none".to_string()
+            } else {
+                key_names.join(", ")
+            },
+            if config.auth_file.is_some() {
+                ConfigSource::File
+            } else {
+                ConfigSource::Default
+            },
+        ),
+    );
+
+    // Runs in the background so the server can accept requests during a model pull
+    tokio::spawn(warm_up_embedder(config.embedder.clone()));
 
-    let state = Arc::new(AppState::new(
-        spatial_store,
-        vector_store,
-        document_store,
-        workspace_store,
+    let mut state = AppState::new(
+        backends.spatial,
+        backends.vector,
+        backends.document,
+        backends.workspace,
         config.embedder.clone(),
-    ));
+        config.query.clone(),
+    )
+    .with_default_workspace(config.default_workspace.clone())
+    .with_live_config(LiveConfig::new(&config, redactor, effective_config))
+    .with_auth(auth)
+    .with_chunk_properties(config.chunk_properties.clone())
+    .with_read_policy(config.read_policy)
+    .with_axis_order(config.axis_order)
+    .with_blob_store(blob_store)
+    .with_area_store(backends.area)
+    .with_audit_store(backends.audit)
+    .with_event_log(backends.event_log)
+    .with_event_retention(config.event_retention)
+    .with_source_policy(config.source_policy)
+    .with_download_policy(config.download_policy.clone())
+    .with_feature_limits(config.feature_limits)
+    .with_z_coordinates(config.z_coordinates)
+    .with_pipeline_buffers(config.pipeline_buffers);
+    // CLI runs against the same database take slots of the same limits
+    let mut governor = ResourceGovernor::new(config.resource_limits.clone());
+    if let Some(store) = &postgres {
+        governor = governor.with_shared_slots(store.clone());
+    }
+    state = state.with_governor(governor);
+    if let Some(path) = &config.config_file {
+        tracing::info!(path = %path.display(), "Configuration file loaded");
+        state = state.with_config_file(path);
+    }
+    let supervisor = match postgres {
+        Some(store) => Some(Arc::new(init_supervisor(&config, store).await)),
+        None => None,
+    };
+    if let Some(supervisor) = &supervisor {
+        state = state.with_store_supervisor(supervisor.clone());
+    }
 
-    let cors = CorsLayer::new()
-        .allow_origin(config.cors_origin.parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
+    // Only the memory backend keeps other workspaces apart from the default one
+    if config.storage_backend() == "memory" {
+        let mut provider = MemoryStoreProvider::new();
+        if let Some(dir) = &config.blob_dir {
+            // The default workspace keeps its files in the directory itself
+            provider = provider.with_blob_dir(dir);
+        }
+        state = state.with_store_provider(Arc::new(provider));
+    } else {
+        tracing::info!(
+            workspace = %config.default_workspace,
+            "Storage backend serves the default workspace only"
+        );
+    }
+    let state = Arc::new(state);
 
-    let app = create_router(state).layer(cors);
+    // Their data would be mixed up with the default workspace's
+    match state.unservable_workspaces().await {
+        Ok(others) if others.is_empty() => {}
+        Ok(others) => {
+            let names: Vec<&str> = others.iter().map(|w| w.name.as_str()).collect();
+            tracing::error!(
+                backend = %config.storage_backend(),
+                workspaces = %names.join(", "),
+                "Storage backend serves the default workspace only, and other workspaces exist"
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            tracing::error!(error = ?e, "Failed to list workspaces");
+            std::process::exit(1);
+        }
+    }
+
+    // Bundles are queried with the index they were exported from
+    if let Some(index_state) = bundle.as_ref().and_then(|b| b.index_state()) {
+        state.set_index_state(index_state.clone()).await;
+    }
+
+    // Garbage scores follow from dimensions that disagree, so check before serving
+    let index_state = state.get_index_state().await.ok();
+    match config.embedder.reconcile(index_state.as_ref()).await {
+        Ok(check) => tracing::info!(
+            model = %check.model,
+            configured = check.configured,
+            actual = ?check.actual,
+            index = ?check.index,
+            "Embedding dimensions checked"
+        ),
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(supervisor) = supervisor {
+        tokio::spawn(supervisor.run());
+    }
+
+    // CORS origins, redaction rules and quotas can be reloaded without a restart
+    if state.config_file.is_some() || config.redaction_file.is_some() {
+        tokio::spawn(watch_files(state.clone(), CONFIG_POLL_INTERVAL));
+    }
+    #[cfg(unix)]
+    tokio::spawn(georag_api::reload::reload_on_hangup(state.clone()));
+
+    let app = create_router(state.clone()).layer(cors_layer(&state));
 
     let addr = config.bind_address();
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
@@ -61,24 +224,154 @@ fn init_tracing() {
         .init();
 }
 
-async fn init_storage(
-    config: &ApiConfig,
-) -> (
-    Arc<dyn SpatialStore>,
-    Arc<dyn VectorStore>,
-    Arc<dyn DocumentStore>,
-    Arc<dyn WorkspaceStore>,
-) {
+/// Check the embedding model is available in Ollama (pulling it if auto-pull is on)
+///
+/// Failures are logged rather than fatal so the server can start before Ollama.
+async fn warm_up_embedder(config: EmbedderConfig) {
+    let embedder = match config.create(&config.configured()) {
+        Ok(embedder) => embedder,
+        Err(e) => {
+            tracing::warn!(model = %config.model, "Embedder configuration invalid: {}", e);
+            return;
+        }
+    };
+
+    match embedder.ensure_ready().await {
+        Ok(()) => tracing::info!(model = %config.model, "Embedding model available"),
+        Err(e) => tracing::warn!(model = %config.model, "Embedding model check failed: {}", e),
+    }
+}
+
+fn init_redactor(config: &ApiConfig) -> Redactor {
+    match load_redactor(config.redaction_file.as_deref()) {
+        Ok(redactor) => {
+            if !redactor.is_empty() {
+                tracing::info!(
+                    properties = redactor.rules().properties.len(),
+                    patterns = redactor.rules().patterns.len(),
+                    "Redaction rules loaded"
+                );
+            }
+            redactor
+        }
+        Err(e) => {
+            tracing::error!("Failed to load redaction rules: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn init_auth(config: &ApiConfig) -> AuthConfig {
+    let Some(path) = &config.auth_file else {
+        tracing::warn!("No GEORAG_AUTH_FILE set; API keys are not required");
+        return AuthConfig::default();
+    };
+
+    match AuthConfig::load_from_file(path) {
+        Ok(auth) => {
+            let restricted =
+                auth.api_keys.iter().filter(|k| k.visibility().is_restricted()).count();
+            tracing::info!(keys = auth.api_keys.len(), restricted, "API keys loaded");
+            auth
+        }
+        Err(e) => {
+            tracing::error!("Failed to load API keys from {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn init_bundle(path: &Path) -> Arc<BundleStore> {
+    match BundleStore::open(path).await {
+        Ok(bundle) => {
+            let index = bundle.index_load();
+            tracing::info!(
+                path = %path.display(),
+                bbox = ?bundle.bbox(),
+                spatial_index = index.origin.as_str(),
+                indexed_features = index.features,
+                index_ms = index.elapsed.as_millis() as u64,
+                "Serving read-only from offline bundle"
+            );
+            Arc::new(bundle)
+        }
+        Err(e) => {
+            tracing::error!("Failed to open bundle {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Stores backing the server, one per port `AppState` takes
+struct Backends {
+    spatial: Arc<dyn SpatialStore>,
+    vector: Arc<dyn VectorStore>,
+    document: Arc<dyn DocumentStore>,
+    workspace: Arc<dyn WorkspaceStore>,
+    /// Original files, unless `GEORAG_BLOB_DIR` keeps them on disk
+    blob: Arc<dyn BlobStore>,
+    area: Arc<dyn AreaStore>,
+    audit: Arc<dyn AuditStore>,
+    event_log: Arc<dyn EventLogStore>,
+}
+
+/// Health checks of the storage backend, with the fallback bundle if one is configured
+///
+/// Ended outages are recorded in `audit.log` beside the configuration file.
+async fn init_supervisor(config: &ApiConfig, probe: Arc<dyn HealthProbe>) -> StoreSupervisor {
+    let mut supervisor = StoreSupervisor::new(probe, config.health);
+    if let Some(path) = &config.config_file {
+        supervisor = supervisor.with_audit_log(path.with_file_name("audit.log"));
+    }
+    if let Some(path) = &config.fallback_bundle {
+        let bundle = init_bundle(path).await;
+        match bundle.index_state() {
+            Some(index) => tracing::info!(
+                path = %path.display(),
+                index_hash = %index.hash,
+                "Fallback bundle serves queries while the storage backend is down"
+            ),
+            None => tracing::warn!(
+                path = %path.display(),
+                "Fallback bundle has no index state and will never be used"
+            ),
+        }
+        supervisor = supervisor.with_fallback(bundle);
+    }
+    supervisor
+}
+
+/// Stores of the configured backend, and the PostgreSQL store when it is one
+async fn init_storage(config: &ApiConfig) -> (Backends, Option<Arc<PostgresStore>>) {
     match &config.database_url {
         Some(database_url) => {
-            tracing::info!("DATABASE_URL found, connecting to PostgreSQL...");
+            tracing::info!(
+                database_url = %mask_url_credentials(database_url),
+                "DATABASE_URL found, connecting to PostgreSQL..."
+            );
             match init_postgres_storage(database_url).await {
                 Ok(store) => {
                     tracing::info!("Connected to PostgreSQL");
-                    (store.clone(), store.clone(), store.clone(), store)
+                    (
+                        Backends {
+                            spatial: store.clone(),
+                            vector: store.clone(),
+                            document: store.clone(),
+                            workspace: store.clone(),
+                            blob: store.clone(),
+                            area: store.clone(),
+                            audit: store.clone(),
+                            event_log: store.clone(),
+                        },
+                        Some(store),
+                    )
                 }
                 Err(e) => {
-                    tracing::error!("Failed to connect to PostgreSQL: {}", e);
+                    // Driver errors can quote the connection string
+                    tracing::error!(
+                        "Failed to connect to PostgreSQL: {}",
+                        mask_url_credentials(&e)
+                    );
                     tracing::error!(
                         "Remediation:\n\
                         1. Ensure PostgreSQL is running\n\
@@ -92,10 +385,19 @@ async fn init_storage(
         None => {
             tracing::info!("Using in-memory storage (set DATABASE_URL for PostgreSQL)");
             (
-                Arc::new(MemorySpatialStore::new()),
-                Arc::new(MemoryVectorStore::new()),
-                Arc::new(MemoryDocumentStore::new()),
-                Arc::new(MemoryWorkspaceStore::new()),
+                Backends {
+                    spatial: Arc::new(MemorySpatialStore::new()),
+                    vector: Arc::new(MemoryVectorStore::new()),
+                    document: Arc::new(MemoryDocumentStore::new()),
+                    workspace: Arc::new(MemoryWorkspaceStore::new()),
+                    blob: Arc::new(MemoryBlobStore::new()),
+                    area: Arc::new(MemoryAreaStore::new()),
+                    audit: Arc::new(MemoryAuditStore::new()),
+                    event_log: Arc::new(MemoryEventLog::with_capacity(
+                        config.event_retention.max_events,
+                    )),
+                },
+                None,
             )
         }
     }
@@ -0,0 +1,89 @@
+//! Redaction of API responses
+//!
+//! Every JSON response of the API routes passes through [`redact_responses`]
+//! on its way out, so handlers return stored values as they are. Responses
+//! of a workspace's routes are masked with that workspace's rules: its
+//! `redaction` setting, else the server's rules (`GEORAG_REDACTION_FILE`).
+//! Routes that do not select a workspace, such as the event log, may carry
+//! the data of any workspace and are masked with the rules of all of them.
+//!
+//! Downloads of original files are attachments and are passed through; the
+//! CSV and PNG query renderings are masked by the query service.
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::workspace::Workspace;
+
+/// Mask redacted properties and pattern matches in a JSON response
+///
+/// Runs inside `workspace::select_workspace` on scoped routes, whose
+/// workspace it reads from the request's extensions.
+pub async fn redact_responses(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let scoped = request.extensions().get::<Workspace>().map(|w| w.state.clone());
+    let response = next.run(request).await;
+    if !is_json(response.headers()) {
+        return response;
+    }
+
+    let redactor = match scoped {
+        Some(scoped) => scoped.redactor(),
+        None => match state.all_workspaces_redactor().await {
+            Ok(redactor) => redactor,
+            Err(e) => return e.into_response(),
+        },
+    };
+    if redactor.is_empty() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let mut value = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => match serde_json::from_slice::<Value>(&bytes) {
+            Ok(value) => value,
+            Err(e) => {
+                return ApiError::internal("Failed to redact response")
+                    .with_details(e.to_string())
+                    .into_response()
+            }
+        },
+        Err(e) => {
+            return ApiError::internal("Failed to redact response")
+                .with_details(e.to_string())
+                .into_response()
+        }
+    };
+    redactor.redact_response(&mut value);
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(value.to_string()))
+}
+
+/// Whether a response carries a JSON body that is not a file download
+fn is_json(headers: &HeaderMap) -> bool {
+    let attachment = headers
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim_start().starts_with("attachment"));
+    let json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| mime == "application/json" || mime.ends_with("+json"));
+    json && !attachment
+}
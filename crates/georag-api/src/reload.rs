@@ -0,0 +1,316 @@
+//! Live reload of the API configuration
+//!
+//! Part of the configuration can change without a restart: the CORS
+//! origins, the redaction rules and the default workspace quotas. A reload
+//! loads the configuration again (environment, `GEORAG_CONFIG_FILE` and the
+//! redaction rules file), validates it and swaps the reloadable values in at
+//! once; requests in flight keep the values they started with. Changes to
+//! other values, such as the storage backend or the embedder's dimensions,
+//! are refused and logged with the reason.
+//!
+//! Reloads are triggered by `POST /api/v1/admin/reload`, by `SIGHUP` and by
+//! changes to the configuration or redaction file. Each outcome is logged,
+//! appended to `audit.log` next to the configuration file and listed by
+//! `GET /api/v1/admin/config`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use axum::http::HeaderValue;
+use chrono::{DateTime, Utc};
+use georag_core::config::ConfigSource;
+use georag_core::error::Result;
+use georag_core::models::WorkspaceQuotas;
+use georag_core::redaction::{record_rules_change, RedactionConfig, Redactor};
+use serde::Serialize;
+
+use crate::config::ApiConfig;
+use crate::state::AppState;
+
+/// How often the configuration and redaction files are checked for changes
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reload outcomes kept for the admin config endpoint
+const RELOAD_HISTORY: usize = 20;
+
+/// Audit log event of a reload
+const CONFIG_RELOADED_EVENT: &str = "config_reloaded";
+
+/// Masked configuration values and their sources, keyed like `ApiConfig::inspection_map`
+pub type EffectiveConfig = BTreeMap<String, (String, ConfigSource)>;
+
+/// Configuration values a reload can replace
+#[derive(Debug, Clone, Default)]
+pub struct LiveConfig {
+    /// Origins allowed by CORS, comma-separated
+    pub cors_origin: String,
+    pub redactor: Redactor,
+    /// File the redaction rules were read from
+    pub redaction_file: Option<PathBuf>,
+    /// Quotas for workspaces whose settings leave them unset
+    pub quotas: WorkspaceQuotas,
+    /// Masked effective configuration served by the admin config endpoint
+    pub effective_config: EffectiveConfig,
+}
+
+impl LiveConfig {
+    /// Reloadable values of a configuration
+    pub fn new(config: &ApiConfig, redactor: Redactor, effective_config: EffectiveConfig) -> Self {
+        Self {
+            cors_origin: config.cors_origin.clone(),
+            redactor,
+            redaction_file: config.redaction_file.clone(),
+            quotas: config.quotas,
+            effective_config,
+        }
+    }
+
+    /// Whether CORS allows requests from an origin
+    pub fn allows_origin(&self, origin: &HeaderValue) -> bool {
+        self.cors_origin
+            .split(',')
+            .map(str::trim)
+            .any(|allowed| !allowed.is_empty() && allowed.as_bytes() == origin.as_bytes())
+    }
+}
+
+/// What started a reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadTrigger {
+    /// `POST /api/v1/admin/reload`
+    Request,
+    /// `SIGHUP`
+    Signal,
+    /// The configuration or redaction file changed
+    FileChange,
+}
+
+/// A changed value a reload left as it was
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RefusedChange {
+    pub key: String,
+    /// Why the change needs a restart
+    pub reason: &'static str,
+}
+
+/// Result of one configuration reload
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadOutcome {
+    pub reloaded_at: DateTime<Utc>,
+    pub trigger: ReloadTrigger,
+    /// Keys whose new values are in effect
+    pub applied: Vec<String>,
+    /// Changed keys whose new values need a restart
+    pub refused: Vec<RefusedChange>,
+    /// Why the configuration could not be loaded; nothing was applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The latest reload outcomes, oldest first
+#[derive(Debug, Default)]
+pub struct ReloadHistory {
+    outcomes: VecDeque<ReloadOutcome>,
+}
+
+impl ReloadHistory {
+    /// Outcomes kept so far, oldest first
+    pub fn outcomes(&self) -> impl Iterator<Item = &ReloadOutcome> {
+        self.outcomes.iter()
+    }
+
+    /// When the configuration was last loaded without an error
+    pub fn last_reloaded(&self) -> Option<DateTime<Utc>> {
+        self.outcomes.iter().rev().find(|o| o.error.is_none()).map(|o| o.reloaded_at)
+    }
+
+    fn push(&mut self, outcome: ReloadOutcome) {
+        if self.outcomes.len() == RELOAD_HISTORY {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(outcome);
+    }
+}
+
+/// Whether a reload can change the value of a configuration key
+pub fn is_reloadable(key: &str) -> bool {
+    matches!(key, "server.cors_origin" | "redaction.file") || key.starts_with("quota.")
+}
+
+/// Why a changed value needs a restart
+fn restart_reason(key: &str) -> &'static str {
+    if key == "server.port" {
+        return "the listener is bound at startup";
+    }
+    match key.split_once('.').map_or(key, |(section, _)| section) {
+        "storage" => "the storage backend is opened at startup",
+        "embedder" => {
+            "indexes are built and queried with the embedder and dimensions set at startup"
+        }
+        "auth" => "API keys are loaded at startup",
+        "health" => "the storage backend's health checks start with the server",
+        _ => "the value is read at startup",
+    }
+}
+
+/// Compile the redaction rules of a file, recording changed rules in `audit.log` beside it
+///
+/// Without a file nothing is redacted.
+pub fn load_redactor(path: Option<&Path>) -> Result<Redactor> {
+    let Some(path) = path else {
+        return Ok(Redactor::default());
+    };
+
+    let rules = RedactionConfig::load_from_file(path)?;
+    let redactor = Redactor::new(&rules)?;
+
+    let audit_log = path.with_file_name("audit.log");
+    if let Err(e) = record_rules_change(&audit_log, &rules) {
+        tracing::warn!("Failed to write redaction audit entry: {}", e);
+    }
+    Ok(redactor)
+}
+
+/// Load the configuration again and apply its reloadable values
+///
+/// Reloads run one at a time. Nothing is applied when the configuration
+/// fails to load or validate; changes to values that are not reloadable are
+/// refused while the others are applied.
+pub async fn reload(state: &AppState, trigger: ReloadTrigger) -> ReloadOutcome {
+    let mut history = state.reload_history.lock().await;
+
+    let mut outcome = ReloadOutcome {
+        reloaded_at: Utc::now(),
+        trigger,
+        applied: Vec::new(),
+        refused: Vec::new(),
+        error: None,
+    };
+    match load_changes(state, &mut outcome) {
+        Ok(live) => {
+            if !outcome.applied.is_empty() {
+                state.set_live_config(live);
+            }
+            for change in &outcome.refused {
+                tracing::warn!(
+                    key = %change.key,
+                    "Configuration change needs a restart: {}",
+                    change.reason
+                );
+            }
+            tracing::info!(
+                trigger = ?trigger,
+                applied = ?outcome.applied,
+                refused = outcome.refused.len(),
+                "Configuration reloaded"
+            );
+        }
+        Err(e) => {
+            tracing::error!(
+                trigger = ?trigger,
+                "Configuration reload failed, keeping the running configuration: {}",
+                e
+            );
+            outcome.error = Some(e.to_string());
+        }
+    }
+
+    if let Some(path) = &state.config_file {
+        if let Err(e) = record_reload(&path.with_file_name("audit.log"), &outcome) {
+            tracing::warn!("Failed to write configuration audit entry: {}", e);
+        }
+    }
+    history.push(outcome.clone());
+    outcome
+}
+
+/// Load the configuration and compare it with the live one
+///
+/// Returns the live configuration with the reloadable changes applied,
+/// listing them and the refused ones in `outcome`.
+fn load_changes(state: &AppState, outcome: &mut ReloadOutcome) -> Result<LiveConfig> {
+    let config = ApiConfig::load_from(state.config_file.as_deref())?;
+    let redactor = load_redactor(config.redaction_file.as_deref())?;
+    let current = state.live_config();
+
+    let mut effective_config = current.effective_config.clone();
+    for (key, (value, source)) in config.inspection_map() {
+        if current.effective_config.get(&key).map(|(value, _)| value) == Some(&value) {
+            continue;
+        }
+        if is_reloadable(&key) {
+            effective_config.insert(key.clone(), (value, source));
+            outcome.applied.push(key);
+        } else {
+            outcome.refused.push(RefusedChange { reason: restart_reason(&key), key });
+        }
+    }
+    if redactor.rules() != current.redactor.rules() {
+        outcome.applied.push("redaction.rules".to_string());
+    }
+
+    Ok(LiveConfig {
+        cors_origin: config.cors_origin,
+        redactor,
+        redaction_file: config.redaction_file,
+        quotas: config.quotas,
+        effective_config,
+    })
+}
+
+/// Append a reload outcome to a JSON Lines audit log
+fn record_reload(audit_log: &Path, outcome: &ReloadOutcome) -> std::io::Result<()> {
+    let mut entry = serde_json::to_value(outcome)?;
+    entry["event"] = CONFIG_RELOADED_EVENT.into();
+
+    let mut file = OpenOptions::new().create(true).append(true).open(audit_log)?;
+    writeln!(file, "{}", entry)
+}
+
+/// Reload whenever the configuration file or the redaction file changes
+///
+/// The files' modification times are checked every `interval`.
+pub async fn watch_files(state: Arc<AppState>, interval: Duration) {
+    let mut seen = modified_times(&state);
+    let mut ticks = tokio::time::interval(interval);
+    // The first tick completes at once
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        if modified_times(&state) != seen {
+            reload(&state, ReloadTrigger::FileChange).await;
+            // A reload can name another redaction file
+            seen = modified_times(&state);
+        }
+    }
+}
+
+fn modified_times(state: &AppState) -> [Option<SystemTime>; 2] {
+    let modified = |path: Option<&Path>| path.and_then(|p| p.metadata().ok()?.modified().ok());
+    [
+        modified(state.config_file.as_deref()),
+        modified(state.live_config().redaction_file.as_deref()),
+    ]
+}
+
+/// Reload on every `SIGHUP`
+#[cfg(unix)]
+pub async fn reload_on_hangup(state: Arc<AppState>) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!("Cannot reload the configuration on SIGHUP: {}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        reload(&state, ReloadTrigger::Signal).await;
+    }
+}
@@ -1,38 +1,145 @@
 use std::sync::Arc;
 
 use axum::{
-    routing::{delete, get, post},
+    extract::DefaultBodyLimit,
+    http::{header, HeaderName, Method},
+    middleware,
+    routing::{delete, get, post, put},
     Router,
 };
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 
+use crate::auth;
 use crate::handlers;
+use crate::health;
+use crate::redaction;
 use crate::state::AppState;
+use crate::workspace::{self, WORKSPACE_HEADER};
 
 /// Create the API router with all routes
 pub fn create_router(state: Arc<AppState>) -> Router {
-    Router::new()
-        // Health
-        .route("/health", get(handlers::health_check))
+    let query_body_limit = DefaultBodyLimit::max(state.query_config.max_body_bytes);
 
+    // Routes operating on one workspace: the one in the path, else the one
+    // named by X-Georag-Workspace, else the default workspace
+    let scoped = Router::new()
         // Workspaces
-        .route("/api/v1/workspaces", post(handlers::create_workspace))
-        .route("/api/v1/workspaces", get(handlers::list_workspaces))
-        .route("/api/v1/workspaces/:workspace_id", delete(handlers::delete_workspace))
+        .route("/api/v1/workspaces/{workspace_id}", delete(handlers::delete_workspace).patch(handlers::update_workspace))
+        .route("/api/v1/workspaces/{workspace_id}/settings", get(handlers::get_workspace_settings).put(handlers::put_workspace_settings))
+        .route("/api/v1/workspaces/{workspace_id}/usage", get(handlers::get_workspace_usage))
+        .route("/api/v1/workspaces/{workspace_id}/timeline", get(handlers::get_workspace_timeline))
+        .route("/api/v1/workspaces/{workspace_id}/clone", post(handlers::clone_workspace))
+
+        // Query and ingest
+        .route("/api/v1/workspaces/{workspace_id}/query", post(handlers::handle_query).layer(query_body_limit))
+        .route("/api/v1/workspaces/{workspace_id}/query/next", post(handlers::handle_query_next))
+        .route("/api/v1/workspaces/{workspace_id}/query/by-feature", post(handlers::handle_query_by_feature).layer(query_body_limit))
+        .route("/api/v1/workspaces/{workspace_id}/ingest", post(handlers::handle_ingest))
 
-        // Datasets (workspace-scoped)
-        .route("/api/v1/workspaces/:workspace_id/datasets", get(handlers::list_datasets_for_workspace))
-        .route("/api/v1/workspaces/:workspace_id/datasets/:dataset_id", delete(handlers::delete_dataset))
+        // Datasets
+        .route("/api/v1/workspaces/{workspace_id}/datasets", get(handlers::list_datasets_for_workspace))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/bulk-delete", post(handlers::bulk_delete_datasets))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/{dataset_id}", delete(handlers::delete_dataset).patch(handlers::update_dataset_tags))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/{dataset_id}/sample", get(handlers::sample_dataset))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/{dataset_id}/source", get(handlers::download_dataset_source))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/{dataset_id}/schema", get(handlers::get_dataset_schema))
+        .route("/api/v1/workspaces/{workspace_id}/datasets/{dataset_id}/features/{feature_id}", get(handlers::get_dataset_feature))
 
-        // Index (workspace-scoped)
-        .route("/api/v1/workspaces/:workspace_id/index/rebuild", post(handlers::rebuild_index))
-        .route("/api/v1/workspaces/:workspace_id/index/status", get(handlers::get_workspace_index_status))
+        // Index
+        .route("/api/v1/workspaces/{workspace_id}/index/rebuild", post(handlers::rebuild_index))
+        .route("/api/v1/workspaces/{workspace_id}/index/status", get(handlers::get_workspace_index_status))
+        .route("/api/v1/workspaces/{workspace_id}/index/integrity", get(handlers::get_index_integrity))
+        .route("/api/v1/workspaces/{workspace_id}/index/verify", post(handlers::verify_index))
+
+        // Saved areas
+        .route("/api/v1/workspaces/{workspace_id}/areas", post(handlers::create_area).get(handlers::list_areas))
+        .route("/api/v1/workspaces/{workspace_id}/areas/{name}", get(handlers::get_area).delete(handlers::delete_area))
+        .route("/api/v1/workspaces/{workspace_id}/presets", get(handlers::list_presets))
+        .route("/api/v1/workspaces/{workspace_id}/presets/{name}", get(handlers::get_preset).put(handlers::put_preset).delete(handlers::delete_preset))
+        .route("/api/v1/workspaces/{workspace_id}/presets/{name}/default", put(handlers::set_default_preset).delete(handlers::clear_default_preset))
+        .route("/api/v1/areas", post(handlers::create_area).get(handlers::list_areas))
+        .route("/api/v1/areas/{name}", get(handlers::get_area).delete(handlers::delete_area))
+        .route("/api/v1/presets", get(handlers::list_presets))
+        .route("/api/v1/presets/{name}", get(handlers::get_preset).put(handlers::put_preset).delete(handlers::delete_preset))
+        .route("/api/v1/presets/{name}/default", put(handlers::set_default_preset).delete(handlers::clear_default_preset))
 
         // Legacy routes (backward compatibility)
-        .route("/api/v1/query", post(handlers::handle_query))
+        .route("/api/v1/query", post(handlers::handle_query).layer(query_body_limit))
+        .route("/api/v1/query/next", post(handlers::handle_query_next))
+        .route("/api/v1/query/by-feature", post(handlers::handle_query_by_feature).layer(query_body_limit))
         .route("/api/v1/datasets", get(handlers::list_datasets))
+        .route("/api/v1/datasets/bulk-delete", post(handlers::bulk_delete_datasets))
+        .route("/api/v1/datasets/{dataset_id}/sample", get(handlers::sample_dataset))
+        .route("/api/v1/datasets/{dataset_id}/source", get(handlers::download_dataset_source))
+        .route("/api/v1/datasets/{dataset_id}/schema", get(handlers::get_dataset_schema))
+        .route("/api/v1/datasets/{dataset_id}/features/{feature_id}", get(handlers::get_dataset_feature))
         .route("/api/v1/ingest", post(handlers::handle_ingest))
         .route("/api/v1/index/integrity", get(handlers::get_index_integrity))
         .route("/api/v1/index/verify", post(handlers::verify_index))
 
+        // Responses are masked with the selected workspace's redaction rules
+        .route_layer(middleware::from_fn_with_state(state.clone(), redaction::redact_responses))
+        .route_layer(middleware::from_fn_with_state(state.clone(), workspace::select_workspace));
+
+    let api = Router::new()
+        // Workspaces
+        .route("/api/v1/workspaces", post(handlers::create_workspace))
+        .route("/api/v1/workspaces", get(handlers::list_workspaces))
+
+        // Admin
+        .route("/api/v1/admin/config", get(handlers::get_config))
+        .route("/api/v1/admin/reload", post(handlers::reload_config))
+        .route("/api/v1/admin/compact", post(handlers::compact))
+
+        // Change events
+        .route("/api/v1/events", get(handlers::list_events))
+        .route("/api/v1/events/cursors/{consumer}", get(handlers::get_event_cursor).put(handlers::put_event_cursor))
+
+        .route("/api/v1/formats", get(handlers::list_formats))
+
+        // Responses are masked with the redaction rules of every workspace
+        .route_layer(middleware::from_fn_with_state(state.clone(), redaction::redact_responses))
+        .merge(scoped)
+
+        // Writes wait for an unhealthy storage backend to recover
+        .route_layer(middleware::from_fn_with_state(state.clone(), health::reject_writes))
+
+        // Every API route resolves the caller's key and dataset visibility
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::authenticate));
+
+    Router::new()
+        // Health
+        .route("/health", get(handlers::health_check))
+        .route("/metrics", get(handlers::metrics))
+        .merge(api)
+        // gzip or brotli, whichever the client accepts; others get identity
+        .layer(CompressionLayer::new().gzip(true).br(true))
         .with_state(state)
 }
+
+/// CORS layer allowing the origins of the live configuration
+///
+/// Origins are checked on every request, so reloads take effect at once.
+pub fn cors_layer(state: &Arc<AppState>) -> CorsLayer {
+    let state = state.clone();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            state.live_config().allows_origin(origin)
+        }))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-api-key"),
+            HeaderName::from_static(WORKSPACE_HEADER),
+        ])
+        .expose_headers([handlers::NEXT_CURSOR_HEADER])
+}
@@ -1,5 +0,0 @@
-mod ingest;
-mod query;
-
-pub use ingest::IngestService;
-pub use query::QueryService;
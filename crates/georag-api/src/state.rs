@@ -1,20 +1,63 @@
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use georag_core::config::{
+    parse_confidence_cutoffs, parse_source_url_template, parse_spatial_predicate, ConfigSource,
+    WorkspaceSettings,
+};
 use georag_core::error::GeoragError;
-use georag_core::models::{IndexState, WorkspaceId};
-use georag_store::ports::{DocumentStore, SpatialStore, VectorStore, WorkspaceStore};
-use tokio::sync::RwLock;
+use georag_core::formats::{
+    FeatureTransform, FormatRegistry, IngestBuffers, ReadPolicy, DEFAULT_MAX_FEATURE_ERRORS,
+};
+use georag_core::geo::{FeatureLimits, FilterCache, PointQueryDefaults, ZCoordinates};
+use georag_core::models::{
+    AuditEvent, AuditEventKind, AxisOrder, ConfidenceCutoffs, DatasetId, DatasetMeta, DistanceUnit,
+    EventRetention, IndexState, PreviousIndex, SourceUrlTemplate, UsageDelta, ValidityMode,
+    WorkspaceConfig, WorkspaceId, WorkspaceMeta, WorkspaceQuotas, ARCHIVED_TAG,
+};
+use georag_core::redaction::Redactor;
+use georag_retrieval::{Basemap, Reranker};
+use georag_service::{
+    index_generation, AreaService, AuditService, BulkDatasetService, CompactionService,
+    DownloadPolicy, EventLogService, IndexStatsService, IngestService, QueryPages, QueryService,
+    SourcePolicy, WorkspaceQuota, WorkspaceService,
+};
+use georag_store::feature_cache::{CachedSpatialStore, FeatureCache};
+use georag_store::memory::{MemoryAreaStore, MemoryAuditStore, MemoryBlobStore, MemoryEventLog};
+use georag_store::ports::{
+    AreaStore, AuditStore, BlobStore, DocumentStore, EventLogStore, SpatialStore, VectorStore,
+    WorkspaceStore, WorkspaceStoreProvider, WorkspaceStores,
+};
+use tokio::sync::{Mutex, Notify, RwLock};
 
-use crate::config::EmbedderConfig;
+use crate::auth::AuthConfig;
+use crate::config::{EmbedderConfig, QueryConfig};
 use crate::error::ApiError;
+use crate::governor::ResourceGovernor;
+use crate::health::StoreSupervisor;
+use crate::reload::{EffectiveConfig, LiveConfig, ReloadHistory};
+
+/// How long workspace settings read from the store are reused
+const SETTINGS_TTL: Duration = Duration::from_secs(10);
+
+/// Workspace served by the unscoped routes unless another is configured
+///
+/// The CLI stores its datasets and settings under this workspace.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Workspace settings read from the store and when they were read
+type CachedSettings = (Instant, Option<WorkspaceSettings>);
 
 /// Rebuild status for a workspace
 #[derive(Debug, Clone)]
 pub enum RebuildStatus {
     InProgress,
+    /// Waiting for the storage backend to recover
+    Paused,
     Completed,
     Failed(String),
 }
@@ -26,10 +69,78 @@ pub struct AppState {
     pub vector_store: Arc<dyn VectorStore>,
     pub document_store: Arc<dyn DocumentStore>,
     pub workspace_store: Arc<dyn WorkspaceStore>,
+    /// Original files of ingested datasets
+    pub blob_store: Arc<dyn BlobStore>,
+    /// Named geometries reusable as query filters
+    pub area_store: Arc<dyn AreaStore>,
+    /// What happened in each workspace, for activity timelines
+    pub audit_store: Arc<dyn AuditStore>,
+    /// Ordered changes of all workspaces, read by downstream consumers
+    pub event_log: Arc<dyn EventLogStore>,
+    /// How much of the event log is kept
+    pub event_retention: EventRetention,
+    /// Wakes reads waiting for new events when one is appended
+    event_appended: Arc<Notify>,
     pub embedder_config: EmbedderConfig,
+    pub query_config: QueryConfig,
+    /// Rates candidates for queries asking for `llm` reranking
+    pub llm_reranker: Arc<dyn Reranker>,
+    pub format_registry: Arc<FormatRegistry>,
+    pub auth: Arc<AuthConfig>,
+    /// Feature properties copied into chunk metadata on rebuild
+    pub chunk_properties: Vec<String>,
+    /// How uploads with unreadable features are handled
+    pub read_policy: ReadPolicy,
+    /// Axis order assumed for uploads that do not set one
+    pub axis_order: AxisOrder,
+    /// Which original upload files are kept in the blob store
+    pub source_policy: SourcePolicy,
+    /// Limits of datasets ingested from URLs; a size limit of 0 refuses URLs
+    pub download_policy: DownloadPolicy,
+    /// Vertex limit for uploaded features and the handling of larger ones
+    pub feature_limits: FeatureLimits,
+    /// Whether the Z ordinates of uploaded features are kept
+    pub z_coordinates: ZCoordinates,
+    /// Batch size and channel capacity of the ingest pipeline
+    pub pipeline_buffers: IngestBuffers,
+    /// TOML file read again by configuration reloads
+    pub config_file: Option<PathBuf>,
+    /// Outcomes of configuration reloads, locked while one runs
+    pub reload_history: Arc<Mutex<ReloadHistory>>,
+    /// Held by index rebuilds and compaction so they never overlap
+    pub build_lock: Arc<Mutex<()>>,
+    /// Caps concurrent ingests, builds, embedding batches and large exports
+    pub governor: Arc<ResourceGovernor>,
+    /// Prepared filter geometries shared by queries, so hot areas are prepared once
+    pub filter_cache: Arc<FilterCache>,
+    /// Features of the default workspace's spatial store, so hot features are read once
+    pub feature_cache: Arc<FeatureCache>,
+    /// Ranked results of paged queries, kept for their next pages
+    pub query_pages: Arc<QueryPages>,
+    /// Name of the workspace served by routes that do not select one
+    pub default_workspace: String,
+    /// Stores of the workspaces other than the default one
+    pub store_provider: Option<Arc<dyn WorkspaceStoreProvider>>,
+    /// Health checks of the storage backend, when it can become unreachable
+    pub store_supervisor: Option<Arc<StoreSupervisor>>,
+    /// Workspace this state was scoped to by `for_workspace`
+    workspace: Option<WorkspaceId>,
+    /// Whether the stores above hold that workspace's data alone
+    own_stores: bool,
+    /// Whether the stores above are the fallback bundle's
+    serving_fallback: bool,
+    /// The default workspace as last resolved, for queries served by the fallback
+    known_default: Arc<std::sync::RwLock<Option<WorkspaceMeta>>>,
     index_state: Arc<RwLock<Option<IndexState>>>,
     workspace_index_states: Arc<RwLock<HashMap<WorkspaceId, IndexState>>>,
     rebuild_status: Arc<RwLock<HashMap<WorkspaceId, RebuildStatus>>>,
+    settings_cache: Arc<RwLock<HashMap<WorkspaceId, CachedSettings>>>,
+    /// Redaction rules of the workspace this state was scoped to
+    redactor: Option<Redactor>,
+    /// Compiled `redaction` settings of workspaces, reused while unchanged
+    redactors: Arc<RwLock<HashMap<WorkspaceId, Redactor>>>,
+    /// Values a configuration reload replaces, swapped as a whole
+    live: Arc<std::sync::RwLock<Arc<LiveConfig>>>,
 }
 
 impl AppState {
@@ -39,19 +150,298 @@ impl AppState {
         document_store: Arc<dyn DocumentStore>,
         workspace_store: Arc<dyn WorkspaceStore>,
         embedder_config: EmbedderConfig,
+        query_config: QueryConfig,
     ) -> Self {
+        let llm_reranker = Arc::new(query_config.llm_reranker(&embedder_config));
+        let query_pages =
+            QueryPages::new(query_config.page_ttl).with_max_page_size(query_config.max_page_size);
+        let feature_cache = Arc::new(FeatureCache::default());
         Self {
-            spatial_store,
+            spatial_store: Arc::new(CachedSpatialStore::new(spatial_store, feature_cache.clone())),
             vector_store,
             document_store,
             workspace_store,
+            blob_store: Arc::new(MemoryBlobStore::new()),
+            area_store: Arc::new(MemoryAreaStore::new()),
+            audit_store: Arc::new(MemoryAuditStore::new()),
+            event_log: Arc::new(MemoryEventLog::new()),
+            event_retention: EventRetention::default(),
+            event_appended: Arc::new(Notify::new()),
             embedder_config,
+            query_config,
+            llm_reranker,
+            format_registry: Arc::new(FormatRegistry::with_defaults()),
+            auth: Arc::new(AuthConfig::default()),
+            chunk_properties: Vec::new(),
+            read_policy: ReadPolicy::lenient(DEFAULT_MAX_FEATURE_ERRORS),
+            axis_order: AxisOrder::default(),
+            source_policy: SourcePolicy::default(),
+            download_policy: DownloadPolicy::default().with_allowed_hosts(Some(Vec::new())),
+            feature_limits: FeatureLimits::default(),
+            z_coordinates: ZCoordinates::default(),
+            pipeline_buffers: IngestBuffers::default(),
+            config_file: None,
+            reload_history: Arc::new(Mutex::new(ReloadHistory::default())),
+            build_lock: Arc::new(Mutex::new(())),
+            governor: Arc::new(ResourceGovernor::default()),
+            filter_cache: Arc::new(FilterCache::default()),
+            feature_cache,
+            query_pages: Arc::new(query_pages),
+            default_workspace: DEFAULT_WORKSPACE.to_string(),
+            store_provider: None,
+            store_supervisor: None,
+            workspace: None,
+            own_stores: false,
+            serving_fallback: false,
+            known_default: Arc::new(std::sync::RwLock::new(None)),
             index_state: Arc::new(RwLock::new(None)),
             workspace_index_states: Arc::new(RwLock::new(HashMap::new())),
             rebuild_status: Arc::new(RwLock::new(HashMap::new())),
+            settings_cache: Arc::new(RwLock::new(HashMap::new())),
+            redactor: None,
+            redactors: Arc::new(RwLock::new(HashMap::new())),
+            live: Arc::new(std::sync::RwLock::new(Arc::new(LiveConfig::default()))),
         }
     }
 
+    /// Set the values a configuration reload replaces
+    pub fn with_live_config(mut self, live: LiveConfig) -> Self {
+        self.live = Arc::new(std::sync::RwLock::new(Arc::new(live)));
+        self
+    }
+
+    /// Set the configuration file read again by configuration reloads
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Set the redaction rules of workspaces without a `redaction` setting
+    pub fn with_redactor(self, redactor: Redactor) -> Self {
+        self.with_live(|live| live.redactor = redactor)
+    }
+
+    /// Set the API keys accepted by the server
+    ///
+    /// With no keys configured the API is open and callers see every dataset.
+    pub fn with_auth(mut self, auth: AuthConfig) -> Self {
+        self.auth = Arc::new(auth);
+        self
+    }
+
+    /// Set the feature properties copied into chunk metadata on rebuild
+    pub fn with_chunk_properties(mut self, properties: Vec<String>) -> Self {
+        self.chunk_properties = properties;
+        self
+    }
+
+    /// Set how uploads with unreadable features are handled
+    pub fn with_read_policy(mut self, policy: ReadPolicy) -> Self {
+        self.read_policy = policy;
+        self
+    }
+
+    /// Set the axis order assumed for uploads that do not set one
+    pub fn with_axis_order(mut self, axis_order: AxisOrder) -> Self {
+        self.axis_order = axis_order;
+        self
+    }
+
+    /// Set where the original files of uploads are kept
+    pub fn with_blob_store(mut self, blob_store: Arc<dyn BlobStore>) -> Self {
+        self.blob_store = blob_store;
+        self
+    }
+
+    /// Set where saved areas are kept
+    pub fn with_area_store(mut self, area_store: Arc<dyn AreaStore>) -> Self {
+        self.area_store = area_store;
+        self
+    }
+
+    /// Set where the audit events of workspaces are kept
+    pub fn with_audit_store(mut self, audit_store: Arc<dyn AuditStore>) -> Self {
+        self.audit_store = audit_store;
+        self
+    }
+
+    /// Set where the change events of workspaces are kept
+    pub fn with_event_log(mut self, event_log: Arc<dyn EventLogStore>) -> Self {
+        self.event_log = event_log;
+        self
+    }
+
+    /// Set how much of the event log is kept
+    pub fn with_event_retention(mut self, retention: EventRetention) -> Self {
+        self.event_retention = retention;
+        self
+    }
+
+    /// Set which original upload files are kept for download
+    pub fn with_source_policy(mut self, policy: SourcePolicy) -> Self {
+        self.source_policy = policy;
+        self
+    }
+
+    /// Set the limits of datasets ingested from URLs
+    pub fn with_download_policy(mut self, policy: DownloadPolicy) -> Self {
+        self.download_policy = policy;
+        self
+    }
+
+    /// Set the vertex limit for uploaded features and the handling of larger ones
+    pub fn with_feature_limits(mut self, limits: FeatureLimits) -> Self {
+        self.feature_limits = limits;
+        self
+    }
+
+    /// Set whether the Z ordinates of uploaded features are kept
+    pub fn with_z_coordinates(mut self, z_coordinates: ZCoordinates) -> Self {
+        self.z_coordinates = z_coordinates;
+        self
+    }
+
+    /// Set the batch size and channel capacity of the ingest pipeline
+    pub fn with_pipeline_buffers(mut self, buffers: IngestBuffers) -> Self {
+        self.pipeline_buffers = buffers;
+        self
+    }
+
+    /// Set the quotas of workspaces whose settings leave them unset
+    pub fn with_quotas(self, quotas: WorkspaceQuotas) -> Self {
+        self.with_live(|live| live.quotas = quotas)
+    }
+
+    /// Set the configuration reported by `GET /api/v1/admin/config`
+    ///
+    /// Values must already be masked, e.g. by `ApiConfig::inspection_map`.
+    pub fn with_effective_config(self, config: EffectiveConfig) -> Self {
+        self.with_live(|live| live.effective_config = config)
+    }
+
+    fn with_live(self, update: impl FnOnce(&mut LiveConfig)) -> Self {
+        let mut live = (*self.live_config()).clone();
+        update(&mut live);
+        self.with_live_config(live)
+    }
+
+    /// Configuration values in effect; a reload swaps in new ones as a whole
+    pub fn live_config(&self) -> Arc<LiveConfig> {
+        // Writers only swap the Arc, so the lock is never poisoned
+        self.live.read().unwrap().clone()
+    }
+
+    /// Replace the values in effect with reloaded ones
+    pub(crate) fn set_live_config(&self, live: LiveConfig) {
+        *self.live.write().unwrap() = Arc::new(live);
+    }
+
+    /// Set the workspace served by routes that do not select one
+    pub fn with_default_workspace(mut self, name: impl Into<String>) -> Self {
+        self.default_workspace = name.into();
+        self
+    }
+
+    /// Set where the data of workspaces other than the default one is kept
+    ///
+    /// The stores passed to `new` serve the default workspace. Without a
+    /// provider only the default workspace can store and query data.
+    pub fn with_store_provider(mut self, provider: Arc<dyn WorkspaceStoreProvider>) -> Self {
+        self.store_provider = Some(provider);
+        self
+    }
+
+    /// Set the health checks of the storage backend
+    ///
+    /// While the backend is not healthy writes are rejected, and once it is
+    /// down queries of the default workspace may be served from the
+    /// supervisor's fallback bundle.
+    pub fn with_store_supervisor(mut self, supervisor: Arc<StoreSupervisor>) -> Self {
+        self.store_supervisor = Some(supervisor);
+        self
+    }
+
+    /// Set the caps on concurrent expensive operations
+    pub fn with_governor(mut self, governor: ResourceGovernor) -> Self {
+        self.governor = Arc::new(governor);
+        self
+    }
+
+    /// Set the cache holding the results of paged queries
+    pub fn with_query_pages(mut self, pages: QueryPages) -> Self {
+        self.query_pages = Arc::new(pages);
+        self
+    }
+
+    /// Set the format readers used for uploads
+    ///
+    /// Downstream crates embedding the API register extra readers on
+    /// `FormatRegistry::with_defaults()` and pass the result here.
+    pub fn with_format_registry(mut self, registry: FormatRegistry) -> Self {
+        self.format_registry = Arc::new(registry);
+        self
+    }
+
+    /// Ingest service storing uploads with the configured format readers
+    ///
+    /// Original files are kept in the blob store under `policy`.
+    pub fn ingest_service(&self, policy: SourcePolicy) -> IngestService {
+        IngestService::new(self.spatial_store.clone(), self.format_registry.clone())
+            .with_blob_store(self.blob_store.clone(), policy)
+    }
+
+    /// Query service applying the workspace's redaction, geometry limits and reranker
+    ///
+    /// JSON responses are redacted again on their way out; the service's
+    /// redaction covers the CSV and PNG renderings.
+    pub fn query_service(&self) -> QueryService {
+        QueryService::new(
+            self.spatial_store.clone(),
+            self.vector_store.clone(),
+            self.document_store.clone(),
+        )
+        .with_redactor(self.redactor())
+        .with_geometry_limits(self.query_config.geometry_limits)
+        .with_llm_reranker(self.llm_reranker.clone())
+        .with_filter_cache(self.filter_cache.clone())
+    }
+
+    /// Area service resolving saved areas against the shared stores
+    pub fn area_service(&self) -> AreaService {
+        AreaService::new(self.area_store.clone(), self.spatial_store.clone())
+    }
+
+    /// Audit service recording workspace events and aggregating them into timelines
+    pub fn audit_service(&self) -> AuditService {
+        AuditService::new(self.audit_store.clone())
+    }
+
+    /// Event log service recording changes and serving them to downstream consumers
+    pub fn event_log_service(&self) -> EventLogService {
+        EventLogService::new(self.event_log.clone())
+            .with_notify(self.event_appended.clone())
+            .with_retention(self.event_retention)
+    }
+
+    /// Workspace service creating workspaces and updating their settings
+    pub fn workspace_service(&self) -> WorkspaceService {
+        WorkspaceService::new(self.workspace_store.clone()).with_audit(self.audit_service())
+    }
+
+    /// Compaction service over the shared stores
+    pub fn compaction_service(&self) -> CompactionService {
+        CompactionService::new(
+            self.spatial_store.clone(),
+            self.document_store.clone(),
+            self.vector_store.clone(),
+        )
+    }
+
+    /// Service reading the per-dataset index counters of the stores
+    pub fn index_stats_service(&self) -> IndexStatsService {
+        IndexStatsService::new(self.document_store.clone(), self.vector_store.clone())
+    }
+
     /// Set the index state (called after build)
     pub async fn set_index_state(&self, state: IndexState) {
         let mut guard = self.index_state.write().await;
@@ -59,11 +449,27 @@ impl AppState {
     }
 
     /// Get the current index state
+    ///
+    /// A scoped state reports its workspace's index; the default workspace
+    /// falls back to the index set with `set_index_state`, e.g. a bundle's.
     pub async fn get_index_state(&self) -> Result<IndexState, ApiError> {
+        if let Some(workspace_id) = self.workspace {
+            if let Some(state) = self.get_workspace_index_state(workspace_id).await {
+                return Ok(state);
+            }
+            if self.own_stores {
+                return Err(ApiError::not_found("Index has not been built yet"));
+            }
+        }
         let guard = self.index_state.read().await;
         guard.clone().ok_or_else(|| ApiError::not_found("Index has not been built yet"))
     }
 
+    /// Generation of the index queries are ranked against, for query cursors
+    pub async fn index_generation(&self) -> String {
+        index_generation(self.get_index_state().await.ok().as_ref())
+    }
+
     /// Compute current index hash from stored data
     pub async fn compute_index_hash(&self) -> Result<String, ApiError> {
         let chunk_ids =
@@ -98,10 +504,503 @@ impl AppState {
         Ok(format!("{:x}", hasher.finish()))
     }
 
-    /// Check if a workspace is currently rebuilding
+    /// Settings stored for a workspace, cached for a few seconds
+    ///
+    /// Settings changed by the CLI are picked up once the cached copy expires.
+    ///
+    /// While queries are served from the fallback bundle the store is not
+    /// read; the cached copy is used however old it is.
+    pub async fn workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Option<WorkspaceSettings>, ApiError> {
+        if let Some((read_at, settings)) = self.settings_cache.read().await.get(&workspace_id) {
+            if self.serving_fallback || read_at.elapsed() < SETTINGS_TTL {
+                return Ok(settings.clone());
+            }
+        }
+        if self.serving_fallback {
+            return Ok(None);
+        }
+
+        let settings =
+            self.workspace_store.get_workspace_settings(workspace_id).await.map_err(|e| {
+                ApiError::internal("Failed to read workspace settings").with_details(e.to_string())
+            })?;
+        self.settings_cache
+            .write()
+            .await
+            .insert(workspace_id, (Instant::now(), settings.clone()));
+        Ok(settings)
+    }
+
+    /// Redaction rules applied to the responses of this state
+    ///
+    /// A state scoped to a workspace applies that workspace's rules; others
+    /// the server's.
+    pub fn redactor(&self) -> Redactor {
+        self.redactor.clone().unwrap_or_else(|| self.live_config().redactor.clone())
+    }
+
+    /// Redaction rules of a workspace: its `redaction` setting, else the server's
+    pub async fn workspace_redactor(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Redactor, ApiError> {
+        let settings = self.workspace_settings(workspace_id).await?;
+        let Some(rules) = settings.and_then(|settings| settings.redaction) else {
+            return Ok(self.live_config().redactor.clone());
+        };
+        if let Some(redactor) = self.redactors.read().await.get(&workspace_id) {
+            if *redactor.rules() == rules {
+                return Ok(redactor.clone());
+            }
+        }
+
+        let redactor = Redactor::new(&rules).map_err(|e| {
+            ApiError::internal("Invalid redaction rules").with_details(e.to_string())
+        })?;
+        self.redactors.write().await.insert(workspace_id, redactor.clone());
+        Ok(redactor)
+    }
+
+    /// Redaction rules of every workspace together
+    ///
+    /// Applied to the responses of routes that do not select a workspace,
+    /// such as the event log, which may carry any workspace's data.
+    pub async fn all_workspaces_redactor(&self) -> Result<Redactor, ApiError> {
+        let mut redactor = self.live_config().redactor.clone();
+        for workspace in self.list_workspaces().await? {
+            let rules = self.workspace_redactor(workspace.id).await?;
+            if rules.is_empty() {
+                continue;
+            }
+            redactor = redactor.union(&rules).map_err(|e| {
+                ApiError::internal("Invalid redaction rules").with_details(e.to_string())
+            })?;
+        }
+        Ok(redactor)
+    }
+
+    /// Find a workspace by ID or name
+    pub async fn find_workspace(&self, key: &str) -> Result<WorkspaceMeta, ApiError> {
+        let workspace = match key.parse::<WorkspaceId>() {
+            Ok(id) => self.workspace_store.get_workspace(id).await.map_err(|e| {
+                tracing::error!(error = %e, "Failed to check workspace existence");
+                ApiError::internal("Failed to verify workspace").with_details(e.to_string())
+            })?,
+            Err(_) => self.list_workspaces().await?.into_iter().find(|w| w.name == key),
+        };
+
+        match workspace {
+            Some(workspace) => Ok(workspace),
+            None if key == self.default_workspace => self.default_workspace().await,
+            None => Err(ApiError::not_found("Workspace not found")
+                .with_details(format!("No workspace has the ID or name '{}'", key))),
+        }
+    }
+
+    /// The workspace served by routes that do not select one, created on first use
+    pub async fn default_workspace(&self) -> Result<WorkspaceMeta, ApiError> {
+        let workspaces = self.list_workspaces().await?;
+        if let Some(workspace) = workspaces.into_iter().find(|w| w.name == self.default_workspace) {
+            return Ok(workspace);
+        }
+
+        let config = WorkspaceConfig {
+            crs: 4326,
+            distance_unit: DistanceUnit::Meters,
+            geometry_validity: ValidityMode::Lenient,
+        };
+        let id = self
+            .workspace_store
+            .create_workspace(&self.default_workspace, &config)
+            .await
+            .map_err(|e| {
+                ApiError::internal("Failed to create default workspace").with_details(e.to_string())
+            })?;
+        self.workspace_store
+            .get_workspace(id)
+            .await
+            .map_err(|e| {
+                ApiError::internal("Failed to load default workspace").with_details(e.to_string())
+            })?
+            .ok_or_else(|| ApiError::internal("Default workspace not found after creation"))
+    }
+
+    async fn list_workspaces(&self) -> Result<Vec<WorkspaceMeta>, ApiError> {
+        self.workspace_store.list_workspaces().await.map_err(|e| {
+            ApiError::internal("Failed to list workspaces").with_details(e.to_string())
+        })
+    }
+
+    /// Workspaces this state cannot serve, for lack of a store provider
+    ///
+    /// Without a provider every workspace would be served from the default
+    /// workspace's stores, so the server refuses to start while there are any.
+    pub async fn unservable_workspaces(&self) -> Result<Vec<WorkspaceMeta>, ApiError> {
+        if self.store_provider.is_some() {
+            return Ok(Vec::new());
+        }
+        let mut workspaces = self.list_workspaces().await?;
+        workspaces.retain(|workspace| workspace.name != self.default_workspace);
+        Ok(workspaces)
+    }
+
+    /// Whether a workspace named `name` could be served
+    pub fn can_serve_workspace(&self, name: &str) -> bool {
+        self.store_provider.is_some() || name.trim() == self.default_workspace
+    }
+
+    /// State serving one workspace's data
+    ///
+    /// The default workspace is served by the stores this state was created
+    /// with; other workspaces by the stores of the store provider.
+    pub async fn for_workspace(&self, workspace: &WorkspaceMeta) -> Result<AppState, ApiError> {
+        let mut scoped = self.clone();
+        scoped.workspace = Some(workspace.id);
+        scoped.redactor = Some(self.workspace_redactor(workspace.id).await?);
+        if workspace.name == self.default_workspace {
+            if self.store_supervisor.is_some() {
+                *self.known_default.write().unwrap() = Some(workspace.clone());
+            }
+            return Ok(scoped);
+        }
+
+        let provider = self.store_provider.as_ref().ok_or_else(|| {
+            ApiError::not_implemented("This storage backend serves the default workspace only")
+                .with_details(format!("Use the '{}' workspace", self.default_workspace))
+        })?;
+        let stores = provider.open_workspace(workspace.id).await.map_err(|e| {
+            tracing::error!(error = %e, "Failed to open workspace stores");
+            ApiError::internal("Failed to open workspace stores").with_details(e.to_string())
+        })?;
+        scoped.spatial_store = stores.spatial;
+        scoped.vector_store = stores.vector;
+        scoped.document_store = stores.document;
+        scoped.blob_store = stores.blob;
+        scoped.own_stores = true;
+        Ok(scoped)
+    }
+
+    /// State serving a query of the default workspace from the fallback bundle
+    ///
+    /// Only while the storage backend is down, the default workspace has been
+    /// served before and the bundle was exported from its current index;
+    /// `key` is the ID or name the request selected, if any.
+    pub async fn for_fallback(&self, key: Option<&str>) -> Option<(WorkspaceMeta, AppState)> {
+        let supervisor = self.store_supervisor.as_ref()?;
+        let workspace = self.known_default.read().unwrap().clone()?;
+        if key.is_some_and(|key| key != workspace.name && key != workspace.id.to_string()) {
+            return None;
+        }
+
+        let index = match self.get_workspace_index_state(workspace.id).await {
+            Some(index) => Some(index),
+            None => self.index_state.read().await.clone(),
+        };
+        let bundle = supervisor.fallback_for(index.as_ref().map(|index| index.hash.as_str()))?;
+
+        let mut scoped = self.clone();
+        scoped.workspace = Some(workspace.id);
+        scoped.spatial_store = bundle.clone();
+        scoped.vector_store = bundle.clone();
+        scoped.document_store = bundle;
+        scoped.serving_fallback = true;
+        // Settings cached when the workspace was last served
+        scoped.redactor = scoped.workspace_redactor(workspace.id).await.ok();
+        Some((workspace, scoped))
+    }
+
+    /// The stores this state serves, as one bundle
+    pub fn workspace_stores(&self) -> WorkspaceStores {
+        WorkspaceStores {
+            spatial: self.spatial_store.clone(),
+            vector: self.vector_store.clone(),
+            document: self.document_store.clone(),
+            blob: self.blob_store.clone(),
+        }
+    }
+
+    /// Drop the stores of a deleted workspace
+    pub async fn forget_workspace_stores(&self, workspace_id: WorkspaceId) -> Result<(), ApiError> {
+        if let Some(provider) = &self.store_provider {
+            provider.drop_workspace(workspace_id).await.map_err(|e| {
+                ApiError::internal("Failed to delete workspace data").with_details(e.to_string())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Datasets of a workspace, sorted by name and then ID
+    ///
+    /// A workspace with stores of its own holds nothing but its datasets;
+    /// the shared stores record which workspace each dataset belongs to.
+    pub async fn workspace_datasets(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<DatasetMeta>, GeoragError> {
+        if self.own_stores {
+            self.spatial_store.list_datasets().await
+        } else {
+            self.workspace_store.list_datasets_for_workspace(workspace_id).await
+        }
+    }
+
+    /// Delete a dataset of a workspace
+    pub async fn delete_workspace_dataset(
+        &self,
+        workspace_id: WorkspaceId,
+        dataset_id: DatasetId,
+    ) -> Result<(), GeoragError> {
+        if self.own_stores {
+            self.spatial_store.delete_dataset(dataset_id).await
+        } else {
+            self.workspace_store.delete_dataset_in_workspace(workspace_id, dataset_id).await
+        }
+    }
+
+    /// Quotas of a workspace: its stored settings, then the server defaults
+    pub fn workspace_quotas(&self, settings: Option<&WorkspaceSettings>) -> WorkspaceQuotas {
+        settings
+            .map(WorkspaceSettings::quotas)
+            .unwrap_or_default()
+            .or(self.live_config().quotas)
+    }
+
+    /// Quotas and usage counters of a workspace, for services that write to it
+    pub async fn workspace_quota(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<WorkspaceQuota, ApiError> {
+        let settings = self.workspace_settings(workspace_id).await?;
+        Ok(WorkspaceQuota::new(
+            self.workspace_store.clone(),
+            workspace_id,
+            self.workspace_quotas(settings.as_ref()),
+        ))
+    }
+
+    /// Bulk dataset service over a workspace's stores and usage counters
+    pub async fn bulk_dataset_service(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<BulkDatasetService, ApiError> {
+        let service = BulkDatasetService::new(
+            self.spatial_store.clone(),
+            self.document_store.clone(),
+            self.vector_store.clone(),
+            self.blob_store.clone(),
+            self.workspace_quota(workspace_id).await?,
+            self.audit_service(),
+        )
+        .with_event_log(self.event_log_service());
+        Ok(if self.own_stores {
+            service
+        } else {
+            service.with_shared_stores(self.workspace_store.clone())
+        })
+    }
+
+    /// Drop the cached settings of a workspace after they changed
+    pub async fn forget_workspace_settings(&self, workspace_id: WorkspaceId) {
+        self.settings_cache.write().await.remove(&workspace_id);
+    }
+
+    /// Whether a configuration key was set by the server's environment
+    ///
+    /// Environment values take precedence over stored workspace settings.
+    fn set_by_environment(&self, key: &str) -> bool {
+        self.live_config()
+            .effective_config
+            .get(key)
+            .is_some_and(|(_, source)| *source == ConfigSource::Environment)
+    }
+
+    /// Read policy for uploads, taking unset values from stored settings
+    pub fn ingest_read_policy(&self, settings: Option<&WorkspaceSettings>) -> ReadPolicy {
+        let Some(settings) = settings else {
+            return self.read_policy;
+        };
+
+        let mut validity = self.read_policy.validity;
+        if let Some(stored) = settings.geometry_validity {
+            if !self.set_by_environment("ingest.geometry_validity") {
+                validity = stored;
+            }
+        }
+
+        // A strict server policy carries no error budget of its own
+        let mut max_errors = match self.read_policy.validity {
+            ValidityMode::Lenient => self.read_policy.max_errors,
+            ValidityMode::Strict => DEFAULT_MAX_FEATURE_ERRORS,
+        };
+        if let Some(stored) = settings.max_feature_errors {
+            if !self.set_by_environment("ingest.max_feature_errors") {
+                max_errors = stored;
+            }
+        }
+
+        ReadPolicy::for_validity(validity, max_errors)
+    }
+
+    /// Axis order for uploads that do not set one, taking stored settings into account
+    pub fn ingest_axis_order(&self, settings: Option<&WorkspaceSettings>) -> AxisOrder {
+        match settings.and_then(|s| s.axis_order) {
+            Some(order) if !self.set_by_environment("ingest.axis_order") => order,
+            _ => self.axis_order,
+        }
+    }
+
+    /// Source file policy for uploads, taking stored settings into account
+    pub fn ingest_source_policy(&self, settings: Option<&WorkspaceSettings>) -> SourcePolicy {
+        let mut policy = self.source_policy;
+        if let Some(max_bytes) = settings.and_then(|s| s.max_source_bytes) {
+            if !self.set_by_environment("ingest.max_source_bytes") {
+                policy.max_bytes = max_bytes;
+            }
+        }
+        if let Some(hash) = settings.and_then(|s| s.hash_sources) {
+            if !self.set_by_environment("ingest.hash_sources") {
+                policy.hash = hash;
+            }
+        }
+        policy
+    }
+
+    /// Feature vertex limits for uploads, taking stored settings into account
+    pub fn ingest_feature_limits(&self, settings: Option<&WorkspaceSettings>) -> FeatureLimits {
+        let mut limits = self.feature_limits;
+        if let Some(max_vertices) = settings.and_then(|s| s.max_feature_vertices) {
+            if !self.set_by_environment("ingest.max_feature_vertices") {
+                limits.max_vertices = max_vertices;
+            }
+        }
+        if let Some(oversized) = settings.and_then(|s| s.oversized_features) {
+            if !self.set_by_environment("ingest.oversized_features") {
+                limits.oversized = oversized;
+            }
+        }
+        limits
+    }
+
+    /// Z ordinate handling for uploads, taking stored settings into account
+    pub fn ingest_z_coordinates(&self, settings: Option<&WorkspaceSettings>) -> ZCoordinates {
+        match settings.and_then(|s| s.z_coordinates) {
+            Some(z) if !self.set_by_environment("ingest.z_coordinates") => z,
+            _ => self.z_coordinates,
+        }
+    }
+
+    /// Transforms applied to each feature of an upload
+    ///
+    /// Transforms are a workspace setting only; the server configuration has none.
+    pub fn ingest_transforms(&self, settings: Option<&WorkspaceSettings>) -> Vec<FeatureTransform> {
+        settings.and_then(|s| s.feature_transforms.clone()).unwrap_or_default()
+    }
+
+    /// Ingest pipeline buffers for uploads, taking stored settings into account
+    pub fn ingest_buffers(&self, settings: Option<&WorkspaceSettings>) -> IngestBuffers {
+        let mut buffers = self.pipeline_buffers;
+        if let Some(batch_size) = settings.and_then(|s| s.ingest_batch_size) {
+            if !self.set_by_environment("ingest.batch_size") {
+                buffers.batch_size = batch_size;
+            }
+        }
+        if let Some(capacity) = settings.and_then(|s| s.ingest_channel_capacity) {
+            if !self.set_by_environment("ingest.channel_capacity") {
+                buffers.channel_capacity = capacity;
+            }
+        }
+        IngestBuffers::new(buffers.batch_size, buffers.channel_capacity)
+    }
+
+    /// Minimum score for queries that do not set one, taking stored settings into account
+    pub fn query_min_score(&self, settings: Option<&WorkspaceSettings>) -> Option<f32> {
+        match settings.and_then(|s| s.min_score) {
+            Some(score) if !self.set_by_environment("query.min_score") => Some(score),
+            _ => self.query_config.min_score,
+        }
+    }
+
+    /// Defaults of point queries, taking stored settings into account
+    pub fn query_point_defaults(&self, settings: Option<&WorkspaceSettings>) -> PointQueryDefaults {
+        let mut defaults = self.query_config.point_defaults;
+        let stored = |key: &str| settings.filter(|_| !self.set_by_environment(key));
+        if let Some(Ok(predicate)) = stored("query.default_spatial_predicate")
+            .and_then(|s| s.default_spatial_predicate.as_deref())
+            .map(parse_spatial_predicate)
+        {
+            defaults.predicate = predicate;
+        }
+        if let Some(radius) = stored("query.default_radius").and_then(|s| s.default_radius) {
+            defaults.radius = Some(radius);
+        }
+        if let Some(unit) = stored("query.default_radius_unit").and_then(|s| s.default_radius_unit)
+        {
+            defaults.radius_unit = unit;
+        }
+        defaults
+    }
+
+    /// Basemap for `png` results, taking stored settings into account
+    pub fn query_basemap(&self, settings: Option<&WorkspaceSettings>) -> Option<Basemap> {
+        let stored =
+            |key: &str, value: Option<String>| value.filter(|_| !self.set_by_environment(key));
+        let configured = self.query_config.basemap.as_ref();
+
+        let url = stored("query.map_tile_url", settings.and_then(|s| s.map_tile_url.clone()))
+            .or_else(|| configured.map(|b| b.url_template.clone()))?;
+        let attribution = stored(
+            "query.map_tile_attribution",
+            settings.and_then(|s| s.map_tile_attribution.clone()),
+        )
+        .or_else(|| configured.and_then(|b| b.attribution.clone()));
+
+        let basemap = Basemap::new(url);
+        Some(match attribution {
+            Some(attribution) => basemap.with_attribution(attribution),
+            None => basemap,
+        })
+    }
+
+    /// Template of result source URLs, taking stored settings into account
+    pub fn query_source_url_template(
+        &self,
+        settings: Option<&WorkspaceSettings>,
+    ) -> Option<SourceUrlTemplate> {
+        let stored = settings
+            .and_then(|s| s.source_url_template.as_deref())
+            .filter(|_| !self.set_by_environment("query.source_url_template"))
+            .and_then(|t| parse_source_url_template(t).ok());
+        stored.or_else(|| self.query_config.source_url_template.clone())
+    }
+
+    /// Confidence cutoffs of result sources, taking stored settings into account
+    pub fn query_confidence_cutoffs(
+        &self,
+        settings: Option<&WorkspaceSettings>,
+    ) -> ConfidenceCutoffs {
+        settings
+            .and_then(|s| s.confidence_cutoffs.as_deref())
+            .filter(|_| !self.set_by_environment("query.confidence_cutoffs"))
+            .and_then(|c| parse_confidence_cutoffs(c).ok())
+            .unwrap_or(self.query_config.confidence_cutoffs)
+    }
+
+    /// Check if a workspace is currently rebuilding, or waiting to resume
     pub async fn is_rebuilding(&self, workspace_id: WorkspaceId) -> bool {
         let guard = self.rebuild_status.read().await;
-        matches!(guard.get(&workspace_id), Some(RebuildStatus::InProgress))
+        matches!(
+            guard.get(&workspace_id),
+            Some(RebuildStatus::InProgress | RebuildStatus::Paused)
+        )
+    }
+
+    /// Check if a workspace rebuild waits for the storage backend
+    pub async fn is_rebuild_paused(&self, workspace_id: WorkspaceId) -> bool {
+        let guard = self.rebuild_status.read().await;
+        matches!(guard.get(&workspace_id), Some(RebuildStatus::Paused))
     }
 
     /// Mark a workspace as rebuilding
@@ -110,6 +1009,12 @@ impl AppState {
         guard.insert(workspace_id, RebuildStatus::InProgress);
     }
 
+    /// Mark a workspace rebuild as waiting for the storage backend
+    pub async fn pause_rebuild(&self, workspace_id: WorkspaceId) {
+        let mut guard = self.rebuild_status.write().await;
+        guard.insert(workspace_id, RebuildStatus::Paused);
+    }
+
     /// Mark a workspace rebuild as finished
     pub async fn finish_rebuild(&self, workspace_id: WorkspaceId) {
         let mut guard = self.rebuild_status.write().await;
@@ -142,11 +1047,23 @@ impl AppState {
         workspace_id: WorkspaceId,
     ) -> Result<(), GeoragError> {
         use georag_core::geo::models::Crs;
-        use georag_core::llm::OllamaEmbedder;
         use georag_retrieval::IndexBuilder;
 
-        // Get datasets for workspace
-        let datasets = self.workspace_store.list_datasets_for_workspace(workspace_id).await?;
+        // Wait for a running compaction or another workspace's rebuild
+        let _build = self.build_lock.lock().await;
+
+        // Get datasets for workspace, leaving out archived ones; tags are
+        // current in the spatial store only
+        let mut datasets = Vec::new();
+        for meta in self.workspace_datasets(workspace_id).await? {
+            let archived = match self.spatial_store.get_dataset(meta.id).await? {
+                Some(dataset) => dataset.tags.iter().any(|tag| tag == ARCHIVED_TAG),
+                None => meta.is_archived(),
+            };
+            if !archived {
+                datasets.push(meta);
+            }
+        }
 
         if datasets.is_empty() {
             return Err(GeoragError::IndexNotBuilt(
@@ -160,18 +1077,20 @@ impl AppState {
             "Starting index rebuild"
         );
 
-        // Create embedder from config (default Ollama URL)
-        let ollama_url =
-            std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
-        let embedder = OllamaEmbedder::new(
-            ollama_url,
-            &self.embedder_config.model,
-            self.embedder_config.dimensions,
-        );
+        // Chunks the rebuild replaces, for the change events of those it removes
+        let chunks_before = self.document_store.list_chunk_ids().await?;
+
+        // Create the configured embedder
+        let embedder = self.embedder_config.create(&self.embedder_config.configured())?;
 
         // Create workspace CRS (default to WGS84)
         let workspace_crs = Crs::wgs84();
 
+        // Chunks beyond the workspace's quota fail the build before the old index is cleared
+        let settings = self.workspace_store.get_workspace_settings(workspace_id).await?;
+        let quotas = self.workspace_quotas(settings.as_ref());
+        let usage = self.workspace_store.get_workspace_usage(workspace_id).await?;
+
         // Create IndexBuilder with stores
         let builder = IndexBuilder::new(
             self.spatial_store.clone(),
@@ -180,7 +1099,13 @@ impl AppState {
             embedder,
             workspace_crs,
         )
-        .with_batch_size(32);
+        .with_batch_size(32)
+        .with_resource_gate(self.governor.clone())
+        .with_chunk_properties(self.chunk_properties.clone())
+        .with_chunk_quota(quotas, usage)
+        .with_previous_index(PreviousIndex::from(
+            self.get_workspace_index_state(workspace_id).await,
+        ));
 
         // Perform full rebuild with progress logging
         let result = builder
@@ -198,15 +1123,33 @@ impl AppState {
         tracing::info!(
             workspace_id = %workspace_id,
             chunk_count = result.chunk_count,
+            chunks_skipped = result.chunks_skipped,
+            chunks_unembedded = result.unembedded_chunks.len(),
             embedding_dim = result.embedding_dim,
             hash = %result.index_hash,
             "Index rebuild completed"
         );
 
+        // The rebuild replaced every chunk of the workspace
+        let delta = UsageDelta {
+            chunks: result.chunk_count as i64 - usage.chunks as i64,
+            ..Default::default()
+        };
+        if !delta.is_empty() {
+            self.workspace_store.adjust_workspace_usage(workspace_id, &delta).await?;
+        }
+
         // Create and store the index state
         let index_state = builder.create_index_state(&result);
         self.set_workspace_index_state(workspace_id, index_state).await;
 
+        let event = AuditEvent::new(AuditEventKind::BuildCompleted)
+            .with_details(format!("{} chunks", result.chunk_count));
+        self.audit_service().record(workspace_id, event).await;
+        self.event_log_service()
+            .record_chunks_built(workspace_id, self.document_store.as_ref(), &chunks_before)
+            .await;
+
         Ok(())
     }
 }
@@ -0,0 +1,161 @@
+//! Workspace selection for workspace-scoped routes
+//!
+//! Each data route operates on one workspace, taken from the first of:
+//!
+//! 1. the path, as in `/api/v1/workspaces/{workspace_id}/query`
+//! 2. the `X-Georag-Workspace` header
+//! 3. the server's default workspace (`GEORAG_DEFAULT_WORKSPACE`)
+//!
+//! Workspaces are given by ID or name. Unknown workspaces are rejected with
+//! 404 and workspaces the caller's API key is not bound to with 403.
+//!
+//! While the storage backend is down, queries of the default workspace are
+//! served from the fallback bundle when it is fresh; see [`crate::health`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    RequestExt,
+};
+use chrono::Utc;
+use georag_core::models::WorkspaceId;
+
+use crate::auth::Caller;
+use crate::error::ApiError;
+use crate::health::{is_query, FallbackRequest};
+use crate::state::AppState;
+
+/// Header selecting the workspace of an unprefixed route
+pub const WORKSPACE_HEADER: &str = "x-georag-workspace";
+
+/// Path parameter naming the workspace of a prefixed route
+const WORKSPACE_PARAM: &str = "workspace_id";
+
+/// The workspace a request operates on
+#[derive(Clone)]
+pub struct Workspace {
+    pub id: WorkspaceId,
+    pub name: String,
+    /// State serving this workspace's stores
+    pub state: Arc<AppState>,
+}
+
+/// Resolve the request's workspace and attach it as an extension
+///
+/// Runs after `auth::authenticate`, whose caller it checks the workspace against.
+pub async fn select_workspace(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let params = request
+        .extract_parts::<Path<HashMap<String, String>>>()
+        .await
+        .map(|Path(params)| params)
+        .unwrap_or_default();
+    let caller = request
+        .extensions()
+        .get::<Caller>()
+        .cloned()
+        .unwrap_or_else(Caller::unrestricted);
+
+    if is_query(request.method(), request.uri().path()) {
+        let served = FallbackRequest {
+            at: Utc::now(),
+            method: request.method().to_string(),
+            path: request.uri().path().to_string(),
+            workspace: String::new(),
+        };
+        match fallback(&state, &caller, params.get(WORKSPACE_PARAM), request.headers(), served)
+            .await
+        {
+            Ok(Some(workspace)) => {
+                request.extensions_mut().insert(workspace);
+                return next.run(request).await;
+            }
+            Ok(None) => {}
+            Err(e) => return e.into_response(),
+        }
+    }
+
+    match resolve(&state, &caller, params.get(WORKSPACE_PARAM), request.headers()).await {
+        Ok(workspace) => {
+            request.extensions_mut().insert(workspace);
+            next.run(request).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// The default workspace served from the fallback bundle, when it can serve the query
+async fn fallback(
+    state: &AppState,
+    caller: &Caller,
+    from_path: Option<&String>,
+    headers: &HeaderMap,
+    served: FallbackRequest,
+) -> Result<Option<Workspace>, ApiError> {
+    let Some(supervisor) = &state.store_supervisor else {
+        return Ok(None);
+    };
+    let key = match from_path {
+        Some(key) => Some(key.as_str()),
+        None => header_workspace(headers)?,
+    };
+    let Some((workspace, scoped)) = state.for_fallback(key).await else {
+        return Ok(None);
+    };
+    caller.require_workspace(&workspace)?;
+
+    tracing::info!(workspace = %workspace.name, "Serving query from the fallback bundle");
+    supervisor.record_fallback(FallbackRequest {
+        workspace: workspace.name.clone(),
+        ..served
+    });
+    Ok(Some(Workspace {
+        id: workspace.id,
+        name: workspace.name,
+        state: Arc::new(scoped),
+    }))
+}
+
+/// Workspace named by the `X-Georag-Workspace` header, if any
+fn header_workspace(headers: &HeaderMap) -> Result<Option<&str>, ApiError> {
+    match headers.get(WORKSPACE_HEADER) {
+        Some(value) => value
+            .to_str()
+            .map(str::trim)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .map(Some)
+            .ok_or_else(|| ApiError::bad_request("Invalid X-Georag-Workspace header")),
+        None => Ok(None),
+    }
+}
+
+async fn resolve(
+    state: &AppState,
+    caller: &Caller,
+    from_path: Option<&String>,
+    headers: &HeaderMap,
+) -> Result<Workspace, ApiError> {
+    let from_header = header_workspace(headers)?;
+
+    let workspace = match from_path.map(String::as_str).or(from_header) {
+        Some(key) => state.find_workspace(key).await?,
+        None => state.default_workspace().await?,
+    };
+    caller.require_workspace(&workspace)?;
+
+    let scoped = state.for_workspace(&workspace).await?;
+    Ok(Workspace {
+        id: workspace.id,
+        name: workspace.name,
+        state: Arc::new(scoped),
+    })
+}
@@ -0,0 +1,100 @@
+//! Integration tests for adaptive result geometry
+//!
+//! A lake outline with thousands of vertices is queried with a small vertex
+//! budget. The result carries a simplified outline and says so, and the
+//! dataset feature endpoint it points to returns the outline as uploaded.
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{app, create_workspace, get, json_request, rebuild, send, upload};
+use serde_json::{json, Value};
+
+const QUERY_URI: &str = "/api/v1/workspaces/lakes/query";
+
+/// Vertices of the lake outline, the closing vertex included
+const LAKE_VERTICES: usize = 2001;
+
+/// Lake outline on a wobbly circle
+fn lake() -> Value {
+    let n = LAKE_VERTICES - 1;
+    let mut ring: Vec<[f64; 2]> = (0..n)
+        .map(|i| {
+            let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+            let radius = 0.05 * (1.0 + 0.01 * (i as f64 * 0.37).sin());
+            [106.8 + radius * angle.cos(), -6.2 + radius * angle.sin()]
+        })
+        .collect();
+    ring.push(ring[0]);
+    json!({ "type": "Polygon", "coordinates": [ring] })
+}
+
+/// App with workspace `lakes` holding the lake, with its index built
+async fn lakes_workspace() -> Router {
+    let app = app();
+    create_workspace(&app, "lakes").await;
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": lake(),
+            "properties": { "content": "crater lake with a sandy shore" }
+        }]
+    });
+    let upload = upload(
+        "/api/v1/workspaces/lakes/ingest",
+        "lakes.geojson",
+        geojson.to_string().as_bytes(),
+    );
+    let (status, body) = send(&app, upload).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(&app, "lakes").await;
+    app
+}
+
+#[tokio::test]
+async fn test_simplified_result_points_to_the_full_geometry() {
+    let app = lakes_workspace().await;
+
+    let request =
+        json!({ "text": "crater lake", "geometry_detail": "adaptive", "vertex_budget": 100 });
+    let (status, result) = send(&app, json_request("POST", QUERY_URI, request)).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert_eq!(result["geometry_detail"], "adaptive");
+    assert_eq!(result["vertex_budget"], 100);
+
+    let feature = &result["features"][0];
+    let ring = feature["geometry"]["coordinates"][0].as_array().unwrap();
+    assert!(ring.len() <= 100, "{} vertices", ring.len());
+    assert_eq!(feature["properties"]["geometry_simplified"], true);
+    assert_eq!(feature["properties"]["original_vertex_count"], LAKE_VERTICES);
+
+    let uri = format!(
+        "/api/v1/workspaces/lakes/datasets/{}/features/{}",
+        feature["properties"]["dataset_id"], feature["properties"]["feature_id"]
+    );
+    let (status, full) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", full);
+    assert_eq!(full["geometry"], lake());
+
+    // Features the dataset does not have are not found
+    let uri = format!(
+        "/api/v1/workspaces/lakes/datasets/{}/features/999999",
+        feature["properties"]["dataset_id"]
+    );
+    let (status, _) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_vertex_budget_too_small_is_rejected() {
+    let app = lakes_workspace().await;
+
+    let request =
+        json!({ "text": "crater lake", "geometry_detail": "adaptive", "vertex_budget": 3 });
+    let (status, body) = send(&app, json_request("POST", QUERY_URI, request)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+}
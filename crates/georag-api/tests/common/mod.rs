@@ -0,0 +1,108 @@
+//! Fixtures shared by the API integration tests
+//!
+//! Each test binary declares `mod common;` and uses what it needs, so not
+//! every item is used everywhere.
+
+#![allow(dead_code)]
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use georag_api::{create_router, AppState, EmbedderConfig, QueryConfig};
+use georag_store::memory::{
+    MemoryDocumentStore, MemorySpatialStore, MemoryStoreProvider, MemoryVectorStore,
+    MemoryWorkspaceStore,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+pub const BOUNDARY: &str = "georag-test-boundary";
+
+/// Empty in-memory state embedding with `mock:32`, serving the default workspace only
+pub fn default_only_state() -> AppState {
+    let embedder = EmbedderConfig {
+        model: "mock:32".to_string(),
+        dimensions: 32,
+        ..Default::default()
+    };
+    AppState::new(
+        Arc::new(MemorySpatialStore::new()),
+        Arc::new(MemoryVectorStore::new()),
+        Arc::new(MemoryDocumentStore::new()),
+        Arc::new(MemoryWorkspaceStore::new()),
+        embedder,
+        QueryConfig::default(),
+    )
+}
+
+/// Empty in-memory state embedding with `mock:32`, serving any workspace
+pub fn state() -> AppState {
+    default_only_state().with_store_provider(Arc::new(MemoryStoreProvider::new()))
+}
+
+/// Router over [`state`]
+pub fn app() -> Router {
+    create_router(Arc::new(state()))
+}
+
+/// Send `request`, returning the status and the JSON body, or null if it is not JSON
+pub async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+pub fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+pub fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+pub fn post(uri: &str) -> Request<Body> {
+    Request::post(uri).body(Body::empty()).unwrap()
+}
+
+/// Multipart upload of `content` as file `filename` to `uri`
+pub fn upload(uri: &str, filename: &str, content: &[u8]) -> Request<Body> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; \
+         filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend(format!("\r\n--{BOUNDARY}--\r\n").into_bytes());
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={BOUNDARY}"))
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Create workspace `name`
+pub async fn create_workspace(app: &Router, name: &str) {
+    let (status, body) =
+        send(app, json_request("POST", "/api/v1/workspaces", json!({ "name": name }))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+}
+
+/// Rebuild the index of workspace `name` and wait for the rebuild to finish
+pub async fn rebuild(app: &Router, name: &str) {
+    let uri = format!("/api/v1/workspaces/{name}/index/rebuild");
+    assert_eq!(send(app, post(&uri)).await.0, StatusCode::ACCEPTED);
+    for _ in 0..200 {
+        let uri = format!("/api/v1/workspaces/{name}/index/status");
+        let (_, status) = send(app, get(&uri)).await;
+        if status["rebuilding"] == json!(false) {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("rebuild of workspace '{}' did not finish", name);
+}
@@ -0,0 +1,222 @@
+//! Integration tests for reloading the configuration without a restart
+//!
+//! The server starts from a configuration file. Editing the file and asking
+//! for a reload must change the CORS origins and default quotas at once,
+//! refuse a new embedder dimension, and show up on the admin config
+//! endpoint; a file that no longer validates must leave everything as it was.
+//! Preflight requests from an allowed origin may use every method the routes
+//! accept.
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use georag_api::{cors_layer, create_router, ApiConfig, AppState, LiveConfig, QueryConfig};
+use georag_core::config::ConfigSource;
+use georag_core::redaction::Redactor;
+use georag_store::memory::{
+    MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore, MemoryWorkspaceStore,
+};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const STARTUP: &str = r#"
+[server]
+cors_origin = "http://maps.example"
+
+[quota]
+max_datasets = 2
+"#;
+
+/// Router serving the configuration in `path` as the API binary does
+fn app(path: &Path) -> Router {
+    let config = ApiConfig::load_from(Some(path)).unwrap();
+    let live = LiveConfig::new(&config, Redactor::default(), config.inspection_map());
+    let state = AppState::new(
+        Arc::new(MemorySpatialStore::new()),
+        Arc::new(MemoryVectorStore::new()),
+        Arc::new(MemoryDocumentStore::new()),
+        Arc::new(MemoryWorkspaceStore::new()),
+        config.embedder.clone(),
+        QueryConfig::default(),
+    )
+    .with_live_config(live)
+    .with_config_file(path);
+    let state = Arc::new(state);
+    create_router(state.clone()).layer(cors_layer(&state))
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+/// Origin CORS allows for a request sent from `origin`
+async fn allowed_origin(app: &Router, origin: &str) -> Option<String> {
+    let request = Request::get("/api/v1/formats")
+        .header(header::ORIGIN, origin)
+        .body(Body::empty())
+        .unwrap();
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|value| value.to_str().unwrap().to_string())
+}
+
+/// Methods CORS allows a page on `origin` to send to `uri`, from a preflight request
+async fn allowed_methods(app: &Router, origin: &str, uri: &str, method: &str) -> String {
+    let request = Request::options(uri)
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, method)
+        .body(Body::empty())
+        .unwrap();
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    assert!(response.status().is_success(), "{}", response.status());
+    response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_METHODS)
+        .map(|value| value.to_str().unwrap().to_string())
+        .unwrap_or_default()
+}
+
+async fn max_datasets(app: &Router) -> Value {
+    let request = Request::get("/api/v1/workspaces/default/usage").body(Body::empty()).unwrap();
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["quotas"]["max_datasets"].clone()
+}
+
+async fn reload(app: &Router) -> (StatusCode, Value) {
+    send(app, Request::post("/api/v1/admin/reload").body(Body::empty()).unwrap()).await
+}
+
+async fn admin_config(app: &Router) -> Value {
+    let (status, body) =
+        send(app, Request::get("/api/v1/admin/config").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+#[tokio::test]
+async fn test_reload_applies_reloadable_values_and_refuses_the_rest() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("georag.toml");
+    std::fs::write(&path, STARTUP).unwrap();
+    let app = app(&path);
+
+    assert_eq!(
+        allowed_origin(&app, "http://maps.example").await.as_deref(),
+        Some("http://maps.example")
+    );
+    assert_eq!(max_datasets(&app).await, 2);
+
+    std::fs::write(
+        &path,
+        r#"
+        [server]
+        cors_origin = "http://maps.example, http://atlas.example"
+
+        [quota]
+        max_datasets = 10
+
+        [embedder]
+        dimensions = 1024
+        "#,
+    )
+    .unwrap();
+    let (status, outcome) = reload(&app).await;
+    assert_eq!(status, StatusCode::OK, "{}", outcome);
+    assert_eq!(outcome["trigger"], "request");
+    assert_eq!(
+        outcome["applied"],
+        serde_json::json!(["quota.max_datasets", "server.cors_origin"])
+    );
+    assert_eq!(outcome["refused"][0]["key"], "embedder.dimensions");
+    assert_eq!(outcome["refused"].as_array().unwrap().len(), 1);
+
+    assert_eq!(
+        allowed_origin(&app, "http://atlas.example").await.as_deref(),
+        Some("http://atlas.example")
+    );
+    assert_eq!(allowed_origin(&app, "http://other.example").await, None);
+    assert_eq!(max_datasets(&app).await, 10);
+
+    let config = admin_config(&app).await;
+    assert_eq!(config["config"]["quota.max_datasets"]["value"], "10");
+    assert_eq!(
+        config["config"]["quota.max_datasets"]["source"],
+        serde_json::to_value(ConfigSource::File).unwrap()
+    );
+    // The refused change is not in effect
+    assert_eq!(config["config"]["embedder.dimensions"]["value"], "768");
+    assert!(config["last_reloaded"].is_string(), "{}", config);
+    assert_eq!(config["reloads"].as_array().unwrap().len(), 1);
+
+    // Every reload is recorded in the audit log next to the file
+    let audit = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+    assert!(audit.contains("\"event\":\"config_reloaded\""), "{}", audit);
+}
+
+#[tokio::test]
+async fn test_invalid_configuration_is_not_applied() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("georag.toml");
+    std::fs::write(&path, STARTUP).unwrap();
+    let app = app(&path);
+
+    std::fs::write(
+        &path,
+        "[server]\ncors_origin = \"http://atlas.example\"\n\n[quota]\nmax_datasets = \"many\"\n",
+    )
+    .unwrap();
+    let (status, body) = reload(&app).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+
+    // Unknown settings are rejected too
+    std::fs::write(&path, "[server]\ncors_origins = \"http://atlas.example\"\n").unwrap();
+    let (status, _) = reload(&app).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    assert_eq!(
+        allowed_origin(&app, "http://maps.example").await.as_deref(),
+        Some("http://maps.example")
+    );
+    assert_eq!(allowed_origin(&app, "http://atlas.example").await, None);
+    assert_eq!(max_datasets(&app).await, 2);
+
+    let config = admin_config(&app).await;
+    assert!(config["last_reloaded"].is_null());
+    let reloads = config["reloads"].as_array().unwrap();
+    assert_eq!(reloads.len(), 2);
+    assert!(reloads[0]["error"].as_str().unwrap().contains("quota.max_datasets"));
+    assert!(reloads[1]["error"].as_str().unwrap().contains("server.cors_origins"));
+}
+
+#[tokio::test]
+async fn test_preflight_allows_every_method_the_routes_accept() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("georag.toml");
+    std::fs::write(&path, STARTUP).unwrap();
+    let app = app(&path);
+
+    for (uri, method) in [
+        ("/api/v1/workspaces/default/settings", "PUT"),
+        ("/api/v1/presets/mobile", "PUT"),
+        ("/api/v1/events/cursors/search-sync", "PUT"),
+        ("/api/v1/workspaces/default", "PATCH"),
+        ("/api/v1/workspaces/default/areas/harbor", "DELETE"),
+    ] {
+        let methods = allowed_methods(&app, "http://maps.example", uri, method).await;
+        assert!(
+            methods.split(',').any(|allowed| allowed.trim() == method),
+            "{} {}: {}",
+            method,
+            uri,
+            methods
+        );
+    }
+}
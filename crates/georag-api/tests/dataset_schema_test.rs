@@ -0,0 +1,60 @@
+//! Integration tests for the dataset schema endpoint
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{default_only_state, get, send, upload};
+use georag_api::create_router;
+use serde_json::json;
+use std::sync::Arc;
+
+fn app() -> Router {
+    let state = default_only_state().with_chunk_properties(vec!["name".to_string()]);
+    create_router(Arc::new(state))
+}
+
+async fn ingest(app: &Router) -> String {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.8, -6.1] },
+                "properties": { "name": "Crane A", "capacity_t": 40 }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.9, -6.1] },
+                "properties": { "name": "Crane B" }
+            }
+        ]
+    });
+    let upload = upload("/api/v1/ingest", "cranes.geojson", geojson.to_string().as_bytes());
+    let (status, body) = send(app, upload).await;
+    assert!(status.is_success(), "{}", body);
+
+    let (_, datasets) = send(app, get("/api/v1/datasets")).await;
+    datasets[0]["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_dataset_schema_describes_properties() {
+    let app = app();
+    let id = ingest(&app).await;
+
+    let (status, schema) = send(&app, get(&format!("/api/v1/datasets/{}/schema", id))).await;
+    assert_eq!(status, StatusCode::OK, "{}", schema);
+
+    let properties = &schema["properties"]["properties"];
+    assert_eq!(properties["properties"]["name"]["type"], "string");
+    assert_eq!(properties["properties"]["capacity_t"]["type"], "integer");
+    let required = properties["required"].as_array().unwrap();
+    assert!(required.contains(&json!("name")));
+    assert!(!required.contains(&json!("capacity_t")));
+    assert_eq!(schema["x-georag"]["feature_count"], 2);
+    assert_eq!(schema["x-georag"]["chunking"]["chunk_properties"], json!(["name"]));
+
+    let (status, _) = send(&app, get("/api/v1/datasets/999999/schema")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
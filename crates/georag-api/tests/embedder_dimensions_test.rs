@@ -0,0 +1,148 @@
+//! Integration tests for the startup check of embedding dimensions
+//!
+//! A stand-in Ollama server returns 384-dimensional embeddings. The check
+//! compares that with the configured dimensions and with the index built
+//! with the model, and names the number to fix when they disagree.
+
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+use georag_api::EmbedderConfig;
+use georag_core::llm::DimensionCheckMode;
+use georag_core::models::{IndexState, INDEX_SCHEMA_VERSION};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Dimensions of the stand-in model's embeddings
+const ACTUAL: usize = 384;
+
+/// Serve a stand-in Ollama with `model` installed, counting embedding requests
+async fn fake_ollama(model: &str) -> (String, Arc<AtomicUsize>) {
+    let probes = Arc::new(AtomicUsize::new(0));
+    let tags = json!({ "models": [{ "name": format!("{}:latest", model) }] });
+    let app = Router::new()
+        .route(
+            "/api/tags",
+            get(move || {
+                let tags = tags.clone();
+                async move { Json(tags) }
+            }),
+        )
+        .route(
+            "/api/embeddings",
+            post(|State(probes): State<Arc<AtomicUsize>>| async move {
+                probes.fetch_add(1, Ordering::SeqCst);
+                Json(json!({ "embedding": vec![0.1; ACTUAL] }))
+            }),
+        )
+        .with_state(probes.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, probes)
+}
+
+fn config(model: &str, dimensions: usize, ollama_url: &str) -> EmbedderConfig {
+    EmbedderConfig {
+        model: model.to_string(),
+        dimensions,
+        ollama_url: ollama_url.to_string(),
+        ..Default::default()
+    }
+}
+
+fn index(embedder: &str, embedding_dim: usize) -> IndexState {
+    IndexState {
+        schema_version: INDEX_SCHEMA_VERSION,
+        hash: "abc123".to_string(),
+        built_at: Utc::now(),
+        embedder: embedder.to_string(),
+        chunk_count: 10,
+        embedding_dim,
+        dataset_built_at: Default::default(),
+        contents: None,
+        last_build_diff: None,
+        tokenizer: None,
+        score_baseline: None,
+    }
+}
+
+#[tokio::test]
+async fn test_each_mismatch_names_the_number_to_fix() {
+    let (url, probes) = fake_ollama("probe-embed").await;
+
+    let check = config("probe-embed", ACTUAL, &url).reconcile(None).await.unwrap();
+    assert_eq!(check.actual, Some(ACTUAL));
+    let indexed = index("ollama:probe-embed", ACTUAL);
+    assert!(config("probe-embed", ACTUAL, &url).reconcile(Some(&indexed)).await.is_ok());
+
+    // Wrong configured dimensions
+    let message = config("probe-embed", 768, &url)
+        .reconcile(Some(&indexed))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("configured 768, model returns 384, index built with 384"));
+    assert!(message.ends_with("Fix: set GEORAG_EMBEDDER_DIM to 384"), "{}", message);
+
+    // Index built with wrong dimensions
+    let stale = index("ollama:probe-embed", 768);
+    let message = config("probe-embed", ACTUAL, &url)
+        .reconcile(Some(&stale))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.ends_with("Fix: rebuild the index with probe-embed"), "{}", message);
+
+    // Both wrong, in agreement with each other
+    let message = config("probe-embed", 768, &url)
+        .reconcile(Some(&stale))
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(message.contains("configured 768, model returns 384, index built with 768"));
+    assert!(
+        message.ends_with("to 384 and rebuild the index with probe-embed"),
+        "{}",
+        message
+    );
+
+    // Indexes of other models are not compared
+    let other = index("ollama:nomic-embed-text", 768);
+    let check = config("probe-embed", ACTUAL, &url).reconcile(Some(&other)).await.unwrap();
+    assert_eq!(check.index, None);
+
+    // The model was probed once, however many checks ran
+    assert_eq!(probes.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_warn_only_check_serves_anyway() {
+    let (url, _) = fake_ollama("warn-embed").await;
+    let config = EmbedderConfig {
+        dimension_check: DimensionCheckMode::Warn,
+        ..config("warn-embed", 768, &url)
+    };
+
+    let check = config.reconcile(None).await.unwrap();
+    assert_eq!((check.configured, check.actual), (768, Some(ACTUAL)));
+    assert!(check.mismatch().is_some());
+}
+
+#[tokio::test]
+async fn test_unprobed_models_are_compared_with_the_index() {
+    // The mock is never probed; its spec gives its dimensions
+    let mock = config("mock:32", 32, "http://127.0.0.1:9");
+    let check = mock.reconcile(Some(&index("mock:32", 32))).await.unwrap();
+    assert_eq!(check.actual, None);
+    let message = mock.reconcile(Some(&index("mock:32", 16))).await.unwrap_err().to_string();
+    assert!(message.contains("configured 32, model not probed, index built with 16"));
+
+    // An unreachable Ollama skips the probe rather than failing the check
+    let offline = config("offline-embed", 768, "http://127.0.0.1:9");
+    let check = offline.reconcile(None).await.unwrap();
+    assert_eq!(check.actual, None);
+}
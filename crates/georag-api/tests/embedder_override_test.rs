@@ -0,0 +1,120 @@
+//! Integration tests for queries selecting another embedder model
+//!
+//! Workspace `parks` is indexed with `mock:32` and workspace `lakes` is not
+//! indexed. A server configured with `mock:16` that allows `mock:32` serves
+//! the override for `parks` only, since the other workspace's index does not
+//! hold `mock:32` vectors.
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{json_request, rebuild, send, upload};
+use georag_api::{create_router, AllowedEmbedder, AppState, EmbedderConfig};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn embedder(
+    model: &str,
+    dimensions: usize,
+    allowed: &str,
+    allowed_dimensions: usize,
+) -> EmbedderConfig {
+    EmbedderConfig {
+        model: model.to_string(),
+        dimensions,
+        allowed_models: vec![AllowedEmbedder {
+            model: allowed.to_string(),
+            dimensions: allowed_dimensions,
+        }],
+        ..Default::default()
+    }
+}
+
+fn state() -> AppState {
+    let mut state = common::state();
+    state.embedder_config = embedder("mock:32", 32, "mock:16", 16);
+    state
+}
+
+/// Create workspace `name` and upload a few points to it
+async fn create_workspace(app: &Router, name: &str) {
+    common::create_workspace(app, name).await;
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.8, -6.18] },
+                "properties": { "content": format!("{} by the river", name) }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.9, -6.2] },
+                "properties": { "content": format!("{} on the hill", name) }
+            }
+        ]
+    });
+    let uri = format!("/api/v1/workspaces/{name}/ingest");
+    let filename = format!("{name}.geojson");
+    let (status, body) = send(app, upload(&uri, &filename, geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+}
+
+async fn query(app: &Router, workspace: &str, model: &str) -> (StatusCode, Value) {
+    let body = json!({ "text": "river", "embedder_model": model });
+    send(
+        app,
+        json_request("POST", &format!("/api/v1/workspaces/{workspace}/query"), body),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_models_not_allowed_are_rejected() {
+    let app = create_router(Arc::new(state()));
+    create_workspace(&app, "parks").await;
+    rebuild(&app, "parks").await;
+
+    let (status, body) = query(&app, "parks", "nomic-embed-text").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    let details = body["details"].as_str().unwrap();
+    assert!(details.contains("mock:32") && details.contains("mock:16"), "{}", details);
+}
+
+#[tokio::test]
+async fn test_models_without_a_built_index_are_rejected() {
+    let app = create_router(Arc::new(state()));
+    create_workspace(&app, "parks").await;
+    rebuild(&app, "parks").await;
+
+    // Allowed, but the index of parks holds mock:32 vectors
+    let (status, body) = query(&app, "parks", "mock:16").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(body["details"].as_str().unwrap().contains("mock:32"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_override_is_served_from_the_workspace_index_only() {
+    let state = state();
+    let app = create_router(Arc::new(state.clone()));
+    create_workspace(&app, "parks").await;
+    create_workspace(&app, "lakes").await;
+    rebuild(&app, "parks").await;
+
+    // The same stores, now configured with mock:16 and allowing mock:32
+    let mut switched = state;
+    switched.embedder_config = embedder("mock:16", 16, "mock:32", 32);
+    let app = create_router(Arc::new(switched));
+
+    let (status, body) = query(&app, "parks", "mock:32").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["embedder_model"], "mock:32");
+    assert!(body["features"].is_array(), "{}", body);
+
+    // Another workspace's index does not make the model servable for lakes
+    let (status, body) = query(&app, "lakes", "mock:32").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert_eq!(body["details"], "Model of the workspace's index: none");
+}
@@ -0,0 +1,240 @@
+//! Integration tests for the change event feed
+//!
+//! Workspace `parks` gets three datasets, one of which is deleted, and an
+//! index build. Replaying the feed into a fresh spatial store yields the
+//! datasets and features the workspace lists, and consumers resume from
+//! their stored cursors. Dataset and feature payloads are redacted like any
+//! response, with the rules of every workspace.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{get, json_request, rebuild, send, state, upload};
+use georag_api::create_router;
+use georag_core::models::{ChangeEntity, ChangeEvent, WorkspaceId};
+use georag_core::redaction::{RedactionConfig, Redactor, REDACTED_MARKER};
+use georag_service::EventReplay;
+use georag_store::memory::{MemoryEventLog, MemorySpatialStore};
+use georag_store::ports::SpatialStore;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/parks";
+
+/// Create workspace `parks`, returning its ID
+async fn create_workspace(app: &Router) -> WorkspaceId {
+    let (status, body) =
+        send(app, json_request("POST", "/api/v1/workspaces", json!({ "name": "parks" }))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+/// Upload `name`.geojson with `count` points, returning the dataset ID
+async fn ingest(app: &Router, name: &str, count: usize) -> u64 {
+    let features: Vec<Value> = (0..count)
+        .map(|i| {
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.8 + i as f64 * 0.01, -6.18] },
+                "properties": { "content": format!("{} feature {}", name, i) }
+            })
+        })
+        .collect();
+    let geojson = json!({ "type": "FeatureCollection", "features": features });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let filename = format!("{name}.geojson");
+    let (status, body) = send(app, upload(&uri, &filename, geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+    body["dataset_id"].as_u64().unwrap()
+}
+
+/// Read the whole feed in pages of `limit`
+async fn read_feed(app: &Router, limit: usize) -> Vec<ChangeEvent> {
+    let mut events = Vec::new();
+    let mut after_seq = 0;
+    loop {
+        let (status, page) =
+            send(app, get(&format!("/api/v1/events?after_seq={after_seq}&limit={limit}"))).await;
+        assert_eq!(status, StatusCode::OK, "{}", page);
+        let batch: Vec<ChangeEvent> = serde_json::from_value(page["events"].clone()).unwrap();
+        if batch.is_empty() {
+            assert_eq!(page["next_after_seq"], after_seq);
+            return events;
+        }
+        after_seq = page["next_after_seq"].as_u64().unwrap();
+        events.extend(batch);
+    }
+}
+
+#[tokio::test]
+async fn test_replaying_the_feed_rebuilds_the_workspace() {
+    let app = create_router(Arc::new(state()));
+    let workspace_id = create_workspace(&app).await;
+
+    ingest(&app, "parks", 3).await;
+    let lakes = ingest(&app, "lakes", 2).await;
+    ingest(&app, "trails", 4).await;
+    rebuild(&app, "parks").await;
+    let delete = Request::delete(format!("{WORKSPACE_URI}/datasets/{lakes}"))
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(&app, delete).await.0, StatusCode::OK);
+
+    let events = read_feed(&app, 2).await;
+    assert!(!events.is_empty());
+    for (expected, event) in (1..).zip(&events) {
+        assert_eq!(event.seq, expected, "sequence numbers have no gaps");
+    }
+
+    let target = Arc::new(MemorySpatialStore::new());
+    let mut replay = EventReplay::new(workspace_id, target.clone());
+    replay.apply(&events).await.unwrap();
+    // A consumer retrying a page it already applied changes nothing
+    assert_eq!(replay.apply(&events[events.len() - 2..]).await.unwrap(), 0);
+
+    let (status, listed) = send(&app, get(&format!("{WORKSPACE_URI}/datasets"))).await;
+    assert_eq!(status, StatusCode::OK, "{}", listed);
+    let mut expected: Vec<(String, usize)> = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            (
+                d["name"].as_str().unwrap().to_string(),
+                d["feature_count"].as_u64().unwrap() as usize,
+            )
+        })
+        .collect();
+    expected.sort();
+
+    let mut replayed = Vec::new();
+    for dataset in target.list_datasets().await.unwrap() {
+        let features = target.get_features_for_dataset(dataset.id).await.unwrap();
+        assert_eq!(features.len(), dataset.feature_count, "{}", dataset.name);
+        replayed.push((dataset.name, features.len()));
+    }
+    replayed.sort();
+    assert_eq!(replayed, expected);
+    assert_eq!(replayed.len(), 2);
+}
+
+#[tokio::test]
+async fn test_consumers_resume_from_their_cursor() {
+    let app = create_router(Arc::new(state()));
+    create_workspace(&app).await;
+    ingest(&app, "parks", 1).await;
+
+    let (status, cursor) = send(&app, get("/api/v1/events/cursors/indexer")).await;
+    assert_eq!(status, StatusCode::OK, "{}", cursor);
+    assert_eq!(cursor["seq"], 0);
+
+    let (_, page) = send(&app, get("/api/v1/events?consumer=indexer")).await;
+    let latest = page["latest_seq"].as_u64().unwrap();
+    assert_eq!(page["events"].as_array().unwrap().len() as u64, latest);
+    assert_eq!(page["next_after_seq"], latest);
+
+    let ack = json_request("PUT", "/api/v1/events/cursors/indexer", json!({ "seq": latest }));
+    assert_eq!(send(&app, ack).await.0, StatusCode::OK);
+    let (_, page) = send(&app, get("/api/v1/events?consumer=indexer")).await;
+    assert!(page["events"].as_array().unwrap().is_empty(), "{}", page);
+
+    // Waiting readers get the next ingest's events
+    let reader = app.clone();
+    let wait = tokio::spawn(async move {
+        send(&reader, get(&format!("/api/v1/events?after_seq={latest}&wait=30"))).await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    ingest(&app, "lakes", 1).await;
+    let (status, page) = tokio::time::timeout(std::time::Duration::from_secs(10), wait)
+        .await
+        .expect("the ingest should end the wait")
+        .unwrap();
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(page["events"][0]["seq"], latest + 1);
+    assert_eq!(page["events"][0]["entity"], "dataset");
+
+    let ahead = json_request("PUT", "/api/v1/events/cursors/indexer", json!({ "seq": 1000 }));
+    assert_eq!(send(&app, ahead).await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        send(&app, get("/api/v1/events/cursors/bad%20name")).await.0,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(send(&app, get("/api/v1/events?limit=5000")).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_readers_behind_pruned_events_get_gone() {
+    let state = state().with_event_log(Arc::new(MemoryEventLog::with_capacity(2)));
+    let app = create_router(Arc::new(state));
+    create_workspace(&app).await;
+    ingest(&app, "parks", 1).await;
+    ingest(&app, "lakes", 1).await;
+
+    let (status, body) = send(&app, get("/api/v1/events?after_seq=0")).await;
+    assert_eq!(status, StatusCode::GONE, "{}", body);
+    assert_eq!(body["code"], "events_pruned");
+
+    let (_, page) = send(&app, get("/api/v1/events?after_seq=2")).await;
+    assert_eq!(page["earliest_seq"], 3);
+    assert_eq!(page["latest_seq"], 4);
+}
+
+/// Payloads of the feed's first page of `entity` events
+fn payloads(page: &Value, entity: ChangeEntity) -> Vec<Value> {
+    let events: Vec<ChangeEvent> = serde_json::from_value(page["events"].clone()).unwrap();
+    events
+        .into_iter()
+        .filter(|event| event.entity == entity)
+        .filter_map(|event| event.payload)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_payloads_are_redacted() {
+    let rules = RedactionConfig {
+        properties: vec!["content".to_string()],
+        patterns: Vec::new(),
+    };
+    let state = state().with_redactor(Redactor::new(&rules).unwrap());
+    let app = create_router(Arc::new(state));
+    create_workspace(&app).await;
+    ingest(&app, "parks", 2).await;
+
+    let (status, page) = send(&app, get("/api/v1/events")).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert!(!page.to_string().contains("parks feature"), "{}", page);
+
+    let features = payloads(&page, ChangeEntity::Feature);
+    let features = features[0].as_array().unwrap();
+    assert_eq!(features.len(), 2);
+    for feature in features {
+        assert_eq!(feature["properties"]["content"], REDACTED_MARKER);
+    }
+
+    // The dataset carries its preview, whose property keys are redacted too
+    let datasets = payloads(&page, ChangeEntity::Dataset);
+    assert_eq!(datasets[0]["name"], "parks");
+    assert_eq!(datasets[0]["format"]["preview"]["property_keys"], json!([REDACTED_MARKER]));
+}
+
+#[tokio::test]
+async fn test_payloads_are_redacted_with_workspace_rules() {
+    let app = create_router(Arc::new(state()));
+    create_workspace(&app).await;
+    let settings = json!({ "redaction": { "patterns": [r"feature \d+"] } });
+    let (status, body) =
+        send(&app, json_request("PUT", &format!("{WORKSPACE_URI}/settings"), settings)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    ingest(&app, "parks", 2).await;
+
+    // The feed is not scoped to the workspace, yet its rules apply
+    let (status, page) = send(&app, get("/api/v1/events")).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    let features = payloads(&page, ChangeEntity::Feature);
+    for feature in features[0].as_array().unwrap() {
+        assert_eq!(feature["properties"]["content"], "parks [REDACTED]");
+    }
+    assert!(!payloads(&page, ChangeEntity::Dataset).is_empty(), "{}", page);
+}
@@ -0,0 +1,75 @@
+//! Snapshot tests for the responses of the ingest endpoint
+//!
+//! `tests/snapshots/ingest.json` holds what the endpoint answered before
+//! ingestion moved to the shared ingest service: the upload response, the
+//! dataset listing and the error for a file that cannot be read. Fields added
+//! to these responses since are not part of the snapshot and not compared.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::{app, get, send, upload};
+use serde_json::{json, Value};
+
+const INGEST: &str = "/api/v1/ingest";
+
+const SNAPSHOT: &str = include_str!("snapshots/ingest.json");
+
+const PARKS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+      "properties": { "name": "Pocket Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]] },
+      "properties": { "name": "Central Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.9, -6.1] },
+      "properties": { "name": "Corner Garden" }
+    }
+  ]
+}"#;
+
+/// The parts of `actual` the snapshot has, so fields added since are ignored
+fn project(actual: &Value, snapshot: &Value) -> Value {
+    match (actual, snapshot) {
+        (Value::Object(actual), Value::Object(snapshot)) => snapshot
+            .iter()
+            .map(|(key, expected)| {
+                let value = actual.get(key).map_or(Value::Null, |v| project(v, expected));
+                (key.clone(), value)
+            })
+            .collect(),
+        (Value::Array(actual), Value::Array(snapshot)) => actual
+            .iter()
+            .zip(snapshot.iter().chain(std::iter::repeat(&Value::Null)))
+            .map(|(value, expected)| project(value, expected))
+            .collect(),
+        (actual, _) => actual.clone(),
+    }
+}
+
+#[tokio::test]
+async fn test_responses_match_snapshot() {
+    let snapshot: Value = serde_json::from_str(SNAPSHOT).unwrap();
+    let app = app();
+
+    let (status, ingest) = send(&app, upload(INGEST, "parks.geojson", PARKS.as_bytes())).await;
+    assert_eq!(status, StatusCode::OK, "{}", ingest);
+    assert_eq!(project(&ingest, &snapshot["ingest"]), snapshot["ingest"]);
+
+    let (status, datasets) = send(&app, get("/api/v1/datasets")).await;
+    assert_eq!(status, StatusCode::OK, "{}", datasets);
+    assert_eq!(project(&datasets, &snapshot["datasets"]), snapshot["datasets"]);
+
+    // Uploads are not validated before reading, so the reader's error is reported
+    let (status, error) = send(&app, upload(INGEST, "broken.geojson", b"{ not json")).await;
+    let unreadable = json!({ "status": status.as_u16(), "error": error["error"] });
+    assert_eq!(unreadable, snapshot["unreadable"], "{}", error);
+}
@@ -0,0 +1,175 @@
+//! Integration tests for point queries and the workspace defaults they fall back to
+//!
+//! A workspace stores a default predicate and radius, the way a deployment
+//! serving mobile clients would. Queries sending only a location get nearby
+//! results and learn what was assumed; fields a query sets are kept as sent.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{app, create_workspace, json_request, rebuild, send};
+use serde_json::{json, Value};
+
+const QUERY_URI: &str = "/api/v1/workspaces/mobile/query";
+
+/// Multipart upload of a lighthouse in Jakarta and a windmill about 350 km away
+fn upload() -> Request<Body> {
+    let place = |coordinates: [f64; 2], content: &str| {
+        json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": coordinates },
+            "properties": { "content": content }
+        })
+    };
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [
+            place([106.8, -6.1], "lighthouse on the cape"),
+            place([110.0, -7.0], "windmill by the canal"),
+        ]
+    });
+    common::upload(
+        "/api/v1/workspaces/mobile/ingest",
+        "places.geojson",
+        geojson.to_string().as_bytes(),
+    )
+}
+
+/// App with workspace `mobile` defaulting to dwithin 2 km, with its index built
+async fn mobile_workspace() -> Router {
+    let app = app();
+    create_workspace(&app, "mobile").await;
+
+    let settings = json!({
+        "default_spatial_predicate": "dwithin",
+        "default_radius": 2.0,
+        "default_radius_unit": "Kilometers"
+    });
+    let (status, body) =
+        send(&app, json_request("PUT", "/api/v1/workspaces/mobile/settings", settings)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let (status, body) = send(&app, upload()).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(&app, "mobile").await;
+    app
+}
+
+async fn query(app: &Router, body: Value) -> Value {
+    let (status, body) = send(app, json_request("POST", QUERY_URI, body)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+#[tokio::test]
+async fn test_point_alone_uses_the_workspace_defaults() {
+    let app = mobile_workspace().await;
+
+    let result =
+        query(&app, json!({ "text": "landmark", "point": [106.81, -6.1], "explain": true })).await;
+    let body = result.to_string();
+    assert!(body.contains("lighthouse"));
+    assert!(!body.contains("windmill"));
+    assert_eq!(
+        result["point_defaults"],
+        json!({ "predicate": "DWithin", "radius": { "value": 2.0, "unit": "Kilometers" } })
+    );
+}
+
+#[tokio::test]
+async fn test_explicit_fields_are_not_overridden() {
+    let app = mobile_workspace().await;
+
+    // An explicit radius reaches the windmill; only the predicate is assumed
+    let result = query(
+        &app,
+        json!({
+            "text": "landmark",
+            "point": [106.81, -6.1],
+            "radius": { "distance": 500, "unit": "km" },
+            "explain": true
+        }),
+    )
+    .await;
+    assert!(result.to_string().contains("windmill"));
+    assert_eq!(result["point_defaults"], json!({ "predicate": "DWithin" }));
+
+    // Nothing is assumed when the query sets both
+    let result = query(
+        &app,
+        json!({
+            "text": "landmark",
+            "point": [106.81, -6.1],
+            "predicate": "dwithin",
+            "radius": { "distance": 100, "unit": "m" },
+            "explain": true
+        }),
+    )
+    .await;
+    assert!(result.get("point_defaults").is_none());
+    assert!(!result.to_string().contains("lighthouse"));
+}
+
+#[tokio::test]
+async fn test_invalid_point_queries_are_rejected() {
+    let app = mobile_workspace().await;
+
+    for body in [
+        json!({ "text": "landmark", "radius": { "distance": 1 } }),
+        json!({ "text": "landmark", "point": [106.8, -6.1], "bbox": [106.0, -7.0, 107.0, -6.0] }),
+        json!({ "text": "landmark", "point": [106.8, -6.1], "predicate": "within",
+                "radius": { "distance": 1 } }),
+        json!({ "text": "landmark", "point": [-6.1, 106.8] }),
+        json!({ "text": "landmark", "geometry": { "type": "Point", "coordinates": [106.8, -6.1] },
+                "predicate": "dwithin" }),
+    ] {
+        let (status, response) = send(&app, json_request("POST", QUERY_URI, body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", body, response);
+    }
+
+    let settings = json!({ "default_radius": -1.0 });
+    let (status, _) =
+        send(&app, json_request("PUT", "/api/v1/workspaces/mobile/settings", settings)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_excluded_geometries_drop_results() {
+    let app = mobile_workspace().await;
+    let around_windmill = json!({
+        "type": "Polygon",
+        "coordinates": [[[109.5, -7.5], [110.5, -7.5], [110.5, -6.5], [109.5, -6.5], [109.5, -7.5]]]
+    });
+
+    let result = query(
+        &app,
+        json!({
+            "text": "landmark",
+            "point": [106.81, -6.1],
+            "radius": { "distance": 500, "unit": "km" },
+            "exclude_geometries": [around_windmill],
+            "explain": true
+        }),
+    )
+    .await;
+    let body = result.to_string();
+    assert!(body.contains("lighthouse"));
+    assert!(!body.contains("windmill"));
+    assert_eq!(result["exclusions"]["areas"], json!(1));
+    assert_eq!(result["exclusions"]["features_excluded"], json!(1));
+
+    // Exclusions narrow a filter; alone they are rejected
+    for body in [
+        json!({ "text": "landmark", "exclude_geometries": [around_windmill] }),
+        json!({ "text": "landmark", "point": [106.8, -6.1], "exclude_geometries": [{ "type": "Circle" }] }),
+    ] {
+        let (status, response) = send(&app, json_request("POST", QUERY_URI, body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", body, response);
+    }
+    let unknown = json!({ "text": "landmark", "point": [106.8, -6.1], "exclude_areas": ["park"] });
+    let (status, _) = send(&app, json_request("POST", QUERY_URI, unknown)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
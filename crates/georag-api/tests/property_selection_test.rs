@@ -0,0 +1,128 @@
+//! Integration tests for property selection in responses
+//!
+//! Workspace `parks` holds one park with a few attributes. Query results keep
+//! only the keys a request includes, or drop the keys it excludes, in every
+//! format, while score, excerpt and document_path are always kept. Dataset
+//! samples and features take the same selection as query parameters.
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{app, create_workspace, get, json_request, rebuild, send, upload};
+use serde_json::{json, Value};
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/parks";
+
+async fn send_raw(app: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+/// App with workspace `parks` holding one park, with its index built
+async fn parks_workspace() -> Router {
+    let app = app();
+    create_workspace(&app, "parks").await;
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+            "properties": {
+                "content": "city park with a playground",
+                "name": "Taman Suropati",
+                "category": "park",
+                "owner": "city"
+            }
+        }]
+    });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let (status, body) =
+        send(&app, upload(&uri, "parks.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(&app, "parks").await;
+    app
+}
+
+fn keys(object: &Value) -> Vec<&str> {
+    object.as_object().unwrap().keys().map(String::as_str).collect()
+}
+
+#[tokio::test]
+async fn test_query_outputs_honor_the_selection() {
+    let app = parks_workspace().await;
+    let uri = format!("{WORKSPACE_URI}/query");
+
+    let include = json!({ "text": "park", "properties": { "include": ["feature_id"] } });
+    let (status, result) = send(&app, json_request("POST", &uri, include.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert_eq!(
+        keys(&result["features"][0]["properties"]),
+        vec!["document_path", "excerpt", "feature_id", "score"]
+    );
+
+    let (status, rows) =
+        send(&app, json_request("POST", &format!("{uri}?format=json"), include)).await;
+    assert_eq!(status, StatusCode::OK, "{}", rows);
+    assert_eq!(keys(&rows[0]), vec!["document_path", "excerpt", "feature_id", "score"]);
+
+    // Excluded keys leave the CSV columns; built-in keys cannot be excluded
+    let exclude = json!({ "text": "park", "properties": { "exclude": ["chunk_id", "score"] } });
+    let (status, csv) =
+        send_raw(&app, json_request("POST", &format!("{uri}?format=csv"), exclude.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", csv);
+    assert_eq!(csv.split("\r\n").next(), Some("score,excerpt,document_path,feature_id,lon,lat"));
+
+    let (_, result) = send(&app, json_request("POST", &uri, exclude)).await;
+    let properties = &result["features"][0]["properties"];
+    assert!(properties.get("chunk_id").is_none(), "{}", properties);
+    assert!(properties.get("centroid").is_some(), "{}", properties);
+    assert!(properties.get("score").is_some(), "{}", properties);
+}
+
+#[tokio::test]
+async fn test_including_and_excluding_a_key_is_rejected() {
+    let app = parks_workspace().await;
+
+    let request = json!({
+        "text": "park",
+        "properties": { "include": ["lon", "lat"], "exclude": ["lat"] }
+    });
+    let (status, body) =
+        send(&app, json_request("POST", &format!("{WORKSPACE_URI}/query"), request)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(body["details"].as_str().unwrap().contains("lat"), "{}", body);
+
+    let (_, datasets) = send(&app, get(&format!("{WORKSPACE_URI}/datasets"))).await;
+    let uri = format!(
+        "{WORKSPACE_URI}/datasets/{}/sample?include=name&exclude=name",
+        datasets[0]["id"]
+    );
+    assert_eq!(send(&app, get(&uri)).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_dataset_features_honor_the_selection() {
+    let app = parks_workspace().await;
+    let (_, datasets) = send(&app, get(&format!("{WORKSPACE_URI}/datasets"))).await;
+    let dataset_uri = format!("{WORKSPACE_URI}/datasets/{}", datasets[0]["id"]);
+
+    let (status, sample) =
+        send(&app, get(&format!("{dataset_uri}/sample?include=name,owner"))).await;
+    assert_eq!(status, StatusCode::OK, "{}", sample);
+    let feature = &sample["features"][0];
+    assert_eq!(keys(&feature["properties"]), vec!["name", "owner"]);
+
+    let uri = format!("{dataset_uri}/features/{}?exclude=owner,content", feature["id"]);
+    let (status, feature) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", feature);
+    let properties = &feature["properties"];
+    assert_eq!(properties["name"], "Taman Suropati");
+    assert_eq!(properties["category"], "park");
+    assert!(properties.get("owner").is_none() && properties.get("content").is_none());
+}
@@ -0,0 +1,110 @@
+//! Integration tests for operators in query text
+//!
+//! Workspace `bali` holds a drain report inside the saved area `ubud` and
+//! one far outside it. With `parse_operators`, `near:ubud within:1km` in the
+//! text becomes the spatial filter and the response says how the text was
+//! read; without it the text is searched as written.
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{app, create_workspace, json_request, rebuild, send, upload};
+use serde_json::{json, Value};
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/bali";
+
+fn report(lon: f64, lat: f64, content: &str, reported_at: &str) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": { "type": "Point", "coordinates": [lon, lat] },
+        "properties": { "content": content, "reported_at": reported_at }
+    })
+}
+
+/// App with workspace `bali`, area `ubud` and two indexed drain reports
+async fn bali() -> Router {
+    let app = app();
+    create_workspace(&app, "bali").await;
+
+    let ubud = json!({
+        "name": "ubud",
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[115.25, -8.52], [115.27, -8.52], [115.27, -8.50],
+                             [115.25, -8.50], [115.25, -8.52]]]
+        }
+    });
+    let (status, body) =
+        send(&app, json_request("POST", &format!("{WORKSPACE_URI}/areas"), ubud)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [
+            report(115.26, -8.51, "blocked drain by the market", "2023-04-18"),
+            report(115.17, -8.72, "blocked drain near the beach", "2023-05-02")
+        ]
+    });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let (status, body) =
+        send(&app, upload(&uri, "reports.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(&app, "bali").await;
+    app
+}
+
+async fn query(app: &Router, request: Value) -> Value {
+    let uri = format!("{WORKSPACE_URI}/query");
+    let (status, result) = send(app, json_request("POST", &uri, request)).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    result
+}
+
+#[tokio::test]
+async fn test_operators_become_filters_when_asked_for() {
+    let app = bali().await;
+    let text = "blocked drain near:ubud within:1km since:2023 dataset:reports";
+
+    let result = query(
+        &app,
+        json!({
+            "text": text,
+            "parse_operators": true,
+            "time_property": "reported_at",
+            "explain": true
+        }),
+    )
+    .await;
+    assert_eq!(result["features"].as_array().unwrap().len(), 1, "{}", result);
+    assert_eq!(result["operators"]["text"], "blocked drain");
+    assert_eq!(result["operators"]["applied"].as_array().unwrap().len(), 4, "{}", result);
+    assert!(result["operators"].get("ignored").is_none(), "{}", result);
+    assert_eq!(result["named_area"]["name"], "ubud");
+
+    // Without the flag the operators are plain text and both reports are found
+    let result = query(&app, json!({ "text": text })).await;
+    assert_eq!(result["features"].as_array().unwrap().len(), 2, "{}", result);
+    assert!(result.get("operators").is_none());
+}
+
+#[tokio::test]
+async fn test_malformed_operators_never_fail_the_query() {
+    let app = bali().await;
+
+    let text = r#"blocked drain near:kuta within:far since:2023-13 near:"ubud"#;
+    let result = query(&app, json!({ "text": text, "parse_operators": true })).await;
+    assert_eq!(result["features"].as_array().unwrap().len(), 2, "{}", result);
+    assert_eq!(
+        result["operators"]["text"],
+        r#"blocked drain within:far since:2023-13 near:"ubud"#
+    );
+    let ignored: Vec<&str> = result["operators"]["ignored"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["token"].as_str().unwrap())
+        .collect();
+    assert_eq!(ignored, vec!["within:far", "since:2023-13", r#"near:"ubud"#, "near:kuta"]);
+}
@@ -0,0 +1,177 @@
+//! Integration tests for paging through query results
+//!
+//! A paged query hands out a cursor with its first page; the next pages are
+//! served from the results it ranked. Cursors stop working once those results
+//! expire or the index is rebuilt, with a code telling the two apart, and
+//! only in the workspace whose query handed them out.
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use georag_api::create_router;
+use georag_service::QueryPages;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn app(pages: QueryPages) -> Router {
+    create_router(Arc::new(common::state().with_query_pages(pages)))
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Option<String>, Value) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let cursor = response
+        .headers()
+        .get("x-georag-next-cursor")
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, cursor, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn post_json(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Ingest `n` market stalls and rebuild the index
+async fn ingest_and_rebuild(app: &Router, file: &str, n: usize) {
+    let features: Vec<Value> = (0..n)
+        .map(|i| {
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.8 + i as f64 / 100.0, -6.2] },
+                "properties": { "content": format!("market stall number {} in {}", i, file) }
+            })
+        })
+        .collect();
+    let geojson = json!({ "type": "FeatureCollection", "features": features });
+    let upload = common::upload(
+        "/api/v1/ingest",
+        &format!("{file}.geojson"),
+        geojson.to_string().as_bytes(),
+    );
+    let (status, _, body) = send(app, upload).await;
+    assert!(status.is_success(), "{}", body);
+
+    common::rebuild(app, "default").await;
+}
+
+fn first_page(page_size: usize) -> Request<Body> {
+    post_json(
+        "/api/v1/query",
+        json!({ "text": "market stall", "top_k": 12, "page_size": page_size }),
+    )
+}
+
+fn next_page(cursor: &str) -> Request<Body> {
+    post_json("/api/v1/query/next", json!({ "cursor": cursor }))
+}
+
+fn paths(page: &Value) -> Vec<String> {
+    page["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| format!("{}#{}", f["properties"]["document_path"], f["properties"]["excerpt"]))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_pages_cover_the_ranked_results_once() {
+    let app = app(QueryPages::default());
+    ingest_and_rebuild(&app, "stalls", 12).await;
+
+    let (status, header_cursor, page) = send(&app, first_page(5)).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(page["page"], json!({ "offset": 0, "size": 5, "total": 12 }));
+    assert_eq!(header_cursor.as_deref(), page["next_cursor"].as_str());
+
+    let mut seen = paths(&page);
+    let mut cursor = page["next_cursor"].as_str().map(str::to_string);
+    let mut offsets = vec![0];
+    while let Some(next) = cursor {
+        let (status, _, page) = send(&app, next_page(&next)).await;
+        assert_eq!(status, StatusCode::OK, "{}", page);
+        offsets.push(page["page"]["offset"].as_u64().unwrap());
+        seen.extend(paths(&page));
+        cursor = page["next_cursor"].as_str().map(str::to_string);
+    }
+
+    assert_eq!(offsets, vec![0, 5, 10]);
+    assert_eq!(seen.len(), 12);
+    assert_eq!(seen.iter().collect::<HashSet<_>>().len(), 12);
+}
+
+#[tokio::test]
+async fn test_oversized_pages_are_refused() {
+    let app = app(QueryPages::default().with_max_page_size(10));
+
+    let (status, _, body) = send(&app, first_page(11)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    let (status, _, body) = send(&app, first_page(0)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+    let (status, _, body) = send(&app, next_page("not-a-cursor")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_cursor");
+}
+
+#[tokio::test]
+async fn test_cursor_expires() {
+    let app = app(QueryPages::new(Duration::from_millis(100)));
+    ingest_and_rebuild(&app, "stalls", 12).await;
+
+    let (_, _, page) = send(&app, first_page(5)).await;
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let (status, _, body) = send(&app, next_page(&cursor)).await;
+    assert_eq!(status, StatusCode::GONE, "{}", body);
+    assert_eq!(body["code"], "cursor_expired");
+}
+
+#[tokio::test]
+async fn test_rebuild_invalidates_cursors() {
+    let app = app(QueryPages::default());
+    ingest_and_rebuild(&app, "stalls", 12).await;
+
+    let (_, _, page) = send(&app, first_page(5)).await;
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+    ingest_and_rebuild(&app, "more-stalls", 3).await;
+
+    let (status, _, body) = send(&app, next_page(&cursor)).await;
+    assert_eq!(status, StatusCode::GONE, "{}", body);
+    assert_eq!(body["code"], "cursor_invalidated");
+
+    // Running the query again pages over the new index
+    let (status, _, page) = send(&app, first_page(5)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _, _) = send(&app, next_page(page["next_cursor"].as_str().unwrap())).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_cursors_are_refused_in_another_workspace() {
+    let app = app(QueryPages::default());
+    ingest_and_rebuild(&app, "stalls", 12).await;
+    let create = post_json("/api/v1/workspaces", json!({ "name": "harbour" }));
+    assert_eq!(send(&app, create).await.0, StatusCode::CREATED);
+
+    let (_, _, page) = send(&app, first_page(5)).await;
+    let cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+    let next = post_json("/api/v1/workspaces/harbour/query/next", json!({ "cursor": cursor }));
+    let (status, _, body) = send(&app, next).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(body["code"], "cursor_not_found");
+
+    // The workspace that ran the query still pages through its results
+    let (status, _, body) = send(&app, next_page(&cursor)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
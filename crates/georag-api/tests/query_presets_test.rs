@@ -0,0 +1,149 @@
+//! Integration tests for workspace query presets
+//!
+//! Presets are saved, listed and deleted through the preset routes and
+//! checked like query requests. A query takes the preset it names, or the
+//! workspace default, and its own options win over the preset's.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{app, create_workspace, json_request, rebuild, send, upload};
+use serde_json::{json, Value};
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/parks";
+
+fn empty_request(method: &str, uri: &str) -> Request<Body> {
+    Request::builder().method(method).uri(uri).body(Body::empty()).unwrap()
+}
+
+/// Create workspace `parks` with two parks and build its index
+async fn parks_workspace() -> Router {
+    let app = app();
+    create_workspace(&app, "parks").await;
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+                "properties": { "content": "city park with a playground" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [106.85, -6.2] },
+                "properties": { "content": "river park with a jogging track" }
+            }
+        ]
+    });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let (status, body) =
+        send(&app, upload(&uri, "parks.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(&app, "parks").await;
+    app
+}
+
+async fn put_preset(app: &Router, name: &str, options: Value) -> (StatusCode, Value) {
+    send(app, json_request("PUT", &format!("{WORKSPACE_URI}/presets/{name}"), options)).await
+}
+
+async fn query(app: &Router, request: Value) -> (StatusCode, Value) {
+    send(app, json_request("POST", &format!("{WORKSPACE_URI}/query"), request)).await
+}
+
+#[tokio::test]
+async fn test_presets_are_saved_listed_and_checked() {
+    let app = parks_workspace().await;
+
+    let (status, preset) = put_preset(&app, "Planners", json!({ "top_k": 25 })).await;
+    assert_eq!(status, StatusCode::OK, "{}", preset);
+    assert_eq!(
+        preset,
+        json!({ "name": "planners", "default": false, "options": { "top_k": 25 } })
+    );
+    let mobile = json!({ "top_k": 1, "geometry_detail": "centroid" });
+    assert_eq!(put_preset(&app, "mobile", mobile).await.0, StatusCode::OK);
+
+    // Options are checked as in a query request
+    let (status, body) = put_preset(&app, "broken", json!({ "rerank": "semantic" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    let (status, _) = put_preset(&app, "broken", json!({ "coordinate_precision": 20 })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = put_preset(&app, "broken", json!({ "topk": 5 })).await;
+    assert!(status.is_client_error());
+
+    let uri = format!("{WORKSPACE_URI}/presets/mobile/default");
+    let (status, preset) = send(&app, empty_request("PUT", &uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", preset);
+    assert_eq!(preset["default"], true);
+
+    let (status, presets) =
+        send(&app, empty_request("GET", &format!("{WORKSPACE_URI}/presets"))).await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<(&str, bool)> = presets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| (p["name"].as_str().unwrap(), p["default"].as_bool().unwrap()))
+        .collect();
+    assert_eq!(names, [("mobile", true), ("planners", false)]);
+
+    // Deleting the default preset leaves no default
+    let uri = format!("{WORKSPACE_URI}/presets/mobile");
+    assert_eq!(send(&app, empty_request("DELETE", &uri)).await.0, StatusCode::OK);
+    assert_eq!(send(&app, empty_request("GET", &uri)).await.0, StatusCode::NOT_FOUND);
+    let (status, settings) =
+        send(&app, empty_request("GET", &format!("{WORKSPACE_URI}/settings"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(settings["settings"].get("default_query_preset").is_none(), "{}", settings);
+}
+
+#[tokio::test]
+async fn test_queries_take_the_default_or_named_preset() {
+    let app = parks_workspace().await;
+    let mobile = json!({ "top_k": 1, "geometry_detail": "centroid", "explain": true });
+    assert_eq!(put_preset(&app, "mobile", mobile).await.0, StatusCode::OK);
+    let planners = json!({ "top_k": 25, "rerank": "lexical", "explain": true });
+    assert_eq!(put_preset(&app, "planners", planners).await.0, StatusCode::OK);
+    let uri = format!("{WORKSPACE_URI}/presets/mobile/default");
+    assert_eq!(send(&app, empty_request("PUT", &uri)).await.0, StatusCode::OK);
+
+    // A request with no options gets the default preset
+    let (status, result) = query(&app, json!({ "text": "park" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert!(result["features"].as_array().unwrap().len() <= 1, "{}", result);
+    assert_eq!(result["geometry_detail"], "centroid");
+    assert_eq!(result["preset"]["name"], "mobile");
+    assert_eq!(result["preset"]["default"], true);
+    assert_eq!(result["preset"]["effective"]["top_k"], 1);
+
+    // Request fields win over the named preset's
+    let request = json!({ "text": "park", "preset": "planners", "top_k": 2 });
+    let (status, result) = query(&app, request).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    let preset = &result["preset"];
+    assert_eq!(preset["name"], "planners");
+    assert_eq!(preset["default"], false);
+    assert_eq!(preset["applied"], json!(["rerank", "explain"]));
+    assert_eq!(preset["overridden"], json!(["top_k"]));
+    assert_eq!(preset["effective"]["top_k"], 2);
+    assert_eq!(preset["effective"]["rerank"], "lexical");
+    assert_eq!(result["geometry_detail"], "full");
+
+    let (status, body) = query(&app, json!({ "text": "park", "preset": "field" })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+
+    // Without a default, a request names its preset or takes none
+    let uri = format!("{WORKSPACE_URI}/presets/mobile/default");
+    let (status, preset) = send(&app, empty_request("DELETE", &uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", preset);
+    assert_eq!(preset["default"], false);
+    let (status, result) = query(&app, json!({ "text": "park" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert!(result.get("preset").is_none(), "{}", result);
+    assert_eq!(result["geometry_detail"], "full");
+}
@@ -0,0 +1,199 @@
+//! Integration tests for the instance-wide caps on expensive operations
+//!
+//! The test holds every slot of a resource itself, so a burst of requests
+//! finds them all taken: as many as the queue holds wait, the rest are
+//! turned away with 429, and the waiting ones run once the slots are freed
+//! without ever exceeding the cap.
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use georag_api::{create_router, AppState, ResourceGovernor};
+use georag_core::resources::{ResourceKind, ResourceLimits};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn state(limits: ResourceLimits) -> AppState {
+    common::default_only_state().with_governor(ResourceGovernor::new(limits))
+}
+
+fn upload(i: usize) -> Request<Body> {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+            "properties": { "name": format!("market {}", i) }
+        }]
+    });
+    common::upload(
+        "/api/v1/ingest",
+        &format!("markets-{i}.geojson"),
+        geojson.to_string().as_bytes(),
+    )
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Option<String>, Value) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, retry_after, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// Wait until `check` holds for the governor's stats of `kind`
+async fn wait_for(
+    governor: &ResourceGovernor,
+    kind: ResourceKind,
+    check: impl Fn(usize, u64) -> bool,
+) {
+    for _ in 0..500 {
+        let stats = governor.stats().into_iter().find(|s| s.kind == kind).unwrap();
+        if check(stats.queued, stats.rejected) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("{} slots never reached the expected state: {:?}", kind, governor.stats());
+}
+
+#[tokio::test]
+async fn test_ingest_burst_queues_up_to_the_limit_and_turns_away_the_rest() {
+    let limits = ResourceLimits::default()
+        .with_limit(ResourceKind::Ingest, 2)
+        .with_max_queued(3)
+        .with_retry_after(Duration::from_secs(7));
+    let state = Arc::new(state(limits));
+    let governor = state.governor.clone();
+    let app = create_router(state);
+
+    let held = vec![
+        governor.acquire(ResourceKind::Ingest).await.unwrap(),
+        governor.acquire(ResourceKind::Ingest).await.unwrap(),
+    ];
+
+    // Sample the holders while the burst runs
+    let done = Arc::new(AtomicBool::new(false));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let sampler = {
+        let (governor, done, peak) = (governor.clone(), done.clone(), peak.clone());
+        tokio::spawn(async move {
+            while !done.load(Ordering::SeqCst) {
+                for stats in governor.stats() {
+                    if stats.kind == ResourceKind::Ingest {
+                        peak.fetch_max(stats.holders, Ordering::SeqCst);
+                    }
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+    };
+
+    let burst: Vec<_> = (0..8)
+        .map(|i| {
+            let app = app.clone();
+            tokio::spawn(async move { send(&app, upload(i)).await })
+        })
+        .collect();
+
+    // Three wait for a slot and five are turned away
+    wait_for(&governor, ResourceKind::Ingest, |queued, rejected| queued == 3 && rejected == 5)
+        .await;
+    drop(held);
+
+    let mut ingested = 0;
+    let mut turned_away = 0;
+    for request in burst {
+        let (status, retry_after, body) = request.await.unwrap();
+        match status {
+            StatusCode::OK => ingested += 1,
+            StatusCode::TOO_MANY_REQUESTS => {
+                turned_away += 1;
+                assert_eq!(retry_after.as_deref(), Some("7"));
+                assert_eq!(body["error"], "Server busy");
+            }
+            other => panic!("unexpected status {}: {}", other, body),
+        }
+    }
+    done.store(true, Ordering::SeqCst);
+    sampler.await.unwrap();
+
+    assert_eq!((ingested, turned_away), (3, 5));
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    let stats = governor.stats().into_iter().find(|s| s.kind == ResourceKind::Ingest).unwrap();
+    assert_eq!((stats.holders, stats.queued, stats.rejected), (0, 0, 5));
+}
+
+#[tokio::test]
+async fn test_rebuild_is_refused_while_the_build_queue_is_full() {
+    let limits = ResourceLimits::default().with_max_queued(0);
+    let state = Arc::new(state(limits));
+    let governor = state.governor.clone();
+    let app = create_router(state);
+
+    let held = governor.acquire(ResourceKind::Build).await.unwrap();
+    let rebuild = || {
+        Request::post("/api/v1/workspaces/default/index/rebuild")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let (status, retry_after, body) = send(&app, rebuild()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{}", body);
+    assert_eq!(retry_after.as_deref(), Some("5"));
+    assert!(body["details"].as_str().unwrap().contains("build"), "{}", body);
+
+    // A refused rebuild never started
+    let status = Request::get("/api/v1/workspaces/default/index/status")
+        .body(Body::empty())
+        .unwrap();
+    let (_, _, status) = send(&app, status).await;
+    assert_eq!(status["rebuilding"], json!(false));
+
+    drop(held);
+    let (status, _, _) = send(&app, rebuild()).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_metrics_report_holders_and_queues() {
+    let limits = ResourceLimits::default().with_limit(ResourceKind::Export, 1);
+    let state = Arc::new(state(limits));
+    let governor = state.governor.clone();
+    let app = create_router(state);
+
+    let held = governor.acquire(ResourceKind::Export).await.unwrap();
+    let waiting = {
+        let governor = governor.clone();
+        tokio::spawn(async move { governor.acquire(ResourceKind::Export).await.is_ok() })
+    };
+    wait_for(&governor, ResourceKind::Export, |queued, _| queued == 1).await;
+
+    let response = tower::ServiceExt::oneshot(
+        app.clone(),
+        Request::get("/metrics").body(Body::empty()).unwrap(),
+    )
+    .await
+    .unwrap();
+    let metrics = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let metrics = String::from_utf8(metrics.to_vec()).unwrap();
+    for line in [
+        "georag_resource_limit{resource=\"export\"} 1",
+        "georag_resource_holders{resource=\"export\"} 1",
+        "georag_resource_queued{resource=\"export\"} 1",
+        "georag_resource_limit{resource=\"build\"} 1",
+        "georag_resource_holders{resource=\"ingest\"} 0",
+        "georag_resource_rejected_total{resource=\"export\"} 0",
+    ] {
+        assert!(metrics.contains(line), "missing {:?} in\n{}", line, metrics);
+    }
+
+    drop(held);
+    assert!(waiting.await.unwrap());
+}
@@ -0,0 +1,80 @@
+//! Integration tests for downloading the original file of a dataset
+//!
+//! The file is uploaded once and downloaded again through the global and
+//! the workspace routes, byte for byte and as an attachment named after the
+//! uploaded file.
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, HeaderMap, Request, StatusCode};
+use axum::Router;
+use common::{app, get, upload};
+use serde_json::{json, Value};
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, headers, body.to_vec())
+}
+
+fn geojson() -> String {
+    json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.1] },
+            "properties": { "name": "Crane A" }
+        }]
+    })
+    .to_string()
+}
+
+/// Upload `content` as cranes.geojson to `uri`, returning the dataset ID
+async fn ingest(app: &Router, uri: &str, content: &str) -> u64 {
+    let upload = upload(uri, "cranes.geojson", content.as_bytes());
+    let (status, _, body) = send(app, upload).await;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    assert!(status.is_success(), "{}", body);
+    body["dataset_id"].as_u64().unwrap()
+}
+
+fn assert_download(status: StatusCode, headers: &HeaderMap, body: &[u8], content: &str) {
+    assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(body));
+    assert_eq!(body, content.as_bytes());
+    let disposition = headers[header::CONTENT_DISPOSITION].to_str().unwrap();
+    assert!(disposition.starts_with("attachment"), "{}", disposition);
+    assert!(disposition.contains("cranes.geojson"), "{}", disposition);
+}
+
+#[tokio::test]
+async fn test_download_dataset_source() {
+    let app = app();
+    let content = geojson();
+    let id = ingest(&app, "/api/v1/ingest", &content).await;
+
+    let (status, headers, body) = send(&app, get(&format!("/api/v1/datasets/{id}/source"))).await;
+    assert_download(status, &headers, &body, &content);
+
+    let (status, _, _) = send(&app, get("/api/v1/datasets/999999/source")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_download_workspace_dataset_source() {
+    let app = app();
+    let create = Request::post("/api/v1/workspaces")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "name": "parks" }).to_string()))
+        .unwrap();
+    assert_eq!(send(&app, create).await.0, StatusCode::CREATED);
+
+    let content = geojson();
+    let id = ingest(&app, "/api/v1/workspaces/parks/ingest", &content).await;
+
+    let uri = format!("/api/v1/workspaces/parks/datasets/{id}/source");
+    let (status, headers, body) = send(&app, get(&uri)).await;
+    assert_download(status, &headers, &body, &content);
+}
@@ -0,0 +1,278 @@
+//! Integration tests for storage backend health supervision
+//!
+//! A probe that can be switched off stands in for an unreachable database.
+//! After a failed check writes are refused with 503; once the backend is
+//! down, queries of the default workspace are served from the fallback
+//! bundle if it was exported from the current index. The outage is written
+//! to the audit log when checks pass again.
+
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use georag_api::health::CircuitState;
+use georag_api::{
+    create_router, AppState, EmbedderConfig, HealthPolicy, QueryConfig, StoreSupervisor,
+};
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::IndexState;
+use georag_store::bundle::{BundleStore, OfflineBundle};
+use georag_store::memory::{
+    MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore, MemoryWorkspaceStore,
+};
+use georag_store::ports::HealthProbe;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BOUNDARY: &str = "georag-test-boundary";
+
+/// Probe whose backend is up until switched off
+#[derive(Default)]
+struct SwitchedProbe {
+    down: AtomicBool,
+}
+
+#[async_trait]
+impl HealthProbe for SwitchedProbe {
+    fn backend(&self) -> &str {
+        "switched"
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        if self.down.load(Ordering::SeqCst) {
+            return Err(GeoragError::Serialization("connection refused".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Stores of the default workspace, shared by every state of a test
+#[derive(Clone, Default)]
+struct Stores {
+    spatial: Arc<MemorySpatialStore>,
+    vector: Arc<MemoryVectorStore>,
+    document: Arc<MemoryDocumentStore>,
+    workspace: Arc<MemoryWorkspaceStore>,
+}
+
+impl Stores {
+    fn state(&self) -> AppState {
+        let embedder = EmbedderConfig {
+            model: "mock:32".to_string(),
+            dimensions: 32,
+            ..Default::default()
+        };
+        AppState::new(
+            self.spatial.clone(),
+            self.vector.clone(),
+            self.document.clone(),
+            self.workspace.clone(),
+            embedder,
+            QueryConfig::default(),
+        )
+    }
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn upload() -> Request<Body> {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+            "properties": { "content": "city park with a playground" }
+        }]
+    });
+    let body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; \
+         filename=\"parks.geojson\"\r\nContent-Type: application/geo+json\r\n\r\n\
+         {geojson}\r\n--{BOUNDARY}--\r\n"
+    );
+    Request::post("/api/v1/ingest")
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={BOUNDARY}"))
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn park_query() -> Request<Body> {
+    Request::post("/api/v1/query")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "text": "park" }).to_string()))
+        .unwrap()
+}
+
+/// Ingest a park into the default workspace and build its index
+async fn build_default_workspace(stores: &Stores) -> IndexState {
+    let state = Arc::new(stores.state());
+    let app = create_router(state.clone());
+    let (status, body) = send(&app, upload()).await;
+    assert!(status.is_success(), "{}", body);
+
+    let rebuild = Request::post("/api/v1/workspaces/default/index/rebuild")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(&app, rebuild).await.0, StatusCode::ACCEPTED);
+
+    let id = state.default_workspace().await.unwrap().id;
+    for _ in 0..200 {
+        if !state.is_rebuilding(id).await {
+            if let Some(index) = state.get_workspace_index_state(id).await {
+                return index;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("index of the default workspace was not built");
+}
+
+/// Bundle of the whole world exported with `index`
+async fn bundle(stores: &Stores, index: IndexState) -> Arc<BundleStore> {
+    let bundle = OfflineBundle::extract(
+        stores.spatial.as_ref(),
+        stores.vector.as_ref(),
+        stores.document.as_ref(),
+        [-180.0, -90.0, 180.0, 90.0],
+        Some(index),
+    )
+    .await
+    .unwrap();
+    Arc::new(BundleStore::from_bundle(bundle).await.unwrap())
+}
+
+/// Supervised app over `stores`, serving an index built before it started
+async fn supervised(
+    stores: &Stores,
+    index: &IndexState,
+    supervisor: Arc<StoreSupervisor>,
+) -> Router {
+    let state = stores.state().with_store_supervisor(supervisor);
+    let id = state.default_workspace().await.unwrap().id;
+    state.set_workspace_index_state(id, index.clone()).await;
+    create_router(Arc::new(state))
+}
+
+fn policy() -> HealthPolicy {
+    HealthPolicy {
+        check_interval: Duration::from_secs(1),
+        down_after: 2,
+    }
+}
+
+#[tokio::test]
+async fn test_outage_rejects_writes_and_serves_queries_from_the_fallback() {
+    let stores = Stores::default();
+    let index = build_default_workspace(&stores).await;
+    let dir = tempfile::tempdir().unwrap();
+    let audit_log = dir.path().join("audit.log");
+
+    let probe = Arc::new(SwitchedProbe::default());
+    let supervisor = Arc::new(
+        StoreSupervisor::new(probe.clone(), policy())
+            .with_fallback(bundle(&stores, index.clone()).await)
+            .with_audit_log(&audit_log),
+    );
+    let app = supervised(&stores, &index, supervisor.clone()).await;
+
+    // Served by the backend while it is healthy
+    let (status, result) = send(&app, park_query()).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert_eq!(supervisor.check().await, CircuitState::Healthy);
+
+    probe.down.store(true, Ordering::SeqCst);
+    assert_eq!(supervisor.check().await, CircuitState::Degraded);
+    let (status, body) = send(&app, upload()).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{}", body);
+    assert!(body["details"].as_str().unwrap().contains("degraded"), "{}", body);
+
+    // Degraded queries still go to the backend
+    let (status, _) = send(&app, park_query()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(supervisor.health().counters.fallback_queries, 0);
+
+    assert_eq!(supervisor.check().await, CircuitState::Down);
+    let (status, result) = send(&app, park_query()).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert_eq!(result["features"].as_array().unwrap().len(), 1, "{}", result);
+    assert_eq!(supervisor.health().counters.fallback_queries, 1);
+
+    let (status, health) = send(&app, Request::get("/health").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health["status"], "down");
+    assert_eq!(health["store"]["state"], "down");
+    assert_eq!(health["store"]["last_error"], "Serialization error: connection refused");
+    assert_eq!(health["store"]["outage"]["rejected_writes"], 1);
+    assert_eq!(health["store"]["fallback"]["index_hash"], index.hash.as_str());
+
+    let metrics = tower::ServiceExt::oneshot(
+        app.clone(),
+        Request::get("/metrics").body(Body::empty()).unwrap(),
+    )
+    .await
+    .unwrap();
+    let metrics = to_bytes(metrics.into_body(), usize::MAX).await.unwrap();
+    let metrics = String::from_utf8(metrics.to_vec()).unwrap();
+    assert!(
+        metrics.contains("georag_store_circuit_state{backend=\"switched\",state=\"down\"} 1"),
+        "{}",
+        metrics
+    );
+    assert!(metrics.contains("georag_store_fallback_queries_total{backend=\"switched\"} 1"));
+
+    // Recovery is automatic and the outage is audited
+    probe.down.store(false, Ordering::SeqCst);
+    assert_eq!(supervisor.check().await, CircuitState::Healthy);
+    let (status, body) = send(&app, upload()).await;
+    assert!(status.is_success(), "{}", body);
+
+    let entries = std::fs::read_to_string(&audit_log).unwrap();
+    let entries: Vec<Value> = entries.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(entries.len(), 1);
+    let outage = &entries[0];
+    assert_eq!(outage["event"], "store_outage");
+    assert_eq!(outage["backend"], "switched");
+    assert_eq!(outage["failed_checks"], 2);
+    assert_eq!(outage["rejected_writes"], 1);
+    assert_eq!(outage["fallback_queries"], 1);
+    assert_eq!(outage["fallback_requests"][0]["path"], "/api/v1/query");
+    assert_eq!(outage["fallback_requests"][0]["workspace"], "default");
+    assert!(outage["ended_at"].is_string());
+
+    let (_, health) = send(&app, Request::get("/health").body(Body::empty()).unwrap()).await;
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["store"]["recent_outages"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_stale_fallback_is_not_used() {
+    let stores = Stores::default();
+    let index = build_default_workspace(&stores).await;
+    let stale = IndexState {
+        hash: "from-an-older-index".to_string(),
+        ..index.clone()
+    };
+
+    let probe = Arc::new(SwitchedProbe::default());
+    let supervisor = Arc::new(
+        StoreSupervisor::new(probe.clone(), policy()).with_fallback(bundle(&stores, stale).await),
+    );
+    let app = supervised(&stores, &index, supervisor.clone()).await;
+    let (status, _) = send(&app, park_query()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    probe.down.store(true, Ordering::SeqCst);
+    supervisor.check().await;
+    assert_eq!(supervisor.check().await, CircuitState::Down);
+
+    // The query goes to the backend, which the memory stores still serve
+    let (status, _) = send(&app, park_query()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(supervisor.health().counters.fallback_queries, 0);
+}
@@ -0,0 +1,101 @@
+//! Integration tests for the workspace activity timeline
+//!
+//! Workspace `parks` gets a dataset, a build, two queries and a deletion,
+//! then a rebuild that fails for lack of datasets. The timeline counts each
+//! event in today's bucket and lists the failed build with its error.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use chrono::{Duration, Utc};
+use common::{app, create_workspace, get, json_request, rebuild, send, upload};
+use serde_json::{json, Value};
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/parks";
+
+async fn ingest(app: &Router) -> Value {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+            "properties": { "content": "city park with a playground" }
+        }]
+    });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let (status, body) =
+        send(app, upload(&uri, "parks.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+    body
+}
+
+#[tokio::test]
+async fn test_timeline_counts_workspace_events() {
+    let app = app();
+    create_workspace(&app, "parks").await;
+
+    let dataset_id = ingest(&app).await["dataset_id"].clone();
+    rebuild(&app, "parks").await;
+    for _ in 0..2 {
+        let query =
+            json_request("POST", &format!("{WORKSPACE_URI}/query"), json!({ "text": "park" }));
+        assert_eq!(send(&app, query).await.0, StatusCode::OK);
+    }
+    let delete =
+        Request::delete(format!("{WORKSPACE_URI}/datasets/{}", dataset_id.as_u64().unwrap()))
+            .body(Body::empty())
+            .unwrap();
+    assert_eq!(send(&app, delete).await.0, StatusCode::OK);
+    // Nothing is left to index
+    rebuild(&app, "parks").await;
+
+    let (status, timeline) = send(&app, get(&format!("{WORKSPACE_URI}/timeline"))).await;
+    assert_eq!(status, StatusCode::OK, "{}", timeline);
+    assert_eq!(timeline["granularity"], "day");
+    let buckets = timeline["buckets"].as_array().unwrap();
+    assert_eq!(buckets.len(), 15, "two weeks back through today");
+    let today = &buckets[14]["counts"];
+    assert_eq!(today["dataset_added"], 1);
+    assert_eq!(today["build_completed"], 1);
+    assert_eq!(today["query"], 2);
+    assert_eq!(today["dataset_deleted"], 1);
+    assert_eq!(today["build_failed"], 1);
+    assert_eq!(buckets[0]["counts"]["query"], 0);
+    assert_eq!(timeline["totals"]["query"], 2);
+
+    let notable = timeline["notable"].as_array().unwrap();
+    assert_eq!(notable.len(), 1, "{}", timeline);
+    assert_eq!(notable[0]["kind"], "build_failed");
+    assert!(notable[0]["details"].as_str().unwrap().contains("No datasets"), "{}", timeline);
+
+    // Other workspaces have their own timeline
+    create_workspace(&app, "lakes").await;
+    let (status, other) = send(&app, get("/api/v1/workspaces/lakes/timeline")).await;
+    assert_eq!(status, StatusCode::OK, "{}", other);
+    assert_eq!(other["totals"]["query"], 0);
+}
+
+#[tokio::test]
+async fn test_timeline_range_is_bounded() {
+    let app = app();
+    create_workspace(&app, "parks").await;
+
+    let since = (Utc::now() - Duration::hours(3)).format("%Y-%m-%dT%H:%M:%SZ");
+    let uri = format!("{WORKSPACE_URI}/timeline?since={since}&granularity=hour");
+    let (status, timeline) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", timeline);
+    assert_eq!(timeline["buckets"].as_array().unwrap().len(), 4);
+
+    let too_old = (Utc::now() - Duration::days(120)).format("%Y-%m-%d");
+    let (status, body) =
+        send(&app, get(&format!("{WORKSPACE_URI}/timeline?since={too_old}"))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["details"].as_str().unwrap().contains("90 days"), "{}", body);
+
+    for query in ["since=yesterday", "granularity=month"] {
+        let (status, _) = send(&app, get(&format!("{WORKSPACE_URI}/timeline?{query}"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
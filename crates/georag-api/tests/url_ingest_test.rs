@@ -0,0 +1,175 @@
+//! Integration tests for ingesting a dataset from a URL through the API
+//!
+//! The `url` form field replaces the uploaded file: the server downloads it,
+//! records the URL as the dataset path and maps download failures to their
+//! own status codes. The test server is on a loopback address, which the
+//! server refuses unless its policy allows private addresses.
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use georag_api::{create_router, AppState, EmbedderConfig, QueryConfig};
+use georag_service::DownloadPolicy;
+use georag_store::memory::{
+    MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore, MemoryWorkspaceStore,
+};
+use georag_store::ports::SpatialStore;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const BOUNDARY: &str = "georag-test-boundary";
+
+/// Policy allowing any host, loopback addresses included
+fn local_policy() -> DownloadPolicy {
+    DownloadPolicy::default().with_private_addresses(true)
+}
+
+fn state() -> AppState {
+    base_state().with_download_policy(local_policy())
+}
+
+/// State with the default download policy, which allows no host
+fn base_state() -> AppState {
+    let embedder = EmbedderConfig {
+        model: "mock:32".to_string(),
+        dimensions: 32,
+        ..Default::default()
+    };
+    AppState::new(
+        Arc::new(MemorySpatialStore::new()),
+        Arc::new(MemoryVectorStore::new()),
+        Arc::new(MemoryDocumentStore::new()),
+        Arc::new(MemoryWorkspaceStore::new()),
+        embedder,
+        QueryConfig::default(),
+    )
+}
+
+/// Answer every request on a local port with `status` and `body`
+async fn serve(status: &'static str, body: String) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let body = body.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\nConnection: close\r\nContent-Type: application/json\r\n\
+                     ETag: \"cities-v2\"\r\nContent-Length: {}\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+    base
+}
+
+fn url_form(fields: &[(&str, &str)]) -> Request<Body> {
+    let mut body = String::new();
+    for (name, value) in fields {
+        body.push_str(&format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        ));
+    }
+    body.push_str(&format!("--{BOUNDARY}--\r\n"));
+    Request::post("/api/v1/ingest")
+        .header(header::CONTENT_TYPE, format!("multipart/form-data; boundary={BOUNDARY}"))
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn send(state: AppState, request: Request<Body>) -> (StatusCode, Value) {
+    let app = create_router(Arc::new(state));
+    let response = tower::ServiceExt::oneshot(app, request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_url_is_downloaded_and_recorded_as_the_dataset_path() {
+    let cities = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+            "properties": { "name": "Jakarta" }
+        }]
+    });
+    let base = serve("200 OK", cities.to_string()).await;
+    let url = format!("{}/open-data/cities", base);
+
+    let state = state();
+    let spatial = state.spatial_store.clone();
+    let (status, body) = send(state, url_form(&[("url", &url), ("tags", "public")])).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["message"], "Successfully ingested cities.geojson with 1 features");
+    assert_eq!(body["remote"]["url"], url);
+    assert_eq!(body["remote"]["etag"], "\"cities-v2\"");
+
+    let datasets = spatial.list_datasets().await.unwrap();
+    let dataset = spatial.get_dataset(datasets[0].id).await.unwrap().unwrap();
+    assert_eq!(dataset.path.to_str(), Some(url.as_str()));
+    assert_eq!(dataset.tags, vec!["public"]);
+}
+
+#[tokio::test]
+async fn test_download_failures_map_to_status_codes() {
+    let base = serve("404 Not Found", "missing".to_string()).await;
+    let (status, body) =
+        send(state(), url_form(&[("url", &format!("{}/cities.geojson", base))])).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert!(body["details"].as_str().unwrap().contains("404"), "{}", body);
+
+    let (status, _) = send(state(), url_form(&[("url", "file:///etc/passwd")])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let big = serve("200 OK", "x".repeat(2048)).await;
+    let limited = state().with_download_policy(local_policy().with_max_bytes(1024));
+    let (status, _) = send(limited, url_form(&[("url", &format!("{}/big.geojson", big))])).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let disabled = state().with_download_policy(local_policy().with_max_bytes(0));
+    let (status, _) = send(disabled, url_form(&[("url", "https://example.com/a.geojson")])).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_url_ingest_is_off_until_hosts_are_allowed() {
+    let base = serve("200 OK", "{}".to_string()).await;
+    let url = format!("{}/cities.geojson", base);
+
+    let (status, body) = send(base_state(), url_form(&[("url", &url)])).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["error"], "Ingest from URLs is disabled");
+}
+
+#[tokio::test]
+async fn test_private_addresses_are_refused() {
+    let base = serve("200 OK", "{}".to_string()).await;
+    let any_host = DownloadPolicy::default().with_allowed_hosts(None);
+
+    let loopback = format!("{}/cities.geojson", base);
+    let localhost = loopback.replace("127.0.0.1", "localhost");
+    let metadata = "http://169.254.169.254/latest/meta-data".to_string();
+    for url in [loopback, localhost, metadata] {
+        let state = base_state().with_download_policy(any_host.clone());
+        let (status, body) = send(state, url_form(&[("url", &url)])).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}: {}", url, body);
+        assert_eq!(body["error"], "Download refused", "{}", url);
+    }
+}
@@ -0,0 +1,129 @@
+//! Integration tests for creating and configuring workspaces
+//!
+//! A workspace is created with a full configuration and changed later with
+//! PATCH. Invalid settings are refused as `georag config check` refuses them,
+//! and once the workspace holds features and an index its CRS and embedder
+//! dimensions can no longer change.
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{app, get, json_request, rebuild, send, upload};
+use serde_json::json;
+
+const WORKSPACE_URI: &str = "/api/v1/workspaces/parks";
+
+/// Upload a park to workspace `parks` and build its index
+async fn ingest_and_build(app: &Router) {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+            "properties": { "content": "city park with a playground" }
+        }]
+    });
+    let uri = format!("{WORKSPACE_URI}/ingest");
+    let (status, body) =
+        send(app, upload(&uri, "parks.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+
+    rebuild(app, "parks").await;
+}
+
+#[tokio::test]
+async fn test_create_with_full_configuration() {
+    let app = app();
+
+    let request = json!({
+        "name": "parks",
+        "crs": 3857,
+        "distance_unit": "Kilometers",
+        "min_score": 0.4,
+        "chunk_properties": ["name"],
+        "max_datasets": 5
+    });
+    let (status, workspace) = send(&app, json_request("POST", "/api/v1/workspaces", request)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", workspace);
+    assert_eq!(workspace["name"], "parks");
+    assert_eq!(workspace["crs"], 3857);
+    assert_eq!(workspace["distance_unit"], "Kilometers");
+    assert_eq!(workspace["settings"]["min_score"], 0.4);
+    assert_eq!(workspace["settings"]["geometry_validity"], "Lenient");
+    assert_eq!(workspace["effective"]["crs"]["value"], "EPSG:3857");
+    assert_eq!(workspace["effective"]["crs"]["source"], "Store");
+    assert_eq!(workspace["effective"]["max_sample"]["source"], "Default");
+    assert_eq!(workspace["effective"]["embedder"]["value"], "mock:32");
+
+    // The settings endpoint serves what was stored
+    let (status, stored) = send(&app, get(&format!("{WORKSPACE_URI}/settings"))).await;
+    assert_eq!(status, StatusCode::OK, "{}", stored);
+    assert_eq!(stored["settings"], workspace["settings"]);
+
+    let (status, _) =
+        send(&app, json_request("POST", "/api/v1/workspaces", json!({ "name": "parks" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_invalid_configuration_is_rejected() {
+    let app = app();
+
+    for settings in [
+        json!({ "name": "parks", "min_score": 1.5 }),
+        json!({ "name": "parks", "embedder": "mock:0" }),
+        json!({ "name": "parks", "default_spatial_predicate": "nearby" }),
+        json!({ "name": " " }),
+    ] {
+        let (status, body) =
+            send(&app, json_request("POST", "/api/v1/workspaces", settings.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", settings, body);
+    }
+
+    let (status, workspaces) = send(&app, get("/api/v1/workspaces")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(workspaces, json!([]));
+}
+
+#[tokio::test]
+async fn test_patch_refuses_settings_the_data_depends_on() {
+    let app = app();
+    let (status, body) =
+        send(&app, json_request("POST", "/api/v1/workspaces", json!({ "name": "parks" }))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+
+    // Before any data, the CRS may change
+    let (status, workspace) =
+        send(&app, json_request("PATCH", WORKSPACE_URI, json!({ "crs": 3857 }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", workspace);
+    assert_eq!(workspace["crs"], 3857);
+    let (status, workspace) =
+        send(&app, json_request("PATCH", WORKSPACE_URI, json!({ "crs": 4326 }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", workspace);
+
+    ingest_and_build(&app).await;
+
+    let (status, body) =
+        send(&app, json_request("PATCH", WORKSPACE_URI, json!({ "crs": 3857 }))).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+    assert!(body["details"].as_str().unwrap().contains("EPSG:4326"), "{}", body);
+
+    let (status, body) =
+        send(&app, json_request("PATCH", WORKSPACE_URI, json!({ "embedder": "mock:16" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+    assert!(body["details"].as_str().unwrap().contains("16-dimensional"), "{}", body);
+
+    // Other settings still change, and the ones left out are kept
+    let patch = json!({ "min_score": 0.5, "embedder": "mock:32" });
+    let (status, workspace) = send(&app, json_request("PATCH", WORKSPACE_URI, patch)).await;
+    assert_eq!(status, StatusCode::OK, "{}", workspace);
+    assert_eq!(workspace["crs"], 4326);
+    assert_eq!(workspace["settings"]["min_score"], 0.5);
+    assert_eq!(workspace["settings"]["crs"], 4326);
+    assert_eq!(workspace["effective"]["embedder"]["source"], "Store");
+
+    let (status, _) =
+        send(&app, json_request("PATCH", WORKSPACE_URI, json!({ "min_score": 3 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
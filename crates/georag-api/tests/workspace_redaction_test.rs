@@ -0,0 +1,177 @@
+//! Integration tests for redaction rules stored as a workspace setting
+//!
+//! Workspaces `parks` and `lakes` hold the same features. Only `parks` has
+//! redaction rules, so its samples, features, schemas and query results are
+//! masked while those of `lakes` are not, as are the previews in its
+//! dataset listings. Changing the rules is recorded in the timeline of `parks`.
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use common::{app, get, json_request, rebuild, send, upload};
+use georag_core::redaction::REDACTED_MARKER;
+use serde_json::{json, Value};
+use std::io::Cursor;
+
+/// Create workspace `name` holding one parcel, returning the dataset ID
+async fn create_workspace(app: &Router, name: &str) -> u64 {
+    common::create_workspace(app, name).await;
+
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.18] },
+            "properties": {
+                "content": "parcel by the river, call 555-123-4567",
+                "owner_name": "Jane Doe"
+            }
+        }]
+    });
+    let uri = format!("/api/v1/workspaces/{name}/ingest");
+    let (status, body) =
+        send(app, upload(&uri, "parcels.geojson", geojson.to_string().as_bytes())).await;
+    assert!(status.is_success(), "{}", body);
+    body["dataset_id"].as_u64().unwrap()
+}
+
+/// Upload a DOCX of one paragraph holding `text` to workspace `name`
+async fn ingest_docx(app: &Router, name: &str, text: &str) {
+    let mut docx = Cursor::new(Vec::new());
+    docx_rs::Docx::new()
+        .add_paragraph(docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text)))
+        .build()
+        .pack(&mut docx)
+        .unwrap();
+
+    let uri = format!("/api/v1/workspaces/{name}/ingest");
+    let (status, body) = send(app, upload(&uri, "notes.docx", &docx.into_inner())).await;
+    assert!(status.is_success(), "{}", body);
+}
+
+async fn set_redaction(app: &Router, workspace: &str, redaction: Value) -> (StatusCode, Value) {
+    let uri = format!("/api/v1/workspaces/{workspace}/settings");
+    send(app, json_request("PUT", &uri, json!({ "redaction": redaction }))).await
+}
+
+fn rules() -> Value {
+    json!({ "properties": ["owner_name"], "patterns": [r"\d{3}-\d{3}-\d{4}"] })
+}
+
+#[tokio::test]
+async fn test_rules_mask_the_responses_of_their_workspace_only() {
+    let app = app();
+    let parks = create_workspace(&app, "parks").await;
+    let lakes = create_workspace(&app, "lakes").await;
+    let (status, body) = set_redaction(&app, "parks", rules()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["settings"]["redaction"], rules());
+
+    let uri = format!("/api/v1/workspaces/parks/datasets/{parks}/sample");
+    let (status, sample) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", sample);
+    let feature = &sample["features"][0];
+    assert_eq!(feature["properties"]["owner_name"], REDACTED_MARKER);
+    assert_eq!(feature["properties"]["content"], "parcel by the river, call [REDACTED]");
+
+    let feature_id = feature["id"].as_u64().unwrap();
+    let uri = format!("/api/v1/workspaces/parks/datasets/{parks}/features/{feature_id}");
+    let (status, feature) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", feature);
+    assert_eq!(feature["properties"]["owner_name"], REDACTED_MARKER);
+
+    let uri = format!("/api/v1/workspaces/parks/datasets/{parks}/schema");
+    let (status, schema) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", schema);
+    let owner = &schema["properties"]["properties"]["properties"]["owner_name"];
+    assert_eq!(owner["type"], "string");
+    assert_eq!(owner["examples"], json!([REDACTED_MARKER]));
+
+    rebuild(&app, "parks").await;
+    let query = json_request("POST", "/api/v1/workspaces/parks/query", json!({ "text": "river" }));
+    let (status, result) = send(&app, query).await;
+    assert_eq!(status, StatusCode::OK, "{}", result);
+    assert!(!result.to_string().contains("555-123-4567"), "{}", result);
+    assert!(!result.to_string().contains("Jane Doe"), "{}", result);
+
+    // The other workspace has no rules of its own
+    let uri = format!("/api/v1/workspaces/lakes/datasets/{lakes}/sample");
+    let (status, sample) = send(&app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", sample);
+    let properties = &sample["features"][0]["properties"];
+    assert_eq!(properties["owner_name"], "Jane Doe");
+    assert_eq!(properties["content"], "parcel by the river, call 555-123-4567");
+}
+
+#[tokio::test]
+async fn test_rule_changes_are_recorded_in_the_timeline() {
+    let app = app();
+    let parks = create_workspace(&app, "parks").await;
+    assert_eq!(set_redaction(&app, "parks", rules()).await.0, StatusCode::OK);
+    // Storing the same rules again is not a change
+    assert_eq!(set_redaction(&app, "parks", rules()).await.0, StatusCode::OK);
+
+    // Removing them unmasks the workspace's responses at once
+    let uri = "/api/v1/workspaces/parks/settings";
+    let (status, body) = send(&app, json_request("PUT", uri, json!({}))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let uri = format!("/api/v1/workspaces/parks/datasets/{parks}/sample");
+    let (_, sample) = send(&app, get(&uri)).await;
+    assert_eq!(sample["features"][0]["properties"]["owner_name"], "Jane Doe");
+
+    let (status, timeline) = send(&app, get("/api/v1/workspaces/parks/timeline")).await;
+    assert_eq!(status, StatusCode::OK, "{}", timeline);
+    assert_eq!(timeline["totals"]["redaction_changed"], 2);
+    let notable = timeline["notable"].as_array().unwrap();
+    assert!(notable.iter().all(|event| event["kind"] == "redaction_changed"), "{}", timeline);
+    assert_eq!(notable[0]["details"], "0 properties, 0 patterns");
+    assert_eq!(notable[1]["details"], "1 properties, 1 patterns");
+
+    // Rules that do not compile are refused
+    let (status, body) = set_redaction(&app, "parks", json!({ "patterns": ["(unclosed"] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+}
+
+#[tokio::test]
+async fn test_previews_are_redacted_in_dataset_listings() {
+    let app = app();
+    let parcels = create_workspace(&app, "parks").await;
+    ingest_docx(&app, "parks", "Site visit notes. Call the owner at 555-123-4567.").await;
+    assert_eq!(set_redaction(&app, "parks", rules()).await.0, StatusCode::OK);
+
+    let (status, listing) = send(&app, get("/api/v1/workspaces/parks/datasets")).await;
+    assert_eq!(status, StatusCode::OK, "{}", listing);
+    assert!(!listing.to_string().contains("555-123-4567"), "{}", listing);
+    let datasets = listing.as_array().unwrap();
+    let preview = |kind: &str| {
+        datasets
+            .iter()
+            .map(|dataset| &dataset["preview"])
+            .find(|preview| preview["kind"] == kind)
+            .unwrap()
+            .clone()
+    };
+    assert_eq!(
+        preview("document")["snippet"],
+        "Site visit notes. Call the owner at [REDACTED]."
+    );
+    assert_eq!(preview("vector")["property_keys"], json!(["content", REDACTED_MARKER]));
+
+    // The listing of the legacy route and the dataset returned by PATCH too
+    let request = Request::get("/api/v1/datasets")
+        .header("x-georag-workspace", "parks")
+        .body(Body::empty())
+        .unwrap();
+    let (status, listing) = send(&app, request).await;
+    assert_eq!(status, StatusCode::OK, "{}", listing);
+    assert!(!listing.to_string().contains("555-123-4567"), "{}", listing);
+    assert!(!listing.to_string().contains("owner_name"), "{}", listing);
+
+    let uri = format!("/api/v1/workspaces/parks/datasets/{parcels}");
+    let (status, dataset) =
+        send(&app, json_request("PATCH", &uri, json!({ "tags": ["survey"] }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", dataset);
+    assert_eq!(dataset["preview"]["property_keys"], json!(["content", REDACTED_MARKER]));
+}
@@ -0,0 +1,387 @@
+//! Integration tests for routing requests to workspaces
+//!
+//! Two workspaces are filled through the API, one addressed by path prefix
+//! and one by the `X-Georag-Workspace` header. Whatever route is used, data
+//! ingested into one must never show up in the other's responses, and a
+//! clone of one holds its data and nothing of the other's. Without a store
+//! provider to keep them apart, only the default workspace is served.
+
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use common::{create_workspace, rebuild};
+use georag_api::auth::ApiKey;
+use georag_api::{create_router, AppState, AuthConfig, EmbedderConfig, QueryConfig};
+use georag_core::models::{DistanceUnit, ValidityMode, WorkspaceConfig};
+use georag_store::memory::{
+    MemoryDocumentStore, MemorySpatialStore, MemoryStoreProvider, MemoryVectorStore,
+    MemoryWorkspaceStore,
+};
+use georag_store::ports::WorkspaceStore;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn state() -> AppState {
+    default_only_state(Arc::new(MemoryWorkspaceStore::new()))
+        .with_store_provider(Arc::new(MemoryStoreProvider::new()))
+}
+
+/// State over `workspaces` serving the default workspace only, as PostgreSQL does
+fn default_only_state(workspaces: Arc<MemoryWorkspaceStore>) -> AppState {
+    let embedder = EmbedderConfig {
+        model: "mock:32".to_string(),
+        dimensions: 32,
+        ..Default::default()
+    };
+    AppState::new(
+        Arc::new(MemorySpatialStore::new()),
+        Arc::new(MemoryVectorStore::new()),
+        Arc::new(MemoryDocumentStore::new()),
+        workspaces,
+        embedder,
+        QueryConfig::default(),
+    )
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = tower::ServiceExt::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+fn get(uri: &str) -> axum::http::request::Builder {
+    Request::get(uri)
+}
+
+fn post_json(uri: &str, body: Value) -> Request<Body> {
+    Request::post(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Multipart upload of one point whose chunk text is `content`
+fn upload(uri: &str, workspace_header: Option<&str>, content: &str) -> Request<Body> {
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.1] },
+            "properties": { "content": content }
+        }]
+    });
+    let mut request = common::upload(uri, "places.geojson", geojson.to_string().as_bytes());
+    if let Some(workspace) = workspace_header {
+        request.headers_mut().insert("X-Georag-Workspace", workspace.parse().unwrap());
+    }
+    request
+}
+
+/// App with workspace `alpha` holding a lighthouse and `beta` a windmill
+async fn two_workspaces() -> Router {
+    let app = create_router(Arc::new(state()));
+    create_workspace(&app, "alpha").await;
+    create_workspace(&app, "beta").await;
+
+    let (status, body) =
+        send(&app, upload("/api/v1/workspaces/alpha/ingest", None, "lighthouse on the cape")).await;
+    assert!(status.is_success(), "{}", body);
+    let (status, body) =
+        send(&app, upload("/api/v1/ingest", Some("beta"), "windmill by the canal")).await;
+    assert!(status.is_success(), "{}", body);
+    app
+}
+
+#[tokio::test]
+async fn test_datasets_stay_in_their_workspace() {
+    let app = two_workspaces().await;
+
+    for (workspace, own, other) in
+        [("alpha", "lighthouse", "windmill"), ("beta", "windmill", "lighthouse")]
+    {
+        let (status, body) = send(
+            &app,
+            get(&format!("/api/v1/workspaces/{}/datasets", workspace))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let datasets: Vec<Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(datasets.len(), 1, "{}: {}", workspace, body);
+
+        // The unprefixed route sees the same dataset through the header
+        let (_, listed) = send(
+            &app,
+            get("/api/v1/datasets")
+                .header("X-Georag-Workspace", workspace)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        let listed: Vec<Value> = serde_json::from_str(&listed).unwrap();
+        assert_eq!(listed.len(), 1, "{}", workspace);
+
+        // Both listings carry the preview made at ingest
+        for dataset in [&datasets[0], &listed[0]] {
+            assert_eq!(dataset["preview"]["kind"], "vector");
+            assert_eq!(dataset["preview"]["property_keys"], json!(["content"]));
+        }
+
+        let id = &datasets[0]["id"];
+        let uri = format!("/api/v1/workspaces/{}/datasets/{}/sample", workspace, id);
+        let (status, sample) = send(&app, get(&uri).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK, "{}", sample);
+        assert!(sample.contains(own));
+        assert!(!sample.contains(other));
+    }
+
+    // Nothing was ingested into the default workspace
+    let (_, body) = send(&app, get("/api/v1/datasets").body(Body::empty()).unwrap()).await;
+    assert_eq!(body, "[]");
+}
+
+#[tokio::test]
+async fn test_queries_only_return_their_workspace() {
+    let app = two_workspaces().await;
+    rebuild(&app, "alpha").await;
+    rebuild(&app, "beta").await;
+
+    let query = json!({ "text": "landmark", "top_k": 10 });
+    let (status, alpha) =
+        send(&app, post_json("/api/v1/workspaces/alpha/query", query.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", alpha);
+    assert!(alpha.contains("lighthouse"));
+    assert!(!alpha.contains("windmill"));
+
+    let mut request = post_json("/api/v1/query", query);
+    request.headers_mut().insert("X-Georag-Workspace", "beta".parse().unwrap());
+    let (status, beta) = send(&app, request).await;
+    assert_eq!(status, StatusCode::OK, "{}", beta);
+    assert!(beta.contains("windmill"));
+    assert!(!beta.contains("lighthouse"));
+}
+
+#[tokio::test]
+async fn test_path_takes_precedence_over_header() {
+    let app = two_workspaces().await;
+
+    let (_, body) = send(
+        &app,
+        get("/api/v1/workspaces/alpha/datasets")
+            .header("X-Georag-Workspace", "beta")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    let datasets: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let uri = format!("/api/v1/workspaces/alpha/datasets/{}/sample", datasets[0]["id"]);
+    let (_, sample) = send(
+        &app,
+        get(&uri).header("X-Georag-Workspace", "beta").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert!(sample.contains("lighthouse"));
+    assert!(!sample.contains("windmill"));
+}
+
+#[tokio::test]
+async fn test_unknown_workspace_is_not_found() {
+    let app = two_workspaces().await;
+
+    let (status, _) =
+        send(&app, get("/api/v1/workspaces/gamma/datasets").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        &app,
+        get("/api/v1/datasets")
+            .header("X-Georag-Workspace", "gamma")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&app, upload("/api/v1/ingest", Some(""), "empty header")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_api_key_is_bound_to_its_workspaces() {
+    let auth = AuthConfig {
+        api_keys: vec![
+            ApiKey {
+                name: "admin".to_string(),
+                key: "admin-key".to_string(),
+                allowed_tags: None,
+                workspaces: None,
+            },
+            ApiKey {
+                name: "alpha-only".to_string(),
+                key: "alpha-key".to_string(),
+                allowed_tags: None,
+                workspaces: Some(vec!["alpha".to_string()]),
+            },
+        ],
+    };
+    let app = create_router(Arc::new(state().with_auth(auth)));
+    for name in ["alpha", "beta"] {
+        let mut request = post_json("/api/v1/workspaces", json!({ "name": name }));
+        request.headers_mut().insert("X-Api-Key", "admin-key".parse().unwrap());
+        assert_eq!(send(&app, request).await.0, StatusCode::CREATED);
+    }
+
+    let with_key = |uri: &str| get(uri).header("X-Api-Key", "alpha-key");
+    let (status, _) =
+        send(&app, with_key("/api/v1/workspaces/alpha/datasets").body(Body::empty()).unwrap())
+            .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) =
+        send(&app, with_key("/api/v1/workspaces/beta/datasets").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(
+        &app,
+        with_key("/api/v1/datasets")
+            .header("X-Georag-Workspace", "beta")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // The unprefixed routes default to a workspace the key is not bound to
+    let (status, _) = send(&app, with_key("/api/v1/datasets").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) =
+        send(&app, with_key("/api/v1/workspaces").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let workspaces: Vec<Value> = serde_json::from_str(&body).unwrap();
+    let names: Vec<&str> = workspaces.iter().filter_map(|w| w["name"].as_str()).collect();
+    assert_eq!(names, vec!["alpha"]);
+}
+
+#[tokio::test]
+async fn test_restricted_keys_cannot_delete_hidden_datasets() {
+    let auth = AuthConfig {
+        api_keys: vec![
+            ApiKey {
+                name: "admin".to_string(),
+                key: "admin-key".to_string(),
+                allowed_tags: None,
+                workspaces: None,
+            },
+            ApiKey {
+                name: "public".to_string(),
+                key: "public-key".to_string(),
+                allowed_tags: Some(vec!["public".to_string()]),
+                workspaces: None,
+            },
+        ],
+    };
+    let app = create_router(Arc::new(state().with_auth(auth)));
+    let as_admin = |mut request: Request<Body>| {
+        request.headers_mut().insert("X-Api-Key", "admin-key".parse().unwrap());
+        request
+    };
+    let request = as_admin(post_json("/api/v1/workspaces", json!({ "name": "alpha" })));
+    assert_eq!(send(&app, request).await.0, StatusCode::CREATED);
+    let request = as_admin(upload("/api/v1/workspaces/alpha/ingest", None, "lighthouse"));
+    let (status, body) = send(&app, request).await;
+    assert!(status.is_success(), "{}", body);
+    let id = serde_json::from_str::<Value>(&body).unwrap()["dataset_id"].as_u64().unwrap();
+
+    let uri = format!("/api/v1/workspaces/alpha/datasets/{id}");
+    let tag = Request::patch(&uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(json!({ "tags": ["internal"] }).to_string()))
+        .unwrap();
+    assert_eq!(send(&app, as_admin(tag)).await.0, StatusCode::OK);
+
+    // Hidden from the restricted key, so not found for it and kept
+    let delete =
+        |key: &str| Request::delete(&uri).header("X-Api-Key", key).body(Body::empty()).unwrap();
+    let (status, body) = send(&app, delete("public-key")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+    let listing = as_admin(get("/api/v1/workspaces/alpha/datasets").body(Body::empty()).unwrap());
+    let (_, body) = send(&app, listing).await;
+    assert_eq!(serde_json::from_str::<Vec<Value>>(&body).unwrap().len(), 1);
+
+    let (status, body) = send(&app, delete("admin-key")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_clone_copies_a_workspace() {
+    let app = two_workspaces().await;
+    rebuild(&app, "alpha").await;
+
+    let request = json!({ "name": "alpha-experiment", "include_blobs": true });
+    let (status, body) =
+        send(&app, post_json("/api/v1/workspaces/alpha/clone", request.clone())).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    let clone: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(clone["workspace"]["settings"]["cloned_from"]["workspace"], "alpha");
+    assert_eq!(clone["verification"]["source"], clone["verification"]["clone"]);
+    assert_eq!(clone["verification"]["clone"]["datasets"], 1);
+    assert_eq!(clone["verification"]["clone"]["blobs"], 1);
+    assert_eq!(clone["usage"]["datasets"], 1);
+
+    // The clone has the source's index and answers queries from its own copy
+    let query = json!({ "text": "landmark", "top_k": 10 });
+    let (status, body) =
+        send(&app, post_json("/api/v1/workspaces/alpha-experiment/query", query)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.contains("lighthouse"));
+    assert!(!body.contains("windmill"));
+
+    let uri = "/api/v1/workspaces/alpha-experiment/usage";
+    let (_, body) = send(&app, get(uri).body(Body::empty()).unwrap()).await;
+    let usage: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(usage["usage"]["datasets"], 1);
+
+    // Names stay unique
+    let (status, _) = send(&app, post_json("/api/v1/workspaces/beta/clone", request)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_backends_without_a_store_provider_serve_the_default_workspace_only() {
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let state = Arc::new(default_only_state(workspaces.clone()));
+    let app = create_router(state.clone());
+
+    let (status, body) =
+        send(&app, post_json("/api/v1/workspaces", json!({ "name": "alpha" }))).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED, "{}", body);
+    let (status, body) = send(&app, upload("/api/v1/ingest", None, "lighthouse on the cape")).await;
+    assert!(status.is_success(), "{}", body);
+    assert!(state.unservable_workspaces().await.unwrap().is_empty());
+
+    // A workspace created behind the server's back, e.g. by an older version
+    let config = WorkspaceConfig {
+        crs: 4326,
+        distance_unit: DistanceUnit::Meters,
+        geometry_validity: ValidityMode::Lenient,
+    };
+    workspaces.create_workspace("beta", &config).await.unwrap();
+    let names: Vec<String> = state
+        .unservable_workspaces()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|w| w.name)
+        .collect();
+    assert_eq!(names, vec!["beta"]);
+
+    // A store provider keeps it apart, so it can be served
+    let state =
+        default_only_state(workspaces).with_store_provider(Arc::new(MemoryStoreProvider::new()));
+    assert!(state.unservable_workspaces().await.unwrap().is_empty());
+}
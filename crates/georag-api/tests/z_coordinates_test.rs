@@ -0,0 +1,104 @@
+//! Integration tests for Z coordinates on the way through the API
+//!
+//! A survey upload has elevations on every position. Sampling the dataset
+//! must return each geometry type with its Z as uploaded; a workspace set to
+//! drop Z stores and returns the same features in two dimensions.
+
+mod common;
+
+use axum::http::StatusCode;
+use axum::Router;
+use common::{app, create_workspace, get, json_request, send, upload};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// One geometry of every type, each position with an elevation
+fn survey_geometries() -> Vec<(&'static str, Value)> {
+    let ring = json!([
+        [106.80, -6.20, 10.0],
+        [106.81, -6.20, 11.0],
+        [106.81, -6.19, 12.5],
+        [106.80, -6.20, 10.0]
+    ]);
+    vec![
+        ("benchmark", json!({ "type": "Point", "coordinates": [106.80, -6.20, 7.25] })),
+        (
+            "levee",
+            json!({ "type": "LineString", "coordinates": [[106.80, -6.20, 3.0], [106.82, -6.21, 3.5]] }),
+        ),
+        ("pond", json!({ "type": "Polygon", "coordinates": [ring] })),
+        (
+            "wells",
+            json!({ "type": "MultiPoint", "coordinates": [[106.80, -6.20, -40.0], [106.83, -6.22, -55.5]] }),
+        ),
+        (
+            "channels",
+            json!({ "type": "MultiLineString", "coordinates": [
+                [[106.80, -6.20, 1.0], [106.81, -6.21, 1.5]],
+                [[106.82, -6.22, 2.0], [106.83, -6.23, 2.5]]
+            ] }),
+        ),
+        ("paddies", json!({ "type": "MultiPolygon", "coordinates": [[ring], [ring]] })),
+    ]
+}
+
+/// Create workspace `survey` with the given settings and upload the survey to it
+async fn survey_workspace(settings: Value) -> (Router, u64) {
+    let app = app();
+    create_workspace(&app, "survey").await;
+    let (status, body) =
+        send(&app, json_request("PUT", "/api/v1/workspaces/survey/settings", settings)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+
+    let features: Vec<Value> = survey_geometries()
+        .into_iter()
+        .map(|(name, geometry)| {
+            json!({ "type": "Feature", "geometry": geometry, "properties": { "name": name } })
+        })
+        .collect();
+    let geojson = json!({ "type": "FeatureCollection", "features": features });
+    let upload = upload(
+        "/api/v1/workspaces/survey/ingest",
+        "survey.geojson",
+        geojson.to_string().as_bytes(),
+    );
+    let (status, body) = send(&app, upload).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    (app, body["dataset_id"].as_u64().unwrap())
+}
+
+/// Geometries of the sampled features by name
+async fn sampled_geometries(app: &Router, dataset_id: u64) -> HashMap<String, Value> {
+    let uri = format!("/api/v1/workspaces/survey/datasets/{}/sample?n=20", dataset_id);
+    let (status, body) = send(app, get(&uri)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["properties"]["name"].as_str().unwrap().to_string(), f["geometry"].clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_every_geometry_type_keeps_its_z() {
+    let (app, dataset_id) = survey_workspace(json!({ "z_coordinates": "keep" })).await;
+    let sampled = sampled_geometries(&app, dataset_id).await;
+
+    assert_eq!(sampled.len(), 6);
+    for (name, geometry) in survey_geometries() {
+        assert_eq!(sampled[name], geometry, "{}", name);
+    }
+}
+
+#[tokio::test]
+async fn test_workspace_can_drop_z() {
+    let (app, dataset_id) = survey_workspace(json!({ "z_coordinates": "drop" })).await;
+    let sampled = sampled_geometries(&app, dataset_id).await;
+
+    assert_eq!(sampled["benchmark"], json!({ "type": "Point", "coordinates": [106.80, -6.20] }));
+    assert_eq!(
+        sampled["levee"],
+        json!({ "type": "LineString", "coordinates": [[106.80, -6.20], [106.82, -6.21]] })
+    );
+}
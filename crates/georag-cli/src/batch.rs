@@ -1,6 +1,11 @@
+use crate::errors::{EXIT_ABORTED, EXIT_ERROR, EXIT_PARTIAL_FAILURE, EXIT_SUCCESS};
 use crate::output::OutputWriter;
+use crate::output_types::{BatchFailureInfo, BatchOutput};
+use crate::progress::report_throughput;
 use anyhow::{Context, Result};
 use georag_core::formats::FormatRegistry;
+use georag_core::progress::PhaseRate;
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -26,6 +31,70 @@ pub struct FileProcessingResult {
     pub dataset_name: Option<String>,
 }
 
+/// Policy deciding when a batch run stops after file failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Process every file regardless of failures
+    ContinueAll,
+
+    /// Stop at the first failed file
+    FailFast,
+
+    /// Stop once more than the given number of files have failed
+    MaxFailures(usize),
+}
+
+impl FailurePolicy {
+    /// Build a policy from the `add` command flags
+    pub fn from_flags(fail_fast: bool, max_failures: Option<usize>) -> Self {
+        if fail_fast {
+            FailurePolicy::FailFast
+        } else if let Some(max) = max_failures {
+            FailurePolicy::MaxFailures(max)
+        } else {
+            FailurePolicy::ContinueAll
+        }
+    }
+
+    /// Check whether the batch should stop given the current failure count
+    pub fn should_abort(&self, failures: usize) -> bool {
+        match self {
+            FailurePolicy::ContinueAll => false,
+            FailurePolicy::FailFast => failures > 0,
+            FailurePolicy::MaxFailures(max) => failures > *max,
+        }
+    }
+}
+
+/// Overall outcome of a batch run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOutcome {
+    /// Every file was processed successfully
+    Success,
+
+    /// Some files failed, the rest were added
+    PartialFailure,
+
+    /// Every file failed
+    Failure,
+
+    /// The failure policy stopped the run before all files were processed
+    Aborted,
+}
+
+impl BatchOutcome {
+    /// Process exit code for this outcome
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            BatchOutcome::Success => EXIT_SUCCESS,
+            BatchOutcome::PartialFailure => EXIT_PARTIAL_FAILURE,
+            BatchOutcome::Failure => EXIT_ERROR,
+            BatchOutcome::Aborted => EXIT_ABORTED,
+        }
+    }
+}
+
 /// Summary of batch processing results
 #[derive(Debug, Clone)]
 pub struct BatchSummary {
@@ -37,6 +106,12 @@ pub struct BatchSummary {
 
     /// Failed files
     pub failed: Vec<FileProcessingResult>,
+
+    /// Files that were not processed because the batch was aborted
+    pub skipped: Vec<PathBuf>,
+
+    /// Average rate of each phase of the batch
+    pub throughput: Vec<PhaseRate>,
 }
 
 impl BatchSummary {
@@ -46,6 +121,8 @@ impl BatchSummary {
             total_files: 0,
             successful: Vec::new(),
             failed: Vec::new(),
+            skipped: Vec::new(),
+            throughput: Vec::new(),
         }
     }
 
@@ -59,6 +136,19 @@ impl BatchSummary {
         self.failed.push(result);
     }
 
+    /// Record a file that was not processed because the batch was aborted
+    pub fn add_skipped(&mut self, path: PathBuf) {
+        self.skipped.push(path);
+    }
+
+    /// Whether the failure policy left files unprocessed
+    ///
+    /// A policy tripping on the last file leaves none, so that batch
+    /// completed, with failures.
+    pub fn aborted(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+
     /// Get success count
     pub fn success_count(&self) -> usize {
         self.successful.len()
@@ -74,6 +164,43 @@ impl BatchSummary {
         self.failed.is_empty()
     }
 
+    /// Overall outcome of the batch
+    pub fn outcome(&self) -> BatchOutcome {
+        if self.aborted() {
+            BatchOutcome::Aborted
+        } else if self.failed.is_empty() {
+            BatchOutcome::Success
+        } else if self.successful.is_empty() {
+            BatchOutcome::Failure
+        } else {
+            BatchOutcome::PartialFailure
+        }
+    }
+
+    /// Build the machine-readable summary
+    pub fn to_output(&self) -> BatchOutput {
+        let outcome = self.outcome();
+        BatchOutput {
+            outcome,
+            exit_code: outcome.exit_code(),
+            total_files: self.total_files,
+            successful: self.success_count(),
+            failed: self.failure_count(),
+            skipped: self.skipped.len(),
+            datasets: self.successful.iter().filter_map(|r| r.dataset_name.clone()).collect(),
+            failures: self
+                .failed
+                .iter()
+                .map(|r| BatchFailureInfo {
+                    path: r.path.display().to_string(),
+                    format_name: r.format_name.clone(),
+                    error: r.error.clone().unwrap_or_else(|| "unknown error".to_string()),
+                })
+                .collect(),
+            throughput: self.throughput.clone(),
+        }
+    }
+
     /// Get summary by format
     pub fn summary_by_format(&self) -> std::collections::HashMap<String, FormatSummary> {
         let mut format_summaries = std::collections::HashMap::new();
@@ -99,10 +226,19 @@ impl BatchSummary {
 
     /// Display summary to output
     pub fn display(&self, output: &OutputWriter) {
+        if output.is_json() {
+            let _ = output.result(self.to_output());
+            return;
+        }
+
         output.section("Batch Processing Summary");
         output.kv("Total Files", self.total_files);
         output.kv("Successful", self.success_count());
         output.kv("Failed", self.failure_count());
+        if self.aborted() {
+            output.kv("Not Processed", self.skipped.len());
+        }
+        report_throughput(&self.throughput, output);
 
         // Display summary by format
         let format_summaries = self.summary_by_format();
@@ -139,6 +275,13 @@ impl BatchSummary {
                 ));
             }
         }
+
+        if self.aborted() {
+            output.warning(format!(
+                "Batch aborted by failure policy; {} file(s) were not processed",
+                self.skipped.len()
+            ));
+        }
     }
 }
 
@@ -211,14 +354,16 @@ pub fn scan_directory(
     Ok(discovered)
 }
 
-/// Display progress for a file being processed
+/// Display the file about to be processed, in verbose mode
+///
+/// The count of files processed is shown by the batch's progress.
 pub fn display_file_progress(
     output: &OutputWriter,
     current: usize,
     total: usize,
     file: &DiscoveredFile,
 ) {
-    output.info(format!(
+    output.verbose(format!(
         "[{}/{}] Processing {} ({}, {} bytes)",
         current,
         total,
@@ -318,6 +463,63 @@ mod tests {
         assert_eq!(format_summaries.get("PDF").unwrap().failed, 0);
     }
 
+    #[test]
+    fn test_failure_policy_from_flags() {
+        assert_eq!(FailurePolicy::from_flags(false, None), FailurePolicy::ContinueAll);
+        assert_eq!(FailurePolicy::from_flags(true, None), FailurePolicy::FailFast);
+        assert_eq!(FailurePolicy::from_flags(false, Some(3)), FailurePolicy::MaxFailures(3));
+    }
+
+    #[test]
+    fn test_failure_policy_should_abort() {
+        assert!(!FailurePolicy::ContinueAll.should_abort(100));
+        assert!(!FailurePolicy::FailFast.should_abort(0));
+        assert!(FailurePolicy::FailFast.should_abort(1));
+        assert!(!FailurePolicy::MaxFailures(2).should_abort(2));
+        assert!(FailurePolicy::MaxFailures(2).should_abort(3));
+    }
+
+    #[test]
+    fn test_batch_outcome() {
+        let success = FileProcessingResult {
+            path: PathBuf::from("ok.geojson"),
+            format_name: "GeoJSON".to_string(),
+            error: None,
+            dataset_name: Some("ok".to_string()),
+        };
+        let failure = FileProcessingResult {
+            path: PathBuf::from("bad.geojson"),
+            format_name: "GeoJSON".to_string(),
+            error: Some("Invalid file".to_string()),
+            dataset_name: None,
+        };
+
+        let mut summary = BatchSummary::new();
+        summary.add_success(success.clone());
+        assert_eq!(summary.outcome(), BatchOutcome::Success);
+        assert_eq!(summary.outcome().exit_code(), EXIT_SUCCESS);
+
+        summary.add_failure(failure.clone());
+        assert_eq!(summary.outcome(), BatchOutcome::PartialFailure);
+        assert_eq!(summary.outcome().exit_code(), EXIT_PARTIAL_FAILURE);
+
+        summary.add_skipped(PathBuf::from("later.geojson"));
+        assert_eq!(summary.outcome(), BatchOutcome::Aborted);
+        assert_eq!(summary.to_output().skipped, 1);
+
+        let mut all_failed = BatchSummary::new();
+        all_failed.add_failure(failure.clone());
+        assert_eq!(all_failed.outcome(), BatchOutcome::Failure);
+
+        // --fail-fast tripping on the last file leaves nothing unprocessed
+        let mut last_failed = BatchSummary::new();
+        last_failed.add_success(success);
+        last_failed.add_failure(failure);
+        assert!(FailurePolicy::FailFast.should_abort(last_failed.failure_count()));
+        assert!(!last_failed.aborted());
+        assert_eq!(last_failed.outcome(), BatchOutcome::PartialFailure);
+    }
+
     #[test]
     fn test_format_summary() {
         let summary = FormatSummary::new();
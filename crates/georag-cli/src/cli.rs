@@ -1,4 +1,11 @@
 use clap::{Parser, Subcommand};
+use georag_core::formats::DEFAULT_INSPECT_SAMPLE;
+use georag_retrieval::export::ResultFormat;
+use georag_retrieval::grouping::TimeGrouping;
+use georag_retrieval::models::{AttributeFilter, SeedMode};
+use georag_retrieval::rerank::{RerankMode, DEFAULT_RERANK_MODEL};
+use georag_service::remote::DEFAULT_MAX_DOWNLOAD_BYTES;
+use georag_service::ConvertFormat;
 use std::path::PathBuf;
 
 /// GeoRAG - Geospatial retrieval-augmented system
@@ -23,6 +30,19 @@ pub struct Cli {
     #[arg(long, global = true, default_value = "memory")]
     pub storage: StorageBackend,
 
+    /// Serve read-only from an offline bundle instead of the storage backend
+    #[arg(long, global = true, value_name = "PATH")]
+    pub from_bundle: Option<PathBuf>,
+
+    /// Workspace directory to operate on (overrides GEORAG_WORKSPACE and the
+    /// search upward from the current directory)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub workspace: Option<PathBuf>,
+
+    /// Show detailed output
+    #[arg(long, short = 'v', global = true)]
+    pub verbose: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -44,15 +64,51 @@ pub enum Commands {
     /// Add a dataset to the workspace
     Add(AddArgs),
 
+    /// Show or change the access tags of a dataset
+    Tags(TagsArgs),
+
+    /// Inspect the features of a stored dataset
+    Dataset(DatasetArgs),
+
+    /// Document the properties and geometry of stored datasets
+    Schema(SchemaArgs),
+
+    /// Copy properties from one dataset's features onto another's by location
+    Join(JoinArgs),
+
+    /// Compare a new version of a dataset file with the stored dataset
+    Diff(DiffArgs),
+
+    /// Geometry utilities for preparing dataset files
+    Geo(GeoArgs),
+
+    /// List the supported file formats and their reader options
+    Formats(FormatsArgs),
+
+    /// Convert a dataset file to GeoJSON or CSV without a workspace
+    Convert(ConvertArgs),
+
+    /// Summarize a dataset file before adding it, reading as little as possible
+    Inspect(InspectArgs),
+
     /// Build the retrieval index
     Build(BuildArgs),
 
+    /// Export a query-ready subset of the stores
+    Export(ExportArgs),
+
     /// Query the index
     Query(QueryArgs),
 
     /// Show workspace status and information
     Status(StatusArgs),
 
+    /// Sync workspace settings between config.toml and the store
+    Config(ConfigArgs),
+
+    /// Manage named query presets of the workspace
+    Preset(PresetArgs),
+
     /// Migrate data from in-memory storage to PostgreSQL
     Migrate(MigrateArgs),
 
@@ -61,6 +117,9 @@ pub enum Commands {
 
     /// Run health checks and diagnostics
     Doctor(DoctorArgs),
+
+    /// Run an end-to-end smoke test against an embedded fixture
+    SelfTest(SelfTestArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -93,7 +152,8 @@ pub struct InitArgs {
 #[derive(Parser, Debug)]
 pub struct AddArgs {
     /// Path to the dataset file or directory (GeoJSON, Shapefile, GPX, KML, PDF, DOCX)
-    /// If a directory is provided, all supported files will be processed
+    /// If a directory is provided, all supported files will be processed; an http(s)
+    /// URL of a single file is downloaded first
     pub path: PathBuf,
 
     /// Dataset name (defaults to filename)
@@ -108,8 +168,23 @@ pub struct AddArgs {
     #[arg(long, short = 'i')]
     pub interactive: bool,
 
+    /// Access tags for the dataset (comma-separated, e.g. "public" or "internal,finance")
+    /// API keys restricted to other tags cannot see the dataset
+    #[arg(long, value_name = "TAGS", value_delimiter = ',')]
+    pub tags: Vec<String>,
+
+    /// License the dataset is published under (e.g. "CC-BY-4.0")
+    /// Overrides a license declared by the file itself
+    #[arg(long, value_name = "LICENSE")]
+    pub license: Option<String>,
+
+    /// Credit line the license requires, shown with query results from the dataset
+    /// Overrides an attribution declared by the file itself
+    #[arg(long, value_name = "TEXT")]
+    pub attribution: Option<String>,
+
     /// GPX track type filter (tracks, routes, waypoints, or all)
-    /// Only applicable for GPX files
+    /// Only applicable for GPX files; see `georag formats list` for every format's options
     #[arg(long, value_name = "TYPE")]
     pub track_type: Option<String>,
 
@@ -118,6 +193,16 @@ pub struct AddArgs {
     #[arg(long, value_name = "PATH")]
     pub folder: Option<String>,
 
+    /// Coordinate order of the file: lonlat, latlon or auto (defaults to the axis_order setting)
+    /// Only applicable for GeoJSON files; coordinates are always stored lon,lat
+    #[arg(long, value_name = "ORDER", value_parser = ["lonlat", "latlon", "auto"])]
+    pub axis_order: Option<String>,
+
+    /// EPSG code of the dataset, for files whose CRS cannot be identified
+    /// Only applicable for Shapefiles; overrides the .prj file
+    #[arg(long, value_name = "EPSG")]
+    pub crs: Option<u32>,
+
     /// Associate geometry with document (for PDF, DOCX)
     /// Can be a GeoJSON geometry string or path to a GeoJSON file
     /// Example: --geometry '{"type":"Point","coordinates":[-122.4,47.6]}'
@@ -125,6 +210,12 @@ pub struct AddArgs {
     #[arg(long, value_name = "GEOMETRY")]
     pub geometry: Option<String>,
 
+    /// Associate named places with document (for PDF, DOCX that discuss several sites)
+    /// A GeoJSON FeatureCollection whose features have a "name" property; each chunk
+    /// is located by the places its text names
+    #[arg(long, value_name = "FILE", conflicts_with = "geometry")]
+    pub places: Option<String>,
+
     /// Process files in parallel (for batch operations)
     #[arg(long, default_value = "true")]
     pub parallel: bool,
@@ -133,39 +224,414 @@ pub struct AddArgs {
     #[arg(long, short = 'j', default_value = "0")]
     pub jobs: usize,
 
-    /// Continue processing remaining files if one fails
-    #[arg(long)]
+    /// Deprecated: batches continue after failed files unless --fail-fast or
+    /// --max-failures is given
+    #[arg(long, hide = true, conflicts_with_all = ["fail_fast", "max_failures"])]
     pub continue_on_error: bool,
+
+    /// Stop the batch at the first failed file
+    #[arg(long, conflicts_with = "max_failures")]
+    pub fail_fast: bool,
+
+    /// Abort the batch once more than N files have failed
+    #[arg(long, value_name = "N")]
+    pub max_failures: Option<usize>,
+
+    /// Largest file downloaded when the path is a URL, in bytes
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_DOWNLOAD_BYTES)]
+    pub max_download_bytes: u64,
+}
+
+#[derive(Parser, Debug)]
+pub struct TagsArgs {
+    /// Dataset name
+    pub dataset: String,
+
+    /// Replace the dataset's tags (comma-separated)
+    #[arg(long, value_name = "TAGS", value_delimiter = ',', conflicts_with = "clear")]
+    pub set: Option<Vec<String>>,
+
+    /// Remove all tags, making the dataset visible to every API key
+    #[arg(long)]
+    pub clear: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct DatasetArgs {
+    /// Dataset operation
+    #[command(subcommand)]
+    pub command: DatasetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DatasetCommand {
+    /// Show a sample of a dataset's features
+    Sample(SampleArgs),
+
+    /// Find features stored lat,lon and swap them to lon,lat
+    RepairAxes(RepairAxesArgs),
+
+    /// Delete or archive every dataset matching a filter
+    Delete(DeleteDatasetsArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct SampleArgs {
+    /// Dataset name
+    pub name: String,
+
+    /// Number of features, capped at the max_sample setting
+    #[arg(short = 'n', long = "count", value_name = "N", default_value_t = 20)]
+    pub count: usize,
+
+    /// Draw features at random, or spread them across the dataset extent
+    #[arg(long, default_value = "random", value_parser = ["random", "spatial"])]
+    pub strategy: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct RepairAxesArgs {
+    /// Dataset name
+    pub name: String,
+
+    /// Swap the flagged features; without it only the findings are reported
+    #[arg(long)]
+    pub apply: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct DeleteDatasetsArgs {
+    /// Names matching a shell-style pattern, e.g. 'test_*'
+    #[arg(long = "match", value_name = "PATTERN")]
+    pub pattern: Option<String>,
+
+    /// Names containing a match of a regular expression
+    #[arg(long, value_name = "REGEX", conflicts_with = "pattern")]
+    pub regex: Option<String>,
+
+    /// Only datasets added before this date (YYYY-MM-DD) or RFC 3339 time
+    #[arg(long, value_name = "DATE")]
+    pub added_before: Option<String>,
+
+    /// Only datasets carrying this access tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Archive the datasets instead: keep their features but leave them out of builds
+    #[arg(long)]
+    pub archive: bool,
+
+    /// Delete the matching datasets; without it they are only listed
+    #[arg(long)]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct SchemaArgs {
+    /// Schema operation
+    #[command(subcommand)]
+    pub command: SchemaCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SchemaCommand {
+    /// Write a data dictionary of the workspace's datasets
+    Export(SchemaExportArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct SchemaExportArgs {
+    /// Only document this dataset
+    #[arg(long)]
+    pub dataset: Option<String>,
+
+    /// Markdown data dictionary, or one JSON Schema document per dataset
+    #[arg(long, default_value = "markdown", value_parser = ["markdown", "json"])]
+    pub format: String,
+
+    /// File to write (defaults to standard output)
+    #[arg(long, short = 'o')]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct JoinArgs {
+    /// Dataset whose features receive the properties
+    #[arg(long)]
+    pub target: String,
+
+    /// Dataset the properties are copied from
+    #[arg(long)]
+    pub source: String,
+
+    /// How target features are matched to source features
+    #[arg(long, default_value = "within", value_parser = ["within", "intersects", "nearest"])]
+    pub predicate: String,
+
+    /// Source properties to copy (comma-separated)
+    #[arg(long, value_name = "KEYS", value_delimiter = ',', required = true)]
+    pub take: Vec<String>,
+
+    /// Prefix for copied property names (defaults to "<source>_")
+    #[arg(long)]
+    pub prefix: Option<String>,
+
+    /// Maximum distance for the nearest predicate (e.g., "500m", "2km")
+    #[arg(long)]
+    pub max_distance: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DiffArgs {
+    /// Path to the new version of the dataset file
+    pub path: PathBuf,
+
+    /// Name of the stored dataset to compare against
+    #[arg(long, value_name = "DATASET")]
+    pub against: String,
+
+    /// Property identifying a feature across versions; features without it
+    /// are matched by content hash
+    #[arg(long, value_name = "PROPERTY", default_value = "id")]
+    pub key: String,
+
+    /// Write the changed, added and removed features to the dataset
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Number of features listed per change class
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub sample: usize,
+}
+
+#[derive(Parser, Debug)]
+pub struct GeoArgs {
+    /// Geometry utility
+    #[command(subcommand)]
+    pub command: GeoCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GeoCommand {
+    /// Buffer every feature of a dataset file and write the result as GeoJSON
+    Buffer(BufferArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct BufferArgs {
+    /// Dataset file to buffer
+    pub input: PathBuf,
+
+    /// Buffer distance (e.g., "200m", "1.5km"; meters when no unit is given)
+    #[arg(long)]
+    pub distance: String,
+
+    /// GeoJSON file to write the buffered features to
+    #[arg(long, short = 'o')]
+    pub output: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+pub struct FormatsArgs {
+    /// Formats operation
+    #[command(subcommand)]
+    pub command: FormatsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FormatsCommand {
+    /// Show each format's extensions and the options `add` accepts for it
+    List,
+}
+
+#[derive(Parser, Debug)]
+pub struct InspectArgs {
+    /// Dataset file to inspect, in any format `georag formats list` shows
+    pub path: PathBuf,
+
+    /// Features to parse for the property keys and the time estimate
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_INSPECT_SAMPLE)]
+    pub sample: usize,
+
+    /// Reader option, as "KEY=VALUE" (repeatable; see `georag formats list`)
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub options: Vec<(String, String)>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ConvertArgs {
+    /// Dataset file to convert, in any format `georag formats list` shows
+    pub input: PathBuf,
+
+    /// Format to write: geojson or csv
+    #[arg(long, value_name = "FORMAT")]
+    pub to: ConvertFormat,
+
+    /// File to write (defaults to standard output)
+    #[arg(long, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// EPSG code to reproject the features to (defaults to the file's CRS)
+    #[arg(long, value_name = "EPSG")]
+    pub crs: Option<u32>,
+
+    /// Rename a property, as "OLD=NEW"; "OLD=" drops it (repeatable)
+    #[arg(long = "map", value_name = "OLD=NEW", value_parser = parse_key_value)]
+    pub map: Vec<(String, String)>,
+
+    /// Simplify features with more vertices than this
+    #[arg(long, value_name = "N")]
+    pub max_vertices: Option<usize>,
+
+    /// Write one file per layer (KML folder, GPX waypoints/tracks/routes), named
+    /// after --output with the layer appended, instead of one combined collection
+    #[arg(long, requires = "output")]
+    pub split_layers: bool,
+
+    /// Reader option, as "KEY=VALUE" (repeatable; see `georag formats list`)
+    #[arg(long = "option", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub options: Vec<(String, String)>,
+
+    /// Skip features that cannot be converted instead of failing, listing them
+    #[arg(long)]
+    pub skip_invalid: bool,
+}
+
+/// Split a "KEY=VALUE" argument; the value may be empty
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("'{}' is not of the form KEY=VALUE", s)),
+    }
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    /// Write an offline bundle of the features, chunks and embeddings in --bbox
+    #[arg(long, requires = "bbox")]
+    pub offline_bundle: bool,
+
+    /// Bounding box to export: "min_lon,min_lat,max_lon,max_lat"
+    #[arg(long, allow_hyphen_values = true)]
+    pub bbox: Option<String>,
+
+    /// Bundle file to write (e.g., "bundle.georag")
+    #[arg(long, short = 'o')]
+    pub output: PathBuf,
 }
 
 #[derive(Parser, Debug)]
 pub struct BuildArgs {
-    /// Embedder to use (e.g., "ollama:nomic-embed-text")
-    #[arg(long, default_value = "ollama:nomic-embed-text")]
-    pub embedder: String,
+    /// Embedder to use (e.g., "ollama:nomic-embed-text"); defaults to the workspace setting
+    #[arg(long)]
+    pub embedder: Option<String>,
 
     /// Force rebuild even if index is up to date
     #[arg(long)]
     pub force: bool,
+
+    /// Continue an interrupted build from its last checkpoint
+    #[arg(long)]
+    pub resume_build: bool,
+
+    /// Maximum embeddings per second sent to the embedder
+    #[arg(long, value_name = "PER_SECOND", value_parser = parse_rate_limit)]
+    pub rate_limit: Option<f64>,
+
+    /// Save a build checkpoint every N embedded batches
+    #[arg(long, value_name = "BATCHES", default_value_t = 10)]
+    pub checkpoint_every: usize,
+
+    /// Embed the last chunk of each batch again on its own to catch embeddings returned out of order
+    #[arg(long)]
+    pub check_order: bool,
+
+    /// Build only these datasets (comma-separated names), merging them into the existing index
+    #[arg(
+        long,
+        value_name = "NAMES",
+        value_delimiter = ',',
+        conflicts_with_all = ["force", "resume_build"]
+    )]
+    pub datasets: Vec<String>,
+}
+
+fn parse_rate_limit(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if rate.is_finite() && rate > 0.0 => Ok(rate),
+        _ => Err(format!("'{}' is not a positive number of embeddings per second", s)),
+    }
 }
 
 #[derive(Parser, Debug)]
 pub struct QueryArgs {
-    /// The query text
-    pub query: String,
-
-    /// Spatial filter predicate (within, intersects, contains, bbox, dwithin)
-    #[arg(long)]
+    /// The query text; optional with --like-feature, where it only labels
+    /// the query
+    #[arg(required_unless_present = "like_feature")]
+    pub query: Option<String>,
+
+    /// Rank results by similarity to a stored feature instead of the query
+    /// text, searching with its chunks' stored embeddings: "DATASET:FEATURE"
+    /// with the dataset's name or ID
+    #[arg(long, value_name = "DATASET:FEATURE", conflicts_with_all = ["no_rerank", "parse_operators"])]
+    pub like_feature: Option<String>,
+
+    /// How --like-feature searches with a feature's chunks: average (the mean
+    /// of their embeddings) or individual (each one, keeping a result's best
+    /// score)
+    #[arg(long, value_name = "MODE", default_value = "average")]
+    pub seed_mode: SeedMode,
+
+    /// Spatial filter predicate, read as "feature <predicate> filter geometry"
+    /// (within, intersects, contains, coveredby, touches, crosses, overlaps,
+    /// bbox, dwithin); defaults to intersects for --geometry and --area, bbox
+    /// for --bbox and default_spatial_predicate for --at
+    #[arg(long, visible_alias = "predicate")]
     pub spatial: Option<String>,
 
-    /// Filter geometry (GeoJSON string or file path)
+    /// Filter geometry (GeoJSON string, or a file holding a geometry, Feature
+    /// or FeatureCollection)
     #[arg(long)]
     pub geometry: Option<String>,
 
-    /// Distance for proximity queries (e.g., "5km", "100m")
+    /// Filter bounding box: "min_lon,min_lat,max_lon,max_lat"
+    #[arg(long, allow_hyphen_values = true)]
+    pub bbox: Option<String>,
+
+    /// Saved area used as the filter geometry (see `POST /api/v1/areas`)
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["geometry", "bbox"])]
+    pub area: Option<String>,
+
+    /// Search around a point: "lon,lat" in WGS 84
+    #[arg(
+        long,
+        value_name = "LON,LAT",
+        allow_hyphen_values = true,
+        conflicts_with_all = ["geometry", "bbox", "area"]
+    )]
+    pub at: Option<String>,
+
+    /// Distance for dwithin queries (e.g., "5km", "100m"); defaults to
+    /// default_radius for --at
     #[arg(long)]
     pub distance: Option<String>,
 
+    /// Buffer the filter geometry before matching (e.g., "200m" around a road)
+    #[arg(long)]
+    pub buffer: Option<String>,
+
+    /// Saved area removed from the filter: results intersecting it are
+    /// dropped; repeat to exclude several
+    #[arg(long, value_name = "NAME")]
+    pub exclude_area: Vec<String>,
+
+    /// Geometry removed from the filter, given like --geometry; repeat to
+    /// exclude several
+    #[arg(long, value_name = "GEOJSON")]
+    pub exclude_geometry: Vec<String>,
+
     /// Keywords that must appear in results (comma-separated)
     #[arg(long, value_delimiter = ',')]
     pub must_contain: Option<Vec<String>>,
@@ -174,13 +640,83 @@ pub struct QueryArgs {
     #[arg(long, value_delimiter = ',')]
     pub exclude: Option<Vec<String>>,
 
+    /// Keep results whose feature property has this value (e.g., "category=school");
+    /// repeat to require several
+    #[arg(long = "where", value_name = "PROPERTY=VALUE")]
+    pub attributes: Vec<AttributeFilter>,
+
     /// Disable semantic reranking
     #[arg(long)]
     pub no_rerank: bool,
 
-    /// Number of results to return
-    #[arg(long, short = 'k', default_value = "10")]
-    pub top_k: usize,
+    /// Rerank the best candidates before cutting to --top-k: none, lexical
+    /// (BM25 over the pool) or llm (ratings from an Ollama model); defaults
+    /// to none
+    #[arg(long, value_name = "MODE")]
+    pub rerank: Option<RerankMode>,
+
+    /// Number of candidates reranked [default: 50]
+    #[arg(long, value_name = "N")]
+    pub rerank_pool: Option<usize>,
+
+    /// Ollama model rating candidates with --rerank llm
+    #[arg(long, value_name = "MODEL", default_value = DEFAULT_RERANK_MODEL)]
+    pub rerank_model: String,
+
+    /// Number of results to return [default: 10]
+    #[arg(long, short = 'k')]
+    pub top_k: Option<usize>,
+
+    /// Query preset of the workspace whose options fill in those not given
+    /// here; defaults to default_query_preset
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Show the results this many at a time; at a terminal, :next shows the
+    /// next page without running the query again
+    #[arg(long, value_name = "N")]
+    pub page_size: Option<usize>,
+
+    /// Drop results with a similarity score below this value (0.0 to 1.0)
+    #[arg(long, value_name = "SCORE")]
+    pub min_score: Option<f32>,
+
+    /// Merge results cut from overlapping stretches of the same text into one
+    #[arg(long)]
+    pub merge_overlapping: bool,
+
+    /// Simplify filter geometries over the vertex limit instead of rejecting them
+    #[arg(long)]
+    pub simplify_filter: bool,
+
+    /// Bucket results by a timestamp property (e.g., "reported_at:month");
+    /// intervals: day, week, month, year
+    #[arg(long, value_name = "PROPERTY:INTERVAL")]
+    pub group_by_time: Option<TimeGrouping>,
+
+    /// Turn near:AREA, within:DISTANCE, dataset:NAME and since:DATE in the
+    /// query into filters (quote values with spaces: near:"ubud centre")
+    #[arg(long)]
+    pub parse_operators: bool,
+
+    /// Timestamp property since: applies to (defaults to the --group-by-time
+    /// property)
+    #[arg(long, value_name = "PROPERTY", requires = "parse_operators")]
+    pub time_property: Option<String>,
+
+    /// Print only the results as geojson, json (flat rows) or csv
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<ResultFormat>,
+
+    /// Show sources as a table of these result fields, also kept in --format
+    /// output; score, excerpt and document_path are always shown
+    #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+    pub fields: Vec<String>,
+
+    /// Also write a PNG map of the result geometries to this file; the
+    /// basemap comes from map_tile_url when set
+    #[arg(long, value_name = "PATH")]
+    pub map: Option<PathBuf>,
 
     /// Interactive mode - build query with prompts
     #[arg(long, short = 'i')]
@@ -189,10 +725,6 @@ pub struct QueryArgs {
 
 #[derive(Parser, Debug)]
 pub struct StatusArgs {
-    /// Show detailed status
-    #[arg(long)]
-    pub verbose: bool,
-
     /// Show only datasets information
     #[arg(long)]
     pub datasets: bool,
@@ -208,6 +740,131 @@ pub struct StatusArgs {
     /// Show only configuration
     #[arg(long)]
     pub config: bool,
+
+    /// Show only workspace usage against its quotas
+    #[arg(long)]
+    pub usage: bool,
+
+    /// Show only workspace activity over the last two weeks
+    #[arg(long)]
+    pub timeline: bool,
+
+    /// Sort datasets by name, added or features
+    #[arg(long, default_value = "name")]
+    pub sort: String,
+
+    /// Dataset sort order: asc or desc
+    #[arg(long, default_value = "asc")]
+    pub order: String,
+}
+
+#[derive(Parser, Debug)]
+pub struct ConfigArgs {
+    /// Config operation
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Write the stored settings into config.toml
+    Pull,
+
+    /// Replace the stored settings with those in config.toml
+    Push,
+
+    /// Compare config.toml with the stored settings
+    Check,
+}
+
+#[derive(Parser, Debug)]
+pub struct PresetArgs {
+    /// Preset operation
+    #[command(subcommand)]
+    pub command: PresetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PresetCommand {
+    /// List the query presets and which one is the default
+    List,
+
+    /// Show the options of a preset
+    Show {
+        /// Preset name
+        name: String,
+    },
+
+    /// Save a preset, replacing any preset of the same name
+    Set(PresetSetArgs),
+
+    /// Delete a preset; deleting the default preset leaves no default
+    Delete {
+        /// Preset name
+        name: String,
+    },
+
+    /// Show the default preset, or make another one the default
+    Default {
+        /// Preset queries take when they name none
+        #[arg(conflicts_with = "clear")]
+        name: Option<String>,
+
+        /// Leave the workspace without a default preset
+        #[arg(long)]
+        clear: bool,
+    },
+}
+
+/// Options of a preset, checked as `georag query` checks the same flags
+#[derive(Parser, Debug)]
+pub struct PresetSetArgs {
+    /// Preset name: letters, digits, '-', '_' and '.'
+    pub name: String,
+
+    /// Number of results to return
+    #[arg(long, short = 'k')]
+    pub top_k: Option<usize>,
+
+    /// Drop results with a similarity score below this value (0.0 to 1.0)
+    #[arg(long, value_name = "SCORE")]
+    pub min_score: Option<f32>,
+
+    /// Rerank the best candidates: none, lexical or llm
+    #[arg(long, value_name = "MODE")]
+    pub rerank: Option<String>,
+
+    /// Number of candidates reranked
+    #[arg(long, value_name = "N")]
+    pub rerank_pool: Option<usize>,
+
+    /// Explain the queries taking the preset
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Merge results cut from overlapping stretches of the same text
+    #[arg(long)]
+    pub merge_overlapping: bool,
+
+    /// Turn operators in the query text into filters
+    #[arg(long)]
+    pub parse_operators: bool,
+
+    /// Geometry of API responses: full, centroid, bbox, none or adaptive
+    #[arg(long, value_name = "DETAIL")]
+    pub geometry_detail: Option<String>,
+
+    /// Vertex budget of adaptive geometry in API responses
+    #[arg(long, value_name = "N")]
+    pub vertex_budget: Option<usize>,
+
+    /// Decimal places of coordinates in API responses
+    #[arg(long, value_name = "DIGITS")]
+    pub coordinate_precision: Option<u32>,
+
+    /// Also make the preset the workspace default
+    #[arg(long)]
+    pub default: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -246,6 +903,18 @@ pub enum DbCommand {
 
     /// Run VACUUM and ANALYZE for maintenance
     Vacuum(VacuumArgs),
+
+    /// Find and delete embeddings and chunks that no longer belong to anything
+    Compact(CompactArgs),
+
+    /// Delete chunks of deleted features and mark chunks of changed features stale
+    Gc(GcArgs),
+
+    /// Check the per-dataset chunk and embedding counters against the stored data
+    Verify(VerifyArgs),
+
+    /// Upgrade the index to the schema version of this build
+    Upgrade(UpgradeArgs),
 }
 
 #[derive(Parser, Debug)]
@@ -282,8 +951,43 @@ pub struct VacuumArgs {
 }
 
 #[derive(Parser, Debug)]
-pub struct DoctorArgs {
-    /// Show detailed diagnostic information
+pub struct CompactArgs {
+    /// Delete the orphans found (only reports them by default)
     #[arg(long)]
-    pub verbose: bool,
+    pub apply: bool,
+
+    /// Number of IDs deleted per batch
+    #[arg(long, default_value = "500")]
+    pub batch_size: usize,
+}
+
+#[derive(Parser, Debug)]
+pub struct GcArgs {}
+
+#[derive(Parser, Debug)]
+pub struct VerifyArgs {
+    /// Count every dataset from its chunks and embeddings instead of comparing totals
+    #[arg(long)]
+    pub deep: bool,
+
+    /// Rebuild the counters from scratch when they drifted
+    #[arg(long)]
+    pub recount: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct UpgradeArgs {}
+
+#[derive(Parser, Debug)]
+pub struct DoctorArgs {}
+
+#[derive(Parser, Debug)]
+pub struct SelfTestArgs {
+    /// Also build and query the fixture index with the configured Ollama embedder
+    #[arg(long)]
+    pub with_ollama: bool,
+
+    /// Also check connectivity to PostgreSQL using DATABASE_URL (read-only)
+    #[arg(long)]
+    pub with_postgres: bool,
 }
@@ -1,50 +1,66 @@
 use crate::batch::{
-    display_file_progress, scan_directory, BatchSummary, DiscoveredFile, FileProcessingResult,
+    display_file_progress, scan_directory, BatchOutcome, BatchSummary, DiscoveredFile,
+    FailurePolicy, FileProcessingResult,
 };
 use crate::cli::AddArgs;
+use crate::config::{load_workspace_config_with_store, store_workspace_quota};
 use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::errors::CliError;
+use crate::geometry_arg::{parse_geometry_argument, parse_places_argument};
 use crate::output::OutputWriter;
-use crate::output_types::{AddOutput, CrsMismatchInfo};
+use crate::output_types::{AddOutput, CrsMismatchInfo, PipelineOutput};
+use crate::progress::{report_throughput, PhaseProgress};
 use crate::storage::Storage;
 use anyhow::{bail, Context, Result};
-use chrono::Utc;
 use futures::stream::{self, StreamExt};
+use georag_core::config::parse_axis_order;
 use georag_core::formats::{
-    docx::DocxReader, geojson::GeoJsonReader, gpx::GpxReader, kml::KmlReader, pdf::PdfReader,
-    shapefile::ShapefileFormatReader, FormatFeature, FormatRegistry,
+    FeatureError, FormatRegistry, ReadPolicy, TransformErrors, TransformPreview,
 };
-use georag_core::models::{Dataset, DatasetId, GeometryType};
+use georag_core::geo::OversizedFeature;
+use georag_core::models::{AuditEvent, AuditEventKind, AxisOrder, RemoteSource};
+use georag_core::resources::ResourceKind;
+use georag_service::remote::{self, is_remote, DownloadPolicy};
+use georag_service::{IngestRequest, ServiceError, SourcePolicy};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::Semaphore;
 
+/// Skipped features listed individually before the rest are summarized
+const MAX_LISTED_FEATURE_ERRORS: usize = 5;
+
 pub async fn execute(
     args: AddArgs,
     output: &OutputWriter,
     dry_run: bool,
     storage: &Storage,
+    workspace: Option<&Path>,
 ) -> Result<()> {
-    if !args.path.exists() {
+    let remote = args.path.to_str().filter(|path| is_remote(path)).map(String::from);
+    if remote.is_none() && !args.path.exists() {
         bail!("Path not found: {}", args.path.display());
     }
 
-    // Register all format readers
-    let mut registry = FormatRegistry::new();
-    registry.register(Box::new(GeoJsonReader));
-    registry.register(Box::new(ShapefileFormatReader));
-    registry.register(Box::new(GpxReader));
-    registry.register(Box::new(KmlReader));
-    registry.register(Box::new(PdfReader));
-    registry.register(Box::new(DocxReader));
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let registry = &storage.formats;
 
-    if args.path.is_dir() {
+    let result = if let Some(url) = remote {
+        execute_remote(args, &url, output, dry_run, &workspace_root, storage).await
+    } else if args.path.is_dir() {
         // Batch processing mode
-        execute_batch(args, output, dry_run, storage, &registry).await
+        execute_batch(args, output, dry_run, &workspace_root, storage, registry).await
     } else {
         // Single file mode
-        execute_single(args, output, dry_run, storage, &registry).await
+        execute_single(args, None, output, dry_run, &workspace_root, storage).await
+    };
+
+    if result.is_ok() && !dry_run {
+        super::seed_settings(storage, &workspace_root, output).await;
     }
+    result
 }
 
 /// Execute batch processing for a directory
@@ -52,6 +68,7 @@ async fn execute_batch(
     args: AddArgs,
     output: &OutputWriter,
     dry_run: bool,
+    workspace_root: &Path,
     storage: &Storage,
     registry: &FormatRegistry,
 ) -> Result<()> {
@@ -85,10 +102,21 @@ async fn execute_batch(
     }
 
     let total_files = discovered_files.len();
-    let continue_on_error = args.continue_on_error;
+    if args.continue_on_error {
+        output.warning(
+            "--continue-on-error is deprecated: batches process every file unless \
+             --fail-fast or --max-failures is given",
+        );
+    }
+    let policy = FailurePolicy::from_flags(args.fail_fast, args.max_failures);
+
+    let mut summary = BatchSummary::new();
+    summary.total_files = total_files;
+    let progress =
+        Mutex::new(PhaseProgress::start(output, "files", "Adding files", Some(total_files)));
 
     // Process files based on parallel flag
-    let results = if args.parallel {
+    if args.parallel {
         // Determine concurrency level
         let concurrency = if args.jobs == 0 {
             std::thread::available_parallelism().map(|p| p.get()).unwrap_or(4)
@@ -99,168 +127,205 @@ async fn execute_batch(
         output.info(format!("Processing with {} parallel jobs", concurrency));
 
         let semaphore = Arc::new(Semaphore::new(concurrency));
+        let failures = Arc::new(AtomicUsize::new(0));
+        let aborted = Arc::new(AtomicBool::new(false));
+        let completed = AtomicUsize::new(0);
 
-        // Clone shared state for parallel processing
-        let args_force = args.force;
-        let args_track_type = args.track_type.clone();
-        let args_folder = args.folder.clone();
-        let args_geometry = args.geometry.clone();
-
-        // Process files in parallel using buffer_unordered
-        let results: Vec<FileProcessingResult> = stream::iter(discovered_files)
+        // Process files in parallel using buffer_unordered; files picked up after
+        // the policy has tripped are reported as not processed
+        let results: Vec<(PathBuf, Option<FileProcessingResult>)> = stream::iter(discovered_files)
             .map(|file| {
                 let sem = semaphore.clone();
-                let track_type = args_track_type.clone();
-                let folder = args_folder.clone();
-                let geometry = args_geometry.clone();
+                let failures = failures.clone();
+                let aborted = aborted.clone();
+                let args = &args;
+                let completed = &completed;
+                let progress = &progress;
 
                 async move {
                     let _permit = sem.acquire().await.expect("Semaphore closed");
 
+                    if aborted.load(Ordering::SeqCst) {
+                        return (file.path.clone(), None);
+                    }
+
                     // Process the file
-                    let result = process_single_file(
-                        &file, args_force, track_type, folder, geometry, storage, registry,
-                    )
-                    .await;
-
-                    match result {
-                        Ok(dataset_name) => FileProcessingResult {
-                            path: file.path.clone(),
-                            format_name: file.format_name.clone(),
-                            error: None,
-                            dataset_name: Some(dataset_name),
-                        },
-                        Err(e) => FileProcessingResult {
-                            path: file.path.clone(),
-                            format_name: file.format_name.clone(),
-                            error: Some(e.to_string()),
-                            dataset_name: None,
-                        },
+                    let result = process_single_file(&file, args, workspace_root, storage).await;
+
+                    if result.is_err() {
+                        let failed = failures.fetch_add(1, Ordering::SeqCst) + 1;
+                        if policy.should_abort(failed) {
+                            aborted.store(true, Ordering::SeqCst);
+                        }
                     }
+
+                    let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                    progress.lock().unwrap().set(done);
+                    (file.path.clone(), Some(to_processing_result(&file, result)))
                 }
             })
             .buffer_unordered(concurrency)
             .collect()
             .await;
 
-        results
+        for (path, result) in results {
+            match result {
+                Some(result) if result.error.is_some() => summary.add_failure(result),
+                Some(result) => summary.add_success(result),
+                None => summary.add_skipped(path),
+            }
+        }
     } else {
         // Sequential processing
-        let mut results = Vec::with_capacity(discovered_files.len());
-
-        for (idx, file) in discovered_files.iter().enumerate() {
-            display_file_progress(output, idx + 1, discovered_files.len(), file);
-
-            let result = process_single_file(
-                file,
-                args.force,
-                args.track_type.clone(),
-                args.folder.clone(),
-                args.geometry.clone(),
-                storage,
-                registry,
-            )
-            .await;
+        let mut remaining = discovered_files.iter().enumerate();
 
-            let file_result = match result {
-                Ok(dataset_name) => FileProcessingResult {
-                    path: file.path.clone(),
-                    format_name: file.format_name.clone(),
-                    error: None,
-                    dataset_name: Some(dataset_name),
-                },
-                Err(e) => {
-                    let result = FileProcessingResult {
-                        path: file.path.clone(),
-                        format_name: file.format_name.clone(),
-                        error: Some(e.to_string()),
-                        dataset_name: None,
-                    };
-
-                    if !continue_on_error {
-                        results.push(result);
-                        break;
-                    }
+        for (idx, file) in remaining.by_ref() {
+            display_file_progress(output, idx + 1, total_files, file);
 
-                    result
-                }
-            };
+            let result = process_single_file(file, &args, workspace_root, storage).await;
+            progress.lock().unwrap().set(idx + 1);
 
-            results.push(file_result);
+            let file_result = to_processing_result(file, result);
+            if file_result.error.is_some() {
+                summary.add_failure(file_result);
+                if policy.should_abort(summary.failure_count()) {
+                    break;
+                }
+            } else {
+                summary.add_success(file_result);
+            }
         }
 
-        results
-    };
-
-    // Build summary from results
-    let mut summary = BatchSummary::new();
-    summary.total_files = total_files;
-
-    for result in results {
-        if result.error.is_some() {
-            summary.add_failure(result);
-        } else {
-            summary.add_success(result);
+        for (_, file) in remaining {
+            summary.add_skipped(file.path.clone());
         }
     }
+    summary.throughput.push(progress.into_inner().unwrap().finish());
 
     // Display summary
     summary.display(output);
 
-    // Return error if any files failed (but still show summary)
-    if !summary.all_succeeded() && !continue_on_error {
-        bail!("{} of {} files failed to process", summary.failure_count(), summary.total_files);
+    // Return an error carrying the outcome's exit code (the summary has already been shown)
+    match summary.outcome() {
+        BatchOutcome::Success => Ok(()),
+        BatchOutcome::PartialFailure => Err(CliError::BatchPartialFailure {
+            failed: summary.failure_count(),
+            succeeded: summary.success_count(),
+            total: summary.total_files,
+        }
+        .into()),
+        BatchOutcome::Failure => {
+            bail!("All {} files failed to process", summary.total_files)
+        }
+        BatchOutcome::Aborted => Err(CliError::BatchAborted {
+            failed: summary.failure_count(),
+            succeeded: summary.success_count(),
+            skipped: summary.skipped.len(),
+        }
+        .into()),
     }
+}
 
-    Ok(())
+/// Convert the outcome of processing a file into a batch result entry
+fn to_processing_result(file: &DiscoveredFile, result: Result<String>) -> FileProcessingResult {
+    match result {
+        Ok(dataset_name) => FileProcessingResult {
+            path: file.path.clone(),
+            format_name: file.format_name.clone(),
+            error: None,
+            dataset_name: Some(dataset_name),
+        },
+        Err(e) => FileProcessingResult {
+            path: file.path.clone(),
+            format_name: file.format_name.clone(),
+            error: Some(e.to_string()),
+            dataset_name: None,
+        },
+    }
 }
 
 /// Process a single file (extracted for parallel use)
 async fn process_single_file(
     file: &DiscoveredFile,
-    force: bool,
-    track_type: Option<String>,
-    folder: Option<String>,
-    geometry: Option<String>,
+    batch_args: &AddArgs,
+    workspace_root: &Path,
     storage: &Storage,
-    registry: &FormatRegistry,
 ) -> Result<String> {
+    // Format options only go to the files whose reader accepts them
+    let schema = storage.formats.detect_format(&file.path)?.option_schema();
+    let accepts = |key: &str| schema.iter().any(|option| option.key == key);
+
     let file_args = AddArgs {
         path: file.path.clone(),
         name: None,
-        force,
+        force: batch_args.force,
         interactive: false,
-        track_type,
-        folder,
-        geometry,
+        tags: batch_args.tags.clone(),
+        license: batch_args.license.clone(),
+        attribution: batch_args.attribution.clone(),
+        track_type: batch_args.track_type.clone().filter(|_| accepts("track_type")),
+        folder: batch_args.folder.clone().filter(|_| accepts("folder")),
+        crs: batch_args.crs.filter(|_| accepts("crs")),
+        axis_order: batch_args.axis_order.clone(),
+        geometry: batch_args.geometry.clone(),
+        places: batch_args.places.clone(),
         parallel: false,
         jobs: 0,
         continue_on_error: false,
+        fail_fast: false,
+        max_failures: None,
+        max_download_bytes: batch_args.max_download_bytes,
     };
 
-    // We need a silent output writer for parallel processing (json=false for no output)
-    let silent_output = OutputWriter::new(false);
+    // Files report nothing but warnings and errors; the batch shows their progress
+    let silent_output = OutputWriter::quiet();
 
-    execute_single(file_args, &silent_output, false, storage, registry).await?;
+    execute_single(file_args, None, &silent_output, false, workspace_root, storage).await?;
 
     Ok(file.path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown").to_string())
 }
 
+/// Download a URL and add the file as a single dataset whose path is the URL
+async fn execute_remote(
+    mut args: AddArgs,
+    url: &str,
+    output: &OutputWriter,
+    dry_run: bool,
+    workspace_root: &Path,
+    storage: &Storage,
+) -> Result<()> {
+    output.info(format!("Downloading {}", url));
+    // The URL comes from the user running the CLI, who may fetch from their
+    // own network; only the API refuses private addresses
+    let policy = DownloadPolicy::default()
+        .with_max_bytes(args.max_download_bytes)
+        .with_private_addresses(true);
+    let download = remote::fetch(url, &storage.formats, &policy)
+        .await
+        .map_err(|e| ingest_error(e, output))?;
+    output.info(format!("Downloaded {} ({} bytes)", download.file_name(), download.source.size));
+
+    args.path = download.path.clone();
+    execute_single(args, Some(download.source.clone()), output, dry_run, workspace_root, storage)
+        .await
+}
+
 /// Execute single file processing
+///
+/// `remote` is the URL a downloaded file came from.
 async fn execute_single(
     args: AddArgs,
+    remote: Option<RemoteSource>,
     output: &OutputWriter,
     dry_run: bool,
+    workspace_root: &Path,
     storage: &Storage,
-    registry: &FormatRegistry,
 ) -> Result<()> {
     // Check if dataset file exists
     if !args.path.exists() {
         bail!("Dataset file not found: {}", args.path.display());
     }
 
-    // Find workspace root
-    let workspace_root = find_workspace_root()?;
     let georag_dir = workspace_root.join(".georag");
 
     // Load workspace config
@@ -269,104 +334,128 @@ async fn execute_single(
     let config: georag_core::models::WorkspaceConfig =
         toml::from_str(&config_content).context("Failed to parse config.toml")?;
 
-    // Detect format
-    let reader = registry.detect_format(&args.path).context("Failed to detect file format")?;
-
-    output.info(format!("Detected format: {}", reader.format_name()));
+    // Lenient workspaces skip unreadable features up to the error budget
+    let layered =
+        load_workspace_config_with_store(workspace_root, storage.workspaces.as_ref()).await?;
+    let read_policy =
+        ReadPolicy::for_validity(layered.geometry_validity.value, layered.max_feature_errors.value);
+    let axis_order = match &args.axis_order {
+        Some(order) => parse_axis_order(order)?,
+        None => layered.axis_order.value,
+    };
 
-    // Validate format
-    let validation = reader.validate(&args.path).await.context("Failed to validate file")?;
+    // Build the ingest request from CLI arguments
+    let mut request = IngestRequest::new(&args.path)
+        .with_tags(&args.tags)
+        .with_workspace_crs(config.crs, args.force)
+        .with_read_policy(read_policy)
+        .with_axis_order(axis_order)
+        .with_feature_limits(layered.feature_limits())
+        .with_z_coordinates(layered.z_coordinates.value)
+        .with_transforms(layered.feature_transforms.value.clone())
+        .with_store_features(false);
+
+    if let Some(remote) = remote {
+        request = request.with_remote(remote);
+    }
 
-    if !validation.is_valid() {
-        for error in &validation.errors {
-            output.error(error.clone());
-        }
-        bail!("Format validation failed");
+    if let Some(name) = &args.name {
+        request = request.with_name(name);
     }
 
-    for warning in &validation.warnings {
-        output.warning(warning.clone());
+    if let Some(license) = &args.license {
+        request = request.with_license(license);
     }
 
-    // Build format options from CLI arguments
-    let mut format_options = georag_core::formats::FormatOptions::new();
+    if let Some(attribution) = &args.attribution {
+        request = request.with_attribution(attribution);
+    }
 
     if let Some(track_type) = &args.track_type {
-        format_options = format_options.with_option("track_type", track_type);
+        request = request.with_option("track_type", track_type);
         output.info(format!("GPX track type filter: {}", track_type));
     }
 
     if let Some(folder) = &args.folder {
-        format_options = format_options.with_option("folder", folder);
+        request = request.with_option("folder", folder);
         output.info(format!("KML folder filter: {}", folder));
     }
 
-    // Read dataset using format reader with options and optional geometry association
-    let format_dataset = if let Some(geometry_arg) = &args.geometry {
-        // Parse geometry argument
+    if let Some(crs) = args.crs {
+        request = request.with_option("crs", crs.to_string());
+        output.info(format!("Dataset CRS: EPSG:{}", crs));
+    }
+
+    if let Some(geometry_arg) = &args.geometry {
         let geometry =
             parse_geometry_argument(geometry_arg).context("Failed to parse geometry argument")?;
 
         output.info("Associating geometry with document".to_string());
+        request = request.with_geometry(geometry);
+    }
 
-        // Read with geometry association
-        reader
-            .read_with_geometry(&args.path, geometry)
-            .await
-            .context("Failed to read dataset with geometry")?
-    } else if format_options.options.is_empty() {
-        reader.read(&args.path).await.context("Failed to read dataset")?
-    } else {
-        reader
-            .read_with_options(&args.path, &format_options)
-            .await
-            .context("Failed to read dataset")?
-    };
-
-    // Extract metadata from the parsed format dataset
-    let geometry_type = detect_geometry_type(&format_dataset.features);
-    let feature_count = format_dataset.features.len();
-    let crs = format_dataset.crs;
+    if let Some(places_arg) = &args.places {
+        let places = parse_places_argument(places_arg).context("Failed to read places")?;
 
-    // Check for CRS mismatch
-    if crs != config.crs && !args.force {
-        output.warning(format!(
-            "CRS mismatch: dataset has EPSG:{}, workspace expects EPSG:{}",
-            crs, config.crs
-        ));
-        bail!("Use --force to add dataset with mismatched CRS, or reproject the dataset first");
+        output.info(format!("Associating {} named places with document", places.len()));
+        request = request.with_places(places);
     }
 
-    // Determine dataset name
-    let dataset_name = args.name.unwrap_or_else(|| {
-        args.path.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string()
-    });
-
+    let source_policy = SourcePolicy {
+        max_bytes: layered.max_source_bytes.value,
+        hash: layered.hash_sources.value,
+    };
+    let quota = store_workspace_quota(storage.workspaces.clone(), &layered).await?;
+    let service = storage
+        .ingest_service()
+        .with_blob_store(storage.blobs.clone(), source_policy)
+        .with_quota(quota.clone());
     if dry_run {
+        let prepared = service.prepare(&request).await.map_err(|e| ingest_error(e, output))?;
+        let dataset = &prepared.dataset;
+        let metadata = &prepared.format_metadata;
+        let crs = dataset.crs;
+
+        output.info(format!("Detected format: {}", metadata.format_name));
+        for warning in &prepared.warnings {
+            output.warning(warning.clone());
+        }
+        report_feature_errors(&prepared.feature_errors, output);
+        report_oversized_features(&prepared.oversized_features, output);
+        report_transform_errors(&prepared.transform_errors, output);
+        report_transform_preview(&prepared.transform_preview, output);
+
         let mut actions = vec![
             PlannedAction::new(ActionType::ModifyFile, "Store dataset in database")
-                .with_detail(format!("Add dataset: {}", dataset_name))
-                .with_detail(format!("Format: {}", format_dataset.format_metadata.format_name))
-                .with_detail(format!("Geometry Type: {:?}", geometry_type))
-                .with_detail(format!("Feature Count: {}", feature_count))
+                .with_detail(format!("Add dataset: {}", dataset.name))
+                .with_detail(format!("Format: {}", metadata.format_name))
+                .with_detail(format!("Geometry Type: {:?}", dataset.geometry_type))
+                .with_detail(format!("Feature Count: {}", dataset.feature_count))
+                .with_detail(format!("Skipped Features: {}", prepared.feature_errors.len()))
+                .with_detail(format!("Oversized Features: {}", prepared.oversized_features.len()))
                 .with_detail(format!("CRS: EPSG:{}", crs)),
             PlannedAction::new(ActionType::CopyFile, "Copy dataset file to workspace".to_string())
-                .with_detail(format!("Source: {}", args.path.display()))
+                .with_detail(format!("Source: {}", dataset.path.display()))
                 .with_detail("Destination: .georag/datasets/".to_string()),
         ];
 
         // Add format-specific metadata to dry-run output
-        if let Some(layer_name) = &format_dataset.format_metadata.layer_name {
+        if let Some(layer_name) = &metadata.layer_name {
             actions[0] = actions[0].clone().with_detail(format!("Layer: {}", layer_name));
         }
-        if let Some(page_count) = format_dataset.format_metadata.page_count {
+        if let Some(page_count) = metadata.page_count {
             actions[0] = actions[0].clone().with_detail(format!("Pages: {}", page_count));
         }
-        if let Some(paragraph_count) = format_dataset.format_metadata.paragraph_count {
+        if let Some(paragraph_count) = metadata.paragraph_count {
             actions[0] = actions[0].clone().with_detail(format!("Paragraphs: {}", paragraph_count));
         }
+        if let Some(decision) = &metadata.axis_order {
+            actions[0] = actions[0]
+                .clone()
+                .with_detail(format!("Axis order: {} ({})", decision.applied, decision.reason));
+        }
 
-        if crs != config.crs {
+        if prepared.has_crs_mismatch() {
             actions.insert(
                 0,
                 PlannedAction::new(ActionType::ModifyFile, "CRS mismatch warning")
@@ -379,28 +468,45 @@ async fn execute_single(
         return Ok(());
     }
 
-    // Create dataset object
-    let dataset = Dataset {
-        id: DatasetId(0),
-        name: dataset_name.clone(),
-        path: args.path.clone(),
-        geometry_type,
-        feature_count,
-        crs,
-        format: georag_core::models::dataset::FormatMetadata {
-            format_name: format_dataset.format_metadata.format_name.clone(),
-            format_version: format_dataset.format_metadata.format_version.clone(),
-            layer_name: format_dataset.format_metadata.layer_name.clone(),
-            page_count: format_dataset.format_metadata.page_count,
-            paragraph_count: format_dataset.format_metadata.paragraph_count,
-            extraction_method: format_dataset.format_metadata.extraction_method.clone(),
-            spatial_association: None,
-        },
-        added_at: Utc::now(),
-    };
+    // Stream the file through the ingest pipeline and store the dataset
+    let _slot = storage.acquire_slot(ResourceKind::Ingest, output).await?;
+    let request = request.with_buffers(layered.ingest_buffers());
+    let storing = Arc::new(Mutex::new(PhaseProgress::start(
+        output,
+        "features_stored",
+        "Storing features",
+        None,
+    )));
+    let progress = storing.clone();
+    let service =
+        service.with_store_progress(Arc::new(move |stored| progress.lock().unwrap().set(stored)));
+    let report = service.ingest(&request).await;
+    let throughput = vec![storing.lock().unwrap().finish()];
+    let audit = storage.audit_service();
+    if let Err(ServiceError::Core(error)) = &report {
+        audit.record_failure(quota.workspace_id(), None, error).await;
+    }
+    let report = report.map_err(|e| ingest_error(e, output))?;
+    let metadata = report.format_metadata.clone();
+    let crs = report.dataset.crs;
+    let crs_mismatch = crs != config.crs;
 
-    // Store dataset using SpatialStore trait
-    let dataset_id = storage.spatial.store_dataset(&dataset).await?;
+    output.info(format!("Detected format: {}", metadata.format_name));
+    for warning in &report.warnings {
+        output.warning(warning.clone());
+    }
+    report_feature_errors(&report.feature_errors, output);
+    report_oversized_features(&report.oversized_features, output);
+    report_transform_errors(&report.transform_errors, output);
+
+    let features_skipped = report.features_skipped();
+    let pipeline = report.pipeline.as_ref().map(PipelineOutput::from);
+    let feature_errors = report.feature_errors;
+    let oversized_features = report.oversized_features;
+    let transform_errors = report.transform_errors;
+    let dataset = report.dataset;
+    let dataset_id = report.dataset_id;
+    let report_usage = report.usage;
 
     // Copy dataset file to workspace (for backward compatibility with file-based operations)
     // This is wrapped in transaction-like logic: if copy fails, we clean up the database entry
@@ -411,17 +517,35 @@ async fn execute_single(
     let dest_path = datasets_dir.join(&dataset_filename);
 
     if let Err(copy_err) = fs::copy(&args.path, &dest_path) {
-        // Rollback: remove dataset from database since file copy failed
+        // Rollback: remove dataset and its original file since file copy failed
         if let Err(rollback_err) = storage.spatial.delete_dataset(dataset_id).await {
             output
                 .warning(format!("Failed to rollback dataset after copy error: {}", rollback_err));
         }
+        if let Err(rollback_err) = storage.blobs.delete_blob(dataset_id).await {
+            output.warning(format!(
+                "Failed to remove original file after copy error: {}",
+                rollback_err
+            ));
+        }
+        if let Err(rollback_err) = quota.release(&report_usage).await {
+            output.warning(format!(
+                "Failed to release workspace usage after copy error: {}",
+                rollback_err
+            ));
+        }
         return Err(copy_err).context("Failed to copy dataset file to workspace");
     }
+    let event = AuditEvent::new(AuditEventKind::DatasetAdded).with_details(&dataset.name);
+    audit.record(quota.workspace_id(), event).await;
+    storage
+        .event_log_service()
+        .record_dataset_added(quota.workspace_id(), storage.spatial.as_ref(), dataset_id)
+        .await;
 
     // Output success
     if output.is_json() {
-        let crs_mismatch = if crs != config.crs {
+        let crs_mismatch = if crs_mismatch {
             Some(CrsMismatchInfo {
                 dataset_crs: crs,
                 workspace_crs: config.crs,
@@ -431,42 +555,109 @@ async fn execute_single(
         };
 
         let json_output = AddOutput {
-            dataset_name: dataset_name.clone(),
-            geometry_type,
-            feature_count,
+            dataset_name: dataset.name.clone(),
+            geometry_type: dataset.geometry_type,
+            feature_count: dataset.feature_count,
             crs,
             crs_mismatch,
+            tags: dataset.tags.clone(),
+            license: dataset.license.clone(),
+            attribution: dataset.attribution.clone(),
+            features_skipped,
+            feature_errors,
+            oversized_features,
+            transform_errors,
+            axis_order: metadata.axis_order.clone(),
+            source: dataset.format.source.clone(),
+            remote: dataset.format.remote.clone(),
+            pipeline,
+            throughput,
         };
         output.result(json_output)?;
     } else {
-        output.success(format!("Added dataset: {}", dataset_name));
+        output.success(format!("Added dataset: {}", dataset.name));
         output.section("Dataset Information");
-        output.kv("Format", &format_dataset.format_metadata.format_name);
-        output.kv("Geometry Type", format!("{:?}", geometry_type));
-        output.kv("Feature Count", feature_count);
+        output.kv("Format", &metadata.format_name);
+        output.kv("Geometry Type", format!("{:?}", dataset.geometry_type));
+        output.kv("Feature Count", dataset.feature_count);
+        if features_skipped > 0 {
+            output.kv("Skipped Features", features_skipped);
+        }
         output.kv("CRS", format!("EPSG:{}", crs));
+        if let Some(decision) = &metadata.axis_order {
+            output.kv("Axis Order", format!("{} ({})", decision.applied, decision.reason));
+            if decision.applied == AxisOrder::LatLon {
+                output.info("Coordinates were swapped to lon,lat");
+            }
+        }
+        if !dataset.tags.is_empty() {
+            output.kv("Tags", dataset.tags.join(", "));
+        }
+        if let Some(license) = &dataset.license {
+            output.kv("License", license);
+        }
+        if let Some(attribution) = &dataset.attribution {
+            output.kv("Attribution", attribution);
+        }
 
         // Show format-specific metadata
-        if let Some(layer_name) = &format_dataset.format_metadata.layer_name {
+        if let Some(layer_name) = &metadata.layer_name {
             output.kv("Layer", layer_name);
         }
-        if let Some(page_count) = format_dataset.format_metadata.page_count {
+        if let Some(page_count) = metadata.page_count {
             output.kv("Pages", page_count);
         }
-        if let Some(paragraph_count) = format_dataset.format_metadata.paragraph_count {
+        if let Some(paragraph_count) = metadata.paragraph_count {
             output.kv("Paragraphs", paragraph_count);
         }
-        if let Some(extraction_method) = &format_dataset.format_metadata.extraction_method {
+        if let Some(extraction_method) = &metadata.extraction_method {
             output.kv("Extraction Method", extraction_method);
         }
-        if let Some(spatial_assoc) = &format_dataset.format_metadata.spatial_association {
+        if let Some(source) = &dataset.format.source {
+            output.kv("Source", format!("{} ({} bytes)", source.filename, source.size));
+            if let Some(sha256) = &source.sha256 {
+                output.kv("SHA-256", sha256);
+            }
+        }
+        if let Some(remote) = &dataset.format.remote {
+            output.kv("URL", &remote.url);
+            if let Some(final_url) = &remote.final_url {
+                output.kv("Redirected To", final_url);
+            }
+            if let Some(etag) = &remote.etag {
+                output.kv("ETag", etag);
+            }
+            if let Some(last_modified) = &remote.last_modified {
+                output.kv("Last Modified", last_modified);
+            }
+        }
+        if let Some(spatial_assoc) = &metadata.spatial_association {
             output.kv("Spatial Association", &spatial_assoc.source);
             if let Some(desc) = &spatial_assoc.description {
                 output.kv("Association Details", desc);
             }
         }
 
-        if crs != config.crs {
+        if let Some(pipeline) = &pipeline {
+            output.section("Ingest Pipeline");
+            output.kv(
+                "Batches",
+                format!("{} of up to {} features", pipeline.batches, pipeline.batch_size),
+            );
+            output.kv("Peak Features In Flight", pipeline.peak_features_in_flight);
+            for stage in &pipeline.stages {
+                output.kv(
+                    &stage.stage,
+                    format!(
+                        "{} features, {:.0}/s, waited {} ms",
+                        stage.features, stage.features_per_second, stage.waiting_ms
+                    ),
+                );
+            }
+        }
+        report_throughput(&throughput, output);
+
+        if crs_mismatch {
             output.warning(format!(
                 "Dataset CRS (EPSG:{}) differs from workspace CRS (EPSG:{})",
                 crs, config.crs
@@ -477,184 +668,102 @@ async fn execute_single(
     Ok(())
 }
 
-/// Find the workspace root by looking for .georag directory
-fn find_workspace_root() -> Result<PathBuf> {
-    let mut current = std::env::current_dir()?;
-    loop {
-        let georag_dir = current.join(".georag");
-        if georag_dir.exists() && georag_dir.is_dir() {
-            return Ok(current);
-        }
-        if !current.pop() {
-            bail!("Not in a GeoRAG workspace. Run 'georag init' first.");
-        }
+/// Warn about features the reader skipped, listing the first few
+pub(super) fn report_feature_errors(errors: &[FeatureError], output: &OutputWriter) {
+    if errors.is_empty() {
+        return;
     }
-}
 
-/// Detect geometry type from parsed features
-fn detect_geometry_type(features: &[FormatFeature]) -> GeometryType {
-    features
-        .first()
-        .and_then(|f| f.geometry.as_ref())
-        .and_then(|g| g.get("type"))
-        .and_then(|t| t.as_str())
-        .map(|t| match t {
-            "Point" => GeometryType::Point,
-            "LineString" => GeometryType::LineString,
-            "Polygon" => GeometryType::Polygon,
-            "MultiPoint" => GeometryType::MultiPoint,
-            "MultiLineString" => GeometryType::MultiLineString,
-            "MultiPolygon" => GeometryType::MultiPolygon,
-            _ => GeometryType::GeometryCollection,
-        })
-        .unwrap_or(GeometryType::Point)
+    output.warning(format!("Skipped {} features that could not be read", errors.len()));
+    if output.is_json() {
+        // The result lists every skipped feature
+        return;
+    }
+    for error in errors.iter().take(MAX_LISTED_FEATURE_ERRORS) {
+        output.warning(format!("  {}", error));
+    }
+    if errors.len() > MAX_LISTED_FEATURE_ERRORS {
+        output.warning(format!(
+            "  ... and {} more (use --json for the full list)",
+            errors.len() - MAX_LISTED_FEATURE_ERRORS
+        ));
+    }
 }
 
-/// Read dataset metadata from a GeoJSON file
-#[allow(dead_code)]
-fn read_dataset_metadata(path: &Path) -> Result<(GeometryType, usize, u32)> {
-    // Read the file
-    let content = fs::read_to_string(path).context("Failed to read dataset file")?;
-
-    // Parse as GeoJSON
-    let geojson: geojson::GeoJson = content.parse().context("Failed to parse GeoJSON")?;
-
-    // Extract metadata
-    match geojson {
-        geojson::GeoJson::FeatureCollection(fc) => {
-            let feature_count = fc.features.len();
-
-            // Determine geometry type from first feature
-            let geometry_type = if let Some(first_feature) = fc.features.first() {
-                if let Some(ref geom) = first_feature.geometry {
-                    match &geom.value {
-                        geojson::Value::Point(_) => GeometryType::Point,
-                        geojson::Value::LineString(_) => GeometryType::LineString,
-                        geojson::Value::Polygon(_) => GeometryType::Polygon,
-                        geojson::Value::MultiPoint(_) => GeometryType::MultiPoint,
-                        geojson::Value::MultiLineString(_) => GeometryType::MultiLineString,
-                        geojson::Value::MultiPolygon(_) => GeometryType::MultiPolygon,
-                        geojson::Value::GeometryCollection(_) => GeometryType::GeometryCollection,
-                    }
-                } else {
-                    GeometryType::Point // Default
-                }
-            } else {
-                GeometryType::Point // Default for empty collection
-            };
-
-            // Extract CRS (default to WGS84 if not specified)
-            let crs = fc
-                .foreign_members
-                .as_ref()
-                .and_then(|fm| fm.get("crs"))
-                .and_then(extract_epsg_from_crs)
-                .unwrap_or(4326);
-
-            Ok((geometry_type, feature_count, crs))
-        }
-        geojson::GeoJson::Feature(feature) => {
-            let geometry_type = if let Some(ref geom) = feature.geometry {
-                match &geom.value {
-                    geojson::Value::Point(_) => GeometryType::Point,
-                    geojson::Value::LineString(_) => GeometryType::LineString,
-                    geojson::Value::Polygon(_) => GeometryType::Polygon,
-                    geojson::Value::MultiPoint(_) => GeometryType::MultiPoint,
-                    geojson::Value::MultiLineString(_) => GeometryType::MultiLineString,
-                    geojson::Value::MultiPolygon(_) => GeometryType::MultiPolygon,
-                    geojson::Value::GeometryCollection(_) => GeometryType::GeometryCollection,
-                }
-            } else {
-                GeometryType::Point
-            };
-
-            Ok((geometry_type, 1, 4326))
-        }
-        geojson::GeoJson::Geometry(geom) => {
-            let geometry_type = match &geom.value {
-                geojson::Value::Point(_) => GeometryType::Point,
-                geojson::Value::LineString(_) => GeometryType::LineString,
-                geojson::Value::Polygon(_) => GeometryType::Polygon,
-                geojson::Value::MultiPoint(_) => GeometryType::MultiPoint,
-                geojson::Value::MultiLineString(_) => GeometryType::MultiLineString,
-                geojson::Value::MultiPolygon(_) => GeometryType::MultiPolygon,
-                geojson::Value::GeometryCollection(_) => GeometryType::GeometryCollection,
-            };
+/// Print features over the vertex limit that were simplified or subdivided
+fn report_oversized_features(oversized: &[OversizedFeature], output: &OutputWriter) {
+    if oversized.is_empty() {
+        return;
+    }
 
-            Ok((geometry_type, 1, 4326))
-        }
+    output.info(format!("Reduced {} features over the vertex limit", oversized.len()));
+    if output.is_json() {
+        // The result lists every reduced feature
+        return;
+    }
+    for feature in oversized.iter().take(MAX_LISTED_FEATURE_ERRORS) {
+        output.info(format!("  {}", feature));
+    }
+    if oversized.len() > MAX_LISTED_FEATURE_ERRORS {
+        output.info(format!(
+            "  ... and {} more (use --json for the full list)",
+            oversized.len() - MAX_LISTED_FEATURE_ERRORS
+        ));
     }
 }
 
-/// Extract EPSG code from CRS object
-fn extract_epsg_from_crs(crs: &serde_json::Value) -> Option<u32> {
-    // Try to extract from properties.name
-    if let Some(props) = crs.get("properties") {
-        if let Some(name) = props.get("name") {
-            if let Some(name_str) = name.as_str() {
-                // Parse "EPSG:4326" or "urn:ogc:def:crs:EPSG::4326"
-                if let Some(epsg_str) = name_str.split(':').next_back() {
-                    return epsg_str.parse().ok();
-                }
-            }
-        }
+/// Warn about the workspace transforms that failed on some features
+fn report_transform_errors(errors: &[TransformErrors], output: &OutputWriter) {
+    for transform in errors.iter().filter(|t| t.errors > 0) {
+        output.warning(format!(
+            "Transform {} ({}) failed on {} features",
+            transform.position, transform.transform, transform.errors
+        ));
     }
-    None
 }
 
-/// Parse geometry argument - can be inline GeoJSON or path to file
-fn parse_geometry_argument(geometry_arg: &str) -> Result<serde_json::Value> {
-    // Try to parse as JSON first (inline geometry)
-    if let Ok(geom) = serde_json::from_str::<serde_json::Value>(geometry_arg) {
-        // Validate it's a valid GeoJSON geometry
-        if geom.get("type").is_some() && geom.get("coordinates").is_some() {
-            return Ok(geom);
-        }
+/// Print sample features before and after the workspace transforms
+fn report_transform_preview(preview: &[TransformPreview], output: &OutputWriter) {
+    if preview.is_empty() || output.is_json() {
+        return;
     }
 
-    // Try to read as file path
-    let path = PathBuf::from(geometry_arg);
-    if path.exists() {
-        let content = fs::read_to_string(&path).context("Failed to read geometry file")?;
-
-        // Parse the file content
-        let geojson: serde_json::Value =
-            serde_json::from_str(&content).context("Failed to parse geometry file as JSON")?;
+    let properties = |properties: &BTreeMap<String, serde_json::Value>| {
+        serde_json::to_string(properties).unwrap_or_default()
+    };
+    output.section("Transform Preview");
+    for feature in preview {
+        output.kv(format!("Feature {} before", feature.index), properties(&feature.before));
+        let after = match (&feature.after, &feature.error) {
+            (Some(after), _) => properties(after),
+            (None, error) => {
+                format!("rejected, {}", error.as_deref().unwrap_or("transform failed"))
+            }
+        };
+        output.kv(format!("Feature {} after", feature.index), after);
+    }
+}
 
-        // Extract geometry from GeoJSON
-        if let Some(geom_type) = geojson.get("type") {
-            match geom_type.as_str() {
-                Some("Feature") => {
-                    // Extract geometry from Feature
-                    if let Some(geometry) = geojson.get("geometry") {
-                        return Ok(geometry.clone());
-                    }
-                }
-                Some("FeatureCollection") => {
-                    // Use geometry from first feature
-                    if let Some(features) = geojson.get("features").and_then(|f| f.as_array()) {
-                        if let Some(first_feature) = features.first() {
-                            if let Some(geometry) = first_feature.get("geometry") {
-                                return Ok(geometry.clone());
-                            }
-                        }
-                    }
-                }
-                Some("Point")
-                | Some("LineString")
-                | Some("Polygon")
-                | Some("MultiPoint")
-                | Some("MultiLineString")
-                | Some("MultiPolygon") => {
-                    // It's already a geometry
-                    return Ok(geojson);
-                }
-                _ => {}
+/// Convert an ingest failure into a CLI error, printing validation details
+fn ingest_error(err: ServiceError, output: &OutputWriter) -> anyhow::Error {
+    match err {
+        ServiceError::UnsupportedFormat(e) => {
+            anyhow::Error::new(e).context("Failed to detect file format")
+        }
+        ServiceError::InvalidOptions(e) => anyhow::Error::new(e).context("Invalid format options"),
+        ServiceError::InvalidDataset(errors) => {
+            for error in errors {
+                output.error(error);
             }
+            anyhow::anyhow!("Format validation failed")
         }
-
-        bail!("Geometry file does not contain valid GeoJSON geometry");
+        ServiceError::Read(e) => anyhow::Error::new(e).context("Failed to read dataset"),
+        ServiceError::CrsMismatch { .. } => {
+            output.warning(err.to_string());
+            anyhow::anyhow!(
+                "Use --force to add dataset with mismatched CRS, or reproject the dataset first"
+            )
+        }
+        other => other.into(),
     }
-
-    bail!("Geometry argument must be valid GeoJSON geometry string or path to GeoJSON file");
 }
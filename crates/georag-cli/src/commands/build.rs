@@ -1,60 +1,169 @@
 use crate::cli::BuildArgs;
-use crate::config::{find_workspace_root, load_workspace_config_with_overrides};
+use crate::config::{load_workspace_config_with_overrides, store_workspace_quota};
 use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::lock::BuildLock;
 use crate::output::OutputWriter;
 use crate::output_types::BuildOutput;
+use crate::progress::{report_throughput, PhaseProgress};
 use crate::storage::Storage;
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use georag_core::config::CliConfigOverrides;
 use georag_core::geo::models::Crs;
-use georag_core::llm::OllamaEmbedder;
+use georag_core::llm::{
+    self, AnyEmbedder, DimensionCheck, DimensionCheckMode, EmbedderOptions, EmbedderSpec,
+    PullProgress,
+};
+use georag_core::models::{
+    AuditEvent, AuditEventKind, BuildDiff, DatasetMeta, Delta, DiffBaseline, IndexState,
+    PreviousIndex, UsageDelta, INDEX_SCHEMA_VERSION,
+};
+use georag_core::processing::chunk::{is_meaningful, ChunkGenerator};
+use georag_core::processing::tokenizer::{create_tokenizer, TokenLimits, Tokenizer};
+use georag_core::resources::ResourceKind;
 use georag_retrieval::{IndexBuilder, IndexPhase, IndexProgress};
+use georag_service::GcService;
 use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Setting named when the configured embedding dimension is wrong
+const DIMENSIONS_SETTING: &str = "embedder_dimensions (GEORAG_EMBEDDER_DIM)";
 
 pub async fn execute(
     args: BuildArgs,
     output: &OutputWriter,
     dry_run: bool,
     storage: &Storage,
+    workspace: Option<&Path>,
 ) -> Result<()> {
     // Find workspace root
-    let workspace_root = find_workspace_root()?;
+    let workspace_root = super::workspace_root(workspace, output)?;
     let georag_dir = workspace_root.join(".georag");
 
     // Load layered configuration with CLI overrides
     let overrides = CliConfigOverrides {
-        embedder: Some(args.embedder.clone()),
+        embedder: args.embedder.clone(),
         ..Default::default()
     };
-    let config = load_workspace_config_with_overrides(&workspace_root, overrides)?;
+    let config = load_workspace_config_with_overrides(
+        &workspace_root,
+        storage.workspaces.as_ref(),
+        overrides,
+    )
+    .await?;
 
-    // Load datasets from storage
-    let datasets = storage.spatial.list_datasets().await?;
+    // Load datasets from storage; archived datasets stay out of the index
+    let mut datasets = storage.spatial.list_datasets().await?;
+    datasets.retain(|dataset| !dataset.is_archived());
 
     if datasets.is_empty() {
         bail!("No datasets to build. Add datasets with 'georag add' first.");
     }
 
+    // With --datasets, the selected datasets are merged into the existing index
+    let previous_state = if args.datasets.is_empty() {
+        None
+    } else {
+        Some(super::query::load_index_state(&georag_dir)?)
+    };
+    let datasets = select_datasets(datasets, &args.datasets)?;
+
     // Check if index already exists and is up to date
     let index_state_path = georag_dir.join("index").join("state.json");
-    if index_state_path.exists() && !args.force {
+    if index_state_path.exists() && !args.force && !args.resume_build && previous_state.is_none() {
         output.info("Index already exists. Use --force to rebuild.");
         return Ok(());
     }
 
+    // The index this build replaces, to report what the build changed
+    let replaced = read_previous_index(&index_state_path);
+
+    // A full rebuild replaces an index of an older schema, but a resumed
+    // build continues it, and an index of a newer schema is never replaced
+    if let PreviousIndex::Built(state) = &replaced {
+        if args.resume_build || state.schema_version > INDEX_SCHEMA_VERSION {
+            state.check_schema_version()?;
+        }
+    }
+
+    // Chunk sizing, the input limit and the dry-run estimate count with the same tokenizer
+    let tokenizer = create_tokenizer(
+        &config.tokenizer_spec()?,
+        &config.embedder.value,
+        &georag_dir.join("tokenizers"),
+    )?;
+    let token_limits = config.token_limits(tokenizer.clone())?;
+    let max_input_tokens = EmbedderSpec::parse(&config.embedder.value)?.max_input_tokens();
+
     if dry_run {
+        let generator = chunk_generator(&config.chunk_properties.value, token_limits.as_ref());
+        let estimate =
+            estimate_chunks(storage, &datasets, &generator, tokenizer.as_ref(), max_input_tokens)
+                .await?;
         let mut actions = vec![
             PlannedAction::new(ActionType::ModifyFile, "Normalize geometries to workspace CRS")
                 .with_detail(format!("Target CRS: EPSG:{}", config.crs.value))
                 .with_detail(format!("Datasets to process: {}", datasets.len())),
+            PlannedAction::new(ActionType::ModifyFile, "Select datasets to build").with_detail(
+                if previous_state.is_some() {
+                    format!(
+                        "{} (merged into the existing index)",
+                        datasets.iter().map(|d| d.name.as_str()).collect::<Vec<_>>().join(", ")
+                    )
+                } else {
+                    "All datasets (replacing the existing index)".to_string()
+                },
+            ),
             PlannedAction::new(ActionType::ModifyFile, "Validate and fix geometries")
                 .with_detail("Check for invalid geometries")
                 .with_detail("Apply fixes where possible"),
             PlannedAction::new(ActionType::CreateFile, "Generate embeddings")
                 .with_detail(format!("Embedder: {}", config.embedder.value))
                 .with_detail(format!(
-                    "Estimated chunks: {}",
-                    datasets.iter().map(|d| d.feature_count).sum::<usize>()
+                    "Chunk properties: {}",
+                    if config.chunk_properties.value.is_empty() {
+                        "none".to_string()
+                    } else {
+                        config.chunk_properties.value.join(", ")
+                    }
+                ))
+                .with_detail(format!(
+                    "Chunk sizing: {}",
+                    match &token_limits {
+                        Some(limits) => format!(
+                            "at most {} tokens, {} overlapping",
+                            limits.max_chunk_tokens, limits.overlap_tokens
+                        ),
+                        None => "words".to_string(),
+                    }
+                ))
+                .with_detail(format!(
+                    "Estimated chunks: {} ({} tokens, {} tokenizer)",
+                    estimate.chunks,
+                    estimate.tokens,
+                    tokenizer.name()
+                ))
+                .with_detail(match max_input_tokens {
+                    Some(max_tokens) => format!(
+                        "Truncated to the embedder's {} input tokens: {} chunks",
+                        max_tokens, estimate.truncated
+                    ),
+                    None => "Embedder input limit: unknown, chunks are sent in full".to_string(),
+                })
+                .with_detail(format!(
+                    "Rate limit: {}",
+                    args.rate_limit
+                        .map(|rate| format!("{} embeddings/s", rate))
+                        .unwrap_or_else(|| "none".to_string())
+                ))
+                .with_detail(format!(
+                    "Checkpoint: every {} batches{}",
+                    args.checkpoint_every,
+                    if args.resume_build {
+                        ", resuming the last build"
+                    } else {
+                        ""
+                    }
                 )),
             PlannedAction::new(ActionType::WriteFile, "Create index state file")
                 .with_detail("Path: .georag/index/state.json")
@@ -79,10 +188,20 @@ pub async fn execute(
         return Ok(());
     }
 
+    // Keep `db compact` from deleting chunks stored ahead of their embeddings
+    let _lock = BuildLock::acquire(&georag_dir, "build")?;
+    // Counts towards the build limit of an API server sharing the database
+    let _slot = storage.acquire_slot(ResourceKind::Build, output).await?;
+
     output.info("Building index...");
 
     // Create embedder from config
-    let embedder = create_embedder(&config.embedder.value).map_err(|e| {
+    let embedder = create_embedder(
+        &config.embedder.value,
+        config.auto_pull.value,
+        config.embedder_dimensions.value,
+    )
+    .map_err(|e| {
         if e.to_string().contains("Failed to connect to Ollama")
             || e.to_string().contains("Embedder unavailable")
         {
@@ -101,6 +220,17 @@ pub async fn execute(
         }
     })?;
 
+    // Make sure the model is installed (pulling it if auto_pull is set) before embedding
+    embedder.ensure_ready().await.map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    // A merge keeps the existing vectors, so their dimension must match too
+    check_dimensions(&embedder, previous_state.as_ref(), config.dimension_check.value, output)
+        .await?;
+
+    // A build that would exceed the chunk quota fails before the current index is cleared
+    let quota = store_workspace_quota(storage.workspaces.clone(), &config).await?;
+    let usage = quota.usage().await?;
+
     // Create workspace CRS
     let workspace_crs = Crs::new(config.crs.value, format!("EPSG:{}", config.crs.value));
 
@@ -112,49 +242,124 @@ pub async fn execute(
         embedder,
         workspace_crs,
     )
-    .with_batch_size(32);
+    .with_batch_size(32)
+    .with_chunk_properties(config.chunk_properties.value.clone())
+    .with_checkpoints(storage.checkpoints.clone(), args.checkpoint_every)
+    .with_resume(args.resume_build)
+    .with_chunk_quota(quota.quotas(), usage)
+    .with_order_check(args.check_order)
+    .with_previous_index(replaced);
+    let builder = match token_limits {
+        Some(limits) => builder.with_token_limits(limits),
+        None => builder,
+    };
+    let builder = match max_input_tokens {
+        Some(max_tokens) => builder.with_input_limit(tokenizer.clone(), max_tokens),
+        None => builder,
+    };
+    let builder = match &storage.slots {
+        Some(slots) => builder.with_resource_gate(Arc::new(slots.clone())),
+        None => builder,
+    };
+    let builder = match args.rate_limit {
+        Some(rate) => builder.with_rate_limit(rate),
+        None => builder,
+    };
+
+    // Chunks the build replaces, for the change events of those it removes
+    let chunks_before = storage.document.list_chunk_ids().await?;
 
     // Track state for output
     let mut last_phase = IndexPhase::Initializing;
+    let mut embedding: Option<PhaseProgress> = None;
 
-    // Perform full rebuild with progress display
-    let result = builder
-        .full_rebuild(&datasets, args.force, |progress: IndexProgress| {
-            // Only print section headers when phase changes
-            if progress.phase != last_phase {
-                match progress.phase {
-                    IndexPhase::Initializing => output.section("Initializing"),
-                    IndexPhase::GeneratingChunks => output.section("Generating chunks"),
-                    IndexPhase::GeneratingEmbeddings => output.section("Generating embeddings"),
-                    IndexPhase::StoringData => output.section("Storing data"),
-                    IndexPhase::Finalizing => output.section("Finalizing index"),
-                }
-                last_phase = progress.phase;
+    // Rebuild the index, or just the selected datasets, with progress display
+    let report = |progress: IndexProgress| {
+        // Only print section headers when phase changes
+        if progress.phase != last_phase {
+            if let Some(mut embedding) = embedding.take() {
+                embedding.finish();
+            }
+            match progress.phase {
+                IndexPhase::Initializing => output.section("Initializing"),
+                IndexPhase::GeneratingChunks => output.section("Generating chunks"),
+                IndexPhase::GeneratingEmbeddings => output.section("Generating embeddings"),
+                IndexPhase::StoringData => output.section("Storing data"),
+                IndexPhase::Finalizing => output.section("Finalizing index"),
             }
+            last_phase = progress.phase;
+        }
+
+        // Embedding shows its rate and ETA instead of a line per batch
+        if progress.phase == IndexPhase::GeneratingEmbeddings && progress.total > 0 {
+            embedding
+                .get_or_insert_with(|| {
+                    PhaseProgress::start(
+                        output,
+                        "embeddings",
+                        "Embedding chunks",
+                        Some(progress.total),
+                    )
+                })
+                .set(progress.current);
+        } else {
             output.info(format!("  {}", progress.message));
+        }
+    };
+    let result = match &previous_state {
+        Some(previous) => builder.rebuild_datasets(&datasets, previous, report).await,
+        None => builder.full_rebuild(&datasets, args.force, report).await,
+    };
+    let audit = storage.audit_service();
+    if let Err(e) = &result {
+        audit
+            .record_failure(quota.workspace_id(), Some(AuditEventKind::BuildFailed), e)
+            .await;
+    }
+    let mut result = result.map_err(|e| {
+        if e.to_string().contains("Failed to connect to Ollama")
+            || e.to_string().contains("Embedder unavailable")
+        {
+            anyhow::anyhow!(
+                "Failed to generate embeddings using Ollama\n\n\
+                Remediation:\n\
+                  1. Ensure Ollama is running: ollama serve\n\
+                  2. Verify the model is available: ollama list\n\
+                  3. Pull the model if needed: ollama pull {}\n\n\
+                Error: {}",
+                config.embedder.value.strip_prefix("ollama:").unwrap_or(&config.embedder.value),
+                e
+            )
+        } else {
+            anyhow::anyhow!("Failed to build index: {}", e)
+        }
+    })?;
+
+    // Retire chunks left by features that changed or disappeared since they were chunked
+    let gc =
+        GcService::new(storage.spatial.clone(), storage.document.clone(), storage.vector.clone())
+            .collect()
+            .await
+            .context("Failed to collect outdated chunks")?;
+    result.chunk_count -= gc.chunks_deleted.min(result.chunk_count);
+    result.diff = builder.diff(&result);
+
+    // The result counts every chunk of the index, including those of datasets left out
+    quota
+        .record(&UsageDelta {
+            chunks: result.chunk_count as i64 - usage.chunks as i64,
+            ..Default::default()
         })
-        .await
-        .map_err(|e| {
-            if e.to_string().contains("Failed to connect to Ollama")
-                || e.to_string().contains("Embedder unavailable")
-            {
-                anyhow::anyhow!(
-                    "Failed to generate embeddings using Ollama\n\n\
-                    Remediation:\n\
-                      1. Ensure Ollama is running: ollama serve\n\
-                      2. Verify the model is available: ollama list\n\
-                      3. Pull the model if needed: ollama pull {}\n\n\
-                    Error: {}",
-                    config.embedder.value.strip_prefix("ollama:").unwrap_or(&config.embedder.value),
-                    e
-                )
-            } else {
-                anyhow::anyhow!("Failed to build index: {}", e)
-            }
-        })?;
+        .await?;
 
     // Create index state
-    let index_state = builder.create_index_state(&result);
+    let mut index_state = builder.create_index_state(&result);
+    if let Some(previous) = &previous_state {
+        // Datasets left out of this build keep their earlier build times
+        for (name, built_at) in &previous.dataset_built_at {
+            index_state.dataset_built_at.entry(name.clone()).or_insert(*built_at);
+        }
+    }
 
     // Save index state to disk
     let index_dir = georag_dir.join("index");
@@ -162,16 +367,34 @@ pub async fn execute(
 
     let state_json = serde_json::to_string_pretty(&index_state)?;
     fs::write(&index_state_path, state_json)?;
+    let event = AuditEvent::new(AuditEventKind::BuildCompleted)
+        .with_details(format!("{} chunks", result.chunk_count));
+    audit.record(quota.workspace_id(), event).await;
+    storage
+        .event_log_service()
+        .record_chunks_built(quota.workspace_id(), storage.document.as_ref(), &chunks_before)
+        .await;
 
     // Output success
     if output.is_json() {
         let json_output = BuildOutput {
             index_hash: result.index_hash.clone(),
             chunk_count: result.chunk_count,
+            chunks_skipped: result.chunks_skipped,
+            unembedded_chunks: result.unembedded_chunks.iter().map(|id| id.0).collect(),
+            datasets: result.datasets.clone(),
+            outdated_chunks_deleted: gc.chunks_deleted,
+            stale_chunks: gc.chunks_marked_stale,
             embedding_dim: result.embedding_dim,
             embedder: config.embedder.value.clone(),
             normalized_count: result.geometries_normalized,
             fixed_count: result.geometries_fixed,
+            resumed_chunks: result.resumed_from.as_ref().map(|c| c.embedded_chunks),
+            attempts: result.resumed_from.as_ref().map_or(1, |c| c.attempts + 1),
+            wall_time_secs: result.wall_time.as_secs_f64(),
+            throughput: result.throughput.clone(),
+            diff: result.diff.clone(),
+            tokenizer: index_state.tokenizer.clone(),
         };
         output.result(json_output)?;
     } else {
@@ -179,30 +402,251 @@ pub async fn execute(
         output.section("Index Information");
         output.kv("Hash", &result.index_hash);
         output.kv("Chunks", result.chunk_count);
+        if previous_state.is_some() {
+            output.kv("Datasets Built", result.datasets.join(", "));
+        }
+        if gc.chunks_deleted + gc.chunks_marked_stale > 0 {
+            output.kv(
+                "Outdated Chunks",
+                format!("{} deleted, {} marked stale", gc.chunks_deleted, gc.chunks_marked_stale),
+            );
+        }
+        if result.chunks_skipped > 0 {
+            output.kv("Skipped (no text)", result.chunks_skipped);
+        }
+        if !result.unembedded_chunks.is_empty() {
+            output.kv("Unembedded", result.unembedded_chunks.len());
+        }
         output.kv("Embedding Dimension", result.embedding_dim);
         output.kv("Embedder", &config.embedder.value);
+        if let Some(tokenizer) = &index_state.tokenizer {
+            output.kv("Tokenizer", tokenizer);
+        }
+        if let Some(checkpoint) = &result.resumed_from {
+            output.kv(
+                "Resumed From",
+                format!(
+                    "{}/{} chunks (attempt {})",
+                    checkpoint.embedded_chunks,
+                    checkpoint.total_chunks,
+                    checkpoint.attempts + 1
+                ),
+            );
+        }
+        output.kv("Wall Time", format!("{:.1}s", result.wall_time.as_secs_f64()));
+        report_throughput(&result.throughput, output);
+        if let Some(diff) = &result.diff {
+            report_diff(diff, output);
+        }
     }
 
+    super::seed_settings(storage, &workspace_root, output).await;
+
     Ok(())
 }
 
-/// Parse embedder string and create an OllamaEmbedder
-/// Format: "ollama:model-name" or just "model-name"
-fn create_embedder(embedder_str: &str) -> Result<OllamaEmbedder> {
-    // Parse the embedder string
-    let model = if let Some(stripped) = embedder_str.strip_prefix("ollama:") {
-        stripped
-    } else {
-        embedder_str
+/// Keep the datasets named in `names`, or all of them when no names are given
+fn select_datasets(datasets: Vec<DatasetMeta>, names: &[String]) -> Result<Vec<DatasetMeta>> {
+    if names.is_empty() {
+        return Ok(datasets);
+    }
+
+    if let Some(missing) = names.iter().find(|name| !datasets.iter().any(|d| &d.name == *name)) {
+        bail!(
+            "Dataset '{}' not found. Available datasets: {}",
+            missing,
+            datasets.iter().map(|d| d.name.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    Ok(datasets.into_iter().filter(|d| names.contains(&d.name)).collect())
+}
+
+/// Chunk generator of a build with these chunk properties and token limits
+///
+/// The index builder configures its generator the same way, so estimates
+/// made with this one agree with the build.
+fn chunk_generator(properties: &[String], token_limits: Option<&TokenLimits>) -> ChunkGenerator {
+    let generator = ChunkGenerator::default().with_properties(properties.iter().cloned());
+    match token_limits {
+        Some(limits) => generator.with_token_limits(limits.clone()),
+        None => generator,
+    }
+}
+
+/// What a build of `datasets` would chunk, counted without embedding anything
+struct ChunkEstimate {
+    chunks: usize,
+    tokens: usize,
+    /// Chunks longer than the embedder's input, truncated before embedding
+    truncated: usize,
+}
+
+async fn estimate_chunks(
+    storage: &Storage,
+    datasets: &[DatasetMeta],
+    generator: &ChunkGenerator,
+    tokenizer: &dyn Tokenizer,
+    max_input_tokens: Option<usize>,
+) -> Result<ChunkEstimate> {
+    let mut estimate = ChunkEstimate { chunks: 0, tokens: 0, truncated: 0 };
+    for meta in datasets {
+        let Some(dataset) = storage.spatial.get_dataset(meta.id).await? else {
+            continue;
+        };
+        let features = storage.spatial.get_features_for_dataset(meta.id).await?;
+        let chunks = generator.generate_chunks(&dataset, &features);
+        for chunk in chunks.iter().filter(|chunk| is_meaningful(&chunk.content)) {
+            let tokens = tokenizer.count(&chunk.content);
+            estimate.chunks += 1;
+            estimate.tokens += tokens;
+            if max_input_tokens.is_some_and(|max_tokens| tokens > max_tokens) {
+                estimate.truncated += 1;
+            }
+        }
+    }
+    Ok(estimate)
+}
+
+/// Read the state of the last build to compare the new index with
+///
+/// A state this version cannot parse is reported as unreadable instead of
+/// failing the build, which replaces it anyway.
+fn read_previous_index(path: &Path) -> PreviousIndex {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return PreviousIndex::None,
+        Err(e) => return PreviousIndex::Unreadable(e.to_string()),
     };
+    match serde_json::from_str(&content) {
+        Ok(state) => PreviousIndex::Built(state),
+        Err(e) => PreviousIndex::Unreadable(e.to_string()),
+    }
+}
+
+/// Print what the build changed in the index
+fn report_diff(diff: &BuildDiff, output: &OutputWriter) {
+    output.section("Changes Since Last Build");
+    match &diff.baseline {
+        DiffBaseline::FirstBuild => output.info("First build of this workspace"),
+        DiffBaseline::Previous { hash, built_at } => output.kv(
+            "Previous Build",
+            format!("{} ({})", hash, built_at.format("%Y-%m-%d %H:%M UTC")),
+        ),
+        DiffBaseline::Unreadable { reason } => output.warning(format!(
+            "The previous index state could not be read ({}); only the new index is shown",
+            reason
+        )),
+    }
+
+    output.kv("Chunks", format_delta(&diff.chunks, |count| count.to_string()));
+    output.kv("Embeddings", format_delta(&diff.embeddings, |count| count.to_string()));
+    output.kv(
+        "Size (estimated)",
+        format_delta(&diff.size_bytes, |bytes| super::db::format_bytes(bytes as i64)),
+    );
+    if !diff.new_datasets.is_empty() {
+        output.kv("New Datasets", diff.new_datasets.join(", "));
+    }
+    if !diff.removed_datasets.is_empty() {
+        output.kv("Removed Datasets", diff.removed_datasets.join(", "));
+    }
+    if let Some(change) = &diff.embedder_changed {
+        output.kv(
+            "Embedder Changed",
+            format!(
+                "{} ({} dimensions) -> {} ({} dimensions)",
+                change.from, change.from_dimensions, change.to, change.to_dimensions
+            ),
+        );
+    }
+
+    if !diff.datasets.is_empty() {
+        let rows = diff
+            .datasets
+            .iter()
+            .map(|dataset| {
+                vec![
+                    dataset.name.clone(),
+                    dataset.chunks.before.map_or("?".to_string(), |before| before.to_string()),
+                    dataset.chunks.after.to_string(),
+                    dataset.chunks.change.map_or("?".to_string(), |change| format!("{:+}", change)),
+                ]
+            })
+            .collect();
+        output.grid(&["Dataset", "Chunks Before", "Chunks After", "Change"], rows);
+    }
+}
+
+/// A count after the build with its change, e.g. "120 (+20)"
+fn format_delta(delta: &Delta, format: impl Fn(u64) -> String) -> String {
+    match delta.change {
+        Some(0) => format!("{} (unchanged)", format(delta.after)),
+        Some(change) => format!(
+            "{} ({}{})",
+            format(delta.after),
+            if change > 0 { "+" } else { "-" },
+            format(change.unsigned_abs())
+        ),
+        None => format!("{} (previous unknown)", format(delta.after)),
+    }
+}
 
-    // Determine dimensions based on known models
-    let dimensions = match model {
-        "nomic-embed-text" => 768,
-        "mxbai-embed-large" => 1024,
-        "all-minilm" => 384,
-        _ => 768, // Default to 768 for unknown models
+/// Create the embedder named by an embedder string
+///
+/// With `auto_pull`, a missing model is pulled on first use and the pull
+/// progress is printed to stderr.
+/// `dimensions` overrides the dimension the model string implies.
+/// Format: "ollama:model-name", "model-name" or "mock:dimensions"
+pub(super) fn create_embedder(
+    embedder_str: &str,
+    auto_pull: bool,
+    dimensions: Option<usize>,
+) -> Result<AnyEmbedder> {
+    let mut options = EmbedderOptions::default().with_auto_pull(auto_pull);
+    if let Some(dimensions) = dimensions {
+        options = options.with_dimensions(dimensions);
+    }
+    Ok(llm::create_embedder(embedder_str, &options)?.with_pull_progress(pull_progress_printer()))
+}
+
+/// Compare the embedder's dimension with the model's and the index's
+///
+/// A model that cannot be probed is compared with the index only. Depending
+/// on `mode`, a mismatch fails the command or is printed as a warning.
+pub(super) async fn check_dimensions(
+    embedder: &AnyEmbedder,
+    index: Option<&IndexState>,
+    mode: DimensionCheckMode,
+    output: &OutputWriter,
+) -> Result<()> {
+    let check = match DimensionCheck::run(embedder, DIMENSIONS_SETTING, index).await {
+        Ok(check) => check,
+        Err(e) => {
+            output.warning(format!("Could not probe the embedding dimensions: {}", e));
+            DimensionCheck::unprobed(embedder, DIMENSIONS_SETTING, index)
+        }
     };
+    if let Some(warning) = check.enforce(mode)? {
+        output.warning(warning);
+    }
+    Ok(())
+}
+
+/// Print model pull progress once per phase and every 10% of a download
+fn pull_progress_printer() -> impl Fn(&PullProgress) + Send + Sync {
+    let last = Mutex::new((String::new(), None));
+    move |progress: &PullProgress| {
+        let mut last = last.lock().unwrap();
+        let step = progress.percent().map(|percent| percent / 10);
+        if progress.status == last.0 && step == last.1 {
+            return;
+        }
 
-    Ok(OllamaEmbedder::localhost(model, dimensions))
+        match progress.percent() {
+            Some(percent) => eprintln!("  Pulling model: {} ({}%)", progress.status, percent),
+            None => eprintln!("  Pulling model: {}", progress.status),
+        }
+        *last = (progress.status.clone(), step);
+    }
 }
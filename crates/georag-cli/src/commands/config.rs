@@ -0,0 +1,215 @@
+use crate::cli::{ConfigArgs, ConfigCommand};
+use crate::config::{
+    load_stored_settings, read_file_settings, save_stored_settings, write_file_settings,
+};
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::{ConfigCheckOutput, ConfigSyncOutput};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::config::SettingDifference;
+use georag_service::validate_presets;
+use std::path::Path;
+use tabled::Tabled;
+
+/// Execute config sync commands
+pub async fn execute(
+    args: ConfigArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+
+    match args.command {
+        ConfigCommand::Pull => execute_pull(output, dry_run, storage, &workspace_root).await,
+        ConfigCommand::Push => execute_push(output, dry_run, storage, &workspace_root).await,
+        ConfigCommand::Check => execute_check(output, storage, &workspace_root).await,
+    }
+}
+
+/// Write the stored settings into config.toml
+async fn execute_pull(
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace_root: &Path,
+) -> Result<()> {
+    let stored = load_stored_settings(storage.workspaces.as_ref())
+        .await
+        .context("Failed to read stored settings")?;
+    let Some(stored) = stored else {
+        bail!("No settings stored for this workspace; run 'georag config push' first");
+    };
+
+    let file = read_file_settings(workspace_root)?;
+    let changed = changed_keys(&file.differences(&stored)?);
+
+    if dry_run {
+        let action = changed.iter().fold(
+            PlannedAction::new(ActionType::ModifyFile, "Write stored settings to config.toml"),
+            |action, key| action.with_detail(format!("Update {}", key)),
+        );
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    if !changed.is_empty() {
+        write_file_settings(workspace_root, &stored)?;
+    }
+
+    report_sync(output, "pull", changed, "config.toml")
+}
+
+/// Replace the stored settings with those in config.toml
+async fn execute_push(
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace_root: &Path,
+) -> Result<()> {
+    let file = read_file_settings(workspace_root)?;
+    let stored = load_stored_settings(storage.workspaces.as_ref())
+        .await
+        .context("Failed to read stored settings")?
+        .unwrap_or_default();
+    let changed = changed_keys(&file.differences(&stored)?);
+
+    if dry_run {
+        let action = changed.iter().fold(
+            PlannedAction::new(ActionType::WriteFile, "Store settings from config.toml"),
+            |action, key| action.with_detail(format!("Update {}", key)),
+        );
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    if !changed.is_empty() {
+        save_stored_settings(storage.workspaces.as_ref(), &file)
+            .await
+            .context("Failed to store settings")?;
+    }
+
+    report_sync(output, "push", changed, "the store")
+}
+
+/// Compare config.toml with the stored settings
+async fn execute_check(
+    output: &OutputWriter,
+    storage: &Storage,
+    workspace_root: &Path,
+) -> Result<()> {
+    let file = read_file_settings(workspace_root)?;
+    let stored = load_stored_settings(storage.workspaces.as_ref())
+        .await
+        .context("Failed to read stored settings")?;
+    let differences = match &stored {
+        Some(stored) => file.differences(stored)?,
+        None => Vec::new(),
+    };
+    let conflicts = differences.iter().filter(|d| d.is_conflict()).count();
+
+    // Values are checked the same way the API checks settings it stores
+    let mut invalid = Vec::new();
+    if let Err(e) = file.validate() {
+        invalid.push(format!("config.toml: {}", e));
+    }
+    if let Err(e) = validate_presets(&file) {
+        invalid.push(format!("config.toml: {}", e));
+    }
+    if let Some(stored) = &stored {
+        if let Err(e) = stored.validate() {
+            invalid.push(format!("store: {}", e));
+        }
+        if let Err(e) = validate_presets(stored) {
+            invalid.push(format!("store: {}", e));
+        }
+    }
+
+    if output.is_json() {
+        output.result(ConfigCheckOutput {
+            stored: stored.is_some(),
+            in_sync: stored.is_some() && differences.is_empty(),
+            conflicts,
+            differences,
+            invalid: invalid.clone(),
+        })?;
+        return check_valid(&invalid);
+    }
+
+    output.section("Workspace Settings");
+    for problem in &invalid {
+        output.error(problem);
+    }
+    if stored.is_none() {
+        output.info("No settings stored yet; config.toml applies on its own");
+        output.info("Run 'georag config push' to store them");
+        return check_valid(&invalid);
+    }
+    if differences.is_empty() {
+        output.success("config.toml matches the stored settings");
+        return check_valid(&invalid);
+    }
+
+    output.table(differences.iter().map(difference_row).collect());
+    if conflicts > 0 {
+        output.warning(format!(
+            "{} setting(s) differ between config.toml and the store; the stored value wins",
+            conflicts
+        ));
+    }
+    output.info("Run 'georag config pull' or 'georag config push' to reconcile them");
+
+    check_valid(&invalid)
+}
+
+/// Fail the check when a setting has an invalid value
+fn check_valid(invalid: &[String]) -> Result<()> {
+    if !invalid.is_empty() {
+        bail!("{} invalid setting(s) found", invalid.len());
+    }
+    Ok(())
+}
+
+fn changed_keys(differences: &[SettingDifference]) -> Vec<String> {
+    differences.iter().map(|d| d.key.clone()).collect()
+}
+
+fn report_sync(
+    output: &OutputWriter,
+    action: &'static str,
+    changed: Vec<String>,
+    target: &str,
+) -> Result<()> {
+    if output.is_json() {
+        output.result(ConfigSyncOutput { action, changed })?;
+    } else if changed.is_empty() {
+        output.success(format!("{} is already up to date", target));
+    } else {
+        output.success(format!("Updated {} setting(s) in {}", changed.len(), target));
+        for key in &changed {
+            output.kv("Updated", key);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct DifferenceRow {
+    #[tabled(rename = "Setting")]
+    key: String,
+    #[tabled(rename = "config.toml")]
+    file: String,
+    #[tabled(rename = "Store")]
+    store: String,
+}
+
+fn difference_row(difference: &SettingDifference) -> DifferenceRow {
+    let unset = || "-".to_string();
+    DifferenceRow {
+        key: difference.key.clone(),
+        file: difference.file.clone().unwrap_or_else(unset),
+        store: difference.store.clone().unwrap_or_else(unset),
+    }
+}
@@ -0,0 +1,158 @@
+use super::add::report_feature_errors;
+use crate::cli::ConvertArgs;
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::{ConvertOutput, ConvertedFile};
+use crate::storage::Storage;
+use anyhow::{Context, Result};
+use georag_core::formats::{FormatOptions, ReadPolicy, DEFAULT_MAX_FEATURE_ERRORS};
+use georag_service::{ConvertOptions, ConvertService, ConvertedLayer, PropertyMapping};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Convert a dataset file to GeoJSON or CSV, to a file or standard output
+pub async fn execute(
+    args: ConvertArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    // Without --output only the converted features go to stdout
+    let quiet;
+    let output = if args.output.is_none() {
+        quiet = OutputWriter::quiet();
+        &quiet
+    } else {
+        output
+    };
+
+    let mut read = FormatOptions::new();
+    for (key, value) in &args.options {
+        read = read.with_option(key, value);
+    }
+    if args.skip_invalid {
+        read = read.with_read_policy(ReadPolicy::lenient(DEFAULT_MAX_FEATURE_ERRORS));
+    }
+    let mapping = args
+        .map
+        .iter()
+        .fold(PropertyMapping::new(), |mapping, (from, to)| mapping.rename(from, to));
+    let options = ConvertOptions::new(args.to)
+        .with_read_options(read)
+        .with_crs(args.crs)
+        .with_mapping(mapping)
+        .with_max_vertices(args.max_vertices)
+        .with_split_layers(args.split_layers);
+
+    let service = ConvertService::new(storage.formats.clone());
+    let conversion = service
+        .convert(&args.input, &options)
+        .await
+        .with_context(|| format!("Failed to convert {}", args.input.display()))?;
+
+    let Some(destination) = &args.output else {
+        for layer in &conversion.layers {
+            print!("{}", layer.content);
+        }
+        report_feature_errors(&conversion.feature_errors, output);
+        return Ok(());
+    };
+
+    let files: Vec<(PathBuf, &ConvertedLayer)> = conversion
+        .layers
+        .iter()
+        .map(|layer| {
+            let path = match &layer.layer {
+                Some(name) if args.split_layers => layer_path(destination, name),
+                _ => destination.clone(),
+            };
+            (path, layer)
+        })
+        .collect();
+
+    if dry_run {
+        let actions: Vec<PlannedAction> = files
+            .iter()
+            .map(|(path, layer)| {
+                PlannedAction::new(
+                    ActionType::CreateFile,
+                    format!("Write {} features to {}", layer.feature_count, path.display()),
+                )
+                .with_detail(format!("Layer: {}", layer.layer.as_deref().unwrap_or("(none)")))
+            })
+            .collect();
+        display_planned_actions(output, &actions);
+        return Ok(());
+    }
+
+    for (path, layer) in &files {
+        fs::write(path, &layer.content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+
+    if output.is_json() {
+        report_feature_errors(&conversion.feature_errors, output);
+        return output.result(ConvertOutput {
+            input: args.input.display().to_string(),
+            source_format: conversion.source_format,
+            format: args.to.to_string(),
+            source_crs: conversion.source_crs,
+            crs: conversion.crs,
+            files: files
+                .iter()
+                .map(|(path, layer)| ConvertedFile {
+                    path: path.display().to_string(),
+                    layer: layer.layer.clone(),
+                    features: layer.feature_count,
+                })
+                .collect(),
+            simplified: conversion.simplified,
+            feature_errors: conversion.feature_errors,
+        });
+    }
+
+    output.success(format!(
+        "Converted {} features from {} to {}",
+        conversion.feature_count(),
+        conversion.source_format,
+        args.to
+    ));
+    if conversion.crs != conversion.source_crs {
+        output.kv(
+            "Reprojected",
+            format!("EPSG:{} → EPSG:{}", conversion.source_crs, conversion.crs),
+        );
+    }
+    if conversion.simplified > 0 {
+        output.kv("Simplified", conversion.simplified);
+    }
+    for (path, layer) in &files {
+        match &layer.layer {
+            Some(name) if args.split_layers => output.kv(name, path.display()),
+            _ => output.kv("Output", path.display()),
+        }
+    }
+    report_feature_errors(&conversion.feature_errors, output);
+
+    Ok(())
+}
+
+/// Output file of one layer: the layer name appended to the output file's stem
+fn layer_path(output: &Path, layer: &str) -> PathBuf {
+    let stem = output.file_stem().and_then(|s| s.to_str()).unwrap_or("converted");
+    let layer: String = layer
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = format!("{}-{}", stem, layer);
+    if let Some(extension) = output.extension().and_then(|e| e.to_str()) {
+        name = format!("{}.{}", name, extension);
+    }
+    output.with_file_name(name)
+}
@@ -0,0 +1,407 @@
+use crate::cli::{DatasetArgs, DatasetCommand, DeleteDatasetsArgs, RepairAxesArgs, SampleArgs};
+use crate::config::{
+    load_workspace_config, load_workspace_config_with_store, store_workspace_quota,
+};
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::lock::BuildLock;
+use crate::output::OutputWriter;
+use crate::output_types::{
+    DatasetDeleteEntry, DatasetDeleteOutput, RepairAxesOutput, SampleOutput,
+};
+use crate::storage::Storage;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use georag_core::config::LayeredConfig;
+use georag_core::geo::{GeometryExt, SampleStrategy};
+use georag_core::models::{Feature, TagVisibility};
+use georag_core::processing::text::truncate_chars;
+use georag_service::{
+    AxisRepairService, BulkAction, BulkDatasetService, BulkReport, DatasetFilter, NamePattern,
+};
+use serde_json::{json, Value};
+use std::path::Path;
+use tabled::Tabled;
+
+/// Longest property summary shown in the sample table
+const MAX_PROPERTIES_WIDTH: usize = 60;
+
+/// Execute dataset inspection and repair commands
+pub async fn execute(
+    args: DatasetArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    match args.command {
+        DatasetCommand::Sample(sample_args) => {
+            execute_sample(sample_args, output, storage, workspace).await
+        }
+        DatasetCommand::RepairAxes(repair_args) => {
+            execute_repair_axes(repair_args, output, dry_run, storage).await
+        }
+        DatasetCommand::Delete(delete_args) => {
+            execute_delete(delete_args, output, dry_run, storage, workspace).await
+        }
+    }
+}
+
+/// Print a sample of a dataset's features
+async fn execute_sample(
+    args: SampleArgs,
+    output: &OutputWriter,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let strategy: SampleStrategy = args.strategy.parse().map_err(|e: String| anyhow!(e))?;
+
+    // Outside a workspace (e.g. reading a bundle) only the environment applies
+    let config = match super::workspace_root(workspace, output) {
+        Ok(root) => load_workspace_config(&root)?,
+        Err(_) => LayeredConfig::with_defaults().load_from_env(),
+    };
+    let max_sample = config.max_sample.value;
+    if args.count > max_sample {
+        output.warning(format!(
+            "Sample size {} exceeds the maximum of {}; showing {} features",
+            args.count, max_sample, max_sample
+        ));
+    }
+    let count = args.count.min(max_sample);
+
+    let datasets = storage.spatial.list_datasets().await?;
+    let dataset = datasets
+        .into_iter()
+        .find(|d| d.name == args.name)
+        .with_context(|| format!("Dataset not found: {}", args.name))?;
+
+    let features = storage
+        .spatial
+        .sample_features(dataset.id, count, strategy)
+        .await
+        .context("Failed to sample dataset")?;
+
+    if output.is_json() {
+        output.result(SampleOutput {
+            kind: "FeatureCollection",
+            dataset: dataset.name,
+            strategy: strategy.to_string(),
+            features: features.into_iter().map(feature_to_geojson).collect(),
+        })?;
+        return Ok(());
+    }
+
+    output.section(format!("Sample of {}", dataset.name));
+    output.kv("Strategy", strategy);
+    output.kv("Features", format!("{} of {}", features.len(), dataset.feature_count));
+    output.table(features.iter().map(sample_row).collect());
+
+    Ok(())
+}
+
+/// Find features stored lat,lon and, with `--apply`, swap them to lon,lat
+async fn execute_repair_axes(
+    args: RepairAxesArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    let datasets = storage.spatial.list_datasets().await?;
+    let dataset = datasets
+        .into_iter()
+        .find(|d| d.name == args.name)
+        .with_context(|| format!("Dataset not found: {}", args.name))?;
+    if dataset.crs != 4326 {
+        bail!(
+            "Dataset '{}' is stored in EPSG:{}; axis repair only applies to EPSG:4326",
+            dataset.name,
+            dataset.crs
+        );
+    }
+
+    let service = AxisRepairService::new(
+        storage.spatial.clone(),
+        storage.document.clone(),
+        storage.vector.clone(),
+    );
+    let plan = service.scan(dataset.id).await.context("Failed to scan dataset")?;
+
+    if args.apply && dry_run {
+        let action = PlannedAction::new(
+            ActionType::ModifyFile,
+            format!("Repair axis order of dataset '{}'", dataset.name),
+        )
+        .with_detail(format!("Would swap {} features to lon,lat", plan.flips.len()));
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    let report = if args.apply && !plan.is_empty() {
+        Some(
+            service
+                .apply(&plan)
+                .await
+                .with_context(|| format!("Failed to update dataset '{}'", dataset.name))?,
+        )
+    } else {
+        None
+    };
+
+    if output.is_json() {
+        output.result(RepairAxesOutput {
+            dataset_name: dataset.name,
+            features_scanned: plan.features_scanned,
+            flagged: plan.flips.len(),
+            undecided: plan.undecided,
+            applied: report.is_some(),
+            chunks_invalidated: report.map_or(0, |r| r.chunks_invalidated),
+        })?;
+        return Ok(());
+    }
+
+    output.section(format!("Axis order of {}", dataset.name));
+    output.kv("Features scanned", plan.features_scanned);
+    output.kv("Stored lat,lon", plan.flips.len());
+    output.kv("Undecided", plan.undecided);
+
+    match report {
+        Some(report) => {
+            output.success(format!("Swapped {} feature(s) to lon,lat", report.features_flipped));
+            if report.chunks_invalidated > 0 {
+                output.info("Run 'georag build' to re-embed the repaired features");
+            }
+        }
+        None if plan.is_empty() => output.success("No features need swapping"),
+        None => output.info("Run again with --apply to swap them"),
+    }
+
+    Ok(())
+}
+
+/// Delete or archive the datasets matching a filter, listing them first
+///
+/// Without `--yes` (or with `--dry-run`) the matching datasets are only
+/// listed. Deleting holds the build lock so a concurrent build cannot index
+/// a dataset while it is removed.
+async fn execute_delete(
+    args: DeleteDatasetsArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let mut filter = DatasetFilter::default();
+    if let Some(pattern) = &args.pattern {
+        filter = filter.with_name(NamePattern::glob(pattern)?);
+    }
+    if let Some(regex) = &args.regex {
+        filter = filter.with_name(NamePattern::regex(regex)?);
+    }
+    if let Some(before) = &args.added_before {
+        filter = filter.with_added_before(parse_added_before(before)?);
+    }
+    if let Some(tag) = &args.tag {
+        filter = filter.with_tag(tag);
+    }
+    let action = if args.archive {
+        BulkAction::Archive
+    } else {
+        BulkAction::Delete
+    };
+
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let confirmed = args.yes && !dry_run;
+    let _lock = if confirmed {
+        Some(BuildLock::acquire(&workspace_root.join(".georag"), "dataset deletion")?)
+    } else {
+        None
+    };
+
+    let layered =
+        load_workspace_config_with_store(&workspace_root, storage.workspaces.as_ref()).await?;
+    let quota = store_workspace_quota(storage.workspaces.clone(), &layered).await?;
+    let service = BulkDatasetService::new(
+        storage.spatial.clone(),
+        storage.document.clone(),
+        storage.vector.clone(),
+        storage.blobs.clone(),
+        quota,
+        storage.audit_service(),
+    )
+    .with_event_log(storage.event_log_service());
+
+    let plan = service.plan(&filter, action, &TagVisibility::All).await?;
+    let report = if confirmed && !plan.is_empty() {
+        Some(
+            service
+                .apply(&plan, |progress| {
+                    if !output.is_json() {
+                        output.info(format!("  Handled {}/{}", progress.done, progress.total));
+                    }
+                })
+                .await,
+        )
+    } else {
+        None
+    };
+
+    let status = |i: usize| match report.as_ref().map(|report| &report.results[i]) {
+        None => "planned",
+        Some(result) if !result.succeeded() => "failed",
+        Some(_) if action == BulkAction::Archive => "archived",
+        Some(_) => "deleted",
+    };
+
+    if output.is_json() {
+        output.result(DatasetDeleteOutput {
+            action: action.as_str().to_string(),
+            confirmed: report.is_some(),
+            datasets_scanned: plan.datasets_scanned,
+            matched: plan.datasets.len(),
+            feature_count: plan.feature_count(),
+            source_bytes: plan.source_bytes(),
+            succeeded: report.as_ref().map(BulkReport::succeeded),
+            failed: report.as_ref().map(BulkReport::failed),
+            datasets: plan
+                .datasets
+                .iter()
+                .enumerate()
+                .map(|(i, candidate)| DatasetDeleteEntry {
+                    name: candidate.name.clone(),
+                    added_at: candidate.added_at,
+                    feature_count: candidate.feature_count,
+                    status: status(i).to_string(),
+                    error: report.as_ref().and_then(|report| report.results[i].error.clone()),
+                })
+                .collect(),
+        })?;
+        return Ok(());
+    }
+
+    if plan.is_empty() {
+        output.info(format!(
+            "No dataset matches the filter ({} datasets checked)",
+            plan.datasets_scanned
+        ));
+        return Ok(());
+    }
+
+    output.section(match action {
+        BulkAction::Delete => "Datasets to delete",
+        BulkAction::Archive => "Datasets to archive",
+    });
+    output.table(
+        plan.datasets
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| DeleteRow {
+                name: candidate.name.clone(),
+                added: candidate.added_at.format("%Y-%m-%d").to_string(),
+                features: candidate.feature_count,
+                status: status(i).to_string(),
+            })
+            .collect(),
+    );
+    output.kv("Matched", format!("{} of {}", plan.datasets.len(), plan.datasets_scanned));
+    output.kv("Features", plan.feature_count());
+    output.kv("Source files", super::db::format_bytes(plan.source_bytes() as i64));
+
+    match report {
+        Some(report) => {
+            for result in report.results.iter().filter(|r| !r.succeeded()) {
+                output.warning(format!(
+                    "{}: {}",
+                    result.name,
+                    result.error.as_deref().unwrap_or_default()
+                ));
+            }
+            let verb = match action {
+                BulkAction::Delete => "Deleted",
+                BulkAction::Archive => "Archived",
+            };
+            output.success(format!("{} {} dataset(s)", verb, report.succeeded()));
+            if report.failed() > 0 {
+                bail!("{} dataset(s) could not be changed", report.failed());
+            }
+        }
+        None if dry_run => output.info("Dry run: nothing was changed"),
+        None => output.info("Re-run with --yes to apply"),
+    }
+
+    Ok(())
+}
+
+/// Parse `--added-before` as a date (midnight UTC) or an RFC 3339 time
+fn parse_added_before(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN).and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| {
+            anyhow!("Invalid --added-before '{}': use YYYY-MM-DD or an RFC 3339 time", value)
+        })
+}
+
+#[derive(Tabled)]
+struct DeleteRow {
+    #[tabled(rename = "Dataset")]
+    name: String,
+    #[tabled(rename = "Added")]
+    added: String,
+    #[tabled(rename = "Features")]
+    features: usize,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+#[derive(Tabled)]
+struct SampleRow {
+    #[tabled(rename = "ID")]
+    id: u64,
+    #[tabled(rename = "Type")]
+    geometry_type: String,
+    #[tabled(rename = "Location")]
+    location: String,
+    #[tabled(rename = "Properties")]
+    properties: String,
+}
+
+fn sample_row(feature: &Feature) -> SampleRow {
+    let geometry = feature.geometry.as_ref();
+
+    let mut properties: Vec<String> = feature
+        .properties
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(s) => format!("{}={}", key, s),
+            other => format!("{}={}", key, other),
+        })
+        .collect();
+    properties.sort();
+    let mut properties = properties.join(", ");
+    if properties.chars().count() > MAX_PROPERTIES_WIDTH {
+        properties = truncate_chars(&properties, MAX_PROPERTIES_WIDTH - 3).to_string() + "...";
+    }
+
+    SampleRow {
+        id: feature.id.0,
+        geometry_type: geometry
+            .map(|g| format!("{:?}", g.geometry_type()))
+            .unwrap_or_else(|| "-".to_string()),
+        location: geometry
+            .and_then(|g| g.centroid_coords())
+            .map(|[x, y]| format!("{:.5}, {:.5}", x, y))
+            .unwrap_or_else(|| "-".to_string()),
+        properties,
+    }
+}
+
+fn feature_to_geojson(feature: Feature) -> Value {
+    json!({
+        "type": "Feature",
+        "id": feature.id.0,
+        "geometry": feature.geometry_geojson(),
+        "properties": feature.properties,
+    })
+}
@@ -1,10 +1,24 @@
-use crate::cli::{DbArgs, DbCommand};
+use crate::cli::{CompactArgs, DbCommand, GcArgs, UpgradeArgs, VerifyArgs};
+use crate::lock::{BuildLock, LOCK_FILE};
 use crate::output::OutputWriter;
-use anyhow::{Context, Result};
+use crate::output_types::{
+    CompactOutput, GcOutput, IndexDriftInfo, UpgradeOutput, UpgradeStepInfo, VerifyOutput,
+};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use georag_core::models::{DatasetIndexStats, IndexState, INDEX_SCHEMA_VERSION};
+use georag_service::{CompactionService, GcService, IndexStatsService, UpgradeService};
 use georag_store::postgres::{PostgresConfig, PostgresStore};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory inside `.georag` holding the backups taken before upgrades
+const BACKUP_DIR: &str = "backups";
 
 /// Execute database management commands
-pub fn execute(args: DbArgs, output: &OutputWriter, dry_run: bool) -> Result<()> {
+pub fn execute(command: DbCommand, output: &OutputWriter, dry_run: bool) -> Result<()> {
     let rt = tokio::runtime::Runtime::new().context("Failed to create async runtime")?;
 
     rt.block_on(async {
@@ -15,7 +29,7 @@ pub fn execute(args: DbArgs, output: &OutputWriter, dry_run: bool) -> Result<()>
         // Create store connection
         let store = PostgresStore::new(config).await.context("Failed to connect to database")?;
 
-        match args.command {
+        match command {
             DbCommand::Rebuild(rebuild_args) => {
                 execute_rebuild(&store, rebuild_args, output, dry_run).await
             }
@@ -23,6 +37,11 @@ pub fn execute(args: DbArgs, output: &OutputWriter, dry_run: bool) -> Result<()>
             DbCommand::Vacuum(vacuum_args) => {
                 execute_vacuum(&store, vacuum_args, output, dry_run).await
             }
+            // Compaction works on any backend and is dispatched with the workspace storage
+            DbCommand::Compact(_) => unreachable!("db compact is dispatched separately"),
+            DbCommand::Gc(_) => unreachable!("db gc is dispatched separately"),
+            DbCommand::Verify(_) => unreachable!("db verify is dispatched separately"),
+            DbCommand::Upgrade(_) => unreachable!("db upgrade is dispatched separately"),
         }
     })
 }
@@ -155,8 +174,377 @@ async fn execute_vacuum(
     Ok(())
 }
 
+/// Execute compaction of orphaned embeddings and chunks
+///
+/// Chunks whose content has no letters or digits are removed as well.
+///
+/// Only reports what would be deleted unless `--apply` is given. The build
+/// lock is held throughout so a concurrent build cannot look orphaned.
+pub async fn compact(
+    args: CompactArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let _lock = BuildLock::acquire(&workspace_root.join(".georag"), "compaction")?;
+
+    let service = CompactionService::new(
+        storage.spatial.clone(),
+        storage.document.clone(),
+        storage.vector.clone(),
+    )
+    .with_batch_size(args.batch_size);
+
+    output.info("Scanning embeddings and chunks...");
+    let plan = service.scan().await.context("Failed to scan the index stores")?;
+
+    let apply = args.apply && !dry_run && !plan.is_empty();
+    let report = if apply {
+        output.section("Deleting orphans");
+        Some(
+            service
+                .apply(&plan, |progress| {
+                    output.info(format!("  Deleted {}/{}", progress.deleted, progress.total));
+                })
+                .await
+                .context("Failed to delete orphaned data")?,
+        )
+    } else {
+        None
+    };
+
+    if output.is_json() {
+        output.result(CompactOutput {
+            embeddings_scanned: plan.embeddings_scanned,
+            chunks_scanned: plan.chunks_scanned,
+            orphan_embeddings: plan.orphan_embeddings.len(),
+            dangling_chunks: plan.dangling_chunks.len(),
+            degenerate_chunks: plan.degenerate_chunks.len(),
+            reclaimable_bytes: plan.reclaimable_bytes,
+            applied: report.is_some(),
+            embeddings_deleted: report.map_or(0, |r| r.embeddings_deleted),
+            chunks_deleted: report.map_or(0, |r| r.chunks_deleted),
+            degenerate_deleted: report.map_or(0, |r| r.degenerate_deleted),
+        })?;
+        return Ok(());
+    }
+
+    output.kv("Embeddings scanned", plan.embeddings_scanned);
+    output.kv("Chunks scanned", plan.chunks_scanned);
+    output.kv("Orphaned embeddings", plan.orphan_embeddings.len());
+    output.kv("Chunks without feature", plan.dangling_chunks.len());
+    output.kv("Chunks without text", plan.degenerate_chunks.len());
+    output.kv("Reclaimable", format_bytes(plan.reclaimable_bytes as i64));
+
+    match report {
+        Some(report) => output.success(format!(
+            "Deleted {} embedding(s) and {} chunk(s)",
+            report.embeddings_deleted,
+            report.chunks_deleted + report.degenerate_deleted
+        )),
+        None if plan.is_empty() => output.success("Nothing to compact"),
+        None => output.info("Dry run: re-run with --apply to delete them"),
+    }
+
+    Ok(())
+}
+
+/// Execute garbage collection of chunks left by changed or deleted features
+///
+/// With `--dry-run` only the counts are reported. The build lock is held
+/// throughout so a concurrent build cannot look stale.
+pub async fn gc(
+    _args: GcArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let _lock = BuildLock::acquire(&workspace_root.join(".georag"), "garbage collection")?;
+
+    let service =
+        GcService::new(storage.spatial.clone(), storage.document.clone(), storage.vector.clone());
+
+    output.info("Comparing chunks with their features...");
+    let plan = service.scan().await.context("Failed to scan the index stores")?;
+
+    let report = if dry_run || plan.is_empty() {
+        None
+    } else {
+        Some(service.apply(&plan).await.context("Failed to collect outdated chunks")?)
+    };
+
+    if output.is_json() {
+        output.result(GcOutput {
+            chunks_scanned: plan.chunks_scanned,
+            orphaned_chunks: plan.orphaned_chunks.len(),
+            stale_chunks: plan.stale_chunks.len(),
+            already_stale: plan.already_stale,
+            applied: report.is_some(),
+            chunks_deleted: report.map_or(0, |r| r.chunks_deleted),
+            chunks_marked_stale: report.map_or(0, |r| r.chunks_marked_stale),
+        })?;
+        return Ok(());
+    }
+
+    output.kv("Chunks scanned", plan.chunks_scanned);
+    output.kv("Chunks without feature", plan.orphaned_chunks.len());
+    output.kv("Chunks with changed text", plan.stale_chunks.len());
+    output.kv("Already stale", plan.already_stale);
+
+    match report {
+        Some(report) => output.success(format!(
+            "Deleted {} chunk(s) and marked {} stale; run 'georag build' to re-embed them",
+            report.chunks_deleted, report.chunks_marked_stale
+        )),
+        None if plan.is_empty() => output.success("Nothing to collect"),
+        None => output.info("Dry run: re-run without --dry-run to collect them"),
+    }
+
+    Ok(())
+}
+
+/// Execute a check of the per-dataset index counters
+///
+/// `--recount` rebuilds the counters when drift is found; with `--dry-run`
+/// the drift is only reported. The build lock is held throughout so a
+/// concurrent build does not show up as drift.
+pub async fn verify(
+    args: VerifyArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let _lock = BuildLock::acquire(&workspace_root.join(".georag"), "index verification")?;
+
+    let service = IndexStatsService::new(storage.document.clone(), storage.vector.clone());
+
+    output.info(if args.deep {
+        "Counting every dataset's chunks and embeddings..."
+    } else {
+        "Comparing counter totals with the stored chunks and embeddings..."
+    });
+    let verification =
+        service.verify(args.deep).await.context("Failed to verify the index counters")?;
+
+    let recounted = args.recount && !dry_run && !verification.is_consistent();
+    if recounted {
+        service.recount().await.context("Failed to recount the index counters")?;
+    }
+
+    let names: HashMap<_, _> = storage
+        .spatial
+        .list_datasets()
+        .await?
+        .into_iter()
+        .map(|meta| (meta.id, meta.name))
+        .collect();
+    let drift: Vec<IndexDriftInfo> = verification
+        .drift
+        .iter()
+        .map(|drift| IndexDriftInfo {
+            dataset: drift.dataset_id.map(|id| {
+                names.get(&id).cloned().unwrap_or_else(|| format!("deleted dataset {}", id.0))
+            }),
+            counted: drift.counted,
+            actual: drift.actual,
+        })
+        .collect();
+
+    if output.is_json() {
+        output.result(VerifyOutput {
+            deep: verification.deep,
+            consistent: verification.is_consistent(),
+            drift,
+            unattributed_chunks: verification.unattributed_chunks,
+            recounted,
+        })?;
+        return Ok(());
+    }
+
+    for info in &drift {
+        let describe = |stats: &DatasetIndexStats| {
+            format!("{} chunks, {} embeddings", stats.chunks, stats.embeddings)
+        };
+        output.warning(format!(
+            "{}: counted {}, stored {}",
+            info.dataset.as_deref().unwrap_or("Totals"),
+            describe(&info.counted),
+            describe(&info.actual)
+        ));
+    }
+    if verification.unattributed_chunks > 0 {
+        output.kv("Chunks without dataset", verification.unattributed_chunks);
+        output.info("These chunks predate the counters; rebuild the index to attribute them");
+    }
+
+    if verification.is_consistent() {
+        output.success("Index counters match the stored data");
+    } else if recounted {
+        output.success("Rebuilt the index counters from the stored data");
+    } else if args.recount {
+        output.info("Dry run: re-run without --dry-run to rebuild the counters");
+    } else if args.deep {
+        output.info("Run 'georag db verify --deep --recount' to rebuild the counters");
+    } else {
+        output.info("Run 'georag db verify --deep' to find the datasets that drifted");
+    }
+
+    Ok(())
+}
+
+/// Execute an upgrade of the index to the schema version of this build
+///
+/// The workspace's `.georag` directory is copied to `.georag/backups` before
+/// the first step runs; with `--dry-run` the pending steps are only listed.
+/// Indexes written by a newer build are refused untouched. The memory
+/// backend keeps no chunks or embeddings between runs, so its steps have no
+/// stored data to change and only the index state is upgraded.
+pub async fn upgrade(
+    _args: UpgradeArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let georag_dir = workspace_root.join(".georag");
+    let _lock = BuildLock::acquire(&georag_dir, "index upgrade")?;
+
+    let state_path = georag_dir.join("index").join("state.json");
+    if !state_path.exists() {
+        bail!("Index not built. Run 'georag build' first; new indexes need no upgrade.");
+    }
+    let content = fs::read_to_string(&state_path).context("Failed to read the index state")?;
+    let mut state: IndexState =
+        serde_json::from_str(&content).context("Failed to parse the index state")?;
+
+    let from = state.schema_version;
+    let steps = UpgradeService::pending(from)?;
+    let applied = !dry_run && !steps.is_empty();
+
+    let backup = if applied {
+        let backup = upgrade_backup_path(&georag_dir, from);
+        copy_workspace_data(&georag_dir, &backup).with_context(|| {
+            format!("Failed to back up the workspace data to {}", backup.display())
+        })?;
+        output.info(format!("Backed up the workspace data to {}", backup.display()));
+        Some(backup)
+    } else {
+        None
+    };
+
+    if applied && storage.persistent {
+        let service = UpgradeService::new(storage.document.clone(), storage.vector.clone());
+        let result = service
+            .upgrade(&mut state, |step| {
+                output.info(format!("Version {} to {}: {}", step.from, step.to(), step.description))
+            })
+            .await;
+
+        // Steps that completed are recorded even when a later one failed
+        if state.schema_version != from {
+            fs::write(&state_path, serde_json::to_string_pretty(&state)?)
+                .context("Failed to write the index state")?;
+        }
+        result.context("Failed to upgrade the index")?;
+    } else if applied {
+        output.info(
+            "The memory backend stores no index data between runs; upgrading the index state only",
+        );
+        state.schema_version = INDEX_SCHEMA_VERSION;
+        fs::write(&state_path, serde_json::to_string_pretty(&state)?)
+            .context("Failed to write the index state")?;
+    }
+
+    if output.is_json() {
+        output.result(UpgradeOutput {
+            from_version: from,
+            to_version: state.schema_version,
+            current_version: INDEX_SCHEMA_VERSION,
+            steps: steps
+                .iter()
+                .map(|step| UpgradeStepInfo {
+                    from: step.from,
+                    to: step.to(),
+                    description: step.description.to_string(),
+                })
+                .collect(),
+            backup: backup.map(|path| path.display().to_string()),
+            applied,
+        })?;
+        return Ok(());
+    }
+
+    if steps.is_empty() {
+        output.success(format!("Index schema is already current (version {})", from));
+    } else if applied {
+        output.success(format!(
+            "Upgraded the index from schema version {} to {}",
+            from, state.schema_version
+        ));
+    } else {
+        output.section("Pending Upgrade Steps");
+        for step in &steps {
+            output.kv(format!("Version {} to {}", step.from, step.to()), step.description);
+        }
+        output.info("Dry run: re-run without --dry-run to upgrade the index");
+    }
+
+    Ok(())
+}
+
+/// Where the workspace data of index schema `version` is backed up before an upgrade
+fn upgrade_backup_path(georag_dir: &Path, version: u32) -> PathBuf {
+    georag_dir.join(BACKUP_DIR).join(format!(
+        "upgrade-v{}-{}",
+        version,
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ))
+}
+
+/// Copy the workspace data in `georag_dir` to `backup`
+///
+/// Earlier backups and the build lock are left out.
+fn copy_workspace_data(georag_dir: &Path, backup: &Path) -> std::io::Result<()> {
+    fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            let target = to.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                copy_dir(&entry.path(), &target)?;
+            } else {
+                fs::copy(entry.path(), target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fs::create_dir_all(backup)?;
+    for entry in fs::read_dir(georag_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == BACKUP_DIR || name == LOCK_FILE {
+            continue;
+        }
+        let target = backup.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 /// Format bytes into human-readable format
-fn format_bytes(bytes: i64) -> String {
+pub(super) fn format_bytes(bytes: i64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
 
     if bytes < 0 {
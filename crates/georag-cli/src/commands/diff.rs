@@ -0,0 +1,191 @@
+use crate::cli::DiffArgs;
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::{DiffApplyOutput, DiffOutput, DiffSample};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::models::Feature;
+use georag_service::{DatasetDiff, DiffService, IngestRequest};
+
+pub async fn execute(
+    args: DiffArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    if !args.path.exists() {
+        bail!("Dataset file not found: {}", args.path.display());
+    }
+
+    let datasets = storage.spatial.list_datasets().await?;
+    let meta = datasets
+        .into_iter()
+        .find(|d| d.name == args.against)
+        .with_context(|| format!("Dataset not found: {}", args.against))?;
+
+    // Read the incoming file the same way `add` would, without storing anything
+    let request = IngestRequest::new(&args.path)
+        .with_workspace_crs(meta.crs, true)
+        .with_store_features(false);
+    let prepared = storage
+        .ingest_service()
+        .prepare(&request)
+        .await
+        .with_context(|| format!("Failed to read {}", args.path.display()))?;
+    if prepared.has_crs_mismatch() {
+        bail!(
+            "'{}' is in EPSG:{} but dataset '{}' is stored in EPSG:{}; reproject the file first",
+            args.path.display(),
+            prepared.dataset.crs,
+            args.against,
+            meta.crs
+        );
+    }
+
+    let service =
+        DiffService::new(storage.spatial.clone(), storage.document.clone(), storage.vector.clone());
+    let diff = service.diff(meta.id, prepared.features, Some(&args.key)).await?;
+    let counts = diff.counts();
+
+    if args.apply && dry_run {
+        let action = PlannedAction::new(
+            ActionType::ModifyFile,
+            format!("Update features of dataset '{}'", args.against),
+        )
+        .with_detail(format!("Would add {} features", counts.added))
+        .with_detail(format!("Would replace {} features", diff.changed.len()))
+        .with_detail(format!("Would delete {} features", counts.removed));
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    let applied = if args.apply && !diff.is_empty() {
+        Some(
+            service
+                .apply(&diff)
+                .await
+                .with_context(|| format!("Failed to update dataset '{}'", args.against))?,
+        )
+    } else {
+        None
+    };
+
+    let samples = samples(&diff, args.sample);
+
+    if output.is_json() {
+        output.result(DiffOutput {
+            dataset_name: args.against,
+            path: args.path.display().to_string(),
+            key: diff.key.clone(),
+            matched_by_key: diff.matched_by_key,
+            added: counts.added,
+            removed: counts.removed,
+            geometry_changed: counts.geometry_changed,
+            property_changed: counts.property_changed,
+            unchanged: counts.unchanged,
+            samples,
+            applied: applied.map(|report| DiffApplyOutput {
+                features_upserted: report.features_upserted,
+                features_deleted: report.features_deleted,
+                chunks_invalidated: report.chunks_invalidated,
+            }),
+        })?;
+        return Ok(());
+    }
+
+    if diff.is_empty() {
+        output.success(format!("'{}' matches the stored dataset", args.against));
+    } else {
+        output.success(format!("Compared '{}' with '{}'", args.path.display(), args.against));
+    }
+    output.kv("Matched by key", format!("{} (key '{}')", diff.matched_by_key, diff.key));
+    output.kv("Added", counts.added);
+    output.kv("Removed", counts.removed);
+    output.kv("Geometry changed", counts.geometry_changed);
+    output.kv("Properties changed", counts.property_changed);
+    output.kv("Unchanged", counts.unchanged);
+
+    if !samples.is_empty() {
+        output.section("Changes");
+        for sample in &samples {
+            output.info(describe(sample));
+        }
+    }
+
+    match applied {
+        Some(report) => {
+            output.success(format!("Updated dataset '{}'", args.against));
+            output.kv("Features written", report.features_upserted);
+            output.kv("Features deleted", report.features_deleted);
+            output.kv("Chunks to re-embed", report.chunks_invalidated);
+            if report.chunks_invalidated > 0 || counts.added > 0 {
+                output.info("Run 'georag build' to embed the updated features");
+            }
+        }
+        None if !diff.is_empty() && !args.apply => {
+            output.info("Run again with --apply to update the dataset");
+        }
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Up to `limit` features of each change class
+fn samples(diff: &DatasetDiff, limit: usize) -> Vec<DiffSample> {
+    let key_of = |feature: &Feature| {
+        feature.properties.get(&diff.key).filter(|v| !v.is_null()).map(|v| match v {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+    };
+
+    let mut samples = Vec::new();
+    samples.extend(diff.added.iter().take(limit).map(|feature| DiffSample {
+        change: "added".to_string(),
+        feature_id: None,
+        key: key_of(feature),
+        properties: Vec::new(),
+    }));
+    samples.extend(diff.removed.iter().take(limit).map(|feature| DiffSample {
+        change: "removed".to_string(),
+        feature_id: Some(feature.id.0),
+        key: key_of(feature),
+        properties: Vec::new(),
+    }));
+    samples.extend(diff.changed.iter().filter(|c| c.geometry_changed).take(limit).map(|change| {
+        DiffSample {
+            change: "geometry_changed".to_string(),
+            feature_id: Some(change.before.id.0),
+            key: change.key.clone(),
+            properties: Vec::new(),
+        }
+    }));
+    samples.extend(
+        diff.changed
+            .iter()
+            .filter(|c| !c.changed_properties.is_empty())
+            .take(limit)
+            .map(|change| DiffSample {
+                change: "property_changed".to_string(),
+                feature_id: Some(change.before.id.0),
+                key: change.key.clone(),
+                properties: change.changed_properties.clone(),
+            }),
+    );
+    samples
+}
+
+fn describe(sample: &DiffSample) -> String {
+    let mut line = sample.change.replace('_', " ");
+    if let Some(id) = sample.feature_id {
+        line.push_str(&format!(" feature {}", id));
+    }
+    if let Some(key) = &sample.key {
+        line.push_str(&format!(" ({})", key));
+    }
+    if !sample.properties.is_empty() {
+        line.push_str(&format!(": {}", sample.properties.join(", ")));
+    }
+    line
+}
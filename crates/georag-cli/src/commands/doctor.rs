@@ -4,8 +4,12 @@ use crate::config;
 use crate::output::OutputWriter;
 use anyhow::Result;
 use console::style;
+use georag_core::config::{mask_url_credentials, LayeredConfig};
+use std::path::Path;
+
+pub fn execute(_args: DoctorArgs, output: &OutputWriter, workspace: Option<&Path>) -> Result<()> {
+    let verbose = output.is_verbose();
 
-pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
     println!("\n{}", style("GeoRAG Health Check").bold().underlined());
     println!("{}", style("═".repeat(60)).dim());
     println!();
@@ -13,13 +17,20 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
     let mut checks_passed = 0;
     let mut total_checks = 0;
 
+    // Embedder model and auto-pull setting from the workspace, or the defaults
+    let mut embedder_config = LayeredConfig::with_defaults().load_from_env();
+
     // Check workspace
     total_checks += 1;
-    match config::find_workspace_root() {
+    match config::find_workspace_root(workspace) {
         Ok(workspace_path) => {
             println!("{} Workspace: Found at {}", style("✓").green(), workspace_path.display());
             checks_passed += 1;
 
+            if let Ok(layered) = config::load_workspace_config(&workspace_path) {
+                embedder_config = layered;
+            }
+
             // Check for issues
             let issues = detect_workspace_issues(&workspace_path);
             if !issues.is_empty() {
@@ -35,7 +46,7 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
                     println!("{} Config: Valid configuration", style("✓").green());
                     checks_passed += 1;
 
-                    if args.verbose {
+                    if verbose {
                         println!("  Storage backend: {}", config.storage.backend);
                         if let Some(pg) = config.postgres {
                             println!("  PostgreSQL: {}:{}/{}", pg.host, pg.port, pg.database);
@@ -100,7 +111,7 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
         checks_passed += 1;
 
         if let Some(ref version) = pg_detection.version {
-            if args.verbose {
+            if verbose {
                 println!("  Version: {}", version);
             }
         }
@@ -116,8 +127,8 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
         checks_passed += 1;
 
         if let Some(ref url) = pg_detection.suggested_url {
-            if args.verbose {
-                println!("  Suggested URL: {}", url);
+            if verbose {
+                println!("  Suggested URL: {}", mask_url_credentials(url));
             }
         }
     } else if pg_detection.installed {
@@ -132,10 +143,8 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
         println!("{} DATABASE_URL: Set", style("✓").green());
         checks_passed += 1;
 
-        if args.verbose {
-            // Hide password
-            let display_url = url.split('@').next_back().unwrap_or(&url);
-            println!("  Value: ...@{}", display_url);
+        if verbose {
+            println!("  Value: {}", mask_url_credentials(&url));
         }
     } else {
         println!("{} DATABASE_URL: Not set", style("⚠").yellow());
@@ -163,7 +172,7 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
         println!("{} Ollama: Running", style("✓").green());
         checks_passed += 1;
 
-        if args.verbose && !ollama_detection.available_models.is_empty() {
+        if verbose && !ollama_detection.available_models.is_empty() {
             println!("  Available models:");
             for model in &ollama_detection.available_models {
                 println!("    • {}", model);
@@ -174,14 +183,25 @@ pub fn execute(args: DoctorArgs, _output: &OutputWriter) -> Result<()> {
         println!("  → Start: ollama serve");
     }
 
-    // Check for nomic-embed-text model
+    // Check for the configured embedding model
+    let embedder = &embedder_config.embedder.value;
+    let model = embedder.strip_prefix("ollama:").unwrap_or(embedder);
     total_checks += 1;
-    if ollama_detection.has_model("nomic-embed-text") {
-        println!("{} Model: nomic-embed-text available", style("✓").green());
+    if ollama_detection.has_model(model) {
+        println!("{} Model: {} available", style("✓").green(), model);
         checks_passed += 1;
     } else if ollama_detection.running {
-        println!("{} Model: nomic-embed-text not found", style("⚠").yellow());
-        println!("  → Pull: ollama pull nomic-embed-text");
+        if embedder_config.auto_pull.value {
+            println!(
+                "{} Model: {} not found (will be pulled on first build, auto_pull is on)",
+                style("⚠").yellow(),
+                model
+            );
+        } else {
+            println!("{} Model: {} not found", style("⚠").yellow(), model);
+            println!("  → Pull: ollama pull {}", model);
+            println!("  → Or set auto_pull = true in .georag/config.toml");
+        }
     }
 
     // Summary
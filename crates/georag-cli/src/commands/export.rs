@@ -0,0 +1,98 @@
+use crate::cli::ExportArgs;
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::geometry_arg::parse_bbox;
+use crate::output::OutputWriter;
+use crate::output_types::ExportOutput;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::resources::ResourceKind;
+use georag_store::bundle::OfflineBundle;
+use std::path::Path;
+
+pub async fn execute(
+    args: ExportArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    if !args.offline_bundle {
+        bail!("Nothing to export. Pass --offline-bundle with --bbox to write an offline bundle");
+    }
+    let bbox = parse_bbox(args.bbox.as_deref().context("--offline-bundle requires --bbox")?)?;
+
+    // Queries against the bundle need the embedder the index was built with
+    let index_state = match &storage.bundle_index {
+        Some(state) => state.clone(),
+        None => {
+            let workspace_root = super::workspace_root(workspace, output)?;
+            super::query::load_index_state(&workspace_root.join(".georag"))?
+        }
+    };
+
+    let _slot = storage.acquire_slot(ResourceKind::Export, output).await?;
+    let bundle = OfflineBundle::extract(
+        storage.spatial.as_ref(),
+        storage.vector.as_ref(),
+        storage.document.as_ref(),
+        bbox,
+        Some(index_state),
+    )
+    .await
+    .context("Failed to read the stores")?;
+
+    if dry_run {
+        let action = PlannedAction::new(
+            ActionType::CreateFile,
+            format!("Write offline bundle to {}", args.output.display()),
+        )
+        .with_detail(format!("Bounding box: {:?}", bbox))
+        .with_detail(format!("Format version: {}", bundle.format_version))
+        .with_detail(format!("Datasets: {}", bundle.datasets.len()))
+        .with_detail(format!("Features: {}", bundle.features.len()))
+        .with_detail(format!("Chunks: {}", bundle.chunks.len()))
+        .with_detail(format!("Embeddings: {}", bundle.embeddings.len()));
+        let action = bundle
+            .attributions
+            .iter()
+            .fold(action, |action, line| action.with_detail(format!("Attribution: {}", line)));
+
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    bundle
+        .write_to(&args.output)
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    if output.is_json() {
+        output.result(ExportOutput {
+            output: args.output.display().to_string(),
+            format_version: bundle.format_version,
+            bbox,
+            datasets: bundle.datasets.len(),
+            features: bundle.features.len(),
+            chunks: bundle.chunks.len(),
+            embeddings: bundle.embeddings.len(),
+            attributions: bundle.attributions.clone(),
+        })?;
+    } else {
+        output.success(format!("Exported offline bundle to {}", args.output.display()));
+        output.kv("Datasets", bundle.datasets.len());
+        output.kv("Features", bundle.features.len());
+        output.kv("Chunks", bundle.chunks.len());
+        output.kv("Embeddings", bundle.embeddings.len());
+        for line in &bundle.attributions {
+            output.kv("Attribution", line);
+        }
+        if bundle.chunks.is_empty() {
+            output.warning("No indexed chunks in the bounding box; run 'georag build' first");
+        }
+        output.info(format!(
+            "Serve it with: georag --from-bundle {} query \"...\"",
+            args.output.display()
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::cli::{FormatsArgs, FormatsCommand};
+use crate::output::OutputWriter;
+use crate::output_types::FormatsOutput;
+use crate::storage::Storage;
+use anyhow::Result;
+use georag_core::formats::OptionSpec;
+
+/// Execute format commands
+pub fn execute(args: FormatsArgs, output: &OutputWriter, storage: &Storage) -> Result<()> {
+    match args.command {
+        FormatsCommand::List => execute_list(output, storage),
+    }
+}
+
+/// List the registered formats with their extensions and reader options
+fn execute_list(output: &OutputWriter, storage: &Storage) -> Result<()> {
+    let formats = storage.formats.describe();
+
+    if output.is_json() {
+        return output.result(FormatsOutput { formats });
+    }
+
+    for format in &formats {
+        let extensions: Vec<String> =
+            format.extensions.iter().map(|ext| format!(".{}", ext)).collect();
+        output.section(format!("{} ({})", format.name, extensions.join(", ")));
+        if format.options.is_empty() {
+            output.info("No format options");
+        }
+        for option in &format.options {
+            output.kv(option.key, describe_option(option));
+        }
+    }
+    Ok(())
+}
+
+/// One-line description of an option, its type and default
+fn describe_option(option: &OptionSpec) -> String {
+    match option.default {
+        Some(default) => {
+            format!("{} [{}, default {}]", option.description, option.kind, default)
+        }
+        None => format!("{} [{}]", option.description, option.kind),
+    }
+}
@@ -0,0 +1,127 @@
+use crate::cli::{BufferArgs, GeoArgs, GeoCommand};
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::BufferOutput;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::geo::{buffer_geometry, geometry_from_geojson};
+use georag_core::models::DistanceUnit;
+use serde_json::{json, Value};
+use std::fs;
+
+/// Execute geometry utility commands
+pub async fn execute(
+    args: GeoArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    match args.command {
+        GeoCommand::Buffer(buffer_args) => {
+            execute_buffer(buffer_args, output, dry_run, storage).await
+        }
+    }
+}
+
+/// Buffer every feature geometry of a dataset file into a GeoJSON file
+async fn execute_buffer(
+    args: BufferArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    let distance = super::query::parse_distance(&args.distance, DistanceUnit::Meters)?;
+    if !distance.value.is_finite() || distance.value <= 0.0 {
+        bail!("Invalid distance: {}. The buffer distance must be positive", args.distance);
+    }
+
+    let reader = storage.formats.detect_format(&args.input)?;
+    let dataset = reader
+        .read(&args.input)
+        .await
+        .with_context(|| format!("Failed to read {}", args.input.display()))?;
+
+    // Buffer distances are measured in meters around longitude/latitude coordinates
+    if dataset.crs != 4326 {
+        bail!(
+            "Cannot buffer {}: it uses EPSG:{}, but buffering expects longitude/latitude \
+            coordinates (EPSG:4326)",
+            args.input.display(),
+            dataset.crs
+        );
+    }
+
+    let mut buffered = 0;
+    let mut skipped = 0;
+    let features = dataset
+        .features
+        .iter()
+        .map(|feature| {
+            let geometry = match feature.geometry.as_ref().map(geometry_from_geojson) {
+                Some(Err((_, reason))) => {
+                    bail!("Cannot buffer feature {}: {}", feature.id, reason)
+                }
+                Some(Ok(geometry)) => geometry,
+                None => None,
+            };
+            let geometry = match geometry {
+                Some(geometry) => {
+                    buffered += 1;
+                    buffer_geometry(&geometry, &distance).to_geojson()
+                }
+                None => {
+                    skipped += 1;
+                    Value::Null
+                }
+            };
+            Ok(json!({
+                "type": "Feature",
+                "id": feature.id,
+                "geometry": geometry,
+                "properties": feature.properties,
+            }))
+        })
+        .collect::<Result<Vec<Value>>>()?;
+
+    if dry_run {
+        let action = PlannedAction::new(
+            ActionType::CreateFile,
+            format!("Write buffered features to {}", args.output.display()),
+        )
+        .with_detail(format!("Input: {}", args.input.display()))
+        .with_detail(format!("Distance: {} {:?}", distance.value, distance.unit))
+        .with_detail(format!("Features buffered: {}", buffered))
+        .with_detail(format!("Features without geometry: {}", skipped));
+
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    fs::write(&args.output, serde_json::to_string_pretty(&collection)?)
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    if output.is_json() {
+        output.result(BufferOutput {
+            input: args.input.display().to_string(),
+            output: args.output.display().to_string(),
+            distance_meters: distance.to_meters(),
+            buffered,
+            skipped,
+        })?;
+    } else {
+        output.success(format!(
+            "Buffered {} features by {} {:?}",
+            buffered, distance.value, distance.unit
+        ));
+        output.kv("Output", args.output.display());
+        if skipped > 0 {
+            output.warning(format!("{} features without geometry were copied unchanged", skipped));
+        }
+    }
+
+    Ok(())
+}
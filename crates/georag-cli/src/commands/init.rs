@@ -1,27 +1,35 @@
 use crate::cli::InitArgs;
+use crate::config::STORE_WORKSPACE;
 use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
 use crate::interactive;
 use crate::output::OutputWriter;
 use crate::output_types::InitOutput;
+use crate::storage::Storage;
 use anyhow::{bail, Context, Result};
-use georag_core::config::{parse_distance_unit, parse_validity_mode};
+use georag_core::config::{parse_distance_unit, parse_validity_mode, WorkspaceSettings};
 use georag_core::models::workspace::WorkspaceConfig;
+use georag_service::{new_workspace_config, ServiceError, WorkspaceService};
 use std::fs;
+use std::path::Path;
 
-pub fn execute(args: InitArgs, output: &OutputWriter, dry_run: bool) -> Result<()> {
+pub async fn execute(
+    args: InitArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
     // If interactive mode, use interactive prompts
     if args.interactive {
         let interactive_result = interactive::interactive_init()?;
 
         // Use the interactive results
-        let distance_unit = parse_distance_unit(&interactive_result.distance_unit)?;
-        let validity_mode = parse_validity_mode(&interactive_result.validity_mode)?;
-
-        let config = WorkspaceConfig {
-            crs: interactive_result.crs,
-            distance_unit,
-            geometry_validity: validity_mode,
+        let settings = WorkspaceSettings {
+            crs: Some(interactive_result.crs),
+            distance_unit: Some(parse_distance_unit(&interactive_result.distance_unit)?),
+            geometry_validity: Some(parse_validity_mode(&interactive_result.validity_mode)?),
+            ..Default::default()
         };
+        let config = new_workspace_config(&settings)?;
 
         // Create workspace with interactive settings
         create_workspace(&interactive_result.path, &config, output, dry_run)?;
@@ -37,24 +45,55 @@ pub fn execute(args: InitArgs, output: &OutputWriter, dry_run: bool) -> Result<(
             }
         }
 
+        if !dry_run {
+            register_workspace(storage, &interactive_result.path, &settings, output).await;
+        }
+
         return Ok(());
     }
 
     // Non-interactive mode (original behavior)
-    let distance_unit = parse_distance_unit(&args.distance_unit)?;
-    let validity_mode = parse_validity_mode(&args.validity_mode)?;
-
-    let config = WorkspaceConfig {
-        crs: args.crs,
-        distance_unit,
-        geometry_validity: validity_mode,
+    let settings = WorkspaceSettings {
+        crs: Some(args.crs),
+        distance_unit: Some(parse_distance_unit(&args.distance_unit)?),
+        geometry_validity: Some(parse_validity_mode(&args.validity_mode)?),
+        ..Default::default()
     };
+    let config = new_workspace_config(&settings)?;
+
+    create_workspace(&args.path, &config, output, dry_run)?;
 
-    create_workspace(&args.path, &config, output, dry_run)
+    if !dry_run {
+        register_workspace(storage, &args.path, &settings, output).await;
+    }
+
+    Ok(())
+}
+
+/// Create the store workspace holding the CLI's settings
+///
+/// Goes through the same service as `POST /api/v1/workspaces`. When the
+/// store already has the workspace its settings are seeded from config.toml
+/// as add and build do, leaving settings already stored alone. Failing to
+/// store the settings does not fail the init that just succeeded.
+async fn register_workspace(
+    storage: &Storage,
+    workspace_root: &Path,
+    settings: &WorkspaceSettings,
+    output: &OutputWriter,
+) {
+    let service = WorkspaceService::new(storage.workspaces.clone());
+    match service.create(STORE_WORKSPACE, settings).await {
+        Ok(_) => output.verbose("Stored workspace settings from config.toml"),
+        Err(ServiceError::WorkspaceExists { .. }) => {
+            super::seed_settings(storage, workspace_root, output).await
+        }
+        Err(e) => output.warning(format!("Failed to store workspace settings: {:#}", e)),
+    }
 }
 
 fn create_workspace(
-    path: &std::path::Path,
+    path: &Path,
     config: &WorkspaceConfig,
     output: &OutputWriter,
     dry_run: bool,
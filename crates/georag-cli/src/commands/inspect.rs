@@ -0,0 +1,170 @@
+use crate::cli::InspectArgs;
+use crate::output::OutputWriter;
+use crate::output_types::InspectFileOutput;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::formats::{FormatInspection, FormatOptions, InspectMethod};
+use georag_core::geo::axis::Extent;
+use georag_core::models::{DatasetMeta, DatasetPreview};
+
+/// Summarize a dataset file without adding it, comparing it with the workspace
+pub async fn execute(args: InspectArgs, output: &OutputWriter, storage: &Storage) -> Result<()> {
+    if !args.path.exists() {
+        bail!("Dataset file not found: {}", args.path.display());
+    }
+
+    let reader = storage.formats.detect_format(&args.path)?;
+    let mut options = FormatOptions::new();
+    for (key, value) in &args.options {
+        options = options.with_option(key, value);
+    }
+    options.validate(reader)?;
+
+    let inspection = reader
+        .inspect(&args.path, &options, args.sample)
+        .await
+        .with_context(|| format!("Failed to inspect {}", args.path.display()))?;
+
+    let datasets = storage.spatial.list_datasets().await?;
+    let workspace_extent = inspection.crs.and_then(|crs| stored_extent(&datasets, crs));
+    let overlaps = workspace_extent.as_ref().and_then(|extent| inspection.overlaps(extent));
+    let estimate = inspection.estimated_read_time();
+
+    if output.is_json() {
+        return output.result(InspectFileOutput {
+            path: args.path.display().to_string(),
+            format: inspection.format_name.clone(),
+            method: inspection.method,
+            crs: inspection.crs,
+            feature_count: inspection.feature_count,
+            extent: inspection.extent,
+            property_keys: inspection.property_keys.iter().cloned().collect(),
+            page_count: inspection.page_count,
+            sampled_features: inspection.sampled_features,
+            sample_ms: inspection.sample_time.as_millis() as u64,
+            estimated_ingest_ms: estimate.map(|estimate| estimate.as_millis() as u64),
+            workspace_extent,
+            overlaps_workspace: overlaps,
+        });
+    }
+
+    output.section(format!("Inspect: {}", args.path.display()));
+    output.kv("Format", &inspection.format_name);
+    output.kv("Method", describe_method(&inspection));
+    match inspection.crs {
+        Some(crs) => output.kv("CRS", format!("EPSG:{}", crs)),
+        None => output.kv("CRS", "unknown; pass --option crs=<EPSG> if the format takes one"),
+    }
+    match inspection.feature_count {
+        Some(count) => output.kv("Features", count),
+        None => output.kv("Features", "unknown; only the sample was read"),
+    }
+    if let Some(pages) = inspection.page_count {
+        output.kv("Pages", pages);
+    }
+    if let Some([min_x, min_y, max_x, max_y]) = inspection.extent {
+        let scope = if inspection.feature_count.is_some() {
+            ""
+        } else {
+            " (sample only)"
+        };
+        output.kv("Extent", format!("{}, {}, {}, {}{}", min_x, min_y, max_x, max_y, scope));
+    }
+    if inspection.sampled_features > 0 {
+        let keys: Vec<&str> = inspection.property_keys.iter().map(String::as_str).collect();
+        output.kv(
+            "Properties",
+            if keys.is_empty() {
+                "none".to_string()
+            } else {
+                keys.join(", ")
+            },
+        );
+    }
+
+    output.kv(
+        "Overlaps Workspace",
+        match overlaps {
+            Some(true) => "yes".to_string(),
+            Some(false) => "no".to_string(),
+            None if inspection.extent.is_none() => "unknown; the file has no extent".to_string(),
+            None => match inspection.crs {
+                Some(crs) => format!("unknown; no stored dataset in EPSG:{} has an extent", crs),
+                None => "unknown; the file's CRS is unknown".to_string(),
+            },
+        },
+    );
+    match estimate {
+        Some(estimate) => output.kv(
+            "Estimated Ingest",
+            format!(
+                "{:.1}s (from {} features parsed in {} ms)",
+                estimate.as_secs_f64(),
+                inspection.sampled_features,
+                inspection.sample_time.as_millis()
+            ),
+        ),
+        None => output.kv("Estimated Ingest", "unknown"),
+    }
+    if overlaps == Some(false) {
+        output.warning("The file lies outside the data already in the workspace");
+    }
+
+    Ok(())
+}
+
+/// How the counts were learned, for the human output
+fn describe_method(inspection: &FormatInspection) -> &'static str {
+    match inspection.method {
+        InspectMethod::Header => "header (features were not read)",
+        InspectMethod::Scan => "scan (every feature counted, only the sample parsed)",
+        InspectMethod::Sample => "sample (features read until the sample was full)",
+    }
+}
+
+/// Union of the preview extents of the stored datasets in `crs`
+fn stored_extent(datasets: &[DatasetMeta], crs: u32) -> Option<Extent> {
+    datasets
+        .iter()
+        .filter(|dataset| dataset.crs == crs)
+        .filter_map(|dataset| match &dataset.preview {
+            Some(DatasetPreview::Vector(vector)) => vector.extent,
+            _ => None,
+        })
+        .reduce(|a, b| [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use georag_core::models::{DatasetId, GeometryType, VectorPreview};
+
+    fn dataset(id: u64, crs: u32, extent: Option<Extent>) -> DatasetMeta {
+        DatasetMeta {
+            id: DatasetId(id),
+            name: format!("dataset-{}", id),
+            geometry_type: GeometryType::Point,
+            feature_count: 1,
+            crs,
+            added_at: Utc::now(),
+            tags: Vec::new(),
+            license: None,
+            attribution: None,
+            preview: Some(DatasetPreview::Vector(VectorPreview { extent, ..Default::default() })),
+        }
+    }
+
+    #[test]
+    fn test_workspace_extent_joins_datasets_of_the_same_crs() {
+        let datasets = [
+            dataset(1, 4326, Some([106.0, -7.0, 107.0, -6.0])),
+            dataset(2, 4326, Some([106.5, -6.5, 108.0, -5.5])),
+            dataset(3, 32748, Some([700000.0, 9200000.0, 710000.0, 9300000.0])),
+            dataset(4, 4326, None),
+        ];
+
+        assert_eq!(stored_extent(&datasets, 4326), Some([106.0, -7.0, 108.0, -5.5]));
+        assert_eq!(stored_extent(&datasets, 3857), None);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::cli::JoinArgs;
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::JoinOutput;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::geo::{JoinPredicate, SpatialJoin};
+use georag_core::models::DatasetId;
+use georag_service::JoinService;
+use std::path::Path;
+
+/// Number of matched features shown in the dry-run preview
+const PREVIEW_SAMPLES: usize = 5;
+
+pub async fn execute(
+    args: JoinArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let mut predicate: JoinPredicate =
+        args.predicate.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+
+    if let Some(distance) = &args.max_distance {
+        let JoinPredicate::Nearest { max_distance } = &mut predicate else {
+            bail!("--max-distance only applies to the nearest predicate");
+        };
+        let workspace_root = super::workspace_root(workspace, output)?;
+        let config = super::query::load_workspace_config(&workspace_root.join(".georag"))?;
+        *max_distance = Some(super::query::parse_distance(distance, config.distance_unit)?);
+    }
+
+    let target = find_dataset(storage, &args.target).await?;
+    let source = find_dataset(storage, &args.source).await?;
+
+    let take: Vec<String> = args
+        .take
+        .iter()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect();
+    let prefix = args.prefix.clone().unwrap_or_else(|| format!("{}_", args.source));
+    let join = SpatialJoin::new(predicate, take).with_prefix(prefix);
+    let properties: Vec<String> = join.take.iter().map(|key| join.target_key(key)).collect();
+
+    let service = JoinService::new(storage.spatial.clone());
+
+    if dry_run {
+        let outcome = service.preview(target, source, &join).await?;
+        let total = outcome.counts.matched + outcome.counts.missed;
+
+        let mut action = PlannedAction::new(
+            ActionType::ModifyFile,
+            format!("Update features of dataset '{}'", args.target),
+        )
+        .with_detail(format!("Source: {}", args.source))
+        .with_detail(format!("Predicate: {}", join.predicate))
+        .with_detail(format!("Properties: {}", properties.join(", ")))
+        .with_detail(format!("Would match {} of {} features", outcome.counts.matched, total));
+
+        for feature in outcome.updated.iter().take(PREVIEW_SAMPLES) {
+            let values: Vec<String> = properties
+                .iter()
+                .filter_map(|key| feature.properties.get(key).map(|v| format!("{}={}", key, v)))
+                .collect();
+            action = action.with_detail(format!("Feature {}: {}", feature.id.0, values.join(", ")));
+        }
+
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    let report = service
+        .run(target, source, &join)
+        .await
+        .with_context(|| format!("Failed to join '{}' onto '{}'", args.source, args.target))?;
+
+    if output.is_json() {
+        output.result(JoinOutput {
+            target: args.target,
+            source: args.source,
+            predicate: join.predicate.to_string(),
+            properties,
+            matched: report.counts.matched,
+            missed: report.counts.missed,
+            pushed_down: report.pushed_down,
+        })?;
+    } else {
+        output.success(format!("Joined '{}' onto '{}'", args.source, args.target));
+        output.kv("Predicate", join.predicate);
+        output.kv("Properties", properties.join(", "));
+        output.kv("Matched", report.counts.matched);
+        output.kv("Missed", report.counts.missed);
+        if report.pushed_down {
+            output.verbose("Join evaluated by the database");
+        }
+        if report.counts.matched == 0 {
+            output.warning("No target features matched; check that both datasets share a CRS");
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolve a dataset ID by name
+async fn find_dataset(storage: &Storage, name: &str) -> Result<DatasetId> {
+    let datasets = storage.spatial.list_datasets().await?;
+    datasets
+        .into_iter()
+        .find(|d| d.name == name)
+        .map(|d| d.id)
+        .with_context(|| format!("Dataset not found: {}", name))
+}
@@ -5,7 +5,7 @@ use anyhow::{Context, Result};
 use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
 use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
 use georag_store::postgres::{PostgresConfig, PostgresStore};
-use std::path::PathBuf;
+use std::path::Path;
 use std::time::Instant;
 
 /// Progress information for migration
@@ -46,9 +46,14 @@ impl MigrationProgress {
 }
 
 /// Execute the migrate command
-pub fn execute(args: MigrateArgs, output: &OutputWriter, _dry_run: bool) -> Result<()> {
+pub fn execute(
+    args: MigrateArgs,
+    output: &OutputWriter,
+    _dry_run: bool,
+    workspace: Option<&Path>,
+) -> Result<()> {
     // Load workspace configuration
-    let workspace_root = PathBuf::from(".");
+    let workspace_root = super::workspace_root(workspace, output)?;
     let _config =
         load_workspace_config(&workspace_root).context("Failed to load workspace configuration")?;
 
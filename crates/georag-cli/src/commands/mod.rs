@@ -1,32 +1,110 @@
 mod add;
 mod build;
+mod config;
+mod convert;
+mod dataset;
 mod db;
+mod diff;
 mod doctor;
+mod export;
+mod formats;
+mod geo;
 mod init;
+mod inspect;
+mod join;
 mod migrate;
+mod preset;
 mod query;
+mod schema;
+mod self_test;
 mod status;
+mod tags;
 
-use crate::cli::{Cli, Commands};
+use crate::cli::{Cli, Commands, DbCommand};
+use crate::config::{find_workspace_root, seed_stored_settings};
 use crate::output::OutputWriter;
 use crate::storage::Storage;
 use anyhow::Result;
+use georag_core::formats::FormatRegistry;
+use std::path::{Path, PathBuf};
 
-/// Execute a CLI command
-pub async fn execute(cli: Cli) -> Result<()> {
-    let output = OutputWriter::new(cli.json);
+/// Execute a CLI command, reading datasets with the readers of `formats`
+pub async fn execute(cli: Cli, formats: FormatRegistry) -> Result<()> {
+    let output = OutputWriter::new(cli.json).with_verbose(cli.verbose);
+    let workspace = cli.workspace.as_deref();
 
-    // Create storage backend based on CLI flag
-    let storage = Storage::new(cli.storage.clone()).await?;
+    // Create storage backend based on CLI flags; a bundle replaces the backend
+    let storage = match &cli.from_bundle {
+        Some(path) => Storage::from_bundle(path).await?,
+        None => Storage::new(cli.storage.clone()).await?,
+    }
+    .with_format_registry(formats);
 
     match cli.command {
-        Commands::Init(args) => init::execute(args, &output, cli.dry_run),
-        Commands::Add(args) => add::execute(args, &output, cli.dry_run, &storage).await,
-        Commands::Build(args) => build::execute(args, &output, cli.dry_run, &storage).await,
-        Commands::Query(args) => query::execute(args, &output, cli.explain, &storage).await,
-        Commands::Status(args) => status::execute(args, &output),
-        Commands::Migrate(args) => migrate::execute(args, &output, cli.dry_run),
-        Commands::Db(args) => db::execute(args, &output, cli.dry_run),
-        Commands::Doctor(args) => doctor::execute(args, &output),
+        Commands::Init(args) => init::execute(args, &output, cli.dry_run, &storage).await,
+        Commands::Add(args) => add::execute(args, &output, cli.dry_run, &storage, workspace).await,
+        Commands::Tags(args) => tags::execute(args, &output, cli.dry_run, &storage).await,
+        Commands::Dataset(args) => {
+            dataset::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Schema(args) => schema::execute(args, &output, &storage, workspace).await,
+        Commands::Join(args) => {
+            join::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Diff(args) => diff::execute(args, &output, cli.dry_run, &storage).await,
+        Commands::Geo(args) => geo::execute(args, &output, cli.dry_run, &storage).await,
+        Commands::Formats(args) => formats::execute(args, &output, &storage),
+        Commands::Convert(args) => convert::execute(args, &output, cli.dry_run, &storage).await,
+        Commands::Inspect(args) => inspect::execute(args, &output, &storage).await,
+        Commands::Build(args) => {
+            build::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Export(args) => {
+            export::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Query(args) => {
+            query::execute(args, &output, cli.explain, &storage, workspace).await
+        }
+        Commands::Status(args) => status::execute(args, &output, &storage, workspace).await,
+        Commands::Config(args) => {
+            config::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Preset(args) => {
+            preset::execute(args, &output, cli.dry_run, &storage, workspace).await
+        }
+        Commands::Migrate(args) => migrate::execute(args, &output, cli.dry_run, workspace),
+        Commands::Db(args) => match args.command {
+            DbCommand::Compact(compact) => {
+                db::compact(compact, &output, cli.dry_run, &storage, workspace).await
+            }
+            DbCommand::Gc(gc) => db::gc(gc, &output, cli.dry_run, &storage, workspace).await,
+            DbCommand::Verify(verify) => {
+                db::verify(verify, &output, cli.dry_run, &storage, workspace).await
+            }
+            DbCommand::Upgrade(upgrade) => {
+                db::upgrade(upgrade, &output, cli.dry_run, &storage, workspace).await
+            }
+            command => db::execute(command, &output, cli.dry_run),
+        },
+        Commands::Doctor(args) => doctor::execute(args, &output, workspace),
+        Commands::SelfTest(args) => self_test::execute(args, &output, workspace).await,
+    }
+}
+
+/// Resolve the workspace root for a command and report it in verbose mode
+fn workspace_root(workspace: Option<&Path>, output: &OutputWriter) -> Result<PathBuf> {
+    let root = find_workspace_root(workspace)?;
+    output.verbose(format!("Using workspace: {}", root.display()));
+    Ok(root)
+}
+
+/// Write the workspace's config.toml settings through to the store
+///
+/// Failing to seed the store does not fail the command that just succeeded.
+async fn seed_settings(storage: &Storage, workspace_root: &Path, output: &OutputWriter) {
+    match seed_stored_settings(storage.workspaces.as_ref(), workspace_root).await {
+        Ok(true) => output.verbose("Stored workspace settings from config.toml"),
+        Ok(false) => {}
+        Err(e) => output.warning(format!("Failed to store workspace settings: {:#}", e)),
     }
 }
@@ -0,0 +1,262 @@
+use crate::cli::{PresetArgs, PresetCommand, PresetSetArgs};
+use crate::config::{
+    load_stored_settings, load_workspace_config_with_store, read_file_settings,
+    save_stored_settings, write_file_settings,
+};
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::{PresetChangeOutput, PresetOutput};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::config::WorkspaceSettings;
+use georag_core::models::{normalize_preset_name, QueryPreset};
+use georag_service::{validate_preset, validate_presets};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tabled::Tabled;
+
+/// Presets of the workspace and the name of its default preset
+type Presets = (BTreeMap<String, QueryPreset>, Option<String>);
+
+/// Execute preset commands
+pub async fn execute(
+    args: PresetArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let workspace_root = super::workspace_root(workspace, output)?;
+    let (mut presets, mut default) = load_presets(storage, &workspace_root).await?;
+
+    let (action, preset) = match args.command {
+        PresetCommand::List => return list(output, &presets, default.as_deref()),
+        PresetCommand::Show { name } => {
+            let name = find_preset(&presets, &name)?;
+            return show(output, &name, &presets[&name], default.as_deref());
+        }
+        PresetCommand::Set(args) => {
+            let name = preset_name(&args.name)?;
+            let preset = preset_options(&args);
+            validate_preset(&preset).with_context(|| format!("Invalid preset {}", name))?;
+            presets.insert(name.clone(), preset);
+            if args.default {
+                default = Some(name.clone());
+            }
+            ("set", Some(name))
+        }
+        PresetCommand::Delete { name } => {
+            let name = find_preset(&presets, &name)?;
+            presets.remove(&name);
+            if default.as_deref() == Some(name.as_str()) {
+                default = None;
+            }
+            ("delete", Some(name))
+        }
+        PresetCommand::Default { name: None, clear: false } => {
+            match &default {
+                Some(name) => {
+                    let name = find_preset(&presets, name)?;
+                    show(output, &name, &presets[&name], Some(name.as_str()))?
+                }
+                None if output.is_json() => output.result(PresetChangeOutput {
+                    action: "default",
+                    preset: None,
+                    default_preset: None,
+                })?,
+                None => output.info("No default preset; queries take one only with --preset"),
+            }
+            return Ok(());
+        }
+        PresetCommand::Default { name, .. } => {
+            let name = name.map(|name| find_preset(&presets, &name)).transpose()?;
+            default = name.clone();
+            ("default", name)
+        }
+    };
+
+    save_presets(output, dry_run, storage, &workspace_root, (presets, default.clone())).await?;
+    if dry_run {
+        return Ok(());
+    }
+
+    if output.is_json() {
+        output.result(PresetChangeOutput { action, preset, default_preset: default })?;
+        return Ok(());
+    }
+    match (action, preset) {
+        ("set", Some(name)) => output.success(format!("Saved preset: {}", name)),
+        ("delete", Some(name)) => output.success(format!("Deleted preset: {}", name)),
+        (_, Some(name)) => output.success(format!("Default preset: {}", name)),
+        (_, None) => output.success("Cleared the default preset"),
+    }
+    output.kv("Default", default.as_deref().unwrap_or("none"));
+    Ok(())
+}
+
+/// Presets queries see: the stored settings win over config.toml
+async fn load_presets(storage: &Storage, workspace_root: &Path) -> Result<Presets> {
+    let config = load_workspace_config_with_store(workspace_root, storage.workspaces.as_ref())
+        .await
+        .context("Failed to load workspace settings")?;
+    Ok((config.query_presets.value, config.default_query_preset.value))
+}
+
+/// Write the presets to config.toml and, when settings are stored, to the store
+///
+/// Both are checked as the API checks settings it stores before either is
+/// written, so the two never disagree about the presets.
+async fn save_presets(
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+    workspace_root: &Path,
+    presets: Presets,
+) -> Result<()> {
+    let mut file = read_file_settings(workspace_root)?;
+    with_presets(&mut file, &presets).context("Invalid presets for config.toml")?;
+    let mut stored = load_stored_settings(storage.workspaces.as_ref())
+        .await
+        .context("Failed to read stored settings")?;
+    if let Some(stored) = &mut stored {
+        with_presets(stored, &presets).context("Invalid presets for the store")?;
+    }
+
+    if dry_run {
+        let (presets, default) = &presets;
+        let mut actions = vec![presets
+            .iter()
+            .fold(
+                PlannedAction::new(ActionType::ModifyFile, "Write query presets to config.toml"),
+                |action, (name, preset)| action.with_detail(format!("{}: {}", name, preset)),
+            )
+            .with_detail(format!("Default: {}", default.as_deref().unwrap_or("none")))];
+        if stored.is_some() {
+            actions.push(PlannedAction::new(ActionType::WriteFile, "Store the query presets"));
+        }
+        display_planned_actions(output, &actions);
+        return Ok(());
+    }
+
+    write_file_settings(workspace_root, &file)?;
+    if let Some(stored) = &stored {
+        save_stored_settings(storage.workspaces.as_ref(), stored)
+            .await
+            .context("Failed to store settings")?;
+    }
+    Ok(())
+}
+
+/// Replace the presets of `settings` and check the result
+fn with_presets(settings: &mut WorkspaceSettings, (presets, default): &Presets) -> Result<()> {
+    settings.query_presets = Some(presets.clone()).filter(|presets| !presets.is_empty());
+    settings.default_query_preset = default.clone();
+    settings.validate()?;
+    validate_presets(settings)?;
+    Ok(())
+}
+
+fn list(
+    output: &OutputWriter,
+    presets: &BTreeMap<String, QueryPreset>,
+    default: Option<&str>,
+) -> Result<()> {
+    if output.is_json() {
+        let presets: Vec<PresetOutput> = presets
+            .iter()
+            .map(|(name, preset)| PresetOutput {
+                name: name.clone(),
+                default: default == Some(name.as_str()),
+                options: preset.clone(),
+            })
+            .collect();
+        return output.result(presets);
+    }
+
+    output.section("Query Presets");
+    if presets.is_empty() {
+        output.info("No presets; save one with 'georag preset set <NAME>'");
+        return Ok(());
+    }
+    output.table(
+        presets
+            .iter()
+            .map(|(name, preset)| PresetRow {
+                name: name.clone(),
+                default: if default == Some(name.as_str()) {
+                    "yes"
+                } else {
+                    ""
+                }
+                .to_string(),
+                options: preset.to_string(),
+            })
+            .collect(),
+    );
+    Ok(())
+}
+
+fn show(
+    output: &OutputWriter,
+    name: &str,
+    preset: &QueryPreset,
+    default: Option<&str>,
+) -> Result<()> {
+    if output.is_json() {
+        return output.result(PresetOutput {
+            name: name.to_string(),
+            default: default == Some(name),
+            options: preset.clone(),
+        });
+    }
+
+    output.section(format!("Preset: {}", name));
+    output.kv("Default", if default == Some(name) { "yes" } else { "no" });
+    if preset.is_empty() {
+        output.info("The preset sets no options");
+    }
+    for (key, value) in preset.entries() {
+        output.kv(key, value);
+    }
+    Ok(())
+}
+
+/// Name of a preset, normalized
+fn preset_name(name: &str) -> Result<String> {
+    normalize_preset_name(name).map_err(|reason| anyhow::anyhow!("Invalid preset name: {}", reason))
+}
+
+/// Normalized name of a preset the workspace has
+fn find_preset(presets: &BTreeMap<String, QueryPreset>, name: &str) -> Result<String> {
+    let name = preset_name(name)?;
+    if !presets.contains_key(&name) {
+        bail!("Preset not found: {}\n\nRun 'georag preset list' to see the presets", name);
+    }
+    Ok(name)
+}
+
+/// Options given to `preset set`; flags left off are not set by the preset
+fn preset_options(args: &PresetSetArgs) -> QueryPreset {
+    QueryPreset {
+        top_k: args.top_k,
+        min_score: args.min_score,
+        rerank: args.rerank.clone(),
+        rerank_pool: args.rerank_pool,
+        explain: args.explain.then_some(true),
+        merge_overlapping: args.merge_overlapping.then_some(true),
+        parse_operators: args.parse_operators.then_some(true),
+        geometry_detail: args.geometry_detail.clone(),
+        vertex_budget: args.vertex_budget,
+        coordinate_precision: args.coordinate_precision,
+    }
+}
+
+#[derive(Tabled)]
+struct PresetRow {
+    #[tabled(rename = "Preset")]
+    name: String,
+    #[tabled(rename = "Default")]
+    default: String,
+    #[tabled(rename = "Options")]
+    options: String,
+}
@@ -1,44 +1,157 @@
 use crate::cli::QueryArgs;
+use crate::config::{find_store_workspace, load_workspace_config_with_overrides};
+use crate::geometry_arg::{bbox_geometry, parse_bbox, parse_geometry_argument, parse_point};
 use crate::output::OutputWriter;
-use crate::output_types::{QueryOutput, QueryResultItem};
+use crate::output_types::{QueryOutput, QueryResultItem, TimeBucketInfo};
 use crate::storage::Storage;
 use anyhow::{bail, Context, Result};
+use dialoguer::Input;
+use georag_core::config::{parse_distance_unit, CliConfigOverrides};
 use georag_core::geo::models::{Distance, DistanceUnit};
-use georag_core::llm::OllamaEmbedder;
+use georag_core::geo::{AppliedPointDefaults, PointQueryDefaults};
+use georag_core::llm::factory::DEFAULT_OLLAMA_URL;
+use georag_core::llm::{create_embedder, EmbedderOptions, OllamaGenerator};
 use georag_core::models::workspace::IndexState;
-use georag_core::models::WorkspaceConfig;
-use georag_retrieval::models::QueryPlan;
-use georag_retrieval::pipeline::RetrievalPipeline;
+use georag_core::models::{
+    AuditEvent, AuditEventKind, DatasetId, FeatureId, SavedArea, WorkspaceConfig,
+};
+use georag_core::redaction::{record_rules_change, RedactionConfig, Redactor};
+use georag_retrieval::export::ResultFormat;
+use georag_retrieval::grouping::TimeBucket;
+use georag_retrieval::models::{
+    AppliedPreset, AttributePhaseExplanation, ConfidenceExplanation, ExampleExplanation,
+    ExclusionExplanation, NamedAreaExpansion, QueryPlan, QueryResult,
+};
+use georag_retrieval::timing::QueryTimings;
+use georag_retrieval::{Basemap, LlmReranker, MapOptions, PropertySelection, RerankMode};
+use georag_service::{
+    apply_operators, index_generation, parse_operators, parse_rerank, plan_hash, select_preset,
+    ExampleSeed, PagedQuery, QueryPages, QueryService, ServiceError,
+};
+use serde_json::{Map, Value};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::sync::Arc;
+use tabled::Tabled;
+
+/// Width of the longest bar in the time histogram
+const HISTOGRAM_WIDTH: usize = 20;
 
 pub async fn execute(
-    args: QueryArgs,
+    mut args: QueryArgs,
     output: &OutputWriter,
     explain: bool,
     storage: &Storage,
+    workspace: Option<&Path>,
 ) -> Result<()> {
+    if args.format == Some(ResultFormat::Png) {
+        bail!("PNG maps are written to a file: use --map <PATH> instead of --format png");
+    }
+
+    // With --format only the serialized results go to stdout
+    let quiet;
+    let output = if args.format.is_some() {
+        quiet = OutputWriter::quiet();
+        &quiet
+    } else {
+        output
+    };
+
+    let pages = QueryPages::default();
+    if let Some(page_size) = args.page_size {
+        pages.check_page_size(page_size)?;
+    }
+
     // Find workspace root
-    let workspace_root = find_workspace_root()?;
+    let workspace_root = super::workspace_root(workspace, output)?;
     let georag_dir = workspace_root.join(".georag");
 
     // Load workspace config
     let config = load_workspace_config(&georag_dir)?;
 
-    // Check if index exists
-    let index_state = load_index_state(&georag_dir)?;
-
-    // Parse spatial filter if provided
-    let spatial_filter = if let Some(ref spatial_str) = args.spatial {
-        Some(parse_spatial_filter(
-            spatial_str,
-            args.geometry.as_deref(),
-            args.distance.as_deref(),
-            &config,
-        )?)
-    } else {
-        None
+    // Resolve the minimum score (CLI flag > GEORAG_MIN_SCORE > stored settings > config file)
+    if let Some(score) = args.min_score {
+        if !(0.0..=1.0).contains(&score) {
+            bail!("Invalid --min-score {}: expected a number between 0.0 and 1.0", score);
+        }
+    }
+    let layered_config = load_workspace_config_with_overrides(
+        &workspace_root,
+        storage.workspaces.as_ref(),
+        CliConfigOverrides {
+            min_score: args.min_score,
+            simplify_filters: args.simplify_filter.then_some(true),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // The workspace preset fills in the options not given on the command line
+    let mut min_score = args.min_score;
+    let mut explain = explain.then_some(true);
+    let mut merge_overlapping = args.merge_overlapping.then_some(true);
+    let mut parse_operators = args.parse_operators.then_some(true);
+    let preset = match select_preset(
+        Some(&layered_config.query_presets.value),
+        layered_config.default_query_preset.value.as_deref(),
+        args.preset.as_deref(),
+    )? {
+        Some(mut merge) => {
+            let preset = merge.preset.clone();
+            let rerank = preset.rerank.as_deref().map(parse_rerank).transpose()?;
+            merge.fill("top_k", &mut args.top_k, preset.top_k);
+            merge.fill("min_score", &mut min_score, preset.min_score);
+            merge.fill("rerank", &mut args.rerank, rerank);
+            merge.fill("rerank_pool", &mut args.rerank_pool, preset.rerank_pool);
+            merge.fill("explain", &mut explain, preset.explain);
+            merge.fill("merge_overlapping", &mut merge_overlapping, preset.merge_overlapping);
+            merge.fill("parse_operators", &mut parse_operators, preset.parse_operators);
+            Some(merge.finish())
+        }
+        None => None,
     };
+    let min_score = min_score.or(layered_config.min_score.value);
+    let explain = explain.unwrap_or(false);
+    let geometry_limits = layered_config.geometry_limits();
+    let basemap = layered_config.map_tile_url.value.clone().map(|url| {
+        match layered_config.map_tile_attribution.value.clone() {
+            Some(attribution) => Basemap::new(url).with_attribution(attribution),
+            None => Basemap::new(url),
+        }
+    });
+
+    // Check if index exists; a bundle carries its own index state
+    let index_state = match &storage.bundle_index {
+        Some(state) => state.clone(),
+        None => load_index_state(&georag_dir)?,
+    };
+
+    // Saved areas are kept in the store workspace shared with the API
+    let area = match &args.area {
+        Some(name) => {
+            let workspace_id = find_store_workspace(storage.workspaces.as_ref())
+                .await?
+                .with_context(|| format!("Area not found: {} (no areas are saved)", name))?;
+            Some(storage.area_service().resolve(workspace_id, name).await?)
+        }
+        None => None,
+    };
+    let mut excluded_areas = Vec::with_capacity(args.exclude_area.len());
+    for name in &args.exclude_area {
+        let workspace_id = find_store_workspace(storage.workspaces.as_ref())
+            .await?
+            .with_context(|| format!("Area not found: {} (no areas are saved)", name))?;
+        excluded_areas.push(storage.area_service().resolve(workspace_id, name).await?);
+    }
+
+    // Parse spatial filter if provided; --at falls back to the point query defaults
+    let (spatial_filter, point_defaults) =
+        match point_filter(&args, &config, &layered_config.point_query_defaults())? {
+            Some((filter, applied)) => (Some(filter), Some(applied).filter(|a| !a.is_empty())),
+            None => (parse_spatial_filter(&args, &config, area.as_ref())?, None),
+        };
+    let spatial_filter = exclude_areas(spatial_filter, &args, &excluded_areas)?;
 
     // Build text filter from CLI args
     let text_filter = if args.must_contain.is_some() || args.exclude.is_some() {
@@ -58,34 +171,210 @@ pub async fn execute(
         None
     };
 
+    // With --like-feature the results resemble a stored feature: its chunks'
+    // stored embeddings are searched with, and the query text only labels it
+    let example = match &args.like_feature {
+        Some(reference) => {
+            let (dataset, feature_id) = parse_like_feature(reference)?;
+            let dataset_id = find_example_dataset(storage, dataset).await?;
+            let seed = ExampleSeed::Feature { dataset_id, feature_id };
+            let service = QueryService::new(
+                storage.spatial.clone(),
+                storage.vector.clone(),
+                storage.document.clone(),
+            );
+            let example = service.example(seed, args.seed_mode).await.map_err(|e| match e {
+                ServiceError::ExampleNotIndexed { .. } => {
+                    anyhow::anyhow!("{}\n\nRemediation: run 'georag build' to index the feature", e)
+                }
+                e => e.into(),
+            })?;
+            Some(example)
+        }
+        None => None,
+    };
+    let query_text = match (&args.query, &example) {
+        (Some(query), _) => query.clone(),
+        (None, Some(example)) => format!("like {}", example.source),
+        (None, None) => bail!("A query text or --like-feature is required"),
+    };
+
     // Create query plan
-    let query_plan = QueryPlan::new(&args.query)
+    let query_plan = QueryPlan::new(&query_text)
         .with_semantic_rerank(!args.no_rerank)
-        .with_top_k(args.top_k)
+        .with_rerank(args.rerank.unwrap_or_default())
+        .with_merge_overlapping(merge_overlapping.unwrap_or(false))
         .with_explain(explain);
 
+    let query_plan = if let Some(top_k) = args.top_k {
+        query_plan.with_top_k(top_k)
+    } else {
+        query_plan
+    };
+
+    let query_plan = if let Some(pool) = args.rerank_pool {
+        query_plan.with_rerank_pool(pool)
+    } else {
+        query_plan
+    };
+
+    let query_plan = if let Some(preset) = preset {
+        query_plan.with_preset(preset)
+    } else {
+        query_plan
+    };
+
     let query_plan = if let Some(filter) = spatial_filter.clone() {
         query_plan.with_spatial_filter(filter)
     } else {
         query_plan
     };
 
+    let query_plan = if let Some(example) = example {
+        query_plan.with_example(example)
+    } else {
+        query_plan
+    };
+
+    let query_plan = if let Some(area) = &area {
+        query_plan.with_named_area(NamedAreaExpansion {
+            name: area.name.clone(),
+            vertices: area.vertex_count(),
+        })
+    } else {
+        query_plan
+    };
+
+    let query_plan = excluded_areas.iter().fold(query_plan, |plan, area| {
+        plan.with_excluded_area(NamedAreaExpansion {
+            name: area.name.clone(),
+            vertices: area.vertex_count(),
+        })
+    });
+
+    let query_plan = if let Some(applied) = point_defaults {
+        query_plan.with_point_defaults(applied)
+    } else {
+        query_plan
+    };
+
     let query_plan = if let Some(filter) = text_filter.clone() {
         query_plan.with_text_filter(filter)
     } else {
         query_plan
     };
 
+    let query_plan = args
+        .attributes
+        .iter()
+        .cloned()
+        .fold(query_plan, |plan, filter| plan.with_attribute_filter(filter));
+
+    let query_plan = if let Some(score) = min_score {
+        query_plan.with_min_score(score)
+    } else {
+        query_plan
+    };
+
+    let query_plan = if let Some(grouping) = args.group_by_time.clone() {
+        query_plan.with_group_by_time(grouping)
+    } else {
+        query_plan
+    };
+
+    // Operators in the query become filters; an unknown near: area is reported as ignored
+    let query_plan = if parse_operators.unwrap_or(false) && query_plan.example.is_none() {
+        let parsed = parse_operators(&query_text);
+        let place = match parsed.near().filter(|_| area.is_none()) {
+            Some(name) => match find_store_workspace(storage.workspaces.as_ref()).await? {
+                Some(workspace_id) => {
+                    match storage.area_service().resolve(workspace_id, name).await {
+                        Ok(place) => Some(place),
+                        Err(ServiceError::AreaNotFound { .. } | ServiceError::InvalidArea(_)) => {
+                            None
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                None => None,
+            },
+            None => None,
+        };
+        apply_operators(query_plan, parsed, place.as_ref(), args.time_property.as_deref())
+    } else {
+        query_plan
+    };
+    let spatial_filter = query_plan.spatial_filter.clone();
+
     // Display query plan
     output.section("Query Plan");
-    output.kv("Query", &args.query);
+    output.kv("Query", &query_plan.text_query);
+    if let Some(preset) = &query_plan.preset {
+        output.kv("Preset", describe_preset(preset));
+    }
+    if let Some(example) = &query_plan.example {
+        output.kv(
+            "Like Feature",
+            format!(
+                "{} ({} seed chunks, {})",
+                example.source,
+                example.seed_chunks.len(),
+                example.mode
+            ),
+        );
+    }
+    if let Some(operators) = &query_plan.operators {
+        for applied in &operators.applied {
+            output.kv("Operator", format!("{} -> {}", applied.token, applied.filter));
+        }
+        for ignored in &operators.ignored {
+            output.kv("Ignored", format!("{} ({})", ignored.token, ignored.reason));
+        }
+    }
 
     if let Some(ref filter) = spatial_filter {
         output.kv("Spatial Predicate", format!("{:?}", filter.predicate));
+        if let Some(ref geometry) = filter.geometry {
+            let source = if let Some(area) = &area {
+                format!("--area {}", area.name)
+            } else if let Some(named_area) = &query_plan.named_area {
+                format!("near:{}", named_area.name)
+            } else if args.at.is_some() {
+                "--at".to_string()
+            } else if args.bbox.is_some() {
+                "--bbox".to_string()
+            } else {
+                "--geometry".to_string()
+            };
+            output.kv(
+                "Filter Geometry",
+                format!(
+                    "{:?}, {} vertices (from {})",
+                    geometry.geometry_type(),
+                    geometry.vertex_count(),
+                    source
+                ),
+            );
+        }
         output.kv("CRS", format!("EPSG:{}", filter.crs.epsg));
         if let Some(ref dist) = filter.distance {
             output.kv("Distance", format!("{} {:?}", dist.value, dist.unit));
         }
+        if let Some(ref buffer) = filter.buffer {
+            output.kv("Buffer", format!("{} {:?}", buffer.value, buffer.unit));
+        }
+        if !filter.exclude.is_empty() {
+            let mut sources: Vec<String> =
+                args.exclude_geometry.iter().map(|_| "--exclude-geometry".to_string()).collect();
+            sources.extend(args.exclude_area.iter().map(|name| format!("--exclude-area {}", name)));
+            output.kv("Excluded", sources.join(", "));
+        }
+        if let Some(ref applied) = point_defaults {
+            output.kv("Defaults Applied", describe_point_defaults(applied));
+        }
+        if explain {
+            output.kv("Filter", serde_json::to_string(filter)?);
+        }
     } else {
         output.kv("Spatial Filter", "None");
     }
@@ -99,6 +388,17 @@ pub async fn execute(
         }
     }
 
+    if !args.attributes.is_empty() {
+        output.kv(
+            "Where",
+            args.attributes
+                .iter()
+                .map(|f| format!("{}={}", f.property, f.value))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+
     output.kv(
         "Semantic Reranking",
         if !args.no_rerank {
@@ -107,26 +407,80 @@ pub async fn execute(
             "Disabled"
         },
     );
-    output.kv("Top K", args.top_k);
+    match query_plan.rerank {
+        RerankMode::None => {}
+        RerankMode::Lexical => {
+            output.kv("Rerank", format!("lexical, {} candidates", query_plan.rerank_pool))
+        }
+        RerankMode::Llm => output.kv(
+            "Rerank",
+            format!("llm ({}), {} candidates", args.rerank_model, query_plan.rerank_pool),
+        ),
+    }
+    output.kv("Top K", query_plan.top_k);
+    if let Some(score) = min_score {
+        output.kv("Min Score", format!("{:.2}", score));
+    }
+    if query_plan.merge_overlapping {
+        output.kv("Merge Overlapping", "Enabled");
+    }
+    if let Some(ref grouping) = args.group_by_time {
+        output.kv("Group By Time", format!("{} per {}", grouping.property, grouping.interval));
+    }
 
     // Execute query using RetrievalPipeline
     output.section("Executing Query");
 
-    // Initialize embedder from index state
+    // Initialize embedder from index state, checking its dimension against the model and index
     output.info(format!("Using embedder: {}", index_state.embedder));
-    let embedder = OllamaEmbedder::localhost(&index_state.embedder, index_state.embedding_dim);
+    let mut options = EmbedderOptions::default();
+    if let Some(dimensions) = layered_config.embedder_dimensions.value {
+        options = options.with_dimensions(dimensions);
+    }
+    let embedder = create_embedder(&index_state.embedder, &options)?;
+    super::build::check_dimensions(
+        &embedder,
+        Some(&index_state),
+        layered_config.dimension_check.value,
+        output,
+    )
+    .await?;
 
-    // Use the persisted storage passed from CLI
-    // Clone the Arc references to pass to the pipeline
-    let spatial_store = storage.spatial.clone();
-    let vector_store = storage.vector.clone();
-    let document_store = storage.document.clone();
+    // Load redaction rules and record any change in the audit log
+    let redaction = RedactionConfig::load_from_file(georag_dir.join("config.toml"))
+        .context("Failed to load redaction rules")?;
+    if record_rules_change(georag_dir.join("audit.log"), &redaction)? {
+        output.info("Redaction rules changed; recorded in .georag/audit.log");
+    }
+    let redactor = Redactor::new(&redaction)?;
 
-    // Create retrieval pipeline with trait objects
-    let pipeline = RetrievalPipeline::new(spatial_store, vector_store, document_store, embedder);
+    let service = QueryService::new(
+        storage.spatial.clone(),
+        storage.vector.clone(),
+        storage.document.clone(),
+    )
+    .with_redactor(redactor)
+    .with_geometry_limits(geometry_limits)
+    .with_property_selection(PropertySelection::include(&args.fields))
+    .with_index_state(index_state.clone());
+    let service = if query_plan.rerank == RerankMode::Llm {
+        let generator = OllamaGenerator::new(DEFAULT_OLLAMA_URL, &args.rerank_model);
+        service.with_llm_reranker(Arc::new(LlmReranker::new(generator)))
+    } else {
+        service
+    };
+    let service = match basemap {
+        Some(basemap) => service.with_basemap(basemap),
+        None => service,
+    };
+    let service = match layered_config.source_url_template() {
+        Some(template) => service.with_source_url_template(template),
+        None => service,
+    };
+    let service = service.with_confidence_cutoffs(layered_config.confidence_cutoffs.value);
 
     // Execute the query
-    let result = pipeline.execute(&query_plan).await.map_err(|e| {
+    let result = service.execute(&query_plan, embedder).await.map_err(|e| {
         // Enhance error message for Ollama connection issues
         if e.to_string().contains("Failed to connect to Ollama")
             || e.to_string().contains("Embedder unavailable")
@@ -145,9 +499,39 @@ pub async fn execute(
             anyhow::anyhow!("Failed to execute query: {}", e)
         }
     })?;
+    if let Some(workspace_id) = find_store_workspace(storage.workspaces.as_ref()).await? {
+        storage
+            .audit_service()
+            .record(workspace_id, AuditEvent::new(AuditEventKind::Query))
+            .await;
+    }
+
+    // With --page-size the ranked results are shown a page at a time
+    let generation = index_generation(Some(&index_state));
+    let (result, mut next_page) = match args.page_size {
+        Some(page_size) => {
+            let hash = plan_hash(&query_plan, &workspace_root.to_string_lossy());
+            let query = PagedQuery::new(query_plan.clone(), result, index_state.embedder.clone());
+            let page = pages.first_page(hash, &generation, query, page_size);
+            (page.result, page.next)
+        }
+        None => (result, None),
+    };
+
+    if let Some(path) = &args.map {
+        let png = service
+            .to_map_png(&result, MapOptions::default())
+            .await
+            .context("Failed to render map")?;
+        fs::write(path, png)
+            .with_context(|| format!("Failed to write map to {}", path.display()))?;
+        output.info(format!("Map written to {}", path.display()));
+    }
 
     // Display results
-    if output.is_json() {
+    if let Some(format) = args.format {
+        print!("{}", export_results(&result, format, &service).await?);
+    } else if output.is_json() {
         let result_items: Vec<QueryResultItem> = result
             .sources
             .iter()
@@ -155,11 +539,14 @@ pub async fn execute(
                 content: s.excerpt.clone(),
                 source: s.document_path.clone(),
                 score: Some(s.score),
+                spatial_match: s.spatial_match,
+                source_url: s.source_url.clone(),
+                confidence: s.confidence,
             })
             .collect();
 
         let explanation_text = result.explanation.as_ref().map(|explanation| {
-            format!(
+            let mut text = format!(
                 "Spatial Phase: {} features evaluated, {} matched. Semantic Phase: {}",
                 explanation.spatial_phase.features_evaluated,
                 explanation.spatial_phase.features_matched,
@@ -171,14 +558,89 @@ pub async fn execute(
                         s.candidates_reranked, s.embedder_model
                     ))
                     .unwrap_or_else(|| "Disabled".to_string())
-            )
+            );
+            if let Some(example) =
+                explanation.semantic_phase.as_ref().and_then(|s| s.example.as_ref())
+            {
+                text.push_str(&format!(". Example: {}", describe_example(example)));
+            }
+            if let Some(named_area) = &explanation.spatial_phase.named_area {
+                text.push_str(&format!(
+                    ". Filter geometry expanded from area {} ({} vertices)",
+                    named_area.name, named_area.vertices
+                ));
+            }
+            if let Some(applied) = &explanation.spatial_phase.point_defaults {
+                text.push_str(&format!(
+                    ". Point query defaults applied: {}",
+                    describe_point_defaults(applied)
+                ));
+            }
+            if let Some(exclusions) = &explanation.spatial_phase.exclusions {
+                text.push_str(&format!(". Exclusions: {}", describe_exclusions(exclusions)));
+            }
+            if let Some(simplification) = &explanation.spatial_phase.filter_simplification {
+                text.push_str(&format!(
+                    ". Filter geometry simplified from {} to {} vertices",
+                    simplification.original_vertices, simplification.simplified_vertices
+                ));
+            }
+            if let Some(attributes) = &explanation.attribute_phase {
+                text.push_str(&format!(
+                    ". Attribute Phase: {} of {} candidates kept ({})",
+                    attributes.candidates_after,
+                    attributes.candidates_before,
+                    attribute_levels(attributes)
+                ));
+            }
+            if let Some(rerank) = &explanation.rerank_phase {
+                text.push_str(&format!(
+                    ". Rerank Phase: {} candidates reranked using {}",
+                    rerank.candidates_reranked, rerank.reranker
+                ));
+            }
+            if let Some(dist) = &explanation.score_distribution {
+                text.push_str(&format!(
+                    ". Scores: min {:.3}, median {:.3}, max {:.3}",
+                    dist.min, dist.median, dist.max
+                ));
+            }
+            if let Some(confidence) = &explanation.confidence {
+                text.push_str(&format!(". Confidence: {}", describe_confidence(confidence)));
+            }
+            if let Some(preset) = &explanation.preset {
+                text.push_str(&format!(". Preset: {}", describe_preset(&preset.preset)));
+            }
+            text
         });
 
         output.result(QueryOutput {
-            query: args.query.clone(),
+            query: query_text.clone(),
             spatial_matches: result.spatial_matches,
             results: result_items,
+            filtered_by_threshold: result.filtered_by_threshold,
+            time_groups: result.time_groups.as_ref().map(|buckets| {
+                buckets
+                    .iter()
+                    .map(|b| TimeBucketInfo {
+                        bucket: b.key.clone(),
+                        count: b.count,
+                        top_sources: b
+                            .top_sources
+                            .iter()
+                            .map(|s| s.document_path.clone())
+                            .collect(),
+                        max_score: b.scores.as_ref().map(|d| d.max),
+                        median_score: b.scores.as_ref().map(|d| d.median),
+                    })
+                    .collect()
+            }),
             explanation: explanation_text,
+            timings: result.explanation.as_ref().map(|e| e.timings.clone()),
+            spatial_filter: spatial_filter.filter(|_| explain),
+            diagnostics: result.diagnostics.clone(),
+            attributions: result.attributions.clone(),
+            operators: query_plan.operators.clone(),
         })?;
     } else {
         output.info(format!("Found {} spatial matches", result.spatial_matches));
@@ -190,18 +652,32 @@ pub async fn execute(
         output.section("Results");
         output.info(&result.answer);
 
-        output.section("Sources");
-        for (i, source) in result.sources.iter().enumerate() {
+        if !result.diagnostics.is_empty() {
+            output.section("Why No Results");
+            for diagnostic in &result.diagnostics {
+                output.warning(&diagnostic.message);
+                output.info(format!("  Next step: {}", diagnostic.suggestion));
+            }
+        } else if result.filtered_by_threshold > 0 {
             output.info(format!(
-                "\n{}. {} (score: {:.2})",
-                i + 1,
-                source.document_path,
-                source.score
+                "{} results below the minimum score were dropped",
+                result.filtered_by_threshold
             ));
-            if let Some(feature_id) = source.feature_id {
-                output.kv("  Feature", feature_id.0);
+        }
+
+        output.section("Sources");
+        print_sources(&result, 0, &args.fields, &service, output).await;
+
+        if !result.attributions.is_empty() {
+            output.section("Attribution");
+            for line in &result.attributions {
+                output.info(line);
             }
-            output.info(format!("  {}", source.excerpt));
+        }
+
+        if let Some(ref buckets) = result.time_groups {
+            output.section("Results Over Time");
+            output.table(histogram_rows(buckets));
         }
 
         if let Some(explanation) = result.explanation {
@@ -214,6 +690,61 @@ pub async fn execute(
                     explanation.spatial_phase.features_matched
                 ),
             );
+            if explanation.spatial_phase.chunks_matched_by_geometry > 0 {
+                output.kv(
+                    "Chunk Geometry",
+                    format!(
+                        "{} chunks matched by the places they name",
+                        explanation.spatial_phase.chunks_matched_by_geometry
+                    ),
+                );
+            }
+
+            if let Some(named_area) = &explanation.spatial_phase.named_area {
+                output.kv(
+                    "Named Area",
+                    format!("{} ({} vertices)", named_area.name, named_area.vertices),
+                );
+            }
+
+            if let Some(applied) = &explanation.spatial_phase.point_defaults {
+                output.kv("Point Defaults", describe_point_defaults(applied));
+            }
+
+            if let Some(exclusions) = &explanation.spatial_phase.exclusions {
+                output.kv("Exclusions", describe_exclusions(exclusions));
+            }
+
+            if let Some(simplification) = &explanation.spatial_phase.filter_simplification {
+                output.kv(
+                    "Filter Simplified",
+                    format!(
+                        "{} -> {} vertices (tolerance {:.6})",
+                        simplification.original_vertices,
+                        simplification.simplified_vertices,
+                        simplification.tolerance
+                    ),
+                );
+            }
+
+            if let Some(attributes) = &explanation.attribute_phase {
+                output.kv(
+                    "Attribute Phase",
+                    format!(
+                        "{} of {} candidates kept",
+                        attributes.candidates_after, attributes.candidates_before
+                    ),
+                );
+                for filter in &attributes.filters {
+                    output.kv(
+                        format!("  {}={}", filter.filter.property, filter.filter.value),
+                        format!(
+                            "{} level ({} chunks from metadata, {} feature lookups)",
+                            filter.level, filter.chunks_evaluated, filter.features_looked_up
+                        ),
+                    );
+                }
+            }
 
             if let Some(semantic) = explanation.semantic_phase {
                 output.kv(
@@ -226,38 +757,271 @@ pub async fn execute(
                 output.kv("Embedding Model", &semantic.embedder_model);
                 output.kv("Embedding Dimension", semantic.embedding_dim);
                 output.kv("Query Norm", format!("{:.3}", semantic.query_norm));
+                if let Some(example) = &semantic.example {
+                    output.kv("Example", describe_example(example));
+                }
             }
 
+            if let Some(rerank) = &explanation.rerank_phase {
+                output.kv(
+                    "Rerank Phase",
+                    format!(
+                        "{} candidates reranked using {} ({})",
+                        rerank.candidates_reranked, rerank.reranker, rerank.mode
+                    ),
+                );
+            }
+
+            if let Some(dist) = &explanation.score_distribution {
+                output.kv(
+                    "Score Distribution",
+                    format!(
+                        "min {:.3}, median {:.3}, max {:.3} ({} candidates)",
+                        dist.min, dist.median, dist.max, dist.count
+                    ),
+                );
+            }
+
+            if let Some(confidence) = &explanation.confidence {
+                output.kv(
+                    "Confidence",
+                    format!(
+                        "percentiles of {} sampled scores; high from {}, medium from {}",
+                        confidence.baseline_samples,
+                        confidence.high_cutoff,
+                        confidence.medium_cutoff
+                    ),
+                );
+                output.info(format!("  {}", confidence.caveat));
+            }
+
+            if let Some(preset) = &explanation.preset {
+                output.kv("Preset", describe_preset(&preset.preset));
+                let effective = &preset.effective;
+                output.kv(
+                    "Effective Plan",
+                    format!(
+                        "top_k {}, rerank {} ({} candidates), min_score {}",
+                        effective.top_k,
+                        effective.rerank,
+                        effective.rerank_pool,
+                        effective
+                            .min_score
+                            .map(|score| format!("{:.2}", score))
+                            .unwrap_or_else(|| "none".to_string())
+                    ),
+                );
+            }
+
+            output.section("Timing");
+            output.table(timing_rows(&explanation.timings));
+
             if !explanation.ranking_details.is_empty() {
                 output.section("Ranking Details");
                 for (i, detail) in explanation.ranking_details.iter().enumerate().take(5) {
                     output.info(format!("\n{}. Chunk ID: {}", i + 1, detail.chunk_id.0));
                     output.kv("  Final Score", format!("{:.3}", detail.final_score));
+                    if let Some(before) = detail.pre_rerank_rank {
+                        output.kv("  Rank", format!("{} -> {}", before, detail.rank));
+                    }
                     output.info(format!("  {}", detail.score_explanation));
                 }
             }
         }
+
+        if next_page.is_some() && !(io::stdin().is_terminal() && io::stdout().is_terminal()) {
+            output.info("More results follow; page through them at a terminal with :next");
+            next_page = None;
+        }
+        while let Some(cursor) = next_page.take() {
+            let command: String = Input::new()
+                .with_prompt(format!("{} results shown; :next or :quit", cursor.offset))
+                .default(":next".to_string())
+                .interact_text()?;
+            match command.trim() {
+                ":next" | ":n" => {
+                    let page = pages.page(&cursor, &generation, None)?;
+                    output.section(format!(
+                        "Sources {}-{} of {}",
+                        page.offset + 1,
+                        page.offset + page.result.sources.len(),
+                        page.total
+                    ));
+                    print_sources(&page.result, page.offset, &args.fields, &service, output).await;
+                    next_page = page.next;
+                }
+                ":quit" | ":q" => break,
+                other => {
+                    output.warning(format!("Unknown command '{}': use :next or :quit", other));
+                    next_page = Some(cursor);
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
-/// Find the workspace root by looking for .georag directory
-fn find_workspace_root() -> Result<PathBuf> {
-    let mut current = std::env::current_dir()?;
-    loop {
-        let georag_dir = current.join(".georag");
-        if georag_dir.exists() && georag_dir.is_dir() {
-            return Ok(current);
+/// Print ranked sources numbered from `offset + 1`, as a table of `fields` when given
+async fn print_sources(
+    result: &QueryResult,
+    offset: usize,
+    fields: &[String],
+    service: &QueryService,
+    output: &OutputWriter,
+) {
+    if !fields.is_empty() {
+        let columns = PropertySelection::include(fields).columns();
+        output.grid(&columns, source_grid(&service.to_rows(result).await, &columns));
+        return;
+    }
+    for (i, source) in result.sources.iter().enumerate() {
+        output.info(format!(
+            "\n{}. {} (score: {:.2})",
+            offset + i + 1,
+            source.document_path,
+            source.score
+        ));
+        if let Some(feature_id) = source.feature_id {
+            output.kv("  Feature", feature_id.0);
+        }
+        if let Some(spatial_match) = source.spatial_match {
+            output.kv("  Spatial Match", format!("{} geometry", spatial_match));
         }
-        if !current.pop() {
-            bail!("Not in a GeoRAG workspace. Run 'georag init' first.");
+        if let Some(url) = &source.source_url {
+            output.kv("  Source URL", url);
         }
+        if let Some(confidence) = source.confidence {
+            output.kv(
+                "  Confidence",
+                format!("{} (percentile {:.1})", confidence.level, confidence.percentile),
+            );
+        }
+        output.info(format!("  {}", source.excerpt));
     }
 }
 
+/// Baseline size, cutoffs and caveat of the confidence estimate
+fn describe_confidence(confidence: &ConfidenceExplanation) -> String {
+    format!(
+        "percentiles of {} sampled scores, high from {}, medium from {}. {}",
+        confidence.baseline_samples,
+        confidence.high_cutoff,
+        confidence.medium_cutoff,
+        confidence.caveat
+    )
+}
+
+/// One "property=value at level" entry per attribute filter
+fn attribute_levels(phase: &AttributePhaseExplanation) -> String {
+    phase
+        .filters
+        .iter()
+        .map(|f| format!("{}={} at {} level", f.filter.property, f.filter.value, f.level))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Serialize results with the same row shape the API returns
+async fn export_results(
+    result: &QueryResult,
+    format: ResultFormat,
+    service: &QueryService,
+) -> Result<String> {
+    let rendered = match format {
+        ResultFormat::GeoJson => {
+            let collection = service.to_geojson(result).await;
+            format!("{}\n", serde_json::to_string_pretty(&collection)?)
+        }
+        ResultFormat::Json => {
+            let rows = service.to_rows(result).await;
+            format!("{}\n", serde_json::to_string_pretty(&rows)?)
+        }
+        ResultFormat::Csv => service.to_csv(result).await,
+        ResultFormat::Png => bail!("PNG maps are written with --map"),
+    };
+
+    Ok(rendered)
+}
+
+/// Cells of the `--fields` table, one row per source
+fn source_grid(rows: &[Map<String, Value>], columns: &[&str]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| match row.get(*column) {
+                    None | Some(Value::Null) => String::new(),
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Tabled)]
+struct HistogramRow {
+    #[tabled(rename = "Bucket")]
+    bucket: String,
+    #[tabled(rename = "Count")]
+    count: usize,
+    #[tabled(rename = "")]
+    bar: String,
+    #[tabled(rename = "Top Score")]
+    top_score: String,
+}
+
+/// Render time buckets as histogram rows with bars scaled to the largest bucket
+fn histogram_rows(buckets: &[TimeBucket]) -> Vec<HistogramRow> {
+    let max_count = buckets.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+
+    buckets
+        .iter()
+        .map(|b| HistogramRow {
+            bucket: b.key.clone(),
+            count: b.count,
+            bar: "█".repeat((b.count * HISTOGRAM_WIDTH).div_ceil(max_count)),
+            top_score: b
+                .scores
+                .as_ref()
+                .map(|d| format!("{:.3}", d.max))
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect()
+}
+
+#[derive(Tabled)]
+struct TimingRow {
+    #[tabled(rename = "Phase")]
+    phase: String,
+    #[tabled(rename = "Start (ms)")]
+    start: String,
+    #[tabled(rename = "Duration (ms)")]
+    duration: String,
+}
+
+/// One row per phase that ran, then the total
+fn timing_rows(timings: &QueryTimings) -> Vec<TimingRow> {
+    let mut rows: Vec<TimingRow> = timings
+        .phases()
+        .into_iter()
+        .map(|(phase, timing)| TimingRow {
+            phase: phase.replace('_', " "),
+            start: format!("{:.1}", timing.start_ms),
+            duration: format!("{:.1}", timing.duration_ms),
+        })
+        .collect();
+    rows.push(TimingRow {
+        phase: "total".to_string(),
+        start: "-".to_string(),
+        duration: format!("{:.1}", timings.total_ms),
+    });
+    rows
+}
+
 /// Load workspace configuration
-fn load_workspace_config(georag_dir: &Path) -> Result<WorkspaceConfig> {
+pub(super) fn load_workspace_config(georag_dir: &Path) -> Result<WorkspaceConfig> {
     let config_path = georag_dir.join("config.toml");
     let config_content = fs::read_to_string(&config_path).context("Failed to read config.toml")?;
     let config: WorkspaceConfig =
@@ -266,7 +1030,7 @@ fn load_workspace_config(georag_dir: &Path) -> Result<WorkspaceConfig> {
 }
 
 /// Load index state
-fn load_index_state(georag_dir: &Path) -> Result<IndexState> {
+pub(super) fn load_index_state(georag_dir: &Path) -> Result<IndexState> {
     let state_path = georag_dir.join("index").join("state.json");
     if !state_path.exists() {
         bail!(
@@ -278,58 +1042,289 @@ fn load_index_state(georag_dir: &Path) -> Result<IndexState> {
 
     let content = fs::read_to_string(&state_path)?;
     let state: IndexState = serde_json::from_str(&content)?;
+    state.check_schema_version()?;
     Ok(state)
 }
 
+/// Build the spatial filter from --spatial/--predicate, --geometry, --bbox,
+/// --area, --distance and --buffer
+///
+/// `area` is the saved area named by --area, already resolved; its geometry
+/// is in WGS 84 whatever the workspace CRS. Returns `None` when no filter
+/// geometry was given and rejects flags that would otherwise be ignored.
 fn parse_spatial_filter(
-    predicate_str: &str,
-    _geometry_str: Option<&str>,
-    distance_str: Option<&str>,
+    args: &QueryArgs,
     config: &WorkspaceConfig,
-) -> Result<georag_core::models::SpatialFilter> {
+    area: Option<&SavedArea>,
+) -> Result<Option<georag_core::models::SpatialFilter>> {
     use georag_core::models::{
-        Crs, Distance as CoreDistance, DistanceUnit as CoreDistanceUnit, SpatialPredicate,
+        Crs, Distance as CoreDistance, DistanceUnit as CoreDistanceUnit, Geometry, SpatialPredicate,
+    };
+
+    let geometry = match (&args.geometry, &args.bbox) {
+        (Some(_), Some(_)) => {
+            bail!("--geometry and --bbox both set the filter geometry; pass only one of them")
+        }
+        (Some(geometry_arg), None) => {
+            let value =
+                parse_geometry_argument(geometry_arg).context("Failed to parse --geometry")?;
+            let geometry = Geometry::from_geojson(&value).with_context(|| {
+                format!(
+                    "Unsupported filter geometry type {}. Use a Point, LineString, Polygon or \
+                    their Multi variants",
+                    value.get("type").and_then(|t| t.as_str()).unwrap_or("(missing)")
+                )
+            })?;
+            Some(geometry)
+        }
+        (None, Some(bbox)) => Some(bbox_geometry(parse_bbox(bbox)?)),
+        (None, None) => area.map(|area| area.geometry.clone()),
+    };
+
+    let Some(geometry) = geometry else {
+        if let Some(predicate) = &args.spatial {
+            bail!(
+                "--spatial {} needs a filter geometry: pass --geometry, --bbox or --area",
+                predicate
+            );
+        }
+        if args.distance.is_some() {
+            bail!("--distance requires --spatial dwithin with --geometry, --bbox or --area");
+        }
+        if args.buffer.is_some() {
+            bail!("--buffer requires a filter geometry (--geometry, --bbox or --area)");
+        }
+        return Ok(None);
+    };
+
+    let predicate = match args.spatial.as_deref() {
+        None if args.bbox.is_some() => SpatialPredicate::BoundingBox,
+        None => SpatialPredicate::Intersects,
+        Some(predicate) => parse_predicate(predicate)?,
+    };
+
+    // Parse distance; only dwithin measures one
+    let distance = match (&args.distance, predicate) {
+        (Some(dist_str), SpatialPredicate::DWithin) => {
+            let dist = parse_distance(dist_str, config.distance_unit)?;
+            if !dist.value.is_finite() || dist.value <= 0.0 {
+                bail!("Invalid distance: {}. The distance must be positive", dist_str);
+            }
+            Some(CoreDistance {
+                value: dist.value,
+                unit: match dist.unit {
+                    DistanceUnit::Meters => CoreDistanceUnit::Meters,
+                    DistanceUnit::Kilometers => CoreDistanceUnit::Kilometers,
+                    DistanceUnit::Miles => CoreDistanceUnit::Miles,
+                    DistanceUnit::Feet => CoreDistanceUnit::Feet,
+                },
+            })
+        }
+        (Some(_), predicate) => bail!(
+            "--distance only applies to --spatial dwithin (got {:?}); use --buffer to widen \
+            the filter geometry for other predicates",
+            predicate
+        ),
+        (None, SpatialPredicate::DWithin) => {
+            bail!("--spatial dwithin requires --distance (e.g., --distance 2km)")
+        }
+        (None, _) => None,
+    };
+
+    Ok(Some(georag_core::models::SpatialFilter {
+        predicate,
+        geometry: Some(geometry),
+        distance,
+        crs: if area.is_some() {
+            Crs::wgs84()
+        } else {
+            Crs::new(config.crs, "")
+        },
+        buffer: parse_buffer(args, config)?,
+        exclude: Vec::new(),
+    }))
+}
+
+/// Add --exclude-geometry and --exclude-area to the spatial filter
+///
+/// `areas` are the saved areas named by --exclude-area, already resolved.
+/// Exclusions only narrow a filter, so they are rejected without one.
+fn exclude_areas(
+    filter: Option<georag_core::models::SpatialFilter>,
+    args: &QueryArgs,
+    areas: &[SavedArea],
+) -> Result<Option<georag_core::models::SpatialFilter>> {
+    use georag_core::models::Geometry;
+
+    if args.exclude_geometry.is_empty() && areas.is_empty() {
+        return Ok(filter);
+    }
+    let Some(mut filter) = filter else {
+        bail!(
+            "--exclude-geometry and --exclude-area require a spatial filter \
+            (--geometry, --bbox, --area or --at)"
+        );
+    };
+
+    for geometry_arg in &args.exclude_geometry {
+        let value =
+            parse_geometry_argument(geometry_arg).context("Failed to parse --exclude-geometry")?;
+        let geometry = Geometry::from_geojson(&value).with_context(|| {
+            format!(
+                "Unsupported exclusion geometry type {}. Use a Point, LineString, Polygon or \
+                their Multi variants",
+                value.get("type").and_then(|t| t.as_str()).unwrap_or("(missing)")
+            )
+        })?;
+        filter.exclude.push(geometry);
+    }
+    filter.exclude.extend(areas.iter().map(|area| area.geometry.clone()));
+
+    Ok(Some(filter))
+}
+
+/// Build the spatial filter of an --at query, `None` without --at
+///
+/// --spatial and --distance are kept as given; whichever is unset comes from
+/// the point query defaults, which are returned as applied.
+fn point_filter(
+    args: &QueryArgs,
+    config: &WorkspaceConfig,
+    defaults: &PointQueryDefaults,
+) -> Result<Option<(georag_core::models::SpatialFilter, AppliedPointDefaults)>> {
+    let Some(at) = &args.at else {
+        return Ok(None);
     };
+    let point = parse_point(at)?;
+    let predicate = args.spatial.as_deref().map(parse_predicate).transpose()?;
+    let radius = args
+        .distance
+        .as_deref()
+        .map(|distance| parse_distance(distance, config.distance_unit))
+        .transpose()?;
 
-    // Parse predicate
-    let predicate = match predicate_str.to_lowercase().as_str() {
+    let (filter, applied) = defaults
+        .filter(point, predicate, radius)
+        .map_err(|e| anyhow::anyhow!("Invalid --at query: {}", e))?;
+    let filter = georag_core::models::SpatialFilter {
+        buffer: parse_buffer(args, config)?,
+        ..filter
+    };
+    Ok(Some((filter, applied)))
+}
+
+/// Parse a --spatial predicate
+fn parse_predicate(predicate: &str) -> Result<georag_core::models::SpatialPredicate> {
+    use georag_core::models::SpatialPredicate;
+
+    Ok(match predicate.to_lowercase().as_str() {
         "within" => SpatialPredicate::Within,
         "intersects" => SpatialPredicate::Intersects,
         "contains" => SpatialPredicate::Contains,
+        "coveredby" | "covered_by" | "covered-by" => SpatialPredicate::CoveredBy,
+        "touches" => SpatialPredicate::Touches,
+        "crosses" => SpatialPredicate::Crosses,
+        "overlaps" => SpatialPredicate::Overlaps,
         "bbox" | "boundingbox" => SpatialPredicate::BoundingBox,
         "dwithin" | "distance" | "near" => SpatialPredicate::DWithin,
         _ => bail!(
-            "Invalid spatial predicate: {}. Use within, intersects, contains, bbox, or dwithin",
-            predicate_str
+            "Invalid spatial predicate: {}. Use within, intersects, contains, coveredby, \
+            touches, crosses, overlaps, bbox, or dwithin",
+            predicate
         ),
+    })
+}
+
+/// Parse --buffer, rejecting non-positive distances
+fn parse_buffer(args: &QueryArgs, config: &WorkspaceConfig) -> Result<Option<Distance>> {
+    let Some(buffer_str) = &args.buffer else {
+        return Ok(None);
     };
+    let buffer = parse_distance(buffer_str, config.distance_unit)?;
+    if !buffer.value.is_finite() || buffer.value <= 0.0 {
+        bail!("Invalid buffer: {}. The buffer distance must be positive", buffer_str);
+    }
+    Ok(Some(buffer))
+}
 
-    // Parse distance if provided
-    let distance = if let Some(dist_str) = distance_str {
-        let dist = parse_distance(dist_str, config.distance_unit)?;
-        Some(CoreDistance {
-            value: dist.value,
-            unit: match dist.unit {
-                DistanceUnit::Meters => CoreDistanceUnit::Meters,
-                DistanceUnit::Kilometers => CoreDistanceUnit::Kilometers,
-                DistanceUnit::Miles => CoreDistanceUnit::Miles,
-                DistanceUnit::Feet => CoreDistanceUnit::Feet,
-            },
-        })
+/// Exclusions as shown to the user, e.g. "2 areas removed 5 features and 9 chunks"
+fn describe_exclusions(exclusions: &ExclusionExplanation) -> String {
+    let mut text = format!(
+        "{} areas removed {} features and {} chunks",
+        exclusions.areas, exclusions.features_excluded, exclusions.chunks_excluded
+    );
+    if !exclusions.named_areas.is_empty() {
+        let names: Vec<&str> = exclusions.named_areas.iter().map(|a| a.name.as_str()).collect();
+        text.push_str(&format!(" (saved areas {})", names.join(", ")));
+    }
+    text
+}
+
+/// Example of a query by example as shown to the user, e.g.
+/// "parcels:42, mean of 3 seed chunks"
+fn describe_example(example: &ExampleExplanation) -> String {
+    if example.averaged {
+        format!("{}, mean of {} seed chunks", example.source, example.seed_chunks)
     } else {
-        None
-    };
+        format!("{}, {} seed chunks searched individually", example.source, example.seed_chunks)
+    }
+}
 
-    Ok(georag_core::models::SpatialFilter {
-        predicate,
-        geometry: None,
-        distance,
-        crs: Crs::new(config.crs, ""),
-    })
+/// Preset of a query as shown to the user, e.g.
+/// "planners (default), applied top_k, rerank; overridden min_score"
+fn describe_preset(preset: &AppliedPreset) -> String {
+    let mut text = preset.name.clone();
+    if preset.default {
+        text.push_str(" (default)");
+    }
+    if !preset.applied.is_empty() {
+        text.push_str(&format!(", applied {}", preset.applied.join(", ")));
+    }
+    if !preset.overridden.is_empty() {
+        text.push_str(&format!("; overridden {}", preset.overridden.join(", ")));
+    }
+    text
+}
+
+/// Split a --like-feature reference "DATASET:FEATURE" at its last colon
+fn parse_like_feature(reference: &str) -> Result<(&str, FeatureId)> {
+    let (dataset, feature) = reference
+        .rsplit_once(':')
+        .filter(|(dataset, _)| !dataset.trim().is_empty())
+        .with_context(|| {
+            format!("Invalid --like-feature '{}': expected DATASET:FEATURE", reference)
+        })?;
+    let feature_id = feature.trim().parse().with_context(|| {
+        format!("Invalid --like-feature '{}': feature ID must be a number", reference)
+    })?;
+    Ok((dataset.trim(), FeatureId(feature_id)))
+}
+
+/// Resolve the dataset of a --like-feature reference by name, or by ID
+async fn find_example_dataset(storage: &Storage, dataset: &str) -> Result<DatasetId> {
+    let datasets = storage.spatial.list_datasets().await?;
+    datasets
+        .iter()
+        .find(|d| d.name == dataset)
+        .or_else(|| datasets.iter().find(|d| d.id.0.to_string() == dataset))
+        .map(|d| d.id)
+        .with_context(|| format!("Dataset not found: {}", dataset))
+}
+
+/// Point query defaults as shown to the user, e.g. "predicate DWithin, radius 2 Kilometers"
+fn describe_point_defaults(applied: &AppliedPointDefaults) -> String {
+    let mut parts = Vec::new();
+    if let Some(predicate) = applied.predicate {
+        parts.push(format!("predicate {:?}", predicate));
+    }
+    if let Some(radius) = applied.radius {
+        parts.push(format!("radius {} {:?}", radius.value, radius.unit));
+    }
+    parts.join(", ")
 }
 
 /// Parse distance string like "5km" or "100m"
-fn parse_distance(
+pub(super) fn parse_distance(
     dist_str: &str,
     default_unit: georag_core::models::workspace::DistanceUnit,
 ) -> Result<Distance> {
@@ -344,23 +1339,245 @@ fn parse_distance(
 
     let value: f64 = value_str.parse().context("Invalid distance value")?;
 
+    // Use default unit from config when none is given
     let unit = if unit_str.is_empty() {
-        // Use default unit from config
-        match default_unit {
-            georag_core::models::workspace::DistanceUnit::Meters => DistanceUnit::Meters,
-            georag_core::models::workspace::DistanceUnit::Kilometers => DistanceUnit::Kilometers,
-            georag_core::models::workspace::DistanceUnit::Miles => DistanceUnit::Miles,
-            georag_core::models::workspace::DistanceUnit::Feet => DistanceUnit::Feet,
-        }
+        default_unit
     } else {
-        match unit_str.to_lowercase().as_str() {
-            "m" | "meters" | "meter" => DistanceUnit::Meters,
-            "km" | "kilometers" | "kilometer" => DistanceUnit::Kilometers,
-            "mi" | "miles" | "mile" => DistanceUnit::Miles,
-            "ft" | "feet" | "foot" => DistanceUnit::Feet,
-            _ => bail!("Invalid distance unit: {}", unit_str),
-        }
+        parse_distance_unit(unit_str)
+            .with_context(|| format!("Invalid distance unit in '{}'", dist_str))?
     };
 
     Ok(Distance::new(value, unit))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use georag_core::models::workspace::ValidityMode;
+    use georag_core::models::{Geometry, SpatialPredicate};
+    use georag_retrieval::models::SeedMode;
+
+    fn filter(flags: &[&str]) -> Result<Option<georag_core::models::SpatialFilter>> {
+        let args = QueryArgs::try_parse_from(["query", "drainage issues"].iter().chain(flags))?;
+        let config = WorkspaceConfig {
+            crs: 4326,
+            distance_unit: DistanceUnit::Meters,
+            geometry_validity: ValidityMode::Lenient,
+        };
+        parse_spatial_filter(&args, &config, None)
+    }
+
+    const AREA: &str = r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,1],[0,0]]]}"#;
+
+    #[test]
+    fn test_geometry_from_file_with_predicate() {
+        let path = std::env::temp_dir().join(format!("georag-area-{}.geojson", std::process::id()));
+        let feature = format!(r#"{{"type":"Feature","geometry":{},"properties":{{}}}}"#, AREA);
+        fs::write(&path, feature).unwrap();
+
+        let filter = filter(&[
+            "--geometry",
+            path.to_str().unwrap(),
+            "--predicate",
+            "dwithin",
+            "--distance",
+            "2km",
+        ])
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(filter.predicate, SpatialPredicate::DWithin);
+        assert_eq!(filter.geometry.as_ref().map(Geometry::vertex_count), Some(5));
+        let distance = filter.distance.unwrap();
+        assert_eq!(distance.value, 2.0);
+        assert_eq!(distance.unit, DistanceUnit::Kilometers);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_default_predicates() {
+        let geometry = filter(&["--geometry", AREA]).unwrap().unwrap();
+        assert_eq!(geometry.predicate, SpatialPredicate::Intersects);
+
+        let bbox = filter(&["--bbox", "-1,-1,1,1"]).unwrap().unwrap();
+        assert_eq!(bbox.predicate, SpatialPredicate::BoundingBox);
+        assert_eq!(bbox.geometry, Some(bbox_geometry([-1.0, -1.0, 1.0, 1.0])));
+
+        let within = filter(&["--bbox", "-1,-1,1,1", "--spatial", "within"]).unwrap().unwrap();
+        assert_eq!(within.predicate, SpatialPredicate::Within);
+
+        let touches = filter(&["--geometry", AREA, "--predicate", "touches"]).unwrap().unwrap();
+        assert_eq!(touches.predicate, SpatialPredicate::Touches);
+
+        assert!(filter(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_incompatible_flags_are_rejected() {
+        let error = |flags: &[&str]| filter(flags).unwrap_err().to_string();
+
+        assert!(error(&["--geometry", AREA, "--bbox", "0,0,1,1"]).contains("only one"));
+        assert!(error(&["--geometry", AREA, "--distance", "2km"]).contains("only applies"));
+        assert!(error(&["--geometry", AREA, "--predicate", "dwithin"]).contains("requires"));
+        assert!(error(&["--predicate", "within"]).contains("needs a filter geometry"));
+        assert!(error(&["--distance", "2km"]).contains("requires"));
+        assert!(error(&["--buffer", "200m"]).contains("requires"));
+        assert!(error(&["--bbox", "0,0,1,1", "--predicate", "nearby"]).contains("Invalid"));
+    }
+
+    #[test]
+    fn test_exclusions_are_added_to_the_filter() {
+        let excluding = |flags: &[&str]| {
+            let args = QueryArgs::try_parse_from(["query", "parks"].iter().chain(flags)).unwrap();
+            exclude_areas(filter(flags)?, &args, &[])
+        };
+        let park = r#"{"type":"Point","coordinates":[0.5,0.5]}"#;
+
+        let filter = excluding(&[
+            "--geometry",
+            AREA,
+            "--exclude-geometry",
+            park,
+            "--exclude-geometry",
+            AREA,
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(filter.exclude.len(), 2);
+        assert_eq!(filter.exclude[0], Geometry::point(0.5, 0.5));
+        assert_eq!(filter.exclude[1].vertex_count(), 5);
+
+        let error = excluding(&["--exclude-geometry", park]).unwrap_err().to_string();
+        assert!(error.contains("require a spatial filter"), "{}", error);
+        let error = excluding(&["--bbox", "0,0,1,1", "--exclude-geometry", "{}"]).unwrap_err();
+        assert!(error.to_string().contains("--exclude-geometry"), "{}", error);
+    }
+
+    #[test]
+    fn test_point_query_takes_unset_fields_from_defaults() {
+        let config = WorkspaceConfig {
+            crs: 4326,
+            distance_unit: DistanceUnit::Meters,
+            geometry_validity: ValidityMode::Lenient,
+        };
+        let defaults = PointQueryDefaults {
+            predicate: SpatialPredicate::DWithin,
+            radius: Some(1.5),
+            radius_unit: DistanceUnit::Kilometers,
+        };
+        let point = |flags: &[&str]| {
+            let args = QueryArgs::try_parse_from(["query", "cafes"].iter().chain(flags)).unwrap();
+            point_filter(&args, &config, &defaults)
+        };
+
+        let (filter, applied) = point(&["--at", "-73.98,40.75"]).unwrap().unwrap();
+        assert_eq!(filter.geometry, Some(Geometry::point(-73.98, 40.75)));
+        assert_eq!(filter.distance, Some(Distance::kilometers(1.5)));
+        assert_eq!(describe_point_defaults(&applied), "predicate DWithin, radius 1.5 Kilometers");
+
+        let (filter, applied) =
+            point(&["--at", "106.8,-6.2", "--distance", "300m"]).unwrap().unwrap();
+        assert_eq!(filter.distance, Some(Distance::meters(300.0)));
+        assert_eq!(applied.radius, None);
+        assert_eq!(applied.predicate, Some(SpatialPredicate::DWithin));
+
+        let (filter, applied) =
+            point(&["--at", "106.8,-6.2", "--spatial", "intersects"]).unwrap().unwrap();
+        assert_eq!(filter.predicate, SpatialPredicate::Intersects);
+        assert_eq!(filter.distance, None);
+        assert!(applied.is_empty());
+
+        assert!(point(&["--at", "106.8,-6.2", "--spatial", "within", "--distance", "1km"]).is_err());
+        assert!(point(&["--at", "106.8"]).is_err());
+        assert!(point(&[]).unwrap().is_none());
+        assert!(
+            QueryArgs::try_parse_from(["query", "cafes", "--at", "1,2", "--bbox", "0,0,1,1"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_preset_options_are_left_unset_by_default() {
+        let args = QueryArgs::try_parse_from(["query", "parks", "--preset", "mobile"]).unwrap();
+        assert_eq!(args.preset.as_deref(), Some("mobile"));
+        assert_eq!((args.top_k, args.rerank, args.rerank_pool), (None, None, None));
+
+        let args = QueryArgs::try_parse_from(["query", "parks", "-k", "3"]).unwrap();
+        assert_eq!(args.top_k, Some(3));
+
+        let preset = AppliedPreset {
+            name: "planners".to_string(),
+            default: true,
+            applied: vec!["rerank".to_string(), "rerank_pool".to_string()],
+            overridden: vec!["top_k".to_string()],
+        };
+        assert_eq!(
+            describe_preset(&preset),
+            "planners (default), applied rerank, rerank_pool; overridden top_k"
+        );
+    }
+
+    #[test]
+    fn test_saved_area_is_the_filter_geometry() {
+        let config = WorkspaceConfig {
+            crs: 3857,
+            distance_unit: DistanceUnit::Meters,
+            geometry_validity: ValidityMode::Lenient,
+        };
+        let area = SavedArea::new(
+            "project-x",
+            Geometry::from_geojson(&serde_json::from_str(AREA).unwrap()).unwrap(),
+        );
+        let parse = |flags: &[&str]| {
+            let args =
+                QueryArgs::try_parse_from(["query", "drainage"].iter().chain(flags)).unwrap();
+            parse_spatial_filter(&args, &config, Some(&area))
+        };
+
+        let filter = parse(&["--area", "project-x", "--buffer", "200m"]).unwrap().unwrap();
+        assert_eq!(filter.predicate, SpatialPredicate::Intersects);
+        assert_eq!(filter.geometry, Some(area.geometry.clone()));
+        assert_eq!(filter.crs.epsg, 4326);
+        assert!(filter.buffer.is_some());
+
+        let within = parse(&["--area", "project-x", "--predicate", "within"]).unwrap().unwrap();
+        assert_eq!(within.predicate, SpatialPredicate::Within);
+
+        let conflict = ["query", "drainage", "--area", "project-x", "--bbox", "0,0,1,1"];
+        assert!(QueryArgs::try_parse_from(conflict).is_err());
+    }
+
+    #[test]
+    fn test_like_feature_reference() {
+        let (dataset, feature_id) = parse_like_feature("parcels:42").unwrap();
+        assert_eq!((dataset, feature_id), ("parcels", FeatureId(42)));
+
+        // Dataset names may hold colons themselves
+        let (dataset, _) = parse_like_feature("survey:2024:7").unwrap();
+        assert_eq!(dataset, "survey:2024");
+
+        assert!(parse_like_feature("parcels").is_err());
+        assert!(parse_like_feature(":42").is_err());
+        assert!(parse_like_feature("parcels:north").is_err());
+
+        // The query text becomes optional, but semantic ranking cannot be turned off
+        let args = QueryArgs::try_parse_from(["query", "--like-feature", "parcels:42"]).unwrap();
+        assert_eq!(args.query, None);
+        assert_eq!(args.seed_mode, SeedMode::Average);
+        assert!(QueryArgs::try_parse_from(["query"]).is_err());
+        let no_rerank = ["query", "--like-feature", "parcels:42", "--no-rerank"];
+        assert!(QueryArgs::try_parse_from(no_rerank).is_err());
+    }
+
+    #[test]
+    fn test_parse_distance_units() {
+        let distance = parse_distance("3mile", DistanceUnit::Meters).unwrap();
+        assert_eq!(distance.unit, DistanceUnit::Miles);
+
+        let distance = parse_distance("250", DistanceUnit::Feet).unwrap();
+        assert_eq!(distance.unit, DistanceUnit::Feet);
+
+        assert!(parse_distance("5 parsecs", DistanceUnit::Meters).is_err());
+    }
+}
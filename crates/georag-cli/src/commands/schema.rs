@@ -0,0 +1,103 @@
+use crate::cli::{SchemaArgs, SchemaCommand, SchemaExportArgs};
+use crate::config::load_workspace_config;
+use crate::output::OutputWriter;
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use georag_core::config::LayeredConfig;
+use georag_core::models::{data_dictionary, ChunkingSettings};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::redaction::{RedactionConfig, Redactor};
+use georag_service::SchemaService;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Execute dataset schema commands
+pub async fn execute(
+    args: SchemaArgs,
+    output: &OutputWriter,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    match args.command {
+        SchemaCommand::Export(export_args) => {
+            execute_export(export_args, output, storage, workspace).await
+        }
+    }
+}
+
+/// Write a Markdown data dictionary or JSON Schema documents of the datasets
+async fn execute_export(
+    args: SchemaExportArgs,
+    output: &OutputWriter,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    // Outside a workspace (e.g. reading a bundle) only the environment applies
+    let (config, redaction) = match super::workspace_root(workspace, output) {
+        Ok(root) => {
+            let redaction =
+                RedactionConfig::load_from_file(root.join(".georag").join("config.toml"))
+                    .context("Failed to load redaction rules")?;
+            (load_workspace_config(&root)?, redaction)
+        }
+        Err(_) => (LayeredConfig::with_defaults().load_from_env(), RedactionConfig::default()),
+    };
+
+    let mut datasets = storage.spatial.list_datasets().await?;
+    if let Some(name) = &args.dataset {
+        datasets.retain(|d| &d.name == name);
+        if datasets.is_empty() {
+            bail!("Dataset not found: {}", name);
+        }
+    }
+    datasets.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.0.cmp(&b.id.0)));
+
+    let service = SchemaService::new(storage.spatial.clone())
+        .with_chunking(chunking(&config))
+        .with_redactor(Redactor::new(&redaction)?);
+    let schemas = service.schemas(&datasets).await.context("Failed to infer dataset schemas")?;
+
+    let content = match args.format.as_str() {
+        "json" => {
+            let mut documents: Vec<Value> =
+                schemas.iter().map(|schema| schema.to_json_schema()).collect();
+            // One document for one dataset, an array for the workspace
+            let json = match (&args.dataset, documents.len()) {
+                (Some(_), 1) => documents.remove(0),
+                _ => Value::Array(documents),
+            };
+            format!("{}\n", serde_json::to_string_pretty(&json)?)
+        }
+        _ => data_dictionary(&schemas),
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, &content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            output.success(format!(
+                "Wrote the schema of {} dataset(s) to {}",
+                schemas.len(),
+                path.display()
+            ));
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// Chunking settings `georag build` uses with this configuration
+fn chunking(config: &LayeredConfig) -> ChunkingSettings {
+    let properties = config.chunk_properties.value.clone();
+    match config.max_chunk_tokens.value {
+        Some(max_tokens) => ChunkingSettings::tokens(
+            config.tokenizer.value.clone(),
+            max_tokens,
+            config.overlap_tokens.value,
+            properties,
+        ),
+        None => ChunkingSettings::from(&ChunkGenerator::default().with_properties(properties)),
+    }
+}
@@ -0,0 +1,472 @@
+use crate::cli::{SelfTestArgs, StorageBackend};
+use crate::config::{find_workspace_root, load_workspace_config};
+use crate::output::OutputWriter;
+use crate::output_types::{SelfTestOutput, SelfTestStageInfo};
+use crate::storage::Storage;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use georag_core::config::LayeredConfig;
+use georag_core::formats::geojson::GeoJsonReader;
+use georag_core::formats::FormatReader;
+use georag_core::geo::models::Crs;
+use georag_core::llm::{AnyEmbedder, Embedder};
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType, SpatialFilter, SpatialPredicate,
+};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{IndexBuilder, QueryPlan, RetrievalPipeline};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Embedded fixture: a few GeoJSON points around a small harbor town
+const PLACES_FILE: &str = "places.geojson";
+const PLACES_GEOJSON: &str = include_str!("../../fixtures/self-test/places.geojson");
+
+/// Embedded fixture: a short text document located at the ferry terminal
+const NOTES_FILE: &str = "harbor-notes.txt";
+const NOTES_TEXT: &str = include_str!("../../fixtures/self-test/harbor-notes.txt");
+const NOTES_LOCATION: [f64; 2] = [10.052, 59.909];
+
+/// Query expected to rank the text document first
+const QUERY_TEXT: &str = "When does the ferry leave the harbor terminal?";
+
+/// Query area around the harbor; excludes the observatory on the ridge
+const QUERY_BBOX: [f64; 4] = [10.0, 59.9, 10.1, 59.95];
+
+/// Deterministic offline embedder of the core stages
+const OFFLINE_EMBEDDER: &str = "mock:64";
+
+pub async fn execute(
+    args: SelfTestArgs,
+    output: &OutputWriter,
+    workspace: Option<&Path>,
+) -> Result<()> {
+    let fixture_dir = std::env::temp_dir().join(format!("georag-self-test-{}", std::process::id()));
+    let mut report = SelfTestReport::new(output);
+
+    if let Err(e) = write_fixtures(&fixture_dir) {
+        report.record("fixtures", Err(e));
+        report.skip_all(&CORE_STAGES);
+    } else {
+        run_core_stages(&fixture_dir, &mut report).await;
+
+        if args.with_ollama {
+            let result = run_ollama_stage(&fixture_dir, workspace).await;
+            report.record("ollama", result);
+        }
+    }
+
+    if args.with_postgres {
+        let result = run_postgres_stage().await;
+        report.record("postgres", result);
+    }
+
+    let _ = fs::remove_dir_all(&fixture_dir);
+
+    report.finish()
+}
+
+/// Stages run against the in-memory stores, in order
+const CORE_STAGES: [&str; 5] = ["stores", "ingest", "chunking", "index", "query"];
+
+/// Run the in-memory stages; a failed stage skips the ones that depend on it
+async fn run_core_stages(fixture_dir: &Path, report: &mut SelfTestReport<'_>) {
+    let stores = MemoryStores::new();
+
+    if !report.record(CORE_STAGES[0], check_stores(&stores).await) {
+        report.skip_all(&CORE_STAGES[1..]);
+        return;
+    }
+
+    let datasets = match ingest_fixtures(&stores, fixture_dir).await {
+        Ok(datasets) => {
+            let features: usize = datasets.iter().map(|(_, f)| f.len()).sum();
+            let detail = format!("{} datasets, {} features", datasets.len(), features);
+            report.record(CORE_STAGES[1], Ok(detail));
+            datasets
+        }
+        Err(e) => {
+            report.record(CORE_STAGES[1], Err(e));
+            report.skip_all(&CORE_STAGES[2..]);
+            return;
+        }
+    };
+
+    let chunking = generate_chunks(&stores, &datasets).await.map(|n| format!("{} chunks", n));
+    if !report.record(CORE_STAGES[2], chunking) {
+        report.skip_all(&CORE_STAGES[3..]);
+        return;
+    }
+
+    let index = match offline_embedder() {
+        Ok(embedder) => build_index(&stores, embedder).await,
+        Err(e) => Err(e),
+    };
+    if !report.record(CORE_STAGES[3], index) {
+        report.skip_all(&CORE_STAGES[4..]);
+        return;
+    }
+
+    let query = match offline_embedder() {
+        Ok(embedder) => query_fixtures(&stores, embedder).await,
+        Err(e) => Err(e),
+    };
+    report.record(CORE_STAGES[4], query);
+}
+
+/// The offline embedder, which needs the `mock` feature (on by default)
+fn offline_embedder() -> Result<AnyEmbedder> {
+    super::build::create_embedder(OFFLINE_EMBEDDER, false, None)
+}
+
+/// Build the fixture index with the configured Ollama embedder and query it
+async fn run_ollama_stage(fixture_dir: &Path, workspace: Option<&Path>) -> Result<String> {
+    let config = match find_workspace_root(workspace) {
+        Ok(root) => load_workspace_config(&root)?,
+        Err(_) => LayeredConfig::with_defaults().load_from_env(),
+    };
+    let model = &config.embedder.value;
+
+    let stores = MemoryStores::new();
+    let datasets = ingest_fixtures(&stores, fixture_dir).await?;
+    generate_chunks(&stores, &datasets).await?;
+    build_index(&stores, super::build::create_embedder(model, false, None)?)
+        .await
+        .context("Failed to embed fixtures with Ollama. Is 'ollama serve' running?")?;
+    let detail =
+        query_fixtures(&stores, super::build::create_embedder(model, false, None)?).await?;
+
+    Ok(format!("{}: {}", model, detail))
+}
+
+/// Connect to PostgreSQL using DATABASE_URL and run read-only checks
+///
+/// Nothing is written so the check is safe to run against a live database.
+async fn run_postgres_stage() -> Result<String> {
+    let storage = Storage::new(StorageBackend::Postgres).await?;
+    let datasets = storage.spatial.list_datasets().await?;
+    let chunks = storage.document.list_chunk_ids().await?;
+    let dimensions = storage.vector.dimensions().await?;
+
+    Ok(format!(
+        "connected, {} datasets, {} chunks, {} dimensions",
+        datasets.len(),
+        chunks.len(),
+        dimensions
+    ))
+}
+
+/// Write the embedded fixtures to disk so they go through the regular readers
+fn write_fixtures(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).context("Failed to create fixture directory")?;
+    fs::write(dir.join(PLACES_FILE), PLACES_GEOJSON)?;
+    fs::write(dir.join(NOTES_FILE), NOTES_TEXT)?;
+    Ok(())
+}
+
+/// In-memory stores used by the self-test
+struct MemoryStores {
+    spatial: Arc<MemorySpatialStore>,
+    vector: Arc<MemoryVectorStore>,
+    document: Arc<MemoryDocumentStore>,
+}
+
+impl MemoryStores {
+    fn new() -> Self {
+        Self {
+            spatial: Arc::new(MemorySpatialStore::new()),
+            vector: Arc::new(MemoryVectorStore::new()),
+            document: Arc::new(MemoryDocumentStore::new()),
+        }
+    }
+}
+
+async fn check_stores(stores: &MemoryStores) -> Result<String> {
+    let datasets = stores.spatial.list_datasets().await?;
+    let chunks = stores.document.list_chunk_ids().await?;
+    if !datasets.is_empty() || !chunks.is_empty() {
+        bail!("Fresh in-memory stores are not empty");
+    }
+
+    Ok("in-memory stores ready".to_string())
+}
+
+/// Store the GeoJSON fixture and the text document with their features
+async fn ingest_fixtures(
+    stores: &MemoryStores,
+    fixture_dir: &Path,
+) -> Result<Vec<(Dataset, Vec<Feature>)>> {
+    let mut ingested = Vec::new();
+    let mut next_feature_id = 1;
+
+    // GeoJSON fixture through the regular format reader
+    let places_path = fixture_dir.join(PLACES_FILE);
+    let format_dataset = GeoJsonReader
+        .read(&places_path)
+        .await
+        .context("Failed to read GeoJSON fixture")?;
+
+    let features: Vec<Feature> = format_dataset
+        .features
+        .iter()
+        .filter_map(|f| {
+            let geometry = f.geometry.as_ref().and_then(Geometry::from_geojson)?;
+            let id = FeatureId(next_feature_id);
+            next_feature_id += 1;
+            Some(Feature::with_geometry(id, geometry, f.properties.clone(), format_dataset.crs))
+        })
+        .collect();
+
+    if features.len() != format_dataset.features.len() {
+        bail!(
+            "Only {} of {} GeoJSON fixture features had a valid geometry",
+            features.len(),
+            format_dataset.features.len()
+        );
+    }
+
+    let places = fixture_dataset(
+        PLACES_FILE,
+        places_path,
+        features.len(),
+        format_dataset.crs,
+        &format_dataset.format_metadata.format_name,
+    );
+    ingested.push((places, features));
+
+    // Text document associated with a point geometry
+    let notes_path = fixture_dir.join(NOTES_FILE);
+    let text = fs::read_to_string(&notes_path).context("Failed to read text fixture")?;
+    let properties = HashMap::from([
+        ("name".to_string(), serde_json::Value::from("Harbor notes")),
+        ("content".to_string(), serde_json::Value::from(text)),
+    ]);
+    let notes_feature = Feature::with_geometry(
+        FeatureId(next_feature_id),
+        Geometry::point(NOTES_LOCATION[0], NOTES_LOCATION[1]),
+        properties,
+        4326,
+    );
+    let notes = fixture_dataset(NOTES_FILE, notes_path, 1, 4326, "Text");
+    ingested.push((notes, vec![notes_feature]));
+
+    for (dataset, features) in &mut ingested {
+        dataset.id = stores.spatial.store_dataset(dataset).await?;
+        stores.spatial.store_features(features).await?;
+        stores
+            .spatial
+            .associate_features_with_dataset(dataset.id, features.iter().map(|f| f.id).collect());
+    }
+
+    Ok(ingested)
+}
+
+fn fixture_dataset(
+    name: &str,
+    path: PathBuf,
+    feature_count: usize,
+    crs: u32,
+    format_name: &str,
+) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path,
+        geometry_type: GeometryType::Point,
+        feature_count,
+        crs,
+        format: FormatMetadata {
+            format_name: format_name.to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+/// Chunk every fixture feature and store the chunks
+async fn generate_chunks(
+    stores: &MemoryStores,
+    datasets: &[(Dataset, Vec<Feature>)],
+) -> Result<usize> {
+    let generator = ChunkGenerator::default();
+    let mut chunks = Vec::new();
+    for (dataset, features) in datasets {
+        chunks.extend(generator.generate_chunks(dataset, features));
+    }
+
+    let feature_count: usize = datasets.iter().map(|(_, f)| f.len()).sum();
+    if chunks.len() < feature_count {
+        bail!("Expected at least {} chunks, got {}", feature_count, chunks.len());
+    }
+
+    stores.document.store_chunks(&chunks).await?;
+    Ok(chunks.len())
+}
+
+/// Embed the stored chunks and check every chunk got a vector
+async fn build_index<E: Embedder>(stores: &MemoryStores, embedder: E) -> Result<String> {
+    let builder = IndexBuilder::new(
+        stores.spatial.clone(),
+        stores.vector.clone(),
+        stores.document.clone(),
+        embedder,
+        Crs::wgs84(),
+    );
+    let result = builder.build().await?;
+
+    let chunk_count = stores.document.list_chunk_ids().await?.len();
+    if result.chunk_count != chunk_count {
+        bail!("Indexed {} of {} chunks", result.chunk_count, chunk_count);
+    }
+
+    let dimensions = stores.vector.dimensions().await?;
+    if dimensions != result.embedding_dim {
+        bail!(
+            "Stored vectors have {} dimensions, embedder reports {}",
+            dimensions,
+            result.embedding_dim
+        );
+    }
+
+    Ok(format!("{} embeddings, {} dimensions", result.chunk_count, dimensions))
+}
+
+/// Run a spatial + semantic query and check the text document ranks first
+async fn query_fixtures<E: Embedder>(stores: &MemoryStores, embedder: E) -> Result<String> {
+    let [min_x, min_y, max_x, max_y] = QUERY_BBOX;
+    let filter = SpatialFilter {
+        predicate: SpatialPredicate::BoundingBox,
+        geometry: Some(Geometry::Polygon {
+            coordinates: vec![vec![
+                [min_x, min_y],
+                [max_x, min_y],
+                [max_x, max_y],
+                [min_x, max_y],
+                [min_x, min_y],
+            ]],
+        }),
+        distance: None,
+        crs: Crs::wgs84(),
+        buffer: None,
+        exclude: Vec::new(),
+    };
+
+    let pipeline = RetrievalPipeline::new(
+        stores.spatial.clone(),
+        stores.vector.clone(),
+        stores.document.clone(),
+        embedder,
+    );
+    let plan = QueryPlan::new(QUERY_TEXT).with_spatial_filter(filter).with_top_k(5);
+    let result = pipeline.execute(&plan).await?;
+
+    let total_chunks = stores.document.list_chunk_ids().await?.len();
+    if result.spatial_matches == 0 || result.spatial_matches >= total_chunks {
+        bail!(
+            "Spatial filter matched {} of {} chunks; expected a strict subset",
+            result.spatial_matches,
+            total_chunks
+        );
+    }
+
+    let Some(top) = result.sources.first() else {
+        bail!("Query returned no results");
+    };
+    if !top.document_path.ends_with(NOTES_FILE) {
+        bail!("Expected '{}' to rank first, got '{}'", NOTES_FILE, top.document_path);
+    }
+
+    Ok(format!(
+        "{} spatial matches, top source {} (score {:.3})",
+        result.spatial_matches, NOTES_FILE, top.score
+    ))
+}
+
+/// Collects stage results and prints them as they complete
+struct SelfTestReport<'a> {
+    output: &'a OutputWriter,
+    stages: Vec<SelfTestStageInfo>,
+}
+
+impl<'a> SelfTestReport<'a> {
+    fn new(output: &'a OutputWriter) -> Self {
+        Self { output, stages: Vec::new() }
+    }
+
+    /// Record a stage result; returns true if the stage passed
+    fn record(&mut self, name: &str, result: Result<String>) -> bool {
+        let (status, detail) = match result {
+            Ok(detail) => {
+                if !self.output.is_json() {
+                    self.output.success(format!("{}: {}", name, detail));
+                }
+                ("passed", detail)
+            }
+            Err(e) => {
+                let detail = format!("{:#}", e);
+                if !self.output.is_json() {
+                    self.output.error(format!("{}: {}", name, detail));
+                }
+                ("failed", detail)
+            }
+        };
+
+        let passed = status == "passed";
+        self.stages.push(SelfTestStageInfo {
+            name: name.to_string(),
+            status: status.to_string(),
+            detail,
+        });
+        passed
+    }
+
+    /// Record stages that were not run because an earlier stage failed
+    fn skip_all(&mut self, names: &[&str]) {
+        for name in names {
+            let detail = "skipped after an earlier failure".to_string();
+            if !self.output.is_json() {
+                self.output.warning(format!("{}: {}", name, detail));
+            }
+            self.stages.push(SelfTestStageInfo {
+                name: name.to_string(),
+                status: "skipped".to_string(),
+                detail,
+            });
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        let failed = self.stages.iter().filter(|s| s.status != "passed").count();
+        let total = self.stages.len();
+
+        if self.output.is_json() {
+            self.output
+                .result(SelfTestOutput { passed: failed == 0, stages: self.stages })?;
+        } else if failed == 0 {
+            self.output.success(format!("Self-test passed ({} stages)", total));
+        }
+
+        if failed > 0 {
+            bail!("Self-test failed: {} of {} stages did not pass", failed, total);
+        }
+
+        Ok(())
+    }
+}
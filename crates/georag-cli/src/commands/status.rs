@@ -1,30 +1,49 @@
 use crate::cli::StatusArgs;
+use crate::config::{find_store_workspace, load_workspace_config_with_store};
 use crate::output::OutputWriter;
 use crate::output_types::{
-    ConfigValue, DatasetCrsInfo, DatasetInfo, IndexStatus, InspectConfigOutput, InspectCrsOutput,
-    InspectDatasetsOutput, InspectIndexOutput, StatusOutput, StorageStatus,
+    ConfigValue, DatasetCrsInfo, DatasetIndexInfo, DatasetInfo, IndexStatus, InspectConfigOutput,
+    InspectCrsOutput, InspectDatasetsOutput, InspectIndexOutput, InspectUsageOutput, StatusOutput,
+    StorageStatus,
 };
-use anyhow::{bail, Context, Result};
+use crate::storage::Storage;
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
+use georag_core::config::format_quota;
 use georag_core::models::workspace::IndexState;
-use georag_core::models::{DatasetMeta, WorkspaceConfig};
+use georag_core::models::{
+    sort_datasets, AuditEventKind, DatasetMeta, DatasetPreview, DatasetSort, QuotaResource,
+    SortOrder, Timeline, TimelineBucket, TimelineGranularity, WorkspaceConfig, WorkspaceQuotas,
+    WorkspaceUsage, DEFAULT_TIMELINE_DAYS,
+};
+use georag_service::IndexStatsService;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use tabled::Tabled;
 
-pub fn execute(args: StatusArgs, output: &OutputWriter) -> Result<()> {
+pub async fn execute(
+    args: StatusArgs,
+    output: &OutputWriter,
+    storage: &Storage,
+    workspace: Option<&Path>,
+) -> Result<()> {
     // Find workspace root
-    let workspace_root = find_workspace_root()?;
+    let workspace_root = super::workspace_root(workspace, output)?;
     let georag_dir = workspace_root.join(".georag");
 
     // Determine what to show based on flags
-    let show_all = !args.datasets && !args.index && !args.crs && !args.config;
+    let show_all =
+        !args.datasets && !args.index && !args.crs && !args.config && !args.usage && !args.timeline;
 
     if args.datasets || show_all {
-        show_datasets(&georag_dir, output, show_all)?;
+        let sort: DatasetSort = args.sort.parse().map_err(|e: String| anyhow!(e))?;
+        let order: SortOrder = args.order.parse().map_err(|e: String| anyhow!(e))?;
+        show_datasets(&georag_dir, output, show_all, sort, order)?;
     }
 
     if args.index || show_all {
-        show_index(&georag_dir, output, show_all)?;
+        show_index(&georag_dir, storage, output, show_all).await?;
     }
 
     if args.crs || show_all {
@@ -35,8 +54,17 @@ pub fn execute(args: StatusArgs, output: &OutputWriter) -> Result<()> {
         show_config(&georag_dir, output, show_all)?;
     }
 
+    // Usage is read from the store, so it is only shown when asked for
+    if args.usage {
+        show_usage(&workspace_root, storage, output).await?;
+    }
+
+    if args.timeline {
+        show_timeline(storage, output).await?;
+    }
+
     if show_all {
-        show_overall_status(&workspace_root, &georag_dir, output, args.verbose)?;
+        show_overall_status(&workspace_root, &georag_dir, output, output.is_verbose())?;
     }
 
     Ok(())
@@ -82,6 +110,7 @@ fn show_overall_status(
             IndexStatus {
                 built: false,
                 hash: None,
+                schema_version: None,
                 built_at: None,
                 embedder: None,
                 chunk_count: None,
@@ -118,8 +147,15 @@ fn show_overall_status(
 }
 
 /// Show datasets information
-fn show_datasets(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) -> Result<()> {
-    let datasets = load_datasets(georag_dir)?;
+fn show_datasets(
+    georag_dir: &Path,
+    output: &OutputWriter,
+    is_part_of_all: bool,
+    sort: DatasetSort,
+    order: SortOrder,
+) -> Result<()> {
+    let mut datasets = load_datasets(georag_dir)?;
+    sort_datasets(&mut datasets, sort, order);
 
     if datasets.is_empty() {
         if output.is_json() {
@@ -140,6 +176,9 @@ fn show_datasets(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool)
                 feature_count: d.feature_count,
                 crs: d.crs,
                 added_at: d.added_at,
+                license: d.license.clone(),
+                attribution: d.attribution.clone(),
+                preview: d.preview.clone().filter(|_| output.is_verbose()),
             })
             .collect();
 
@@ -173,13 +212,55 @@ fn show_datasets(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool)
             .collect();
 
         output.table(rows);
+
+        if output.is_verbose() {
+            show_previews(&datasets, output);
+        }
     }
 
     Ok(())
 }
 
+/// Show the content preview of each dataset that has one
+fn show_previews(datasets: &[DatasetMeta], output: &OutputWriter) {
+    for dataset in datasets {
+        let Some(preview) = &dataset.preview else {
+            continue;
+        };
+        output.section(format!("Preview: {}", dataset.name));
+        match preview {
+            DatasetPreview::Document(document) => {
+                output.kv("Words", document.word_count);
+                output.kv("Characters", document.character_count);
+                let ellipsis = if document.truncated { "…" } else { "" };
+                output.kv("Text", format!("{}{}", document.snippet, ellipsis));
+            }
+            DatasetPreview::Vector(vector) => {
+                if let Some([min_x, min_y, max_x, max_y]) = vector.extent {
+                    output.kv("Extent", format!("{}, {}, {}, {}", min_x, min_y, max_x, max_y));
+                }
+                let mut types: Vec<String> = vector
+                    .geometry_types
+                    .iter()
+                    .map(|c| format!("{:?} ({})", c.geometry_type, c.count))
+                    .collect();
+                if vector.without_geometry > 0 {
+                    types.push(format!("none ({})", vector.without_geometry));
+                }
+                output.kv("Geometry Types", types.join(", "));
+                output.kv("Properties", vector.property_keys.join(", "));
+            }
+        }
+    }
+}
+
 /// Show index information
-fn show_index(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) -> Result<()> {
+async fn show_index(
+    georag_dir: &Path,
+    storage: &Storage,
+    output: &OutputWriter,
+    is_part_of_all: bool,
+) -> Result<()> {
     let state_path = georag_dir.join("index").join("state.json");
 
     if !state_path.exists() {
@@ -191,6 +272,8 @@ fn show_index(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) ->
                 embedder: None,
                 chunk_count: None,
                 embedding_dim: None,
+                dataset_built_at: BTreeMap::new(),
+                datasets: Vec::new(),
             })?;
         } else if is_part_of_all {
             output.section("Index Status");
@@ -203,29 +286,102 @@ fn show_index(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) ->
     }
 
     let state = load_index_state(georag_dir)?;
+    let datasets = dataset_index_info(storage).await?;
 
     if output.is_json() {
         output.result(InspectIndexOutput {
             built: true,
             hash: Some(state.hash.clone()),
+            schema_version: Some(state.schema_version),
             built_at: Some(state.built_at),
             embedder: Some(state.embedder.clone()),
             chunk_count: Some(state.chunk_count),
             embedding_dim: Some(state.embedding_dim),
+            dataset_built_at: state.dataset_built_at.clone(),
+            datasets,
         })?;
     } else {
         output.section("Index Status");
         output.kv("Status", "Built");
         output.kv("Hash", &state.hash);
+        output.kv("Schema Version", state.schema_version);
+        if let Err(e) = state.check_schema_version() {
+            output.warning(e.to_string());
+        }
         output.kv("Built At", state.built_at.format("%Y-%m-%d %H:%M:%S UTC"));
         output.kv("Embedder", &state.embedder);
         output.kv("Chunks", state.chunk_count);
         output.kv("Embedding Dimension", state.embedding_dim);
+
+        // Datasets built with `build --datasets` show when their part of the index was last built
+        if !state.dataset_built_at.is_empty() {
+            output.section("Dataset Builds");
+            for (name, built_at) in &state.dataset_built_at {
+                let age = if *built_at < state.built_at {
+                    " (before the last build)"
+                } else {
+                    ""
+                };
+                output.kv(name, format!("{}{}", built_at.format("%Y-%m-%d %H:%M:%S UTC"), age));
+            }
+        }
+
+        if !datasets.is_empty() {
+            output.section("Dataset Index");
+
+            #[derive(Tabled)]
+            struct DatasetIndexRow {
+                #[tabled(rename = "Dataset")]
+                name: String,
+                #[tabled(rename = "Chunks")]
+                chunks: u64,
+                #[tabled(rename = "Content")]
+                content: String,
+                #[tabled(rename = "Embeddings")]
+                embeddings: u64,
+            }
+
+            let rows: Vec<DatasetIndexRow> = datasets
+                .into_iter()
+                .map(|info| DatasetIndexRow {
+                    name: info.name,
+                    chunks: info.stats.chunks,
+                    content: super::db::format_bytes(info.stats.content_bytes as i64),
+                    embeddings: info.stats.embeddings,
+                })
+                .collect();
+
+            output.table(rows);
+        }
     }
 
     Ok(())
 }
 
+/// Chunks, content bytes and embeddings of each stored dataset
+///
+/// Read from the stores' counters rather than the chunks, so this stays fast
+/// on large workspaces. Empty when the backend holds no index, e.g. memory.
+async fn dataset_index_info(storage: &Storage) -> Result<Vec<DatasetIndexInfo>> {
+    let stats = IndexStatsService::new(storage.document.clone(), storage.vector.clone())
+        .stats()
+        .await
+        .context("Failed to read index counters")?;
+    if stats.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut datasets = storage.spatial.list_datasets().await?;
+    datasets.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(datasets
+        .into_iter()
+        .filter_map(|meta| {
+            let stats = stats.get(&meta.id).copied()?;
+            Some(DatasetIndexInfo { id: meta.id.0, name: meta.name, stats })
+        })
+        .collect())
+}
+
 /// Show CRS information
 fn show_crs(georag_dir: &Path, output: &OutputWriter, _is_part_of_all: bool) -> Result<()> {
     let config = load_workspace_config(georag_dir)?;
@@ -279,9 +435,137 @@ fn show_crs(georag_dir: &Path, output: &OutputWriter, _is_part_of_all: bool) ->
     Ok(())
 }
 
+/// Show the store workspace's usage against its quotas
+async fn show_usage(workspace_root: &Path, storage: &Storage, output: &OutputWriter) -> Result<()> {
+    let config =
+        load_workspace_config_with_store(workspace_root, storage.workspaces.as_ref()).await?;
+    let quotas = config.quotas();
+
+    // Nothing was added yet if the store workspace does not exist
+    let usage = match find_store_workspace(storage.workspaces.as_ref()).await? {
+        Some(id) => storage
+            .workspaces
+            .get_workspace_usage(id)
+            .await
+            .context("Failed to read workspace usage")?,
+        None => WorkspaceUsage::default(),
+    };
+
+    if output.is_json() {
+        output.result(InspectUsageOutput { usage, quotas })?;
+    } else {
+        output.section("Workspace Usage");
+        output.table(usage_rows(&usage, &quotas));
+    }
+
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct UsageRow {
+    #[tabled(rename = "Resource")]
+    resource: String,
+    #[tabled(rename = "Used")]
+    used: u64,
+    #[tabled(rename = "Limit")]
+    limit: String,
+    #[tabled(rename = "Remaining")]
+    remaining: String,
+}
+
+/// One row per quota resource; usage over a lowered limit leaves nothing remaining
+fn usage_rows(usage: &WorkspaceUsage, quotas: &WorkspaceQuotas) -> Vec<UsageRow> {
+    QuotaResource::ALL
+        .iter()
+        .map(|resource| {
+            let used = usage.get(*resource);
+            let limit = quotas.limit(*resource);
+            UsageRow {
+                resource: resource.to_string(),
+                used,
+                limit: format_quota(limit),
+                remaining: format_quota(limit.map(|limit| limit.saturating_sub(used))),
+            }
+        })
+        .collect()
+}
+
+/// Show the store workspace's activity per day over the last two weeks
+async fn show_timeline(storage: &Storage, output: &OutputWriter) -> Result<()> {
+    let granularity = TimelineGranularity::Day;
+    // Nothing happened yet if the store workspace does not exist
+    let timeline = match find_store_workspace(storage.workspaces.as_ref()).await? {
+        Some(id) => storage
+            .audit_service()
+            .timeline(id, None, granularity)
+            .await
+            .context("Failed to read workspace activity")?,
+        None => {
+            let until = Utc::now();
+            let since = until - Duration::days(DEFAULT_TIMELINE_DAYS);
+            Timeline::from_counts(since, until, granularity, &[], Vec::new())
+        }
+    };
+
+    if output.is_json() {
+        output.result(timeline)?;
+        return Ok(());
+    }
+
+    output.section("Workspace Activity");
+    output.table(timeline.buckets.iter().map(TimelineRow::from).collect::<Vec<_>>());
+
+    if !timeline.notable.is_empty() {
+        output.section("Notable Events");
+        for event in &timeline.notable {
+            output.info(format!(
+                "{}  {}  {}",
+                event.at.format("%Y-%m-%d %H:%M"),
+                event.kind,
+                event.details.as_deref().unwrap_or("")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct TimelineRow {
+    #[tabled(rename = "Day")]
+    day: String,
+    #[tabled(rename = "Added")]
+    added: u64,
+    #[tabled(rename = "Deleted")]
+    deleted: u64,
+    #[tabled(rename = "Builds")]
+    builds: u64,
+    #[tabled(rename = "Failed")]
+    failed: u64,
+    #[tabled(rename = "Queries")]
+    queries: u64,
+    #[tabled(rename = "Over Quota")]
+    over_quota: u64,
+}
+
+impl From<&TimelineBucket> for TimelineRow {
+    fn from(bucket: &TimelineBucket) -> Self {
+        let count = |kind| bucket.counts.get(&kind).copied().unwrap_or(0);
+        Self {
+            day: bucket.start.format("%Y-%m-%d").to_string(),
+            added: count(AuditEventKind::DatasetAdded),
+            deleted: count(AuditEventKind::DatasetDeleted),
+            builds: count(AuditEventKind::BuildCompleted),
+            failed: count(AuditEventKind::BuildFailed),
+            queries: count(AuditEventKind::Query),
+            over_quota: count(AuditEventKind::QuotaExceeded),
+        }
+    }
+}
+
 /// Show configuration
 fn show_config(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) -> Result<()> {
-    use georag_core::config::LayeredConfig;
+    use georag_core::config::{mask_config_value, ConfigSource, LayeredConfig};
 
     let config_path = georag_dir.join("config.toml");
 
@@ -290,7 +574,19 @@ fn show_config(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) -
         .unwrap_or_else(|_| LayeredConfig::with_defaults())
         .load_from_env();
 
-    let inspection_map = layered_config.to_inspection_map();
+    // Values are masked the same way as the API's admin config endpoint
+    let mut inspection_map = layered_config.to_inspection_map();
+    if let Ok(database_url) = std::env::var("DATABASE_URL") {
+        inspection_map
+            .insert("database_url".to_string(), (database_url, ConfigSource::Environment));
+    }
+    let inspection_map: HashMap<String, (String, ConfigSource)> = inspection_map
+        .into_iter()
+        .map(|(key, (value, source))| {
+            let value = mask_config_value(&key, &value);
+            (key, (value, source))
+        })
+        .collect();
 
     if output.is_json() {
         let crs_entry = inspection_map
@@ -377,19 +673,6 @@ fn show_config(georag_dir: &Path, output: &OutputWriter, is_part_of_all: bool) -
     Ok(())
 }
 
-fn find_workspace_root() -> Result<PathBuf> {
-    let mut current = std::env::current_dir()?;
-    loop {
-        let georag_dir = current.join(".georag");
-        if georag_dir.exists() && georag_dir.is_dir() {
-            return Ok(current);
-        }
-        if !current.pop() {
-            bail!("Not in a GeoRAG workspace. Run 'georag init' first.");
-        }
-    }
-}
-
 fn load_workspace_config(georag_dir: &Path) -> Result<WorkspaceConfig> {
     let config_path = georag_dir.join("config.toml");
     let config_content = fs::read_to_string(&config_path).context("Failed to read config.toml")?;
@@ -426,3 +709,33 @@ fn load_index_state(georag_dir: &Path) -> Result<IndexState> {
     let state: IndexState = serde_json::from_str(&content)?;
     Ok(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_rows() {
+        let usage = WorkspaceUsage {
+            datasets: 3,
+            features: 120,
+            chunks: 0,
+            blob_bytes: 0,
+        };
+        let quotas = WorkspaceQuotas {
+            max_datasets: Some(3),
+            max_features: Some(100),
+            ..Default::default()
+        };
+
+        let rows = usage_rows(&usage, &quotas);
+        assert_eq!(rows.len(), QuotaResource::ALL.len());
+        assert_eq!(rows[0].resource, "datasets");
+        assert_eq!(rows[0].remaining, "0");
+        // Over a limit lowered after the data was added
+        assert_eq!(rows[1].used, 120);
+        assert_eq!(rows[1].remaining, "0");
+        assert_eq!(rows[2].limit, "unlimited");
+        assert_eq!(rows[2].remaining, "unlimited");
+    }
+}
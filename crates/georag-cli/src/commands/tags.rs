@@ -0,0 +1,79 @@
+use crate::cli::TagsArgs;
+use crate::dry_run::{display_planned_actions, ActionType, PlannedAction};
+use crate::output::OutputWriter;
+use crate::output_types::TagsOutput;
+use crate::storage::Storage;
+use anyhow::{Context, Result};
+use georag_core::models::normalize_tags;
+
+pub async fn execute(
+    args: TagsArgs,
+    output: &OutputWriter,
+    dry_run: bool,
+    storage: &Storage,
+) -> Result<()> {
+    let datasets = storage.spatial.list_datasets().await?;
+    let dataset = datasets
+        .into_iter()
+        .find(|d| d.name == args.dataset)
+        .with_context(|| format!("Dataset not found: {}", args.dataset))?;
+
+    let new_tags = match (&args.set, args.clear) {
+        (Some(tags), _) => Some(normalize_tags(tags)),
+        (None, true) => Some(Vec::new()),
+        (None, false) => None,
+    };
+
+    let Some(tags) = new_tags else {
+        if output.is_json() {
+            output.result(TagsOutput {
+                dataset_name: dataset.name,
+                tags: dataset.tags,
+                changed: false,
+            })?;
+        } else {
+            output.kv("Dataset", &dataset.name);
+            output.kv("Tags", format_tags(&dataset.tags));
+        }
+        return Ok(());
+    };
+
+    if dry_run {
+        let action = PlannedAction::new(
+            ActionType::ModifyFile,
+            format!("Update tags of dataset '{}'", dataset.name),
+        )
+        .with_detail(format!("Current: {}", format_tags(&dataset.tags)))
+        .with_detail(format!("New: {}", format_tags(&tags)));
+        display_planned_actions(output, &[action]);
+        return Ok(());
+    }
+
+    storage
+        .spatial
+        .set_dataset_tags(dataset.id, &tags)
+        .await
+        .context("Failed to update dataset tags")?;
+
+    if output.is_json() {
+        output.result(TagsOutput {
+            dataset_name: dataset.name,
+            tags,
+            changed: true,
+        })?;
+    } else {
+        output.success(format!("Updated tags of dataset: {}", dataset.name));
+        output.kv("Tags", format_tags(&tags));
+    }
+
+    Ok(())
+}
+
+/// Render tags for display, noting that untagged datasets are public
+fn format_tags(tags: &[String]) -> String {
+    if tags.is_empty() {
+        "(none, visible to every API key)".to_string()
+    } else {
+        tags.join(", ")
+    }
+}
@@ -1,8 +1,12 @@
 use anyhow::{Context, Result};
-use georag_core::config::{CliConfigOverrides, LayeredConfig};
+use georag_core::config::{CliConfigOverrides, LayeredConfig, WorkspaceSettings};
+use georag_core::models::{WorkspaceConfig, WorkspaceId};
+use georag_service::WorkspaceQuota;
+use georag_store::ports::WorkspaceStore;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Complete configuration file structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -247,8 +251,22 @@ pub fn load_config_with_fallback(workspace_dir: &Path) -> Result<ConfigFile> {
     })
 }
 
+/// Environment variable naming the workspace to operate on
+pub const WORKSPACE_ENV: &str = "GEORAG_WORKSPACE";
+
 /// Find workspace root directory
-pub fn find_workspace_root() -> Result<PathBuf> {
+///
+/// Precedence: the `--workspace` flag, then `GEORAG_WORKSPACE`, then an
+/// upward search from the current directory.
+pub fn find_workspace_root(workspace: Option<&Path>) -> Result<PathBuf> {
+    if let Some(path) = workspace {
+        return check_workspace_root(path, "--workspace");
+    }
+
+    if let Some(path) = std::env::var_os(WORKSPACE_ENV).filter(|v| !v.is_empty()) {
+        return check_workspace_root(Path::new(&path), WORKSPACE_ENV);
+    }
+
     let mut current = std::env::current_dir()?;
     loop {
         let georag_dir = current.join(".georag");
@@ -261,6 +279,20 @@ pub fn find_workspace_root() -> Result<PathBuf> {
     }
 }
 
+/// Check that an explicitly selected path is a workspace root
+fn check_workspace_root(path: &Path, source: &str) -> Result<PathBuf> {
+    if !path.join(".georag").is_dir() {
+        anyhow::bail!(
+            "No GeoRAG workspace at {} (set by {}). Run 'georag init {}' first.",
+            path.display(),
+            source,
+            path.display()
+        );
+    }
+
+    Ok(fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()))
+}
+
 // ============================================================================
 // Layered configuration loading utilities
 // ============================================================================
@@ -277,12 +309,141 @@ pub fn load_workspace_config(workspace_root: &Path) -> Result<LayeredConfig> {
     Ok(config)
 }
 
-/// Load layered configuration with CLI overrides
-pub fn load_workspace_config_with_overrides(
+/// Load layered configuration for a workspace, including the stored settings
+pub async fn load_workspace_config_with_store(
     workspace_root: &Path,
+    store: &dyn WorkspaceStore,
+) -> Result<LayeredConfig> {
+    let config = load_workspace_config(workspace_root)?;
+    Ok(match load_stored_settings(store).await? {
+        Some(settings) => config.load_from_store(&settings),
+        None => config,
+    })
+}
+
+/// Load layered configuration with the stored settings and CLI overrides
+pub async fn load_workspace_config_with_overrides(
+    workspace_root: &Path,
+    store: &dyn WorkspaceStore,
     overrides: CliConfigOverrides,
 ) -> Result<LayeredConfig> {
-    let mut config = load_workspace_config(workspace_root)?;
+    let mut config = load_workspace_config_with_store(workspace_root, store).await?;
     config.update_from_cli(overrides);
     Ok(config)
 }
+
+// ============================================================================
+// Workspace settings shared with the store
+// ============================================================================
+
+/// Store workspace holding the CLI's settings
+///
+/// The PostgreSQL spatial store files datasets added by the CLI under a
+/// workspace of this name, so the settings live with them.
+pub const STORE_WORKSPACE: &str = "default";
+
+/// Settings in the workspace's config.toml
+pub fn read_file_settings(workspace_root: &Path) -> Result<WorkspaceSettings> {
+    let config_path = workspace_root.join(".georag").join("config.toml");
+    let content = fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+    WorkspaceSettings::from_toml(&content)
+        .with_context(|| format!("Failed to parse config file: {}", config_path.display()))
+}
+
+/// Replace the settings in config.toml, keeping its other tables
+///
+/// Comments in the file are not preserved.
+pub fn write_file_settings(workspace_root: &Path, settings: &WorkspaceSettings) -> Result<()> {
+    let config_path = workspace_root.join(".georag").join("config.toml");
+    let content = fs::read_to_string(&config_path).unwrap_or_default();
+    let mut table: toml::Table = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
+
+    // Top-level values are settings; tables such as [storage] are kept
+    table.retain(|_, value| value.is_table());
+    table.extend(settings.to_toml_table()?);
+
+    let content = toml::to_string_pretty(&table).context("Failed to serialize configuration")?;
+    fs::write(&config_path, content)
+        .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+
+    Ok(())
+}
+
+/// ID of the store workspace holding the CLI's settings, if it exists
+pub async fn find_store_workspace(store: &dyn WorkspaceStore) -> Result<Option<WorkspaceId>> {
+    Ok(store
+        .list_workspaces()
+        .await?
+        .into_iter()
+        .find(|workspace| workspace.name == STORE_WORKSPACE)
+        .map(|workspace| workspace.id))
+}
+
+/// Quotas and usage counters of the CLI's store workspace, creating it when needed
+///
+/// Limits come from the layered configuration, so environment variables and
+/// config.toml apply as well as the stored settings.
+pub async fn store_workspace_quota(
+    store: Arc<dyn WorkspaceStore>,
+    layered: &LayeredConfig,
+) -> Result<WorkspaceQuota> {
+    let id = match find_store_workspace(store.as_ref()).await? {
+        Some(id) => id,
+        None => {
+            let config = WorkspaceConfig {
+                crs: layered.crs.value,
+                distance_unit: layered.distance_unit.value,
+                geometry_validity: layered.geometry_validity.value,
+            };
+            store.create_workspace(STORE_WORKSPACE, &config).await?
+        }
+    };
+    Ok(WorkspaceQuota::new(store, id, layered.quotas()))
+}
+
+/// Settings stored for the CLI's workspace, if any were saved
+pub async fn load_stored_settings(store: &dyn WorkspaceStore) -> Result<Option<WorkspaceSettings>> {
+    match find_store_workspace(store).await? {
+        Some(id) => Ok(store.get_workspace_settings(id).await?),
+        None => Ok(None),
+    }
+}
+
+/// Replace the stored settings, creating the store workspace when needed
+pub async fn save_stored_settings(
+    store: &dyn WorkspaceStore,
+    settings: &WorkspaceSettings,
+) -> Result<()> {
+    let id = match find_store_workspace(store).await? {
+        Some(id) => id,
+        None => {
+            let config = WorkspaceConfig {
+                crs: settings.crs.unwrap_or(4326),
+                distance_unit: settings.distance_unit.unwrap_or_default(),
+                geometry_validity: settings.geometry_validity.unwrap_or_default(),
+            };
+            store.create_workspace(STORE_WORKSPACE, &config).await?
+        }
+    };
+    store.save_workspace_settings(id, settings).await?;
+    Ok(())
+}
+
+/// Write the config file's settings through to the store unless it holds some
+///
+/// Run by init, add and build so a workspace used from the CLI has its
+/// settings in the store before the API serves it. Settings already stored
+/// are left alone; `georag config push` replaces them. Returns whether the
+/// settings were written.
+pub async fn seed_stored_settings(
+    store: &dyn WorkspaceStore,
+    workspace_root: &Path,
+) -> Result<bool> {
+    if load_stored_settings(store).await?.is_some() {
+        return Ok(false);
+    }
+    save_stored_settings(store, &read_file_settings(workspace_root)?).await?;
+    Ok(true)
+}
@@ -0,0 +1,84 @@
+//! CLI error types and process exit codes
+//!
+//! The `georag` binary exits with one of the following codes so scripts and CI
+//! pipelines can branch on the outcome of a command:
+//!
+//! | Code | Meaning         | Description                                                   |
+//! |------|-----------------|---------------------------------------------------------------|
+//! | `0`  | Success         | Command completed successfully                                |
+//! | `1`  | Error           | Command failed (see error message)                            |
+//! | `2`  | Usage Error     | Invalid arguments or options                                  |
+//! | `3`  | Partial Failure | Batch finished, some files failed, successful files were kept |
+//! | `4`  | Aborted         | Batch stopped by its failure policy with files left over      |
+
+/// Command completed successfully
+pub const EXIT_SUCCESS: i32 = 0;
+
+/// Command failed
+pub const EXIT_ERROR: i32 = 1;
+
+/// Invalid arguments or options (reported by clap)
+pub const EXIT_USAGE: i32 = 2;
+
+/// Batch completed with some failures; successfully processed files were kept
+pub const EXIT_PARTIAL_FAILURE: i32 = 3;
+
+/// Batch was stopped early by its failure policy
+pub const EXIT_ABORTED: i32 = 4;
+
+/// Errors that carry a specific exit code
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// Some files in a batch failed while others were added
+    #[error("{failed} of {total} files failed to process ({succeeded} added)")]
+    BatchPartialFailure {
+        failed: usize,
+        succeeded: usize,
+        total: usize,
+    },
+
+    /// A batch was stopped before all files were processed
+    #[error("Batch aborted after {failed} failure(s): {succeeded} added, {skipped} not processed")]
+    BatchAborted {
+        failed: usize,
+        succeeded: usize,
+        skipped: usize,
+    },
+}
+
+impl CliError {
+    /// Exit code for this error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::BatchPartialFailure { .. } => EXIT_PARTIAL_FAILURE,
+            CliError::BatchAborted { .. } => EXIT_ABORTED,
+        }
+    }
+}
+
+/// Map an error returned by a command to the process exit code
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<CliError>().map(CliError::exit_code).unwrap_or(EXIT_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code_for_cli_errors() {
+        let partial: anyhow::Error =
+            CliError::BatchPartialFailure { failed: 1, succeeded: 2, total: 3 }.into();
+        assert_eq!(exit_code(&partial), EXIT_PARTIAL_FAILURE);
+
+        let aborted: anyhow::Error =
+            CliError::BatchAborted { failed: 1, succeeded: 0, skipped: 5 }.into();
+        assert_eq!(exit_code(&aborted), EXIT_ABORTED);
+    }
+
+    #[test]
+    fn test_exit_code_for_other_errors() {
+        let err = anyhow::anyhow!("something went wrong");
+        assert_eq!(exit_code(&err), EXIT_ERROR);
+    }
+}
@@ -0,0 +1,122 @@
+//! Filter and association geometries given on the command line
+//!
+//! `add --geometry`, `query --geometry` and the `--bbox` flags of `query` and
+//! `export` share these parsers so the same inputs are accepted everywhere.
+//! `add --places` reads a file of named places.
+
+use anyhow::{bail, Context, Result};
+use georag_core::geo::Gazetteer;
+use georag_core::models::Geometry;
+use std::fs;
+use std::path::PathBuf;
+
+/// Parse geometry argument - can be inline GeoJSON or path to file
+pub fn parse_geometry_argument(geometry_arg: &str) -> Result<serde_json::Value> {
+    // Try to parse as JSON first (inline geometry)
+    if let Ok(geom) = serde_json::from_str::<serde_json::Value>(geometry_arg) {
+        // Validate it's a valid GeoJSON geometry
+        if geom.get("type").is_some() && geom.get("coordinates").is_some() {
+            return Ok(geom);
+        }
+    }
+
+    // Try to read as file path
+    let path = PathBuf::from(geometry_arg);
+    if path.exists() {
+        let content = fs::read_to_string(&path).context("Failed to read geometry file")?;
+
+        // Parse the file content
+        let geojson: serde_json::Value =
+            serde_json::from_str(&content).context("Failed to parse geometry file as JSON")?;
+
+        // Extract geometry from GeoJSON
+        if let Some(geom_type) = geojson.get("type") {
+            match geom_type.as_str() {
+                Some("Feature") => {
+                    // Extract geometry from Feature
+                    if let Some(geometry) = geojson.get("geometry") {
+                        return Ok(geometry.clone());
+                    }
+                }
+                Some("FeatureCollection") => {
+                    // Use geometry from first feature
+                    if let Some(features) = geojson.get("features").and_then(|f| f.as_array()) {
+                        if let Some(first_feature) = features.first() {
+                            if let Some(geometry) = first_feature.get("geometry") {
+                                return Ok(geometry.clone());
+                            }
+                        }
+                    }
+                }
+                Some("Point")
+                | Some("LineString")
+                | Some("Polygon")
+                | Some("MultiPoint")
+                | Some("MultiLineString")
+                | Some("MultiPolygon") => {
+                    // It's already a geometry
+                    return Ok(geojson);
+                }
+                _ => {}
+            }
+        }
+
+        bail!("Geometry file does not contain valid GeoJSON geometry");
+    }
+
+    bail!("Geometry argument must be valid GeoJSON geometry string or path to GeoJSON file");
+}
+
+/// Read a GeoJSON FeatureCollection of places, each with a `name` property
+pub fn parse_places_argument(path: &str) -> Result<Gazetteer> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read places file '{}'", path))?;
+    let geojson: serde_json::Value =
+        serde_json::from_str(&content).context("Failed to parse places file as JSON")?;
+
+    Ok(Gazetteer::from_geojson(&geojson)?)
+}
+
+/// Parse "min_lon,min_lat,max_lon,max_lat"
+pub fn parse_bbox(value: &str) -> Result<[f64; 4]> {
+    let parts: Vec<f64> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .with_context(|| format!("Invalid bounding box '{}': expected four numbers", value))?;
+
+    let [min_x, min_y, max_x, max_y] = parts[..] else {
+        bail!("Invalid bounding box '{}': expected min_lon,min_lat,max_lon,max_lat", value);
+    };
+    if ![min_x, min_y, max_x, max_y].iter().all(|v| v.is_finite()) || min_x > max_x || min_y > max_y
+    {
+        bail!("Invalid bounding box '{}': the minimum must not exceed the maximum", value);
+    }
+
+    Ok([min_x, min_y, max_x, max_y])
+}
+
+/// Parse a "lon,lat" point
+pub fn parse_point(value: &str) -> Result<[f64; 2]> {
+    let parts: Vec<f64> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<std::result::Result<_, _>>()
+        .with_context(|| format!("Invalid point '{}': expected two numbers", value))?;
+
+    let [lon, lat] = parts[..] else {
+        bail!("Invalid point '{}': expected lon,lat", value);
+    };
+    Ok([lon, lat])
+}
+
+/// Polygon covering a bounding box, ring closed and counter-clockwise
+pub fn bbox_geometry([min_x, min_y, max_x, max_y]: [f64; 4]) -> Geometry {
+    Geometry::polygon(vec![vec![
+        [min_x, min_y],
+        [max_x, min_y],
+        [max_x, max_y],
+        [min_x, max_y],
+        [min_x, min_y],
+    ]])
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use dialoguer::{Confirm, Input, Select};
+use georag_core::config::mask_url_credentials;
 use std::path::PathBuf;
 
 /// Interactive workspace initialization
@@ -115,9 +116,7 @@ pub fn interactive_init() -> Result<InteractiveInitResult> {
     println!("  Validity Mode:  {}", validity_mode);
     println!("  Storage:        {}", if use_postgres { "PostgreSQL" } else { "Memory" });
     if let Some(ref url) = database_url {
-        // Hide password in display
-        let display_url = url.split('@').next_back().unwrap_or(url);
-        println!("  Database:       {}", display_url);
+        println!("  Database:       {}", mask_url_credentials(url));
     }
     println!();
 
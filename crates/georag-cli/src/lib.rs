@@ -0,0 +1,72 @@
+//! The `georag` command-line interface
+//!
+//! The `georag` binary runs [`main_with_formats`] with the built-in format
+//! readers. Downstream crates add their own readers by building a binary that
+//! hands a registry with them to [`main_with_formats`]:
+//!
+//! ```ignore
+//! fn main() {
+//!     let formats = FormatRegistry::with_defaults().with_reader(Box::new(SurveyReader));
+//!     georag_cli::main_with_formats(formats);
+//! }
+//! ```
+
+mod auto_detect;
+mod batch;
+mod cli;
+mod commands;
+mod config;
+mod dry_run;
+mod errors;
+mod geometry_arg;
+mod interactive;
+mod lock;
+mod output;
+mod output_types;
+mod progress;
+mod storage;
+
+use anyhow::Result;
+use clap::Parser;
+use cli::Cli;
+use georag_core::formats::FormatRegistry;
+
+/// Run the CLI with the readers of `formats`, exiting with its exit code on failure
+pub fn main_with_formats(formats: FormatRegistry) {
+    if let Err(err) = run(formats) {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(errors::exit_code(&err));
+    }
+}
+
+fn run(formats: FormatRegistry) -> Result<()> {
+    // Initialize tracing
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    // Parse CLI arguments; help and version go to stdout and exit successfully
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            let code = if err.use_stderr() {
+                errors::EXIT_USAGE
+            } else {
+                errors::EXIT_SUCCESS
+            };
+            std::process::exit(code);
+        }
+    };
+
+    // Create async runtime
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    // Execute the command
+    runtime.block_on(async { commands::execute(cli, formats).await })?;
+
+    Ok(())
+}
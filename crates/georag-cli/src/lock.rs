@@ -0,0 +1,74 @@
+//! Workspace build lock
+//!
+//! `georag build` and `georag db compact` both rewrite the index stores and
+//! must not run at the same time. The lock is a file in `.georag` created
+//! exclusively and removed when the guard is dropped.
+
+use anyhow::{bail, Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the lock file inside `.georag`
+pub(crate) const LOCK_FILE: &str = "build.lock";
+
+/// Held build lock; released on drop
+#[derive(Debug)]
+pub struct BuildLock {
+    path: PathBuf,
+}
+
+impl BuildLock {
+    /// Take the build lock of a workspace, failing if another process holds it
+    pub fn acquire(georag_dir: &Path, operation: &str) -> Result<Self> {
+        let path = georag_dir.join(LOCK_FILE);
+
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let holder = fs::read_to_string(&path).unwrap_or_default();
+                bail!(
+                    "Another build or compaction is running ({}).\n\
+                     If no georag process is running, remove {} and retry.",
+                    holder.trim(),
+                    path.display()
+                );
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to create lock file {}", path.display()))
+            }
+        };
+
+        writeln!(file, "{} by pid {}", operation, std::process::id())
+            .with_context(|| format!("Failed to write lock file {}", path.display()))?;
+
+        Ok(Self { path })
+    }
+}
+
+impl Drop for BuildLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = std::env::temp_dir().join(format!("georag-lock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let lock = BuildLock::acquire(&dir, "build").unwrap();
+        let err = BuildLock::acquire(&dir, "compaction").unwrap_err();
+        assert!(err.to_string().contains("build by pid"));
+
+        drop(lock);
+        assert!(BuildLock::acquire(&dir, "compaction").is_ok());
+        assert!(!dir.join(LOCK_FILE).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,35 +1,5 @@
-mod auto_detect;
-mod batch;
-mod cli;
-mod commands;
-mod config;
-mod dry_run;
-mod interactive;
-mod output;
-mod output_types;
-mod storage;
+use georag_core::formats::FormatRegistry;
 
-use anyhow::Result;
-use clap::Parser;
-use cli::Cli;
-
-fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
-        )
-        .init();
-
-    // Parse CLI arguments
-    let cli = Cli::parse();
-
-    // Create async runtime
-    let runtime = tokio::runtime::Runtime::new()?;
-
-    // Execute the command
-    runtime.block_on(async { commands::execute(cli).await })?;
-
-    Ok(())
+fn main() {
+    georag_cli::main_with_formats(FormatRegistry::with_defaults());
 }
@@ -1,17 +1,21 @@
 use console::style;
 use serde::Serialize;
 use std::fmt::Display;
-use tabled::{settings::Style, Table, Tabled};
+use tabled::{builder::Builder, settings::Style, Table, Tabled};
 
 /// Output format mode
 #[derive(Debug, Clone, Copy)]
 pub enum OutputFormat {
     Human,
     Json,
+    /// Suppress everything but warnings and errors
+    Quiet,
 }
 
+#[derive(Clone)]
 pub struct OutputWriter {
     format: OutputFormat,
+    verbose: bool,
 }
 
 impl OutputWriter {
@@ -22,6 +26,32 @@ impl OutputWriter {
             } else {
                 OutputFormat::Human
             },
+            verbose: false,
+        }
+    }
+
+    /// Create a writer that only prints warnings and errors
+    ///
+    /// Used when stdout carries machine-readable output written elsewhere.
+    pub fn quiet() -> Self {
+        Self {
+            format: OutputFormat::Quiet,
+            verbose: false,
+        }
+    }
+
+    /// Enable verbose output
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Print a detail message, only in verbose mode
+    ///
+    /// Written to stderr so JSON output on stdout stays parseable.
+    pub fn verbose(&self, message: impl Display) {
+        if self.verbose {
+            eprintln!("{} {}", style("·").dim(), style(message).dim());
         }
     }
 
@@ -30,6 +60,7 @@ impl OutputWriter {
             OutputFormat::Human => {
                 println!("{} {}", style("✓").green().bold(), message);
             }
+            OutputFormat::Quiet => {}
             OutputFormat::Json => {
                 let output = serde_json::json!({
                     "status": "success",
@@ -45,6 +76,7 @@ impl OutputWriter {
             OutputFormat::Human => {
                 println!("{} {}", style("ℹ").blue().bold(), message);
             }
+            OutputFormat::Quiet => {}
             OutputFormat::Json => {
                 let output = serde_json::json!({
                     "status": "info",
@@ -57,7 +89,7 @@ impl OutputWriter {
 
     pub fn warning(&self, message: impl Display) {
         match self.format {
-            OutputFormat::Human => {
+            OutputFormat::Human | OutputFormat::Quiet => {
                 eprintln!("{} {}", style("⚠").yellow().bold(), message);
             }
             OutputFormat::Json => {
@@ -71,7 +103,7 @@ impl OutputWriter {
     }
     pub fn error(&self, message: impl Display) {
         match self.format {
-            OutputFormat::Human => {
+            OutputFormat::Human | OutputFormat::Quiet => {
                 eprintln!("{} {}", style("✗").red().bold(), message);
             }
             OutputFormat::Json => {
@@ -95,6 +127,7 @@ impl OutputWriter {
                     println!("{}", table);
                 }
             }
+            OutputFormat::Quiet => {}
             OutputFormat::Json => {
                 println!(
                     "{}",
@@ -107,6 +140,22 @@ impl OutputWriter {
         }
     }
 
+    /// Table with columns chosen at run time, e.g. by `--fields`
+    pub fn grid(&self, header: &[&str], rows: Vec<Vec<String>>) {
+        if let OutputFormat::Human = self.format {
+            if rows.is_empty() {
+                println!("{}", style("(no data)").dim());
+            } else {
+                let mut builder = Builder::default();
+                builder.push_record(header.iter().copied());
+                rows.into_iter().for_each(|row| builder.push_record(row));
+                let mut table = builder.build();
+                table.with(Style::rounded());
+                println!("{}", table);
+            }
+        }
+    }
+
     pub fn data<T: Serialize>(&self, data: &T) -> anyhow::Result<()> {
         let json_str = serde_json::to_string_pretty(data)?;
         println!("{}", json_str);
@@ -118,6 +167,7 @@ impl OutputWriter {
             OutputFormat::Human => {
                 self.data(&data)?;
             }
+            OutputFormat::Quiet => {}
             OutputFormat::Json => {
                 let output = serde_json::json!({
                     "status": "success",
@@ -134,6 +184,7 @@ impl OutputWriter {
             OutputFormat::Human => {
                 println!("{}: {}", style(key).bold(), value);
             }
+            OutputFormat::Quiet => {}
             OutputFormat::Json => {
                 let output = serde_json::json!({
                     key.to_string(): value.to_string(),
@@ -148,11 +199,19 @@ impl OutputWriter {
             OutputFormat::Human => {
                 println!("\n{}", style(title).bold().underlined());
             }
-            OutputFormat::Json => {}
+            OutputFormat::Json | OutputFormat::Quiet => {}
         }
     }
 
     pub fn is_json(&self) -> bool {
         matches!(self.format, OutputFormat::Json)
     }
+
+    pub fn is_quiet(&self) -> bool {
+        matches!(self.format, OutputFormat::Quiet)
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
 }
@@ -1,6 +1,18 @@
+use crate::batch::BatchOutcome;
 use chrono::{DateTime, Utc};
-use georag_core::models::GeometryType;
+use georag_core::config::SettingDifference;
+use georag_core::formats::{FeatureError, FormatDescription, InspectMethod, TransformErrors};
+use georag_core::geo::OversizedFeature;
+use georag_core::models::{
+    AxisOrderDecision, BuildDiff, DatasetIndexStats, DatasetPreview, GeometryType, QueryPreset,
+    RemoteSource, ResultConfidence, SourceFile, SpatialFilter, WorkspaceQuotas, WorkspaceUsage,
+};
+use georag_core::progress::PhaseRate;
+use georag_retrieval::timing::QueryTimings;
+use georag_retrieval::{Diagnostic, QueryOperators, SpatialMatch};
+use georag_service::PipelineStats;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
 /// Output for init command
 #[derive(Debug, Serialize)]
@@ -19,6 +31,77 @@ pub struct AddOutput {
     pub feature_count: usize,
     pub crs: u32,
     pub crs_mismatch: Option<CrsMismatchInfo>,
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Features skipped because they could not be read
+    pub features_skipped: usize,
+    pub feature_errors: Vec<FeatureError>,
+    /// Features over the vertex limit that were simplified or subdivided
+    pub oversized_features: Vec<OversizedFeature>,
+    /// Features each workspace transform failed on
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transform_errors: Vec<TransformErrors>,
+    /// Axis order the coordinates were read with (GeoJSON only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_order: Option<AxisOrderDecision>,
+    /// Original file kept in the blob store
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceFile>,
+    /// URL the dataset was downloaded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<RemoteSource>,
+    /// How the file moved through the ingest pipeline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<PipelineOutput>,
+    /// Average rate of each phase, for comparing runs
+    pub throughput: Vec<PhaseRate>,
+}
+
+/// Ingest pipeline stats of an added dataset
+#[derive(Debug, Serialize)]
+pub struct PipelineOutput {
+    pub batch_size: usize,
+    pub channel_capacity: usize,
+    pub batches: usize,
+    pub peak_features_in_flight: usize,
+    pub elapsed_ms: u64,
+    pub stages: Vec<StageOutput>,
+}
+
+/// Throughput of one ingest pipeline stage
+#[derive(Debug, Serialize)]
+pub struct StageOutput {
+    pub stage: String,
+    pub features: usize,
+    pub busy_ms: u64,
+    pub waiting_ms: u64,
+    pub features_per_second: f64,
+}
+
+impl From<&PipelineStats> for PipelineOutput {
+    fn from(stats: &PipelineStats) -> Self {
+        Self {
+            batch_size: stats.buffers.batch_size,
+            channel_capacity: stats.buffers.channel_capacity,
+            batches: stats.batches,
+            peak_features_in_flight: stats.peak_features_in_flight,
+            elapsed_ms: stats.elapsed.as_millis() as u64,
+            stages: stats
+                .stages
+                .iter()
+                .map(|stage| StageOutput {
+                    stage: stage.stage.to_string(),
+                    features: stage.features,
+                    busy_ms: stage.busy.as_millis() as u64,
+                    waiting_ms: stage.waiting.as_millis() as u64,
+                    features_per_second: stage.features_per_second(),
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -27,15 +110,341 @@ pub struct CrsMismatchInfo {
     pub workspace_crs: u32,
 }
 
+/// Output for tags command
+#[derive(Debug, Serialize)]
+pub struct TagsOutput {
+    pub dataset_name: String,
+    pub tags: Vec<String>,
+    pub changed: bool,
+}
+
+/// Output for join command
+#[derive(Debug, Serialize)]
+pub struct JoinOutput {
+    pub target: String,
+    pub source: String,
+    pub predicate: String,
+    pub properties: Vec<String>,
+    pub matched: usize,
+    pub missed: usize,
+    pub pushed_down: bool,
+}
+
+/// Output for dataset sample command, shaped as a GeoJSON FeatureCollection
+#[derive(Debug, Serialize)]
+pub struct SampleOutput {
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub dataset: String,
+    pub strategy: String,
+    pub features: Vec<serde_json::Value>,
+}
+
+/// Output for diff command
+#[derive(Debug, Serialize)]
+pub struct DiffOutput {
+    pub dataset_name: String,
+    pub path: String,
+    pub key: String,
+    pub matched_by_key: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub geometry_changed: usize,
+    pub property_changed: usize,
+    pub unchanged: usize,
+    pub samples: Vec<DiffSample>,
+    pub applied: Option<DiffApplyOutput>,
+}
+
+/// One sampled feature of a diff
+#[derive(Debug, Serialize)]
+pub struct DiffSample {
+    /// added, removed, geometry_changed or property_changed
+    pub change: String,
+    /// Stored feature ID; absent for added features
+    pub feature_id: Option<u64>,
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffApplyOutput {
+    pub features_upserted: usize,
+    pub features_deleted: usize,
+    pub chunks_invalidated: usize,
+}
+
+/// Output for dataset delete command
+#[derive(Debug, Serialize)]
+pub struct DatasetDeleteOutput {
+    pub action: String,
+    pub confirmed: bool,
+    pub datasets_scanned: usize,
+    pub matched: usize,
+    pub feature_count: usize,
+    pub source_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<usize>,
+    pub datasets: Vec<DatasetDeleteEntry>,
+}
+
+/// One dataset of the dataset delete command
+#[derive(Debug, Serialize)]
+pub struct DatasetDeleteEntry {
+    pub name: String,
+    pub added_at: DateTime<Utc>,
+    pub feature_count: usize,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Output for dataset repair-axes command
+#[derive(Debug, Serialize)]
+pub struct RepairAxesOutput {
+    pub dataset_name: String,
+    pub features_scanned: usize,
+    pub flagged: usize,
+    pub undecided: usize,
+    pub applied: bool,
+    pub chunks_invalidated: usize,
+}
+
+/// Output for config check command
+#[derive(Debug, Serialize)]
+pub struct ConfigCheckOutput {
+    pub stored: bool,
+    pub in_sync: bool,
+    pub conflicts: usize,
+    pub differences: Vec<SettingDifference>,
+    /// Settings with invalid values, prefixed with where they are set
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub invalid: Vec<String>,
+}
+
+/// Output for config pull and push commands
+#[derive(Debug, Serialize)]
+pub struct ConfigSyncOutput {
+    pub action: &'static str,
+    pub changed: Vec<String>,
+}
+
+/// A query preset in preset list and show output
+#[derive(Debug, Serialize)]
+pub struct PresetOutput {
+    pub name: String,
+    pub default: bool,
+    pub options: QueryPreset,
+}
+
+/// Output for preset set, delete and default commands
+#[derive(Debug, Serialize)]
+pub struct PresetChangeOutput {
+    pub action: &'static str,
+    pub preset: Option<String>,
+    /// Default preset after the change
+    pub default_preset: Option<String>,
+}
+
+/// Output for geo buffer command
+#[derive(Debug, Serialize)]
+pub struct BufferOutput {
+    pub input: String,
+    pub output: String,
+    pub distance_meters: f64,
+    pub buffered: usize,
+    pub skipped: usize,
+}
+
+/// Output for convert command
+#[derive(Debug, Serialize)]
+pub struct ConvertOutput {
+    pub input: String,
+    pub source_format: String,
+    pub format: String,
+    pub source_crs: u32,
+    pub crs: u32,
+    pub files: Vec<ConvertedFile>,
+    pub simplified: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub feature_errors: Vec<FeatureError>,
+}
+
+/// A file written by the convert command
+#[derive(Debug, Serialize)]
+pub struct ConvertedFile {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    pub features: usize,
+}
+
+/// Output for formats list command
+#[derive(Debug, Serialize)]
+pub struct FormatsOutput {
+    pub formats: Vec<FormatDescription>,
+}
+
+/// Output for inspect command
+#[derive(Debug, Serialize)]
+pub struct InspectFileOutput {
+    pub path: String,
+    pub format: String,
+    pub method: InspectMethod,
+    pub crs: Option<u32>,
+    pub feature_count: Option<usize>,
+    pub extent: Option<[f64; 4]>,
+    pub property_keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<usize>,
+    pub sampled_features: usize,
+    pub sample_ms: u64,
+    pub estimated_ingest_ms: Option<u64>,
+    pub workspace_extent: Option<[f64; 4]>,
+    pub overlaps_workspace: Option<bool>,
+}
+
+/// Output for export command
+#[derive(Debug, Serialize)]
+pub struct ExportOutput {
+    pub output: String,
+    pub format_version: u32,
+    pub bbox: [f64; 4],
+    pub datasets: usize,
+    pub features: usize,
+    pub chunks: usize,
+    pub embeddings: usize,
+    /// Attribution lines the bundled datasets require
+    pub attributions: Vec<String>,
+}
+
+/// Output for batch add command
+#[derive(Debug, Serialize)]
+pub struct BatchOutput {
+    pub outcome: BatchOutcome,
+    pub exit_code: i32,
+    pub total_files: usize,
+    pub successful: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub datasets: Vec<String>,
+    pub failures: Vec<BatchFailureInfo>,
+    /// Average rate of each phase, for comparing runs
+    pub throughput: Vec<PhaseRate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchFailureInfo {
+    pub path: String,
+    pub format_name: String,
+    pub error: String,
+}
+
+/// Output for db compact command
+#[derive(Debug, Serialize)]
+pub struct CompactOutput {
+    pub embeddings_scanned: usize,
+    pub chunks_scanned: usize,
+    pub orphan_embeddings: usize,
+    pub dangling_chunks: usize,
+    pub degenerate_chunks: usize,
+    pub reclaimable_bytes: u64,
+    pub applied: bool,
+    pub embeddings_deleted: usize,
+    pub chunks_deleted: usize,
+    pub degenerate_deleted: usize,
+}
+
+/// Output for db gc command
+#[derive(Debug, Serialize)]
+pub struct GcOutput {
+    pub chunks_scanned: usize,
+    pub orphaned_chunks: usize,
+    pub stale_chunks: usize,
+    pub already_stale: usize,
+    pub applied: bool,
+    pub chunks_deleted: usize,
+    pub chunks_marked_stale: usize,
+}
+
+/// Output for db verify command
+#[derive(Debug, Serialize)]
+pub struct VerifyOutput {
+    pub deep: bool,
+    pub consistent: bool,
+    pub drift: Vec<IndexDriftInfo>,
+    /// Chunks without a dataset; counted with `--deep` only
+    pub unattributed_chunks: usize,
+    pub recounted: bool,
+}
+
+/// Counters that disagree with the stored data
+#[derive(Debug, Serialize)]
+pub struct IndexDriftInfo {
+    /// Dataset name, `null` for the totals of a quick check
+    pub dataset: Option<String>,
+    pub counted: DatasetIndexStats,
+    pub actual: DatasetIndexStats,
+}
+
+/// Output for db upgrade command
+#[derive(Debug, Serialize)]
+pub struct UpgradeOutput {
+    /// Schema version the index had
+    pub from_version: u32,
+    /// Schema version the index has now; unchanged on a dry run
+    pub to_version: u32,
+    /// Schema version of this build
+    pub current_version: u32,
+    pub steps: Vec<UpgradeStepInfo>,
+    /// Copy of the workspace data taken before the first step ran
+    pub backup: Option<String>,
+    pub applied: bool,
+}
+
+/// Step of an index upgrade
+#[derive(Debug, Serialize)]
+pub struct UpgradeStepInfo {
+    pub from: u32,
+    pub to: u32,
+    pub description: String,
+}
+
 /// Output for build command
 #[derive(Debug, Serialize)]
 pub struct BuildOutput {
     pub index_hash: String,
     pub chunk_count: usize,
+    /// Chunks left out because their content had no letters or digits
+    pub chunks_skipped: usize,
+    /// IDs of chunks the embedder gave no usable vector, left out of retrieval
+    pub unembedded_chunks: Vec<u64>,
+    /// Datasets whose chunks the build generated
+    pub datasets: Vec<String>,
+    /// Chunks of deleted features removed after the build
+    pub outdated_chunks_deleted: usize,
+    /// Chunks of changed features left out of retrieval until rebuilt
+    pub stale_chunks: usize,
     pub embedding_dim: usize,
     pub embedder: String,
     pub normalized_count: usize,
     pub fixed_count: usize,
+    /// Chunks embedded by earlier attempts when the build was resumed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumed_chunks: Option<usize>,
+    pub attempts: u32,
+    pub wall_time_secs: f64,
+    /// Average rate of each phase, for comparing runs
+    pub throughput: Vec<PhaseRate>,
+    /// What the build changed compared with the previous build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<BuildDiff>,
+    /// Tokenizer the chunks were sized with, when sized in tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
 }
 
 /// Output for query command
@@ -44,7 +453,34 @@ pub struct QueryOutput {
     pub query: String,
     pub spatial_matches: usize,
     pub results: Vec<QueryResultItem>,
+    pub filtered_by_threshold: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_groups: Option<Vec<TimeBucketInfo>>,
     pub explanation: Option<String>,
+    /// Per-phase wall time, with --explain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<QueryTimings>,
+    /// Spatial filter exactly as sent to the pipeline, with --explain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial_filter: Option<SpatialFilter>,
+    /// Likely causes of an empty result and what to try next
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+    /// Attribution lines of the datasets the results come from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributions: Vec<String>,
+    /// How operators in the query were interpreted, with --parse-operators
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operators: Option<QueryOperators>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeBucketInfo {
+    pub bucket: String,
+    pub count: usize,
+    pub top_sources: Vec<String>,
+    pub max_score: Option<f32>,
+    pub median_score: Option<f32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -52,6 +488,29 @@ pub struct QueryResultItem {
     pub content: String,
     pub source: String,
     pub score: Option<f32>,
+    /// Whether the chunk or its feature matched the spatial filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spatial_match: Option<SpatialMatch>,
+    /// Link to the source's document, when a source URL template is configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Percentile of the semantic score in the index's score baseline, and its label
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ResultConfidence>,
+}
+
+/// Output for self-test command
+#[derive(Debug, Serialize)]
+pub struct SelfTestOutput {
+    pub passed: bool,
+    pub stages: Vec<SelfTestStageInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfTestStageInfo {
+    pub name: String,
+    pub status: String,
+    pub detail: String,
 }
 
 /// Output for inspect datasets command
@@ -68,6 +527,13 @@ pub struct DatasetInfo {
     pub feature_count: usize,
     pub crs: u32,
     pub added_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+    /// Content preview, included with `--verbose`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<DatasetPreview>,
 }
 
 /// Output for inspect index command
@@ -75,10 +541,24 @@ pub struct DatasetInfo {
 pub struct InspectIndexOutput {
     pub built: bool,
     pub hash: Option<String>,
+    /// Schema version the index was written with
+    pub schema_version: Option<u32>,
     pub built_at: Option<DateTime<Utc>>,
     pub embedder: Option<String>,
     pub chunk_count: Option<usize>,
     pub embedding_dim: Option<usize>,
+    /// When each dataset was last built into the index, by dataset name
+    pub dataset_built_at: BTreeMap<String, DateTime<Utc>>,
+    /// What the store holds of each dataset, from its counters
+    pub datasets: Vec<DatasetIndexInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetIndexInfo {
+    pub id: u64,
+    pub name: String,
+    #[serde(flatten)]
+    pub stats: DatasetIndexStats,
 }
 
 /// Output for inspect CRS command
@@ -95,6 +575,14 @@ pub struct DatasetCrsInfo {
     pub matches_workspace: bool,
 }
 
+/// Output for status --usage
+#[derive(Debug, Serialize)]
+pub struct InspectUsageOutput {
+    pub usage: WorkspaceUsage,
+    /// Effective limits; `null` means unlimited
+    pub quotas: WorkspaceQuotas,
+}
+
 /// Output for inspect config command
 #[derive(Debug, Serialize)]
 pub struct InspectConfigOutput {
@@ -0,0 +1,231 @@
+//! Progress of long-running phases with rate and ETA
+//!
+//! The rate is a moving average from [`RateEstimator`]. On a terminal each
+//! phase gets a progress bar showing it with the ETA. When stdout is not a
+//! terminal the same numbers are logged every few seconds instead, and with
+//! `--json` they are written to stderr as one-line JSON events, so the result
+//! on stdout stays parseable.
+
+use crate::output::OutputWriter;
+use console::Term;
+use georag_core::progress::{PhaseRate, RateEstimator};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::{Duration, Instant};
+
+/// How often progress is logged when there is no progress bar
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where progress goes
+enum Display {
+    Bar(ProgressBar),
+    /// Periodic log lines, or JSON events with `--json`
+    Log {
+        output: OutputWriter,
+        logged: Option<Duration>,
+    },
+    Hidden,
+}
+
+/// Progress of one phase, e.g. the files of a batch or the chunks being embedded
+pub struct PhaseProgress {
+    phase: &'static str,
+    label: String,
+    started: Instant,
+    /// When `done` was last set
+    updated: Duration,
+    estimator: RateEstimator,
+    display: Display,
+}
+
+impl PhaseProgress {
+    /// Start showing progress of `phase`, with `total` items if known
+    pub fn start(
+        output: &OutputWriter,
+        phase: &'static str,
+        label: impl Into<String>,
+        total: Option<usize>,
+    ) -> Self {
+        let label = label.into();
+        let mut estimator = RateEstimator::default();
+        if let Some(total) = total {
+            estimator = estimator.with_total(total as u64);
+        }
+
+        let display = if output.is_quiet() {
+            Display::Hidden
+        } else if !output.is_json() && Term::stdout().is_term() {
+            Display::Bar(progress_bar(&label, total))
+        } else {
+            Display::Log { output: output.clone(), logged: None }
+        };
+
+        Self {
+            phase,
+            label,
+            started: Instant::now(),
+            updated: Duration::ZERO,
+            estimator,
+            display,
+        }
+    }
+
+    /// Record that `done` items are complete
+    pub fn set(&mut self, done: usize) {
+        let now = self.started.elapsed();
+        self.updated = now;
+        self.estimator.record(now, done as u64);
+
+        let status = self.status(now);
+        match &mut self.display {
+            Display::Bar(bar) => {
+                bar.set_position(done as u64);
+                bar.set_message(status);
+            }
+            Display::Log { logged, .. } => {
+                if logged.is_none_or(|at| now.saturating_sub(at) >= LOG_INTERVAL) {
+                    *logged = Some(now);
+                    self.log(now);
+                }
+            }
+            Display::Hidden => {}
+        }
+    }
+
+    /// Stop showing progress and return the phase's average rate
+    pub fn finish(&mut self) -> PhaseRate {
+        let now = self.started.elapsed();
+        match &mut self.display {
+            Display::Bar(bar) => bar.finish_and_clear(),
+            Display::Log { logged, .. } => {
+                // The last count is logged unless it already was
+                if *logged != Some(self.updated) {
+                    *logged = Some(self.updated);
+                    self.log(now);
+                }
+            }
+            Display::Hidden => {}
+        }
+        self.estimator.summary(self.phase)
+    }
+
+    fn log(&self, now: Duration) {
+        let Display::Log { output, .. } = &self.display else {
+            return;
+        };
+        let done = self.estimator.done();
+        let total = self.estimator.total();
+        let rate = self.estimator.rate(now);
+        let eta = self.estimator.eta(now);
+
+        if output.is_json() {
+            let event = serde_json::json!({
+                "status": "progress",
+                "phase": self.phase,
+                "done": done,
+                "total": total,
+                "per_second": rate,
+                "eta_secs": eta.map(|eta| eta.as_secs_f64()),
+            });
+            eprintln!("{}", event);
+        } else {
+            let count = match total {
+                Some(total) => format!("{}/{}", done, total),
+                None => done.to_string(),
+            };
+            output.info(format!("{}: {} ({})", self.label, count, self.status(now)));
+        }
+    }
+
+    /// Rate and ETA at `now`
+    fn status(&self, now: Duration) -> String {
+        let rate = format_rate(self.estimator.rate(now));
+        match self.estimator.total() {
+            Some(_) => format!("{}, ETA {}", rate, format_eta(self.estimator.eta(now))),
+            None => rate,
+        }
+    }
+}
+
+/// Show the average rate of each finished phase
+pub fn report_throughput(throughput: &[PhaseRate], output: &OutputWriter) {
+    if throughput.is_empty() {
+        return;
+    }
+    output.section("Throughput");
+    for rate in throughput {
+        output.kv(
+            &rate.phase,
+            format!(
+                "{} in {:.1}s ({})",
+                rate.items,
+                rate.elapsed_secs,
+                format_rate(rate.per_second)
+            ),
+        );
+    }
+}
+
+fn progress_bar(label: &str, total: Option<usize>) -> ProgressBar {
+    let (bar, template) = match total {
+        Some(total) => (ProgressBar::new(total as u64), "{prefix} [{bar:30}] {pos}/{len} {msg}"),
+        None => (ProgressBar::new_spinner(), "{prefix} {spinner} {pos} {msg}"),
+    };
+    let style = ProgressStyle::with_template(template)
+        .expect("progress template is valid")
+        .progress_chars("=> ");
+    bar.with_style(style).with_prefix(label.to_string())
+}
+
+/// Items per second, with one decimal below 10
+pub fn format_rate(per_second: f64) -> String {
+    if per_second < 10.0 {
+        format!("{:.1}/s", per_second)
+    } else {
+        format!("{:.0}/s", per_second)
+    }
+}
+
+/// Time left as hours and minutes, minutes and seconds, or seconds
+pub fn format_eta(eta: Option<Duration>) -> String {
+    let Some(eta) = eta else {
+        return "unknown".to_string();
+    };
+    let seconds = eta.as_secs_f64().ceil() as u64;
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(0.0), "0.0/s");
+        assert_eq!(format_rate(2.345), "2.3/s");
+        assert_eq!(format_rate(1234.6), "1235/s");
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(None), "unknown");
+        assert_eq!(format_eta(Some(Duration::from_millis(11_200))), "12s");
+        assert_eq!(format_eta(Some(Duration::from_secs(185))), "3m 05s");
+        assert_eq!(format_eta(Some(Duration::from_secs(3720))), "1h 02m");
+    }
+
+    #[test]
+    fn test_hidden_progress_still_measures() {
+        let mut progress =
+            PhaseProgress::start(&OutputWriter::quiet(), "files", "Adding files", Some(3));
+        progress.set(1);
+        progress.set(3);
+        let rate = progress.finish();
+        assert_eq!(progress.finish(), rate);
+        assert_eq!(rate.phase, "files");
+        assert_eq!(rate.items, 3);
+    }
+}
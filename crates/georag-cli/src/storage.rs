@@ -1,8 +1,23 @@
 use crate::cli::StorageBackend;
+use crate::output::OutputWriter;
 use anyhow::{Context, Result};
-use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
-use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use georag_core::config::mask_url_credentials;
+use georag_core::formats::FormatRegistry;
+use georag_core::models::IndexState;
+use georag_core::resources::{ResourceGate, ResourceGuard, ResourceKind, ResourceLimits};
+use georag_service::{AreaService, AuditService, EventLogService, IngestService};
+use georag_store::bundle::BundleStore;
+use georag_store::memory::{
+    MemoryAreaStore, MemoryAuditStore, MemoryBlobStore, MemoryCheckpointStore, MemoryDocumentStore,
+    MemoryEventLog, MemorySpatialStore, MemoryVectorStore, MemoryWorkspaceStore,
+};
+use georag_store::ports::{
+    AreaStore, AuditStore, BlobStore, CheckpointStore, DocumentStore, EventLogStore, SpatialStore,
+    VectorStore, WorkspaceStore,
+};
 use georag_store::postgres::{PostgresConfig, PostgresStore};
+use georag_store::slots::SharedSlots;
+use std::path::Path;
 use std::sync::Arc;
 
 /// Parse database URL to extract connection details for error messages
@@ -38,6 +53,29 @@ pub struct Storage {
     pub spatial: Arc<dyn SpatialStore>,
     pub vector: Arc<dyn VectorStore>,
     pub document: Arc<dyn DocumentStore>,
+    /// Checkpoints of interrupted index builds
+    pub checkpoints: Arc<dyn CheckpointStore>,
+    /// Workspace settings shared with the API
+    pub workspaces: Arc<dyn WorkspaceStore>,
+    /// Original files of added datasets
+    pub blobs: Arc<dyn BlobStore>,
+    /// Saved areas shared with the API
+    pub areas: Arc<dyn AreaStore>,
+    /// Workspace events shared with the API's activity timeline
+    pub audit: Arc<dyn AuditStore>,
+    /// Change events shared with the API's event feed
+    pub events: Arc<dyn EventLogStore>,
+    /// Format readers used when adding datasets
+    pub formats: Arc<FormatRegistry>,
+    /// Index state shipped with an offline bundle
+    pub bundle_index: Option<IndexState>,
+    /// Slots of the limits shared with the API server, on a shared backend
+    pub slots: Option<SharedSlots>,
+    /// Whether the stores keep their data between runs
+    ///
+    /// The memory backend starts empty on every run, and a bundle is loaded
+    /// read-only from its file.
+    pub persistent: bool,
 }
 
 impl Storage {
@@ -48,12 +86,69 @@ impl Storage {
         }
     }
 
+    /// Serve read-only from an offline bundle file
+    pub async fn from_bundle(path: &Path) -> Result<Self> {
+        let bundle = BundleStore::open(path)
+            .await
+            .with_context(|| format!("Failed to open bundle {}", path.display()))?;
+        let bundle_index = bundle.index_state().cloned();
+        let bundle = Arc::new(bundle);
+
+        Ok(Self {
+            spatial: bundle.clone(),
+            vector: bundle.clone(),
+            document: bundle,
+            checkpoints: Arc::new(MemoryCheckpointStore::new()),
+            workspaces: Arc::new(MemoryWorkspaceStore::new()),
+            blobs: Arc::new(MemoryBlobStore::new()),
+            areas: Arc::new(MemoryAreaStore::new()),
+            audit: Arc::new(MemoryAuditStore::new()),
+            events: Arc::new(MemoryEventLog::new()),
+            formats: Arc::new(FormatRegistry::with_defaults()),
+            bundle_index,
+            slots: None,
+            persistent: false,
+        })
+    }
+
+    /// Ingest service storing into the spatial store with the configured readers
+    pub fn ingest_service(&self) -> IngestService {
+        IngestService::new(self.spatial.clone(), self.formats.clone())
+    }
+
+    /// Area service resolving saved areas against the spatial store
+    pub fn area_service(&self) -> AreaService {
+        AreaService::new(self.areas.clone(), self.spatial.clone())
+    }
+
+    /// Audit service recording workspace events in the audit store
+    pub fn audit_service(&self) -> AuditService {
+        AuditService::new(self.audit.clone())
+    }
+
+    /// Event log service recording changes in the event log store
+    ///
+    /// Never prunes the log; the API server prunes it to its retention.
+    pub fn event_log_service(&self) -> EventLogService {
+        EventLogService::new(self.events.clone())
+    }
+
     /// Create in-memory storage adapters
     fn new_memory() -> Result<Self> {
         Ok(Self {
             spatial: Arc::new(MemorySpatialStore::new()),
             vector: Arc::new(MemoryVectorStore::new()),
             document: Arc::new(MemoryDocumentStore::new()),
+            checkpoints: Arc::new(MemoryCheckpointStore::new()),
+            workspaces: Arc::new(MemoryWorkspaceStore::new()),
+            blobs: Arc::new(MemoryBlobStore::new()),
+            areas: Arc::new(MemoryAreaStore::new()),
+            audit: Arc::new(MemoryAuditStore::new()),
+            events: Arc::new(MemoryEventLog::new()),
+            formats: Arc::new(FormatRegistry::with_defaults()),
+            bundle_index: None,
+            slots: None,
+            persistent: false,
         })
     }
 
@@ -82,8 +177,8 @@ impl Storage {
                 host,
                 port,
                 database,
-                config.database_url,
-                e
+                mask_url_credentials(&config.database_url),
+                mask_url_credentials(&e.to_string())
             )
         })?;
         let store = Arc::new(store);
@@ -92,9 +187,51 @@ impl Storage {
             spatial: store.clone(),
             vector: store.clone(),
             document: store.clone(),
+            checkpoints: store.clone(),
+            workspaces: store.clone(),
+            blobs: store.clone(),
+            areas: store.clone(),
+            audit: store.clone(),
+            events: store.clone(),
+            formats: Arc::new(FormatRegistry::with_defaults()),
+            bundle_index: None,
+            slots: Some(SharedSlots::new(store, ResourceLimits::from_env())),
+            persistent: true,
         })
     }
 
+    /// Take a slot of the limits shared with the API server
+    ///
+    /// Waits while the server and other CLI runs hold every slot of `kind`.
+    /// Backends not shared with a server need no slot.
+    pub async fn acquire_slot(
+        &self,
+        kind: ResourceKind,
+        output: &OutputWriter,
+    ) -> Result<ResourceGuard> {
+        let Some(slots) = &self.slots else {
+            return Ok(ResourceGuard::unlimited());
+        };
+        if let Some(guard) = slots.try_acquire(kind).await? {
+            return Ok(guard);
+        }
+        output.info(format!(
+            "Waiting for one of {} {} slots shared with other GeoRAG processes",
+            slots.limits().limit(kind),
+            kind
+        ));
+        Ok(slots.acquire(kind).await?)
+    }
+
+    /// Replace the format registry, e.g. to add readers from downstream crates
+    ///
+    /// Build the registry with `FormatRegistry::with_defaults()` and register
+    /// extra readers on it; readers registered later take precedence.
+    pub fn with_format_registry(mut self, registry: FormatRegistry) -> Self {
+        self.formats = Arc::new(registry);
+        self
+    }
+
     /// Check if storage has any data
     #[allow(dead_code)]
     pub async fn is_empty(&self) -> Result<bool> {
@@ -121,3 +258,72 @@ impl Storage {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use georag_core::formats::{FormatDataset, FormatFeature, FormatMetadata, FormatReader};
+    use georag_service::IngestRequest;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// Reader of a format the built-in readers do not know, one point per file
+    struct SurveyReader;
+
+    #[async_trait]
+    impl FormatReader for SurveyReader {
+        async fn read(&self, _path: &Path) -> georag_core::error::Result<FormatDataset> {
+            Ok(FormatDataset {
+                name: "survey".to_string(),
+                format_metadata: FormatMetadata {
+                    format_name: "Survey".to_string(),
+                    format_version: None,
+                    layer_name: None,
+                    page_count: None,
+                    paragraph_count: None,
+                    extraction_method: None,
+                    spatial_association: None,
+                    axis_order: None,
+                    license: None,
+                    attribution: None,
+                },
+                crs: 4326,
+                features: vec![FormatFeature {
+                    id: "0".to_string(),
+                    geometry: Some(json!({ "type": "Point", "coordinates": [106.8, -6.2] })),
+                    properties: HashMap::from([("station".to_string(), json!("S1"))]),
+                }],
+                errors: Vec::new(),
+            })
+        }
+
+        fn supported_extensions(&self) -> &[&str] {
+            &["survey"]
+        }
+
+        fn format_name(&self) -> &str {
+            "Survey"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_readers_are_used_for_adding_datasets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stations.survey");
+        std::fs::write(&path, "S1 106.8 -6.2").unwrap();
+
+        // The built-in readers do not know the format
+        let storage = Storage::new_memory().unwrap();
+        assert!(storage.formats.detect_format(&path).is_err());
+
+        let registry = FormatRegistry::with_defaults().with_reader(Box::new(SurveyReader));
+        let storage = storage.with_format_registry(registry);
+        let report = storage.ingest_service().ingest(&IngestRequest::new(&path)).await.unwrap();
+        assert_eq!(report.features_stored, 1);
+        assert_eq!(report.dataset.format.format_name, "Survey");
+
+        // Built-in readers are still registered
+        assert!(storage.formats.detect_format(Path::new("parks.geojson")).is_ok());
+    }
+}
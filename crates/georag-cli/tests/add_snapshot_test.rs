@@ -0,0 +1,115 @@
+//! Snapshot tests for the output of the add command
+//!
+//! `tests/snapshots/add.json` holds what `georag add --json` reported before
+//! ingestion moved to the shared ingest service, and the error for a file
+//! that fails validation. Fields added to the report since are not part of
+//! the snapshot and not compared.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const SNAPSHOT: &str = include_str!("snapshots/add.json");
+
+const PARKS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+      "properties": { "name": "Pocket Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]] },
+      "properties": { "name": "Central Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.9, -6.1] },
+      "properties": { "name": "Corner Garden" }
+    }
+  ]
+}"#;
+
+fn georag_bin() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop(); // Remove test binary name
+    path.pop(); // Remove 'deps' directory
+    path.push("georag");
+    path
+}
+
+/// Create a fresh workspace under /tmp
+fn init_workspace(dir: &str) -> PathBuf {
+    let _ = std::fs::remove_dir_all(dir);
+
+    let output = Command::new(georag_bin())
+        .args(["init", dir])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "init should succeed");
+
+    std::fs::canonicalize(dir).unwrap()
+}
+
+fn add(workspace: &Path, file: &Path) -> Output {
+    Command::new(georag_bin())
+        .args(["add", file.to_str().unwrap(), "--json"])
+        .current_dir(workspace)
+        .env_remove("GEORAG_WORKSPACE")
+        .output()
+        .expect("Failed to execute command")
+}
+
+/// The `data` of the result among the JSON documents printed to stdout
+fn result(output: &Output) -> Value {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::Deserializer::from_str(&stdout)
+        .into_iter::<Value>()
+        .filter_map(Result::ok)
+        .find(|value| value["status"] == "success" && value.get("data").is_some())
+        .map(|value| value["data"].clone())
+        .unwrap_or_else(|| panic!("no result in output: {}", stdout))
+}
+
+/// The parts of `actual` the snapshot has, so fields added since are ignored
+fn project(actual: &Value, snapshot: &Value) -> Value {
+    match (actual, snapshot) {
+        (Value::Object(actual), Value::Object(snapshot)) => snapshot
+            .iter()
+            .map(|(key, expected)| {
+                let value = actual.get(key).map_or(Value::Null, |v| project(v, expected));
+                (key.clone(), value)
+            })
+            .collect(),
+        (actual, _) => actual.clone(),
+    }
+}
+
+#[test]
+fn test_add_output_matches_snapshot() {
+    let snapshot: Value = serde_json::from_str(SNAPSHOT).unwrap();
+    let workspace = init_workspace("/tmp/test-add-snapshot");
+    let data_dir = PathBuf::from("/tmp/test-add-snapshot-data");
+    let _ = std::fs::remove_dir_all(&data_dir);
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let parks = data_dir.join("parks.geojson");
+    std::fs::write(&parks, PARKS).unwrap();
+    let output = add(&workspace, &parks);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(project(&result(&output), &snapshot["added"]), snapshot["added"]);
+
+    // The CLI validates the file before reading it
+    let broken = data_dir.join("broken.geojson");
+    std::fs::write(&broken, "{ not json").unwrap();
+    let output = add(&workspace, &broken);
+    assert!(!output.status.success(), "adding an unreadable file should fail");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let error = snapshot["unreadable"]["error"].as_str().unwrap();
+    assert!(stderr.contains(error), "{}", stderr);
+
+    let _ = std::fs::remove_dir_all(&workspace);
+    let _ = std::fs::remove_dir_all(&data_dir);
+}
@@ -106,3 +106,42 @@ fn test_actual_init_creates_files() {
     // Clean up
     let _ = std::fs::remove_dir_all(test_dir);
 }
+
+#[test]
+fn test_self_test_passes_with_json_report() {
+    let output = Command::new(georag_bin())
+        .args(["self-test", "--json"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success(), "Self-test should pass offline");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let parsed: serde_json::Value =
+        serde_json::from_str(&stdout).expect("Output should be valid JSON");
+
+    let data = parsed.get("data").expect("Should have data field");
+    assert_eq!(data.get("passed").and_then(|v| v.as_bool()), Some(true));
+
+    let stages = data.get("stages").and_then(|v| v.as_array()).expect("Should list stages");
+    let names: Vec<&str> = stages.iter().filter_map(|s| s["name"].as_str()).collect();
+    assert_eq!(names, ["stores", "ingest", "chunking", "index", "query"]);
+    assert!(stages.iter().all(|s| s["status"] == "passed"));
+}
+
+#[test]
+fn test_query_rejects_unknown_result_format() {
+    let output = Command::new(georag_bin())
+        .args(["query", "parks", "--format", "xml"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success(), "Unknown format should be rejected");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("geojson, json, csv"),
+        "Should list supported formats: {}",
+        stderr
+    );
+}
@@ -0,0 +1,144 @@
+//! Integration tests for index schema versions
+//!
+//! `fixtures/workspace-v1` is a workspace whose index state predates schema
+//! versions. Each test copies it under /tmp, since upgrading rewrites it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn georag_bin() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop(); // Remove test binary name
+    path.pop(); // Remove 'deps' directory
+    path.push("georag");
+    path
+}
+
+/// Copy a directory tree
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// Copy the old-format fixture workspace to `dir`
+fn v1_workspace(dir: &str) -> PathBuf {
+    let _ = fs::remove_dir_all(dir);
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/workspace-v1");
+    copy_dir(&fixture, Path::new(dir));
+    PathBuf::from(dir)
+}
+
+fn run(workspace: &Path, args: &[&str]) -> Output {
+    Command::new(georag_bin())
+        .args(args)
+        .arg("--workspace")
+        .arg(workspace)
+        .env_remove("GEORAG_WORKSPACE")
+        .output()
+        .expect("Failed to execute command")
+}
+
+fn state(workspace: &Path) -> serde_json::Value {
+    let path = workspace.join(".georag/index/state.json");
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The command's result, printed after any progress messages
+fn result(output: &Output) -> serde_json::Value {
+    serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter::<serde_json::Value>()
+        .map(|value| value.unwrap())
+        .last()
+        .expect("no JSON output")["data"]
+        .clone()
+}
+
+fn backups(workspace: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(workspace.join(".georag/backups")) {
+        Ok(entries) => entries.map(|entry| entry.unwrap().path()).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[test]
+fn test_old_index_is_refused_until_upgraded() {
+    let workspace = v1_workspace("/tmp/test-schema-upgrade");
+
+    let output = run(&workspace, &["query", "parcels"]);
+    assert!(!output.status.success(), "query should refuse the old index");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Index schema version 1 is older than version 2"), "{}", stderr);
+    assert!(stderr.contains("georag db upgrade"), "{}", stderr);
+
+    // A dry run lists the steps and changes nothing
+    let output = run(&workspace, &["db", "upgrade", "--dry-run", "--json"]);
+    assert!(output.status.success());
+    let plan = result(&output);
+    assert_eq!(plan["applied"], false);
+    assert_eq!(plan["steps"].as_array().unwrap().len(), 1);
+    assert!(state(&workspace).get("schema_version").is_none());
+    assert!(backups(&workspace).is_empty());
+
+    let output = run(&workspace, &["db", "upgrade", "--json"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report = result(&output);
+    assert_eq!(report["from_version"], 1);
+    assert_eq!(report["to_version"], 2);
+    assert_eq!(report["applied"], true);
+
+    // The state is upgraded in place and the workspace data kept as a backup
+    let upgraded = state(&workspace);
+    assert_eq!(upgraded["schema_version"], 2);
+    assert_eq!(upgraded["hash"], "4f1c2a9e8b7d6c5a");
+    assert_eq!(upgraded["chunk_count"], 42);
+    let backups = backups(&workspace);
+    assert_eq!(backups.len(), 1);
+    assert_eq!(report["backup"], backups[0].display().to_string());
+    let backup: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(backups[0].join("index/state.json")).unwrap())
+            .unwrap();
+    assert!(backup.get("schema_version").is_none());
+    assert_eq!(backup["hash"], "4f1c2a9e8b7d6c5a");
+    for file in ["config.toml", "datasets.json"] {
+        assert!(backups[0].join(file).is_file(), "{} was not backed up", file);
+    }
+    assert!(!backups[0].join("build.lock").exists());
+
+    // Upgrading again is a no-op
+    let output = run(&workspace, &["db", "upgrade"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("already current"));
+
+    let _ = fs::remove_dir_all(&workspace);
+}
+
+#[test]
+fn test_newer_index_is_refused() {
+    let workspace = v1_workspace("/tmp/test-schema-upgrade-newer");
+    let path = workspace.join(".georag/index/state.json");
+    let mut newer = state(&workspace);
+    newer["schema_version"] = 99.into();
+    fs::write(&path, newer.to_string()).unwrap();
+
+    for args in [&["query", "parcels"][..], &["db", "upgrade"]] {
+        let output = run(&workspace, args);
+        assert!(!output.status.success(), "{:?} should refuse the newer index", args);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Index schema version 99 is newer than version 2"), "{}", stderr);
+    }
+
+    // Nothing was touched
+    assert_eq!(state(&workspace)["schema_version"], 99);
+    assert!(backups(&workspace).is_empty());
+
+    let _ = fs::remove_dir_all(&workspace);
+}
@@ -0,0 +1,112 @@
+//! Integration tests for workspace selection
+//!
+//! These tests run commands from a directory outside the workspace and select
+//! it with `--workspace` or `GEORAG_WORKSPACE`.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn georag_bin() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop(); // Remove test binary name
+    path.pop(); // Remove 'deps' directory
+    path.push("georag");
+    path
+}
+
+/// Create a fresh workspace under /tmp
+fn init_workspace(dir: &str) -> PathBuf {
+    let _ = std::fs::remove_dir_all(dir);
+
+    let output = Command::new(georag_bin())
+        .args(["init", dir])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "init should succeed");
+
+    std::fs::canonicalize(dir).unwrap()
+}
+
+/// Run georag from the filesystem root, outside any workspace
+fn run_outside(args: &[&str], env_workspace: Option<&Path>) -> Output {
+    let mut command = Command::new(georag_bin());
+    command.args(args).current_dir("/").env_remove("GEORAG_WORKSPACE");
+    if let Some(path) = env_workspace {
+        command.env("GEORAG_WORKSPACE", path);
+    }
+    command.output().expect("Failed to execute command")
+}
+
+#[test]
+fn test_workspace_flag_from_unrelated_cwd() {
+    let workspace = init_workspace("/tmp/test-workspace-flag");
+
+    let output =
+        run_outside(&["status", "--crs", "--workspace", workspace.to_str().unwrap()], None);
+    assert!(output.status.success(), "status should find the workspace via --workspace");
+
+    let _ = std::fs::remove_dir_all(&workspace);
+}
+
+#[test]
+fn test_workspace_env_from_unrelated_cwd() {
+    let workspace = init_workspace("/tmp/test-workspace-env");
+
+    let output = run_outside(&["status", "--crs"], Some(&workspace));
+    assert!(output.status.success(), "status should find the workspace via GEORAG_WORKSPACE");
+
+    let _ = std::fs::remove_dir_all(&workspace);
+}
+
+#[test]
+fn test_workspace_flag_takes_precedence_over_env() {
+    let workspace = init_workspace("/tmp/test-workspace-precedence");
+    let not_a_workspace = Path::new("/tmp/test-workspace-precedence-missing");
+
+    let output = run_outside(
+        &["status", "--crs", "--workspace", workspace.to_str().unwrap()],
+        Some(not_a_workspace),
+    );
+    assert!(output.status.success(), "--workspace should override GEORAG_WORKSPACE");
+
+    let _ = std::fs::remove_dir_all(&workspace);
+}
+
+#[test]
+fn test_workspace_flag_without_workspace_fails_clearly() {
+    let dir = "/tmp/test-workspace-flag-empty";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+
+    let output = run_outside(&["status", "--workspace", dir], None);
+    assert!(!output.status.success(), "status should fail without a workspace");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No GeoRAG workspace at") && stderr.contains("--workspace"),
+        "Error should name the path and where it came from: {}",
+        stderr
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn test_verbose_reports_workspace_used() {
+    let workspace = init_workspace("/tmp/test-workspace-verbose");
+
+    let output = run_outside(
+        &["status", "--crs", "--verbose", "--workspace", workspace.to_str().unwrap()],
+        None,
+    );
+    assert!(output.status.success(), "status should succeed");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!("Using workspace: {}", workspace.display())),
+        "Verbose output should mention the workspace: {}",
+        stderr
+    );
+
+    let _ = std::fs::remove_dir_all(&workspace);
+}
@@ -1,6 +1,8 @@
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::models::workspace::QuotaResource;
+
 #[derive(Debug, Error)]
 pub enum GeoragError {
     // Workspace errors
@@ -23,6 +25,23 @@ pub enum GeoragError {
     #[error("Invalid geometry at feature {feature_id}: {reason}")]
     InvalidGeometry { feature_id: String, reason: String },
 
+    #[error(
+        "Filter geometry has {vertices} vertices, more than the limit of {limit}. \
+        Simplify the geometry or enable filter auto-simplification"
+    )]
+    GeometryTooComplex { vertices: usize, limit: usize },
+
+    #[error(
+        "Workspace quota exceeded for {resource}: limit {limit}, current usage {usage}, \
+        requested {requested} more"
+    )]
+    QuotaExceeded {
+        resource: QuotaResource,
+        limit: u64,
+        usage: u64,
+        requested: u64,
+    },
+
     // Index errors
     #[error("Index not built: {0}")]
     IndexNotBuilt(String),
@@ -30,10 +49,37 @@ pub enum GeoragError {
     #[error("Index is stale. Rebuild required after dataset changes")]
     IndexStale,
 
+    #[error(
+        "Index schema version {found} is older than version {current} of this build. \
+        Run 'georag db upgrade' to upgrade it"
+    )]
+    IndexSchemaOutdated { found: u32, current: u32 },
+
+    #[error(
+        "Index schema version {found} is newer than version {supported}, the newest this \
+        build reads. Upgrade georag to open it"
+    )]
+    IndexSchemaTooNew { found: u32, supported: u32 },
+
+    // Store errors
+    #[error("Cannot {operation}: the store is read-only")]
+    ReadOnly { operation: String },
+
     // Embedder errors
     #[error("Embedder unavailable: {reason}. Try: {remediation}")]
     EmbedderUnavailable { reason: String, remediation: String },
 
+    #[error("Embedding dimensions of {model} disagree: {dimensions}. Fix: {fix}")]
+    EmbedderDimensionMismatch {
+        model: String,
+        dimensions: String,
+        fix: String,
+    },
+
+    // Generator errors
+    #[error("Generator unavailable: {reason}. Try: {remediation}")]
+    GeneratorUnavailable { reason: String, remediation: String },
+
     // Configuration errors
     #[error("Missing required configuration: {key}")]
     ConfigMissing { key: String },
@@ -62,6 +108,12 @@ pub enum GeoragError {
     #[error("Format validation failed for {format}: {reason}")]
     FormatValidation { format: String, reason: String },
 
+    #[error("Invalid option for {format}: {reason}")]
+    InvalidFormatOption { format: String, reason: String },
+
+    #[error("Ingest stopped: {reason}")]
+    IngestStopped { reason: String },
+
     #[error("Document extraction failed for {format}: {reason}")]
     DocumentExtraction { format: String, reason: String },
 
@@ -89,9 +89,13 @@ impl FormatReader for DocxReader {
                 paragraph_count: Some(paragraphs.len()),
                 extraction_method: Some("docx-rs".to_string()),
                 spatial_association: None,
+                axis_order: None,
+                license: None,
+                attribution: None,
             },
             crs: 4326, // Default to WGS84 (EPSG:4326) for documents without inherent geometry
             features: vec![feature],
+            errors: Vec::new(),
         })
     }
 
@@ -1,35 +1,62 @@
 use async_trait::async_trait;
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
+use std::io::BufReader;
 use std::path::Path;
+use std::time::Instant;
 
 use crate::error::{GeoragError, Result};
 use crate::formats::validation::FormatValidator;
 use crate::formats::{
-    FormatDataset, FormatFeature, FormatMetadata, FormatReader, FormatValidation,
+    check_geometry, sample_inspection, FeatureError, FeatureErrorKind, FeatureErrors, FeatureSink,
+    FormatDataset, FormatFeature, FormatInspection, FormatMetadata, FormatOptions, FormatReader,
+    FormatValidation, InspectMethod,
 };
+use crate::geo::axis::{swap_axes, AxisVotes};
+use crate::models::{AxisOrder, AxisOrderDecision};
+use serde_json::Value;
 
 /// GeoJSON format reader.
 ///
 /// Per RFC 7946, GeoJSON coordinates use WGS84 (EPSG:4326). Legacy CRS members
 /// are parsed if present, but files without explicit CRS default to 4326.
+/// Coordinates are read in the order set by [`FormatOptions::axis_order`] and
+/// always returned lon,lat.
+///
+/// A streaming read of a FeatureCollection parses one feature at a time and
+/// reads the file twice: first for the members around `features` (and, with
+/// an automatic axis order, the votes of the geometries), then for the
+/// features themselves.
 pub struct GeoJsonReader;
 
 #[async_trait]
 impl FormatReader for GeoJsonReader {
     async fn read(&self, path: &Path) -> Result<FormatDataset> {
+        self.read_with_options(path, &FormatOptions::new()).await
+    }
+
+    async fn read_with_options(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+    ) -> Result<FormatDataset> {
         // Read the file
         let content = fs::read_to_string(path).map_err(GeoragError::Io)?;
 
-        // Parse as GeoJSON
-        let geojson: geojson::GeoJson =
-            content.parse().map_err(|e| GeoragError::FormatValidation {
+        // Parse as plain JSON so one bad feature does not reject the whole file
+        let document: Value =
+            serde_json::from_str(&content).map_err(|e| GeoragError::FormatValidation {
                 format: "GeoJSON".to_string(),
                 reason: format!("Failed to parse GeoJSON: {}", e),
             })?;
 
         // Extract features and metadata
-        let (features, crs) = self.extract_features_and_crs(&geojson)?;
+        let mut errors = FeatureErrors::new("GeoJSON", options.read_policy);
+        let (mut features, crs) = self.extract_features_and_crs(&document, &mut errors)?;
+        let crs84 = document.get("crs").is_some_and(declares_crs84);
+        let axis_order = normalize_axis_order(&mut features, options, crs84);
 
         // Get dataset name from filename
         let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string();
@@ -44,9 +71,13 @@ impl FormatReader for GeoJsonReader {
                 paragraph_count: None,
                 extraction_method: None,
                 spatial_association: None,
+                axis_order: Some(axis_order),
+                license: foreign_member(&document, &["license", "licence"]),
+                attribution: foreign_member(&document, &["attribution"]),
             },
             crs,
             features,
+            errors: errors.into_vec(),
         })
     }
 
@@ -58,6 +89,160 @@ impl FormatReader for GeoJsonReader {
         "GeoJSON"
     }
 
+    async fn read_streaming(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+        mut sink: FeatureSink,
+    ) -> Result<FormatDataset> {
+        let auto = options.axis_order == AxisOrder::Auto;
+        let extent = options.extent;
+        let scan_path = path.to_path_buf();
+        let (header, votes) = run_blocking(move || {
+            let mut votes = AxisVotes::new(extent);
+            let header = if auto {
+                scan_document(
+                    &scan_path,
+                    Some(&mut |idx, member| {
+                        if let Ok(FormatFeature { geometry: Some(geometry), .. }) =
+                            GeoJsonReader.convert_feature(&member, idx)
+                        {
+                            votes.add(&geometry);
+                        }
+                        Ok(())
+                    }),
+                )?
+            } else {
+                scan_document(&scan_path, None)?
+            };
+            Ok((header, votes))
+        })
+        .await?;
+
+        header.check().map_err(|reason| GeoragError::FormatValidation {
+            format: "GeoJSON".to_string(),
+            reason,
+        })?;
+        if header.document_type() != Some("FeatureCollection") {
+            // A single feature or geometry is small enough to read whole
+            let dataset = self.read_with_options(path, options).await?;
+            return sink.forward(dataset).await;
+        }
+
+        let crs = collection_crs(&header.document);
+        let crs84 = header.document.get("crs").is_some_and(declares_crs84);
+        let axis_order = axis_decision(options, crs84, &votes);
+        let swap = axis_order.applied == AxisOrder::LatLon;
+        let format_metadata = FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: Some(axis_order),
+            license: foreign_member(&header.document, &["license", "licence"]),
+            attribution: foreign_member(&header.document, &["attribution"]),
+        };
+        sink.start(crs, format_metadata.clone())?;
+
+        let policy = options.read_policy;
+        let stream_path = path.to_path_buf();
+        let errors = run_blocking(move || {
+            let mut errors = FeatureErrors::new("GeoJSON", policy);
+            scan_document(
+                &stream_path,
+                Some(&mut |idx, member| match GeoJsonReader.convert_feature(&member, idx) {
+                    Ok(mut feature) => {
+                        if let Some(geometry) = feature.geometry.as_mut().filter(|_| swap) {
+                            swap_axes(geometry);
+                        }
+                        sink.blocking_send(feature)
+                    }
+                    Err(error) => errors.record(error),
+                }),
+            )?;
+            sink.blocking_finish()?;
+            Ok(errors.into_vec())
+        })
+        .await?;
+
+        Ok(FormatDataset {
+            name: path.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string(),
+            format_metadata,
+            crs,
+            features: Vec::new(),
+            errors,
+        })
+    }
+
+    /// Scan every feature for the count and extent, building only the sample
+    ///
+    /// The extent is of the raw coordinates, swapped when the axis order of
+    /// the options is lat,lon so it is given lon,lat like the features.
+    async fn inspect(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+        sample: usize,
+    ) -> Result<FormatInspection> {
+        let auto = options.axis_order == AxisOrder::Auto;
+        let extent = options.extent;
+        let policy = options.read_policy;
+        let scan_path = path.to_path_buf();
+        let (header, mut inspection, votes) = run_blocking(move || {
+            let mut inspection = FormatInspection::new("GeoJSON", InspectMethod::Scan);
+            let mut votes = AxisVotes::new(extent);
+            let mut errors = FeatureErrors::new("GeoJSON", policy);
+            let started = Instant::now();
+            let header = scan_document(
+                &scan_path,
+                Some(&mut |idx, member| {
+                    if let Some(geometry) = member.get("geometry") {
+                        inspection.extend_extent(geometry);
+                        if auto {
+                            votes.add(geometry);
+                        }
+                    }
+                    if idx >= sample {
+                        return Ok(());
+                    }
+                    match GeoJsonReader.convert_feature(&member, idx) {
+                        Ok(feature) => inspection.add_sample(&feature),
+                        Err(error) => errors.record(error)?,
+                    }
+                    if idx + 1 == sample {
+                        inspection.sample_time = started.elapsed();
+                    }
+                    Ok(())
+                }),
+            )?;
+            if header.features.is_some_and(|count| count < sample) {
+                inspection.sample_time = started.elapsed();
+            }
+            Ok((header, inspection, votes))
+        })
+        .await?;
+
+        header.check().map_err(|reason| GeoragError::FormatValidation {
+            format: "GeoJSON".to_string(),
+            reason,
+        })?;
+        if header.document_type() != Some("FeatureCollection") {
+            // A single feature or geometry is small enough to read whole
+            return sample_inspection(self, path, options, sample).await;
+        }
+
+        let crs84 = header.document.get("crs").is_some_and(declares_crs84);
+        if axis_decision(options, crs84, &votes).applied == AxisOrder::LatLon {
+            inspection.extent = inspection.extent.map(|[y0, x0, y1, x1]| [x0, y0, x1, y1]);
+        }
+        inspection.crs = Some(collection_crs(&header.document));
+        inspection.feature_count = header.features;
+        Ok(inspection)
+    }
+
     async fn validate(&self, path: &Path) -> Result<FormatValidation> {
         // Basic file validation
         let mut validation = FormatValidator::validate_file_exists(path);
@@ -65,73 +250,72 @@ impl FormatReader for GeoJsonReader {
             return Ok(validation);
         }
 
-        // Validate JSON structure
-        let json_validation = FormatValidator::validate_json_structure(path);
-
-        // If JSON is valid, try to parse as GeoJSON
-        if json_validation.is_valid() {
-            match fs::read_to_string(path) {
-                Ok(content) => {
-                    if let Err(e) = content.parse::<geojson::GeoJson>() {
-                        validation.errors.push(format!("Invalid GeoJSON: {}", e));
-                    }
-                }
-                Err(e) => {
-                    validation.errors.push(format!("Cannot read file: {}", e));
+        // Check the JSON structure and the document type, streaming past the
+        // features so a large file is not loaded. Individual features are
+        // checked while reading, where the read policy decides what a bad one means.
+        let path = path.to_path_buf();
+        match run_blocking(move || scan_document(&path, None)).await {
+            Ok(header) => {
+                if let Err(reason) = header.check() {
+                    validation.errors.push(format!("Invalid GeoJSON: {}", reason));
                 }
             }
+            Err(GeoragError::Io(e)) => {
+                validation.errors.push(format!("Cannot read file: {}", e));
+            }
+            Err(GeoragError::FormatValidation { reason, .. }) => {
+                validation.errors.push(format!("Invalid JSON structure: {}", reason));
+            }
+            Err(e) => return Err(e),
         }
 
-        // Merge validations
-        Ok(FormatValidator::merge_validations(vec![validation, json_validation]))
+        Ok(validation)
     }
 }
 
 impl GeoJsonReader {
-    /// Extract features and CRS from GeoJSON
+    /// Extract features and CRS from a GeoJSON document
     fn extract_features_and_crs(
         &self,
-        geojson: &geojson::GeoJson,
+        document: &Value,
+        errors: &mut FeatureErrors,
     ) -> Result<(Vec<FormatFeature>, u32)> {
-        match geojson {
-            geojson::GeoJson::FeatureCollection(fc) => {
-                let features = fc
-                    .features
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, feature)| self.convert_feature(feature, idx))
-                    .collect();
-
-                // Extract CRS (default to WGS84 if not specified)
-                let crs = fc
-                    .foreign_members
-                    .as_ref()
-                    .and_then(|fm| fm.get("crs"))
-                    .and_then(extract_epsg_from_crs);
-
-                let crs = match crs {
-                    Some(epsg) => epsg,
-                    None => {
-                        tracing::warn!(
-                            "GeoJSON does not specify CRS, defaulting to EPSG:4326 (WGS84)"
-                        );
-                        4326
+        check_document(document).map_err(|reason| GeoragError::FormatValidation {
+            format: "GeoJSON".to_string(),
+            reason,
+        })?;
+
+        match document.get("type").and_then(Value::as_str) {
+            Some("FeatureCollection") => {
+                let members = document["features"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+                let mut features = Vec::with_capacity(members.len());
+                for (idx, member) in members.iter().enumerate() {
+                    match self.convert_feature(member, idx) {
+                        Ok(feature) => features.push(feature),
+                        Err(error) => errors.record(error)?,
                     }
-                };
+                }
 
-                Ok((features, crs))
+                Ok((features, collection_crs(document)))
             }
-            geojson::GeoJson::Feature(feature) => {
-                let features = vec![self.convert_feature(feature, 0)];
+            Some("Feature") => {
+                let mut features = Vec::new();
+                match self.convert_feature(document, 0) {
+                    Ok(feature) => features.push(feature),
+                    Err(error) => errors.record(error)?,
+                }
                 tracing::warn!(
                     "Single GeoJSON Feature does not specify CRS, defaulting to EPSG:4326 (WGS84)"
                 );
                 Ok((features, 4326))
             }
-            geojson::GeoJson::Geometry(geom) => {
+            _ => {
                 // Single geometry - wrap in a feature
-                let geometry_json = serde_json::to_value(geom).map_err(|e| {
-                    GeoragError::Serialization(format!("Failed to serialize geometry: {}", e))
+                let geometry_json = parse_geometry(document).map_err(|(_, message)| {
+                    GeoragError::FormatValidation {
+                        format: "GeoJSON".to_string(),
+                        reason: message,
+                    }
                 })?;
 
                 let feature = FormatFeature {
@@ -148,51 +332,353 @@ impl GeoJsonReader {
         }
     }
 
-    /// Convert a GeoJSON feature to FormatFeature
-    fn convert_feature(&self, feature: &geojson::Feature, idx: usize) -> FormatFeature {
+    /// Convert a GeoJSON feature object to FormatFeature
+    fn convert_feature(
+        &self,
+        value: &Value,
+        idx: usize,
+    ) -> std::result::Result<FormatFeature, FeatureError> {
+        let object = value
+            .as_object()
+            .filter(|object| object.get("type").and_then(Value::as_str) == Some("Feature"))
+            .ok_or_else(|| {
+                FeatureError::new(idx, FeatureErrorKind::Malformed, "not a GeoJSON Feature object")
+            })?;
+
         // Get feature ID (use index if not present)
-        let id = feature
-            .id
-            .as_ref()
-            .map(|id| match id {
-                geojson::feature::Id::String(s) => s.clone(),
-                geojson::feature::Id::Number(n) => n.to_string(),
-            })
-            .unwrap_or_else(|| idx.to_string());
-
-        // Convert geometry to JSON value
-        let geometry = feature.geometry.as_ref().and_then(|geom| serde_json::to_value(geom).ok());
-
-        // Convert properties from serde_json::Map to HashMap
-        let properties = feature
-            .properties
-            .as_ref()
-            .map(|props| props.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
-            .unwrap_or_default();
-
-        FormatFeature { id, geometry, properties }
+        let id = match object.get("id") {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Number(n)) => n.to_string(),
+            _ => idx.to_string(),
+        };
+        let error = |kind, message: String| FeatureError::new(idx, kind, message).with_id(&id);
+
+        let geometry = match object.get("geometry") {
+            None | Some(Value::Null) => None,
+            Some(geometry) => {
+                Some(parse_geometry(geometry).map_err(|(kind, message)| error(kind, message))?)
+            }
+        };
+
+        // Convert properties from a JSON object to HashMap
+        let properties = match object.get("properties") {
+            None | Some(Value::Null) => HashMap::new(),
+            Some(Value::Object(props)) => {
+                props.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+            }
+            Some(other) => {
+                return Err(error(
+                    FeatureErrorKind::InvalidProperties,
+                    format!("properties must be an object, found {}", other),
+                ))
+            }
+        };
+
+        Ok(FormatFeature { id, geometry, properties })
+    }
+}
+
+/// CRS of a FeatureCollection, defaulting to WGS84 if not specified
+fn collection_crs(document: &Value) -> u32 {
+    match document.get("crs").and_then(extract_epsg_from_crs) {
+        Some(epsg) => epsg,
+        None => {
+            tracing::warn!("GeoJSON does not specify CRS, defaulting to EPSG:4326 (WGS84)");
+            4326
+        }
+    }
+}
+
+/// Run blocking file work off the async runtime
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(work).await.map_err(|e| GeoragError::FormatError {
+        format: "GeoJSON".to_string(),
+        message: format!("Reading task failed: {}", e),
+    })?
+}
+
+/// Top-level members kept while a document's features stream past
+const HEADER_MEMBERS: [&str; 5] = ["type", "crs", "license", "licence", "attribution"];
+
+/// Called with the index and value of each member of a features array
+type OnFeature<'a> = &'a mut dyn FnMut(usize, Value) -> Result<()>;
+
+/// A GeoJSON document without its features
+struct DocumentHeader {
+    /// The members in [`HEADER_MEMBERS`] that the document has
+    document: Value,
+
+    /// Length of the features array, `None` without one
+    features: Option<usize>,
+}
+
+impl DocumentHeader {
+    fn document_type(&self) -> Option<&str> {
+        self.document.get("type").and_then(Value::as_str)
+    }
+
+    /// The checks of [`check_document`], for a document read without its features
+    fn check(&self) -> std::result::Result<(), String> {
+        match self.document_type() {
+            Some("FeatureCollection") if self.features.is_none() => {
+                Err("FeatureCollection has no features array".to_string())
+            }
+            Some(_) => Ok(()),
+            None => Err("missing \"type\" member".to_string()),
+        }
+    }
+}
+
+/// Stream a GeoJSON document from disk, keeping only its [`HEADER_MEMBERS`]
+///
+/// Each member of the features array is parsed on its own and handed to
+/// `on_feature`, or skipped without being built when there is none. An error
+/// returned by `on_feature` stops the scan and is returned as is.
+fn scan_document(path: &Path, on_feature: Option<OnFeature<'_>>) -> Result<DocumentHeader> {
+    let file = fs::File::open(path).map_err(GeoragError::Io)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(file));
+    let mut scan = DocumentScan { on_feature, stopped: None };
+    let scanned = (&mut scan)
+        .deserialize(&mut deserializer)
+        .and_then(|header| deserializer.end().map(|()| header));
+
+    match (scanned, scan.stopped) {
+        (_, Some(error)) => Err(error),
+        (Ok(header), None) => Ok(header),
+        (Err(e), None) => Err(GeoragError::FormatValidation {
+            format: "GeoJSON".to_string(),
+            reason: format!("Failed to parse GeoJSON: {}", e),
+        }),
+    }
+}
+
+/// Visitor for the top level of a document, see [`scan_document`]
+struct DocumentScan<'f> {
+    on_feature: Option<OnFeature<'f>>,
+
+    /// Error returned by `on_feature`, which stopped the scan
+    stopped: Option<GeoragError>,
+}
+
+impl<'de> DeserializeSeed<'de> for &mut DocumentScan<'_> {
+    type Value = DocumentHeader;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<DocumentHeader, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for &mut DocumentScan<'_> {
+    type Value = DocumentHeader;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a GeoJSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<DocumentHeader, A::Error> {
+        let mut members = serde_json::Map::new();
+        let mut features = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "features" {
+                features = map.next_value_seed(FeaturesSeed { scan: &mut *self })?;
+            } else if HEADER_MEMBERS.contains(&key.as_str()) {
+                members.insert(key, map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        Ok(DocumentHeader {
+            document: Value::Object(members),
+            features,
+        })
     }
 }
 
-/// Extract EPSG code from CRS object
-fn extract_epsg_from_crs(crs: &serde_json::Value) -> Option<u32> {
-    // Try to extract from properties.name
-    if let Some(props) = crs.get("properties") {
-        if let Some(name) = props.get("name") {
-            if let Some(name_str) = name.as_str() {
-                // Parse "EPSG:4326" or "urn:ogc:def:crs:EPSG::4326"
-                if let Some(epsg_str) = name_str.split(':').next_back() {
-                    return epsg_str.parse().ok();
+/// Visitor for a document's `features` member; anything but an array counts as none
+struct FeaturesSeed<'s, 'f> {
+    scan: &'s mut DocumentScan<'f>,
+}
+
+impl<'de> DeserializeSeed<'de> for FeaturesSeed<'_, '_> {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Option<usize>, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FeaturesSeed<'_, '_> {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a features array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Option<usize>, A::Error> {
+        let mut count = 0;
+        match self.scan.on_feature.as_mut() {
+            Some(on_feature) => {
+                while let Some(member) = seq.next_element::<Value>()? {
+                    if let Err(error) = on_feature(count, member) {
+                        self.scan.stopped = Some(error);
+                        return Err(de::Error::custom("feature scan stopped"));
+                    }
+                    count += 1;
+                }
+            }
+            None => {
+                while seq.next_element::<IgnoredAny>()?.is_some() {
+                    count += 1;
                 }
             }
         }
+        Ok(Some(count))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Option<usize>, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(None)
+    }
+
+    fn visit_bool<E: de::Error>(self, _: bool) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
+    }
+
+    fn visit_i64<E: de::Error>(self, _: i64) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
     }
-    None
+
+    fn visit_u64<E: de::Error>(self, _: u64) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
+    }
+
+    fn visit_f64<E: de::Error>(self, _: f64) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Option<usize>, E> {
+        Ok(None)
+    }
+}
+
+/// Check that a document is a FeatureCollection, Feature or Geometry
+fn check_document(document: &Value) -> std::result::Result<(), String> {
+    match document.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => match document.get("features") {
+            Some(Value::Array(_)) => Ok(()),
+            _ => Err("FeatureCollection has no features array".to_string()),
+        },
+        Some(_) => Ok(()),
+        None => Err("missing \"type\" member".to_string()),
+    }
+}
+
+/// First non-blank string among a document's top-level foreign members
+///
+/// Open-data publishers commonly declare the dataset license and the credit
+/// line it requires as members next to `features`.
+fn foreign_member(document: &Value, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| {
+        crate::models::normalize_credit(document.get(*name).and_then(Value::as_str))
+    })
+}
+
+/// Parse and check a GeoJSON geometry, normalizing it through the geojson crate
+fn parse_geometry(value: &Value) -> std::result::Result<Value, (FeatureErrorKind, String)> {
+    let geometry: geojson::Geometry = serde_json::from_value(value.clone())
+        .map_err(|e| (FeatureErrorKind::InvalidGeometry, e.to_string()))?;
+    let value = serde_json::to_value(&geometry)
+        .map_err(|e| (FeatureErrorKind::InvalidGeometry, e.to_string()))?;
+    check_geometry(&value)?;
+    Ok(value)
+}
+
+/// Extract EPSG code from a legacy CRS member
+///
+/// Accepts "EPSG:4326", "urn:ogc:def:crs:EPSG::4326" and the CRS84 names
+/// ("urn:ogc:def:crs:OGC:1.3:CRS84", "OGC:CRS84"), which map to 4326.
+pub fn extract_epsg_from_crs(crs: &serde_json::Value) -> Option<u32> {
+    let name = crs_name(crs)?;
+    if is_crs84(name) {
+        return Some(4326);
+    }
+    name.split(':').next_back()?.parse().ok()
+}
+
+/// Whether a legacy CRS member names CRS84, which fixes the order to lon,lat
+pub fn declares_crs84(crs: &serde_json::Value) -> bool {
+    crs_name(crs).is_some_and(is_crs84)
+}
+
+fn crs_name(crs: &serde_json::Value) -> Option<&str> {
+    crs.get("properties")?.get("name")?.as_str()
+}
+
+fn is_crs84(name: &str) -> bool {
+    name.rsplit(':').next().is_some_and(|code| code.eq_ignore_ascii_case("CRS84"))
+}
+
+/// Decide the file's axis order and swap latitude-first coordinates to lon,lat
+fn normalize_axis_order(
+    features: &mut [FormatFeature],
+    options: &FormatOptions,
+    crs84: bool,
+) -> AxisOrderDecision {
+    let mut votes = AxisVotes::new(options.extent);
+    if options.axis_order == AxisOrder::Auto && !crs84 {
+        for geometry in features.iter().filter_map(|f| f.geometry.as_ref()) {
+            votes.add(geometry);
+        }
+    }
+    let decision = axis_decision(options, crs84, &votes);
+
+    if decision.applied == AxisOrder::LatLon {
+        for geometry in features.iter_mut().filter_map(|f| f.geometry.as_mut()) {
+            swap_axes(geometry);
+        }
+    }
+
+    decision
+}
+
+/// Axis order of a file, deciding `Auto` by the votes of its geometries
+fn axis_decision(options: &FormatOptions, crs84: bool, votes: &AxisVotes) -> AxisOrderDecision {
+    let requested = options.axis_order;
+    let (applied, reason) = match requested {
+        AxisOrder::Auto if crs84 => (AxisOrder::LonLat, "declared CRS84".to_string()),
+        AxisOrder::Auto => votes.decide(),
+        order => (order, "requested".to_string()),
+    };
+
+    AxisOrderDecision { requested, applied, reason }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::formats::{feature_channel, IngestBuffers, ReadPolicy};
 
     #[tokio::test]
     async fn test_geojson_reader_feature_collection() {
@@ -230,6 +716,24 @@ mod tests {
         assert_eq!(result.features[0].id, "feature1");
     }
 
+    #[tokio::test]
+    async fn test_geojson_reader_reads_license_members() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("parks.geojson");
+        let content = r#"{
+            "type": "FeatureCollection",
+            "licence": "ODbL-1.0",
+            "attribution": " © OpenStreetMap contributors ",
+            "features": []
+        }"#;
+        fs::write(&file_path, content).unwrap();
+
+        let metadata = GeoJsonReader.read(&file_path).await.unwrap().format_metadata;
+
+        assert_eq!(metadata.license.as_deref(), Some("ODbL-1.0"));
+        assert_eq!(metadata.attribution.as_deref(), Some("© OpenStreetMap contributors"));
+    }
+
     #[tokio::test]
     async fn test_geojson_reader_single_feature() {
         let reader = GeoJsonReader;
@@ -272,6 +776,258 @@ mod tests {
         assert!(!validation.errors.is_empty());
     }
 
+    const CORRUPT_COLLECTION: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "id": "a", "geometry": {"type": "Point", "coordinates": [0.0, 0.0]}, "properties": {}},
+            {"type": "Feature", "id": "b", "geometry": {"type": "Point", "coordinates": "oops"}, "properties": {}},
+            "not a feature",
+            {"type": "Feature", "id": "d", "geometry": {"type": "LineString", "coordinates": [[0.0, 0.0]]}, "properties": {}},
+            {"type": "Feature", "id": "e", "geometry": null, "properties": [1, 2]},
+            {"type": "Feature", "id": "f", "geometry": {"type": "Point", "coordinates": [1.0, 1.0]}, "properties": {"ok": true}}
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn test_geojson_reader_strict_fails_on_corrupt_feature() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("corrupt.geojson");
+        fs::write(&file_path, CORRUPT_COLLECTION).unwrap();
+
+        let err = GeoJsonReader.read(&file_path).await.unwrap_err();
+        assert!(err.to_string().contains("feature 1 (id b)"));
+    }
+
+    #[tokio::test]
+    async fn test_geojson_reader_lenient_skips_corrupt_features() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("corrupt.geojson");
+        fs::write(&file_path, CORRUPT_COLLECTION).unwrap();
+
+        let options = FormatOptions::new().with_read_policy(ReadPolicy::lenient(10));
+        let result = GeoJsonReader.read_with_options(&file_path, &options).await.unwrap();
+
+        let ids: Vec<&str> = result.features.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "f"]);
+
+        let errors: Vec<(usize, FeatureErrorKind)> =
+            result.errors.iter().map(|e| (e.index, e.kind)).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (1, FeatureErrorKind::InvalidGeometry),
+                (2, FeatureErrorKind::Malformed),
+                (3, FeatureErrorKind::InvalidGeometry),
+                (4, FeatureErrorKind::InvalidProperties),
+            ]
+        );
+        assert_eq!(result.errors[0].id.as_deref(), Some("b"));
+        assert_eq!(result.errors[1].id, None);
+
+        // A budget smaller than the number of bad features still aborts
+        let options = FormatOptions::new().with_read_policy(ReadPolicy::lenient(3));
+        assert!(GeoJsonReader.read_with_options(&file_path, &options).await.is_err());
+
+        // Per-feature problems are left to the read policy
+        assert!(GeoJsonReader.validate(&file_path).await.unwrap().is_valid());
+    }
+
+    const LATLON_POINTS: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "geometry": { "type": "Point", "coordinates": [-6.2, 106.8] },
+              "properties": {} },
+            { "type": "Feature", "geometry": { "type": "Point", "coordinates": [-6.1, 106.9, 12.0] },
+              "properties": {} }
+        ]
+    }"#;
+
+    async fn read_points(content: &str, options: &FormatOptions) -> FormatDataset {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("points.geojson");
+        fs::write(&file_path, content).unwrap();
+        GeoJsonReader.read_with_options(&file_path, options).await.unwrap()
+    }
+
+    #[test]
+    fn test_crs84_names_map_to_4326() {
+        let crs84 = serde_json::json!({
+            "type": "name",
+            "properties": { "name": "urn:ogc:def:crs:OGC:1.3:CRS84" }
+        });
+        let epsg = serde_json::json!({
+            "type": "name",
+            "properties": { "name": "urn:ogc:def:crs:EPSG::4326" }
+        });
+
+        assert_eq!(extract_epsg_from_crs(&crs84), Some(4326));
+        assert!(declares_crs84(&crs84));
+        assert_eq!(extract_epsg_from_crs(&epsg), Some(4326));
+        assert!(!declares_crs84(&epsg));
+    }
+
+    #[tokio::test]
+    async fn test_default_axis_order_leaves_coordinates() {
+        let dataset = read_points(LATLON_POINTS, &FormatOptions::new()).await;
+
+        let decision = dataset.format_metadata.axis_order.unwrap();
+        assert_eq!(decision.requested, AxisOrder::LonLat);
+        assert_eq!(decision.applied, AxisOrder::LonLat);
+        assert_eq!(
+            dataset.features[0].geometry.as_ref().unwrap()["coordinates"],
+            serde_json::json!([-6.2, 106.8])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_latlon_axis_order_swaps_coordinates() {
+        let options = FormatOptions::new().with_axis_order(AxisOrder::LatLon);
+        let dataset = read_points(LATLON_POINTS, &options).await;
+
+        let decision = dataset.format_metadata.axis_order.unwrap();
+        assert_eq!(decision.applied, AxisOrder::LatLon);
+        assert_eq!(decision.reason, "requested");
+        assert_eq!(
+            dataset.features[1].geometry.as_ref().unwrap()["coordinates"],
+            serde_json::json!([106.9, -6.1, 12.0])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_axis_order_detects_latlon() {
+        let options = FormatOptions::new().with_axis_order(AxisOrder::Auto);
+        let dataset = read_points(LATLON_POINTS, &options).await;
+
+        let decision = dataset.format_metadata.axis_order.unwrap();
+        assert_eq!(decision.requested, AxisOrder::Auto);
+        assert_eq!(decision.applied, AxisOrder::LatLon);
+        assert_eq!(
+            dataset.features[0].geometry.as_ref().unwrap()["coordinates"],
+            serde_json::json!([106.8, -6.2])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_auto_axis_order_trusts_crs84() {
+        // Ambiguous coordinates that would fit the extent only lat,lon
+        let content = r#"{
+            "type": "FeatureCollection",
+            "crs": { "type": "name", "properties": { "name": "urn:ogc:def:crs:OGC:1.3:CRS84" } },
+            "features": [
+                { "type": "Feature", "geometry": { "type": "Point", "coordinates": [51.0, 11.0] },
+                  "properties": {} }
+            ]
+        }"#;
+        let options = FormatOptions::new()
+            .with_axis_order(AxisOrder::Auto)
+            .with_extent(Some([10.0, 50.0, 12.0, 52.0]));
+        let dataset = read_points(content, &options).await;
+
+        assert_eq!(dataset.crs, 4326);
+        let decision = dataset.format_metadata.axis_order.unwrap();
+        assert_eq!(decision.applied, AxisOrder::LonLat);
+        assert_eq!(decision.reason, "declared CRS84");
+        assert_eq!(
+            dataset.features[0].geometry.as_ref().unwrap()["coordinates"],
+            serde_json::json!([51.0, 11.0])
+        );
+    }
+
+    /// Read through `read_streaming`, collecting the streamed features
+    async fn read_streamed(
+        content: &str,
+        options: &FormatOptions,
+    ) -> (Result<FormatDataset>, Vec<FormatFeature>) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("streamed.geojson");
+        fs::write(&file_path, content).unwrap();
+
+        let (sink, mut stream) = feature_channel(IngestBuffers::new(2, 1));
+        let collect = async {
+            let mut features = Vec::new();
+            if stream.header().await.is_some() {
+                while let Some(batch) = stream.next_batch().await {
+                    features.extend(batch);
+                }
+            }
+            features
+        };
+        tokio::join!(GeoJsonReader.read_streaming(&file_path, options, sink), collect)
+    }
+
+    #[tokio::test]
+    async fn test_streaming_read_matches_whole_read() {
+        let options = FormatOptions::new().with_read_policy(ReadPolicy::lenient(10));
+        let (dataset, features) = read_streamed(CORRUPT_COLLECTION, &options).await;
+        let dataset = dataset.unwrap();
+
+        assert!(dataset.features.is_empty());
+        let ids: Vec<&str> = features.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "f"]);
+        let indexes: Vec<usize> = dataset.errors.iter().map(|e| e.index).collect();
+        assert_eq!(indexes, vec![1, 2, 3, 4]);
+
+        let (dataset, _) = read_streamed(CORRUPT_COLLECTION, &FormatOptions::new()).await;
+        assert!(dataset.unwrap_err().to_string().contains("feature 1 (id b)"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_read_decides_axis_order_before_features() {
+        let options = FormatOptions::new().with_axis_order(AxisOrder::Auto);
+        let (dataset, features) = read_streamed(LATLON_POINTS, &options).await;
+
+        let decision = dataset.unwrap().format_metadata.axis_order.unwrap();
+        assert_eq!(decision.applied, AxisOrder::LatLon);
+        assert_eq!(
+            features[1].geometry.as_ref().unwrap()["coordinates"],
+            serde_json::json!([106.9, -6.1, 12.0])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_streaming_read_of_single_feature_reads_it_whole() {
+        let content = r#"{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [1.0, 2.0] },
+            "properties": { "name": "Single Feature" }
+        }"#;
+        let (dataset, features) = read_streamed(content, &FormatOptions::new()).await;
+
+        assert_eq!(dataset.unwrap().crs, 4326);
+        assert_eq!(features.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_inspection_tallies_every_feature_but_samples_few() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("points.geojson");
+        fs::write(&file_path, LATLON_POINTS).unwrap();
+
+        let options = FormatOptions::new().with_axis_order(AxisOrder::Auto);
+        let inspection = GeoJsonReader.inspect(&file_path, &options, 1).await.unwrap();
+
+        assert_eq!(inspection.method, InspectMethod::Scan);
+        assert_eq!(inspection.crs, Some(4326));
+        assert_eq!(inspection.feature_count, Some(2));
+        assert_eq!(inspection.sampled_features, 1);
+        assert_eq!(inspection.extent, Some([106.8, -6.2, 106.9, -6.1]));
+        assert!(inspection.estimated_read_time().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_validation_reports_missing_features_array() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("empty.geojson");
+        fs::write(&file_path, r#"{"type": "FeatureCollection", "features": {}}"#).unwrap();
+
+        let validation = GeoJsonReader.validate(&file_path).await.unwrap();
+
+        assert_eq!(
+            validation.errors,
+            vec!["Invalid GeoJSON: FeatureCollection has no features array".to_string()]
+        );
+    }
+
     #[test]
     fn test_supported_extensions() {
         let reader = GeoJsonReader;
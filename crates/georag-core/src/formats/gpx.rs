@@ -1,6 +1,8 @@
 use async_trait::async_trait;
-use gpx::{read, Gpx};
+use gpx::{read, Gpx, Route, Track, Waypoint};
+use quick_xml::events::Event;
 use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
@@ -8,25 +10,34 @@ use std::path::Path;
 use crate::error::{GeoragError, Result};
 use crate::formats::validation::FormatValidator;
 use crate::formats::{
-    FormatDataset, FormatFeature, FormatMetadata, FormatReader, FormatValidation,
+    check_geometry, FeatureError, FeatureErrorKind, FeatureErrors, FormatDataset, FormatFeature,
+    FormatMetadata, FormatOptions, FormatReader, FormatValidation, OptionSpec, ReadPolicy,
 };
+use crate::models::ValidityMode;
+
+/// Top-level GPX elements that hold features
+const FEATURE_ELEMENTS: [&[u8]; 3] = [b"wpt", b"trk", b"rte"];
 
 /// GPX format reader
+///
+/// A GPX file that the gpx crate rejects as a whole is read element by element
+/// under a lenient [`ReadPolicy`], so one bad waypoint, track or route only
+/// skips that element. The file must still be well-formed XML.
 pub struct GpxReader;
 
 #[async_trait]
 impl FormatReader for GpxReader {
     async fn read(&self, path: &Path) -> Result<FormatDataset> {
-        self.read_internal(path, None).await
+        self.read_with_options(path, &FormatOptions::new()).await
     }
 
     async fn read_with_options(
         &self,
         path: &Path,
-        options: &crate::formats::FormatOptions,
+        options: &FormatOptions,
     ) -> Result<FormatDataset> {
         let track_type = options.get("track_type").map(|s| s.as_str());
-        self.read_internal(path, track_type).await
+        self.read_internal(path, track_type, options.read_policy).await
     }
 
     fn supported_extensions(&self) -> &[&str] {
@@ -37,6 +48,15 @@ impl FormatReader for GpxReader {
         "GPX"
     }
 
+    fn option_schema(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::choice(
+            "track_type",
+            &["waypoints", "tracks", "routes", "all"],
+            "Kind of GPX elements to read",
+        )
+        .with_default("all")]
+    }
+
     async fn validate(&self, path: &Path) -> Result<FormatValidation> {
         // Basic file validation
         let mut validation = FormatValidator::validate_file_exists(path);
@@ -47,13 +67,14 @@ impl FormatReader for GpxReader {
         // Validate XML structure
         let xml_validation = FormatValidator::validate_xml_structure(path);
 
-        // If XML is valid, try to parse as GPX
+        // If XML is valid, try to parse as GPX. A lenient read can still skip
+        // the elements that fail, so this is only a warning.
         if xml_validation.is_valid() {
             match File::open(path) {
                 Ok(file) => {
                     let reader = BufReader::new(file);
                     if let Err(e) = read(reader) {
-                        validation.errors.push(format!("Invalid GPX: {}", e));
+                        validation.warnings.push(format!("Invalid GPX: {}", e));
                     }
                 }
                 Err(e) => {
@@ -67,50 +88,88 @@ impl FormatReader for GpxReader {
     }
 }
 
+/// Feature kinds selected by the `track_type` option
+#[derive(Debug, Clone, Copy)]
+struct Selection {
+    waypoints: bool,
+    tracks: bool,
+    routes: bool,
+}
+
+impl Selection {
+    fn parse(track_type: Option<&str>) -> Result<Self> {
+        let only = |waypoints, tracks, routes| Self { waypoints, tracks, routes };
+        match track_type {
+            Some("waypoints") => Ok(only(true, false, false)),
+            Some("tracks") => Ok(only(false, true, false)),
+            Some("routes") => Ok(only(false, false, true)),
+            // Extract all types (default behavior)
+            Some("all") | None => Ok(only(true, true, true)),
+            Some(other) => Err(GeoragError::FormatError {
+                format: "GPX".to_string(),
+                message: format!(
+                    "Invalid track type '{}'. Valid options: waypoints, tracks, routes, all",
+                    other
+                ),
+            }),
+        }
+    }
+}
+
+/// Elements seen so far, numbering feature IDs and error positions
+#[derive(Debug, Default)]
+struct ElementCounts {
+    waypoints: usize,
+    tracks: usize,
+    routes: usize,
+    features: usize,
+}
+
+impl ElementCounts {
+    fn next_feature(&mut self) -> usize {
+        self.features += 1;
+        self.features - 1
+    }
+}
+
 impl GpxReader {
     /// Internal read method that supports track type filtering
-    async fn read_internal(&self, path: &Path, track_type: Option<&str>) -> Result<FormatDataset> {
+    async fn read_internal(
+        &self,
+        path: &Path,
+        track_type: Option<&str>,
+        policy: ReadPolicy,
+    ) -> Result<FormatDataset> {
+        let selection = Selection::parse(track_type)?;
+
         // Open and parse the GPX file
-        let file = File::open(path).map_err(|e| GeoragError::FormatError {
+        let content = fs::read_to_string(path).map_err(|e| GeoragError::FormatError {
             format: "GPX".to_string(),
             message: format!("Failed to open GPX file: {}", e),
         })?;
 
-        let reader = BufReader::new(file);
-        let gpx: Gpx = read(reader).map_err(|e| GeoragError::FormatValidation {
-            format: "GPX".to_string(),
-            reason: format!("Failed to parse GPX: {}", e),
-        })?;
-
-        // Extract features based on track type filter
-        let mut features = Vec::new();
-
-        match track_type {
-            Some("waypoints") => {
-                features.extend(self.extract_waypoints(&gpx)?);
-            }
-            Some("tracks") => {
-                features.extend(self.extract_tracks(&gpx)?);
+        let mut errors = FeatureErrors::new("GPX", policy);
+        let mut counts = ElementCounts::default();
+        let (gpx, features) = match read(content.as_bytes()) {
+            Ok(gpx) => {
+                let features = self.extract_features(&gpx, selection, &mut counts, &mut errors)?;
+                (gpx, features)
             }
-            Some("routes") => {
-                features.extend(self.extract_routes(&gpx)?);
+            Err(e) if policy.validity == ValidityMode::Lenient => {
+                tracing::warn!(
+                    "GPX {} is invalid ({}); reading it element by element",
+                    path.display(),
+                    e
+                );
+                self.read_elements(&content, selection, &mut counts, &mut errors)?
             }
-            Some("all") | None => {
-                // Extract all types (default behavior)
-                features.extend(self.extract_waypoints(&gpx)?);
-                features.extend(self.extract_tracks(&gpx)?);
-                features.extend(self.extract_routes(&gpx)?);
-            }
-            Some(other) => {
-                return Err(GeoragError::FormatError {
+            Err(e) => {
+                return Err(GeoragError::FormatValidation {
                     format: "GPX".to_string(),
-                    message: format!(
-                        "Invalid track type '{}'. Valid options: waypoints, tracks, routes, all",
-                        other
-                    ),
-                });
+                    reason: format!("Failed to parse GPX: {}", e),
+                })
             }
-        }
+        };
 
         // Get dataset name from filename
         let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string();
@@ -123,170 +182,266 @@ impl GpxReader {
             format_metadata: metadata,
             crs: 4326, // GPX always uses WGS84 (EPSG:4326) per specification
             features,
+            errors: errors.into_vec(),
         })
     }
-    /// Extract waypoints from GPX as Point features
-    fn extract_waypoints(&self, gpx: &Gpx) -> Result<Vec<FormatFeature>> {
-        let mut features = Vec::new();
 
-        for (idx, waypoint) in gpx.waypoints.iter().enumerate() {
-            let mut properties = HashMap::new();
-
-            // Add waypoint metadata
-            properties.insert("type".to_string(), serde_json::json!("waypoint"));
-
-            if let Some(name) = &waypoint.name {
-                properties.insert("name".to_string(), serde_json::json!(name));
-            }
+    /// Extract the selected features of a parsed GPX document
+    fn extract_features(
+        &self,
+        gpx: &Gpx,
+        selection: Selection,
+        counts: &mut ElementCounts,
+        errors: &mut FeatureErrors,
+    ) -> Result<Vec<FormatFeature>> {
+        let mut features = Vec::new();
 
-            if let Some(desc) = &waypoint.description {
-                properties.insert("description".to_string(), serde_json::json!(desc));
+        for waypoint in &gpx.waypoints {
+            counts.waypoints += 1;
+            if selection.waypoints {
+                let feature = self.waypoint_feature(counts.waypoints - 1, waypoint);
+                accept(feature, counts.next_feature(), &mut features, errors)?;
             }
+        }
 
-            if let Some(time) = waypoint.time {
-                if let Ok(time_str) = time.format() {
-                    properties.insert("time".to_string(), serde_json::json!(time_str));
+        for track in &gpx.tracks {
+            counts.tracks += 1;
+            if selection.tracks {
+                for feature in self.track_features(counts.tracks - 1, track) {
+                    accept(feature, counts.next_feature(), &mut features, errors)?;
                 }
             }
+        }
 
-            if let Some(elevation) = waypoint.elevation {
-                properties.insert("elevation".to_string(), serde_json::json!(elevation));
+        for route in &gpx.routes {
+            counts.routes += 1;
+            if selection.routes {
+                let feature = self.route_feature(counts.routes - 1, route);
+                accept(feature, counts.next_feature(), &mut features, errors)?;
             }
-
-            // Create Point geometry with optional elevation
-            let geometry = if let Some(elevation) = waypoint.elevation {
-                serde_json::json!({
-                    "type": "Point",
-                    "coordinates": [waypoint.point().x(), waypoint.point().y(), elevation]
-                })
-            } else {
-                serde_json::json!({
-                    "type": "Point",
-                    "coordinates": [waypoint.point().x(), waypoint.point().y()]
-                })
-            };
-
-            features.push(FormatFeature {
-                id: format!("waypoint_{}", idx),
-                geometry: Some(geometry),
-                properties,
-            });
         }
 
         Ok(features)
     }
 
-    /// Extract tracks from GPX as LineString features
-    fn extract_tracks(&self, gpx: &Gpx) -> Result<Vec<FormatFeature>> {
+    /// Read a GPX document one top-level waypoint, track or route at a time
+    ///
+    /// Each element is parsed on its own inside a copy of the root `<gpx>` tag;
+    /// the ones that fail are recorded as malformed features.
+    fn read_elements(
+        &self,
+        content: &str,
+        selection: Selection,
+        counts: &mut ElementCounts,
+        errors: &mut FeatureErrors,
+    ) -> Result<(Gpx, Vec<FormatFeature>)> {
+        let invalid =
+            |reason: String| GeoragError::FormatValidation { format: "GPX".to_string(), reason };
+
+        let mut reader = quick_xml::Reader::from_str(content);
+        let mut depth = 0usize;
+        let mut root: Option<&str> = None;
+        let mut element: Option<(usize, Vec<u8>)> = None;
         let mut features = Vec::new();
 
-        for (track_idx, track) in gpx.tracks.iter().enumerate() {
-            // Each track can have multiple segments
-            for (seg_idx, segment) in track.segments.iter().enumerate() {
-                let mut properties = HashMap::new();
+        loop {
+            let start = reader.buffer_position() as usize;
+            let event = reader
+                .read_event()
+                .map_err(|e| invalid(format!("Failed to parse GPX: {}", e)))?;
+            let end = reader.buffer_position() as usize;
+
+            match event {
+                Event::Start(tag) => {
+                    let name = tag.name().as_ref().to_vec();
+                    if depth == 0 {
+                        root = Some(&content[start..end]);
+                    } else if depth == 1 && FEATURE_ELEMENTS.contains(&name.as_slice()) {
+                        element = Some((start, name));
+                    }
+                    depth += 1;
+                }
+                Event::Empty(tag) if depth == 1 => {
+                    let name = tag.name().as_ref().to_vec();
+                    if let Some(root) = root.filter(|_| FEATURE_ELEMENTS.contains(&name.as_slice()))
+                    {
+                        let xml = &content[start..end];
+                        features.extend(
+                            self.read_element(root, &name, xml, selection, counts, errors)?,
+                        );
+                    }
+                }
+                Event::End(_) => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 1 {
+                        if let (Some(root), Some((element_start, name))) = (root, element.take()) {
+                            let xml = &content[element_start..end];
+                            features.extend(
+                                self.read_element(root, &name, xml, selection, counts, errors)?,
+                            );
+                        }
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
 
-                // Add track metadata
-                properties.insert("type".to_string(), serde_json::json!("track"));
+        // The root element on its own carries the document metadata
+        let root = root.ok_or_else(|| invalid("no <gpx> element found".to_string()))?;
+        let gpx = read(format!("{}</gpx>", root).as_bytes())
+            .map_err(|e| invalid(format!("Failed to parse GPX: {}", e)))?;
 
-                if let Some(name) = &track.name {
-                    properties.insert("name".to_string(), serde_json::json!(name));
-                }
+        Ok((gpx, features))
+    }
 
-                if let Some(desc) = &track.description {
-                    properties.insert("description".to_string(), serde_json::json!(desc));
+    /// Parse one top-level element wrapped in the document's root tag
+    fn read_element(
+        &self,
+        root: &str,
+        name: &[u8],
+        xml: &str,
+        selection: Selection,
+        counts: &mut ElementCounts,
+        errors: &mut FeatureErrors,
+    ) -> Result<Vec<FormatFeature>> {
+        match read(format!("{}{}</gpx>", root, xml).as_bytes()) {
+            Ok(gpx) => self.extract_features(&gpx, selection, counts, errors),
+            Err(e) => {
+                let (id, selected) = match name {
+                    b"wpt" => {
+                        counts.waypoints += 1;
+                        (format!("waypoint_{}", counts.waypoints - 1), selection.waypoints)
+                    }
+                    b"trk" => {
+                        counts.tracks += 1;
+                        (format!("track_{}", counts.tracks - 1), selection.tracks)
+                    }
+                    _ => {
+                        counts.routes += 1;
+                        (format!("route_{}", counts.routes - 1), selection.routes)
+                    }
+                };
+                if selected {
+                    let index = counts.next_feature();
+                    errors.record(
+                        FeatureError::new(index, FeatureErrorKind::Malformed, e.to_string())
+                            .with_id(id),
+                    )?;
                 }
+                Ok(Vec::new())
+            }
+        }
+    }
 
-                properties.insert("segment".to_string(), serde_json::json!(seg_idx));
-
-                // Extract track points with elevation if available
-                let has_elevation = segment.points.iter().any(|p| p.elevation.is_some());
-
-                let coordinates: Vec<serde_json::Value> = if has_elevation {
-                    segment
-                        .points
-                        .iter()
-                        .map(|point| {
-                            let elevation = point.elevation.unwrap_or(0.0);
-                            serde_json::json!([point.point().x(), point.point().y(), elevation])
-                        })
-                        .collect()
-                } else {
-                    segment
-                        .points
-                        .iter()
-                        .map(|point| serde_json::json!([point.point().x(), point.point().y()]))
-                        .collect()
-                };
+    /// Convert a waypoint to a Point feature
+    fn waypoint_feature(&self, idx: usize, waypoint: &Waypoint) -> FormatFeature {
+        let mut properties = HashMap::new();
 
-                // Create LineString geometry
-                let geometry = serde_json::json!({
-                    "type": "LineString",
-                    "coordinates": coordinates
-                });
-
-                features.push(FormatFeature {
-                    id: format!("track_{}_{}", track_idx, seg_idx),
-                    geometry: Some(geometry),
-                    properties,
-                });
+        // Add waypoint metadata
+        properties.insert("type".to_string(), serde_json::json!("waypoint"));
+
+        if let Some(name) = &waypoint.name {
+            properties.insert("name".to_string(), serde_json::json!(name));
+        }
+
+        if let Some(desc) = &waypoint.description {
+            properties.insert("description".to_string(), serde_json::json!(desc));
+        }
+
+        if let Some(time) = waypoint.time {
+            if let Ok(time_str) = time.format() {
+                properties.insert("time".to_string(), serde_json::json!(time_str));
             }
         }
 
-        Ok(features)
+        if let Some(elevation) = waypoint.elevation {
+            properties.insert("elevation".to_string(), serde_json::json!(elevation));
+        }
+
+        // Create Point geometry with optional elevation
+        let geometry = if let Some(elevation) = waypoint.elevation {
+            serde_json::json!({
+                "type": "Point",
+                "coordinates": [waypoint.point().x(), waypoint.point().y(), elevation]
+            })
+        } else {
+            serde_json::json!({
+                "type": "Point",
+                "coordinates": [waypoint.point().x(), waypoint.point().y()]
+            })
+        };
+
+        FormatFeature {
+            id: format!("waypoint_{}", idx),
+            geometry: Some(geometry),
+            properties,
+        }
     }
 
-    /// Extract routes from GPX as LineString features
-    fn extract_routes(&self, gpx: &Gpx) -> Result<Vec<FormatFeature>> {
+    /// Convert a track to LineString features, one per segment
+    fn track_features(&self, track_idx: usize, track: &Track) -> Vec<FormatFeature> {
         let mut features = Vec::new();
 
-        for (idx, route) in gpx.routes.iter().enumerate() {
+        // Each track can have multiple segments
+        for (seg_idx, segment) in track.segments.iter().enumerate() {
             let mut properties = HashMap::new();
 
-            // Add route metadata
-            properties.insert("type".to_string(), serde_json::json!("route"));
+            // Add track metadata
+            properties.insert("type".to_string(), serde_json::json!("track"));
 
-            if let Some(name) = &route.name {
+            if let Some(name) = &track.name {
                 properties.insert("name".to_string(), serde_json::json!(name));
             }
 
-            if let Some(desc) = &route.description {
+            if let Some(desc) = &track.description {
                 properties.insert("description".to_string(), serde_json::json!(desc));
             }
 
-            // Extract route points with elevation if available
-            let has_elevation = route.points.iter().any(|p| p.elevation.is_some());
-
-            let coordinates: Vec<serde_json::Value> = if has_elevation {
-                route
-                    .points
-                    .iter()
-                    .map(|point| {
-                        let elevation = point.elevation.unwrap_or(0.0);
-                        serde_json::json!([point.point().x(), point.point().y(), elevation])
-                    })
-                    .collect()
-            } else {
-                route
-                    .points
-                    .iter()
-                    .map(|point| serde_json::json!([point.point().x(), point.point().y()]))
-                    .collect()
-            };
+            properties.insert("segment".to_string(), serde_json::json!(seg_idx));
 
             // Create LineString geometry
             let geometry = serde_json::json!({
                 "type": "LineString",
-                "coordinates": coordinates
+                "coordinates": line_coordinates(&segment.points)
             });
 
             features.push(FormatFeature {
-                id: format!("route_{}", idx),
+                id: format!("track_{}_{}", track_idx, seg_idx),
                 geometry: Some(geometry),
                 properties,
             });
         }
 
-        Ok(features)
+        features
+    }
+
+    /// Convert a route to a LineString feature
+    fn route_feature(&self, idx: usize, route: &Route) -> FormatFeature {
+        let mut properties = HashMap::new();
+
+        // Add route metadata
+        properties.insert("type".to_string(), serde_json::json!("route"));
+
+        if let Some(name) = &route.name {
+            properties.insert("name".to_string(), serde_json::json!(name));
+        }
+
+        if let Some(desc) = &route.description {
+            properties.insert("description".to_string(), serde_json::json!(desc));
+        }
+
+        // Create LineString geometry
+        let geometry = serde_json::json!({
+            "type": "LineString",
+            "coordinates": line_coordinates(&route.points)
+        });
+
+        FormatFeature {
+            id: format!("route_{}", idx),
+            geometry: Some(geometry),
+            properties,
+        }
     }
 
     /// Extract GPX metadata
@@ -299,8 +454,44 @@ impl GpxReader {
             paragraph_count: None,
             extraction_method: Some("gpx-rs".to_string()),
             spatial_association: None,
+            axis_order: None,
+            license: None,
+            attribution: None,
+        }
+    }
+}
+
+/// Coordinates of track or route points, with elevation if any point has one
+fn line_coordinates(points: &[Waypoint]) -> Vec<serde_json::Value> {
+    let has_elevation = points.iter().any(|p| p.elevation.is_some());
+
+    points
+        .iter()
+        .map(|point| {
+            if has_elevation {
+                let elevation = point.elevation.unwrap_or(0.0);
+                serde_json::json!([point.point().x(), point.point().y(), elevation])
+            } else {
+                serde_json::json!([point.point().x(), point.point().y()])
+            }
+        })
+        .collect()
+}
+
+/// Keep a feature whose geometry passes [`check_geometry`], recording it otherwise
+fn accept(
+    feature: FormatFeature,
+    index: usize,
+    features: &mut Vec<FormatFeature>,
+    errors: &mut FeatureErrors,
+) -> Result<()> {
+    match feature.geometry.as_ref().map_or(Ok(()), check_geometry) {
+        Ok(()) => features.push(feature),
+        Err((kind, message)) => {
+            errors.record(FeatureError::new(index, kind, message).with_id(feature.id))?
         }
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -308,6 +499,24 @@ mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_track_type_option_is_validated() {
+        let valid = FormatOptions::new().with_option("track_type", "routes");
+        assert!(valid.validate(&GpxReader).is_ok());
+
+        let err = FormatOptions::new()
+            .with_option("track_type", "trackz")
+            .validate(&GpxReader)
+            .unwrap_err();
+        assert!(err.to_string().contains("expected one of waypoints, tracks, routes, all"));
+
+        let err = FormatOptions::new()
+            .with_option("folder", "A")
+            .validate(&GpxReader)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown option 'folder'"));
+    }
+
     #[tokio::test]
     async fn test_gpx_reader_waypoints() {
         let reader = GpxReader;
@@ -415,6 +624,65 @@ mod tests {
         assert!(!validation.errors.is_empty());
     }
 
+    const CORRUPT_GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test">
+  <wpt lat="47.644548" lon="-122.326897"><name>Good</name></wpt>
+  <wpt lat="north" lon="-122.326897"><name>Bad</name></wpt>
+  <wpt lat="47.5" lon="-122.5"/>
+  <trk>
+    <name>Single point</name>
+    <trkseg><trkpt lat="47.644548" lon="-122.326897"/></trkseg>
+  </trk>
+  <rte>
+    <rtept lat="47.644548" lon="-122.326897"/>
+    <rtept lat="47.644549" lon="-122.326898"/>
+  </rte>
+</gpx>"#;
+
+    #[tokio::test]
+    async fn test_gpx_reader_strict_fails_on_corrupt_waypoint() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("corrupt.gpx");
+        fs::write(&file_path, CORRUPT_GPX).unwrap();
+
+        assert!(GpxReader.read(&file_path).await.is_err());
+
+        // The file is well-formed XML, so validation only warns
+        let validation = GpxReader.validate(&file_path).await.unwrap();
+        assert!(validation.is_valid());
+        assert!(validation.has_warnings());
+    }
+
+    #[tokio::test]
+    async fn test_gpx_reader_lenient_skips_corrupt_elements() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("corrupt.gpx");
+        fs::write(&file_path, CORRUPT_GPX).unwrap();
+
+        let options = FormatOptions::new().with_read_policy(ReadPolicy::lenient(10));
+        let result = GpxReader.read_with_options(&file_path, &options).await.unwrap();
+
+        let ids: Vec<&str> = result.features.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["waypoint_0", "waypoint_2", "route_0"]);
+        assert!(result.format_metadata.format_version.is_some());
+
+        let errors: Vec<(Option<&str>, FeatureErrorKind)> =
+            result.errors.iter().map(|e| (e.id.as_deref(), e.kind)).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (Some("waypoint_1"), FeatureErrorKind::Malformed),
+                (Some("track_0_0"), FeatureErrorKind::InvalidGeometry),
+            ]
+        );
+
+        // Filtering by track type skips the bad waypoint without counting it
+        let options = options.with_option("track_type", "routes");
+        let result = GpxReader.read_with_options(&file_path, &options).await.unwrap();
+        assert_eq!(result.features.len(), 1);
+        assert!(result.errors.is_empty());
+    }
+
     #[test]
     fn test_supported_extensions() {
         let reader = GpxReader;
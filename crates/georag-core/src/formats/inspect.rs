@@ -0,0 +1,285 @@
+//! Quick summaries of a file before it is ingested
+//!
+//! [`FormatReader::inspect`](super::FormatReader::inspect) tells what it can
+//! about a file cheaply: how many features it has, their extent and property
+//! keys, a document's page count, and how long a small sample of features
+//! took to parse. Readers whose format records the count or extent in a
+//! header, or that can scan past features without building them, override it;
+//! the default reads features until the sample is full and stops the read.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::{feature_channel, FormatFeature, FormatOptions, FormatReader, IngestBuffers};
+use crate::error::{GeoragError, Result};
+use crate::geo::axis::{extent_of, Extent};
+
+/// Features parsed for the schema and the timing sample, by default
+pub const DEFAULT_INSPECT_SAMPLE: usize = 100;
+
+/// How an inspection learned the count and extent of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InspectMethod {
+    /// Read from the file's header, without reading features
+    Header,
+
+    /// Tallied over every feature, building only the sample
+    Scan,
+
+    /// Read from the sample alone; exact only if the file ended within it
+    Sample,
+}
+
+/// What [`FormatReader::inspect`] found out about a file
+#[derive(Debug, Clone)]
+pub struct FormatInspection {
+    /// Format name (e.g., "Shapefile", "GeoJSON", "PDF")
+    pub format_name: String,
+
+    /// CRS EPSG code, `None` if the file names one the reader cannot identify
+    pub crs: Option<u32>,
+
+    /// Features in the file, `None` if only a part of it was read
+    pub feature_count: Option<usize>,
+
+    /// Extent of the features in the file's CRS, lon,lat for geographic data
+    ///
+    /// Covers only the sample when `feature_count` is `None`.
+    pub extent: Option<Extent>,
+
+    /// Property keys of the sampled features
+    pub property_keys: BTreeSet<String>,
+
+    /// Page count for document formats
+    pub page_count: Option<usize>,
+
+    /// Features parsed in `sample_time`
+    pub sampled_features: usize,
+
+    /// Time taken to parse the sampled features
+    pub sample_time: Duration,
+
+    /// How the count and extent were learned
+    pub method: InspectMethod,
+}
+
+impl FormatInspection {
+    pub fn new(format_name: impl Into<String>, method: InspectMethod) -> Self {
+        Self {
+            format_name: format_name.into(),
+            crs: None,
+            feature_count: None,
+            extent: None,
+            property_keys: BTreeSet::new(),
+            page_count: None,
+            sampled_features: 0,
+            sample_time: Duration::ZERO,
+            method,
+        }
+    }
+
+    /// Count a sampled feature and take its property keys
+    ///
+    /// The extent is left alone: readers extend it over every feature they
+    /// see, sampled or not, with [`Self::extend_extent`].
+    pub fn add_sample(&mut self, feature: &FormatFeature) {
+        self.sampled_features += 1;
+        self.property_keys.extend(feature.properties.keys().cloned());
+    }
+
+    /// Grow the extent to cover a GeoJSON geometry
+    pub fn extend_extent(&mut self, geometry: &Value) {
+        let Some([min_x, min_y, max_x, max_y]) = extent_of([geometry]) else {
+            return;
+        };
+        let extent = self.extent.get_or_insert([min_x, min_y, max_x, max_y]);
+        *extent = [
+            extent[0].min(min_x),
+            extent[1].min(min_y),
+            extent[2].max(max_x),
+            extent[3].max(max_y),
+        ];
+    }
+
+    /// Time to parse every feature, extrapolated from the sample
+    ///
+    /// `None` without a feature count or without a sample to time.
+    pub fn estimated_read_time(&self) -> Option<Duration> {
+        let count = self.feature_count?;
+        if self.sampled_features == 0 {
+            return None;
+        }
+        let nanos = self.sample_time.as_nanos() * count as u128 / self.sampled_features as u128;
+        Some(Duration::from_nanos(nanos.min(u64::MAX as u128) as u64))
+    }
+
+    /// Whether the extent overlaps `other`, `None` without an extent
+    ///
+    /// Both extents must be in the same CRS. Touching edges count as overlap.
+    pub fn overlaps(&self, other: &Extent) -> Option<bool> {
+        let extent = self.extent?;
+        Some(
+            extent[0] <= other[2]
+                && other[0] <= extent[2]
+                && extent[1] <= other[3]
+                && other[1] <= extent[3],
+        )
+    }
+}
+
+/// Inspect a file by streaming features from `reader` until `sample` arrived
+///
+/// The read is stopped once the sample is full, so the count is only known,
+/// and the extent only whole, for files that end within the first batch
+/// beyond it. This is the default of [`FormatReader::inspect`].
+pub async fn sample_inspection<R: FormatReader + ?Sized>(
+    reader: &R,
+    path: &Path,
+    options: &FormatOptions,
+    sample: usize,
+) -> Result<FormatInspection> {
+    let mut inspection = FormatInspection::new(reader.format_name(), InspectMethod::Sample);
+    let (sink, mut stream) = feature_channel(IngestBuffers::new(sample, 1));
+
+    let started = Instant::now();
+    let sampling = async move {
+        let header = stream.header().await;
+        let mut features = Vec::new();
+        let mut exhausted = header.is_none();
+        while !exhausted && features.len() < sample.max(1) {
+            match stream.next_batch().await {
+                Some(batch) => features.extend(batch),
+                None => exhausted = true,
+            }
+        }
+        // Dropping the stream stops the reader at its next feature
+        (header, features, exhausted, started.elapsed())
+    };
+    let (read, (header, features, exhausted, elapsed)) =
+        tokio::join!(reader.read_streaming(path, options, sink), sampling);
+
+    match read {
+        Ok(_) => {}
+        Err(GeoragError::IngestStopped { .. }) if !exhausted => {}
+        Err(e) => return Err(e),
+    }
+    if let Some(header) = header {
+        inspection.crs = Some(header.crs);
+        inspection.page_count = header.format_metadata.page_count;
+    }
+
+    for feature in &features {
+        inspection.add_sample(feature);
+        if let Some(geometry) = &feature.geometry {
+            inspection.extend_extent(geometry);
+        }
+    }
+    inspection.sample_time = elapsed;
+    inspection.feature_count = exhausted.then_some(features.len());
+    Ok(inspection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{FormatDataset, FormatMetadata};
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    /// Reader of `count` points along the diagonal, ignoring the path
+    struct Points {
+        count: usize,
+    }
+
+    #[async_trait]
+    impl FormatReader for Points {
+        async fn read(&self, _path: &Path) -> Result<FormatDataset> {
+            let features = (0..self.count)
+                .map(|i| FormatFeature {
+                    id: i.to_string(),
+                    geometry: Some(json!({"type": "Point", "coordinates": [i as f64, i as f64]})),
+                    properties: HashMap::from([(format!("key{}", i % 3), json!(i))]),
+                })
+                .collect();
+            Ok(FormatDataset {
+                name: "points".to_string(),
+                format_metadata: FormatMetadata {
+                    format_name: "Points".to_string(),
+                    format_version: None,
+                    layer_name: None,
+                    page_count: None,
+                    paragraph_count: None,
+                    extraction_method: None,
+                    spatial_association: None,
+                    axis_order: None,
+                    license: None,
+                    attribution: None,
+                },
+                crs: 3857,
+                features,
+                errors: Vec::new(),
+            })
+        }
+
+        fn supported_extensions(&self) -> &[&str] {
+            &["pts"]
+        }
+
+        fn format_name(&self) -> &str {
+            "Points"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_inspection_counts_files_ending_within_the_sample() {
+        let reader = Points { count: 5 };
+        let inspection = reader
+            .inspect(Path::new("points.pts"), &FormatOptions::new(), 10)
+            .await
+            .unwrap();
+
+        assert_eq!(inspection.method, InspectMethod::Sample);
+        assert_eq!(inspection.crs, Some(3857));
+        assert_eq!(inspection.feature_count, Some(5));
+        assert_eq!(inspection.sampled_features, 5);
+        assert_eq!(inspection.extent, Some([0.0, 0.0, 4.0, 4.0]));
+        assert_eq!(inspection.property_keys.len(), 3);
+        assert!(inspection.estimated_read_time().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_default_inspection_stops_after_the_sample() {
+        let reader = Points { count: 1000 };
+        let inspection = reader
+            .inspect(Path::new("points.pts"), &FormatOptions::new(), 10)
+            .await
+            .unwrap();
+
+        assert_eq!(inspection.feature_count, None);
+        assert_eq!(inspection.sampled_features, 10);
+        assert_eq!(inspection.extent, Some([0.0, 0.0, 9.0, 9.0]));
+        assert_eq!(inspection.estimated_read_time(), None);
+    }
+
+    #[test]
+    fn test_overlap_and_estimate() {
+        let mut inspection = FormatInspection::new("GeoJSON", InspectMethod::Scan);
+        assert_eq!(inspection.overlaps(&[0.0, 0.0, 1.0, 1.0]), None);
+
+        inspection.extend_extent(&json!({"type": "Point", "coordinates": [106.8, -6.2]}));
+        inspection.extend_extent(&json!({"type": "Point", "coordinates": [107.0, -6.0]}));
+        assert_eq!(inspection.extent, Some([106.8, -6.2, 107.0, -6.0]));
+        assert_eq!(inspection.overlaps(&[106.0, -7.0, 106.9, -5.5]), Some(true));
+        assert_eq!(inspection.overlaps(&[110.0, -8.0, 112.0, -7.0]), Some(false));
+
+        inspection.feature_count = Some(1000);
+        inspection.sampled_features = 10;
+        inspection.sample_time = Duration::from_millis(20);
+        assert_eq!(inspection.estimated_read_time(), Some(Duration::from_secs(2)));
+    }
+}
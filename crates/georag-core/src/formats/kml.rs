@@ -7,7 +7,8 @@ use std::path::Path;
 use crate::error::{GeoragError, Result};
 use crate::formats::validation::FormatValidator;
 use crate::formats::{
-    FormatDataset, FormatFeature, FormatMetadata, FormatReader, FormatValidation,
+    check_geometry, FeatureError, FeatureErrorKind, FeatureErrors, FormatDataset, FormatFeature,
+    FormatMetadata, FormatOptions, FormatReader, FormatValidation, OptionSpec, ReadPolicy,
 };
 
 /// KML format reader
@@ -16,16 +17,16 @@ pub struct KmlReader;
 #[async_trait]
 impl FormatReader for KmlReader {
     async fn read(&self, path: &Path) -> Result<FormatDataset> {
-        self.read_internal(path, None).await
+        self.read_with_options(path, &FormatOptions::new()).await
     }
 
     async fn read_with_options(
         &self,
         path: &Path,
-        options: &crate::formats::FormatOptions,
+        options: &FormatOptions,
     ) -> Result<FormatDataset> {
         let folder_path = options.get("folder").map(|s| s.as_str());
-        self.read_internal(path, folder_path).await
+        self.read_internal(path, folder_path, options.read_policy).await
     }
 
     fn supported_extensions(&self) -> &[&str] {
@@ -36,6 +37,13 @@ impl FormatReader for KmlReader {
         "KML"
     }
 
+    fn option_schema(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::string(
+            "folder",
+            "Folder path to read (e.g. \"Parent/Child\"); placemarks outside it are skipped",
+        )]
+    }
+
     async fn validate(&self, path: &Path) -> Result<FormatValidation> {
         // Basic file validation
         let mut validation = FormatValidator::validate_file_exists(path);
@@ -71,6 +79,7 @@ impl KmlReader {
         &self,
         path: &Path,
         folder_filter: Option<&str>,
+        policy: ReadPolicy,
     ) -> Result<FormatDataset> {
         // Read the KML file as string
         let content = fs::read_to_string(path).map_err(|e| GeoragError::FormatError {
@@ -90,11 +99,13 @@ impl KmlReader {
 
         // Extract features from the KML structure
         let mut features = Vec::new();
+        let mut errors = FeatureErrors::new("KML", policy);
         let mut feature_counter = 0;
 
         self.extract_features_recursive(
             &kml,
             &mut features,
+            &mut errors,
             &mut feature_counter,
             Vec::new(),
             target_folders.as_ref(),
@@ -113,9 +124,13 @@ impl KmlReader {
                 paragraph_count: None,
                 extraction_method: Some("kml-rs".to_string()),
                 spatial_association: None,
+                axis_order: None,
+                license: None,
+                attribution: None,
             },
             crs: 4326, // KML always uses WGS84 (EPSG:4326) per specification
             features,
+            errors: errors.into_vec(),
         })
     }
     // Recursively extract features from KML structure
@@ -123,6 +138,7 @@ impl KmlReader {
         &self,
         kml: &Kml,
         features: &mut Vec<FormatFeature>,
+        errors: &mut FeatureErrors,
         counter: &mut usize,
         folder_path: Vec<String>,
         target_folders: Option<&Vec<String>>,
@@ -153,6 +169,7 @@ impl KmlReader {
                     self.extract_features_recursive(
                         element,
                         features,
+                        errors,
                         counter,
                         folder_path.clone(),
                         target_folders,
@@ -170,6 +187,7 @@ impl KmlReader {
                     self.extract_features_recursive(
                         element,
                         features,
+                        errors,
                         counter,
                         new_path.clone(),
                         target_folders,
@@ -181,6 +199,7 @@ impl KmlReader {
                     self.extract_features_recursive(
                         element,
                         features,
+                        errors,
                         counter,
                         folder_path.clone(),
                         target_folders,
@@ -189,11 +208,13 @@ impl KmlReader {
             }
             Kml::Placemark(placemark) => {
                 if at_target {
-                    // Extract feature from placemark
-                    if let Some(feature) =
-                        self.extract_placemark(placemark, *counter, &folder_path)?
-                    {
-                        features.push(feature);
+                    // Extract feature from placemark; placemarks without geometry are skipped
+                    if placemark.geometry.is_some() {
+                        match self.extract_placemark(placemark, *counter, &folder_path) {
+                            Ok(Some(feature)) => features.push(feature),
+                            Ok(None) => {}
+                            Err(error) => errors.record(error)?,
+                        }
                         *counter += 1;
                     }
                 }
@@ -212,10 +233,18 @@ impl KmlReader {
         placemark: &kml::types::Placemark,
         id: usize,
         folder_path: &[String],
-    ) -> Result<Option<FormatFeature>> {
+    ) -> std::result::Result<Option<FormatFeature>, FeatureError> {
+        let feature_id = format!("placemark_{}", id);
+        let error =
+            |kind, message: String| FeatureError::new(id, kind, message).with_id(&feature_id);
+
         // Extract geometry
         let geometry = if let Some(geom) = &placemark.geometry {
-            self.convert_geometry(geom)?
+            let geometry = self
+                .convert_geometry(geom)
+                .map_err(|e| error(FeatureErrorKind::UnsupportedGeometry, e.to_string()))?;
+            check_geometry(&geometry).map_err(|(kind, message)| error(kind, message))?;
+            geometry
         } else {
             // Placemark without geometry - skip it
             return Ok(None);
@@ -251,7 +280,7 @@ impl KmlReader {
         }
 
         Ok(Some(FormatFeature {
-            id: format!("placemark_{}", id),
+            id: feature_id,
             geometry: Some(geometry),
             properties,
         }))
@@ -396,6 +425,24 @@ mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_folder_option_is_validated() {
+        let valid = FormatOptions::new().with_option("folder", "Parent/Child");
+        assert!(valid.validate(&KmlReader).is_ok());
+
+        let err = FormatOptions::new()
+            .with_option("folder", " ")
+            .validate(&KmlReader)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid value ' ' for option 'folder'"));
+
+        let err = FormatOptions::new()
+            .with_option("track_type", "tracks")
+            .validate(&KmlReader)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown option 'track_type'; valid options: folder"));
+    }
+
     #[tokio::test]
     async fn test_kml_reader_point() {
         let reader = KmlReader;
@@ -561,6 +608,45 @@ mod tests {
         assert!(!validation.errors.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_kml_reader_corrupt_placemarks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("corrupt.kml");
+
+        let kml_content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Placemark>
+      <name>Good</name>
+      <Point><coordinates>-122.326897,47.644548</coordinates></Point>
+    </Placemark>
+    <Placemark>
+      <name>Too short</name>
+      <LineString><coordinates>-122.326897,47.644548</coordinates></LineString>
+    </Placemark>
+    <Placemark>
+      <name>Also good</name>
+      <Point><coordinates>-122.5,47.5</coordinates></Point>
+    </Placemark>
+  </Document>
+</kml>"#;
+
+        fs::write(&file_path, kml_content).unwrap();
+
+        // Strict reads stop at the degenerate line
+        let err = KmlReader.read(&file_path).await.unwrap_err();
+        assert!(err.to_string().contains("placemark_1"), "{}", err);
+
+        let options = FormatOptions::new().with_read_policy(ReadPolicy::lenient(10));
+        let result = KmlReader.read_with_options(&file_path, &options).await.unwrap();
+
+        let ids: Vec<&str> = result.features.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["placemark_0", "placemark_2"]);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].index, 1);
+        assert_eq!(result.errors[0].kind, FeatureErrorKind::InvalidGeometry);
+    }
+
     #[test]
     fn test_supported_extensions() {
         let reader = KmlReader;
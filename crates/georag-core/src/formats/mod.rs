@@ -1,21 +1,55 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 
-use crate::error::Result;
+use crate::error::{GeoragError, Result};
+use crate::geo::axis::Extent;
+use crate::models::{AxisOrder, AxisOrderDecision, ValidityMode};
 
+#[cfg(feature = "format-docx")]
 pub mod docx;
 pub mod geojson;
+#[cfg(feature = "format-gpx")]
 pub mod gpx;
+pub mod inspect;
+#[cfg(feature = "format-kml")]
 pub mod kml;
+#[cfg(feature = "format-pdf")]
 pub mod pdf;
+pub mod prj;
+pub mod schema;
+#[cfg(feature = "format-shapefile")]
 pub mod shapefile;
+pub mod stream;
+pub mod transform;
 pub mod validation;
 
+pub use inspect::{sample_inspection, FormatInspection, InspectMethod, DEFAULT_INSPECT_SAMPLE};
+pub use schema::{FormatDescription, OptionKind, OptionSpec};
+pub use stream::{
+    feature_channel, FeatureSink, FeatureStream, IngestBuffers, StreamGauge, StreamHeader,
+};
+pub use transform::{
+    FeatureTransform, FeatureTransforms, PropertyTemplate, TransformErrors, TransformFailure,
+    TransformPattern, TransformPreview,
+};
+
 /// Format-specific options for reading datasets
 #[derive(Debug, Clone, Default)]
 pub struct FormatOptions {
     pub options: HashMap<String, String>,
+
+    /// How features that cannot be read are handled
+    pub read_policy: ReadPolicy,
+
+    /// Axis order of the file's coordinates, for formats where it is ambiguous
+    pub axis_order: AxisOrder,
+
+    /// Extent of the data already in the workspace, used by `AxisOrder::Auto`
+    pub extent: Option<Extent>,
 }
 
 impl FormatOptions {
@@ -26,9 +60,26 @@ impl FormatOptions {
         self.options.insert(key.into(), value.into());
         self
     }
+    pub fn with_read_policy(mut self, policy: ReadPolicy) -> Self {
+        self.read_policy = policy;
+        self
+    }
+    pub fn with_axis_order(mut self, axis_order: AxisOrder) -> Self {
+        self.axis_order = axis_order;
+        self
+    }
+    pub fn with_extent(mut self, extent: Option<Extent>) -> Self {
+        self.extent = extent;
+        self
+    }
     pub fn get(&self, key: &str) -> Option<&String> {
         self.options.get(key)
     }
+
+    /// Check the options against the option schema of `reader`
+    pub fn validate(&self, reader: &dyn FormatReader) -> Result<()> {
+        schema::validate_options(reader.format_name(), &reader.option_schema(), &self.options)
+    }
 }
 
 /// Format reader trait that all format implementations must implement
@@ -72,12 +123,51 @@ pub trait FormatReader: Send + Sync {
         Ok(dataset)
     }
 
+    /// Read a dataset with format-specific options, handing its features to `sink`
+    ///
+    /// The returned dataset has no features; its metadata and skipped features
+    /// are final. Readers that can parse a file incrementally override this so
+    /// that memory stays bounded by the sink's buffers; the default reads the
+    /// whole file and forwards its features.
+    async fn read_streaming(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+        sink: FeatureSink,
+    ) -> Result<FormatDataset> {
+        let dataset = self.read_with_options(path, options).await?;
+        sink.forward(dataset).await
+    }
+
+    /// Summarize a file cheaply, parsing at most about `sample` features
+    ///
+    /// Readers whose format records the feature count or extent up front, or
+    /// that can tally them without building every feature, override this;
+    /// the default streams features until the sample is full and stops the
+    /// read, see [`sample_inspection`].
+    async fn inspect(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+        sample: usize,
+    ) -> Result<FormatInspection> {
+        sample_inspection(self, path, options, sample).await
+    }
+
     /// Get supported file extensions (e.g., ["shp", "geojson"])
     fn supported_extensions(&self) -> &[&str];
 
     /// Get human-readable format name (e.g., "Shapefile", "GeoJSON")
     fn format_name(&self) -> &str;
 
+    /// Options accepted by `read_with_options`, checked before a read
+    ///
+    /// Readers without format-specific options keep the empty default, which
+    /// rejects every option.
+    fn option_schema(&self) -> Vec<OptionSpec> {
+        Vec::new()
+    }
+
     /// Validate file structure without full read (optional)
     async fn validate(&self, _path: &Path) -> Result<FormatValidation> {
         Ok(FormatValidation::default())
@@ -120,6 +210,9 @@ pub struct FormatDataset {
 
     /// Features extracted from the format
     pub features: Vec<FormatFeature>,
+
+    /// Features skipped because they could not be read (lenient reads only)
+    pub errors: Vec<FeatureError>,
 }
 
 /// Format-specific metadata
@@ -145,6 +238,15 @@ pub struct FormatMetadata {
 
     /// Spatial association metadata for documents
     pub spatial_association: Option<SpatialAssociationInfo>,
+
+    /// Axis order the coordinates were read with (GeoJSON only)
+    pub axis_order: Option<AxisOrderDecision>,
+
+    /// License declared by the file itself (GeoJSON only)
+    pub license: Option<String>,
+
+    /// Attribution declared by the file itself (GeoJSON only)
+    pub attribution: Option<String>,
 }
 
 /// Spatial association information for documents
@@ -173,7 +275,281 @@ pub struct FormatFeature {
     pub properties: std::collections::HashMap<String, serde_json::Value>,
 }
 
+/// Default number of unreadable features a lenient read skips before failing
+pub const DEFAULT_MAX_FEATURE_ERRORS: usize = 1000;
+
+/// How a reader handles features it cannot read
+///
+/// `Strict` fails the read on the first bad feature. `Lenient` skips bad
+/// features and reports them in [`FormatDataset::errors`], failing only once
+/// more than `max_errors` features were skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadPolicy {
+    pub validity: ValidityMode,
+    pub max_errors: usize,
+}
+
+impl ReadPolicy {
+    /// Fail on the first unreadable feature
+    pub fn strict() -> Self {
+        Self {
+            validity: ValidityMode::Strict,
+            max_errors: 0,
+        }
+    }
+
+    /// Skip up to `max_errors` unreadable features
+    pub fn lenient(max_errors: usize) -> Self {
+        Self {
+            validity: ValidityMode::Lenient,
+            max_errors,
+        }
+    }
+
+    /// Policy for a workspace validity mode
+    pub fn for_validity(validity: ValidityMode, max_errors: usize) -> Self {
+        match validity {
+            ValidityMode::Strict => Self::strict(),
+            ValidityMode::Lenient => Self::lenient(max_errors),
+        }
+    }
+}
+
+impl Default for ReadPolicy {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+/// Why a feature could not be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureErrorKind {
+    /// The feature record itself could not be decoded
+    Malformed,
+
+    /// The geometry has invalid coordinates or too few positions
+    InvalidGeometry,
+
+    /// The geometry type cannot be represented
+    UnsupportedGeometry,
+
+    /// The feature's attributes could not be decoded
+    InvalidProperties,
+
+    /// The geometry has more vertices than ingest accepts
+    TooComplex,
+
+    /// A workspace feature transform failed on the feature
+    TransformFailed,
+}
+
+impl fmt::Display for FeatureErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureErrorKind::Malformed => write!(f, "malformed feature"),
+            FeatureErrorKind::InvalidGeometry => write!(f, "invalid geometry"),
+            FeatureErrorKind::UnsupportedGeometry => write!(f, "unsupported geometry"),
+            FeatureErrorKind::InvalidProperties => write!(f, "invalid properties"),
+            FeatureErrorKind::TooComplex => write!(f, "too many vertices"),
+            FeatureErrorKind::TransformFailed => write!(f, "transform failed"),
+        }
+    }
+}
+
+/// A feature a reader could not read
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureError {
+    /// Position of the feature in the file, counting from 0
+    pub index: usize,
+
+    /// Feature identifier, when one could be read
+    pub id: Option<String>,
+
+    pub kind: FeatureErrorKind,
+
+    pub message: String,
+}
+
+impl FeatureError {
+    pub fn new(index: usize, kind: FeatureErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            index,
+            id: None,
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+}
+
+impl fmt::Display for FeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "feature {}", self.index)?;
+        if let Some(id) = &self.id {
+            write!(f, " (id {})", id)?;
+        }
+        write!(f, ": {}: {}", self.kind, self.message)
+    }
+}
+
+/// Per-feature errors collected during a read under a [`ReadPolicy`]
+#[derive(Debug)]
+pub struct FeatureErrors {
+    format: String,
+    policy: ReadPolicy,
+    errors: Vec<FeatureError>,
+}
+
+impl FeatureErrors {
+    pub fn new(format: impl Into<String>, policy: ReadPolicy) -> Self {
+        Self {
+            format: format.into(),
+            policy,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Continue collecting after a read that already skipped `errors`
+    ///
+    /// Later errors count against the same budget.
+    pub fn resume(
+        format: impl Into<String>,
+        policy: ReadPolicy,
+        errors: Vec<FeatureError>,
+    ) -> Self {
+        Self { format: format.into(), policy, errors }
+    }
+
+    /// Record a skipped feature
+    ///
+    /// Fails in strict mode, and in lenient mode once the error budget is spent.
+    pub fn record(&mut self, error: FeatureError) -> Result<()> {
+        if self.policy.validity == ValidityMode::Strict {
+            return Err(GeoragError::FormatValidation {
+                format: self.format.clone(),
+                reason: error.to_string(),
+            });
+        }
+
+        self.errors.push(error);
+        if self.errors.len() > self.policy.max_errors {
+            return Err(GeoragError::FormatValidation {
+                format: self.format.clone(),
+                reason: format!(
+                    "more than {} features could not be read; the last was {}",
+                    self.policy.max_errors,
+                    self.errors[self.errors.len() - 1]
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Number of features skipped so far
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<FeatureError> {
+        self.errors
+    }
+}
+
+/// Check a GeoJSON geometry produced by a reader
+///
+/// Rejects non-numeric coordinates, lines with fewer than two positions and
+/// polygon rings with fewer than four. A `null` geometry is accepted.
+pub fn check_geometry(geometry: &Value) -> std::result::Result<(), (FeatureErrorKind, String)> {
+    if geometry.is_null() {
+        return Ok(());
+    }
+
+    let invalid = |message: String| (FeatureErrorKind::InvalidGeometry, message);
+    let geometry_type = geometry.get("type").and_then(Value::as_str).unwrap_or_default();
+
+    if geometry_type == "GeometryCollection" {
+        let members = geometry
+            .get("geometries")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("GeometryCollection without geometries".to_string()))?;
+        return members.iter().try_for_each(check_geometry);
+    }
+
+    let coordinates = geometry
+        .get("coordinates")
+        .ok_or_else(|| invalid(format!("{} without coordinates", geometry_type)))?;
+
+    // Depth of nesting above positions, and the minimum length at each line level
+    let (depth, min_positions) = match geometry_type {
+        "Point" => (0, 0),
+        "MultiPoint" => (1, 0),
+        "LineString" => (1, 2),
+        "MultiLineString" => (2, 2),
+        "Polygon" => (2, 4),
+        "MultiPolygon" => (3, 4),
+        other => {
+            return Err((
+                FeatureErrorKind::UnsupportedGeometry,
+                format!("unknown geometry type '{}'", other),
+            ))
+        }
+    };
+
+    check_coordinates(coordinates, depth, min_positions).map_err(invalid)
+}
+
+fn check_coordinates(
+    value: &Value,
+    depth: usize,
+    min_positions: usize,
+) -> std::result::Result<(), String> {
+    if depth == 0 {
+        let position = value.as_array().ok_or("position is not an array")?;
+        if position.len() < 2 {
+            return Err(format!("position has {} coordinates", position.len()));
+        }
+        if position.iter().any(|c| !c.as_f64().is_some_and(f64::is_finite)) {
+            return Err(format!("position {} has a non-numeric coordinate", value));
+        }
+        return Ok(());
+    }
+
+    let items = value.as_array().ok_or("coordinates are not an array")?;
+    if depth == 1 && items.len() < min_positions {
+        return Err(format!(
+            "{} positions where at least {} are required",
+            items.len(),
+            min_positions
+        ));
+    }
+    items
+        .iter()
+        .try_for_each(|item| check_coordinates(item, depth - 1, min_positions))
+}
+
 /// Central registry for format readers
+///
+/// Downstream crates add their own readers by registering them on the
+/// registry handed to the CLI (`georag_cli::main_with_formats`, which passes
+/// it to `Storage::with_format_registry`) or the API
+/// (`AppState::with_format_registry`):
+///
+/// ```ignore
+/// let registry = FormatRegistry::with_defaults().with_reader(Box::new(SurveyReader));
+/// ```
+///
+/// When several readers claim the same extension, the most recently registered
+/// reader wins, so external readers override the built-in ones. Extensions are
+/// matched case-insensitively.
 pub struct FormatRegistry {
     readers: Vec<Box<dyn FormatReader>>,
 }
@@ -184,12 +560,37 @@ impl FormatRegistry {
         Self { readers: Vec::new() }
     }
 
+    /// Create a registry with all built-in readers enabled by cargo features
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(geojson::GeoJsonReader));
+        #[cfg(feature = "format-shapefile")]
+        registry.register(Box::new(shapefile::ShapefileFormatReader));
+        #[cfg(feature = "format-gpx")]
+        registry.register(Box::new(gpx::GpxReader));
+        #[cfg(feature = "format-kml")]
+        registry.register(Box::new(kml::KmlReader));
+        #[cfg(feature = "format-pdf")]
+        registry.register(Box::new(pdf::PdfReader));
+        #[cfg(feature = "format-docx")]
+        registry.register(Box::new(docx::DocxReader));
+        registry
+    }
+
     /// Register a format reader
     pub fn register(&mut self, reader: Box<dyn FormatReader>) {
         self.readers.push(reader);
     }
 
+    /// Register a format reader (builder style)
+    pub fn with_reader(mut self, reader: Box<dyn FormatReader>) -> Self {
+        self.register(reader);
+        self
+    }
+
     /// Detect format and return appropriate reader
+    ///
+    /// Readers are checked in reverse registration order.
     pub fn detect_format(&self, path: &Path) -> Result<&dyn FormatReader> {
         let extension = path.extension().and_then(|e| e.to_str()).ok_or_else(|| {
             crate::error::GeoragError::UnsupportedFormat {
@@ -200,7 +601,10 @@ impl FormatRegistry {
 
         self.readers
             .iter()
-            .find(|r| r.supported_extensions().contains(&extension))
+            .rev()
+            .find(|r| {
+                r.supported_extensions().iter().any(|ext| ext.eq_ignore_ascii_case(extension))
+            })
             .map(|r| r.as_ref())
             .ok_or_else(|| crate::error::GeoragError::UnsupportedFormat {
                 extension: extension.to_string(),
@@ -210,17 +614,42 @@ impl FormatRegistry {
 
     /// Get list of all supported format extensions
     pub fn supported_formats(&self) -> Vec<String> {
-        self.readers
-            .iter()
-            .flat_map(|r| r.supported_extensions())
-            .map(|s| s.to_string())
-            .collect()
+        let mut formats: Vec<String> = Vec::new();
+        for ext in self.readers.iter().flat_map(|r| r.supported_extensions()) {
+            if !formats.iter().any(|f| f == ext) {
+                formats.push(ext.to_string());
+            }
+        }
+        formats
     }
 
     /// Get all registered readers
     pub fn readers(&self) -> &[Box<dyn FormatReader>] {
         &self.readers
     }
+
+    /// Describe each reader that is detected for at least one extension
+    ///
+    /// Readers whose extensions were all taken over by later registrations
+    /// are left out, so the listed options are the ones ingest accepts.
+    pub fn describe(&self) -> Vec<FormatDescription> {
+        self.readers
+            .iter()
+            .enumerate()
+            .filter(|(index, reader)| {
+                reader.supported_extensions().iter().any(|ext| {
+                    self.readers.iter().rposition(|r| {
+                        r.supported_extensions().iter().any(|e| e.eq_ignore_ascii_case(ext))
+                    }) == Some(*index)
+                })
+            })
+            .map(|(_, reader)| FormatDescription {
+                name: reader.format_name().to_string(),
+                extensions: reader.supported_extensions().iter().map(|e| e.to_string()).collect(),
+                options: reader.option_schema(),
+            })
+            .collect()
+    }
 }
 
 impl Default for FormatRegistry {
@@ -252,9 +681,13 @@ mod tests {
                     paragraph_count: None,
                     extraction_method: None,
                     spatial_association: None,
+                    axis_order: None,
+                    license: None,
+                    attribution: None,
                 },
                 crs: 4326,
                 features: vec![],
+                errors: vec![],
             })
         }
 
@@ -306,6 +739,114 @@ mod tests {
         assert_eq!(reader.format_name(), "Shapefile");
     }
 
+    #[test]
+    fn test_later_registration_takes_precedence() {
+        let registry = FormatRegistry::new()
+            .with_reader(Box::new(MockReader {
+                extensions: vec!["json", "geojson"],
+                name: "GeoJSON",
+            }))
+            .with_reader(Box::new(MockReader {
+                extensions: vec!["json", "survey"],
+                name: "Survey",
+            }));
+
+        let reader = registry.detect_format(Path::new("points.json")).unwrap();
+        assert_eq!(reader.format_name(), "Survey");
+
+        // Extensions claimed by a single reader are unaffected
+        let reader = registry.detect_format(Path::new("points.geojson")).unwrap();
+        assert_eq!(reader.format_name(), "GeoJSON");
+
+        // Shared extensions are listed once
+        assert_eq!(registry.supported_formats(), vec!["json", "geojson", "survey"]);
+    }
+
+    #[test]
+    fn test_format_detection_ignores_case() {
+        let registry = FormatRegistry::new().with_reader(Box::new(MockReader {
+            extensions: vec!["shp"],
+            name: "Shapefile",
+        }));
+
+        let reader = registry.detect_format(Path::new("ROADS.SHP")).unwrap();
+        assert_eq!(reader.format_name(), "Shapefile");
+    }
+
+    #[test]
+    fn test_with_defaults_includes_geojson() {
+        let registry = FormatRegistry::with_defaults();
+        let reader = registry.detect_format(Path::new("places.geojson")).unwrap();
+        assert_eq!(reader.format_name(), "GeoJSON");
+    }
+
+    #[cfg(feature = "format-pdf")]
+    #[test]
+    fn test_with_defaults_includes_enabled_features() {
+        let registry = FormatRegistry::with_defaults();
+        let reader = registry.detect_format(Path::new("report.pdf")).unwrap();
+        assert_eq!(reader.format_name(), "PDF");
+    }
+
+    #[test]
+    fn test_options_are_checked_against_schema() {
+        let schema = vec![
+            OptionSpec::choice("track_type", &["tracks", "routes"], "Kind of elements"),
+            OptionSpec::epsg("crs", "Dataset CRS"),
+        ];
+        let check = |key: &str, value: &str| {
+            let options = FormatOptions::new().with_option(key, value);
+            schema::validate_options("GPX", &schema, &options.options)
+        };
+
+        assert!(check("track_type", "routes").is_ok());
+        assert!(check("crs", "EPSG:3857").is_ok());
+
+        let err = check("track_type", "trackz").unwrap_err().to_string();
+        assert!(err.contains("invalid value 'trackz' for option 'track_type'"), "{}", err);
+        assert!(err.contains("one of tracks, routes"), "{}", err);
+
+        let err = check("crs", "WGS84").unwrap_err().to_string();
+        assert!(err.contains("expected EPSG code"), "{}", err);
+
+        let err = check("trak_type", "tracks").unwrap_err();
+        assert!(
+            matches!(err, GeoragError::InvalidFormatOption { ref format, .. } if format == "GPX")
+        );
+        assert!(err
+            .to_string()
+            .contains("unknown option 'trak_type'; valid options: track_type (one of tracks, routes); crs (EPSG code)"));
+    }
+
+    #[test]
+    fn test_readers_without_schema_reject_options() {
+        let reader = MockReader {
+            extensions: vec!["json"],
+            name: "GeoJSON",
+        };
+        assert!(FormatOptions::new().validate(&reader).is_ok());
+
+        let err = FormatOptions::new().with_option("folder", "A").validate(&reader).unwrap_err();
+        assert!(err.to_string().contains("GeoJSON takes no options"));
+    }
+
+    #[test]
+    fn test_describe_skips_overridden_readers() {
+        let registry = FormatRegistry::new()
+            .with_reader(Box::new(MockReader {
+                extensions: vec!["json"],
+                name: "GeoJSON",
+            }))
+            .with_reader(Box::new(MockReader {
+                extensions: vec!["shp"],
+                name: "Shapefile",
+            }))
+            .with_reader(Box::new(MockReader { extensions: vec!["JSON"], name: "Survey" }));
+
+        let names: Vec<String> = registry.describe().into_iter().map(|d| d.name).collect();
+        assert_eq!(names, vec!["Shapefile", "Survey"]);
+    }
+
     #[test]
     fn test_unsupported_format() {
         let registry = FormatRegistry::new();
@@ -340,4 +881,53 @@ mod tests {
         assert!(validation.is_valid());
         assert!(validation.has_warnings());
     }
+
+    #[test]
+    fn test_strict_policy_fails_on_first_feature_error() {
+        let mut errors = FeatureErrors::new("GeoJSON", ReadPolicy::strict());
+        let err = errors
+            .record(FeatureError::new(3, FeatureErrorKind::Malformed, "not an object").with_id("a"))
+            .unwrap_err();
+        assert!(err.to_string().contains("feature 3 (id a): malformed feature: not an object"));
+    }
+
+    #[test]
+    fn test_lenient_policy_collects_until_budget_is_spent() {
+        let mut errors = FeatureErrors::new("GeoJSON", ReadPolicy::lenient(2));
+        let error = |i| FeatureError::new(i, FeatureErrorKind::InvalidGeometry, "bad");
+
+        errors.record(error(0)).unwrap();
+        errors.record(error(1)).unwrap();
+        assert_eq!(errors.len(), 2);
+
+        let err = errors.record(error(2)).unwrap_err();
+        assert!(err.to_string().contains("more than 2 features"));
+    }
+
+    #[test]
+    fn test_check_geometry() {
+        let ok = |v: Value| check_geometry(&v).is_ok();
+        let kind = |v: Value| check_geometry(&v).unwrap_err().0;
+
+        assert!(ok(serde_json::json!({"type": "Point", "coordinates": [1.0, 2.0]})));
+        assert!(ok(serde_json::json!({"type": "LineString", "coordinates": [[0, 0], [1, 1]]})));
+        assert!(ok(Value::Null));
+
+        assert_eq!(
+            kind(serde_json::json!({"type": "LineString", "coordinates": [[0, 0]]})),
+            FeatureErrorKind::InvalidGeometry
+        );
+        assert_eq!(
+            kind(serde_json::json!({"type": "Polygon", "coordinates": [[[0, 0], [1, 1], [0, 0]]]})),
+            FeatureErrorKind::InvalidGeometry
+        );
+        assert_eq!(
+            kind(serde_json::json!({"type": "Point", "coordinates": [1.0, null]})),
+            FeatureErrorKind::InvalidGeometry
+        );
+        assert_eq!(
+            kind(serde_json::json!({"type": "Curve", "coordinates": []})),
+            FeatureErrorKind::UnsupportedGeometry
+        );
+    }
 }
@@ -1,12 +1,15 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
 
 use crate::error::{GeoragError, Result};
 use crate::formats::validation::FormatValidator;
 use crate::formats::{
-    FormatDataset, FormatFeature, FormatMetadata, FormatReader, FormatValidation,
+    FormatDataset, FormatFeature, FormatInspection, FormatMetadata, FormatOptions, FormatReader,
+    FormatValidation, InspectMethod,
 };
+use crate::processing::text::{tokens, tokens_within, MAX_CHUNK_BYTES};
 
 /// PDF format reader
 pub struct PdfReader;
@@ -59,12 +62,35 @@ impl FormatReader for PdfReader {
                 paragraph_count: None,
                 extraction_method: Some("pdf-extract".to_string()),
                 spatial_association: None,
+                axis_order: None,
+                license: None,
+                attribution: None,
             },
             crs: 4326,
             features: vec![feature],
+            errors: Vec::new(),
         })
     }
 
+    /// Page count from the page tree, without extracting any text
+    async fn inspect(
+        &self,
+        path: &Path,
+        _options: &FormatOptions,
+        _sample: usize,
+    ) -> Result<FormatInspection> {
+        let document =
+            pdf_extract::Document::load(path).map_err(|e| GeoragError::DocumentExtraction {
+                format: "PDF".to_string(),
+                reason: format!("Failed to load PDF: {}", e),
+            })?;
+
+        let mut inspection = FormatInspection::new("PDF", InspectMethod::Header);
+        inspection.feature_count = Some(1);
+        inspection.page_count = Some(document.get_pages().len());
+        Ok(inspection)
+    }
+
     fn supported_extensions(&self) -> &[&str] {
         &["pdf"]
     }
@@ -115,88 +141,137 @@ impl PdfReader {
         }
     }
 
+    /// Normalized text of an extracted document
+    ///
+    /// Its paragraphs, split at blank lines and page breaks, trimmed and
+    /// joined by a blank line. Chunk offsets count characters of this text.
+    pub fn normalize_text(&self, text: &str) -> String {
+        paragraphs(text).join("\n\n")
+    }
+
     /// Split text into chunks suitable for embedding generation
     ///
     /// Chunks are created with the following strategy:
     /// - Target chunk size: ~500 words (approximately 2000-3000 characters)
     /// - Preserve paragraph boundaries where possible
     /// - Include overlap between chunks for context continuity
+    /// - Never exceed [`MAX_CHUNK_BYTES`], however long a paragraph or word
     ///
+    /// Every chunk is a slice of [`Self::normalize_text`].
     pub fn chunk_text(&self, text: &str, chunk_size: usize, overlap: usize) -> Vec<TextChunk> {
         let mut chunks = Vec::new();
 
-        // Split into paragraphs (double newline or form feed)
-        let paragraphs: Vec<&str> = text
-            .split('\x0C')
-            .flat_map(|section| section.split("\n\n"))
-            .filter(|p| !p.trim().is_empty())
-            .collect();
-
+        let paragraphs = paragraphs(text);
         if paragraphs.is_empty() {
             return chunks;
         }
+        let normalized = paragraphs.join("\n\n");
 
-        let mut current_chunk = String::new();
+        // Byte range of the current chunk in the normalized text
+        let mut chunk_start = None;
+        let mut chunk_end = 0;
         let mut current_word_count = 0;
-        let mut chunk_index = 0;
+        let mut paragraph_start = 0;
 
-        for paragraph in paragraphs {
-            let paragraph = paragraph.trim();
-            let words: Vec<&str> = paragraph.split_whitespace().collect();
-            let word_count = words.len();
+        for paragraph in &paragraphs {
+            let paragraph_end = paragraph_start + paragraph.len();
+            let word_count = paragraph.split_whitespace().count();
 
             // If adding this paragraph would exceed chunk size, finalize current chunk
-            if current_word_count > 0 && current_word_count + word_count > chunk_size {
-                let overlap_words: Vec<String> = current_chunk
-                    .split_whitespace()
-                    .rev()
-                    .take(overlap)
-                    .map(|s| s.to_string())
-                    .collect::<Vec<_>>()
-                    .into_iter()
-                    .rev()
-                    .collect();
-
-                // Store the chunk
-                chunks.push(TextChunk {
-                    index: chunk_index,
-                    text: current_chunk.trim().to_string(),
-                    word_count: current_word_count,
-                    start_offset: 0, // Could be calculated if needed
-                });
-                chunk_index += 1;
-
-                // Start new chunk with overlap
-                current_chunk = overlap_words.join(" ");
-                current_word_count = overlap_words.len();
-
-                if !current_chunk.is_empty() {
-                    current_chunk.push(' ');
+            if let Some(start) = chunk_start.filter(|_| current_word_count > 0) {
+                if current_word_count + word_count > chunk_size
+                    || paragraph_end - start > MAX_CHUNK_BYTES
+                {
+                    chunks.push(TextChunk::new(
+                        chunks.len(),
+                        &normalized,
+                        start..chunk_end,
+                        current_word_count,
+                    ));
+
+                    // At most half a chunk of overlap, leaving room for the paragraph
+                    let mut overlap_start = None;
+                    current_word_count = 0;
+                    for word_start in
+                        word_starts(&normalized[start..chunk_end]).into_iter().rev().take(overlap)
+                    {
+                        if chunk_end - (start + word_start) + 2 > MAX_CHUNK_BYTES / 2 {
+                            break;
+                        }
+                        overlap_start = Some(start + word_start);
+                        current_word_count += 1;
+                    }
+                    chunk_start = overlap_start;
                 }
             }
 
             // Add paragraph to current chunk
-            if !current_chunk.is_empty() && !current_chunk.ends_with(' ') {
-                current_chunk.push_str("\n\n");
-            }
-            current_chunk.push_str(paragraph);
+            chunk_start.get_or_insert(paragraph_start);
+            chunk_end = paragraph_end;
             current_word_count += word_count;
+            paragraph_start = paragraph_end + 2;
         }
 
         // Add final chunk if there's remaining content
-        if !current_chunk.trim().is_empty() {
-            chunks.push(TextChunk {
-                index: chunk_index,
-                text: current_chunk.trim().to_string(),
-                word_count: current_word_count,
-                start_offset: 0,
-            });
+        if let Some(start) = chunk_start {
+            chunks.push(TextChunk::new(
+                chunks.len(),
+                &normalized,
+                start..chunk_end,
+                current_word_count,
+            ));
         }
 
         chunks
     }
 }
 
+/// Paragraphs of a document, split at blank lines and page breaks
+fn paragraphs(text: &str) -> Vec<String> {
+    text.split('\x0C')
+        .flat_map(|section| section.split("\n\n"))
+        .filter(|p| !p.trim().is_empty())
+        .flat_map(paragraph_pieces)
+        .collect()
+}
+
+/// Byte positions at which the words of a text start
+fn word_starts(text: &str) -> Vec<usize> {
+    let mut previous_is_space = true;
+    let mut starts = Vec::new();
+    for (i, c) in text.char_indices() {
+        if previous_is_space && !c.is_whitespace() {
+            starts.push(i);
+        }
+        previous_is_space = c.is_whitespace();
+    }
+    starts
+}
+
+/// A paragraph as it is added to chunks
+///
+/// Kept whole when it fits in half a chunk and none of its words is over-long;
+/// otherwise its tokens are joined into pieces of at most half a chunk.
+fn paragraph_pieces(paragraph: &str) -> Vec<String> {
+    let paragraph = paragraph.trim();
+    let words: Vec<&str> = tokens(paragraph).collect();
+    if paragraph.len() <= MAX_CHUNK_BYTES / 2
+        && !paragraph.contains('\0')
+        && words.len() == paragraph.split_whitespace().count()
+    {
+        return vec![paragraph.to_string()];
+    }
+
+    let mut pieces = Vec::new();
+    let mut rest = &words[..];
+    while !rest.is_empty() {
+        let count = tokens_within(rest, MAX_CHUNK_BYTES / 2);
+        pieces.push(rest[..count].join(" "));
+        rest = &rest[count..];
+    }
+    pieces
+}
+
 /// A chunk of text extracted from a document
 #[derive(Debug, Clone)]
 pub struct TextChunk {
@@ -209,8 +284,25 @@ pub struct TextChunk {
     /// Number of words in this chunk
     pub word_count: usize,
 
-    /// Character offset where this chunk starts in the original document
+    /// Character offset where this chunk starts in the normalized text
     pub start_offset: usize,
+
+    /// Length of this chunk in characters
+    pub length: usize,
+}
+
+impl TextChunk {
+    /// Chunk of the normalized text in the byte range `range`
+    fn new(index: usize, normalized: &str, range: Range<usize>, word_count: usize) -> Self {
+        let text = normalized[range.clone()].to_string();
+        Self {
+            index,
+            start_offset: normalized[..range.start].chars().count(),
+            length: text.chars().count(),
+            text,
+            word_count,
+        }
+    }
 }
 
 #[cfg(test)]
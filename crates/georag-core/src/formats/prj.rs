@@ -0,0 +1,438 @@
+//! CRS identification for `.prj` files without authority codes
+//!
+//! ESRI software writes WKT without `AUTHORITY["EPSG",...]`, for example
+//! `PROJCS["WGS_1984_UTM_Zone_48S",GEOGCS["GCS_WGS_1984",...]]`. These
+//! definitions are identified by their datum, projection and parameters
+//! against a table of common coordinate systems: geographic systems, UTM
+//! zones on the common datums, several national grids and Web Mercator.
+
+/// Tolerance for angles in degrees
+const DEGREE_TOLERANCE: f64 = 1e-4;
+
+/// Tolerance for false eastings and northings in meters
+const METER_TOLERANCE: f64 = 0.01;
+
+/// Identify the EPSG code of a WKT definition from its parameters
+///
+/// Returns `None` when the definition matches no known coordinate system.
+/// Authority codes are not consulted; callers check those first.
+pub fn identify_epsg(wkt: &str) -> Option<u32> {
+    let definition = PrjDefinition::parse(wkt);
+    let datum = definition.datum.as_deref().and_then(Datum::from_name);
+
+    let Some(projection) = definition.projection.as_deref() else {
+        // A geographic system without a projection
+        return datum.and_then(Datum::geographic_epsg);
+    };
+
+    let projection = Projection::from_name(projection)?;
+    match projection {
+        Projection::WebMercator => Some(3857),
+        Projection::Mercator if datum == Some(Datum::WebMercatorSphere) => Some(3857),
+        _ => {
+            let datum = datum?;
+            utm_epsg(datum, projection, &definition)
+                .or_else(|| national_grid_epsg(datum, projection, &definition))
+        }
+    }
+}
+
+/// Name of the coordinate system a WKT definition describes, for messages
+///
+/// Falls back to the start of the definition when it has no name.
+pub fn describe(wkt: &str) -> String {
+    let wkt = wkt.trim();
+    ["PROJCS", "PROJCRS", "GEOGCS", "GEOGCRS"]
+        .iter()
+        .find_map(|keyword| quoted_after(wkt, keyword))
+        .unwrap_or_else(|| wkt.chars().take(80).collect())
+}
+
+/// Datum, projection and parameters read from a WKT definition
+#[derive(Debug, Default)]
+struct PrjDefinition {
+    datum: Option<String>,
+    projection: Option<String>,
+    parameters: Vec<(String, f64)>,
+}
+
+impl PrjDefinition {
+    fn parse(wkt: &str) -> Self {
+        let mut parameters = Vec::new();
+        let mut rest = wkt;
+        while let Some(start) = rest.find("PARAMETER[") {
+            rest = &rest[start + "PARAMETER[".len()..];
+            let Some(end) = rest.find(']') else {
+                break;
+            };
+            if let Some((name, value)) = rest[..end].rsplit_once(',') {
+                if let Ok(value) = value.trim().parse::<f64>() {
+                    parameters.push((normalize(name.trim().trim_matches('"')), value));
+                }
+            }
+        }
+
+        Self {
+            datum: quoted_after(wkt, "DATUM"),
+            projection: quoted_after(wkt, "PROJECTION"),
+            parameters,
+        }
+    }
+
+    /// First parameter matching one of the normalized names
+    fn parameter(&self, names: &[&str]) -> Option<f64> {
+        self.parameters
+            .iter()
+            .find(|(name, _)| names.contains(&name.as_str()))
+            .map(|(_, value)| *value)
+    }
+
+    fn central_meridian(&self) -> Option<f64> {
+        self.parameter(&["centralmeridian", "longitudeofcenter", "longitudeoforigin"])
+    }
+
+    fn latitude_of_origin(&self) -> f64 {
+        self.parameter(&["latitudeoforigin", "latitudeofcenter"]).unwrap_or(0.0)
+    }
+
+    fn false_easting(&self) -> f64 {
+        self.parameter(&["falseeasting"]).unwrap_or(0.0)
+    }
+
+    fn false_northing(&self) -> f64 {
+        self.parameter(&["falsenorthing"]).unwrap_or(0.0)
+    }
+
+    fn scale_factor(&self) -> Option<f64> {
+        self.parameter(&["scalefactor", "scalefactoratnaturalorigin"])
+    }
+}
+
+/// First quoted string after `KEYWORD[`
+fn quoted_after(wkt: &str, keyword: &str) -> Option<String> {
+    let start = wkt.find(&format!("{}[", keyword))? + keyword.len() + 1;
+    let rest = wkt[start..].trim_start().strip_prefix('"')?;
+    let end = rest.find('"')?;
+    Some(rest[..end].to_string())
+}
+
+/// Lowercase a name and drop everything but letters and digits
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Datum {
+    Wgs84,
+    /// Sphere ESRI uses for Web Mercator
+    WebMercatorSphere,
+    Nad83,
+    Nad27,
+    Etrs89,
+    Osgb36,
+    Gda94,
+    Gda2020,
+    Rgf93,
+    Amersfoort,
+    Nzgd2000,
+    Irenet95,
+    Ch1903,
+}
+
+impl Datum {
+    fn from_name(name: &str) -> Option<Self> {
+        // ESRI prefixes datum names with "D_"
+        let name = normalize(name.strip_prefix("D_").unwrap_or(name));
+        let datum = match name.as_str() {
+            "wgs1984" | "wgs84" | "worldgeodeticsystem1984" => Datum::Wgs84,
+            "wgs1984majorauxiliarysphere" => Datum::WebMercatorSphere,
+            "northamerican1983" | "nad83" | "northamericandatum1983" => Datum::Nad83,
+            "northamerican1927" | "nad27" | "northamericandatum1927" => Datum::Nad27,
+            "etrs1989" | "etrs89" | "europeanterrestrialreferencesystem1989" => Datum::Etrs89,
+            "osgb1936" | "osgb36" | "ordnancesurveyofgreatbritain1936" => Datum::Osgb36,
+            "gda1994" | "gda94" | "geocentricdatumofaustralia1994" => Datum::Gda94,
+            "gda2020" | "geocentricdatumofaustralia2020" => Datum::Gda2020,
+            "rgf1993" | "rgf93" | "reseaugeodesiquefrancais1993" => Datum::Rgf93,
+            "amersfoort" => Datum::Amersfoort,
+            "nzgd2000" | "newzealandgeodeticdatum2000" => Datum::Nzgd2000,
+            "irenet95" => Datum::Irenet95,
+            // "CH1903+" normalizes to the same name as CH1903
+            "ch1903" | "swissch1903" => Datum::Ch1903,
+            _ => return None,
+        };
+        Some(datum)
+    }
+
+    fn geographic_epsg(self) -> Option<u32> {
+        match self {
+            Datum::Wgs84 => Some(4326),
+            Datum::Nad83 => Some(4269),
+            Datum::Nad27 => Some(4267),
+            Datum::Etrs89 => Some(4258),
+            Datum::Osgb36 => Some(4277),
+            Datum::Gda94 => Some(4283),
+            Datum::Gda2020 => Some(7844),
+            Datum::Rgf93 => Some(4171),
+            Datum::Amersfoort => Some(4289),
+            Datum::Nzgd2000 => Some(4167),
+            Datum::Irenet95 => Some(4173),
+            // CH1903 and CH1903+ share a name once normalized
+            Datum::Ch1903 | Datum::WebMercatorSphere => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Projection {
+    TransverseMercator,
+    LambertConformalConic,
+    ObliqueStereographic,
+    LambertAzimuthalEqualArea,
+    ObliqueMercator,
+    Mercator,
+    WebMercator,
+}
+
+impl Projection {
+    fn from_name(name: &str) -> Option<Self> {
+        let projection = match normalize(name).as_str() {
+            "transversemercator" | "gausskruger" => Projection::TransverseMercator,
+            "lambertconformalconic" | "lambertconformalconic2sp" => {
+                Projection::LambertConformalConic
+            }
+            "doublestereographic" | "obliquestereographic" => Projection::ObliqueStereographic,
+            "lambertazimuthalequalarea" => Projection::LambertAzimuthalEqualArea,
+            "hotineobliquemercatorazimuthcenter"
+            | "hotineobliquemercator"
+            | "obliquemercator"
+            | "swissobliquecylindrical" => Projection::ObliqueMercator,
+            "mercator" | "mercator1sp" => Projection::Mercator,
+            "mercatorauxiliarysphere" | "popularvisualisationpseudomercator" => {
+                Projection::WebMercator
+            }
+            _ => return None,
+        };
+        Some(projection)
+    }
+}
+
+/// UTM zones, identified by their central meridian and false northing
+fn utm_epsg(datum: Datum, projection: Projection, definition: &PrjDefinition) -> Option<u32> {
+    if projection != Projection::TransverseMercator
+        || !close(definition.scale_factor()?, 0.9996, 1e-7)
+        || !close(definition.latitude_of_origin(), 0.0, DEGREE_TOLERANCE)
+        || !close(definition.false_easting(), 500_000.0, METER_TOLERANCE)
+    {
+        return None;
+    }
+
+    let zone = (definition.central_meridian()? + 183.0) / 6.0;
+    if !close(zone, zone.round(), DEGREE_TOLERANCE / 6.0) || !(1.0..=60.0).contains(&zone) {
+        return None;
+    }
+    let zone = zone.round() as u32;
+
+    let false_northing = definition.false_northing();
+    let south = if close(false_northing, 10_000_000.0, METER_TOLERANCE) {
+        true
+    } else if close(false_northing, 0.0, METER_TOLERANCE) {
+        false
+    } else {
+        return None;
+    };
+
+    match (datum, south) {
+        (Datum::Wgs84, false) => Some(32600 + zone),
+        (Datum::Wgs84, true) => Some(32700 + zone),
+        (Datum::Nad83, false) if zone <= 23 => Some(26900 + zone),
+        (Datum::Nad27, false) if zone <= 22 => Some(26700 + zone),
+        (Datum::Etrs89, false) if (28..=38).contains(&zone) => Some(25800 + zone),
+        (Datum::Gda94, true) if (48..=58).contains(&zone) => Some(28300 + zone),
+        (Datum::Gda2020, true) if (46..=59).contains(&zone) => Some(7800 + zone),
+        _ => None,
+    }
+}
+
+/// A national grid, identified by its origin and false coordinates
+struct NationalGrid {
+    epsg: u32,
+    datum: Datum,
+    projection: Projection,
+    central_meridian: f64,
+    false_easting: f64,
+    false_northing: f64,
+}
+
+const NATIONAL_GRIDS: &[NationalGrid] = &[
+    // British National Grid
+    NationalGrid {
+        epsg: 27700,
+        datum: Datum::Osgb36,
+        projection: Projection::TransverseMercator,
+        central_meridian: -2.0,
+        false_easting: 400_000.0,
+        false_northing: -100_000.0,
+    },
+    // Irish Transverse Mercator
+    NationalGrid {
+        epsg: 2157,
+        datum: Datum::Irenet95,
+        projection: Projection::TransverseMercator,
+        central_meridian: -8.0,
+        false_easting: 600_000.0,
+        false_northing: 750_000.0,
+    },
+    // New Zealand Transverse Mercator 2000
+    NationalGrid {
+        epsg: 2193,
+        datum: Datum::Nzgd2000,
+        projection: Projection::TransverseMercator,
+        central_meridian: 173.0,
+        false_easting: 1_600_000.0,
+        false_northing: 10_000_000.0,
+    },
+    // RGF93 / Lambert-93 (France)
+    NationalGrid {
+        epsg: 2154,
+        datum: Datum::Rgf93,
+        projection: Projection::LambertConformalConic,
+        central_meridian: 3.0,
+        false_easting: 700_000.0,
+        false_northing: 6_600_000.0,
+    },
+    // Amersfoort / RD New (Netherlands)
+    NationalGrid {
+        epsg: 28992,
+        datum: Datum::Amersfoort,
+        projection: Projection::ObliqueStereographic,
+        central_meridian: 5.387_638_888_888_89,
+        false_easting: 155_000.0,
+        false_northing: 463_000.0,
+    },
+    // ETRS89 / LAEA Europe
+    NationalGrid {
+        epsg: 3035,
+        datum: Datum::Etrs89,
+        projection: Projection::LambertAzimuthalEqualArea,
+        central_meridian: 10.0,
+        false_easting: 4_321_000.0,
+        false_northing: 3_210_000.0,
+    },
+    // CH1903+ / LV95 (Switzerland)
+    NationalGrid {
+        epsg: 2056,
+        datum: Datum::Ch1903,
+        projection: Projection::ObliqueMercator,
+        central_meridian: 7.439_583_333_333_33,
+        false_easting: 2_600_000.0,
+        false_northing: 1_200_000.0,
+    },
+    // CH1903 / LV03 (Switzerland)
+    NationalGrid {
+        epsg: 21781,
+        datum: Datum::Ch1903,
+        projection: Projection::ObliqueMercator,
+        central_meridian: 7.439_583_333_333_33,
+        false_easting: 600_000.0,
+        false_northing: 200_000.0,
+    },
+];
+
+fn national_grid_epsg(
+    datum: Datum,
+    projection: Projection,
+    definition: &PrjDefinition,
+) -> Option<u32> {
+    let central_meridian = definition.central_meridian()?;
+    NATIONAL_GRIDS
+        .iter()
+        .find(|grid| {
+            grid.datum == datum
+                && grid.projection == projection
+                && close(central_meridian, grid.central_meridian, DEGREE_TOLERANCE)
+                && close(definition.false_easting(), grid.false_easting, METER_TOLERANCE)
+                && close(definition.false_northing(), grid.false_northing, METER_TOLERANCE)
+        })
+        .map(|grid| grid.epsg)
+}
+
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn esri_utm(name: &str, gcs: &str, datum: &str, central_meridian: f64, north: f64) -> String {
+        format!(
+            r#"PROJCS["{name}",GEOGCS["{gcs}",DATUM["{datum}",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Transverse_Mercator"],PARAMETER["False_Easting",500000.0],PARAMETER["False_Northing",{north}],PARAMETER["Central_Meridian",{central_meridian}],PARAMETER["Scale_Factor",0.9996],PARAMETER["Latitude_Of_Origin",0.0],UNIT["Meter",1.0]]"#
+        )
+    }
+
+    #[test]
+    fn test_esri_utm_zones() {
+        let jakarta = esri_utm("WGS_1984_UTM_Zone_48S", "GCS_WGS_1984", "D_WGS_1984", 105.0, 1e7);
+        assert_eq!(identify_epsg(&jakarta), Some(32748));
+
+        let oslo = esri_utm("WGS_1984_UTM_Zone_32N", "GCS_WGS_1984", "D_WGS_1984", 9.0, 0.0);
+        assert_eq!(identify_epsg(&oslo), Some(32632));
+
+        let seattle = esri_utm(
+            "NAD_1983_UTM_Zone_10N",
+            "GCS_North_American_1983",
+            "D_North_American_1983",
+            -123.0,
+            0.0,
+        );
+        assert_eq!(identify_epsg(&seattle), Some(26910));
+
+        let madrid = esri_utm("ETRS_1989_UTM_Zone_30N", "GCS_ETRS_1989", "D_ETRS_1989", -3.0, 0.0);
+        assert_eq!(identify_epsg(&madrid), Some(25830));
+    }
+
+    #[test]
+    fn test_ogc_utm_without_authority() {
+        let wkt = r#"PROJCS["WGS 84 / UTM zone 48S",GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563]],PRIMEM["Greenwich",0],UNIT["degree",0.0174532925199433]],PROJECTION["Transverse_Mercator"],PARAMETER["latitude_of_origin",0],PARAMETER["central_meridian",105],PARAMETER["scale_factor",0.9996],PARAMETER["false_easting",500000],PARAMETER["false_northing",10000000],UNIT["metre",1]]"#;
+        assert_eq!(identify_epsg(wkt), Some(32748));
+    }
+
+    #[test]
+    fn test_british_national_grid() {
+        let wkt = r#"PROJCS["British_National_Grid",GEOGCS["GCS_OSGB_1936",DATUM["D_OSGB_1936",SPHEROID["Airy_1830",6377563.396,299.3249646]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Transverse_Mercator"],PARAMETER["False_Easting",400000.0],PARAMETER["False_Northing",-100000.0],PARAMETER["Central_Meridian",-2.0],PARAMETER["Scale_Factor",0.9996012717],PARAMETER["Latitude_Of_Origin",49.0],UNIT["Meter",1.0]]"#;
+        assert_eq!(identify_epsg(wkt), Some(27700));
+    }
+
+    #[test]
+    fn test_esri_web_mercator() {
+        let wkt = r#"PROJCS["WGS_1984_Web_Mercator_Auxiliary_Sphere",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Mercator_Auxiliary_Sphere"],PARAMETER["False_Easting",0.0],PARAMETER["False_Northing",0.0],PARAMETER["Central_Meridian",0.0],PARAMETER["Standard_Parallel_1",0.0],PARAMETER["Auxiliary_Sphere_Type",0.0],UNIT["Meter",1.0]]"#;
+        assert_eq!(identify_epsg(wkt), Some(3857));
+    }
+
+    #[test]
+    fn test_geographic_systems() {
+        let wgs84 = r#"GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+        assert_eq!(identify_epsg(wgs84), Some(4326));
+
+        let nad83 = r#"GEOGCS["GCS_North_American_1983",DATUM["D_North_American_1983",SPHEROID["GRS_1980",6378137.0,298.257222101]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]]"#;
+        assert_eq!(identify_epsg(nad83), Some(4269));
+    }
+
+    #[test]
+    fn test_unknown_definitions_are_not_guessed() {
+        // A custom Albers projection
+        let albers = r#"PROJCS["Custom_Albers",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Albers"],PARAMETER["False_Easting",0.0],PARAMETER["False_Northing",0.0],PARAMETER["Central_Meridian",110.0],UNIT["Meter",1.0]]"#;
+        assert_eq!(identify_epsg(albers), None);
+        assert_eq!(describe(albers), "Custom_Albers");
+
+        // A transverse Mercator that is not a UTM zone
+        let shifted = esri_utm("Local_TM", "GCS_WGS_1984", "D_WGS_1984", 106.5, 0.0);
+        assert_eq!(identify_epsg(&shifted), None);
+
+        assert_eq!(identify_epsg("not a projection"), None);
+        assert_eq!(describe("not a projection"), "not a projection");
+    }
+}
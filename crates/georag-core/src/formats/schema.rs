@@ -0,0 +1,155 @@
+//! Option schemas declared by format readers
+//!
+//! Each reader lists the options it understands in
+//! [`FormatReader::option_schema`](super::FormatReader::option_schema). Ingest
+//! checks the options of a request against the schema of the detected reader
+//! before reading, so a misspelled key or value fails up front with the valid
+//! choices instead of being ignored. The same schemas are listed by
+//! `georag formats list` and `GET /api/v1/formats`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::{GeoragError, Result};
+
+/// Type of an option value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OptionKind {
+    /// Any non-empty text
+    String,
+
+    /// An EPSG code, with or without an `EPSG:` prefix
+    Epsg,
+
+    /// One of a fixed set of values
+    Choice { values: Vec<&'static str> },
+}
+
+impl fmt::Display for OptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionKind::String => write!(f, "text"),
+            OptionKind::Epsg => write!(f, "EPSG code"),
+            OptionKind::Choice { values } => write!(f, "one of {}", values.join(", ")),
+        }
+    }
+}
+
+/// An option a reader accepts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OptionSpec {
+    pub key: &'static str,
+
+    #[serde(flatten)]
+    pub kind: OptionKind,
+
+    pub description: &'static str,
+
+    /// Behavior when the option is not given, if it has a value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<&'static str>,
+}
+
+impl OptionSpec {
+    /// A free-text option
+    pub fn string(key: &'static str, description: &'static str) -> Self {
+        Self {
+            key,
+            kind: OptionKind::String,
+            description,
+            default: None,
+        }
+    }
+
+    /// An EPSG code option
+    pub fn epsg(key: &'static str, description: &'static str) -> Self {
+        Self {
+            key,
+            kind: OptionKind::Epsg,
+            description,
+            default: None,
+        }
+    }
+
+    /// An option restricted to `values`
+    pub fn choice(key: &'static str, values: &[&'static str], description: &'static str) -> Self {
+        Self {
+            key,
+            kind: OptionKind::Choice { values: values.to_vec() },
+            description,
+            default: None,
+        }
+    }
+
+    pub fn with_default(mut self, default: &'static str) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Check a value against the option's type
+    pub fn check(&self, value: &str) -> std::result::Result<(), String> {
+        let valid = match &self.kind {
+            OptionKind::String => !value.trim().is_empty(),
+            OptionKind::Epsg => parse_epsg(value).is_some(),
+            OptionKind::Choice { values } => values.iter().any(|v| *v == value),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "invalid value '{}' for option '{}': expected {}",
+                value, self.key, self.kind
+            ))
+        }
+    }
+}
+
+/// Parse an EPSG code such as `4326` or `EPSG:4326`
+pub fn parse_epsg(value: &str) -> Option<u32> {
+    let value = value.trim();
+    let code = value
+        .strip_prefix("EPSG:")
+        .or_else(|| value.strip_prefix("epsg:"))
+        .unwrap_or(value);
+    code.parse::<u32>().ok().filter(|code| *code > 0)
+}
+
+/// Check reader options against a schema
+///
+/// Fails on the first unknown key or invalid value, in key order, listing the
+/// options the format accepts.
+pub fn validate_options(
+    format: &str,
+    schema: &[OptionSpec],
+    options: &HashMap<String, String>,
+) -> Result<()> {
+    let invalid =
+        |reason: String| GeoragError::InvalidFormatOption { format: format.to_string(), reason };
+
+    let mut keys: Vec<&String> = options.keys().collect();
+    keys.sort();
+    for key in keys {
+        let Some(spec) = schema.iter().find(|spec| spec.key == key) else {
+            let valid = if schema.is_empty() {
+                format!("{} takes no options", format)
+            } else {
+                let listed: Vec<String> =
+                    schema.iter().map(|spec| format!("{} ({})", spec.key, spec.kind)).collect();
+                format!("valid options: {}", listed.join("; "))
+            };
+            return Err(invalid(format!("unknown option '{}'; {}", key, valid)));
+        };
+        spec.check(&options[key]).map_err(invalid)?;
+    }
+    Ok(())
+}
+
+/// A registered format and the options its reader accepts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FormatDescription {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub options: Vec<OptionSpec>,
+}
@@ -1,17 +1,23 @@
 use async_trait::async_trait;
 use shapefile::dbase::FieldValue as DbaseFieldValue;
-use shapefile::{Reader as ShapefileReader, Shape};
+use shapefile::{Reader as ShapefileReader, Shape, ShapeReader, NO_DATA};
 use std::collections::HashMap;
 use std::fs;
 use std::io::BufReader;
 use std::path::Path;
-use std::str::FromStr;
+use std::time::Instant;
 
 use crate::error::{GeoragError, Result};
+use crate::formats::prj;
+use crate::formats::schema::parse_epsg;
 use crate::formats::validation::FormatValidator;
 use crate::formats::{
-    FormatDataset, FormatFeature, FormatMetadata, FormatReader, FormatValidation,
+    check_geometry, FeatureError, FeatureErrorKind, FeatureErrors, FormatDataset, FormatFeature,
+    FormatInspection, FormatMetadata, FormatOptions, FormatReader, FormatValidation, InspectMethod,
+    OptionSpec,
 };
+use crate::geo::geometry_to_geojson_z;
+use crate::models::Geometry;
 
 /// Shapefile format reader
 pub struct ShapefileFormatReader;
@@ -19,6 +25,17 @@ pub struct ShapefileFormatReader;
 #[async_trait]
 impl FormatReader for ShapefileFormatReader {
     async fn read(&self, path: &Path) -> Result<FormatDataset> {
+        self.read_with_options(path, &FormatOptions::new()).await
+    }
+
+    /// Read with an optional `crs` option (an EPSG code) overriding the .prj
+    async fn read_with_options(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+    ) -> Result<FormatDataset> {
+        let crs_override = self.crs_option(options)?;
+
         // Verify all required component files exist
         self.verify_components(path)?;
 
@@ -30,10 +47,27 @@ impl FormatReader for ShapefileFormatReader {
             })?;
 
         // Extract CRS
-        let crs = self.extract_crs(path)?;
+        let crs = match crs_override {
+            Some(crs) => {
+                let declared = self.read_prj(path)?.and_then(|prj| self.crs_from_prj(&prj));
+                if let Some(detected) = declared {
+                    if detected != crs {
+                        tracing::warn!(
+                            "Shapefile {} declares EPSG:{} in its .prj file; using EPSG:{}",
+                            path.display(),
+                            detected,
+                            crs
+                        );
+                    }
+                }
+                crs
+            }
+            None => self.extract_crs(path)?.ok_or_else(|| self.unresolved_crs(path))?,
+        };
 
         // Read features
-        let features = self.read_features(&mut reader)?;
+        let mut errors = FeatureErrors::new("Shapefile", options.read_policy);
+        let features = self.read_features(&mut reader, &mut errors)?;
 
         // Get dataset name from filename
         let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unnamed").to_string();
@@ -48,12 +82,64 @@ impl FormatReader for ShapefileFormatReader {
                 paragraph_count: None,
                 extraction_method: Some("shapefile-rs".to_string()),
                 spatial_association: None,
+                axis_order: None,
+                license: None,
+                attribution: None,
             },
             crs,
             features,
+            errors: errors.into_vec(),
         })
     }
 
+    /// Count and extent from the .shx and .shp headers; only the sample is read
+    async fn inspect(
+        &self,
+        path: &Path,
+        options: &FormatOptions,
+        sample: usize,
+    ) -> Result<FormatInspection> {
+        let crs_override = self.crs_option(options)?;
+        self.verify_components(path)?;
+        let mut reader =
+            ShapefileReader::from_path(path).map_err(|e| GeoragError::FormatError {
+                format: "Shapefile".to_string(),
+                message: format!("Failed to open Shapefile: {}", e),
+            })?;
+
+        let mut inspection = FormatInspection::new("Shapefile", InspectMethod::Header);
+        inspection.crs = match crs_override {
+            Some(crs) => Some(crs),
+            None => self.extract_crs(path)?,
+        };
+        let count = reader.shape_count().map_err(|e| GeoragError::FormatError {
+            format: "Shapefile".to_string(),
+            message: format!("Failed to read the .shx index: {}", e),
+        })?;
+        inspection.feature_count = Some(count);
+        if count > 0 {
+            let bbox = &reader.header().bbox;
+            inspection.extent = Some([bbox.min.x, bbox.min.y, bbox.max.x, bbox.max.y]);
+        }
+
+        let mut errors = FeatureErrors::new("Shapefile", options.read_policy);
+        let started = Instant::now();
+        for (index, result) in reader.iter_shapes_and_records().take(sample).enumerate() {
+            let unreadable = result.is_err();
+            match self.convert_record(index, result) {
+                Ok(feature) => inspection.add_sample(&feature),
+                Err(error) => {
+                    errors.record(error)?;
+                    if unreadable {
+                        break;
+                    }
+                }
+            }
+        }
+        inspection.sample_time = started.elapsed();
+        Ok(inspection)
+    }
+
     fn supported_extensions(&self) -> &[&str] {
         &["shp"]
     }
@@ -62,6 +148,13 @@ impl FormatReader for ShapefileFormatReader {
         "Shapefile"
     }
 
+    fn option_schema(&self) -> Vec<OptionSpec> {
+        vec![OptionSpec::epsg(
+            "crs",
+            "EPSG code of the dataset, overriding the .prj file (e.g. 4326 or EPSG:4326)",
+        )]
+    }
+
     async fn validate(&self, path: &Path) -> Result<FormatValidation> {
         // Basic file validation
         let mut validation = FormatValidator::validate_file_exists(path);
@@ -83,7 +176,29 @@ impl FormatReader for ShapefileFormatReader {
             FormatValidator::validate_component_files(&base, &["shp", "shx", "dbf"], &["prj"]);
 
         // Merge validations
-        Ok(FormatValidator::merge_validations(vec![validation, component_validation]))
+        let mut validation =
+            FormatValidator::merge_validations(vec![validation, component_validation]);
+
+        // An unrecognized .prj must not silently become EPSG:4326
+        if let Ok(Some(prj)) = self.read_prj(path) {
+            if self.crs_from_prj(&prj).is_none() {
+                validation.warnings.push(self.unresolved_crs(path).to_string());
+            }
+        }
+
+        // Measures are dropped; say so once for the file rather than per shape
+        if let Ok(shapes) = ShapeReader::from_path(path) {
+            let header = shapes.header();
+            let [min, max] = [header.bbox.min.m, header.bbox.max.m];
+            if header.shape_type.has_m() && min > NO_DATA && (min, max) != (0.0, 0.0) {
+                validation.warnings.push(format!(
+                    "{} shapes have M (measure) ordinates; they are dropped, only X, Y and Z are kept",
+                    header.shape_type
+                ));
+            }
+        }
+
+        Ok(validation)
     }
 }
 
@@ -108,6 +223,19 @@ impl ShapefileFormatReader {
             .unwrap_or(false)
     }
 
+    /// EPSG code given by the `crs` option, if any
+    fn crs_option(&self, options: &FormatOptions) -> Result<Option<u32>> {
+        options
+            .get("crs")
+            .map(|crs| {
+                parse_epsg(crs).ok_or_else(|| GeoragError::FormatError {
+                    format: "Shapefile".to_string(),
+                    message: format!("Invalid CRS '{}': expected an EPSG code", crs),
+                })
+            })
+            .transpose()
+    }
+
     /// Verify that all required Shapefile component files exist
     fn verify_components(&self, path: &Path) -> Result<()> {
         let base = self.get_shapefile_base(path)?;
@@ -132,62 +260,83 @@ impl ShapefileFormatReader {
     }
 
     /// Extract CRS from the Shapefile .prj file
-    fn extract_crs(&self, path: &Path) -> Result<u32> {
-        let base = self.get_shapefile_base(path)?;
-        let prj_path = base.with_extension("prj");
-
-        if !prj_path.exists() {
+    ///
+    /// Returns `None` when the .prj file exists but names no coordinate system
+    /// this reader can identify.
+    fn extract_crs(&self, path: &Path) -> Result<Option<u32>> {
+        let Some(prj_content) = self.read_prj(path)? else {
             // No .prj file, default to EPSG:4326 with warning
             tracing::warn!(
                 "Shapefile {} has no .prj file, defaulting to EPSG:4326 (WGS84). \
                  CRS may be incorrect.",
                 path.display()
             );
-            return Ok(4326);
-        }
+            return Ok(Some(4326));
+        };
 
-        // Read .prj file content
-        let prj_content = fs::read_to_string(&prj_path).map_err(|e| GeoragError::FormatError {
-            format: "Shapefile".to_string(),
-            message: format!("Failed to read .prj file: {}", e),
-        })?;
+        Ok(self.crs_from_prj(&prj_content))
+    }
 
+    /// Identify the EPSG code of a .prj definition
+    fn crs_from_prj(&self, prj_content: &str) -> Option<u32> {
         // Try to parse WKT and extract EPSG code
-        if let Some(epsg) = self.parse_epsg_from_wkt(&prj_content) {
-            return Ok(epsg);
+        if let Some(epsg) = self.parse_epsg_from_wkt(prj_content) {
+            return Some(epsg);
         }
 
-        // Try using wkt crate to parse
-        match wkt::Wkt::<f64>::from_str(&prj_content) {
-            Ok(_wkt) => {
-                // Successfully parsed WKT, but couldn't extract EPSG
-                // Try to find AUTHORITY in the string
-                if let Some(epsg) = self.extract_authority_code(&prj_content) {
-                    return Ok(epsg);
-                }
-            }
-            Err(_) => {
-                // Failed to parse WKT
-            }
+        // ESRI WKT carries no authority; match its parameters instead
+        prj::identify_epsg(prj_content)
+    }
+
+    /// Read the .prj file next to a Shapefile, if there is one
+    fn read_prj(&self, path: &Path) -> Result<Option<String>> {
+        let prj_path = self.get_shapefile_base(path)?.with_extension("prj");
+        if !prj_path.exists() {
+            return Ok(None);
         }
 
-        // Could not extract EPSG code, default to 4326 with warning
-        tracing::warn!(
-            "Shapefile {} has .prj file but could not extract EPSG code, \
-             defaulting to EPSG:4326 (WGS84). CRS may be incorrect.",
-            path.display()
-        );
-        Ok(4326)
+        fs::read_to_string(&prj_path).map(Some).map_err(|e| GeoragError::FormatError {
+            format: "Shapefile".to_string(),
+            message: format!("Failed to read .prj file: {}", e),
+        })
+    }
+
+    /// Error for a .prj file naming an unknown coordinate system
+    fn unresolved_crs(&self, path: &Path) -> GeoragError {
+        let projection = self
+            .read_prj(path)
+            .ok()
+            .flatten()
+            .map(|content| prj::describe(&content))
+            .unwrap_or_default();
+
+        GeoragError::CrsExtraction {
+            format: "Shapefile".to_string(),
+            reason: format!(
+                "could not identify the coordinate system \"{}\" in the .prj file of {}. \
+                Pass its EPSG code with --crs <EPSG>",
+                projection,
+                path.display()
+            ),
+        }
     }
 
     /// Parse EPSG code from WKT string
+    ///
+    /// Only the authority of the outermost definition counts: the codes of a
+    /// nested datum, spheroid or unit do not identify the coordinate system.
     fn parse_epsg_from_wkt(&self, wkt: &str) -> Option<u32> {
-        // Look for AUTHORITY["EPSG","4326"] pattern
-        if let Some(start) = wkt.find("AUTHORITY[\"EPSG\",\"") {
+        let wkt = wkt.trim();
+
+        // Look for AUTHORITY["EPSG","4326"] closing the definition
+        if let Some(start) = wkt.rfind("AUTHORITY[\"EPSG\",\"") {
             let code_start = start + 18; // Length of 'AUTHORITY["EPSG","'
             if let Some(end) = wkt[code_start..].find('\"') {
-                if let Ok(code) = wkt[code_start..code_start + end].parse::<u32>() {
-                    return Some(code);
+                let closing = wkt[code_start + end + 1..].trim();
+                if closing == "]]" {
+                    if let Ok(code) = wkt[code_start..code_start + end].parse::<u32>() {
+                        return Some(code);
+                    }
                 }
             }
         }
@@ -205,199 +354,137 @@ impl ShapefileFormatReader {
         None
     }
 
-    /// Extract AUTHORITY code from WKT string
-    fn extract_authority_code(&self, wkt: &str) -> Option<u32> {
-        // Look for AUTHORITY["EPSG","code"] anywhere in the string
-        for line in wkt.lines() {
-            if line.contains("AUTHORITY") && line.contains("EPSG") {
-                // Extract the number after EPSG
-                if let Some(start) = line.find("EPSG") {
-                    let after_epsg = &line[start + 4..];
-                    // Find the first sequence of digits
-                    let digits: String = after_epsg
-                        .chars()
-                        .skip_while(|c| !c.is_ascii_digit())
-                        .take_while(|c| c.is_ascii_digit())
-                        .collect();
-                    if let Ok(code) = digits.parse::<u32>() {
-                        return Some(code);
-                    }
-                }
-            }
-        }
-        None
-    }
-
     /// Read features from the Shapefile
     fn read_features(
         &self,
         reader: &mut shapefile::Reader<BufReader<fs::File>, BufReader<fs::File>>,
+        errors: &mut FeatureErrors,
     ) -> Result<Vec<FormatFeature>> {
         let mut features = Vec::new();
 
         // Iterate through all shapes and records
-        for result in reader.iter_shapes_and_records() {
-            let (shape, record) = result.map_err(|e| GeoragError::FormatError {
-                format: "Shapefile".to_string(),
-                message: format!("Failed to read feature: {}", e),
-            })?;
+        for (index, result) in reader.iter_shapes_and_records().enumerate() {
+            let unreadable = result.is_err();
+            match self.convert_record(index, result) {
+                Ok(feature) => features.push(feature),
+                Err(error) => {
+                    errors.record(error)?;
+                    // The record stream cannot be resynchronized after a decoding error
+                    if unreadable {
+                        tracing::warn!(
+                            "Stopped reading Shapefile after record {}: the rest of the file is unreadable",
+                            index
+                        );
+                        break;
+                    }
+                }
+            }
+        }
 
-            // Convert shape to GeoJSON geometry
-            let geometry = self.convert_shape_to_geojson(&shape)?;
+        Ok(features)
+    }
 
-            // Extract properties from DBF record
-            let properties = self.extract_properties(&record)?;
+    /// Convert one shape and its DBF record to a feature
+    fn convert_record(
+        &self,
+        index: usize,
+        result: std::result::Result<(Shape, shapefile::dbase::Record), shapefile::Error>,
+    ) -> std::result::Result<FormatFeature, FeatureError> {
+        // Generate feature ID from record number
+        let id = index.to_string();
+
+        let (shape, record) = result.map_err(|e| {
+            FeatureError::new(
+                index,
+                FeatureErrorKind::Malformed,
+                format!("Failed to read record: {}", e),
+            )
+        })?;
 
-            // Generate feature ID from record number
-            let id = features.len().to_string();
+        // Convert shape to GeoJSON geometry
+        let geometry = self.convert_shape_to_geojson(&shape).map_err(|e| {
+            FeatureError::new(index, FeatureErrorKind::UnsupportedGeometry, e.to_string())
+                .with_id(&id)
+        })?;
+        check_geometry(&geometry)
+            .map_err(|(kind, message)| FeatureError::new(index, kind, message).with_id(&id))?;
 
-            features.push(FormatFeature { id, geometry: Some(geometry), properties });
-        }
+        // Extract properties from DBF record
+        let properties = self.extract_properties(&record).map_err(|e| {
+            FeatureError::new(index, FeatureErrorKind::InvalidProperties, e.to_string())
+                .with_id(&id)
+        })?;
 
-        Ok(features)
+        Ok(FormatFeature { id, geometry: Some(geometry), properties })
     }
 
     /// Convert shapefile Shape to GeoJSON Value
+    ///
+    /// Z shapes keep their elevation as a third ordinate; measure values are
+    /// dropped. A null shape has no geometry.
     fn convert_shape_to_geojson(&self, shape: &Shape) -> Result<serde_json::Value> {
-        match shape {
-            Shape::Point(point) => Ok(serde_json::json!({
-                "type": "Point",
-                "coordinates": [point.x, point.y]
-            })),
-            Shape::PointZ(point) => Ok(serde_json::json!({
-                "type": "Point",
-                "coordinates": [point.x, point.y, point.z]
-            })),
-            Shape::PointM(point) => Ok(serde_json::json!({
-                "type": "Point",
-                "coordinates": [point.x, point.y]
-            })),
-            Shape::Polyline(polyline) => {
-                let coordinates: Vec<Vec<[f64; 2]>> = polyline
-                    .parts()
-                    .iter()
-                    .map(|part| part.iter().map(|p| [p.x, p.y]).collect())
-                    .collect();
-
-                if coordinates.len() == 1 {
-                    Ok(serde_json::json!({
-                        "type": "LineString",
-                        "coordinates": coordinates[0]
-                    }))
-                } else {
-                    Ok(serde_json::json!({
-                        "type": "MultiLineString",
-                        "coordinates": coordinates
-                    }))
-                }
-            }
-            Shape::PolylineZ(polyline) => {
-                let coordinates: Vec<Vec<[f64; 3]>> = polyline
-                    .parts()
-                    .iter()
-                    .map(|part| part.iter().map(|p| [p.x, p.y, p.z]).collect())
-                    .collect();
-
-                if coordinates.len() == 1 {
-                    Ok(serde_json::json!({
-                        "type": "LineString",
-                        "coordinates": coordinates[0]
-                    }))
-                } else {
-                    Ok(serde_json::json!({
-                        "type": "MultiLineString",
-                        "coordinates": coordinates
-                    }))
-                }
-            }
-            Shape::PolylineM(polyline) => {
-                let coordinates: Vec<Vec<[f64; 2]>> = polyline
-                    .parts()
-                    .iter()
-                    .map(|part| part.iter().map(|p| [p.x, p.y]).collect())
-                    .collect();
-
-                if coordinates.len() == 1 {
-                    Ok(serde_json::json!({
-                        "type": "LineString",
-                        "coordinates": coordinates[0]
-                    }))
-                } else {
-                    Ok(serde_json::json!({
-                        "type": "MultiLineString",
-                        "coordinates": coordinates
-                    }))
-                }
+        // Point, PointM and PointZ are distinct types sharing `x` and `y`
+        macro_rules! xy {
+            ($points:expr) => {
+                $points.iter().map(|p| [p.x, p.y]).collect::<Vec<[f64; 2]>>()
+            };
+        }
+        macro_rules! parts {
+            ($polyline:expr) => {
+                $polyline.parts().iter().map(|part| xy!(part)).collect()
+            };
+        }
+        macro_rules! rings {
+            ($polygon:expr) => {
+                $polygon.rings().iter().map(|ring| xy!(ring.points())).collect()
+            };
+        }
+
+        let geometry = match shape {
+            Shape::Point(point) => Geometry::point(point.x, point.y),
+            Shape::PointZ(point) => Geometry::point(point.x, point.y),
+            Shape::PointM(point) => Geometry::point(point.x, point.y),
+            Shape::Polyline(polyline) => line_geometry(parts!(polyline)),
+            Shape::PolylineZ(polyline) => line_geometry(parts!(polyline)),
+            Shape::PolylineM(polyline) => line_geometry(parts!(polyline)),
+            Shape::Polygon(polygon) => Geometry::polygon(rings!(polygon)),
+            Shape::PolygonZ(polygon) => Geometry::polygon(rings!(polygon)),
+            Shape::PolygonM(polygon) => Geometry::polygon(rings!(polygon)),
+            Shape::Multipoint(multipoint) => {
+                Geometry::MultiPoint { coordinates: xy!(multipoint.points()) }
             }
-            Shape::Polygon(polygon) => {
-                let rings: Vec<Vec<[f64; 2]>> = polygon
-                    .rings()
-                    .iter()
-                    .map(|ring| ring.points().iter().map(|p| [p.x, p.y]).collect())
-                    .collect();
-
-                Ok(serde_json::json!({
-                    "type": "Polygon",
-                    "coordinates": rings
-                }))
+            Shape::MultipointZ(multipoint) => {
+                Geometry::MultiPoint { coordinates: xy!(multipoint.points()) }
             }
-            Shape::PolygonZ(polygon) => {
-                let rings: Vec<Vec<[f64; 3]>> = polygon
-                    .rings()
-                    .iter()
-                    .map(|ring| ring.points().iter().map(|p| [p.x, p.y, p.z]).collect())
-                    .collect();
-
-                Ok(serde_json::json!({
-                    "type": "Polygon",
-                    "coordinates": rings
-                }))
+            Shape::MultipointM(multipoint) => {
+                Geometry::MultiPoint { coordinates: xy!(multipoint.points()) }
             }
-            Shape::PolygonM(polygon) => {
-                let rings: Vec<Vec<[f64; 2]>> = polygon
-                    .rings()
-                    .iter()
-                    .map(|ring| ring.points().iter().map(|p| [p.x, p.y]).collect())
-                    .collect();
-
-                Ok(serde_json::json!({
-                    "type": "Polygon",
-                    "coordinates": rings
-                }))
+            Shape::Multipatch(_) => {
+                return Err(GeoragError::FormatError {
+                    format: "Shapefile".to_string(),
+                    message: "Multipatch geometry type is not supported".to_string(),
+                })
             }
-            Shape::Multipoint(multipoint) => {
-                let coordinates: Vec<[f64; 2]> =
-                    multipoint.points().iter().map(|p| [p.x, p.y]).collect();
+            Shape::NullShape => return Ok(serde_json::Value::Null),
+        };
 
-                Ok(serde_json::json!({
-                    "type": "MultiPoint",
-                    "coordinates": coordinates
-                }))
+        let z: Option<Vec<f64>> = match shape {
+            Shape::PointZ(point) => Some(vec![point.z]),
+            Shape::PolylineZ(polyline) => {
+                Some(polyline.parts().iter().flatten().map(|p| p.z).collect())
             }
-            Shape::MultipointZ(multipoint) => {
-                let coordinates: Vec<[f64; 3]> =
-                    multipoint.points().iter().map(|p| [p.x, p.y, p.z]).collect();
-
-                Ok(serde_json::json!({
-                    "type": "MultiPoint",
-                    "coordinates": coordinates
-                }))
+            Shape::PolygonZ(polygon) => {
+                Some(polygon.rings().iter().flat_map(|ring| ring.points()).map(|p| p.z).collect())
             }
-            Shape::MultipointM(multipoint) => {
-                let coordinates: Vec<[f64; 2]> =
-                    multipoint.points().iter().map(|p| [p.x, p.y]).collect();
-
-                Ok(serde_json::json!({
-                    "type": "MultiPoint",
-                    "coordinates": coordinates
-                }))
+            Shape::MultipointZ(multipoint) => {
+                Some(multipoint.points().iter().map(|p| p.z).collect())
             }
-            Shape::Multipatch(_) => Err(GeoragError::FormatError {
-                format: "Shapefile".to_string(),
-                message: "Multipatch geometry type is not supported".to_string(),
-            }),
-            Shape::NullShape => Ok(serde_json::Value::Null),
-        }
+            _ => None,
+        };
+        Ok(match z {
+            Some(z) if z.len() == geometry.vertex_count() => geometry_to_geojson_z(&geometry, &z),
+            _ => geometry.to_geojson(),
+        })
     }
 
     /// Extract properties from DBF record
@@ -456,10 +543,38 @@ impl ShapefileFormatReader {
     }
 }
 
+/// A single part as a LineString, several as a MultiLineString
+fn line_geometry(parts: Vec<Vec<[f64; 2]>>) -> Geometry {
+    if parts.len() == 1 {
+        Geometry::line_string(parts.into_iter().next().unwrap_or_default())
+    } else {
+        Geometry::MultiLineString { coordinates: parts }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_crs_option_is_validated() {
+        let reader = ShapefileFormatReader;
+        for crs in ["4326", "EPSG:32748"] {
+            assert!(FormatOptions::new().with_option("crs", crs).validate(&reader).is_ok());
+        }
+
+        let err = FormatOptions::new().with_option("crs", "utm48s").validate(&reader).unwrap_err();
+        assert!(err.to_string().contains("invalid value 'utm48s' for option 'crs'"));
+
+        let err = FormatOptions::new()
+            .with_option("layer", "roads")
+            .validate(&reader)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown option 'layer'; valid options: crs (EPSG code)"));
+    }
+
     #[test]
     fn test_supported_extensions() {
         let reader = ShapefileFormatReader;
@@ -494,5 +609,146 @@ mod tests {
         // Test EPSG: prefix
         let wkt2 = "EPSG:3857";
         assert_eq!(reader.parse_epsg_from_wkt(wkt2), Some(3857));
+
+        // Nested authorities belong to the datum and spheroid, not the CRS
+        let wkt3 = r#"PROJCS["Local",GEOGCS["WGS 84",DATUM["WGS_1984",SPHEROID["WGS 84",6378137,298.257223563,AUTHORITY["EPSG","7030"]],AUTHORITY["EPSG","6326"]]],PROJECTION["Albers"]]"#;
+        assert_eq!(reader.parse_epsg_from_wkt(wkt3), None);
+    }
+
+    #[test]
+    fn test_corrupt_records_become_feature_errors() {
+        use shapefile::dbase::Record;
+        use shapefile::Point;
+
+        let reader = ShapefileFormatReader;
+
+        let feature =
+            reader.convert_record(0, Ok((Shape::Point(Point::new(1.0, 2.0)), Record::default())));
+        assert_eq!(feature.unwrap().id, "0");
+
+        let unreadable = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated");
+        let error = reader
+            .convert_record(1, Err(shapefile::Error::IoError(unreadable)))
+            .unwrap_err();
+        assert_eq!(error.kind, FeatureErrorKind::Malformed);
+        assert_eq!(error.id, None);
+
+        let error = reader
+            .convert_record(2, Ok((Shape::Point(Point::new(f64::NAN, 2.0)), Record::default())))
+            .unwrap_err();
+        assert_eq!((error.index, error.kind), (2, FeatureErrorKind::InvalidGeometry));
+        assert_eq!(error.id.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_z_shapes_keep_elevation_and_m_shapes_convert_to_two_dimensions() {
+        use crate::geo::z_from_geojson;
+        use shapefile::{Point, PointM, PointZ, Polyline, PolylineZ};
+
+        let reader = ShapefileFormatReader;
+        let convert = |shape: Shape| {
+            let geojson = reader.convert_shape_to_geojson(&shape).unwrap();
+            (Geometry::from_geojson(&geojson), z_from_geojson(&geojson))
+        };
+
+        assert_eq!(
+            convert(Shape::PointZ(PointZ::new(106.8, -6.2, 12.0, 0.0))),
+            (Some(Geometry::point(106.8, -6.2)), Some(vec![12.0]))
+        );
+        assert_eq!(
+            convert(Shape::PointM(PointM::new(106.8, -6.2, 3.0))),
+            (Some(Geometry::point(106.8, -6.2)), None)
+        );
+        assert_eq!(
+            convert(Shape::PolylineZ(PolylineZ::new(vec![
+                PointZ::new(0.0, 0.0, 1.0, 0.0),
+                PointZ::new(1.0, 1.0, 2.0, 0.0),
+            ]))),
+            (Some(Geometry::line_string(vec![[0.0, 0.0], [1.0, 1.0]])), Some(vec![1.0, 2.0]))
+        );
+        assert_eq!(
+            convert(Shape::Polyline(Polyline::with_parts(vec![
+                vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+                vec![Point::new(2.0, 2.0), Point::new(3.0, 3.0)],
+            ]))),
+            (
+                Some(Geometry::MultiLineString {
+                    coordinates: vec![vec![[0.0, 0.0], [1.0, 1.0]], vec![[2.0, 2.0], [3.0, 3.0]]],
+                }),
+                None
+            )
+        );
+        assert_eq!(
+            reader.convert_shape_to_geojson(&Shape::NullShape).unwrap(),
+            serde_json::Value::Null
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inspection_reads_count_and_extent_from_headers() {
+        use shapefile::dbase::{FieldValue, Record, TableWriterBuilder};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("places.shp");
+        let table = TableWriterBuilder::new().add_character_field("name".try_into().unwrap(), 50);
+        let mut writer = shapefile::Writer::from_path(&path, table).unwrap();
+        for (name, x, y) in
+            [("Harbor", 106.81, -6.1), ("Market", 106.8, -6.2), ("Pier", 106.9, -6.0)]
+        {
+            let mut record = Record::default();
+            record.insert("name".to_string(), FieldValue::Character(Some(name.to_string())));
+            writer.write_shape_and_record(&shapefile::Point::new(x, y), &record).unwrap();
+        }
+        drop(writer);
+
+        let options = FormatOptions::new().with_option("crs", "4326");
+        let inspection = ShapefileFormatReader.inspect(&path, &options, 2).await.unwrap();
+
+        assert_eq!(inspection.method, InspectMethod::Header);
+        assert_eq!(inspection.crs, Some(4326));
+        assert_eq!(inspection.feature_count, Some(3));
+        assert_eq!(inspection.sampled_features, 2);
+        assert_eq!(inspection.extent, Some([106.8, -6.2, 106.9, -6.0]));
+        assert!(inspection.property_keys.contains("name"));
+    }
+
+    /// Write a .prj file next to empty Shapefile components
+    fn write_prj(dir: &Path, content: &str) -> std::path::PathBuf {
+        for ext in ["shp", "shx", "dbf"] {
+            fs::write(dir.join("roads").with_extension(ext), b"").unwrap();
+        }
+        fs::write(dir.join("roads.prj"), content).unwrap();
+        dir.join("roads.shp")
+    }
+
+    #[test]
+    fn test_esri_prj_without_authority_is_identified() {
+        let reader = ShapefileFormatReader;
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_prj(
+            dir.path(),
+            r#"PROJCS["WGS_1984_UTM_Zone_48S",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]],PRIMEM["Greenwich",0.0],UNIT["Degree",0.0174532925199433]],PROJECTION["Transverse_Mercator"],PARAMETER["False_Easting",500000.0],PARAMETER["False_Northing",10000000.0],PARAMETER["Central_Meridian",105.0],PARAMETER["Scale_Factor",0.9996],PARAMETER["Latitude_Of_Origin",0.0],UNIT["Meter",1.0]]"#,
+        );
+
+        assert_eq!(reader.extract_crs(&path).unwrap(), Some(32748));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_prj_is_reported_instead_of_assuming_4326() {
+        let reader = ShapefileFormatReader;
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_prj(
+            dir.path(),
+            r#"PROJCS["Custom_Albers",GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984",SPHEROID["WGS_1984",6378137.0,298.257223563]]],PROJECTION["Albers"],PARAMETER["Central_Meridian",110.0],UNIT["Meter",1.0]]"#,
+        );
+
+        assert_eq!(reader.extract_crs(&path).unwrap(), None);
+
+        let message = reader.unresolved_crs(&path).to_string();
+        assert!(message.contains("Custom_Albers"), "{}", message);
+        assert!(message.contains("--crs"), "{}", message);
+
+        let validation = reader.validate(&path).await.unwrap();
+        assert!(validation.warnings.iter().any(|w| w.contains("Custom_Albers")));
     }
 }
@@ -0,0 +1,341 @@
+//! Handing a reader's features to a consumer in bounded batches
+//!
+//! [`FormatReader::read_streaming`](super::FormatReader::read_streaming) gives
+//! its features to a [`FeatureSink`] instead of collecting them. The sink sends
+//! them on in batches over a channel that holds a bounded number of batches, so
+//! a reader waits while its consumer is behind and a file is never held in
+//! memory whole. Before any feature, the reader sends a [`StreamHeader`] with
+//! what the consumer needs to handle them: the CRS and the format metadata.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+use super::{FormatDataset, FormatFeature, FormatMetadata};
+use crate::error::{GeoragError, Result};
+
+/// Features sent on together by default
+pub const DEFAULT_INGEST_BATCH_SIZE: usize = 1000;
+
+/// Batches a channel holds before its sender waits, by default
+pub const DEFAULT_INGEST_CHANNEL_CAPACITY: usize = 4;
+
+/// Batch size and channel capacity of a streaming read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestBuffers {
+    /// Features sent on together
+    pub batch_size: usize,
+
+    /// Batches a channel holds before its sender waits
+    pub channel_capacity: usize,
+}
+
+impl Default for IngestBuffers {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_INGEST_BATCH_SIZE,
+            channel_capacity: DEFAULT_INGEST_CHANNEL_CAPACITY,
+        }
+    }
+}
+
+impl IngestBuffers {
+    /// Buffers of the given sizes, each at least 1
+    pub fn new(batch_size: usize, channel_capacity: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            channel_capacity: channel_capacity.max(1),
+        }
+    }
+}
+
+/// What a reader knows about a file before its features
+#[derive(Debug, Clone)]
+pub struct StreamHeader {
+    /// CRS EPSG code
+    pub crs: u32,
+
+    /// Format-specific metadata; per-feature errors are reported at the end
+    pub format_metadata: FormatMetadata,
+}
+
+/// Counters shared by a stream's reader and its consumers
+///
+/// Features count as in flight from the moment the reader hands them to the
+/// sink until a consumer releases them, e.g. once they are stored.
+#[derive(Debug, Default)]
+pub struct StreamGauge {
+    read: AtomicUsize,
+    in_flight: AtomicUsize,
+    peak: AtomicUsize,
+    waited_micros: AtomicU64,
+}
+
+impl StreamGauge {
+    /// Count features entering the stream after the reader
+    pub fn hold(&self, count: usize) {
+        let now = self.in_flight.fetch_add(count, Ordering::Relaxed) + count;
+        self.peak.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Count features leaving the stream
+    pub fn release(&self, count: usize) {
+        self.in_flight.fetch_sub(count, Ordering::Relaxed);
+    }
+
+    /// Features read so far
+    pub fn read(&self) -> usize {
+        self.read.load(Ordering::Relaxed)
+    }
+
+    /// Features held and not released yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Most features ever in flight at once
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Time the reader spent waiting for room in the channel
+    pub fn reader_waited(&self) -> Duration {
+        Duration::from_micros(self.waited_micros.load(Ordering::Relaxed))
+    }
+}
+
+/// Create a sink for a reader and the stream its features arrive on
+pub fn feature_channel(buffers: IngestBuffers) -> (FeatureSink, FeatureStream) {
+    let buffers = IngestBuffers::new(buffers.batch_size, buffers.channel_capacity);
+    let (header_tx, header_rx) = oneshot::channel();
+    let (batch_tx, batch_rx) = mpsc::channel(buffers.channel_capacity);
+    let gauge = Arc::new(StreamGauge::default());
+
+    let sink = FeatureSink {
+        header: Some(header_tx),
+        batches: batch_tx,
+        batch: Vec::with_capacity(buffers.batch_size),
+        batch_size: buffers.batch_size,
+        gauge: gauge.clone(),
+    };
+    let stream = FeatureStream {
+        header: Some(header_rx),
+        batches: batch_rx,
+        gauge,
+    };
+    (sink, stream)
+}
+
+/// Reader end of a feature stream
+///
+/// Sending fails with [`GeoragError::IngestStopped`] once the consumer has
+/// dropped its [`FeatureStream`]; a reader should return that error as is.
+/// The blocking variants are for readers parsing on a blocking thread.
+#[derive(Debug)]
+pub struct FeatureSink {
+    header: Option<oneshot::Sender<StreamHeader>>,
+    batches: mpsc::Sender<Vec<FormatFeature>>,
+    batch: Vec<FormatFeature>,
+    batch_size: usize,
+    gauge: Arc<StreamGauge>,
+}
+
+impl FeatureSink {
+    /// Send the header; later calls are ignored
+    pub fn start(&mut self, crs: u32, format_metadata: FormatMetadata) -> Result<()> {
+        match self.header.take() {
+            Some(header) => {
+                header.send(StreamHeader { crs, format_metadata }).map_err(|_| stopped())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Add a feature, waiting for room in the channel when its batch is full
+    pub async fn send(&mut self, feature: FormatFeature) -> Result<()> {
+        if let Some(batch) = self.push(feature)? {
+            let started = Instant::now();
+            self.batches.send(batch).await.map_err(|_| stopped())?;
+            self.waited(started);
+        }
+        Ok(())
+    }
+
+    /// Add a feature from a blocking thread
+    pub fn blocking_send(&mut self, feature: FormatFeature) -> Result<()> {
+        if let Some(batch) = self.push(feature)? {
+            let started = Instant::now();
+            self.batches.blocking_send(batch).map_err(|_| stopped())?;
+            self.waited(started);
+        }
+        Ok(())
+    }
+
+    /// Send the last, partial batch
+    pub async fn finish(mut self) -> Result<()> {
+        if !self.batch.is_empty() {
+            let batch = std::mem::take(&mut self.batch);
+            self.batches.send(batch).await.map_err(|_| stopped())?;
+        }
+        Ok(())
+    }
+
+    /// Send the last, partial batch from a blocking thread
+    pub fn blocking_finish(mut self) -> Result<()> {
+        if !self.batch.is_empty() {
+            let batch = std::mem::take(&mut self.batch);
+            self.batches.blocking_send(batch).map_err(|_| stopped())?;
+        }
+        Ok(())
+    }
+
+    /// Stream the features of a dataset that was read whole
+    ///
+    /// Returns the dataset without its features.
+    pub async fn forward(mut self, mut dataset: FormatDataset) -> Result<FormatDataset> {
+        self.start(dataset.crs, dataset.format_metadata.clone())?;
+        for feature in std::mem::take(&mut dataset.features) {
+            self.send(feature).await?;
+        }
+        self.finish().await?;
+        Ok(dataset)
+    }
+
+    /// Add a feature to the batch, returning the batch once it is full
+    fn push(&mut self, feature: FormatFeature) -> Result<Option<Vec<FormatFeature>>> {
+        if self.header.is_some() {
+            return Err(GeoragError::IngestStopped {
+                reason: "a feature was sent before the stream header".to_string(),
+            });
+        }
+
+        self.gauge.read.fetch_add(1, Ordering::Relaxed);
+        self.gauge.hold(1);
+        self.batch.push(feature);
+        if self.batch.len() < self.batch_size {
+            return Ok(None);
+        }
+        let next = Vec::with_capacity(self.batch_size);
+        Ok(Some(std::mem::replace(&mut self.batch, next)))
+    }
+
+    fn waited(&self, started: Instant) {
+        let micros = started.elapsed().as_micros() as u64;
+        self.gauge.waited_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+/// Consumer end of a feature stream
+#[derive(Debug)]
+pub struct FeatureStream {
+    header: Option<oneshot::Receiver<StreamHeader>>,
+    batches: mpsc::Receiver<Vec<FormatFeature>>,
+    gauge: Arc<StreamGauge>,
+}
+
+impl FeatureStream {
+    /// Wait for the header
+    ///
+    /// Returns `None` if the reader stopped before sending one, or if the
+    /// header was already taken.
+    pub async fn header(&mut self) -> Option<StreamHeader> {
+        self.header.take()?.await.ok()
+    }
+
+    /// Wait for the next batch; `None` once the reader is done
+    pub async fn next_batch(&mut self) -> Option<Vec<FormatFeature>> {
+        self.batches.recv().await
+    }
+
+    /// Counters shared with the reader
+    pub fn gauge(&self) -> Arc<StreamGauge> {
+        self.gauge.clone()
+    }
+}
+
+fn stopped() -> GeoragError {
+    GeoragError::IngestStopped {
+        reason: "the consumer of the feature stream is gone".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn feature(id: usize) -> FormatFeature {
+        FormatFeature {
+            id: id.to_string(),
+            geometry: None,
+            properties: HashMap::new(),
+        }
+    }
+
+    fn metadata() -> FormatMetadata {
+        FormatMetadata {
+            format_name: "Test".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            license: None,
+            attribution: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_features_arrive_in_batches_after_the_header() {
+        let (mut sink, mut stream) = feature_channel(IngestBuffers::new(2, 4));
+
+        sink.start(3857, metadata()).unwrap();
+        for id in 0..5 {
+            sink.send(feature(id)).await.unwrap();
+        }
+        sink.finish().await.unwrap();
+
+        assert_eq!(stream.header().await.unwrap().crs, 3857);
+        let mut sizes = Vec::new();
+        while let Some(batch) = stream.next_batch().await {
+            sizes.push(batch.len());
+        }
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert_eq!(stream.gauge().read(), 5);
+        assert_eq!(stream.gauge().in_flight(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_a_full_channel_holds_the_reader_back() {
+        let (mut sink, mut stream) = feature_channel(IngestBuffers::new(1, 1));
+        sink.start(4326, metadata()).unwrap();
+
+        // One batch fits in the channel; the second waits for the consumer
+        sink.send(feature(0)).await.unwrap();
+        let waiting = tokio::time::timeout(Duration::from_millis(20), sink.send(feature(1))).await;
+        assert!(waiting.is_err());
+
+        assert_eq!(stream.next_batch().await.unwrap().len(), 1);
+        sink.send(feature(2)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sending_fails_once_the_consumer_is_gone() {
+        let (mut sink, stream) = feature_channel(IngestBuffers::new(1, 1));
+        sink.start(4326, metadata()).unwrap();
+        drop(stream);
+
+        let err = sink.send(feature(0)).await.unwrap_err();
+        assert!(matches!(err, GeoragError::IngestStopped { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_features_need_a_header_first() {
+        let (mut sink, _stream) = feature_channel(IngestBuffers::default());
+
+        assert!(sink.send(feature(0)).await.is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! Built-in transforms applied to each feature as it is read
+//!
+//! A workspace lists transforms under `feature_transforms`; ingest applies
+//! them in order to every [`FormatFeature`] after reading and before the
+//! geometry is validated, so small per-organization fixes (stripping a prefix
+//! from parcel IDs, deriving a label from two fields) need no code of their
+//! own. Patterns and templates are checked when the settings are parsed, so a
+//! bad transform fails at config load rather than halfway through a file.
+//!
+//! A transform that cannot produce its output for a feature fails that
+//! feature only. [`FeatureTransforms`] counts the failures of each transform;
+//! ingest records the feature like an unreadable one, so a lenient read skips
+//! it and a strict one fails.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use super::FormatFeature;
+use crate::geo::{centroid, geometry_from_geojson};
+
+/// One built-in transform and its parameters
+///
+/// Written as a table with a `type` field, e.g. in `config.toml`:
+///
+/// ```toml
+/// [[feature_transforms]]
+/// type = "regex_replace"
+/// key = "parcel_id"
+/// pattern = "^PRC-"
+/// replacement = ""
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeatureTransform {
+    /// Move property `from` to `to`, replacing any value there
+    ///
+    /// Features without `from` are left as they are.
+    Rename { from: String, to: String },
+
+    /// Set property `key` from a template over other properties, e.g.
+    /// `"{zone}-{class}"`
+    ///
+    /// Fails on features missing a property the template uses.
+    Template { key: String, template: PropertyTemplate },
+
+    /// Replace every match of `pattern` in the string property `key`
+    ///
+    /// The replacement may refer to capture groups as `$1` or `${name}`.
+    /// Features without the property are left as they are; a value that is
+    /// not a string fails.
+    RegexReplace {
+        key: String,
+        pattern: TransformPattern,
+        replacement: String,
+    },
+
+    /// Remove property `key`
+    DropKey { key: String },
+
+    /// Set property `key` to the `[x, y]` centroid of the geometry, in the
+    /// dataset's CRS
+    ///
+    /// Fails on features without a geometry.
+    ComputeCentroid { key: String },
+}
+
+impl FeatureTransform {
+    /// Name of the transform, as written in its `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeatureTransform::Rename { .. } => "rename",
+            FeatureTransform::Template { .. } => "template",
+            FeatureTransform::RegexReplace { .. } => "regex_replace",
+            FeatureTransform::DropKey { .. } => "drop_key",
+            FeatureTransform::ComputeCentroid { .. } => "compute_centroid",
+        }
+    }
+
+    /// Apply the transform to one feature
+    ///
+    /// On failure the feature is left as it was and the reason is returned.
+    pub fn apply(&self, feature: &mut FormatFeature) -> Result<(), String> {
+        let properties = &mut feature.properties;
+        match self {
+            FeatureTransform::Rename { from, to } => {
+                if let Some(value) = properties.remove(from) {
+                    properties.insert(to.clone(), value);
+                }
+            }
+            FeatureTransform::Template { key, template } => {
+                let value = template.render(properties)?;
+                properties.insert(key.clone(), Value::String(value));
+            }
+            FeatureTransform::RegexReplace { key, pattern, replacement } => {
+                match properties.get_mut(key) {
+                    None | Some(Value::Null) => {}
+                    Some(Value::String(value)) => {
+                        let replaced = pattern.0.replace_all(value, replacement.as_str());
+                        *value = replaced.into_owned();
+                    }
+                    Some(other) => {
+                        return Err(format!(
+                            "property '{}' is {}, not a string",
+                            key,
+                            json_kind(other)
+                        ))
+                    }
+                }
+            }
+            FeatureTransform::DropKey { key } => {
+                properties.remove(key);
+            }
+            FeatureTransform::ComputeCentroid { key } => {
+                let geometry = feature
+                    .geometry
+                    .as_ref()
+                    .map(geometry_from_geojson)
+                    .transpose()
+                    .map_err(|(_, message)| message)?
+                    .flatten()
+                    .ok_or_else(|| "feature has no geometry".to_string())?;
+                let [x, y] =
+                    centroid(&geometry).ok_or_else(|| "geometry has no coordinates".to_string())?;
+                properties.insert(key.clone(), serde_json::json!([x, y]));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FeatureTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureTransform::Rename { from, to } => write!(f, "rename {} to {}", from, to),
+            FeatureTransform::Template { key, template } => {
+                write!(f, "template {} = \"{}\"", key, template.as_str())
+            }
+            FeatureTransform::RegexReplace { key, pattern, replacement } => {
+                write!(f, "regex_replace {} /{}/ -> \"{}\"", key, pattern.as_str(), replacement)
+            }
+            FeatureTransform::DropKey { key } => write!(f, "drop_key {}", key),
+            FeatureTransform::ComputeCentroid { key } => write!(f, "compute_centroid {}", key),
+        }
+    }
+}
+
+/// Regular expression of a `regex_replace` transform, compiled when parsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TransformPattern(Regex);
+
+impl TransformPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        Regex::new(pattern)
+            .map(Self)
+            .map_err(|e| format!("Invalid regex_replace pattern '{}': {}", pattern, e))
+    }
+
+    /// The pattern as configured
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for TransformPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl TryFrom<String> for TransformPattern {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Self::parse(&pattern)
+    }
+}
+
+impl From<TransformPattern> for String {
+    fn from(pattern: TransformPattern) -> Self {
+        pattern.as_str().to_string()
+    }
+}
+
+/// Template of a `template` transform, with `{property}` placeholders
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PropertyTemplate {
+    template: String,
+    segments: Vec<TemplateSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplateSegment {
+    Text(String),
+    Property(String),
+}
+
+impl PropertyTemplate {
+    /// Parse a template, rejecting empty placeholders and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start..].starts_with('}') {
+                return Err(format!("Unmatched '}}' in property template: {}", template));
+            }
+            let Some(end) = rest[start..].find('}') else {
+                return Err(format!("Unclosed '{{' in property template: {}", template));
+            };
+            let name = rest[start + 1..start + end].trim();
+            if name.is_empty() || name.contains('{') {
+                return Err(format!("Invalid placeholder in property template: {}", template));
+            }
+            if start > 0 {
+                segments.push(TemplateSegment::Text(rest[..start].to_string()));
+            }
+            segments.push(TemplateSegment::Property(name.to_string()));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(TemplateSegment::Text(rest.to_string()));
+        }
+
+        Ok(Self { template: template.to_string(), segments })
+    }
+
+    /// The template as configured
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Fill the template from a feature's properties
+    ///
+    /// Strings are inserted as they are, other values as JSON. A missing or
+    /// `null` property fails.
+    pub fn render(&self, properties: &HashMap<String, Value>) -> Result<String, String> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Text(text) => rendered.push_str(text),
+                TemplateSegment::Property(name) => match properties.get(name) {
+                    None | Some(Value::Null) => {
+                        return Err(format!("property '{}' is missing", name))
+                    }
+                    Some(Value::String(value)) => rendered.push_str(value),
+                    Some(value) => rendered.push_str(&value.to_string()),
+                },
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+impl TryFrom<String> for PropertyTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template)
+    }
+}
+
+impl From<PropertyTemplate> for String {
+    fn from(template: PropertyTemplate) -> Self {
+        template.template
+    }
+}
+
+/// A transform that failed on a feature
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformFailure {
+    /// Position of the transform in the list, counting from 0
+    pub position: usize,
+
+    /// Name of the transform, see [`FeatureTransform::name`]
+    pub transform: &'static str,
+
+    pub message: String,
+}
+
+impl fmt::Display for TransformFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transform {} ({}): {}", self.position, self.transform, self.message)
+    }
+}
+
+/// Number of features one transform failed on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransformErrors {
+    /// Position of the transform in the list, counting from 0
+    pub position: usize,
+
+    /// The transform, as displayed
+    pub transform: String,
+
+    pub errors: usize,
+}
+
+/// An ordered list of transforms, counting the failures of each
+#[derive(Debug, Clone, Default)]
+pub struct FeatureTransforms {
+    transforms: Vec<FeatureTransform>,
+    errors: Vec<usize>,
+}
+
+impl FeatureTransforms {
+    pub fn new(transforms: Vec<FeatureTransform>) -> Self {
+        let errors = vec![0; transforms.len()];
+        Self { transforms, errors }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Apply every transform to a feature in order
+    ///
+    /// Stops at the first failure, which is counted against its transform.
+    pub fn apply(&mut self, feature: &mut FormatFeature) -> Result<(), TransformFailure> {
+        for (position, transform) in self.transforms.iter().enumerate() {
+            if let Err(message) = transform.apply(feature) {
+                self.errors[position] += 1;
+                return Err(TransformFailure {
+                    position,
+                    transform: transform.name(),
+                    message,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Failures of every transform so far, including those without any
+    pub fn errors(&self) -> Vec<TransformErrors> {
+        self.transforms
+            .iter()
+            .zip(&self.errors)
+            .enumerate()
+            .map(|(position, (transform, errors))| TransformErrors {
+                position,
+                transform: transform.to_string(),
+                errors: *errors,
+            })
+            .collect()
+    }
+}
+
+/// Properties of a sample feature before and after the transforms, for dry runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransformPreview {
+    /// Position of the feature in the file, counting from 0
+    pub index: usize,
+
+    pub id: String,
+
+    pub before: BTreeMap<String, Value>,
+
+    /// Properties after the transforms, `None` when one failed
+    pub after: Option<BTreeMap<String, Value>>,
+
+    /// Why a transform failed on the feature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Describe a value's JSON type for error messages
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn feature(geometry: Option<Value>, properties: Value) -> FormatFeature {
+        let properties: HashMap<String, Value> = serde_json::from_value(properties).unwrap();
+        FormatFeature {
+            id: "f".to_string(),
+            geometry,
+            properties,
+        }
+    }
+
+    fn transforms(toml: &str) -> Vec<FeatureTransform> {
+        #[derive(Deserialize)]
+        struct Config {
+            feature_transforms: Vec<FeatureTransform>,
+        }
+        toml::from_str::<Config>(toml).unwrap().feature_transforms
+    }
+
+    #[test]
+    fn test_transforms_chain_in_order() {
+        let mut chain = FeatureTransforms::new(transforms(
+            r#"
+            [[feature_transforms]]
+            type = "regex_replace"
+            key = "parcel"
+            pattern = "^PRC-0*"
+            replacement = ""
+
+            [[feature_transforms]]
+            type = "template"
+            key = "label"
+            template = "{zone}/{parcel} ({area})"
+
+            [[feature_transforms]]
+            type = "rename"
+            from = "zone"
+            to = "district"
+
+            [[feature_transforms]]
+            type = "drop_key"
+            key = "area"
+
+            [[feature_transforms]]
+            type = "compute_centroid"
+            key = "centroid"
+            "#,
+        ));
+        let square = json!({
+            "type": "Polygon",
+            "coordinates": [[[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [0.0, 0.0]]]
+        });
+        let mut parcel =
+            feature(Some(square), json!({ "parcel": "PRC-0042", "zone": "R1", "area": 120.5 }));
+
+        chain.apply(&mut parcel).unwrap();
+        assert_eq!(
+            Value::Object(parcel.properties.into_iter().collect()),
+            json!({
+                "parcel": "42",
+                "district": "R1",
+                "label": "R1/42 (120.5)",
+                "centroid": [1.0, 1.0]
+            })
+        );
+        assert!(chain.errors().iter().all(|t| t.errors == 0));
+    }
+
+    #[test]
+    fn test_failures_are_counted_per_transform() {
+        let mut chain = FeatureTransforms::new(transforms(
+            r#"
+            [[feature_transforms]]
+            type = "regex_replace"
+            key = "parcel"
+            pattern = "-"
+            replacement = "_"
+
+            [[feature_transforms]]
+            type = "compute_centroid"
+            key = "centroid"
+            "#,
+        ));
+
+        let mut numeric = feature(None, json!({ "parcel": 42 }));
+        let failure = chain.apply(&mut numeric).unwrap_err();
+        assert_eq!(failure.position, 0);
+        assert_eq!(
+            failure.to_string(),
+            "transform 0 (regex_replace): property 'parcel' is a number, not a string"
+        );
+
+        let mut document = feature(None, json!({ "parcel": "a-b" }));
+        let failure = chain.apply(&mut document).unwrap_err();
+        assert_eq!(failure.position, 1);
+        assert_eq!(failure.message, "feature has no geometry");
+
+        // Features without the property are left alone
+        let mut point =
+            feature(Some(json!({ "type": "Point", "coordinates": [3.0, 4.0] })), json!({}));
+        chain.apply(&mut point).unwrap();
+        assert_eq!(point.properties["centroid"], json!([3.0, 4.0]));
+
+        let counts: Vec<usize> = chain.errors().iter().map(|t| t.errors).collect();
+        assert_eq!(counts, [1, 1]);
+    }
+
+    #[test]
+    fn test_template_requires_its_properties() {
+        let template = PropertyTemplate::parse("{zone}-{class}").unwrap();
+        let properties = HashMap::from([("zone".to_string(), json!("R1"))]);
+        assert_eq!(template.render(&properties).unwrap_err(), "property 'class' is missing");
+    }
+
+    #[test]
+    fn test_invalid_definitions_fail_to_parse() {
+        assert!(PropertyTemplate::parse("{zone").is_err());
+        assert!(PropertyTemplate::parse("zone}").is_err());
+        assert!(PropertyTemplate::parse("{}-x").is_err());
+        assert!(TransformPattern::parse("(unclosed").is_err());
+
+        let parse = |toml: &str| toml::from_str::<HashMap<String, Vec<FeatureTransform>>>(toml);
+        assert!(parse(
+            "t = [{ type = \"regex_replace\", key = \"a\", pattern = \"[\", replacement = \"\" }]"
+        )
+        .is_err());
+        assert!(parse("t = [{ type = \"uppercase\", key = \"a\" }]").is_err());
+        assert!(parse("t = [{ type = \"rename\", from = \"a\" }]").is_err());
+    }
+
+    #[test]
+    fn test_transforms_round_trip_through_toml() {
+        let chain = transforms(
+            r#"
+            [[feature_transforms]]
+            type = "regex_replace"
+            key = "id"
+            pattern = "^x(\\d+)$"
+            replacement = "$1"
+            "#,
+        );
+        let table = HashMap::from([("feature_transforms", chain.clone())]);
+        let written = toml::to_string(&table).unwrap();
+        assert_eq!(transforms(&written), chain);
+    }
+}
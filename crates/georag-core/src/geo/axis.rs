@@ -0,0 +1,260 @@
+//! Axis-order detection for geographic coordinates
+//!
+//! Files claiming EPSG:4326 sometimes list latitude first although GeoJSON
+//! requires longitude first. A coordinate whose absolute value exceeds 90 can
+//! only be a longitude, which settles the order of that feature. Features
+//! without such a coordinate are compared against the workspace extent: one
+//! that fits the extent only one way round is decided by it, anything else
+//! stays undecided.
+
+use serde_json::Value;
+
+use crate::models::AxisOrder;
+
+/// Bounding box as `[min_x, min_y, max_x, max_y]` in lon,lat order
+pub type Extent = [f64; 4];
+
+/// Every position of a GeoJSON geometry as `[x, y]`
+pub fn positions(geometry: &Value) -> Vec<[f64; 2]> {
+    let mut positions = Vec::new();
+    visit(geometry, &mut |position| {
+        if let (Some(x), Some(y)) = (position[0].as_f64(), position[1].as_f64()) {
+            positions.push([x, y]);
+        }
+    });
+    positions
+}
+
+/// Swap the first two ordinates of every position, in place
+///
+/// Any further ordinates (elevation, measure) are left where they are.
+pub fn swap_axes(geometry: &mut Value) {
+    visit_mut(geometry, &mut |position| position.swap(0, 1));
+}
+
+/// Bounding box of the positions of several geometries
+pub fn extent_of<'a>(geometries: impl IntoIterator<Item = &'a Value>) -> Option<Extent> {
+    let mut extent: Option<Extent> = None;
+    for [x, y] in geometries.into_iter().flat_map(positions) {
+        let e = extent.get_or_insert([x, y, x, y]);
+        *e = [e[0].min(x), e[1].min(y), e[2].max(x), e[3].max(y)];
+    }
+    extent
+}
+
+/// Axis order of one geometry, if it can be told with confidence
+///
+/// Returns `None` for geometries that read plausibly either way, and for
+/// coordinates outside ±180 that are not geographic at all.
+pub fn assess_geometry(geometry: &Value, extent: Option<&Extent>) -> Option<AxisOrder> {
+    let positions = positions(geometry);
+    if positions.is_empty() || positions.iter().flatten().any(|v| v.abs() > 180.0) {
+        return None;
+    }
+
+    let x_is_lon = positions.iter().any(|[x, _]| x.abs() > 90.0);
+    let y_is_lon = positions.iter().any(|[_, y]| y.abs() > 90.0);
+    match (x_is_lon, y_is_lon) {
+        (true, false) => return Some(AxisOrder::LonLat),
+        (false, true) => return Some(AxisOrder::LatLon),
+        (true, true) => return None,
+        (false, false) => {}
+    }
+
+    let extent = extent?;
+    let fits = positions.iter().all(|&[x, y]| contains(extent, x, y));
+    let fits_swapped = positions.iter().all(|&[x, y]| contains(extent, y, x));
+    match (fits, fits_swapped) {
+        (true, false) => Some(AxisOrder::LonLat),
+        (false, true) => Some(AxisOrder::LatLon),
+        _ => None,
+    }
+}
+
+/// Axis order of a whole file, with the reason for it
+///
+/// Each geometry that can be told votes; the majority wins and ties or an
+/// undecided file fall back to lon,lat.
+pub fn decide_axis_order<'a>(
+    geometries: impl IntoIterator<Item = &'a Value>,
+    extent: Option<&Extent>,
+) -> (AxisOrder, String) {
+    let mut votes = AxisVotes::new(extent.copied());
+    for geometry in geometries {
+        votes.add(geometry);
+    }
+    votes.decide()
+}
+
+/// Votes of a file's geometries on its axis order, counted one at a time
+///
+/// For readers that see each geometry once, while streaming a file; see
+/// [`decide_axis_order`] for how the votes are weighed.
+#[derive(Debug, Clone, Default)]
+pub struct AxisVotes {
+    extent: Option<Extent>,
+    lonlat: usize,
+    latlon: usize,
+}
+
+impl AxisVotes {
+    /// Start counting, comparing undecided geometries against `extent`
+    pub fn new(extent: Option<Extent>) -> Self {
+        Self { extent, lonlat: 0, latlon: 0 }
+    }
+
+    /// Count the vote of one geometry
+    pub fn add(&mut self, geometry: &Value) {
+        match assess_geometry(geometry, self.extent.as_ref()) {
+            Some(AxisOrder::LonLat) => self.lonlat += 1,
+            Some(AxisOrder::LatLon) => self.latlon += 1,
+            _ => {}
+        }
+    }
+
+    /// Axis order the votes so far decide, with the reason for it
+    pub fn decide(&self) -> (AxisOrder, String) {
+        match (self.lonlat, self.latlon) {
+            (0, 0) => {
+                (AxisOrder::LonLat, "no feature decides the order; lon,lat assumed".to_string())
+            }
+            (n, 0) => (AxisOrder::LonLat, format!("{} feature(s) only fit lon,lat", n)),
+            (0, n) => (AxisOrder::LatLon, format!("{} feature(s) only fit lat,lon", n)),
+            (lonlat, latlon) => {
+                let order = if latlon > lonlat {
+                    AxisOrder::LatLon
+                } else {
+                    AxisOrder::LonLat
+                };
+                (
+                    order,
+                    format!(
+                        "mixed: {} feature(s) fit lon,lat and {} lat,lon; majority used",
+                        lonlat, latlon
+                    ),
+                )
+            }
+        }
+    }
+}
+
+fn contains(extent: &Extent, x: f64, y: f64) -> bool {
+    x >= extent[0] && x <= extent[2] && y >= extent[1] && y <= extent[3]
+}
+
+/// Call `f` with every position of a geometry (including collections)
+fn visit(geometry: &Value, f: &mut impl FnMut(&[Value])) {
+    if let Some(members) = geometry.get("geometries").and_then(Value::as_array) {
+        for member in members {
+            visit(member, f);
+        }
+    } else if let Some(coordinates) = geometry.get("coordinates") {
+        visit_coordinates(coordinates, f);
+    }
+}
+
+fn visit_coordinates(value: &Value, f: &mut impl FnMut(&[Value])) {
+    if let Some(items) = value.as_array() {
+        if items.len() >= 2 && items[0].is_number() {
+            f(items);
+        } else {
+            for item in items {
+                visit_coordinates(item, f);
+            }
+        }
+    }
+}
+
+fn visit_mut(geometry: &mut Value, f: &mut impl FnMut(&mut Vec<Value>)) {
+    if let Some(members) = geometry.get_mut("geometries").and_then(Value::as_array_mut) {
+        for member in members {
+            visit_mut(member, f);
+        }
+    } else if let Some(coordinates) = geometry.get_mut("coordinates") {
+        visit_coordinates_mut(coordinates, f);
+    }
+}
+
+fn visit_coordinates_mut(value: &mut Value, f: &mut impl FnMut(&mut Vec<Value>)) {
+    if let Some(items) = value.as_array_mut() {
+        if items.len() >= 2 && items[0].is_number() {
+            f(items);
+        } else {
+            for item in items {
+                visit_coordinates_mut(item, f);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Jakarta and surroundings, lon,lat
+    const JAKARTA: Extent = [106.0, -7.0, 107.5, -5.5];
+
+    #[test]
+    fn test_range_decides_order() {
+        let lonlat = json!({"type": "Point", "coordinates": [106.8, -6.2]});
+        let latlon = json!({"type": "Point", "coordinates": [-6.2, 106.8]});
+
+        assert_eq!(assess_geometry(&lonlat, None), Some(AxisOrder::LonLat));
+        assert_eq!(assess_geometry(&latlon, None), Some(AxisOrder::LatLon));
+    }
+
+    #[test]
+    fn test_extent_decides_ambiguous_coordinates() {
+        // Both ordinates are valid latitudes; only one order lies in the extent
+        let extent = [10.0, 50.0, 12.0, 52.0];
+        let lonlat = json!({"type": "Point", "coordinates": [11.0, 51.0]});
+        let latlon = json!({"type": "Point", "coordinates": [51.0, 11.0]});
+        let elsewhere = json!({"type": "Point", "coordinates": [30.0, 30.0]});
+
+        assert_eq!(assess_geometry(&lonlat, Some(&extent)), Some(AxisOrder::LonLat));
+        assert_eq!(assess_geometry(&latlon, Some(&extent)), Some(AxisOrder::LatLon));
+        assert_eq!(assess_geometry(&elsewhere, Some(&extent)), None);
+        assert_eq!(assess_geometry(&lonlat, None), None);
+    }
+
+    #[test]
+    fn test_projected_coordinates_are_undecided() {
+        let projected = json!({"type": "Point", "coordinates": [701234.5, 9314567.0]});
+        assert_eq!(assess_geometry(&projected, Some(&JAKARTA)), None);
+    }
+
+    #[test]
+    fn test_decide_uses_majority() {
+        let geometries = [
+            json!({"type": "Point", "coordinates": [-6.2, 106.8]}),
+            json!({"type": "Point", "coordinates": [-6.1, 106.9]}),
+            json!({"type": "Point", "coordinates": [106.7, -6.3]}),
+            json!({"type": "Point", "coordinates": [1.0, 2.0]}),
+        ];
+
+        let (order, reason) = decide_axis_order(&geometries, None);
+        assert_eq!(order, AxisOrder::LatLon);
+        assert!(reason.starts_with("mixed"));
+
+        let (order, _) = decide_axis_order(&geometries[3..], None);
+        assert_eq!(order, AxisOrder::LonLat);
+    }
+
+    #[test]
+    fn test_swap_axes_keeps_elevation() {
+        let mut geometry = json!({
+            "type": "GeometryCollection",
+            "geometries": [
+                {"type": "Point", "coordinates": [-6.2, 106.8, 12.0]},
+                {"type": "LineString", "coordinates": [[-6.2, 106.8], [-6.3, 106.9]]}
+            ]
+        });
+
+        swap_axes(&mut geometry);
+
+        assert_eq!(geometry["geometries"][0]["coordinates"], json!([106.8, -6.2, 12.0]));
+        assert_eq!(geometry["geometries"][1]["coordinates"], json!([[106.8, -6.2], [106.9, -6.3]]));
+        assert_eq!(extent_of([&geometry]), Some([106.8, -6.3, 106.9, -6.2]));
+    }
+}
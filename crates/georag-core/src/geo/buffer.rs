@@ -0,0 +1,207 @@
+//! Geodesic-aware buffering
+//!
+//! The `geo` buffer is planar, so buffering longitude/latitude degrees by a
+//! distance in meters needs a projection first. Geometries are projected onto
+//! a local equirectangular plane centered on their bounding box, buffered there
+//! in meters and projected back. The east-west scale is exact only at the
+//! center latitude; for buffers under 50 km around geometries of similar size
+//! the error stays within a few percent outside the polar regions.
+
+use geo::algorithm::bounding_rect::BoundingRect;
+use geo::{Buffer, Coord, MapCoords};
+
+use crate::geo::models::{from_geo_geometry, to_geo_geometry, Distance, Geometry, SpatialFilter};
+
+/// Mean Earth radius in meters, the same radius `geo::Haversine` uses
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Buffer a longitude/latitude geometry by a distance
+///
+/// Returns a Polygon, or a MultiPolygon when the buffered parts stay
+/// disjoint. Negative distances shrink polygons; lines and points buffered by
+/// a non-positive distance become an empty MultiPolygon. Empty geometries are
+/// returned unchanged.
+pub fn buffer_geometry(geometry: &Geometry, distance: &Distance) -> Geometry {
+    let geo_geometry = to_geo_geometry(geometry);
+    let Some(bbox) = geo_geometry.bounding_rect() else {
+        return geometry.clone();
+    };
+
+    let plane = LocalPlane::new(bbox.center());
+    let projected = geo_geometry.map_coords(|c| plane.project(c));
+    let buffered = projected.buffer(distance.to_meters()).map_coords(|c| plane.unproject(c));
+
+    if buffered.0.len() == 1 {
+        from_geo_geometry(&geo::Geometry::Polygon(buffered.0[0].clone()))
+    } else {
+        from_geo_geometry(&geo::Geometry::MultiPolygon(buffered))
+    }
+}
+
+/// Replace a filter's geometry with its buffer
+///
+/// The returned filter has no buffer left to apply. Filters without a buffer
+/// or without a geometry are returned unchanged.
+pub fn buffer_filter(filter: &SpatialFilter) -> SpatialFilter {
+    match (&filter.geometry, &filter.buffer) {
+        (Some(geometry), Some(distance)) => SpatialFilter {
+            geometry: Some(buffer_geometry(geometry, distance)),
+            buffer: None,
+            ..filter.clone()
+        },
+        _ => filter.clone(),
+    }
+}
+
+/// Equirectangular projection in meters around an origin
+struct LocalPlane {
+    origin: Coord,
+    meters_per_degree_x: f64,
+    meters_per_degree_y: f64,
+}
+
+impl LocalPlane {
+    fn new(origin: Coord) -> Self {
+        let meters_per_degree_y = EARTH_RADIUS_METERS.to_radians();
+        // Keep a usable scale for geometries touching the poles
+        let meters_per_degree_x = meters_per_degree_y * origin.y.to_radians().cos().max(1e-6);
+
+        Self {
+            origin,
+            meters_per_degree_x,
+            meters_per_degree_y,
+        }
+    }
+
+    fn project(&self, c: Coord) -> Coord {
+        Coord {
+            x: (c.x - self.origin.x) * self.meters_per_degree_x,
+            y: (c.y - self.origin.y) * self.meters_per_degree_y,
+        }
+    }
+
+    fn unproject(&self, c: Coord) -> Coord {
+        Coord {
+            x: self.origin.x + c.x / self.meters_per_degree_x,
+            y: self.origin.y + c.y / self.meters_per_degree_y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::models::SpatialPredicate;
+    use crate::geo::spatial::evaluate_spatial_filter;
+    use geo::{Distance as _, Haversine, Point};
+
+    /// One meter in degrees of latitude
+    const DEGREES_PER_METER: f64 = 1.0 / 111_195.08;
+
+    fn extent(geometry: &Geometry) -> ([f64; 2], [f64; 2]) {
+        let rect = to_geo_geometry(geometry).bounding_rect().unwrap();
+        ([rect.min().x, rect.min().y], [rect.max().x, rect.max().y])
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(
+            error <= tolerance,
+            "{} differs from {} by {:.2}%",
+            actual,
+            expected,
+            error * 100.0
+        );
+    }
+
+    fn exterior(geometry: &Geometry) -> &Vec<[f64; 2]> {
+        match geometry {
+            Geometry::Polygon { coordinates } => &coordinates[0],
+            other => panic!("expected Polygon, got {:?}", other.geometry_type()),
+        }
+    }
+
+    #[test]
+    fn test_point_buffer_extent_at_equator() {
+        let buffered = buffer_geometry(&Geometry::point(0.0, 0.0), &Distance::kilometers(1.0));
+        let (min, max) = extent(&buffered);
+
+        let expected = 1000.0 * DEGREES_PER_METER;
+        for half_width in [max[0], -min[0], max[1], -min[1]] {
+            assert_close(half_width, expected, 0.02);
+        }
+    }
+
+    #[test]
+    fn test_point_buffer_widens_in_longitude_at_high_latitude() {
+        let buffered = buffer_geometry(&Geometry::point(10.0, 60.0), &Distance::kilometers(5.0));
+        let (min, max) = extent(&buffered);
+
+        let lat_half = 5000.0 * DEGREES_PER_METER;
+        // A degree of longitude at 60° is half as long as at the equator
+        assert_close((max[0] - min[0]) / 2.0, lat_half * 2.0, 0.02);
+        assert_close((max[1] - min[1]) / 2.0, lat_half, 0.02);
+    }
+
+    #[test]
+    fn test_buffer_ring_is_at_buffer_distance() {
+        let center = Geometry::point(106.8, -6.2);
+        let buffered = buffer_geometry(&center, &Distance::kilometers(50.0));
+
+        for vertex in exterior(&buffered) {
+            let meters =
+                Haversine.distance(Point::new(106.8, -6.2), Point::new(vertex[0], vertex[1]));
+            assert_close(meters, 50_000.0, 0.03);
+        }
+    }
+
+    #[test]
+    fn test_line_buffer_extent() {
+        // A 10 km road running east along the equator
+        let length = 10_000.0 * DEGREES_PER_METER;
+        let road = Geometry::line_string(vec![[0.0, 0.0], [length, 0.0]]);
+        let buffered = buffer_geometry(&road, &Distance::meters(200.0));
+        let (min, max) = extent(&buffered);
+
+        let margin = 200.0 * DEGREES_PER_METER;
+        assert_close(max[1], margin, 0.02);
+        assert_close(-min[1], margin, 0.02);
+        assert_close(max[0] - min[0], length + 2.0 * margin, 0.02);
+    }
+
+    #[test]
+    fn test_disjoint_parts_stay_separate() {
+        let points = Geometry::MultiPoint {
+            coordinates: vec![[0.0, 0.0], [1.0, 0.0]],
+        };
+        let buffered = buffer_geometry(&points, &Distance::meters(100.0));
+
+        match buffered {
+            Geometry::MultiPolygon { coordinates } => assert_eq!(coordinates.len(), 2),
+            other => panic!("expected MultiPolygon, got {:?}", other.geometry_type()),
+        }
+    }
+
+    #[test]
+    fn test_buffered_filter_matches_points_near_a_road() {
+        let road = Geometry::line_string(vec![[0.0, 0.0], [0.1, 0.0]]);
+        let filter = SpatialFilter::new(SpatialPredicate::Intersects)
+            .geometry(road)
+            .buffer(Distance::meters(200.0));
+
+        let buffered = buffer_filter(&filter);
+        assert!(buffered.buffer.is_none());
+
+        let near = Geometry::point(0.05, 150.0 * DEGREES_PER_METER);
+        let far = Geometry::point(0.05, 250.0 * DEGREES_PER_METER);
+        assert!(evaluate_spatial_filter(&near, &buffered));
+        assert!(!evaluate_spatial_filter(&far, &buffered));
+    }
+
+    #[test]
+    fn test_filter_without_buffer_is_unchanged() {
+        let filter =
+            SpatialFilter::new(SpatialPredicate::Within).geometry(Geometry::point(1.0, 2.0));
+        assert_eq!(buffer_filter(&filter).geometry, filter.geometry);
+    }
+}
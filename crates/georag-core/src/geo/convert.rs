@@ -0,0 +1,631 @@
+//! Conversion of GeoJSON geometries into [`Geometry`]
+//!
+//! Readers hand features over with their geometry as GeoJSON; this module is
+//! the one place that turns those values into the canonical [`Geometry`].
+//! Every GeoJSON type is handled:
+//!
+//! - `null`, and geometries with empty coordinates, are missing geometries
+//! - positions keep their first two ordinates; a third is read separately as
+//!   Z by [`z_from_geojson`], and a fourth (M) is dropped
+//! - a `GeometryCollection` becomes its only member, or the multi-geometry of
+//!   its members when they are all points, all lines or all polygons; empty
+//!   members are skipped and mixed collections cannot be converted
+//! - unknown types and non-numeric coordinates cannot be converted
+//!
+//! [`GeometryPolicy`] decides what ingest does with a feature whose geometry
+//! is missing or cannot be converted, so the CLI and the API treat such
+//! features the same way. [`ZCoordinates`] decides whether Z is kept.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::formats::FeatureErrorKind;
+use crate::models::{Geometry, GeometryType, ValidityMode};
+
+/// Why a GeoJSON geometry could not be converted
+pub type ConversionError = (FeatureErrorKind, String);
+
+/// Geometry type named by a GeoJSON `type`, `None` for unknown names
+pub fn geometry_type_from_name(name: &str) -> Option<GeometryType> {
+    match name {
+        "Point" => Some(GeometryType::Point),
+        "LineString" => Some(GeometryType::LineString),
+        "Polygon" => Some(GeometryType::Polygon),
+        "MultiPoint" => Some(GeometryType::MultiPoint),
+        "MultiLineString" => Some(GeometryType::MultiLineString),
+        "MultiPolygon" => Some(GeometryType::MultiPolygon),
+        "GeometryCollection" => Some(GeometryType::GeometryCollection),
+        _ => None,
+    }
+}
+
+/// Geometry type of a dataset, from the first geometry that is not missing
+///
+/// A geometry that converts reports the type it is stored as, so a collection
+/// of polygons reports `MultiPolygon`. One that does not convert reports the
+/// type it names, with unknown names mapping to `GeometryCollection`. `None`
+/// when every geometry is missing; datasets without any geometry (e.g.
+/// documents) are described as `Point`.
+pub fn detect_geometry_type<'a>(
+    geometries: impl IntoIterator<Item = &'a Value>,
+) -> Option<GeometryType> {
+    geometries.into_iter().find_map(|value| match geometry_from_geojson(value) {
+        Ok(geometry) => geometry.map(|g| g.geometry_type()),
+        Err(_) => Some(
+            value
+                .get("type")
+                .and_then(Value::as_str)
+                .and_then(geometry_type_from_name)
+                .unwrap_or(GeometryType::GeometryCollection),
+        ),
+    })
+}
+
+/// Convert a GeoJSON geometry, `Ok(None)` when it is missing or empty
+pub fn geometry_from_geojson(value: &Value) -> Result<Option<Geometry>, ConversionError> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    let geometry_type = value
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("geometry without a type".to_string()))?;
+
+    if geometry_type == "GeometryCollection" {
+        let members = value
+            .get("geometries")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("GeometryCollection without geometries".to_string()))?;
+        return collect_members(members);
+    }
+
+    let coordinates = match value.get("coordinates") {
+        Some(Value::Null) | None if geometry_type_from_name(geometry_type).is_some() => {
+            return Err(invalid(format!("{} without coordinates", geometry_type)))
+        }
+        Some(coordinates) => coordinates,
+        None => return Err(unsupported(geometry_type)),
+    };
+
+    let geometry = match geometry_type {
+        "Point" => {
+            if is_empty(coordinates) {
+                return Ok(None);
+            }
+            Geometry::Point { coordinates: position(coordinates)? }
+        }
+        "LineString" => Geometry::LineString { coordinates: positions(coordinates)? },
+        "MultiPoint" => Geometry::MultiPoint { coordinates: positions(coordinates)? },
+        "Polygon" => Geometry::Polygon { coordinates: lines(coordinates)? },
+        "MultiLineString" => Geometry::MultiLineString { coordinates: lines(coordinates)? },
+        "MultiPolygon" => Geometry::MultiPolygon { coordinates: nested(coordinates, lines)? },
+        other => return Err(unsupported(other)),
+    };
+
+    Ok((geometry.vertex_count() > 0).then_some(geometry))
+}
+
+/// What ingest does with a feature whose geometry is missing or cannot be converted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeometryPolicy {
+    /// Keep features with a missing geometry as geometry-less features
+    Keep,
+
+    /// Drop features with a missing geometry
+    Drop,
+}
+
+impl GeometryPolicy {
+    /// Policy for a validity mode: lenient keeps, strict drops
+    pub fn for_validity(validity: ValidityMode) -> Self {
+        match validity {
+            ValidityMode::Strict => GeometryPolicy::Drop,
+            ValidityMode::Lenient => GeometryPolicy::Keep,
+        }
+    }
+
+    /// Convert a feature's geometry and decide whether the feature is kept
+    ///
+    /// Returns `Ok(Some(geometry))` to keep the feature, with `None` for a
+    /// geometry-less one, and `Ok(None)` to drop it. A geometry that cannot be
+    /// converted is an error under either policy; ingest records it like a
+    /// feature the reader could not read.
+    pub fn apply(
+        self,
+        geometry: Option<&Value>,
+    ) -> Result<Option<Option<Geometry>>, ConversionError> {
+        match geometry.map(geometry_from_geojson).transpose()?.flatten() {
+            Some(geometry) => Ok(Some(Some(geometry))),
+            None if self == GeometryPolicy::Keep => Ok(Some(None)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// What ingest does with the Z ordinates of a dataset's positions
+///
+/// Z is kept beside the two-dimensional [`Geometry`], so spatial predicates
+/// ignore it either way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZCoordinates {
+    /// Keep Z for geometries whose positions all have one
+    #[default]
+    Keep,
+
+    /// Store every geometry in two dimensions
+    Drop,
+}
+
+impl ZCoordinates {
+    /// Z ordinates to store for a GeoJSON geometry, `None` when there are none to keep
+    pub fn apply(self, value: &Value) -> Option<Vec<f64>> {
+        match self {
+            ZCoordinates::Keep => z_from_geojson(value),
+            ZCoordinates::Drop => None,
+        }
+    }
+}
+
+impl fmt::Display for ZCoordinates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZCoordinates::Keep => write!(f, "keep"),
+            ZCoordinates::Drop => write!(f, "drop"),
+        }
+    }
+}
+
+impl FromStr for ZCoordinates {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "keep" => Ok(ZCoordinates::Keep),
+            "drop" => Ok(ZCoordinates::Drop),
+            other => {
+                Err(format!("unknown Z coordinate handling '{}': expected keep or drop", other))
+            }
+        }
+    }
+}
+
+/// Z ordinates of a GeoJSON geometry, one per vertex of its converted [`Geometry`]
+///
+/// Positions are visited in the order the converted geometry keeps them, so
+/// the values line up with its vertices. `None` when any position lacks a
+/// third ordinate.
+pub fn z_from_geojson(value: &Value) -> Option<Vec<f64>> {
+    let mut z = Vec::new();
+    let mut complete = true;
+    visit_positions(value, &mut |ordinates| match ordinates.get(2).and_then(Value::as_f64) {
+        Some(value) => z.push(value),
+        None => complete = false,
+    });
+    (complete && !z.is_empty()).then_some(z)
+}
+
+/// Whether a position of a GeoJSON geometry has a fourth (M) ordinate
+pub fn has_measures(value: &Value) -> bool {
+    let mut measured = false;
+    visit_positions(value, &mut |ordinates| measured |= ordinates.len() > 3);
+    measured
+}
+
+/// GeoJSON of a geometry with a Z ordinate appended to each position
+///
+/// `z` holds one value per vertex, as returned by [`z_from_geojson`].
+pub fn geometry_to_geojson_z(geometry: &Geometry, z: &[f64]) -> Value {
+    let mut value = geometry.to_geojson();
+    if let Some(coordinates) = value.get_mut("coordinates") {
+        append_z(coordinates, &mut z.iter());
+    }
+    value
+}
+
+/// Visit the positions of a GeoJSON geometry depth first
+fn visit_positions(value: &Value, visit: &mut impl FnMut(&[Value])) {
+    if let Some(members) = value.get("geometries").and_then(Value::as_array) {
+        members.iter().for_each(|member| visit_positions(member, visit));
+    } else if let Some(coordinates) = value.get("coordinates") {
+        visit_coordinates(coordinates, visit);
+    }
+}
+
+fn visit_coordinates(value: &Value, visit: &mut impl FnMut(&[Value])) {
+    let Some(items) = value.as_array() else {
+        return;
+    };
+    if items.first().is_some_and(Value::is_number) {
+        visit(items);
+    } else {
+        items.iter().for_each(|item| visit_coordinates(item, visit));
+    }
+}
+
+fn append_z<'a>(value: &mut Value, z: &mut impl Iterator<Item = &'a f64>) {
+    let Some(items) = value.as_array_mut() else {
+        return;
+    };
+    if items.first().is_some_and(Value::is_number) {
+        items.extend(z.next().map(|z| Value::from(*z)));
+    } else {
+        items.iter_mut().for_each(|item| append_z(item, z));
+    }
+}
+
+fn invalid(message: String) -> ConversionError {
+    (FeatureErrorKind::InvalidGeometry, message)
+}
+
+fn unsupported(geometry_type: &str) -> ConversionError {
+    (
+        FeatureErrorKind::UnsupportedGeometry,
+        format!("unknown geometry type '{}'", geometry_type),
+    )
+}
+
+fn is_empty(value: &Value) -> bool {
+    value.as_array().is_some_and(Vec::is_empty)
+}
+
+/// One position, keeping its first two ordinates
+fn position(value: &Value) -> Result<[f64; 2], ConversionError> {
+    let ordinates = value
+        .as_array()
+        .ok_or_else(|| invalid(format!("position {} is not an array", value)))?;
+    if ordinates.len() < 2 {
+        return Err(invalid(format!("position has {} coordinates", ordinates.len())));
+    }
+    if ordinates.iter().any(|c| !c.as_f64().is_some_and(f64::is_finite)) {
+        return Err(invalid(format!("position {} has a non-numeric coordinate", value)));
+    }
+    Ok([
+        ordinates[0].as_f64().unwrap_or_default(),
+        ordinates[1].as_f64().unwrap_or_default(),
+    ])
+}
+
+fn positions(value: &Value) -> Result<Vec<[f64; 2]>, ConversionError> {
+    nested(value, position)
+}
+
+fn lines(value: &Value) -> Result<Vec<Vec<[f64; 2]>>, ConversionError> {
+    nested(value, positions)
+}
+
+/// Convert an array item by item, skipping items that come out empty
+fn nested<T: HasItems>(
+    value: &Value,
+    item: fn(&Value) -> Result<T, ConversionError>,
+) -> Result<Vec<T>, ConversionError> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| invalid("coordinates are not an array".to_string()))?;
+    let mut converted = Vec::with_capacity(items.len());
+    for value in items {
+        let value = item(value)?;
+        if value.has_items() {
+            converted.push(value);
+        }
+    }
+    Ok(converted)
+}
+
+trait HasItems {
+    fn has_items(&self) -> bool;
+}
+
+impl HasItems for [f64; 2] {
+    fn has_items(&self) -> bool {
+        true
+    }
+}
+
+impl<T> HasItems for Vec<T> {
+    fn has_items(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+/// Merge the members of a GeometryCollection into one geometry
+fn collect_members(members: &[Value]) -> Result<Option<Geometry>, ConversionError> {
+    let mut geometries = Vec::with_capacity(members.len());
+    for member in members {
+        if let Some(geometry) = geometry_from_geojson(member)? {
+            geometries.push(geometry);
+        }
+    }
+    if geometries.len() <= 1 {
+        return Ok(geometries.pop());
+    }
+
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    let mut polygons = Vec::new();
+    for geometry in geometries {
+        match geometry {
+            Geometry::Point { coordinates } => points.push(coordinates),
+            Geometry::MultiPoint { coordinates } => points.extend(coordinates),
+            Geometry::LineString { coordinates } => lines.push(coordinates),
+            Geometry::MultiLineString { coordinates } => lines.extend(coordinates),
+            Geometry::Polygon { coordinates } => polygons.push(coordinates),
+            Geometry::MultiPolygon { coordinates } => polygons.extend(coordinates),
+        }
+    }
+
+    match (points.is_empty(), lines.is_empty(), polygons.is_empty()) {
+        (false, true, true) => Ok(Some(Geometry::MultiPoint { coordinates: points })),
+        (true, false, true) => Ok(Some(Geometry::MultiLineString { coordinates: lines })),
+        (true, true, false) => Ok(Some(Geometry::MultiPolygon { coordinates: polygons })),
+        _ => Err((
+            FeatureErrorKind::UnsupportedGeometry,
+            "GeometryCollection mixes points, lines and polygons".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn square(x: f64) -> Vec<Vec<[f64; 2]>> {
+        vec![vec![[x, 0.0], [x + 1.0, 0.0], [x + 1.0, 1.0], [x, 0.0]]]
+    }
+
+    #[test]
+    fn test_geometry_from_geojson_corpus() {
+        let converted = |geometry: Geometry| Ok(Some(geometry));
+        let kind = |result: Result<Option<Geometry>, ConversionError>| result.map_err(|e| e.0);
+
+        let cases: Vec<(&str, Value, Result<Option<Geometry>, FeatureErrorKind>)> = vec![
+            ("null", Value::Null, Ok(None)),
+            (
+                "2D point",
+                json!({"type": "Point", "coordinates": [106.8, -6.2]}),
+                converted(Geometry::point(106.8, -6.2)),
+            ),
+            (
+                "3D point",
+                json!({"type": "Point", "coordinates": [106.8, -6.2, 12.5]}),
+                converted(Geometry::point(106.8, -6.2)),
+            ),
+            (
+                "point with elevation and measure",
+                json!({"type": "Point", "coordinates": [1.0, 2.0, 3.0, 4.0]}),
+                converted(Geometry::point(1.0, 2.0)),
+            ),
+            ("empty point", json!({"type": "Point", "coordinates": []}), Ok(None)),
+            (
+                "3D line",
+                json!({"type": "LineString", "coordinates": [[0.0, 0.0, 5.0], [1.0, 1.0, 6.0]]}),
+                converted(Geometry::line_string(vec![[0.0, 0.0], [1.0, 1.0]])),
+            ),
+            ("empty line", json!({"type": "LineString", "coordinates": []}), Ok(None)),
+            (
+                "3D polygon",
+                json!({"type": "Polygon", "coordinates": [[[0.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 1.0, 1.0], [0.0, 0.0, 1.0]]]}),
+                converted(Geometry::polygon(square(0.0))),
+            ),
+            ("empty polygon", json!({"type": "Polygon", "coordinates": []}), Ok(None)),
+            (
+                "multipolygon with an empty part",
+                json!({"type": "MultiPolygon", "coordinates": [[], [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]]}),
+                converted(Geometry::MultiPolygon { coordinates: vec![square(0.0)] }),
+            ),
+            ("empty multipoint", json!({"type": "MultiPoint", "coordinates": []}), Ok(None)),
+            (
+                "collection of one polygon",
+                json!({"type": "GeometryCollection", "geometries": [
+                    {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]}
+                ]}),
+                converted(Geometry::polygon(square(0.0))),
+            ),
+            (
+                "collection of polygons",
+                json!({"type": "GeometryCollection", "geometries": [
+                    {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]},
+                    {"type": "MultiPolygon", "coordinates": [[[[2.0, 0.0], [3.0, 0.0], [3.0, 1.0], [2.0, 0.0]]]]}
+                ]}),
+                converted(Geometry::MultiPolygon {
+                    coordinates: vec![square(0.0), square(2.0)],
+                }),
+            ),
+            (
+                "collection of points with an empty member",
+                json!({"type": "GeometryCollection", "geometries": [
+                    {"type": "Point", "coordinates": [0.0, 0.0]},
+                    {"type": "Point", "coordinates": []},
+                    {"type": "MultiPoint", "coordinates": [[1.0, 1.0, 9.0]]}
+                ]}),
+                converted(Geometry::MultiPoint {
+                    coordinates: vec![[0.0, 0.0], [1.0, 1.0]],
+                }),
+            ),
+            (
+                "nested collection of lines",
+                json!({"type": "GeometryCollection", "geometries": [
+                    {"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]},
+                    {"type": "GeometryCollection", "geometries": [
+                        {"type": "LineString", "coordinates": [[2.0, 2.0], [3.0, 3.0]]}
+                    ]}
+                ]}),
+                converted(Geometry::MultiLineString {
+                    coordinates: vec![vec![[0.0, 0.0], [1.0, 1.0]], vec![[2.0, 2.0], [3.0, 3.0]]],
+                }),
+            ),
+            (
+                "empty collection",
+                json!({"type": "GeometryCollection", "geometries": []}),
+                Ok(None),
+            ),
+            (
+                "mixed collection",
+                json!({"type": "GeometryCollection", "geometries": [
+                    {"type": "Point", "coordinates": [0.0, 0.0]},
+                    {"type": "LineString", "coordinates": [[0.0, 0.0], [1.0, 1.0]]}
+                ]}),
+                Err(FeatureErrorKind::UnsupportedGeometry),
+            ),
+            (
+                "collection without geometries",
+                json!({"type": "GeometryCollection"}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "unknown type",
+                json!({"type": "Circle", "coordinates": [0.0, 0.0]}),
+                Err(FeatureErrorKind::UnsupportedGeometry),
+            ),
+            (
+                "missing type",
+                json!({"coordinates": [0.0, 0.0]}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "missing coordinates",
+                json!({"type": "Point"}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "one-ordinate position",
+                json!({"type": "Point", "coordinates": [1.0]}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "string coordinate",
+                json!({"type": "LineString", "coordinates": [[0.0, 0.0], ["1", 1.0]]}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "null coordinate",
+                json!({"type": "Point", "coordinates": [0.0, null]}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+            (
+                "flat polygon coordinates",
+                json!({"type": "Polygon", "coordinates": [[0.0, 0.0], [1.0, 1.0]]}),
+                Err(FeatureErrorKind::InvalidGeometry),
+            ),
+        ];
+
+        for (name, value, expected) in cases {
+            assert_eq!(kind(geometry_from_geojson(&value)), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_geometry_policy_per_validity() {
+        let point = json!({"type": "Point", "coordinates": [1.0, 2.0, 3.0]});
+        let empty = json!({"type": "MultiPoint", "coordinates": []});
+        let mixed = json!({"type": "GeometryCollection", "geometries": [
+            {"type": "Point", "coordinates": [0.0, 0.0]},
+            {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]}
+        ]});
+
+        // (policy, geometry, kept, kept geometry-less)
+        let cases = [
+            (GeometryPolicy::Keep, Some(&point), true, false),
+            (GeometryPolicy::Drop, Some(&point), true, false),
+            (GeometryPolicy::Keep, None, true, true),
+            (GeometryPolicy::Drop, None, false, false),
+            (GeometryPolicy::Keep, Some(&empty), true, true),
+            (GeometryPolicy::Drop, Some(&empty), false, false),
+        ];
+        for (policy, geometry, kept, without_geometry) in cases {
+            let outcome = policy.apply(geometry).unwrap();
+            assert_eq!(outcome.is_some(), kept, "{:?} {:?}", policy, geometry);
+            assert_eq!(
+                outcome.is_some_and(|g| g.is_none()),
+                without_geometry,
+                "{:?} {:?}",
+                policy,
+                geometry
+            );
+        }
+
+        for policy in [GeometryPolicy::Keep, GeometryPolicy::Drop] {
+            let err = policy.apply(Some(&mixed)).unwrap_err();
+            assert_eq!(err.0, FeatureErrorKind::UnsupportedGeometry);
+        }
+
+        assert_eq!(GeometryPolicy::for_validity(ValidityMode::Lenient), GeometryPolicy::Keep);
+        assert_eq!(GeometryPolicy::for_validity(ValidityMode::Strict), GeometryPolicy::Drop);
+    }
+
+    #[test]
+    fn test_detect_geometry_type() {
+        let polygons = json!({"type": "GeometryCollection", "geometries": [
+            {"type": "Polygon", "coordinates": [[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]},
+            {"type": "Polygon", "coordinates": [[[2.0, 0.0], [3.0, 0.0], [3.0, 1.0], [2.0, 0.0]]]}
+        ]});
+        let empty = json!({"type": "Point", "coordinates": []});
+        let line = json!({"type": "LineString", "coordinates": [[0.0, 0.0, 1.0], [1.0, 1.0, 1.0]]});
+        let circle = json!({"type": "Circle", "radius": 3});
+
+        assert_eq!(detect_geometry_type([&polygons]), Some(GeometryType::MultiPolygon));
+        assert_eq!(detect_geometry_type([&empty, &line]), Some(GeometryType::LineString));
+        assert_eq!(detect_geometry_type([&circle, &line]), Some(GeometryType::GeometryCollection));
+        assert_eq!(detect_geometry_type([&empty]), None);
+        assert_eq!(detect_geometry_type(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_z_round_trips_for_every_geometry_type() {
+        let ring = json!([[0.0, 0.0, 1.0], [1.0, 0.0, 2.0], [1.0, 1.0, 3.0], [0.0, 0.0, 1.0]]);
+        let geometries = [
+            json!({"type": "Point", "coordinates": [106.8, -6.2, 12.5]}),
+            json!({"type": "LineString", "coordinates": [[0.0, 0.0, 5.0], [1.0, 1.0, 6.0]]}),
+            json!({"type": "Polygon", "coordinates": [ring]}),
+            json!({"type": "MultiPoint", "coordinates": [[0.0, 0.0, 1.0], [2.0, 2.0, -1.0]]}),
+            json!({"type": "MultiLineString", "coordinates": [
+                [[0.0, 0.0, 1.0], [1.0, 1.0, 2.0]],
+                [[2.0, 2.0, 3.0], [3.0, 3.0, 4.0]]
+            ]}),
+            json!({"type": "MultiPolygon", "coordinates": [[ring], [ring]]}),
+        ];
+
+        for value in &geometries {
+            let geometry = geometry_from_geojson(value).unwrap().unwrap();
+            let z = z_from_geojson(value).unwrap();
+            assert_eq!(z.len(), geometry.vertex_count(), "{}", value);
+            assert_eq!(&geometry_to_geojson_z(&geometry, &z), value);
+        }
+
+        // Collection members come out in member order, like their coordinates
+        let collection = json!({"type": "GeometryCollection", "geometries": [
+            {"type": "Point", "coordinates": [0.0, 0.0, 1.0]},
+            {"type": "MultiPoint", "coordinates": [[1.0, 1.0, 2.0], [2.0, 2.0, 3.0]]}
+        ]});
+        let geometry = geometry_from_geojson(&collection).unwrap().unwrap();
+        let z = z_from_geojson(&collection).unwrap();
+        assert_eq!(z, vec![1.0, 2.0, 3.0]);
+        assert_eq!(
+            geometry_to_geojson_z(&geometry, &z),
+            json!({"type": "MultiPoint", "coordinates": [
+                [0.0, 0.0, 1.0], [1.0, 1.0, 2.0], [2.0, 2.0, 3.0]
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_partial_z_and_measures() {
+        let partial = json!({"type": "LineString", "coordinates": [[0.0, 0.0, 5.0], [1.0, 1.0]]});
+        assert_eq!(z_from_geojson(&partial), None);
+        assert_eq!(z_from_geojson(&json!({"type": "Point", "coordinates": [1.0, 2.0]})), None);
+
+        let measured = json!({"type": "Point", "coordinates": [1.0, 2.0, 3.0, 40.0]});
+        assert!(has_measures(&measured));
+        assert!(!has_measures(&partial));
+        assert_eq!(z_from_geojson(&measured), Some(vec![3.0]));
+        assert_eq!(geometry_from_geojson(&measured).unwrap(), Some(Geometry::point(1.0, 2.0)));
+
+        assert_eq!(ZCoordinates::Drop.apply(&measured), None);
+        assert_eq!("Keep".parse::<ZCoordinates>(), Ok(ZCoordinates::Keep));
+        assert!("force_2d".parse::<ZCoordinates>().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Prepared spatial filters shared across queries
+//!
+//! Saved areas and default extents are queried over and over with the same
+//! geometry. [`FilterCache`] keeps the [`PreparedFilter`]s of recent filters,
+//! keyed by a hash of what decides their evaluation, so a repeated filter is
+//! prepared once rather than once per query.
+
+use crate::geo::spatial::PreparedFilter;
+use crate::models::SpatialFilter;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+/// Prepared filters kept by default
+pub const DEFAULT_FILTER_CACHE_SIZE: usize = 64;
+
+/// Least recently used cache of prepared spatial filters
+pub struct FilterCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    filters: HashMap<u64, (Arc<PreparedFilter>, u64)>,
+    /// Use counter; an entry's stamp is the count at its last use
+    clock: u64,
+    stats: FilterCacheStats,
+}
+
+/// Lookups served by a [`FilterCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterCacheStats {
+    /// Lookups answered with an already prepared filter
+    pub hits: u64,
+    /// Lookups that prepared the filter
+    pub misses: u64,
+}
+
+impl FilterCache {
+    /// Create a cache keeping up to `capacity` prepared filters
+    ///
+    /// A capacity of zero disables caching: every lookup prepares the filter.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Prepared form of a filter, from the cache when it was prepared before
+    ///
+    /// Filters are only shared when they evaluate the same, so a hash
+    /// collision prepares the filter again instead of answering for another.
+    pub fn get_or_prepare(&self, filter: &SpatialFilter) -> Arc<PreparedFilter> {
+        let key = filter_key(filter);
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.clock += 1;
+            let clock = entries.clock;
+            if let Some((prepared, used)) = entries.filters.get_mut(&key) {
+                if prepared.is_prepared_from(filter) {
+                    *used = clock;
+                    let prepared = prepared.clone();
+                    entries.stats.hits += 1;
+                    return prepared;
+                }
+            }
+            entries.stats.misses += 1;
+        }
+
+        // Prepared outside the lock so other queries are not held up
+        let prepared = Arc::new(PreparedFilter::new(filter));
+        if self.capacity == 0 {
+            return prepared;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.filters.len() >= self.capacity && !entries.filters.contains_key(&key) {
+            let oldest = entries.filters.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| *k);
+            if let Some(oldest) = oldest {
+                entries.filters.remove(&oldest);
+            }
+        }
+        let clock = entries.clock;
+        entries.filters.insert(key, (prepared.clone(), clock));
+        prepared
+    }
+
+    /// Number of prepared filters held
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().filters.len()
+    }
+
+    /// Check if no prepared filter is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hits and misses since the cache was created
+    pub fn stats(&self) -> FilterCacheStats {
+        self.entries.lock().unwrap().stats
+    }
+}
+
+impl Default for FilterCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_FILTER_CACHE_SIZE)
+    }
+}
+
+/// Hash of the parts of a filter that decide how it evaluates
+fn filter_key(filter: &SpatialFilter) -> u64 {
+    let text = serde_json::to_string(&(
+        filter.predicate,
+        filter.distance,
+        &filter.geometry,
+        &filter.exclude,
+    ))
+    .unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Distance, Geometry, SpatialPredicate};
+
+    fn square(size: f64) -> SpatialFilter {
+        SpatialFilter::new(SpatialPredicate::Within).geometry(Geometry::polygon(vec![vec![
+            [0.0, 0.0],
+            [size, 0.0],
+            [size, size],
+            [0.0, size],
+            [0.0, 0.0],
+        ]]))
+    }
+
+    #[test]
+    fn test_repeated_filter_is_prepared_once() {
+        let cache = FilterCache::new(4);
+        let first = cache.get_or_prepare(&square(10.0));
+        let second = cache.get_or_prepare(&square(10.0));
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.stats(), FilterCacheStats { hits: 1, misses: 1 });
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_filters_differing_in_evaluation_are_kept_apart() {
+        let cache = FilterCache::new(4);
+        let within = cache.get_or_prepare(&square(10.0));
+        let intersects = SpatialFilter {
+            predicate: SpatialPredicate::Intersects,
+            ..square(10.0)
+        };
+        let near = SpatialFilter {
+            predicate: SpatialPredicate::DWithin,
+            distance: Some(Distance::meters(100.0)),
+            ..square(10.0)
+        };
+        let farther = SpatialFilter {
+            distance: Some(Distance::meters(200.0)),
+            ..near.clone()
+        };
+
+        let excluding = square(10.0).exclude(Geometry::point(5.0, 5.0));
+
+        for filter in [&intersects, &near, &farther, &square(11.0), &excluding] {
+            let prepared = cache.get_or_prepare(filter);
+            assert!(!Arc::ptr_eq(&within, &prepared));
+            assert!(prepared.is_prepared_from(filter));
+        }
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn test_least_recently_used_filter_is_evicted() {
+        let cache = FilterCache::new(2);
+        let a = cache.get_or_prepare(&square(1.0));
+        cache.get_or_prepare(&square(2.0));
+        // Using the first filter again makes the second the oldest
+        cache.get_or_prepare(&square(1.0));
+        cache.get_or_prepare(&square(3.0));
+
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&a, &cache.get_or_prepare(&square(1.0))));
+        let misses = cache.stats().misses;
+        cache.get_or_prepare(&square(2.0));
+        assert_eq!(cache.stats().misses, misses + 1, "evicted filter is prepared again");
+    }
+
+    #[test]
+    fn test_zero_capacity_never_caches() {
+        let cache = FilterCache::new(0);
+        let first = cache.get_or_prepare(&square(1.0));
+        let second = cache.get_or_prepare(&square(1.0));
+
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(cache.is_empty());
+    }
+}
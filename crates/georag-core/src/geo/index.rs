@@ -1,6 +1,8 @@
-use crate::geo::models::{to_geo_geometry, Geometry, SpatialFilter};
-use crate::geo::spatial::evaluate_spatial_filter;
+use crate::geo::models::{to_geo_geometry, Geometry, SpatialFilter, SpatialPredicate};
+use crate::geo::spatial::PreparedFilter;
 use rstar::{RTree, RTreeObject, AABB};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Indexed geometry with ID
 #[derive(Debug, Clone, PartialEq)]
@@ -22,6 +24,22 @@ impl IndexedGeometry {
         Self { id, geometry, envelope }
     }
 
+    /// Create an indexed geometry with an envelope computed beforehand
+    ///
+    /// `envelope` is `[min_x, min_y, max_x, max_y]`, as returned by
+    /// [`bounds`](Self::bounds); it is trusted to cover the geometry.
+    pub fn with_envelope(id: usize, geometry: Geometry, envelope: [f64; 4]) -> Self {
+        let [min_x, min_y, max_x, max_y] = envelope;
+        let envelope = AABB::from_corners([min_x, min_y], [max_x, max_y]);
+        Self { id, geometry, envelope }
+    }
+
+    /// Bounding box of the geometry: `[min_x, min_y, max_x, max_y]`
+    pub fn bounds(&self) -> [f64; 4] {
+        let (lower, upper) = (self.envelope.lower(), self.envelope.upper());
+        [lower[0], lower[1], upper[0], upper[1]]
+    }
+
     /// Compute the bounding box (envelope) for a geometry
     fn compute_envelope(geometry: &Geometry) -> AABB<[f64; 2]> {
         use geo::algorithm::bounding_rect::BoundingRect;
@@ -53,7 +71,17 @@ impl RTreeObject for IndexedGeometry {
     }
 }
 
+/// ID and envelope of an indexed geometry, as saved with a snapshot of an index
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub id: usize,
+
+    /// Bounding box: `[min_x, min_y, max_x, max_y]`
+    pub envelope: [f64; 4],
+}
+
 /// Spatial index for efficient geometric queries
+#[derive(Debug)]
 pub struct SpatialIndex {
     tree: RTree<IndexedGeometry>,
 }
@@ -74,6 +102,26 @@ impl SpatialIndex {
         Self { tree: RTree::bulk_load(indexed) }
     }
 
+    /// Create a spatial index from geometries whose envelopes are known
+    ///
+    /// Bulk loading is deterministic, so geometries given in the same order
+    /// as to [`from_geometries`](Self::from_geometries) build the same tree
+    /// without computing an envelope per geometry.
+    pub fn from_indexed(indexed: Vec<IndexedGeometry>) -> Self {
+        Self { tree: RTree::bulk_load(indexed) }
+    }
+
+    /// IDs and envelopes of every geometry in the index, sorted by ID
+    pub fn entries(&self) -> Vec<IndexEntry> {
+        let mut entries: Vec<IndexEntry> = self
+            .tree
+            .iter()
+            .map(|g| IndexEntry { id: g.id, envelope: g.bounds() })
+            .collect();
+        entries.sort_by_key(|entry| entry.id);
+        entries
+    }
+
     /// Insert a geometry into the index
     pub fn insert(&mut self, id: usize, geometry: Geometry) {
         let indexed = IndexedGeometry::new(id, geometry);
@@ -99,6 +147,15 @@ impl SpatialIndex {
         self.tree.locate_in_envelope(&bbox).collect()
     }
 
+    /// Query geometries whose bounding boxes intersect a bounding box
+    ///
+    /// Unlike [`query_bbox`](Self::query_bbox), geometries only partly inside
+    /// the box (e.g. a polygon around a query point) are included.
+    pub fn query_bbox_intersecting(&self, min: [f64; 2], max: [f64; 2]) -> Vec<&IndexedGeometry> {
+        let bbox = AABB::from_corners(min, max);
+        self.tree.locate_in_envelope_intersecting(&bbox).collect()
+    }
+
     /// Query geometries near a point within a distance
     pub fn query_nearest(&self, point: [f64; 2], max_distance: f64) -> Vec<&IndexedGeometry> {
         // Use bounding box query as approximation
@@ -145,28 +202,41 @@ impl SpatialIndex {
 
     /// Query geometries using a spatial filter
     pub fn query_filter(&self, filter: &SpatialFilter) -> Vec<usize> {
-        // First, get candidates using bounding box query
-        let candidates = if let Some(filter_geom) = &filter.geometry {
-            let geo_geom = to_geo_geometry(filter_geom);
+        self.query_prepared(&PreparedFilter::new(filter))
+    }
 
-            use geo::algorithm::bounding_rect::BoundingRect;
-            if let Some(bbox) = geo_geom.bounding_rect() {
+    /// Query geometries using a spatial filter prepared beforehand
+    pub fn query_prepared(&self, prepared: &PreparedFilter) -> Vec<usize> {
+        // First, get candidates whose envelopes overlap the filter's bounding box
+        let candidates = match prepared.bounding_rect() {
+            // DWithin reaches past the bounding box by a distance in meters
+            Some(bbox) if prepared.filter().predicate != SpatialPredicate::DWithin => {
                 let min = bbox.min();
                 let max = bbox.max();
-                self.query_bbox([min.x, min.y], [max.x, max.y])
-            } else {
-                // No bounding box, return all geometries
-                self.tree.iter().collect()
+                self.query_bbox_intersecting([min.x, min.y], [max.x, max.y])
             }
-        } else {
-            // No filter geometry, return all
-            self.tree.iter().collect()
+            // No filter geometry or no bounding box, return all geometries
+            _ => self.tree.iter().collect(),
         };
 
-        // Then, apply the actual spatial predicate
+        // Only candidates whose envelopes overlap an exclusion area can be excluded
+        let near_exclusions: HashSet<usize> = prepared
+            .exclusion_bounds()
+            .flat_map(|bbox| {
+                let (min, max) = (bbox.min(), bbox.max());
+                self.query_bbox_intersecting([min.x, min.y], [max.x, max.y])
+            })
+            .map(|indexed| indexed.id)
+            .collect();
+
+        // Then, apply the actual spatial predicate and exclusions
         candidates
             .into_iter()
-            .filter(|indexed| evaluate_spatial_filter(&indexed.geometry, filter))
+            .filter(|indexed| {
+                prepared.includes(&indexed.geometry)
+                    && !(near_exclusions.contains(&indexed.id)
+                        && prepared.excludes(&indexed.geometry))
+            })
             .map(|indexed| indexed.id)
             .collect()
     }
@@ -236,7 +306,6 @@ impl Default for SpatialIndexBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::geo::models::SpatialPredicate;
 
     #[test]
     fn test_spatial_index_creation() {
@@ -339,6 +408,76 @@ mod tests {
         assert_eq!(results[0], 1);
     }
 
+    #[test]
+    fn test_filter_matches_geometries_partly_inside_its_box() {
+        let index = SpatialIndex::from_geometries(vec![
+            (
+                1,
+                Geometry::polygon(vec![vec![
+                    [-5.0, -5.0],
+                    [5.0, -5.0],
+                    [5.0, 5.0],
+                    [-5.0, 5.0],
+                    [-5.0, -5.0],
+                ]]),
+            ),
+            (2, Geometry::point(20.0, 20.0)),
+        ]);
+
+        let filter =
+            SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::polygon(vec![
+                vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            ]));
+        assert_eq!(index.query_filter(&filter), vec![1]);
+    }
+
+    #[test]
+    fn test_filter_exclusions_remove_candidates() {
+        let index = SpatialIndex::from_geometries(
+            (0..10).map(|i| (i, Geometry::point(i as f64 + 0.5, 5.0))).collect(),
+        );
+        let rect = |x0: f64, x1: f64| {
+            Geometry::polygon(vec![vec![[x0, 0.0], [x1, 0.0], [x1, 10.0], [x0, 10.0], [x0, 0.0]]])
+        };
+        let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(rect(0.0, 10.0));
+
+        let mut matched = index.query_filter(&filter.clone().exclude(rect(-1.0, 5.0)));
+        matched.sort();
+        assert_eq!(matched, vec![5, 6, 7, 8, 9]);
+        assert!(index.query_filter(&filter.clone().exclude(rect(-1.0, 11.0))).is_empty());
+        assert_eq!(index.query_filter(&filter.exclude(rect(20.0, 30.0))).len(), 10);
+    }
+
+    #[test]
+    fn test_index_rebuilt_from_entries_answers_the_same() {
+        let geometries = vec![
+            (3, Geometry::point(1.0, 1.0)),
+            (1, Geometry::line_string(vec![[0.0, 0.0], [4.0, 2.0]])),
+            (2, Geometry::point(8.0, 8.0)),
+        ];
+        let built = SpatialIndex::from_geometries(geometries.clone());
+
+        let entries = built.entries();
+        assert_eq!(entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(entries[0].envelope, [0.0, 0.0, 4.0, 2.0]);
+
+        let envelopes: std::collections::HashMap<usize, [f64; 4]> =
+            entries.iter().map(|e| (e.id, e.envelope)).collect();
+        let loaded = SpatialIndex::from_indexed(
+            geometries
+                .into_iter()
+                .map(|(id, geometry)| IndexedGeometry::with_envelope(id, geometry, envelopes[&id]))
+                .collect(),
+        );
+        assert_eq!(loaded.entries(), entries);
+
+        let mut expected = built.query_bbox_intersecting([0.5, 0.5], [3.0, 3.0]);
+        let mut actual = loaded.query_bbox_intersecting([0.5, 0.5], [3.0, 3.0]);
+        expected.sort_by_key(|g| g.id);
+        actual.sort_by_key(|g| g.id);
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_spatial_index_clear() {
         let mut index = SpatialIndex::new();
@@ -0,0 +1,358 @@
+//! Spatial join: copy properties from source features onto target features
+//!
+//! Source geometries are bulk-loaded into a [`SpatialIndex`] and each target
+//! feature is matched against the candidates whose envelopes overlap it. A
+//! target takes its properties from at most one source: the first matching
+//! source for `within`/`intersects`, the closest one for `nearest`.
+
+use crate::geo::index::SpatialIndex;
+use crate::geo::models::{to_geo_geometry, Distance, Geometry, SpatialFilter, SpatialPredicate};
+use crate::geo::spatial::{geodesic_distance, PreparedFilter};
+use crate::models::Feature;
+use geo::algorithm::bounding_rect::BoundingRect;
+use geo::algorithm::centroid::Centroid;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Approximate length of one degree of latitude in meters
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// How target features are matched to source features
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinPredicate {
+    /// Target lies completely inside the source
+    Within,
+
+    /// Target and source share any point
+    Intersects,
+
+    /// Closest source, optionally no farther than `max_distance`
+    Nearest { max_distance: Option<Distance> },
+}
+
+impl JoinPredicate {
+    /// Name of the predicate as accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            JoinPredicate::Within => "within",
+            JoinPredicate::Intersects => "intersects",
+            JoinPredicate::Nearest { .. } => "nearest",
+        }
+    }
+}
+
+impl fmt::Display for JoinPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinPredicate::Nearest { max_distance: Some(d) } => {
+                write!(f, "nearest (max {} {:?})", d.value, d.unit)
+            }
+            _ => write!(f, "{}", self.name()),
+        }
+    }
+}
+
+impl FromStr for JoinPredicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "within" => Ok(JoinPredicate::Within),
+            "intersects" => Ok(JoinPredicate::Intersects),
+            "nearest" => Ok(JoinPredicate::Nearest { max_distance: None }),
+            _ => Err(format!(
+                "Invalid join predicate '{}': expected within, intersects or nearest",
+                s
+            )),
+        }
+    }
+}
+
+/// A spatial join between a target and a source dataset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpatialJoin {
+    /// How targets are matched to sources
+    pub predicate: JoinPredicate,
+
+    /// Source property keys copied onto matched targets
+    pub take: Vec<String>,
+
+    /// Prefix added to copied keys so they do not overwrite target properties
+    pub prefix: String,
+}
+
+/// Number of target features that did and did not find a source
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinCounts {
+    pub matched: usize,
+    pub missed: usize,
+}
+
+/// Result of applying a join in memory
+#[derive(Debug, Clone)]
+pub struct JoinOutcome {
+    pub counts: JoinCounts,
+
+    /// Matched target features with the copied properties set
+    pub updated: Vec<Feature>,
+}
+
+impl SpatialJoin {
+    /// Create a join copying the `take` properties without a prefix
+    pub fn new(predicate: JoinPredicate, take: Vec<String>) -> Self {
+        Self { predicate, take, prefix: String::new() }
+    }
+
+    /// Set the prefix added to copied property keys
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Key under which a source property is written on the target
+    pub fn target_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// Match every target against the sources and copy the selected properties
+    ///
+    /// Targets without a geometry count as missed. Source properties that are
+    /// absent on the matched source are not written.
+    pub fn apply(&self, targets: &[Feature], sources: &[Feature]) -> JoinOutcome {
+        let index = SpatialIndex::from_geometries(
+            sources
+                .iter()
+                .enumerate()
+                .filter_map(|(i, f)| f.geometry.clone().map(|g| (i, g)))
+                .collect(),
+        );
+
+        let predicate = match self.predicate {
+            JoinPredicate::Within => Some(SpatialPredicate::Within),
+            JoinPredicate::Intersects => Some(SpatialPredicate::Intersects),
+            JoinPredicate::Nearest { .. } => None,
+        };
+        let prepared: Vec<Option<PreparedFilter>> = sources
+            .iter()
+            .map(|f| {
+                let geometry = f.geometry.clone()?;
+                Some(PreparedFilter::from_filter(SpatialFilter::new(predicate?).geometry(geometry)))
+            })
+            .collect();
+
+        let mut counts = JoinCounts::default();
+        let mut updated = Vec::new();
+
+        for target in targets {
+            let matched = target.geometry.as_ref().and_then(|geometry| match self.predicate {
+                JoinPredicate::Nearest { max_distance } => {
+                    nearest(&index, geometry, sources, max_distance)
+                }
+                _ => {
+                    let mut candidates = envelope_candidates(&index, geometry);
+                    candidates.sort_unstable();
+                    candidates.into_iter().find(|&i| {
+                        prepared[i].as_ref().is_some_and(|filter| filter.evaluate(geometry))
+                    })
+                }
+            });
+
+            let Some(source) = matched.map(|i| &sources[i]) else {
+                counts.missed += 1;
+                continue;
+            };
+
+            counts.matched += 1;
+            let mut feature = target.clone();
+            for key in &self.take {
+                if let Some(value) = source.properties.get(key) {
+                    feature.properties.insert(self.target_key(key), value.clone());
+                }
+            }
+            updated.push(feature);
+        }
+
+        JoinOutcome { counts, updated }
+    }
+}
+
+/// IDs of sources whose envelopes overlap the geometry's bounding box
+fn envelope_candidates(index: &SpatialIndex, geometry: &Geometry) -> Vec<usize> {
+    match to_geo_geometry(geometry).bounding_rect() {
+        Some(rect) => {
+            let (min, max) = (rect.min(), rect.max());
+            index
+                .query_bbox_intersecting([min.x, min.y], [max.x, max.y])
+                .iter()
+                .map(|g| g.id)
+                .collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Closest source to the geometry's centroid, within `max_distance` if given
+fn nearest(
+    index: &SpatialIndex,
+    geometry: &Geometry,
+    sources: &[Feature],
+    max_distance: Option<Distance>,
+) -> Option<usize> {
+    let centroid = to_geo_geometry(geometry).centroid()?;
+    let origin = [centroid.x(), centroid.y()];
+
+    let candidates: Vec<usize> = match max_distance {
+        Some(distance) => {
+            // Widen the search box by the distance in degrees of longitude at
+            // this latitude, which is never smaller than in degrees of latitude
+            let cos_lat = origin[1].to_radians().cos().abs().max(0.01);
+            let radius = distance.to_meters() / (METERS_PER_DEGREE * cos_lat);
+            index
+                .query_bbox_intersecting(
+                    [origin[0] - radius, origin[1] - radius],
+                    [origin[0] + radius, origin[1] + radius],
+                )
+                .iter()
+                .map(|g| g.id)
+                .collect()
+        }
+        None => index.all_ids(),
+    };
+
+    let point = Geometry::point(origin[0], origin[1]);
+    let limit = max_distance.map(|d| d.to_meters());
+
+    candidates
+        .into_iter()
+        .filter_map(|i| {
+            let source = sources[i].geometry.as_ref()?;
+            let meters = geodesic_distance(&point, source)?;
+            Some((i, meters))
+        })
+        .filter(|(_, meters)| match limit {
+            Some(limit) => *meters <= limit,
+            None => true,
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DistanceUnit, FeatureId};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn square(min: f64, max: f64) -> Geometry {
+        Geometry::polygon(vec![vec![[min, min], [max, min], [max, max], [min, max], [min, min]]])
+    }
+
+    fn feature(id: u64, geometry: Option<Geometry>, properties: Value) -> Feature {
+        let properties: HashMap<String, Value> =
+            serde_json::from_value(properties).unwrap_or_default();
+        Feature {
+            id: FeatureId(id),
+            geometry,
+            properties,
+            crs: 4326,
+            z: None,
+        }
+    }
+
+    fn villages() -> Vec<Feature> {
+        vec![
+            feature(10, Some(square(0.0, 1.0)), json!({ "name": "Alpha", "kode": "A1" })),
+            feature(11, Some(square(2.0, 3.0)), json!({ "name": "Beta" })),
+        ]
+    }
+
+    #[test]
+    fn test_within_copies_selected_properties_with_prefix() {
+        let points = vec![
+            feature(1, Some(Geometry::point(0.5, 0.5)), json!({ "name": "well" })),
+            feature(2, Some(Geometry::point(2.5, 2.5)), json!({})),
+            feature(3, Some(Geometry::point(5.0, 5.0)), json!({})),
+            feature(4, None, json!({})),
+        ];
+
+        let join = SpatialJoin::new(JoinPredicate::Within, vec!["name".into(), "kode".into()])
+            .with_prefix("village_");
+        let outcome = join.apply(&points, &villages());
+
+        assert_eq!(outcome.counts, JoinCounts { matched: 2, missed: 2 });
+        assert_eq!(outcome.updated.len(), 2);
+
+        let well = &outcome.updated[0];
+        assert_eq!(well.properties["name"], json!("well"));
+        assert_eq!(well.properties["village_name"], json!("Alpha"));
+        assert_eq!(well.properties["village_kode"], json!("A1"));
+
+        let second = &outcome.updated[1];
+        assert_eq!(second.properties["village_name"], json!("Beta"));
+        assert!(!second.properties.contains_key("village_kode"));
+    }
+
+    #[test]
+    fn test_intersects_matches_lines_crossing_a_source() {
+        let road = feature(
+            1,
+            Some(Geometry::LineString {
+                coordinates: vec![[-1.0, 0.5], [0.5, 0.5]],
+            }),
+            json!({}),
+        );
+
+        let within = SpatialJoin::new(JoinPredicate::Within, vec!["name".into()]);
+        assert_eq!(within.apply(std::slice::from_ref(&road), &villages()).counts.matched, 0);
+
+        let intersects = SpatialJoin::new(JoinPredicate::Intersects, vec!["name".into()]);
+        let outcome = intersects.apply(&[road], &villages());
+        assert_eq!(outcome.counts.matched, 1);
+        assert_eq!(outcome.updated[0].properties["name"], json!("Alpha"));
+    }
+
+    #[test]
+    fn test_nearest_respects_max_distance() {
+        let stations = vec![
+            feature(10, Some(Geometry::point(106.80, -6.20)), json!({ "name": "Central" })),
+            feature(11, Some(Geometry::point(106.90, -6.20)), json!({ "name": "East" })),
+        ];
+        // About 1.1 km east of Central
+        let point = feature(1, Some(Geometry::point(106.81, -6.20)), json!({}));
+
+        let unbounded =
+            SpatialJoin::new(JoinPredicate::Nearest { max_distance: None }, vec!["name".into()]);
+        let outcome = unbounded.apply(std::slice::from_ref(&point), &stations);
+        assert_eq!(outcome.updated[0].properties["name"], json!("Central"));
+
+        let within_2km = SpatialJoin::new(
+            JoinPredicate::Nearest {
+                max_distance: Some(Distance::new(2.0, DistanceUnit::Kilometers)),
+            },
+            vec!["name".into()],
+        );
+        assert_eq!(within_2km.apply(std::slice::from_ref(&point), &stations).counts.matched, 1);
+
+        let within_500m = SpatialJoin::new(
+            JoinPredicate::Nearest {
+                max_distance: Some(Distance::new(500.0, DistanceUnit::Meters)),
+            },
+            vec!["name".into()],
+        );
+        let outcome = within_500m.apply(&[point], &stations);
+        assert_eq!(outcome.counts, JoinCounts { matched: 0, missed: 1 });
+    }
+
+    #[test]
+    fn test_parse_predicate() {
+        assert_eq!("Within".parse::<JoinPredicate>(), Ok(JoinPredicate::Within));
+        assert_eq!(
+            "nearest".parse::<JoinPredicate>(),
+            Ok(JoinPredicate::Nearest { max_distance: None })
+        );
+        assert!("contains".parse::<JoinPredicate>().is_err());
+    }
+}
@@ -2,17 +2,48 @@
 //!
 //! This module provides spatial algorithms, CRS transforms, indexing, and validation.
 
+pub mod axis;
+pub mod buffer;
+pub mod convert;
+pub mod filter_cache;
 pub mod index;
+pub mod join;
 pub mod models;
+pub mod nearby;
+pub mod places;
+pub mod representative;
+pub mod sample;
+pub mod simplify;
 pub mod spatial;
+pub mod subdivide;
 pub mod transform;
 pub mod validation;
+pub mod wkb;
 
 // Re-export key types for convenience
-pub use index::{IndexedGeometry, SpatialIndex, SpatialIndexBuilder};
+pub use axis::{assess_geometry, decide_axis_order, extent_of, swap_axes, AxisVotes, Extent};
+pub use buffer::{buffer_filter, buffer_geometry};
+pub use convert::{
+    detect_geometry_type, geometry_from_geojson, geometry_to_geojson_z, geometry_type_from_name,
+    has_measures, z_from_geojson, ConversionError, GeometryPolicy, ZCoordinates,
+};
+pub use filter_cache::{FilterCache, FilterCacheStats, DEFAULT_FILTER_CACHE_SIZE};
+pub use index::{IndexEntry, IndexedGeometry, SpatialIndex, SpatialIndexBuilder};
+pub use join::{JoinCounts, JoinOutcome, JoinPredicate, SpatialJoin};
 pub use models::{from_geo_geometry, to_geo_geometry, GeometryExt};
+pub use nearby::{AppliedPointDefaults, PointQueryDefaults};
+pub use places::{Gazetteer, Place, PLACES_PROPERTY};
+pub use representative::{centroid, representative_point};
+pub use sample::{sample_features, SampleStrategy, DEFAULT_MAX_SAMPLE};
+pub use simplify::{FilterSimplification, GeometryLimits};
 pub use spatial::{
     count_spatial_matches, evaluate_spatial_filter, filter_geometries, geodesic_distance,
+    PreparedFilter,
+};
+pub use subdivide::{
+    subdivide, FeatureLimits, OversizedAction, OversizedFeature, OversizedFeatures,
+    DEFAULT_MAX_FEATURE_VERTICES,
 };
 pub use transform::{crs_match, normalize_geometries, normalize_geometry, reproject_geometry};
 pub use validation::{fix_geometry, validate_geometry, ValidationError, ValidationResult};
+pub use wkb::{from_wkb, from_wkb_z, to_wkb, to_wkb_z};
@@ -0,0 +1,177 @@
+//! Spatial filters around a single point
+//!
+//! Clients that only know where the user is send a point and expect nearby
+//! results. The predicate and radius such a query leaves out come from the
+//! workspace defaults, and the defaults filled in are reported back so the
+//! client knows what was assumed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Crs, Distance, DistanceUnit, Geometry, SpatialFilter, SpatialPredicate};
+
+/// Predicate and radius of point queries that do not set them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointQueryDefaults {
+    /// Predicate applied around the point
+    pub predicate: SpatialPredicate,
+
+    /// Radius of `DWithin` queries, `None` requires every query to set one
+    pub radius: Option<f64>,
+
+    /// Unit of `radius`
+    pub radius_unit: DistanceUnit,
+}
+
+impl Default for PointQueryDefaults {
+    fn default() -> Self {
+        Self {
+            predicate: SpatialPredicate::DWithin,
+            radius: None,
+            radius_unit: DistanceUnit::Meters,
+        }
+    }
+}
+
+/// Defaults filled into a point query; unset fields were given by the query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppliedPointDefaults {
+    /// Predicate taken from the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<SpatialPredicate>,
+
+    /// Radius taken from the defaults
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub radius: Option<Distance>,
+}
+
+impl AppliedPointDefaults {
+    /// Whether the query set everything itself
+    pub fn is_empty(&self) -> bool {
+        self.predicate.is_none() && self.radius.is_none()
+    }
+}
+
+impl PointQueryDefaults {
+    /// Default radius with its unit
+    pub fn radius(&self) -> Option<Distance> {
+        self.radius.map(|value| Distance::new(value, self.radius_unit))
+    }
+
+    /// Filter around a WGS 84 `[lon, lat]` point
+    ///
+    /// `predicate` and `radius` are the query's own; whichever is `None` is
+    /// taken from the defaults. A radius only applies to `DWithin`, so the
+    /// default radius is left out for other predicates and an explicit one
+    /// is rejected.
+    pub fn filter(
+        &self,
+        point: [f64; 2],
+        predicate: Option<SpatialPredicate>,
+        radius: Option<Distance>,
+    ) -> std::result::Result<(SpatialFilter, AppliedPointDefaults), String> {
+        let [lon, lat] = point;
+        if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+            return Err(format!("Point {},{} is not a WGS 84 longitude,latitude pair", lon, lat));
+        }
+
+        let mut applied = AppliedPointDefaults::default();
+        let predicate = predicate.unwrap_or_else(|| {
+            applied.predicate = Some(self.predicate);
+            self.predicate
+        });
+
+        let distance = match (predicate, radius) {
+            (SpatialPredicate::DWithin, Some(radius)) => Some(radius),
+            (SpatialPredicate::DWithin, None) => {
+                let radius = self.radius().ok_or_else(|| {
+                    "A dwithin point query needs a radius: set one on the query or configure \
+                    default_radius"
+                        .to_string()
+                })?;
+                applied.radius = Some(radius);
+                Some(radius)
+            }
+            (_, Some(_)) => {
+                return Err(format!(
+                    "A radius only applies to the dwithin predicate, not {:?}",
+                    predicate
+                ))
+            }
+            (_, None) => None,
+        };
+        if let Some(distance) = distance {
+            if !distance.value.is_finite() || distance.value <= 0.0 {
+                return Err(format!("Radius must be positive, got {}", distance.value));
+            }
+        }
+
+        let filter = SpatialFilter {
+            predicate,
+            geometry: Some(Geometry::point(lon, lat)),
+            distance,
+            crs: Crs::wgs84(),
+            buffer: None,
+            exclude: Vec::new(),
+        };
+        Ok((filter, applied))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nearby() -> PointQueryDefaults {
+        PointQueryDefaults {
+            predicate: SpatialPredicate::DWithin,
+            radius: Some(2.0),
+            radius_unit: DistanceUnit::Kilometers,
+        }
+    }
+
+    #[test]
+    fn test_defaults_fill_unset_fields() {
+        let (filter, applied) = nearby().filter([106.8, -6.2], None, None).unwrap();
+        assert_eq!(filter.predicate, SpatialPredicate::DWithin);
+        assert_eq!(filter.distance, Some(Distance::kilometers(2.0)));
+        assert_eq!(filter.geometry, Some(Geometry::point(106.8, -6.2)));
+        assert_eq!(applied.predicate, Some(SpatialPredicate::DWithin));
+        assert_eq!(applied.radius, Some(Distance::kilometers(2.0)));
+    }
+
+    #[test]
+    fn test_explicit_fields_are_never_overridden() {
+        let radius = Distance::meters(300.0);
+        let (filter, applied) = nearby()
+            .filter([106.8, -6.2], Some(SpatialPredicate::DWithin), Some(radius))
+            .unwrap();
+        assert_eq!(filter.distance, Some(radius));
+        assert!(applied.is_empty());
+
+        // Only the radius is filled in when the predicate is given
+        let (_, applied) =
+            nearby().filter([106.8, -6.2], Some(SpatialPredicate::DWithin), None).unwrap();
+        assert_eq!(applied.predicate, None);
+        assert_eq!(applied.radius, Some(Distance::kilometers(2.0)));
+
+        // Other predicates take no radius, default or not
+        let (filter, applied) = nearby()
+            .filter([106.8, -6.2], Some(SpatialPredicate::Intersects), None)
+            .unwrap();
+        assert_eq!(filter.predicate, SpatialPredicate::Intersects);
+        assert_eq!(filter.distance, None);
+        assert!(applied.is_empty());
+        assert!(nearby()
+            .filter([106.8, -6.2], Some(SpatialPredicate::Within), Some(radius))
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_point_queries_are_rejected() {
+        let unset = PointQueryDefaults::default();
+        assert!(unset.filter([106.8, -6.2], None, None).is_err());
+        assert!(nearby().filter([-6.2, 106.8], None, None).is_err());
+        assert!(nearby().filter([f64::NAN, 0.0], None, None).is_err());
+        assert!(nearby().filter([0.0, 0.0], None, Some(Distance::meters(0.0))).is_err());
+    }
+}
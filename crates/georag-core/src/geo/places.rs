@@ -0,0 +1,285 @@
+//! Named places of documents that discuss several sites
+//!
+//! A document associated with a gazetteer of named places keeps it on its
+//! feature under [`PLACES_PROPERTY`], and the feature's geometry covers every
+//! place. When the document is chunked, each chunk gets the geometry of the
+//! places its text names, so a filter around one site does not match chunks
+//! about another. Chunks naming no place fall back to the feature.
+
+use crate::error::{GeoragError, Result};
+use crate::models::{Feature, Geometry};
+use serde::{Deserialize, Serialize};
+
+/// Feature property holding the places of a multi-location document
+pub const PLACES_PROPERTY: &str = "_places";
+
+/// A named location a document may mention
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Place {
+    /// Name as it appears in the document text
+    pub name: String,
+
+    /// Location of the place
+    pub geometry: Geometry,
+}
+
+/// Places matched by name against the text of a document's chunks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Gazetteer {
+    places: Vec<Place>,
+}
+
+impl Gazetteer {
+    /// Create a gazetteer from a list of places
+    pub fn new(places: Vec<Place>) -> Self {
+        Self { places }
+    }
+
+    /// Read places from a GeoJSON FeatureCollection
+    ///
+    /// Every feature needs a geometry and a non-empty `name` property.
+    pub fn from_geojson(value: &serde_json::Value) -> Result<Self> {
+        let invalid =
+            |message: String| GeoragError::FormatError { format: "GeoJSON".to_string(), message };
+
+        let features = match value.get("type").and_then(|t| t.as_str()) {
+            Some("FeatureCollection") => value
+                .get("features")
+                .and_then(|f| f.as_array())
+                .ok_or_else(|| invalid("Places FeatureCollection has no features".to_string()))?,
+            _ => return Err(invalid("Places must be a GeoJSON FeatureCollection".to_string())),
+        };
+
+        let mut places = Vec::with_capacity(features.len());
+        for (index, feature) in features.iter().enumerate() {
+            let name = feature
+                .get("properties")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .map(|n| n.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|n| !n.is_empty())
+                .ok_or_else(|| invalid(format!("Place {} has no name property", index)))?;
+            let geometry = feature
+                .get("geometry")
+                .and_then(Geometry::from_geojson)
+                .ok_or_else(|| invalid(format!("Place '{}' has no valid geometry", name)))?;
+            places.push(Place { name, geometry });
+        }
+
+        if places.is_empty() {
+            return Err(invalid("Places FeatureCollection has no features".to_string()));
+        }
+
+        Ok(Self { places })
+    }
+
+    /// Places stored on a feature, if it has any
+    pub fn from_feature(feature: &Feature) -> Option<Self> {
+        let value = feature.properties.get(PLACES_PROPERTY)?;
+        serde_json::from_value(value.clone()).ok().filter(|g: &Gazetteer| !g.is_empty())
+    }
+
+    /// The gazetteer as a feature property value
+    pub fn to_property(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Places in the gazetteer
+    pub fn places(&self) -> &[Place] {
+        &self.places
+    }
+
+    /// Number of places
+    pub fn len(&self) -> usize {
+        self.places.len()
+    }
+
+    /// Check if the gazetteer has no places
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty()
+    }
+
+    /// Places whose name appears in the text as a whole word, ignoring case
+    pub fn mentioned_in(&self, text: &str) -> Vec<&Place> {
+        let text = text.to_lowercase();
+        self.places
+            .iter()
+            .filter(|place| mentions(&text, &place.name.to_lowercase()))
+            .collect()
+    }
+
+    /// Geometry of the places the text names, `None` when it names none
+    pub fn geometry_for(&self, text: &str) -> Option<Geometry> {
+        combine(&self.mentioned_in(text).iter().map(|p| &p.geometry).collect::<Vec<_>>())
+    }
+
+    /// Geometry covering every place
+    pub fn footprint(&self) -> Option<Geometry> {
+        combine(&self.places.iter().map(|p| &p.geometry).collect::<Vec<_>>())
+    }
+}
+
+/// Check if `name` occurs in `text` with no letter or digit on either side
+///
+/// Both are expected in lower case.
+fn mentions(text: &str, name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+
+    text.match_indices(name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Combine geometries into one
+///
+/// Geometries of one kind become a multi-geometry of that kind. Points, lines
+/// and polygons mixed together have no common type here, so they are
+/// combined into the bounding box of all of them.
+fn combine(geometries: &[&Geometry]) -> Option<Geometry> {
+    if let [single] = geometries {
+        return Some((*single).clone());
+    }
+
+    let mut points = Vec::new();
+    let mut lines = Vec::new();
+    let mut polygons = Vec::new();
+    for geometry in geometries {
+        match geometry {
+            Geometry::Point { coordinates } => points.push(*coordinates),
+            Geometry::MultiPoint { coordinates } => points.extend(coordinates.iter().copied()),
+            Geometry::LineString { coordinates } => lines.push(coordinates.clone()),
+            Geometry::MultiLineString { coordinates } => lines.extend(coordinates.iter().cloned()),
+            Geometry::Polygon { coordinates } => polygons.push(coordinates.clone()),
+            Geometry::MultiPolygon { coordinates } => polygons.extend(coordinates.iter().cloned()),
+        }
+    }
+
+    match (points.is_empty(), lines.is_empty(), polygons.is_empty()) {
+        (true, true, true) => None,
+        (false, true, true) => Some(Geometry::MultiPoint { coordinates: points }),
+        (true, false, true) => Some(Geometry::MultiLineString { coordinates: lines }),
+        (true, true, false) => Some(Geometry::MultiPolygon { coordinates: polygons }),
+        _ => {
+            let coordinates = points
+                .iter()
+                .chain(lines.iter().flatten())
+                .chain(polygons.iter().flatten().flatten());
+            let mut min = [f64::INFINITY; 2];
+            let mut max = [f64::NEG_INFINITY; 2];
+            for [x, y] in coordinates {
+                min = [min[0].min(*x), min[1].min(*y)];
+                max = [max[0].max(*x), max[1].max(*y)];
+            }
+            Some(Geometry::polygon(vec![vec![
+                [min[0], min[1]],
+                [max[0], min[1]],
+                [max[0], max[1]],
+                [min[0], max[1]],
+                [min[0], min[1]],
+            ]]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn gazetteer() -> Gazetteer {
+        Gazetteer::new(vec![
+            Place {
+                name: "Harbour Road".to_string(),
+                geometry: Geometry::point(1.0, 1.0),
+            },
+            Place {
+                name: "Mill".to_string(),
+                geometry: Geometry::point(2.0, 2.0),
+            },
+        ])
+    }
+
+    #[test]
+    fn test_names_match_whole_words_ignoring_case() {
+        let places = gazetteer();
+
+        let names = |text: &str| -> Vec<String> {
+            places.mentioned_in(text).into_iter().map(|p| p.name.clone()).collect()
+        };
+
+        assert_eq!(names("Works on harbour road start in May"), vec!["Harbour Road"]);
+        assert_eq!(names("The MILL, then Harbour Road."), vec!["Harbour Road", "Mill"]);
+        assert!(names("Milling and the harbour roadside").is_empty());
+    }
+
+    #[test]
+    fn test_geometry_of_several_places_is_combined() {
+        let places = gazetteer();
+
+        assert_eq!(places.geometry_for("Mill only"), Some(Geometry::point(2.0, 2.0)));
+        assert_eq!(
+            places.geometry_for("Mill and Harbour Road"),
+            Some(Geometry::MultiPoint {
+                coordinates: vec![[1.0, 1.0], [2.0, 2.0]]
+            })
+        );
+        assert_eq!(places.geometry_for("Nowhere in particular"), None);
+        assert_eq!(places.footprint(), places.geometry_for("Mill and Harbour Road"));
+    }
+
+    #[test]
+    fn test_mixed_kinds_combine_to_their_bounding_box() {
+        let point = Geometry::point(0.0, 5.0);
+        let line = Geometry::line_string(vec![[2.0, 0.0], [4.0, 1.0]]);
+
+        assert_eq!(
+            combine(&[&point, &line]),
+            Some(Geometry::polygon(vec![vec![
+                [0.0, 0.0],
+                [4.0, 0.0],
+                [4.0, 5.0],
+                [0.0, 5.0],
+                [0.0, 0.0],
+            ]]))
+        );
+    }
+
+    #[test]
+    fn test_from_geojson_requires_names() {
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [1.0, 1.0] },
+                "properties": { "name": "  Site   A " }
+            }]
+        });
+        let places = Gazetteer::from_geojson(&collection).unwrap();
+        assert_eq!(places.places()[0].name, "Site A");
+
+        let unnamed = json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [1.0, 1.0] },
+                "properties": {}
+            }]
+        });
+        assert!(Gazetteer::from_geojson(&unnamed).is_err());
+        assert!(Gazetteer::from_geojson(&json!({ "type": "Point" })).is_err());
+    }
+
+    #[test]
+    fn test_round_trips_through_feature_property() {
+        let places = gazetteer();
+        let properties = [(PLACES_PROPERTY.to_string(), places.to_property())].into();
+        let feature = Feature::without_geometry(crate::models::FeatureId(1), properties, 4326);
+
+        assert_eq!(Gazetteer::from_feature(&feature), Some(places));
+    }
+}
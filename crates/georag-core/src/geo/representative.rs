@@ -0,0 +1,227 @@
+//! Centroids and representative points of geometries
+//!
+//! Clients placing a pin for a result need a point on the geometry, and a
+//! centroid does not guarantee one: the centroid of a crescent, or of two
+//! islands, falls outside them. [`representative_point`] always returns a
+//! point on the geometry, like PostGIS `ST_PointOnSurface`.
+
+use std::cmp::Ordering;
+
+use crate::geo::GeometryExt;
+use crate::models::Geometry;
+
+/// Centroid of a geometry, `None` when it has no coordinates
+pub fn centroid(geometry: &Geometry) -> Option<[f64; 2]> {
+    match geometry {
+        Geometry::Point { coordinates } => Some(*coordinates),
+        _ => geometry.centroid_coords(),
+    }
+}
+
+/// A point guaranteed to lie on the geometry, `None` when it has no coordinates
+///
+/// - Polygons use the interior scanline method: a horizontal line through
+///   the middle of each polygon, placed between vertices so it crosses edges
+///   cleanly, is cut into its interior sections, and the midpoint of the
+///   widest section across all parts is returned. Holes are respected.
+/// - Lines give the interior vertex closest to the centroid, or the closest
+///   end of a two-vertex line.
+/// - Points give the point closest to the centroid.
+///
+/// Polygons without area are handled as their rings would be as lines.
+pub fn representative_point(geometry: &Geometry) -> Option<[f64; 2]> {
+    match geometry {
+        Geometry::Point { coordinates } => Some(*coordinates),
+        Geometry::MultiPoint { coordinates } => {
+            closest_to_centroid(geometry, coordinates.iter().copied())
+        }
+        Geometry::LineString { coordinates } => {
+            line_point(geometry, std::slice::from_ref(coordinates))
+        }
+        Geometry::MultiLineString { coordinates } => line_point(geometry, coordinates),
+        Geometry::Polygon { coordinates } => polygon_point(std::slice::from_ref(coordinates))
+            .or_else(|| line_point(geometry, coordinates)),
+        Geometry::MultiPolygon { coordinates } => polygon_point(coordinates).or_else(|| {
+            let rings: Vec<Vec<[f64; 2]>> = coordinates.iter().flatten().cloned().collect();
+            line_point(geometry, &rings)
+        }),
+    }
+}
+
+/// Interior vertex of the lines closest to the geometry's centroid
+fn line_point(geometry: &Geometry, lines: &[Vec<[f64; 2]>]) -> Option<[f64; 2]> {
+    let vertices = lines.iter().flat_map(|line| {
+        let interior = if line.len() > 2 {
+            &line[1..line.len() - 1]
+        } else {
+            &line[..]
+        };
+        interior.iter().copied()
+    });
+    closest_to_centroid(geometry, vertices)
+}
+
+/// The candidate closest to the geometry's centroid, or the first without one
+fn closest_to_centroid(
+    geometry: &Geometry,
+    candidates: impl Iterator<Item = [f64; 2]>,
+) -> Option<[f64; 2]> {
+    let mut candidates = candidates.filter(|[x, y]| x.is_finite() && y.is_finite());
+    let Some([cx, cy]) = centroid(geometry) else {
+        return candidates.next();
+    };
+    let distance = |[x, y]: &[f64; 2]| (x - cx).powi(2) + (y - cy).powi(2);
+    candidates.min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal))
+}
+
+/// Midpoint of the widest interior scanline section across the polygons
+fn polygon_point(polygons: &[Vec<Vec<[f64; 2]>>]) -> Option<[f64; 2]> {
+    let mut widest: Option<(f64, [f64; 2])> = None;
+
+    for rings in polygons {
+        let Some(y) = scanline(rings) else {
+            continue;
+        };
+
+        let mut crossings = Vec::new();
+        for ring in rings {
+            for (i, a) in ring.iter().enumerate() {
+                let b = ring[(i + 1) % ring.len()];
+                if (a[1] > y) != (b[1] > y) {
+                    crossings.push(a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1]));
+                }
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+
+        // Crossings alternate between entering and leaving the interior
+        for section in crossings.chunks_exact(2) {
+            let width = section[1] - section[0];
+            if width > 0.0 && widest.is_none_or(|(best, _)| width > best) {
+                widest = Some((width, [(section[0] + section[1]) / 2.0, y]));
+            }
+        }
+    }
+
+    widest.map(|(_, point)| point)
+}
+
+/// Height of a horizontal line through the middle of a polygon that passes
+/// between vertices, `None` when the polygon has no height
+fn scanline(rings: &[Vec<[f64; 2]>]) -> Option<f64> {
+    let exterior = rings.first()?;
+    let ys = exterior.iter().map(|c| c[1]).filter(|y| y.is_finite());
+    let min = ys.clone().fold(f64::INFINITY, f64::min);
+    let max = ys.fold(f64::NEG_INFINITY, f64::max);
+    if max <= min {
+        return None;
+    }
+
+    let middle = (min + max) / 2.0;
+    let (mut below, mut above) = (min, max);
+    for y in rings.iter().flatten().map(|c| c[1]) {
+        if y <= middle && y > below {
+            below = y;
+        } else if y > middle && y < above {
+            above = y;
+        }
+    }
+    Some((below + above) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo::{Contains, Coord, Intersects};
+
+    fn inside(geometry: &Geometry, [x, y]: [f64; 2]) -> bool {
+        geometry.to_geo().contains(&Coord { x, y })
+    }
+
+    fn on(geometry: &Geometry, [x, y]: [f64; 2]) -> bool {
+        geometry.to_geo().intersects(&Coord { x, y })
+    }
+
+    /// A "C" open to the east, whose centroid lies in the opening
+    fn crescent() -> Geometry {
+        Geometry::polygon(vec![vec![
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 2.0],
+            [2.0, 2.0],
+            [2.0, 8.0],
+            [10.0, 8.0],
+            [10.0, 10.0],
+            [0.0, 10.0],
+            [0.0, 0.0],
+        ]])
+    }
+
+    #[test]
+    fn test_point_on_concave_polygon_where_centroid_is_outside() {
+        let polygon = crescent();
+        let centroid = centroid(&polygon).unwrap();
+        assert!(!inside(&polygon, centroid));
+
+        let point = representative_point(&polygon).unwrap();
+        assert!(inside(&polygon, point), "{:?} is outside", point);
+    }
+
+    #[test]
+    fn test_point_on_multipolygon_where_centroid_is_between_parts() {
+        let square = |x0: f64, size: f64| {
+            vec![vec![[x0, 0.0], [x0 + size, 0.0], [x0 + size, size], [x0, size], [x0, 0.0]]]
+        };
+        let islands = Geometry::MultiPolygon {
+            coordinates: vec![square(0.0, 2.0), square(10.0, 4.0)],
+        };
+        let centroid = centroid(&islands).unwrap();
+        assert!(!inside(&islands, centroid));
+
+        let point = representative_point(&islands).unwrap();
+        assert!(inside(&islands, point));
+        // The widest section is in the larger island
+        assert!(point[0] > 10.0);
+    }
+
+    #[test]
+    fn test_point_avoids_holes() {
+        let ring = Geometry::polygon(vec![
+            vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            vec![[1.0, 1.0], [9.0, 1.0], [9.0, 9.0], [1.0, 9.0], [1.0, 1.0]],
+        ]);
+        assert!(!inside(&ring, centroid(&ring).unwrap()));
+        assert!(inside(&ring, representative_point(&ring).unwrap()));
+    }
+
+    #[test]
+    fn test_point_on_lines_is_a_vertex() {
+        let bend = Geometry::line_string(vec![[0.0, 0.0], [5.0, 5.0], [10.0, 0.0]]);
+        assert_eq!(representative_point(&bend), Some([5.0, 5.0]));
+
+        let segment = Geometry::line_string(vec![[0.0, 0.0], [4.0, 0.0]]);
+        assert!(on(&segment, representative_point(&segment).unwrap()));
+    }
+
+    #[test]
+    fn test_degenerate_geometries_fall_back() {
+        let point = Geometry::point(3.0, 4.0);
+        assert_eq!(representative_point(&point), Some([3.0, 4.0]));
+        assert_eq!(centroid(&point), Some([3.0, 4.0]));
+
+        let points = Geometry::MultiPoint {
+            coordinates: vec![[0.0, 0.0], [1.0, 0.0], [9.0, 0.0]],
+        };
+        assert_eq!(representative_point(&points), Some([1.0, 0.0]));
+
+        // A polygon without area is treated as its outline
+        let flat = Geometry::polygon(vec![vec![[0.0, 1.0], [5.0, 1.0], [8.0, 1.0], [0.0, 1.0]]]);
+        let point = representative_point(&flat).unwrap();
+        assert_eq!(point[1], 1.0);
+
+        let empty = Geometry::LineString { coordinates: Vec::new() };
+        assert_eq!(representative_point(&empty), None);
+        assert_eq!(centroid(&empty), None);
+        assert_eq!(representative_point(&Geometry::MultiPolygon { coordinates: Vec::new() }), None);
+    }
+}
@@ -0,0 +1,244 @@
+//! Feature sampling for dataset previews
+//!
+//! `random` draws features uniformly. `spatial` lays a grid over the dataset
+//! extent and takes one random feature per cell, using a [`SpatialIndex`] to
+//! find each cell's candidates, so the sample covers the whole extent instead
+//! of clustering where features are dense.
+
+use crate::geo::index::SpatialIndex;
+use crate::geo::models::to_geo_geometry;
+use crate::models::{Feature, Geometry};
+use geo::algorithm::bounding_rect::BoundingRect;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Default maximum number of features returned by one sample
+pub const DEFAULT_MAX_SAMPLE: usize = 100;
+
+/// How sampled features are chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleStrategy {
+    /// Uniformly random features
+    #[default]
+    Random,
+
+    /// One random feature per cell of a grid over the dataset extent
+    Spatial,
+}
+
+impl fmt::Display for SampleStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SampleStrategy::Random => write!(f, "random"),
+            SampleStrategy::Spatial => write!(f, "spatial"),
+        }
+    }
+}
+
+impl FromStr for SampleStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "random" => Ok(SampleStrategy::Random),
+            "spatial" => Ok(SampleStrategy::Spatial),
+            _ => Err(format!("Invalid sample strategy '{}': expected random or spatial", s)),
+        }
+    }
+}
+
+/// Pick up to `n` features with the given strategy
+///
+/// The same `seed` always picks the same features. Features without a
+/// geometry are only used by the spatial strategy to fill a sample when there
+/// are fewer occupied cells than `n`.
+pub fn sample_features(
+    features: &[Feature],
+    n: usize,
+    strategy: SampleStrategy,
+    seed: u64,
+) -> Vec<Feature> {
+    let mut rng = SplitMix64(seed);
+    let picked = match strategy {
+        SampleStrategy::Random => random_indices(features.len(), n, &mut rng),
+        SampleStrategy::Spatial => spatial_indices(features, n, &mut rng),
+    };
+    picked.into_iter().map(|idx| features[idx].clone()).collect()
+}
+
+/// `n` distinct indices below `len`, by a partial Fisher-Yates shuffle
+fn random_indices(len: usize, n: usize, rng: &mut SplitMix64) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..len).collect();
+    let n = n.min(len);
+    for i in 0..n {
+        let j = i + rng.below(len - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(n);
+    indices
+}
+
+fn spatial_indices(features: &[Feature], n: usize, rng: &mut SplitMix64) -> Vec<usize> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Bounding-box centers stand in for each geometry's location
+    let centers: Vec<Option<[f64; 2]>> = features
+        .iter()
+        .map(|feature| {
+            let rect = to_geo_geometry(feature.geometry.as_ref()?).bounding_rect()?;
+            let center = rect.center();
+            Some([center.x, center.y])
+        })
+        .collect();
+
+    let located: Vec<(usize, [f64; 2])> = centers
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, center)| center.map(|c| (idx, c)))
+        .collect();
+    if located.is_empty() {
+        return random_indices(features.len(), n, rng);
+    }
+
+    let (mut min, mut max) = (located[0].1, located[0].1);
+    for (_, [x, y]) in &located {
+        min = [min[0].min(*x), min[1].min(*y)];
+        max = [max[0].max(*x), max[1].max(*y)];
+    }
+
+    let index = SpatialIndex::from_geometries(
+        located.iter().map(|(idx, [x, y])| (*idx, Geometry::point(*x, *y))).collect(),
+    );
+
+    // A k x k grid has at least n cells
+    let k = (n as f64).sqrt().ceil() as usize;
+    let width = (max[0] - min[0]) / k as f64;
+    let height = (max[1] - min[1]) / k as f64;
+
+    let mut cells: Vec<usize> = (0..k * k).collect();
+    for i in (1..cells.len()).rev() {
+        cells.swap(i, rng.below(i + 1));
+    }
+
+    let mut picked = Vec::with_capacity(n);
+    let mut taken = vec![false; features.len()];
+    for cell in cells {
+        if picked.len() == n {
+            break;
+        }
+        let (col, row) = (cell % k, cell / k);
+        let cell_min = [min[0] + col as f64 * width, min[1] + row as f64 * height];
+        let cell_max = [cell_min[0] + width, cell_min[1] + height];
+
+        // Cells are half-open except along the far edges of the extent
+        let mut candidates: Vec<usize> = index
+            .query_bbox_intersecting(cell_min, cell_max)
+            .into_iter()
+            .map(|indexed| indexed.id)
+            .filter(|idx| {
+                let [x, y] = centers[*idx].unwrap_or_default();
+                let in_x = x < cell_max[0] || col == k - 1;
+                let in_y = y < cell_max[1] || row == k - 1;
+                in_x && in_y && x >= cell_min[0] && y >= cell_min[1] && !taken[*idx]
+            })
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+        candidates.sort_unstable();
+        let idx = candidates[rng.below(candidates.len())];
+        taken[idx] = true;
+        picked.push(idx);
+    }
+
+    // Empty cells leave room for more features from anywhere
+    if picked.len() < n {
+        let rest: Vec<usize> = (0..features.len()).filter(|idx| !taken[*idx]).collect();
+        let extra = random_indices(rest.len(), n - picked.len(), rng);
+        picked.extend(extra.into_iter().map(|i| rest[i]));
+    }
+
+    picked
+}
+
+/// Small deterministic generator; sampling needs variety, not cryptographic quality
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..bound`; `bound` must be greater than zero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureId;
+    use std::collections::{HashMap, HashSet};
+
+    fn point(id: u64, x: f64, y: f64) -> Feature {
+        Feature::with_geometry(FeatureId(id), Geometry::point(x, y), HashMap::new(), 4326)
+    }
+
+    #[test]
+    fn test_strategy_parses() {
+        assert_eq!("Spatial".parse::<SampleStrategy>().unwrap(), SampleStrategy::Spatial);
+        assert_eq!(SampleStrategy::Random.to_string(), "random");
+        assert!("first".parse::<SampleStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_random_sample_is_distinct_and_repeatable() {
+        let features: Vec<Feature> = (0..50).map(|i| point(i, i as f64, 0.0)).collect();
+
+        let sample = sample_features(&features, 10, SampleStrategy::Random, 7);
+        let ids: HashSet<u64> = sample.iter().map(|f| f.id.0).collect();
+        assert_eq!(ids.len(), 10);
+
+        let again = sample_features(&features, 10, SampleStrategy::Random, 7);
+        let ids_of = |sample: &[Feature]| sample.iter().map(|f| f.id.0).collect::<Vec<_>>();
+        assert_eq!(ids_of(&sample), ids_of(&again));
+
+        assert_eq!(sample_features(&features, 80, SampleStrategy::Random, 7).len(), 50);
+    }
+
+    #[test]
+    fn test_spatial_sample_covers_the_extent() {
+        // 96 features crowded in one corner, one in each other corner
+        let mut features: Vec<Feature> = (0..96)
+            .map(|i| point(i, (i % 10) as f64 * 0.01, (i / 10) as f64 * 0.01))
+            .collect();
+        features.push(point(100, 10.0, 0.0));
+        features.push(point(101, 0.0, 10.0));
+        features.push(point(102, 10.0, 10.0));
+
+        for seed in 0..5 {
+            let sample = sample_features(&features, 4, SampleStrategy::Spatial, seed);
+            let ids: HashSet<u64> = sample.iter().map(|f| f.id.0).collect();
+            assert_eq!(sample.len(), 4);
+            assert!(ids.contains(&100) && ids.contains(&101) && ids.contains(&102));
+        }
+    }
+
+    #[test]
+    fn test_spatial_sample_fills_from_empty_cells() {
+        let features: Vec<Feature> = (0..6).map(|i| point(i, 0.0, i as f64 * 1e-6)).collect();
+
+        let sample = sample_features(&features, 4, SampleStrategy::Spatial, 1);
+        let ids: HashSet<u64> = sample.iter().map(|f| f.id.0).collect();
+        assert_eq!(ids.len(), 4);
+    }
+}
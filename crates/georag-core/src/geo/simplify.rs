@@ -0,0 +1,477 @@
+//! Filter geometry size limits and simplification
+//!
+//! Query filters are evaluated against every candidate feature, so a highly
+//! detailed filter polygon (e.g. a full-resolution country outline) makes each
+//! query do millions of point-in-polygon tests. `GeometryLimits` rejects such
+//! filters or reduces them with Douglas-Peucker to a target vertex count.
+//! The same reduction keeps large result geometries within a vertex budget.
+
+use geo::Validation;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GeoragError, Result};
+use crate::geo::models::to_geo_geometry;
+use crate::models::Geometry;
+
+/// Default maximum number of vertices in a query filter geometry
+pub const DEFAULT_MAX_FILTER_VERTICES: usize = 10_000;
+
+/// Number of bisection steps used to find a simplification tolerance
+const TOLERANCE_SEARCH_STEPS: usize = 48;
+
+/// Size limits applied to query filter geometries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeometryLimits {
+    /// Maximum number of vertices
+    pub max_vertices: usize,
+
+    /// Simplify oversized geometries instead of rejecting them
+    pub auto_simplify: bool,
+}
+
+impl Default for GeometryLimits {
+    fn default() -> Self {
+        Self {
+            max_vertices: DEFAULT_MAX_FILTER_VERTICES,
+            auto_simplify: false,
+        }
+    }
+}
+
+/// Record of a filter geometry simplified to fit the vertex limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterSimplification {
+    /// Vertices in the submitted geometry
+    pub original_vertices: usize,
+
+    /// Vertices after simplification
+    pub simplified_vertices: usize,
+
+    /// Douglas-Peucker tolerance used, in CRS units
+    pub tolerance: f64,
+}
+
+impl GeometryLimits {
+    /// Check a filter geometry against the limits
+    ///
+    /// Returns the geometry to use, simplified when it is over the limit and
+    /// auto-simplification is enabled, and a record of the simplification.
+    pub fn apply(&self, geometry: &Geometry) -> Result<(Geometry, Option<FilterSimplification>)> {
+        let vertices = geometry.vertex_count();
+        if vertices <= self.max_vertices {
+            return Ok((geometry.clone(), None));
+        }
+
+        if !self.auto_simplify {
+            return Err(GeoragError::GeometryTooComplex { vertices, limit: self.max_vertices });
+        }
+
+        let (simplified, tolerance) = simplify_to_vertex_count(geometry, self.max_vertices);
+        let simplified_vertices = simplified.vertex_count();
+        if simplified_vertices > self.max_vertices {
+            return Err(GeoragError::GeometryTooComplex { vertices, limit: self.max_vertices });
+        }
+
+        Ok((
+            simplified,
+            Some(FilterSimplification {
+                original_vertices: vertices,
+                simplified_vertices,
+                tolerance,
+            }),
+        ))
+    }
+}
+
+/// Simplify a geometry with Douglas-Peucker until it has at most `target` vertices
+///
+/// The tolerance is found by bisection so the result keeps as much detail as
+/// the target allows. Returns the simplified geometry and the tolerance used.
+/// Shells never drop below four vertices, so the target may be unreachable for
+/// geometries with many polygons.
+pub fn simplify_to_vertex_count(geometry: &Geometry, target: usize) -> (Geometry, f64) {
+    if geometry.vertex_count() <= target {
+        return (geometry.clone(), 0.0);
+    }
+
+    // Which vertices a tolerance keeps is decided by one ranking, so each
+    // step of the search only filters
+    let ranked = Ranked::new(geometry);
+    let mut low = 0.0;
+    let mut high = extent(geometry);
+    let mut best = ranked.simplify(high);
+
+    for _ in 0..TOLERANCE_SEARCH_STEPS {
+        let mid = (low + high) / 2.0;
+        let candidate = ranked.simplify(mid);
+        if candidate.vertex_count() <= target {
+            high = mid;
+            best = candidate;
+        } else {
+            low = mid;
+        }
+    }
+
+    (best, high)
+}
+
+/// Simplify a geometry to at most `budget` vertices without breaking its polygons
+///
+/// Returns `None` when the budget cannot be met (e.g. more rings than it
+/// allows, or points that cannot be simplified) or when a simplified polygon
+/// is no longer valid: a ring crossing itself or another ring, or collapsed.
+/// Geometries already within the budget are returned unchanged.
+pub fn simplify_within_budget(geometry: &Geometry, budget: usize) -> Option<(Geometry, f64)> {
+    if geometry.vertex_count() <= budget {
+        return Some((geometry.clone(), 0.0));
+    }
+
+    let (simplified, tolerance) = simplify_to_vertex_count(geometry, budget);
+    if simplified.vertex_count() > budget {
+        return None;
+    }
+
+    let polygonal = matches!(simplified, Geometry::Polygon { .. } | Geometry::MultiPolygon { .. });
+    if polygonal && !to_geo_geometry(&simplified).is_valid() {
+        return None;
+    }
+
+    Some((simplified, tolerance))
+}
+
+/// Simplify every line and ring of a geometry with the given tolerance
+///
+/// Holes that collapse are dropped; shells keep at least a triangle.
+pub fn simplify(geometry: &Geometry, tolerance: f64) -> Geometry {
+    Ranked::new(geometry).simplify(tolerance)
+}
+
+/// A geometry with the Douglas-Peucker significance of each vertex
+///
+/// Simplifying with a tolerance keeps the vertices whose significance is
+/// above it, the same vertices Douglas-Peucker keeps at that tolerance.
+struct Ranked<'a> {
+    geometry: &'a Geometry,
+    /// Significance of each vertex of every line and ring, in geometry order
+    lines: Vec<Vec<f64>>,
+}
+
+impl<'a> Ranked<'a> {
+    fn new(geometry: &'a Geometry) -> Self {
+        let lines = match geometry {
+            Geometry::Point { .. } | Geometry::MultiPoint { .. } => Vec::new(),
+            Geometry::LineString { coordinates } => vec![significance(coordinates)],
+            Geometry::MultiLineString { coordinates } => {
+                coordinates.iter().map(|line| significance(line)).collect()
+            }
+            Geometry::Polygon { coordinates } => ring_significance(coordinates),
+            Geometry::MultiPolygon { coordinates } => {
+                coordinates.iter().flat_map(|rings| ring_significance(rings)).collect()
+            }
+        };
+        Self { geometry, lines }
+    }
+
+    fn simplify(&self, tolerance: f64) -> Geometry {
+        let mut lines = self.lines.iter();
+        let mut keep = |points: &[[f64; 2]]| -> Vec<[f64; 2]> {
+            let significance = lines.next().expect("every line is ranked");
+            points
+                .iter()
+                .zip(significance)
+                .filter(|(_, significance)| **significance > tolerance)
+                .map(|(point, _)| *point)
+                .collect()
+        };
+
+        match self.geometry {
+            Geometry::Point { .. } | Geometry::MultiPoint { .. } => self.geometry.clone(),
+            Geometry::LineString { coordinates } => {
+                Geometry::LineString { coordinates: keep(coordinates) }
+            }
+            Geometry::MultiLineString { coordinates } => Geometry::MultiLineString {
+                coordinates: coordinates.iter().map(|line| keep(line)).collect(),
+            },
+            Geometry::Polygon { coordinates } => Geometry::Polygon {
+                coordinates: keep_rings(coordinates, &mut keep),
+            },
+            Geometry::MultiPolygon { coordinates } => Geometry::MultiPolygon {
+                coordinates: coordinates.iter().map(|rings| keep_rings(rings, &mut keep)).collect(),
+            },
+        }
+    }
+}
+
+/// Simplify polygon rings, dropping holes that collapse
+fn keep_rings(
+    rings: &[Vec<[f64; 2]>],
+    keep: &mut impl FnMut(&[[f64; 2]]) -> Vec<[f64; 2]>,
+) -> Vec<Vec<[f64; 2]>> {
+    let mut kept = Vec::with_capacity(rings.len());
+    for (i, ring) in rings.iter().enumerate() {
+        let ring = keep(ring);
+        if i == 0 || ring.len() >= 4 {
+            kept.push(ring);
+        }
+    }
+    kept
+}
+
+/// Significance of the vertices of a polygon's rings
+fn ring_significance(rings: &[Vec<[f64; 2]>]) -> Vec<Vec<f64>> {
+    rings
+        .iter()
+        .enumerate()
+        .map(|(i, ring)| {
+            if i == 0 {
+                shell_significance(ring)
+            } else {
+                significance(ring)
+            }
+        })
+        .collect()
+}
+
+/// Significance of the vertices of a polygon shell, which keeps a triangle
+///
+/// The first and last vertex of a closed ring coincide, so Douglas-Peucker
+/// between them would collapse the ring at any tolerance wider than the
+/// ring. The vertex farthest from the start and the vertex farthest from
+/// that diagonal are always kept, and each stretch between them is ranked
+/// on its own.
+fn shell_significance(ring: &[[f64; 2]]) -> Vec<f64> {
+    let mut ranked = vec![f64::INFINITY; ring.len()];
+    if ring.len() < 5 {
+        return ranked;
+    }
+
+    let last = ring.len() - 1;
+    let distance = |[x, y]: [f64; 2]| ((x - ring[0][0]).powi(2) + (y - ring[0][1]).powi(2)).sqrt();
+    let far = farthest(1..last, |i| distance(ring[i]));
+    let wide = farthest((1..last).filter(|&i| i != far), |i| {
+        segment_distance(ring[i], ring[0], ring[far])
+    });
+
+    let anchors = [0, far.min(wide), far.max(wide), last];
+    for pair in anchors.windows(2) {
+        ranked[pair[0]..=pair[1]].copy_from_slice(&significance(&ring[pair[0]..=pair[1]]));
+    }
+    ranked
+}
+
+/// Index whose distance is largest, the first of them on ties
+fn farthest(indices: impl Iterator<Item = usize>, distance: impl Fn(usize) -> f64) -> usize {
+    let mut best = (0, f64::NEG_INFINITY);
+    for i in indices {
+        let d = distance(i);
+        if d > best.1 {
+            best = (i, d);
+        }
+    }
+    best.0
+}
+
+/// Douglas-Peucker significance of each vertex of a coordinate sequence
+///
+/// Douglas-Peucker keeps a vertex when the distance that selects it, and the
+/// distances that selected the vertices around it, are above the tolerance;
+/// the smallest of them is its significance. The end points are always kept.
+fn significance(points: &[[f64; 2]]) -> Vec<f64> {
+    let mut ranked = vec![0.0; points.len()];
+    let Some(last) = points.len().checked_sub(1) else {
+        return ranked;
+    };
+    ranked[0] = f64::INFINITY;
+    ranked[last] = f64::INFINITY;
+
+    let mut stack = vec![(0, last, f64::INFINITY)];
+    while let Some((start, end, bound)) = stack.pop() {
+        let mut max_distance = 0.0;
+        let mut index = start;
+        for i in (start + 1)..end {
+            let distance = segment_distance(points[i], points[start], points[end]);
+            if distance > max_distance {
+                max_distance = distance;
+                index = i;
+            }
+        }
+
+        if max_distance > 0.0 {
+            let selected = max_distance.min(bound);
+            ranked[index] = selected;
+            stack.push((start, index, selected));
+            stack.push((index, end, selected));
+        }
+    }
+
+    ranked
+}
+
+/// Distance from a point to a segment
+fn segment_distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length_sq = dx * dx + dy * dy;
+    if length_sq == 0.0 {
+        return ((p[0] - a[0]).powi(2) + (p[1] - a[1]).powi(2)).sqrt();
+    }
+
+    let t = (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / length_sq).clamp(0.0, 1.0);
+    let (x, y) = (a[0] + t * dx, a[1] + t * dy);
+    ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt()
+}
+
+/// Diagonal of the geometry's bounding box, an upper bound for useful tolerances
+fn extent(geometry: &Geometry) -> f64 {
+    let coords: Vec<[f64; 2]> = match geometry {
+        Geometry::Point { coordinates } => vec![*coordinates],
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.clone()
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter().flatten().copied().collect()
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter().flatten().flatten().copied().collect()
+        }
+    };
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) =
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for [x, y] in coords {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+
+    if min_x > max_x {
+        return 0.0;
+    }
+    ((max_x - min_x).powi(2) + (max_y - min_y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Circle-like polygon with `n` vertices plus the closing vertex
+    fn detailed_polygon(n: usize) -> Geometry {
+        let mut ring: Vec<[f64; 2]> = (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+                // Small wobble so every vertex carries some detail
+                let radius = 1.0 + 0.01 * (i % 7) as f64;
+                [radius * angle.cos(), radius * angle.sin()]
+            })
+            .collect();
+        ring.push(ring[0]);
+        Geometry::polygon(vec![ring])
+    }
+
+    #[test]
+    fn test_vertex_count() {
+        assert_eq!(Geometry::point(0.0, 0.0).vertex_count(), 1);
+        assert_eq!(detailed_polygon(100).vertex_count(), 101);
+    }
+
+    #[test]
+    fn test_geometry_under_limit_is_unchanged() {
+        let polygon = detailed_polygon(100);
+        let (result, note) = GeometryLimits::default().apply(&polygon).unwrap();
+
+        assert_eq!(result, polygon);
+        assert!(note.is_none());
+    }
+
+    #[test]
+    fn test_geometry_over_limit_is_rejected() {
+        let limits = GeometryLimits { max_vertices: 500, auto_simplify: false };
+        let err = limits.apply(&detailed_polygon(2000)).unwrap_err();
+
+        assert!(matches!(err, GeoragError::GeometryTooComplex { vertices: 2001, limit: 500 }));
+    }
+
+    #[test]
+    fn test_geometry_over_limit_is_simplified() {
+        let limits = GeometryLimits { max_vertices: 500, auto_simplify: true };
+        let (result, note) = limits.apply(&detailed_polygon(2000)).unwrap();
+        let note = note.expect("simplification should be recorded");
+
+        assert!(result.vertex_count() <= 500);
+        assert_eq!(note.original_vertices, 2001);
+        assert_eq!(note.simplified_vertices, result.vertex_count());
+        // Bisection keeps as much detail as the target allows
+        assert!(result.vertex_count() > 100, "over-simplified to {}", result.vertex_count());
+    }
+
+    #[test]
+    fn test_simplify_within_budget_keeps_polygon_valid() {
+        // 100k vertices down to a budget of 1000
+        let polygon = detailed_polygon(100_000);
+        let (simplified, tolerance) = simplify_within_budget(&polygon, 1000).unwrap();
+
+        assert!(simplified.vertex_count() <= 1000);
+        assert!(
+            simplified.vertex_count() > 100,
+            "over-simplified to {}",
+            simplified.vertex_count()
+        );
+        assert!(tolerance > 0.0);
+        assert!(to_geo_geometry(&simplified).is_valid());
+
+        let small = detailed_polygon(50);
+        assert_eq!(simplify_within_budget(&small, 1000), Some((small, 0.0)));
+    }
+
+    #[test]
+    fn test_simplify_within_budget_refuses_broken_rings() {
+        // A square with a notch, and a triangle sitting in the notch
+        let notched = vec![
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [6.0, 10.0],
+            [5.0, 7.0],
+            [4.0, 10.0],
+            [0.0, 10.0],
+            [0.0, 0.0],
+        ];
+        let triangle = vec![[4.8, 9.5], [5.2, 9.5], [5.0, 8.0], [4.8, 9.5]];
+        let shapes = Geometry::MultiPolygon {
+            coordinates: vec![vec![notched], vec![triangle]],
+        };
+        assert!(to_geo_geometry(&shapes).is_valid());
+
+        // Cutting the notch's shoulders keeps the triangle outside the square
+        let (simplified, _) = simplify_within_budget(&shapes, 10).unwrap();
+        assert_eq!(simplified.vertex_count(), 10);
+
+        // Filling the notch would cover the triangle
+        assert_eq!(simplify_within_budget(&shapes, 9), None);
+
+        // Points cannot be simplified
+        let points = Geometry::MultiPoint { coordinates: vec![[0.0, 0.0]; 20] };
+        assert_eq!(simplify_within_budget(&points, 10), None);
+    }
+
+    #[test]
+    fn test_shell_keeps_a_triangle() {
+        let square = detailed_polygon(4);
+        let Geometry::Polygon { coordinates } = simplify(&square, 100.0) else {
+            unreachable!()
+        };
+        assert_eq!(coordinates[0].len(), 4);
+        assert_eq!(coordinates[0].first(), coordinates[0].last());
+    }
+
+    #[test]
+    fn test_douglas_peucker_removes_collinear_points() {
+        let line = Geometry::LineString {
+            coordinates: vec![[0.0, 0.0], [1.0, 0.0], [2.0, 0.0], [3.0, 1.0]],
+        };
+        let expected = Geometry::LineString {
+            coordinates: vec![[0.0, 0.0], [2.0, 0.0], [3.0, 1.0]],
+        };
+        assert_eq!(simplify(&line, 0.1), expected);
+    }
+}
@@ -3,73 +3,371 @@ use geo::algorithm::bounding_rect::BoundingRect;
 use geo::algorithm::centroid::Centroid;
 use geo::algorithm::contains::Contains;
 use geo::algorithm::intersects::Intersects;
+use geo::algorithm::relate::{IntersectionMatrix, Relate};
 use geo::{Distance, Geometry as GeoGeometry, Haversine, Point, Rect};
 
 /// Evaluate if a geometry satisfies a spatial filter
+///
+/// This prepares the filter for a single evaluation. When testing many
+/// geometries against the same filter, build a [`PreparedFilter`] once instead.
 pub fn evaluate_spatial_filter(geometry: &Geometry, filter: &SpatialFilter) -> bool {
-    // DWithin is special: it can work without a filter geometry (using distance from origin)
-    if filter.predicate == SpatialPredicate::DWithin {
-        return evaluate_dwithin(geometry, filter);
+    PreparedFilter::new(filter).evaluate(geometry)
+}
+
+/// A spatial filter prepared for evaluation against many geometries
+///
+/// The filter geometry is converted and its bounding box computed once, and
+/// polygon edges are indexed into horizontal bands so point-in-polygon tests
+/// only visit the edges near the point instead of every ring vertex. The
+/// prepared filter owns a copy of its filter, so it can be shared across
+/// queries through a [`FilterCache`](crate::geo::FilterCache).
+///
+/// Exclusion areas are prepared the same way, as intersects filters, so a
+/// geometry outside their bounding boxes is kept without an exact test.
+pub struct PreparedFilter {
+    filter: SpatialFilter,
+    geometry: Option<GeoGeometry>,
+    bbox: Option<Rect>,
+    rings: Option<RingIndex>,
+    exclusions: Vec<PreparedFilter>,
+}
+
+impl PreparedFilter {
+    /// Prepare a spatial filter
+    pub fn new(filter: &SpatialFilter) -> Self {
+        Self::from_filter(filter.clone())
     }
 
-    // For other predicates, if no filter geometry is specified, return true (no spatial constraint)
-    let filter_geom = match &filter.geometry {
-        Some(g) => g,
-        None => return true,
-    };
+    /// Prepare a spatial filter, taking ownership of it
+    pub fn from_filter(filter: SpatialFilter) -> Self {
+        let geometry = filter.geometry.as_ref().map(to_geo_geometry);
+        let bbox = geometry.as_ref().and_then(|g| g.bounding_rect());
+        let rings = filter.geometry.as_ref().and_then(RingIndex::new);
+        let exclusions = filter
+            .exclude
+            .iter()
+            .map(|area| {
+                PreparedFilter::from_filter(
+                    SpatialFilter::new(SpatialPredicate::Intersects).geometry(area.clone()),
+                )
+            })
+            .collect();
+
+        Self {
+            filter,
+            geometry,
+            bbox,
+            rings,
+            exclusions,
+        }
+    }
 
-    match filter.predicate {
-        SpatialPredicate::Within => evaluate_within(geometry, filter_geom),
-        SpatialPredicate::Intersects => evaluate_intersects(geometry, filter_geom),
-        SpatialPredicate::Contains => evaluate_contains(geometry, filter_geom),
-        SpatialPredicate::BoundingBox => evaluate_bounding_box(geometry, filter_geom),
-        SpatialPredicate::DWithin => unreachable!(),
+    /// The filter this was prepared from
+    pub fn filter(&self) -> &SpatialFilter {
+        &self.filter
     }
-}
 
-/// Check if geometry is within the filter geometry
-fn evaluate_within(geometry: &Geometry, filter: &Geometry) -> bool {
-    let geo_geom = to_geo_geometry(geometry);
-    let filter_geom = to_geo_geometry(filter);
+    /// Bounding box of the filter geometry
+    pub fn bounding_rect(&self) -> Option<Rect> {
+        self.bbox
+    }
 
-    // Within means the geometry is completely inside the filter
-    filter_geom.contains(&geo_geom)
-}
+    /// Check if this evaluates the same as a filter
+    ///
+    /// The predicate, distance, geometry and exclusions decide how a filter
+    /// evaluates; its CRS and buffer were applied before it was prepared.
+    pub fn is_prepared_from(&self, filter: &SpatialFilter) -> bool {
+        self.filter.predicate == filter.predicate
+            && self.filter.distance == filter.distance
+            && self.filter.geometry == filter.geometry
+            && self.filter.exclude == filter.exclude
+    }
 
-/// Check if geometry intersects the filter geometry
-fn evaluate_intersects(geometry: &Geometry, filter: &Geometry) -> bool {
-    let geo_geom = to_geo_geometry(geometry);
-    let filter_geom = to_geo_geometry(filter);
+    /// Check if the filter removes geometries in exclusion areas
+    pub fn has_exclusions(&self) -> bool {
+        !self.exclusions.is_empty()
+    }
 
-    geo_geom.intersects(&filter_geom)
-}
+    /// Bounding boxes of the exclusion areas
+    pub fn exclusion_bounds(&self) -> impl Iterator<Item = Rect> + '_ {
+        self.exclusions.iter().filter_map(|exclusion| exclusion.bbox)
+    }
+
+    /// Evaluate if a geometry satisfies the filter
+    ///
+    /// The geometry must match the predicate and stay clear of every
+    /// exclusion area.
+    pub fn evaluate(&self, geometry: &Geometry) -> bool {
+        self.includes(geometry) && !self.excludes(geometry)
+    }
 
-/// Check if geometry contains the filter geometry
-fn evaluate_contains(geometry: &Geometry, filter: &Geometry) -> bool {
-    let geo_geom = to_geo_geometry(geometry);
-    let filter_geom = to_geo_geometry(filter);
+    /// Check if a geometry intersects any exclusion area
+    pub fn excludes(&self, geometry: &Geometry) -> bool {
+        self.exclusions.iter().any(|exclusion| exclusion.includes(geometry))
+    }
+
+    /// Evaluate the predicate alone, ignoring the exclusion areas
+    pub fn includes(&self, geometry: &Geometry) -> bool {
+        let Some(filter_geom) = &self.geometry else {
+            // DWithin needs a geometry to measure from; other predicates are unconstrained
+            return self.filter.predicate != SpatialPredicate::DWithin;
+        };
+
+        match self.filter.predicate {
+            SpatialPredicate::Within => self.evaluate_within(geometry, filter_geom),
+            SpatialPredicate::Intersects => self.evaluate_intersects(geometry, filter_geom),
+            SpatialPredicate::Contains => self.evaluate_contains(geometry, filter_geom),
+            SpatialPredicate::CoveredBy => self.evaluate_covered_by(geometry, filter_geom),
+            SpatialPredicate::Touches => self.evaluate_touches(geometry, filter_geom),
+            SpatialPredicate::Crosses => {
+                self.evaluate_relation(geometry, filter_geom, IntersectionMatrix::is_crosses)
+            }
+            SpatialPredicate::Overlaps => {
+                self.evaluate_relation(geometry, filter_geom, IntersectionMatrix::is_overlaps)
+            }
+            SpatialPredicate::BoundingBox => self.evaluate_bounding_box(geometry),
+            SpatialPredicate::DWithin => self.evaluate_dwithin(geometry, filter_geom),
+        }
+    }
+
+    /// Check if geometry is within the filter geometry
+    fn evaluate_within(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        if let (Geometry::Point { coordinates }, Some(rings)) = (geometry, &self.rings) {
+            // The boundary is not part of the interior
+            return rings.contains_point(*coordinates) && !rings.on_boundary(*coordinates);
+        }
+
+        let geo_geom = to_geo_geometry(geometry);
+        match (geo_geom.bounding_rect(), &self.bbox) {
+            (Some(geom_bbox), Some(filter_bbox)) if !rect_contains(filter_bbox, &geom_bbox) => {
+                false
+            }
+            // Within means the geometry is completely inside the filter
+            _ => filter_geom.contains(&geo_geom),
+        }
+    }
+
+    /// Check if geometry intersects the filter geometry
+    fn evaluate_intersects(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        if let (Geometry::Point { coordinates }, Some(rings)) = (geometry, &self.rings) {
+            return rings.contains_point(*coordinates) || rings.on_boundary(*coordinates);
+        }
+
+        let geo_geom = to_geo_geometry(geometry);
+        match (geo_geom.bounding_rect(), &self.bbox) {
+            (Some(geom_bbox), Some(filter_bbox))
+                if !bounding_boxes_intersect(&geom_bbox, filter_bbox) =>
+            {
+                false
+            }
+            _ => geo_geom.intersects(filter_geom),
+        }
+    }
+
+    /// Check if geometry contains the filter geometry
+    fn evaluate_contains(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        let geo_geom = to_geo_geometry(geometry);
+        match (geo_geom.bounding_rect(), &self.bbox) {
+            (Some(geom_bbox), Some(filter_bbox)) if !rect_contains(&geom_bbox, filter_bbox) => {
+                false
+            }
+            _ => geo_geom.contains(filter_geom),
+        }
+    }
+
+    /// Check if geometry has no point outside the filter geometry
+    fn evaluate_covered_by(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        if let (Geometry::Point { coordinates }, Some(rings)) = (geometry, &self.rings) {
+            return rings.contains_point(*coordinates) || rings.on_boundary(*coordinates);
+        }
+
+        let geo_geom = to_geo_geometry(geometry);
+        match (geo_geom.bounding_rect(), &self.bbox) {
+            (Some(geom_bbox), Some(filter_bbox)) if !rect_contains(filter_bbox, &geom_bbox) => {
+                false
+            }
+            _ => geo_geom.relate(filter_geom).is_coveredby(),
+        }
+    }
+
+    /// Check if geometry meets the filter geometry only at their boundaries
+    fn evaluate_touches(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        if let (Geometry::Point { coordinates }, Some(rings)) = (geometry, &self.rings) {
+            return rings.on_boundary(*coordinates);
+        }
+
+        self.evaluate_relation(geometry, filter_geom, IntersectionMatrix::is_touches)
+    }
+
+    /// Check a DE-9IM relation that needs the geometries to intersect
+    ///
+    /// Geometries whose bounding boxes are disjoint are rejected before the
+    /// intersection matrix is computed.
+    fn evaluate_relation(
+        &self,
+        geometry: &Geometry,
+        filter_geom: &GeoGeometry,
+        relation: fn(&IntersectionMatrix) -> bool,
+    ) -> bool {
+        let geo_geom = to_geo_geometry(geometry);
+        match (geo_geom.bounding_rect(), &self.bbox) {
+            (Some(geom_bbox), Some(filter_bbox))
+                if !bounding_boxes_intersect(&geom_bbox, filter_bbox) =>
+            {
+                false
+            }
+            _ => relation(&geo_geom.relate(filter_geom)),
+        }
+    }
+
+    /// Check if geometry's bounding box intersects the filter's bounding box
+    fn evaluate_bounding_box(&self, geometry: &Geometry) -> bool {
+        let Some(filter_bbox) = &self.bbox else {
+            return false;
+        };
+
+        match to_geo_geometry(geometry).bounding_rect() {
+            Some(geom_bbox) => bounding_boxes_intersect(&geom_bbox, filter_bbox),
+            None => false,
+        }
+    }
+
+    /// Evaluate DWithin predicate (distance within threshold)
+    /// Requires both a filter geometry and a distance to be specified.
+    fn evaluate_dwithin(&self, geometry: &Geometry, filter_geom: &GeoGeometry) -> bool {
+        // DWithin requires a distance threshold
+        let distance = match &self.filter.distance {
+            Some(d) => d,
+            None => return false,
+        };
+
+        let threshold_meters = distance.to_meters();
+
+        // Calculate geodesic distance and compare to threshold
+        match geo_distance(&to_geo_geometry(geometry), filter_geom) {
+            Some(dist) => dist <= threshold_meters,
+            None => false,
+        }
+    }
+}
 
-    geo_geom.contains(&filter_geom)
+/// Polygon edges bucketed into horizontal bands for fast point-in-polygon tests
+struct RingIndex {
+    edges: Vec<([f64; 2], [f64; 2])>,
+    bands: Vec<Vec<usize>>,
+    min: [f64; 2],
+    max: [f64; 2],
+    band_height: f64,
 }
 
-/// Check if geometry's bounding box intersects the filter's bounding box
-fn evaluate_bounding_box(geometry: &Geometry, filter: &Geometry) -> bool {
-    let geo_geom = to_geo_geometry(geometry);
-    let filter_geom = to_geo_geometry(filter);
+impl RingIndex {
+    /// Target number of edges per band
+    const EDGES_PER_BAND: usize = 4;
+
+    /// Index the rings of a polygon or multipolygon (None for other geometries)
+    fn new(geometry: &Geometry) -> Option<Self> {
+        let rings: Vec<&Vec<[f64; 2]>> = match geometry {
+            Geometry::Polygon { coordinates } => coordinates.iter().collect(),
+            Geometry::MultiPolygon { coordinates } => coordinates.iter().flatten().collect(),
+            _ => return None,
+        };
+
+        let mut edges = Vec::new();
+        for ring in rings {
+            edges.extend(ring.windows(2).map(|w| (w[0], w[1])));
+            // Close rings that do not repeat their first vertex
+            if let (Some(first), Some(last)) = (ring.first(), ring.last()) {
+                if first != last {
+                    edges.push((*last, *first));
+                }
+            }
+        }
+        if edges.is_empty() {
+            return None;
+        }
 
-    // Get bounding rectangles
-    let geom_bbox = match geo_geom.bounding_rect() {
-        Some(bbox) => bbox,
-        None => return false,
-    };
+        let mut min = [f64::INFINITY; 2];
+        let mut max = [f64::NEG_INFINITY; 2];
+        for (a, b) in &edges {
+            for p in [a, b] {
+                min = [min[0].min(p[0]), min[1].min(p[1])];
+                max = [max[0].max(p[0]), max[1].max(p[1])];
+            }
+        }
 
-    let filter_bbox = match filter_geom.bounding_rect() {
-        Some(bbox) => bbox,
-        None => return false,
-    };
+        let band_count = (edges.len() / Self::EDGES_PER_BAND).max(1);
+        let band_height = ((max[1] - min[1]) / band_count as f64).max(f64::MIN_POSITIVE);
+        let mut index = Self {
+            edges,
+            bands: vec![Vec::new(); band_count],
+            min,
+            max,
+            band_height,
+        };
+
+        let spans: Vec<(usize, usize)> = index
+            .edges
+            .iter()
+            .map(|(a, b)| (index.band(a[1].min(b[1])), index.band(a[1].max(b[1]))))
+            .collect();
+        for (i, (first, last)) in spans.into_iter().enumerate() {
+            for band in &mut index.bands[first..=last] {
+                band.push(i);
+            }
+        }
+
+        Some(index)
+    }
+
+    fn band(&self, y: f64) -> usize {
+        (((y - self.min[1]) / self.band_height) as usize).min(self.bands.len() - 1)
+    }
+
+    /// Check if the point lies exactly on one of the ring edges
+    ///
+    /// The even-odd test below is undefined for such points, so predicates
+    /// that treat the boundary differently check it first.
+    fn on_boundary(&self, [x, y]: [f64; 2]) -> bool {
+        if x < self.min[0] || x > self.max[0] || y < self.min[1] || y > self.max[1] {
+            return false;
+        }
 
-    // Check if bounding boxes intersect
-    bounding_boxes_intersect(&geom_bbox, &filter_bbox)
+        self.bands[self.band(y)].iter().any(|&i| {
+            let (a, b) = self.edges[i];
+            let in_span = x >= a[0].min(b[0])
+                && x <= a[0].max(b[0])
+                && y >= a[1].min(b[1])
+                && y <= a[1].max(b[1]);
+            in_span && (b[0] - a[0]) * (y - a[1]) == (b[1] - a[1]) * (x - a[0])
+        })
+    }
+
+    /// Even-odd point-in-polygon test against the edges crossing the point's band
+    fn contains_point(&self, [x, y]: [f64; 2]) -> bool {
+        if x < self.min[0] || x > self.max[0] || y < self.min[1] || y > self.max[1] {
+            return false;
+        }
+
+        let mut inside = false;
+        for &i in &self.bands[self.band(y)] {
+            let (a, b) = self.edges[i];
+            if (a[1] > y) != (b[1] > y) {
+                let crossing = a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+                if x < crossing {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+/// Check if the outer rectangle fully covers the inner one
+fn rect_contains(outer: &Rect, inner: &Rect) -> bool {
+    outer.min().x <= inner.min().x
+        && outer.min().y <= inner.min().y
+        && outer.max().x >= inner.max().x
+        && outer.max().y >= inner.max().y
 }
 
 /// Check if two bounding boxes intersect
@@ -84,10 +382,11 @@ fn bounding_boxes_intersect(bbox1: &Rect, bbox2: &Rect) -> bool {
 /// Calculate geodesic distance between two geometries in meters
 /// Returns None if centroids cannot be computed (e.g., empty geometries).
 pub fn geodesic_distance(geom1: &Geometry, geom2: &Geometry) -> Option<f64> {
-    let geo1 = to_geo_geometry(geom1);
-    let geo2 = to_geo_geometry(geom2);
+    geo_distance(&to_geo_geometry(geom1), &to_geo_geometry(geom2))
+}
 
-    match (&geo1, &geo2) {
+fn geo_distance(geo1: &GeoGeometry, geo2: &GeoGeometry) -> Option<f64> {
+    match (geo1, geo2) {
         // Exact point-to-point distance using Haversine
         (GeoGeometry::Point(p1), GeoGeometry::Point(p2)) => Some(Haversine.distance(*p1, *p2)),
         // For other geometries, compute centroids and measure distance between them
@@ -99,36 +398,13 @@ pub fn geodesic_distance(geom1: &Geometry, geom2: &Geometry) -> Option<f64> {
     }
 }
 
-/// Evaluate DWithin predicate (distance within threshold)
-/// Requires both a filter geometry and a distance to be specified.
-fn evaluate_dwithin(geometry: &Geometry, filter: &SpatialFilter) -> bool {
-    // DWithin requires a filter geometry
-    let filter_geom = match &filter.geometry {
-        Some(g) => g,
-        None => return false,
-    };
-
-    // DWithin requires a distance threshold
-    let distance = match &filter.distance {
-        Some(d) => d,
-        None => return false,
-    };
-
-    let threshold_meters = distance.to_meters();
-
-    // Calculate geodesic distance and compare to threshold
-    match geodesic_distance(geometry, filter_geom) {
-        Some(dist) => dist <= threshold_meters,
-        None => false,
-    }
-}
-
 /// Filter a collection of geometries by a spatial filter
 pub fn filter_geometries(geometries: &[(Geometry, usize)], filter: &SpatialFilter) -> Vec<usize> {
+    let prepared = PreparedFilter::new(filter);
     geometries
         .iter()
         .filter_map(|(geom, idx)| {
-            if evaluate_spatial_filter(geom, filter) {
+            if prepared.evaluate(geom) {
                 Some(*idx)
             } else {
                 None
@@ -139,7 +415,8 @@ pub fn filter_geometries(geometries: &[(Geometry, usize)], filter: &SpatialFilte
 
 /// Count how many geometries satisfy a spatial filter
 pub fn count_spatial_matches(geometries: &[Geometry], filter: &SpatialFilter) -> usize {
-    geometries.iter().filter(|geom| evaluate_spatial_filter(geom, filter)).count()
+    let prepared = PreparedFilter::new(filter);
+    geometries.iter().filter(|geom| prepared.evaluate(geom)).count()
 }
 
 #[cfg(test)]
@@ -173,6 +450,143 @@ mod tests {
         assert!(!evaluate_spatial_filter(&point_outside, &filter));
     }
 
+    #[test]
+    fn test_prepared_filter_respects_holes() {
+        let with_hole = Geometry::polygon(vec![
+            vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+            vec![[4.0, 4.0], [6.0, 4.0], [6.0, 6.0], [4.0, 6.0], [4.0, 4.0]],
+        ]);
+        let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(with_hole);
+        let prepared = PreparedFilter::new(&filter);
+
+        assert!(prepared.evaluate(&Geometry::point(2.0, 2.0)));
+        assert!(!prepared.evaluate(&Geometry::point(5.0, 5.0)), "point in hole");
+        assert!(!prepared.evaluate(&Geometry::point(12.0, 5.0)));
+    }
+
+    #[test]
+    fn test_exclusions_remove_intersecting_geometries() {
+        let rect = |x0: f64, y0: f64, x1: f64, y1: f64| {
+            Geometry::polygon(vec![vec![[x0, y0], [x1, y0], [x1, y1], [x0, y1], [x0, y0]]])
+        };
+        let points: Vec<Geometry> = (0..10).map(|i| Geometry::point(i as f64 + 0.5, 5.0)).collect();
+        let matched = |filter: &SpatialFilter| {
+            let prepared = PreparedFilter::new(filter);
+            points.iter().filter(|p| prepared.evaluate(p)).count()
+        };
+        let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(square_polygon());
+        assert_eq!(matched(&filter), 10);
+
+        // Covering the whole filter, overlapping its west half, missing it
+        assert_eq!(matched(&filter.clone().exclude(rect(-1.0, -1.0, 11.0, 11.0))), 0);
+        assert_eq!(matched(&filter.clone().exclude(rect(-1.0, -1.0, 5.0, 11.0))), 5);
+        assert_eq!(matched(&filter.clone().exclude(rect(20.0, 20.0, 30.0, 30.0))), 10);
+
+        // A line touching an exclusion is removed although it is inside the filter
+        let prepared = PreparedFilter::new(&filter.clone().exclude(rect(4.0, 4.0, 6.0, 6.0)));
+        let line = Geometry::LineString {
+            coordinates: vec![[1.0, 1.0], [4.0, 4.0]],
+        };
+        assert!(prepared.includes(&line));
+        assert!(prepared.excludes(&line));
+        assert!(!prepared.evaluate(&line));
+
+        // Without a filter geometry only the exclusions constrain
+        let unbounded =
+            SpatialFilter::new(SpatialPredicate::Intersects).exclude(rect(-1.0, -1.0, 5.0, 11.0));
+        assert_eq!(matched(&unbounded), 5);
+    }
+
+    #[test]
+    fn test_prepared_filter_matches_unprepared_geo() {
+        let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(square_polygon());
+        let prepared = PreparedFilter::new(&filter);
+        let filter_geom = to_geo_geometry(filter.geometry.as_ref().unwrap());
+
+        for i in 0..40 {
+            for j in 0..40 {
+                let point = Geometry::point(i as f64 * 0.37 - 2.0, j as f64 * 0.41 - 3.0);
+                assert_eq!(
+                    prepared.evaluate(&point),
+                    filter_geom.contains(&to_geo_geometry(&point)),
+                    "mismatch at {:?}",
+                    point
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_boundary_points_follow_de9im() {
+        let on_edge = Geometry::point(10.0, 5.0);
+        let on_vertex = Geometry::point(0.0, 0.0);
+        let filter_geom = to_geo_geometry(&square_polygon());
+
+        for point in [&on_edge, &on_vertex] {
+            let matches = |predicate| {
+                evaluate_spatial_filter(
+                    point,
+                    &SpatialFilter::new(predicate).geometry(square_polygon()),
+                )
+            };
+            let relation = to_geo_geometry(point).relate(&filter_geom);
+
+            assert!(!matches(SpatialPredicate::Within));
+            assert!(matches(SpatialPredicate::CoveredBy));
+            assert!(matches(SpatialPredicate::Intersects));
+            assert!(!relation.is_within() && relation.is_coveredby() && relation.is_intersects());
+        }
+    }
+
+    #[test]
+    fn test_prepared_point_predicates_match_relate_on_grid() {
+        // Half-unit steps land on edges and vertices of the square
+        let filter_geom = to_geo_geometry(&square_polygon());
+        for predicate in [
+            SpatialPredicate::Within,
+            SpatialPredicate::CoveredBy,
+            SpatialPredicate::Intersects,
+            SpatialPredicate::Touches,
+        ] {
+            let filter = SpatialFilter::new(predicate).geometry(square_polygon());
+            let prepared = PreparedFilter::new(&filter);
+
+            for i in 0..30 {
+                for j in 0..30 {
+                    let point = Geometry::point(i as f64 * 0.5 - 2.5, j as f64 * 0.5 - 2.5);
+                    let relation = to_geo_geometry(&point).relate(&filter_geom);
+                    let expected = match predicate {
+                        SpatialPredicate::Within => relation.is_within(),
+                        SpatialPredicate::CoveredBy => relation.is_coveredby(),
+                        SpatialPredicate::Touches => relation.is_touches(),
+                        _ => relation.is_intersects(),
+                    };
+                    assert_eq!(
+                        prepared.evaluate(&point),
+                        expected,
+                        "{:?} mismatch at {:?}",
+                        predicate,
+                        point
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_reads_feature_first() {
+        let big = square_polygon();
+        let small = Geometry::point(5.0, 5.0);
+
+        let polygon_contains_point =
+            SpatialFilter::new(SpatialPredicate::Contains).geometry(small.clone());
+        let point_contains_polygon =
+            SpatialFilter::new(SpatialPredicate::Contains).geometry(big.clone());
+
+        assert!(evaluate_spatial_filter(&big, &polygon_contains_point));
+        assert!(!evaluate_spatial_filter(&small, &point_contains_polygon));
+    }
+
     #[test]
     fn test_intersects() {
         // Create two overlapping polygons
@@ -0,0 +1,469 @@
+//! Vertex limits for ingested features and subdivision of oversized ones
+//!
+//! A single feature with hundreds of thousands of vertices (a coastline, a
+//! country outline) makes every spatial test against it slow and every chunk
+//! built from it huge. `FeatureLimits` caps the vertices of one feature;
+//! features over the cap are rejected, simplified, or cut into tiles the way
+//! PostGIS `ST_Subdivide` does: the bounding box is halved along its longer
+//! side until every piece fits.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::Geometry;
+
+/// Default maximum number of vertices in one ingested feature
+pub const DEFAULT_MAX_FEATURE_VERTICES: usize = 100_000;
+
+/// Smallest vertex limit subdivision works with; each cut adds vertices on the cut line
+pub const MIN_SUBDIVIDE_VERTICES: usize = 8;
+
+/// Maximum number of times a geometry is halved
+const MAX_SUBDIVIDE_DEPTH: usize = 32;
+
+/// What ingest does with a feature over the vertex limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizedFeatures {
+    /// Reject the feature like an unreadable one
+    Reject,
+
+    /// Simplify the feature to the limit, rejecting it if that is not possible
+    Simplify,
+
+    /// Cut the feature into tiles that fit the limit
+    #[default]
+    Subdivide,
+}
+
+impl fmt::Display for OversizedFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OversizedFeatures::Reject => write!(f, "reject"),
+            OversizedFeatures::Simplify => write!(f, "simplify"),
+            OversizedFeatures::Subdivide => write!(f, "subdivide"),
+        }
+    }
+}
+
+impl FromStr for OversizedFeatures {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reject" => Ok(OversizedFeatures::Reject),
+            "simplify" => Ok(OversizedFeatures::Simplify),
+            "subdivide" => Ok(OversizedFeatures::Subdivide),
+            other => Err(format!(
+                "unknown oversized feature handling '{}': expected reject, simplify or subdivide",
+                other
+            )),
+        }
+    }
+}
+
+/// Size limits applied to ingested features
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureLimits {
+    /// Maximum number of vertices in one feature
+    pub max_vertices: usize,
+
+    /// Handling of features over the limit
+    pub oversized: OversizedFeatures,
+}
+
+impl Default for FeatureLimits {
+    fn default() -> Self {
+        Self {
+            max_vertices: DEFAULT_MAX_FEATURE_VERTICES,
+            oversized: OversizedFeatures::default(),
+        }
+    }
+}
+
+impl FeatureLimits {
+    /// Check if a geometry is over the vertex limit
+    pub fn exceeds(&self, geometry: &Geometry) -> bool {
+        geometry.vertex_count() > self.max_vertices
+    }
+}
+
+/// What ingest did with a feature over the vertex limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OversizedAction {
+    /// Simplified to fit the limit
+    Simplified { simplified_vertices: usize },
+
+    /// Cut into `parts` features sharing the original feature ID
+    Subdivided { parts: usize },
+}
+
+impl fmt::Display for OversizedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OversizedAction::Simplified { simplified_vertices } => {
+                write!(f, "simplified to {} vertices", simplified_vertices)
+            }
+            OversizedAction::Subdivided { parts } => write!(f, "subdivided into {} parts", parts),
+        }
+    }
+}
+
+/// A feature over the vertex limit that was kept in a reduced form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OversizedFeature {
+    /// Position of the feature in the file, counting from 0
+    pub index: usize,
+
+    /// Vertices in the feature as read
+    pub vertices: usize,
+
+    #[serde(flatten)]
+    pub action: OversizedAction,
+}
+
+impl fmt::Display for OversizedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "feature {}: {} vertices, {}", self.index, self.vertices, self.action)
+    }
+}
+
+/// Cut a geometry into parts of at most `max_vertices` vertices each
+///
+/// The bounding box is split at the middle of its longer side and each half
+/// is clipped, recursively, until a part fits. Parts cover the geometry
+/// exactly: polygon parts add up to the original area and line parts to the
+/// original length. Limits below [`MIN_SUBDIVIDE_VERTICES`] are raised to it.
+/// Points cannot be cut and are returned whole.
+pub fn subdivide(geometry: &Geometry, max_vertices: usize) -> Vec<Geometry> {
+    let max_vertices = max_vertices.max(MIN_SUBDIVIDE_VERTICES);
+    let mut parts = Vec::new();
+    split(geometry.clone(), max_vertices, 0, &mut parts);
+    parts
+}
+
+fn split(geometry: Geometry, max_vertices: usize, depth: usize, parts: &mut Vec<Geometry>) {
+    if geometry.vertex_count() <= max_vertices || depth >= MAX_SUBDIVIDE_DEPTH {
+        parts.push(geometry);
+        return;
+    }
+
+    let Some([min_x, min_y, max_x, max_y]) = bounds(&geometry) else {
+        parts.push(geometry);
+        return;
+    };
+    let axis = if max_x - min_x >= max_y - min_y { 0 } else { 1 };
+    let (min, max) = if axis == 0 {
+        (min_x, max_x)
+    } else {
+        (min_y, max_y)
+    };
+    if max <= min {
+        // All vertices share one location, e.g. a multipoint of duplicates
+        parts.push(geometry);
+        return;
+    }
+
+    let value = (min + max) / 2.0;
+    for low in [true, false] {
+        let plane = HalfPlane { axis, value, low };
+        if let Some(half) = clip(&geometry, plane) {
+            split(half, max_vertices, depth + 1, parts);
+        }
+    }
+}
+
+/// One side of an axis-aligned cut line
+#[derive(Debug, Clone, Copy)]
+struct HalfPlane {
+    axis: usize,
+    value: f64,
+    low: bool,
+}
+
+impl HalfPlane {
+    /// Whether a vertex lies on this side, cut line included
+    fn contains(&self, p: [f64; 2]) -> bool {
+        if self.low {
+            p[self.axis] <= self.value
+        } else {
+            p[self.axis] >= self.value
+        }
+    }
+
+    /// Whether a point belongs to this side; points on the cut line go to the high side only
+    fn owns(&self, p: [f64; 2]) -> bool {
+        if self.low {
+            p[self.axis] < self.value
+        } else {
+            p[self.axis] >= self.value
+        }
+    }
+
+    /// Point where the segment from `a` to `b` crosses the cut line
+    fn intersection(&self, a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+        let t = (self.value - a[self.axis]) / (b[self.axis] - a[self.axis]);
+        let mut p = [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])];
+        p[self.axis] = self.value;
+        p
+    }
+}
+
+/// Part of a geometry on one side of a cut line, `None` if nothing is left
+fn clip(geometry: &Geometry, plane: HalfPlane) -> Option<Geometry> {
+    match geometry {
+        Geometry::Point { coordinates } => plane.owns(*coordinates).then(|| geometry.clone()),
+        Geometry::MultiPoint { coordinates } => {
+            let kept: Vec<[f64; 2]> =
+                coordinates.iter().copied().filter(|p| plane.owns(*p)).collect();
+            (!kept.is_empty()).then_some(Geometry::MultiPoint { coordinates: kept })
+        }
+        Geometry::LineString { coordinates } => lines(clip_line(coordinates, plane)),
+        Geometry::MultiLineString { coordinates } => {
+            lines(coordinates.iter().flat_map(|line| clip_line(line, plane)).collect())
+        }
+        Geometry::Polygon { coordinates } => {
+            clip_rings(coordinates, plane).map(|rings| Geometry::Polygon { coordinates: rings })
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            let mut polygons: Vec<Vec<Vec<[f64; 2]>>> =
+                coordinates.iter().filter_map(|rings| clip_rings(rings, plane)).collect();
+            match polygons.len() {
+                0 => None,
+                1 => Some(Geometry::Polygon { coordinates: polygons.remove(0) }),
+                _ => Some(Geometry::MultiPolygon { coordinates: polygons }),
+            }
+        }
+    }
+}
+
+/// A linestring for one piece, a multilinestring for several
+fn lines(mut pieces: Vec<Vec<[f64; 2]>>) -> Option<Geometry> {
+    match pieces.len() {
+        0 => None,
+        1 => Some(Geometry::LineString { coordinates: pieces.remove(0) }),
+        _ => Some(Geometry::MultiLineString { coordinates: pieces }),
+    }
+}
+
+/// Pieces of a line on one side of a cut line
+fn clip_line(points: &[[f64; 2]], plane: HalfPlane) -> Vec<Vec<[f64; 2]>> {
+    let mut pieces = Vec::new();
+    let mut current: Vec<[f64; 2]> = Vec::new();
+
+    if let Some(&first) = points.first() {
+        if plane.contains(first) {
+            current.push(first);
+        }
+    }
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        match (plane.contains(a), plane.contains(b)) {
+            (true, true) => current.push(b),
+            (true, false) => {
+                current.push(plane.intersection(a, b));
+                pieces.push(std::mem::take(&mut current));
+            }
+            (false, true) => {
+                current.push(plane.intersection(a, b));
+                current.push(b);
+            }
+            (false, false) => {}
+        }
+    }
+    pieces.push(current);
+
+    // Pieces that only touch the cut line have no length on this side
+    pieces.retain(|piece| piece.len() >= 2 && piece.iter().any(|p| *p != piece[0]));
+    pieces
+}
+
+/// Rings of a polygon on one side of a cut line, `None` if the shell is cut away
+fn clip_rings(rings: &[Vec<[f64; 2]>], plane: HalfPlane) -> Option<Vec<Vec<[f64; 2]>>> {
+    let (shell, holes) = rings.split_first()?;
+    let mut clipped = vec![clip_ring(shell, plane)?];
+    clipped.extend(holes.iter().filter_map(|hole| clip_ring(hole, plane)));
+    Some(clipped)
+}
+
+/// Sutherland-Hodgman clipping of a closed ring against a half-plane
+///
+/// A concave ring crossing the cut line several times comes back as one ring
+/// joined by zero-width edges along the cut line, which leaves its area exact.
+fn clip_ring(ring: &[[f64; 2]], plane: HalfPlane) -> Option<Vec<[f64; 2]>> {
+    // Work on the open ring; the closing vertex is added back at the end
+    let open = match ring.split_last() {
+        Some((last, rest)) if !rest.is_empty() && *last == rest[0] => rest,
+        _ => ring,
+    };
+    if open.len() < 3 {
+        return None;
+    }
+
+    let mut clipped: Vec<[f64; 2]> = Vec::with_capacity(open.len() + 2);
+    let mut previous = open[open.len() - 1];
+    for &current in open {
+        match (plane.contains(previous), plane.contains(current)) {
+            (true, true) => clipped.push(current),
+            (true, false) => clipped.push(plane.intersection(previous, current)),
+            (false, true) => {
+                clipped.push(plane.intersection(previous, current));
+                clipped.push(current);
+            }
+            (false, false) => {}
+        }
+        previous = current;
+    }
+    clipped.dedup();
+    if clipped.len() > 1 && clipped.first() == clipped.last() {
+        clipped.pop();
+    }
+
+    // Rings lying entirely on the cut line have no area on this side
+    let on_line = clipped.iter().all(|p| p[plane.axis] == plane.value);
+    if clipped.len() < 3 || on_line {
+        return None;
+    }
+    clipped.push(clipped[0]);
+    Some(clipped)
+}
+
+/// Bounding box as `[min_x, min_y, max_x, max_y]`, `None` for an empty geometry
+fn bounds(geometry: &Geometry) -> Option<[f64; 4]> {
+    let points: Box<dyn Iterator<Item = &[f64; 2]>> = match geometry {
+        Geometry::Point { coordinates } => Box::new(std::iter::once(coordinates)),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            Box::new(coordinates.iter())
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            Box::new(coordinates.iter().flatten())
+        }
+        Geometry::MultiPolygon { coordinates } => Box::new(coordinates.iter().flatten().flatten()),
+    };
+
+    points.fold(None, |bounds, [x, y]| {
+        Some(match bounds {
+            None => [*x, *y, *x, *y],
+            Some([min_x, min_y, max_x, max_y]) => {
+                [min_x.min(*x), min_y.min(*y), max_x.max(*x), max_y.max(*y)]
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::to_geo_geometry;
+    use geo::{Area, Euclidean, Length};
+
+    /// Star-shaped polygon with `n` points, concave between the tips
+    fn star(n: usize) -> Geometry {
+        let mut ring: Vec<[f64; 2]> = (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+                let radius = if i % 2 == 0 { 10.0 } else { 4.0 };
+                [radius * angle.cos(), radius * angle.sin()]
+            })
+            .collect();
+        ring.push(ring[0]);
+        Geometry::polygon(vec![ring])
+    }
+
+    fn area(geometry: &Geometry) -> f64 {
+        to_geo_geometry(geometry).unsigned_area()
+    }
+
+    fn total_area(parts: &[Geometry]) -> f64 {
+        parts.iter().map(area).sum()
+    }
+
+    #[test]
+    fn test_geometry_under_limit_is_one_part() {
+        let polygon = star(20);
+        assert_eq!(subdivide(&polygon, 100), vec![polygon]);
+    }
+
+    #[test]
+    fn test_parts_fit_the_limit_and_keep_the_area() {
+        let polygon = star(2000);
+        let parts = subdivide(&polygon, 256);
+
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.vertex_count() <= 256, "part has {} vertices", part.vertex_count());
+        }
+        let (original, divided) = (area(&polygon), total_area(&parts));
+        assert!((original - divided).abs() < original * 1e-9, "{} != {}", original, divided);
+    }
+
+    #[test]
+    fn test_holes_are_kept_in_their_parts() {
+        let mut shell = match star(400) {
+            Geometry::Polygon { coordinates } => coordinates,
+            _ => unreachable!(),
+        };
+        // Square hole across the first vertical cut
+        shell.push(vec![[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0], [-1.0, -1.0]]);
+        let polygon = Geometry::polygon(shell);
+
+        let parts = subdivide(&polygon, 64);
+        let (original, divided) = (area(&polygon), total_area(&parts));
+        assert!((original - divided).abs() < original * 1e-9, "{} != {}", original, divided);
+    }
+
+    #[test]
+    fn test_multipolygon_parts_keep_the_area() {
+        let polygons: Vec<Vec<Vec<[f64; 2]>>> = [star(300), star(300)]
+            .iter()
+            .enumerate()
+            .map(|(i, polygon)| match polygon {
+                Geometry::Polygon { coordinates } => coordinates
+                    .iter()
+                    .map(|ring| ring.iter().map(|[x, y]| [x + 30.0 * i as f64, *y]).collect())
+                    .collect(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let multi = Geometry::MultiPolygon { coordinates: polygons };
+
+        let parts = subdivide(&multi, 100);
+        assert!(parts.iter().all(|part| part.vertex_count() <= 100));
+        let (original, divided) = (area(&multi), total_area(&parts));
+        assert!((original - divided).abs() < original * 1e-9);
+    }
+
+    #[test]
+    fn test_line_parts_keep_the_length() {
+        let line = Geometry::line_string(
+            (0..1000).map(|i| [i as f64 * 0.01, (i as f64 * 0.1).sin()]).collect(),
+        );
+        let parts = subdivide(&line, 50);
+
+        assert!(parts.iter().all(|part| part.vertex_count() <= 50));
+        let length = |g: &Geometry| match to_geo_geometry(g) {
+            geo::Geometry::LineString(l) => Euclidean.length(&l),
+            geo::Geometry::MultiLineString(l) => Euclidean.length(&l),
+            other => panic!("unexpected part {:?}", other),
+        };
+        let divided: f64 = parts.iter().map(length).sum();
+        assert!((length(&line) - divided).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_multipoint_points_are_not_duplicated() {
+        let points: Vec<[f64; 2]> = (0..100).map(|i| [(i % 10) as f64, (i / 10) as f64]).collect();
+        let parts = subdivide(&Geometry::MultiPoint { coordinates: points }, 10);
+
+        assert!(parts.iter().all(|part| part.vertex_count() <= 10));
+        assert_eq!(parts.iter().map(Geometry::vertex_count).sum::<usize>(), 100);
+    }
+
+    #[test]
+    fn test_oversized_features_parse() {
+        assert_eq!("Simplify".parse::<OversizedFeatures>(), Ok(OversizedFeatures::Simplify));
+        assert!("split".parse::<OversizedFeatures>().is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//! Well-Known Binary encoding of geometries
+//!
+//! Stores exchange geometries with PostGIS as WKB rather than GeoJSON text:
+//! coordinates travel as the eight bytes of their f64, so nothing is lost to
+//! decimal formatting (`ST_AsGeoJSON` keeps 9 decimal places by default).
+//! Geometries are written as little-endian WKB, with ISO Z type codes when a
+//! feature has a Z ordinate per vertex. Reading accepts either byte order,
+//! ISO and PostGIS EWKB dimension flags, and skips the SRID of EWKB; Z is
+//! returned beside the geometry and M ordinates are dropped.
+
+use crate::error::{GeoragError, Result};
+use crate::models::Geometry;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+
+/// Offset of ISO type codes with a Z ordinate
+const ISO_Z: u32 = 1000;
+
+/// EWKB flags set in the type word
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+/// Encode a geometry as little-endian 2D WKB
+pub fn to_wkb(geometry: &Geometry) -> Vec<u8> {
+    to_wkb_z(geometry, None)
+}
+
+/// Encode a geometry as little-endian WKB, 3D when `z` holds one value per vertex
+pub fn to_wkb_z(geometry: &Geometry, z: Option<&[f64]>) -> Vec<u8> {
+    let z = z.filter(|z| z.len() == geometry.vertex_count());
+    let size = if z.is_some() { 24 } else { 16 };
+    let mut writer = Writer {
+        out: Vec::with_capacity(9 + size * geometry.vertex_count()),
+        z: z.map(|z| z.iter()),
+    };
+    writer.geometry(geometry);
+    writer.out
+}
+
+/// Decode WKB or PostGIS EWKB into a geometry, dropping Z and M ordinates
+pub fn from_wkb(bytes: &[u8]) -> Result<Geometry> {
+    from_wkb_z(bytes).map(|(geometry, _)| geometry)
+}
+
+/// Decode WKB or PostGIS EWKB into a geometry and the Z ordinate of each vertex
+///
+/// Z is `None` unless every coordinate has one. M ordinates are dropped.
+pub fn from_wkb_z(bytes: &[u8]) -> Result<(Geometry, Option<Vec<f64>>)> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        little_endian: true,
+        dimensions: Dimensions::default(),
+        z: Vec::new(),
+        missing_z: false,
+    };
+    let geometry = reader.geometry(None)?;
+    if reader.pos != bytes.len() {
+        return Err(wkb_error(format!(
+            "{} trailing bytes after the geometry",
+            bytes.len() - reader.pos
+        )));
+    }
+    let z = (!reader.missing_z && !reader.z.is_empty()).then_some(reader.z);
+    Ok((geometry, z))
+}
+
+struct Writer<'a> {
+    out: Vec<u8>,
+    z: Option<std::slice::Iter<'a, f64>>,
+}
+
+impl Writer<'_> {
+    fn geometry(&mut self, geometry: &Geometry) {
+        match geometry {
+            Geometry::Point { coordinates } => {
+                self.header(POINT);
+                self.coord(coordinates);
+            }
+            Geometry::LineString { coordinates } => {
+                self.header(LINE_STRING);
+                self.coords(coordinates);
+            }
+            Geometry::Polygon { coordinates } => {
+                self.header(POLYGON);
+                self.rings(coordinates);
+            }
+            Geometry::MultiPoint { coordinates } => {
+                self.header(MULTI_POINT);
+                self.len(coordinates.len());
+                for coord in coordinates {
+                    self.header(POINT);
+                    self.coord(coord);
+                }
+            }
+            Geometry::MultiLineString { coordinates } => {
+                self.header(MULTI_LINE_STRING);
+                self.len(coordinates.len());
+                for line in coordinates {
+                    self.header(LINE_STRING);
+                    self.coords(line);
+                }
+            }
+            Geometry::MultiPolygon { coordinates } => {
+                self.header(MULTI_POLYGON);
+                self.len(coordinates.len());
+                for polygon in coordinates {
+                    self.header(POLYGON);
+                    self.rings(polygon);
+                }
+            }
+        }
+    }
+
+    fn header(&mut self, kind: u32) {
+        let kind = if self.z.is_some() { kind + ISO_Z } else { kind };
+        self.out.push(1);
+        self.out.extend_from_slice(&kind.to_le_bytes());
+    }
+
+    fn len(&mut self, len: usize) {
+        self.out.extend_from_slice(&(len as u32).to_le_bytes());
+    }
+
+    fn coord(&mut self, coord: &[f64; 2]) {
+        self.out.extend_from_slice(&coord[0].to_le_bytes());
+        self.out.extend_from_slice(&coord[1].to_le_bytes());
+        if let Some(z) = self.z.as_mut().and_then(Iterator::next) {
+            self.out.extend_from_slice(&z.to_le_bytes());
+        }
+    }
+
+    fn coords(&mut self, coords: &[[f64; 2]]) {
+        self.len(coords.len());
+        coords.iter().for_each(|coord| self.coord(coord));
+    }
+
+    fn rings(&mut self, rings: &[Vec<[f64; 2]>]) {
+        self.len(rings.len());
+        rings.iter().for_each(|ring| self.coords(ring));
+    }
+}
+
+/// Ordinates after X and Y in the coordinates of the geometry being read
+#[derive(Debug, Clone, Copy, Default)]
+struct Dimensions {
+    z: bool,
+    m: bool,
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+    dimensions: Dimensions,
+    /// Z of each coordinate read so far
+    z: Vec<f64>,
+    /// Whether a coordinate without Z was read
+    missing_z: bool,
+}
+
+impl Reader<'_> {
+    /// Read one geometry, which must be of `expected` type when nested in a multi-geometry
+    fn geometry(&mut self, expected: Option<u32>) -> Result<Geometry> {
+        self.little_endian = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            other => return Err(wkb_error(format!("invalid byte order marker {}", other))),
+        };
+
+        let word = self.u32()?;
+        if word & EWKB_SRID != 0 {
+            self.u32()?;
+        }
+        let kind = word & 0x0fff_ffff;
+        let (iso_z, iso_m) = match kind / 1000 {
+            0 => (false, false),
+            1 => (true, false),
+            2 => (false, true),
+            3 => (true, true),
+            _ => return Err(wkb_error(format!("unsupported geometry type {}", kind))),
+        };
+        self.dimensions = Dimensions {
+            z: iso_z || word & EWKB_Z != 0,
+            m: iso_m || word & EWKB_M != 0,
+        };
+        let kind = kind % 1000;
+        if expected.is_some_and(|expected| expected != kind) {
+            return Err(wkb_error(format!("unexpected geometry type {} in collection", kind)));
+        }
+
+        Ok(match kind {
+            POINT => Geometry::Point { coordinates: self.coord()? },
+            LINE_STRING => Geometry::LineString { coordinates: self.coords()? },
+            POLYGON => Geometry::Polygon { coordinates: self.rings()? },
+            MULTI_POINT => Geometry::MultiPoint {
+                coordinates: self.members(POINT, |geometry| match geometry {
+                    Geometry::Point { coordinates } => coordinates,
+                    _ => unreachable!("member type is checked"),
+                })?,
+            },
+            MULTI_LINE_STRING => Geometry::MultiLineString {
+                coordinates: self.members(LINE_STRING, |geometry| match geometry {
+                    Geometry::LineString { coordinates } => coordinates,
+                    _ => unreachable!("member type is checked"),
+                })?,
+            },
+            MULTI_POLYGON => Geometry::MultiPolygon {
+                coordinates: self.members(POLYGON, |geometry| match geometry {
+                    Geometry::Polygon { coordinates } => coordinates,
+                    _ => unreachable!("member type is checked"),
+                })?,
+            },
+            other => return Err(wkb_error(format!("unsupported geometry type {}", other))),
+        })
+    }
+
+    fn members<T>(&mut self, kind: u32, unwrap: impl Fn(Geometry) -> T) -> Result<Vec<T>> {
+        let count = self.len(5)?;
+        (0..count).map(|_| self.geometry(Some(kind)).map(&unwrap)).collect()
+    }
+
+    fn rings(&mut self) -> Result<Vec<Vec<[f64; 2]>>> {
+        let count = self.len(4)?;
+        (0..count).map(|_| self.coords()).collect()
+    }
+
+    fn coords(&mut self) -> Result<Vec<[f64; 2]>> {
+        let count = self.len(16)?;
+        (0..count).map(|_| self.coord()).collect()
+    }
+
+    fn coord(&mut self) -> Result<[f64; 2]> {
+        let coord = [self.f64()?, self.f64()?];
+        if self.dimensions.z {
+            let z = self.f64()?;
+            self.z.push(z);
+        } else {
+            self.missing_z = true;
+        }
+        if self.dimensions.m {
+            self.f64()?;
+        }
+        Ok(coord)
+    }
+
+    /// Read an element count, checking the remaining bytes can hold that many elements
+    fn len(&mut self, min_size: usize) -> Result<usize> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_size) > self.bytes.len() - self.pos {
+            return Err(wkb_error(format!("count {} exceeds the remaining input", count)));
+        }
+        Ok(count)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take::<4>()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        let bytes = self.take::<8>()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let end = self.pos + N;
+        let bytes = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or_else(|| wkb_error("unexpected end of input"))?;
+        self.pos = end;
+        Ok(bytes.try_into().expect("slice has N bytes"))
+    }
+}
+
+fn wkb_error(message: impl Into<String>) -> GeoragError {
+    GeoragError::FormatError {
+        format: "WKB".to_string(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_layout() {
+        let wkb = to_wkb(&Geometry::point(1.0, 2.0));
+        assert_eq!(wkb.len(), 21);
+        assert_eq!(wkb[0], 1);
+        assert_eq!(&wkb[1..5], &POINT.to_le_bytes());
+        assert_eq!(&wkb[5..13], &1.0f64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_reads_big_endian_ewkb_with_srid() {
+        // SRID=4326;POINT(106.8 -6.2) as PostGIS writes it with ST_AsEWKB(.., 'XDR')
+        let mut wkb = vec![0];
+        wkb.extend_from_slice(&(POINT | EWKB_SRID).to_be_bytes());
+        wkb.extend_from_slice(&4326u32.to_be_bytes());
+        wkb.extend_from_slice(&106.8f64.to_be_bytes());
+        wkb.extend_from_slice(&(-6.2f64).to_be_bytes());
+
+        assert_eq!(from_wkb(&wkb).unwrap(), Geometry::point(106.8, -6.2));
+    }
+
+    #[test]
+    fn test_multi_polygon_round_trip() {
+        let square = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]];
+        let geometry = Geometry::MultiPolygon {
+            coordinates: vec![vec![square.clone()], vec![square.clone(), square]],
+        };
+        assert_eq!(from_wkb(&to_wkb(&geometry)).unwrap(), geometry);
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let wkb = to_wkb(&Geometry::line_string(vec![[0.0, 0.0], [1.0, 1.0]]));
+        assert!(from_wkb(&wkb[..wkb.len() - 1]).is_err());
+
+        let mut trailing = wkb.clone();
+        trailing.push(0);
+        assert!(from_wkb(&trailing).is_err());
+
+        // A count far beyond the input must not allocate for it
+        let mut huge = wkb[..5].to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert!(from_wkb(&huge).is_err());
+
+        let mut unknown = to_wkb(&Geometry::point(1.0, 2.0));
+        unknown[1..5].copy_from_slice(&(POINT + 4000).to_le_bytes());
+        assert!(from_wkb(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_z_round_trip_for_every_geometry_type() {
+        let square = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]];
+        let geometries = [
+            Geometry::point(106.8, -6.2),
+            Geometry::line_string(vec![[0.0, 0.0], [1.0, 1.0]]),
+            Geometry::polygon(vec![square.clone()]),
+            Geometry::MultiPoint {
+                coordinates: vec![[0.0, 0.0], [2.0, 2.0]],
+            },
+            Geometry::MultiLineString {
+                coordinates: vec![vec![[0.0, 0.0], [1.0, 1.0]], vec![[2.0, 2.0], [3.0, 3.0]]],
+            },
+            Geometry::MultiPolygon {
+                coordinates: vec![vec![square.clone()], vec![square]],
+            },
+        ];
+
+        for geometry in geometries {
+            let z: Vec<f64> = (0..geometry.vertex_count()).map(|i| i as f64 * 1.5 - 3.0).collect();
+            let wkb = to_wkb_z(&geometry, Some(&z));
+            assert_eq!(wkb.len(), to_wkb(&geometry).len() + 8 * z.len());
+            assert_eq!(from_wkb_z(&wkb).unwrap(), (geometry.clone(), Some(z)));
+            assert_eq!(from_wkb_z(&to_wkb(&geometry)).unwrap(), (geometry, None));
+        }
+
+        // Z values that do not match the vertices are not written
+        let point = Geometry::point(1.0, 2.0);
+        assert_eq!(to_wkb_z(&point, Some(&[1.0, 2.0])), to_wkb(&point));
+    }
+
+    #[test]
+    fn test_reads_z_flags_and_drops_m() {
+        // SRID=4326;POINT Z (1 2 3) as PostGIS writes it with ST_AsEWKB
+        let mut ewkb = vec![1];
+        ewkb.extend_from_slice(&(POINT | EWKB_Z | EWKB_SRID).to_le_bytes());
+        ewkb.extend_from_slice(&4326u32.to_le_bytes());
+        for ordinate in [1.0f64, 2.0, 3.0] {
+            ewkb.extend_from_slice(&ordinate.to_le_bytes());
+        }
+        assert_eq!(from_wkb_z(&ewkb).unwrap(), (Geometry::point(1.0, 2.0), Some(vec![3.0])));
+
+        // LINESTRING ZM and LINESTRING M in ISO codes
+        let mut zm = vec![1];
+        zm.extend_from_slice(&(LINE_STRING + 3000).to_le_bytes());
+        zm.extend_from_slice(&2u32.to_le_bytes());
+        let mut m = zm.clone();
+        m[1..5].copy_from_slice(&(LINE_STRING + 2000).to_le_bytes());
+        for [x, y, z, measure] in [[0.0f64, 0.0, 10.0, 7.0], [1.0, 1.0, 11.0, 8.0]] {
+            for ordinate in [x, y, z, measure] {
+                zm.extend_from_slice(&ordinate.to_le_bytes());
+            }
+            for ordinate in [x, y, measure] {
+                m.extend_from_slice(&ordinate.to_le_bytes());
+            }
+        }
+        let line = Geometry::line_string(vec![[0.0, 0.0], [1.0, 1.0]]);
+        assert_eq!(from_wkb_z(&zm).unwrap(), (line.clone(), Some(vec![10.0, 11.0])));
+        assert_eq!(from_wkb_z(&m).unwrap(), (line, None));
+    }
+}
@@ -5,6 +5,9 @@ pub mod geo;
 pub mod llm;
 pub mod models;
 pub mod processing;
+pub mod progress;
+pub mod redaction;
+pub mod resources;
 
 pub use error::{GeoragError, Result};
 pub use llm::{Embedder, Generator, OllamaEmbedder};
@@ -0,0 +1,240 @@
+//! Cross-check of embedding dimensions
+//!
+//! A configured dimension that does not match the model gives nonsense
+//! scores rather than errors, and so does an index built with another one.
+//! Before serving, the dimension the model actually returns (probed with one
+//! embedding) is compared with the configured one and with the dimension the
+//! index was built with, and a mismatch names the number to fix.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{GeoragError, Result};
+use crate::llm::factory::{AnyEmbedder, EmbedderSpec};
+use crate::llm::ports::Embedder;
+use crate::models::workspace::IndexState;
+
+/// What happens when the embedding dimensions disagree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DimensionCheckMode {
+    /// Refuse to serve
+    #[default]
+    Refuse,
+
+    /// Log a warning and serve anyway
+    Warn,
+}
+
+impl fmt::Display for DimensionCheckMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimensionCheckMode::Refuse => write!(f, "refuse"),
+            DimensionCheckMode::Warn => write!(f, "warn"),
+        }
+    }
+}
+
+impl FromStr for DimensionCheckMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "refuse" => Ok(DimensionCheckMode::Refuse),
+            "warn" => Ok(DimensionCheckMode::Warn),
+            other => Err(format!("unknown dimension check '{}': expected refuse or warn", other)),
+        }
+    }
+}
+
+/// Embedding dimensions of one model as configured, probed and indexed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimensionCheck {
+    /// Embedder string of the model
+    pub model: String,
+
+    /// Setting the configured dimension comes from, named in the fix
+    pub setting: String,
+
+    /// Dimension the embedder is configured with
+    pub configured: usize,
+
+    /// Dimension the model returned; `None` when it was not probed
+    pub actual: Option<usize>,
+
+    /// Dimension of the index built with this model, if there is one
+    pub index: Option<usize>,
+}
+
+impl DimensionCheck {
+    /// Compare an embedder's configured dimension with the model and the index
+    ///
+    /// The model is probed unless it runs offline (the mock embedder). The
+    /// index counts only when it was built with the same model. A failed
+    /// probe is returned as an error; callers decide whether to go on
+    /// without it.
+    pub async fn run(
+        embedder: &AnyEmbedder,
+        setting: impl Into<String>,
+        index: Option<&IndexState>,
+    ) -> Result<Self> {
+        let actual = embedder.probe_dimensions().await?;
+        Ok(Self::unprobed(embedder, setting, index).with_actual(actual))
+    }
+
+    /// Compare an embedder's configured dimension with the index only
+    pub fn unprobed(
+        embedder: &AnyEmbedder,
+        setting: impl Into<String>,
+        index: Option<&IndexState>,
+    ) -> Self {
+        let model = embedder.model_name().to_string();
+        let spec = EmbedderSpec::parse(&model).ok();
+        let index = index
+            .filter(|index| spec.is_some() && EmbedderSpec::parse(&index.embedder).ok() == spec)
+            .map(|index| index.embedding_dim);
+        Self {
+            model,
+            setting: setting.into(),
+            configured: embedder.dimensions(),
+            actual: None,
+            index,
+        }
+    }
+
+    /// Set the dimension the model returned
+    pub fn with_actual(mut self, actual: Option<usize>) -> Self {
+        self.actual = actual;
+        self
+    }
+
+    /// Error naming every dimension and the one to fix, if they disagree
+    pub fn mismatch(&self) -> Option<GeoragError> {
+        let fix = match (self.actual, self.index) {
+            (Some(actual), index) => {
+                let config_wrong = self.configured != actual;
+                let index_wrong = index.is_some_and(|index| index != actual);
+                match (config_wrong, index_wrong) {
+                    (false, false) => return None,
+                    (true, false) => format!("set {} to {}", self.setting, actual),
+                    (false, true) => format!("rebuild the index with {}", self.model),
+                    (true, true) => format!(
+                        "set {} to {} and rebuild the index with {}",
+                        self.setting, actual, self.model
+                    ),
+                }
+            }
+            (None, Some(index)) if index != self.configured => format!(
+                "set {} to {} if the index is right, or rebuild the index",
+                self.setting, index
+            ),
+            (None, _) => return None,
+        };
+
+        let actual = match self.actual {
+            Some(actual) => format!("model returns {}", actual),
+            None => "model not probed".to_string(),
+        };
+        let index = match self.index {
+            Some(index) => format!("index built with {}", index),
+            None => "no index".to_string(),
+        };
+        Some(GeoragError::EmbedderDimensionMismatch {
+            model: self.model.clone(),
+            dimensions: format!("configured {}, {}, {}", self.configured, actual, index),
+            fix,
+        })
+    }
+
+    /// Apply the check mode to a mismatch
+    ///
+    /// Refusing returns the mismatch as an error; warning returns its message
+    /// for the caller to log.
+    pub fn enforce(&self, mode: DimensionCheckMode) -> Result<Option<String>> {
+        match (self.mismatch(), mode) {
+            (None, _) => Ok(None),
+            (Some(mismatch), DimensionCheckMode::Refuse) => Err(mismatch),
+            (Some(mismatch), DimensionCheckMode::Warn) => Ok(Some(mismatch.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "mock")]
+    fn index(embedder: &str, embedding_dim: usize) -> IndexState {
+        IndexState {
+            schema_version: crate::models::INDEX_SCHEMA_VERSION,
+            hash: "abc".to_string(),
+            built_at: chrono::Utc::now(),
+            embedder: embedder.to_string(),
+            chunk_count: 1,
+            embedding_dim,
+            dataset_built_at: Default::default(),
+            contents: None,
+            last_build_diff: None,
+            tokenizer: None,
+            score_baseline: None,
+        }
+    }
+
+    fn check(configured: usize, actual: Option<usize>, index: Option<usize>) -> DimensionCheck {
+        DimensionCheck {
+            model: "nomic-embed-text".to_string(),
+            setting: "GEORAG_EMBEDDER_DIM".to_string(),
+            configured,
+            actual,
+            index,
+        }
+    }
+
+    #[test]
+    fn test_mismatch_names_the_number_to_fix() {
+        assert!(check(768, Some(768), Some(768)).mismatch().is_none());
+        assert!(check(768, Some(768), None).mismatch().is_none());
+
+        let message = check(1024, Some(768), Some(768)).mismatch().unwrap().to_string();
+        assert!(message.contains("configured 1024, model returns 768, index built with 768"));
+        assert!(message.ends_with("Fix: set GEORAG_EMBEDDER_DIM to 768"), "{}", message);
+
+        let message = check(768, Some(768), Some(1024)).mismatch().unwrap().to_string();
+        assert!(message.ends_with("Fix: rebuild the index with nomic-embed-text"), "{}", message);
+
+        let message = check(1024, Some(768), Some(1024)).mismatch().unwrap().to_string();
+        assert!(message.ends_with("to 768 and rebuild the index with nomic-embed-text"));
+
+        let message = check(1024, None, Some(768)).mismatch().unwrap().to_string();
+        assert!(message.contains("configured 1024, model not probed, index built with 768"));
+        assert!(message.contains("set GEORAG_EMBEDDER_DIM to 768 if the index is right"));
+        assert!(check(768, None, None).mismatch().is_none());
+    }
+
+    #[test]
+    fn test_enforce_refuses_or_warns() {
+        let mismatch = check(1024, Some(768), None);
+        assert!(mismatch.enforce(DimensionCheckMode::Refuse).is_err());
+        assert!(mismatch.enforce(DimensionCheckMode::Warn).unwrap().is_some());
+        assert_eq!(check(768, Some(768), None).enforce(DimensionCheckMode::Refuse).unwrap(), None);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn test_mock_embedder_is_not_probed() {
+        use crate::llm::factory::{create_embedder, EmbedderOptions};
+
+        let embedder = create_embedder("mock:32", &EmbedderOptions::default()).unwrap();
+        let result = DimensionCheck::run(&embedder, "dims", Some(&index("mock:32", 32)))
+            .await
+            .unwrap();
+        assert_eq!(result.actual, None);
+        assert_eq!(result.index, Some(32));
+        assert!(result.mismatch().is_none());
+
+        // An index of another model is not compared
+        let result = DimensionCheck::unprobed(&embedder, "dims", Some(&index("mock:64", 64)));
+        assert_eq!(result.index, None);
+    }
+}
@@ -12,12 +12,18 @@ pub fn create_embedding_with_spatial_metadata(
         chunk_id,
         vector,
         spatial_metadata: Some(SpatialMetadata { feature_id, crs, bbox }),
+        dataset_id: None,
     }
 }
 
 /// Create an embedding without spatial metadata
 pub fn create_embedding(chunk_id: ChunkId, vector: Vec<f32>) -> Embedding {
-    Embedding { chunk_id, vector, spatial_metadata: None }
+    Embedding {
+        chunk_id,
+        vector,
+        spatial_metadata: None,
+        dataset_id: None,
+    }
 }
 
 #[cfg(test)]
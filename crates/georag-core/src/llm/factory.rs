@@ -0,0 +1,320 @@
+//! Embedder selection from configuration strings
+//!
+//! The CLI, the API and the index builder all pick an embedder from a string
+//! such as `ollama:nomic-embed-text` or `mock:768`. [`create_embedder`] turns
+//! that string into an [`AnyEmbedder`] so callers never name a backend.
+
+use crate::error::{GeoragError, Result};
+use crate::llm::ollama::{OllamaEmbedder, PullProgress};
+use crate::llm::ports::Embedder;
+use crate::llm::request::RequestPolicy;
+
+#[cfg(feature = "mock")]
+use crate::llm::mock::MockEmbedder;
+
+/// Ollama URL used when none is configured
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Dimensions assumed for an Ollama model missing from the known list
+const DEFAULT_DIMENSIONS: usize = 768;
+
+/// Dimensions of the mock embedder when the spec gives none
+pub const DEFAULT_MOCK_DIMENSIONS: usize = 768;
+
+/// Embedding dimensions of well-known Ollama models
+pub fn known_dimensions(model: &str) -> Option<usize> {
+    match model {
+        "nomic-embed-text" => Some(768),
+        "mxbai-embed-large" => Some(1024),
+        "all-minilm" => Some(384),
+        _ => None,
+    }
+}
+
+/// Longest input, in tokens, of well-known Ollama models
+pub fn known_input_tokens(model: &str) -> Option<usize> {
+    match model {
+        "nomic-embed-text" => Some(8192),
+        "mxbai-embed-large" => Some(512),
+        "all-minilm" => Some(256),
+        _ => None,
+    }
+}
+
+/// A parsed embedder string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmbedderSpec {
+    /// `ollama:<model>` or a bare model name
+    Ollama { model: String },
+    /// `mock` or `mock:<dimensions>`
+    Mock { dimensions: usize },
+}
+
+impl EmbedderSpec {
+    /// Parse an embedder string
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec == "mock" {
+            return Ok(Self::Mock { dimensions: DEFAULT_MOCK_DIMENSIONS });
+        }
+        if let Some(dimensions) = spec.strip_prefix("mock:") {
+            return match dimensions.parse::<usize>() {
+                Ok(dimensions) if dimensions > 0 => Ok(Self::Mock { dimensions }),
+                _ => Err(invalid(format!(
+                    "'{}' is not a valid mock embedder; expected mock:<dimensions>, e.g. mock:768",
+                    spec
+                ))),
+            };
+        }
+
+        let model = spec.strip_prefix("ollama:").unwrap_or(spec);
+        if model.is_empty() {
+            return Err(invalid("embedder model name is empty".to_string()));
+        }
+        Ok(Self::Ollama { model: model.to_string() })
+    }
+
+    /// Dimensions of the embeddings when no dimensions are configured
+    ///
+    /// Ollama models missing from the known list are assumed to have 768.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Self::Ollama { model } => known_dimensions(model).unwrap_or(DEFAULT_DIMENSIONS),
+            Self::Mock { dimensions } => *dimensions,
+        }
+    }
+
+    /// Longest input the model embeds in full, in tokens, when known
+    ///
+    /// Ollama cuts longer input silently, so the index builder truncates it
+    /// itself; the mock embedder has no limit.
+    pub fn max_input_tokens(&self) -> Option<usize> {
+        match self {
+            Self::Ollama { model } => known_input_tokens(model),
+            Self::Mock { .. } => None,
+        }
+    }
+}
+
+/// Settings applied when creating an embedder
+#[derive(Debug, Clone)]
+pub struct EmbedderOptions {
+    /// Ollama base URL
+    pub base_url: String,
+    /// Pull a missing Ollama model instead of failing
+    pub auto_pull: bool,
+    /// Dimensions of an Ollama model, overriding the known-model table
+    ///
+    /// Mock embedders always take their dimensions from the spec.
+    pub dimensions: Option<usize>,
+    /// Timeout and retries of each Ollama request
+    pub request_policy: RequestPolicy,
+}
+
+impl Default for EmbedderOptions {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_OLLAMA_URL.to_string(),
+            auto_pull: false,
+            dimensions: None,
+            request_policy: RequestPolicy::default(),
+        }
+    }
+}
+
+impl EmbedderOptions {
+    /// Set the Ollama base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Pull a missing Ollama model instead of failing
+    pub fn with_auto_pull(mut self, enabled: bool) -> Self {
+        self.auto_pull = enabled;
+        self
+    }
+
+    /// Use these dimensions, e.g. the ones recorded with an existing index
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Set the timeout and retries of each Ollama request
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.request_policy = policy;
+        self
+    }
+}
+
+/// Any embedder [`create_embedder`] can return
+pub enum AnyEmbedder {
+    Ollama(OllamaEmbedder),
+    #[cfg(feature = "mock")]
+    Mock(MockEmbedder),
+}
+
+impl AnyEmbedder {
+    /// Receive progress updates while an Ollama model is pulled
+    pub fn with_pull_progress(
+        self,
+        callback: impl Fn(&PullProgress) + Send + Sync + 'static,
+    ) -> Self {
+        match self {
+            Self::Ollama(embedder) => Self::Ollama(embedder.with_pull_progress(callback)),
+            #[cfg(feature = "mock")]
+            other => other,
+        }
+    }
+
+    /// Make sure the embedder can serve requests
+    ///
+    /// For Ollama this checks (or pulls) the model; the mock is always ready.
+    pub async fn ensure_ready(&self) -> Result<()> {
+        match self {
+            Self::Ollama(embedder) => embedder.ensure_model().await,
+            #[cfg(feature = "mock")]
+            Self::Mock(_) => Ok(()),
+        }
+    }
+
+    /// Dimensions the model actually returns, probed with one embedding
+    ///
+    /// `None` for the mock, whose dimensions are its spec's. Probes are
+    /// cached for the rest of the process.
+    pub async fn probe_dimensions(&self) -> Result<Option<usize>> {
+        match self {
+            Self::Ollama(embedder) => embedder.probe_dimensions().await.map(Some),
+            #[cfg(feature = "mock")]
+            Self::Mock(_) => Ok(None),
+        }
+    }
+
+    /// Whether the embedder runs without a model server
+    pub fn is_offline(&self) -> bool {
+        match self {
+            Self::Ollama(_) => false,
+            #[cfg(feature = "mock")]
+            Self::Mock(_) => true,
+        }
+    }
+}
+
+impl Embedder for AnyEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Self::Ollama(embedder) => embedder.embed(texts),
+            #[cfg(feature = "mock")]
+            Self::Mock(embedder) => embedder.embed(texts),
+        }
+    }
+
+    fn dimensions(&self) -> usize {
+        match self {
+            Self::Ollama(embedder) => embedder.dimensions(),
+            #[cfg(feature = "mock")]
+            Self::Mock(embedder) => embedder.dimensions(),
+        }
+    }
+
+    fn model_name(&self) -> &str {
+        match self {
+            Self::Ollama(embedder) => embedder.model_name(),
+            #[cfg(feature = "mock")]
+            Self::Mock(embedder) => embedder.model_name(),
+        }
+    }
+}
+
+/// Create the embedder named by an embedder string
+///
+/// `mock:<dimensions>` needs the `mock` feature; without it the string is
+/// rejected as invalid configuration.
+pub fn create_embedder(spec: &str, options: &EmbedderOptions) -> Result<AnyEmbedder> {
+    match EmbedderSpec::parse(spec)? {
+        EmbedderSpec::Ollama { model } => {
+            let dimensions = options
+                .dimensions
+                .or_else(|| known_dimensions(&model))
+                .unwrap_or(DEFAULT_DIMENSIONS);
+            Ok(AnyEmbedder::Ollama(
+                OllamaEmbedder::new(options.base_url.clone(), model, dimensions)
+                    .with_auto_pull(options.auto_pull)
+                    .with_request_policy(options.request_policy),
+            ))
+        }
+        #[cfg(feature = "mock")]
+        EmbedderSpec::Mock { dimensions } => Ok(AnyEmbedder::Mock(MockEmbedder::new(dimensions))),
+        #[cfg(not(feature = "mock"))]
+        EmbedderSpec::Mock { .. } => Err(invalid(format!(
+            "'{}' needs the mock embedder, which this build does not include (feature `mock`)",
+            spec
+        ))),
+    }
+}
+
+fn invalid(reason: String) -> GeoragError {
+    GeoragError::ConfigInvalid { key: "embedder".to_string(), reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_embedder_spec() {
+        assert_eq!(
+            EmbedderSpec::parse("ollama:nomic-embed-text").unwrap(),
+            EmbedderSpec::Ollama { model: "nomic-embed-text".to_string() }
+        );
+        assert_eq!(
+            EmbedderSpec::parse("all-minilm").unwrap(),
+            EmbedderSpec::Ollama { model: "all-minilm".to_string() }
+        );
+        assert_eq!(EmbedderSpec::parse("mock").unwrap(), EmbedderSpec::Mock { dimensions: 768 });
+        assert_eq!(
+            EmbedderSpec::parse("mock:384").unwrap(),
+            EmbedderSpec::Mock { dimensions: 384 }
+        );
+        assert!(EmbedderSpec::parse("mock:0").is_err());
+        assert!(EmbedderSpec::parse("mock:big").is_err());
+        assert!(EmbedderSpec::parse("ollama:").is_err());
+    }
+
+    #[test]
+    fn test_embedder_spec_dimensions() {
+        assert_eq!(EmbedderSpec::parse("mxbai-embed-large").unwrap().dimensions(), 1024);
+        assert_eq!(EmbedderSpec::parse("ollama:custom").unwrap().dimensions(), 768);
+        assert_eq!(EmbedderSpec::parse("mock:32").unwrap().dimensions(), 32);
+    }
+
+    #[test]
+    fn test_create_ollama_embedder_dimensions() {
+        let options = EmbedderOptions::default();
+        let embedder = create_embedder("ollama:mxbai-embed-large", &options).unwrap();
+        assert_eq!(embedder.dimensions(), 1024);
+        assert_eq!(embedder.model_name(), "mxbai-embed-large");
+
+        let embedder = create_embedder("custom-model", &options.with_dimensions(512)).unwrap();
+        assert_eq!(embedder.dimensions(), 512);
+        assert!(!embedder.is_offline());
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn test_create_mock_embedder() {
+        let options = EmbedderOptions::default().with_dimensions(768);
+        let embedder = create_embedder("mock:32", &options).unwrap();
+        assert!(embedder.is_offline());
+        assert_eq!(embedder.model_name(), "mock:32");
+        assert_eq!(embedder.embed(&["a"]).unwrap()[0].len(), 32);
+    }
+
+    #[cfg(not(feature = "mock"))]
+    #[test]
+    fn test_mock_embedder_requires_feature() {
+        let err = create_embedder("mock:32", &EmbedderOptions::default()).err().unwrap();
+        assert!(matches!(err, GeoragError::ConfigInvalid { ref key, .. } if key == "embedder"));
+    }
+}
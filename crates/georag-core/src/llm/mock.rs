@@ -0,0 +1,221 @@
+//! Deterministic embedder and generator for offline tests and CI
+//!
+//! Neither talks to a model server. [`MockEmbedder`] gives the same vector
+//! for the same text on every run and platform, and texts sharing words get
+//! similar vectors, so retrieval ranking can be asserted without Ollama.
+//! Select it with the embedder string `mock:<dimensions>`.
+//!
+//! [`KeywordEmbedder`] counts the words of a fixed vocabulary instead, for
+//! tests that need to know exactly which texts match a query.
+
+use crate::error::Result;
+use crate::llm::factory::DEFAULT_MOCK_DIMENSIONS;
+use crate::llm::ports::{Embedder, Generator};
+
+/// Embedder deriving unit vectors from hashes of the words of a text
+///
+/// Each lowercased word seeds a sparse pseudo-random vector with positive
+/// weights; a text embeds as the normalized sum of its words' vectors, so
+/// similarities stay within 0..1. Text without words embeds as the vector
+/// seeded by the whole text.
+#[derive(Debug, Clone)]
+pub struct MockEmbedder {
+    dimensions: usize,
+    name: String,
+}
+
+impl MockEmbedder {
+    /// Create a mock embedder producing vectors of `dimensions` values
+    pub fn new(dimensions: usize) -> Self {
+        let dimensions = dimensions.max(1);
+        Self {
+            dimensions,
+            name: format!("mock:{}", dimensions),
+        }
+    }
+
+    fn embed_one(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; self.dimensions];
+        let mut words = 0;
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            add_seeded(&mut vector, fnv1a(word.to_lowercase().as_bytes()));
+            words += 1;
+        }
+        if words == 0 {
+            add_seeded(&mut vector, fnv1a(text.as_bytes()));
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Default for MockEmbedder {
+    fn default() -> Self {
+        Self::new(DEFAULT_MOCK_DIMENSIONS)
+    }
+}
+
+impl Embedder for MockEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_one(text)).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    fn model_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Bag-of-words embedder over a fixed vocabulary
+///
+/// A text embeds as the number of times each vocabulary term occurs among
+/// its lowercased, whitespace-separated words. Texts without any term embed
+/// as the zero vector.
+#[derive(Debug, Clone)]
+pub struct KeywordEmbedder {
+    vocabulary: Vec<String>,
+}
+
+impl KeywordEmbedder {
+    /// Create a keyword embedder with one dimension per vocabulary term
+    pub fn new(vocabulary: &[&str]) -> Self {
+        Self {
+            vocabulary: vocabulary.iter().map(|term| term.to_string()).collect(),
+        }
+    }
+}
+
+impl Embedder for KeywordEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let words: Vec<String> =
+                    text.split_whitespace().map(|w| w.to_lowercase()).collect();
+                self.vocabulary
+                    .iter()
+                    .map(|term| words.iter().filter(|w| w == &term).count() as f32)
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        self.vocabulary.len()
+    }
+
+    fn model_name(&self) -> &str {
+        "keyword"
+    }
+}
+
+/// Generator answering with the retrieved excerpts it was given
+///
+/// The answer is `Answer to "<prompt>" from N excerpt(s):` followed by one
+/// `[i] <excerpt>` line per context entry, so tests can check which excerpts
+/// reached generation and in what order.
+#[derive(Debug, Clone, Default)]
+pub struct MockGenerator;
+
+impl MockGenerator {
+    /// Create a mock generator
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Generator for MockGenerator {
+    fn generate(&self, prompt: &str, context: &[&str]) -> Result<String> {
+        let mut answer = format!("Answer to \"{}\" from {} excerpt(s):", prompt, context.len());
+        for (i, excerpt) in context.iter().enumerate() {
+            answer.push_str(&format!("\n[{}] {}", i + 1, excerpt));
+        }
+        Ok(answer)
+    }
+}
+
+/// Positions each word sets in its vector
+const POSITIONS_PER_WORD: usize = 8;
+
+/// Add the sparse vector seeded by `seed`: weights in 0.5..1 at a few positions
+fn add_seeded(vector: &mut [f32], seed: u64) {
+    let mut state = seed;
+    for _ in 0..POSITIONS_PER_WORD {
+        let index = (splitmix64(&mut state) % vector.len() as u64) as usize;
+        let bits = splitmix64(&mut state) >> 40;
+        vector[index] += 0.5 + bits as f32 / (1u64 << 25) as f32;
+    }
+}
+
+/// SplitMix64 step; stable across platforms and Rust versions
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// FNV-1a hash; stable across platforms and Rust versions
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_mock_embedder_is_deterministic_unit_length() {
+        let embedder = MockEmbedder::new(64);
+        let first = embedder.embed(&["Central Park fountain"]).unwrap();
+        let second = MockEmbedder::new(64).embed(&["central park, fountain"]).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first[0].len(), 64);
+        assert!((cosine(&first[0], &first[0]) - 1.0).abs() < 1e-5);
+        assert_eq!(embedder.model_name(), "mock:64");
+
+        let empty = embedder.embed(&[""]).unwrap();
+        assert!((cosine(&empty[0], &empty[0]) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mock_embedder_ranks_shared_words_higher() {
+        let embedder = MockEmbedder::new(256);
+        let vectors = embedder
+            .embed(&["flood risk near river", "river flood warning", "bakery opening hours"])
+            .unwrap();
+
+        assert!(cosine(&vectors[0], &vectors[1]) > cosine(&vectors[0], &vectors[2]));
+        assert!(cosine(&vectors[0], &vectors[2]) >= 0.0);
+    }
+
+    #[test]
+    fn test_keyword_embedder_counts_vocabulary_terms() {
+        let embedder = KeywordEmbedder::new(&["park", "bench"]);
+        let vectors = embedder.embed(&["Park bench, park", "market stall"]).unwrap();
+
+        assert_eq!(vectors, vec![vec![2.0, 1.0], vec![0.0, 0.0]]);
+        assert_eq!(embedder.dimensions(), 2);
+    }
+
+    #[test]
+    fn test_mock_generator_echoes_excerpts() {
+        let answer = MockGenerator::new().generate("Where?", &["first", "second"]).unwrap();
+        assert_eq!(answer, "Answer to \"Where?\" from 2 excerpt(s):\n[1] first\n[2] second");
+    }
+}
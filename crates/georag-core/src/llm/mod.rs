@@ -1,7 +1,17 @@
+pub mod dimensions;
 pub mod embedding;
+pub mod factory;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod ollama;
 pub mod ports;
+pub mod request;
 
+pub use dimensions::{DimensionCheck, DimensionCheckMode};
 pub use embedding::{create_embedding, create_embedding_with_spatial_metadata};
-pub use ollama::OllamaEmbedder;
+pub use factory::{create_embedder, AnyEmbedder, EmbedderOptions, EmbedderSpec};
+#[cfg(feature = "mock")]
+pub use mock::{KeywordEmbedder, MockEmbedder, MockGenerator};
+pub use ollama::{OllamaEmbedder, OllamaGenerator, PullProgress};
 pub use ports::{Embedder, Generator};
+pub use request::{RequestFailure, RequestPolicy};
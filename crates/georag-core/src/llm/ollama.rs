@@ -1,6 +1,58 @@
 use crate::error::{GeoragError, Result};
 use crate::llm::ports::Embedder;
+use crate::llm::request::{RequestFailure, RequestPolicy};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Default time allowed for pulling a missing model
+pub const DEFAULT_PULL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Callback receiving progress updates while a model is pulled
+pub type PullProgressFn = Arc<dyn Fn(&PullProgress) + Send + Sync>;
+
+/// Progress update streamed by Ollama's pull API
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PullProgress {
+    /// Pull phase (e.g. "pulling manifest", "downloading", "success")
+    #[serde(default)]
+    pub status: String,
+
+    /// Layer being downloaded
+    pub digest: Option<String>,
+
+    /// Layer size in bytes
+    pub total: Option<u64>,
+
+    /// Bytes of the layer downloaded so far
+    pub completed: Option<u64>,
+}
+
+impl PullProgress {
+    /// Download progress of the current layer in percent
+    pub fn percent(&self) -> Option<u64> {
+        match (self.completed, self.total) {
+            (Some(completed), Some(total)) if total > 0 => Some(completed * 100 / total),
+            _ => None,
+        }
+    }
+}
+
+/// (base URL, model) pairs confirmed available, cached for the process lifetime
+fn available_models() -> &'static Mutex<HashSet<(String, String)>> {
+    static AVAILABLE: OnceLock<Mutex<HashSet<(String, String)>>> = OnceLock::new();
+    AVAILABLE.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Embedding dimensions probed per (base URL, model), cached for the process lifetime
+fn probed_dimensions() -> &'static Mutex<HashMap<(String, String), usize>> {
+    static PROBED: OnceLock<Mutex<HashMap<(String, String), usize>>> = OnceLock::new();
+    PROBED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Text embedded to learn a model's dimensions
+const DIMENSION_PROBE: &str = "dimension probe";
 
 /// Ollama embedder implementation
 pub struct OllamaEmbedder {
@@ -15,6 +67,18 @@ pub struct OllamaEmbedder {
 
     /// HTTP client
     client: reqwest::Client,
+
+    /// Pull the model when it is not installed
+    auto_pull: bool,
+
+    /// Time allowed for pulling the model
+    pull_timeout: Duration,
+
+    /// Receives pull progress updates
+    pull_progress: Option<PullProgressFn>,
+
+    /// Timeout and retries of each embedding request
+    request_policy: RequestPolicy,
 }
 
 impl OllamaEmbedder {
@@ -25,6 +89,10 @@ impl OllamaEmbedder {
             model: model.into(),
             dimensions,
             client: reqwest::Client::new(),
+            auto_pull: false,
+            pull_timeout: DEFAULT_PULL_TIMEOUT,
+            pull_progress: None,
+            request_policy: RequestPolicy::default(),
         }
     }
 
@@ -32,6 +100,303 @@ impl OllamaEmbedder {
     pub fn localhost(model: impl Into<String>, dimensions: usize) -> Self {
         Self::new("http://localhost:11434", model, dimensions)
     }
+
+    /// Pull the model through Ollama when it is not installed
+    pub fn with_auto_pull(mut self, enabled: bool) -> Self {
+        self.auto_pull = enabled;
+        self
+    }
+
+    /// Set the time allowed for pulling the model
+    pub fn with_pull_timeout(mut self, timeout: Duration) -> Self {
+        self.pull_timeout = timeout;
+        self
+    }
+
+    /// Set the timeout and retries of each embedding request
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.request_policy = policy;
+        self
+    }
+
+    /// Receive progress updates while the model is pulled
+    pub fn with_pull_progress(
+        mut self,
+        callback: impl Fn(&PullProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.pull_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Make sure the model is installed in Ollama
+    ///
+    /// Queries `/api/tags` and, if the model is missing, either fails with the
+    /// `ollama pull` command to run or pulls it when auto-pull is enabled.
+    /// A successful check is cached for the rest of the process.
+    pub async fn ensure_model(&self) -> Result<()> {
+        let key = (self.base_url.clone(), self.model.clone());
+        if available_models().lock().unwrap().contains(&key) {
+            return Ok(());
+        }
+
+        let installed = self.list_models().await?;
+        if !installed.iter().any(|name| model_matches(name, &self.model)) {
+            if !self.auto_pull {
+                return Err(GeoragError::EmbedderUnavailable {
+                    reason: format!(
+                        "Model '{}' is not installed in Ollama at {}",
+                        self.model, self.base_url
+                    ),
+                    remediation: format!(
+                        "Run 'ollama pull {}', or set auto_pull = true to download it automatically",
+                        self.model
+                    ),
+                });
+            }
+
+            tracing::info!(model = %self.model, "Model not installed, pulling from Ollama");
+            tokio::time::timeout(self.pull_timeout, self.pull_model()).await.map_err(|_| {
+                GeoragError::EmbedderUnavailable {
+                    reason: format!(
+                        "Pulling model '{}' timed out after {}s",
+                        self.model,
+                        self.pull_timeout.as_secs()
+                    ),
+                    remediation: format!(
+                        "Run 'ollama pull {}' manually; Ollama resumes partial downloads",
+                        self.model
+                    ),
+                }
+            })??;
+            tracing::info!(model = %self.model, "Model pulled");
+        }
+
+        available_models().lock().unwrap().insert(key);
+        Ok(())
+    }
+
+    /// Dimensions of the embeddings the model actually returns
+    ///
+    /// Embeds a short probe text, whatever dimensions the embedder was
+    /// created with. The answer is cached for the rest of the process.
+    pub async fn probe_dimensions(&self) -> Result<usize> {
+        let key = (self.base_url.clone(), self.model.clone());
+        if let Some(dimensions) = probed_dimensions().lock().unwrap().get(&key) {
+            return Ok(*dimensions);
+        }
+
+        self.ensure_model().await?;
+        let embedding = self
+            .request_policy
+            .run(
+                || self.request_embedding(DIMENSION_PROBE),
+                |timeout| GeoragError::EmbedderUnavailable {
+                    reason: format!(
+                        "Ollama did not return the probe embedding within {}s",
+                        timeout.as_secs()
+                    ),
+                    remediation: format!("Check that Ollama at {} is responding", self.base_url),
+                },
+            )
+            .await?;
+
+        probed_dimensions().lock().unwrap().insert(key, embedding.len());
+        Ok(embedding.len())
+    }
+
+    /// List the models installed in Ollama
+    pub async fn list_models(&self) -> Result<Vec<String>> {
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(|e| self.connection_error(e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(GeoragError::EmbedderUnavailable {
+                reason: format!("Ollama API error ({}): {}", status, error_text),
+                remediation: "Check Ollama API compatibility".to_string(),
+            });
+        }
+
+        let tags: OllamaTagsResponse =
+            response.json().await.map_err(|e| GeoragError::EmbedderUnavailable {
+                reason: format!("Failed to parse Ollama model list: {}", e),
+                remediation: "Check Ollama API compatibility".to_string(),
+            })?;
+
+        Ok(tags.models.into_iter().map(|m| m.name).collect())
+    }
+
+    /// Pull the model, streaming progress until Ollama reports success
+    async fn pull_model(&self) -> Result<()> {
+        let request = OllamaPullRequest { model: self.model.clone(), stream: true };
+        let mut response = self
+            .client
+            .post(format!("{}/api/pull", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| self.connection_error(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(pull_error(&self.model, &error_text));
+        }
+
+        // Progress arrives as newline-delimited JSON
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut last_status = String::new();
+        loop {
+            let chunk = response.chunk().await.map_err(|e| {
+                partial_pull_error(&self.model, &format!("{} (last status: {})", e, last_status))
+            })?;
+            let Some(chunk) = chunk else { break };
+
+            buffer.extend_from_slice(&chunk);
+            while let Some(newline) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                if self.handle_pull_line(&line, &mut last_status)? {
+                    return Ok(());
+                }
+            }
+        }
+
+        if self.handle_pull_line(&buffer, &mut last_status)? {
+            return Ok(());
+        }
+
+        Err(partial_pull_error(
+            &self.model,
+            &format!("stream ended before completion (last status: {})", last_status),
+        ))
+    }
+
+    /// Handle one line of pull output, returning true once the pull succeeded
+    fn handle_pull_line(&self, line: &[u8], last_status: &mut String) -> Result<bool> {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(false);
+        }
+
+        let message: OllamaPullMessage =
+            serde_json::from_str(line).map_err(|e| GeoragError::EmbedderUnavailable {
+                reason: format!("Failed to parse Ollama pull progress: {}", e),
+                remediation: "Check Ollama API compatibility".to_string(),
+            })?;
+
+        if let Some(error) = message.error {
+            return Err(pull_error(&self.model, &error));
+        }
+
+        let progress = message.progress;
+        if progress.status != *last_status {
+            tracing::info!(model = %self.model, status = %progress.status, "Pulling model");
+            last_status.clone_from(&progress.status);
+        }
+        if let Some(callback) = &self.pull_progress {
+            callback(&progress);
+        }
+
+        Ok(progress.status == "success")
+    }
+
+    /// One attempt at embedding a text
+    async fn request_embedding(&self, text: &str) -> std::result::Result<Vec<f32>, RequestFailure> {
+        let request = OllamaEmbedRequest {
+            model: self.model.clone(),
+            prompt: text.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/embeddings", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| RequestFailure::Transient(self.connection_error(e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            let error = GeoragError::EmbedderUnavailable {
+                reason: format!("Ollama API error ({}): {}", status, error_text),
+                remediation: format!(
+                    "Check that the model '{}' is available. Run 'ollama list' to see installed models.",
+                    self.model
+                ),
+            };
+            return Err(if status.is_server_error() {
+                RequestFailure::Transient(error)
+            } else {
+                RequestFailure::Fatal(error)
+            });
+        }
+
+        let embed_response: OllamaEmbedResponse = response.json().await.map_err(|e| {
+            RequestFailure::Fatal(GeoragError::EmbedderUnavailable {
+                reason: format!("Failed to parse Ollama response: {}", e),
+                remediation: "Check Ollama API compatibility".to_string(),
+            })
+        })?;
+
+        Ok(embed_response.embedding)
+    }
+
+    fn connection_error(&self, e: reqwest::Error) -> GeoragError {
+        GeoragError::EmbedderUnavailable {
+            reason: format!("Failed to connect to Ollama: {}", e),
+            remediation: format!(
+                "Ensure Ollama is running at {} and the model '{}' is available. \
+                 Run 'ollama pull {}' to download the model.",
+                self.base_url, self.model, self.model
+            ),
+        }
+    }
+}
+
+/// Check an installed model name against the configured one (`model` matches `model:latest`)
+fn model_matches(installed: &str, wanted: &str) -> bool {
+    installed == wanted
+        || (!wanted.contains(':') && installed.strip_suffix(":latest") == Some(wanted))
+}
+
+/// Error for a pull that Ollama rejected, with remediation for common causes
+fn pull_error(model: &str, error: &str) -> GeoragError {
+    let lower = error.to_lowercase();
+    let remediation = if lower.contains("no space left") || lower.contains("disk full") {
+        format!(
+            "Free disk space in Ollama's model directory (OLLAMA_MODELS), then run 'ollama pull {}'",
+            model
+        )
+    } else if lower.contains("file does not exist") || lower.contains("manifest unknown") {
+        format!(
+            "Check that '{}' exists in the Ollama library (https://ollama.com/library)",
+            model
+        )
+    } else {
+        format!("Run 'ollama pull {}' to retry; Ollama resumes partial downloads", model)
+    };
+
+    GeoragError::EmbedderUnavailable {
+        reason: format!("Pulling model '{}' failed: {}", model, error.trim()),
+        remediation,
+    }
+}
+
+/// Error for a pull that stopped before Ollama reported success
+fn partial_pull_error(model: &str, detail: &str) -> GeoragError {
+    GeoragError::EmbedderUnavailable {
+        reason: format!("Pull of model '{}' did not complete: {}", model, detail),
+        remediation: format!(
+            "Run 'ollama pull {}' to finish the download; Ollama resumes partial downloads",
+            model
+        ),
+    }
 }
 
 impl Embedder for OllamaEmbedder {
@@ -44,50 +409,28 @@ impl Embedder for OllamaEmbedder {
             })?;
 
         runtime.block_on(async {
-            let mut embeddings = Vec::with_capacity(texts.len());
+            self.ensure_model().await?;
 
+            let mut embeddings = Vec::with_capacity(texts.len());
             for text in texts {
-                let request = OllamaEmbedRequest {
-                    model: self.model.clone(),
-                    prompt: text.to_string(),
-                };
-
-                let response = self
-                    .client
-                    .post(format!("{}/api/embeddings", self.base_url))
-                    .json(&request)
-                    .send()
-                    .await
-                    .map_err(|e| GeoragError::EmbedderUnavailable {
-                        reason: format!("Failed to connect to Ollama: {}", e),
-                        remediation: format!(
-                            "Ensure Ollama is running at {} and the model '{}' is available. \
-                             Run 'ollama pull {}' to download the model.",
-                            self.base_url, self.model, self.model
-                        ),
-                    })?;
-
-                if !response.status().is_success() {
-                    let status = response.status();
-                    let error_text = response.text().await.unwrap_or_default();
-                    return Err(GeoragError::EmbedderUnavailable {
-                        reason: format!("Ollama API error ({}): {}", status, error_text),
-                        remediation: format!(
-                            "Check that the model '{}' is available. Run 'ollama list' to see installed models.",
-                            self.model
-                        ),
-                    });
-                }
-
-                let embed_response: OllamaEmbedResponse = response
-                    .json()
-                    .await
-                    .map_err(|e| GeoragError::EmbedderUnavailable {
-                        reason: format!("Failed to parse Ollama response: {}", e),
-                        remediation: "Check Ollama API compatibility".to_string(),
-                    })?;
-
-                embeddings.push(embed_response.embedding);
+                let embedding = self
+                    .request_policy
+                    .run(
+                        || self.request_embedding(text),
+                        |timeout| GeoragError::EmbedderUnavailable {
+                            reason: format!(
+                                "Ollama did not return an embedding within {}s",
+                                timeout.as_secs()
+                            ),
+                            remediation: format!(
+                                "Check that Ollama at {} is not overloaded, or raise the \
+                                 embedder request timeout",
+                                self.base_url
+                            ),
+                        },
+                    )
+                    .await?;
+                embeddings.push(embedding);
             }
 
             Ok(embeddings)
@@ -103,6 +446,120 @@ impl Embedder for OllamaEmbedder {
     }
 }
 
+/// Ollama text generation
+///
+/// Prompts are answered in one response without streaming. Each request
+/// runs under the generator's [`RequestPolicy`].
+#[derive(Debug, Clone)]
+pub struct OllamaGenerator {
+    /// Base URL for Ollama API (e.g., "http://localhost:11434")
+    base_url: String,
+
+    /// Model name to generate with
+    model: String,
+
+    /// HTTP client
+    client: reqwest::Client,
+
+    /// Timeout and retries of each generation request
+    request_policy: RequestPolicy,
+}
+
+impl OllamaGenerator {
+    /// Create a new Ollama generator
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            model: model.into(),
+            client: reqwest::Client::new(),
+            request_policy: RequestPolicy::default(),
+        }
+    }
+
+    /// Set the timeout and retries of each generation request
+    pub fn with_request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.request_policy = policy;
+        self
+    }
+
+    /// Get the name of the generation model
+    pub fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    /// Answer a prompt
+    pub async fn complete(&self, prompt: &str) -> Result<String> {
+        self.request_policy
+            .run(
+                || self.request_completion(prompt),
+                |timeout| GeoragError::GeneratorUnavailable {
+                    reason: format!(
+                        "Ollama did not answer within {}s using '{}'",
+                        timeout.as_secs(),
+                        self.model
+                    ),
+                    remediation: format!(
+                        "Check that Ollama at {} is not overloaded, or use a smaller model",
+                        self.base_url
+                    ),
+                },
+            )
+            .await
+    }
+
+    /// One attempt at answering a prompt
+    async fn request_completion(
+        &self,
+        prompt: &str,
+    ) -> std::result::Result<String, RequestFailure> {
+        let request = OllamaGenerateRequest {
+            model: self.model.clone(),
+            prompt: prompt.to_string(),
+            stream: false,
+            options: OllamaGenerateOptions { temperature: 0.0 },
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.base_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| {
+                RequestFailure::Transient(GeoragError::GeneratorUnavailable {
+                    reason: format!("Failed to connect to Ollama: {}", e),
+                    remediation: format!("Ensure Ollama is running at {}", self.base_url),
+                })
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            let error = GeoragError::GeneratorUnavailable {
+                reason: format!("Ollama API error ({}): {}", status, error_text),
+                remediation: format!(
+                    "Run 'ollama pull {}' if the model is not installed",
+                    self.model
+                ),
+            };
+            return Err(if status.is_server_error() {
+                RequestFailure::Transient(error)
+            } else {
+                RequestFailure::Fatal(error)
+            });
+        }
+
+        let generated: OllamaGenerateResponse = response.json().await.map_err(|e| {
+            RequestFailure::Fatal(GeoragError::GeneratorUnavailable {
+                reason: format!("Failed to parse Ollama response: {}", e),
+                remediation: "Check Ollama API compatibility".to_string(),
+            })
+        })?;
+
+        Ok(generated.response)
+    }
+}
+
 /// Request body for Ollama embeddings API
 #[derive(Debug, Serialize)]
 struct OllamaEmbedRequest {
@@ -116,6 +573,53 @@ struct OllamaEmbedResponse {
     embedding: Vec<f32>,
 }
 
+/// Request body for Ollama generate API
+#[derive(Debug, Serialize)]
+struct OllamaGenerateRequest {
+    model: String,
+    prompt: String,
+    stream: bool,
+    options: OllamaGenerateOptions,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaGenerateOptions {
+    temperature: f32,
+}
+
+/// Response from Ollama generate API
+#[derive(Debug, Deserialize)]
+struct OllamaGenerateResponse {
+    response: String,
+}
+
+/// Response from Ollama tags API
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<OllamaModelTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModelTag {
+    name: String,
+}
+
+/// Request body for Ollama pull API
+#[derive(Debug, Serialize)]
+struct OllamaPullRequest {
+    model: String,
+    stream: bool,
+}
+
+/// One line of Ollama pull output: progress or an error
+#[derive(Debug, Deserialize)]
+struct OllamaPullMessage {
+    error: Option<String>,
+    #[serde(flatten)]
+    progress: PullProgress,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,4 +638,57 @@ mod tests {
         assert_eq!(embedder.model_name(), "test-model");
         assert_eq!(embedder.dimensions(), 512);
     }
+
+    #[test]
+    fn test_ollama_generator_creation() {
+        let generator = OllamaGenerator::new("http://custom:11434", "llama3.2:1b");
+        assert_eq!(generator.base_url, "http://custom:11434");
+        assert_eq!(generator.model_name(), "llama3.2:1b");
+        assert_eq!(generator.request_policy, RequestPolicy::default());
+    }
+
+    #[test]
+    fn test_model_matches_latest_tag() {
+        assert!(model_matches("nomic-embed-text:latest", "nomic-embed-text"));
+        assert!(model_matches("nomic-embed-text:v1.5", "nomic-embed-text:v1.5"));
+        assert!(!model_matches("nomic-embed-text:v1.5", "nomic-embed-text"));
+        assert!(!model_matches("all-minilm:latest", "nomic-embed-text"));
+    }
+
+    #[test]
+    fn test_pull_lines() {
+        let embedder = OllamaEmbedder::localhost("test-model", 8);
+        let mut status = String::new();
+
+        let downloading =
+            br#"{"status":"downloading","digest":"sha256:abc","total":200,"completed":50}"#;
+        assert!(!embedder.handle_pull_line(downloading, &mut status).unwrap());
+        assert_eq!(status, "downloading");
+
+        assert!(embedder.handle_pull_line(br#"{"status":"success"}"#, &mut status).unwrap());
+    }
+
+    #[test]
+    fn test_pull_disk_full_error() {
+        let embedder = OllamaEmbedder::localhost("test-model", 8);
+        let mut status = String::new();
+
+        let line =
+            br#"{"error":"write /root/.ollama/models/blobs/sha256-x: no space left on device"}"#;
+        let err = embedder.handle_pull_line(line, &mut status).unwrap_err().to_string();
+
+        assert!(err.contains("no space left on device"), "{}", err);
+        assert!(err.contains("Free disk space"), "{}", err);
+    }
+
+    #[test]
+    fn test_pull_progress_percent() {
+        let progress = PullProgress {
+            total: Some(200),
+            completed: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(progress.percent(), Some(25));
+        assert_eq!(PullProgress::default().percent(), None);
+    }
 }
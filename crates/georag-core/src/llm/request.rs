@@ -0,0 +1,192 @@
+//! Timeouts and retries for model server requests
+//!
+//! Every request to a model server runs under a [`RequestPolicy`]. An attempt
+//! that takes longer than the policy's timeout is abandoned, and a transient
+//! failure (a timeout, a refused connection or a server error) is retried
+//! after a delay that doubles with each retry. Failures that would repeat,
+//! such as a missing model, are returned at once.
+
+use crate::error::{GeoragError, Result};
+use std::future::Future;
+use std::time::Duration;
+
+/// Time allowed for one attempt at a request by default
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Retries after a transient failure by default
+pub const DEFAULT_REQUEST_RETRIES: u32 = 2;
+
+/// Delay before the first retry by default
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Failure of one attempt at a request
+#[derive(Debug)]
+pub enum RequestFailure {
+    /// The next attempt may succeed
+    Transient(GeoragError),
+
+    /// Every attempt would fail the same way
+    Fatal(GeoragError),
+}
+
+/// Timeout and retries applied to each request to a model server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestPolicy {
+    /// Time allowed for one attempt
+    pub timeout: Duration,
+
+    /// Retries after a transient failure
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for each later one
+    pub backoff: Duration,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retries: DEFAULT_REQUEST_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+        }
+    }
+}
+
+impl RequestPolicy {
+    /// Set the time allowed for one attempt
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of retries after a transient failure
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set the delay before the first retry
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Run a request, retrying transient failures
+    ///
+    /// `attempt` is called once per attempt. `timed_out` builds the error for
+    /// an attempt that exceeded the timeout; when the retries run out, the
+    /// last attempt's error is returned.
+    pub async fn run<T, F, Fut>(
+        &self,
+        mut attempt: F,
+        timed_out: impl Fn(Duration) -> GeoragError,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, RequestFailure>>,
+    {
+        let mut delay = self.backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let failure = match tokio::time::timeout(self.timeout, attempt()).await {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(failure)) => failure,
+                Err(_) => RequestFailure::Transient(timed_out(self.timeout)),
+            };
+
+            match failure {
+                RequestFailure::Transient(error) if attempts <= self.retries => {
+                    tracing::warn!(attempt = attempts, error = %error, "Model request failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                RequestFailure::Transient(error) | RequestFailure::Fatal(error) => {
+                    return Err(error)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn policy() -> RequestPolicy {
+        RequestPolicy::default()
+            .with_timeout(Duration::from_millis(50))
+            .with_backoff(Duration::ZERO)
+    }
+
+    fn error(reason: &str) -> GeoragError {
+        GeoragError::EmbedderUnavailable {
+            reason: reason.to_string(),
+            remediation: "none".to_string(),
+        }
+    }
+
+    fn timed_out(timeout: Duration) -> GeoragError {
+        error(&format!("timed out after {}ms", timeout.as_millis()))
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let attempts = &AtomicU32::new(0);
+
+        let value = policy()
+            .run(
+                move || async move {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => Err(RequestFailure::Transient(error("connection refused"))),
+                        _ => Ok(42),
+                    }
+                },
+                timed_out,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(value, 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fatal_failures_are_not_retried() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<()> = policy()
+            .run(
+                move || async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(RequestFailure::Fatal(error("model not found")))
+                },
+                timed_out,
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("model not found"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_count_as_attempts() {
+        let attempts = &AtomicU32::new(0);
+
+        let result: Result<()> = policy()
+            .with_retries(1)
+            .run(
+                move || async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(())
+                },
+                timed_out,
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("timed out after 50ms"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
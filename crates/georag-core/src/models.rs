@@ -1,14 +1,63 @@
+pub mod area;
+pub mod audit;
+pub mod build_diff;
+pub mod change;
+pub mod citation;
+pub mod confidence;
 pub mod dataset;
 pub mod document;
 pub mod geometry;
+pub mod preset;
+pub mod preview;
 pub mod query;
+pub mod schema;
 pub mod workspace;
 
-pub use dataset::{Dataset, DatasetId, DatasetMeta};
-pub use document::{ChunkId, ChunkMetadata, ChunkSource, Embedding, SpatialMetadata, TextChunk};
+pub use area::{normalize_area_name, AreaSource, SavedArea, MAX_AREA_NAME_LEN};
+pub use audit::{
+    count_events, AuditEvent, AuditEventKind, EventCount, Timeline, TimelineBucket,
+    TimelineGranularity, DEFAULT_TIMELINE_DAYS, MAX_TIMELINE_DAYS,
+};
+pub use build_diff::{
+    BuildDiff, DatasetDelta, Delta, DiffBaseline, EmbedderChange, IndexContents, PreviousIndex,
+};
+pub use change::{
+    ChangeEntity, ChangeEvent, ChangeOperation, EventLogBounds, EventRetention, DEFAULT_EVENT_PAGE,
+    DEFAULT_EVENT_RETENTION_DAYS, DEFAULT_EVENT_RETENTION_EVENTS, MAX_EVENT_PAGE,
+    MAX_EVENT_RECORDS,
+};
+pub use citation::{
+    dataset_slug, relative_document_path, CitationFields, SourceUrlTemplate,
+    SOURCE_URL_PLACEHOLDERS,
+};
+pub use confidence::{
+    ConfidenceCutoffs, ConfidenceLevel, ResultConfidence, ScoreBaseline, BASELINE_SEED,
+    CONFIDENCE_CAVEAT, DEFAULT_BASELINE_LEVELS, DEFAULT_BASELINE_PAIRS,
+};
+pub use dataset::{
+    distinct_attributions, normalize_credit, normalize_tags, sort_datasets, source_content_type,
+    Dataset, DatasetId, DatasetMeta, DatasetSort, RemoteSource, SortOrder, SourceFile,
+    TagVisibility, ARCHIVED_TAG,
+};
+pub use document::{
+    ChunkId, ChunkMetadata, ChunkSource, DatasetIndexStats, Embedding, SpatialMetadata, TextChunk,
+};
 pub use geometry::{
-    Crs, Distance, DistanceUnit, Geometry, GeometryType, SpatialFilter, SpatialPredicate,
-    ValidityMode,
+    AxisOrder, AxisOrderDecision, Crs, Distance, DistanceUnit, Geometry, GeometryType,
+    SpatialFilter, SpatialPredicate, ValidityMode,
+};
+pub use preset::{normalize_preset_name, QueryPreset, MAX_PRESET_NAME_LEN};
+pub use preview::{
+    DatasetPreview, DocumentPreview, GeometryTypeCount, PreviewBuilder, VectorPreview,
+    PREVIEW_PROPERTY_KEYS, PREVIEW_SNIPPET_CHARS,
+};
+pub use query::{Feature, FeatureId, ScoredResult, PART_OF_PROPERTY};
+pub use schema::{
+    data_dictionary, ChunkingSettings, DatasetSchema, PropertySchema, PropertyType, SchemaBuilder,
+    SCHEMA_EXAMPLES, SCHEMA_EXAMPLE_CHARS,
+};
+pub use workspace::{
+    BuildCheckpoint, IndexState, QuotaResource, UsageDelta, Workspace, WorkspaceConfig,
+    WorkspaceId, WorkspaceMeta, WorkspaceQuotas, WorkspaceUsage, INDEX_SCHEMA_VERSION,
+    UNVERSIONED_INDEX_SCHEMA,
 };
-pub use query::{Feature, FeatureId, ScoredResult};
-pub use workspace::{IndexState, Workspace, WorkspaceConfig, WorkspaceId, WorkspaceMeta};
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{DatasetId, FeatureId, Geometry};
+
+/// Longest name a saved area may have
+pub const MAX_AREA_NAME_LEN: usize = 64;
+
+/// Named geometry saved in a workspace and reusable as a query filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedArea {
+    /// Normalized name, unique within the workspace
+    pub name: String,
+
+    /// Area geometry in WGS 84
+    pub geometry: Geometry,
+
+    /// Feature the area was copied from, if it was created from one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<AreaSource>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavedArea {
+    /// Create an area saved now
+    pub fn new(name: impl Into<String>, geometry: Geometry) -> Self {
+        Self {
+            name: name.into(),
+            geometry,
+            source: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Record the feature the area was copied from
+    pub fn with_source(mut self, source: AreaSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Number of vertices in the area geometry
+    pub fn vertex_count(&self) -> usize {
+        self.geometry.vertex_count()
+    }
+}
+
+/// Feature a saved area's geometry was copied from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AreaSource {
+    pub dataset_id: DatasetId,
+    pub feature_id: FeatureId,
+}
+
+/// Normalize an area name: trimmed and lowercased
+///
+/// Names may hold ASCII letters, digits, `-`, `_` and `.`, and are at most
+/// [`MAX_AREA_NAME_LEN`] characters long. Returns the reason when the name
+/// is not acceptable.
+pub fn normalize_area_name(name: &str) -> std::result::Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err("Area name must not be empty".to_string());
+    }
+    if name.len() > MAX_AREA_NAME_LEN {
+        return Err(format!("Area name must be at most {} characters", MAX_AREA_NAME_LEN));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || "-_.".contains(*c))) {
+        return Err(format!(
+            "Area name may only hold letters, digits, '-', '_' and '.', found '{}'",
+            c
+        ));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_area_name() {
+        assert_eq!(normalize_area_name("  Project-X ").unwrap(), "project-x");
+        assert_eq!(normalize_area_name("zone_1.north").unwrap(), "zone_1.north");
+        assert!(normalize_area_name("   ").is_err());
+        assert!(normalize_area_name("project x").is_err());
+        assert!(normalize_area_name("proyek/utara").is_err());
+        assert!(normalize_area_name(&"a".repeat(MAX_AREA_NAME_LEN + 1)).is_err());
+        assert!(normalize_area_name(&"a".repeat(MAX_AREA_NAME_LEN)).is_ok());
+    }
+
+    #[test]
+    fn test_saved_area_serialization_omits_missing_source() {
+        let area = SavedArea::new("site", Geometry::point(106.8, -6.2));
+        let json = serde_json::to_value(&area).unwrap();
+        assert!(json.get("source").is_none());
+
+        let area = area.with_source(AreaSource {
+            dataset_id: DatasetId(3),
+            feature_id: FeatureId(7),
+        });
+        let back: SavedArea = serde_json::from_value(serde_json::to_value(&area).unwrap()).unwrap();
+        assert_eq!(back, area);
+    }
+}
@@ -0,0 +1,303 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest range a timeline may cover
+pub const MAX_TIMELINE_DAYS: i64 = 90;
+
+/// Range a timeline covers when none is given
+pub const DEFAULT_TIMELINE_DAYS: i64 = 14;
+
+/// What happened in a workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    DatasetAdded,
+    DatasetDeleted,
+    BuildCompleted,
+    BuildFailed,
+    Query,
+    /// An ingest or build refused because it would exceed a quota
+    QuotaExceeded,
+    /// The workspace's redaction rules were set, changed or removed
+    RedactionChanged,
+}
+
+impl AuditEventKind {
+    /// All kinds, in reporting order
+    pub const ALL: [AuditEventKind; 7] = [
+        Self::DatasetAdded,
+        Self::DatasetDeleted,
+        Self::BuildCompleted,
+        Self::BuildFailed,
+        Self::Query,
+        Self::QuotaExceeded,
+        Self::RedactionChanged,
+    ];
+
+    /// Name stored in the audit table and used in API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DatasetAdded => "dataset_added",
+            Self::DatasetDeleted => "dataset_deleted",
+            Self::BuildCompleted => "build_completed",
+            Self::BuildFailed => "build_failed",
+            Self::Query => "query",
+            Self::QuotaExceeded => "quota_exceeded",
+            Self::RedactionChanged => "redaction_changed",
+        }
+    }
+
+    /// Whether events of this kind are listed one by one in a timeline
+    pub fn is_notable(&self) -> bool {
+        matches!(self, Self::BuildFailed | Self::QuotaExceeded | Self::RedactionChanged)
+    }
+}
+
+impl std::fmt::Display for AuditEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for AuditEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown audit event kind '{}'", s))
+    }
+}
+
+/// One thing that happened in a workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub kind: AuditEventKind,
+
+    pub at: DateTime<Utc>,
+
+    /// What the event concerned, e.g. the dataset name or the error of a failed build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl AuditEvent {
+    /// Create an event that happened now
+    pub fn new(kind: AuditEventKind) -> Self {
+        Self { kind, at: Utc::now(), details: None }
+    }
+
+    /// Describe what the event concerned
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Width of the buckets a timeline counts events in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineGranularity {
+    Hour,
+    #[default]
+    Day,
+    /// Weeks starting on Monday
+    Week,
+}
+
+impl TimelineGranularity {
+    /// Name accepted by `granularity` and passed to PostgreSQL's `date_trunc`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    /// Start of the bucket holding `at`
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let day = at.duration_trunc(Duration::days(1)).unwrap_or(at);
+        match self {
+            Self::Hour => at.duration_trunc(Duration::hours(1)).unwrap_or(at),
+            Self::Day => day,
+            Self::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+        }
+    }
+
+    /// Width of one bucket
+    pub fn step(&self) -> Duration {
+        match self {
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        }
+    }
+}
+
+impl std::fmt::Display for TimelineGranularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TimelineGranularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hour" => Ok(Self::Hour),
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            other => Err(format!("unknown granularity '{}': expected hour, day or week", other)),
+        }
+    }
+}
+
+/// Number of events of one kind in one bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventCount {
+    pub bucket: DateTime<Utc>,
+    pub kind: AuditEventKind,
+    pub count: u64,
+}
+
+/// Count events per bucket and kind, for stores without their own aggregation
+pub fn count_events(events: &[AuditEvent], granularity: TimelineGranularity) -> Vec<EventCount> {
+    let mut counts: BTreeMap<(DateTime<Utc>, AuditEventKind), u64> = BTreeMap::new();
+    for event in events {
+        *counts.entry((granularity.bucket_start(event.at), event.kind)).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|((bucket, kind), count)| EventCount { bucket, kind, count })
+        .collect()
+}
+
+/// Events of one bucket of a timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineBucket {
+    pub start: DateTime<Utc>,
+
+    /// Events per kind; every kind is present, most with 0
+    pub counts: BTreeMap<AuditEventKind, u64>,
+}
+
+/// What happened in a workspace over a range, counted per bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timeline {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub granularity: TimelineGranularity,
+
+    /// One bucket per step from the one holding `since` to the one holding `until`
+    pub buckets: Vec<TimelineBucket>,
+
+    /// Events per kind over the whole range
+    pub totals: BTreeMap<AuditEventKind, u64>,
+
+    /// Failed builds and quota refusals with their details, newest first
+    pub notable: Vec<AuditEvent>,
+}
+
+impl Timeline {
+    /// Lay counted events out in contiguous buckets, filling empty ones with zeros
+    pub fn from_counts(
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        granularity: TimelineGranularity,
+        counts: &[EventCount],
+        notable: Vec<AuditEvent>,
+    ) -> Self {
+        let zeros: BTreeMap<AuditEventKind, u64> =
+            AuditEventKind::ALL.into_iter().map(|kind| (kind, 0)).collect();
+
+        let mut buckets = Vec::new();
+        let mut start = granularity.bucket_start(since);
+        while start <= until {
+            buckets.push(TimelineBucket { start, counts: zeros.clone() });
+            start += granularity.step();
+        }
+
+        let mut totals = zeros;
+        for count in counts {
+            let index = (count.bucket - granularity.bucket_start(since)).num_seconds()
+                / granularity.step().num_seconds();
+            if let Some(bucket) = usize::try_from(index).ok().and_then(|i| buckets.get_mut(i)) {
+                *bucket.counts.entry(count.kind).or_default() += count.count;
+                *totals.entry(count.kind).or_default() += count.count;
+            }
+        }
+
+        Self {
+            since,
+            until,
+            granularity,
+            buckets,
+            totals,
+            notable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, day, hour, 30, 0).unwrap()
+    }
+
+    fn event(kind: AuditEventKind, at: DateTime<Utc>) -> AuditEvent {
+        AuditEvent { kind, at, details: None }
+    }
+
+    #[test]
+    fn test_bucket_start() {
+        // 2026-03-04 is a Wednesday
+        assert_eq!(
+            TimelineGranularity::Hour.bucket_start(at(4, 13)),
+            at(4, 13) - Duration::minutes(30)
+        );
+        assert_eq!(
+            TimelineGranularity::Day.bucket_start(at(4, 13)),
+            Utc.with_ymd_and_hms(2026, 3, 4, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            TimelineGranularity::Week.bucket_start(at(4, 13)),
+            Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_timeline_fills_empty_buckets() {
+        let events = [
+            event(AuditEventKind::Query, at(2, 9)),
+            event(AuditEventKind::Query, at(2, 17)),
+            event(AuditEventKind::BuildFailed, at(4, 8)),
+        ];
+        let counts = count_events(&events, TimelineGranularity::Day);
+        assert_eq!(counts.len(), 2);
+
+        let timeline =
+            Timeline::from_counts(at(1, 12), at(4, 12), TimelineGranularity::Day, &counts, vec![]);
+        assert_eq!(timeline.buckets.len(), 4);
+        assert_eq!(timeline.buckets[1].counts[&AuditEventKind::Query], 2);
+        assert_eq!(timeline.buckets[2].counts[&AuditEventKind::Query], 0);
+        assert_eq!(timeline.buckets[3].counts[&AuditEventKind::BuildFailed], 1);
+        assert_eq!(timeline.totals[&AuditEventKind::Query], 2);
+        assert_eq!(timeline.buckets[0].counts.len(), AuditEventKind::ALL.len());
+    }
+
+    #[test]
+    fn test_event_kind_names_round_trip() {
+        for kind in AuditEventKind::ALL {
+            assert_eq!(kind.as_str().parse::<AuditEventKind>(), Ok(kind));
+            assert_eq!(serde_json::to_value(kind).unwrap(), kind.as_str());
+        }
+        assert!("deleted".parse::<AuditEventKind>().is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! What a build changed in the index
+//!
+//! Every build records what the index holds (chunks per dataset, embeddings,
+//! an estimated size) in its index state. Comparing that with the state it
+//! replaces gives a one-screen summary of the build.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::IndexState;
+
+/// Bytes of one embedding component
+const BYTES_PER_DIMENSION: u64 = 4;
+
+/// What an index holds, recorded with its state
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IndexContents {
+    /// Chunks of each dataset, by dataset name
+    pub dataset_chunks: BTreeMap<String, usize>,
+
+    /// Chunks with an embedding
+    pub embedding_count: usize,
+
+    /// Estimated bytes of chunk text and embedding vectors
+    pub size_bytes: u64,
+}
+
+impl IndexContents {
+    /// Estimated storage of chunk text and embeddings of `dimensions` components
+    pub fn estimate_size(text_bytes: u64, embedding_count: usize, dimensions: usize) -> u64 {
+        text_bytes + embedding_count as u64 * dimensions as u64 * BYTES_PER_DIMENSION
+    }
+}
+
+/// The index a build replaces, as far as it could be read
+#[derive(Debug, Clone, Default)]
+pub enum PreviousIndex {
+    /// No index was built before
+    #[default]
+    None,
+    /// The state recorded by the previous build
+    Built(IndexState),
+    /// A state that could not be read, e.g. one written by an incompatible version
+    Unreadable(String),
+}
+
+impl From<Option<IndexState>> for PreviousIndex {
+    fn from(state: Option<IndexState>) -> Self {
+        state.map_or(Self::None, Self::Built)
+    }
+}
+
+/// What a build was compared with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffBaseline {
+    /// The first build; everything in the index is new
+    FirstBuild,
+    /// The previous build
+    Previous { hash: String, built_at: DateTime<Utc> },
+    /// A previous state that could not be read; only the new index is described
+    Unreadable { reason: String },
+}
+
+/// A count before and after a build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delta {
+    /// `None` when the previous build did not record it
+    pub before: Option<u64>,
+    pub after: u64,
+    /// `after - before`, when `before` is known
+    pub change: Option<i64>,
+}
+
+impl Delta {
+    pub fn new(before: Option<u64>, after: u64) -> Self {
+        Self {
+            before,
+            after,
+            change: before.map(|before| after as i64 - before as i64),
+        }
+    }
+}
+
+/// How the chunks of one dataset changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetDelta {
+    pub name: String,
+    pub chunks: Delta,
+}
+
+/// Embedder of the previous build, when a different one built the new index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbedderChange {
+    pub from: String,
+    pub to: String,
+    pub from_dimensions: usize,
+    pub to_dimensions: usize,
+}
+
+/// How a build changed the index compared with the previous build
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildDiff {
+    pub baseline: DiffBaseline,
+
+    pub chunks: Delta,
+
+    pub embeddings: Delta,
+
+    /// Estimated bytes of chunk text and embedding vectors
+    pub size_bytes: Delta,
+
+    /// Datasets whose chunk count changed, by name
+    pub datasets: Vec<DatasetDelta>,
+
+    /// Datasets the index covers now but did not before
+    pub new_datasets: Vec<String>,
+
+    /// Datasets the previous index covered but this one does not
+    pub removed_datasets: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedder_changed: Option<EmbedderChange>,
+}
+
+impl BuildDiff {
+    /// Compare a new index state with the index it replaces
+    ///
+    /// Counts the previous state did not record (states written by older
+    /// versions have no contents) are reported with an unknown `before`.
+    pub fn between(previous: &PreviousIndex, current: &IndexState) -> Self {
+        let contents = current.contents.clone().unwrap_or_else(|| IndexContents {
+            embedding_count: current.chunk_count,
+            ..Default::default()
+        });
+        let covered = covered_datasets(current);
+
+        let (baseline, before, before_covered) = match previous {
+            PreviousIndex::None => {
+                (DiffBaseline::FirstBuild, Some(IndexContents::default()), Some(BTreeSet::new()))
+            }
+            PreviousIndex::Built(state) => (
+                DiffBaseline::Previous {
+                    hash: state.hash.clone(),
+                    built_at: state.built_at,
+                },
+                state.contents.clone(),
+                Some(covered_datasets(state)),
+            ),
+            PreviousIndex::Unreadable(reason) => {
+                (DiffBaseline::Unreadable { reason: reason.clone() }, None, None)
+            }
+        };
+
+        let chunks_before = match previous {
+            PreviousIndex::None => Some(0),
+            PreviousIndex::Built(state) => Some(state.chunk_count as u64),
+            PreviousIndex::Unreadable(_) => None,
+        };
+
+        let names: BTreeSet<&String> = contents
+            .dataset_chunks
+            .keys()
+            .chain(before.iter().flat_map(|before| before.dataset_chunks.keys()))
+            .collect();
+        let datasets = names
+            .into_iter()
+            .map(|name| DatasetDelta {
+                name: name.clone(),
+                chunks: Delta::new(
+                    before.as_ref().map(|before| chunks_of(&before.dataset_chunks, name)),
+                    chunks_of(&contents.dataset_chunks, name),
+                ),
+            })
+            .filter(|delta| delta.chunks.change != Some(0))
+            .collect();
+
+        let (new_datasets, removed_datasets) = match &before_covered {
+            Some(before_covered) => (
+                covered.difference(before_covered).cloned().collect(),
+                before_covered.difference(&covered).cloned().collect(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let embedder_changed = match previous {
+            PreviousIndex::Built(state)
+                if state.embedder != current.embedder
+                    || state.embedding_dim != current.embedding_dim =>
+            {
+                Some(EmbedderChange {
+                    from: state.embedder.clone(),
+                    to: current.embedder.clone(),
+                    from_dimensions: state.embedding_dim,
+                    to_dimensions: current.embedding_dim,
+                })
+            }
+            _ => None,
+        };
+
+        Self {
+            baseline,
+            chunks: Delta::new(chunks_before, current.chunk_count as u64),
+            embeddings: Delta::new(
+                before.as_ref().map(|before| before.embedding_count as u64),
+                contents.embedding_count as u64,
+            ),
+            size_bytes: Delta::new(
+                before.as_ref().map(|before| before.size_bytes),
+                contents.size_bytes,
+            ),
+            datasets,
+            new_datasets,
+            removed_datasets,
+            embedder_changed,
+        }
+    }
+}
+
+/// Datasets an index state covers, from its contents or, in older states, its build times
+fn covered_datasets(state: &IndexState) -> BTreeSet<String> {
+    match &state.contents {
+        Some(contents) => contents.dataset_chunks.keys().cloned().collect(),
+        None => state.dataset_built_at.keys().cloned().collect(),
+    }
+}
+
+fn chunks_of(dataset_chunks: &BTreeMap<String, usize>, name: &str) -> u64 {
+    dataset_chunks.get(name).copied().unwrap_or(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::INDEX_SCHEMA_VERSION;
+
+    fn state(embedder: &str, dataset_chunks: &[(&str, usize)]) -> IndexState {
+        let dataset_chunks: BTreeMap<String, usize> =
+            dataset_chunks.iter().map(|(name, count)| (name.to_string(), *count)).collect();
+        let chunk_count = dataset_chunks.values().sum();
+        IndexState {
+            schema_version: INDEX_SCHEMA_VERSION,
+            hash: format!("hash-{}", chunk_count),
+            built_at: Utc::now(),
+            embedder: embedder.to_string(),
+            chunk_count,
+            embedding_dim: 768,
+            dataset_built_at: dataset_chunks
+                .keys()
+                .map(|name| (name.clone(), Utc::now()))
+                .collect(),
+            contents: Some(IndexContents {
+                embedding_count: chunk_count,
+                size_bytes: IndexContents::estimate_size(
+                    100 * chunk_count as u64,
+                    chunk_count,
+                    768,
+                ),
+                dataset_chunks,
+            }),
+            last_build_diff: None,
+            tokenizer: None,
+            score_baseline: None,
+        }
+    }
+
+    #[test]
+    fn test_first_build_is_all_new() {
+        let current = state("ollama:nomic", &[("parks", 4), ("roads", 2)]);
+        let diff = BuildDiff::between(&PreviousIndex::None, &current);
+        assert_eq!(diff.baseline, DiffBaseline::FirstBuild);
+        assert_eq!(diff.chunks, Delta::new(Some(0), 6));
+        assert_eq!(diff.new_datasets, vec!["parks", "roads"]);
+        assert_eq!(diff.datasets.len(), 2);
+        assert_eq!(diff.size_bytes.change, Some(600 + 6 * 768 * 4));
+    }
+
+    #[test]
+    fn test_changes_against_the_previous_build() {
+        let previous = state("ollama:nomic", &[("parks", 4), ("roads", 2)]);
+        let current = state("ollama:nomic", &[("parks", 5), ("roads", 2), ("rivers", 3)]);
+        let diff = BuildDiff::between(&PreviousIndex::Built(previous), &current);
+        assert_eq!(diff.chunks.change, Some(4));
+        assert_eq!(diff.new_datasets, vec!["rivers"]);
+        assert!(diff.removed_datasets.is_empty());
+        // Unchanged datasets are left out
+        let names: Vec<&str> = diff.datasets.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["parks", "rivers"]);
+        assert_eq!(diff.embedder_changed, None);
+
+        let switched = state("ollama:mxbai", &[("parks", 5)]);
+        let diff = BuildDiff::between(&PreviousIndex::Built(current), &switched);
+        assert_eq!(diff.removed_datasets, vec!["rivers", "roads"]);
+        assert_eq!(diff.embedder_changed.unwrap().from, "ollama:nomic");
+    }
+
+    #[test]
+    fn test_older_and_unreadable_states_leave_counts_unknown() {
+        let mut older = state("ollama:nomic", &[("parks", 4)]);
+        older.contents = None;
+        let current = state("ollama:nomic", &[("parks", 4), ("roads", 1)]);
+        let diff = BuildDiff::between(&PreviousIndex::Built(older), &current);
+        assert_eq!(diff.chunks.change, Some(1));
+        assert_eq!(diff.embeddings.before, None);
+        assert_eq!(diff.datasets[0].chunks.before, None);
+        // Build times still tell which datasets are new
+        assert_eq!(diff.new_datasets, vec!["roads"]);
+
+        let unreadable = PreviousIndex::Unreadable("unknown field".to_string());
+        let diff = BuildDiff::between(&unreadable, &current);
+        assert_eq!(diff.chunks, Delta::new(None, 5));
+        assert!(diff.new_datasets.is_empty());
+        assert!(matches!(diff.baseline, DiffBaseline::Unreadable { .. }));
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{DatasetId, WorkspaceId};
+
+/// Events kept by default, across all workspaces
+pub const DEFAULT_EVENT_RETENTION_EVENTS: usize = 100_000;
+
+/// Days events are kept by default
+pub const DEFAULT_EVENT_RETENTION_DAYS: i64 = 7;
+
+/// Events returned by one read of the log unless fewer are asked for
+pub const DEFAULT_EVENT_PAGE: usize = 100;
+
+/// Most events returned by one read of the log
+pub const MAX_EVENT_PAGE: usize = 1000;
+
+/// Records carried by one event at most, so large datasets span several events
+pub const MAX_EVENT_RECORDS: usize = 500;
+
+/// Kind of record a change event concerns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeEntity {
+    Dataset,
+    Feature,
+    /// Indexed text chunks, changed by builds
+    Chunk,
+}
+
+impl ChangeEntity {
+    /// All entities
+    pub const ALL: [ChangeEntity; 3] = [Self::Dataset, Self::Feature, Self::Chunk];
+
+    /// Name stored in the event log and used in API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dataset => "dataset",
+            Self::Feature => "feature",
+            Self::Chunk => "chunk",
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ChangeEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|entity| entity.as_str() == s)
+            .ok_or_else(|| format!("unknown change entity '{}'", s))
+    }
+}
+
+/// What happened to the records of a change event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOperation {
+    /// The records were created or replaced
+    Upsert,
+    /// The records were removed; removing a dataset removes its features too
+    Delete,
+}
+
+impl ChangeOperation {
+    /// All operations
+    pub const ALL: [ChangeOperation; 2] = [Self::Upsert, Self::Delete];
+
+    /// Name stored in the event log and used in API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Upsert => "upsert",
+            Self::Delete => "delete",
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ChangeOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|operation| operation.as_str() == s)
+            .ok_or_else(|| format!("unknown change operation '{}'", s))
+    }
+}
+
+/// One change to the data of a workspace, in the order of the event log
+///
+/// Applying the events of a workspace in sequence order rebuilds its
+/// datasets and features: upserts carry the new state of their records, and
+/// applying an event twice leaves the same state as applying it once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    /// Position in the log, assigned when the event is appended; 0 before
+    pub seq: u64,
+
+    pub workspace_id: WorkspaceId,
+
+    pub at: DateTime<Utc>,
+
+    pub entity: ChangeEntity,
+
+    pub operation: ChangeOperation,
+
+    /// Dataset the records belong to, or the dataset itself for dataset events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset_id: Option<DatasetId>,
+
+    /// IDs of the changed records
+    pub ids: Vec<u64>,
+
+    /// New state of upserted datasets and features: the dataset, or an array of features
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Value>,
+}
+
+impl ChangeEvent {
+    /// Create an event that happened now, not yet in the log
+    pub fn new(
+        workspace_id: WorkspaceId,
+        entity: ChangeEntity,
+        operation: ChangeOperation,
+        ids: Vec<u64>,
+    ) -> Self {
+        Self {
+            seq: 0,
+            workspace_id,
+            at: Utc::now(),
+            entity,
+            operation,
+            dataset_id: None,
+            ids,
+            payload: None,
+        }
+    }
+
+    /// Set the dataset the records belong to
+    pub fn with_dataset(mut self, dataset_id: DatasetId) -> Self {
+        self.dataset_id = Some(dataset_id);
+        self
+    }
+
+    /// Set the new state of the records
+    pub fn with_payload(mut self, payload: Value) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+}
+
+/// How much of the event log is kept
+///
+/// Pruning drops the oldest events only, so the events kept never have gaps
+/// in their sequence numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventRetention {
+    /// Most events kept
+    pub max_events: usize,
+
+    /// Age after which events are dropped, `None` to keep them regardless of age
+    pub max_age: Option<Duration>,
+}
+
+impl Default for EventRetention {
+    fn default() -> Self {
+        Self {
+            max_events: DEFAULT_EVENT_RETENTION_EVENTS,
+            max_age: Some(Duration::days(DEFAULT_EVENT_RETENTION_DAYS)),
+        }
+    }
+}
+
+impl EventRetention {
+    /// Events that happened before this are dropped
+    pub fn oldest(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.max_age.map(|age| now - age)
+    }
+}
+
+/// Sequence numbers the event log holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLogBounds {
+    /// Oldest event kept, `None` when the log holds no events
+    pub first: Option<u64>,
+
+    /// Newest event ever appended, 0 before the first
+    pub last: u64,
+}
+
+impl EventLogBounds {
+    /// Whether events right after `after_seq` were already pruned
+    ///
+    /// A reader that last saw `after_seq` has missed events it cannot get
+    /// back and must start over, e.g. from a fresh copy of the workspace.
+    pub fn is_pruned(&self, after_seq: u64) -> bool {
+        after_seq < self.last && self.first.is_none_or(|first| after_seq + 1 < first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for entity in ChangeEntity::ALL {
+            assert_eq!(entity.as_str().parse::<ChangeEntity>(), Ok(entity));
+            assert_eq!(serde_json::to_value(entity).unwrap(), entity.as_str());
+        }
+        for operation in ChangeOperation::ALL {
+            assert_eq!(operation.as_str().parse::<ChangeOperation>(), Ok(operation));
+            assert_eq!(serde_json::to_value(operation).unwrap(), operation.as_str());
+        }
+        assert!("features".parse::<ChangeEntity>().is_err());
+        assert!("insert".parse::<ChangeOperation>().is_err());
+    }
+
+    #[test]
+    fn test_readers_behind_the_retained_events_missed_some() {
+        let empty = EventLogBounds::default();
+        assert!(!empty.is_pruned(0));
+
+        let bounds = EventLogBounds { first: Some(5), last: 9 };
+        assert!(bounds.is_pruned(0));
+        assert!(bounds.is_pruned(3));
+        assert!(!bounds.is_pruned(4), "event 5 is the next one and is kept");
+        assert!(!bounds.is_pruned(9));
+        assert!(!bounds.is_pruned(12));
+
+        // Every event was pruned, e.g. by age
+        let drained = EventLogBounds { first: None, last: 9 };
+        assert!(drained.is_pruned(8));
+        assert!(!drained.is_pruned(9));
+    }
+}
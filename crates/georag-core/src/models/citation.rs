@@ -0,0 +1,218 @@
+//! Links from query sources back to a viewable location
+//!
+//! A deployment that serves its documents elsewhere configures a URL
+//! template such as `https://docs.example.com/{dataset}/{document_path}#page={page}`.
+//! Each source of a query result gets the template filled from its
+//! provenance, every value percent-encoded for the URL.
+
+use std::path::{Component, Path};
+
+/// Placeholders a source URL template may use
+pub const SOURCE_URL_PLACEHOLDERS: [&str; 4] = ["dataset", "document_path", "page", "offset"];
+
+/// URL template filled from the provenance of a source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceUrlTemplate {
+    template: String,
+}
+
+/// Provenance of a source, as filled into a [`SourceUrlTemplate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CitationFields<'a> {
+    /// Slug of the dataset name, see [`dataset_slug`]
+    pub dataset: &'a str,
+
+    /// Path of the document relative to the directory holding the dataset
+    pub document_path: &'a str,
+
+    /// Page of the chunk, for paged documents
+    pub page: Option<usize>,
+
+    /// Character offset of the chunk in its normalized text
+    pub offset: Option<usize>,
+}
+
+impl SourceUrlTemplate {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let template = template.trim();
+        if template.is_empty() {
+            return Err("Source URL template is empty".to_string());
+        }
+
+        let mut rest = template;
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start..].starts_with('}') {
+                return Err(format!("Unmatched '}}' in source URL template: {}", template));
+            }
+            let Some(end) = rest[start..].find('}') else {
+                return Err(format!("Unclosed '{{' in source URL template: {}", template));
+            };
+            let name = &rest[start + 1..start + end];
+            if !SOURCE_URL_PLACEHOLDERS.contains(&name) {
+                return Err(format!(
+                    "Unknown placeholder {{{}}} in source URL template. Use {}",
+                    name,
+                    SOURCE_URL_PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                ));
+            }
+            rest = &rest[start + end + 1..];
+        }
+
+        Ok(Self { template: template.to_string() })
+    }
+
+    /// The template as configured
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// URL of a source
+    ///
+    /// The document path keeps its `/` separators with each segment encoded;
+    /// a missing page or offset leaves its placeholder empty.
+    pub fn url(&self, fields: &CitationFields) -> String {
+        let number = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_default();
+        let document_path =
+            fields.document_path.split('/').map(encode).collect::<Vec<_>>().join("/");
+
+        self.template
+            .replace("{dataset}", &encode(fields.dataset))
+            .replace("{document_path}", &document_path)
+            .replace("{page}", &number(fields.page))
+            .replace("{offset}", &number(fields.offset))
+    }
+}
+
+/// URL-friendly form of a dataset name
+///
+/// Letters and digits are lowercased and kept, including non-ASCII ones;
+/// every other run of characters becomes one `-`.
+pub fn dataset_slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// Path of a document relative to the directory holding its dataset, with
+/// `/` separators
+///
+/// Documents outside that directory keep their whole path.
+pub fn relative_document_path(document_path: &str, dataset_path: &Path) -> String {
+    let document = Path::new(document_path);
+    let relative = dataset_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .and_then(|dir| document.strip_prefix(dir).ok())
+        .unwrap_or(document);
+
+    relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields<'a>(dataset: &'a str, document_path: &'a str) -> CitationFields<'a> {
+        CitationFields {
+            dataset,
+            document_path,
+            page: Some(3),
+            offset: Some(120),
+        }
+    }
+
+    #[test]
+    fn test_template_placeholders_are_checked() {
+        let template = "https://docs.example.com/{dataset}/{document_path}#page={page}&w={offset}";
+        assert_eq!(SourceUrlTemplate::parse(template).unwrap().as_str(), template);
+        assert!(SourceUrlTemplate::parse("https://docs.example.com/static").is_ok());
+
+        for invalid in [
+            "",
+            "https://docs.example.com/{dataset}/{file}",
+            "https://docs.example.com/{dataset",
+            "https://docs.example.com/dataset}",
+            "https://docs.example.com/{}",
+        ] {
+            assert!(SourceUrlTemplate::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_url_encodes_spaces_and_non_ascii() {
+        let template = SourceUrlTemplate::parse(
+            "https://docs.example.com/{dataset}/{document_path}#page={page}",
+        )
+        .unwrap();
+
+        let url = template.url(&fields("port-reports", "annual/Port Report 2023.pdf"));
+        assert_eq!(
+            url,
+            "https://docs.example.com/port-reports/annual/Port%20Report%202023.pdf#page=3"
+        );
+
+        let url = template.url(&fields("jalan", "Laporan Tahunan — Jakarta/café.pdf"));
+        assert_eq!(
+            url,
+            "https://docs.example.com/jalan/Laporan%20Tahunan%20%E2%80%94%20Jakarta/caf%C3%A9.pdf#page=3"
+        );
+
+        let url = template.url(&CitationFields {
+            page: None,
+            ..fields("parcels", "a&b?.geojson")
+        });
+        assert_eq!(url, "https://docs.example.com/parcels/a%26b%3F.geojson#page=");
+    }
+
+    #[test]
+    fn test_dataset_slug() {
+        assert_eq!(dataset_slug("Port Reports 2023"), "port-reports-2023");
+        assert_eq!(dataset_slug("  roads (OSM) / Java "), "roads-osm-java");
+        assert_eq!(dataset_slug("Zürich Straßen"), "zürich-straßen");
+    }
+
+    #[test]
+    fn test_relative_document_path() {
+        let dataset = Path::new("/data/reports/annual.pdf");
+        assert_eq!(relative_document_path("/data/reports/annual.pdf", dataset), "annual.pdf");
+        assert_eq!(
+            relative_document_path("/data/reports/scans/page one.pdf", dataset),
+            "scans/page one.pdf"
+        );
+        assert_eq!(relative_document_path("/elsewhere/notes.pdf", dataset), "elsewhere/notes.pdf");
+        assert_eq!(
+            relative_document_path("parcels.geojson", Path::new("parcels.geojson")),
+            "parcels.geojson"
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Confidence of query results against a sampled score baseline
+//!
+//! A raw similarity score says little on its own: one embedder puts
+//! unrelated texts at 0.2, another at 0.7. The index build samples the
+//! similarity between random pairs of its own embeddings, treating one of
+//! each pair as a pseudo-query, and keeps the quantiles of those scores as a
+//! [`ScoreBaseline`]. A result's score is then expressed as the percentile
+//! it reaches in that baseline and labelled by [`ConfidenceCutoffs`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Number of random embedding pairs sampled at build time
+pub const DEFAULT_BASELINE_PAIRS: usize = 2000;
+
+/// Number of evenly spaced quantiles kept of the sampled scores
+pub const DEFAULT_BASELINE_LEVELS: usize = 101;
+
+/// Seed of the baseline sample, so rebuilding the same index samples the same pairs
+pub const BASELINE_SEED: u64 = 0x6765_6f72_6167;
+
+/// How the percentiles of a query should be read
+pub const CONFIDENCE_CAVEAT: &str = "Percentiles compare each semantic score with the scores \
+of random pairs of indexed chunks. They say how unusual a score is for this index, not how \
+likely a result is to be correct. The top results of any query are the best of many \
+candidates, so they reach high percentiles even when nothing relevant is indexed, and \
+percentiles from a small index rest on few distinct scores.";
+
+/// Quantiles of the similarity between random pairs of indexed embeddings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBaseline {
+    /// Number of scores the quantiles were computed from
+    pub samples: usize,
+
+    /// Scores at evenly spaced levels from the minimum to the maximum
+    pub quantiles: Vec<f32>,
+}
+
+impl ScoreBaseline {
+    /// Baseline of the given scores, keeping `levels` quantiles
+    ///
+    /// Returns `None` when there are no scores to describe.
+    pub fn from_scores(scores: &[f32], levels: usize) -> Option<Self> {
+        let mut sorted: Vec<f32> = scores.iter().copied().filter(|s| s.is_finite()).collect();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f32::total_cmp);
+
+        let levels = levels.max(2);
+        let last = (sorted.len() - 1) as f32;
+        let quantiles = (0..levels)
+            .map(|level| {
+                let position = level as f32 / (levels - 1) as f32 * last;
+                let below = position.floor() as usize;
+                let above = position.ceil() as usize;
+                let fraction = position - below as f32;
+                sorted[below] + (sorted[above] - sorted[below]) * fraction
+            })
+            .collect();
+
+        Some(Self { samples: sorted.len(), quantiles })
+    }
+
+    /// Sample the cosine similarity of `pairs` random distinct pairs of vectors
+    ///
+    /// Returns `None` when there are fewer than two vectors to pair.
+    pub fn sample(vectors: &[&[f32]], pairs: usize, seed: u64) -> Option<Self> {
+        if vectors.len() < 2 || pairs == 0 {
+            return None;
+        }
+
+        let mut rng = SplitMix64(seed);
+        let scores: Vec<f32> = (0..pairs)
+            .map(|_| {
+                let query = rng.below(vectors.len());
+                let mut other = rng.below(vectors.len() - 1);
+                if other >= query {
+                    other += 1;
+                }
+                cosine(vectors[query], vectors[other])
+            })
+            .collect();
+
+        Self::from_scores(&scores, DEFAULT_BASELINE_LEVELS)
+    }
+
+    /// Percentile of a score in the baseline, from 0 to 100
+    ///
+    /// Interpolates linearly between the quantiles around the score; scores
+    /// below the minimum are at 0 and scores at or above the maximum at 100.
+    pub fn percentile(&self, score: f32) -> f32 {
+        let quantiles = &self.quantiles;
+        let above = quantiles.partition_point(|q| *q <= score);
+        if above == 0 {
+            return 0.0;
+        }
+        if above == quantiles.len() {
+            return 100.0;
+        }
+
+        let below = above - 1;
+        let (low, high) = (quantiles[below], quantiles[above]);
+        let fraction = (score - low) / (high - low);
+        (below as f32 + fraction) / (quantiles.len() - 1) as f32 * 100.0
+    }
+
+    /// Percentile and confidence level of a score
+    pub fn confidence(&self, score: f32, cutoffs: &ConfidenceCutoffs) -> ResultConfidence {
+        let percentile = self.percentile(score);
+        ResultConfidence {
+            percentile,
+            level: cutoffs.level(percentile),
+        }
+    }
+}
+
+/// Confidence label of a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfidenceLevel {
+    High,
+    Medium,
+    Low,
+}
+
+impl fmt::Display for ConfidenceLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfidenceLevel::High => write!(f, "high"),
+            ConfidenceLevel::Medium => write!(f, "medium"),
+            ConfidenceLevel::Low => write!(f, "low"),
+        }
+    }
+}
+
+/// Percentiles a result must reach to be labelled high or medium confidence
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceCutoffs {
+    /// Lowest percentile of a high confidence result
+    pub high: f32,
+
+    /// Lowest percentile of a medium confidence result
+    pub medium: f32,
+}
+
+impl Default for ConfidenceCutoffs {
+    fn default() -> Self {
+        Self { high: 99.0, medium: 95.0 }
+    }
+}
+
+impl ConfidenceCutoffs {
+    /// Label of a percentile
+    pub fn level(&self, percentile: f32) -> ConfidenceLevel {
+        if percentile >= self.high {
+            ConfidenceLevel::High
+        } else if percentile >= self.medium {
+            ConfidenceLevel::Medium
+        } else {
+            ConfidenceLevel::Low
+        }
+    }
+}
+
+impl fmt::Display for ConfidenceCutoffs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.high, self.medium)
+    }
+}
+
+/// Percentile of a result's score in the baseline, and its label
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResultConfidence {
+    /// Percentile of the semantic score, from 0 to 100
+    pub percentile: f32,
+
+    /// Label given by the confidence cutoffs
+    pub level: ConfidenceLevel,
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// SplitMix64 step; stable across platforms and Rust versions
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..bound`; `bound` must be greater than zero
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scores 0.00, 0.01, ..., 1.00
+    fn uniform() -> Vec<f32> {
+        (0..=100).map(|i| i as f32 / 100.0).collect()
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_percentiles_of_a_uniform_baseline() {
+        let baseline = ScoreBaseline::from_scores(&uniform(), 101).unwrap();
+        assert_eq!(baseline.samples, 101);
+
+        assert_close(baseline.percentile(0.5), 50.0);
+        assert_close(baseline.percentile(0.505), 50.5);
+        assert_close(baseline.percentile(0.95), 95.0);
+        assert_close(baseline.percentile(0.0), 0.0);
+        assert_eq!(baseline.percentile(-0.3), 0.0);
+        assert_eq!(baseline.percentile(1.0), 100.0);
+        assert_eq!(baseline.percentile(1.7), 100.0);
+    }
+
+    #[test]
+    fn test_coarse_quantiles_interpolate() {
+        let scores: Vec<f32> = (0..=1000).map(|i| i as f32 / 1000.0).collect();
+        let baseline = ScoreBaseline::from_scores(&scores, 11).unwrap();
+        assert_eq!(baseline.quantiles.len(), 11);
+        assert_close(baseline.quantiles[3], 0.3);
+        assert_close(baseline.percentile(0.25), 25.0);
+        assert_close(baseline.percentile(0.99), 99.0);
+    }
+
+    #[test]
+    fn test_tied_scores_take_the_highest_level() {
+        let baseline = ScoreBaseline::from_scores(&[0.2, 0.2, 0.2, 0.8], 5).unwrap();
+        assert_close(baseline.percentile(0.2), 50.0);
+        assert_close(baseline.quantiles[3], 0.35);
+        assert_close(baseline.percentile(0.5), 83.333);
+    }
+
+    #[test]
+    fn test_cutoffs_label_percentiles() {
+        let cutoffs = ConfidenceCutoffs::default();
+        assert_eq!(cutoffs.level(99.5), ConfidenceLevel::High);
+        assert_eq!(cutoffs.level(99.0), ConfidenceLevel::High);
+        assert_eq!(cutoffs.level(97.0), ConfidenceLevel::Medium);
+        assert_eq!(cutoffs.level(50.0), ConfidenceLevel::Low);
+        assert_eq!(cutoffs.to_string(), "99,95");
+
+        let baseline = ScoreBaseline::from_scores(&uniform(), 101).unwrap();
+        let confidence = baseline.confidence(0.97, &cutoffs);
+        assert_eq!(confidence.level, ConfidenceLevel::Medium);
+        assert_close(confidence.percentile, 97.0);
+    }
+
+    #[test]
+    fn test_sample_is_repeatable_and_needs_two_vectors() {
+        let vectors: Vec<Vec<f32>> = (0..20).map(|i| vec![1.0, i as f32 * 0.1]).collect();
+        let refs: Vec<&[f32]> = vectors.iter().map(Vec::as_slice).collect();
+
+        let first = ScoreBaseline::sample(&refs, 200, BASELINE_SEED).unwrap();
+        let again = ScoreBaseline::sample(&refs, 200, BASELINE_SEED).unwrap();
+        assert_eq!(first, again);
+        assert_eq!(first.samples, 200);
+        assert_eq!(first.quantiles.len(), DEFAULT_BASELINE_LEVELS);
+        assert!(first.quantiles.windows(2).all(|w| w[0] <= w[1]));
+
+        assert!(ScoreBaseline::sample(&refs[..1], 200, BASELINE_SEED).is_none());
+        assert!(ScoreBaseline::from_scores(&[], 101).is_none());
+    }
+}
@@ -1,11 +1,15 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use super::geometry::GeometryType;
+use super::geometry::{AxisOrderDecision, GeometryType};
+use super::preview::DatasetPreview;
 
 /// Unique identifier for a dataset
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct DatasetId(pub u64);
 
 /// Dataset metadata
@@ -28,6 +32,29 @@ pub struct DatasetMeta {
 
     /// When the dataset was added
     pub added_at: DateTime<Utc>,
+
+    /// Access tags (e.g. "public", "internal"); untagged datasets are visible to everyone
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// License the dataset is published under (e.g. "CC-BY-4.0")
+    #[serde(default)]
+    pub license: Option<String>,
+
+    /// Credit line the license requires in derived output
+    #[serde(default)]
+    pub attribution: Option<String>,
+
+    /// Preview of the dataset's content, for datasets ingested with one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<DatasetPreview>,
+}
+
+impl DatasetMeta {
+    /// Whether the dataset carries [`ARCHIVED_TAG`]
+    pub fn is_archived(&self) -> bool {
+        self.tags.iter().any(|tag| tag == ARCHIVED_TAG)
+    }
 }
 
 /// Full dataset information
@@ -56,6 +83,18 @@ pub struct Dataset {
 
     /// When the dataset was added
     pub added_at: DateTime<Utc>,
+
+    /// Access tags (e.g. "public", "internal"); untagged datasets are visible to everyone
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// License the dataset is published under (e.g. "CC-BY-4.0")
+    #[serde(default)]
+    pub license: Option<String>,
+
+    /// Credit line the license requires in derived output
+    #[serde(default)]
+    pub attribution: Option<String>,
 }
 
 /// Format-specific metadata for datasets
@@ -81,6 +120,114 @@ pub struct FormatMetadata {
 
     /// Spatial association metadata for documents
     pub spatial_association: Option<SpatialAssociation>,
+
+    /// Axis order the coordinates were read with, for GeoJSON datasets
+    #[serde(default)]
+    pub axis_order: Option<AxisOrderDecision>,
+
+    /// Original file kept in the blob store, if any
+    #[serde(default)]
+    pub source: Option<SourceFile>,
+
+    /// Preview of the content, generated at ingest and when features are replaced
+    #[serde(default)]
+    pub preview: Option<DatasetPreview>,
+
+    /// URL the dataset was downloaded from, for datasets added from http(s)
+    #[serde(default)]
+    pub remote: Option<RemoteSource>,
+}
+
+/// Largest original file kept in the blob store by default (100 MiB)
+pub const DEFAULT_MAX_SOURCE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Original file of a dataset, kept in the blob store under the dataset ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceFile {
+    /// File name the dataset was ingested from
+    pub filename: String,
+
+    /// MIME type served when the file is downloaded
+    pub content_type: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// Hex-encoded SHA-256 of the content, when hashing is enabled
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+impl SourceFile {
+    /// Describe a source file, deriving the content type from its extension
+    pub fn new(filename: impl Into<String>, size: u64) -> Self {
+        let filename = filename.into();
+        let content_type = source_content_type(&filename).to_string();
+        Self {
+            filename,
+            content_type,
+            size,
+            sha256: None,
+        }
+    }
+
+    /// Record the SHA-256 of the content
+    pub fn with_sha256(mut self, sha256: impl Into<String>) -> Self {
+        self.sha256 = Some(sha256.into());
+        self
+    }
+}
+
+/// Where a dataset added from a URL was downloaded from
+///
+/// The validators are kept so a later fetch can tell whether the file changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteSource {
+    /// URL the dataset was added from
+    pub url: String,
+
+    /// URL the file was served from after redirects, when it differs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+
+    /// `ETag` header of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+
+    /// `Last-Modified` header of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+
+    /// `Content-Type` header of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// Bytes downloaded
+    pub size: u64,
+
+    /// When the download finished
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// MIME type for a dataset file name, by extension
+///
+/// Unknown extensions are served as `application/octet-stream`.
+pub fn source_content_type(filename: &str) -> &'static str {
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("geojson") => "application/geo+json",
+        Some("json") => "application/json",
+        Some("kml") => "application/vnd.google-earth.kml+xml",
+        Some("kmz") => "application/vnd.google-earth.kmz",
+        Some("gpx") => "application/gpx+xml",
+        Some("pdf") => "application/pdf",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("zip") => "application/zip",
+        Some("shp") => "application/x-esri-shape",
+        Some("csv") => "text/csv",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
 }
 
 /// Spatial association metadata for documents
@@ -98,3 +245,241 @@ pub struct SpatialAssociation {
     /// Description of the association
     pub description: Option<String>,
 }
+
+/// Which datasets a caller may see, based on dataset access tags
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagVisibility {
+    /// Every dataset is visible
+    #[default]
+    All,
+
+    /// Only datasets whose tags are all in this set (and untagged datasets)
+    Only(BTreeSet<String>),
+}
+
+impl TagVisibility {
+    /// Restrict visibility to the given tags
+    pub fn only(tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        TagVisibility::Only(tags.into_iter().map(Into::into).collect())
+    }
+
+    /// Check whether a dataset with these tags is visible
+    ///
+    /// A dataset carrying any tag outside the allowed set is hidden, so adding
+    /// a more sensitive tag to a dataset can only narrow who sees it.
+    pub fn allows(&self, tags: &[String]) -> bool {
+        match self {
+            TagVisibility::All => true,
+            TagVisibility::Only(allowed) => tags.iter().all(|tag| allowed.contains(tag)),
+        }
+    }
+
+    /// Check whether any dataset could be hidden
+    pub fn is_restricted(&self) -> bool {
+        matches!(self, TagVisibility::Only(_))
+    }
+}
+
+/// Access tag of archived datasets
+///
+/// An archived dataset keeps its features and source file but is left out
+/// of index builds. Removing the tag restores it at the next build.
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// Normalize access tags: trimmed, lowercase, sorted and without duplicates or empty entries
+pub fn normalize_tags(tags: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<String> {
+    tags.into_iter()
+        .map(|tag| tag.as_ref().trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Trim a license or attribution, treating a blank one as absent
+pub fn normalize_credit(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// Distinct attribution lines, in the order they first appear
+pub fn distinct_attributions<'a>(attributions: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    attributions
+        .into_iter()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && seen.insert(*line))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Field a dataset listing is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetSort {
+    /// Dataset name, the order stores list datasets in
+    #[default]
+    Name,
+
+    /// Time the dataset was added
+    Added,
+
+    /// Number of features
+    Features,
+}
+
+impl FromStr for DatasetSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "name" => Ok(DatasetSort::Name),
+            "added" => Ok(DatasetSort::Added),
+            "features" => Ok(DatasetSort::Features),
+            _ => Err(format!("Invalid sort field '{}': expected name, added or features", s)),
+        }
+    }
+}
+
+/// Direction of a sorted listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid sort order '{}': expected asc or desc", s)),
+        }
+    }
+}
+
+/// Sort datasets by a field, breaking ties by name and then ID
+///
+/// Only the field is reversed by [`SortOrder::Desc`]; ties always list in
+/// ascending name and ID order, so equal keys never swap between calls.
+pub fn sort_datasets(datasets: &mut [DatasetMeta], sort: DatasetSort, order: SortOrder) {
+    datasets.sort_by(|a, b| {
+        let by_field = match sort {
+            DatasetSort::Name => Ordering::Equal,
+            DatasetSort::Added => a.added_at.cmp(&b.added_at),
+            DatasetSort::Features => a.feature_count.cmp(&b.feature_count),
+        };
+        let by_field = match order {
+            SortOrder::Asc => by_field,
+            SortOrder::Desc => by_field.reverse(),
+        };
+        let by_name = match (sort, order) {
+            (DatasetSort::Name, SortOrder::Desc) => b.name.cmp(&a.name),
+            _ => a.name.cmp(&b.name),
+        };
+        by_field.then(by_name).then(a.id.0.cmp(&b.id.0))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_all_allows_everything() {
+        assert!(TagVisibility::All.allows(&tags(&["restricted"])));
+        assert!(!TagVisibility::All.is_restricted());
+    }
+
+    #[test]
+    fn test_only_requires_every_tag() {
+        let visibility = TagVisibility::only(["public", "internal"]);
+
+        assert!(visibility.allows(&[]));
+        assert!(visibility.allows(&tags(&["public"])));
+        assert!(visibility.allows(&tags(&["public", "internal"])));
+        assert!(!visibility.allows(&tags(&["public", "restricted"])));
+        assert!(!visibility.allows(&tags(&["restricted"])));
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags([" Public", "internal", "public", ""]),
+            tags(&["internal", "public"])
+        );
+    }
+
+    #[test]
+    fn test_credits() {
+        assert_eq!(normalize_credit(Some("  CC-BY-4.0 ")), Some("CC-BY-4.0".to_string()));
+        assert_eq!(normalize_credit(Some("  ")), None);
+        assert_eq!(normalize_credit(None), None);
+
+        assert_eq!(
+            distinct_attributions(["© City of Tasmin", "", "OpenStreetMap", " © City of Tasmin"]),
+            tags(&["© City of Tasmin", "OpenStreetMap"])
+        );
+    }
+
+    fn meta(id: u64, name: &str, feature_count: usize, added: i64) -> DatasetMeta {
+        DatasetMeta {
+            id: DatasetId(id),
+            name: name.to_string(),
+            geometry_type: GeometryType::Point,
+            feature_count,
+            crs: 4326,
+            added_at: DateTime::from_timestamp(added, 0).unwrap(),
+            tags: Vec::new(),
+            license: None,
+            attribution: None,
+            preview: None,
+        }
+    }
+
+    fn ids(datasets: &[DatasetMeta]) -> Vec<u64> {
+        datasets.iter().map(|d| d.id.0).collect()
+    }
+
+    #[test]
+    fn test_sort_datasets() {
+        let mut datasets =
+            vec![meta(3, "roads", 10, 300), meta(1, "parks", 10, 100), meta(2, "parks", 5, 200)];
+
+        sort_datasets(&mut datasets, DatasetSort::Name, SortOrder::Asc);
+        assert_eq!(ids(&datasets), vec![1, 2, 3]);
+
+        sort_datasets(&mut datasets, DatasetSort::Name, SortOrder::Desc);
+        assert_eq!(ids(&datasets), vec![3, 1, 2]);
+
+        sort_datasets(&mut datasets, DatasetSort::Added, SortOrder::Desc);
+        assert_eq!(ids(&datasets), vec![3, 2, 1]);
+
+        // Ties on the field stay in name and ID order either way
+        sort_datasets(&mut datasets, DatasetSort::Features, SortOrder::Desc);
+        assert_eq!(ids(&datasets), vec![1, 3, 2]);
+
+        assert_eq!("Added".parse::<DatasetSort>(), Ok(DatasetSort::Added));
+        assert!("size".parse::<DatasetSort>().is_err());
+        assert!("up".parse::<SortOrder>().is_err());
+    }
+
+    #[test]
+    fn test_source_content_type() {
+        assert_eq!(source_content_type("parcels.GeoJSON"), "application/geo+json");
+        assert_eq!(source_content_type("report.pdf"), "application/pdf");
+        assert_eq!(source_content_type("notes"), "application/octet-stream");
+
+        let source = SourceFile::new("trail.gpx", 42).with_sha256("abc");
+        assert_eq!(source.content_type, "application/gpx+xml");
+        assert_eq!(source.sha256.as_deref(), Some("abc"));
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::FeatureId;
+use super::{DatasetId, FeatureId, Geometry};
 
 /// Unique identifier for a text chunk
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -22,6 +22,13 @@ pub struct TextChunk {
     /// Optional spatial reference
     pub spatial_ref: Option<FeatureId>,
 
+    /// Geometry of the places the chunk's text names
+    ///
+    /// Set for chunks of documents that cover several sites. Spatial filters
+    /// test it in place of the geometry of the `spatial_ref` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry: Option<Geometry>,
+
     /// Additional metadata
     pub metadata: ChunkMetadata,
 }
@@ -35,8 +42,16 @@ pub struct ChunkSource {
     /// Page number (for PDFs)
     pub page: Option<usize>,
 
-    /// Character offset in source
+    /// Character offset of the chunk in the normalized text it was cut from
+    ///
+    /// The content is the `length` characters of the normalized text from
+    /// here on, so chunks of one feature with overlapping ranges share text.
+    /// `ChunkGenerator::normalized_text` gives the text of a feature.
     pub offset: usize,
+
+    /// Length of the chunk in characters
+    #[serde(default)]
+    pub length: usize,
 }
 
 /// Chunk metadata
@@ -45,8 +60,23 @@ pub struct ChunkMetadata {
     /// Chunk size in characters
     pub size: usize,
 
-    /// Additional properties
+    /// Feature properties copied at chunking time, see `ChunkGenerator::with_properties`
+    #[serde(default)]
     pub properties: HashMap<String, String>,
+
+    /// Hash of the feature text the chunk was cut from, see `source_text_hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<String>,
+
+    /// Whether the feature text changed after the chunk was generated
+    ///
+    /// Stale chunks are left out of retrieval until a build replaces them.
+    #[serde(default)]
+    pub stale: bool,
+
+    /// Dataset the chunk was cut from, see [`DatasetIndexStats`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset_id: Option<DatasetId>,
 }
 
 /// Embedding vector with spatial metadata
@@ -60,6 +90,37 @@ pub struct Embedding {
 
     /// Optional spatial metadata
     pub spatial_metadata: Option<SpatialMetadata>,
+
+    /// Dataset of the embedded chunk, see [`DatasetIndexStats`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataset_id: Option<DatasetId>,
+}
+
+/// Index counters of one dataset
+///
+/// Stores keep these counters up to date as chunks and embeddings are
+/// written and deleted, so reading them never scans the chunks. Chunks and
+/// embeddings without a `dataset_id` are not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetIndexStats {
+    pub chunks: u64,
+    /// Bytes of chunk content
+    pub content_bytes: u64,
+    pub embeddings: u64,
+}
+
+impl DatasetIndexStats {
+    /// Add the counts of `other`, e.g. embeddings counted by another store
+    pub fn add(&mut self, other: &Self) {
+        self.chunks += other.chunks;
+        self.content_bytes += other.content_bytes;
+        self.embeddings += other.embeddings;
+    }
+
+    /// Whether nothing is counted
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// Spatial metadata attached to embeddings
@@ -67,7 +67,7 @@ impl DistanceUnit {
 }
 
 /// Distance with unit
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Distance {
     pub value: f64,
     pub unit: DistanceUnit,
@@ -105,19 +105,77 @@ pub enum ValidityMode {
     Lenient,
 }
 
+/// Axis order of coordinates in an ingested file
+///
+/// Features are always stored in lon,lat order; this describes the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AxisOrder {
+    /// Longitude first, as RFC 7946 requires
+    #[default]
+    LonLat,
+    /// Latitude first, as EPSG:4326 officially defines
+    LatLon,
+    /// Decide from coordinate ranges and the workspace extent
+    Auto,
+}
+
+impl std::fmt::Display for AxisOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisOrder::LonLat => write!(f, "lonlat"),
+            AxisOrder::LatLon => write!(f, "latlon"),
+            AxisOrder::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Axis order a dataset was read with, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AxisOrderDecision {
+    /// Order asked for at ingest
+    pub requested: AxisOrder,
+
+    /// Order the file was read as, `LonLat` or `LatLon`; `LatLon` files were swapped
+    pub applied: AxisOrder,
+
+    /// Explanation of the decision (e.g. "coordinate ranges", "declared CRS84")
+    pub reason: String,
+}
+
 /// Spatial predicate for filtering
+///
+/// Predicates read with the stored feature first and the filter geometry
+/// second: `Within` keeps features inside the filter area, `Contains` keeps
+/// features that enclose the filter geometry. Every store evaluates them with
+/// the same DE-9IM semantics as the matching PostGIS function.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum SpatialPredicate {
-    /// Geometry is completely within the filter geometry
+    /// Feature lies inside the filter geometry (`ST_Within(feature, filter)`);
+    /// features only touching the filter boundary do not match
     Within,
-    /// Geometry intersects (overlaps) the filter geometry
+    /// Feature and filter geometry share at least one point (`ST_Intersects`)
     #[default]
     Intersects,
-    /// Geometry contains the filter geometry
+    /// Feature encloses the filter geometry (`ST_Contains(feature, filter)`)
     Contains,
-    /// Bounding boxes intersect (fast approximation)
+    /// Feature has no point outside the filter geometry
+    /// (`ST_CoveredBy(feature, filter)`); unlike `Within`, features on the
+    /// filter boundary match
+    CoveredBy,
+    /// Feature and filter geometry share boundary points but no interior
+    /// points (`ST_Touches`), e.g. a parcel adjacent to a road reserve
+    Touches,
+    /// Feature and filter geometry share some interior points, and the shared
+    /// part has a lower dimension than the larger of the two (`ST_Crosses`),
+    /// e.g. a pipeline crossing a river
+    Crosses,
+    /// Feature and filter geometry have the same dimension, share interior
+    /// points, and neither covers the other (`ST_Overlaps`)
+    Overlaps,
+    /// Bounding boxes intersect (fast approximation, `feature && filter`)
     BoundingBox,
-    /// Geometry is within specified distance of filter geometry (geodesic)
+    /// Feature is within the filter distance of the filter geometry (geodesic)
     DWithin,
 }
 
@@ -178,9 +236,28 @@ impl Geometry {
         }
     }
 
+    /// Count the coordinates of the geometry (closing ring vertices included)
+    pub fn vertex_count(&self) -> usize {
+        match self {
+            Geometry::Point { .. } => 1,
+            Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+                coordinates.len()
+            }
+            Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+                coordinates.iter().map(Vec::len).sum()
+            }
+            Geometry::MultiPolygon { coordinates } => {
+                coordinates.iter().flatten().map(Vec::len).sum()
+            }
+        }
+    }
+
     /// Try to parse from a serde_json::Value (GeoJSON)
+    ///
+    /// `None` for a missing, empty or unconvertible geometry; see
+    /// [`crate::geo::convert`] for the reason a geometry does not convert.
     pub fn from_geojson(value: &serde_json::Value) -> Option<Self> {
-        serde_json::from_value(value.clone()).ok()
+        crate::geo::geometry_from_geojson(value).ok().flatten()
     }
 
     /// Convert to serde_json::Value (GeoJSON)
@@ -196,6 +273,13 @@ pub struct SpatialFilter {
     pub geometry: Option<Geometry>,
     pub distance: Option<Distance>,
     pub crs: Crs,
+    /// Buffer applied to the filter geometry before the predicate is evaluated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<Distance>,
+    /// Areas removed from the results: a geometry matching the filter is
+    /// rejected when it intersects any of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<Geometry>,
 }
 
 impl Default for SpatialFilter {
@@ -205,6 +289,8 @@ impl Default for SpatialFilter {
             geometry: None,
             distance: None,
             crs: Crs::wgs84(),
+            buffer: None,
+            exclude: Vec::new(),
         }
     }
 }
@@ -231,6 +317,18 @@ impl SpatialFilter {
         self.distance = Some(distance);
         self
     }
+
+    /// Buffer the filter geometry by a distance before evaluation
+    pub fn buffer(mut self, distance: Distance) -> Self {
+        self.buffer = Some(distance);
+        self
+    }
+
+    /// Remove geometries intersecting an area from the matches
+    pub fn exclude(mut self, geometry: Geometry) -> Self {
+        self.exclude.push(geometry);
+        self
+    }
 }
 
 #[cfg(test)]
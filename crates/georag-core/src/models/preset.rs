@@ -0,0 +1,142 @@
+//! Named query presets stored in workspace settings
+//!
+//! A preset bundles query options a team wants by default, such as more
+//! results and reranking for planners or a few centroids for a mobile app.
+//! Every option is optional: a query naming the preset takes the options it
+//! sets, unless the query sets them itself, and the service defaults fill in
+//! the rest.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Longest name a query preset may have
+pub const MAX_PRESET_NAME_LEN: usize = 64;
+
+/// Query and result options of a named preset
+///
+/// Rerank mode and geometry detail are kept as written and checked by the
+/// query service, as the same values in a query request are.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryPreset {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<f32>,
+    /// Second-pass reranking: none, lexical or llm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerank_pool: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_overlapping: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parse_operators: Option<bool>,
+    /// Geometry returned per source: full, centroid, bbox, none or adaptive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geometry_detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vertex_budget: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinate_precision: Option<u32>,
+}
+
+impl QueryPreset {
+    /// Options the preset sets, with their values, in declaration order
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = Vec::new();
+        let mut push = |key, value: Option<String>| {
+            if let Some(value) = value {
+                entries.push((key, value));
+            }
+        };
+        push("top_k", self.top_k.map(|v| v.to_string()));
+        push("min_score", self.min_score.map(|v| v.to_string()));
+        push("rerank", self.rerank.clone());
+        push("rerank_pool", self.rerank_pool.map(|v| v.to_string()));
+        push("explain", self.explain.map(|v| v.to_string()));
+        push("merge_overlapping", self.merge_overlapping.map(|v| v.to_string()));
+        push("parse_operators", self.parse_operators.map(|v| v.to_string()));
+        push("geometry_detail", self.geometry_detail.clone());
+        push("vertex_budget", self.vertex_budget.map(|v| v.to_string()));
+        push("coordinate_precision", self.coordinate_precision.map(|v| v.to_string()));
+        entries
+    }
+
+    /// Whether the preset sets no option
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for QueryPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no options");
+        }
+        let entries: Vec<String> = self
+            .entries()
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+/// Normalize a preset name: trimmed and lowercased
+///
+/// Names follow the rules of saved area names: ASCII letters, digits, `-`,
+/// `_` and `.`, at most [`MAX_PRESET_NAME_LEN`] characters. Returns the
+/// reason when the name is not acceptable.
+pub fn normalize_preset_name(name: &str) -> std::result::Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err("Preset name must not be empty".to_string());
+    }
+    if name.len() > MAX_PRESET_NAME_LEN {
+        return Err(format!("Preset name must be at most {} characters", MAX_PRESET_NAME_LEN));
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || "-_.".contains(*c))) {
+        return Err(format!(
+            "Preset name may only hold letters, digits, '-', '_' and '.', found '{}'",
+            c
+        ));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_lists_the_options_it_sets() {
+        let preset = QueryPreset {
+            top_k: Some(25),
+            rerank: Some("lexical".to_string()),
+            geometry_detail: Some("centroid".to_string()),
+            ..Default::default()
+        };
+        let keys: Vec<&str> = preset.entries().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["top_k", "rerank", "geometry_detail"]);
+        assert_eq!(preset.to_string(), "top_k=25, rerank=lexical, geometry_detail=centroid");
+        assert_eq!(QueryPreset::default().to_string(), "no options");
+    }
+
+    #[test]
+    fn test_preset_rejects_unknown_options() {
+        let preset: QueryPreset = serde_json::from_str(r#"{"top_k": 5}"#).unwrap();
+        assert_eq!(preset.top_k, Some(5));
+        assert!(serde_json::from_str::<QueryPreset>(r#"{"topk": 5}"#).is_err());
+    }
+
+    #[test]
+    fn test_normalize_preset_name() {
+        assert_eq!(normalize_preset_name(" Planners ").unwrap(), "planners");
+        assert!(normalize_preset_name("").is_err());
+        assert!(normalize_preset_name("mobile app").is_err());
+        assert!(normalize_preset_name(&"a".repeat(MAX_PRESET_NAME_LEN + 1)).is_err());
+    }
+}
@@ -0,0 +1,308 @@
+//! Dataset previews for catalogs
+//!
+//! A preview is computed once at ingest, as features stream past, and kept
+//! in the dataset metadata. Documents get the start of their text and word
+//! counts; vector data gets its extent, geometry types and a few property
+//! keys. Every part is bounded, so a preview stays small however large the
+//! dataset.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+use super::{Feature, Geometry, GeometryType, PART_OF_PROPERTY};
+use crate::processing::text::truncate_chars;
+
+/// Longest document snippet, in characters
+pub const PREVIEW_SNIPPET_CHARS: usize = 500;
+
+/// Most property keys listed in a vector preview
+pub const PREVIEW_PROPERTY_KEYS: usize = 10;
+
+/// Property holding the text of a document feature
+const CONTENT_PROPERTY: &str = "content";
+
+/// Page break in extracted PDF text
+const PAGE_BREAK: char = '\x0C';
+
+/// Short description of a dataset's content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DatasetPreview {
+    Document(DocumentPreview),
+    Vector(VectorPreview),
+}
+
+/// Preview of a document dataset (PDF, DOCX)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentPreview {
+    /// Start of the text with whitespace collapsed: the first page of a paged
+    /// document, at most [`PREVIEW_SNIPPET_CHARS`] characters
+    pub snippet: String,
+
+    /// Whether the text goes on past the snippet
+    pub truncated: bool,
+
+    /// Characters of text across all documents
+    pub character_count: usize,
+
+    /// Words of text across all documents
+    pub word_count: usize,
+}
+
+/// Preview of a vector dataset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VectorPreview {
+    /// `[min_x, min_y, max_x, max_y]` of all geometries, `None` without any
+    pub extent: Option<[f64; 4]>,
+
+    /// Number of features of each geometry type, most common first
+    pub geometry_types: Vec<GeometryTypeCount>,
+
+    /// Features without a geometry
+    pub without_geometry: usize,
+
+    /// Property keys in order of first appearance, at most
+    /// [`PREVIEW_PROPERTY_KEYS`]; internal `_`-prefixed keys are left out
+    pub property_keys: Vec<String>,
+}
+
+/// Number of features of one geometry type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeometryTypeCount {
+    pub geometry_type: GeometryType,
+    pub count: usize,
+}
+
+/// Builds a preview from features one at a time
+#[derive(Debug, Clone)]
+pub enum PreviewBuilder {
+    Document(DocumentPreview),
+    Vector(VectorPreview),
+}
+
+impl PreviewBuilder {
+    /// Start a document preview, for formats reporting pages or paragraphs,
+    /// or a vector preview for all others
+    pub fn new(page_count: Option<usize>, paragraph_count: Option<usize>) -> Self {
+        if page_count.is_some() || paragraph_count.is_some() {
+            Self::Document(DocumentPreview::default())
+        } else {
+            Self::Vector(VectorPreview::default())
+        }
+    }
+
+    /// Add a feature to the preview
+    pub fn add(&mut self, feature: &Feature) {
+        match self {
+            Self::Document(preview) => add_document(preview, feature),
+            Self::Vector(preview) => add_vector(preview, feature),
+        }
+    }
+
+    /// Finish the preview
+    pub fn finish(self) -> DatasetPreview {
+        match self {
+            Self::Document(preview) => DatasetPreview::Document(preview),
+            Self::Vector(mut preview) => {
+                // Stable, so equally common types keep the order they were seen in
+                preview.geometry_types.sort_by_key(|c| Reverse(c.count));
+                DatasetPreview::Vector(preview)
+            }
+        }
+    }
+}
+
+fn add_document(preview: &mut DocumentPreview, feature: &Feature) {
+    let Some(text) = feature.properties.get(CONTENT_PROPERTY).and_then(|v| v.as_str()) else {
+        return;
+    };
+    preview.character_count += text.chars().count();
+    preview.word_count += text.split_whitespace().count();
+
+    if !preview.snippet.is_empty() {
+        preview.truncated |= !text.trim().is_empty();
+        return;
+    }
+    let (first_page, rest) = text.split_once(PAGE_BREAK).unwrap_or((text, ""));
+    let mut words = first_page.split_whitespace();
+    for word in words.by_ref() {
+        let needed = word.chars().count() + usize::from(!preview.snippet.is_empty());
+        if preview.snippet.chars().count() + needed > PREVIEW_SNIPPET_CHARS {
+            if preview.snippet.is_empty() {
+                // One word longer than the whole snippet is cut mid-word
+                preview.snippet = truncate_chars(word, PREVIEW_SNIPPET_CHARS).to_string();
+            }
+            preview.truncated = true;
+            return;
+        }
+        if !preview.snippet.is_empty() {
+            preview.snippet.push(' ');
+        }
+        preview.snippet.push_str(word);
+    }
+    preview.truncated |= !rest.trim().is_empty();
+}
+
+fn add_vector(preview: &mut VectorPreview, feature: &Feature) {
+    // Extra tiles of a subdivided feature widen the extent but are not counted
+    if feature.properties.contains_key(PART_OF_PROPERTY) {
+        if let Some(geometry) = &feature.geometry {
+            extend_extent(preview, geometry);
+        }
+        return;
+    }
+
+    match &feature.geometry {
+        Some(geometry) => {
+            let geometry_type = geometry.geometry_type();
+            match preview.geometry_types.iter_mut().find(|c| c.geometry_type == geometry_type) {
+                Some(entry) => entry.count += 1,
+                None => preview.geometry_types.push(GeometryTypeCount { geometry_type, count: 1 }),
+            }
+            extend_extent(preview, geometry);
+        }
+        None => preview.without_geometry += 1,
+    }
+
+    if preview.property_keys.len() < PREVIEW_PROPERTY_KEYS {
+        let mut keys: Vec<&String> = feature
+            .properties
+            .keys()
+            .filter(|key| !key.starts_with('_') && !preview.property_keys.contains(key))
+            .collect();
+        keys.sort();
+        let room = PREVIEW_PROPERTY_KEYS - preview.property_keys.len();
+        preview.property_keys.extend(keys.into_iter().take(room).cloned());
+    }
+}
+
+fn extend_extent(preview: &mut VectorPreview, geometry: &Geometry) {
+    for [x, y] in positions(geometry).filter(|[x, y]| x.is_finite() && y.is_finite()) {
+        let e = preview.extent.get_or_insert([x, y, x, y]);
+        *e = [e[0].min(x), e[1].min(y), e[2].max(x), e[3].max(y)];
+    }
+}
+
+/// Every coordinate of a geometry
+fn positions(geometry: &Geometry) -> Box<dyn Iterator<Item = [f64; 2]> + '_> {
+    match geometry {
+        Geometry::Point { coordinates } => Box::new(std::iter::once(*coordinates)),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            Box::new(coordinates.iter().copied())
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            Box::new(coordinates.iter().flatten().copied())
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            Box::new(coordinates.iter().flatten().flatten().copied())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeatureId;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn document(text: &str) -> Feature {
+        let properties = HashMap::from([("content".to_string(), json!(text))]);
+        Feature::without_geometry(FeatureId(0), properties, 4326)
+    }
+
+    fn feature(geometry: Option<Geometry>, keys: &[&str]) -> Feature {
+        let properties = keys.iter().map(|key| (key.to_string(), json!(1))).collect();
+        match geometry {
+            Some(geometry) => Feature::with_geometry(FeatureId(0), geometry, properties, 4326),
+            None => Feature::without_geometry(FeatureId(0), properties, 4326),
+        }
+    }
+
+    fn document_preview(features: &[Feature]) -> DocumentPreview {
+        let mut builder = PreviewBuilder::new(Some(1), None);
+        features.iter().for_each(|f| builder.add(f));
+        match builder.finish() {
+            DatasetPreview::Document(preview) => preview,
+            other => panic!("expected a document preview, got {:?}", other),
+        }
+    }
+
+    fn vector_preview(features: &[Feature]) -> VectorPreview {
+        let mut builder = PreviewBuilder::new(None, None);
+        features.iter().for_each(|f| builder.add(f));
+        match builder.finish() {
+            DatasetPreview::Vector(preview) => preview,
+            other => panic!("expected a vector preview, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_document_snippet_is_the_first_page() {
+        let preview = document_preview(&[document("Annual   report\n\nof the port\x0CPage two")]);
+        assert_eq!(preview.snippet, "Annual report of the port");
+        assert!(preview.truncated);
+        assert_eq!(preview.word_count, 7);
+        assert_eq!(preview.character_count, 37);
+    }
+
+    #[test]
+    fn test_document_snippet_is_bounded() {
+        let text = "word ".repeat(400);
+        let preview = document_preview(&[document(&text)]);
+        assert!(preview.snippet.chars().count() <= PREVIEW_SNIPPET_CHARS);
+        assert!(preview.snippet.ends_with("word"));
+        assert!(preview.truncated);
+        assert_eq!(preview.word_count, 400);
+
+        let long_word = "ä".repeat(PREVIEW_SNIPPET_CHARS * 2);
+        let preview = document_preview(&[document(&long_word)]);
+        assert_eq!(preview.snippet.chars().count(), PREVIEW_SNIPPET_CHARS);
+
+        let preview = document_preview(&[document("Short memo")]);
+        assert_eq!(preview.snippet, "Short memo");
+        assert!(!preview.truncated);
+    }
+
+    #[test]
+    fn test_vector_preview_summarizes_features() {
+        let preview = vector_preview(&[
+            feature(Some(Geometry::point(106.8, -6.2)), &["name", "_places"]),
+            feature(
+                Some(Geometry::line_string(vec![[106.0, -7.0], [107.0, -6.0]])),
+                &["name", "lanes"],
+            ),
+            feature(Some(Geometry::point(106.9, -6.1)), &["name"]),
+            feature(None, &["note"]),
+            // An extra tile of a subdivided feature
+            feature(Some(Geometry::point(107.5, -6.5)), &["name", "_part_of"]),
+        ]);
+
+        assert_eq!(preview.extent, Some([106.0, -7.0, 107.5, -6.0]));
+        assert_eq!(
+            preview.geometry_types,
+            vec![
+                GeometryTypeCount {
+                    geometry_type: GeometryType::Point,
+                    count: 2
+                },
+                GeometryTypeCount {
+                    geometry_type: GeometryType::LineString,
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(preview.without_geometry, 1);
+        assert_eq!(preview.property_keys, vec!["name", "lanes", "note"]);
+    }
+
+    #[test]
+    fn test_property_keys_are_bounded() {
+        let keys: Vec<String> = (0..30).map(|i| format!("key{:02}", i)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let preview = vector_preview(&[feature(None, &keys), feature(None, &["late"])]);
+        assert_eq!(preview.property_keys.len(), PREVIEW_PROPERTY_KEYS);
+        assert_eq!(preview.property_keys[0], "key00");
+        assert_eq!(preview.extent, None);
+    }
+}
@@ -10,6 +10,12 @@ use super::ChunkId;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FeatureId(pub u64);
 
+/// Property holding the original feature ID on the extra tiles of a subdivided feature
+///
+/// The first tile keeps the original ID; the others get new IDs and point
+/// back to it, so results matching any tile can be reported as the original.
+pub const PART_OF_PROPERTY: &str = "_part_of";
+
 /// Spatial feature with geometry and properties
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feature {
@@ -25,6 +31,14 @@ pub struct Feature {
 
     /// CRS EPSG code
     pub crs: u32,
+
+    /// Z ordinate of each vertex, in the order the geometry keeps its coordinates
+    ///
+    /// Set when every position of the source geometry had one. Spatial
+    /// predicates only look at X and Y; see [`Feature::z_ordinates`] for the
+    /// values that still match the geometry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z: Option<Vec<f64>>,
 }
 
 impl Feature {
@@ -40,6 +54,7 @@ impl Feature {
             geometry: Some(geometry),
             properties,
             crs,
+            z: None,
         }
     }
 
@@ -49,7 +64,37 @@ impl Feature {
         properties: HashMap<String, serde_json::Value>,
         crs: u32,
     ) -> Self {
-        Self { id, geometry: None, properties, crs }
+        Self {
+            id,
+            geometry: None,
+            properties,
+            crs,
+            z: None,
+        }
+    }
+
+    /// Keep the Z ordinate of each vertex
+    pub fn with_z(mut self, z: Option<Vec<f64>>) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Z ordinates, if there is one for every vertex of the geometry
+    ///
+    /// A geometry simplified or subdivided after it was read no longer
+    /// matches its Z values, which are then ignored.
+    pub fn z_ordinates(&self) -> Option<&[f64]> {
+        let vertices = self.geometry.as_ref()?.vertex_count();
+        self.z.as_deref().filter(|z| z.len() == vertices)
+    }
+
+    /// GeoJSON of the geometry, with Z in each position when the feature has it
+    pub fn geometry_geojson(&self) -> Option<serde_json::Value> {
+        let geometry = self.geometry.as_ref()?;
+        Some(match self.z_ordinates() {
+            Some(z) => crate::geo::geometry_to_geojson_z(geometry, z),
+            None => geometry.to_geojson(),
+        })
     }
 
     /// Associate a geometry with this feature
@@ -67,6 +112,16 @@ impl Feature {
         self.has_geometry()
     }
 
+    /// ID of the feature this one is a tile of, if it is an extra tile of a subdivided feature
+    pub fn part_of(&self) -> Option<FeatureId> {
+        self.properties.get(PART_OF_PROPERTY).and_then(|id| id.as_u64()).map(FeatureId)
+    }
+
+    /// ID of the original feature, which is this feature's own ID unless it is an extra tile
+    pub fn original_id(&self) -> FeatureId {
+        self.part_of().unwrap_or(self.id)
+    }
+
     /// Get the CRS as a Crs struct
     pub fn crs_struct(&self) -> Crs {
         Crs::new(self.crs, "")
@@ -85,3 +140,10 @@ pub struct ScoredResult {
     /// Optional spatial score
     pub spatial_score: Option<f32>,
 }
+
+impl ScoredResult {
+    /// Ranking order: highest score first, ties by ascending chunk ID
+    pub fn rank_cmp(a: &ScoredResult, b: &ScoredResult) -> std::cmp::Ordering {
+        b.score.total_cmp(&a.score).then(a.chunk_id.0.cmp(&b.chunk_id.0))
+    }
+}
@@ -0,0 +1,415 @@
+//! Data dictionaries of datasets
+//!
+//! A dataset schema is inferred from the stored features: the JSON type of
+//! each property key, how many features carry it and a few example values,
+//! along with the geometry types, the CRS and the chunking settings the index
+//! is built with. [`PropertyType::of`] is the one place a property value is
+//! classified, so every listing of property types agrees. Schemas render as
+//! JSON Schema documents for integrators or as a Markdown data dictionary.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::{Dataset, Feature, GeometryTypeCount, PART_OF_PROPERTY};
+use crate::processing::chunk::ChunkGenerator;
+use crate::processing::text::truncate_chars;
+
+/// Distinct example values kept per property
+pub const SCHEMA_EXAMPLES: usize = 3;
+
+/// Longest example value, in characters
+pub const SCHEMA_EXAMPLE_CHARS: usize = 40;
+
+/// JSON type of a property value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    Null,
+}
+
+impl PropertyType {
+    /// Classify a property value
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::String(_) => Self::String,
+            Value::Number(n) if n.is_i64() || n.is_u64() => Self::Integer,
+            Value::Number(_) => Self::Number,
+            Value::Bool(_) => Self::Boolean,
+            Value::Array(_) => Self::Array,
+            Value::Object(_) => Self::Object,
+            Value::Null => Self::Null,
+        }
+    }
+
+    /// Name of the type in JSON Schema
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Array => "array",
+            Self::Object => "object",
+            Self::Null => "null",
+        }
+    }
+}
+
+impl fmt::Display for PropertyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Inferred schema of one property key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertySchema {
+    pub key: String,
+
+    /// Types seen for the key; integers count as numbers once both are seen
+    pub types: Vec<PropertyType>,
+
+    /// Features carrying the key, including those with a `null` value
+    pub present: usize,
+
+    /// First distinct non-null values, at most [`SCHEMA_EXAMPLES`]
+    pub examples: Vec<Value>,
+}
+
+/// How the features of a dataset are cut into chunks for the index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkingSettings {
+    /// Tokenizer chunks are sized with; `None` when they are sized in words
+    pub tokenizer: Option<String>,
+
+    /// Largest chunk, in words or tokens
+    pub max_chunk_size: usize,
+
+    /// Words or tokens repeated between consecutive chunks
+    pub overlap: usize,
+
+    /// Feature properties copied into the metadata of every chunk
+    pub chunk_properties: Vec<String>,
+}
+
+impl ChunkingSettings {
+    /// Chunks sized in the tokens of `tokenizer`
+    pub fn tokens(
+        tokenizer: impl Into<String>,
+        max_chunk_tokens: usize,
+        overlap_tokens: usize,
+        chunk_properties: Vec<String>,
+    ) -> Self {
+        Self {
+            tokenizer: Some(tokenizer.into()),
+            max_chunk_size: max_chunk_tokens,
+            overlap: overlap_tokens,
+            chunk_properties,
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        if self.tokenizer.is_some() {
+            "tokens"
+        } else {
+            "words"
+        }
+    }
+}
+
+impl From<&ChunkGenerator> for ChunkingSettings {
+    fn from(generator: &ChunkGenerator) -> Self {
+        match &generator.token_limits {
+            Some(limits) => Self::tokens(
+                limits.tokenizer.name(),
+                limits.max_chunk_tokens,
+                limits.overlap_tokens,
+                generator.properties.clone(),
+            ),
+            None => Self {
+                tokenizer: None,
+                max_chunk_size: generator.max_chunk_size,
+                overlap: generator.overlap,
+                chunk_properties: generator.properties.clone(),
+            },
+        }
+    }
+}
+
+/// Inferred schema of a dataset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatasetSchema {
+    pub dataset: String,
+
+    /// Format the dataset was read from
+    pub format: String,
+
+    /// CRS EPSG code
+    pub crs: u32,
+
+    /// Features described; extra tiles of subdivided features are not counted
+    pub feature_count: usize,
+
+    /// Number of features of each geometry type, most common first
+    pub geometry_types: Vec<GeometryTypeCount>,
+
+    /// Features without a geometry
+    pub without_geometry: usize,
+
+    /// Property keys in name order; internal `_`-prefixed keys are left out
+    pub properties: Vec<PropertySchema>,
+
+    pub chunking: ChunkingSettings,
+}
+
+/// Builds a dataset schema from features one at a time
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    feature_count: usize,
+    geometry_types: Vec<GeometryTypeCount>,
+    without_geometry: usize,
+    properties: BTreeMap<String, PropertySchema>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a feature to the schema
+    pub fn add(&mut self, feature: &Feature) {
+        // Extra tiles of a subdivided feature repeat its properties
+        if feature.properties.contains_key(PART_OF_PROPERTY) {
+            return;
+        }
+        self.feature_count += 1;
+
+        match &feature.geometry {
+            Some(geometry) => {
+                let geometry_type = geometry.geometry_type();
+                match self.geometry_types.iter_mut().find(|c| c.geometry_type == geometry_type) {
+                    Some(entry) => entry.count += 1,
+                    None => self.geometry_types.push(GeometryTypeCount { geometry_type, count: 1 }),
+                }
+            }
+            None => self.without_geometry += 1,
+        }
+
+        for (key, value) in feature.properties.iter().filter(|(key, _)| !key.starts_with('_')) {
+            let property = self.properties.entry(key.clone()).or_insert_with(|| PropertySchema {
+                key: key.clone(),
+                types: Vec::new(),
+                present: 0,
+                examples: Vec::new(),
+            });
+            property.present += 1;
+            let property_type = PropertyType::of(value);
+            if !property.types.contains(&property_type) {
+                property.types.push(property_type);
+            }
+            if !value.is_null()
+                && property.examples.len() < SCHEMA_EXAMPLES
+                && !property.examples.contains(value)
+            {
+                property.examples.push(value.clone());
+            }
+        }
+    }
+
+    /// Finish the schema of `dataset`, chunked with `chunking`
+    pub fn finish(self, dataset: &Dataset, chunking: ChunkingSettings) -> DatasetSchema {
+        let mut geometry_types = self.geometry_types;
+        // Stable, so equally common types keep the order they were seen in
+        geometry_types.sort_by_key(|c| Reverse(c.count));
+
+        let properties = self
+            .properties
+            .into_values()
+            .map(|mut property| {
+                if property.types.contains(&PropertyType::Number) {
+                    property.types.retain(|t| *t != PropertyType::Integer);
+                }
+                property.types.sort();
+                property
+            })
+            .collect();
+
+        DatasetSchema {
+            dataset: dataset.name.clone(),
+            format: dataset.format.format_name.clone(),
+            crs: dataset.crs,
+            feature_count: self.feature_count,
+            geometry_types,
+            without_geometry: self.without_geometry,
+            properties,
+            chunking,
+        }
+    }
+}
+
+impl DatasetSchema {
+    /// JSON Schema of the dataset's GeoJSON features
+    ///
+    /// Properties carried by every feature are required. Facts JSON Schema
+    /// has no keyword for (CRS, counts, chunking) are kept under `x-georag`.
+    pub fn to_json_schema(&self) -> Value {
+        let mut properties = Map::new();
+        for property in &self.properties {
+            let types: Vec<&str> = property.types.iter().map(PropertyType::as_str).collect();
+            let mut schema = Map::new();
+            schema.insert(
+                "type".to_string(),
+                match types[..] {
+                    [single] => json!(single),
+                    _ => json!(types),
+                },
+            );
+            if !property.examples.is_empty() {
+                schema.insert("examples".to_string(), json!(property.examples));
+            }
+            properties.insert(property.key.clone(), Value::Object(schema));
+        }
+        let required: Vec<&str> = self
+            .properties
+            .iter()
+            .filter(|property| property.present == self.feature_count)
+            .map(|property| property.key.as_str())
+            .collect();
+
+        let geometry_names: Vec<String> =
+            self.geometry_types.iter().map(|c| format!("{:?}", c.geometry_type)).collect();
+        let geometry = if self.without_geometry > 0 {
+            json!({
+                "type": ["object", "null"],
+                "properties": { "type": { "enum": geometry_names } }
+            })
+        } else {
+            json!({
+                "type": "object",
+                "properties": { "type": { "enum": geometry_names } }
+            })
+        };
+
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": self.dataset,
+            "description": format!(
+                "Features of the {} dataset ({}, EPSG:{})",
+                self.dataset, self.format, self.crs
+            ),
+            "type": "object",
+            "properties": {
+                "geometry": geometry,
+                "properties": {
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                }
+            },
+            "x-georag": {
+                "dataset": self.dataset,
+                "format": self.format,
+                "crs": self.crs,
+                "feature_count": self.feature_count,
+                "geometry_types": self.geometry_types,
+                "without_geometry": self.without_geometry,
+                "chunking": self.chunking,
+            }
+        })
+    }
+
+    /// Markdown section describing the dataset in a data dictionary
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("## {}\n\n", self.dataset);
+        md.push_str(&format!("- Format: {}\n", self.format));
+        md.push_str(&format!("- CRS: EPSG:{}\n", self.crs));
+        md.push_str(&format!("- Features: {}\n", self.feature_count));
+
+        let mut geometry: Vec<String> = self
+            .geometry_types
+            .iter()
+            .map(|c| format!("{:?} ({})", c.geometry_type, c.count))
+            .collect();
+        if self.without_geometry > 0 {
+            geometry.push(format!("none ({})", self.without_geometry));
+        }
+        if geometry.is_empty() {
+            geometry.push("none".to_string());
+        }
+        md.push_str(&format!("- Geometry: {}\n", geometry.join(", ")));
+
+        let chunking = &self.chunking;
+        let sizing = match &chunking.tokenizer {
+            Some(tokenizer) => format!(" ({} tokenizer)", tokenizer),
+            None => String::new(),
+        };
+        md.push_str(&format!(
+            "- Chunking: up to {max} {unit}{sizing}, {overlap} {unit} overlap\n",
+            max = chunking.max_chunk_size,
+            unit = chunking.unit(),
+            overlap = chunking.overlap,
+        ));
+        if !chunking.chunk_properties.is_empty() {
+            let keys: Vec<String> =
+                chunking.chunk_properties.iter().map(|key| format!("`{}`", key)).collect();
+            md.push_str(&format!("- Chunk properties: {}\n", keys.join(", ")));
+        }
+        md.push('\n');
+
+        if self.properties.is_empty() {
+            md.push_str("No properties.\n");
+            return md;
+        }
+        md.push_str("| Property | Type | Present | Examples |\n");
+        md.push_str("|----------|------|---------|----------|\n");
+        for property in &self.properties {
+            let types: Vec<&str> = property.types.iter().map(PropertyType::as_str).collect();
+            let examples: Vec<String> = property
+                .examples
+                .iter()
+                .map(|value| format!("`{}`", example_cell(value)))
+                .collect();
+            md.push_str(&format!(
+                "| `{}` | {} | {} of {} | {} |\n",
+                table_cell(&property.key),
+                types.join(", "),
+                property.present,
+                self.feature_count,
+                examples.join(", ")
+            ));
+        }
+        md
+    }
+}
+
+/// Markdown data dictionary of several datasets
+pub fn data_dictionary(schemas: &[DatasetSchema]) -> String {
+    let sections: Vec<String> = schemas.iter().map(DatasetSchema::to_markdown).collect();
+    format!("# Data dictionary\n\n{}", sections.join("\n"))
+}
+
+/// An example value shortened to fit a table cell
+fn example_cell(value: &Value) -> String {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let short = truncate_chars(&text, SCHEMA_EXAMPLE_CHARS);
+    let ellipsis = if short.len() < text.len() { "…" } else { "" };
+    format!("{}{}", table_cell(short), ellipsis)
+}
+
+/// Text safe inside a Markdown table cell and code span
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('`', "'")
+}
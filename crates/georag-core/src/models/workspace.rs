@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
-use super::DatasetMeta;
+use super::{BuildDiff, ChunkId, Dataset, DatasetMeta, IndexContents, ScoreBaseline};
+use crate::error::GeoragError;
 
 // Re-export from geometry module (single source of truth)
 pub use super::geometry::{DistanceUnit, ValidityMode};
@@ -78,9 +81,26 @@ pub struct Workspace {
     pub index_state: Option<IndexState>,
 }
 
+/// Version of the index layout written by this build
+///
+/// Bump it whenever chunk IDs, chunk metadata or the stored embeddings
+/// change meaning, and register the step upgrading the previous version.
+pub const INDEX_SCHEMA_VERSION: u32 = 2;
+
+/// Schema version of index states written before versions were recorded
+pub const UNVERSIONED_INDEX_SCHEMA: u32 = 1;
+
+fn unversioned_index_schema() -> u32 {
+    UNVERSIONED_INDEX_SCHEMA
+}
+
 /// Index build state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexState {
+    /// Layout version of the index, see [`INDEX_SCHEMA_VERSION`]
+    #[serde(default = "unversioned_index_schema")]
+    pub schema_version: u32,
+
     /// Deterministic hash of the index
     pub hash: String,
 
@@ -95,4 +115,430 @@ pub struct IndexState {
 
     /// Embedding dimension
     pub embedding_dim: usize,
+
+    /// When each dataset was last built into the index, by dataset name
+    #[serde(default)]
+    pub dataset_built_at: BTreeMap<String, DateTime<Utc>>,
+
+    /// What the index holds; `None` in states written before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contents: Option<IndexContents>,
+
+    /// How the build that wrote this state changed the index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_build_diff: Option<BuildDiff>,
+
+    /// Tokenizer the chunks were sized with; `None` when they were sized in words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+
+    /// Sampled similarity scores results are compared with; `None` when the
+    /// index had too few embeddings to pair or was built before it was sampled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_baseline: Option<ScoreBaseline>,
+}
+
+impl IndexState {
+    /// Check this build can serve the index as it is
+    ///
+    /// Indexes of an older schema version must be upgraded first; those of
+    /// a newer one were written by a newer build and are refused.
+    pub fn check_schema_version(&self) -> crate::error::Result<()> {
+        match self.schema_version.cmp(&INDEX_SCHEMA_VERSION) {
+            Ordering::Equal => Ok(()),
+            Ordering::Less => Err(GeoragError::IndexSchemaOutdated {
+                found: self.schema_version,
+                current: INDEX_SCHEMA_VERSION,
+            }),
+            Ordering::Greater => Err(GeoragError::IndexSchemaTooNew {
+                found: self.schema_version,
+                supported: INDEX_SCHEMA_VERSION,
+            }),
+        }
+    }
+}
+
+/// Progress of an interrupted index build
+///
+/// Saved every few embedded batches so a crashed build can resume instead
+/// of starting over. Embeddings present in the vector store while this
+/// checkpoint exists belong to its build generation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildCheckpoint {
+    /// Identifier of the build the checkpoint belongs to
+    pub generation: Uuid,
+
+    /// Embedder the build uses; resuming with another embedder is refused
+    pub embedder: String,
+
+    /// When the first attempt of the build started
+    pub started_at: DateTime<Utc>,
+
+    /// When the checkpoint was last saved
+    pub updated_at: DateTime<Utc>,
+
+    /// Number of attempts, including the one that saved the checkpoint
+    pub attempts: u32,
+
+    /// Chunks generated for the build
+    pub total_chunks: usize,
+
+    /// Chunks embedded and stored so far
+    pub embedded_chunks: usize,
+
+    /// Batches completed by the attempt that saved the checkpoint
+    pub batches_completed: usize,
+
+    /// Last chunk of the last completed batch
+    pub last_chunk_id: Option<ChunkId>,
+
+    /// Wall time spent by earlier attempts and the current one, in seconds
+    pub elapsed_secs: f64,
+}
+
+impl BuildCheckpoint {
+    /// Start a checkpoint for a new build generation
+    pub fn new(embedder: impl Into<String>, total_chunks: usize) -> Self {
+        let now = Utc::now();
+        Self {
+            generation: Uuid::new_v4(),
+            embedder: embedder.into(),
+            started_at: now,
+            updated_at: now,
+            attempts: 1,
+            total_chunks,
+            embedded_chunks: 0,
+            batches_completed: 0,
+            last_chunk_id: None,
+            elapsed_secs: 0.0,
+        }
+    }
+}
+
+/// Resource counted against a workspace quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    Datasets,
+    Features,
+    Chunks,
+    BlobBytes,
+}
+
+impl QuotaResource {
+    /// All resources, in reporting order
+    pub const ALL: [QuotaResource; 4] =
+        [Self::Datasets, Self::Features, Self::Chunks, Self::BlobBytes];
+
+    /// Name used in settings keys and API responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Datasets => "datasets",
+            Self::Features => "features",
+            Self::Chunks => "chunks",
+            Self::BlobBytes => "blob_bytes",
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Resources a workspace currently uses
+///
+/// Stores keep these counters up to date as datasets, chunks and original
+/// files are added and removed, so reading them never scans the data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceUsage {
+    pub datasets: u64,
+    pub features: u64,
+    pub chunks: u64,
+    pub blob_bytes: u64,
+}
+
+impl WorkspaceUsage {
+    /// Current usage of a resource
+    pub fn get(&self, resource: QuotaResource) -> u64 {
+        match resource {
+            QuotaResource::Datasets => self.datasets,
+            QuotaResource::Features => self.features,
+            QuotaResource::Chunks => self.chunks,
+            QuotaResource::BlobBytes => self.blob_bytes,
+        }
+    }
+
+    /// Apply a change, stopping at zero
+    pub fn apply(&mut self, delta: &UsageDelta) {
+        for resource in QuotaResource::ALL {
+            let value = self.get(resource).saturating_add_signed(delta.get(resource));
+            match resource {
+                QuotaResource::Datasets => self.datasets = value,
+                QuotaResource::Features => self.features = value,
+                QuotaResource::Chunks => self.chunks = value,
+                QuotaResource::BlobBytes => self.blob_bytes = value,
+            }
+        }
+    }
+}
+
+/// Change in workspace usage; negative values release usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageDelta {
+    pub datasets: i64,
+    pub features: i64,
+    pub chunks: i64,
+    pub blob_bytes: i64,
+}
+
+impl UsageDelta {
+    /// Usage a stored dataset takes up, including its kept source file
+    pub fn for_dataset(dataset: &Dataset) -> Self {
+        Self {
+            datasets: 1,
+            features: dataset.feature_count as i64,
+            chunks: 0,
+            blob_bytes: dataset.format.source.as_ref().map_or(0, |source| source.size as i64),
+        }
+    }
+
+    /// Change of a resource
+    pub fn get(&self, resource: QuotaResource) -> i64 {
+        match resource {
+            QuotaResource::Datasets => self.datasets,
+            QuotaResource::Features => self.features,
+            QuotaResource::Chunks => self.chunks,
+            QuotaResource::BlobBytes => self.blob_bytes,
+        }
+    }
+
+    /// The change that undoes this one
+    pub fn negated(&self) -> Self {
+        Self {
+            datasets: -self.datasets,
+            features: -self.features,
+            chunks: -self.chunks,
+            blob_bytes: -self.blob_bytes,
+        }
+    }
+
+    /// Both changes together
+    pub fn plus(&self, other: &Self) -> Self {
+        Self {
+            datasets: self.datasets + other.datasets,
+            features: self.features + other.features,
+            chunks: self.chunks + other.chunks,
+            blob_bytes: self.blob_bytes + other.blob_bytes,
+        }
+    }
+
+    /// Whether nothing changes
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Limits on what a workspace may use; `None` is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceQuotas {
+    pub max_datasets: Option<u64>,
+    pub max_features: Option<u64>,
+    pub max_chunks: Option<u64>,
+    pub max_blob_bytes: Option<u64>,
+}
+
+impl WorkspaceQuotas {
+    /// Limit on a resource, if any
+    pub fn limit(&self, resource: QuotaResource) -> Option<u64> {
+        match resource {
+            QuotaResource::Datasets => self.max_datasets,
+            QuotaResource::Features => self.max_features,
+            QuotaResource::Chunks => self.max_chunks,
+            QuotaResource::BlobBytes => self.max_blob_bytes,
+        }
+    }
+
+    /// Take limits this set leaves unset from `defaults`
+    pub fn or(self, defaults: Self) -> Self {
+        Self {
+            max_datasets: self.max_datasets.or(defaults.max_datasets),
+            max_features: self.max_features.or(defaults.max_features),
+            max_chunks: self.max_chunks.or(defaults.max_chunks),
+            max_blob_bytes: self.max_blob_bytes.or(defaults.max_blob_bytes),
+        }
+    }
+
+    /// Check that applying `delta` keeps `usage` within the limits
+    ///
+    /// Reaching a limit exactly is allowed. Only growth is refused, so a
+    /// workspace already over a lowered limit can still shrink.
+    pub fn check(&self, usage: &WorkspaceUsage, delta: &UsageDelta) -> crate::error::Result<()> {
+        for resource in QuotaResource::ALL {
+            let (Some(limit), Ok(requested)) =
+                (self.limit(resource), u64::try_from(delta.get(resource)))
+            else {
+                continue;
+            };
+            let current = usage.get(resource);
+            if requested > 0 && current.saturating_add(requested) > limit {
+                return Err(crate::error::GeoragError::QuotaExceeded {
+                    resource,
+                    limit,
+                    usage: current,
+                    requested,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any limit is set
+    pub fn is_limited(&self) -> bool {
+        QuotaResource::ALL.iter().any(|resource| self.limit(*resource).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(resource: QuotaResource, amount: i64) -> UsageDelta {
+        let mut delta = UsageDelta::default();
+        match resource {
+            QuotaResource::Datasets => delta.datasets = amount,
+            QuotaResource::Features => delta.features = amount,
+            QuotaResource::Chunks => delta.chunks = amount,
+            QuotaResource::BlobBytes => delta.blob_bytes = amount,
+        }
+        delta
+    }
+
+    #[test]
+    fn test_quota_allows_reaching_each_limit_exactly() {
+        let quotas = WorkspaceQuotas {
+            max_datasets: Some(3),
+            max_features: Some(100),
+            max_chunks: Some(50),
+            max_blob_bytes: Some(1024),
+        };
+        let usage = WorkspaceUsage {
+            datasets: 2,
+            features: 90,
+            chunks: 45,
+            blob_bytes: 1000,
+        };
+
+        for (resource, room) in [
+            (QuotaResource::Datasets, 1),
+            (QuotaResource::Features, 10),
+            (QuotaResource::Chunks, 5),
+            (QuotaResource::BlobBytes, 24),
+        ] {
+            assert!(quotas.check(&usage, &delta(resource, room)).is_ok(), "{}", resource);
+
+            match quotas.check(&usage, &delta(resource, room + 1)) {
+                Err(GeoragError::QuotaExceeded {
+                    resource: exceeded,
+                    limit,
+                    usage: used,
+                    requested,
+                }) => {
+                    assert_eq!(exceeded, resource);
+                    assert_eq!(limit, quotas.limit(resource).unwrap());
+                    assert_eq!(used, usage.get(resource));
+                    assert_eq!(requested, room as u64 + 1);
+                }
+                other => panic!("expected {} quota error, got {:?}", resource, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_quota_allows_shrinking_over_limit() {
+        let quotas = WorkspaceQuotas {
+            max_chunks: Some(10),
+            ..Default::default()
+        };
+        let usage = WorkspaceUsage { chunks: 20, ..Default::default() };
+
+        assert!(quotas.check(&usage, &delta(QuotaResource::Chunks, -5)).is_ok());
+        assert!(quotas.check(&usage, &delta(QuotaResource::Chunks, 1)).is_err());
+        assert!(WorkspaceQuotas::default()
+            .check(&usage, &delta(QuotaResource::Chunks, 1))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_usage_apply_stops_at_zero() {
+        let mut usage = WorkspaceUsage {
+            datasets: 1,
+            features: 5,
+            ..Default::default()
+        };
+        let added = UsageDelta {
+            datasets: 1,
+            features: 10,
+            chunks: 3,
+            blob_bytes: 0,
+        };
+        usage.apply(&added);
+        assert_eq!(
+            usage,
+            WorkspaceUsage {
+                datasets: 2,
+                features: 15,
+                chunks: 3,
+                blob_bytes: 0
+            }
+        );
+
+        usage.apply(&UsageDelta { features: -20, ..added.negated() });
+        assert_eq!(usage, WorkspaceUsage { datasets: 1, ..Default::default() });
+    }
+
+    #[test]
+    fn test_quotas_fall_back_to_defaults() {
+        let workspace = WorkspaceQuotas {
+            max_datasets: Some(5),
+            ..Default::default()
+        };
+        let defaults = WorkspaceQuotas {
+            max_datasets: Some(50),
+            max_features: Some(1000),
+            ..Default::default()
+        };
+
+        let effective = workspace.or(defaults);
+        assert_eq!(effective.max_datasets, Some(5));
+        assert_eq!(effective.max_features, Some(1000));
+        assert_eq!(effective.max_chunks, None);
+    }
+
+    #[test]
+    fn test_index_schema_version_is_checked() {
+        // States written before versions were recorded read as the first version
+        let legacy = r#"{
+            "hash": "abc",
+            "built_at": "2024-03-01T12:00:00Z",
+            "embedder": "ollama:nomic-embed-text",
+            "chunk_count": 12,
+            "embedding_dim": 768
+        }"#;
+        let mut state: IndexState = serde_json::from_str(legacy).unwrap();
+        assert_eq!(state.schema_version, UNVERSIONED_INDEX_SCHEMA);
+        assert!(matches!(
+            state.check_schema_version(),
+            Err(GeoragError::IndexSchemaOutdated { found: 1, current: INDEX_SCHEMA_VERSION })
+        ));
+
+        state.schema_version = INDEX_SCHEMA_VERSION;
+        assert!(state.check_schema_version().is_ok());
+
+        state.schema_version = INDEX_SCHEMA_VERSION + 1;
+        let err = state.check_schema_version().unwrap_err();
+        assert!(matches!(err, GeoragError::IndexSchemaTooNew { .. }));
+        assert!(err.to_string().contains(&format!("version {}", INDEX_SCHEMA_VERSION + 1)));
+    }
 }
@@ -1,8 +1,11 @@
 pub mod chunk;
+pub mod text;
+pub mod tokenizer;
 
 use crate::error::{GeoragError, Result};
 use crate::models::{ChunkId, ChunkMetadata, ChunkSource, FeatureId, TextChunk};
 use std::collections::HashMap;
+use text::{floor_grapheme_boundary, truncate_bytes};
 
 pub use chunk::ChunkGenerator;
 
@@ -53,6 +56,8 @@ pub fn chunk_text(text: &str, config: &ChunkConfig, document_path: &str) -> Resu
     let mut chunks = Vec::new();
     let mut chunk_id = 0u64;
     let mut offset = 0;
+    // Sizes are bytes; chunk sources count characters
+    let mut char_offset = 0;
 
     while offset < text.len() {
         let remaining = text.len() - offset;
@@ -65,6 +70,7 @@ pub fn chunk_text(text: &str, config: &ChunkConfig, document_path: &str) -> Resu
 
         let chunk_end = offset + chunk_size;
         let content = text[offset..chunk_end].to_string();
+        let length = content.chars().count();
 
         let chunk = TextChunk {
             id: ChunkId(chunk_id),
@@ -72,12 +78,17 @@ pub fn chunk_text(text: &str, config: &ChunkConfig, document_path: &str) -> Resu
             source: ChunkSource {
                 document_path: document_path.to_string(),
                 page: None,
-                offset,
+                offset: char_offset,
+                length,
             },
             spatial_ref: None,
+            geometry: None,
             metadata: ChunkMetadata {
                 size: chunk_size,
                 properties: HashMap::new(),
+                source_hash: None,
+                stale: false,
+                dataset_id: None,
             },
         };
 
@@ -88,23 +99,38 @@ pub fn chunk_text(text: &str, config: &ChunkConfig, document_path: &str) -> Resu
             break;
         }
 
-        offset = chunk_end.saturating_sub(config.overlap);
+        // The overlap starts on a grapheme boundary, past the chunk's start
+        let overlap_start = offset
+            + floor_grapheme_boundary(
+                &text[offset..chunk_end],
+                chunk_size.saturating_sub(config.overlap),
+            );
+        let next = if overlap_start > offset {
+            overlap_start
+        } else {
+            chunk_end
+        };
+        char_offset += text[offset..next].chars().count();
+        offset = next;
     }
 
     Ok(chunks)
 }
 
 /// Find a good break point for chunking near the ideal position
+///
+/// Without whitespace to break at, the chunk ends on the last grapheme
+/// boundary that fits.
 fn find_break_point(text: &str, start: usize, ideal_end: usize, min_size: usize) -> usize {
-    let max_end = ideal_end.min(text.len());
-    let search_start = (start + min_size).max(start);
-    let search_range = &text[search_start..max_end];
+    let chunk = truncate_bytes(&text[start..], ideal_end - start);
+    let search_start = floor_grapheme_boundary(chunk, min_size);
 
-    if let Some(last_space) = search_range.rfind(|c: char| c.is_whitespace()) {
-        return search_start - start + last_space + 1;
+    if let Some(last_space) = chunk[search_start..].rfind(char::is_whitespace) {
+        let space = search_start + last_space;
+        return space + chunk[space..].chars().next().map_or(1, char::len_utf8);
     }
 
-    max_end - start
+    chunk.len()
 }
 
 /// Associate a text chunk with a spatial feature
@@ -212,7 +238,7 @@ mod tests {
             let curr_chunk = &chunks[i];
 
             // Current chunk should start before previous chunk ended
-            assert!(curr_chunk.source.offset < prev_chunk.source.offset + prev_chunk.metadata.size);
+            assert!(curr_chunk.source.offset < prev_chunk.source.offset + prev_chunk.source.length);
         }
     }
 
@@ -294,7 +320,7 @@ mod tests {
         // Verify ChunkSource is properly tracked
         for chunk in &chunks {
             assert_eq!(chunk.source.document_path, "document.pdf");
-            assert!(chunk.source.offset < text.len());
+            assert!(chunk.source.offset < text.chars().count());
             assert_eq!(chunk.source.page, None);
         }
 
@@ -1,18 +1,31 @@
 use crate::error::{GeoragError, Result};
+use crate::geo::places::Gazetteer;
 use crate::models::{
     ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, Feature, FeatureId, TextChunk,
 };
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use super::text::{tokens, tokens_within, MAX_CHUNK_BYTES};
+use super::tokenizer::TokenLimits;
 
 /// Configuration for chunk generation
 #[derive(Debug, Clone)]
 pub struct ChunkGenerator {
     /// Minimum words per chunk
+    ///
+    /// A trailing window with fewer new words is folded into the chunk before
+    /// it. A feature whose whole text is shorter still gets one chunk.
     pub min_chunk_size: usize,
     /// Maximum words per chunk
     pub max_chunk_size: usize,
     /// Word overlap between chunks
     pub overlap: usize,
+    /// Feature properties copied into the metadata of every chunk
+    pub properties: Vec<String>,
+    /// Size chunks in model tokens instead of words
+    pub token_limits: Option<TokenLimits>,
 }
 
 impl Default for ChunkGenerator {
@@ -21,6 +34,8 @@ impl Default for ChunkGenerator {
             min_chunk_size: 50,
             max_chunk_size: 500,
             overlap: 50,
+            properties: Vec::new(),
+            token_limits: None,
         }
     }
 }
@@ -48,23 +63,57 @@ impl ChunkGenerator {
             });
         }
 
-        Ok(Self { min_chunk_size, max_chunk_size, overlap })
+        Ok(Self {
+            min_chunk_size,
+            max_chunk_size,
+            overlap,
+            properties: Vec::new(),
+            token_limits: None,
+        })
+    }
+
+    /// Copy the named feature properties into chunk metadata
+    ///
+    /// Properties a feature does not have, or that are null, are skipped.
+    pub fn with_properties(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.properties = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Size chunks in the tokens of `limits.tokenizer` instead of words
+    ///
+    /// The word sizes are then ignored: chunks end at the token limit and no
+    /// short tail is folded into the chunk before it, so no chunk goes over.
+    pub fn with_token_limits(mut self, limits: TokenLimits) -> Self {
+        self.token_limits = Some(limits);
+        self
     }
 
     /// Generate chunks from a dataset's features
+    ///
+    /// Features whose text has no letters or digits get no chunks. Extra tiles
+    /// of a subdivided feature get none either; the tile keeping the feature's
+    /// ID is chunked once for all of them. Chunks of a feature with named
+    /// places get the geometry of the places their text names.
     pub fn generate_chunks(&self, dataset: &Dataset, features: &[Feature]) -> Vec<TextChunk> {
         let mut chunks = Vec::new();
         let mut global_chunk_index = 0u64;
 
-        for feature in features {
-            if let Some(text) = self.extract_text(feature) {
-                let feature_chunks = self.chunk_text(
+        for feature in features.iter().filter(|f| f.part_of().is_none()) {
+            if let Some(text) = self.feature_text(feature) {
+                let mut feature_chunks = self.chunk_text(
                     &text,
                     dataset.id,
                     feature.id,
+                    &self.extract_properties(feature),
                     &dataset.path.to_string_lossy(),
                     &mut global_chunk_index,
                 );
+                if let Some(places) = Gazetteer::from_feature(feature) {
+                    for chunk in &mut feature_chunks {
+                        chunk.geometry = places.geometry_for(&chunk.content);
+                    }
+                }
                 chunks.extend(feature_chunks);
             }
         }
@@ -72,12 +121,14 @@ impl ChunkGenerator {
         chunks
     }
 
-    /// Extract text content from a feature following priority rules
-    fn extract_text(&self, feature: &Feature) -> Option<String> {
+    /// Text a feature is chunked from, following priority rules
+    ///
+    /// `None` when the feature has no text worth indexing.
+    pub fn feature_text(&self, feature: &Feature) -> Option<String> {
         // Rule 1: If feature has "content" property, use it
         if let Some(content) = feature.properties.get("content") {
             if let Some(text) = content.as_str() {
-                if !text.trim().is_empty() {
+                if is_meaningful(text) {
                     return Some(text.to_string());
                 }
             }
@@ -88,13 +139,13 @@ impl ChunkGenerator {
             .properties
             .get("name")
             .and_then(|v| v.as_str())
-            .filter(|s| !s.trim().is_empty());
+            .filter(|s| is_meaningful(s));
 
         let description = feature
             .properties
             .get("description")
             .and_then(|v| v.as_str())
-            .filter(|s| !s.trim().is_empty());
+            .filter(|s| is_meaningful(s));
 
         match (name, description) {
             (Some(n), Some(d)) => Some(format!("{}: {}", n, d)),
@@ -107,32 +158,73 @@ impl ChunkGenerator {
         }
     }
 
+    /// Whitelisted feature properties as strings
+    fn extract_properties(&self, feature: &Feature) -> HashMap<String, String> {
+        self.properties
+            .iter()
+            .filter_map(|name| {
+                let value = property_text(feature.properties.get(name)?)?;
+                Some((name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Words chunks are cut from
+    ///
+    /// Words are the tokens of [`tokens`], so an over-long run of characters
+    /// counts as several words. With token limits, words longer than the
+    /// limit are split further so that every chunk fits.
+    fn words<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        match &self.token_limits {
+            Some(limits) => tokens(text).flat_map(|word| limits.pieces(word)).collect(),
+            None => tokens(text).collect(),
+        }
+    }
+
+    /// Normalized text of a feature text: its words joined by single spaces
+    ///
+    /// Chunk offsets and lengths are characters of this text, and the content
+    /// of every chunk is a slice of it.
+    pub fn normalized_text(&self, text: &str) -> String {
+        self.words(text).join(" ")
+    }
+
     /// Chunk text into segments with word-based boundaries
+    ///
+    /// A chunk ends early rather than grow past [`MAX_CHUNK_BYTES`]. Offsets
+    /// are counted in characters of [`Self::normalized_text`].
     fn chunk_text(
         &self,
         text: &str,
         dataset_id: DatasetId,
         feature_id: FeatureId,
+        properties: &HashMap<String, String>,
         document_path: &str,
         global_chunk_index: &mut u64,
     ) -> Vec<TextChunk> {
-        let words: Vec<&str> = text.split_whitespace().collect();
+        let words = self.words(text);
 
         if words.is_empty() {
             return Vec::new();
         }
 
+        // Character offset of every word in the normalized text
+        let mut char_offsets = Vec::with_capacity(words.len());
+        let mut char_offset = 0;
+        for word in &words {
+            char_offsets.push(char_offset);
+            char_offset += word.chars().count() + 1;
+        }
+
+        let source_hash = source_text_hash(text);
+
         let mut chunks = Vec::new();
         let mut word_offset = 0;
 
         while word_offset < words.len() {
-            let remaining_words = words.len() - word_offset;
-
-            // Determine chunk size in words
-            let chunk_word_count = if remaining_words <= self.max_chunk_size {
-                remaining_words
-            } else {
-                self.max_chunk_size
+            let (chunk_word_count, overlap) = match &self.token_limits {
+                Some(limits) => limits.window(&words[word_offset..]),
+                None => self.word_window(&words[word_offset..]),
             };
 
             let chunk_words = &words[word_offset..word_offset + chunk_word_count];
@@ -147,12 +239,17 @@ impl ChunkGenerator {
                 source: ChunkSource {
                     document_path: document_path.to_string(),
                     page: None,
-                    offset: word_offset,
+                    offset: char_offsets[word_offset],
+                    length: content.chars().count(),
                 },
                 spatial_ref: Some(feature_id),
+                geometry: None,
                 metadata: ChunkMetadata {
                     size: content.len(),
-                    properties: HashMap::new(),
+                    properties: properties.clone(),
+                    source_hash: Some(source_hash.clone()),
+                    stale: false,
+                    dataset_id: Some(dataset_id),
                 },
             };
 
@@ -163,13 +260,33 @@ impl ChunkGenerator {
             if word_offset + chunk_word_count >= words.len() {
                 break;
             }
-
-            word_offset += chunk_word_count.saturating_sub(self.overlap);
+            word_offset += chunk_word_count.saturating_sub(overlap);
         }
 
         chunks
     }
 
+    /// Words of the next chunk and how many of them the chunk after repeats
+    fn word_window(&self, words: &[&str]) -> (usize, usize) {
+        // Take a short tail along rather than leaving it as a chunk of its own
+        let full_word_count = if words.len() <= self.max_chunk_size
+            || words.len() - self.max_chunk_size < self.min_chunk_size
+        {
+            words.len()
+        } else {
+            self.max_chunk_size
+        };
+        let chunk_word_count = tokens_within(&words[..full_word_count], MAX_CHUNK_BYTES);
+
+        // A chunk cut short by its bytes overlaps the next by at most half
+        let overlap = if chunk_word_count < full_word_count {
+            self.overlap.min(chunk_word_count / 2)
+        } else {
+            self.overlap
+        };
+        (chunk_word_count, overlap)
+    }
+
     /// Generate deterministic ChunkId from dataset_id + feature_id + chunk_index
     fn generate_chunk_id(
         &self,
@@ -184,9 +301,96 @@ impl ChunkGenerator {
     }
 }
 
+impl TokenLimits {
+    /// Pieces of a word of at most `max_chunk_tokens` each
+    fn pieces<'a>(&self, word: &'a str) -> Vec<&'a str> {
+        let mut pieces = Vec::new();
+        let mut rest = word;
+        while !rest.is_empty() {
+            let piece = self.tokenizer.truncate(rest, self.max_chunk_tokens);
+            pieces.push(piece);
+            rest = &rest[piece.len()..];
+        }
+        pieces
+    }
+
+    /// Words of the next chunk and how many of them the chunk after repeats
+    ///
+    /// Words are added while the chunk stays within the token and byte
+    /// limits; the joined text is counted again, since tokenizers may count
+    /// it differently from its words.
+    fn window(&self, words: &[&str]) -> (usize, usize) {
+        let words = &words[..tokens_within(words, MAX_CHUNK_BYTES)];
+
+        let mut costs = Vec::new();
+        let mut total = 0;
+        for (i, word) in words.iter().enumerate() {
+            let cost = if i == 0 {
+                self.tokenizer.count(word)
+            } else {
+                self.tokenizer.count(&format!(" {}", word))
+            };
+            if i > 0 && total + cost > self.max_chunk_tokens {
+                break;
+            }
+            total += cost;
+            costs.push(cost);
+        }
+
+        let mut count = costs.len();
+        while count > 1 && self.tokenizer.count(&words[..count].join(" ")) > self.max_chunk_tokens {
+            count -= 1;
+        }
+
+        // Repeat trailing words up to the overlap, always moving forward
+        let mut overlap = 0;
+        let mut overlap_tokens = 0;
+        while overlap + 1 < count {
+            let cost = costs[count - 1 - overlap];
+            if overlap_tokens + cost > self.overlap_tokens {
+                break;
+            }
+            overlap_tokens += cost;
+            overlap += 1;
+        }
+        (count, overlap)
+    }
+}
+
+/// Whether text is worth embedding
+///
+/// Empty, whitespace-only and punctuation-only text embeds to near-degenerate
+/// vectors that can rank arbitrarily high, so it needs a letter or digit.
+pub fn is_meaningful(text: &str) -> bool {
+    text.chars().any(char::is_alphanumeric)
+}
+
+/// Deterministic hash of the feature text chunks are cut from
+///
+/// Stored with every chunk so garbage collection can tell when the text of
+/// the chunk's feature has changed since the chunk was generated.
+pub fn source_text_hash(text: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Text form of a feature property as stored in chunk metadata
+///
+/// Strings are kept as they are, other values use their JSON text and null
+/// has no text form.
+pub fn property_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::PART_OF_PROPERTY;
     use std::path::PathBuf;
 
     fn create_test_dataset() -> Dataset {
@@ -205,8 +409,15 @@ mod tests {
                 paragraph_count: None,
                 extraction_method: None,
                 spatial_association: None,
+                axis_order: None,
+                source: None,
+                preview: None,
+                remote: None,
             },
             added_at: chrono::Utc::now(),
+            tags: Vec::new(),
+            license: None,
+            attribution: None,
         }
     }
 
@@ -217,6 +428,7 @@ mod tests {
             geometry: Some(Geometry::point(0.0, 0.0)),
             properties,
             crs: 4326,
+            z: None,
         }
     }
 
@@ -258,7 +470,7 @@ mod tests {
         props.insert("description".to_string(), serde_json::json!("Description text"));
 
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, Some("Content text".to_string()));
     }
@@ -272,7 +484,7 @@ mod tests {
         props.insert("description".to_string(), serde_json::json!("A beautiful park"));
 
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, Some("Park Name: A beautiful park".to_string()));
     }
@@ -285,7 +497,7 @@ mod tests {
         props.insert("name".to_string(), serde_json::json!("Location Name"));
 
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, Some("Location Name".to_string()));
     }
@@ -298,7 +510,7 @@ mod tests {
         props.insert("description".to_string(), serde_json::json!("Just a description"));
 
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, Some("Just a description".to_string()));
     }
@@ -309,7 +521,7 @@ mod tests {
 
         let props = HashMap::new();
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, None);
     }
@@ -323,7 +535,7 @@ mod tests {
         props.insert("description".to_string(), serde_json::json!(""));
 
         let feature = create_test_feature(1, props);
-        let text = generator.extract_text(&feature);
+        let text = generator.feature_text(&feature);
 
         assert_eq!(text, None);
     }
@@ -369,6 +581,28 @@ mod tests {
         assert_eq!(chunks[1].spatial_ref, Some(FeatureId(2)));
     }
 
+    #[test]
+    fn test_generate_chunks_copies_whitelisted_properties() {
+        let generator = ChunkGenerator::default().with_properties(["category", "year", "village"]);
+        let dataset = create_test_dataset();
+
+        let mut props = HashMap::new();
+        props.insert("content".to_string(), serde_json::json!("School next to the market"));
+        props.insert("category".to_string(), serde_json::json!("school"));
+        props.insert("year".to_string(), serde_json::json!(2019));
+        props.insert("village".to_string(), serde_json::Value::Null);
+        props.insert("owner".to_string(), serde_json::json!("not copied"));
+        let feature = create_test_feature(1, props);
+
+        let chunks = generator.generate_chunks(&dataset, &[feature]);
+
+        assert_eq!(chunks.len(), 1);
+        let properties = &chunks[0].metadata.properties;
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties.get("category").map(String::as_str), Some("school"));
+        assert_eq!(properties.get("year").map(String::as_str), Some("2019"));
+    }
+
     #[test]
     fn test_generate_chunks_skips_features_without_text() {
         let generator = ChunkGenerator::default();
@@ -387,6 +621,155 @@ mod tests {
         assert_eq!(chunks[0].spatial_ref, Some(FeatureId(2)));
     }
 
+    #[test]
+    fn test_generate_chunks_skips_extra_tiles() {
+        let generator = ChunkGenerator::default();
+        let dataset = create_test_dataset();
+
+        let mut props = HashMap::new();
+        props.insert("content".to_string(), serde_json::json!("Coastline"));
+        let original = create_test_feature(1, props.clone());
+        props.insert(PART_OF_PROPERTY.to_string(), serde_json::json!(1));
+        let tile = create_test_feature(7, props);
+
+        let chunks = generator.generate_chunks(&dataset, &[original, tile]);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].spatial_ref, Some(FeatureId(1)));
+    }
+
+    #[test]
+    fn test_generate_chunks_assigns_geometry_of_named_places() {
+        use crate::geo::places::{Gazetteer, Place, PLACES_PROPERTY};
+        use crate::models::Geometry;
+
+        let generator = ChunkGenerator::new(1, 4, 0).unwrap();
+        let dataset = create_test_dataset();
+        let places = Gazetteer::new(vec![
+            Place {
+                name: "Alder".to_string(),
+                geometry: Geometry::point(1.0, 1.0),
+            },
+            Place {
+                name: "Birch".to_string(),
+                geometry: Geometry::point(2.0, 2.0),
+            },
+        ]);
+
+        let mut props = HashMap::new();
+        props.insert(
+            "content".to_string(),
+            serde_json::json!(
+                "Alder park drainage works Birch lane street lights general budget summary table"
+            ),
+        );
+        props.insert(PLACES_PROPERTY.to_string(), places.to_property());
+        let document = create_test_feature(1, props);
+
+        let chunks = generator.generate_chunks(&dataset, &[document]);
+
+        let geometries: Vec<Option<Geometry>> = chunks.into_iter().map(|c| c.geometry).collect();
+        assert_eq!(
+            geometries,
+            vec![Some(Geometry::point(1.0, 1.0)), Some(Geometry::point(2.0, 2.0)), None]
+        );
+    }
+
+    #[test]
+    fn test_generate_chunks_skips_whitespace_and_punctuation() {
+        let generator = ChunkGenerator::default();
+        let dataset = create_test_dataset();
+
+        let mut features = Vec::new();
+        for (id, (key, value)) in
+            [("content", " "), ("name", ""), ("name", "--"), ("description", " ... ")]
+                .into_iter()
+                .enumerate()
+        {
+            let mut props = HashMap::new();
+            props.insert(key.to_string(), serde_json::json!(value));
+            features.push(create_test_feature(id as u64 + 1, props));
+        }
+
+        let mut props = HashMap::new();
+        props.insert("name".to_string(), serde_json::json!("?"));
+        props.insert("description".to_string(), serde_json::json!("Ferry pier"));
+        features.push(create_test_feature(5, props));
+
+        let chunks = generator.generate_chunks(&dataset, &features);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "Ferry pier");
+        assert!(chunks.iter().all(|chunk| is_meaningful(&chunk.content)));
+    }
+
+    #[test]
+    fn test_short_tail_is_folded_into_previous_chunk() {
+        let generator = ChunkGenerator::new(4, 10, 2).unwrap();
+        let dataset = create_test_dataset();
+
+        let words: Vec<String> = (0..12).map(|i| format!("w{}", i)).collect();
+        let mut props = HashMap::new();
+        props.insert("content".to_string(), serde_json::json!(words.join(" ")));
+        let feature = create_test_feature(1, props);
+
+        // Two words past the first window is under min_chunk_size
+        let chunks = generator.generate_chunks(&dataset, &[feature]);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content.split_whitespace().count(), 12);
+    }
+
+    #[test]
+    fn test_text_shorter_than_min_chunk_size_keeps_one_chunk() {
+        let generator = ChunkGenerator::new(5, 10, 2).unwrap();
+        let dataset = create_test_dataset();
+
+        let mut props = HashMap::new();
+        props.insert("name".to_string(), serde_json::json!("Harbour"));
+        let feature = create_test_feature(1, props);
+
+        let chunks = generator.generate_chunks(&dataset, &[feature]);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "Harbour");
+    }
+
+    #[test]
+    fn test_token_limits_bound_every_chunk() {
+        use crate::processing::tokenizer::{HeuristicTokenizer, Tokenizer};
+        use std::sync::Arc;
+
+        let tokenizer: Arc<dyn Tokenizer> = Arc::new(HeuristicTokenizer);
+        let limits = TokenLimits::new(tokenizer.clone(), 16, 4).unwrap();
+        let generator = ChunkGenerator::default().with_token_limits(limits);
+        let dataset = create_test_dataset();
+
+        let words: Vec<String> = (0..60).map(|i| format!("kata{}", i)).collect();
+        let text = format!("{} {}", words.join(" "), "x".repeat(200));
+        let mut props = HashMap::new();
+        props.insert("content".to_string(), serde_json::json!(text));
+        let chunks = generator.generate_chunks(&dataset, &[create_test_feature(1, props)]);
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(tokenizer.count(&chunk.content) <= 16, "{}", chunk.content);
+        }
+        // The next chunk starts within the last words of the one before
+        let first_word = chunks[1].content.split(' ').next().unwrap();
+        assert!(chunks[0].content.split(' ').skip(1).any(|word| word == first_word));
+    }
+
+    #[test]
+    fn test_is_meaningful() {
+        assert!(!is_meaningful(""));
+        assert!(!is_meaningful(" \t\n"));
+        assert!(!is_meaningful("-- / ..."));
+        assert!(is_meaningful("A"));
+        assert!(is_meaningful("42"));
+        assert!(is_meaningful("Jalan Sudirman"));
+    }
+
     #[test]
     fn test_chunk_id_deterministic() {
         let generator = ChunkGenerator::default();
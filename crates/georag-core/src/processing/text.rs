@@ -0,0 +1,155 @@
+//! Bounded, grapheme-safe pieces of text
+//!
+//! Extracted text can be pathological: a machine-generated PDF may yield a
+//! single multi-megabyte line without whitespace. Tokens are capped in
+//! length and chunks in bytes, and every cut falls on a grapheme boundary,
+//! so neither a character nor an emoji sequence is split when there is any
+//! other choice.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Longest token, in bytes; longer runs without whitespace are split
+pub const MAX_TOKEN_BYTES: usize = 256;
+
+/// Longest chunk, in bytes, however few words it has
+pub const MAX_CHUNK_BYTES: usize = 8 * 1024;
+
+/// Whitespace-separated tokens of at most [`MAX_TOKEN_BYTES`] each
+///
+/// NUL characters separate tokens like whitespace, so none is kept.
+pub fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| c.is_whitespace() || c == '\0')
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| pieces(word, MAX_TOKEN_BYTES))
+}
+
+/// Consecutive pieces of `text` of at most `max_bytes` each
+fn pieces(text: &str, max_bytes: usize) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (piece, tail) = rest.split_at(truncate_bytes(rest, max_bytes).len());
+        rest = tail;
+        Some(piece)
+    })
+}
+
+/// Number of leading tokens that fit in `max_bytes` when joined with spaces
+///
+/// At least one, so a chunk is never empty.
+pub fn tokens_within(tokens: &[&str], max_bytes: usize) -> usize {
+    let mut bytes = 0;
+    let count = tokens
+        .iter()
+        .take_while(|token| {
+            bytes += token.len() + usize::from(bytes > 0);
+            bytes <= max_bytes
+        })
+        .count();
+    count.max(1).min(tokens.len())
+}
+
+/// Last grapheme boundary at or before byte `index`
+///
+/// The start of `text` counts as a boundary. Only the text up to `index`
+/// is segmented, so the cost does not grow with what follows.
+pub fn floor_grapheme_boundary(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    let floor = floor_char_boundary(text, index);
+    // The character after the cut decides whether the cut is a boundary
+    let window = floor + text[floor..].chars().next().map_or(0, char::len_utf8);
+    text[..window]
+        .grapheme_indices(true)
+        .map(|(start, _)| start)
+        .take_while(|&start| start <= floor)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Longest prefix of at most `max_bytes` ending on a grapheme boundary
+///
+/// A first grapheme longer than `max_bytes` (a letter with hundreds of
+/// combining marks) is cut between its characters instead, and a non-empty
+/// text keeps at least one character.
+pub fn truncate_bytes(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let end = match floor_grapheme_boundary(text, max_bytes) {
+        0 => floor_char_boundary(text, max_bytes),
+        end => end,
+    };
+    let end = match end {
+        0 => text.chars().next().map_or(0, char::len_utf8),
+        end => end,
+    };
+    &text[..end]
+}
+
+/// Longest prefix of at most `max_chars` characters ending on a grapheme boundary
+///
+/// For excerpts and labels; like [`truncate_bytes`], a first grapheme longer
+/// than the limit is cut between its characters.
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => truncate_bytes(text, index),
+        None => text,
+    }
+}
+
+/// Last character boundary at or before byte `index`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    (0..=index.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_runs_are_split_into_bounded_tokens() {
+        let text = format!("short {} end", "x".repeat(MAX_TOKEN_BYTES * 2 + 10));
+        let tokens: Vec<&str> = tokens(&text).collect();
+        assert_eq!(tokens.len(), 5);
+        assert!(tokens.iter().all(|t| t.len() <= MAX_TOKEN_BYTES));
+        assert_eq!(tokens[3].len(), 10);
+    }
+
+    #[test]
+    fn test_cuts_keep_graphemes_whole() {
+        // A family emoji is one grapheme of 25 bytes
+        let family = "👨‍👩‍👧‍👦";
+        let text = family.repeat(3);
+        assert_eq!(truncate_bytes(&text, 60), family.repeat(2));
+        assert_eq!(truncate_chars("e\u{301}te\u{301}", 2), "e\u{301}");
+        assert_eq!(floor_grapheme_boundary(&text, 30), family.len());
+    }
+
+    #[test]
+    fn test_oversized_grapheme_is_cut_between_characters() {
+        let zalgo = format!("a{}", "\u{301}".repeat(200));
+        let cut = truncate_bytes(&zalgo, 9);
+        assert_eq!(cut, format!("a{}", "\u{301}".repeat(4)));
+        assert_eq!(truncate_bytes("日本", 1), "日");
+    }
+
+    #[test]
+    fn test_nul_separates_tokens() {
+        let tokens: Vec<&str> = tokens("a\0b \0\0 c").collect();
+        assert_eq!(tokens, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_tokens_within() {
+        assert_eq!(tokens_within(&["ab", "cd", "ef"], 5), 2);
+        assert_eq!(tokens_within(&["abcdef"], 3), 1);
+        assert_eq!(tokens_within(&[], 3), 0);
+    }
+}
@@ -0,0 +1,265 @@
+//! Token counts for sizing chunks to an embedder's input
+//!
+//! Word counts map poorly onto model tokens: Indonesian averages fewer
+//! tokens per word than English, and text without spaces has few words but
+//! many tokens. A [`Tokenizer`] counts and cuts text in the tokens of the
+//! embedder. The heuristic tokenizer needs no model files; with the
+//! `tokenizers` feature the model's own `tokenizer.json` can be loaded.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::{GeoragError, Result};
+use crate::llm::factory::EmbedderSpec;
+
+use super::text::truncate_chars;
+
+/// Characters per token assumed by [`HeuristicTokenizer`]
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens repeated between consecutive chunks when sizing in tokens
+pub const DEFAULT_OVERLAP_TOKENS: usize = 32;
+
+/// Counts and cuts text in the tokens of an embedding model
+pub trait Tokenizer: Send + Sync + fmt::Debug {
+    /// Name recorded with the index, e.g. `heuristic`
+    fn name(&self) -> String;
+
+    /// Number of tokens in `text`
+    fn count(&self, text: &str) -> usize;
+
+    /// Longest prefix of `text` with at most `max_tokens` tokens
+    ///
+    /// The cut falls on a grapheme boundary, and a non-empty text keeps at
+    /// least one character.
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str;
+}
+
+/// One token per [`CHARS_PER_TOKEN`] characters, rounded up
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> String {
+        "heuristic".to_string()
+    }
+
+    fn count(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(CHARS_PER_TOKEN)
+    }
+
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        truncate_chars(text, max_tokens.max(1) * CHARS_PER_TOKEN)
+    }
+}
+
+/// The tokenizer of a HuggingFace model, loaded from its `tokenizer.json`
+#[cfg(feature = "tokenizers")]
+pub struct HuggingFaceTokenizer {
+    model: String,
+    inner: tokenizers::Tokenizer,
+}
+
+#[cfg(feature = "tokenizers")]
+impl HuggingFaceTokenizer {
+    /// Load the tokenizer of `model` from a `tokenizer.json` file
+    pub fn from_file(path: &Path, model: impl Into<String>) -> Result<Self> {
+        let inner =
+            tokenizers::Tokenizer::from_file(path).map_err(|e| GeoragError::ConfigInvalid {
+                key: "tokenizer".to_string(),
+                reason: format!("Failed to load tokenizer from {}: {}", path.display(), e),
+            })?;
+        Ok(Self { model: model.into(), inner })
+    }
+
+    fn encode(&self, text: &str) -> Option<tokenizers::Encoding> {
+        self.inner.encode(text, false).ok()
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl fmt::Debug for HuggingFaceTokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HuggingFaceTokenizer").field("model", &self.model).finish()
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl Tokenizer for HuggingFaceTokenizer {
+    fn name(&self) -> String {
+        format!("huggingface:{}", self.model)
+    }
+
+    /// Text the tokenizer cannot encode is counted by the heuristic
+    fn count(&self, text: &str) -> usize {
+        match self.encode(text) {
+            Some(encoding) => encoding.len(),
+            None => HeuristicTokenizer.count(text),
+        }
+    }
+
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let Some(encoding) = self.encode(text) else {
+            return HeuristicTokenizer.truncate(text, max_tokens);
+        };
+        if encoding.len() <= max_tokens {
+            return text;
+        }
+        // Offsets are in bytes; the end of the last token kept is the cut
+        let end = encoding.get_offsets()[max_tokens.max(1) - 1].1;
+        super::text::truncate_bytes(text, end.max(1))
+    }
+}
+
+/// A parsed tokenizer setting
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TokenizerSpec {
+    /// `heuristic`: one token per four characters
+    #[default]
+    Heuristic,
+    /// `huggingface` or `huggingface:<path to tokenizer.json>`
+    ///
+    /// Without a path, the file is looked up in a directory per embedder
+    /// model, see [`create_tokenizer`].
+    HuggingFace { path: Option<PathBuf> },
+}
+
+impl TokenizerSpec {
+    /// Parse a tokenizer setting
+    pub fn parse(spec: &str) -> Result<Self> {
+        match spec.trim() {
+            "heuristic" => Ok(Self::Heuristic),
+            "huggingface" => Ok(Self::HuggingFace { path: None }),
+            spec => match spec.strip_prefix("huggingface:") {
+                Some(path) if !path.trim().is_empty() => {
+                    Ok(Self::HuggingFace { path: Some(PathBuf::from(path.trim())) })
+                }
+                _ => Err(GeoragError::ConfigInvalid {
+                    key: "tokenizer".to_string(),
+                    reason: format!(
+                        "Unknown tokenizer '{}'. Use heuristic, huggingface or huggingface:<path to tokenizer.json>",
+                        spec
+                    ),
+                }),
+            },
+        }
+    }
+}
+
+/// Create the tokenizer a setting names for an embedder
+///
+/// A HuggingFace tokenizer without a path is loaded from
+/// `<dir>/<model>/tokenizer.json`, where `model` is the embedder's model
+/// name, e.g. `nomic-embed-text`. It needs the `tokenizers` feature.
+pub fn create_tokenizer(
+    spec: &TokenizerSpec,
+    embedder: &str,
+    dir: &Path,
+) -> Result<Arc<dyn Tokenizer>> {
+    match spec {
+        TokenizerSpec::Heuristic => Ok(Arc::new(HeuristicTokenizer)),
+        TokenizerSpec::HuggingFace { path } => {
+            let model = match EmbedderSpec::parse(embedder)? {
+                EmbedderSpec::Ollama { model } => model,
+                EmbedderSpec::Mock { .. } => "mock".to_string(),
+            };
+            let path = path.clone().unwrap_or_else(|| dir.join(&model).join("tokenizer.json"));
+            load_huggingface(&path, model)
+        }
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+fn load_huggingface(path: &Path, model: String) -> Result<Arc<dyn Tokenizer>> {
+    Ok(Arc::new(HuggingFaceTokenizer::from_file(path, model)?))
+}
+
+#[cfg(not(feature = "tokenizers"))]
+fn load_huggingface(path: &Path, _model: String) -> Result<Arc<dyn Tokenizer>> {
+    Err(GeoragError::ConfigInvalid {
+        key: "tokenizer".to_string(),
+        reason: format!(
+            "Cannot load {}: this build has no HuggingFace tokenizer support. Rebuild with the `tokenizers` feature or use the heuristic tokenizer",
+            path.display()
+        ),
+    })
+}
+
+/// Token-based chunk sizing
+///
+/// Chunks hold as many words as fit in `max_chunk_tokens` and repeat the
+/// last words of the chunk before them up to `overlap_tokens`.
+#[derive(Debug, Clone)]
+pub struct TokenLimits {
+    pub tokenizer: Arc<dyn Tokenizer>,
+    pub max_chunk_tokens: usize,
+    pub overlap_tokens: usize,
+}
+
+impl TokenLimits {
+    pub fn new(
+        tokenizer: Arc<dyn Tokenizer>,
+        max_chunk_tokens: usize,
+        overlap_tokens: usize,
+    ) -> Result<Self> {
+        if max_chunk_tokens == 0 {
+            return Err(GeoragError::ConfigInvalid {
+                key: "max_chunk_tokens".to_string(),
+                reason: "max_chunk_tokens must be a positive integer".to_string(),
+            });
+        }
+        if overlap_tokens >= max_chunk_tokens {
+            return Err(GeoragError::ConfigInvalid {
+                key: "overlap_tokens".to_string(),
+                reason: format!(
+                    "overlap_tokens ({}) must be less than max_chunk_tokens ({})",
+                    overlap_tokens, max_chunk_tokens
+                ),
+            });
+        }
+        Ok(Self {
+            tokenizer,
+            max_chunk_tokens,
+            overlap_tokens,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_counts_characters() {
+        let tokenizer = HeuristicTokenizer;
+        assert_eq!(tokenizer.count(""), 0);
+        assert_eq!(tokenizer.count("abc"), 1);
+        assert_eq!(tokenizer.count("jalan raya"), 3);
+        // Characters, not bytes
+        assert_eq!(tokenizer.count("日本語の"), 1);
+        assert_eq!(tokenizer.truncate("jalan raya bogor", 2), "jalan ra");
+        assert_eq!(tokenizer.truncate("x", 0), "x");
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        assert_eq!(TokenizerSpec::parse("heuristic").unwrap(), TokenizerSpec::Heuristic);
+        assert_eq!(
+            TokenizerSpec::parse("huggingface:/models/tokenizer.json").unwrap(),
+            TokenizerSpec::HuggingFace {
+                path: Some(PathBuf::from("/models/tokenizer.json"))
+            }
+        );
+        assert!(TokenizerSpec::parse("tiktoken").is_err());
+        assert!(TokenizerSpec::parse("huggingface:").is_err());
+    }
+
+    #[test]
+    fn test_overlap_must_be_below_the_limit() {
+        let tokenizer: Arc<dyn Tokenizer> = Arc::new(HeuristicTokenizer);
+        assert!(TokenLimits::new(tokenizer.clone(), 128, 16).is_ok());
+        assert!(TokenLimits::new(tokenizer.clone(), 16, 16).is_err());
+        assert!(TokenLimits::new(tokenizer, 0, 0).is_err());
+    }
+}
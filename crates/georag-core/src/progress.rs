@@ -0,0 +1,261 @@
+//! Throughput of long-running phases
+//!
+//! A [`RateEstimator`] is fed the number of items a phase has completed
+//! (files ingested, features stored, chunks embedded) and estimates the
+//! current rate as a moving average over a time window, so a burst or a
+//! stall moves the estimate and the ETA without swinging them from one
+//! update to the next. A [`PhaseRate`] records a finished phase's average
+//! over its whole run, for comparing runs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Window the current rate is averaged over
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Moving average of items per second over a time window
+///
+/// Times are offsets from the start of the phase, so sequences can be
+/// replayed without a clock.
+#[derive(Debug, Clone)]
+pub struct RateEstimator {
+    window: Duration,
+    total: Option<u64>,
+    /// Items done by each offset, oldest first; starts with none done at zero
+    samples: VecDeque<(Duration, u64)>,
+}
+
+impl Default for RateEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_RATE_WINDOW)
+    }
+}
+
+impl RateEstimator {
+    /// Create an estimator averaging over `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            total: None,
+            samples: VecDeque::from([(Duration::ZERO, 0)]),
+        }
+    }
+
+    /// Set the number of items the phase will complete, for the ETA
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Items the phase will complete, if known
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Items done so far
+    pub fn done(&self) -> u64 {
+        self.last().1
+    }
+
+    /// Record that `done` items are complete `at` after the phase started
+    ///
+    /// Samples older than the window are dropped, keeping the last of them
+    /// as the baseline the window is measured from.
+    pub fn record(&mut self, at: Duration, done: u64) {
+        let (last_at, last_done) = self.last();
+        self.samples.push_back((at.max(last_at), done.max(last_done)));
+
+        let cutoff = at.saturating_sub(self.window);
+        while self.samples.len() > 2 && self.samples[1].0 <= cutoff {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Items per second over the window ending `now`
+    ///
+    /// Time passing without progress lowers the rate, down to zero once a
+    /// whole window has gone by without any.
+    pub fn rate(&self, now: Duration) -> f64 {
+        let cutoff = now.saturating_sub(self.window);
+        let (base_at, base_done) = self
+            .samples
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= cutoff)
+            .copied()
+            .unwrap_or(self.samples[0]);
+        let (_, done) = self.last();
+
+        let span = now.saturating_sub(base_at).as_secs_f64();
+        if span > 0.0 {
+            (done - base_done) as f64 / span
+        } else {
+            0.0
+        }
+    }
+
+    /// Time left at the current rate, if the total is known and items are moving
+    pub fn eta(&self, now: Duration) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.done());
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+        let rate = self.rate(now);
+        (rate > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    /// Average over the whole phase up to the last recorded sample
+    pub fn summary(&self, phase: impl Into<String>) -> PhaseRate {
+        let (at, done) = self.last();
+        PhaseRate::new(phase, done as usize, at)
+    }
+
+    fn last(&self) -> (Duration, u64) {
+        *self.samples.back().expect("the estimator starts with a sample")
+    }
+}
+
+/// Average throughput of a finished phase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseRate {
+    /// Phase name, e.g. `files`, `features_stored` or `embeddings`
+    pub phase: String,
+
+    /// Items the phase completed
+    pub items: usize,
+
+    /// How long the phase ran
+    pub elapsed_secs: f64,
+
+    /// Items per second over the whole phase
+    pub per_second: f64,
+}
+
+impl PhaseRate {
+    /// Average of `items` completed in `elapsed`
+    pub fn new(phase: impl Into<String>, items: usize, elapsed: Duration) -> Self {
+        let seconds = elapsed.as_secs_f64();
+        Self {
+            phase: phase.into(),
+            items,
+            elapsed_secs: seconds,
+            per_second: if seconds > 0.0 {
+                items as f64 / seconds
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: f64) -> Duration {
+        Duration::from_secs_f64(s)
+    }
+
+    /// Feed `per_second` items every second from `from` to `to`
+    fn steady(estimator: &mut RateEstimator, from: u64, to: u64, per_second: u64) {
+        let mut done = estimator.done();
+        for second in from + 1..=to {
+            done += per_second;
+            estimator.record(Duration::from_secs(second), done);
+        }
+    }
+
+    #[test]
+    fn test_steady_rate_and_eta() {
+        let mut estimator = RateEstimator::new(secs(10.0)).with_total(1000);
+        steady(&mut estimator, 0, 20, 25);
+
+        assert!((estimator.rate(secs(20.0)) - 25.0).abs() < 1e-9);
+        // 500 done, 500 left at 25 per second
+        assert_eq!(estimator.eta(secs(20.0)), Some(secs(20.0)));
+
+        let summary = estimator.summary("embeddings");
+        assert_eq!(summary.items, 500);
+        assert_eq!(summary.elapsed_secs, 20.0);
+        assert!((summary.per_second - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stall_lowers_the_rate_to_zero() {
+        let mut estimator = RateEstimator::new(secs(10.0)).with_total(10_000);
+        steady(&mut estimator, 0, 20, 100);
+
+        // Half a window without progress halves the rate
+        assert!((estimator.rate(secs(25.0)) - 50.0).abs() < 1e-9);
+        let eta = estimator.eta(secs(25.0)).unwrap();
+        assert!((eta.as_secs_f64() - 160.0).abs() < 1e-6, "{:?}", eta);
+
+        // A whole window without progress leaves no rate and no ETA
+        assert_eq!(estimator.rate(secs(31.0)), 0.0);
+        assert_eq!(estimator.eta(secs(31.0)), None);
+
+        // Progress picks the rate up again
+        steady(&mut estimator, 40, 45, 100);
+        assert!(estimator.rate(secs(45.0)) > 0.0);
+        assert!(estimator.eta(secs(45.0)).is_some());
+
+        // The stall counts against the whole-phase average
+        let summary = estimator.summary("features_stored");
+        assert_eq!(summary.items, 2500);
+        assert!((summary.per_second - 2500.0 / 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_burst_is_smoothed_then_forgotten() {
+        let mut estimator = RateEstimator::new(secs(10.0));
+        steady(&mut estimator, 0, 30, 10);
+
+        // 1000 items in one second after 10 per second
+        estimator.record(secs(31.0), estimator.done() + 1000);
+        let rate = estimator.rate(secs(31.0));
+        assert!(rate > 10.0 && rate < 1000.0, "{}", rate);
+        assert!((rate - 1090.0 / 10.0).abs() < 1e-9);
+
+        // Once the burst leaves the window the steady rate is back
+        steady(&mut estimator, 31, 45, 10);
+        assert!((estimator.rate(secs(45.0)) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_irregular_updates_keep_a_baseline() {
+        let mut estimator = RateEstimator::new(secs(10.0));
+        estimator.record(secs(2.0), 40);
+        estimator.record(secs(30.0), 600);
+
+        // Only one sample in the last window: measured from the one before it
+        assert!((estimator.rate(secs(30.0)) - 560.0 / 28.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_eta_without_total_or_at_completion() {
+        let mut estimator = RateEstimator::new(secs(10.0));
+        steady(&mut estimator, 0, 5, 3);
+        assert_eq!(estimator.eta(secs(5.0)), None);
+
+        let mut estimator = RateEstimator::new(secs(10.0)).with_total(15);
+        steady(&mut estimator, 0, 5, 3);
+        assert_eq!(estimator.eta(secs(5.0)), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_counts_never_go_backwards() {
+        let mut estimator = RateEstimator::new(secs(10.0));
+        estimator.record(secs(4.0), 40);
+        estimator.record(secs(3.0), 10);
+        assert_eq!(estimator.done(), 40);
+        assert!((estimator.rate(secs(4.0)) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_empty_phase_summary() {
+        let summary = RateEstimator::default().summary("files");
+        assert_eq!(summary.items, 0);
+        assert_eq!(summary.per_second, 0.0);
+    }
+}
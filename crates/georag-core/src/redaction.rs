@@ -0,0 +1,407 @@
+//! Redaction of sensitive fields in query outputs
+//!
+//! Rules are configured per workspace in the `[redaction]` table of the
+//! workspace config file, which is also the `redaction` setting stored for
+//! the workspace. Stored data is never modified; redaction is applied only
+//! when responses are assembled, by [`Redactor::redact_response`] for JSON.
+
+use crate::error::{GeoragError, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Marker that replaces redacted values
+pub const REDACTED_MARKER: &str = "[REDACTED]";
+
+/// Redaction rules for a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Property keys whose values are masked in outputs (case-insensitive)
+    #[serde(default)]
+    pub properties: Vec<String>,
+
+    /// Regex patterns masked in excerpts and generated answers
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// Wrapper for reading the `[redaction]` table from a config file
+#[derive(Debug, Default, Deserialize)]
+struct RedactionFile {
+    #[serde(default)]
+    redaction: RedactionConfig,
+}
+
+impl RedactionConfig {
+    /// Check if no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Load the `[redaction]` table from a TOML config file (empty if absent)
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        let file: RedactionFile =
+            toml::from_str(&content).map_err(|e| GeoragError::ConfigInvalid {
+                key: "redaction".to_string(),
+                reason: format!("Failed to parse TOML: {}", e),
+            })?;
+
+        Ok(file.redaction)
+    }
+}
+
+/// Compiled redaction rules applied to response content
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    properties: HashSet<String>,
+    patterns: Vec<Regex>,
+    /// Rules the redactor was compiled from
+    rules: RedactionConfig,
+}
+
+impl Redactor {
+    /// Compile redaction rules
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|p| {
+                Regex::new(p).map_err(|e| GeoragError::ConfigInvalid {
+                    key: "redaction.patterns".to_string(),
+                    reason: format!("Invalid pattern '{}': {}", p, e),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            properties: config.properties.iter().map(|k| k.to_lowercase()).collect(),
+            patterns,
+            rules: config.clone(),
+        })
+    }
+
+    /// Rules the redactor was compiled from
+    pub fn rules(&self) -> &RedactionConfig {
+        &self.rules
+    }
+
+    /// Check if the redactor has no rules
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Check if a property key is redacted
+    pub fn is_redacted_property(&self, key: &str) -> bool {
+        self.properties.contains(&key.to_lowercase())
+    }
+
+    /// Mask all pattern matches in a piece of text
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for pattern in &self.patterns {
+            redacted = pattern.replace_all(&redacted, REDACTED_MARKER).into_owned();
+        }
+        redacted
+    }
+
+    /// Mask redacted keys and pattern matches in a JSON property object
+    pub fn redact_json_properties(&self, properties: &mut serde_json::Map<String, Value>) {
+        for (key, value) in properties.iter_mut() {
+            self.redact_value(key, value);
+        }
+    }
+
+    /// Mask redacted keys and pattern matches in a feature property map
+    pub fn redact_properties(&self, properties: &mut HashMap<String, Value>) {
+        for (key, value) in properties.iter_mut() {
+            self.redact_value(key, value);
+        }
+    }
+
+    /// Mask a JSON response body
+    ///
+    /// The one pass API responses go through, so it reads the shapes that
+    /// carry stored values rather than any one response type:
+    ///
+    /// - values of redacted keys in `properties` objects (features) become the marker
+    /// - redacted keys listed in `property_keys` (dataset previews) become the marker
+    /// - the `examples` of redacted keys in JSON Schema `properties` (dataset
+    ///   schemas) become a single marker, their types are kept
+    /// - pattern matches are masked in every other string
+    pub fn redact_response(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.redact_text(text),
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_response(item);
+                }
+            }
+            Value::Object(object) => {
+                for (key, member) in object.iter_mut() {
+                    match (key.as_str(), member) {
+                        ("properties", Value::Object(properties)) => {
+                            for (key, value) in properties.iter_mut() {
+                                if !self.is_redacted_property(key) {
+                                    self.redact_response(value);
+                                } else if let Some(examples) = value
+                                    .as_object_mut()
+                                    .and_then(|schema| schema.get_mut("examples"))
+                                {
+                                    *examples = Value::Array(vec![Value::String(
+                                        REDACTED_MARKER.to_string(),
+                                    )]);
+                                } else {
+                                    *value = Value::String(REDACTED_MARKER.to_string());
+                                }
+                            }
+                        }
+                        ("property_keys", Value::Array(keys)) => {
+                            for key in keys {
+                                if key.as_str().is_some_and(|k| self.is_redacted_property(k)) {
+                                    *key = Value::String(REDACTED_MARKER.to_string());
+                                }
+                            }
+                        }
+                        (_, member) => self.redact_response(member),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Rules masking everything either redactor masks
+    pub fn union(&self, other: &Redactor) -> Result<Self> {
+        let mut rules = self.rules.clone();
+        for key in &other.rules.properties {
+            if !self.is_redacted_property(key) {
+                rules.properties.push(key.clone());
+            }
+        }
+        for pattern in &other.rules.patterns {
+            if !rules.patterns.contains(pattern) {
+                rules.patterns.push(pattern.clone());
+            }
+        }
+        Self::new(&rules)
+    }
+
+    fn redact_value(&self, key: &str, value: &mut Value) {
+        if self.is_redacted_property(key) {
+            *value = Value::String(REDACTED_MARKER.to_string());
+        } else if let Value::String(text) = value {
+            *text = self.redact_text(text);
+        }
+    }
+}
+
+/// Audit log entry recorded when redaction rules change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub event: String,
+    pub rules: RedactionConfig,
+}
+
+const RULES_CHANGED_EVENT: &str = "redaction_rules_changed";
+
+/// Append an audit entry if the rules differ from the last recorded ones
+///
+/// The audit log is a JSON Lines file. Returns true if an entry was written.
+pub fn record_rules_change<P: AsRef<Path>>(audit_log: P, config: &RedactionConfig) -> Result<bool> {
+    let audit_log = audit_log.as_ref();
+
+    let last_rules = if audit_log.exists() {
+        fs::read_to_string(audit_log)?
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<RedactionAuditEntry>(line).ok())
+            .find(|entry| entry.event == RULES_CHANGED_EVENT)
+            .map(|entry| entry.rules)
+    } else {
+        None
+    };
+
+    let unchanged = match &last_rules {
+        Some(rules) => rules == config,
+        None => config.is_empty(),
+    };
+    if unchanged {
+        return Ok(false);
+    }
+
+    let entry = RedactionAuditEntry {
+        timestamp: Utc::now(),
+        event: RULES_CHANGED_EVENT.to_string(),
+        rules: config.clone(),
+    };
+    let line =
+        serde_json::to_string(&entry).map_err(|e| GeoragError::Serialization(e.to_string()))?;
+
+    if let Some(parent) = audit_log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(audit_log)?;
+    writeln!(file, "{}", line)?;
+
+    tracing::info!(
+        properties = config.properties.len(),
+        patterns = config.patterns.len(),
+        "Redaction rules changed"
+    );
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn phone_config() -> RedactionConfig {
+        RedactionConfig {
+            properties: vec!["owner_name".to_string()],
+            patterns: vec![r"\+?\d{3}[- ]\d{3}[- ]\d{4}".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_redact_text_masks_patterns() {
+        let redactor = Redactor::new(&phone_config()).unwrap();
+        let text = "Call 555-123-4567 for access";
+        assert_eq!(redactor.redact_text(text), "Call [REDACTED] for access");
+    }
+
+    #[test]
+    fn test_redact_properties_uses_marker() {
+        let redactor = Redactor::new(&phone_config()).unwrap();
+        let mut props = serde_json::Map::new();
+        props.insert("Owner_Name".to_string(), Value::from("Jane Doe"));
+        props.insert("note".to_string(), Value::from("phone 555 123 4567"));
+        props.insert("area".to_string(), Value::from(120.5));
+
+        redactor.redact_json_properties(&mut props);
+
+        assert_eq!(props["Owner_Name"], Value::from(REDACTED_MARKER));
+        assert_eq!(props["note"], Value::from("phone [REDACTED]"));
+        assert_eq!(props["area"], Value::from(120.5));
+    }
+
+    #[test]
+    fn test_invalid_pattern_rejected() {
+        let config = RedactionConfig {
+            properties: vec![],
+            patterns: vec!["(unclosed".to_string()],
+        };
+        assert!(Redactor::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_empty_redactor_is_noop() {
+        let redactor = Redactor::default();
+        assert!(redactor.is_empty());
+        assert_eq!(redactor.redact_text("555-123-4567"), "555-123-4567");
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "crs = 4326\n\n[redaction]\nproperties = [\"owner\"]\npatterns = [\"\\\\d+\"]\n",
+        )
+        .unwrap();
+
+        let config = RedactionConfig::load_from_file(&path).unwrap();
+        assert_eq!(config.properties, vec!["owner"]);
+        assert_eq!(config.patterns, vec![r"\d+"]);
+
+        let missing = RedactionConfig::load_from_file(dir.path().join("missing.toml")).unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_record_rules_change_only_on_change() {
+        let dir = TempDir::new().unwrap();
+        let audit = dir.path().join("audit.log");
+
+        // No rules and no history: nothing to record
+        assert!(!record_rules_change(&audit, &RedactionConfig::default()).unwrap());
+
+        let config = phone_config();
+        assert!(record_rules_change(&audit, &config).unwrap());
+        assert!(!record_rules_change(&audit, &config).unwrap());
+
+        // Removing all rules is also a change
+        assert!(record_rules_change(&audit, &RedactionConfig::default()).unwrap());
+
+        let lines = fs::read_to_string(&audit).unwrap().lines().count();
+        assert_eq!(lines, 2);
+    }
+
+    #[test]
+    fn test_redact_response_masks_the_shapes_that_carry_stored_values() {
+        let redactor = Redactor::new(&phone_config()).unwrap();
+        let mut response = serde_json::json!({
+            "name": "parcels",
+            "answer": "Owner reachable at 555-123-4567",
+            "features": [{
+                "type": "Feature",
+                "properties": { "owner_name": "Jane Doe", "note": "tel 555 123 4567", "lots": 3 }
+            }],
+            "preview": { "kind": "vector", "property_keys": ["owner_name", "lots"] },
+            "schema": {
+                "properties": {
+                    "OWNER_NAME": { "type": "string", "examples": ["Jane Doe", "John Roe"] },
+                    "note": { "type": "string", "examples": ["tel 555 123 4567"] }
+                }
+            }
+        });
+
+        redactor.redact_response(&mut response);
+
+        assert_eq!(response["name"], "parcels");
+        assert_eq!(response["answer"], "Owner reachable at [REDACTED]");
+        let properties = &response["features"][0]["properties"];
+        assert_eq!(properties["owner_name"], REDACTED_MARKER);
+        assert_eq!(properties["note"], "tel [REDACTED]");
+        assert_eq!(properties["lots"], 3);
+        assert_eq!(
+            response["preview"]["property_keys"],
+            serde_json::json!([REDACTED_MARKER, "lots"])
+        );
+        let schema = &response["schema"]["properties"];
+        assert_eq!(schema["OWNER_NAME"]["type"], "string");
+        assert_eq!(schema["OWNER_NAME"]["examples"], serde_json::json!([REDACTED_MARKER]));
+        assert_eq!(schema["note"]["examples"], serde_json::json!(["tel [REDACTED]"]));
+    }
+
+    #[test]
+    fn test_union_masks_what_either_masks() {
+        let phone = Redactor::new(&phone_config()).unwrap();
+        let email = Redactor::new(&RedactionConfig {
+            properties: vec!["OWNER_NAME".to_string(), "email".to_string()],
+            patterns: vec![r"\S+@\S+".to_string()],
+        })
+        .unwrap();
+
+        let both = phone.union(&email).unwrap();
+        assert_eq!(both.rules().properties, vec!["owner_name", "email"]);
+        assert_eq!(
+            both.redact_text("555-123-4567 or jane@example.com"),
+            "[REDACTED] or [REDACTED]"
+        );
+    }
+}
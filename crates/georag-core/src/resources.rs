@@ -0,0 +1,212 @@
+//! Instance-wide limits on concurrent expensive operations
+//!
+//! The API holds a slot of a [`ResourceKind`] while it ingests, builds,
+//! embeds a batch or exports; the CLI takes the same slots from a shared
+//! backend so both respect one set of [`ResourceLimits`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::env;
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::Result;
+
+/// Default number of concurrent ingests
+pub const DEFAULT_MAX_INGESTS: usize = 4;
+
+/// Default number of concurrent index builds
+pub const DEFAULT_MAX_BUILDS: usize = 1;
+
+/// Default number of concurrent embedding batches
+pub const DEFAULT_MAX_EMBEDDING_BATCHES: usize = 4;
+
+/// Default number of concurrent large exports
+pub const DEFAULT_MAX_EXPORTS: usize = 2;
+
+/// Default number of requests waiting for each kind of slot
+pub const DEFAULT_MAX_QUEUED: usize = 16;
+
+/// Default wait suggested to callers turned away with a full queue
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Default size from which a dataset download counts as a large export
+pub const DEFAULT_LARGE_EXPORT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// An expensive operation limited instance-wide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Reading and storing an uploaded or downloaded dataset
+    Ingest,
+    /// Rebuilding an index
+    Build,
+    /// One batch of chunks sent to the embedder
+    EmbeddingBatch,
+    /// Copying a workspace or downloading a large original file
+    Export,
+}
+
+impl ResourceKind {
+    /// Every kind, in the order metrics list them
+    pub const ALL: [ResourceKind; 4] =
+        [Self::Ingest, Self::Build, Self::EmbeddingBatch, Self::Export];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ingest => "ingest",
+            Self::Build => "build",
+            Self::EmbeddingBatch => "embedding_batch",
+            Self::Export => "export",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How many operations of each kind may run at once, and how many may wait
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceLimits {
+    pub ingests: usize,
+    pub builds: usize,
+    pub embedding_batches: usize,
+    pub exports: usize,
+    /// Requests waiting for a slot of one kind; further requests are turned away
+    pub max_queued: usize,
+    /// Wait suggested to callers turned away
+    pub retry_after: Duration,
+    /// Size from which a dataset download counts as a large export
+    pub large_export_bytes: u64,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            ingests: DEFAULT_MAX_INGESTS,
+            builds: DEFAULT_MAX_BUILDS,
+            embedding_batches: DEFAULT_MAX_EMBEDDING_BATCHES,
+            exports: DEFAULT_MAX_EXPORTS,
+            max_queued: DEFAULT_MAX_QUEUED,
+            retry_after: DEFAULT_RETRY_AFTER,
+            large_export_bytes: DEFAULT_LARGE_EXPORT_BYTES,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Number of concurrent operations of `kind`, at least one
+    pub fn limit(&self, kind: ResourceKind) -> usize {
+        let limit = match kind {
+            ResourceKind::Ingest => self.ingests,
+            ResourceKind::Build => self.builds,
+            ResourceKind::EmbeddingBatch => self.embedding_batches,
+            ResourceKind::Export => self.exports,
+        };
+        limit.max(1)
+    }
+
+    /// Set the number of concurrent operations of `kind`
+    pub fn with_limit(mut self, kind: ResourceKind, limit: usize) -> Self {
+        let slot = match kind {
+            ResourceKind::Ingest => &mut self.ingests,
+            ResourceKind::Build => &mut self.builds,
+            ResourceKind::EmbeddingBatch => &mut self.embedding_batches,
+            ResourceKind::Export => &mut self.exports,
+        };
+        *slot = limit;
+        self
+    }
+
+    /// Set how many requests may wait for a slot of one kind
+    pub fn with_max_queued(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued;
+        self
+    }
+
+    /// Set the wait suggested to callers turned away
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// Set the size from which a dataset download counts as a large export
+    pub fn with_large_export_bytes(mut self, bytes: u64) -> Self {
+        self.large_export_bytes = bytes;
+        self
+    }
+
+    /// Limits set by the `GEORAG_MAX_CONCURRENT_*` variables, the rest defaults
+    ///
+    /// The CLI reads the same variables as the API server, so both take slots
+    /// of the shared backend against the same limits.
+    pub fn from_env() -> Self {
+        let mut limits = Self::default();
+        for kind in ResourceKind::ALL {
+            let var = limit_variable(kind);
+            if let Ok(value) = env::var(var) {
+                match parse_resource_limit(&value) {
+                    Ok(limit) => limits = limits.with_limit(kind, limit),
+                    Err(_) => tracing::warn!(
+                        "Invalid {} value '{}': expected a positive integer",
+                        var,
+                        value
+                    ),
+                }
+            }
+        }
+        limits
+    }
+}
+
+/// Environment variable setting the number of concurrent operations of `kind`
+pub fn limit_variable(kind: ResourceKind) -> &'static str {
+    match kind {
+        ResourceKind::Ingest => "GEORAG_MAX_CONCURRENT_INGESTS",
+        ResourceKind::Build => "GEORAG_MAX_CONCURRENT_BUILDS",
+        ResourceKind::EmbeddingBatch => "GEORAG_MAX_CONCURRENT_EMBEDDING_BATCHES",
+        ResourceKind::Export => "GEORAG_MAX_CONCURRENT_EXPORTS",
+    }
+}
+
+/// Parse a number of concurrent operations, which must be positive
+pub fn parse_resource_limit(value: &str) -> std::result::Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(limit) if limit > 0 => Ok(limit),
+        _ => Err(format!("expected a positive integer, got '{}'", value)),
+    }
+}
+
+/// A slot held until dropped
+pub struct ResourceGuard {
+    _held: Option<Box<dyn Any + Send>>,
+}
+
+impl ResourceGuard {
+    /// Guard releasing its slot when `held` is dropped
+    pub fn new(held: impl Any + Send) -> Self {
+        Self { _held: Some(Box::new(held)) }
+    }
+
+    /// Guard of an operation that needs no slot
+    pub fn unlimited() -> Self {
+        Self { _held: None }
+    }
+}
+
+impl fmt::Debug for ResourceGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResourceGuard").finish_non_exhaustive()
+    }
+}
+
+/// Grants slots of the instance-wide limits, waiting for one to free up
+#[async_trait]
+pub trait ResourceGate: Send + Sync {
+    /// Wait for a slot of `kind`, held until the guard is dropped
+    async fn acquire(&self, kind: ResourceKind) -> Result<ResourceGuard>;
+}
@@ -0,0 +1,157 @@
+//! Chunk offsets into the normalized document text
+//!
+//! Every chunk's content must be the slice of the normalized text at
+//! `[offset, offset + length)`, counted in characters. The fixture mixes
+//! ASCII with accented Latin, Japanese, Arabic and emoji, so byte and
+//! character offsets diverge from the first paragraph on.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::processing::tokenizer::{HeuristicTokenizer, TokenLimits};
+use georag_core::processing::{chunk_text, ChunkConfig};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const DOCUMENT: &str = "Laporan pelabuhan — Tanjung Priok, édition révisée.\n\n\
+    The harbour café opens at dawn when fishing boats unload their catch.\n\n\
+    東京都千代田区丸の内一丁目は東京駅の西側に位置するオフィス街です。\x0C\
+    يقع المتحف الوطني في وسط المدينة بالقرب من الميناء القديم.\n\n\
+    Pantai 👨‍👩‍👧‍👦🇮🇩🏝️ along the quay, with stalls selling crème brûlée and ñame.";
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "report".to_string(),
+        path: PathBuf::from("report.pdf"),
+        geometry_type: GeometryType::Point,
+        feature_count: 1,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "PDF".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(text: &str) -> Feature {
+    Feature {
+        id: FeatureId(1),
+        geometry: Some(Geometry::point(106.8, -6.1)),
+        properties: HashMap::from([("content".to_string(), serde_json::json!(text))]),
+        crs: 4326,
+        z: None,
+    }
+}
+
+/// The characters of `text` at `[offset, offset + length)`
+fn slice(text: &str, offset: usize, length: usize) -> String {
+    text.chars().skip(offset).take(length).collect()
+}
+
+/// Chunk `text` with `generator`, checking every chunk against the normalized text
+fn check_generator(generator: &ChunkGenerator, text: &str) -> usize {
+    let normalized = generator.normalized_text(text);
+    let chunks = generator.generate_chunks(&dataset(), &[feature(text)]);
+    for chunk in &chunks {
+        let source = &chunk.source;
+        assert_eq!(source.length, chunk.content.chars().count());
+        assert_eq!(chunk.content, slice(&normalized, source.offset, source.length));
+    }
+    chunks.len()
+}
+
+#[test]
+fn test_word_chunks_are_slices_of_the_normalized_text() {
+    let text = DOCUMENT.repeat(5);
+    for (min, max, overlap) in [(1, 1, 0), (4, 8, 3), (10, 40, 5), (50, 500, 50)] {
+        let generator = ChunkGenerator::new(min, max, overlap).unwrap();
+        assert!(check_generator(&generator, &text) > 0);
+    }
+
+    // Offsets count characters, not bytes
+    let generator = ChunkGenerator::new(4, 8, 3).unwrap();
+    let chunks = generator.generate_chunks(&dataset(), &[feature(DOCUMENT)]);
+    let normalized = generator.normalized_text(DOCUMENT);
+    let second = &chunks[1];
+    let byte_offset = normalized.find(second.content.as_str()).unwrap();
+    assert_eq!(second.source.offset, normalized[..byte_offset].chars().count());
+    assert!(second.source.offset < byte_offset);
+}
+
+#[test]
+fn test_token_chunks_are_slices_of_the_normalized_text() {
+    let text = DOCUMENT.repeat(5);
+    for (max_tokens, overlap) in [(1, 0), (16, 4), (128, 32)] {
+        let limits = TokenLimits::new(Arc::new(HeuristicTokenizer), max_tokens, overlap).unwrap();
+        let generator = ChunkGenerator::default().with_token_limits(limits);
+        assert!(check_generator(&generator, &text) > 0);
+    }
+}
+
+#[test]
+fn test_character_chunks_are_slices_of_the_text() {
+    let text = DOCUMENT.repeat(5);
+    for (min_size, max_size, overlap) in [(10, 30, 5), (50, 200, 40)] {
+        let config = ChunkConfig { min_size, max_size, overlap };
+        for chunk in chunk_text(&text, &config, "report.txt").unwrap() {
+            let source = &chunk.source;
+            assert_eq!(chunk.content, slice(&text, source.offset, source.length));
+        }
+    }
+}
+
+#[cfg(feature = "format-pdf")]
+#[test]
+fn test_pdf_chunks_are_slices_of_the_normalized_text() {
+    let reader = georag_core::formats::pdf::PdfReader;
+    let text = DOCUMENT.repeat(5);
+    let normalized = reader.normalize_text(&text);
+    for (chunk_size, overlap) in [(5, 0), (10, 2), (40, 10), (500, 50)] {
+        let chunks = reader.chunk_text(&text, chunk_size, overlap);
+        assert!(!chunks.is_empty());
+        for chunk in &chunks {
+            assert_eq!(chunk.length, chunk.text.chars().count());
+            assert_eq!(chunk.text, slice(&normalized, chunk.start_offset, chunk.length));
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_chunks_are_slices_of_the_normalized_text(
+        text in "\\PC{0,600}",
+        max in 1usize..40,
+        overlap in 0usize..40,
+    ) {
+        let generator = ChunkGenerator::new(1, max, overlap % max).unwrap();
+        check_generator(&generator, &text);
+
+        #[cfg(feature = "format-pdf")]
+        {
+            let reader = georag_core::formats::pdf::PdfReader;
+            let normalized = reader.normalize_text(&text);
+            for chunk in reader.chunk_text(&text, max, overlap) {
+                prop_assert_eq!(&chunk.text, &slice(&normalized, chunk.start_offset, chunk.length));
+            }
+        }
+    }
+}
@@ -143,6 +143,10 @@ distance_unit = "Kilometers"
         distance_unit: Some(DistanceUnit::Feet),
         geometry_validity: None,
         embedder: Some("ollama:cli-model".to_string()),
+        min_score: None,
+        max_filter_vertices: None,
+        simplify_filters: None,
+        auto_pull: None,
     };
 
     config.update_from_cli(cli_overrides);
@@ -70,6 +70,7 @@ async fn test_geojson_without_crs_defaults_to_4326() {
     assert_eq!(result.crs, 4326, "Should default to EPSG:4326 when CRS not specified");
 }
 
+#[cfg(feature = "format-gpx")]
 #[tokio::test]
 async fn test_gpx_always_uses_wgs84() {
     let reader = gpx::GpxReader;
@@ -89,6 +90,7 @@ async fn test_gpx_always_uses_wgs84() {
     assert_eq!(result.crs, 4326, "GPX always uses EPSG:4326 (WGS84) per specification");
 }
 
+#[cfg(feature = "format-kml")]
 #[tokio::test]
 async fn test_kml_always_uses_wgs84() {
     let reader = kml::KmlReader;
@@ -113,6 +115,7 @@ async fn test_kml_always_uses_wgs84() {
     assert_eq!(result.crs, 4326, "KML always uses EPSG:4326 (WGS84) per specification");
 }
 
+#[cfg(feature = "format-pdf")]
 #[tokio::test]
 async fn test_pdf_defaults_to_wgs84() {
     let reader = pdf::PdfReader;
@@ -128,6 +131,7 @@ async fn test_pdf_defaults_to_wgs84() {
     // This is verified in the PDF reader implementation
 }
 
+#[cfg(feature = "format-docx")]
 #[tokio::test]
 async fn test_docx_defaults_to_wgs84() {
     let reader = docx::DocxReader;
@@ -143,6 +147,7 @@ async fn test_docx_defaults_to_wgs84() {
     // This is verified in the DOCX reader implementation
 }
 
+#[cfg(feature = "format-shapefile")]
 #[tokio::test]
 async fn test_shapefile_without_prj_defaults_to_4326() {
     // This test verifies that Shapefile reader defaults to EPSG:4326
@@ -168,7 +173,7 @@ fn test_all_formats_have_crs_handling() {
 
     let formats = vec![
         ("GeoJSON", "Extracts from CRS field or defaults to 4326"),
-        ("Shapefile", "Extracts from .prj file or defaults to 4326"),
+        ("Shapefile", "Extracts from .prj file, defaults to 4326 without one"),
         ("GPX", "Always uses 4326 (WGS84) per specification"),
         ("KML", "Always uses 4326 (WGS84) per specification"),
         ("PDF", "Defaults to 4326 for documents"),
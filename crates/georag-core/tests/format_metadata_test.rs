@@ -23,8 +23,15 @@ fn test_format_metadata_serialization() {
             paragraph_count: None,
             extraction_method: None,
             spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
         },
         added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
     };
 
     // Test serialization
@@ -51,6 +58,10 @@ fn test_format_metadata_with_all_fields() {
         paragraph_count: Some(50),
         extraction_method: Some("GDAL".to_string()),
         spatial_association: None,
+        axis_order: None,
+        source: None,
+        preview: None,
+        remote: None,
     };
 
     // Test serialization
@@ -77,6 +88,10 @@ fn test_format_metadata_document_format() {
         paragraph_count: Some(150),
         extraction_method: Some("pdf-extract".to_string()),
         spatial_association: None,
+        axis_order: None,
+        source: None,
+        preview: None,
+        remote: None,
     };
 
     // Test serialization
@@ -101,6 +116,10 @@ fn test_format_metadata_docx_format() {
         paragraph_count: Some(42),
         extraction_method: Some("docx-rs".to_string()),
         spatial_association: None,
+        axis_order: None,
+        source: None,
+        preview: None,
+        remote: None,
     };
 
     // Test serialization
@@ -133,6 +152,10 @@ fn test_format_metadata_with_spatial_association() {
             associated_at: Utc::now(),
             description: Some("Manually associated with building location".to_string()),
         }),
+        axis_order: None,
+        source: None,
+        preview: None,
+        remote: None,
     };
 
     // Test serialization
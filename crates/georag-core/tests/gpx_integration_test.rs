@@ -1,4 +1,5 @@
 //! Integration tests for GPX format reader
+#![cfg(feature = "format-gpx")]
 
 use georag_core::formats::{gpx::GpxReader, FormatReader};
 use std::fs;
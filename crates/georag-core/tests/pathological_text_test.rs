@@ -0,0 +1,142 @@
+//! Pathological text through chunking and excerpts
+//!
+//! Machine-generated documents can extract to a single line of megabytes
+//! without whitespace, to nothing but emoji, or to mixed right-to-left text
+//! with NUL bytes. Chunking and excerpts must neither panic nor produce
+//! unbounded output for any of them.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    Dataset, DatasetId, DatasetPreview, Feature, FeatureId, Geometry, GeometryType, PreviewBuilder,
+    PREVIEW_SNIPPET_CHARS,
+};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::processing::text::{truncate_chars, MAX_CHUNK_BYTES, MAX_TOKEN_BYTES};
+use georag_core::processing::{chunk_text, ChunkConfig};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "scan".to_string(),
+        path: PathBuf::from("scan.pdf"),
+        geometry_type: GeometryType::Point,
+        feature_count: 1,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "PDF".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: Some(1),
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn document(text: &str) -> Feature {
+    Feature {
+        id: FeatureId(1),
+        geometry: Some(Geometry::point(106.8, -6.2)),
+        properties: HashMap::from([("content".to_string(), serde_json::json!(text))]),
+        crs: 4326,
+        z: None,
+    }
+}
+
+/// Run text through every chunker and the preview, checking each output is bounded
+fn check(text: &str) {
+    let chunks = ChunkGenerator::default().generate_chunks(&dataset(), &[document(text)]);
+    for chunk in &chunks {
+        assert!(chunk.content.len() <= MAX_CHUNK_BYTES, "chunk of {} bytes", chunk.content.len());
+        assert!(!chunk.content.contains('\0'));
+        assert!(chunk.content.split(' ').all(|token| token.len() <= MAX_TOKEN_BYTES));
+    }
+    // Overlap never multiplies the text more than twofold
+    let chunked: usize = chunks.iter().map(|c| c.content.len()).sum();
+    assert!(
+        chunked <= 2 * text.len() + MAX_CHUNK_BYTES,
+        "{} bytes from {}",
+        chunked,
+        text.len()
+    );
+
+    let config = ChunkConfig { min_size: 10, max_size: 100, overlap: 20 };
+    for chunk in chunk_text(text, &config, "scan.txt").unwrap() {
+        assert!(chunk.content.len() <= config.max_size);
+    }
+
+    #[cfg(feature = "format-pdf")]
+    {
+        let reader = georag_core::formats::pdf::PdfReader;
+        for chunk in reader.chunk_text(text, 500, 50) {
+            assert!(chunk.text.len() <= MAX_CHUNK_BYTES, "PDF chunk of {} bytes", chunk.text.len());
+        }
+    }
+
+    let mut preview = PreviewBuilder::new(Some(1), None);
+    preview.add(&document(text));
+    let DatasetPreview::Document(preview) = preview.finish() else {
+        panic!("expected a document preview");
+    };
+    assert!(preview.snippet.chars().count() <= PREVIEW_SNIPPET_CHARS);
+    assert!(truncate_chars(text, 40).chars().count() <= 40);
+}
+
+#[test]
+fn test_one_long_line_without_whitespace() {
+    let line = "ab3d".repeat(1024 * 1024);
+    let chunks = ChunkGenerator::default().generate_chunks(&dataset(), &[document(&line)]);
+    // Chunks end at the byte ceiling rather than after 500 tokens of 256 bytes
+    assert!(chunks.len() > line.len() / MAX_CHUNK_BYTES);
+    assert!(chunks.iter().all(|c| c.content.len() <= MAX_CHUNK_BYTES));
+    check(&"x".repeat(300_000));
+}
+
+#[test]
+fn test_pathological_inputs() {
+    let inputs = [
+        "👨‍👩‍👧‍👦🇮🇩🏳️‍🌈".repeat(2000),
+        "👨‍👩‍👧‍👦x".repeat(2000),
+        "مرحبا بالعالم hello שלום עולם ".repeat(500),
+        "\u{202E}reversed\u{202C}".repeat(3000),
+        format!("a{}", "\u{301}".repeat(5000)),
+        "null\0bytes\0\0between\0words".repeat(500),
+        "\0".repeat(1000),
+        "🇮🇩".repeat(5000),
+        " \n\t ".repeat(1000),
+        String::new(),
+    ];
+    for input in &inputs {
+        check(input);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_arbitrary_text_is_chunked_within_bounds(text in "\\PC{0,2000}") {
+        check(&text);
+    }
+
+    #[test]
+    fn prop_runs_of_one_character_are_chunked_within_bounds(
+        c in any::<char>(),
+        len in 0usize..20_000,
+    ) {
+        check(&c.to_string().repeat(len));
+    }
+}
@@ -0,0 +1,122 @@
+//! Coordinate precision through every serializer
+//!
+//! Survey data carries millimeter precision, so coordinates must come back
+//! bit for bit from every format they pass through: GeoJSON text, JSON
+//! values, WKB and the GeoJSON reader used at ingest.
+
+use georag_core::formats::geojson::GeoJsonReader;
+use georag_core::formats::FormatReader;
+use georag_core::geo::{from_wkb, to_wkb};
+use georag_core::models::{Feature, FeatureId, Geometry};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// Finite coordinates: survey-like lon/lat values and arbitrary magnitudes
+fn coordinate() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        -180.0..180.0f64,
+        -90.0..90.0f64,
+        prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO,
+    ]
+}
+
+fn coord() -> impl Strategy<Value = [f64; 2]> {
+    (coordinate(), coordinate()).prop_map(|(x, y)| [x, y])
+}
+
+fn line() -> impl Strategy<Value = Vec<[f64; 2]>> {
+    prop::collection::vec(coord(), 2..8)
+}
+
+fn rings() -> impl Strategy<Value = Vec<Vec<[f64; 2]>>> {
+    prop::collection::vec(line(), 1..3)
+}
+
+fn geometry() -> impl Strategy<Value = Geometry> {
+    prop_oneof![
+        coord().prop_map(|coordinates| Geometry::Point { coordinates }),
+        line().prop_map(|coordinates| Geometry::LineString { coordinates }),
+        rings().prop_map(|coordinates| Geometry::Polygon { coordinates }),
+        line().prop_map(|coordinates| Geometry::MultiPoint { coordinates }),
+        rings().prop_map(|coordinates| Geometry::MultiLineString { coordinates }),
+        prop::collection::vec(rings(), 1..3)
+            .prop_map(|coordinates| Geometry::MultiPolygon { coordinates }),
+    ]
+}
+
+/// Bit patterns of every coordinate, so 0.0 and -0.0 count as different
+fn bits(geometry: &Geometry) -> Vec<u64> {
+    let coords: Vec<[f64; 2]> = match geometry {
+        Geometry::Point { coordinates } => vec![*coordinates],
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.clone()
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.concat()
+        }
+        Geometry::MultiPolygon { coordinates } => coordinates.concat().concat(),
+    };
+    coords.iter().flat_map(|[x, y]| [x.to_bits(), y.to_bits()]).collect()
+}
+
+proptest! {
+    #[test]
+    fn geojson_text_round_trip(geometry in geometry()) {
+        let text = serde_json::to_string(&geometry).unwrap();
+        let parsed: Geometry = serde_json::from_str(&text).unwrap();
+        prop_assert_eq!(bits(&parsed), bits(&geometry));
+    }
+
+    #[test]
+    fn json_value_round_trip(geometry in geometry()) {
+        let parsed = Geometry::from_geojson(&geometry.to_geojson()).unwrap();
+        prop_assert_eq!(bits(&parsed), bits(&geometry));
+    }
+
+    #[test]
+    fn wkb_round_trip(geometry in geometry()) {
+        let parsed = from_wkb(&to_wkb(&geometry)).unwrap();
+        prop_assert_eq!(bits(&parsed), bits(&geometry));
+    }
+
+    #[test]
+    fn feature_json_round_trip(geometry in geometry()) {
+        let feature = Feature::with_geometry(FeatureId(1), geometry.clone(), HashMap::new(), 4326);
+        let text = serde_json::to_vec(&feature).unwrap();
+        let parsed: Feature = serde_json::from_slice(&text).unwrap();
+        prop_assert_eq!(bits(&parsed.geometry.unwrap()), bits(&geometry));
+    }
+}
+
+#[tokio::test]
+async fn test_geojson_reader_keeps_survey_precision() {
+    // RTK fixes written with more digits than an f64 holds, and shortest forms
+    let coordinates = [
+        [106.827_153_012_345_67, -6.175_392_123_456_789],
+        [106.827_153_1, -6.175_392_2],
+        [0.1, 0.2],
+        [-0.0, 1e-300],
+    ];
+    let text = format!(
+        r#"{{"type": "FeatureCollection", "features": [{}]}}"#,
+        coordinates
+            .iter()
+            .map(|c| format!(
+                r#"{{"type": "Feature", "properties": {{}}, "geometry": {{"type": "Point", "coordinates": [{:?}, {:?}]}}}}"#,
+                c[0], c[1]
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("rtk.geojson");
+    std::fs::write(&path, text).unwrap();
+    let dataset = GeoJsonReader.read(&path).await.unwrap();
+
+    for (feature, expected) in dataset.features.iter().zip(coordinates) {
+        let geometry = Geometry::from_geojson(feature.geometry.as_ref().unwrap()).unwrap();
+        assert_eq!(bits(&geometry), bits(&Geometry::point(expected[0], expected[1])));
+    }
+}
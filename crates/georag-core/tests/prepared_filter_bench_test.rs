@@ -0,0 +1,151 @@
+//! Benchmark test for prepared spatial filters
+//!
+//! Compares evaluating a 10k-vertex filter polygon the unprepared way
+//! (converting the filter and testing every edge for each candidate) against
+//! a `PreparedFilter` built once per query, and a 5k-vertex saved area
+//! queried repeatedly through a `FilterCache` against preparing it per query.
+//!
+//! Both paths must always agree and the cache must prepare an area once. The
+//! speedups are measured on wall-clock time, so those tests are ignored by
+//! default; run them on a quiet machine with
+//! `cargo test --release -p georag-core --test prepared_filter_bench_test -- --ignored`.
+
+use geo::algorithm::contains::Contains;
+use georag_core::geo::{evaluate_spatial_filter, to_geo_geometry, FilterCache, PreparedFilter};
+use georag_core::models::{Geometry, SpatialFilter, SpatialPredicate};
+use std::time::{Duration, Instant};
+
+const FILTER_VERTICES: usize = 10_000;
+const CANDIDATES: usize = 500;
+
+/// Star-shaped polygon with jagged edges, like a detailed administrative outline
+fn detailed_outline() -> Geometry {
+    outline(FILTER_VERTICES)
+}
+
+fn outline(vertices: usize) -> Geometry {
+    let mut ring: Vec<[f64; 2]> = (0..vertices)
+        .map(|i| {
+            let angle = i as f64 / vertices as f64 * std::f64::consts::TAU;
+            let radius = 1.0 + 0.05 * ((i % 13) as f64 / 13.0);
+            [10.0 + radius * angle.cos(), 50.0 + radius * angle.sin()]
+        })
+        .collect();
+    ring.push(ring[0]);
+    Geometry::polygon(vec![ring])
+}
+
+/// Candidate points spread over the filter's bounding box
+fn candidates() -> Vec<Geometry> {
+    (0..CANDIDATES)
+        .map(|i| {
+            let x = 8.9 + (i % 25) as f64 * 0.09;
+            let y = 48.9 + (i / 25) as f64 * 0.11;
+            Geometry::point(x, y)
+        })
+        .collect()
+}
+
+fn time<F: FnMut() -> usize>(mut f: F) -> (Duration, usize) {
+    let start = Instant::now();
+    let matches = f();
+    (start.elapsed(), matches)
+}
+
+#[test]
+fn test_prepared_filter_agrees_with_unprepared_evaluation() {
+    for vertices in [FILTER_VERTICES, 5_000] {
+        let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(outline(vertices));
+        let prepared = PreparedFilter::new(&filter);
+        let points = candidates();
+
+        let unprepared_matches =
+            points.iter().filter(|point| evaluate_spatial_filter(point, &filter)).count();
+        let prepared_matches = points.iter().filter(|point| prepared.evaluate(point)).count();
+        assert_eq!(prepared_matches, unprepared_matches, "{} vertices", vertices);
+        assert!(prepared_matches > 0 && prepared_matches < CANDIDATES);
+    }
+}
+
+#[test]
+#[ignore = "measures wall-clock time; run with --ignored in a release build"]
+fn test_prepared_filter_is_faster_on_detailed_polygon() {
+    let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(detailed_outline());
+    let points = candidates();
+
+    // Unprepared: the filter geometry is converted and fully scanned per candidate
+    let (unprepared_time, unprepared_matches) = time(|| {
+        points
+            .iter()
+            .filter(|point| {
+                let filter_geom = to_geo_geometry(filter.geometry.as_ref().unwrap());
+                filter_geom.contains(&to_geo_geometry(point))
+            })
+            .count()
+    });
+
+    // Prepared: built once, then only the edges near each point are visited
+    let (prepared_time, prepared_matches) = time(|| {
+        let prepared = PreparedFilter::new(&filter);
+        points.iter().filter(|point| prepared.evaluate(point)).count()
+    });
+
+    assert_eq!(prepared_matches, unprepared_matches, "both paths must agree");
+    assert!(prepared_matches > 0 && prepared_matches < CANDIDATES);
+
+    assert!(
+        prepared_time * 5 < unprepared_time,
+        "prepared filter should be at least 5x faster (unprepared {:?}, prepared {:?})",
+        unprepared_time,
+        prepared_time
+    );
+}
+
+#[test]
+#[ignore = "measures wall-clock time; run with --ignored in a release build"]
+fn test_prepared_filter_cuts_per_candidate_cost_on_5k_vertex_area() {
+    let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(outline(5_000));
+    let points = candidates();
+
+    // Unprepared: every candidate converts the filter and computes its bounding box again
+    let (unprepared_time, unprepared_matches) =
+        time(|| points.iter().filter(|point| evaluate_spatial_filter(point, &filter)).count());
+
+    let (prepared_time, prepared_matches) = time(|| {
+        let prepared = PreparedFilter::new(&filter);
+        points.iter().filter(|point| prepared.evaluate(point)).count()
+    });
+
+    assert_eq!(prepared_matches, unprepared_matches, "both paths must agree");
+    assert!(
+        prepared_time * 5 < unprepared_time,
+        "prepared filter should be at least 5x faster (unprepared {:?}, prepared {:?})",
+        unprepared_time,
+        prepared_time
+    );
+}
+
+#[test]
+fn test_cached_area_is_prepared_once_across_queries() {
+    const QUERIES: usize = 20;
+    let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(outline(5_000));
+    let points = candidates();
+    let cache = FilterCache::default();
+
+    let uncached_matches: usize = (0..QUERIES)
+        .map(|_| {
+            let prepared = PreparedFilter::new(&filter);
+            points.iter().filter(|point| prepared.evaluate(point)).count()
+        })
+        .sum();
+    let cached_matches: usize = (0..QUERIES)
+        .map(|_| {
+            let prepared = cache.get_or_prepare(&filter);
+            points.iter().filter(|point| prepared.evaluate(point)).count()
+        })
+        .sum();
+
+    assert_eq!(cached_matches, uncached_matches, "both paths must agree");
+    assert_eq!(cache.stats().misses, 1);
+    assert_eq!(cache.stats().hits, QUERIES as u64 - 1);
+}
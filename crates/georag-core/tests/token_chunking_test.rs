@@ -0,0 +1,161 @@
+//! Token-based chunk sizing across languages
+//!
+//! Chunks sized in tokens must stay within `max_chunk_tokens` whatever the
+//! script: Indonesian and English split on spaces, Japanese and Thai have
+//! none, and emoji sequences are many characters per grapheme. Each fixture
+//! is chunked with the heuristic tokenizer and with a tokenizer that counts
+//! joined text differently from its words.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::processing::tokenizer::{HeuristicTokenizer, TokenLimits, Tokenizer};
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const FIXTURES: &[&str] = &[
+    "Jalan Sudirman adalah jalan protokol utama di Jakarta Pusat yang menghubungkan Bundaran HI dengan Senayan. ",
+    "The harbour district was rebuilt after the flood, with new embankments along the river mouth. ",
+    "東京都千代田区丸の内一丁目は東京駅の西側に位置するオフィス街です。",
+    "ถนนสุขุมวิทเป็นถนนสายหลักที่ผ่านใจกลางกรุงเทพมหานคร",
+    "يقع المتحف الوطني في وسط المدينة بالقرب من الميناء القديم. ",
+    "Pantai 👨‍👩‍👧‍👦🇮🇩🏝️ ",
+];
+
+/// Counts a run of ASCII letters as one token and every other visible character as one
+///
+/// Its count of joined words is not the sum of its counts of the words, so
+/// chunks must be counted as a whole.
+#[derive(Debug)]
+struct WordPieceTokenizer;
+
+impl Tokenizer for WordPieceTokenizer {
+    fn name(&self) -> String {
+        "word-piece".to_string()
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let mut count = 0;
+        let mut in_word = false;
+        for c in text.chars() {
+            if c.is_ascii_alphabetic() {
+                count += usize::from(!in_word);
+                in_word = true;
+            } else {
+                in_word = false;
+                count += usize::from(!c.is_whitespace());
+            }
+        }
+        count
+    }
+
+    fn truncate<'a>(&self, text: &'a str, max_tokens: usize) -> &'a str {
+        let mut end = text.chars().next().map_or(0, char::len_utf8);
+        for (i, c) in text.char_indices().skip(1) {
+            if self.count(&text[..i + c.len_utf8()]) > max_tokens {
+                break;
+            }
+            end = i + c.len_utf8();
+        }
+        &text[..end]
+    }
+}
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "places".to_string(),
+        path: PathBuf::from("places.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 1,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(text: &str) -> Feature {
+    Feature {
+        id: FeatureId(1),
+        geometry: Some(Geometry::point(106.8, -6.2)),
+        properties: HashMap::from([("content".to_string(), serde_json::json!(text))]),
+        crs: 4326,
+        z: None,
+    }
+}
+
+/// Chunk `text` within `max_tokens`, checking every chunk and returning their number
+fn check(tokenizer: Arc<dyn Tokenizer>, max_tokens: usize, overlap: usize, text: &str) -> usize {
+    let limits = TokenLimits::new(tokenizer.clone(), max_tokens, overlap).unwrap();
+    let generator = ChunkGenerator::default().with_token_limits(limits);
+    let chunks = generator.generate_chunks(&dataset(), &[feature(text)]);
+    for chunk in &chunks {
+        let tokens = tokenizer.count(&chunk.content);
+        assert!(
+            tokens <= max_tokens,
+            "{} tokens of {}: {:?}",
+            tokens,
+            tokenizer.name(),
+            chunk.content
+        );
+    }
+    chunks.len()
+}
+
+#[test]
+fn test_multilingual_chunks_stay_within_the_token_limit() {
+    let tokenizers: [Arc<dyn Tokenizer>; 2] =
+        [Arc::new(HeuristicTokenizer), Arc::new(WordPieceTokenizer)];
+    for fixture in FIXTURES {
+        let text = fixture.repeat(40);
+        for tokenizer in &tokenizers {
+            for (max_tokens, overlap) in [(1, 0), (16, 4), (128, 32), (512, 64)] {
+                let chunks = check(tokenizer.clone(), max_tokens, overlap, &text);
+                assert!(chunks > 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_text_without_spaces_is_split_by_tokens() {
+    // 1,650 characters without a space: sized in words they fit in one
+    // chunk of 256-byte pieces, sized in tokens they need many
+    let text = FIXTURES[2].repeat(50);
+    let tokenizer: Arc<dyn Tokenizer> = Arc::new(HeuristicTokenizer);
+    let chunks = check(tokenizer.clone(), 16, 0, &text);
+    assert!(chunks >= tokenizer.count(&text) / 16, "{} chunks", chunks);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn prop_chunks_stay_within_the_token_limit(
+        text in "\\PC{0,1500}",
+        max_tokens in 1usize..200,
+        overlap in 0usize..200,
+    ) {
+        let overlap = overlap % max_tokens;
+        check(Arc::new(HeuristicTokenizer), max_tokens, overlap, &text);
+        check(Arc::new(WordPieceTokenizer), max_tokens, overlap, &text);
+    }
+}
@@ -0,0 +1,6 @@
+//! End-to-end tests across the CLI, the API and PostgreSQL
+//!
+//! This crate has no code of its own; its tests live in `tests/`. They start
+//! a disposable PostgreSQL container, run the real `georag` binary for
+//! `init`, `add` and `build`, and query the result through the API router in
+//! process. They need Docker and only run when `GEORAG_E2E` is set.
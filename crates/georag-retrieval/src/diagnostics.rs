@@ -0,0 +1,129 @@
+//! Explanations for queries that return no sources
+//!
+//! The pipeline counts the candidates left after each filtering phase. When a
+//! query comes back empty, the query service combines those counts with a few
+//! cheap store checks into diagnostics, each naming a likely cause and the
+//! step that would fix it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Candidate chunks left after each filtering phase of a query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CandidateCounts {
+    /// Chunks in the index, not counting stale ones
+    pub indexed_chunks: usize,
+
+    /// Features matching the spatial filter, when the plan has one
+    pub features_matched: Option<usize>,
+
+    /// Chunks left after the spatial filter
+    pub after_spatial: usize,
+
+    /// Chunks left after dropping datasets the caller may not see, and those
+    /// outside the datasets the query names
+    pub after_visibility: usize,
+
+    /// Chunks left after the keyword filter
+    pub after_text: usize,
+
+    /// Chunks left after the attribute filters
+    pub after_attributes: usize,
+
+    /// Chunks left after the time filter
+    #[serde(default)]
+    pub after_time: usize,
+}
+
+/// Likely cause of an empty query result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// The workspace has no datasets
+    NoDatasets,
+
+    /// The index was built with another embedder than the query's
+    EmbedderMismatch,
+
+    /// No chunks have been indexed
+    IndexEmpty,
+
+    /// No features match the spatial filter
+    NoFeaturesInFilter,
+
+    /// Features match the spatial filter but none has indexed chunks
+    NoChunksInFilter,
+
+    /// Every candidate belongs to a dataset hidden from the caller
+    HiddenDatasets,
+
+    /// No candidate belongs to the datasets the query names
+    OutsideDatasets,
+
+    /// The keyword filter removed every candidate
+    TextFilter,
+
+    /// The attribute filters removed every candidate
+    AttributeFilter,
+
+    /// No candidate is dated on or after the time filter's lower bound
+    TimeFilter,
+
+    /// Every ranked candidate scored below the minimum score
+    BelowMinScore,
+
+    /// Candidates passed the filters but none ranked within `top_k`
+    NotInTopMatches,
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiagnosticKind::NoDatasets => "no_datasets",
+            DiagnosticKind::EmbedderMismatch => "embedder_mismatch",
+            DiagnosticKind::IndexEmpty => "index_empty",
+            DiagnosticKind::NoFeaturesInFilter => "no_features_in_filter",
+            DiagnosticKind::NoChunksInFilter => "no_chunks_in_filter",
+            DiagnosticKind::HiddenDatasets => "hidden_datasets",
+            DiagnosticKind::OutsideDatasets => "outside_datasets",
+            DiagnosticKind::TextFilter => "text_filter",
+            DiagnosticKind::AttributeFilter => "attribute_filter",
+            DiagnosticKind::TimeFilter => "time_filter",
+            DiagnosticKind::BelowMinScore => "below_min_score",
+            DiagnosticKind::NotInTopMatches => "not_in_top_matches",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Why a query returned nothing, and what to try next
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+
+    /// What was found
+    pub message: String,
+
+    /// Suggested next step
+    pub suggestion: String,
+}
+
+impl Diagnostic {
+    pub fn new(
+        kind: DiagnosticKind,
+        message: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}. {}", self.message, self.suggestion)
+    }
+}
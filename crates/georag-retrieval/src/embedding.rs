@@ -1,8 +1,13 @@
 use georag_core::error::Result;
 use georag_core::llm::Embedder;
 use georag_core::models::{Embedding, FeatureId, Geometry, SpatialMetadata, TextChunk};
+use std::fmt;
 use std::sync::Arc;
 
+/// Largest distance between the vectors of one text embedded in a batch and
+/// alone, relative to their length
+const ORDER_TOLERANCE: f32 = 1e-3;
+
 /// Port trait for spatial store (re-exported from georag-store)
 pub trait SpatialStore: Send + Sync {
     fn get_feature(
@@ -59,15 +64,17 @@ impl<E: Embedder> EmbeddingPipeline<E> {
         for (batch_idx, chunk_batch) in chunks.chunks(self.batch_size).enumerate() {
             let texts: Vec<&str> = chunk_batch.iter().map(|c| c.content.as_str()).collect();
 
-            // Generate embeddings for this batch
-            let vectors = self.embedder.embed(&texts)?;
+            // Generate embeddings for this batch, leaving out chunks that fail
+            let vectors = embed_checked(&self.embedder, &texts, false)?;
 
             // Create Embedding objects (without spatial metadata for now)
-            for (chunk, vector) in chunk_batch.iter().zip(vectors.into_iter()) {
+            for (chunk, vector) in chunk_batch.iter().zip(vectors) {
+                let Some(vector) = vector else { continue };
                 let embedding = Embedding {
                     chunk_id: chunk.id,
                     vector,
                     spatial_metadata: None,
+                    dataset_id: chunk.metadata.dataset_id,
                 };
                 all_embeddings.push(embedding);
             }
@@ -100,11 +107,12 @@ impl<E: Embedder> EmbeddingPipeline<E> {
         for (batch_idx, chunk_batch) in chunks.chunks(self.batch_size).enumerate() {
             let texts: Vec<&str> = chunk_batch.iter().map(|c| c.content.as_str()).collect();
 
-            // Generate embeddings for this batch
-            let vectors = self.embedder.embed(&texts)?;
+            // Generate embeddings for this batch, leaving out chunks that fail
+            let vectors = embed_checked(&self.embedder, &texts, false)?;
 
             // Create Embedding objects with spatial metadata
-            for (chunk, vector) in chunk_batch.iter().zip(vectors.into_iter()) {
+            for (chunk, vector) in chunk_batch.iter().zip(vectors) {
+                let Some(vector) = vector else { continue };
                 let spatial_metadata = if let Some(feature_id) = chunk.spatial_ref {
                     // Look up feature geometry
                     if let Some(feature) = spatial_store.get_feature(feature_id).await? {
@@ -123,6 +131,7 @@ impl<E: Embedder> EmbeddingPipeline<E> {
                     chunk_id: chunk.id,
                     vector,
                     spatial_metadata,
+                    dataset_id: chunk.metadata.dataset_id,
                 };
                 all_embeddings.push(embedding);
             }
@@ -136,6 +145,134 @@ impl<E: Embedder> EmbeddingPipeline<E> {
     }
 }
 
+/// Embed a batch of texts, making sure each vector belongs to its text
+///
+/// An embedder under memory pressure can answer with fewer vectors than
+/// texts or with vectors of the wrong size, and pairing such an answer with
+/// the texts by position would hand texts each other's vectors. An answer
+/// without one vector of the embedder's dimensions per text is discarded and
+/// the batch split in half and embedded again, down to single texts.
+///
+/// With `check_order`, the last text of a batch is also embedded alone and
+/// compared with its vector from the batch, which catches answers in the
+/// wrong order at the cost of one request per batch.
+///
+/// Returns a vector per text, or `None` for a text that got no usable vector
+/// even on its own. Errors of the embedder itself are returned as they are.
+pub fn embed_checked<E: Embedder + ?Sized>(
+    embedder: &E,
+    texts: &[&str],
+    check_order: bool,
+) -> Result<Vec<Option<Vec<f32>>>> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let vectors = embedder.embed(texts)?;
+    let Some(mismatch) = check_batch(embedder, texts, &vectors, check_order)? else {
+        return Ok(vectors.into_iter().map(Some).collect());
+    };
+
+    if texts.len() == 1 {
+        tracing::warn!(
+            model = embedder.model_name(),
+            %mismatch,
+            "Leaving a chunk unembedded after the embedder failed it on its own"
+        );
+        return Ok(vec![None]);
+    }
+
+    tracing::warn!(
+        model = embedder.model_name(),
+        batch_size = texts.len(),
+        %mismatch,
+        "Embedder answer does not match the batch, retrying in halves"
+    );
+    let (first, second) = texts.split_at(texts.len() / 2);
+    let mut checked = embed_checked(embedder, first, check_order)?;
+    checked.extend(embed_checked(embedder, second, check_order)?);
+    Ok(checked)
+}
+
+/// Why an embedder's answer could not be paired with the batch
+#[derive(Debug, Clone, PartialEq)]
+enum BatchMismatch {
+    Count {
+        returned: usize,
+        expected: usize,
+    },
+    Dimensions {
+        index: usize,
+        found: usize,
+        expected: usize,
+    },
+    Order {
+        index: usize,
+    },
+}
+
+impl fmt::Display for BatchMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count { returned, expected } => {
+                write!(f, "returned {} embeddings for {} texts", returned, expected)
+            }
+            Self::Dimensions { index, found, expected } => {
+                write!(f, "embedding {} has {} dimensions instead of {}", index, found, expected)
+            }
+            Self::Order { index } => {
+                write!(f, "embedding {} differs from the text embedded alone", index)
+            }
+        }
+    }
+}
+
+fn check_batch<E: Embedder + ?Sized>(
+    embedder: &E,
+    texts: &[&str],
+    vectors: &[Vec<f32>],
+    check_order: bool,
+) -> Result<Option<BatchMismatch>> {
+    if vectors.len() != texts.len() {
+        return Ok(Some(BatchMismatch::Count {
+            returned: vectors.len(),
+            expected: texts.len(),
+        }));
+    }
+
+    let expected = embedder.dimensions();
+    if let Some((index, vector)) = vectors.iter().enumerate().find(|(_, v)| v.len() != expected) {
+        return Ok(Some(BatchMismatch::Dimensions { index, found: vector.len(), expected }));
+    }
+
+    if check_order && texts.len() > 1 {
+        let index = texts.len() - 1;
+        let alone = embedder.embed(&texts[index..])?;
+        let same = alone
+            .first()
+            .is_some_and(|vector| relative_distance(vector, &vectors[index]) <= ORDER_TOLERANCE);
+        if !same {
+            return Ok(Some(BatchMismatch::Order { index }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Distance between two vectors divided by the length of the longer one
+fn relative_distance(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return f32::INFINITY;
+    }
+    let distance: f32 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let length = norm(a).max(norm(b));
+    if length == 0.0 {
+        return 0.0;
+    }
+    distance / length
+}
+
 /// Extract bounding box from typed Geometry
 fn extract_bbox(geometry: &Option<Geometry>) -> Option<[f64; 4]> {
     let geom = geometry.as_ref()?;
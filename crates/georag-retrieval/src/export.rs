@@ -0,0 +1,944 @@
+//! Serialization of query results
+//!
+//! The API and the CLI render query results as GeoJSON, a flat JSON array,
+//! CSV or a static map (see `crate::map`). The row shape and column order are
+//! defined here so every output of the same query carries the same fields.
+
+use georag_core::geo;
+use georag_core::geo::simplify::simplify_within_budget;
+use georag_core::models::{FeatureId, Geometry};
+use georag_store::ports::SpatialStore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::SourceReference;
+
+/// Columns of a result row, in output order
+pub const CSV_COLUMNS: [&str; 7] =
+    ["score", "excerpt", "document_path", "chunk_id", "feature_id", "lon", "lat"];
+
+/// Keys every result keeps, whatever the property selection
+pub const RESULT_KEYS: [&str; 3] = ["score", "excerpt", "document_path"];
+
+/// Output format for query results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultFormat {
+    /// GeoJSON FeatureCollection with one feature per source
+    #[default]
+    GeoJson,
+
+    /// JSON array of result rows without geometry
+    Json,
+
+    /// CSV with a header row and the columns in `CSV_COLUMNS`
+    Csv,
+
+    /// PNG map of the result geometries
+    Png,
+}
+
+impl ResultFormat {
+    /// All supported formats
+    pub const ALL: [ResultFormat; 4] =
+        [ResultFormat::GeoJson, ResultFormat::Json, ResultFormat::Csv, ResultFormat::Png];
+
+    /// Media type sent as the response content type
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ResultFormat::GeoJson => "application/geo+json",
+            ResultFormat::Json => "application/json",
+            ResultFormat::Csv => "text/csv; charset=utf-8",
+            ResultFormat::Png => "image/png",
+        }
+    }
+
+    /// Comma-separated list of supported format names
+    pub fn supported() -> String {
+        Self::ALL.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Map a single media type to a format
+    ///
+    /// Wildcards resolve to GeoJSON, except `text/*` which resolves to CSV and
+    /// `image/*` which resolves to PNG.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or("").trim().to_lowercase();
+        match essence.as_str() {
+            "application/geo+json" | "application/vnd.geo+json" => Some(ResultFormat::GeoJson),
+            "application/json" => Some(ResultFormat::Json),
+            "text/csv" | "text/*" => Some(ResultFormat::Csv),
+            "image/png" | "image/*" => Some(ResultFormat::Png),
+            "*/*" | "application/*" => Some(ResultFormat::GeoJson),
+            _ => None,
+        }
+    }
+
+    /// Pick a format from an `Accept` header value
+    ///
+    /// Media ranges are tried in order of their `q` weight. Returns `None`
+    /// when no acceptable range maps to a supported format.
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        let mut ranges: Vec<(f32, usize, &str)> = accept
+            .split(',')
+            .enumerate()
+            .filter_map(|(position, range)| {
+                let range = range.trim();
+                if range.is_empty() {
+                    return None;
+                }
+                let quality = range
+                    .split(';')
+                    .skip(1)
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                Some((quality, position, range))
+            })
+            .filter(|(quality, _, _)| *quality > 0.0)
+            .collect();
+
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        ranges.into_iter().find_map(|(_, _, range)| Self::from_media_type(range))
+    }
+}
+
+impl fmt::Display for ResultFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ResultFormat::GeoJson => "geojson",
+            ResultFormat::Json => "json",
+            ResultFormat::Csv => "csv",
+            ResultFormat::Png => "png",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ResultFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "geojson" => Ok(ResultFormat::GeoJson),
+            "json" => Ok(ResultFormat::Json),
+            "csv" => Ok(ResultFormat::Csv),
+            "png" => Ok(ResultFormat::Png),
+            _ => Err(format!(
+                "Unsupported format '{}': expected one of {}",
+                s,
+                ResultFormat::supported()
+            )),
+        }
+    }
+}
+
+/// How much of a source's geometry a response carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GeometryDetail {
+    /// The feature geometry as stored
+    #[default]
+    Full,
+
+    /// A point at the geometry's centroid, as in the `centroid` property
+    Centroid,
+
+    /// The geometry's bounding box as a polygon
+    Bbox,
+
+    /// No geometry (`null`)
+    None,
+
+    /// The full geometry within the vertex budget, simplified beyond it
+    Adaptive,
+}
+
+impl GeometryDetail {
+    /// All geometry detail levels
+    pub const ALL: [GeometryDetail; 5] = [
+        GeometryDetail::Full,
+        GeometryDetail::Centroid,
+        GeometryDetail::Bbox,
+        GeometryDetail::None,
+        GeometryDetail::Adaptive,
+    ];
+
+    /// Comma-separated list of detail level names
+    pub fn supported() -> String {
+        Self::ALL.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Reduce a geometry to this level of detail
+    ///
+    /// A bounding box without area (a single point, or a horizontal or
+    /// vertical line) is returned as its centroid point rather than as a
+    /// degenerate polygon. `Adaptive` uses the default vertex budget.
+    pub fn apply(&self, geometry: Geometry) -> Option<Geometry> {
+        match self {
+            GeometryDetail::Full => Some(geometry),
+            GeometryDetail::Adaptive => {
+                Some(adaptive(geometry, GeometryOutput::DEFAULT_VERTEX_BUDGET))
+            }
+            GeometryDetail::Centroid => centroid(&geometry),
+            GeometryDetail::Bbox => {
+                let [min_x, min_y, max_x, max_y] = bounding_box(&geometry)?;
+                if min_x == max_x || min_y == max_y {
+                    return centroid(&geometry);
+                }
+                Some(Geometry::polygon(vec![vec![
+                    [min_x, min_y],
+                    [max_x, min_y],
+                    [max_x, max_y],
+                    [min_x, max_y],
+                    [min_x, min_y],
+                ]]))
+            }
+            GeometryDetail::None => None,
+        }
+    }
+}
+
+impl fmt::Display for GeometryDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GeometryDetail::Full => "full",
+            GeometryDetail::Centroid => "centroid",
+            GeometryDetail::Bbox => "bbox",
+            GeometryDetail::None => "none",
+            GeometryDetail::Adaptive => "adaptive",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for GeometryDetail {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "full" => Ok(GeometryDetail::Full),
+            "centroid" => Ok(GeometryDetail::Centroid),
+            "bbox" => Ok(GeometryDetail::Bbox),
+            "none" => Ok(GeometryDetail::None),
+            "adaptive" => Ok(GeometryDetail::Adaptive),
+            _ => Err(format!(
+                "Unsupported geometry detail '{}': expected one of {}",
+                s,
+                GeometryDetail::supported()
+            )),
+        }
+    }
+}
+
+/// Geometry detail and coordinate precision of serialized results
+///
+/// The default keeps full geometries with untrimmed coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeometryOutput {
+    /// How much geometry each source carries
+    pub detail: GeometryDetail,
+
+    /// Decimal places kept in coordinates; `None` keeps them as stored
+    pub precision: Option<u32>,
+
+    /// Most vertices an `Adaptive` geometry keeps
+    pub vertex_budget: usize,
+}
+
+impl Default for GeometryOutput {
+    fn default() -> Self {
+        Self {
+            detail: GeometryDetail::default(),
+            precision: None,
+            vertex_budget: Self::DEFAULT_VERTEX_BUDGET,
+        }
+    }
+}
+
+impl GeometryOutput {
+    /// Most decimal places a precision may request; finer than f64 resolution
+    pub const MAX_PRECISION: u32 = 15;
+
+    /// Vertices an `Adaptive` geometry keeps unless a budget is given
+    pub const DEFAULT_VERTEX_BUDGET: usize = 1_000;
+
+    /// Smallest vertex budget; enough for a polygon with a hole
+    pub const MIN_VERTEX_BUDGET: usize = 8;
+
+    /// Whether a source geometry is simplified for output
+    pub fn simplifies(&self, geometry: &Geometry) -> bool {
+        self.detail == GeometryDetail::Adaptive && geometry.vertex_count() > self.vertex_budget
+    }
+
+    /// Reduce and trim a source geometry for output
+    pub fn apply(&self, geometry: Geometry) -> Option<Geometry> {
+        let mut geometry = match self.detail {
+            GeometryDetail::Adaptive => adaptive(geometry, self.vertex_budget),
+            detail => detail.apply(geometry)?,
+        };
+        if let Some(precision) = self.precision {
+            trim_coordinates(&mut geometry, precision);
+        }
+        Some(geometry)
+    }
+
+    /// Round a single coordinate value to the configured precision
+    pub fn trim(&self, value: f64) -> f64 {
+        match self.precision {
+            Some(precision) => round_to(value, precision),
+            None => value,
+        }
+    }
+}
+
+/// A geometry simplified to the vertex budget, or its bounding box
+///
+/// Douglas-Peucker with a bisected tolerance keeps as much detail as the
+/// budget allows. When that cannot meet the budget or leaves a polygon
+/// invalid, the bounding box is returned instead.
+fn adaptive(geometry: Geometry, budget: usize) -> Geometry {
+    if geometry.vertex_count() <= budget {
+        return geometry;
+    }
+    match simplify_within_budget(&geometry, budget) {
+        Some((simplified, _)) => simplified,
+        None => GeometryDetail::Bbox.apply(geometry.clone()).unwrap_or(geometry),
+    }
+}
+
+/// Centroid of a geometry as a point
+fn centroid(geometry: &Geometry) -> Option<Geometry> {
+    geo::centroid(geometry).map(|[x, y]| Geometry::point(x, y))
+}
+
+/// Bounding box `[min_x, min_y, max_x, max_y]` of a geometry
+fn bounding_box(geometry: &Geometry) -> Option<[f64; 4]> {
+    let mut bbox: Option<[f64; 4]> = None;
+    for_each_coordinate(geometry, &mut |[x, y]| {
+        let b = bbox.get_or_insert([x, y, x, y]);
+        *b = [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)];
+    });
+    bbox
+}
+
+fn for_each_coordinate(geometry: &Geometry, f: &mut impl FnMut([f64; 2])) {
+    match geometry {
+        Geometry::Point { coordinates } => f(*coordinates),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.iter().for_each(|c| f(*c))
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter().flatten().for_each(|c| f(*c))
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter().flatten().flatten().for_each(|c| f(*c))
+        }
+    }
+}
+
+/// Round every coordinate of a geometry to `precision` decimal places
+fn trim_coordinates(geometry: &mut Geometry, precision: u32) {
+    let trim = |c: &mut [f64; 2]| *c = [round_to(c[0], precision), round_to(c[1], precision)];
+    match geometry {
+        Geometry::Point { coordinates } => trim(coordinates),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.iter_mut().for_each(trim)
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter_mut().flatten().for_each(trim)
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter_mut().flatten().flatten().for_each(trim)
+        }
+    }
+}
+
+fn round_to(value: f64, precision: u32) -> f64 {
+    let factor = 10f64.powi(precision.min(GeometryOutput::MAX_PRECISION) as i32);
+    (value * factor).round() / factor
+}
+
+/// Property keys kept in or dropped from serialized results and features
+///
+/// With `include`, only the keys listed are kept and `exclude` is not
+/// consulted; otherwise every key but those in `exclude` is kept. The keys in
+/// `RESULT_KEYS` are always kept in query results. The default keeps every
+/// key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertySelection {
+    /// Keys to keep
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Keys to drop
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl PropertySelection {
+    /// Selection keeping only the given keys
+    pub fn include<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            include: keys.into_iter().map(Into::into).collect(),
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Whether the selection keeps every key
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Refuse a selection that both includes and excludes a key
+    pub fn validate(&self) -> Result<(), String> {
+        let both: Vec<&str> = self
+            .include
+            .iter()
+            .filter(|key| self.exclude.contains(key))
+            .map(String::as_str)
+            .collect();
+        if both.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Keys both included and excluded: {}", both.join(", ")))
+        }
+    }
+
+    /// Whether a key of a feature's properties is kept
+    pub fn selects(&self, key: &str) -> bool {
+        if self.include.is_empty() {
+            !self.exclude.iter().any(|excluded| excluded == key)
+        } else {
+            self.include.iter().any(|included| included == key)
+        }
+    }
+
+    /// Whether a key of a query result is kept
+    pub fn selects_result_key(&self, key: &str) -> bool {
+        RESULT_KEYS.contains(&key) || self.selects(key)
+    }
+
+    /// Drop the unselected keys of a query result's properties or row
+    pub fn apply(&self, properties: &mut Map<String, Value>) {
+        properties.retain(|key, _| self.selects_result_key(key));
+    }
+
+    /// Drop the unselected keys of a feature's properties
+    pub fn apply_to_feature(&self, properties: &mut Map<String, Value>) {
+        properties.retain(|key, _| self.selects(key));
+    }
+
+    /// Selected `CSV_COLUMNS`, in output order
+    pub fn columns(&self) -> Vec<&'static str> {
+        CSV_COLUMNS
+            .into_iter()
+            .filter(|column| self.selects_result_key(column))
+            .collect()
+    }
+}
+
+/// Flat, geometry-free view of a ranked source
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultRow {
+    /// Relevance score
+    pub score: f32,
+
+    /// Text excerpt
+    pub excerpt: String,
+
+    /// Source document path
+    pub document_path: String,
+
+    /// Chunk ID
+    pub chunk_id: u64,
+
+    /// Feature ID, if the chunk belongs to a feature
+    pub feature_id: Option<u64>,
+
+    /// Longitude of the feature's centroid
+    pub lon: Option<f64>,
+
+    /// Latitude of the feature's centroid
+    pub lat: Option<f64>,
+}
+
+impl ResultRow {
+    /// Build a row from a source and the geometry of its feature
+    pub fn from_source(source: &SourceReference, geometry: Option<&Geometry>) -> Self {
+        let location = geometry.and_then(geo::centroid);
+
+        Self {
+            score: source.score,
+            excerpt: source.excerpt.clone(),
+            document_path: source.document_path.clone(),
+            chunk_id: source.chunk_id.0,
+            feature_id: source.feature_id.map(|id| id.0),
+            lon: location.map(|[lon, _]| lon),
+            lat: location.map(|[_, lat]| lat),
+        }
+    }
+
+    /// Convert the row to a JSON object keyed by column name
+    ///
+    /// Callers redact the object before serializing it, the same way GeoJSON
+    /// feature properties are redacted.
+    pub fn to_record(&self) -> Map<String, Value> {
+        let mut record = Map::new();
+        record.insert("score".to_string(), Value::from(self.score));
+        record.insert("excerpt".to_string(), Value::from(self.excerpt.clone()));
+        record.insert("document_path".to_string(), Value::from(self.document_path.clone()));
+        record.insert("chunk_id".to_string(), Value::from(self.chunk_id));
+        record.insert("feature_id".to_string(), self.feature_id.map_or(Value::Null, Value::from));
+        record.insert("lon".to_string(), self.lon.map_or(Value::Null, Value::from));
+        record.insert("lat".to_string(), self.lat.map_or(Value::Null, Value::from));
+        record
+    }
+}
+
+/// GeoJSON feature properties for a ranked source
+///
+/// With the feature's geometry, `centroid` and `representative_point` give
+/// `[x, y]` points of it, the latter always on the geometry, trimmed to the
+/// output's precision. With `Adaptive` detail, `geometry_simplified` tells
+/// whether the geometry was reduced to the vertex budget, and
+/// `original_vertex_count` how many vertices the full geometry has.
+pub fn source_properties(
+    source: &SourceReference,
+    geometry: Option<&Geometry>,
+    output: &GeometryOutput,
+) -> Map<String, Value> {
+    let mut properties = Map::new();
+    properties.insert("score".to_string(), Value::from(source.score));
+    properties.insert("excerpt".to_string(), Value::from(source.excerpt.clone()));
+    properties.insert("document_path".to_string(), Value::from(source.document_path.clone()));
+    properties.insert("chunk_id".to_string(), Value::from(source.chunk_id.0));
+
+    if let Some(feature_id) = source.feature_id {
+        properties.insert("feature_id".to_string(), Value::from(feature_id.0));
+    }
+
+    if let Some(page) = source.page {
+        properties.insert("page".to_string(), Value::from(page));
+    }
+
+    if let Some(spatial_match) = source.spatial_match {
+        properties.insert("spatial_match".to_string(), Value::from(spatial_match.to_string()));
+    }
+
+    if let Some(url) = &source.source_url {
+        properties.insert("source_url".to_string(), Value::from(url.clone()));
+    }
+
+    if let Some(confidence) = source.confidence {
+        properties.insert("confidence".to_string(), Value::from(confidence.level.to_string()));
+        properties.insert("confidence_percentile".to_string(), Value::from(confidence.percentile));
+    }
+
+    if let Some(geometry) = geometry {
+        let point = |[x, y]: [f64; 2]| Value::from(vec![output.trim(x), output.trim(y)]);
+        if let Some(centroid) = geo::centroid(geometry) {
+            properties.insert("centroid".to_string(), point(centroid));
+        }
+        if let Some(representative) = geo::representative_point(geometry) {
+            properties.insert("representative_point".to_string(), point(representative));
+        }
+        if output.detail == GeometryDetail::Adaptive {
+            let simplified = output.simplifies(geometry);
+            properties.insert("geometry_simplified".to_string(), Value::from(simplified));
+            if simplified {
+                properties.insert(
+                    "original_vertex_count".to_string(),
+                    Value::from(geometry.vertex_count()),
+                );
+            }
+        }
+    }
+
+    properties
+}
+
+/// Look up the feature geometry of each source
+///
+/// The features of all sources are fetched in one batch. Sources without a
+/// feature, or whose feature cannot be loaded, get `None`.
+pub async fn source_geometries(
+    sources: &[SourceReference],
+    spatial_store: &dyn SpatialStore,
+) -> Vec<Option<Geometry>> {
+    let ids: Vec<FeatureId> = sources.iter().filter_map(|s| s.feature_id).collect();
+    if ids.is_empty() {
+        return vec![None; sources.len()];
+    }
+
+    let geometries: HashMap<FeatureId, Geometry> = spatial_store
+        .get_features(&ids)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|feature| Some((feature.id, feature.geometry?)))
+        .collect();
+    sources
+        .iter()
+        .map(|source| source.feature_id.and_then(|id| geometries.get(&id).cloned()))
+        .collect()
+}
+
+/// Render records as CSV with a header row and columns in `CSV_COLUMNS` order
+///
+/// Fields containing commas, quotes or line breaks are quoted, and embedded
+/// quotes are doubled (RFC 4180). Missing values are written as empty fields.
+pub fn to_csv(records: &[Map<String, Value>]) -> String {
+    to_csv_columns(records, &CSV_COLUMNS)
+}
+
+/// Render records as CSV with a header row and the given columns, in order
+///
+/// Quoted like [`to_csv`].
+pub fn to_csv_columns(records: &[Map<String, Value>], columns: &[&str]) -> String {
+    let mut csv = columns.join(",");
+    csv.push_str("\r\n");
+
+    for record in records {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| match record.get(*column) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(s)) => csv_field(s),
+                Some(other) => csv_field(&other.to_string()),
+            })
+            .collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use georag_core::models::{ChunkId, FeatureId};
+
+    fn source(excerpt: &str) -> SourceReference {
+        SourceReference {
+            chunk_id: ChunkId(7),
+            feature_id: Some(FeatureId(3)),
+            document_path: "parcels.geojson".to_string(),
+            page: None,
+            offset: Some(0),
+            excerpt: excerpt.to_string(),
+            score: 0.5,
+            spatial_match: None,
+            source_url: None,
+            confidence: None,
+        }
+    }
+
+    fn csv_for(excerpt: &str) -> String {
+        let row = ResultRow::from_source(&source(excerpt), Some(&Geometry::point(115.2, -8.6)));
+        to_csv(&[row.to_record()])
+    }
+
+    #[test]
+    fn test_csv_header_and_column_order() {
+        let csv = csv_for("plain text");
+        let mut lines = csv.split("\r\n");
+
+        assert_eq!(lines.next(), Some("score,excerpt,document_path,chunk_id,feature_id,lon,lat"));
+        assert_eq!(lines.next(), Some("0.5,plain text,parcels.geojson,7,3,115.2,-8.6"));
+    }
+
+    #[test]
+    fn test_csv_quotes_commas() {
+        let csv = csv_for("zoned residential, low density");
+        assert!(csv.contains(",\"zoned residential, low density\",parcels.geojson,"));
+    }
+
+    #[test]
+    fn test_csv_quotes_newlines() {
+        let csv = csv_for("first line\nsecond line\r\nthird");
+        assert!(csv.contains(",\"first line\nsecond line\r\nthird\",parcels.geojson,"));
+    }
+
+    #[test]
+    fn test_csv_doubles_quotes() {
+        let csv = csv_for("the \"old\" mill, east bank");
+        assert!(csv.contains(",\"the \"\"old\"\" mill, east bank\","));
+    }
+
+    #[test]
+    fn test_csv_missing_values_are_empty() {
+        let mut src = source("no feature");
+        src.feature_id = None;
+        let csv = to_csv(&[ResultRow::from_source(&src, None).to_record()]);
+
+        assert!(csv.ends_with("0.5,no feature,parcels.geojson,7,,,\r\n"));
+    }
+
+    #[test]
+    fn test_row_uses_polygon_centroid() {
+        let square = Geometry::polygon(vec![vec![
+            [0.0, 0.0],
+            [2.0, 0.0],
+            [2.0, 2.0],
+            [0.0, 2.0],
+            [0.0, 0.0],
+        ]]);
+        let row = ResultRow::from_source(&source("x"), Some(&square));
+
+        assert_eq!((row.lon, row.lat), (Some(1.0), Some(1.0)));
+    }
+
+    fn l_shape() -> Geometry {
+        Geometry::polygon(vec![vec![
+            [0.0, 0.0],
+            [4.0, 0.0],
+            [4.0, 1.0],
+            [1.0, 1.0],
+            [1.0, 3.0],
+            [0.0, 3.0],
+            [0.0, 0.0],
+        ]])
+    }
+
+    #[test]
+    fn test_centroid_detail_is_geojson_point() {
+        let geometry = GeometryDetail::Centroid.apply(l_shape()).unwrap();
+        let geojson = geometry.to_geojson();
+
+        assert_eq!(geojson["type"], "Point");
+        assert_eq!(geojson["coordinates"].as_array().unwrap().len(), 2);
+        assert!(matches!(Geometry::from_geojson(&geojson), Some(Geometry::Point { .. })));
+    }
+
+    #[test]
+    fn test_bbox_detail_is_closed_geojson_polygon() {
+        let geometry = GeometryDetail::Bbox.apply(l_shape()).unwrap();
+        let geojson = geometry.to_geojson();
+
+        assert_eq!(geojson["type"], "Polygon");
+        let ring = geojson["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        assert_eq!(
+            Geometry::from_geojson(&geojson),
+            Some(Geometry::polygon(vec![vec![
+                [0.0, 0.0],
+                [4.0, 0.0],
+                [4.0, 3.0],
+                [0.0, 3.0],
+                [0.0, 0.0],
+            ]]))
+        );
+    }
+
+    #[test]
+    fn test_bbox_detail_of_point_is_point() {
+        let point = Geometry::point(115.2, -8.6);
+        assert_eq!(GeometryDetail::Bbox.apply(point.clone()), Some(point));
+    }
+
+    #[test]
+    fn test_full_and_none_details() {
+        assert_eq!(GeometryDetail::Full.apply(l_shape()), Some(l_shape()));
+        assert_eq!(GeometryDetail::None.apply(l_shape()), None);
+        assert_eq!("BBOX".parse::<GeometryDetail>(), Ok(GeometryDetail::Bbox));
+        assert!("hull".parse::<GeometryDetail>().is_err());
+    }
+
+    #[test]
+    fn test_precision_trims_coordinates() {
+        let output = GeometryOutput {
+            detail: GeometryDetail::Full,
+            precision: Some(3),
+            ..Default::default()
+        };
+        let trimmed = output.apply(Geometry::point(115.123456, -8.654321)).unwrap();
+
+        assert_eq!(trimmed, Geometry::point(115.123, -8.654));
+        assert_eq!(GeometryOutput::default().apply(l_shape()), Some(l_shape()));
+    }
+
+    /// Polygon with 100,000 vertices on a wobbly circle, plus the closing vertex
+    fn detailed_polygon() -> Geometry {
+        let n = 100_000;
+        let mut ring: Vec<[f64; 2]> = (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+                let radius = 1.0 + 0.001 * (i as f64 * 0.37).sin();
+                [106.8 + radius * angle.cos(), -6.2 + radius * angle.sin()]
+            })
+            .collect();
+        ring.push(ring[0]);
+        Geometry::polygon(vec![ring])
+    }
+
+    #[test]
+    fn test_adaptive_detail_simplifies_to_the_budget() {
+        let output = GeometryOutput {
+            detail: GeometryDetail::Adaptive,
+            vertex_budget: 500,
+            ..Default::default()
+        };
+        let full = detailed_polygon();
+
+        let simplified = output.apply(full.clone()).unwrap();
+        assert!(matches!(simplified, Geometry::Polygon { .. }));
+        assert!(simplified.vertex_count() <= 500);
+        assert!(
+            simplified.vertex_count() > 100,
+            "over-simplified to {}",
+            simplified.vertex_count()
+        );
+
+        let properties = source_properties(&source("x"), Some(&full), &output);
+        assert_eq!(properties["geometry_simplified"], true);
+        assert_eq!(properties["original_vertex_count"], 100_001);
+
+        // Within the budget the geometry is returned whole
+        assert_eq!(output.apply(l_shape()), Some(l_shape()));
+        let properties = source_properties(&source("x"), Some(&l_shape()), &output);
+        assert_eq!(properties["geometry_simplified"], false);
+        assert!(!properties.contains_key("original_vertex_count"));
+
+        // Other details are not annotated
+        let properties = source_properties(&source("x"), Some(&full), &GeometryOutput::default());
+        assert!(!properties.contains_key("geometry_simplified"));
+    }
+
+    #[test]
+    fn test_adaptive_detail_falls_back_to_bbox() {
+        let output = GeometryOutput {
+            detail: GeometryDetail::Adaptive,
+            vertex_budget: 9,
+            ..Default::default()
+        };
+        // Filling the notch of the square to meet the budget would cover the triangle
+        let shapes = Geometry::MultiPolygon {
+            coordinates: vec![
+                vec![vec![
+                    [0.0, 0.0],
+                    [10.0, 0.0],
+                    [10.0, 10.0],
+                    [6.0, 10.0],
+                    [5.0, 7.0],
+                    [4.0, 10.0],
+                    [0.0, 10.0],
+                    [0.0, 0.0],
+                ]],
+                vec![vec![[4.8, 9.5], [5.2, 9.5], [5.0, 8.0], [4.8, 9.5]]],
+            ],
+        };
+
+        assert_eq!(output.apply(shapes.clone()), GeometryDetail::Bbox.apply(shapes));
+        assert_eq!("Adaptive".parse::<GeometryDetail>(), Ok(GeometryDetail::Adaptive));
+    }
+
+    #[test]
+    fn test_properties_carry_centroid_and_representative_point() {
+        let output = GeometryOutput {
+            detail: GeometryDetail::Centroid,
+            precision: Some(2),
+            ..Default::default()
+        };
+        let properties = source_properties(&source("x"), Some(&l_shape()), &output);
+
+        // The centroid detail and the centroid property are the same point
+        let centroid = output.apply(l_shape()).unwrap().to_geojson()["coordinates"].clone();
+        assert_eq!(properties["centroid"], centroid);
+        assert_eq!(properties["centroid"], serde_json::json!([1.5, 1.0]));
+        // In the vertical arm, where the scanline section is widest
+        assert_eq!(properties["representative_point"], serde_json::json!([0.5, 2.0]));
+
+        let properties = source_properties(&source("x"), None, &output);
+        assert!(!properties.contains_key("centroid"));
+        assert!(!properties.contains_key("representative_point"));
+    }
+
+    #[test]
+    fn test_property_selection_keeps_result_keys() {
+        let row = ResultRow::from_source(&source("x"), Some(&Geometry::point(115.2, -8.6)));
+
+        let mut record = row.to_record();
+        PropertySelection::include(["lon", "lat"]).apply(&mut record);
+        let keys: Vec<&str> = record.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["document_path", "excerpt", "lat", "lon", "score"]);
+
+        let exclude = PropertySelection {
+            include: Vec::new(),
+            exclude: vec!["chunk_id".to_string(), "score".to_string()],
+        };
+        let mut record = row.to_record();
+        exclude.apply(&mut record);
+        assert!(!record.contains_key("chunk_id"));
+        assert!(record.contains_key("score"));
+
+        let csv = to_csv_columns(&[row.to_record()], &exclude.columns());
+        assert_eq!(
+            csv.split("\r\n").next(),
+            Some("score,excerpt,document_path,feature_id,lon,lat")
+        );
+    }
+
+    #[test]
+    fn test_property_selection_include_takes_precedence() {
+        let selection = PropertySelection {
+            include: vec!["name".to_string()],
+            exclude: vec!["category".to_string()],
+        };
+        assert!(selection.validate().is_ok());
+
+        let mut properties =
+            serde_json::json!({ "name": "Ubud", "category": "market", "score": 1 });
+        let properties = properties.as_object_mut().unwrap();
+        selection.apply_to_feature(properties);
+        assert_eq!(properties.keys().collect::<Vec<_>>(), vec!["name"]);
+
+        let conflicting = PropertySelection {
+            include: vec!["name".to_string()],
+            exclude: vec!["name".to_string()],
+        };
+        assert!(conflicting.validate().unwrap_err().contains("name"));
+    }
+
+    #[test]
+    fn test_format_from_accept() {
+        assert_eq!(ResultFormat::from_accept("text/csv"), Some(ResultFormat::Csv));
+        assert_eq!(ResultFormat::from_accept("*/*"), Some(ResultFormat::GeoJson));
+        assert_eq!(
+            ResultFormat::from_accept("application/geo+json;q=0.5, application/json"),
+            Some(ResultFormat::Json)
+        );
+        assert_eq!(
+            ResultFormat::from_accept("application/xml, text/csv;q=0.1"),
+            Some(ResultFormat::Csv)
+        );
+        assert_eq!(ResultFormat::from_accept("image/png"), Some(ResultFormat::Png));
+        assert_eq!(ResultFormat::from_accept("application/xml"), None);
+        assert_eq!(ResultFormat::from_accept("text/csv;q=0"), None);
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("CSV".parse::<ResultFormat>(), Ok(ResultFormat::Csv));
+        assert_eq!("geojson".parse::<ResultFormat>(), Ok(ResultFormat::GeoJson));
+        assert!("xml".parse::<ResultFormat>().unwrap_err().contains("geojson, json, csv"));
+    }
+}
@@ -0,0 +1,364 @@
+//! Grounding checks of generated answers
+//!
+//! A generator can state things the retrieved sources do not say. After
+//! generation the answer is split into sentences and each one is compared
+//! with the source excerpts, by embedding similarity with the query's
+//! embedder and by the share of its terms an excerpt contains. A sentence
+//! reaching neither threshold against any excerpt is ungrounded; the
+//! [`GroundingPolicy`] decides whether it is only flagged, marked in the
+//! answer or removed from it.
+
+use georag_core::error::Result;
+use georag_core::llm::Embedder;
+use georag_core::models::ChunkId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::embedding::embed_checked;
+use crate::models::SourceReference;
+
+/// Embedding similarity at which a sentence counts as supported by default
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.7;
+
+/// Share of a sentence's terms an excerpt must contain by default
+pub const DEFAULT_MIN_OVERLAP: f32 = 0.5;
+
+/// Marker placed after ungrounded sentences with [`UngroundedAction::Annotate`]
+pub const UNGROUNDED_MARKER: &str = "[unsupported]";
+
+/// What happens to ungrounded sentences in the answer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UngroundedAction {
+    /// Keep the answer as generated and list the sentences in the result
+    #[default]
+    Flag,
+
+    /// Follow each sentence with [`UNGROUNDED_MARKER`]
+    Annotate,
+
+    /// Remove the sentences from the answer
+    Strip,
+}
+
+impl fmt::Display for UngroundedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UngroundedAction::Flag => "flag",
+            UngroundedAction::Annotate => "annotate",
+            UngroundedAction::Strip => "strip",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for UngroundedAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "flag" => Ok(UngroundedAction::Flag),
+            "annotate" => Ok(UngroundedAction::Annotate),
+            "strip" => Ok(UngroundedAction::Strip),
+            _ => {
+                Err(format!("Invalid ungrounded action '{}': expected flag, annotate or strip", s))
+            }
+        }
+    }
+}
+
+/// Thresholds of the grounding check and the action on ungrounded sentences
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundingPolicy {
+    /// Cosine similarity to an excerpt that supports a sentence
+    pub min_similarity: f32,
+
+    /// Share of a sentence's terms found in one excerpt that supports it
+    pub min_overlap: f32,
+
+    pub action: UngroundedAction,
+}
+
+impl Default for GroundingPolicy {
+    fn default() -> Self {
+        Self {
+            min_similarity: DEFAULT_MIN_SIMILARITY,
+            min_overlap: DEFAULT_MIN_OVERLAP,
+            action: UngroundedAction::default(),
+        }
+    }
+}
+
+impl GroundingPolicy {
+    /// Set the similarity at which a sentence counts as supported
+    pub fn with_min_similarity(mut self, min_similarity: f32) -> Self {
+        self.min_similarity = min_similarity;
+        self
+    }
+
+    /// Set the term overlap at which a sentence counts as supported
+    pub fn with_min_overlap(mut self, min_overlap: f32) -> Self {
+        self.min_overlap = min_overlap;
+        self
+    }
+
+    /// Set what happens to ungrounded sentences
+    pub fn with_action(mut self, action: UngroundedAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// Grounding scores of one sentence of an answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceGrounding {
+    /// The sentence as generated
+    pub sentence: String,
+
+    /// Best cosine similarity to a source excerpt
+    pub similarity: f32,
+
+    /// Best share of the sentence's terms found in a source excerpt
+    pub overlap: f32,
+
+    /// Source supporting the sentence best, by similarity
+    pub chunk_id: Option<ChunkId>,
+
+    /// Whether either score reached its threshold
+    pub grounded: bool,
+}
+
+/// Grounding of every sentence of an answer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnswerGrounding {
+    pub sentences: Vec<SentenceGrounding>,
+
+    /// Byte range of each sentence in the answer
+    spans: Vec<Range<usize>>,
+}
+
+impl AnswerGrounding {
+    /// Sentences no source supports
+    pub fn ungrounded(&self) -> impl Iterator<Item = &SentenceGrounding> {
+        self.sentences.iter().filter(|s| !s.grounded)
+    }
+
+    /// The answer with the action applied to its ungrounded sentences
+    ///
+    /// `answer` must be the text the grounding was checked on.
+    pub fn apply(&self, answer: &str, action: UngroundedAction) -> String {
+        if action == UngroundedAction::Flag {
+            return answer.to_string();
+        }
+
+        let mut out = String::with_capacity(answer.len());
+        let mut end = 0;
+        for (sentence, span) in self.sentences.iter().zip(&self.spans) {
+            if sentence.grounded {
+                continue;
+            }
+            if action == UngroundedAction::Annotate {
+                out.push_str(&answer[end..span.end]);
+                out.push(' ');
+                out.push_str(UNGROUNDED_MARKER);
+                end = span.end;
+            } else {
+                out.push_str(&answer[end..span.start]);
+                // The whitespace after a removed sentence goes with it
+                let rest = &answer[span.end..];
+                end = span.end + (rest.len() - rest.trim_start().len());
+            }
+        }
+        out.push_str(&answer[end..]);
+        out.trim_end().to_string()
+    }
+}
+
+/// Check every sentence of an answer against the excerpts of its sources
+///
+/// Sentences and excerpts are embedded in one batch. Sentences without terms
+/// (list markers, stray punctuation) are not checked. With no sources every
+/// sentence is ungrounded.
+pub fn check_grounding<E: Embedder + ?Sized>(
+    answer: &str,
+    sources: &[SourceReference],
+    embedder: &E,
+    policy: &GroundingPolicy,
+) -> Result<AnswerGrounding> {
+    let spans: Vec<Range<usize>> = split_sentences(answer)
+        .into_iter()
+        .filter(|span| !terms(&answer[span.clone()]).is_empty())
+        .collect();
+    if spans.is_empty() {
+        return Ok(AnswerGrounding::default());
+    }
+
+    let texts: Vec<&str> = spans
+        .iter()
+        .map(|span| &answer[span.clone()])
+        .chain(sources.iter().map(|s| s.excerpt.as_str()))
+        .collect();
+    let vectors = embed_checked(embedder, &texts, false)?;
+    let (sentence_vectors, excerpt_vectors) = vectors.split_at(spans.len());
+    let excerpt_terms: Vec<HashSet<String>> = sources.iter().map(|s| terms(&s.excerpt)).collect();
+
+    let sentences = spans
+        .iter()
+        .zip(sentence_vectors)
+        .map(|(span, vector)| {
+            let sentence = &answer[span.clone()];
+            let mut similarity = 0.0f32;
+            let mut chunk_id = None;
+            for (source, excerpt) in sources.iter().zip(excerpt_vectors) {
+                if let (Some(a), Some(b)) = (vector, excerpt) {
+                    let score = cosine(a, b);
+                    if chunk_id.is_none() || score > similarity {
+                        similarity = score;
+                        chunk_id = Some(source.chunk_id);
+                    }
+                }
+            }
+
+            let sentence_terms = terms(sentence);
+            let overlap = excerpt_terms
+                .iter()
+                .map(|excerpt| {
+                    let shared = sentence_terms.iter().filter(|t| excerpt.contains(*t)).count();
+                    shared as f32 / sentence_terms.len() as f32
+                })
+                .fold(0.0f32, f32::max);
+
+            SentenceGrounding {
+                sentence: sentence.to_string(),
+                similarity,
+                overlap,
+                chunk_id,
+                grounded: similarity >= policy.min_similarity || overlap >= policy.min_overlap,
+            }
+        })
+        .collect();
+
+    Ok(AnswerGrounding { sentences, spans })
+}
+
+/// Byte ranges of the sentences of a text, without surrounding whitespace
+///
+/// A sentence ends at `.`, `!` or `?` followed by whitespace or the end of
+/// the text, and at a line break.
+pub fn split_sentences(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let end = match c {
+            '\n' => Some(index),
+            '.' | '!' | '?' => match chars.peek() {
+                None => Some(index + 1),
+                Some((_, next)) if next.is_whitespace() => Some(index + 1),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(end) = end {
+            push_trimmed(text, start..end, &mut spans);
+            start = end;
+        }
+    }
+    push_trimmed(text, start..text.len(), &mut spans);
+    spans
+}
+
+fn push_trimmed(text: &str, span: Range<usize>, spans: &mut Vec<Range<usize>>) {
+    let part = &text[span.clone()];
+    let trimmed = part.trim_start();
+    let start = span.start + (part.len() - trimmed.len());
+    let end = start + trimmed.trim_end().len();
+    if end > start {
+        spans.push(start..end);
+    }
+}
+
+/// Distinct lowercased terms of at least three characters
+fn terms(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(text: &str) -> Vec<&str> {
+        split_sentences(text).into_iter().map(|span| &text[span]).collect()
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            sentences("The gauge is at 6.2 m. Is it rising? Yes!\n[1] River station"),
+            vec!["The gauge is at 6.2 m.", "Is it rising?", "Yes!", "[1] River station"]
+        );
+        assert!(sentences("  \n ").is_empty());
+    }
+
+    fn grounding(grounded: &[bool], answer: &str) -> AnswerGrounding {
+        let spans = split_sentences(answer);
+        let sentences = spans
+            .iter()
+            .zip(grounded)
+            .map(|(span, grounded)| SentenceGrounding {
+                sentence: answer[span.clone()].to_string(),
+                similarity: 0.0,
+                overlap: 0.0,
+                chunk_id: None,
+                grounded: *grounded,
+            })
+            .collect();
+        AnswerGrounding { sentences, spans }
+    }
+
+    #[test]
+    fn test_ungrounded_sentences_are_annotated_or_stripped() {
+        let answer = "Dikes protect the coast. Pumps run on solar power. Gates close at high tide.";
+        let checked = grounding(&[true, false, true], answer);
+
+        assert_eq!(checked.apply(answer, UngroundedAction::Flag), answer);
+        assert_eq!(
+            checked.apply(answer, UngroundedAction::Annotate),
+            "Dikes protect the coast. Pumps run on solar power. [unsupported] Gates close at high tide."
+        );
+        assert_eq!(
+            checked.apply(answer, UngroundedAction::Strip),
+            "Dikes protect the coast. Gates close at high tide."
+        );
+
+        let checked = grounding(&[true, false], "Dikes protect the coast.\nPumps run on solar.");
+        assert_eq!(
+            checked.apply("Dikes protect the coast.\nPumps run on solar.", UngroundedAction::Strip),
+            "Dikes protect the coast."
+        );
+    }
+
+    #[test]
+    fn test_ungrounded_action_parses() {
+        assert_eq!("Strip".parse::<UngroundedAction>(), Ok(UngroundedAction::Strip));
+        assert_eq!(UngroundedAction::Annotate.to_string(), "annotate");
+        assert!("remove".parse::<UngroundedAction>().is_err());
+    }
+}
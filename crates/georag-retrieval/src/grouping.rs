@@ -0,0 +1,393 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use georag_core::models::FeatureId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::{ScoreDistribution, SourceReference};
+
+/// Key of the bucket for sources without a usable timestamp
+pub const UNDATED_BUCKET: &str = "undated";
+
+/// Number of top sources kept per bucket
+const TOP_SOURCES_PER_BUCKET: usize = 3;
+
+/// Interval used to bucket timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeInterval {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeInterval {
+    /// Bucket key and bucket start for a timestamp
+    fn bucket(&self, timestamp: &DateTime<Utc>) -> (String, DateTime<Utc>) {
+        let date = timestamp.date_naive();
+        let (key, start) = match self {
+            TimeInterval::Day => (date.format("%Y-%m-%d").to_string(), date),
+            TimeInterval::Week => {
+                let week = date.iso_week();
+                let start =
+                    NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Mon)
+                        .unwrap_or(date);
+                (format!("{}-W{:02}", week.year(), week.week()), start)
+            }
+            TimeInterval::Month => (
+                date.format("%Y-%m").to_string(),
+                NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap_or(date),
+            ),
+            TimeInterval::Year => (
+                date.year().to_string(),
+                NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap_or(date),
+            ),
+        };
+
+        let start = Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap_or_default());
+        (key, start)
+    }
+}
+
+impl fmt::Display for TimeInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TimeInterval::Day => "day",
+            TimeInterval::Week => "week",
+            TimeInterval::Month => "month",
+            TimeInterval::Year => "year",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for TimeInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(TimeInterval::Day),
+            "week" => Ok(TimeInterval::Week),
+            "month" => Ok(TimeInterval::Month),
+            "year" => Ok(TimeInterval::Year),
+            _ => Err(format!("Invalid time interval '{}': expected day, week, month or year", s)),
+        }
+    }
+}
+
+/// Group ranked sources by a timestamp property of their features
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeGrouping {
+    /// Feature property holding the timestamp
+    pub property: String,
+
+    /// Bucket size
+    pub interval: TimeInterval,
+}
+
+impl TimeGrouping {
+    /// Create a new time grouping
+    pub fn new(property: impl Into<String>, interval: TimeInterval) -> Self {
+        Self { property: property.into(), interval }
+    }
+}
+
+impl FromStr for TimeGrouping {
+    type Err = String;
+
+    /// Parse `property:interval`, e.g. `reported_at:month`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, interval) = s
+            .rsplit_once(':')
+            .ok_or_else(|| format!("Invalid time grouping '{}': expected PROPERTY:INTERVAL", s))?;
+
+        if property.trim().is_empty() {
+            return Err(format!("Invalid time grouping '{}': property is empty", s));
+        }
+
+        Ok(Self::new(property.trim(), interval.trim().parse()?))
+    }
+}
+
+/// Keep only sources whose features are dated on or after `since`
+///
+/// Dates are read from `property` as [`parse_timestamp`] reads them; sources
+/// without a usable date are dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeFilter {
+    /// Feature property holding the timestamp
+    pub property: String,
+
+    /// Earliest timestamp kept
+    pub since: DateTime<Utc>,
+}
+
+impl TimeFilter {
+    /// Create a new time filter
+    pub fn new(property: impl Into<String>, since: DateTime<Utc>) -> Self {
+        Self { property: property.into(), since }
+    }
+
+    /// Check whether a property value is dated on or after `since`
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        parse_timestamp(value).is_some_and(|timestamp| timestamp >= self.since)
+    }
+}
+
+/// Sources that fall into one time bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeBucket {
+    /// Bucket label (`2024-03-15`, `2024-W11`, `2024-03`, `2024` or `undated`)
+    pub key: String,
+
+    /// Start of the bucket (None for the undated bucket)
+    pub start: Option<DateTime<Utc>>,
+
+    /// Number of sources in the bucket
+    pub count: usize,
+
+    /// Highest ranked sources in the bucket
+    pub top_sources: Vec<SourceReference>,
+
+    /// Scores of the sources in the bucket
+    pub scores: Option<ScoreDistribution>,
+}
+
+/// Parse a property value as a timestamp
+///
+/// Accepts RFC 3339 strings, `YYYY-MM-DD[ HH:MM:SS]` dates and Unix epoch
+/// numbers (seconds, or milliseconds for large values).
+pub fn parse_timestamp(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    match value {
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                return Some(dt.with_timezone(&Utc));
+            }
+            for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y/%m/%d %H:%M:%S"] {
+                if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+                    return Some(Utc.from_utc_datetime(&dt));
+                }
+            }
+            for format in ["%Y-%m-%d", "%Y/%m/%d"] {
+                if let Ok(date) = NaiveDate::parse_from_str(s, format) {
+                    return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+                }
+            }
+            None
+        }
+        serde_json::Value::Number(n) => {
+            let epoch = n.as_i64()?;
+            // Values this large are milliseconds (year 5138+ in seconds)
+            if epoch.abs() >= 100_000_000_000 {
+                DateTime::from_timestamp_millis(epoch)
+            } else {
+                DateTime::from_timestamp(epoch, 0)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Bucket ranked sources by the timestamps of their features
+///
+/// Buckets are ordered chronologically with the undated bucket last. Sources
+/// keep their ranking order within a bucket.
+pub fn group_sources_by_time(
+    sources: &[SourceReference],
+    timestamps: &HashMap<FeatureId, DateTime<Utc>>,
+    interval: TimeInterval,
+) -> Vec<TimeBucket> {
+    let mut dated: BTreeMap<DateTime<Utc>, (String, Vec<&SourceReference>)> = BTreeMap::new();
+    let mut undated = Vec::new();
+
+    for source in sources {
+        match source.feature_id.and_then(|id| timestamps.get(&id)) {
+            Some(timestamp) => {
+                let (key, start) = interval.bucket(timestamp);
+                dated.entry(start).or_insert_with(|| (key, Vec::new())).1.push(source);
+            }
+            None => undated.push(source),
+        }
+    }
+
+    let mut buckets: Vec<TimeBucket> = dated
+        .into_iter()
+        .map(|(start, (key, members))| to_bucket(key, Some(start), &members))
+        .collect();
+
+    if !undated.is_empty() {
+        buckets.push(to_bucket(UNDATED_BUCKET.to_string(), None, &undated));
+    }
+
+    buckets
+}
+
+fn to_bucket(
+    key: String,
+    start: Option<DateTime<Utc>>,
+    members: &[&SourceReference],
+) -> TimeBucket {
+    let scores: Vec<f32> = members.iter().map(|s| s.score).collect();
+    TimeBucket {
+        key,
+        start,
+        count: members.len(),
+        top_sources: members.iter().take(TOP_SOURCES_PER_BUCKET).map(|s| (*s).clone()).collect(),
+        scores: ScoreDistribution::from_scores(&scores),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use georag_core::models::ChunkId;
+    use serde_json::json;
+
+    fn at(date: &str) -> DateTime<Utc> {
+        parse_timestamp(&json!(date)).unwrap()
+    }
+
+    fn source(id: u64, feature: Option<u64>, score: f32) -> SourceReference {
+        SourceReference {
+            chunk_id: ChunkId(id),
+            feature_id: feature.map(FeatureId),
+            document_path: "/data/reports.geojson".to_string(),
+            page: None,
+            offset: None,
+            excerpt: format!("report {}", id),
+            score,
+            spatial_match: None,
+            source_url: None,
+            confidence: None,
+        }
+    }
+
+    /// Bucket keys and counts of sources dated `dates`, in ranking order
+    fn keys(dates: &[&str], interval: TimeInterval) -> Vec<(String, usize)> {
+        let sources: Vec<SourceReference> =
+            (0..dates.len() as u64).map(|id| source(id, Some(id), 1.0)).collect();
+        let timestamps: HashMap<FeatureId, DateTime<Utc>> = dates
+            .iter()
+            .enumerate()
+            .map(|(id, date)| (FeatureId(id as u64), at(date)))
+            .collect();
+        group_sources_by_time(&sources, &timestamps, interval)
+            .into_iter()
+            .map(|bucket| (bucket.key, bucket.count))
+            .collect()
+    }
+
+    #[test]
+    fn test_iso_weeks_roll_over_the_year() {
+        // 2020-12-31 is a Thursday in week 53, which runs until Sunday 2021-01-03
+        let (key, start) = TimeInterval::Week.bucket(&at("2020-12-31"));
+        assert_eq!(key, "2020-W53");
+        assert_eq!(start, at("2020-12-28"));
+        assert_eq!(TimeInterval::Week.bucket(&at("2021-01-03")).0, "2020-W53");
+        assert_eq!(TimeInterval::Week.bucket(&at("2021-01-04")).0, "2021-W01");
+
+        // Monday 2018-12-31 already starts week 1 of 2019
+        let (key, start) = TimeInterval::Week.bucket(&at("2018-12-31T23:59:59Z"));
+        assert_eq!(key, "2019-W01");
+        assert_eq!(start, at("2018-12-31"));
+
+        assert_eq!(
+            keys(&["2021-01-04", "2020-12-31", "2021-01-03"], TimeInterval::Week),
+            vec![("2020-W53".to_string(), 2), ("2021-W01".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn test_month_and_year_buckets() {
+        let (key, start) = TimeInterval::Month.bucket(&at("2024-03-31T23:00:00Z"));
+        assert_eq!(key, "2024-03");
+        assert_eq!(start, at("2024-03-01"));
+        let (key, start) = TimeInterval::Year.bucket(&at("2024-12-31"));
+        assert_eq!(key, "2024");
+        assert_eq!(start, at("2024-01-01"));
+
+        let dates = ["2024-04-01", "2024-03-15", "2023-12-31", "2024-03-31"];
+        assert_eq!(
+            keys(&dates, TimeInterval::Month),
+            vec![
+                ("2023-12".to_string(), 1),
+                ("2024-03".to_string(), 2),
+                ("2024-04".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            keys(&dates, TimeInterval::Year),
+            vec![("2023".to_string(), 1), ("2024".to_string(), 3)]
+        );
+        assert_eq!(keys(&dates, TimeInterval::Day).len(), 4);
+    }
+
+    #[test]
+    fn test_sources_without_a_usable_timestamp_are_undated() {
+        let sources = vec![
+            source(1, Some(1), 0.9),
+            source(2, Some(2), 0.8),
+            source(3, None, 0.7),
+            source(4, Some(4), 0.6),
+        ];
+        // Feature 2 has an unparseable date and feature 4 none, so neither gets a timestamp
+        let timestamps: HashMap<FeatureId, DateTime<Utc>> =
+            [(1, json!("2024-03-15")), (2, json!("last spring"))]
+                .into_iter()
+                .filter_map(|(id, value)| parse_timestamp(&value).map(|ts| (FeatureId(id), ts)))
+                .collect();
+
+        let buckets = group_sources_by_time(&sources, &timestamps, TimeInterval::Month);
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].key, "2024-03");
+        assert_eq!(buckets[1].key, UNDATED_BUCKET);
+        assert_eq!(buckets[1].start, None);
+        assert_eq!(buckets[1].count, 3);
+        let ranked: Vec<ChunkId> =
+            buckets[1].top_sources.iter().map(|source| source.chunk_id).collect();
+        assert_eq!(ranked, vec![ChunkId(2), ChunkId(3), ChunkId(4)]);
+
+        assert!(group_sources_by_time(&[], &timestamps, TimeInterval::Day).is_empty());
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let noon = Utc.with_ymd_and_hms(2024, 3, 15, 12, 30, 0).unwrap();
+        for value in [
+            json!("2024-03-15T12:30:00Z"),
+            json!("2024-03-15T19:30:00+07:00"),
+            json!("2024-03-15 12:30:00"),
+            json!("2024-03-15T12:30:00"),
+            json!("2024/03/15 12:30:00"),
+            json!(" 2024-03-15T12:30:00Z "),
+        ] {
+            assert_eq!(parse_timestamp(&value), Some(noon), "{}", value);
+        }
+
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap();
+        for value in [
+            json!("2024-03-15"),
+            json!("2024/03/15"),
+            json!(1_710_460_800),
+            json!(1_710_460_800_000_i64),
+        ] {
+            assert_eq!(parse_timestamp(&value), Some(midnight), "{}", value);
+        }
+
+        for value in [
+            json!("15/03/2024"),
+            json!("2024-13-01"),
+            json!("last spring"),
+            json!(""),
+            json!(1.5),
+            json!(true),
+            json!(null),
+            json!(["2024-03-15"]),
+        ] {
+            assert_eq!(parse_timestamp(&value), None, "{}", value);
+        }
+    }
+}
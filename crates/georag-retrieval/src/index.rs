@@ -1,16 +1,26 @@
 use chrono::Utc;
-use georag_core::error::Result;
+use georag_core::error::{GeoragError, Result};
 use georag_core::geo::models::{Crs, ValidityMode};
 use georag_core::geo::validation::validate_geometry;
 use georag_core::llm::Embedder;
 use georag_core::models::{
-    DatasetMeta, Embedding, IndexState, SpatialFilter, SpatialMetadata, SpatialPredicate, TextChunk,
+    BuildCheckpoint, BuildDiff, ChunkId, DatasetId, DatasetMeta, Embedding, FeatureId,
+    IndexContents, IndexState, PreviousIndex, ScoreBaseline, SpatialFilter, SpatialMetadata,
+    SpatialPredicate, TextChunk, UsageDelta, WorkspaceQuotas, WorkspaceUsage, BASELINE_SEED,
+    DEFAULT_BASELINE_PAIRS, INDEX_SCHEMA_VERSION,
 };
-use georag_core::processing::chunk::ChunkGenerator;
-use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use georag_core::processing::chunk::{is_meaningful, ChunkGenerator};
+use georag_core::processing::tokenizer::{TokenLimits, Tokenizer};
+use georag_core::progress::PhaseRate;
+use georag_core::resources::{ResourceGate, ResourceKind};
+use georag_store::ports::{CheckpointStore, DocumentStore, SpatialStore, VectorStore};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::embedding::embed_checked;
 
 /// Progress information for index building
 #[derive(Debug, Clone)]
@@ -42,6 +52,17 @@ where
     embedder: E,
     workspace_crs: Crs,
     batch_size: usize,
+    chunk_properties: Vec<String>,
+    checkpoints: Option<Arc<dyn CheckpointStore>>,
+    checkpoint_every: usize,
+    resume: bool,
+    rate_limit: Option<f64>,
+    chunk_quota: Option<(WorkspaceQuotas, WorkspaceUsage)>,
+    check_order: bool,
+    previous: Option<PreviousIndex>,
+    token_limits: Option<TokenLimits>,
+    input_limit: Option<(Arc<dyn Tokenizer>, usize)>,
+    resource_gate: Option<Arc<dyn ResourceGate>>,
 }
 
 impl<E> IndexBuilder<E>
@@ -63,6 +84,17 @@ where
             embedder,
             workspace_crs,
             batch_size: 32,
+            chunk_properties: Vec::new(),
+            checkpoints: None,
+            checkpoint_every: 10,
+            resume: false,
+            rate_limit: None,
+            chunk_quota: None,
+            check_order: false,
+            previous: None,
+            token_limits: None,
+            input_limit: None,
+            resource_gate: None,
         }
     }
 
@@ -72,6 +104,93 @@ where
         self
     }
 
+    /// Set the feature properties copied into chunk metadata during a rebuild
+    pub fn with_chunk_properties(mut self, properties: Vec<String>) -> Self {
+        self.chunk_properties = properties;
+        self
+    }
+
+    /// Save a build checkpoint every `every_batches` embedded batches
+    ///
+    /// With checkpoints enabled, a rebuild stores chunks before embedding and
+    /// stores embeddings batch by batch, so an interrupted build can resume.
+    pub fn with_checkpoints(
+        mut self,
+        store: Arc<dyn CheckpointStore>,
+        every_batches: usize,
+    ) -> Self {
+        self.checkpoints = Some(store);
+        self.checkpoint_every = every_batches.max(1);
+        self
+    }
+
+    /// Continue the build recorded in the checkpoint store, if any
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Limit embedding requests to `per_second` embeddings per second
+    pub fn with_rate_limit(mut self, per_second: f64) -> Self {
+        self.rate_limit = Some(per_second).filter(|rate| *rate > 0.0);
+        self
+    }
+
+    /// Refuse a rebuild that would take the workspace over its chunk quota
+    ///
+    /// `usage` is the workspace's usage before the build. The check runs once
+    /// the chunks are generated and before existing chunks are cleared, so a
+    /// refused rebuild leaves the current index in place.
+    pub fn with_chunk_quota(mut self, quotas: WorkspaceQuotas, usage: WorkspaceUsage) -> Self {
+        self.chunk_quota = Some((quotas, usage));
+        self
+    }
+
+    /// Embed the last chunk of each batch again on its own to catch answers in the wrong order
+    ///
+    /// Answers with the wrong number or size of vectors are always caught;
+    /// an answer in the wrong order only shows against a known vector, which
+    /// costs one more embedding request per batch.
+    pub fn with_order_check(mut self, check_order: bool) -> Self {
+        self.check_order = check_order;
+        self
+    }
+
+    /// Size chunks in model tokens instead of words during a rebuild
+    ///
+    /// The tokenizer's name is recorded in the index state, since it decides
+    /// where chunks end.
+    pub fn with_token_limits(mut self, limits: TokenLimits) -> Self {
+        self.token_limits = Some(limits);
+        self
+    }
+
+    /// Truncate chunks to `max_tokens` tokens of `tokenizer` before embedding them
+    ///
+    /// Embedders cut longer input silently, so the vector would describe
+    /// only the start of the chunk; the stored chunk keeps its full text.
+    pub fn with_input_limit(mut self, tokenizer: Arc<dyn Tokenizer>, max_tokens: usize) -> Self {
+        self.input_limit = Some((tokenizer, max_tokens.max(1)));
+        self
+    }
+
+    /// Take an embedding batch slot of `gate` for each batch sent to the embedder
+    ///
+    /// Caps the batches embedded at once across the builds sharing the gate.
+    pub fn with_resource_gate(mut self, gate: Arc<dyn ResourceGate>) -> Self {
+        self.resource_gate = Some(gate);
+        self
+    }
+
+    /// Compare the built index with the index it replaces
+    ///
+    /// `previous` is what was read of the last build's state before this
+    /// build started; [`Self::diff`] reports the changes against it.
+    pub fn with_previous_index(mut self, previous: PreviousIndex) -> Self {
+        self.previous = Some(previous);
+        self
+    }
+
     /// Build the index from existing chunks (legacy behavior)
     ///
     /// This performs the following steps:
@@ -111,10 +230,13 @@ where
         });
 
         let chunks = self.document_store.list_chunk_ids().await?;
-        let chunk_data = self.document_store.get_chunks(&chunks).await?;
+        let (chunk_data, skipped) = drop_degenerate(self.document_store.get_chunks(&chunks).await?);
         result.chunk_count = chunk_data.len();
+        result.chunks_skipped = skipped;
 
-        let embeddings = self.generate_embeddings_with_progress(&chunk_data, &mut progress).await?;
+        let embeddings = self
+            .generate_embeddings_with_progress(&chunk_data, &mut result, &mut progress)
+            .await?;
         result.embedding_dim = self.embedder.dimensions();
 
         progress(IndexProgress {
@@ -135,6 +257,7 @@ where
 
         let hash = self.generate_index_hash(&chunk_data, &embeddings).await?;
         result.index_hash = hash;
+        result.score_baseline = score_baseline(&embeddings);
 
         Ok(result)
     }
@@ -147,6 +270,11 @@ where
     /// 3. Generate embeddings
     /// 4. Store everything
     /// 5. Return IndexState
+    ///
+    /// With checkpoints enabled and resume requested, an interrupted build is
+    /// continued instead: nothing is cleared, chunks whose embeddings are
+    /// already stored are skipped, and the checkpoint is removed once the
+    /// build completes.
     pub async fn full_rebuild<F>(
         &self,
         datasets: &[DatasetMeta],
@@ -156,61 +284,278 @@ where
     where
         F: FnMut(IndexProgress),
     {
+        let started = Instant::now();
         let mut result = IndexBuildResult::default();
 
-        // Phase 1: Clear existing data if force
-        if force {
+        // Phase 0: Pick up the checkpoint of an interrupted build
+        let resumed = match (&self.checkpoints, self.resume) {
+            (Some(store), true) => store.load_checkpoint().await?,
+            _ => None,
+        };
+        if let Some(checkpoint) = &resumed {
+            if checkpoint.embedder != self.embedder.model_name() {
+                return Err(GeoragError::ConfigInvalid {
+                    key: "embedder".to_string(),
+                    reason: format!(
+                        "The interrupted build used embedder '{}', not '{}'. Resume with the same embedder or rebuild with --force",
+                        checkpoint.embedder,
+                        self.embedder.model_name()
+                    ),
+                });
+            }
+            progress(IndexProgress {
+                phase: IndexPhase::Initializing,
+                current: 0,
+                total: 1,
+                message: format!(
+                    "Resuming build {} ({}/{} chunks embedded)",
+                    checkpoint.generation, checkpoint.embedded_chunks, checkpoint.total_chunks
+                ),
+            });
+        }
+
+        // Phase 1: Clear existing data if force (deferred until the chunk quota is checked)
+        let clear = force && resumed.is_none();
+        if clear {
             progress(IndexProgress {
                 phase: IndexPhase::Initializing,
                 current: 0,
                 total: 1,
                 message: "Clearing existing data".to_string(),
             });
+        }
+
+        // Phase 2: Generate chunks from datasets
+        let all_chunks = self.generate_chunks(datasets, &mut result, &mut progress).await?;
+        result.chunk_count = all_chunks.len();
+
+        if let Some((quotas, usage)) = &self.chunk_quota {
+            let delta = UsageDelta {
+                chunks: all_chunks.len() as i64 - usage.chunks as i64,
+                ..Default::default()
+            };
+            quotas.check(usage, &delta)?;
+        }
 
+        if clear {
             // Clear existing chunks and embeddings
             let chunk_ids = self.document_store.list_chunk_ids().await?;
             if !chunk_ids.is_empty() {
                 self.vector_store.delete_embeddings(&chunk_ids).await?;
                 self.document_store.delete_chunks(&chunk_ids).await?;
             }
+            if let Some(store) = &self.checkpoints {
+                store.clear_checkpoint().await?;
+            }
         }
 
-        // Phase 2: Generate chunks from datasets
+        let embeddings = if let Some(store) = &self.checkpoints {
+            // Phase 3/4: Store chunks up front, then embed and store batch by batch
+            progress(IndexProgress {
+                phase: IndexPhase::StoringData,
+                current: 0,
+                total: 1,
+                message: "Storing chunks".to_string(),
+            });
+
+            self.document_store.store_chunks(&all_chunks).await?;
+
+            let mut checkpoint = match &resumed {
+                Some(previous) => BuildCheckpoint {
+                    attempts: previous.attempts + 1,
+                    total_chunks: all_chunks.len(),
+                    batches_completed: 0,
+                    ..previous.clone()
+                },
+                None => BuildCheckpoint::new(self.embedder.model_name(), all_chunks.len()),
+            };
+            store.save_checkpoint(&checkpoint).await?;
+            let embeddings = self
+                .embed_with_checkpoints(
+                    store.as_ref(),
+                    &all_chunks,
+                    &mut checkpoint,
+                    started,
+                    &mut result,
+                    &mut progress,
+                )
+                .await?;
+            store.clear_checkpoint().await?;
+            embeddings
+        } else {
+            // Phase 3: Generate embeddings
+            let embeddings = self
+                .generate_embeddings_with_progress(
+                    &all_chunks,
+                    &mut result.unembedded_chunks,
+                    &mut progress,
+                )
+                .await?;
+
+            // Phase 4: Store chunks and embeddings
+            progress(IndexProgress {
+                phase: IndexPhase::StoringData,
+                current: 0,
+                total: 2,
+                message: "Storing chunks".to_string(),
+            });
+
+            self.document_store.store_chunks(&all_chunks).await?;
+
+            progress(IndexProgress {
+                phase: IndexPhase::StoringData,
+                current: 1,
+                total: 2,
+                message: "Storing embeddings".to_string(),
+            });
+
+            self.vector_store.store_embeddings(&embeddings).await?;
+            embeddings
+        };
+        result.embedding_dim = self.embedder.dimensions();
+
+        // Phase 5: Generate hash
         progress(IndexProgress {
-            phase: IndexPhase::GeneratingChunks,
+            phase: IndexPhase::Finalizing,
             current: 0,
-            total: datasets.len(),
-            message: "Generating chunks from datasets".to_string(),
+            total: 1,
+            message: "Generating index hash".to_string(),
         });
 
-        let chunk_generator = ChunkGenerator::default();
-        let mut all_chunks = Vec::new();
+        let hash = self.generate_index_hash(&all_chunks, &embeddings).await?;
+        result.index_hash = hash;
+        result.score_baseline = score_baseline(&embeddings);
+        result.contents = self.index_contents(embeddings.len()).await?;
+        result.diff = self.diff(&result);
 
-        for (idx, dataset_meta) in datasets.iter().enumerate() {
-            let dataset =
-                self.spatial_store.get_dataset(dataset_meta.id).await?.ok_or_else(|| {
-                    georag_core::error::GeoragError::DatasetNotFound {
-                        name: format!("Dataset {} not found", dataset_meta.id.0),
-                    }
-                })?;
+        let earlier = resumed.as_ref().map_or(0.0, |checkpoint| checkpoint.elapsed_secs);
+        result.wall_time = Duration::from_secs_f64(earlier) + started.elapsed();
+        result.resumed_from = resumed;
 
-            let features = self.spatial_store.get_features_for_dataset(dataset_meta.id).await?;
-            let chunks = chunk_generator.generate_chunks(&dataset, &features);
-            all_chunks.extend(chunks);
+        Ok(result)
+    }
 
-            progress(IndexProgress {
-                phase: IndexPhase::GeneratingChunks,
-                current: idx + 1,
-                total: datasets.len(),
-                message: format!("Processed dataset '{}'", dataset_meta.name),
+    /// Rebuild the chunks of selected datasets and merge them into the existing index
+    ///
+    /// The previous chunks and embeddings of the selected datasets are
+    /// replaced; those of every other dataset are kept. The result counts and
+    /// hashes the combined index. The existing index must have been built
+    /// with the same embedder, since its embeddings are reused as they are.
+    pub async fn rebuild_datasets<F>(
+        &self,
+        datasets: &[DatasetMeta],
+        previous: &IndexState,
+        mut progress: F,
+    ) -> Result<IndexBuildResult>
+    where
+        F: FnMut(IndexProgress),
+    {
+        let started = Instant::now();
+        let mut result = IndexBuildResult::default();
+
+        if previous.embedder != self.embedder.model_name()
+            || previous.embedding_dim != self.embedder.dimensions()
+        {
+            return Err(GeoragError::ConfigInvalid {
+                key: "embedder".to_string(),
+                reason: format!(
+                    "The index was built with embedder '{}' ({} dimensions), not '{}' ({} dimensions). Build the selected datasets with the same embedder or rebuild the whole index with --force",
+                    previous.embedder,
+                    previous.embedding_dim,
+                    self.embedder.model_name(),
+                    self.embedder.dimensions()
+                ),
             });
         }
 
-        result.chunk_count = all_chunks.len();
+        let tokenizer = self.tokenizer_name();
+        if previous.tokenizer != tokenizer {
+            let describe = |name: &Option<String>| match name {
+                Some(name) => format!("tokenizer '{}'", name),
+                None => "word-based sizing".to_string(),
+            };
+            return Err(GeoragError::ConfigInvalid {
+                key: "tokenizer".to_string(),
+                reason: format!(
+                    "The index was chunked with {}, not {}. Chunks of the selected datasets would end in different places; rebuild the whole index with --force",
+                    describe(&previous.tokenizer),
+                    describe(&tokenizer)
+                ),
+            });
+        }
+
+        // Phase 1: Find the chunks the selected datasets contributed so far
+        progress(IndexProgress {
+            phase: IndexPhase::Initializing,
+            current: 0,
+            total: 1,
+            message: "Finding existing chunks of the selected datasets".to_string(),
+        });
+
+        let existing_ids = self.document_store.list_chunk_ids().await?;
+        let existing = self.document_store.get_chunks(&existing_ids).await?;
+        let mut replaced = Vec::new();
+        for dataset_meta in datasets {
+            let Some(dataset) = self.spatial_store.get_dataset(dataset_meta.id).await? else {
+                continue;
+            };
+            let path = dataset.path.to_string_lossy();
+            let feature_ids: HashSet<FeatureId> = self
+                .spatial_store
+                .get_features_for_dataset(dataset_meta.id)
+                .await?
+                .iter()
+                .map(|feature| feature.id)
+                .collect();
+            replaced.extend(
+                existing
+                    .iter()
+                    .filter(|chunk| {
+                        chunk.spatial_ref.is_some_and(|id| feature_ids.contains(&id))
+                            || chunk.source.document_path == path
+                    })
+                    .map(|chunk| chunk.id),
+            );
+        }
+        replaced.sort_by_key(|id| id.0);
+        replaced.dedup();
+
+        // Phase 2: Generate chunks from the selected datasets
+        let new_chunks = self.generate_chunks(datasets, &mut result, &mut progress).await?;
+
+        if let Some((quotas, usage)) = &self.chunk_quota {
+            // Chunks of the datasets that are not rebuilt, from the store's counters
+            let selected: HashSet<DatasetId> = datasets.iter().map(|meta| meta.id).collect();
+            let kept: u64 = self
+                .document_store
+                .dataset_index_stats()
+                .await?
+                .iter()
+                .filter(|(dataset_id, _)| !selected.contains(dataset_id))
+                .map(|(_, stats)| stats.chunks)
+                .sum();
+            let delta = UsageDelta {
+                chunks: (kept + new_chunks.len() as u64) as i64 - usage.chunks as i64,
+                ..Default::default()
+            };
+            quotas.check(usage, &delta)?;
+        }
+
+        if !replaced.is_empty() {
+            progress(IndexProgress {
+                phase: IndexPhase::Initializing,
+                current: 0,
+                total: 1,
+                message: format!("Removing {} outdated chunks", replaced.len()),
+            });
+            self.vector_store.delete_embeddings(&replaced).await?;
+            self.document_store.delete_chunks(&replaced).await?;
+        }
 
         // Phase 3: Generate embeddings
-        let embeddings = self.generate_embeddings_with_progress(&all_chunks, &mut progress).await?;
-        result.embedding_dim = self.embedder.dimensions();
+        let embeddings = self
+            .generate_embeddings_with_progress(&new_chunks, &mut result, &mut progress)
+            .await?;
 
         // Phase 4: Store chunks and embeddings
         progress(IndexProgress {
@@ -220,7 +565,7 @@ where
             message: "Storing chunks".to_string(),
         });
 
-        self.document_store.store_chunks(&all_chunks).await?;
+        self.document_store.store_chunks(&new_chunks).await?;
 
         progress(IndexProgress {
             phase: IndexPhase::StoringData,
@@ -230,8 +575,9 @@ where
         });
 
         self.vector_store.store_embeddings(&embeddings).await?;
+        result.embedding_dim = self.embedder.dimensions();
 
-        // Phase 5: Generate hash
+        // Phase 5: Hash the combined index
         progress(IndexProgress {
             phase: IndexPhase::Finalizing,
             current: 0,
@@ -239,12 +585,218 @@ where
             message: "Generating index hash".to_string(),
         });
 
-        let hash = self.generate_index_hash(&all_chunks, &embeddings).await?;
-        result.index_hash = hash;
+        let chunk_ids = self.document_store.list_chunk_ids().await?;
+        let all_chunks = self.document_store.get_chunks(&chunk_ids).await?;
+        let mut all_embeddings = Vec::with_capacity(all_chunks.len());
+        for chunk in &all_chunks {
+            if let Some(embedding) = self.vector_store.get_embedding(chunk.id).await? {
+                all_embeddings.push(embedding);
+            }
+        }
+
+        result.chunk_count = all_chunks.len();
+        result.index_hash = self.generate_index_hash(&all_chunks, &all_embeddings).await?;
+        result.score_baseline = score_baseline(&all_embeddings);
+        result.contents = self.index_contents(all_embeddings.len()).await?;
+        result.diff = self.diff(&result);
+        result.wall_time = started.elapsed();
 
         Ok(result)
     }
 
+    /// Generate the chunks of each dataset, leaving out chunks without text
+    async fn generate_chunks<F>(
+        &self,
+        datasets: &[DatasetMeta],
+        result: &mut IndexBuildResult,
+        progress: &mut F,
+    ) -> Result<Vec<TextChunk>>
+    where
+        F: FnMut(IndexProgress),
+    {
+        progress(IndexProgress {
+            phase: IndexPhase::GeneratingChunks,
+            current: 0,
+            total: datasets.len(),
+            message: "Generating chunks from datasets".to_string(),
+        });
+
+        let chunking = Instant::now();
+        let mut chunk_generator =
+            ChunkGenerator::default().with_properties(self.chunk_properties.iter().cloned());
+        if let Some(limits) = &self.token_limits {
+            chunk_generator = chunk_generator.with_token_limits(limits.clone());
+        }
+        let mut all_chunks = Vec::new();
+
+        for (idx, dataset_meta) in datasets.iter().enumerate() {
+            let dataset =
+                self.spatial_store.get_dataset(dataset_meta.id).await?.ok_or_else(|| {
+                    georag_core::error::GeoragError::DatasetNotFound {
+                        name: format!("Dataset {} not found", dataset_meta.id.0),
+                    }
+                })?;
+
+            let features = self.spatial_store.get_features_for_dataset(dataset_meta.id).await?;
+            let (chunks, skipped) =
+                drop_degenerate(chunk_generator.generate_chunks(&dataset, &features));
+            result.chunks_skipped += skipped;
+            result.datasets.push(dataset_meta.name.clone());
+            all_chunks.extend(chunks);
+
+            progress(IndexProgress {
+                phase: IndexPhase::GeneratingChunks,
+                current: idx + 1,
+                total: datasets.len(),
+                message: format!("Processed dataset '{}'", dataset_meta.name),
+            });
+        }
+
+        result
+            .throughput
+            .push(PhaseRate::new("chunks", all_chunks.len(), chunking.elapsed()));
+        Ok(all_chunks)
+    }
+
+    /// Embed chunks batch by batch, storing each batch and saving checkpoints
+    ///
+    /// When the checkpoint continues an earlier attempt, chunks whose
+    /// embedding is already in the vector store are not embedded again.
+    async fn embed_with_checkpoints<F>(
+        &self,
+        store: &dyn CheckpointStore,
+        chunks: &[TextChunk],
+        checkpoint: &mut BuildCheckpoint,
+        started: Instant,
+        result: &mut IndexBuildResult,
+        progress: &mut F,
+    ) -> Result<Vec<Embedding>>
+    where
+        F: FnMut(IndexProgress),
+    {
+        let total = chunks.len();
+        let resuming = checkpoint.attempts > 1;
+        let earlier_secs = checkpoint.elapsed_secs;
+        let mut embeddings = Vec::with_capacity(total);
+        let mut pending = Vec::new();
+
+        for chunk in chunks {
+            let existing = if resuming {
+                self.vector_store.get_embedding(chunk.id).await?
+            } else {
+                None
+            };
+            match existing {
+                Some(embedding) if embedding.vector.len() == self.embedder.dimensions() => {
+                    embeddings.push(embedding)
+                }
+                _ => pending.push(chunk.clone()),
+            }
+        }
+
+        checkpoint.embedded_chunks = embeddings.len();
+        if resuming {
+            progress(IndexProgress {
+                phase: IndexPhase::GeneratingEmbeddings,
+                current: embeddings.len(),
+                total,
+                message: format!(
+                    "Skipped {} chunks embedded by earlier attempts",
+                    embeddings.len()
+                ),
+            });
+        }
+
+        let embedding = Instant::now();
+        let mut throttle = self.rate_limit.map(Throttle::new);
+        for (batch_idx, chunk_batch) in pending.chunks(self.batch_size).enumerate() {
+            let batch_embeddings = self
+                .embed_batch(chunk_batch, throttle.as_mut(), &mut result.unembedded_chunks)
+                .await?;
+            self.vector_store.store_embeddings(&batch_embeddings).await?;
+            checkpoint.embedded_chunks += batch_embeddings.len();
+            embeddings.extend(batch_embeddings);
+
+            checkpoint.batches_completed = batch_idx + 1;
+            checkpoint.last_chunk_id = chunk_batch.last().map(|c| c.id);
+            if checkpoint.batches_completed % self.checkpoint_every == 0 {
+                checkpoint.updated_at = Utc::now();
+                checkpoint.elapsed_secs = earlier_secs + started.elapsed().as_secs_f64();
+                store.save_checkpoint(checkpoint).await?;
+            }
+
+            progress(IndexProgress {
+                phase: IndexPhase::GeneratingEmbeddings,
+                current: checkpoint.embedded_chunks,
+                total,
+                message: format!("Generated {}/{} embeddings", checkpoint.embedded_chunks, total),
+            });
+        }
+
+        // Chunks embedded by earlier attempts are left out of the rate
+        result
+            .throughput
+            .push(PhaseRate::new("embeddings", pending.len(), embedding.elapsed()));
+        Ok(embeddings)
+    }
+
+    /// Embed one batch of chunks, waiting for the rate limit first
+    ///
+    /// Chunks the embedder gives no usable vector, even on their own, are
+    /// added to `unembedded` and left out of the index.
+    async fn embed_batch(
+        &self,
+        chunks: &[TextChunk],
+        throttle: Option<&mut Throttle>,
+        unembedded: &mut Vec<ChunkId>,
+    ) -> Result<Vec<Embedding>> {
+        if let Some(throttle) = throttle {
+            throttle.wait(chunks.len()).await;
+        }
+
+        let mut texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+        if let Some((tokenizer, max_tokens)) = &self.input_limit {
+            let cut: Vec<&str> =
+                texts.iter().map(|&text| tokenizer.truncate(text, *max_tokens)).collect();
+            let truncated =
+                cut.iter().zip(&texts).filter(|(cut, text)| cut.len() < text.len()).count();
+            if truncated > 0 {
+                tracing::warn!(
+                    model = self.embedder.model_name(),
+                    tokenizer = %tokenizer.name(),
+                    max_tokens = *max_tokens,
+                    truncated,
+                    "Truncated chunks longer than the embedder's input before embedding"
+                );
+            }
+            texts = cut;
+        }
+        let slot = match &self.resource_gate {
+            Some(gate) => Some(gate.acquire(ResourceKind::EmbeddingBatch).await?),
+            None => None,
+        };
+        let vectors = embed_checked(&self.embedder, &texts, self.check_order)?;
+        drop(slot);
+
+        let mut embeddings = Vec::with_capacity(chunks.len());
+        for (chunk, vector) in chunks.iter().zip(vectors) {
+            let Some(vector) = vector else {
+                unembedded.push(chunk.id);
+                continue;
+            };
+            let spatial_metadata = self.get_spatial_metadata_for_chunk(chunk).await?;
+
+            embeddings.push(Embedding {
+                chunk_id: chunk.id,
+                vector,
+                spatial_metadata,
+                dataset_id: chunk.metadata.dataset_id,
+            });
+        }
+
+        Ok(embeddings)
+    }
+
     /// Normalize all geometries to workspace CRS
     async fn normalize_geometries(&self) -> Result<usize> {
         let features = self
@@ -254,6 +806,8 @@ where
                 geometry: None,
                 distance: None,
                 crs: self.workspace_crs.clone(),
+                buffer: None,
+                exclude: Vec::new(),
             })
             .await?;
 
@@ -277,6 +831,8 @@ where
                 geometry: None,
                 distance: None,
                 crs: self.workspace_crs.clone(),
+                buffer: None,
+                exclude: Vec::new(),
             })
             .await?;
 
@@ -298,6 +854,7 @@ where
     async fn generate_embeddings_with_progress<F>(
         &self,
         chunks: &[TextChunk],
+        result: &mut IndexBuildResult,
         progress: &mut F,
     ) -> Result<Vec<Embedding>>
     where
@@ -307,24 +864,17 @@ where
             return Ok(Vec::new());
         }
 
+        let embedding = Instant::now();
         let total = chunks.len();
         let mut all_embeddings = Vec::with_capacity(total);
+        let mut throttle = self.rate_limit.map(Throttle::new);
 
         // Process in batches
         for (batch_idx, chunk_batch) in chunks.chunks(self.batch_size).enumerate() {
-            let texts: Vec<&str> = chunk_batch.iter().map(|c| c.content.as_str()).collect();
-
-            let vectors = self.embedder.embed(&texts)?;
-
-            for (chunk, vector) in chunk_batch.iter().zip(vectors.into_iter()) {
-                let spatial_metadata = self.get_spatial_metadata_for_chunk(chunk).await?;
-
-                all_embeddings.push(Embedding {
-                    chunk_id: chunk.id,
-                    vector,
-                    spatial_metadata,
-                });
-            }
+            all_embeddings.extend(
+                self.embed_batch(chunk_batch, throttle.as_mut(), &mut result.unembedded_chunks)
+                    .await?,
+            );
 
             let processed = ((batch_idx + 1) * self.batch_size).min(total);
             progress(IndexProgress {
@@ -335,6 +885,7 @@ where
             });
         }
 
+        result.throughput.push(PhaseRate::new("embeddings", total, embedding.elapsed()));
         Ok(all_embeddings)
     }
 
@@ -408,6 +959,55 @@ where
         }
     }
 
+    /// Chunks per dataset and estimated size of the stored index
+    ///
+    /// Read from the per-dataset counters of the document store, so the
+    /// chunks are not scanned again; chunks of a dataset that no longer
+    /// exists are counted in the size only.
+    async fn index_contents(&self, embedding_count: usize) -> Result<IndexContents> {
+        let names: HashMap<DatasetId, String> = self
+            .spatial_store
+            .list_datasets()
+            .await?
+            .into_iter()
+            .map(|meta| (meta.id, meta.name))
+            .collect();
+
+        let stats = self.document_store.dataset_index_stats().await?;
+        let mut dataset_chunks = BTreeMap::new();
+        for (dataset_id, counters) in &stats {
+            if let Some(name) = names.get(dataset_id) {
+                *dataset_chunks.entry(name.clone()).or_default() += counters.chunks as usize;
+            }
+        }
+
+        let text_bytes = stats.values().map(|counters| counters.content_bytes).sum();
+        Ok(IndexContents {
+            dataset_chunks,
+            embedding_count,
+            size_bytes: IndexContents::estimate_size(
+                text_bytes,
+                embedding_count,
+                self.embedder.dimensions(),
+            ),
+        })
+    }
+
+    /// What a build changed, compared with the index given to [`Self::with_previous_index`]
+    ///
+    /// `None` when no previous index was given. Builds set the diff of their
+    /// result; call this again after adjusting the result, e.g. once garbage
+    /// collection removed chunks.
+    pub fn diff(&self, result: &IndexBuildResult) -> Option<BuildDiff> {
+        let previous = self.previous.as_ref()?;
+        Some(BuildDiff::between(previous, &self.create_index_state(result)))
+    }
+
+    /// Name of the tokenizer chunks are sized with, `None` for word-based sizing
+    pub fn tokenizer_name(&self) -> Option<String> {
+        self.token_limits.as_ref().map(|limits| limits.tokenizer.name())
+    }
+
     /// Generate deterministic index hash
     async fn generate_index_hash(
         &self,
@@ -425,6 +1025,12 @@ where
             if let Some(spatial_ref) = &chunk.spatial_ref {
                 spatial_ref.0.hash(&mut hasher);
             }
+            let mut properties: Vec<_> = chunk.metadata.properties.iter().collect();
+            properties.sort();
+            for (name, value) in properties {
+                name.hash(&mut hasher);
+                value.hash(&mut hasher);
+            }
         }
 
         let mut sorted_embeddings = embeddings.to_vec();
@@ -445,13 +1051,23 @@ where
     }
 
     /// Create an IndexState from build results
+    ///
+    /// Only the datasets the build chunked get a build time; merge in the
+    /// times of the previous state after building selected datasets.
     pub fn create_index_state(&self, result: &IndexBuildResult) -> IndexState {
+        let built_at = Utc::now();
         IndexState {
+            schema_version: INDEX_SCHEMA_VERSION,
             hash: result.index_hash.clone(),
-            built_at: Utc::now(),
+            built_at,
             embedder: self.embedder.model_name().to_string(),
             chunk_count: result.chunk_count,
             embedding_dim: result.embedding_dim,
+            dataset_built_at: result.datasets.iter().map(|name| (name.clone(), built_at)).collect(),
+            contents: Some(result.contents.clone()),
+            last_build_diff: result.diff.clone(),
+            tokenizer: self.tokenizer_name(),
+            score_baseline: result.score_baseline.clone(),
         }
     }
 }
@@ -468,9 +1084,83 @@ pub struct IndexBuildResult {
     /// Total number of chunks indexed
     pub chunk_count: usize,
 
+    /// Chunks left out because their content had no letters or digits
+    pub chunks_skipped: usize,
+
+    /// Chunks stored without an embedding because the embedder gave none
+    /// that could be trusted to be theirs; they are not retrieved by similarity
+    pub unembedded_chunks: Vec<ChunkId>,
+
+    /// Names of the datasets the build generated chunks for
+    pub datasets: Vec<String>,
+
     /// Embedding dimension
     pub embedding_dim: usize,
 
     /// Deterministic index hash
     pub index_hash: String,
+
+    /// Checkpoint of the interrupted build this build continued, if any
+    pub resumed_from: Option<BuildCheckpoint>,
+
+    /// Wall time of the build, summed across resumed attempts
+    pub wall_time: Duration,
+
+    /// Average rate of chunk generation and embedding in this attempt
+    pub throughput: Vec<PhaseRate>,
+
+    /// Chunks per dataset, embeddings and estimated size of the built index
+    pub contents: IndexContents,
+
+    /// What the build changed, when the builder was given the previous index
+    pub diff: Option<BuildDiff>,
+
+    /// Similarity scores between random pairs of the index's embeddings
+    pub score_baseline: Option<ScoreBaseline>,
+}
+
+/// Sample the baseline that query scores are compared with
+///
+/// Covers every embedding of the index, so a rebuild of some datasets
+/// samples the combined index again.
+fn score_baseline(embeddings: &[Embedding]) -> Option<ScoreBaseline> {
+    let vectors: Vec<&[f32]> =
+        embeddings.iter().map(|embedding| embedding.vector.as_slice()).collect();
+    ScoreBaseline::sample(&vectors, DEFAULT_BASELINE_PAIRS, BASELINE_SEED)
+}
+
+/// Split off chunks with nothing worth embedding, returning the rest and how many were dropped
+fn drop_degenerate(chunks: Vec<TextChunk>) -> (Vec<TextChunk>, usize) {
+    let total = chunks.len();
+    let kept: Vec<TextChunk> =
+        chunks.into_iter().filter(|chunk| is_meaningful(&chunk.content)).collect();
+    let skipped = total - kept.len();
+    (kept, skipped)
+}
+
+/// Paces embedding requests to a number of embeddings per second
+struct Throttle {
+    per_second: f64,
+    started: Instant,
+    sent: usize,
+}
+
+impl Throttle {
+    fn new(per_second: f64) -> Self {
+        Self {
+            per_second,
+            started: Instant::now(),
+            sent: 0,
+        }
+    }
+
+    /// Wait until the embeddings sent so far fit the rate, then count `count` more
+    async fn wait(&mut self, count: usize) {
+        let due = Duration::from_secs_f64(self.sent as f64 / self.per_second);
+        let elapsed = self.started.elapsed();
+        if due > elapsed {
+            tokio::time::sleep(due - elapsed).await;
+        }
+        self.sent += count;
+    }
 }
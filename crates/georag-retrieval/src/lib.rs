@@ -1,12 +1,34 @@
+pub mod diagnostics;
 pub mod embedding;
+pub mod export;
+pub mod grounding;
+pub mod grouping;
 pub mod index;
+pub mod map;
+pub mod merge;
 pub mod models;
 pub mod pipeline;
+pub mod rerank;
+pub mod timing;
 
-pub use embedding::EmbeddingPipeline;
+pub use diagnostics::{CandidateCounts, Diagnostic, DiagnosticKind};
+pub use embedding::{embed_checked, EmbeddingPipeline};
+pub use export::{GeometryDetail, GeometryOutput, PropertySelection, ResultFormat, ResultRow};
+pub use grounding::{
+    check_grounding, AnswerGrounding, GroundingPolicy, SentenceGrounding, UngroundedAction,
+};
+pub use grouping::{TimeBucket, TimeFilter, TimeGrouping, TimeInterval};
 pub use index::{IndexBuildResult, IndexBuilder, IndexPhase, IndexProgress};
+pub use map::{Basemap, MapFeature, MapOptions, StaticMap, TileId};
+pub use merge::merge_overlapping_sources;
 pub use models::{
-    QueryExplanation, QueryPlan, QueryResult, RankingDetail, SemanticPhaseExplanation,
-    SourceReference, SpatialPhaseExplanation,
+    AppliedOperator, AppliedPreset, AttributeFilter, AttributeFilterExplanation,
+    AttributePhaseExplanation, ConfidenceExplanation, EffectivePlan, ExampleExplanation,
+    ExclusionExplanation, FilterLevel, IgnoredOperator, NamedAreaExpansion, PresetExplanation,
+    QueryExample, QueryExplanation, QueryOperators, QueryPlan, QueryResult, RankingDetail,
+    RerankPhaseExplanation, ScoreDistribution, SeedMode, SemanticPhaseExplanation, SourceReference,
+    SpatialMatch, SpatialPhaseExplanation,
 };
 pub use pipeline::RetrievalPipeline;
+pub use rerank::{LexicalReranker, LlmReranker, RerankCandidate, RerankMode, Reranker};
+pub use timing::{PhaseTiming, QueryTimings, Stopwatch};
@@ -0,0 +1,1000 @@
+//! Static map images of query results
+//!
+//! Reports for readers without a GIS need a picture of where the results are.
+//! A `StaticMap` projects result geometries to Web Mercator, fits the view to
+//! their extent and draws them styled by score, with a scale bar and the
+//! attribution lines, into a PNG. Rendering needs no network: a basemap is
+//! drawn only from tiles the caller fetched for `StaticMap::tiles`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::f64::consts::PI;
+use std::ops::Range;
+
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::Geometry;
+use tiny_skia::{
+    Color, FillRule, LineCap, LineJoin, Paint, Path, PathBuilder, Pixmap, PixmapPaint, Rect,
+    Stroke, Transform,
+};
+
+/// Edge length of a basemap tile in pixels
+pub const TILE_SIZE: u32 = 256;
+
+/// Highest zoom level a view is fitted to
+pub const MAX_ZOOM: u8 = 18;
+
+/// Zoom level of a view around a single point
+pub const POINT_ZOOM: u8 = 15;
+
+/// Latitude limit of Web Mercator
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Equatorial circumference of the WGS 84 ellipsoid in meters
+const EARTH_CIRCUMFERENCE: f64 = 40_075_016.686;
+
+/// Widest the scale bar is drawn, in pixels
+const MAX_SCALE_BAR: f64 = 120.0;
+
+const BACKGROUND: [u8; 3] = [238, 240, 236];
+const TEXT: [u8; 3] = [40, 40, 40];
+
+/// Colors of the lowest and highest scored results
+const LOW_SCORE: [u8; 3] = [254, 204, 92];
+const HIGH_SCORE: [u8; 3] = [189, 0, 38];
+
+/// Size of a rendered map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapOptions {
+    /// Image width in pixels
+    pub width: u32,
+
+    /// Image height in pixels
+    pub height: u32,
+
+    /// Margin kept between the result extent and the image edges, in pixels
+    ///
+    /// At most a quarter of the shorter side is used.
+    pub padding: u32,
+}
+
+impl MapOptions {
+    /// Smallest width or height accepted
+    pub const MIN_SIZE: u32 = 64;
+
+    /// Largest width or height accepted
+    pub const MAX_SIZE: u32 = 4096;
+
+    /// Options for an image of the given size with the default padding
+    pub fn new(width: u32, height: u32) -> std::result::Result<Self, String> {
+        for (name, value) in [("width", width), ("height", height)] {
+            if !(Self::MIN_SIZE..=Self::MAX_SIZE).contains(&value) {
+                return Err(format!(
+                    "Map {} must be between {} and {} pixels, got {}",
+                    name,
+                    Self::MIN_SIZE,
+                    Self::MAX_SIZE,
+                    value
+                ));
+            }
+        }
+        Ok(Self { width, height, ..Self::default() })
+    }
+}
+
+impl Default for MapOptions {
+    fn default() -> Self {
+        Self { width: 800, height: 600, padding: 40 }
+    }
+}
+
+/// XYZ tile source drawn under the results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basemap {
+    /// Tile URL with `{z}`, `{x}` and `{y}` placeholders
+    pub url_template: String,
+
+    /// Attribution line the tile provider requires
+    pub attribution: Option<String>,
+}
+
+impl Basemap {
+    /// Create a basemap from a tile URL template
+    pub fn new(url_template: impl Into<String>) -> Self {
+        Self {
+            url_template: url_template.into(),
+            attribution: None,
+        }
+    }
+
+    /// Set the attribution line shown on maps drawn over the tiles
+    pub fn with_attribution(mut self, attribution: impl Into<String>) -> Self {
+        self.attribution = Some(attribution.into());
+        self
+    }
+
+    /// URL of a tile
+    pub fn tile_url(&self, tile: TileId) -> String {
+        self.url_template
+            .replace("{z}", &tile.z.to_string())
+            .replace("{x}", &tile.x.to_string())
+            .replace("{y}", &tile.y.to_string())
+    }
+}
+
+/// Address of a basemap tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileId {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+/// A result geometry and the score it is styled by
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapFeature {
+    /// Geometry in WGS 84 longitude/latitude
+    pub geometry: Geometry,
+
+    /// Relevance score of the result
+    pub score: f32,
+}
+
+/// A map of query results, fitted to their extent
+#[derive(Debug, Clone)]
+pub struct StaticMap {
+    features: Vec<MapFeature>,
+    attributions: Vec<String>,
+    options: MapOptions,
+    view: View,
+}
+
+impl StaticMap {
+    /// Fit a map of the given size to the features
+    ///
+    /// Features with non-finite coordinates are left out. Without features
+    /// the map shows the whole world.
+    pub fn new(features: Vec<MapFeature>, options: MapOptions) -> Self {
+        let mut features: Vec<MapFeature> = features
+            .into_iter()
+            .filter(|feature| {
+                let mut finite = true;
+                for_each_coordinate(&feature.geometry, &mut |[x, y]| {
+                    finite &= x.is_finite() && y.is_finite();
+                });
+                finite
+            })
+            .collect();
+        arrange_longitudes(&mut features);
+        let view = View::fit(&features, &options);
+
+        Self {
+            features,
+            attributions: Vec::new(),
+            options,
+            view,
+        }
+    }
+
+    /// Set the attribution lines drawn in the bottom-right corner
+    pub fn with_attributions(mut self, attributions: Vec<String>) -> Self {
+        self.attributions = attributions;
+        self
+    }
+
+    /// Zoom level the view was fitted to
+    pub fn zoom(&self) -> u8 {
+        self.view.zoom
+    }
+
+    /// Basemap tiles covering the view, once each
+    ///
+    /// Column numbers wrap around the antimeridian, so a view wider than the
+    /// world reuses its tiles.
+    pub fn tiles(&self) -> Vec<TileId> {
+        let (columns, rows) = self.view.tile_range();
+        let mut tiles = BTreeSet::new();
+        for y in rows {
+            for x in columns.clone() {
+                tiles.insert(self.view.tile_id(x, y));
+            }
+        }
+        tiles.into_iter().collect()
+    }
+
+    /// Render the map as a PNG
+    ///
+    /// `tiles` holds the PNG data of basemap tiles; missing tiles leave the
+    /// background showing and tiles that do not decode are skipped.
+    pub fn render(&self, tiles: &[(TileId, Vec<u8>)]) -> Result<Vec<u8>> {
+        self.draw(tiles)?
+            .encode_png()
+            .map_err(|e| GeoragError::Serialization(format!("Failed to encode map: {}", e)))
+    }
+
+    fn draw(&self, tiles: &[(TileId, Vec<u8>)]) -> Result<Pixmap> {
+        let mut pixmap = Pixmap::new(self.options.width, self.options.height).ok_or_else(|| {
+            GeoragError::Serialization(format!(
+                "Invalid map size {}x{}",
+                self.options.width, self.options.height
+            ))
+        })?;
+        pixmap.fill(color(BACKGROUND, 255));
+
+        self.draw_basemap(&mut pixmap, tiles);
+        self.draw_features(&mut pixmap);
+        if self.features.is_empty() {
+            let label = "No results";
+            let x = (self.options.width as f32 - text_width(label, 2.0)) / 2.0;
+            let y = (self.options.height as f32 - 14.0) / 2.0;
+            draw_label(&mut pixmap, label, x, y, 2.0);
+        }
+        self.draw_scale_bar(&mut pixmap);
+        self.draw_attribution(&mut pixmap);
+
+        Ok(pixmap)
+    }
+
+    fn draw_basemap(&self, pixmap: &mut Pixmap, tiles: &[(TileId, Vec<u8>)]) {
+        if tiles.is_empty() {
+            return;
+        }
+
+        let decoded: HashMap<TileId, Pixmap> = tiles
+            .iter()
+            .filter_map(|(id, data)| match Pixmap::decode_png(data) {
+                Ok(tile) => Some((*id, tile)),
+                Err(e) => {
+                    tracing::warn!(z = id.z, x = id.x, y = id.y, error = %e, "Skipping undecodable map tile");
+                    None
+                }
+            })
+            .collect();
+
+        let (columns, rows) = self.view.tile_range();
+        let [left, top] = self.view.origin();
+        for y in rows {
+            for x in columns.clone() {
+                if let Some(tile) = decoded.get(&self.view.tile_id(x, y)) {
+                    let tile_x = (x as f64 * TILE_SIZE as f64 - left).round() as i32;
+                    let tile_y = (y as f64 * TILE_SIZE as f64 - top).round() as i32;
+                    pixmap.draw_pixmap(
+                        tile_x,
+                        tile_y,
+                        tile.as_ref(),
+                        &PixmapPaint::default(),
+                        Transform::identity(),
+                        None,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Draw the features, lower scores first so the best results are on top
+    fn draw_features(&self, pixmap: &mut Pixmap) {
+        let weights = relative_scores(&self.features);
+        let mut order: Vec<usize> = (0..self.features.len()).collect();
+        order.sort_by(|a, b| self.features[*a].score.total_cmp(&self.features[*b].score));
+
+        for index in order {
+            self.draw_geometry(pixmap, &self.features[index].geometry, weights[index]);
+        }
+    }
+
+    fn draw_geometry(&self, pixmap: &mut Pixmap, geometry: &Geometry, weight: f32) {
+        let rgb = score_color(weight);
+        match geometry {
+            Geometry::Point { coordinates } => {
+                self.draw_points(pixmap, &[*coordinates], rgb, weight)
+            }
+            Geometry::MultiPoint { coordinates } => {
+                self.draw_points(pixmap, coordinates, rgb, weight)
+            }
+            Geometry::LineString { coordinates } => {
+                self.draw_lines(pixmap, std::slice::from_ref(coordinates), rgb, weight)
+            }
+            Geometry::MultiLineString { coordinates } => {
+                self.draw_lines(pixmap, coordinates, rgb, weight)
+            }
+            Geometry::Polygon { coordinates } => {
+                self.draw_polygon(pixmap, coordinates, rgb, weight)
+            }
+            Geometry::MultiPolygon { coordinates } => {
+                for polygon in coordinates {
+                    self.draw_polygon(pixmap, polygon, rgb, weight);
+                }
+            }
+        }
+    }
+
+    fn draw_points(&self, pixmap: &mut Pixmap, points: &[[f64; 2]], rgb: [u8; 3], weight: f32) {
+        let radius = 3.5 + 3.5 * weight;
+        let outline = stroke(1.5);
+        for point in points {
+            let [x, y] = self.view.pixel(*point);
+            if let Some(circle) = PathBuilder::from_circle(x, y, radius) {
+                fill_path(pixmap, &circle, color(rgb, 230));
+                stroke_path(pixmap, &circle, color([255, 255, 255], 255), &outline);
+            }
+        }
+    }
+
+    fn draw_lines(&self, pixmap: &mut Pixmap, lines: &[Vec<[f64; 2]>], rgb: [u8; 3], weight: f32) {
+        let Some(path) = self.path(lines, false) else {
+            return;
+        };
+        let width = 1.5 + 2.5 * weight;
+        stroke_path(pixmap, &path, color([255, 255, 255], 200), &stroke(width + 2.0));
+        stroke_path(pixmap, &path, color(rgb, 255), &stroke(width));
+    }
+
+    fn draw_polygon(
+        &self,
+        pixmap: &mut Pixmap,
+        rings: &[Vec<[f64; 2]>],
+        rgb: [u8; 3],
+        weight: f32,
+    ) {
+        let Some(path) = self.path(rings, true) else {
+            return;
+        };
+        fill_path(pixmap, &path, color(rgb, (60.0 + 100.0 * weight) as u8));
+        stroke_path(pixmap, &path, color(rgb, 255), &stroke(1.0 + 1.5 * weight));
+    }
+
+    /// Path through the projected lines, closing each one for rings
+    fn path(&self, lines: &[Vec<[f64; 2]>], close: bool) -> Option<Path> {
+        let mut builder = PathBuilder::new();
+        for line in lines {
+            let mut points = line.iter().map(|c| self.view.pixel(*c));
+            let Some([x, y]) = points.next() else {
+                continue;
+            };
+            builder.move_to(x, y);
+            for [x, y] in points {
+                builder.line_to(x, y);
+            }
+            if close {
+                builder.close();
+            }
+        }
+        builder.finish()
+    }
+
+    /// Scale bar of a round length at the center latitude, above the attribution
+    fn draw_scale_bar(&self, pixmap: &mut Pixmap) {
+        let meters_per_pixel = self.view.meters_per_pixel();
+        let max_width = MAX_SCALE_BAR.min(self.options.width as f64 / 4.0);
+        let (meters, label) = scale_length(meters_per_pixel * max_width);
+        let width = (meters / meters_per_pixel) as f32;
+
+        let x = 8.0;
+        let bottom = self.options.height as f32 - 16.0;
+        let backing = width.max(text_width(&label, 1.0)) + 8.0;
+        fill_rect(pixmap, x - 4.0, bottom - 15.0, backing, 18.0, color([255, 255, 255], 200));
+        draw_text(pixmap, &label, x, bottom - 13.0, 1.0);
+
+        let ink = color(TEXT, 255);
+        fill_rect(pixmap, x, bottom - 2.0, width, 2.0, ink);
+        fill_rect(pixmap, x, bottom - 5.0, 1.0, 5.0, ink);
+        fill_rect(pixmap, x + width - 1.0, bottom - 5.0, 1.0, 5.0, ink);
+    }
+
+    /// Attribution lines joined on one strip, shortened to the image width
+    fn draw_attribution(&self, pixmap: &mut Pixmap) {
+        if self.attributions.is_empty() {
+            return;
+        }
+
+        let text = printable(&self.attributions.join(" | "));
+        let max_chars = (self.options.width.saturating_sub(8) / 6) as usize;
+        let text = if text.chars().count() > max_chars {
+            let kept: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+            format!("{}...", kept)
+        } else {
+            text
+        };
+
+        let width = text_width(&text, 1.0) + 6.0;
+        let x = self.options.width as f32 - width;
+        let y = self.options.height as f32 - 11.0;
+        fill_rect(pixmap, x, y, width, 11.0, color([255, 255, 255], 200));
+        draw_text(pixmap, &text, x + 3.0, y + 2.0, 1.0);
+    }
+}
+
+/// Web Mercator view: zoom level and center in pixels of the world at that zoom
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct View {
+    zoom: u8,
+    center: [f64; 2],
+    width: u32,
+    height: u32,
+}
+
+impl View {
+    fn fit(features: &[MapFeature], options: &MapOptions) -> Self {
+        let bounds = features
+            .iter()
+            .filter_map(|feature| bounding_box(&feature.geometry))
+            .reduce(|a, b| [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])])
+            .unwrap_or([-180.0, -MAX_LATITUDE, 180.0, MAX_LATITUDE]);
+
+        // Latitude grows north while pixel rows grow south
+        let [min_x, min_y] = mercator([bounds[0], bounds[3]]);
+        let [max_x, max_y] = mercator([bounds[2], bounds[1]]);
+        let (extent_x, extent_y) = (max_x - min_x, max_y - min_y);
+
+        let padding = options.padding.min(options.width.min(options.height) / 4);
+        let available_x = (options.width - 2 * padding) as f64;
+        let available_y = (options.height - 2 * padding) as f64;
+
+        let zoom = if extent_x <= 0.0 && extent_y <= 0.0 {
+            POINT_ZOOM
+        } else {
+            // A zero extent on one axis leaves the other to decide
+            let scale = (available_x / extent_x).min(available_y / extent_y);
+            scale.log2().floor().clamp(0.0, MAX_ZOOM as f64) as u8
+        };
+
+        let factor = 2f64.powi(zoom as i32);
+        Self {
+            zoom,
+            center: [(min_x + max_x) / 2.0 * factor, (min_y + max_y) / 2.0 * factor],
+            width: options.width,
+            height: options.height,
+        }
+    }
+
+    /// Size of the world in pixels at the view's zoom
+    fn world_size(&self) -> f64 {
+        TILE_SIZE as f64 * 2f64.powi(self.zoom as i32)
+    }
+
+    /// World pixel at the image's top-left corner
+    fn origin(&self) -> [f64; 2] {
+        [
+            self.center[0] - self.width as f64 / 2.0,
+            self.center[1] - self.height as f64 / 2.0,
+        ]
+    }
+
+    /// Image pixel of a longitude/latitude
+    fn pixel(&self, coordinate: [f64; 2]) -> [f32; 2] {
+        let [x, y] = mercator(coordinate);
+        let scale = self.world_size() / TILE_SIZE as f64;
+        let [left, top] = self.origin();
+        [(x * scale - left) as f32, (y * scale - top) as f32]
+    }
+
+    /// Ground distance covered by a pixel at the center latitude
+    fn meters_per_pixel(&self) -> f64 {
+        let world = self.world_size();
+        let latitude = (PI * (1.0 - 2.0 * self.center[1] / world)).sinh().atan();
+        EARTH_CIRCUMFERENCE * latitude.cos() / world
+    }
+
+    /// Tile columns and rows overlapping the image; columns are not wrapped
+    fn tile_range(&self) -> (Range<i64>, Range<i64>) {
+        let tile = TILE_SIZE as f64;
+        let [left, top] = self.origin();
+        let columns =
+            (left / tile).floor() as i64..((left + self.width as f64) / tile).ceil() as i64;
+        let count = 1i64 << self.zoom;
+        let rows = ((top / tile).floor() as i64).max(0)
+            ..(((top + self.height as f64) / tile).ceil() as i64).min(count);
+        (columns, rows)
+    }
+
+    fn tile_id(&self, column: i64, row: i64) -> TileId {
+        TileId {
+            z: self.zoom,
+            x: column.rem_euclid(1i64 << self.zoom) as u32,
+            y: row as u32,
+        }
+    }
+}
+
+/// Pixel of a longitude/latitude in the 256-pixel world of zoom level 0
+///
+/// Longitudes beyond ±180 continue past the world's edges, so unwrapped
+/// geometries stay continuous.
+fn mercator([lon, lat]: [f64; 2]) -> [f64; 2] {
+    let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    let size = TILE_SIZE as f64;
+    [(lon + 180.0) / 360.0 * size, (0.5 - lat.tan().asinh() / (2.0 * PI)) * size]
+}
+
+/// Place every feature on one continuous longitude range
+///
+/// Lines and rings crossing the antimeridian are unwrapped, each feature is
+/// moved so its western edge lies in [-180, 180), and results on both sides
+/// of the antimeridian (Fiji, the Aleutians) are drawn next to each other by
+/// moving the western ones east by 360° when that narrows the extent.
+fn arrange_longitudes(features: &mut [MapFeature]) {
+    for feature in features.iter_mut() {
+        unwrap_geometry(&mut feature.geometry);
+        if let Some([west, _, _, _]) = bounding_box(&feature.geometry) {
+            let offset = -360.0 * ((west + 180.0) / 360.0).floor();
+            if offset != 0.0 {
+                shift_longitudes(&mut feature.geometry, offset);
+            }
+        }
+    }
+
+    let ranges: Vec<[f64; 2]> = features
+        .iter()
+        .filter_map(|feature| bounding_box(&feature.geometry))
+        .map(|[west, _, east, _]| [west, east])
+        .collect();
+    let is_western = |[west, east]: [f64; 2]| (west + east) / 2.0 < 0.0;
+    let span = |shifted: bool| {
+        let (min, max) = ranges.iter().fold((f64::MAX, f64::MIN), |(min, max), range| {
+            let offset = if shifted && is_western(*range) {
+                360.0
+            } else {
+                0.0
+            };
+            (min.min(range[0] + offset), max.max(range[1] + offset))
+        });
+        max - min
+    };
+
+    if !ranges.is_empty() && span(true) < span(false) {
+        for feature in features.iter_mut() {
+            if bounding_box(&feature.geometry).is_some_and(|[w, _, e, _]| is_western([w, e])) {
+                shift_longitudes(&mut feature.geometry, 360.0);
+            }
+        }
+    }
+}
+
+/// Keep longitude steps along lines and rings within 180°
+fn unwrap_geometry(geometry: &mut Geometry) {
+    match geometry {
+        Geometry::Point { .. } | Geometry::MultiPoint { .. } => {}
+        Geometry::LineString { coordinates } => unwrap_line(coordinates),
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter_mut().for_each(|line| unwrap_line(line))
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter_mut().flatten().for_each(|ring| unwrap_line(ring))
+        }
+    }
+}
+
+fn unwrap_line(line: &mut [[f64; 2]]) {
+    for i in 1..line.len() {
+        let step = line[i][0] - line[i - 1][0];
+        if step.abs() > 180.0 {
+            line[i][0] -= 360.0 * (step / 360.0).round();
+        }
+    }
+}
+
+fn shift_longitudes(geometry: &mut Geometry, offset: f64) {
+    let shift = |c: &mut [f64; 2]| c[0] += offset;
+    match geometry {
+        Geometry::Point { coordinates } => shift(coordinates),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.iter_mut().for_each(shift)
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter_mut().flatten().for_each(shift)
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter_mut().flatten().flatten().for_each(shift)
+        }
+    }
+}
+
+/// Bounding box `[min_x, min_y, max_x, max_y]` of a geometry
+fn bounding_box(geometry: &Geometry) -> Option<[f64; 4]> {
+    let mut bbox: Option<[f64; 4]> = None;
+    for_each_coordinate(geometry, &mut |[x, y]| {
+        let b = bbox.get_or_insert([x, y, x, y]);
+        *b = [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)];
+    });
+    bbox
+}
+
+fn for_each_coordinate(geometry: &Geometry, f: &mut impl FnMut([f64; 2])) {
+    match geometry {
+        Geometry::Point { coordinates } => f(*coordinates),
+        Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+            coordinates.iter().for_each(|c| f(*c))
+        }
+        Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+            coordinates.iter().flatten().for_each(|c| f(*c))
+        }
+        Geometry::MultiPolygon { coordinates } => {
+            coordinates.iter().flatten().flatten().for_each(|c| f(*c))
+        }
+    }
+}
+
+/// Scores rescaled to [0, 1] across the map; equal scores all count as best
+fn relative_scores(features: &[MapFeature]) -> Vec<f32> {
+    let min = features.iter().map(|f| f.score).fold(f32::INFINITY, f32::min);
+    let max = features.iter().map(|f| f.score).fold(f32::NEG_INFINITY, f32::max);
+    features
+        .iter()
+        .map(|f| {
+            if max > min {
+                ((f.score - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            }
+        })
+        .collect()
+}
+
+fn score_color(weight: f32) -> [u8; 3] {
+    let mix = |low: u8, high: u8| (low as f32 + (high as f32 - low as f32) * weight).round() as u8;
+    [
+        mix(LOW_SCORE[0], HIGH_SCORE[0]),
+        mix(LOW_SCORE[1], HIGH_SCORE[1]),
+        mix(LOW_SCORE[2], HIGH_SCORE[2]),
+    ]
+}
+
+/// Longest round length (1, 2 or 5 times a power of ten) of at most
+/// `max_meters`, with its label
+fn scale_length(max_meters: f64) -> (f64, String) {
+    let magnitude = 10f64.powi(max_meters.log10().floor() as i32);
+    let meters = [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|length| *length <= max_meters)
+        .unwrap_or(magnitude);
+    let label = if meters >= 1000.0 {
+        format!("{} km", meters / 1000.0)
+    } else {
+        format!("{} m", meters)
+    };
+    (meters, label)
+}
+
+fn color([r, g, b]: [u8; 3], alpha: u8) -> Color {
+    Color::from_rgba8(r, g, b, alpha)
+}
+
+fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(color);
+    paint.anti_alias = true;
+    paint
+}
+
+fn stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    }
+}
+
+fn fill_path(pixmap: &mut Pixmap, path: &Path, color: Color) {
+    pixmap.fill_path(path, &paint(color), FillRule::EvenOdd, Transform::identity(), None);
+}
+
+fn stroke_path(pixmap: &mut Pixmap, path: &Path, color: Color, stroke: &Stroke) {
+    pixmap.stroke_path(path, &paint(color), stroke, Transform::identity(), None);
+}
+
+fn fill_rect(pixmap: &mut Pixmap, x: f32, y: f32, width: f32, height: f32, color: Color) {
+    if let Some(rect) = Rect::from_xywh(x, y, width, height) {
+        let mut paint = paint(color);
+        paint.anti_alias = false;
+        pixmap.fill_rect(rect, &paint, Transform::identity(), None);
+    }
+}
+
+/// Text on a white backing, for messages over the map
+fn draw_label(pixmap: &mut Pixmap, text: &str, x: f32, y: f32, scale: f32) {
+    let padding = 3.0 * scale;
+    fill_rect(
+        pixmap,
+        x - padding,
+        y - padding,
+        text_width(text, scale) + 2.0 * padding,
+        GLYPH_HEIGHT as f32 * scale + 2.0 * padding,
+        color([255, 255, 255], 220),
+    );
+    draw_text(pixmap, text, x, y, scale);
+}
+
+/// Draw text with the built-in bitmap font, `(x, y)` being its top-left corner
+fn draw_text(pixmap: &mut Pixmap, text: &str, x: f32, y: f32, scale: f32) {
+    let ink = color(TEXT, 255);
+    for (index, byte) in printable(text).bytes().enumerate() {
+        let glyph = &FONT[(byte - b' ') as usize];
+        let left = x + (index * GLYPH_ADVANCE) as f32 * scale;
+        for (column, bits) in glyph.iter().enumerate() {
+            for row in 0..GLYPH_HEIGHT {
+                if bits & (1 << row) != 0 {
+                    let px = left + column as f32 * scale;
+                    let py = y + row as f32 * scale;
+                    fill_rect(pixmap, px, py, scale, scale, ink);
+                }
+            }
+        }
+    }
+}
+
+fn text_width(text: &str, scale: f32) -> f32 {
+    let chars = printable(text).len();
+    (chars * GLYPH_ADVANCE).saturating_sub(1) as f32 * scale
+}
+
+/// Text limited to the font's printable ASCII; `©` becomes `(c)` and other
+/// characters `?`
+fn printable(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ' '..='~' => out.push(c),
+            '©' => out.push_str("(c)"),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+const GLYPH_HEIGHT: usize = 7;
+const GLYPH_ADVANCE: usize = 6;
+
+/// 5x7 bitmap font for ' ' to '~', one byte per column, bit 0 the top row
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5F, 0x00, 0x00], // ' ' !
+    [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7F, 0x14, 0x7F, 0x14], // " #
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], // $ %
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], // & '
+    [0x00, 0x1C, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1C, 0x00], // ( )
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], [0x08, 0x08, 0x3E, 0x08, 0x08], // * +
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], // , -
+    [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02], // . /
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], [0x00, 0x42, 0x7F, 0x40, 0x00], // 0 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4B, 0x31], // 2 3
+    [0x18, 0x14, 0x12, 0x7F, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], // 4 5
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03], // 6 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1E], // 8 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00], // : ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14], // < =
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], // > ?
+    [0x32, 0x49, 0x79, 0x41, 0x3E], [0x7E, 0x11, 0x11, 0x11, 0x7E], // @ A
+    [0x7F, 0x49, 0x49, 0x49, 0x36], [0x3E, 0x41, 0x41, 0x41, 0x22], // B C
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], [0x7F, 0x49, 0x49, 0x49, 0x41], // D E
+    [0x7F, 0x09, 0x09, 0x09, 0x01], [0x3E, 0x41, 0x49, 0x49, 0x7A], // F G
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], [0x00, 0x41, 0x7F, 0x41, 0x00], // H I
+    [0x20, 0x40, 0x41, 0x3F, 0x01], [0x7F, 0x08, 0x14, 0x22, 0x41], // J K
+    [0x7F, 0x40, 0x40, 0x40, 0x40], [0x7F, 0x02, 0x0C, 0x02, 0x7F], // L M
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], [0x3E, 0x41, 0x41, 0x41, 0x3E], // N O
+    [0x7F, 0x09, 0x09, 0x09, 0x06], [0x3E, 0x41, 0x51, 0x21, 0x5E], // P Q
+    [0x7F, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31], // R S
+    [0x01, 0x01, 0x7F, 0x01, 0x01], [0x3F, 0x40, 0x40, 0x40, 0x3F], // T U
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], [0x3F, 0x40, 0x38, 0x40, 0x3F], // V W
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x07, 0x08, 0x70, 0x08, 0x07], // X Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7F, 0x41, 0x41, 0x00], // Z [
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7F, 0x00], // \ ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40], // ^ _
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78], // ` a
+    [0x7F, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], // b c
+    [0x38, 0x44, 0x44, 0x48, 0x7F], [0x38, 0x54, 0x54, 0x54, 0x18], // d e
+    [0x08, 0x7E, 0x09, 0x01, 0x02], [0x0C, 0x52, 0x52, 0x52, 0x3E], // f g
+    [0x7F, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7D, 0x40, 0x00], // h i
+    [0x20, 0x40, 0x44, 0x3D, 0x00], [0x7F, 0x10, 0x28, 0x44, 0x00], // j k
+    [0x00, 0x41, 0x7F, 0x40, 0x00], [0x7C, 0x04, 0x18, 0x04, 0x78], // l m
+    [0x7C, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], // n o
+    [0x7C, 0x14, 0x14, 0x14, 0x08], [0x08, 0x14, 0x14, 0x18, 0x7C], // p q
+    [0x7C, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20], // r s
+    [0x04, 0x3F, 0x44, 0x40, 0x20], [0x3C, 0x40, 0x40, 0x20, 0x7C], // t u
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], [0x3C, 0x40, 0x30, 0x40, 0x3C], // v w
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x0C, 0x50, 0x50, 0x50, 0x3C], // x y
+    [0x44, 0x64, 0x54, 0x4C, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], // z {
+    [0x00, 0x00, 0x7F, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], // | }
+    [0x08, 0x04, 0x08, 0x10, 0x08],                                 // ~
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// A park, a road and two sites around Jakarta with distinct scores
+    fn fixture() -> Vec<MapFeature> {
+        vec![
+            MapFeature {
+                geometry: Geometry::polygon(vec![vec![
+                    [106.80, -6.20],
+                    [106.84, -6.20],
+                    [106.84, -6.17],
+                    [106.80, -6.17],
+                    [106.80, -6.20],
+                ]]),
+                score: 0.42,
+            },
+            MapFeature {
+                geometry: Geometry::line_string(vec![
+                    [106.78, -6.22],
+                    [106.82, -6.19],
+                    [106.86, -6.15],
+                ]),
+                score: 0.67,
+            },
+            MapFeature {
+                geometry: Geometry::point(106.83, -6.18),
+                score: 0.91,
+            },
+            MapFeature {
+                geometry: Geometry::MultiPoint {
+                    coordinates: vec![[106.79, -6.16], [106.85, -6.21]],
+                },
+                score: 0.55,
+            },
+        ]
+    }
+
+    fn options() -> MapOptions {
+        MapOptions::new(320, 240).unwrap()
+    }
+
+    fn pixel_digest(map: &StaticMap) -> String {
+        let pixmap = map.draw(&[]).unwrap();
+        format!("{:x}", Sha256::digest(pixmap.data()))
+    }
+
+    #[test]
+    fn test_fixture_render_is_stable() {
+        let map = StaticMap::new(fixture(), options())
+            .with_attributions(vec!["© OpenStreetMap contributors".to_string()]);
+
+        assert_eq!(map.zoom(), 11);
+        assert_eq!(
+            pixel_digest(&map),
+            "f179e5a58c5b661e8f2e64de8ba0485a0bdd0efc7ae753df3ce0b943d620b4e9"
+        );
+    }
+
+    #[test]
+    fn test_empty_results_show_the_world() {
+        let map = StaticMap::new(Vec::new(), options());
+
+        assert_eq!(map.zoom(), 0);
+        assert_eq!(
+            pixel_digest(&map),
+            "7691c08fbc5607da05ae2f085a54750ce59645037035e27354c8a52dc6bb09be"
+        );
+    }
+
+    #[test]
+    fn test_render_encodes_png_of_requested_size() {
+        let png = StaticMap::new(fixture(), options()).render(&[]).unwrap();
+        let decoded = Pixmap::decode_png(&png).unwrap();
+
+        assert_eq!((decoded.width(), decoded.height()), (320, 240));
+    }
+
+    #[test]
+    fn test_single_point_uses_point_zoom() {
+        let point = MapFeature {
+            geometry: Geometry::point(115.2, -8.6),
+            score: 0.8,
+        };
+        let map = StaticMap::new(vec![point], options());
+
+        assert_eq!(map.zoom(), POINT_ZOOM);
+        let [x, y] = map.view.pixel([115.2, -8.6]);
+        assert_eq!((x, y), (160.0, 120.0));
+    }
+
+    #[test]
+    fn test_results_across_antimeridian_stay_together() {
+        let features = vec![
+            MapFeature {
+                geometry: Geometry::point(179.5, -17.0),
+                score: 0.9,
+            },
+            MapFeature {
+                geometry: Geometry::point(-179.5, -16.5),
+                score: 0.7,
+            },
+        ];
+        let map = StaticMap::new(features, options());
+
+        // One degree apart, not 359
+        assert!(map.zoom() >= 7, "zoom {}", map.zoom());
+        let [west, _] = map.view.pixel([179.5, -17.0]);
+        let [east, _] = map.view.pixel([180.5, -16.5]);
+        assert!(west > 0.0 && east < 320.0 && west < east);
+
+        // Tile columns wrap around the antimeridian
+        let columns: BTreeSet<u32> = map.tiles().iter().map(|t| t.x).collect();
+        let last = (1u32 << map.zoom()) - 1;
+        assert!(columns.contains(&0) && columns.contains(&last));
+    }
+
+    #[test]
+    fn test_line_crossing_antimeridian_is_unwrapped() {
+        let mut geometry = Geometry::line_string(vec![[178.0, 0.0], [-179.0, 1.0], [-177.0, 2.0]]);
+        unwrap_geometry(&mut geometry);
+
+        assert_eq!(geometry, Geometry::line_string(vec![[178.0, 0.0], [181.0, 1.0], [183.0, 2.0]]));
+    }
+
+    #[test]
+    fn test_non_finite_features_are_left_out() {
+        let features = vec![MapFeature {
+            geometry: Geometry::point(f64::NAN, 0.0),
+            score: 0.5,
+        }];
+        let map = StaticMap::new(features, options());
+
+        assert!(map.features.is_empty());
+        assert!(map.render(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_undecodable_tiles_are_skipped() {
+        let map = StaticMap::new(fixture(), options());
+        let tiles: Vec<(TileId, Vec<u8>)> =
+            map.tiles().into_iter().map(|id| (id, b"not a png".to_vec())).collect();
+
+        assert_eq!(pixel_digest(&map), {
+            let pixmap = map.draw(&tiles).unwrap();
+            format!("{:x}", Sha256::digest(pixmap.data()))
+        });
+    }
+
+    #[test]
+    fn test_basemap_tiles_are_drawn_under_results() {
+        let map = StaticMap::new(fixture(), options());
+        let mut tile = Pixmap::new(TILE_SIZE, TILE_SIZE).unwrap();
+        tile.fill(Color::from_rgba8(170, 211, 223, 255));
+        let png = tile.encode_png().unwrap();
+        let tiles: Vec<(TileId, Vec<u8>)> =
+            map.tiles().into_iter().map(|id| (id, png.clone())).collect();
+
+        let pixmap = map.draw(&tiles).unwrap();
+        let corner = pixmap.pixel(319, 0).unwrap();
+        assert_eq!((corner.red(), corner.green(), corner.blue()), (170, 211, 223));
+    }
+
+    #[test]
+    fn test_tiles_cover_view() {
+        let map = StaticMap::new(Vec::new(), MapOptions::new(512, 256).unwrap());
+
+        // The whole world at zoom 0 is a single tile
+        assert_eq!(map.tiles(), vec![TileId { z: 0, x: 0, y: 0 }]);
+    }
+
+    #[test]
+    fn test_tile_url() {
+        let basemap = Basemap::new("https://tiles.example.com/{z}/{x}/{y}.png");
+        let url = basemap.tile_url(TileId { z: 3, x: 7, y: 2 });
+
+        assert_eq!(url, "https://tiles.example.com/3/7/2.png");
+    }
+
+    #[test]
+    fn test_scale_length() {
+        assert_eq!(scale_length(730.0), (500.0, "500 m".to_string()));
+        assert_eq!(scale_length(2_400.0), (2_000.0, "2 km".to_string()));
+        assert_eq!(scale_length(99.0), (50.0, "50 m".to_string()));
+        assert_eq!(scale_length(10_000.0), (10_000.0, "10 km".to_string()));
+    }
+
+    #[test]
+    fn test_map_options_bounds() {
+        assert!(MapOptions::new(800, 600).is_ok());
+        assert!(MapOptions::new(32, 600).is_err());
+        assert!(MapOptions::new(800, 5000).is_err());
+    }
+
+    #[test]
+    fn test_printable_text() {
+        assert_eq!(printable("© OSM – data"), "(c) OSM ? data");
+    }
+}
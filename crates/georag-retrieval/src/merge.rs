@@ -0,0 +1,245 @@
+use georag_core::models::ChunkId;
+use std::collections::HashMap;
+
+use crate::models::{SourceReference, SpatialMatch};
+
+/// Merge sources whose chunks overlap in the text they were cut from
+///
+/// Chunks overlap by design, so neighbouring chunks of one feature often
+/// rank together and show nearly the same excerpt twice. Sources with the
+/// same document path, page and feature whose character ranges overlap become
+/// one source spanning the union of the ranges. It takes the place of its
+/// best ranked member and the highest score; sources that only touch or
+/// have a gap between them are left alone.
+///
+/// Ranges are merged only when the characters they share agree, so sources
+/// without an offset, or whose excerpt is not the slice of the normalized
+/// text at their offset, are kept as they are. Excerpts must not be redacted
+/// yet.
+///
+/// Returns the chunks merged into each kept source, in text order.
+pub fn merge_overlapping_sources(
+    sources: &mut Vec<SourceReference>,
+) -> HashMap<ChunkId, Vec<ChunkId>> {
+    let mut groups: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, source) in sources.iter().enumerate() {
+        if source.offset.is_some() {
+            groups
+                .entry((source.document_path.as_str(), source.page, source.feature_id))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    let mut spans = Vec::new();
+    for mut members in groups.into_values() {
+        members.sort_by_key(|&index| sources[index].offset);
+        let mut members = members.into_iter();
+        let Some(first) = members.next() else {
+            continue;
+        };
+        let mut span = Span::new(first, &sources[first]);
+        for index in members {
+            if !span.extend(index, &sources[index]) {
+                spans.push(std::mem::replace(&mut span, Span::new(index, &sources[index])));
+            }
+        }
+        spans.push(span);
+    }
+
+    let mut merged = HashMap::new();
+    let mut absorbed = vec![false; sources.len()];
+    for span in spans.into_iter().filter(|span| span.members.len() > 1) {
+        // Sources are in rank order, so the lowest index is the best ranked
+        let keep = span.members.iter().copied().min().unwrap_or_default();
+        let score = span.members.iter().map(|&i| sources[i].score).fold(f32::MIN, f32::max);
+        let chunk_match = span
+            .members
+            .iter()
+            .any(|&i| sources[i].spatial_match == Some(SpatialMatch::Chunk));
+        let others: Vec<ChunkId> = span
+            .members
+            .iter()
+            .filter(|&&i| i != keep)
+            .map(|&i| sources[i].chunk_id)
+            .collect();
+        for &index in &span.members {
+            absorbed[index] = index != keep;
+        }
+
+        let source = &mut sources[keep];
+        source.excerpt = span.text.iter().collect();
+        source.offset = Some(span.start);
+        source.score = score;
+        if chunk_match {
+            source.spatial_match = Some(SpatialMatch::Chunk);
+        }
+        merged.insert(source.chunk_id, others);
+    }
+
+    *sources = std::mem::take(sources)
+        .into_iter()
+        .zip(absorbed)
+        .filter_map(|(source, absorbed)| (!absorbed).then_some(source))
+        .collect();
+
+    merged
+}
+
+/// Union of overlapping character ranges, built up in offset order
+struct Span {
+    start: usize,
+    text: Vec<char>,
+    members: Vec<usize>,
+}
+
+impl Span {
+    fn new(index: usize, source: &SourceReference) -> Self {
+        Self {
+            start: source.offset.unwrap_or_default(),
+            text: source.excerpt.chars().collect(),
+            members: vec![index],
+        }
+    }
+
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    /// Take in a source starting at or after the span, if the two overlap
+    fn extend(&mut self, index: usize, source: &SourceReference) -> bool {
+        let offset = source.offset.unwrap_or_default();
+        if offset >= self.end() {
+            return false;
+        }
+
+        let text: Vec<char> = source.excerpt.chars().collect();
+        let shared = (self.end() - offset).min(text.len());
+        let tail = &self.text[offset - self.start..offset - self.start + shared];
+        if *tail != text[..shared] {
+            return false;
+        }
+
+        self.text.extend(&text[shared..]);
+        self.members.push(index);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use georag_core::models::FeatureId;
+
+    /// Words `w0 w1 ...` of one feature's text
+    fn text(range: std::ops::Range<usize>) -> String {
+        range.map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Character offset of word `word` in the feature's text
+    fn char_offset(word: usize) -> usize {
+        (0..word).map(|i| format!("w{} ", i).len()).sum()
+    }
+
+    fn source(id: u64, feature: u64, range: std::ops::Range<usize>, score: f32) -> SourceReference {
+        SourceReference {
+            chunk_id: ChunkId(id),
+            feature_id: Some(FeatureId(feature)),
+            document_path: "/data/reports.geojson".to_string(),
+            page: None,
+            offset: Some(char_offset(range.start)),
+            excerpt: text(range),
+            score,
+            spatial_match: None,
+            source_url: None,
+            confidence: None,
+        }
+    }
+
+    #[test]
+    fn test_overlapping_chunks_merge_into_union() {
+        let mut sources = vec![source(2, 1, 8..18, 0.9), source(1, 1, 0..10, 0.7)];
+        let merged = merge_overlapping_sources(&mut sources);
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].chunk_id, ChunkId(2));
+        assert_eq!(sources[0].offset, Some(0));
+        assert_eq!(sources[0].excerpt, text(0..18));
+        assert_eq!(sources[0].score, 0.9);
+        assert_eq!(merged[&ChunkId(2)], vec![ChunkId(1)]);
+    }
+
+    #[test]
+    fn test_chain_of_overlaps_merges_once() {
+        let mut sources =
+            vec![source(3, 1, 16..26, 0.5), source(1, 1, 0..10, 0.8), source(2, 1, 8..18, 0.6)];
+        let merged = merge_overlapping_sources(&mut sources);
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].chunk_id, ChunkId(3));
+        assert_eq!(sources[0].excerpt, text(0..26));
+        assert_eq!(sources[0].score, 0.8);
+        assert_eq!(merged[&ChunkId(3)], vec![ChunkId(1), ChunkId(2)]);
+    }
+
+    #[test]
+    fn test_contained_chunk_keeps_outer_boundaries() {
+        let mut sources = vec![source(2, 1, 4..8, 0.9), source(1, 1, 0..12, 0.4)];
+        merge_overlapping_sources(&mut sources);
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].offset, Some(0));
+        assert_eq!(sources[0].excerpt, text(0..12));
+    }
+
+    #[test]
+    fn test_non_adjacent_chunks_are_never_merged() {
+        // Touching, gapped, other features and other documents
+        let mut other_document = source(6, 1, 2..12, 0.3);
+        other_document.document_path = "/data/other.geojson".to_string();
+        let mut sources = vec![
+            source(1, 1, 0..10, 0.9),
+            source(2, 1, 10..20, 0.8),
+            source(3, 1, 25..35, 0.7),
+            source(4, 2, 5..15, 0.6),
+            source(5, 3, 0..10, 0.5),
+            other_document,
+        ];
+        let merged = merge_overlapping_sources(&mut sources);
+
+        assert!(merged.is_empty());
+        let ids: Vec<u64> = sources.iter().map(|s| s.chunk_id.0).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_disagreeing_overlap_is_not_merged() {
+        let mut shifted = source(2, 1, 5..15, 0.8);
+        shifted.offset = Some(3);
+        let mut unpositioned = source(3, 1, 0..10, 0.7);
+        unpositioned.offset = None;
+        let mut sources = vec![source(1, 1, 0..10, 0.9), shifted, unpositioned];
+        let merged = merge_overlapping_sources(&mut sources);
+
+        assert!(merged.is_empty());
+        assert_eq!(sources.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_keeps_rank_order_of_other_sources() {
+        let mut sources = vec![
+            source(1, 1, 0..10, 0.9),
+            source(7, 2, 0..10, 0.85),
+            source(2, 1, 5..15, 0.8),
+            source(8, 3, 0..10, 0.75),
+        ];
+        sources[2].spatial_match = Some(SpatialMatch::Chunk);
+        sources[0].spatial_match = Some(SpatialMatch::Feature);
+        merge_overlapping_sources(&mut sources);
+
+        let ids: Vec<u64> = sources.iter().map(|s| s.chunk_id.0).collect();
+        assert_eq!(ids, vec![1, 7, 8]);
+        assert_eq!(sources[0].excerpt, text(0..15));
+        assert_eq!(sources[0].spatial_match, Some(SpatialMatch::Chunk));
+    }
+}
@@ -1,5 +1,15 @@
-use georag_core::models::{ChunkId, FeatureId, SpatialFilter};
+use georag_core::geo::{AppliedPointDefaults, FilterSimplification};
+use georag_core::models::{ChunkId, FeatureId, ResultConfidence, SpatialFilter, TagVisibility};
+use georag_core::redaction::Redactor;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::diagnostics::{CandidateCounts, Diagnostic};
+use crate::grounding::SentenceGrounding;
+use crate::grouping::{TimeBucket, TimeFilter, TimeGrouping};
+use crate::rerank::{RerankMode, DEFAULT_RERANK_POOL};
+use crate::timing::{PhaseTiming, QueryTimings};
 
 /// Text filter for keyword-based filtering
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -92,6 +102,45 @@ impl TextFilter {
     }
 }
 
+/// Equality filter on a feature property
+///
+/// Values are compared as text, in the form chunk metadata stores them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeFilter {
+    /// Feature property name
+    pub property: String,
+
+    /// Value the property must have
+    pub value: String,
+}
+
+impl AttributeFilter {
+    /// Create a new attribute filter
+    pub fn new(property: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            property: property.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl FromStr for AttributeFilter {
+    type Err = String;
+
+    /// Parse `property=value`, e.g. `category=school`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (property, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid attribute filter '{}': expected PROPERTY=VALUE", s))?;
+
+        if property.trim().is_empty() {
+            return Err(format!("Invalid attribute filter '{}': property is empty", s));
+        }
+
+        Ok(Self::new(property.trim(), value.trim()))
+    }
+}
+
 /// Query plan with spatial, text, and semantic options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryPlan {
@@ -104,6 +153,10 @@ pub struct QueryPlan {
     /// Optional text filter for keyword matching
     pub text_filter: Option<TextFilter>,
 
+    /// Property equality filters, all of which must match
+    #[serde(default)]
+    pub attribute_filters: Vec<AttributeFilter>,
+
     /// Whether to enable semantic reranking
     pub semantic_rerank: bool,
 
@@ -112,6 +165,66 @@ pub struct QueryPlan {
 
     /// Whether to include detailed explanation
     pub explain: bool,
+
+    /// Minimum semantic similarity score; sources scoring below it are dropped
+    #[serde(default)]
+    pub min_score: Option<f32>,
+
+    /// Optional bucketing of ranked sources by a timestamp property
+    #[serde(default)]
+    pub group_by_time: Option<TimeGrouping>,
+
+    /// Datasets the caller may see; chunks from other datasets are never returned
+    #[serde(default)]
+    pub visibility: TagVisibility,
+
+    /// Second-pass reranking of the best candidates
+    #[serde(default)]
+    pub rerank: RerankMode,
+
+    /// Candidates reranked before the results are cut to `top_k`
+    #[serde(default = "default_rerank_pool")]
+    pub rerank_pool: usize,
+
+    /// Saved area the spatial filter geometry was expanded from
+    #[serde(default)]
+    pub named_area: Option<NamedAreaExpansion>,
+
+    /// Saved areas the spatial filter's exclusions were expanded from
+    #[serde(default)]
+    pub excluded_areas: Vec<NamedAreaExpansion>,
+
+    /// Workspace defaults filled into a point query's spatial filter
+    #[serde(default)]
+    pub point_defaults: Option<AppliedPointDefaults>,
+
+    /// Whether sources cut from overlapping stretches of one text are merged
+    #[serde(default)]
+    pub merge_overlapping: bool,
+
+    /// Names of the datasets searched; empty searches every dataset
+    #[serde(default)]
+    pub datasets: Vec<String>,
+
+    /// Optional lower bound on a timestamp property of the features
+    #[serde(default)]
+    pub time_filter: Option<TimeFilter>,
+
+    /// Operators parsed out of the query text, when operator parsing was asked for
+    #[serde(default)]
+    pub operators: Option<QueryOperators>,
+
+    /// Indexed chunks searched with instead of the embedded query text
+    #[serde(default)]
+    pub example: Option<QueryExample>,
+
+    /// Workspace preset the plan's options were filled in from
+    #[serde(default)]
+    pub preset: Option<AppliedPreset>,
+}
+
+fn default_rerank_pool() -> usize {
+    DEFAULT_RERANK_POOL
 }
 
 impl QueryPlan {
@@ -121,9 +234,24 @@ impl QueryPlan {
             text_query: text_query.into(),
             spatial_filter: None,
             text_filter: None,
+            attribute_filters: Vec::new(),
             semantic_rerank: true,
             top_k: 10,
             explain: false,
+            min_score: None,
+            group_by_time: None,
+            visibility: TagVisibility::All,
+            rerank: RerankMode::None,
+            rerank_pool: DEFAULT_RERANK_POOL,
+            named_area: None,
+            excluded_areas: Vec::new(),
+            point_defaults: None,
+            merge_overlapping: false,
+            datasets: Vec::new(),
+            time_filter: None,
+            operators: None,
+            example: None,
+            preset: None,
         }
     }
 
@@ -139,6 +267,12 @@ impl QueryPlan {
         self
     }
 
+    /// Add a property equality filter
+    pub fn with_attribute_filter(mut self, filter: AttributeFilter) -> Self {
+        self.attribute_filters.push(filter);
+        self
+    }
+
     /// Enable or disable semantic reranking
     pub fn with_semantic_rerank(mut self, enabled: bool) -> Self {
         self.semantic_rerank = enabled;
@@ -156,6 +290,104 @@ impl QueryPlan {
         self.explain = enabled;
         self
     }
+
+    /// Set the minimum semantic similarity score
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = Some(min_score);
+        self
+    }
+
+    /// Group ranked sources into time buckets
+    pub fn with_group_by_time(mut self, grouping: TimeGrouping) -> Self {
+        self.group_by_time = Some(grouping);
+        self
+    }
+
+    /// Restrict results to datasets visible with the given access tags
+    pub fn with_visibility(mut self, visibility: TagVisibility) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    /// Rerank the best candidates before cutting the results to `top_k`
+    pub fn with_rerank(mut self, mode: RerankMode) -> Self {
+        self.rerank = mode;
+        self
+    }
+
+    /// Set how many candidates are reranked
+    pub fn with_rerank_pool(mut self, pool: usize) -> Self {
+        self.rerank_pool = pool;
+        self
+    }
+
+    /// Record the saved area the spatial filter geometry came from
+    pub fn with_named_area(mut self, expansion: NamedAreaExpansion) -> Self {
+        self.named_area = Some(expansion);
+        self
+    }
+
+    /// Record a saved area one of the spatial filter's exclusions came from
+    pub fn with_excluded_area(mut self, expansion: NamedAreaExpansion) -> Self {
+        self.excluded_areas.push(expansion);
+        self
+    }
+
+    /// Record the defaults filled into a point query's spatial filter
+    pub fn with_point_defaults(mut self, applied: AppliedPointDefaults) -> Self {
+        self.point_defaults = Some(applied);
+        self
+    }
+
+    /// Merge sources whose chunks overlap into one source spanning both
+    ///
+    /// Merging can leave fewer than `top_k` sources.
+    pub fn with_merge_overlapping(mut self, enabled: bool) -> Self {
+        self.merge_overlapping = enabled;
+        self
+    }
+
+    /// Search only the named dataset, in addition to any named before
+    pub fn with_dataset(mut self, name: impl Into<String>) -> Self {
+        self.datasets.push(name.into());
+        self
+    }
+
+    /// Keep only features dated on or after the filter's lower bound
+    pub fn with_time_filter(mut self, filter: TimeFilter) -> Self {
+        self.time_filter = Some(filter);
+        self
+    }
+
+    /// Record the operators parsed out of the query text
+    pub fn with_operators(mut self, operators: QueryOperators) -> Self {
+        self.operators = Some(operators);
+        self
+    }
+
+    /// Search with the stored embeddings of example chunks instead of
+    /// embedding the query text
+    ///
+    /// The example chunks themselves are never returned.
+    pub fn with_example(mut self, example: QueryExample) -> Self {
+        self.example = Some(example);
+        self
+    }
+
+    /// Record the workspace preset the plan's options were filled in from
+    pub fn with_preset(mut self, preset: AppliedPreset) -> Self {
+        self.preset = Some(preset);
+        self
+    }
+
+    /// Candidates kept by the first pass: the rerank pool when reranking,
+    /// and never fewer than `top_k`
+    pub fn candidate_pool(&self) -> usize {
+        match self.rerank {
+            RerankMode::None => self.top_k,
+            _ => self.rerank_pool.max(self.top_k),
+        }
+    }
 }
 
 /// Query result with answer and sources
@@ -175,6 +407,30 @@ pub struct QueryResult {
 
     /// Optional detailed explanation
     pub explanation: Option<QueryExplanation>,
+
+    /// Number of ranked candidates dropped by the minimum score threshold
+    #[serde(default)]
+    pub filtered_by_threshold: usize,
+
+    /// Ranked sources bucketed by time, when requested by the plan
+    #[serde(default)]
+    pub time_groups: Option<Vec<TimeBucket>>,
+
+    /// Candidates left after each filtering phase
+    #[serde(default)]
+    pub candidates: CandidateCounts,
+
+    /// Likely causes of an empty result, filled in by the query service
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// Distinct attribution lines of the datasets the sources come from
+    #[serde(default)]
+    pub attributions: Vec<String>,
+
+    /// Sentences of a generated answer that no source supports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ungrounded: Vec<String>,
 }
 
 impl QueryResult {
@@ -190,6 +446,12 @@ impl QueryResult {
             spatial_matches,
             semantic_scores: None,
             explanation: None,
+            filtered_by_threshold: 0,
+            time_groups: None,
+            candidates: CandidateCounts::default(),
+            diagnostics: Vec::new(),
+            attributions: Vec::new(),
+            ungrounded: Vec::new(),
         }
     }
 
@@ -204,6 +466,34 @@ impl QueryResult {
         self.explanation = Some(explanation);
         self
     }
+
+    /// Apply redaction rules to the answer and source excerpts
+    ///
+    /// This is the single place where query output text is redacted; every
+    /// caller of the retrieval pipeline receives already-redacted results.
+    pub fn redact(&mut self, redactor: &Redactor) {
+        if redactor.is_empty() {
+            return;
+        }
+
+        self.answer = redactor.redact_text(&self.answer);
+        for sentence in &mut self.ungrounded {
+            *sentence = redactor.redact_text(sentence);
+        }
+        for source in &mut self.sources {
+            source.excerpt = redactor.redact_text(&source.excerpt);
+        }
+        for bucket in self.time_groups.iter_mut().flatten() {
+            for source in &mut bucket.top_sources {
+                source.excerpt = redactor.redact_text(&source.excerpt);
+            }
+        }
+    }
+
+    /// Check whether every candidate was suppressed by the minimum score threshold
+    pub fn all_below_threshold(&self) -> bool {
+        self.sources.is_empty() && self.filtered_by_threshold > 0
+    }
 }
 
 /// Reference to a source document or feature
@@ -221,11 +511,48 @@ pub struct SourceReference {
     /// Optional page number
     pub page: Option<usize>,
 
+    /// Character offset of the excerpt in the normalized text it was cut from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+
     /// Text excerpt
     pub excerpt: String,
 
     /// Relevance score
     pub score: f32,
+
+    /// Which geometry matched the spatial filter, when the query had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spatial_match: Option<SpatialMatch>,
+
+    /// Link to the source's document, when a source URL template is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+
+    /// Percentile of the semantic score in the index's score baseline, when
+    /// the results were ranked semantically and the index has a baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ResultConfidence>,
+}
+
+/// Geometry a source matched the spatial filter with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpatialMatch {
+    /// The chunk's own geometry, from the places its text names
+    Chunk,
+
+    /// The geometry of the chunk's feature
+    Feature,
+}
+
+impl fmt::Display for SpatialMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpatialMatch::Chunk => write!(f, "chunk"),
+            SpatialMatch::Feature => write!(f, "feature"),
+        }
+    }
 }
 
 /// Detailed query explanation
@@ -234,11 +561,250 @@ pub struct QueryExplanation {
     /// Spatial phase explanation
     pub spatial_phase: SpatialPhaseExplanation,
 
+    /// Attribute filtering, when the plan has attribute filters
+    #[serde(default)]
+    pub attribute_phase: Option<AttributePhaseExplanation>,
+
     /// Optional semantic phase explanation
     pub semantic_phase: Option<SemanticPhaseExplanation>,
 
+    /// Reranking, when the plan asks for it
+    #[serde(default)]
+    pub rerank_phase: Option<RerankPhaseExplanation>,
+
     /// Ranking details for each result
     pub ranking_details: Vec<RankingDetail>,
+
+    /// Distribution of semantic scores before the minimum score threshold
+    #[serde(default)]
+    pub score_distribution: Option<ScoreDistribution>,
+
+    /// Wall time of each phase
+    #[serde(default)]
+    pub timings: QueryTimings,
+
+    /// Grounding scores of each sentence, when the answer was generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding: Option<Vec<SentenceGrounding>>,
+
+    /// How operators in the query text were interpreted, when they were parsed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operators: Option<QueryOperators>,
+
+    /// How result confidence was estimated, when the sources have percentiles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<ConfidenceExplanation>,
+
+    /// Preset the query's options came from, and the plan they made up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<PresetExplanation>,
+}
+
+/// Workspace preset applied to a query and the plan that resulted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetExplanation {
+    #[serde(flatten)]
+    pub preset: AppliedPreset,
+
+    /// Ranking options in effect once the preset and the request were merged
+    pub effective: EffectivePlan,
+}
+
+/// Ranking options of a query plan as it ran
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectivePlan {
+    pub top_k: usize,
+    pub min_score: Option<f32>,
+    pub semantic_rerank: bool,
+    pub rerank: RerankMode,
+    pub rerank_pool: usize,
+    pub explain: bool,
+    pub merge_overlapping: bool,
+}
+
+impl From<&QueryPlan> for EffectivePlan {
+    fn from(plan: &QueryPlan) -> Self {
+        Self {
+            top_k: plan.top_k,
+            min_score: plan.min_score,
+            semantic_rerank: plan.semantic_rerank,
+            rerank: plan.rerank,
+            rerank_pool: plan.rerank_pool,
+            explain: plan.explain,
+            merge_overlapping: plan.merge_overlapping,
+        }
+    }
+}
+
+/// Baseline and cutoffs the confidence of the sources was estimated with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfidenceExplanation {
+    /// Number of random embedding pairs the baseline was sampled from
+    pub baseline_samples: usize,
+
+    /// Lowest percentile of a high confidence result
+    pub high_cutoff: f32,
+
+    /// Lowest percentile of a medium confidence result
+    pub medium_cutoff: f32,
+
+    /// What the percentiles do and do not say
+    pub caveat: String,
+}
+
+/// Summary of the scores in a ranked result set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreDistribution {
+    /// Number of scored candidates
+    pub count: usize,
+
+    /// Lowest score
+    pub min: f32,
+
+    /// Median score
+    pub median: f32,
+
+    /// Highest score
+    pub max: f32,
+}
+
+impl ScoreDistribution {
+    /// Compute the distribution of a set of scores (None if empty)
+    pub fn from_scores(scores: &[f32]) -> Option<Self> {
+        if scores.is_empty() {
+            return None;
+        }
+
+        let mut sorted = scores.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let mid = sorted.len() / 2;
+        let median = if sorted.len() % 2 == 0 {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            median,
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Saved area expanded into a query's spatial filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedAreaExpansion {
+    /// Name of the saved area
+    pub name: String,
+
+    /// Vertices in the area geometry, before any buffer or simplification
+    pub vertices: usize,
+}
+
+/// Workspace preset whose options a query took
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedPreset {
+    /// Name of the preset
+    pub name: String,
+
+    /// Whether the preset is the workspace default rather than one the query named
+    pub default: bool,
+
+    /// Options the query took from the preset
+    pub applied: Vec<String>,
+
+    /// Options the preset sets that the query set itself
+    pub overridden: Vec<String>,
+}
+
+/// How the embeddings of several example chunks are searched with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeedMode {
+    /// One search with the mean of the embeddings
+    #[default]
+    Average,
+
+    /// One search per embedding, keeping each candidate's best score
+    Individual,
+}
+
+impl fmt::Display for SeedMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeedMode::Average => write!(f, "average"),
+            SeedMode::Individual => write!(f, "individual"),
+        }
+    }
+}
+
+impl FromStr for SeedMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "average" => Ok(SeedMode::Average),
+            "individual" => Ok(SeedMode::Individual),
+            other => Err(format!("Unknown seed mode '{}': expected average or individual", other)),
+        }
+    }
+}
+
+/// Indexed chunks a query searches with in place of its text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryExample {
+    /// What the chunks were taken from, e.g. `parcels:42` or `chunk 7`
+    pub source: String,
+
+    /// Chunks whose stored embeddings seed the search
+    pub seed_chunks: Vec<ChunkId>,
+
+    /// Whether the embeddings are averaged or searched one by one
+    #[serde(default)]
+    pub mode: SeedMode,
+}
+
+/// Interpretation of `key:value` operators in the query text
+///
+/// Recognized operators are taken out of the text and become filters; the
+/// rest of the text is what gets embedded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryOperators {
+    /// Query text as given
+    pub original: String,
+
+    /// Text left once the operators are taken out
+    pub text: String,
+
+    /// Operators turned into filters
+    pub applied: Vec<AppliedOperator>,
+
+    /// Operators that were not applied; malformed ones stay in the text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored: Vec<IgnoredOperator>,
+}
+
+/// Operator turned into a filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedOperator {
+    /// Token as written, e.g. `within:2km`
+    pub token: String,
+
+    /// Filter it became, e.g. `buffer of 2 km around area ubud`
+    pub filter: String,
+}
+
+/// Operator that was recognized but not applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoredOperator {
+    /// Token as written
+    pub token: String,
+
+    /// Why it was not applied
+    pub reason: String,
 }
 
 /// Explanation of the spatial filtering phase
@@ -256,8 +822,109 @@ pub struct SpatialPhaseExplanation {
     /// Number of features that passed the spatial filter
     pub features_matched: usize,
 
+    /// Number of chunks that passed the spatial filter by their own geometry
+    #[serde(default)]
+    pub chunks_matched_by_geometry: usize,
+
     /// Optional distance threshold
     pub distance_threshold: Option<f64>,
+
+    /// Buffer applied to the filter geometry, in meters
+    #[serde(default)]
+    pub buffer_meters: Option<f64>,
+
+    /// Set when the filter geometry was simplified to fit the vertex limit
+    #[serde(default)]
+    pub filter_simplification: Option<FilterSimplification>,
+
+    /// Set when the filter geometry was expanded from a saved area
+    #[serde(default)]
+    pub named_area: Option<NamedAreaExpansion>,
+
+    /// Set when the predicate or radius of a point query came from the
+    /// workspace defaults
+    #[serde(default)]
+    pub point_defaults: Option<AppliedPointDefaults>,
+
+    /// Set when the spatial filter excludes areas
+    #[serde(default)]
+    pub exclusions: Option<ExclusionExplanation>,
+
+    /// Wall time of candidate generation
+    #[serde(default)]
+    pub timing: PhaseTiming,
+}
+
+/// Areas excluded from a spatial filter and what they removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExclusionExplanation {
+    /// Number of exclusion areas
+    pub areas: usize,
+
+    /// Saved areas among the exclusion areas
+    #[serde(default)]
+    pub named_areas: Vec<NamedAreaExpansion>,
+
+    /// Features matching the filter that an exclusion area removed
+    pub features_excluded: usize,
+
+    /// Chunks matching the filter that an exclusion area removed
+    pub chunks_excluded: usize,
+}
+
+/// Explanation of the attribute filtering phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributePhaseExplanation {
+    /// How each filter was evaluated
+    pub filters: Vec<AttributeFilterExplanation>,
+
+    /// Candidates entering the phase
+    pub candidates_before: usize,
+
+    /// Candidates matching every filter
+    pub candidates_after: usize,
+
+    /// Wall time of the phase
+    #[serde(default)]
+    pub timing: PhaseTiming,
+}
+
+/// How one attribute filter was evaluated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttributeFilterExplanation {
+    /// The filter
+    pub filter: AttributeFilter,
+
+    /// Where the property values came from
+    pub level: FilterLevel,
+
+    /// Chunks checked against their own metadata
+    pub chunks_evaluated: usize,
+
+    /// Chunks checked by looking up their feature
+    pub features_looked_up: usize,
+}
+
+/// Where an attribute filter read property values from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterLevel {
+    /// Chunk metadata only
+    Chunk,
+    /// Feature properties only (the property is not in `chunk_properties`)
+    Feature,
+    /// Chunk metadata where present, feature properties otherwise
+    Mixed,
+}
+
+impl std::fmt::Display for FilterLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterLevel::Chunk => write!(f, "chunk"),
+            FilterLevel::Feature => write!(f, "feature"),
+            FilterLevel::Mixed => write!(f, "mixed"),
+        }
+    }
 }
 
 /// Explanation of the semantic reranking phase
@@ -274,6 +941,48 @@ pub struct SemanticPhaseExplanation {
 
     /// Query embedding norm
     pub query_norm: f32,
+
+    /// Wall time of embedding the query
+    #[serde(default)]
+    pub embedding_timing: PhaseTiming,
+
+    /// Wall time of the vector search
+    #[serde(default)]
+    pub search_timing: PhaseTiming,
+
+    /// Example chunks searched with, when the query was by example
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<ExampleExplanation>,
+}
+
+/// How a query by example searched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExampleExplanation {
+    /// What the chunks were taken from
+    pub source: String,
+
+    /// Example chunks with a stored embedding
+    pub seed_chunks: usize,
+
+    /// Whether their embeddings were averaged into one query vector
+    pub averaged: bool,
+}
+
+/// Explanation of the reranking phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerankPhaseExplanation {
+    /// Reranking applied
+    pub mode: RerankMode,
+
+    /// Reranker used: `bm25` or the rating model
+    pub reranker: String,
+
+    /// Number of candidates reranked
+    pub candidates_reranked: usize,
+
+    /// Wall time of the phase
+    #[serde(default)]
+    pub timing: PhaseTiming,
 }
 
 /// Ranking detail for a single result
@@ -282,15 +991,31 @@ pub struct RankingDetail {
     /// Chunk ID
     pub chunk_id: ChunkId,
 
+    /// Position in the results, from 1
+    #[serde(default)]
+    pub rank: usize,
+
+    /// Position before reranking, when the results were reranked
+    #[serde(default)]
+    pub pre_rerank_rank: Option<usize>,
+
     /// Spatial score (if applicable)
     pub spatial_score: Option<f32>,
 
-    /// Semantic similarity score (if applicable)
+    /// Semantic similarity score (if applicable), before any reranking
     pub semantic_score: Option<f32>,
 
-    /// Final combined score
+    /// Final combined score, the reranker's when the results were reranked
     pub final_score: f32,
 
+    /// Percentile of the semantic score in the index's score baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<f32>,
+
+    /// Overlapping chunks merged into this result, in text order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_chunks: Vec<ChunkId>,
+
     /// Explanation of score calculation
     pub score_explanation: String,
 }
@@ -1,14 +1,52 @@
 use georag_core::error::{GeoragError, Result};
-use georag_core::llm::Embedder;
-use georag_core::models::{ChunkId, ScoredResult, TextChunk};
+use georag_core::geo::{buffer_filter, FilterCache, GeometryLimits, PreparedFilter};
+use georag_core::llm::{Embedder, Generator};
+use georag_core::models::{
+    dataset_slug, distinct_attributions, relative_document_path, ChunkId, CitationFields,
+    ConfidenceCutoffs, FeatureId, ScoreBaseline, ScoredResult, SourceUrlTemplate, SpatialFilter,
+    TextChunk, CONFIDENCE_CAVEAT,
+};
+use georag_core::processing::chunk::property_text;
+use georag_core::redaction::Redactor;
 use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use crate::diagnostics::CandidateCounts;
+use crate::grounding::{check_grounding, AnswerGrounding, GroundingPolicy};
+use crate::grouping::{group_sources_by_time, parse_timestamp, TimeBucket, TimeGrouping};
+use crate::merge::merge_overlapping_sources;
 use crate::models::{
-    QueryExplanation, QueryPlan, QueryResult, RankingDetail, SemanticPhaseExplanation,
-    SourceReference, SpatialPhaseExplanation,
+    AttributeFilterExplanation, AttributePhaseExplanation, ConfidenceExplanation, EffectivePlan,
+    ExampleExplanation, ExclusionExplanation, FilterLevel, PresetExplanation, QueryExplanation,
+    QueryPlan, QueryResult, RankingDetail, RerankPhaseExplanation, ScoreDistribution, SeedMode,
+    SemanticPhaseExplanation, SourceReference, SpatialMatch, SpatialPhaseExplanation,
 };
+use crate::rerank::{LexicalReranker, RerankCandidate, RerankMode, Reranker};
+use crate::timing::{PhaseTiming, QueryTimings, Stopwatch};
+
+/// Chunks passing the spatial phase
+struct SpatialCandidates {
+    chunk_ids: Vec<ChunkId>,
+
+    /// Candidates that matched by their own geometry rather than their feature's
+    chunk_matches: HashSet<ChunkId>,
+
+    explanation: SpatialPhaseExplanation,
+
+    /// Chunks in the index that are not stale
+    indexed_chunks: usize,
+}
+
+/// Candidates reordered by the rerank phase
+struct Reranked {
+    results: Vec<ScoredResult>,
+
+    /// First-pass position (from 1) and score of each reranked candidate
+    prior: HashMap<ChunkId, (usize, f32)>,
+
+    explanation: Option<RerankPhaseExplanation>,
+}
 
 /// Retrieval pipeline orchestrating spatial and semantic search
 pub struct RetrievalPipeline<E>
@@ -19,6 +57,15 @@ where
     vector_store: Arc<dyn VectorStore>,
     document_store: Arc<dyn DocumentStore>,
     embedder: E,
+    redactor: Redactor,
+    geometry_limits: GeometryLimits,
+    llm_reranker: Option<Arc<dyn Reranker>>,
+    filter_cache: Option<Arc<FilterCache>>,
+    source_urls: Option<SourceUrlTemplate>,
+    generator: Option<Arc<dyn Generator>>,
+    grounding: GroundingPolicy,
+    score_baseline: Option<ScoreBaseline>,
+    confidence_cutoffs: ConfidenceCutoffs,
 }
 
 impl<E> RetrievalPipeline<E>
@@ -37,20 +84,131 @@ where
             vector_store,
             document_store,
             embedder,
+            redactor: Redactor::default(),
+            geometry_limits: GeometryLimits::default(),
+            llm_reranker: None,
+            filter_cache: None,
+            source_urls: None,
+            generator: None,
+            grounding: GroundingPolicy::default(),
+            score_baseline: None,
+            confidence_cutoffs: ConfidenceCutoffs::default(),
         }
     }
 
+    /// Set the redaction rules applied to query results
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Set the size limits applied to the query filter geometry
+    pub fn with_geometry_limits(mut self, limits: GeometryLimits) -> Self {
+        self.geometry_limits = limits;
+        self
+    }
+
+    /// Set the reranker serving plans that ask for `llm` reranking
+    pub fn with_llm_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.llm_reranker = Some(reranker);
+        self
+    }
+
+    /// Share prepared spatial filters with other queries through a cache
+    pub fn with_filter_cache(mut self, cache: Arc<FilterCache>) -> Self {
+        self.filter_cache = Some(cache);
+        self
+    }
+
+    /// Link every source to its document through a URL template
+    pub fn with_source_url_template(mut self, template: SourceUrlTemplate) -> Self {
+        self.source_urls = Some(template);
+        self
+    }
+
+    /// Generate answers from the ranked excerpts with a generator
+    ///
+    /// Generated answers are checked against the excerpts, see
+    /// [`grounding`](crate::grounding).
+    pub fn with_generator(mut self, generator: Arc<dyn Generator>) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    /// Set the thresholds of the grounding check and what happens to
+    /// ungrounded sentences
+    pub fn with_grounding(mut self, policy: GroundingPolicy) -> Self {
+        self.grounding = policy;
+        self
+    }
+
+    /// Express semantic scores as percentiles of the index's score baseline
+    ///
+    /// The baseline must come from an index built with this pipeline's
+    /// embedder; scores of another embedder are not comparable with it.
+    pub fn with_confidence(mut self, baseline: ScoreBaseline, cutoffs: ConfidenceCutoffs) -> Self {
+        self.score_baseline = Some(baseline);
+        self.confidence_cutoffs = cutoffs;
+        self
+    }
+
     /// Execute a query plan
     pub async fn execute(&self, plan: &QueryPlan) -> Result<QueryResult> {
+        let mut stopwatch = Stopwatch::start();
+
         // Phase 1: Spatial filtering
-        let (spatial_candidates, spatial_explanation) = self.spatial_filter_phase(plan).await?;
+        let SpatialCandidates {
+            chunk_ids: spatial_candidates,
+            chunk_matches,
+            explanation: mut spatial_explanation,
+            indexed_chunks,
+        } = self.spatial_filter_phase(plan).await?;
+        let mut candidates = CandidateCounts {
+            indexed_chunks,
+            features_matched: plan
+                .spatial_filter
+                .as_ref()
+                .map(|_| spatial_explanation.features_matched),
+            after_spatial: spatial_candidates.len(),
+            ..Default::default()
+        };
+
+        // Phase 1.1: Example chunks are what the results should resemble, never a result
+        let spatial_candidates = match &plan.example {
+            Some(example) => {
+                let seeds: HashSet<ChunkId> = example.seed_chunks.iter().copied().collect();
+                spatial_candidates.into_iter().filter(|id| !seeds.contains(id)).collect()
+            }
+            None => spatial_candidates,
+        };
+
+        // Phase 1.2: Drop chunks from datasets the caller may not see or the plan leaves out
+        let spatial_candidates = self.visibility_phase(plan, spatial_candidates).await?;
+        candidates.after_visibility = spatial_candidates.len();
 
         // Phase 1.5: Text filtering (keyword must/must-not)
         let text_filtered_candidates = self.text_filter_phase(plan, &spatial_candidates).await?;
+        candidates.after_text = text_filtered_candidates.len();
+        spatial_explanation.timing = stopwatch.lap();
+
+        // Phase 1.7: Attribute filtering, on chunk metadata where it carries the property
+        let (text_filtered_candidates, mut attribute_explanation) =
+            self.attribute_filter_phase(plan, text_filtered_candidates).await?;
+        let attribute_timing = stopwatch.lap();
+        if let Some(attributes) = &mut attribute_explanation {
+            attributes.timing = attribute_timing;
+        }
+        candidates.after_attributes = text_filtered_candidates.len();
+
+        // Phase 1.8: Time filtering on a timestamp property
+        let text_filtered_candidates =
+            self.time_filter_phase(plan, text_filtered_candidates).await?;
+        candidates.after_time = text_filtered_candidates.len();
 
         // Phase 2: Semantic reranking (if enabled)
         let (ranked_results, semantic_explanation) = if plan.semantic_rerank {
-            self.semantic_rerank_phase(plan, &text_filtered_candidates).await?
+            self.semantic_rerank_phase(plan, &text_filtered_candidates, &mut stopwatch)
+                .await?
         } else {
             // No semantic reranking, just use filtered candidates
             let results: Vec<ScoredResult> = text_filtered_candidates
@@ -61,80 +219,310 @@ where
                     score: 1.0 - (idx as f32 / text_filtered_candidates.len().max(1) as f32),
                     spatial_score: None,
                 })
-                .take(plan.top_k)
+                .take(plan.candidate_pool())
                 .collect();
             (results, None)
         };
 
+        // Phase 2.5: Minimum score threshold (semantic scores only; positional scores are
+        // not comparable across queries). The distribution is captured before filtering so
+        // callers can pick a sensible threshold.
+        let score_distribution = ScoreDistribution::from_scores(
+            &ranked_results.iter().map(|r| r.score).collect::<Vec<_>>(),
+        );
+        let (ranked_results, filtered_by_threshold) = match plan.min_score {
+            Some(min_score) if plan.semantic_rerank => {
+                let before = ranked_results.len();
+                let kept: Vec<ScoredResult> =
+                    ranked_results.into_iter().filter(|r| r.score >= min_score).collect();
+                let filtered = before - kept.len();
+                (kept, filtered)
+            }
+            _ => (ranked_results, 0),
+        };
+        let ranking_timing = stopwatch.lap();
+
+        // Phase 2.7: Optional second pass over the candidate pool, then the cut to top_k
+        let Reranked {
+            results: mut ranked_results,
+            prior,
+            explanation: mut rerank_explanation,
+        } = self.rerank_phase(plan, ranked_results).await?;
+        if let Some(rerank) = &mut rerank_explanation {
+            rerank.timing = stopwatch.lap();
+        }
+
         // Phase 3: Result grounding with source references
-        let sources = self.ground_results(&ranked_results).await?;
-
-        // Build explanation if requested
-        let explanation = if plan.explain {
-            let ranking_details = self.build_ranking_details(&ranked_results, &sources).await?;
-            Some(QueryExplanation {
-                spatial_phase: spatial_explanation.clone(),
-                semantic_phase: semantic_explanation.clone(),
-                ranking_details,
-            })
+        let chunk_matches = plan.spatial_filter.is_some().then_some(&chunk_matches);
+        let mut sources = self.ground_results(&ranked_results, chunk_matches).await?;
+
+        // Phase 3.1: Optional merge of sources cut from overlapping stretches of one text
+        let merged = if plan.merge_overlapping {
+            let merged = merge_overlapping_sources(&mut sources);
+            let kept: HashSet<ChunkId> = sources.iter().map(|s| s.chunk_id).collect();
+            ranked_results.retain(|r| kept.contains(&r.chunk_id));
+            merged
+        } else {
+            HashMap::new()
+        };
+
+        // Phase 3.2: Credits of the datasets the sources come from
+        let attributions = self.attributions(&sources).await?;
+
+        // Phase 3.3: Links to the documents, when a URL template is configured
+        if let Some(template) = &self.source_urls {
+            self.fill_source_urls(template, &mut sources).await?;
+        }
+
+        // Phase 3.4: Confidence of each semantic score against the index's score baseline
+        let baseline = self.score_baseline.as_ref().filter(|_| plan.semantic_rerank);
+        if let Some(baseline) = baseline {
+            for source in &mut sources {
+                let score = prior.get(&source.chunk_id).map_or(source.score, |(_, score)| *score);
+                source.confidence = Some(baseline.confidence(score, &self.confidence_cutoffs));
+            }
+        }
+
+        // Phase 3.5: Optional time bucketing of the ranked sources
+        let time_groups = match &plan.group_by_time {
+            Some(grouping) => Some(self.group_by_time(grouping, &sources).await?),
+            None => None,
+        };
+
+        // Ranking details for the explanation, which is assembled once every phase is timed
+        let ranking_details = if plan.explain {
+            Some(self.build_ranking_details(&ranked_results, &sources, &prior, &merged).await?)
         } else {
             None
         };
 
-        // Generate answer (placeholder - would use Generator trait in full implementation)
-        let answer = self.generate_answer(plan, &sources).await?;
+        // Phase 4: Answer, checked against the sources when it is generated
+        let (answer, grounding) = if sources.is_empty() && filtered_by_threshold > 0 {
+            let answer = format!(
+                "No sufficiently relevant results found ({} candidates scored below \
+                the minimum score of {:.2}).",
+                filtered_by_threshold,
+                plan.min_score.unwrap_or_default()
+            );
+            (answer, None)
+        } else {
+            self.generate_answer(plan, &sources).await?
+        };
+        let ungrounded: Vec<String> = grounding
+            .iter()
+            .flat_map(|g| g.ungrounded().map(|s| s.sentence.clone()))
+            .collect();
 
         let semantic_scores = if plan.semantic_rerank {
-            Some(ranked_results.iter().map(|r| r.score).collect())
+            Some(
+                ranked_results
+                    .iter()
+                    .map(|r| prior.get(&r.chunk_id).map_or(r.score, |(_, score)| *score))
+                    .collect(),
+            )
         } else {
             None
         };
 
-        Ok(QueryResult {
+        let mut result = QueryResult {
             answer,
             sources,
             spatial_matches: text_filtered_candidates.len(),
             semantic_scores,
-            explanation,
-        })
+            explanation: None,
+            filtered_by_threshold,
+            time_groups,
+            candidates,
+            diagnostics: Vec::new(),
+            attributions,
+            ungrounded,
+        };
+        result.redact(&self.redactor);
+
+        let timings = QueryTimings {
+            spatial: spatial_explanation.timing,
+            attribute: attribute_explanation.as_ref().map(|a| a.timing),
+            embedding: semantic_explanation.as_ref().map(|s| s.embedding_timing),
+            vector_search: semantic_explanation.as_ref().map(|s| s.search_timing),
+            ranking: ranking_timing,
+            rerank: rerank_explanation.as_ref().map(|r| r.timing),
+            enrichment: stopwatch.lap(),
+            total_ms: stopwatch.elapsed_ms(),
+        };
+
+        if let Some(ranking_details) = ranking_details {
+            result.explanation = Some(QueryExplanation {
+                spatial_phase: spatial_explanation,
+                attribute_phase: attribute_explanation,
+                semantic_phase: semantic_explanation,
+                rerank_phase: rerank_explanation,
+                ranking_details,
+                score_distribution,
+                timings,
+                confidence: baseline.map(|baseline| ConfidenceExplanation {
+                    baseline_samples: baseline.samples,
+                    high_cutoff: self.confidence_cutoffs.high,
+                    medium_cutoff: self.confidence_cutoffs.medium,
+                    caveat: CONFIDENCE_CAVEAT.to_string(),
+                }),
+                operators: plan.operators.clone(),
+                preset: plan.preset.clone().map(|preset| PresetExplanation {
+                    preset,
+                    effective: EffectivePlan::from(plan),
+                }),
+                grounding: grounding.map(|g| {
+                    g.sentences
+                        .into_iter()
+                        .map(|mut s| {
+                            s.sentence = self.redactor.redact_text(&s.sentence);
+                            s
+                        })
+                        .collect()
+                }),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Prepared form of a spatial filter, shared through the filter cache when there is one
+    fn prepare(&self, filter: &SpatialFilter) -> Arc<PreparedFilter> {
+        match &self.filter_cache {
+            Some(cache) => cache.get_or_prepare(filter),
+            None => Arc::new(PreparedFilter::new(filter)),
+        }
     }
 
     /// Phase 1: Spatial filtering
-    async fn spatial_filter_phase(
-        &self,
-        plan: &QueryPlan,
-    ) -> Result<(Vec<ChunkId>, SpatialPhaseExplanation)> {
-        let (chunk_ids, features_evaluated, features_matched) = if let Some(filter) =
-            &plan.spatial_filter
-        {
-            // Apply spatial filter
-            let features = self.spatial_store.spatial_query(filter).await?;
-            let features_matched = features.len();
-
-            // Get all chunks to count features evaluated
-            let all_chunk_ids = self.document_store.list_chunk_ids().await?;
-            let features_evaluated = all_chunk_ids.len();
-
-            // Extract chunk IDs from features with spatial references
-            let chunks = self.document_store.get_chunks(&all_chunk_ids).await?;
-            let feature_ids: std::collections::HashSet<_> = features.iter().map(|f| f.id).collect();
-
-            let filtered_chunk_ids: Vec<ChunkId> = chunks
-                .into_iter()
-                .filter(|chunk| {
-                    chunk.spatial_ref.as_ref().map(|fid| feature_ids.contains(fid)).unwrap_or(false)
-                })
-                .map(|chunk| chunk.id)
-                .collect();
+    ///
+    /// A chunk with its own geometry matches by that geometry; other chunks
+    /// match when their feature does.
+    async fn spatial_filter_phase(&self, plan: &QueryPlan) -> Result<SpatialCandidates> {
+        // Buffer the filter geometry, then guard against oversized geometries
+        // before any candidate is evaluated
+        let buffered_filter = plan.spatial_filter.as_ref().map(buffer_filter);
+        let (spatial_filter, filter_simplification) = match &buffered_filter {
+            Some(filter) => match &filter.geometry {
+                Some(geometry) => {
+                    let (geometry, simplification) = self.geometry_limits.apply(geometry)?;
+                    (
+                        Some(SpatialFilter {
+                            geometry: Some(geometry),
+                            ..filter.clone()
+                        }),
+                        simplification,
+                    )
+                }
+                None => (Some(filter.clone()), None),
+            },
+            None => (None, None),
+        };
 
-            (filtered_chunk_ids, features_evaluated, features_matched)
-        } else {
-            // No spatial filter, return all chunks
-            let chunk_ids = self.document_store.list_chunk_ids().await?;
-            let count = chunk_ids.len();
-            (chunk_ids, count, count)
+        // Exclusion areas are held to the same vertex limit but never buffered
+        let spatial_filter = match spatial_filter {
+            Some(filter) if !filter.exclude.is_empty() => {
+                let exclude = filter
+                    .exclude
+                    .iter()
+                    .map(|area| self.geometry_limits.apply(area).map(|(area, _)| area))
+                    .collect::<Result<Vec<_>>>()?;
+                Some(SpatialFilter { exclude, ..filter })
+            }
+            filter => filter,
         };
 
+        let mut chunk_matches = HashSet::new();
+        let mut exclusions = spatial_filter.as_ref().filter(|f| !f.exclude.is_empty()).map(|f| {
+            ExclusionExplanation {
+                areas: f.exclude.len(),
+                named_areas: plan.excluded_areas.clone(),
+                features_excluded: 0,
+                chunks_excluded: 0,
+            }
+        });
+        let (chunk_ids, features_evaluated, features_matched, indexed_chunks) =
+            if let Some(filter) = &spatial_filter {
+                // Apply spatial filter, prepared once for the store and the chunks
+                let prepared = self.prepare(filter);
+                let features = self.spatial_store.spatial_query_prepared(&prepared).await?;
+
+                // Get all chunks to count features evaluated
+                let all_chunk_ids = self.document_store.list_chunk_ids().await?;
+                let features_evaluated = all_chunk_ids.len();
+
+                // Extract chunk IDs from features with spatial references. Tiles of a
+                // subdivided feature count as the original, which holds its chunks.
+                let chunks = self.document_store.get_chunks(&all_chunk_ids).await?;
+                let feature_ids: HashSet<_> = features.iter().map(|f| f.original_id()).collect();
+                let features_matched = feature_ids.len();
+                let indexed_chunks = chunks.iter().filter(|chunk| !chunk.metadata.stale).count();
+
+                // Features the exclusions removed, found by querying without them
+                // when the explanation asks for their number
+                let mut excluded_features = HashSet::new();
+                if let Some(exclusions) = exclusions.as_mut().filter(|_| plan.explain) {
+                    let included =
+                        self.prepare(&SpatialFilter { exclude: Vec::new(), ..filter.clone() });
+                    excluded_features = self
+                        .spatial_store
+                        .spatial_query_prepared(&included)
+                        .await?
+                        .iter()
+                        .map(|f| f.original_id())
+                        .filter(|id| !feature_ids.contains(id))
+                        .collect();
+                    exclusions.features_excluded = excluded_features.len();
+                }
+
+                // Chunks locating their own places are tested by those places alone
+                let mut chunks_excluded = 0;
+                let filtered_chunk_ids: Vec<ChunkId> = chunks
+                    .into_iter()
+                    .filter(|chunk| !chunk.metadata.stale)
+                    .filter(|chunk| match &chunk.geometry {
+                        Some(geometry) => {
+                            if !prepared.includes(geometry) {
+                                return false;
+                            }
+                            if prepared.excludes(geometry) {
+                                chunks_excluded += 1;
+                                return false;
+                            }
+                            chunk_matches.insert(chunk.id);
+                            true
+                        }
+                        None => match &chunk.spatial_ref {
+                            Some(fid) if feature_ids.contains(fid) => true,
+                            Some(fid) => {
+                                chunks_excluded += usize::from(excluded_features.contains(fid));
+                                false
+                            }
+                            None => false,
+                        },
+                    })
+                    .map(|chunk| chunk.id)
+                    .collect();
+                if let Some(exclusions) = &mut exclusions {
+                    exclusions.chunks_excluded = chunks_excluded;
+                }
+
+                (filtered_chunk_ids, features_evaluated, features_matched, indexed_chunks)
+            } else {
+                // No spatial filter, return all chunks that are not stale
+                let all_chunk_ids = self.document_store.list_chunk_ids().await?;
+                let count = all_chunk_ids.len();
+                let chunk_ids: Vec<ChunkId> = self
+                    .document_store
+                    .get_chunks(&all_chunk_ids)
+                    .await?
+                    .into_iter()
+                    .filter(|chunk| !chunk.metadata.stale)
+                    .map(|chunk| chunk.id)
+                    .collect();
+                let indexed_chunks = chunk_ids.len();
+                (chunk_ids, count, count, indexed_chunks)
+            };
+
         let explanation = SpatialPhaseExplanation {
             predicate: plan
                 .spatial_filter
@@ -144,14 +532,76 @@ where
             crs: plan.spatial_filter.as_ref().map(|f| f.crs.epsg).unwrap_or(4326),
             features_evaluated,
             features_matched,
+            chunks_matched_by_geometry: chunk_matches.len(),
             distance_threshold: plan
                 .spatial_filter
                 .as_ref()
                 .and_then(|f| f.distance.as_ref())
                 .map(|d| d.value),
+            buffer_meters: plan
+                .spatial_filter
+                .as_ref()
+                .and_then(|f| f.buffer.as_ref())
+                .map(|d| d.to_meters()),
+            filter_simplification,
+            named_area: plan.named_area.clone(),
+            point_defaults: plan.point_defaults,
+            exclusions,
+            timing: PhaseTiming::default(),
         };
 
-        Ok((chunk_ids, explanation))
+        Ok(SpatialCandidates {
+            chunk_ids,
+            chunk_matches,
+            explanation,
+            indexed_chunks,
+        })
+    }
+
+    /// Phase 1.2: Dataset visibility filtering
+    ///
+    /// Chunks are attributed to datasets by their source path. A chunk is kept
+    /// only if its path belongs to a visible dataset and to no hidden one, so
+    /// chunks that cannot be attributed are dropped for restricted callers.
+    /// When the plan names datasets, the others count as hidden.
+    async fn visibility_phase(
+        &self,
+        plan: &QueryPlan,
+        candidates: Vec<ChunkId>,
+    ) -> Result<Vec<ChunkId>> {
+        let scoped = !plan.datasets.is_empty();
+        if (!plan.visibility.is_restricted() && !scoped) || candidates.is_empty() {
+            return Ok(candidates);
+        }
+
+        let mut visible_paths = HashSet::new();
+        let mut hidden_paths = HashSet::new();
+        for meta in self.spatial_store.list_datasets().await? {
+            if let Some(dataset) = self.spatial_store.get_dataset(meta.id).await? {
+                let path = dataset.path.to_string_lossy().to_string();
+                let named =
+                    !scoped || plan.datasets.iter().any(|name| dataset_named(&dataset.name, name));
+                if named && plan.visibility.allows(&dataset.tags) {
+                    visible_paths.insert(path);
+                } else {
+                    hidden_paths.insert(path);
+                }
+            }
+        }
+
+        let visible_chunks: HashSet<ChunkId> = self
+            .document_store
+            .get_chunks(&candidates)
+            .await?
+            .into_iter()
+            .filter(|chunk| {
+                let path = &chunk.source.document_path;
+                visible_paths.contains(path) && !hidden_paths.contains(path)
+            })
+            .map(|chunk| chunk.id)
+            .collect();
+
+        Ok(candidates.into_iter().filter(|id| visible_chunks.contains(id)).collect())
     }
 
     /// Phase 1.5: Text content filtering
@@ -182,51 +632,373 @@ where
         Ok(filtered)
     }
 
+    /// Phase 1.7: Attribute filtering
+    ///
+    /// Each filter is checked against the chunk's metadata when the property
+    /// was copied there at build time. Only chunks that pass those checks and
+    /// still need a property their metadata lacks trigger a feature lookup.
+    async fn attribute_filter_phase(
+        &self,
+        plan: &QueryPlan,
+        candidates: Vec<ChunkId>,
+    ) -> Result<(Vec<ChunkId>, Option<AttributePhaseExplanation>)> {
+        if plan.attribute_filters.is_empty() {
+            return Ok((candidates, None));
+        }
+
+        let filters = &plan.attribute_filters;
+        let mut chunks_evaluated = vec![0; filters.len()];
+        let mut features_looked_up = vec![0; filters.len()];
+        let mut feature_properties: HashMap<FeatureId, HashMap<String, String>> = HashMap::new();
+        let mut kept = HashSet::new();
+
+        for chunk in self.document_store.get_chunks(&candidates).await? {
+            let (on_chunk, on_feature): (Vec<_>, Vec<_>) = filters
+                .iter()
+                .enumerate()
+                .partition(|(_, f)| chunk.metadata.properties.contains_key(&f.property));
+
+            let chunk_match = on_chunk.iter().all(|(i, filter)| {
+                chunks_evaluated[*i] += 1;
+                chunk.metadata.properties.get(&filter.property) == Some(&filter.value)
+            });
+            if !chunk_match {
+                continue;
+            }
+
+            if !on_feature.is_empty() {
+                let properties = match chunk.spatial_ref {
+                    Some(feature_id) => {
+                        if !feature_properties.contains_key(&feature_id) {
+                            let properties = self
+                                .spatial_store
+                                .get_feature(feature_id)
+                                .await?
+                                .map(|feature| {
+                                    feature
+                                        .properties
+                                        .iter()
+                                        .filter_map(|(k, v)| Some((k.clone(), property_text(v)?)))
+                                        .collect()
+                                })
+                                .unwrap_or_default();
+                            feature_properties.insert(feature_id, properties);
+                        }
+                        feature_properties.get(&feature_id)
+                    }
+                    None => None,
+                };
+
+                let feature_match = on_feature.iter().all(|(i, filter)| {
+                    features_looked_up[*i] += 1;
+                    properties.and_then(|p| p.get(&filter.property)) == Some(&filter.value)
+                });
+                if !feature_match {
+                    continue;
+                }
+            }
+
+            kept.insert(chunk.id);
+        }
+
+        let candidates_before = candidates.len();
+        let filtered: Vec<ChunkId> =
+            candidates.into_iter().filter(|id| kept.contains(id)).collect();
+
+        let explanation = AttributePhaseExplanation {
+            filters: filters
+                .iter()
+                .enumerate()
+                .map(|(i, filter)| AttributeFilterExplanation {
+                    filter: filter.clone(),
+                    level: match (chunks_evaluated[i], features_looked_up[i]) {
+                        (_, 0) => FilterLevel::Chunk,
+                        (0, _) => FilterLevel::Feature,
+                        _ => FilterLevel::Mixed,
+                    },
+                    chunks_evaluated: chunks_evaluated[i],
+                    features_looked_up: features_looked_up[i],
+                })
+                .collect(),
+            candidates_before,
+            candidates_after: filtered.len(),
+            timing: PhaseTiming::default(),
+        };
+
+        Ok((filtered, Some(explanation)))
+    }
+
+    /// Phase 1.8: Time filtering
+    ///
+    /// The timestamp is read from the chunk's metadata when the property was
+    /// copied there at build time, and from its feature otherwise. Chunks
+    /// without a usable timestamp are dropped.
+    async fn time_filter_phase(
+        &self,
+        plan: &QueryPlan,
+        candidates: Vec<ChunkId>,
+    ) -> Result<Vec<ChunkId>> {
+        let Some(filter) = &plan.time_filter else {
+            return Ok(candidates);
+        };
+
+        let mut feature_dated: HashMap<FeatureId, bool> = HashMap::new();
+        let mut kept = HashSet::new();
+        for chunk in self.document_store.get_chunks(&candidates).await? {
+            let dated = match (chunk.metadata.properties.get(&filter.property), chunk.spatial_ref) {
+                // Metadata keeps property values as text, epoch numbers included
+                (Some(value), _) => filter.matches(
+                    &value
+                        .parse::<i64>()
+                        .map(serde_json::Value::from)
+                        .unwrap_or_else(|_| serde_json::Value::from(value.as_str())),
+                ),
+                (None, Some(feature_id)) => match feature_dated.get(&feature_id) {
+                    Some(dated) => *dated,
+                    None => {
+                        let dated = self
+                            .spatial_store
+                            .get_feature(feature_id)
+                            .await?
+                            .and_then(|feature| feature.properties.get(&filter.property).cloned())
+                            .is_some_and(|value| filter.matches(&value));
+                        feature_dated.insert(feature_id, dated);
+                        dated
+                    }
+                },
+                (None, None) => false,
+            };
+            if dated {
+                kept.insert(chunk.id);
+            }
+        }
+
+        Ok(candidates.into_iter().filter(|id| kept.contains(id)).collect())
+    }
+
     /// Phase 2: Semantic reranking
+    ///
+    /// A query by example searches with the stored embeddings of its example
+    /// chunks and embeds nothing.
     async fn semantic_rerank_phase(
         &self,
         plan: &QueryPlan,
         candidates: &[ChunkId],
+        stopwatch: &mut Stopwatch,
     ) -> Result<(Vec<ScoredResult>, Option<SemanticPhaseExplanation>)> {
         if candidates.is_empty() {
             return Ok((Vec::new(), None));
         }
 
-        // Generate query embedding
-        let query_embeddings = self.embedder.embed(&[&plan.text_query])?;
-        let query_embedding = query_embeddings.into_iter().next().ok_or_else(|| {
-            GeoragError::EmbedderUnavailable {
-                reason: "Failed to generate query embedding".to_string(),
-                remediation: "Check embedder configuration".to_string(),
+        // Generate the query embeddings, or read those of the example chunks
+        let (query_embeddings, example) = match &plan.example {
+            Some(example) => {
+                let mut vectors = Vec::with_capacity(example.seed_chunks.len());
+                for chunk_id in &example.seed_chunks {
+                    if let Some(embedding) = self.vector_store.get_embedding(*chunk_id).await? {
+                        vectors.push(embedding.vector);
+                    }
+                }
+                if vectors.is_empty() {
+                    return Err(GeoragError::IndexNotBuilt(format!(
+                        "no example chunk of {} has an embedding",
+                        example.source
+                    )));
+                }
+                let averaged = example.mode == SeedMode::Average;
+                let explanation = ExampleExplanation {
+                    source: example.source.clone(),
+                    seed_chunks: vectors.len(),
+                    averaged,
+                };
+                let vectors = if averaged {
+                    vec![mean_vector(&vectors)]
+                } else {
+                    vectors
+                };
+                (vectors, Some(explanation))
             }
-        })?;
+            None => {
+                let query_embedding =
+                    self.embedder.embed(&[&plan.text_query])?.into_iter().next().ok_or_else(
+                        || GeoragError::EmbedderUnavailable {
+                            reason: "Failed to generate query embedding".to_string(),
+                            remediation: "Check embedder configuration".to_string(),
+                        },
+                    )?;
+                (vec![query_embedding], None)
+            }
+        };
 
         // Calculate query norm
-        let query_norm = query_embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let query_norm = query_embeddings[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+        let embedding_timing = stopwatch.lap();
 
-        // Perform similarity search
-        let mut results =
-            self.vector_store.similarity_search(&query_embedding, plan.top_k, None).await?;
+        // Perform similarity search; the example chunks can take up places in the
+        // nearest neighbours, so search that many more. Searching with several
+        // vectors keeps each chunk's best score.
+        let pool = plan.candidate_pool();
+        let k = pool + plan.example.as_ref().map_or(0, |e| e.seed_chunks.len());
+        let mut results = match query_embeddings.as_slice() {
+            [query_embedding] => {
+                self.vector_store.similarity_search(query_embedding, k, None).await?
+            }
+            query_embeddings => {
+                let mut best: HashMap<ChunkId, ScoredResult> = HashMap::new();
+                for query_embedding in query_embeddings {
+                    for result in
+                        self.vector_store.similarity_search(query_embedding, k, None).await?
+                    {
+                        match best.get(&result.chunk_id) {
+                            Some(found) if found.score >= result.score => {}
+                            _ => {
+                                best.insert(result.chunk_id, result);
+                            }
+                        }
+                    }
+                }
+                let mut results: Vec<ScoredResult> = best.into_values().collect();
+                results.sort_by(ScoredResult::rank_cmp);
+                results
+            }
+        };
 
         // Filter to only include candidates from spatial phase
         let candidate_set: std::collections::HashSet<_> = candidates.iter().copied().collect();
         results.retain(|r| candidate_set.contains(&r.chunk_id));
 
-        // Take top k
-        results.truncate(plan.top_k);
+        // Keep the candidate pool; the rerank phase cuts it to top k
+        results.truncate(pool);
+        let search_timing = stopwatch.lap();
 
         let explanation = SemanticPhaseExplanation {
             embedder_model: self.embedder.model_name().to_string(),
             embedding_dim: self.embedder.dimensions(),
             candidates_reranked: candidates.len(),
             query_norm,
+            embedding_timing,
+            search_timing,
+            example,
         };
 
         Ok((results, Some(explanation)))
     }
 
-    /// Phase 3: Ground results with source references
-    async fn ground_results(&self, results: &[ScoredResult]) -> Result<Vec<SourceReference>> {
+    /// Phase 2.7: Reranking
+    ///
+    /// The reranker scores every candidate in the pool, which is reordered by
+    /// those scores and cut to `top_k`. Candidates scoring the same keep their
+    /// first-pass order. Without reranking the pool is already `top_k` long.
+    async fn rerank_phase(&self, plan: &QueryPlan, results: Vec<ScoredResult>) -> Result<Reranked> {
+        let reranker: Arc<dyn Reranker> = match plan.rerank {
+            RerankMode::None => {
+                return Ok(Reranked {
+                    results,
+                    prior: HashMap::new(),
+                    explanation: None,
+                })
+            }
+            RerankMode::Lexical => Arc::new(LexicalReranker::default()),
+            RerankMode::Llm => self
+                .llm_reranker
+                .clone()
+                .ok_or_else(|| GeoragError::ConfigMissing { key: "rerank.model".to_string() })?,
+        };
+
+        let chunk_ids: Vec<ChunkId> = results.iter().map(|r| r.chunk_id).collect();
+        let texts: HashMap<ChunkId, String> = self
+            .document_store
+            .get_chunks(&chunk_ids)
+            .await?
+            .into_iter()
+            .map(|chunk| (chunk.id, chunk.content))
+            .collect();
+        let candidates: Vec<RerankCandidate> = results
+            .iter()
+            .map(|r| RerankCandidate {
+                chunk_id: r.chunk_id,
+                text: texts.get(&r.chunk_id).cloned().unwrap_or_default(),
+                score: r.score,
+            })
+            .collect();
+        let candidates_reranked = candidates.len();
+
+        let scores = if candidates.is_empty() {
+            Vec::new()
+        } else {
+            reranker.rerank(&plan.text_query, candidates).await?
+        };
+        if scores.len() != candidates_reranked {
+            return Err(GeoragError::FormatError {
+                format: "rerank".to_string(),
+                message: format!(
+                    "{} returned {} scores for {} candidates",
+                    reranker.name(),
+                    scores.len(),
+                    candidates_reranked
+                ),
+            });
+        }
+
+        let prior = results
+            .iter()
+            .enumerate()
+            .map(|(index, r)| (r.chunk_id, (index + 1, r.score)))
+            .collect();
+        let mut reranked: Vec<ScoredResult> = results
+            .into_iter()
+            .zip(scores)
+            .map(|(result, score)| ScoredResult { score: score.clamp(0.0, 1.0), ..result })
+            .collect();
+        reranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        reranked.truncate(plan.top_k);
+
+        Ok(Reranked {
+            results: reranked,
+            prior,
+            explanation: Some(RerankPhaseExplanation {
+                mode: plan.rerank,
+                reranker: reranker.name().to_string(),
+                candidates_reranked,
+                timing: PhaseTiming::default(),
+            }),
+        })
+    }
+
+    /// Bucket sources by the timestamp property of their features
+    async fn group_by_time(
+        &self,
+        grouping: &TimeGrouping,
+        sources: &[SourceReference],
+    ) -> Result<Vec<TimeBucket>> {
+        let feature_ids: Vec<FeatureId> = sources.iter().filter_map(|s| s.feature_id).collect();
+        let timestamps = if feature_ids.is_empty() {
+            HashMap::new()
+        } else {
+            self.spatial_store
+                .get_features(&feature_ids)
+                .await?
+                .into_iter()
+                .filter_map(|feature| {
+                    let timestamp =
+                        feature.properties.get(&grouping.property).and_then(parse_timestamp)?;
+                    Some((feature.id, timestamp))
+                })
+                .collect()
+        };
+
+        Ok(group_sources_by_time(sources, &timestamps, grouping.interval))
+    }
+
+    /// Phase 3: Ground ranked results in their chunks
+    ///
+    /// With `chunk_matches`, the sources record whether the chunk or its
+    /// feature matched the spatial filter.
+    async fn ground_results(
+        &self,
+        results: &[ScoredResult],
+        chunk_matches: Option<&HashSet<ChunkId>>,
+    ) -> Result<Vec<SourceReference>> {
         let chunk_ids: Vec<ChunkId> = results.iter().map(|r| r.chunk_id).collect();
         let chunks = self.document_store.get_chunks(&chunk_ids).await?;
 
@@ -264,8 +1036,18 @@ where
                     feature_id: chunk.spatial_ref,
                     document_path: chunk.source.document_path.clone(),
                     page: chunk.source.page,
+                    offset: Some(chunk.source.offset),
                     excerpt: chunk.content.clone(),
                     score: result.score,
+                    spatial_match: chunk_matches.map(|matches| {
+                        if matches.contains(&chunk.id) {
+                            SpatialMatch::Chunk
+                        } else {
+                            SpatialMatch::Feature
+                        }
+                    }),
+                    source_url: None,
+                    confidence: None,
                 });
             }
         }
@@ -273,16 +1055,97 @@ where
         Ok(sources)
     }
 
+    /// Attribution lines of the datasets behind the sources, in source order
+    ///
+    /// Sources are matched to datasets by path, the same way dataset
+    /// visibility is applied. Only datasets with an attribution are loaded.
+    async fn attributions(&self, sources: &[SourceReference]) -> Result<Vec<String>> {
+        if sources.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut by_path = HashMap::new();
+        for meta in self.spatial_store.list_datasets().await? {
+            if meta.attribution.is_none() {
+                continue;
+            }
+            if let Some(dataset) = self.spatial_store.get_dataset(meta.id).await? {
+                if let Some(attribution) = dataset.attribution {
+                    by_path.insert(dataset.path.to_string_lossy().to_string(), attribution);
+                }
+            }
+        }
+
+        Ok(distinct_attributions(
+            sources
+                .iter()
+                .filter_map(|source| by_path.get(&source.document_path))
+                .map(String::as_str),
+        ))
+    }
+
+    /// Fill the source URL of each source from the template
+    ///
+    /// Sources are matched to datasets by path, like attributions. Sources
+    /// whose dataset cannot be found keep no URL.
+    async fn fill_source_urls(
+        &self,
+        template: &SourceUrlTemplate,
+        sources: &mut [SourceReference],
+    ) -> Result<()> {
+        if sources.is_empty() {
+            return Ok(());
+        }
+
+        let mut datasets = Vec::new();
+        for meta in self.spatial_store.list_datasets().await? {
+            if let Some(dataset) = self.spatial_store.get_dataset(meta.id).await? {
+                datasets.push(dataset);
+            }
+        }
+
+        for source in sources.iter_mut() {
+            let dataset = datasets
+                .iter()
+                .find(|dataset| dataset.path.to_string_lossy() == source.document_path);
+            let Some(dataset) = dataset else {
+                continue;
+            };
+            let document_path = relative_document_path(&source.document_path, &dataset.path);
+            let dataset = dataset_slug(&dataset.name);
+            source.source_url = Some(template.url(&CitationFields {
+                dataset: &dataset,
+                document_path: &document_path,
+                page: source.page,
+                offset: source.offset,
+            }));
+        }
+
+        Ok(())
+    }
+
     /// Build ranking details for explanation
+    ///
+    /// `prior` holds the first-pass position and score of reranked results,
+    /// `merged` the overlapping chunks merged into each result.
     async fn build_ranking_details(
         &self,
         results: &[ScoredResult],
         sources: &[SourceReference],
+        prior: &HashMap<ChunkId, (usize, f32)>,
+        merged: &HashMap<ChunkId, Vec<ChunkId>>,
     ) -> Result<Vec<RankingDetail>> {
         let mut details = Vec::new();
 
-        for (result, _source) in results.iter().zip(sources.iter()) {
-            let score_explanation = if result.spatial_score.is_some() {
+        for (index, (result, source)) in results.iter().zip(sources.iter()).enumerate() {
+            let rank = index + 1;
+            let pre_rerank = prior.get(&result.chunk_id).copied();
+            let mut score_explanation = if let Some((pre_rank, pre_score)) = pre_rerank {
+                format!(
+                    "Rerank score {:.3} (first pass {:.3}), rank {} -> {}",
+                    result.score, pre_score, pre_rank, rank
+                )
+            } else if result.spatial_score.is_some() {
                 format!(
                     "Combined spatial ({:.3}) and semantic ({:.3}) scores",
                     result.spatial_score.unwrap_or(0.0),
@@ -291,12 +1154,30 @@ where
             } else {
                 format!("Semantic similarity score: {:.3}", result.score)
             };
+            let merged_chunks = merged.get(&result.chunk_id).cloned().unwrap_or_default();
+            if !merged_chunks.is_empty() {
+                score_explanation.push_str(&format!(
+                    "; merged with {} overlapping chunk(s)",
+                    merged_chunks.len()
+                ));
+            }
+            let percentile = source.confidence.map(|confidence| confidence.percentile);
+            if let Some(confidence) = &source.confidence {
+                score_explanation.push_str(&format!(
+                    "; semantic score at percentile {:.1} of the index baseline ({} confidence)",
+                    confidence.percentile, confidence.level
+                ));
+            }
 
             details.push(RankingDetail {
                 chunk_id: result.chunk_id,
+                rank,
+                pre_rerank_rank: pre_rerank.map(|(pre_rank, _)| pre_rank),
                 spatial_score: result.spatial_score,
-                semantic_score: Some(result.score),
+                semantic_score: Some(pre_rerank.map_or(result.score, |(_, score)| score)),
                 final_score: result.score,
+                percentile,
+                merged_chunks,
                 score_explanation,
             });
         }
@@ -304,25 +1185,57 @@ where
         Ok(details)
     }
 
-    /// Generate answer from sources (placeholder implementation)
+    /// Answer from the sources
+    ///
+    /// With a generator the answer is generated from the excerpts and checked
+    /// against them; otherwise it lists the top excerpts.
     async fn generate_answer(
         &self,
         plan: &QueryPlan,
         sources: &[SourceReference],
-    ) -> Result<String> {
-        // In a full implementation, this would use the Generator trait
-        // For now, return a simple concatenation of source excerpts
+    ) -> Result<(String, Option<AnswerGrounding>)> {
+        if let Some(generator) = &self.generator {
+            let excerpts: Vec<&str> = sources.iter().map(|s| s.excerpt.as_str()).collect();
+            let answer = generator.generate(&plan.text_query, &excerpts)?;
+            let grounding = check_grounding(&answer, sources, &self.embedder, &self.grounding)?;
+            let answer = grounding.apply(&answer, self.grounding.action);
+            return Ok((answer, Some(grounding)));
+        }
+
         if sources.is_empty() {
-            return Ok("No relevant information found.".to_string());
+            return Ok(("No relevant information found.".to_string(), None));
         }
 
         let context: Vec<String> =
             sources.iter().take(3).map(|s| format!("- {}", s.excerpt)).collect();
 
-        Ok(format!(
+        let answer = format!(
             "Based on the query '{}', here are the relevant findings:\n\n{}",
             plan.text_query,
             context.join("\n")
-        ))
+        );
+        Ok((answer, None))
     }
 }
+
+/// Component-wise mean of equally long vectors
+fn mean_vector(vectors: &[Vec<f32>]) -> Vec<f32> {
+    let mut mean = vec![0.0; vectors[0].len()];
+    for vector in vectors {
+        for (sum, value) in mean.iter_mut().zip(vector) {
+            *sum += value;
+        }
+    }
+    let count = vectors.len() as f32;
+    mean.iter_mut().for_each(|sum| *sum /= count);
+    mean
+}
+
+/// Check whether a plan's dataset name refers to a dataset
+///
+/// Names match ignoring case, with or without the file extension uploaded
+/// datasets are named with, so `reports` finds `reports.geojson`.
+fn dataset_named(dataset: &str, name: &str) -> bool {
+    let stem = std::path::Path::new(dataset).file_stem().and_then(|s| s.to_str());
+    dataset.eq_ignore_ascii_case(name) || stem.is_some_and(|stem| stem.eq_ignore_ascii_case(name))
+}
@@ -0,0 +1,338 @@
+//! Second-pass reranking of the best candidates of a query
+//!
+//! Embedding similarity finds the right neighbourhood but orders the top of
+//! the list coarsely. A plan can ask for a [`Reranker`] to score a pool of the
+//! best candidates against the query text; the pool is reordered by those
+//! scores before the results are cut to `top_k`. [`LexicalReranker`] scores
+//! with BM25 over the pool and needs no model server. [`LlmReranker`] asks an
+//! Ollama model to rate each candidate.
+
+use async_trait::async_trait;
+use georag_core::error::{GeoragError, Result};
+use georag_core::llm::OllamaGenerator;
+use georag_core::models::ChunkId;
+use georag_core::processing::text::truncate_chars;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Candidates reranked when the plan does not set a pool size
+pub const DEFAULT_RERANK_POOL: usize = 50;
+
+/// Largest candidate pool a plan may ask for
+pub const MAX_RERANK_POOL: usize = 500;
+
+/// Model rating candidates when none is configured
+pub const DEFAULT_RERANK_MODEL: &str = "llama3.2:1b";
+
+/// Candidates the LLM reranker rates at the same time by default
+pub const DEFAULT_RERANK_CONCURRENCY: usize = 4;
+
+/// Characters of a candidate's text shown to the rating model
+const MAX_PROMPT_CHARS: usize = 2000;
+
+/// Reranking applied to the candidate pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RerankMode {
+    /// Keep the first-pass order
+    #[default]
+    None,
+
+    /// BM25 over the candidate pool
+    Lexical,
+
+    /// Ratings from an Ollama model
+    Llm,
+}
+
+impl fmt::Display for RerankMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RerankMode::None => "none",
+            RerankMode::Lexical => "lexical",
+            RerankMode::Llm => "llm",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for RerankMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(RerankMode::None),
+            "lexical" => Ok(RerankMode::Lexical),
+            "llm" => Ok(RerankMode::Llm),
+            _ => Err(format!("Invalid rerank mode '{}': expected none, lexical or llm", s)),
+        }
+    }
+}
+
+/// A candidate as seen by a reranker
+#[derive(Debug, Clone, PartialEq)]
+pub struct RerankCandidate {
+    pub chunk_id: ChunkId,
+
+    /// Chunk text
+    pub text: String,
+
+    /// First-pass score
+    pub score: f32,
+}
+
+/// Scores candidates against the query text
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Name reported in explanations
+    fn name(&self) -> &str;
+
+    /// Score each candidate, in candidate order, between 0.0 and 1.0
+    async fn rerank(&self, query: &str, candidates: Vec<RerankCandidate>) -> Result<Vec<f32>>;
+}
+
+/// BM25 over the candidate pool
+///
+/// Term statistics come from the pool alone, so no corpus index is needed.
+/// Scores are divided by the best one; a pool sharing no term with the query
+/// scores 0.0 throughout.
+#[derive(Debug, Clone, Copy)]
+pub struct LexicalReranker {
+    /// Term frequency saturation
+    pub k1: f32,
+
+    /// Document length normalization
+    pub b: f32,
+}
+
+impl Default for LexicalReranker {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+impl LexicalReranker {
+    /// BM25 scores of texts for a query, normalized to the best one
+    pub fn scores(&self, query: &str, texts: &[&str]) -> Vec<f32> {
+        let documents: Vec<Vec<String>> = texts.iter().map(|text| terms(text)).collect();
+        let query_terms: HashSet<String> = terms(query).into_iter().collect();
+        if documents.is_empty() || query_terms.is_empty() {
+            return vec![0.0; documents.len()];
+        }
+
+        let count = documents.len() as f32;
+        let average_length =
+            (documents.iter().map(Vec::len).sum::<usize>() as f32 / count).max(1.0);
+        let idf: HashMap<&str, f32> = query_terms
+            .iter()
+            .map(|term| {
+                let containing = documents.iter().filter(|d| d.contains(term)).count() as f32;
+                let idf = (1.0 + (count - containing + 0.5) / (containing + 0.5)).ln();
+                (term.as_str(), idf)
+            })
+            .collect();
+
+        let raw: Vec<f32> = documents
+            .iter()
+            .map(|document| {
+                let length = document.len() as f32;
+                idf.iter()
+                    .map(|(term, idf)| {
+                        let frequency = document.iter().filter(|t| t == term).count() as f32;
+                        let norm = self.k1 * (1.0 - self.b + self.b * length / average_length);
+                        idf * frequency * (self.k1 + 1.0) / (frequency + norm)
+                    })
+                    .sum()
+            })
+            .collect();
+
+        let best = raw.iter().copied().fold(0.0_f32, f32::max);
+        if best <= 0.0 {
+            return vec![0.0; raw.len()];
+        }
+        raw.into_iter().map(|score| score / best).collect()
+    }
+}
+
+#[async_trait]
+impl Reranker for LexicalReranker {
+    fn name(&self) -> &str {
+        "bm25"
+    }
+
+    async fn rerank(&self, query: &str, candidates: Vec<RerankCandidate>) -> Result<Vec<f32>> {
+        let texts: Vec<&str> = candidates.iter().map(|c| c.text.as_str()).collect();
+        Ok(self.scores(query, &texts))
+    }
+}
+
+/// Ratings from an Ollama model
+///
+/// Each candidate is rated from 0 to 10 by its own prompt, at most
+/// `concurrency` at a time. A candidate whose answer holds no rating scores
+/// 0.0; a request that still fails after the generator's retries fails the
+/// query.
+#[derive(Debug, Clone)]
+pub struct LlmReranker {
+    generator: OllamaGenerator,
+    concurrency: usize,
+}
+
+impl LlmReranker {
+    /// Create a reranker rating with the given generator
+    pub fn new(generator: OllamaGenerator) -> Self {
+        Self {
+            generator,
+            concurrency: DEFAULT_RERANK_CONCURRENCY,
+        }
+    }
+
+    /// Set how many candidates are rated at the same time
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+#[async_trait]
+impl Reranker for LlmReranker {
+    fn name(&self) -> &str {
+        self.generator.model_name()
+    }
+
+    async fn rerank(&self, query: &str, candidates: Vec<RerankCandidate>) -> Result<Vec<f32>> {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        let count = candidates.len();
+
+        for (index, candidate) in candidates.into_iter().enumerate() {
+            let permit = permits.clone().acquire_owned().await.map_err(|e| {
+                GeoragError::GeneratorUnavailable {
+                    reason: format!("Rating queue closed: {}", e),
+                    remediation: "Retry the query".to_string(),
+                }
+            })?;
+            let generator = self.generator.clone();
+            let prompt = rating_prompt(query, &candidate.text);
+            tasks.spawn(async move {
+                let answer = generator.complete(&prompt).await;
+                drop(permit);
+                answer.map(|answer| (index, answer))
+            });
+        }
+
+        let mut scores = vec![0.0; count];
+        while let Some(joined) = tasks.join_next().await {
+            let (index, answer) = joined.map_err(|e| GeoragError::GeneratorUnavailable {
+                reason: format!("Rating task failed: {}", e),
+                remediation: "Retry the query".to_string(),
+            })??;
+            scores[index] = match parse_rating(&answer) {
+                Some(rating) => rating,
+                None => {
+                    tracing::warn!(
+                        model = %self.generator.model_name(),
+                        answer = %answer.trim(),
+                        "Rerank answer holds no rating"
+                    );
+                    0.0
+                }
+            };
+        }
+
+        Ok(scores)
+    }
+}
+
+/// Lowercased alphanumeric terms of a text
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Prompt asking the model to rate one candidate
+fn rating_prompt(query: &str, text: &str) -> String {
+    let text = truncate_chars(text, MAX_PROMPT_CHARS);
+    format!(
+        "Rate how well the passage answers the query, from 0 (unrelated) to 10 \
+         (answers it fully). Reply with the number only.\n\n\
+         Query: {}\n\nPassage: {}\n\nRating:",
+        query.trim(),
+        text.trim()
+    )
+}
+
+/// First number in a model's answer as a score between 0.0 and 1.0
+fn parse_rating(answer: &str) -> Option<f32> {
+    let start = answer.find(|c: char| c.is_ascii_digit())?;
+    let number: String = answer[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let rating: f32 = number.trim_end_matches('.').parse().ok()?;
+    Some(rating.clamp(0.0, 10.0) / 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rerank_mode_round_trips() {
+        for mode in [RerankMode::None, RerankMode::Lexical, RerankMode::Llm] {
+            assert_eq!(mode.to_string().parse::<RerankMode>(), Ok(mode));
+        }
+        assert_eq!(" LLM ".parse::<RerankMode>(), Ok(RerankMode::Llm));
+        assert!("cross-encoder".parse::<RerankMode>().is_err());
+    }
+
+    #[test]
+    fn test_bm25_prefers_passages_with_rare_query_terms() {
+        let scores = LexicalReranker::default().scores(
+            "culvert flooding",
+            &[
+                "The park has a new playground and benches",
+                "Flooding closed the road after the culvert collapsed",
+                "Flooding was reported along the river",
+            ],
+        );
+
+        assert_eq!(scores[0], 0.0);
+        assert_eq!(scores[1], 1.0);
+        assert!(scores[2] > 0.0 && scores[2] < 1.0, "{:?}", scores);
+    }
+
+    #[test]
+    fn test_bm25_without_shared_terms_scores_zero() {
+        let reranker = LexicalReranker::default();
+
+        assert_eq!(reranker.scores("tram depot", &["bakery hours", "bus stop"]), vec![0.0, 0.0]);
+        assert_eq!(reranker.scores("", &["bakery hours"]), vec![0.0]);
+        assert!(reranker.scores("tram", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_rating() {
+        assert_eq!(parse_rating("7"), Some(0.7));
+        assert_eq!(parse_rating(" Rating: 8.5/10"), Some(0.85));
+        assert_eq!(parse_rating("10."), Some(1.0));
+        assert_eq!(parse_rating("42"), Some(1.0));
+        assert_eq!(parse_rating("not relevant"), None);
+    }
+
+    #[test]
+    fn test_rating_prompt_truncates_long_passages() {
+        let prompt = rating_prompt(" flood ", &"x".repeat(MAX_PROMPT_CHARS * 2));
+
+        assert!(prompt.contains("Query: flood\n"));
+        assert!(prompt.contains(&"x".repeat(MAX_PROMPT_CHARS)));
+        assert!(!prompt.contains(&"x".repeat(MAX_PROMPT_CHARS + 1)));
+    }
+}
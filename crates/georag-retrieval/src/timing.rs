@@ -0,0 +1,124 @@
+//! Wall-clock timing of retrieval phases
+//!
+//! A [`Stopwatch`] is started once per query and lapped at each phase
+//! boundary, so phases are contiguous and their offsets share one origin.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// When a phase started and how long it ran, in milliseconds since the query began
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub start_ms: f64,
+    pub duration_ms: f64,
+}
+
+/// Per-phase wall time of one query
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryTimings {
+    /// Spatial candidate generation, including visibility and keyword filters
+    pub spatial: PhaseTiming,
+
+    /// Attribute filtering, when the plan has attribute filters
+    pub attribute: Option<PhaseTiming>,
+
+    /// Embedding of the query text, when results are reranked
+    pub embedding: Option<PhaseTiming>,
+
+    /// Vector similarity search, when results are reranked
+    pub vector_search: Option<PhaseTiming>,
+
+    /// Scoring and the minimum score threshold
+    pub ranking: PhaseTiming,
+
+    /// Second-pass reranking, when the plan asks for it
+    pub rerank: Option<PhaseTiming>,
+
+    /// Source grounding, time grouping, answer generation and redaction
+    pub enrichment: PhaseTiming,
+
+    /// Whole query
+    pub total_ms: f64,
+}
+
+impl QueryTimings {
+    /// Phases that ran, in execution order
+    pub fn phases(&self) -> Vec<(&'static str, PhaseTiming)> {
+        [
+            ("spatial", Some(self.spatial)),
+            ("attribute", self.attribute),
+            ("embedding", self.embedding),
+            ("vector_search", self.vector_search),
+            ("ranking", Some(self.ranking)),
+            ("rerank", self.rerank),
+            ("enrichment", Some(self.enrichment)),
+        ]
+        .into_iter()
+        .filter_map(|(name, timing)| timing.map(|t| (name, t)))
+        .collect()
+    }
+}
+
+/// Measures consecutive phases against a common origin
+#[derive(Debug, Clone)]
+pub struct Stopwatch {
+    origin: Instant,
+    mark: Instant,
+}
+
+impl Stopwatch {
+    /// Start timing; the first phase begins now
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self { origin: now, mark: now }
+    }
+
+    /// End the current phase and begin the next one
+    pub fn lap(&mut self) -> PhaseTiming {
+        let now = Instant::now();
+        let timing = PhaseTiming {
+            start_ms: millis(self.mark - self.origin),
+            duration_ms: millis(now - self.mark),
+        };
+        self.mark = now;
+        timing
+    }
+
+    /// Time since the stopwatch was started, in milliseconds
+    pub fn elapsed_ms(&self) -> f64 {
+        millis(self.origin.elapsed())
+    }
+}
+
+fn millis(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_laps_are_contiguous() {
+        let mut stopwatch = Stopwatch::start();
+        std::thread::sleep(Duration::from_millis(5));
+        let first = stopwatch.lap();
+        let second = stopwatch.lap();
+
+        assert_eq!(first.start_ms, 0.0);
+        assert!(first.duration_ms >= 5.0);
+        assert!((second.start_ms - (first.start_ms + first.duration_ms)).abs() < 1e-6);
+        assert!(stopwatch.elapsed_ms() >= second.start_ms + second.duration_ms);
+    }
+
+    #[test]
+    fn test_phases_skip_phases_that_did_not_run() {
+        let timings = QueryTimings {
+            embedding: Some(PhaseTiming::default()),
+            ..Default::default()
+        };
+        let names: Vec<&str> = timings.phases().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["spatial", "embedding", "ranking", "enrichment"]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Integration tests for dataset access tags in the retrieval pipeline
+//!
+//! A caller restricted to public datasets must never receive chunks from a
+//! restricted dataset, even when that chunk is the best semantic match.
+
+mod common;
+
+use common::{chunk, dataset, KeywordEmbedder, Stores};
+use georag_core::models::TagVisibility;
+use georag_retrieval::{QueryPlan, RetrievalPipeline};
+use georag_store::ports::SpatialStore;
+
+const RESTRICTED_PATH: &str = "/data/informants.geojson";
+const RESTRICTED_TEXT: &str = "flood damage report for the river district";
+
+const VOCABULARY: [&str; 8] =
+    ["flood", "damage", "report", "river", "district", "park", "bench", "market"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// Stores with a public, a restricted and an untagged dataset, one chunk each
+async fn setup() -> RetrievalPipeline<KeywordEmbedder> {
+    let stores = Stores::new();
+    let spatial = &stores.spatial;
+
+    let public = spatial.store_dataset(&dataset("parks")).await.unwrap();
+    let restricted = spatial.store_dataset(&dataset("informants")).await.unwrap();
+    spatial.store_dataset(&dataset("markets")).await.unwrap();
+
+    spatial.set_dataset_tags(public, &["public".to_string()]).await.unwrap();
+    spatial.set_dataset_tags(restricted, &["restricted".to_string()]).await.unwrap();
+
+    let chunks = vec![
+        chunk(1, "parks", None, "park bench near the river flood wall"),
+        chunk(2, "informants", None, RESTRICTED_TEXT),
+        chunk(3, "markets", None, "market stalls damage after the storm"),
+        // Not attributable to any dataset
+        chunk(4, "unknown", None, "flood damage report"),
+    ];
+    stores.index(&chunks, &embedder()).await;
+
+    stores.pipeline(embedder())
+}
+
+fn query() -> QueryPlan {
+    QueryPlan::new("flood damage report river district").with_top_k(10)
+}
+
+#[tokio::test]
+async fn test_unrestricted_query_ranks_restricted_dataset_first() {
+    let pipeline = setup().await;
+
+    let result = pipeline.execute(&query()).await.unwrap();
+
+    assert_eq!(result.sources[0].document_path, RESTRICTED_PATH);
+}
+
+#[tokio::test]
+async fn test_restricted_caller_never_sees_restricted_dataset() {
+    let pipeline = setup().await;
+    let plan = query().with_visibility(TagVisibility::only(["public"])).with_explain(true);
+
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert!(!result.sources.is_empty());
+    for source in &result.sources {
+        assert_ne!(source.document_path, RESTRICTED_PATH);
+        assert_ne!(source.document_path, "/data/unknown.geojson");
+        assert!(!source.excerpt.contains(RESTRICTED_TEXT));
+    }
+    assert!(!result.answer.contains(RESTRICTED_TEXT));
+
+    let paths: Vec<&str> = result.sources.iter().map(|s| s.document_path.as_str()).collect();
+    assert!(paths.contains(&"/data/parks.geojson"));
+    assert!(paths.contains(&"/data/markets.geojson"), "untagged datasets stay visible");
+}
+
+#[tokio::test]
+async fn test_restricted_caller_without_rerank_never_sees_restricted_dataset() {
+    let pipeline = setup().await;
+    let plan = query()
+        .with_semantic_rerank(false)
+        .with_visibility(TagVisibility::only(["public"]));
+
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert!(result.sources.iter().all(|s| s.document_path != RESTRICTED_PATH));
+    assert!(!result.answer.contains(RESTRICTED_TEXT));
+}
@@ -0,0 +1,148 @@
+//! Integration tests for attribute filters in the retrieval pipeline
+//!
+//! Properties listed in `chunk_properties` are copied into chunk metadata at
+//! build time and filtered there; other properties fall back to a lookup of
+//! the chunk's feature. Both paths must select the same chunks.
+
+mod common;
+
+use common::{dataset, KeywordEmbedder, Stores};
+use georag_core::models::{Dataset, DatasetId, Feature, FeatureId, Geometry};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{AttributeFilter, FilterLevel, QueryPlan, QueryResult, RetrievalPipeline};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::HashMap;
+
+const VOCABULARY: [&str; 4] = ["school", "park", "village", "building"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+fn feature(id: u64, content: &str, category: &str, year: i64) -> Feature {
+    let mut properties = HashMap::new();
+    properties.insert("content".to_string(), json!(content));
+    properties.insert("category".to_string(), json!(category));
+    properties.insert("year".to_string(), json!(year));
+    Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.2), properties, 4326)
+}
+
+/// Four places; only `category` is copied into chunk metadata
+async fn setup() -> RetrievalPipeline<KeywordEmbedder> {
+    let stores = Stores::new();
+
+    let features = vec![
+        feature(1, "village school building", "school", 2019),
+        feature(2, "old school building", "school", 1985),
+        feature(3, "village park", "park", 2019),
+        feature(4, "park near the school", "park", 2001),
+    ];
+    stores.spatial.store_features(&features).await.unwrap();
+
+    let dataset = Dataset {
+        id: DatasetId(1),
+        feature_count: 4,
+        ..dataset("places")
+    };
+    let chunks = ChunkGenerator::default()
+        .with_properties(["category"])
+        .generate_chunks(&dataset, &features);
+    stores.index(&chunks, &embedder()).await;
+
+    stores.pipeline(embedder())
+}
+
+fn query() -> QueryPlan {
+    QueryPlan::new("village school").with_top_k(10).with_explain(true)
+}
+
+fn feature_ids(result: &QueryResult) -> Vec<u64> {
+    let mut ids: Vec<u64> =
+        result.sources.iter().filter_map(|s| s.feature_id).map(|f| f.0).collect();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn test_filter_on_chunk_metadata() {
+    let pipeline = setup().await;
+    let plan = query().with_attribute_filter(AttributeFilter::new("category", "school"));
+
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert_eq!(feature_ids(&result), vec![1, 2]);
+    let phase = result.explanation.unwrap().attribute_phase.unwrap();
+    assert_eq!(phase.candidates_before, 4);
+    assert_eq!(phase.candidates_after, 2);
+    assert_eq!(phase.filters[0].level, FilterLevel::Chunk);
+    assert_eq!(phase.filters[0].chunks_evaluated, 4);
+    assert_eq!(phase.filters[0].features_looked_up, 0);
+}
+
+#[tokio::test]
+async fn test_filter_falls_back_to_feature_properties() {
+    let pipeline = setup().await;
+    let plan = query().with_attribute_filter(AttributeFilter::new("year", "2019"));
+
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert_eq!(feature_ids(&result), vec![1, 3]);
+    let phase = result.explanation.unwrap().attribute_phase.unwrap();
+    assert_eq!(phase.filters[0].level, FilterLevel::Feature);
+    assert_eq!(phase.filters[0].features_looked_up, 4);
+}
+
+#[tokio::test]
+async fn test_chunk_level_mismatch_skips_feature_lookup() {
+    let pipeline = setup().await;
+    let plan = query()
+        .with_attribute_filter(AttributeFilter::new("category", "park"))
+        .with_attribute_filter(AttributeFilter::new("year", "2019"));
+
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert_eq!(feature_ids(&result), vec![3]);
+    let phase = result.explanation.unwrap().attribute_phase.unwrap();
+    assert_eq!(phase.filters[0].level, FilterLevel::Chunk);
+    // Only the two parks reach the feature lookup
+    assert_eq!(phase.filters[1].features_looked_up, 2);
+}
+
+#[tokio::test]
+async fn test_explanation_times_each_phase() {
+    let pipeline = setup().await;
+    let plan = query().with_attribute_filter(AttributeFilter::new("category", "school"));
+
+    let explanation = pipeline.execute(&plan).await.unwrap().explanation.unwrap();
+    let timings = &explanation.timings;
+
+    // Phase explanations carry the same numbers as the breakdown
+    assert_eq!(Some(explanation.attribute_phase.unwrap().timing), timings.attribute);
+    let semantic = explanation.semantic_phase.unwrap();
+    assert_eq!(Some(semantic.embedding_timing), timings.embedding);
+    assert_eq!(Some(semantic.search_timing), timings.vector_search);
+    assert_eq!(explanation.spatial_phase.timing, timings.spatial);
+
+    // Phases follow each other without gaps and fit in the total
+    let phases = timings.phases();
+    assert_eq!(phases.len(), 6);
+    for pair in phases.windows(2) {
+        let (previous, next) = (pair[0].1, pair[1].1);
+        assert!((next.start_ms - (previous.start_ms + previous.duration_ms)).abs() < 1e-6);
+    }
+    let last = phases[phases.len() - 1].1;
+    assert!(timings.total_ms >= last.start_ms + last.duration_ms);
+}
+
+#[test]
+fn test_parse_attribute_filter() {
+    let filter: AttributeFilter = "category = school".parse().unwrap();
+    assert_eq!(filter, AttributeFilter::new("category", "school"));
+
+    let filter: AttributeFilter = "note=a=b".parse().unwrap();
+    assert_eq!(filter.value, "a=b");
+
+    assert!("category".parse::<AttributeFilter>().is_err());
+    assert!("=school".parse::<AttributeFilter>().is_err());
+}
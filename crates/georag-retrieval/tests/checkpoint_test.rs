@@ -0,0 +1,253 @@
+//! Integration tests for checkpointed, resumable index builds
+//!
+//! A build interrupted by a failing embedder leaves a checkpoint and the
+//! embeddings of completed batches behind. Resuming embeds only the rest
+//! and must produce the same index as an uninterrupted build.
+
+use chrono::Utc;
+use georag_core::error::{GeoragError, Result};
+use georag_core::geo::models::Crs;
+use georag_core::llm::Embedder;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, Feature, FeatureId, Geometry,
+    GeometryType, TextChunk,
+};
+use georag_retrieval::IndexBuilder;
+use georag_store::memory::{
+    MemoryCheckpointStore, MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore,
+};
+use georag_store::ports::{CheckpointStore, DocumentStore, SpatialStore};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+const FEATURES: u64 = 10;
+const BATCH_SIZE: usize = 2;
+
+/// Embedder that counts embedded texts and fails after a number of batches
+struct CountingEmbedder {
+    name: &'static str,
+    embedded: Arc<AtomicUsize>,
+    batches: AtomicUsize,
+    fail_after: Option<usize>,
+}
+
+impl CountingEmbedder {
+    fn new(embedded: Arc<AtomicUsize>) -> Self {
+        Self {
+            name: "counting",
+            embedded,
+            batches: AtomicUsize::new(0),
+            fail_after: None,
+        }
+    }
+
+    fn failing_after(mut self, batches: usize) -> Self {
+        self.fail_after = Some(batches);
+        self
+    }
+}
+
+impl Embedder for CountingEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let batch = self.batches.fetch_add(1, Ordering::SeqCst);
+        if self.fail_after.is_some_and(|limit| batch >= limit) {
+            return Err(GeoragError::EmbedderUnavailable {
+                reason: "connection reset".to_string(),
+                remediation: "retry".to_string(),
+            });
+        }
+
+        self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+        Ok(texts.iter().map(|text| vec![text.len() as f32, 1.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        self.name
+    }
+}
+
+struct Stores {
+    spatial: Arc<MemorySpatialStore>,
+    vector: Arc<MemoryVectorStore>,
+    documents: Arc<MemoryDocumentStore>,
+    checkpoints: Arc<MemoryCheckpointStore>,
+}
+
+impl Stores {
+    fn builder(&self, embedder: CountingEmbedder) -> IndexBuilder<CountingEmbedder> {
+        IndexBuilder::new(
+            self.spatial.clone(),
+            self.vector.clone(),
+            self.documents.clone(),
+            embedder,
+            Crs::wgs84(),
+        )
+        .with_batch_size(BATCH_SIZE)
+        .with_checkpoints(self.checkpoints.clone(), 1)
+    }
+}
+
+async fn setup() -> Stores {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let dataset = Dataset {
+        id: DatasetId(0),
+        name: "places".to_string(),
+        path: PathBuf::from("/data/places.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: FEATURES as usize,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    };
+    let dataset_id = spatial.store_dataset(&dataset).await.unwrap();
+
+    let features: Vec<Feature> = (1..=FEATURES)
+        .map(|id| {
+            let mut properties = HashMap::new();
+            properties.insert("content".to_string(), json!(format!("place number {}", id)));
+            Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.2), properties, 4326)
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+    spatial.associate_features_with_dataset(dataset_id, features.iter().map(|f| f.id).collect());
+
+    Stores {
+        spatial,
+        vector: Arc::new(MemoryVectorStore::new()),
+        documents: Arc::new(MemoryDocumentStore::new()),
+        checkpoints: Arc::new(MemoryCheckpointStore::new()),
+    }
+}
+
+#[tokio::test]
+async fn test_resume_embeds_only_remaining_chunks() {
+    let stores = setup().await;
+    let datasets = stores.spatial.list_datasets().await.unwrap();
+    let embedded = Arc::new(AtomicUsize::new(0));
+
+    // First attempt dies after two batches
+    let crashing = stores.builder(CountingEmbedder::new(embedded.clone()).failing_after(2));
+    assert!(crashing.full_rebuild(&datasets, true, |_| {}).await.is_err());
+
+    let checkpoint = stores.checkpoints.load_checkpoint().await.unwrap().unwrap();
+    assert_eq!(checkpoint.embedded_chunks, 2 * BATCH_SIZE);
+    assert_eq!(checkpoint.attempts, 1);
+    assert_eq!(embedded.load(Ordering::SeqCst), 2 * BATCH_SIZE);
+
+    // Second attempt resumes; `force` must not wipe the stored progress
+    embedded.store(0, Ordering::SeqCst);
+    let resumed = stores.builder(CountingEmbedder::new(embedded.clone())).with_resume(true);
+    let result = resumed.full_rebuild(&datasets, true, |_| {}).await.unwrap();
+
+    assert_eq!(embedded.load(Ordering::SeqCst), FEATURES as usize - 2 * BATCH_SIZE);
+    let from = result.resumed_from.as_ref().unwrap();
+    assert_eq!(from.generation, checkpoint.generation);
+    assert_eq!(from.embedded_chunks, 2 * BATCH_SIZE);
+    assert!(stores.checkpoints.load_checkpoint().await.unwrap().is_none());
+    assert!(result.wall_time.as_secs_f64() >= from.elapsed_secs);
+
+    // The resumed index equals an uninterrupted build
+    let fresh = setup().await;
+    let fresh_datasets = fresh.spatial.list_datasets().await.unwrap();
+    let uninterrupted = fresh
+        .builder(CountingEmbedder::new(Arc::new(AtomicUsize::new(0))))
+        .full_rebuild(&fresh_datasets, true, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(result.index_hash, uninterrupted.index_hash);
+    assert_eq!(result.chunk_count, uninterrupted.chunk_count);
+    assert!(uninterrupted.resumed_from.is_none());
+}
+
+#[tokio::test]
+async fn test_resume_rejects_other_embedder() {
+    let stores = setup().await;
+    let datasets = stores.spatial.list_datasets().await.unwrap();
+
+    let crashing =
+        stores.builder(CountingEmbedder::new(Arc::new(AtomicUsize::new(0))).failing_after(1));
+    assert!(crashing.full_rebuild(&datasets, true, |_| {}).await.is_err());
+
+    let mut other = CountingEmbedder::new(Arc::new(AtomicUsize::new(0)));
+    other.name = "other";
+    let result = stores.builder(other).with_resume(true).full_rebuild(&datasets, false, |_| {});
+
+    assert!(matches!(result.await, Err(GeoragError::ConfigInvalid { .. })));
+}
+
+#[tokio::test]
+async fn test_rate_limit_paces_embeddings() {
+    let stores = setup().await;
+    let datasets = stores.spatial.list_datasets().await.unwrap();
+
+    // Ten embeddings at 40/s: the last batch cannot start before 8/40 s
+    let started = Instant::now();
+    stores
+        .builder(CountingEmbedder::new(Arc::new(AtomicUsize::new(0))))
+        .with_rate_limit(40.0)
+        .full_rebuild(&datasets, true, |_| {})
+        .await
+        .unwrap();
+
+    assert!(started.elapsed().as_secs_f64() >= 0.2);
+}
+
+#[tokio::test]
+async fn test_build_skips_chunks_without_text() {
+    let stores = setup().await;
+    let chunk = |id: u64, content: &str| TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: "/data/places.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: Some(FeatureId(id)),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: content.len(),
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    };
+    stores
+        .documents
+        .store_chunks(&[chunk(1, "harbour"), chunk(2, ""), chunk(3, " "), chunk(4, "?!")])
+        .await
+        .unwrap();
+
+    let embedded = Arc::new(AtomicUsize::new(0));
+    let result = stores.builder(CountingEmbedder::new(embedded.clone())).build().await.unwrap();
+
+    assert_eq!(result.chunk_count, 1);
+    assert_eq!(result.chunks_skipped, 3);
+    assert_eq!(embedded.load(Ordering::SeqCst), 1);
+}
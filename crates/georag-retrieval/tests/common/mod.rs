@@ -0,0 +1,116 @@
+//! Fixtures shared by the retrieval pipeline integration tests
+//!
+//! Each test binary declares `mod common;` and uses what it needs, so not
+//! every item is used everywhere.
+
+#![allow(dead_code)]
+
+use chrono::Utc;
+use georag_core::llm::Embedder;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, Embedding, FeatureId, GeometryType,
+    TextChunk,
+};
+use georag_retrieval::RetrievalPipeline;
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub use georag_core::llm::KeywordEmbedder;
+
+/// Empty in-memory stores
+pub struct Stores {
+    pub spatial: Arc<MemorySpatialStore>,
+    pub vector: Arc<MemoryVectorStore>,
+    pub documents: Arc<MemoryDocumentStore>,
+}
+
+impl Stores {
+    pub fn new() -> Self {
+        Self {
+            spatial: Arc::new(MemorySpatialStore::new()),
+            vector: Arc::new(MemoryVectorStore::new()),
+            documents: Arc::new(MemoryDocumentStore::new()),
+        }
+    }
+
+    /// Store `chunks` with their embeddings by `embedder`
+    pub async fn index(&self, chunks: &[TextChunk], embedder: &impl Embedder) {
+        self.documents.store_chunks(chunks).await.unwrap();
+
+        let texts: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+        let embeddings: Vec<Embedding> = embedder
+            .embed(&texts)
+            .unwrap()
+            .into_iter()
+            .zip(chunks)
+            .map(|(vector, chunk)| Embedding {
+                chunk_id: chunk.id,
+                vector,
+                spatial_metadata: None,
+                dataset_id: None,
+            })
+            .collect();
+        self.vector.store_embeddings(&embeddings).await.unwrap();
+    }
+
+    /// Retrieval pipeline over the stores
+    pub fn pipeline<E: Embedder>(self, embedder: E) -> RetrievalPipeline<E> {
+        RetrievalPipeline::new(self.spatial, self.vector, self.documents, embedder)
+    }
+}
+
+/// GeoJSON point dataset `name` without features, read from `/data/<name>.geojson`
+pub fn dataset(name: &str) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}.geojson", name)),
+        geometry_type: GeometryType::Point,
+        feature_count: 0,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+/// Chunk of dataset `name`'s document, attached to `feature` if given
+pub fn chunk(id: u64, name: &str, feature: Option<u64>, content: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: format!("/data/{}.geojson", name),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: feature.map(FeatureId),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: content.len(),
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
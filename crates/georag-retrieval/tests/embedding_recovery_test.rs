@@ -0,0 +1,176 @@
+//! Integration tests for recovering from faulty embedder answers
+//!
+//! The embedder drops, reorders or malforms vectors of larger batches the
+//! way an overloaded model server does. Whatever it answers, a chunk must
+//! only ever be stored with the vector of its own text.
+
+mod common;
+
+use common::{dataset, Stores};
+use georag_core::error::Result;
+use georag_core::geo::models::Crs;
+use georag_core::llm::Embedder;
+use georag_core::models::{ChunkId, Dataset, Feature, FeatureId, Geometry, TextChunk};
+use georag_retrieval::{IndexBuildResult, IndexBuilder};
+use georag_store::memory::MemoryCheckpointStore;
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const FEATURES: u64 = 12;
+const DIMENSIONS: usize = 3;
+
+/// How the embedder spoils its answer
+#[derive(Clone, Copy)]
+enum Fault {
+    /// Batches of more than this many texts lose their first vector
+    Truncate(usize),
+    /// Batches of more than one text come back in reverse order
+    Reverse,
+    /// This text gets a vector of the wrong size, even on its own
+    Malformed(&'static str),
+}
+
+struct FaultyEmbedder {
+    fault: Fault,
+}
+
+/// The vector a text should get, distinct for each text
+fn vector_of(text: &str) -> Vec<f32> {
+    let sum: u32 = text.bytes().map(u32::from).sum();
+    vec![text.len() as f32, sum as f32, 1.0]
+}
+
+impl Embedder for FaultyEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors: Vec<Vec<f32>> = texts.iter().map(|text| vector_of(text)).collect();
+        match self.fault {
+            Fault::Truncate(limit) if texts.len() > limit => {
+                vectors.remove(0);
+            }
+            Fault::Reverse => vectors.reverse(),
+            Fault::Malformed(bad) => {
+                for (text, vector) in texts.iter().zip(&mut vectors) {
+                    if *text == bad {
+                        vector.push(0.0);
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(vectors)
+    }
+
+    fn dimensions(&self) -> usize {
+        DIMENSIONS
+    }
+
+    fn model_name(&self) -> &str {
+        "faulty"
+    }
+}
+
+impl Stores {
+    fn builder(&self, fault: Fault) -> IndexBuilder<FaultyEmbedder> {
+        IndexBuilder::new(
+            self.spatial.clone(),
+            self.vector.clone(),
+            self.documents.clone(),
+            FaultyEmbedder { fault },
+            Crs::wgs84(),
+        )
+        .with_batch_size(8)
+    }
+
+    async fn build(&self, builder: IndexBuilder<FaultyEmbedder>) -> IndexBuildResult {
+        let datasets = self.spatial.list_datasets().await.unwrap();
+        builder.full_rebuild(&datasets, true, |_| {}).await.unwrap()
+    }
+
+    async fn chunks(&self) -> Vec<TextChunk> {
+        let ids = self.documents.list_chunk_ids().await.unwrap();
+        self.documents.get_chunks(&ids).await.unwrap()
+    }
+
+    /// Check every stored vector against its chunk's text, returning the chunks without one
+    async fn assert_vectors_match_chunks(&self) -> Vec<ChunkId> {
+        let mut missing = Vec::new();
+        for chunk in self.chunks().await {
+            match self.vector.get_embedding(chunk.id).await.unwrap() {
+                Some(embedding) => assert_eq!(
+                    embedding.vector,
+                    vector_of(&chunk.content),
+                    "chunk '{}' got another chunk's vector",
+                    chunk.content
+                ),
+                None => missing.push(chunk.id),
+            }
+        }
+        missing
+    }
+}
+
+async fn setup() -> Stores {
+    let stores = Stores::new();
+    let spatial = &stores.spatial;
+    let dataset = Dataset {
+        feature_count: FEATURES as usize,
+        ..dataset("places")
+    };
+    let dataset_id = spatial.store_dataset(&dataset).await.unwrap();
+
+    let features: Vec<Feature> = (1..=FEATURES)
+        .map(|id| {
+            let mut properties = HashMap::new();
+            properties.insert("content".to_string(), json!(format!("place number {}", id)));
+            Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.2), properties, 4326)
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+    spatial.associate_features_with_dataset(dataset_id, features.iter().map(|f| f.id).collect());
+
+    stores
+}
+
+#[tokio::test]
+async fn test_truncated_batches_are_split_until_they_fit() {
+    let stores = setup().await;
+    let result = stores.build(stores.builder(Fault::Truncate(2))).await;
+
+    assert!(result.unembedded_chunks.is_empty());
+    assert_eq!(result.chunk_count, FEATURES as usize);
+    assert!(stores.assert_vectors_match_chunks().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_reordered_batches_are_caught_by_order_check() {
+    let stores = setup().await;
+    let result = stores.build(stores.builder(Fault::Reverse).with_order_check(true)).await;
+
+    assert!(result.unembedded_chunks.is_empty());
+    assert!(stores.assert_vectors_match_chunks().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_chunk_failing_alone_is_left_unembedded() {
+    let stores = setup().await;
+    let builder = stores
+        .builder(Fault::Malformed("place number 7"))
+        .with_checkpoints(Arc::new(MemoryCheckpointStore::new()), 1);
+    let result = stores.build(builder).await;
+
+    let bad: Vec<ChunkId> = stores
+        .chunks()
+        .await
+        .into_iter()
+        .filter(|chunk| chunk.content == "place number 7")
+        .map(|chunk| chunk.id)
+        .collect();
+    assert_eq!(bad.len(), 1);
+    assert_eq!(result.unembedded_chunks, bad);
+
+    // The rest of its batch is embedded, each with its own vector
+    assert_eq!(stores.assert_vectors_match_chunks().await, bad);
+    assert_eq!(stores.chunks().await.len(), FEATURES as usize);
+}
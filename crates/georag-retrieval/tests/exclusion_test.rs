@@ -0,0 +1,185 @@
+//! Integration tests for areas excluded from a spatial filter
+//!
+//! Ten parks lie in a row across a 10 x 10 square used as the filter; each
+//! exclusion area covers all of the square, its western half or none of it.
+
+use chrono::Utc;
+use georag_core::error::Result;
+use georag_core::llm::Embedder;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    Dataset, DatasetId, Embedding, Feature, FeatureId, Geometry, GeometryType, SpatialFilter,
+    SpatialPredicate,
+};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{NamedAreaExpansion, QueryPlan, QueryResult, RetrievalPipeline};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Embeds every text the same, so ranking never drops a candidate
+struct ConstantEmbedder;
+
+impl Embedder for ConstantEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        "constant"
+    }
+}
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "parks".to_string(),
+        path: PathBuf::from("/data/parks.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 10,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn rect(min_x: f64, max_x: f64) -> Geometry {
+    Geometry::polygon(vec![vec![
+        [min_x, 0.0],
+        [max_x, 0.0],
+        [max_x, 10.0],
+        [min_x, 10.0],
+        [min_x, 0.0],
+    ]])
+}
+
+/// Park `i` (from 1) at x = i - 0.5
+async fn setup() -> RetrievalPipeline<ConstantEmbedder> {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let vector = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    let features: Vec<Feature> = (1..=10)
+        .map(|i| {
+            let properties = HashMap::from([("content".to_string(), json!(format!("park {}", i)))]);
+            Feature::with_geometry(
+                FeatureId(i),
+                Geometry::point(i as f64 - 0.5, 5.0),
+                properties,
+                4326,
+            )
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+
+    let chunks = ChunkGenerator::default().generate_chunks(&dataset(), &features);
+    documents.store_chunks(&chunks).await.unwrap();
+    let embeddings: Vec<Embedding> = chunks
+        .iter()
+        .map(|chunk| Embedding {
+            chunk_id: chunk.id,
+            vector: vec![1.0, 0.0],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vector.store_embeddings(&embeddings).await.unwrap();
+
+    RetrievalPipeline::new(spatial, vector, documents, ConstantEmbedder)
+}
+
+async fn query_excluding(
+    pipeline: &RetrievalPipeline<ConstantEmbedder>,
+    area: Geometry,
+) -> QueryResult {
+    let filter = SpatialFilter::new(SpatialPredicate::Within)
+        .geometry(rect(0.0, 10.0))
+        .exclude(area);
+    let plan = QueryPlan::new("park")
+        .with_top_k(20)
+        .with_explain(true)
+        .with_spatial_filter(filter)
+        .with_excluded_area(NamedAreaExpansion { name: "reserve".to_string(), vertices: 5 });
+    pipeline.execute(&plan).await.unwrap()
+}
+
+fn feature_ids(result: &QueryResult) -> Vec<u64> {
+    let mut ids: Vec<u64> =
+        result.sources.iter().filter_map(|s| s.feature_id).map(|f| f.0).collect();
+    ids.sort();
+    ids
+}
+
+/// Features and chunks the exclusions removed, as explained
+fn excluded(result: &QueryResult) -> (usize, usize) {
+    let spatial = &result.explanation.as_ref().unwrap().spatial_phase;
+    let exclusions = spatial.exclusions.as_ref().unwrap();
+    assert_eq!(exclusions.areas, 1);
+    assert_eq!(exclusions.named_areas[0].name, "reserve");
+    (exclusions.features_excluded, exclusions.chunks_excluded)
+}
+
+#[tokio::test]
+async fn test_exclusion_covering_the_filter_removes_everything() {
+    let pipeline = setup().await;
+    let result = query_excluding(&pipeline, rect(-1.0, 11.0)).await;
+
+    assert!(result.sources.is_empty());
+    assert_eq!(result.explanation.as_ref().unwrap().spatial_phase.features_matched, 0);
+    assert_eq!(excluded(&result), (10, 10));
+}
+
+#[tokio::test]
+async fn test_exclusion_overlapping_the_filter_removes_its_part() {
+    let pipeline = setup().await;
+    let result = query_excluding(&pipeline, rect(-1.0, 5.0)).await;
+
+    assert_eq!(feature_ids(&result), vec![6, 7, 8, 9, 10]);
+    assert_eq!(excluded(&result), (5, 5));
+}
+
+#[tokio::test]
+async fn test_exclusion_missing_the_filter_removes_nothing() {
+    let pipeline = setup().await;
+    let result = query_excluding(&pipeline, rect(20.0, 30.0)).await;
+
+    assert_eq!(feature_ids(&result), (1..=10).collect::<Vec<_>>());
+    assert_eq!(excluded(&result), (0, 0));
+    assert!(result.explanation.as_ref().unwrap().spatial_phase.exclusions.is_some());
+}
+
+#[tokio::test]
+async fn test_filter_without_exclusions_explains_none() {
+    let pipeline = setup().await;
+    let filter = SpatialFilter::new(SpatialPredicate::Within).geometry(rect(0.0, 10.0));
+    let plan = QueryPlan::new("park")
+        .with_top_k(20)
+        .with_explain(true)
+        .with_spatial_filter(filter);
+    let result = pipeline.execute(&plan).await.unwrap();
+
+    assert_eq!(result.sources.len(), 10);
+    assert!(result.explanation.unwrap().spatial_phase.exclusions.is_none());
+}
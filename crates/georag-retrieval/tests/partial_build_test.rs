@@ -0,0 +1,194 @@
+//! Integration tests for building selected datasets into an existing index
+//!
+//! Rebuilding a subset replaces only that subset's chunks and embeddings;
+//! the combined index must match a full build of the same content.
+
+mod common;
+
+use common::{dataset, Stores};
+use georag_core::error::{GeoragError, Result};
+use georag_core::geo::models::Crs;
+use georag_core::llm::Embedder;
+use georag_core::models::{
+    Dataset, DatasetId, DatasetMeta, Feature, FeatureId, Geometry, DEFAULT_BASELINE_PAIRS,
+};
+use georag_retrieval::IndexBuilder;
+use georag_store::ports::{DocumentStore, SpatialStore};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Embedder that counts embedded texts
+struct CountingEmbedder {
+    name: &'static str,
+    embedded: Arc<AtomicUsize>,
+}
+
+impl Embedder for CountingEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+        Ok(texts.iter().map(|text| vec![text.len() as f32, 1.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        self.name
+    }
+}
+
+impl Stores {
+    fn builder(
+        &self,
+        name: &'static str,
+        embedded: &Arc<AtomicUsize>,
+    ) -> IndexBuilder<CountingEmbedder> {
+        IndexBuilder::new(
+            self.spatial.clone(),
+            self.vector.clone(),
+            self.documents.clone(),
+            CountingEmbedder { name, embedded: embedded.clone() },
+            Crs::wgs84(),
+        )
+        .with_batch_size(2)
+    }
+
+    async fn datasets(&self, names: &[&str]) -> Vec<DatasetMeta> {
+        let datasets = self.spatial.list_datasets().await.unwrap();
+        datasets.into_iter().filter(|d| names.contains(&d.name.as_str())).collect()
+    }
+
+    /// Add a dataset with one feature per text, numbering features from `first_id`
+    async fn add_dataset(&self, name: &str, first_id: u64, texts: &[&str]) -> DatasetId {
+        let dataset = Dataset {
+            feature_count: texts.len(),
+            ..dataset(name)
+        };
+        let dataset_id = self.spatial.store_dataset(&dataset).await.unwrap();
+        self.set_texts(dataset_id, first_id, texts).await;
+        dataset_id
+    }
+
+    async fn set_texts(&self, dataset_id: DatasetId, first_id: u64, texts: &[&str]) {
+        let features: Vec<Feature> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let mut properties = HashMap::new();
+                properties.insert("content".to_string(), json!(text));
+                Feature::with_geometry(
+                    FeatureId(first_id + i as u64),
+                    Geometry::point(106.8, -6.2),
+                    properties,
+                    4326,
+                )
+            })
+            .collect();
+        self.spatial.store_features(&features).await.unwrap();
+        self.spatial
+            .associate_features_with_dataset(dataset_id, features.iter().map(|f| f.id).collect());
+    }
+}
+
+const PARKS: [&str; 3] = ["central park", "riverside park", "hill park"];
+const SCHOOLS: [&str; 2] = ["north school", "south school"];
+
+#[tokio::test]
+async fn test_selected_dataset_merges_into_existing_index() {
+    let embedded = Arc::new(AtomicUsize::new(0));
+
+    // Index built before the schools were added
+    let stores = Stores::new();
+    stores.add_dataset("parks", 1, &PARKS).await;
+    let builder = stores.builder("counting", &embedded);
+    let parks_only = builder
+        .full_rebuild(&stores.datasets(&["parks"]).await, true, |_| {})
+        .await
+        .unwrap();
+    let previous = builder.create_index_state(&parks_only);
+
+    stores.add_dataset("schools", 10, &SCHOOLS).await;
+    embedded.store(0, Ordering::SeqCst);
+    let merged = builder
+        .rebuild_datasets(&stores.datasets(&["schools"]).await, &previous, |_| {})
+        .await
+        .unwrap();
+
+    // Only the new dataset was embedded, but the result covers the whole index
+    assert_eq!(embedded.load(Ordering::SeqCst), SCHOOLS.len());
+    assert_eq!(merged.chunk_count, PARKS.len() + SCHOOLS.len());
+    assert_eq!(merged.datasets, vec!["schools".to_string()]);
+    let state = builder.create_index_state(&merged);
+    assert_eq!(state.dataset_built_at.keys().collect::<Vec<_>>(), vec!["schools"]);
+
+    // The score baseline is sampled again over the combined embeddings
+    let baseline = state.score_baseline.as_ref().unwrap();
+    assert_eq!(baseline.samples, DEFAULT_BASELINE_PAIRS);
+    assert_ne!(Some(baseline), previous.score_baseline.as_ref());
+
+    // The combined index equals a full build of both datasets
+    let fresh = Stores::new();
+    fresh.add_dataset("parks", 1, &PARKS).await;
+    fresh.add_dataset("schools", 10, &SCHOOLS).await;
+    let full = fresh
+        .builder("counting", &Arc::new(AtomicUsize::new(0)))
+        .full_rebuild(&fresh.datasets(&["parks", "schools"]).await, true, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(merged.index_hash, full.index_hash);
+    assert_eq!(merged.chunk_count, full.chunk_count);
+}
+
+#[tokio::test]
+async fn test_rebuilding_a_dataset_replaces_its_chunks() {
+    let embedded = Arc::new(AtomicUsize::new(0));
+    let stores = Stores::new();
+    let parks = stores.add_dataset("parks", 1, &PARKS).await;
+    stores.add_dataset("schools", 10, &SCHOOLS).await;
+
+    let builder = stores.builder("counting", &embedded);
+    let all = stores.datasets(&["parks", "schools"]).await;
+    let previous =
+        builder.create_index_state(&builder.full_rebuild(&all, true, |_| {}).await.unwrap());
+
+    // One park is renamed; rebuilding the parks keeps the chunk count
+    stores.set_texts(parks, 1, &["central gardens"]).await;
+    let result = builder
+        .rebuild_datasets(&stores.datasets(&["parks"]).await, &previous, |_| {})
+        .await
+        .unwrap();
+
+    assert_eq!(result.chunk_count, PARKS.len() + SCHOOLS.len());
+    assert_ne!(result.index_hash, previous.hash);
+    let ids = stores.documents.list_chunk_ids().await.unwrap();
+    let contents: Vec<String> = stores
+        .documents
+        .get_chunks(&ids)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|c| c.content)
+        .collect();
+    assert!(contents.contains(&"central gardens".to_string()));
+    assert!(!contents.contains(&"central park".to_string()));
+}
+
+#[tokio::test]
+async fn test_selected_build_rejects_other_embedder() {
+    let stores = Stores::new();
+    stores.add_dataset("parks", 1, &PARKS).await;
+    let datasets = stores.datasets(&["parks"]).await;
+
+    let embedded = Arc::new(AtomicUsize::new(0));
+    let builder = stores.builder("counting", &embedded);
+    let previous =
+        builder.create_index_state(&builder.full_rebuild(&datasets, true, |_| {}).await.unwrap());
+
+    let other = stores.builder("other", &embedded);
+    let result = other.rebuild_datasets(&datasets, &previous, |_| {}).await;
+
+    assert!(matches!(result, Err(GeoragError::ConfigInvalid { .. })));
+}
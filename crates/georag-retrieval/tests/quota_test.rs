@@ -0,0 +1,113 @@
+//! Integration tests for the chunk quota of index builds
+//!
+//! A rebuild generating more chunks than the workspace may hold is refused
+//! before anything is cleared, so the previous index keeps serving queries.
+
+mod common;
+
+use common::{dataset, Stores};
+use georag_core::error::{GeoragError, Result};
+use georag_core::geo::models::Crs;
+use georag_core::llm::Embedder;
+use georag_core::models::{
+    Dataset, Feature, FeatureId, Geometry, QuotaResource, WorkspaceQuotas, WorkspaceUsage,
+};
+use georag_retrieval::IndexBuilder;
+use georag_store::ports::{DocumentStore, SpatialStore};
+use serde_json::json;
+use std::collections::HashMap;
+
+const FEATURES: u64 = 6;
+
+struct LengthEmbedder;
+
+impl Embedder for LengthEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| vec![text.len() as f32, 1.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        "length"
+    }
+}
+
+impl Stores {
+    fn builder(&self) -> IndexBuilder<LengthEmbedder> {
+        IndexBuilder::new(
+            self.spatial.clone(),
+            self.vector.clone(),
+            self.documents.clone(),
+            LengthEmbedder,
+            Crs::wgs84(),
+        )
+    }
+}
+
+/// One dataset of `FEATURES` points, each giving one chunk
+async fn setup() -> Stores {
+    let stores = Stores::new();
+    let spatial = &stores.spatial;
+    let dataset = Dataset {
+        feature_count: FEATURES as usize,
+        ..dataset("places")
+    };
+    let dataset_id = spatial.store_dataset(&dataset).await.unwrap();
+
+    let features: Vec<Feature> = (1..=FEATURES)
+        .map(|id| {
+            let mut properties = HashMap::new();
+            properties.insert("content".to_string(), json!(format!("place number {}", id)));
+            Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.2), properties, 4326)
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+    spatial.associate_features_with_dataset(dataset_id, features.iter().map(|f| f.id).collect());
+
+    stores
+}
+
+fn max_chunks(limit: u64) -> WorkspaceQuotas {
+    WorkspaceQuotas {
+        max_chunks: Some(limit),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_rebuild_reaching_chunk_quota_exactly_succeeds() {
+    let stores = setup().await;
+    let datasets = stores.spatial.list_datasets().await.unwrap();
+
+    let builder = stores
+        .builder()
+        .with_chunk_quota(max_chunks(FEATURES), WorkspaceUsage::default());
+    let result = builder.full_rebuild(&datasets, true, |_| {}).await.unwrap();
+
+    assert_eq!(result.chunk_count, FEATURES as usize);
+}
+
+#[tokio::test]
+async fn test_rebuild_over_chunk_quota_keeps_previous_index() {
+    let stores = setup().await;
+    let datasets = stores.spatial.list_datasets().await.unwrap();
+    stores.builder().full_rebuild(&datasets, true, |_| {}).await.unwrap();
+    let before = stores.documents.list_chunk_ids().await.unwrap();
+
+    let usage = WorkspaceUsage { chunks: 2, ..Default::default() };
+    let builder = stores.builder().with_chunk_quota(max_chunks(FEATURES - 1), usage);
+    match builder.full_rebuild(&datasets, true, |_| {}).await {
+        Err(GeoragError::QuotaExceeded { resource, limit, usage, requested }) => {
+            assert_eq!(resource, QuotaResource::Chunks);
+            assert_eq!(limit, FEATURES - 1);
+            assert_eq!(usage, 2);
+            assert_eq!(requested, FEATURES - 2);
+        }
+        other => panic!("expected a chunk quota error, got {:?}", other.map(|r| r.chunk_count)),
+    }
+
+    assert_eq!(stores.documents.list_chunk_ids().await.unwrap(), before);
+}
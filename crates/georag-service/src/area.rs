@@ -0,0 +1,171 @@
+//! Saved areas: named geometries reusable as query filters
+//!
+//! Geometries are normalized before they are stored: repeated consecutive
+//! vertices are dropped, open polygon rings are closed and the result must
+//! pass validation. Areas copied from a feature are reprojected to WGS 84
+//! first, so every stored area can be used directly as a filter geometry.
+
+use georag_core::error::GeoragError;
+use georag_core::geo::{normalize_geometry, validate_geometry};
+use georag_core::models::{
+    normalize_area_name, AreaSource, Crs, DatasetId, FeatureId, Geometry, SavedArea, ValidityMode,
+    WorkspaceId,
+};
+use georag_store::ports::{AreaStore, SpatialStore};
+use std::sync::Arc;
+
+use crate::error::{Result, ServiceError};
+
+/// Service for creating, listing and resolving saved areas
+pub struct AreaService {
+    areas: Arc<dyn AreaStore>,
+    spatial_store: Arc<dyn SpatialStore>,
+}
+
+impl AreaService {
+    /// Create an area service over the area and spatial stores
+    pub fn new(areas: Arc<dyn AreaStore>, spatial_store: Arc<dyn SpatialStore>) -> Self {
+        Self { areas, spatial_store }
+    }
+
+    /// Read an area geometry from GeoJSON: a bare geometry or a Feature holding one
+    pub fn parse_geometry(value: &serde_json::Value) -> Result<Geometry> {
+        let geometry = match value.get("type").and_then(|t| t.as_str()) {
+            Some("Feature") => value.get("geometry").unwrap_or(&serde_json::Value::Null),
+            _ => value,
+        };
+        Geometry::from_geojson(geometry).ok_or_else(|| {
+            ServiceError::InvalidArea(
+                "geometry must be a GeoJSON geometry or a Feature with one".to_string(),
+            )
+        })
+    }
+
+    /// Save a named area with the given geometry, in WGS 84
+    pub async fn create(
+        &self,
+        workspace_id: WorkspaceId,
+        name: &str,
+        geometry: &Geometry,
+    ) -> Result<SavedArea> {
+        let name = normalize_area_name(name).map_err(ServiceError::InvalidArea)?;
+        let area = SavedArea::new(name, normalize_area_geometry(geometry)?);
+        self.store(workspace_id, area).await
+    }
+
+    /// Save a named area with the geometry of a stored feature
+    pub async fn create_from_feature(
+        &self,
+        workspace_id: WorkspaceId,
+        name: &str,
+        dataset_id: DatasetId,
+        feature_id: FeatureId,
+    ) -> Result<SavedArea> {
+        let name = normalize_area_name(name).map_err(ServiceError::InvalidArea)?;
+
+        if self.spatial_store.get_dataset(dataset_id).await?.is_none() {
+            return Err(GeoragError::DatasetNotFound { name: dataset_id.0.to_string() }.into());
+        }
+
+        // Feature IDs are store-wide, so look the feature up among the
+        // dataset's own features rather than by ID alone
+        let feature = self
+            .spatial_store
+            .get_features_for_dataset(dataset_id)
+            .await?
+            .into_iter()
+            .find(|feature| feature.id == feature_id)
+            .ok_or(ServiceError::FeatureNotFound { dataset_id, feature_id })?;
+        let geometry = feature.geometry.ok_or_else(|| {
+            ServiceError::InvalidArea(format!("feature {} has no geometry", feature_id.0))
+        })?;
+
+        let geometry = normalize_geometry(&geometry, &Crs::new(feature.crs, ""), &Crs::wgs84())?;
+        let area = SavedArea::new(name, normalize_area_geometry(&geometry)?)
+            .with_source(AreaSource { dataset_id, feature_id });
+        self.store(workspace_id, area).await
+    }
+
+    /// List the areas of a workspace, sorted by name
+    pub async fn list(&self, workspace_id: WorkspaceId) -> Result<Vec<SavedArea>> {
+        Ok(self.areas.list_areas(workspace_id).await?)
+    }
+
+    /// Look up an area by name, failing when there is none
+    pub async fn resolve(&self, workspace_id: WorkspaceId, name: &str) -> Result<SavedArea> {
+        let key = normalize_area_name(name).map_err(ServiceError::InvalidArea)?;
+        self.areas
+            .get_area(workspace_id, &key)
+            .await?
+            .ok_or_else(|| ServiceError::AreaNotFound { name: name.trim().to_string() })
+    }
+
+    /// Delete an area by name, failing when there is none
+    pub async fn delete(&self, workspace_id: WorkspaceId, name: &str) -> Result<()> {
+        let key = normalize_area_name(name).map_err(ServiceError::InvalidArea)?;
+        if !self.areas.delete_area(workspace_id, &key).await? {
+            return Err(ServiceError::AreaNotFound { name: name.trim().to_string() });
+        }
+        Ok(())
+    }
+
+    async fn store(&self, workspace_id: WorkspaceId, area: SavedArea) -> Result<SavedArea> {
+        if !self.areas.create_area(workspace_id, &area).await? {
+            return Err(ServiceError::AreaExists { name: area.name });
+        }
+        Ok(area)
+    }
+}
+
+/// Drop repeated vertices, close polygon rings and validate the result
+pub fn normalize_area_geometry(geometry: &Geometry) -> Result<Geometry> {
+    let normalized = match geometry {
+        Geometry::Point { coordinates } => Geometry::Point { coordinates: *coordinates },
+        Geometry::LineString { coordinates } => {
+            Geometry::LineString { coordinates: dedup_vertices(coordinates) }
+        }
+        Geometry::MultiPoint { coordinates } => {
+            Geometry::MultiPoint { coordinates: coordinates.clone() }
+        }
+        Geometry::MultiLineString { coordinates } => Geometry::MultiLineString {
+            coordinates: coordinates.iter().map(|line| dedup_vertices(line)).collect(),
+        },
+        Geometry::Polygon { coordinates } => Geometry::Polygon {
+            coordinates: coordinates.iter().map(|ring| close_ring(ring)).collect(),
+        },
+        Geometry::MultiPolygon { coordinates } => Geometry::MultiPolygon {
+            coordinates: coordinates
+                .iter()
+                .map(|polygon| polygon.iter().map(|ring| close_ring(ring)).collect())
+                .collect(),
+        },
+    };
+
+    let validation = validate_geometry(&normalized, ValidityMode::Strict);
+    if !validation.is_valid {
+        let reasons: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|error| format!("{}: {}", error.location, error.reason))
+            .collect();
+        return Err(ServiceError::InvalidArea(format!("invalid geometry: {}", reasons.join("; "))));
+    }
+
+    Ok(normalized)
+}
+
+fn dedup_vertices(coordinates: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut vertices = coordinates.to_vec();
+    vertices.dedup();
+    vertices
+}
+
+fn close_ring(ring: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut ring = dedup_vertices(ring);
+    if let (Some(first), Some(last)) = (ring.first().copied(), ring.last()) {
+        if first != *last {
+            ring.push(first);
+        }
+    }
+    ring
+}
@@ -0,0 +1,94 @@
+//! Workspace activity: audit events and the timelines aggregated from them
+//!
+//! Adapters record what happened (datasets added and deleted, builds,
+//! queries, quota refusals) as it happens. Recording never fails the
+//! operation it describes; a store error is only logged. Timelines count
+//! the events per bucket over a bounded recent range, so a dashboard can
+//! poll them cheaply.
+
+use chrono::{DateTime, Duration, Utc};
+use georag_core::error::GeoragError;
+use georag_core::models::{
+    AuditEvent, AuditEventKind, Timeline, TimelineGranularity, WorkspaceId, DEFAULT_TIMELINE_DAYS,
+    MAX_TIMELINE_DAYS,
+};
+use georag_store::ports::AuditStore;
+use std::sync::Arc;
+
+use crate::error::{Result, ServiceError};
+
+/// Most notable events listed in one timeline
+pub const MAX_NOTABLE_EVENTS: usize = 50;
+
+/// Service recording audit events and aggregating them into timelines
+#[derive(Clone)]
+pub struct AuditService {
+    store: Arc<dyn AuditStore>,
+}
+
+impl AuditService {
+    /// Create an audit service over the audit store
+    pub fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self { store }
+    }
+
+    /// Record an event; a failure is logged rather than returned
+    pub async fn record(&self, workspace_id: WorkspaceId, event: AuditEvent) {
+        if let Err(e) = self.store.record_event(workspace_id, &event).await {
+            tracing::warn!(
+                workspace_id = %workspace_id,
+                kind = %event.kind,
+                error = %e,
+                "Failed to record audit event"
+            );
+        }
+    }
+
+    /// Record an ingest or build that failed, with the error as details
+    ///
+    /// A quota refusal is recorded as `quota_exceeded`; other errors as
+    /// `failed` (e.g. `build_failed`), or not at all when that is `None`.
+    pub async fn record_failure(
+        &self,
+        workspace_id: WorkspaceId,
+        failed: Option<AuditEventKind>,
+        error: &GeoragError,
+    ) {
+        let kind = match error {
+            GeoragError::QuotaExceeded { .. } => AuditEventKind::QuotaExceeded,
+            _ => match failed {
+                Some(kind) => kind,
+                None => return,
+            },
+        };
+        self.record(workspace_id, AuditEvent::new(kind).with_details(error.to_string()))
+            .await;
+    }
+
+    /// Events of a workspace since `since` (default: two weeks ago) counted per bucket
+    ///
+    /// Fails when `since` lies in the future or more than
+    /// [`MAX_TIMELINE_DAYS`] days back.
+    pub async fn timeline(
+        &self,
+        workspace_id: WorkspaceId,
+        since: Option<DateTime<Utc>>,
+        granularity: TimelineGranularity,
+    ) -> Result<Timeline> {
+        let until = Utc::now();
+        let since = since.unwrap_or(until - Duration::days(DEFAULT_TIMELINE_DAYS));
+        if since > until {
+            return Err(ServiceError::InvalidTimeline("since lies in the future".to_string()));
+        }
+        if until - since > Duration::days(MAX_TIMELINE_DAYS) {
+            return Err(ServiceError::InvalidTimeline(format!(
+                "since lies more than {} days back",
+                MAX_TIMELINE_DAYS
+            )));
+        }
+
+        let counts = self.store.count_events(workspace_id, since, granularity).await?;
+        let notable = self.store.notable_events(workspace_id, since, MAX_NOTABLE_EVENTS).await?;
+        Ok(Timeline::from_counts(since, until, granularity, &counts, notable))
+    }
+}
@@ -0,0 +1,158 @@
+//! Repair of features stored with latitude and longitude swapped
+//!
+//! A scan re-examines every feature of a dataset with the axis-order
+//! heuristic, using the extent of the other geographic datasets in the
+//! workspace, and lists the features it flags as lat,lon with confidence.
+//! Undecided features are counted but left alone. Applying the plan swaps the
+//! flagged features back and drops their chunks so the next build re-embeds
+//! them; the dataset preview is rebuilt with the corrected extent.
+
+use georag_core::geo::axis::{assess_geometry, extent_of, swap_axes, Extent};
+use georag_core::models::{AxisOrder, DatasetId, Feature, FeatureId, Geometry};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::diff::{invalidate_feature_chunks, refresh_preview};
+use crate::error::Result;
+
+/// Features of a dataset found to be stored lat,lon
+#[derive(Debug, Clone)]
+pub struct AxisRepairPlan {
+    /// Dataset that was scanned
+    pub dataset_id: DatasetId,
+
+    /// Features with a geometry that were examined
+    pub features_scanned: usize,
+
+    /// Flagged features, already swapped to lon,lat
+    pub flips: Vec<Feature>,
+
+    /// Features whose order could not be told either way
+    pub undecided: usize,
+}
+
+impl AxisRepairPlan {
+    /// Whether any feature needs swapping
+    pub fn is_empty(&self) -> bool {
+        self.flips.is_empty()
+    }
+}
+
+/// Outcome of applying an axis repair plan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AxisRepairReport {
+    /// Features whose coordinates were swapped
+    pub features_flipped: usize,
+
+    /// Chunks of flipped features removed together with their embeddings
+    pub chunks_invalidated: usize,
+}
+
+/// Service for finding and swapping back lat,lon features
+pub struct AxisRepairService {
+    spatial_store: Arc<dyn SpatialStore>,
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+}
+
+impl AxisRepairService {
+    /// Create an axis repair service over the workspace stores
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        document_store: Arc<dyn DocumentStore>,
+        vector_store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            spatial_store,
+            document_store,
+            vector_store,
+        }
+    }
+
+    /// Find the features of a dataset that are stored lat,lon without changing anything
+    pub async fn scan(&self, dataset_id: DatasetId) -> Result<AxisRepairPlan> {
+        let extent = workspace_extent(self.spatial_store.as_ref(), Some(dataset_id)).await?;
+        let features = self.spatial_store.get_features_for_dataset(dataset_id).await?;
+
+        let mut plan = AxisRepairPlan {
+            dataset_id,
+            features_scanned: 0,
+            flips: Vec::new(),
+            undecided: 0,
+        };
+        for mut feature in features {
+            let Some(geometry) = &feature.geometry else {
+                continue;
+            };
+            plan.features_scanned += 1;
+
+            let mut geojson = geometry.to_geojson();
+            match assess_geometry(&geojson, extent.as_ref()) {
+                Some(AxisOrder::LatLon) => {
+                    swap_axes(&mut geojson);
+                    feature.geometry = Geometry::from_geojson(&geojson);
+                    plan.flips.push(feature);
+                }
+                Some(_) => {}
+                None => plan.undecided += 1,
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Swap the flagged features back to lon,lat
+    pub async fn apply(&self, plan: &AxisRepairPlan) -> Result<AxisRepairReport> {
+        let flipped: HashSet<FeatureId> = plan.flips.iter().map(|f| f.id).collect();
+        let chunks_invalidated = invalidate_feature_chunks(
+            self.document_store.as_ref(),
+            self.vector_store.as_ref(),
+            &flipped,
+        )
+        .await?;
+
+        self.spatial_store.upsert_dataset_features(plan.dataset_id, &plan.flips).await?;
+        refresh_preview(self.spatial_store.as_ref(), plan.dataset_id).await?;
+
+        Ok(AxisRepairReport {
+            features_flipped: plan.flips.len(),
+            chunks_invalidated,
+        })
+    }
+}
+
+/// Bounding box of the EPSG:4326 features stored in the workspace
+///
+/// Used by `AxisOrder::Auto` to decide coordinates that fit either order.
+/// The `exclude`d dataset does not contribute, so a dataset under repair is
+/// not judged against its own, possibly swapped, coordinates.
+pub(crate) async fn workspace_extent(
+    store: &dyn SpatialStore,
+    exclude: Option<DatasetId>,
+) -> Result<Option<Extent>> {
+    let mut extent: Option<Extent> = None;
+    for dataset in store.list_datasets().await? {
+        if dataset.crs != 4326 || Some(dataset.id) == exclude {
+            continue;
+        }
+        for feature in store.get_features_for_dataset(dataset.id).await? {
+            let Some(geometry) = feature.geometry else {
+                continue;
+            };
+            let Some(bounds) = extent_of([&geometry.to_geojson()]) else {
+                continue;
+            };
+            extent = Some(match extent {
+                Some(e) => [
+                    e[0].min(bounds[0]),
+                    e[1].min(bounds[1]),
+                    e[2].max(bounds[2]),
+                    e[3].max(bounds[3]),
+                ],
+                None => bounds,
+            });
+        }
+    }
+    Ok(extent)
+}
@@ -0,0 +1,516 @@
+//! Deleting or archiving many datasets at once
+//!
+//! A filter on name, age and tag selects datasets of a workspace. Planning
+//! lists them with the usage they hold without changing anything; applying
+//! the plan works through them in batches, one dataset at a time, so a
+//! dataset that fails is reported and the others still go ahead.
+//!
+//! Deleting removes a dataset's features, chunks, embeddings and source file.
+//! Archiving keeps the features and the source file, tags the dataset
+//! [`ARCHIVED_TAG`] so builds leave it out, and removes its chunks and
+//! embeddings. Either way the workspace's usage counters are released and an
+//! audit event is recorded per dataset, along with change events when the
+//! service has an event log.
+//!
+//! Callers must hold the build lock while applying, otherwise a running build
+//! could store chunks of a dataset that was just removed.
+
+use chrono::{DateTime, Utc};
+use georag_core::models::{
+    normalize_tags, AuditEvent, AuditEventKind, ChunkId, Dataset, DatasetId, TagVisibility,
+    UsageDelta, ARCHIVED_TAG,
+};
+use georag_store::ports::{BlobStore, DocumentStore, SpatialStore, VectorStore, WorkspaceStore};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::audit::AuditService;
+use crate::error::{Result, ServiceError};
+use crate::events::EventLogService;
+use crate::quota::WorkspaceQuota;
+
+/// Default number of datasets handled between progress reports
+pub const DEFAULT_BULK_BATCH: usize = 20;
+
+/// A pattern dataset names are matched against
+#[derive(Debug, Clone)]
+pub struct NamePattern {
+    pattern: String,
+    regex: Regex,
+}
+
+impl NamePattern {
+    /// Names starting with `prefix`
+    pub fn prefix(prefix: &str) -> Self {
+        Self::compile(prefix, &format!("^{}", regex::escape(prefix)))
+            .expect("an escaped prefix is a valid regex")
+    }
+
+    /// Names matching a shell-style pattern: `*` is any run of characters, `?` one
+    pub fn glob(glob: &str) -> Result<Self> {
+        let mut regex = String::from("^");
+        for c in glob.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex.push('$');
+        Self::compile(glob, &regex)
+    }
+
+    /// Names containing a match of a regular expression
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Self::compile(pattern, pattern)
+    }
+
+    fn compile(pattern: &str, regex: &str) -> Result<Self> {
+        let regex = Regex::new(regex).map_err(|e| {
+            ServiceError::InvalidFilter(format!("Invalid name pattern '{}': {}", pattern, e))
+        })?;
+        Ok(Self { pattern: pattern.to_string(), regex })
+    }
+
+    /// Whether `name` matches the pattern
+    pub fn matches(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// Which datasets a bulk operation applies to
+///
+/// A dataset must match every condition that is set. A filter without any
+/// condition is refused rather than matching every dataset.
+#[derive(Debug, Clone, Default)]
+pub struct DatasetFilter {
+    /// Pattern the dataset name must match
+    pub name: Option<NamePattern>,
+
+    /// Only datasets added before this time
+    pub added_before: Option<DateTime<Utc>>,
+
+    /// Only datasets carrying this access tag
+    pub tag: Option<String>,
+}
+
+impl DatasetFilter {
+    /// Match dataset names against `pattern`
+    pub fn with_name(mut self, pattern: NamePattern) -> Self {
+        self.name = Some(pattern);
+        self
+    }
+
+    /// Only match datasets added before `time`
+    pub fn with_added_before(mut self, time: DateTime<Utc>) -> Self {
+        self.added_before = Some(time);
+        self
+    }
+
+    /// Only match datasets carrying `tag`, normalized like stored tags
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = normalize_tags([tag]).into_iter().next();
+        self
+    }
+
+    /// Whether no condition is set
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.added_before.is_none() && self.tag.is_none()
+    }
+
+    /// Whether a stored dataset matches every condition
+    pub fn matches(&self, dataset: &Dataset) -> bool {
+        self.name.as_ref().is_none_or(|pattern| pattern.matches(&dataset.name))
+            && self.added_before.is_none_or(|before| dataset.added_at < before)
+            && self.tag.as_ref().is_none_or(|tag| dataset.tags.contains(tag))
+    }
+}
+
+/// What a bulk operation does to each dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    /// Remove the dataset with its features, chunks, embeddings and source file
+    Delete,
+    /// Keep the dataset out of index builds and remove its chunks and embeddings
+    Archive,
+}
+
+impl BulkAction {
+    /// Name used in API responses and CLI output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Archive => "archive",
+        }
+    }
+}
+
+/// A dataset selected by a bulk operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkCandidate {
+    pub id: DatasetId,
+
+    pub name: String,
+
+    pub added_at: DateTime<Utc>,
+
+    pub feature_count: usize,
+
+    /// Size of the kept source file
+    pub source_bytes: u64,
+}
+
+/// Datasets a bulk operation would change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkPlan {
+    pub action: BulkAction,
+
+    /// Datasets of the workspace the filter was checked against
+    pub datasets_scanned: usize,
+
+    /// Matching datasets, sorted by name
+    pub datasets: Vec<BulkCandidate>,
+}
+
+impl BulkPlan {
+    /// Whether no dataset matched
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+
+    /// Features of the matching datasets
+    pub fn feature_count(&self) -> usize {
+        self.datasets.iter().map(|d| d.feature_count).sum()
+    }
+
+    /// Bytes of source files of the matching datasets
+    pub fn source_bytes(&self) -> u64 {
+        self.datasets.iter().map(|d| d.source_bytes).sum()
+    }
+}
+
+/// Progress of applying a bulk plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkProgress {
+    /// Datasets handled so far, whether they succeeded or not
+    pub done: usize,
+
+    /// Datasets to handle in total
+    pub total: usize,
+}
+
+/// Outcome for one dataset of a bulk operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkDatasetResult {
+    pub id: DatasetId,
+
+    pub name: String,
+
+    /// Chunks removed together with their embeddings
+    pub chunks_removed: usize,
+
+    /// Why the dataset could not be changed; `None` when it succeeded
+    pub error: Option<String>,
+}
+
+impl BulkDatasetResult {
+    /// Whether the dataset was deleted or archived
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Outcome of applying a bulk plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkReport {
+    pub action: BulkAction,
+
+    /// One result per planned dataset, in plan order
+    pub results: Vec<BulkDatasetResult>,
+
+    /// Usage given back to the workspace
+    pub released: UsageDelta,
+}
+
+impl BulkReport {
+    /// Datasets deleted or archived
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.succeeded()).count()
+    }
+
+    /// Datasets left unchanged because of an error
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+}
+
+/// Service for deleting or archiving the datasets a filter selects
+pub struct BulkDatasetService {
+    spatial_store: Arc<dyn SpatialStore>,
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+    blob_store: Arc<dyn BlobStore>,
+    quota: WorkspaceQuota,
+    audit: AuditService,
+    events: Option<EventLogService>,
+    shared: Option<Arc<dyn WorkspaceStore>>,
+    batch_size: usize,
+}
+
+impl BulkDatasetService {
+    /// Create a bulk service over the stores of the workspace `quota` belongs to
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        document_store: Arc<dyn DocumentStore>,
+        vector_store: Arc<dyn VectorStore>,
+        blob_store: Arc<dyn BlobStore>,
+        quota: WorkspaceQuota,
+        audit: AuditService,
+    ) -> Self {
+        Self {
+            spatial_store,
+            document_store,
+            vector_store,
+            blob_store,
+            quota,
+            audit,
+            events: None,
+            shared: None,
+            batch_size: DEFAULT_BULK_BATCH,
+        }
+    }
+
+    /// List and delete datasets through the workspace store
+    ///
+    /// For stores shared by several workspaces, where the workspace store
+    /// records which datasets belong to the workspace.
+    pub fn with_shared_stores(mut self, workspace_store: Arc<dyn WorkspaceStore>) -> Self {
+        self.shared = Some(workspace_store);
+        self
+    }
+
+    /// Record the removed chunks and the deleted or archived datasets as change events
+    pub fn with_event_log(mut self, events: EventLogService) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Set the number of datasets handled between progress reports
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Find the datasets `filter` selects among those the caller may see
+    ///
+    /// Archiving skips datasets that are already archived.
+    pub async fn plan(
+        &self,
+        filter: &DatasetFilter,
+        action: BulkAction,
+        visibility: &TagVisibility,
+    ) -> Result<BulkPlan> {
+        if filter.is_empty() {
+            return Err(ServiceError::InvalidFilter(
+                "Set at least one of a name pattern, an added-before time or a tag".to_string(),
+            ));
+        }
+
+        let listed = match &self.shared {
+            Some(store) => store.list_datasets_for_workspace(self.quota.workspace_id()).await?,
+            None => self.spatial_store.list_datasets().await?,
+        };
+
+        let mut datasets = Vec::new();
+        for meta in &listed {
+            // Tags are current in the spatial store only
+            let Some(dataset) = self.spatial_store.get_dataset(meta.id).await? else {
+                continue;
+            };
+            let archived = dataset.tags.iter().any(|tag| tag == ARCHIVED_TAG);
+            if !visibility.allows(&dataset.tags)
+                || !filter.matches(&dataset)
+                || (action == BulkAction::Archive && archived)
+            {
+                continue;
+            }
+            datasets.push(BulkCandidate {
+                id: dataset.id,
+                name: dataset.name.clone(),
+                added_at: dataset.added_at,
+                feature_count: dataset.feature_count,
+                source_bytes: dataset.format.source.as_ref().map_or(0, |source| source.size),
+            });
+        }
+
+        Ok(BulkPlan {
+            action,
+            datasets_scanned: listed.len(),
+            datasets,
+        })
+    }
+
+    /// Delete or archive the planned datasets, reporting each one's outcome
+    ///
+    /// A failing dataset is recorded in the report and left as it was as far
+    /// as possible; the remaining datasets are still handled.
+    pub async fn apply<F>(&self, plan: &BulkPlan, mut progress: F) -> BulkReport
+    where
+        F: FnMut(BulkProgress),
+    {
+        let total = plan.datasets.len();
+        let mut report = BulkReport {
+            action: plan.action,
+            results: Vec::with_capacity(total),
+            released: UsageDelta::default(),
+        };
+
+        for batch in plan.datasets.chunks(self.batch_size) {
+            // Chunks are looked up once per batch rather than per dataset
+            let chunks = self.chunks_by_document().await;
+            for candidate in batch {
+                let outcome = match &chunks {
+                    Ok(chunks) => self.apply_one(candidate, plan.action, chunks).await,
+                    Err(e) => Err(format!("Failed to list chunks: {}", e)),
+                };
+                let result = match outcome {
+                    Ok((chunks_removed, released)) => {
+                        report.released = report.released.plus(&released);
+                        BulkDatasetResult {
+                            id: candidate.id,
+                            name: candidate.name.clone(),
+                            chunks_removed,
+                            error: None,
+                        }
+                    }
+                    Err(error) => {
+                        tracing::warn!(dataset = %candidate.name, error = %error, "Bulk {} failed", plan.action.as_str());
+                        BulkDatasetResult {
+                            id: candidate.id,
+                            name: candidate.name.clone(),
+                            chunks_removed: 0,
+                            error: Some(error),
+                        }
+                    }
+                };
+                report.results.push(result);
+            }
+            progress(BulkProgress { done: report.results.len(), total });
+        }
+
+        report
+    }
+
+    /// Delete or archive one dataset, returning the chunks removed and the usage released
+    async fn apply_one(
+        &self,
+        candidate: &BulkCandidate,
+        action: BulkAction,
+        chunks: &HashMap<String, Vec<ChunkId>>,
+    ) -> std::result::Result<(usize, UsageDelta), String> {
+        let dataset = self
+            .spatial_store
+            .get_dataset(candidate.id)
+            .await
+            .map_err(|e| format!("Failed to load dataset: {}", e))?
+            .ok_or_else(|| "Dataset no longer exists".to_string())?;
+
+        // Chunks go first: a dataset whose removal fails afterwards is only
+        // missing from the index until the next build
+        let stale = chunks
+            .get(dataset.path.to_string_lossy().as_ref())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if !stale.is_empty() {
+            self.vector_store
+                .delete_embeddings(stale)
+                .await
+                .map_err(|e| format!("Failed to delete embeddings: {}", e))?;
+            self.document_store
+                .delete_chunks(stale)
+                .await
+                .map_err(|e| format!("Failed to delete chunks: {}", e))?;
+        }
+        let chunks_removed = UsageDelta {
+            chunks: -(stale.len() as i64),
+            ..Default::default()
+        };
+
+        let released = match action {
+            BulkAction::Delete => {
+                let removed = match &self.shared {
+                    Some(store) => {
+                        store
+                            .delete_dataset_in_workspace(self.quota.workspace_id(), dataset.id)
+                            .await
+                    }
+                    None => self.spatial_store.delete_dataset(dataset.id).await,
+                };
+                removed.map_err(|e| format!("Failed to delete dataset: {}", e))?;
+
+                // The dataset is gone either way; a leftover file is only logged
+                if let Err(e) = self.blob_store.delete_blob(dataset.id).await {
+                    tracing::warn!(dataset = %dataset.name, error = %e, "Failed to delete dataset source");
+                }
+                UsageDelta::for_dataset(&dataset).negated().plus(&chunks_removed)
+            }
+            BulkAction::Archive => {
+                let tags = archived_tags(&dataset);
+                self.spatial_store
+                    .set_dataset_tags(dataset.id, &tags)
+                    .await
+                    .map_err(|e| format!("Failed to tag dataset: {}", e))?;
+                // Features and the source file are kept, so only chunks are released
+                chunks_removed
+            }
+        };
+
+        if let Err(e) = self.quota.record(&released).await {
+            tracing::warn!(dataset = %dataset.name, error = %e, "Failed to release workspace usage");
+        }
+
+        let details = match action {
+            BulkAction::Delete => dataset.name.clone(),
+            BulkAction::Archive => format!("{} (archived)", dataset.name),
+        };
+        let event = AuditEvent::new(AuditEventKind::DatasetDeleted).with_details(details);
+        self.audit.record(self.quota.workspace_id(), event).await;
+
+        if let Some(events) = &self.events {
+            let workspace_id = self.quota.workspace_id();
+            events.record_chunks_deleted(workspace_id, stale).await;
+            match action {
+                BulkAction::Delete => events.record_dataset_deleted(workspace_id, dataset.id).await,
+                BulkAction::Archive => {
+                    let archived = Dataset { tags: archived_tags(&dataset), ..dataset };
+                    events.record_dataset_updated(workspace_id, &archived).await;
+                }
+            }
+        }
+
+        Ok((stale.len(), released))
+    }
+
+    /// IDs of the stored chunks, by the path of the document they were cut from
+    async fn chunks_by_document(&self) -> Result<HashMap<String, Vec<ChunkId>>> {
+        let chunk_ids = self.document_store.list_chunk_ids().await?;
+        let mut chunks: HashMap<String, Vec<ChunkId>> = HashMap::new();
+        for chunk in self.document_store.get_chunks(&chunk_ids).await? {
+            chunks.entry(chunk.source.document_path).or_default().push(chunk.id);
+        }
+        Ok(chunks)
+    }
+}
+
+/// Tags of a dataset once archived
+fn archived_tags(dataset: &Dataset) -> Vec<String> {
+    normalize_tags(dataset.tags.iter().map(String::as_str).chain([ARCHIVED_TAG]))
+}
@@ -0,0 +1,276 @@
+//! Copying a workspace's data into a new workspace
+//!
+//! Planning reads the source once: its datasets and the chunks built from
+//! them. Running the plan copies datasets with their features and, when
+//! asked, their source files, then chunks with their embeddings, in batches.
+//! Only what the plan read is copied, so data ingested into the source while
+//! the copy runs is left out rather than copied in part.
+//!
+//! Feature and chunk IDs are kept. Datasets get new IDs in the destination;
+//! the report maps source IDs to them. A verification pass compares what was
+//! read from the source with what the destination holds afterwards.
+//!
+//! Callers must hold the build lock of the source while planning and running,
+//! otherwise a build could replace chunks between the two.
+
+use georag_core::models::{ChunkId, Dataset, DatasetId, DatasetMeta, Embedding, UsageDelta};
+use georag_store::ports::WorkspaceStores;
+use std::collections::HashSet;
+
+use crate::error::Result;
+use crate::quota::WorkspaceQuota;
+
+/// Default number of features or chunks copied per batch
+pub const DEFAULT_CLONE_BATCH: usize = 500;
+
+/// What a clone will copy, as read from the source
+#[derive(Debug, Clone, Default)]
+pub struct ClonePlan {
+    pub datasets: Vec<Dataset>,
+
+    /// Chunks built from the datasets, in ID order
+    pub chunk_ids: Vec<ChunkId>,
+
+    /// Usage the copy adds to the destination
+    pub usage: UsageDelta,
+}
+
+/// Stage of a running clone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClonePhase {
+    /// Datasets with their features and source files
+    Datasets,
+    /// Chunks with their embeddings
+    Chunks,
+}
+
+impl ClonePhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Datasets => "datasets",
+            Self::Chunks => "chunks",
+        }
+    }
+}
+
+/// How far a running clone has got in its current phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloneProgress {
+    pub phase: ClonePhase,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// What one side of a clone holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CloneCounts {
+    pub datasets: usize,
+    pub features: usize,
+    pub chunks: usize,
+    pub embeddings: usize,
+    /// Source files; zero when they are not copied
+    pub blobs: usize,
+}
+
+/// Counts read from the source compared with those in the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloneVerification {
+    pub source: CloneCounts,
+    pub destination: CloneCounts,
+}
+
+impl CloneVerification {
+    /// Whether the destination holds everything read from the source
+    pub fn matches(&self) -> bool {
+        self.source == self.destination
+    }
+}
+
+/// A dataset's ID in the source and in the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetMapping {
+    pub source: DatasetId,
+    pub destination: DatasetId,
+}
+
+/// Outcome of a clone
+#[derive(Debug, Clone)]
+pub struct CloneReport {
+    pub datasets: Vec<DatasetMapping>,
+
+    /// Usage recorded for the destination
+    pub copied: UsageDelta,
+
+    pub verification: CloneVerification,
+}
+
+/// Service copying one workspace's stores into another's
+pub struct WorkspaceCloneService {
+    source: WorkspaceStores,
+    destination: WorkspaceStores,
+    quota: WorkspaceQuota,
+    include_blobs: bool,
+    batch_size: usize,
+}
+
+impl WorkspaceCloneService {
+    /// Copy from `source` into the empty `destination`, whose usage `quota` counts
+    pub fn new(
+        source: WorkspaceStores,
+        destination: WorkspaceStores,
+        quota: WorkspaceQuota,
+    ) -> Self {
+        Self {
+            source,
+            destination,
+            quota,
+            include_blobs: false,
+            batch_size: DEFAULT_CLONE_BATCH,
+        }
+    }
+
+    /// Copy source files too; without them the clone's datasets record none
+    pub fn with_blobs(mut self, include_blobs: bool) -> Self {
+        self.include_blobs = include_blobs;
+        self
+    }
+
+    /// Copy this many features or chunks per batch
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Read the datasets to copy and the chunks built from them
+    ///
+    /// `datasets` are the source workspace's datasets; stores shared between
+    /// workspaces hold other workspaces' data too.
+    pub async fn plan(&self, datasets: &[DatasetMeta]) -> Result<ClonePlan> {
+        let mut plan = ClonePlan::default();
+        for meta in datasets {
+            // Removed since it was listed
+            let Some(mut dataset) = self.source.spatial.get_dataset(meta.id).await? else {
+                continue;
+            };
+            if !self.include_blobs {
+                dataset.format.source = None;
+            }
+            plan.usage = plan.usage.plus(&UsageDelta::for_dataset(&dataset));
+            plan.datasets.push(dataset);
+        }
+
+        let paths: HashSet<String> =
+            plan.datasets.iter().map(|d| d.path.to_string_lossy().into_owned()).collect();
+        let mut chunk_ids = self.source.document.list_chunk_ids().await?;
+        chunk_ids.sort_by_key(|id| id.0);
+        for batch in chunk_ids.chunks(self.batch_size) {
+            let chunks = self.source.document.get_chunks(batch).await?;
+            plan.chunk_ids.extend(
+                chunks
+                    .iter()
+                    .filter(|chunk| paths.contains(&chunk.source.document_path))
+                    .map(|chunk| chunk.id),
+            );
+        }
+        plan.chunk_ids.sort_by_key(|id| id.0);
+        plan.usage.chunks = plan.chunk_ids.len() as i64;
+
+        Ok(plan)
+    }
+
+    /// Copy what the plan read into the destination and verify the copy
+    ///
+    /// Fails with `QuotaExceeded` before copying anything when the copy would
+    /// not fit the destination's quotas. A copy failing part way leaves the
+    /// destination partly filled and its usage unrecorded; callers drop it.
+    pub async fn run<F>(&self, plan: &ClonePlan, mut progress: F) -> Result<CloneReport>
+    where
+        F: FnMut(CloneProgress),
+    {
+        self.quota.check(&plan.usage).await?;
+
+        let mut source = CloneCounts {
+            datasets: plan.datasets.len(),
+            chunks: plan.chunk_ids.len(),
+            ..Default::default()
+        };
+        let mut datasets = Vec::with_capacity(plan.datasets.len());
+        let mut copied = UsageDelta::default();
+
+        let total = plan.datasets.len();
+        for (done, dataset) in plan.datasets.iter().enumerate() {
+            let id = self.destination.spatial.store_dataset(dataset).await?;
+            datasets.push(DatasetMapping { source: dataset.id, destination: id });
+            copied = copied.plus(&UsageDelta::for_dataset(dataset));
+
+            let features = self.source.spatial.get_features_for_dataset(dataset.id).await?;
+            source.features += features.len();
+            for batch in features.chunks(self.batch_size) {
+                self.destination.spatial.upsert_dataset_features(id, batch).await?;
+            }
+
+            if self.include_blobs {
+                if let Some(blob) = self.source.blob.get_blob(dataset.id).await? {
+                    self.destination.blob.put_blob(id, &blob).await?;
+                    source.blobs += 1;
+                }
+            }
+            progress(CloneProgress {
+                phase: ClonePhase::Datasets,
+                done: done + 1,
+                total,
+            });
+        }
+
+        let total = plan.chunk_ids.len();
+        let mut done = 0;
+        for batch in plan.chunk_ids.chunks(self.batch_size) {
+            let chunks = self.source.document.get_chunks(batch).await?;
+            self.destination.document.store_chunks(&chunks).await?;
+
+            let mut embeddings: Vec<Embedding> = Vec::new();
+            for id in batch {
+                if let Some(embedding) = self.source.vector.get_embedding(*id).await? {
+                    embeddings.push(embedding);
+                }
+            }
+            if !embeddings.is_empty() {
+                self.destination.vector.store_embeddings(&embeddings).await?;
+            }
+            source.embeddings += embeddings.len();
+
+            done += batch.len();
+            progress(CloneProgress { phase: ClonePhase::Chunks, done, total });
+        }
+        copied.chunks = plan.chunk_ids.len() as i64;
+        self.quota.record(&copied).await?;
+
+        let destination = self.count_destination(&datasets).await?;
+        Ok(CloneReport {
+            datasets,
+            copied,
+            verification: CloneVerification { source, destination },
+        })
+    }
+
+    /// What the destination holds, read back from its stores
+    async fn count_destination(&self, datasets: &[DatasetMapping]) -> Result<CloneCounts> {
+        let mut counts = CloneCounts {
+            datasets: self.destination.spatial.list_datasets().await?.len(),
+            chunks: self.destination.document.list_chunk_ids().await?.len(),
+            embeddings: self.destination.vector.list_embedding_ids().await?.len(),
+            ..Default::default()
+        };
+        for mapping in datasets {
+            let features =
+                self.destination.spatial.get_features_for_dataset(mapping.destination).await?;
+            counts.features += features.len();
+            if self.include_blobs
+                && self.destination.blob.get_blob(mapping.destination).await?.is_some()
+            {
+                counts.blobs += 1;
+            }
+        }
+        Ok(counts)
+    }
+}
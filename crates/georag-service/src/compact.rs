@@ -0,0 +1,202 @@
+//! Cleanup of index data that no longer belongs to anything
+//!
+//! Dataset deletions and interrupted rebuilds can leave embeddings whose chunk
+//! is gone, and chunks whose feature is gone. Indexes built before empty text
+//! was filtered out can also hold degenerate chunks with no letters or digits,
+//! whose embeddings rank unpredictably. A scan finds all three without
+//! changing anything; applying the plan deletes them in batches and lets the
+//! vector store release the freed space.
+//!
+//! Callers must hold the build lock while scanning and applying, otherwise a
+//! build storing chunks before their embeddings would look orphaned.
+
+use georag_core::models::{ChunkId, FeatureId};
+use georag_core::processing::chunk::is_meaningful;
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// Default number of IDs deleted per store call
+pub const DEFAULT_COMPACTION_BATCH: usize = 500;
+
+/// Orphaned index data found by a scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionPlan {
+    /// Embeddings inspected
+    pub embeddings_scanned: usize,
+
+    /// Chunks inspected
+    pub chunks_scanned: usize,
+
+    /// Embeddings whose chunk no longer exists
+    pub orphan_embeddings: Vec<ChunkId>,
+
+    /// Chunks referencing a feature that no longer exists
+    pub dangling_chunks: Vec<ChunkId>,
+
+    /// Chunks whose content is empty, whitespace or punctuation only
+    pub degenerate_chunks: Vec<ChunkId>,
+
+    /// Estimated bytes freed by deleting the orphans
+    pub reclaimable_bytes: u64,
+}
+
+impl CompactionPlan {
+    /// Whether there is anything to delete
+    pub fn is_empty(&self) -> bool {
+        self.orphan_embeddings.is_empty()
+            && self.dangling_chunks.is_empty()
+            && self.degenerate_chunks.is_empty()
+    }
+
+    /// Chunks to delete together with their embeddings
+    fn chunks_to_delete(&self) -> impl Iterator<Item = &ChunkId> {
+        self.dangling_chunks.iter().chain(&self.degenerate_chunks)
+    }
+}
+
+/// Progress of applying a compaction plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionProgress {
+    /// IDs deleted so far
+    pub deleted: usize,
+
+    /// IDs to delete in total
+    pub total: usize,
+}
+
+/// Outcome of applying a compaction plan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// Orphaned embeddings deleted
+    pub embeddings_deleted: usize,
+
+    /// Dangling chunks deleted, together with their embeddings
+    pub chunks_deleted: usize,
+
+    /// Degenerate chunks deleted, together with their embeddings
+    pub degenerate_deleted: usize,
+}
+
+/// Service for finding and removing orphaned chunks and embeddings
+pub struct CompactionService {
+    spatial_store: Arc<dyn SpatialStore>,
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+    batch_size: usize,
+}
+
+impl CompactionService {
+    /// Create a compaction service over the workspace stores
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        document_store: Arc<dyn DocumentStore>,
+        vector_store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            spatial_store,
+            document_store,
+            vector_store,
+            batch_size: DEFAULT_COMPACTION_BATCH,
+        }
+    }
+
+    /// Set the number of IDs deleted per store call
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Find orphaned embeddings, dangling and degenerate chunks without deleting anything
+    pub async fn scan(&self) -> Result<CompactionPlan> {
+        let chunk_ids = self.document_store.list_chunk_ids().await?;
+        let chunks = self.document_store.get_chunks(&chunk_ids).await?;
+        let known: HashSet<ChunkId> = chunk_ids.iter().copied().collect();
+
+        let embedding_ids = self.vector_store.list_embedding_ids().await?;
+        let dimensions = self.vector_store.dimensions().await?;
+        let orphan_embeddings: Vec<ChunkId> =
+            embedding_ids.iter().copied().filter(|id| !known.contains(id)).collect();
+
+        // Look each referenced feature up once
+        let mut features: HashMap<FeatureId, bool> = HashMap::new();
+        let mut dangling_chunks = Vec::new();
+        let mut degenerate_chunks = Vec::new();
+        let mut chunk_bytes = 0u64;
+        for chunk in &chunks {
+            if !is_meaningful(&chunk.content) {
+                degenerate_chunks.push(chunk.id);
+                chunk_bytes += chunk.content.len() as u64;
+                continue;
+            }
+            let Some(feature_id) = chunk.spatial_ref else {
+                continue;
+            };
+            let exists = match features.get(&feature_id) {
+                Some(exists) => *exists,
+                None => {
+                    let exists = self.spatial_store.get_feature(feature_id).await?.is_some();
+                    features.insert(feature_id, exists);
+                    exists
+                }
+            };
+            if !exists {
+                dangling_chunks.push(chunk.id);
+                chunk_bytes += chunk.content.len() as u64;
+            }
+        }
+
+        let embedded: HashSet<ChunkId> = embedding_ids.iter().copied().collect();
+        let vector_bytes = (dimensions * std::mem::size_of::<f32>()) as u64;
+        let mut plan = CompactionPlan {
+            embeddings_scanned: embedding_ids.len(),
+            chunks_scanned: chunks.len(),
+            orphan_embeddings,
+            dangling_chunks,
+            degenerate_chunks,
+            reclaimable_bytes: 0,
+        };
+        let embeddings_freed = plan.orphan_embeddings.len()
+            + plan.chunks_to_delete().filter(|id| embedded.contains(id)).count();
+        plan.reclaimable_bytes = embeddings_freed as u64 * vector_bytes + chunk_bytes;
+
+        Ok(plan)
+    }
+
+    /// Delete everything in the plan, reporting progress after each batch
+    pub async fn apply<F>(&self, plan: &CompactionPlan, mut progress: F) -> Result<CompactionReport>
+    where
+        F: FnMut(CompactionProgress),
+    {
+        let total = plan.orphan_embeddings.len()
+            + plan.dangling_chunks.len()
+            + plan.degenerate_chunks.len();
+        let mut deleted = 0;
+
+        for batch in plan.orphan_embeddings.chunks(self.batch_size) {
+            self.vector_store.delete_embeddings(batch).await?;
+            deleted += batch.len();
+            progress(CompactionProgress { deleted, total });
+        }
+
+        let chunk_ids: Vec<ChunkId> = plan.chunks_to_delete().copied().collect();
+        for batch in chunk_ids.chunks(self.batch_size) {
+            self.vector_store.delete_embeddings(batch).await?;
+            self.document_store.delete_chunks(batch).await?;
+            deleted += batch.len();
+            progress(CompactionProgress { deleted, total });
+        }
+
+        if total > 0 {
+            self.vector_store.compact().await?;
+        }
+
+        Ok(CompactionReport {
+            embeddings_deleted: plan.orphan_embeddings.len(),
+            chunks_deleted: plan.dangling_chunks.len(),
+            degenerate_deleted: plan.degenerate_chunks.len(),
+        })
+    }
+}
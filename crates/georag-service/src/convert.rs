@@ -0,0 +1,447 @@
+//! Converting a dataset file from one format to another
+//!
+//! Conversion reads the file with the registered reader for its format and
+//! writes the features as GeoJSON or CSV, without a workspace or any store.
+//! Between the two it runs the passes ingest runs, in order: property
+//! renames, geometry conversion, reprojection to a target CRS and
+//! simplification of features over a vertex limit. Unlike ingest, features
+//! without a geometry are always kept, since nothing is indexed.
+//!
+//! Formats that hold several layers in one file name a feature's layer in a
+//! property: the KML folder path and the GPX element type. The layers are
+//! written together, or as one output each when split.
+//!
+//! Geometries are written two-dimensional; Z ordinates are dropped. A feature
+//! that fails a pass is reported by its position and ID, failing the
+//! conversion under a strict read policy and skipped under a lenient one.
+
+use georag_core::formats::{
+    FeatureError, FeatureErrorKind, FeatureErrors, FormatFeature, FormatOptions, FormatRegistry,
+};
+use georag_core::geo::{centroid, reproject_geometry, FeatureLimits, GeometryPolicy};
+use georag_core::models::{Crs, Geometry};
+use georag_retrieval::export::to_csv_columns;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::error::{Result, ServiceError};
+use crate::ingest::{simplify_to_limit, too_complex};
+
+/// Property naming the layer of a feature, for formats with several layers per file
+pub const LAYER_PROPERTIES: [(&str, &str); 2] = [("KML", "folder_path"), ("GPX", "type")];
+
+/// CSV columns written after the feature properties
+///
+/// `lon` and `lat` are the centroid of the geometry and `geometry` the
+/// geometry itself as GeoJSON, all in the output CRS. Properties with the
+/// same names are left out; rename them to keep them.
+pub const CSV_GEOMETRY_COLUMNS: [&str; 3] = ["lon", "lat", "geometry"];
+
+/// Format a dataset is converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertFormat {
+    /// GeoJSON FeatureCollection
+    GeoJson,
+    /// CSV with one row per feature
+    Csv,
+}
+
+impl ConvertFormat {
+    /// File extension for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::GeoJson => "geojson",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for ConvertFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GeoJson => write!(f, "geojson"),
+            Self::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+impl FromStr for ConvertFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "geojson" | "json" => Ok(Self::GeoJson),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!("Unknown output format '{}': expected geojson or csv", other)),
+        }
+    }
+}
+
+/// Renames applied to feature properties, in order
+///
+/// Renaming to an empty name drops the property. A property renamed onto an
+/// existing name replaces it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PropertyMapping {
+    renames: Vec<(String, String)>,
+}
+
+impl PropertyMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename `from` to `to`, or drop `from` when `to` is empty
+    pub fn rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.renames.push((from.into(), to.into()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// Apply the renames to a feature's properties
+    pub fn apply(&self, properties: &mut HashMap<String, Value>) {
+        for (from, to) in &self.renames {
+            if let Some(value) = properties.remove(from) {
+                if !to.is_empty() {
+                    properties.insert(to.clone(), value);
+                }
+            }
+        }
+    }
+}
+
+/// How to convert a dataset file
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// Format to write
+    pub format: ConvertFormat,
+
+    /// Reader options and read policy
+    pub read: FormatOptions,
+
+    /// EPSG code to reproject to; the file's own CRS is kept without one
+    pub crs: Option<u32>,
+
+    /// Renames applied to the properties before anything else
+    pub mapping: PropertyMapping,
+
+    /// Simplify features over this many vertices
+    pub max_vertices: Option<usize>,
+
+    /// Write one output per layer instead of all layers together
+    pub split_layers: bool,
+}
+
+impl ConvertOptions {
+    pub fn new(format: ConvertFormat) -> Self {
+        Self {
+            format,
+            read: FormatOptions::default(),
+            crs: None,
+            mapping: PropertyMapping::default(),
+            max_vertices: None,
+            split_layers: false,
+        }
+    }
+
+    pub fn with_read_options(mut self, read: FormatOptions) -> Self {
+        self.read = read;
+        self
+    }
+
+    pub fn with_crs(mut self, crs: Option<u32>) -> Self {
+        self.crs = crs;
+        self
+    }
+
+    pub fn with_mapping(mut self, mapping: PropertyMapping) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    pub fn with_max_vertices(mut self, max_vertices: Option<usize>) -> Self {
+        self.max_vertices = max_vertices;
+        self
+    }
+
+    pub fn with_split_layers(mut self, split_layers: bool) -> Self {
+        self.split_layers = split_layers;
+        self
+    }
+}
+
+/// One converted output
+#[derive(Debug, Clone)]
+pub struct ConvertedLayer {
+    /// Layer the features belong to; `None` for features of no layer, and for
+    /// all features when layers are written together
+    pub layer: Option<String>,
+
+    pub feature_count: usize,
+
+    /// The serialized features
+    pub content: String,
+}
+
+/// Outcome of a conversion
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    /// Format the file was read as
+    pub source_format: String,
+
+    /// CRS of the file
+    pub source_crs: u32,
+
+    /// CRS the features were written in
+    pub crs: u32,
+
+    /// Outputs in layer order; a single one unless layers are split
+    pub layers: Vec<ConvertedLayer>,
+
+    /// Features simplified to fit the vertex limit
+    pub simplified: usize,
+
+    /// Features skipped by the reader or a conversion pass (lenient reads only)
+    pub feature_errors: Vec<FeatureError>,
+}
+
+impl Conversion {
+    pub fn feature_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.feature_count).sum()
+    }
+}
+
+/// A feature that passed every conversion pass
+struct ConvertedFeature {
+    id: String,
+    layer: Option<String>,
+    geometry: Option<Geometry>,
+    properties: HashMap<String, Value>,
+}
+
+/// Service converting dataset files between formats
+pub struct ConvertService {
+    formats: Arc<FormatRegistry>,
+}
+
+impl ConvertService {
+    /// Create a conversion service reading with the registered formats
+    pub fn new(formats: Arc<FormatRegistry>) -> Self {
+        Self { formats }
+    }
+
+    /// Read a dataset file and serialize its features in the output format
+    pub async fn convert(&self, path: &Path, options: &ConvertOptions) -> Result<Conversion> {
+        let reader = self.formats.detect_format(path).map_err(ServiceError::UnsupportedFormat)?;
+        options.read.validate(reader).map_err(ServiceError::InvalidOptions)?;
+        let validation = reader.validate(path).await.map_err(ServiceError::Read)?;
+        if !validation.is_valid() {
+            return Err(ServiceError::InvalidDataset(validation.errors));
+        }
+
+        let dataset = reader
+            .read_with_options(path, &options.read)
+            .await
+            .map_err(ServiceError::Read)?;
+        let source_format = dataset.format_metadata.format_name;
+        let source_crs = dataset.crs;
+        let crs = options.crs.unwrap_or(source_crs);
+        let layer_property = LAYER_PROPERTIES
+            .iter()
+            .find(|(format, _)| *format == source_format)
+            .map(|(_, property)| *property);
+
+        let policy = options.read.read_policy;
+        let mut errors = FeatureErrors::resume(source_format.clone(), policy, dataset.errors);
+        let passes = Passes {
+            from: Crs::new(source_crs, format!("EPSG:{}", source_crs)),
+            to: Crs::new(crs, format!("EPSG:{}", crs)),
+            limits: options
+                .max_vertices
+                .map(|max_vertices| FeatureLimits { max_vertices, ..Default::default() }),
+            mapping: &options.mapping,
+        };
+
+        let mut features = Vec::with_capacity(dataset.features.len());
+        let mut simplified = 0;
+        for (index, feature) in dataset.features.into_iter().enumerate() {
+            let layer = layer_property.and_then(|property| layer_name(&feature, property));
+            match passes.apply(index, feature) {
+                Ok((feature, was_simplified)) => {
+                    simplified += was_simplified as usize;
+                    features.push(ConvertedFeature { layer, ..feature });
+                }
+                Err(error) => errors.record(error).map_err(ServiceError::Read)?,
+            }
+        }
+
+        let layers = if options.split_layers {
+            let mut layers: BTreeMap<Option<String>, Vec<ConvertedFeature>> = BTreeMap::new();
+            for feature in features {
+                layers.entry(feature.layer.clone()).or_default().push(feature);
+            }
+            layers
+                .into_iter()
+                .map(|(layer, features)| serialize(layer, &features, options.format, crs))
+                .collect()
+        } else {
+            vec![serialize(None, &features, options.format, crs)]
+        };
+
+        Ok(Conversion {
+            source_format,
+            source_crs,
+            crs,
+            layers,
+            simplified,
+            feature_errors: errors.into_vec(),
+        })
+    }
+}
+
+/// The ingest passes a feature goes through, in order
+struct Passes<'a> {
+    from: Crs,
+    to: Crs,
+    limits: Option<FeatureLimits>,
+    mapping: &'a PropertyMapping,
+}
+
+impl Passes<'_> {
+    /// Convert one feature, returning it with whether it was simplified
+    fn apply(
+        &self,
+        index: usize,
+        feature: FormatFeature,
+    ) -> std::result::Result<(ConvertedFeature, bool), FeatureError> {
+        let FormatFeature { id, geometry, mut properties } = feature;
+        let failed = |kind, message: String| FeatureError::new(index, kind, message).with_id(&id);
+        self.mapping.apply(&mut properties);
+
+        let geometry = match GeometryPolicy::Keep.apply(geometry.as_ref()) {
+            Ok(geometry) => geometry.flatten(),
+            Err((kind, message)) => return Err(failed(kind, message)),
+        };
+        let geometry = geometry
+            .map(|geometry| reproject_geometry(&geometry, &self.from, &self.to))
+            .transpose()
+            .map_err(|e| {
+                failed(
+                    FeatureErrorKind::InvalidGeometry,
+                    format!("cannot reproject to {}: {}", self.to.name, e),
+                )
+            })?;
+
+        let mut simplified = false;
+        let geometry = match (geometry, &self.limits) {
+            (Some(geometry), Some(limits)) if limits.exceeds(&geometry) => {
+                let Some(reduced) = simplify_to_limit(&geometry, limits) else {
+                    return Err(too_complex(index, &geometry, limits).with_id(&id));
+                };
+                simplified = true;
+                Some(reduced)
+            }
+            (geometry, _) => geometry,
+        };
+
+        let feature = ConvertedFeature { id, layer: None, geometry, properties };
+        Ok((feature, simplified))
+    }
+}
+
+/// Layer named by a feature's layer property, `None` when it has none
+fn layer_name(feature: &FormatFeature, property: &str) -> Option<String> {
+    match feature.properties.get(property)? {
+        Value::String(name) if name.is_empty() => None,
+        Value::String(name) => Some(name.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+fn serialize(
+    layer: Option<String>,
+    features: &[ConvertedFeature],
+    format: ConvertFormat,
+    crs: u32,
+) -> ConvertedLayer {
+    let content = match format {
+        ConvertFormat::GeoJson => to_geojson(features, crs),
+        ConvertFormat::Csv => to_csv(features),
+    };
+    ConvertedLayer {
+        layer,
+        feature_count: features.len(),
+        content,
+    }
+}
+
+/// Write features as a FeatureCollection, naming the CRS when it is not WGS 84
+fn to_geojson(features: &[ConvertedFeature], crs: u32) -> String {
+    let features: Vec<Value> = features
+        .iter()
+        .map(|feature| {
+            let properties: Map<String, Value> = feature
+                .properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect();
+            json!({
+                "type": "Feature",
+                "id": feature.id,
+                "geometry": feature.geometry.as_ref().map_or(Value::Null, Geometry::to_geojson),
+                "properties": properties,
+            })
+        })
+        .collect();
+
+    let mut collection = json!({ "type": "FeatureCollection", "features": features });
+    if crs != 4326 {
+        collection["crs"] = json!({
+            "type": "name",
+            "properties": { "name": format!("urn:ogc:def:crs:EPSG::{}", crs) },
+        });
+    }
+    serde_json::to_string_pretty(&collection).unwrap_or_default()
+}
+
+/// Write features as CSV: `id`, the properties in name order, then [`CSV_GEOMETRY_COLUMNS`]
+fn to_csv(features: &[ConvertedFeature]) -> String {
+    let properties: BTreeSet<&str> = features
+        .iter()
+        .flat_map(|feature| feature.properties.keys().map(String::as_str))
+        .filter(|key| *key != "id" && !CSV_GEOMETRY_COLUMNS.contains(key))
+        .collect();
+    let mut columns = vec!["id"];
+    columns.extend(properties);
+    columns.extend(CSV_GEOMETRY_COLUMNS);
+
+    let records: Vec<Map<String, Value>> = features
+        .iter()
+        .map(|feature| {
+            let mut record: Map<String, Value> = feature
+                .properties
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            record.insert("id".to_string(), Value::from(feature.id.clone()));
+            let location = feature.geometry.as_ref().and_then(centroid);
+            record.insert("lon".to_string(), location.map_or(Value::Null, |[x, _]| x.into()));
+            record.insert("lat".to_string(), location.map_or(Value::Null, |[_, y]| y.into()));
+            let geometry = feature.geometry.as_ref().map(|g| g.to_geojson().to_string());
+            record.insert("geometry".to_string(), geometry.map_or(Value::Null, Value::from));
+            record
+        })
+        .collect();
+
+    to_csv_columns(&records, &columns)
+}
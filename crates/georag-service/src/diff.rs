@@ -0,0 +1,401 @@
+//! Change detection between a stored dataset and a new version of its file
+//!
+//! Features are matched by a key property (the original feature id by
+//! default). Features without a usable key fall back to content hashes of
+//! their geometry and properties. Nothing is written until [`DiffService::apply`]
+//! is called with the computed diff.
+
+use georag_core::models::{DatasetId, Feature, FeatureId, PreviewBuilder};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// Property used to match features when no key is given
+pub const DEFAULT_DIFF_KEY: &str = "id";
+
+/// A stored feature matched to its incoming version
+#[derive(Debug, Clone)]
+pub struct FeatureChange {
+    /// Feature as currently stored
+    pub before: Feature,
+
+    /// Incoming feature, carrying the stored feature's ID
+    pub after: Feature,
+
+    /// Value of the key property, if the features were matched by key
+    pub key: Option<String>,
+
+    /// Whether the geometry differs
+    pub geometry_changed: bool,
+
+    /// Names of added, removed or modified properties, sorted
+    pub changed_properties: Vec<String>,
+}
+
+/// Counts of each change class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffCounts {
+    pub added: usize,
+    pub removed: usize,
+    pub geometry_changed: usize,
+    pub property_changed: usize,
+    pub unchanged: usize,
+}
+
+/// Differences between a stored dataset and an incoming feature set
+#[derive(Debug, Clone)]
+pub struct DatasetDiff {
+    /// Dataset the incoming features were compared against
+    pub dataset_id: DatasetId,
+
+    /// Key property used for matching
+    pub key: String,
+
+    /// Features matched by key rather than by content hash
+    pub matched_by_key: usize,
+
+    /// Incoming features without a stored counterpart
+    pub added: Vec<Feature>,
+
+    /// Stored features without an incoming counterpart
+    pub removed: Vec<Feature>,
+
+    /// Matched features whose geometry or properties differ
+    pub changed: Vec<FeatureChange>,
+
+    /// Matched features that are identical
+    pub unchanged: usize,
+}
+
+impl DatasetDiff {
+    /// Count each change class
+    ///
+    /// A feature whose geometry and properties both changed counts in both
+    /// classes.
+    pub fn counts(&self) -> DiffCounts {
+        DiffCounts {
+            added: self.added.len(),
+            removed: self.removed.len(),
+            geometry_changed: self.changed.iter().filter(|c| c.geometry_changed).count(),
+            property_changed: self
+                .changed
+                .iter()
+                .filter(|c| !c.changed_properties.is_empty())
+                .count(),
+            unchanged: self.unchanged,
+        }
+    }
+
+    /// Whether the incoming features equal the stored ones
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Outcome of applying a diff
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffApplyReport {
+    /// Features inserted or replaced
+    pub features_upserted: usize,
+
+    /// Features deleted
+    pub features_deleted: usize,
+
+    /// Chunks of affected features removed together with their embeddings
+    pub chunks_invalidated: usize,
+}
+
+/// Service for comparing and updating a dataset in place
+pub struct DiffService {
+    spatial_store: Arc<dyn SpatialStore>,
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+}
+
+impl DiffService {
+    /// Create a diff service over the workspace stores
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        document_store: Arc<dyn DocumentStore>,
+        vector_store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            spatial_store,
+            document_store,
+            vector_store,
+        }
+    }
+
+    /// Compare incoming features with the stored dataset without writing anything
+    pub async fn diff(
+        &self,
+        dataset_id: DatasetId,
+        incoming: Vec<Feature>,
+        key: Option<&str>,
+    ) -> Result<DatasetDiff> {
+        let stored = self.spatial_store.get_features_for_dataset(dataset_id).await?;
+        Ok(diff_features(dataset_id, stored, incoming, key.unwrap_or(DEFAULT_DIFF_KEY)))
+    }
+
+    /// Write the minimal set of upserts and deletes for a diff
+    ///
+    /// Chunks derived from changed or removed features are deleted with their
+    /// embeddings so the next build re-embeds only those features; chunks of
+    /// unchanged features stay in place. The dataset preview is rebuilt from
+    /// the updated features.
+    pub async fn apply(&self, diff: &DatasetDiff) -> Result<DiffApplyReport> {
+        let existing = self.spatial_store.get_features_for_dataset(diff.dataset_id).await?;
+        let mut next_id = existing.iter().map(|f| f.id.0 + 1).max().unwrap_or(0);
+
+        let mut upserts: Vec<Feature> = diff.changed.iter().map(|c| c.after.clone()).collect();
+        for feature in &diff.added {
+            let mut feature = feature.clone();
+            feature.id = FeatureId(next_id);
+            next_id += 1;
+            upserts.push(feature);
+        }
+        let deletes: Vec<FeatureId> = diff.removed.iter().map(|f| f.id).collect();
+
+        let affected: HashSet<FeatureId> = diff
+            .changed
+            .iter()
+            .map(|c| c.before.id)
+            .chain(deletes.iter().copied())
+            .collect();
+        let chunks_invalidated = invalidate_feature_chunks(
+            self.document_store.as_ref(),
+            self.vector_store.as_ref(),
+            &affected,
+        )
+        .await?;
+
+        self.spatial_store.upsert_dataset_features(diff.dataset_id, &upserts).await?;
+        self.spatial_store.delete_features(diff.dataset_id, &deletes).await?;
+        refresh_preview(self.spatial_store.as_ref(), diff.dataset_id).await?;
+
+        Ok(DiffApplyReport {
+            features_upserted: upserts.len(),
+            features_deleted: deletes.len(),
+            chunks_invalidated,
+        })
+    }
+}
+
+/// Delete chunks and embeddings that reference any of the features
+///
+/// Returns the number of chunks deleted.
+pub(crate) async fn invalidate_feature_chunks(
+    document_store: &dyn DocumentStore,
+    vector_store: &dyn VectorStore,
+    features: &HashSet<FeatureId>,
+) -> Result<usize> {
+    if features.is_empty() {
+        return Ok(0);
+    }
+
+    let chunk_ids = document_store.list_chunk_ids().await?;
+    let stale: Vec<_> = document_store
+        .get_chunks(&chunk_ids)
+        .await?
+        .into_iter()
+        .filter(|chunk| chunk.spatial_ref.is_some_and(|id| features.contains(&id)))
+        .map(|chunk| chunk.id)
+        .collect();
+
+    if !stale.is_empty() {
+        vector_store.delete_embeddings(&stale).await?;
+        document_store.delete_chunks(&stale).await?;
+    }
+
+    Ok(stale.len())
+}
+
+/// Rebuild a dataset's preview from its stored features
+pub(crate) async fn refresh_preview(
+    spatial_store: &dyn SpatialStore,
+    dataset_id: DatasetId,
+) -> Result<()> {
+    let Some(dataset) = spatial_store.get_dataset(dataset_id).await? else {
+        return Ok(());
+    };
+
+    let mut preview =
+        PreviewBuilder::new(dataset.format.page_count, dataset.format.paragraph_count);
+    for feature in spatial_store.get_features_for_dataset(dataset_id).await? {
+        preview.add(&feature);
+    }
+    spatial_store.set_dataset_preview(dataset_id, &preview.finish()).await?;
+    Ok(())
+}
+
+/// Match stored and incoming features and classify the differences
+pub fn diff_features(
+    dataset_id: DatasetId,
+    stored: Vec<Feature>,
+    incoming: Vec<Feature>,
+    key: &str,
+) -> DatasetDiff {
+    let mut diff = DatasetDiff {
+        dataset_id,
+        key: key.to_string(),
+        matched_by_key: 0,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+
+    // Keys must be unique on both sides; duplicates fall back to hashing
+    let stored_keys = unique_keys(&stored, key);
+    let incoming_keys = unique_keys(&incoming, key);
+
+    let mut stored_by_key: HashMap<String, Feature> = HashMap::new();
+    let mut stored_rest = Vec::new();
+    for feature in stored {
+        match key_value(&feature, key).filter(|value| incoming_keys.contains(value)) {
+            Some(value) if stored_keys.contains(&value) => {
+                stored_by_key.insert(value, feature);
+            }
+            _ => stored_rest.push(feature),
+        }
+    }
+
+    let mut incoming_rest = Vec::new();
+    for feature in incoming {
+        let matched = key_value(&feature, key)
+            .filter(|value| incoming_keys.contains(value))
+            .and_then(|value| stored_by_key.remove(&value).map(|before| (value, before)));
+
+        match matched {
+            Some((value, before)) => {
+                diff.matched_by_key += 1;
+                diff.record(before, feature, Some(value));
+            }
+            None => incoming_rest.push(feature),
+        }
+    }
+
+    diff.match_by_content(stored_rest, incoming_rest);
+    diff
+}
+
+impl DatasetDiff {
+    /// Record a matched pair as changed or unchanged
+    fn record(&mut self, before: Feature, mut after: Feature, key: Option<String>) {
+        after.id = before.id;
+        let geometry_changed = geometry_hash(&before) != geometry_hash(&after);
+        let changed_properties = changed_properties(&before, &after);
+
+        if !geometry_changed && changed_properties.is_empty() {
+            self.unchanged += 1;
+        } else {
+            self.changed.push(FeatureChange {
+                before,
+                after,
+                key,
+                geometry_changed,
+                changed_properties,
+            });
+        }
+    }
+
+    /// Pair the remaining features by identical content, then by identical
+    /// geometry, then by identical properties
+    fn match_by_content(&mut self, stored: Vec<Feature>, incoming: Vec<Feature>) {
+        let mut stored: Vec<Option<Feature>> = stored.into_iter().map(Some).collect();
+        let mut incoming: Vec<Option<Feature>> = incoming.into_iter().map(Some).collect();
+
+        let passes: [fn(&Feature) -> Option<u64>; 3] = [
+            |f| Some(combine(geometry_hash(f), properties_hash(f))),
+            |f| Some(geometry_hash(f)),
+            |f| (!f.properties.is_empty()).then(|| properties_hash(f)),
+        ];
+
+        for hash in passes {
+            let mut candidates: HashMap<u64, Vec<usize>> = HashMap::new();
+            for (idx, feature) in stored.iter().enumerate() {
+                if let Some(value) = feature.as_ref().and_then(hash) {
+                    candidates.entry(value).or_default().push(idx);
+                }
+            }
+            // Pair in file order when several features share a hash
+            for list in candidates.values_mut() {
+                list.reverse();
+            }
+
+            for slot in incoming.iter_mut() {
+                let Some(value) = slot.as_ref().and_then(hash) else {
+                    continue;
+                };
+                let Some(idx) = candidates.get_mut(&value).and_then(|list| list.pop()) else {
+                    continue;
+                };
+                if let (Some(before), Some(after)) = (stored[idx].take(), slot.take()) {
+                    self.record(before, after, None);
+                }
+            }
+        }
+
+        self.removed.extend(stored.into_iter().flatten());
+        self.added.extend(incoming.into_iter().flatten());
+    }
+}
+
+/// Key values that occur exactly once in the features
+fn unique_keys(features: &[Feature], key: &str) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = HashSet::new();
+    for value in features.iter().filter_map(|f| key_value(f, key)) {
+        if !seen.insert(value.clone()) {
+            duplicates.insert(value);
+        }
+    }
+    seen.retain(|value| !duplicates.contains(value));
+    seen
+}
+
+/// Text of the key property, if present and not null
+fn key_value(feature: &Feature, key: &str) -> Option<String> {
+    match feature.properties.get(key)? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn changed_properties(before: &Feature, after: &Feature) -> Vec<String> {
+    let names: BTreeSet<&String> =
+        before.properties.keys().chain(after.properties.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| before.properties.get(*name) != after.properties.get(*name))
+        .cloned()
+        .collect()
+}
+
+fn geometry_hash(feature: &Feature) -> u64 {
+    let geometry = feature.geometry_geojson();
+    hash_json(&serde_json::to_string(&geometry).unwrap_or_default())
+}
+
+fn properties_hash(feature: &Feature) -> u64 {
+    // Sorted so the hash does not depend on map iteration order
+    let properties: BTreeMap<&String, &serde_json::Value> = feature.properties.iter().collect();
+    hash_json(&serde_json::to_string(&properties).unwrap_or_default())
+}
+
+fn hash_json(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn combine(geometry: u64, properties: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (geometry, properties).hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,153 @@
+use georag_core::error::GeoragError;
+use georag_core::models::{ChunkId, DatasetId, FeatureId, WorkspaceId};
+use thiserror::Error;
+
+/// Errors returned by the application services
+///
+/// Variants follow the service steps so adapters can map each one to their
+/// own messages and status codes.
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    /// No reader is registered for the file
+    #[error("Unsupported file format: {0}")]
+    UnsupportedFormat(#[source] GeoragError),
+
+    /// The reader options do not match the detected format's option schema
+    #[error("Invalid format options: {0}")]
+    InvalidOptions(#[source] GeoragError),
+
+    /// The reader rejected the file during validation
+    #[error("Format validation failed: {}", .0.join("; "))]
+    InvalidDataset(Vec<String>),
+
+    /// The file could not be parsed
+    #[error("Failed to read dataset: {0}")]
+    Read(#[source] GeoragError),
+
+    /// The dataset CRS differs from the workspace CRS and mismatches are not allowed
+    #[error(
+        "CRS mismatch: dataset has EPSG:{dataset_crs}, workspace expects EPSG:{workspace_crs}"
+    )]
+    CrsMismatch { dataset_crs: u32, workspace_crs: u32 },
+
+    /// The query options are invalid
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// The spatial join options are invalid
+    #[error("Invalid join: {0}")]
+    InvalidJoin(String),
+
+    /// The area name or geometry is invalid
+    #[error("Invalid area: {0}")]
+    InvalidArea(String),
+
+    /// No saved area has the name
+    #[error("Area not found: {name}")]
+    AreaNotFound { name: String },
+
+    /// A saved area already has the name
+    #[error("Area already exists: {name}")]
+    AreaExists { name: String },
+
+    /// A query preset's name or options are invalid
+    #[error("Invalid preset {name}: {reason}")]
+    InvalidPreset { name: String, reason: String },
+
+    /// The workspace has no query preset of the name
+    #[error("Preset not found: {name}")]
+    PresetNotFound { name: String },
+
+    /// A feature looked up in a dataset, e.g. to copy an area from, is not in it
+    #[error("Feature {} not found in dataset {}", .feature_id.0, .dataset_id.0)]
+    FeatureNotFound {
+        dataset_id: DatasetId,
+        feature_id: FeatureId,
+    },
+
+    /// No dataset has the ID
+    #[error("Dataset not found: {}", .id.0)]
+    DatasetNotFound { id: DatasetId },
+
+    /// A dataset location is not an http(s) URL
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+
+    /// A dataset URL could not be downloaded
+    #[error("Failed to download {url}: {reason}")]
+    Download { url: String, reason: String },
+
+    /// A dataset URL names a host the download policy refuses
+    #[error("Refused to download {url}: {reason}")]
+    DownloadRefused { url: String, reason: String },
+
+    /// A dataset URL serves more than the download limit
+    #[error("Download of {url} exceeds the limit of {limit} bytes")]
+    DownloadTooLarge { url: String, limit: u64 },
+
+    /// A bulk operation's dataset filter is empty or its name pattern is invalid
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// The timeline range is in the future or too long
+    #[error("Invalid timeline: {0}")]
+    InvalidTimeline(String),
+
+    /// The workspace name is empty or otherwise unusable
+    #[error("Invalid workspace name: {0}")]
+    InvalidWorkspaceName(String),
+
+    /// A workspace already has the name
+    #[error("Workspace '{name}' already exists")]
+    WorkspaceExists { name: String },
+
+    /// No workspace has the ID
+    #[error("Workspace not found: {id}")]
+    WorkspaceNotFound { id: WorkspaceId },
+
+    /// Workspace settings fail the checks `georag config check` runs
+    #[error("Invalid workspace settings: {0}")]
+    InvalidSettings(#[source] GeoragError),
+
+    /// A setting cannot change while the workspace holds data depending on it
+    #[error("Cannot change {key}: {reason}")]
+    SettingLocked { key: String, reason: String },
+
+    /// A query cursor is malformed or points past its results
+    #[error("Invalid cursor: {0}")]
+    InvalidCursor(String),
+
+    /// The ranked results a cursor points into were dropped after the page TTL
+    #[error("Cursor expired: run the query again")]
+    CursorExpired,
+
+    /// The index changed since the query a cursor belongs to was ranked
+    #[error("Cursor invalidated: the index changed since the query ran")]
+    CursorInvalidated,
+
+    /// A cursor belongs to a query of another workspace
+    #[error("Cursor not found in this workspace")]
+    CursorNotFound,
+
+    /// No chunk has the ID
+    #[error("Chunk not found: {}", .id.0)]
+    ChunkNotFound { id: ChunkId },
+
+    /// The example of a query by example has no embedded chunks to search with
+    #[error("{example} has no indexed chunks to search with")]
+    ExampleNotIndexed { example: String },
+
+    /// An event log read or cursor names an invalid limit, consumer or sequence number
+    #[error("Invalid event read: {0}")]
+    InvalidEventRead(String),
+
+    /// Events after the sequence number a reader asked for were already pruned
+    #[error("Events after {after_seq} were pruned; the oldest kept is {}", .first.map_or("none".to_string(), |seq| seq.to_string()))]
+    EventsPruned { after_seq: u64, first: Option<u64> },
+
+    /// Storage or retrieval failure
+    #[error(transparent)]
+    Core(#[from] GeoragError),
+}
+
+pub type Result<T> = std::result::Result<T, ServiceError>;
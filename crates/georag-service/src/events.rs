@@ -0,0 +1,429 @@
+//! Ordered change events for downstream consumers
+//!
+//! Adapters record changes to datasets, features and chunks where they
+//! record audit events. Unlike audit events, change events carry the IDs and
+//! new state of what changed, and the log orders them across workspaces:
+//! consumers read the events after the last sequence number they saw, waiting
+//! for new ones if there are none yet, and rebuild a workspace elsewhere by
+//! applying its events in order with [`EventReplay`]. Recording never fails
+//! the operation it describes; a store error is only logged.
+
+use georag_core::error::GeoragError;
+use georag_core::models::{
+    ChangeEntity, ChangeEvent, ChangeOperation, ChunkId, Dataset, DatasetId, EventLogBounds,
+    EventRetention, Feature, FeatureId, WorkspaceId, MAX_EVENT_PAGE, MAX_EVENT_RECORDS,
+};
+use georag_store::ports::{DocumentStore, EventLogStore, SpatialStore};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::error::{Result, ServiceError};
+
+/// Longest a read waits for new events
+pub const MAX_EVENT_WAIT: Duration = Duration::from_secs(60);
+
+/// Longest name of a consumer with a stored cursor
+pub const MAX_CONSUMER_NAME_LEN: usize = 64;
+
+/// How often a waiting read looks for events appended by other processes
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Appends between two prunes of the log
+const PRUNE_EVERY: u64 = 100;
+
+/// Events read from the log, with the sequence numbers it holds
+#[derive(Debug, Clone)]
+pub struct EventPage {
+    /// Events after the requested sequence number, oldest first
+    pub events: Vec<ChangeEvent>,
+
+    /// Oldest event kept and newest event appended when the page was read
+    pub bounds: EventLogBounds,
+
+    after_seq: u64,
+}
+
+impl EventPage {
+    /// Sequence number to read after next: the last event's, or the one read after
+    pub fn next_after_seq(&self) -> u64 {
+        self.events.last().map_or(self.after_seq, |event| event.seq)
+    }
+}
+
+/// Service recording change events and reading them back in order
+#[derive(Clone)]
+pub struct EventLogService {
+    store: Arc<dyn EventLogStore>,
+    appended: Arc<Notify>,
+    retention: Option<EventRetention>,
+}
+
+impl EventLogService {
+    /// Create an event log service over the event log store
+    ///
+    /// Without a retention the service never prunes the log.
+    pub fn new(store: Arc<dyn EventLogStore>) -> Self {
+        Self {
+            store,
+            appended: Arc::new(Notify::new()),
+            retention: None,
+        }
+    }
+
+    /// Share the signal waiting reads get on appends with other services over the store
+    pub fn with_notify(mut self, appended: Arc<Notify>) -> Self {
+        self.appended = appended;
+        self
+    }
+
+    /// Prune the log to the retention as events are appended
+    pub fn with_retention(mut self, retention: EventRetention) -> Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Append events in order; a failure is logged rather than returned
+    pub async fn record(&self, events: Vec<ChangeEvent>) {
+        let Some(first) = events.first() else {
+            return;
+        };
+        let (workspace_id, entity) = (first.workspace_id, first.entity);
+
+        let last = match self.store.append_events(&events).await {
+            Ok(last) => last,
+            Err(e) => {
+                tracing::warn!(
+                    workspace_id = %workspace_id,
+                    entity = %entity,
+                    events = events.len(),
+                    error = %e,
+                    "Failed to record change events"
+                );
+                return;
+            }
+        };
+        self.appended.notify_waiters();
+
+        let crossed = last / PRUNE_EVERY != last.saturating_sub(events.len() as u64) / PRUNE_EVERY;
+        if let Some(retention) = self.retention.filter(|_| crossed) {
+            match self.store.prune_events(&retention).await {
+                Ok(0) => {}
+                Ok(pruned) => tracing::debug!(pruned, "Pruned change events"),
+                Err(e) => tracing::warn!(error = %e, "Failed to prune change events"),
+            }
+        }
+    }
+
+    /// Record an added dataset with all of its features
+    ///
+    /// The dataset and its features are read back from `spatial`, so the
+    /// events carry what was stored. They are appended together, so they
+    /// get consecutive sequence numbers.
+    pub async fn record_dataset_added(
+        &self,
+        workspace_id: WorkspaceId,
+        spatial: &dyn SpatialStore,
+        dataset_id: DatasetId,
+    ) {
+        let stored = match spatial.get_dataset(dataset_id).await {
+            Ok(Some(dataset)) => spatial
+                .get_features_for_dataset(dataset_id)
+                .await
+                .map(|features| (dataset, features)),
+            Ok(None) => Err(GeoragError::DatasetNotFound { name: dataset_id.0.to_string() }),
+            Err(e) => Err(e),
+        };
+        let (dataset, features) = match stored {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!(
+                    workspace_id = %workspace_id,
+                    dataset_id = dataset_id.0,
+                    error = %e,
+                    "Failed to read the dataset for its change events"
+                );
+                return;
+            }
+        };
+
+        let mut events = vec![dataset_event(workspace_id, &dataset)];
+        events.extend(features.chunks(MAX_EVENT_RECORDS).map(|features| {
+            ChangeEvent::new(
+                workspace_id,
+                ChangeEntity::Feature,
+                ChangeOperation::Upsert,
+                features.iter().map(|feature| feature.id.0).collect(),
+            )
+            .with_dataset(dataset_id)
+            .with_payload(serde_json::to_value(features).unwrap_or_default())
+        }));
+        self.record(events).await;
+    }
+
+    /// Record a dataset whose metadata changed in place, e.g. its tags
+    pub async fn record_dataset_updated(&self, workspace_id: WorkspaceId, dataset: &Dataset) {
+        self.record(vec![dataset_event(workspace_id, dataset)]).await;
+    }
+
+    /// Record a deleted dataset; its features went with it
+    pub async fn record_dataset_deleted(&self, workspace_id: WorkspaceId, dataset_id: DatasetId) {
+        let event = ChangeEvent::new(
+            workspace_id,
+            ChangeEntity::Dataset,
+            ChangeOperation::Delete,
+            vec![dataset_id.0],
+        )
+        .with_dataset(dataset_id);
+        self.record(vec![event]).await;
+    }
+
+    /// Record the chunks a build stored and those it removed
+    ///
+    /// `before` lists the chunks held before the build; the chunks held now
+    /// are read from `document`. Every chunk held now counts as upserted,
+    /// since a build may rewrite a chunk under its old ID.
+    pub async fn record_chunks_built(
+        &self,
+        workspace_id: WorkspaceId,
+        document: &dyn DocumentStore,
+        before: &[ChunkId],
+    ) {
+        let after = match document.list_chunk_ids().await {
+            Ok(after) => after,
+            Err(e) => {
+                tracing::warn!(
+                    workspace_id = %workspace_id,
+                    error = %e,
+                    "Failed to list chunks for their change events"
+                );
+                return;
+            }
+        };
+        self.record(chunk_events(workspace_id, before, &after)).await;
+    }
+
+    /// Record chunks removed outside a build, e.g. with their dataset
+    pub async fn record_chunks_deleted(&self, workspace_id: WorkspaceId, chunks: &[ChunkId]) {
+        self.record(chunk_events(workspace_id, chunks, &[])).await;
+    }
+
+    /// Events after `after_seq`, waiting up to `wait` for one if there are none yet
+    ///
+    /// Fails with `EventsPruned` when events right after `after_seq` were
+    /// already pruned, so the reader would miss them.
+    pub async fn read(&self, after_seq: u64, limit: usize, wait: Duration) -> Result<EventPage> {
+        if limit == 0 || limit > MAX_EVENT_PAGE {
+            return Err(ServiceError::InvalidEventRead(format!(
+                "limit must be between 1 and {}",
+                MAX_EVENT_PAGE
+            )));
+        }
+        let deadline = Instant::now() + wait.min(MAX_EVENT_WAIT);
+
+        loop {
+            // Listen before looking, so an append in between is not missed
+            let appended = self.appended.notified();
+            tokio::pin!(appended);
+            appended.as_mut().enable();
+
+            let bounds = self.store.event_bounds().await?;
+            let events = self.store.events_after(after_seq, limit).await?;
+            let skipped = events.first().is_some_and(|event| event.seq > after_seq + 1);
+            if bounds.is_pruned(after_seq) || skipped {
+                return Err(ServiceError::EventsPruned { after_seq, first: bounds.first });
+            }
+
+            let now = Instant::now();
+            if !events.is_empty() || now >= deadline {
+                return Ok(EventPage { events, bounds, after_seq });
+            }
+            // Appends by other processes sharing the store send no signal
+            let _ = tokio::time::timeout((deadline - now).min(EVENT_POLL_INTERVAL), appended).await;
+        }
+    }
+
+    /// Sequence number a consumer last acknowledged, 0 if it never did
+    pub async fn cursor(&self, consumer: &str) -> Result<u64> {
+        validate_consumer(consumer)?;
+        Ok(self.store.get_event_cursor(consumer).await?.unwrap_or(0))
+    }
+
+    /// Record that a consumer applied every event up to `seq`
+    ///
+    /// Fails for sequence numbers the log has not handed out yet.
+    pub async fn acknowledge(&self, consumer: &str, seq: u64) -> Result<()> {
+        validate_consumer(consumer)?;
+        let last = self.store.event_bounds().await?.last;
+        if seq > last {
+            return Err(ServiceError::InvalidEventRead(format!(
+                "seq {} lies past the newest event, {}",
+                seq, last
+            )));
+        }
+        self.store.set_event_cursor(consumer, seq).await?;
+        Ok(())
+    }
+}
+
+/// Check the name a consumer stores its cursor under
+pub fn validate_consumer(consumer: &str) -> Result<()> {
+    if consumer.is_empty() || consumer.len() > MAX_CONSUMER_NAME_LEN {
+        return Err(ServiceError::InvalidEventRead(format!(
+            "consumer names have 1 to {} characters",
+            MAX_CONSUMER_NAME_LEN
+        )));
+    }
+    if !consumer
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(ServiceError::InvalidEventRead(format!(
+            "consumer '{}' may only use letters, digits, '-', '_' and '.'",
+            consumer
+        )));
+    }
+    Ok(())
+}
+
+/// Upsert of a dataset carrying its stored metadata
+fn dataset_event(workspace_id: WorkspaceId, dataset: &Dataset) -> ChangeEvent {
+    ChangeEvent::new(
+        workspace_id,
+        ChangeEntity::Dataset,
+        ChangeOperation::Upsert,
+        vec![dataset.id.0],
+    )
+    .with_dataset(dataset.id)
+    .with_payload(serde_json::to_value(dataset).unwrap_or_default())
+}
+
+/// Deletes of the chunks in `before` but not `after`, then upserts of `after`
+fn chunk_events(
+    workspace_id: WorkspaceId,
+    before: &[ChunkId],
+    after: &[ChunkId],
+) -> Vec<ChangeEvent> {
+    let kept: HashSet<ChunkId> = after.iter().copied().collect();
+    let removed: Vec<u64> = before.iter().filter(|id| !kept.contains(id)).map(|id| id.0).collect();
+    let stored: Vec<u64> = after.iter().map(|id| id.0).collect();
+
+    let events = |operation, ids: Vec<u64>| {
+        ids.chunks(MAX_EVENT_RECORDS)
+            .map(|ids| ChangeEvent::new(workspace_id, ChangeEntity::Chunk, operation, ids.to_vec()))
+            .collect::<Vec<_>>()
+    };
+    let mut all = events(ChangeOperation::Delete, removed);
+    all.extend(events(ChangeOperation::Upsert, stored));
+    all
+}
+
+/// Applies the change events of one workspace to a spatial store
+///
+/// Datasets get new IDs in the target store, which the replay maps from
+/// the IDs of the events. Events at or before the last one applied are
+/// skipped, so reading a page twice applies it once; events of other
+/// workspaces and chunk events are passed over, since indexes are rebuilt
+/// from the features rather than copied.
+pub struct EventReplay {
+    workspace_id: WorkspaceId,
+    target: Arc<dyn SpatialStore>,
+    datasets: HashMap<DatasetId, DatasetId>,
+    applied_seq: u64,
+}
+
+impl EventReplay {
+    /// Create a replay of a workspace's events into `target`
+    pub fn new(workspace_id: WorkspaceId, target: Arc<dyn SpatialStore>) -> Self {
+        Self {
+            workspace_id,
+            target,
+            datasets: HashMap::new(),
+            applied_seq: 0,
+        }
+    }
+
+    /// Sequence number of the last event applied or passed over
+    pub fn applied_seq(&self) -> u64 {
+        self.applied_seq
+    }
+
+    /// ID in the target store of a dataset of the events
+    pub fn target_dataset(&self, source: DatasetId) -> Option<DatasetId> {
+        self.datasets.get(&source).copied()
+    }
+
+    /// Apply events in sequence order, returning how many changed the target
+    pub async fn apply(&mut self, events: &[ChangeEvent]) -> Result<usize> {
+        let mut applied = 0;
+        for event in events {
+            if event.seq <= self.applied_seq {
+                continue;
+            }
+            if event.workspace_id == self.workspace_id && self.apply_one(event).await? {
+                applied += 1;
+            }
+            self.applied_seq = event.seq;
+        }
+        Ok(applied)
+    }
+
+    async fn apply_one(&mut self, event: &ChangeEvent) -> Result<bool> {
+        let mapped = event.dataset_id.and_then(|id| self.target_dataset(id));
+        match (event.entity, event.operation, mapped) {
+            (ChangeEntity::Dataset, ChangeOperation::Upsert, None) => {
+                let dataset: Dataset = serde_json::from_value(payload(event)?)
+                    .map_err(|e| invalid_payload(event, e))?;
+                let target = self.target.store_dataset(&dataset).await?;
+                self.datasets.insert(dataset.id, target);
+            }
+            (ChangeEntity::Dataset, ChangeOperation::Upsert, Some(target)) => {
+                // Datasets change in place only in their tags and preview
+                let dataset: Dataset = serde_json::from_value(payload(event)?)
+                    .map_err(|e| invalid_payload(event, e))?;
+                self.target.set_dataset_tags(target, &dataset.tags).await?;
+                if let Some(preview) = &dataset.format.preview {
+                    self.target.set_dataset_preview(target, preview).await?;
+                }
+            }
+            (ChangeEntity::Dataset, ChangeOperation::Delete, Some(target)) => {
+                let features: Vec<FeatureId> = self
+                    .target
+                    .get_features_for_dataset(target)
+                    .await?
+                    .iter()
+                    .map(|feature| feature.id)
+                    .collect();
+                self.target.delete_features(target, &features).await?;
+                self.target.delete_dataset(target).await?;
+                self.datasets.retain(|_, mapped| *mapped != target);
+            }
+            (ChangeEntity::Feature, ChangeOperation::Upsert, Some(target)) => {
+                let features: Vec<Feature> = serde_json::from_value(payload(event)?)
+                    .map_err(|e| invalid_payload(event, e))?;
+                self.target.upsert_dataset_features(target, &features).await?;
+            }
+            (ChangeEntity::Feature, ChangeOperation::Delete, Some(target)) => {
+                let ids: Vec<FeatureId> = event.ids.iter().map(|&id| FeatureId(id)).collect();
+                self.target.delete_features(target, &ids).await?;
+            }
+            // Chunks, and records of datasets the replay never saw added
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// New state carried by an upsert
+fn payload(event: &ChangeEvent) -> Result<serde_json::Value> {
+    event.payload.clone().ok_or_else(|| {
+        GeoragError::Serialization(format!("Change event {} carries no payload", event.seq)).into()
+    })
+}
+
+fn invalid_payload(event: &ChangeEvent, error: serde_json::Error) -> ServiceError {
+    GeoragError::Serialization(format!("Invalid payload of change event {}: {}", event.seq, error))
+        .into()
+}
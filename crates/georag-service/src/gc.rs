@@ -0,0 +1,142 @@
+//! Garbage collection of chunks generated from outdated features
+//!
+//! Feature edits, upserts and re-added datasets leave chunks behind that were
+//! cut from an older version of their feature. A scan compares each chunk
+//! with the feature it references: chunks whose feature is gone are deleted
+//! together with their embeddings, and chunks whose feature text changed are
+//! marked stale, which keeps them out of retrieval until a build replaces
+//! them. Chunks carry the hash of the text they were cut from; chunks from
+//! indexes built before the hash was recorded are compared by content.
+//!
+//! Callers must hold the build lock while scanning and applying, otherwise a
+//! build storing chunks before their features' new text would look stale.
+
+use georag_core::models::{ChunkId, FeatureId, TextChunk};
+use georag_core::processing::chunk::{source_text_hash, ChunkGenerator};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// Outdated chunks found by a scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcPlan {
+    /// Chunks inspected
+    pub chunks_scanned: usize,
+
+    /// Chunks whose feature no longer exists
+    pub orphaned_chunks: Vec<ChunkId>,
+
+    /// Chunks whose feature text changed since they were generated
+    pub stale_chunks: Vec<ChunkId>,
+
+    /// Chunks already marked stale by an earlier collection
+    pub already_stale: usize,
+}
+
+impl GcPlan {
+    /// Whether there is anything to delete or mark
+    pub fn is_empty(&self) -> bool {
+        self.orphaned_chunks.is_empty() && self.stale_chunks.is_empty()
+    }
+}
+
+/// Outcome of applying a garbage collection plan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Chunks deleted together with their embeddings
+    pub chunks_deleted: usize,
+
+    /// Chunks marked stale
+    pub chunks_marked_stale: usize,
+}
+
+/// Service for finding and retiring chunks of changed or deleted features
+pub struct GcService {
+    spatial_store: Arc<dyn SpatialStore>,
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+}
+
+impl GcService {
+    /// Create a garbage collection service over the workspace stores
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        document_store: Arc<dyn DocumentStore>,
+        vector_store: Arc<dyn VectorStore>,
+    ) -> Self {
+        Self {
+            spatial_store,
+            document_store,
+            vector_store,
+        }
+    }
+
+    /// Find orphaned and stale chunks without changing anything
+    pub async fn scan(&self) -> Result<GcPlan> {
+        let chunk_ids = self.document_store.list_chunk_ids().await?;
+        let chunks = self.document_store.get_chunks(&chunk_ids).await?;
+        let generator = ChunkGenerator::default();
+
+        // Current text of each referenced feature, `None` for features that are gone
+        let mut texts: HashMap<FeatureId, Option<Option<String>>> = HashMap::new();
+        let mut plan = GcPlan {
+            chunks_scanned: chunks.len(),
+            ..Default::default()
+        };
+
+        for chunk in &chunks {
+            let Some(feature_id) = chunk.spatial_ref else {
+                continue;
+            };
+            if !texts.contains_key(&feature_id) {
+                let feature = self.spatial_store.get_feature(feature_id).await?;
+                texts.insert(feature_id, feature.map(|f| generator.feature_text(&f)));
+            }
+
+            match &texts[&feature_id] {
+                None => plan.orphaned_chunks.push(chunk.id),
+                Some(_) if chunk.metadata.stale => plan.already_stale += 1,
+                Some(text) if !matches_source(chunk, text.as_deref()) => {
+                    plan.stale_chunks.push(chunk.id)
+                }
+                Some(_) => {}
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// Delete orphaned chunks with their embeddings and mark stale chunks
+    pub async fn apply(&self, plan: &GcPlan) -> Result<GcReport> {
+        if !plan.orphaned_chunks.is_empty() {
+            self.vector_store.delete_embeddings(&plan.orphaned_chunks).await?;
+            self.document_store.delete_chunks(&plan.orphaned_chunks).await?;
+        }
+        self.document_store.mark_chunks_stale(&plan.stale_chunks).await?;
+
+        Ok(GcReport {
+            chunks_deleted: plan.orphaned_chunks.len(),
+            chunks_marked_stale: plan.stale_chunks.len(),
+        })
+    }
+
+    /// Scan and apply in one step, as done at the end of a build
+    pub async fn collect(&self) -> Result<GcReport> {
+        let plan = self.scan().await?;
+        self.apply(&plan).await
+    }
+}
+
+/// Whether a chunk was cut from the feature's current text
+fn matches_source(chunk: &TextChunk, text: Option<&str>) -> bool {
+    let Some(text) = text else {
+        return false;
+    };
+    match &chunk.metadata.source_hash {
+        Some(hash) => *hash == source_text_hash(text),
+        // Chunks join the words of their text with single spaces
+        None => text.split_whitespace().collect::<Vec<_>>().join(" ").contains(&chunk.content),
+    }
+}
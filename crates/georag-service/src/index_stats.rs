@@ -0,0 +1,172 @@
+//! Per-dataset index counters and their verification
+//!
+//! The document and vector stores keep chunk, content byte and embedding
+//! counters per dataset in the same write that changes the data, so status,
+//! usage and build summaries read them without scanning the chunks. Counters
+//! can still drift, e.g. after manual edits to the database. A quick
+//! verification compares their totals with the ID listings, a deep one
+//! counts every dataset from the stored chunks and embeddings, and a recount
+//! rebuilds them from scratch.
+
+use georag_core::models::{DatasetId, DatasetIndexStats};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// Counters that disagree with the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStatsDrift {
+    /// Dataset of the counters; `None` for the totals of a quick verification
+    pub dataset_id: Option<DatasetId>,
+
+    /// What the counters say
+    pub counted: DatasetIndexStats,
+
+    /// What the stores hold
+    pub actual: DatasetIndexStats,
+}
+
+/// Outcome of comparing the counters with the data
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexStatsVerification {
+    /// Whether every dataset was counted from its chunks and embeddings
+    pub deep: bool,
+
+    /// Counters that disagree with the data, by dataset for a deep verification
+    pub drift: Vec<IndexStatsDrift>,
+
+    /// Chunks without a dataset, which no counter covers; deep verification only
+    pub unattributed_chunks: usize,
+}
+
+impl IndexStatsVerification {
+    /// Whether the counters match the data
+    pub fn is_consistent(&self) -> bool {
+        self.drift.is_empty()
+    }
+}
+
+/// Service reading, verifying and rebuilding the per-dataset index counters
+pub struct IndexStatsService {
+    document_store: Arc<dyn DocumentStore>,
+    vector_store: Arc<dyn VectorStore>,
+}
+
+impl IndexStatsService {
+    /// Create a service over the workspace's document and vector stores
+    pub fn new(document_store: Arc<dyn DocumentStore>, vector_store: Arc<dyn VectorStore>) -> Self {
+        Self { document_store, vector_store }
+    }
+
+    /// Counters of every dataset with chunks or embeddings
+    pub async fn stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let chunks = self.document_store.dataset_index_stats().await?;
+        let embeddings = self.vector_store.dataset_index_stats().await?;
+        Ok(merge(chunks, embeddings))
+    }
+
+    /// Compare the counters with the data
+    ///
+    /// The quick check compares the totals with the chunk and embedding ID
+    /// listings. Chunks from indexes built before the counters existed have
+    /// no dataset and show up as drift there until the next build; `deep`
+    /// reads every chunk and embedding, compares each dataset and reports
+    /// such chunks separately.
+    pub async fn verify(&self, deep: bool) -> Result<IndexStatsVerification> {
+        let counted = self.stats().await?;
+
+        if !deep {
+            let mut totals = DatasetIndexStats::default();
+            for stats in counted.values() {
+                totals.add(stats);
+            }
+            let actual = DatasetIndexStats {
+                chunks: self.document_store.list_chunk_ids().await?.len() as u64,
+                content_bytes: totals.content_bytes,
+                embeddings: self.vector_store.list_embedding_ids().await?.len() as u64,
+            };
+            let drift = (totals != actual)
+                .then_some(IndexStatsDrift {
+                    dataset_id: None,
+                    counted: totals,
+                    actual,
+                })
+                .into_iter()
+                .collect();
+            return Ok(IndexStatsVerification { deep, drift, unattributed_chunks: 0 });
+        }
+
+        let (actual, unattributed_chunks) = self.scan().await?;
+        let mut dataset_ids: Vec<DatasetId> =
+            counted.keys().chain(actual.keys()).copied().collect();
+        dataset_ids.sort();
+        dataset_ids.dedup();
+
+        let drift = dataset_ids
+            .into_iter()
+            .filter_map(|dataset_id| {
+                let counted = counted.get(&dataset_id).copied().unwrap_or_default();
+                let actual = actual.get(&dataset_id).copied().unwrap_or_default();
+                (counted != actual).then_some(IndexStatsDrift {
+                    dataset_id: Some(dataset_id),
+                    counted,
+                    actual,
+                })
+            })
+            .collect();
+
+        Ok(IndexStatsVerification { deep, drift, unattributed_chunks })
+    }
+
+    /// Rebuild the counters from the stored chunks and embeddings
+    pub async fn recount(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let chunks = self.document_store.recount_index_stats().await?;
+        let embeddings = self.vector_store.recount_index_stats().await?;
+        Ok(merge(chunks, embeddings))
+    }
+
+    /// Count every dataset from the stored chunks and embeddings
+    ///
+    /// Also returns the number of chunks without a dataset.
+    async fn scan(&self) -> Result<(BTreeMap<DatasetId, DatasetIndexStats>, usize)> {
+        let mut stats: BTreeMap<DatasetId, DatasetIndexStats> = BTreeMap::new();
+        let mut unattributed = 0;
+
+        let chunk_ids = self.document_store.list_chunk_ids().await?;
+        for chunk in self.document_store.get_chunks(&chunk_ids).await? {
+            match chunk.metadata.dataset_id {
+                Some(dataset_id) => {
+                    let counters = stats.entry(dataset_id).or_default();
+                    counters.chunks += 1;
+                    counters.content_bytes += chunk.content.len() as u64;
+                }
+                None => unattributed += 1,
+            }
+        }
+
+        for chunk_id in self.vector_store.list_embedding_ids().await? {
+            let Some(embedding) = self.vector_store.get_embedding(chunk_id).await? else {
+                continue;
+            };
+            if let Some(dataset_id) = embedding.dataset_id {
+                stats.entry(dataset_id).or_default().embeddings += 1;
+            }
+        }
+
+        Ok((stats, unattributed))
+    }
+}
+
+/// Combine the chunk counters of the document store with the embedding
+/// counters of the vector store
+fn merge(
+    mut chunks: BTreeMap<DatasetId, DatasetIndexStats>,
+    embeddings: BTreeMap<DatasetId, DatasetIndexStats>,
+) -> BTreeMap<DatasetId, DatasetIndexStats> {
+    for (dataset_id, stats) in &embeddings {
+        chunks.entry(*dataset_id).or_default().add(stats);
+    }
+    chunks
+}
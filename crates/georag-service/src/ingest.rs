@@ -0,0 +1,1017 @@
+//! Dataset ingestion shared by the CLI and the API
+//!
+//! Ingestion runs detect → validate → read → normalize → store → report.
+//! The API skips validation for uploads, so an upload the reader rejects
+//! fails as a read error there.
+//! [`IngestService::ingest`] streams the read, normalize and store steps
+//! through bounded buffers (see [`pipeline`]), so memory does not grow with
+//! the file; `prepare` and `commit` hold the whole dataset between the steps,
+//! for dry runs and diffs. Reader options are checked against the detected
+//! format's option schema before the file is validated. Features over the vertex limit are rejected,
+//! simplified or subdivided while normalizing; the extra tiles of a
+//! subdivided feature are stored as features pointing back to the original.
+//! Geometries are converted by [`georag_core::geo::convert`]; features with
+//! a missing geometry are kept without one under a lenient read policy and
+//! dropped under a strict one, whichever adapter ingests them.
+//! Adapters supply the file and handle their own I/O around it: the API
+//! writes uploads to a temporary file, the CLI copies the dataset into the
+//! workspace and prints the report. With a blob store attached, the original
+//! file is kept under the dataset ID so it can be downloaded later. With a
+//! workspace quota attached, datasets that would exceed it are refused.
+//! A bounded [`DatasetPreview`] of the features is kept in the dataset
+//! metadata for catalogs. Files downloaded by [`crate::remote`] are read from
+//! their temporary copy while the dataset records the URL. The workspace's
+//! [`FeatureTransform`]s run on each feature as read, before its geometry is
+//! converted; a feature a transform fails on is rejected like an unreadable one.
+
+use chrono::Utc;
+use georag_core::error::GeoragError;
+use georag_core::formats::{
+    FeatureError, FeatureErrorKind, FeatureErrors, FeatureTransform, FeatureTransforms,
+    FormatDataset, FormatFeature, FormatMetadata, FormatOptions, FormatReader, FormatRegistry,
+    FormatValidation, IngestBuffers, ReadPolicy, SpatialAssociationInfo, TransformErrors,
+    TransformPreview,
+};
+use georag_core::geo::simplify::simplify_to_vertex_count;
+use georag_core::geo::{
+    detect_geometry_type, has_measures, subdivide, FeatureLimits, Gazetteer, GeometryPolicy,
+    OversizedAction, OversizedFeature, OversizedFeatures, ZCoordinates, PLACES_PROPERTY,
+};
+use georag_core::models::dataset::{FormatMetadata as DatasetFormat, DEFAULT_MAX_SOURCE_BYTES};
+use georag_core::models::{
+    normalize_credit, normalize_tags, AxisOrder, Dataset, DatasetId, DatasetPreview, Feature,
+    FeatureId, Geometry, GeometryType, PreviewBuilder, RemoteSource, SourceFile, UsageDelta,
+    PART_OF_PROPERTY,
+};
+use georag_store::ports::{BlobStore, SpatialStore};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::axis::workspace_extent;
+use crate::error::{Result, ServiceError};
+use crate::quota::WorkspaceQuota;
+
+pub mod pipeline;
+
+pub use pipeline::{max_features_in_flight, PipelineStats, StageThroughput};
+
+/// Number of features whose properties `prepare` keeps before and after the transforms
+pub const TRANSFORM_PREVIEW_FEATURES: usize = 3;
+
+/// What to ingest and how
+#[derive(Debug, Clone)]
+pub struct IngestRequest {
+    /// Path of the dataset file
+    pub path: PathBuf,
+
+    /// Dataset name (defaults to the file stem)
+    pub name: Option<String>,
+
+    /// Format-specific reader options (e.g. GPX track type, KML folder)
+    pub options: FormatOptions,
+
+    /// GeoJSON geometry associated with documents that have none
+    pub geometry: Option<serde_json::Value>,
+
+    /// Named places associated with documents that have no geometry, see
+    /// [`IngestRequest::with_places`]
+    pub places: Option<Gazetteer>,
+
+    /// Access tags for the dataset
+    pub tags: Vec<String>,
+
+    /// License of the dataset, overriding one declared by the file
+    pub license: Option<String>,
+
+    /// Attribution of the dataset, overriding one declared by the file
+    pub attribution: Option<String>,
+
+    /// Workspace CRS the dataset is checked against, if any
+    pub workspace_crs: Option<u32>,
+
+    /// Accept a dataset whose CRS differs from the workspace CRS
+    pub allow_crs_mismatch: bool,
+
+    /// Store the dataset's features along with its metadata
+    pub store_features: bool,
+
+    /// Validate the file with its format reader before reading it
+    pub validate: bool,
+
+    /// File name the original file is kept and downloaded under
+    /// (defaults to the file name of `path`)
+    pub source_name: Option<String>,
+
+    /// Vertex limit for one feature and the handling of larger ones
+    pub feature_limits: FeatureLimits,
+
+    /// Whether the Z ordinates of the file's positions are kept
+    pub z_coordinates: ZCoordinates,
+
+    /// Batch size and channel capacity of the ingest pipeline
+    pub buffers: IngestBuffers,
+
+    /// URL `path` was downloaded from; the dataset records it as its path
+    pub remote: Option<RemoteSource>,
+
+    /// Transforms applied in order to each feature before it is converted
+    pub transforms: Vec<FeatureTransform>,
+}
+
+impl IngestRequest {
+    /// Create a request for the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            name: None,
+            options: FormatOptions::new(),
+            geometry: None,
+            places: None,
+            tags: Vec::new(),
+            license: None,
+            attribution: None,
+            workspace_crs: None,
+            allow_crs_mismatch: false,
+            store_features: true,
+            validate: true,
+            source_name: None,
+            feature_limits: FeatureLimits::default(),
+            z_coordinates: ZCoordinates::default(),
+            buffers: IngestBuffers::default(),
+            remote: None,
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Set the dataset name
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set a format-specific reader option
+    pub fn with_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options = self.options.with_option(key, value);
+        self
+    }
+
+    /// Associate a GeoJSON geometry with documents
+    pub fn with_geometry(mut self, geometry: serde_json::Value) -> Self {
+        self.geometry = Some(geometry);
+        self
+    }
+
+    /// Associate named places with documents
+    ///
+    /// Documents without a geometry get the footprint of every place and keep
+    /// the places, so each of their chunks is located by the places its text
+    /// names. A geometry set with `with_geometry` takes precedence.
+    pub fn with_places(mut self, places: Gazetteer) -> Self {
+        self.places = Some(places);
+        self
+    }
+
+    /// Set the access tags (normalized)
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.tags = normalize_tags(tags);
+        self
+    }
+
+    /// Set the license (trimmed; blank leaves the file's own)
+    pub fn with_license(mut self, license: impl AsRef<str>) -> Self {
+        self.license = normalize_credit(Some(license.as_ref()));
+        self
+    }
+
+    /// Set the attribution (trimmed; blank leaves the file's own)
+    pub fn with_attribution(mut self, attribution: impl AsRef<str>) -> Self {
+        self.attribution = normalize_credit(Some(attribution.as_ref()));
+        self
+    }
+
+    /// Check the dataset CRS against the workspace CRS
+    pub fn with_workspace_crs(mut self, crs: u32, allow_mismatch: bool) -> Self {
+        self.workspace_crs = Some(crs);
+        self.allow_crs_mismatch = allow_mismatch;
+        self
+    }
+
+    /// Set how features the reader cannot read are handled
+    pub fn with_read_policy(mut self, policy: ReadPolicy) -> Self {
+        self.options = self.options.with_read_policy(policy);
+        self
+    }
+
+    /// Set the axis order of the file's coordinates
+    ///
+    /// With `AxisOrder::Auto` the extent of the data already in the workspace
+    /// is used to decide coordinates that fit either order.
+    pub fn with_axis_order(mut self, axis_order: AxisOrder) -> Self {
+        self.options = self.options.with_axis_order(axis_order);
+        self
+    }
+
+    /// Set whether features are stored along with the dataset metadata
+    pub fn with_store_features(mut self, store: bool) -> Self {
+        self.store_features = store;
+        self
+    }
+
+    /// Set whether the file is validated before it is read
+    ///
+    /// Without validation, a file the reader rejects fails with
+    /// [`ServiceError::Read`] instead of [`ServiceError::InvalidDataset`] and
+    /// no validation warnings are reported. The API ingests uploads this way.
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Set the file name the original file is kept under, e.g. the name of an upload
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Set the vertex limit for one feature and the handling of larger ones
+    pub fn with_feature_limits(mut self, limits: FeatureLimits) -> Self {
+        self.feature_limits = limits;
+        self
+    }
+
+    /// Set whether Z ordinates are kept
+    pub fn with_z_coordinates(mut self, z_coordinates: ZCoordinates) -> Self {
+        self.z_coordinates = z_coordinates;
+        self
+    }
+
+    /// Set the batch size and channel capacity of the ingest pipeline
+    pub fn with_buffers(mut self, buffers: IngestBuffers) -> Self {
+        self.buffers = buffers;
+        self
+    }
+
+    /// Record the URL the file at `path` was downloaded from, see [`crate::remote`]
+    pub fn with_remote(mut self, remote: RemoteSource) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    /// Set the transforms applied to each feature, in order
+    pub fn with_transforms(
+        mut self,
+        transforms: impl IntoIterator<Item = FeatureTransform>,
+    ) -> Self {
+        self.transforms = transforms.into_iter().collect();
+        self
+    }
+
+    fn source_name(&self) -> String {
+        self.source_name.clone().unwrap_or_else(|| {
+            self.path.file_name().and_then(|s| s.to_str()).unwrap_or("source").to_string()
+        })
+    }
+}
+
+/// Which original files are kept in the blob store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePolicy {
+    /// Largest file kept, in bytes; larger files are ingested without their source
+    /// and 0 keeps none
+    pub max_bytes: u64,
+
+    /// Record the SHA-256 of kept files in the dataset metadata
+    pub hash: bool,
+}
+
+impl Default for SourcePolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_SOURCE_BYTES,
+            hash: false,
+        }
+    }
+}
+
+/// A dataset that has been read and normalized but not stored yet
+///
+/// Dry runs stop here and describe the prepared dataset.
+#[derive(Debug, Clone)]
+pub struct PreparedIngest {
+    /// Dataset metadata to store
+    pub dataset: Dataset,
+
+    /// Features numbered in file order, followed by the extra tiles of
+    /// subdivided features; see [`GeometryPolicy`] for features without a
+    /// geometry
+    pub features: Vec<Feature>,
+
+    /// Metadata reported by the format reader
+    pub format_metadata: FormatMetadata,
+
+    /// Validation warnings that did not prevent reading
+    pub warnings: Vec<String>,
+
+    /// Features the reader skipped under a lenient read policy
+    pub feature_errors: Vec<FeatureError>,
+
+    /// Features over the vertex limit that were simplified or subdivided
+    pub oversized_features: Vec<OversizedFeature>,
+
+    /// Features each of the request's transforms failed on
+    pub transform_errors: Vec<TransformErrors>,
+
+    /// Properties of the first features before and after the transforms
+    /// (empty without transforms)
+    pub transform_preview: Vec<TransformPreview>,
+
+    /// Workspace CRS the dataset was checked against
+    pub workspace_crs: Option<u32>,
+
+    store_features: bool,
+    source: Option<Vec<u8>>,
+}
+
+impl PreparedIngest {
+    /// Check if the dataset CRS differs from the workspace CRS
+    pub fn has_crs_mismatch(&self) -> bool {
+        self.workspace_crs.is_some_and(|crs| crs != self.dataset.crs)
+    }
+
+    /// Workspace usage the dataset adds once stored
+    pub fn usage_delta(&self) -> UsageDelta {
+        UsageDelta::for_dataset(&self.dataset)
+    }
+}
+
+/// Outcome of a completed ingest
+#[derive(Debug, Clone)]
+pub struct IngestReport {
+    /// ID assigned by the spatial store
+    pub dataset_id: DatasetId,
+
+    /// Stored dataset metadata
+    pub dataset: Dataset,
+
+    /// Number of features stored (0 when features are not stored)
+    pub features_stored: usize,
+
+    /// Validation warnings that did not prevent reading
+    pub warnings: Vec<String>,
+
+    /// Features the reader skipped under a lenient read policy
+    pub feature_errors: Vec<FeatureError>,
+
+    /// Features over the vertex limit that were simplified or subdivided
+    pub oversized_features: Vec<OversizedFeature>,
+
+    /// Features each of the request's transforms failed on
+    pub transform_errors: Vec<TransformErrors>,
+
+    /// Workspace usage recorded for the dataset; release it if the dataset is removed
+    pub usage: UsageDelta,
+
+    /// Metadata reported by the format reader
+    pub format_metadata: FormatMetadata,
+
+    /// Throughput of the pipeline stages (`None` for a committed prepared dataset)
+    pub pipeline: Option<PipelineStats>,
+}
+
+impl IngestReport {
+    /// Number of features skipped because they could not be read
+    pub fn features_skipped(&self) -> usize {
+        self.feature_errors.len()
+    }
+}
+
+/// Callback told how many features an ingest has stored so far
+pub type StoreProgress = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// Service for ingesting dataset files
+pub struct IngestService {
+    spatial_store: Arc<dyn SpatialStore>,
+    formats: Arc<FormatRegistry>,
+    blob_store: Option<Arc<dyn BlobStore>>,
+    source_policy: SourcePolicy,
+    quota: Option<WorkspaceQuota>,
+    store_progress: Option<StoreProgress>,
+}
+
+impl IngestService {
+    /// Create an ingest service storing into `spatial_store`
+    pub fn new(spatial_store: Arc<dyn SpatialStore>, formats: Arc<FormatRegistry>) -> Self {
+        Self {
+            spatial_store,
+            formats,
+            blob_store: None,
+            source_policy: SourcePolicy::default(),
+            quota: None,
+            store_progress: None,
+        }
+    }
+
+    /// Keep the original files of ingested datasets in `blob_store`
+    pub fn with_blob_store(mut self, blob_store: Arc<dyn BlobStore>, policy: SourcePolicy) -> Self {
+        self.blob_store = Some(blob_store);
+        self.source_policy = policy;
+        self
+    }
+
+    /// Refuse datasets that would take the workspace over its quotas
+    ///
+    /// Stored datasets are added to the workspace's usage counters.
+    pub fn with_quota(mut self, quota: WorkspaceQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Report the features stored so far after each batch `ingest` stores
+    pub fn with_store_progress(mut self, progress: StoreProgress) -> Self {
+        self.store_progress = Some(progress);
+        self
+    }
+
+    /// Detect, validate, read and normalize a dataset without storing it
+    pub async fn prepare(&self, request: &IngestRequest) -> Result<PreparedIngest> {
+        let (reader, options, validation) = self.open(request).await?;
+
+        let mut format_dataset = if let Some(geometry) = &request.geometry {
+            reader.read_with_geometry(&request.path, geometry.clone()).await
+        } else {
+            reader.read_with_options(&request.path, &options).await
+        }
+        .map_err(ServiceError::Read)?;
+        if let Some(places) = &request.places {
+            associate_places(&mut format_dataset, places);
+        }
+
+        let crs = format_dataset.crs;
+        check_crs(request, crs)?;
+
+        let metadata = format_dataset.format_metadata;
+        log_read(&metadata, format_dataset.errors.len());
+
+        let geometry_type = detect_geometry_type(
+            format_dataset.features.iter().filter_map(|f| f.geometry.as_ref()),
+        )
+        .unwrap_or_default();
+        let read_count = format_dataset.features.len();
+
+        let mut feature_errors = FeatureErrors::resume(
+            metadata.format_name.clone(),
+            request.options.read_policy,
+            format_dataset.errors,
+        );
+        let skipped = feature_errors.len();
+        let mut features = Vec::with_capacity(read_count);
+        let mut measured = 0;
+        let mut transforms = FeatureTransforms::new(request.transforms.clone());
+        let mut transform_preview = Vec::new();
+        for (i, mut f) in format_dataset.features.into_iter().enumerate() {
+            measured += f.geometry.as_ref().is_some_and(has_measures) as usize;
+            let before = (!transforms.is_empty()
+                && transform_preview.len() < TRANSFORM_PREVIEW_FEATURES)
+                .then(|| sorted_properties(&f));
+            let transformed = transform_feature(&mut transforms, i, &mut f);
+            if let Some(before) = before {
+                transform_preview.push(TransformPreview {
+                    index: i,
+                    id: f.id.clone(),
+                    before,
+                    after: transformed.is_ok().then(|| sorted_properties(&f)),
+                    error: transformed.as_ref().err().map(|e| e.message.clone()),
+                });
+            }
+            if let Err(error) = transformed {
+                feature_errors.record(error).map_err(ServiceError::Read)?;
+                continue;
+            }
+            match convert_feature(request, i, f, crs) {
+                Ok(Some(feature)) => features.push(feature),
+                Ok(None) => {}
+                Err(error) => feature_errors.record(error).map_err(ServiceError::Read)?,
+            }
+        }
+        let limited = self
+            .limit_features(features, request.feature_limits, &mut feature_errors)
+            .await?;
+        let rejected = feature_errors.len() - skipped;
+        let mut features = limited.features;
+        features.extend(number_tiles(limited.tiles, read_count as u64));
+        let mut preview = preview_builder(&metadata);
+        features.iter().for_each(|feature| preview.add(feature));
+
+        let mut warnings = validation.warnings;
+        if measured > 0 {
+            warnings.push(measures_dropped(measured));
+        }
+        let (source, source_file) = match self.read_source(request, &mut warnings)? {
+            Some((content, file)) => (Some(content), Some(file)),
+            None => (None, None),
+        };
+        let dataset = describe_dataset(
+            request,
+            &metadata,
+            geometry_type,
+            read_count - rejected,
+            crs,
+            source_file,
+            preview.finish(),
+        );
+
+        let prepared = PreparedIngest {
+            dataset,
+            features,
+            format_metadata: metadata,
+            warnings,
+            feature_errors: feature_errors.into_vec(),
+            oversized_features: limited.oversized,
+            transform_errors: transforms.errors(),
+            transform_preview,
+            workspace_crs: request.workspace_crs,
+            store_features: request.store_features,
+            source,
+        };
+        if let Some(quota) = &self.quota {
+            quota.check(&prepared.usage_delta()).await?;
+        }
+
+        Ok(prepared)
+    }
+
+    /// Detect the format of a file, check the request against it and validate the file
+    ///
+    /// Returns the reader, the options to read with and the validation
+    /// warnings. A workspace at its dataset limit is refused before the file
+    /// is validated; requests without validation skip it.
+    async fn open(
+        &self,
+        request: &IngestRequest,
+    ) -> Result<(&dyn FormatReader, FormatOptions, FormatValidation)> {
+        let reader = self
+            .formats
+            .detect_format(&request.path)
+            .map_err(ServiceError::UnsupportedFormat)?;
+        request.options.validate(reader).map_err(ServiceError::InvalidOptions)?;
+
+        if let Some(quota) = &self.quota {
+            quota.check(&UsageDelta { datasets: 1, ..Default::default() }).await?;
+        }
+
+        let validation = if request.validate {
+            reader.validate(&request.path).await.map_err(ServiceError::Read)?
+        } else {
+            FormatValidation::default()
+        };
+        if !validation.is_valid() {
+            return Err(ServiceError::InvalidDataset(validation.errors));
+        }
+
+        let mut options = request.options.clone();
+        if options.axis_order == AxisOrder::Auto && options.extent.is_none() {
+            options.extent = workspace_extent(self.spatial_store.as_ref(), None).await?;
+        }
+
+        Ok((reader, options, validation))
+    }
+
+    /// Apply the vertex limit to the features of a file
+    ///
+    /// Rejected features are recorded like unreadable ones, so a strict read
+    /// fails on the first. The first tile of a subdivided feature keeps its ID;
+    /// the others are returned as [`ExtraTile`]s, to be numbered after the
+    /// last feature of the file.
+    async fn limit_features(
+        &self,
+        features: Vec<Feature>,
+        limits: FeatureLimits,
+        errors: &mut FeatureErrors,
+    ) -> Result<LimitedFeatures> {
+        let mut limited = Vec::with_capacity(features.len());
+        let mut tiles = Vec::new();
+        let mut oversized = Vec::new();
+
+        for mut feature in features {
+            let geometry = match feature.geometry.take() {
+                Some(geometry) if limits.exceeds(&geometry) => geometry,
+                geometry => {
+                    feature.geometry = geometry;
+                    limited.push(feature);
+                    continue;
+                }
+            };
+            let index = feature.id.0 as usize;
+            let vertices = geometry.vertex_count();
+            let rejected = || too_complex(index, &geometry, &limits);
+
+            match limits.oversized {
+                OversizedFeatures::Reject => {
+                    errors.record(rejected()).map_err(ServiceError::Read)?;
+                }
+                OversizedFeatures::Simplify => {
+                    let Some(simplified) = simplify_to_limit(&geometry, &limits) else {
+                        errors.record(rejected()).map_err(ServiceError::Read)?;
+                        continue;
+                    };
+                    oversized.push(OversizedFeature {
+                        index,
+                        vertices,
+                        action: OversizedAction::Simplified {
+                            simplified_vertices: simplified.vertex_count(),
+                        },
+                    });
+                    // Z belonged to the vertices simplification removed
+                    feature.geometry = Some(simplified);
+                    feature.z = None;
+                    limited.push(feature);
+                }
+                OversizedFeatures::Subdivide => {
+                    let parts = match self
+                        .spatial_store
+                        .subdivide_geometry(&geometry, limits.max_vertices)
+                        .await?
+                    {
+                        Some(parts) => parts,
+                        None => subdivide(&geometry, limits.max_vertices),
+                    };
+                    if parts.is_empty() || parts.iter().any(|part| limits.exceeds(part)) {
+                        errors.record(rejected()).map_err(ServiceError::Read)?;
+                        continue;
+                    }
+                    tracing::debug!(
+                        feature = index,
+                        vertices,
+                        parts = parts.len(),
+                        "Subdivided oversized feature"
+                    );
+                    oversized.push(OversizedFeature {
+                        index,
+                        vertices,
+                        action: OversizedAction::Subdivided { parts: parts.len() },
+                    });
+
+                    let mut parts = parts.into_iter();
+                    feature.geometry = parts.next();
+                    feature.z = None;
+                    let mut properties = feature.properties.clone();
+                    properties
+                        .insert(PART_OF_PROPERTY.to_string(), serde_json::json!(feature.id.0));
+                    let crs = feature.crs;
+                    limited.push(feature);
+                    tiles.extend(parts.map(|geometry| ExtraTile {
+                        geometry,
+                        properties: properties.clone(),
+                        crs,
+                    }));
+                }
+            }
+        }
+
+        Ok(LimitedFeatures { features: limited, tiles, oversized })
+    }
+
+    /// Read the original file for the blob store, if one is attached and the file fits
+    fn read_source(
+        &self,
+        request: &IngestRequest,
+        warnings: &mut Vec<String>,
+    ) -> Result<Option<(Vec<u8>, SourceFile)>> {
+        let policy = self.source_policy;
+        if self.blob_store.is_none() || policy.max_bytes == 0 {
+            return Ok(None);
+        }
+
+        let size = std::fs::metadata(&request.path).map_err(GeoragError::from)?.len();
+        if size > policy.max_bytes {
+            warnings.push(format!(
+                "Original file is {} bytes, over the {} byte source limit; it is not kept",
+                size, policy.max_bytes
+            ));
+            return Ok(None);
+        }
+
+        let content = std::fs::read(&request.path).map_err(GeoragError::from)?;
+        let mut file = SourceFile::new(request.source_name(), content.len() as u64);
+        if policy.hash {
+            file = file.with_sha256(format!("{:x}", Sha256::digest(&content)));
+        }
+        Ok(Some((content, file)))
+    }
+
+    /// Store a prepared dataset
+    ///
+    /// The quota is checked again, since other writes may have happened since
+    /// the dataset was prepared. If storing the features or the original file
+    /// fails, the dataset metadata is removed again.
+    pub async fn commit(&self, prepared: PreparedIngest) -> Result<IngestReport> {
+        let usage = prepared.usage_delta();
+        let features: &[Feature] = if prepared.store_features {
+            &prepared.features
+        } else {
+            &[]
+        };
+        let dataset_id = self
+            .store_dataset(&prepared.dataset, features, prepared.source.as_deref(), &usage)
+            .await?;
+        let features_stored = features.len();
+
+        tracing::info!(dataset_id = dataset_id.0, features_stored, "Successfully ingested dataset");
+
+        let mut dataset = prepared.dataset;
+        dataset.id = dataset_id;
+
+        Ok(IngestReport {
+            dataset_id,
+            dataset,
+            features_stored,
+            warnings: prepared.warnings,
+            feature_errors: prepared.feature_errors,
+            oversized_features: prepared.oversized_features,
+            transform_errors: prepared.transform_errors,
+            usage,
+            format_metadata: prepared.format_metadata,
+            pipeline: None,
+        })
+    }
+
+    /// Store dataset metadata, then `features` and the original file, and record the usage
+    ///
+    /// The quota is checked first. If a later step fails, the steps before it
+    /// are undone.
+    async fn store_dataset(
+        &self,
+        dataset: &Dataset,
+        features: &[Feature],
+        source: Option<&[u8]>,
+        usage: &UsageDelta,
+    ) -> Result<DatasetId> {
+        if let Some(quota) = &self.quota {
+            quota.check(usage).await?;
+        }
+
+        let dataset_id = self.spatial_store.store_dataset(dataset).await?;
+
+        if !features.is_empty() {
+            if let Err(e) = self.spatial_store.store_features(features).await {
+                if let Err(rollback) = self.spatial_store.delete_dataset(dataset_id).await {
+                    tracing::warn!(error = %rollback, "Failed to roll back dataset");
+                }
+                return Err(e.into());
+            }
+        }
+
+        if let (Some(blob_store), Some(content)) = (&self.blob_store, source) {
+            if let Err(e) = blob_store.put_blob(dataset_id, content).await {
+                if let Err(rollback) = self.spatial_store.delete_dataset(dataset_id).await {
+                    tracing::warn!(error = %rollback, "Failed to roll back dataset");
+                }
+                return Err(e.into());
+            }
+        }
+
+        if let Some(quota) = &self.quota {
+            if let Err(e) = quota.record(usage).await {
+                if let Some(blob_store) = &self.blob_store {
+                    if let Err(rollback) = blob_store.delete_blob(dataset_id).await {
+                        tracing::warn!(error = %rollback, "Failed to roll back dataset source");
+                    }
+                }
+                if let Err(rollback) = self.spatial_store.delete_dataset(dataset_id).await {
+                    tracing::warn!(error = %rollback, "Failed to roll back dataset");
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(dataset_id)
+    }
+
+    /// Read, normalize and store a dataset in one pass
+    ///
+    /// Features stream through the pipeline with the request's
+    /// [`buffers`](IngestRequest::buffers); the report includes the
+    /// throughput of each stage.
+    pub async fn ingest(&self, request: &IngestRequest) -> Result<IngestReport> {
+        self.run_pipeline(request).await
+    }
+}
+
+/// Features within the vertex limit, and the extra tiles of subdivided ones
+struct LimitedFeatures {
+    features: Vec<Feature>,
+    tiles: Vec<ExtraTile>,
+    oversized: Vec<OversizedFeature>,
+}
+
+/// Extra tile of a subdivided feature, numbered once the file's feature count is known
+struct ExtraTile {
+    geometry: Geometry,
+    properties: HashMap<String, serde_json::Value>,
+    crs: u32,
+}
+
+/// Number extra tiles from `first_id` on
+fn number_tiles(tiles: Vec<ExtraTile>, first_id: u64) -> impl Iterator<Item = Feature> {
+    tiles.into_iter().zip(first_id..).map(|(tile, id)| {
+        Feature::with_geometry(FeatureId(id), tile.geometry, tile.properties, tile.crs)
+    })
+}
+
+/// Refuse a dataset CRS that differs from the workspace CRS, unless allowed
+fn check_crs(request: &IngestRequest, crs: u32) -> Result<()> {
+    match request.workspace_crs {
+        Some(workspace_crs) if crs != workspace_crs && !request.allow_crs_mismatch => {
+            Err(ServiceError::CrsMismatch { dataset_crs: crs, workspace_crs })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Log how a file was read
+fn log_read(metadata: &FormatMetadata, skipped: usize) {
+    if let Some(decision) = &metadata.axis_order {
+        tracing::debug!(
+            requested = %decision.requested,
+            applied = %decision.applied,
+            reason = %decision.reason,
+            "Resolved axis order"
+        );
+    }
+    if skipped > 0 {
+        tracing::warn!(skipped, "Skipped features that could not be read");
+    }
+}
+
+/// Dataset metadata for a file read under `request`
+fn describe_dataset(
+    request: &IngestRequest,
+    metadata: &FormatMetadata,
+    geometry_type: GeometryType,
+    feature_count: usize,
+    crs: u32,
+    source: Option<SourceFile>,
+    preview: DatasetPreview,
+) -> Dataset {
+    let name = request.name.clone().unwrap_or_else(|| {
+        request
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unnamed")
+            .to_string()
+    });
+
+    Dataset {
+        id: DatasetId(0),
+        name,
+        path: request
+            .remote
+            .as_ref()
+            .map_or_else(|| request.path.clone(), |r| PathBuf::from(&r.url)),
+        geometry_type,
+        feature_count,
+        crs,
+        format: DatasetFormat {
+            format_name: metadata.format_name.clone(),
+            format_version: metadata.format_version.clone(),
+            layer_name: metadata.layer_name.clone(),
+            page_count: metadata.page_count,
+            paragraph_count: metadata.paragraph_count,
+            extraction_method: metadata.extraction_method.clone(),
+            spatial_association: None,
+            axis_order: metadata.axis_order.clone(),
+            source,
+            preview: Some(preview),
+            remote: request.remote.clone(),
+        },
+        added_at: Utc::now(),
+        tags: request.tags.clone(),
+        license: request.license.clone().or_else(|| metadata.license.clone()),
+        attribution: request.attribution.clone().or_else(|| metadata.attribution.clone()),
+    }
+}
+
+/// Preview builder for a file's format: documents get a text snippet, vector data a summary
+fn preview_builder(metadata: &FormatMetadata) -> PreviewBuilder {
+    PreviewBuilder::new(metadata.page_count, metadata.paragraph_count)
+}
+
+/// Give documents without a geometry the footprint of the places and keep the
+/// places on them for chunking
+fn associate_places(dataset: &mut FormatDataset, places: &Gazetteer) {
+    let Some(footprint) = places.footprint().map(|f| f.to_geojson()) else {
+        return;
+    };
+
+    let mut associated = false;
+    for feature in &mut dataset.features {
+        associated |= locate_by_places(feature, &footprint, places);
+    }
+
+    if associated {
+        dataset.format_metadata.spatial_association = Some(places_association(places));
+    }
+}
+
+/// Give a document without a geometry the footprint of the places, returning
+/// whether it had none
+fn locate_by_places(
+    feature: &mut FormatFeature,
+    footprint: &serde_json::Value,
+    places: &Gazetteer,
+) -> bool {
+    if feature.geometry.is_some() {
+        return false;
+    }
+    feature.geometry = Some(footprint.clone());
+    feature.properties.insert(PLACES_PROPERTY.to_string(), places.to_property());
+    true
+}
+
+/// Warning for the features of a file whose M ordinates were dropped
+fn measures_dropped(features: usize) -> String {
+    format!(
+        "{} features have M (measure) ordinates; they are dropped, only X, Y and Z are kept",
+        features
+    )
+}
+
+fn places_association(places: &Gazetteer) -> SpatialAssociationInfo {
+    SpatialAssociationInfo {
+        source: "places".to_string(),
+        geometry_file: None,
+        description: Some(format!(
+            "{} named places; chunks are located by the places they mention",
+            places.len()
+        )),
+    }
+}
+
+/// Turn a feature read from the file into a feature to store
+///
+/// Missing geometries are handled per the read policy's [`GeometryPolicy`]:
+/// lenient reads keep the feature without a geometry, strict reads drop it
+/// (`Ok(None)`). A geometry that cannot be converted is returned as a feature
+/// error, to be recorded like a feature the reader could not read. Z
+/// ordinates are kept as the request's [`ZCoordinates`] says.
+/// Simplify a geometry over the vertex limit, `None` when it cannot be brought under it
+pub(crate) fn simplify_to_limit(geometry: &Geometry, limits: &FeatureLimits) -> Option<Geometry> {
+    let (simplified, _) = simplify_to_vertex_count(geometry, limits.max_vertices);
+    (!limits.exceeds(&simplified)).then_some(simplified)
+}
+
+/// Error for a feature whose geometry is over the vertex limit
+pub(crate) fn too_complex(
+    index: usize,
+    geometry: &Geometry,
+    limits: &FeatureLimits,
+) -> FeatureError {
+    FeatureError::new(
+        index,
+        FeatureErrorKind::TooComplex,
+        format!(
+            "{} vertices, over the limit of {}",
+            geometry.vertex_count(),
+            limits.max_vertices
+        ),
+    )
+}
+
+/// Apply the request's transforms to a feature as read
+fn transform_feature(
+    transforms: &mut FeatureTransforms,
+    index: usize,
+    feature: &mut FormatFeature,
+) -> std::result::Result<(), FeatureError> {
+    transforms.apply(feature).map_err(|failure| {
+        FeatureError::new(index, FeatureErrorKind::TransformFailed, failure.to_string())
+            .with_id(feature.id.clone())
+    })
+}
+
+/// A feature's properties in key order, for transform previews
+fn sorted_properties(feature: &FormatFeature) -> BTreeMap<String, serde_json::Value> {
+    feature.properties.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+}
+
+fn convert_feature(
+    request: &IngestRequest,
+    index: usize,
+    feature: FormatFeature,
+    crs: u32,
+) -> std::result::Result<Option<Feature>, FeatureError> {
+    let policy = GeometryPolicy::for_validity(request.options.read_policy.validity);
+    let id = FeatureId(index as u64);
+    match policy.apply(feature.geometry.as_ref()) {
+        Ok(Some(Some(geometry))) => {
+            let z = feature.geometry.as_ref().and_then(|g| request.z_coordinates.apply(g));
+            Ok(Some(Feature::with_geometry(id, geometry, feature.properties, crs).with_z(z)))
+        }
+        Ok(Some(None)) => Ok(Some(Feature::without_geometry(id, feature.properties, crs))),
+        Ok(None) => Ok(None),
+        Err((kind, message)) => Err(FeatureError::new(index, kind, message).with_id(feature.id)),
+    }
+}
@@ -0,0 +1,440 @@
+//! Streaming ingest through bounded parse, normalize and store stages
+//!
+//! [`IngestService::ingest`] runs three stages at once, joined by channels:
+//!
+//! - **parse**: the format reader streams features into a
+//!   [`FeatureSink`](georag_core::formats::FeatureSink).
+//! - **normalize**: features are numbered in file order, located by named
+//!   places, transformed, converted and held to the vertex limit; the
+//!   workspace quota is checked as the feature count grows.
+//! - **store**: each batch is written to the spatial store.
+//!
+//! Each channel holds at most `channel_capacity` batches of `batch_size`
+//! features, so a slow store holds the reader back and no more than
+//! [`max_features_in_flight`] features are in memory, however large the file.
+//! Only the extra tiles of subdivided features wait until the end, where they
+//! are numbered after the file's last feature. The dataset metadata is stored
+//! once the last batch is, since the feature count and skipped features are
+//! known only then; if the ingest fails after some batches were stored, those
+//! features are left behind like those of a failed `commit`. The dataset
+//! preview is built in the normalize stage as batches pass through.
+
+use georag_core::error::GeoragError;
+use georag_core::formats::{
+    feature_channel, FeatureError, FeatureErrors, FeatureTransforms, FormatDataset, FormatMetadata,
+    IngestBuffers, TransformErrors,
+};
+use georag_core::geo::{detect_geometry_type, has_measures, OversizedFeature};
+use georag_core::models::{DatasetPreview, Feature, GeometryType, UsageDelta};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use super::{
+    check_crs, convert_feature, describe_dataset, locate_by_places, log_read, measures_dropped,
+    number_tiles, places_association, preview_builder, transform_feature, IngestReport,
+    IngestRequest, IngestService,
+};
+use crate::error::{Result, ServiceError};
+
+/// Most features the pipeline holds at once with the given buffers
+///
+/// Each of the two channels holds `channel_capacity` batches; the reader's
+/// open batch and the batch each later stage works on add one more each.
+/// Extra tiles of subdivided features come on top.
+pub fn max_features_in_flight(buffers: IngestBuffers) -> usize {
+    (2 * buffers.channel_capacity + 3) * buffers.batch_size
+}
+
+/// Throughput of one stage of the ingest pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageThroughput {
+    /// Stage name: `parse`, `normalize` or `store`
+    pub stage: &'static str,
+
+    /// Features the stage passed on
+    pub features: usize,
+
+    /// Time spent working
+    pub busy: Duration,
+
+    /// Time spent waiting for the stage before or for room in the next channel
+    pub waiting: Duration,
+}
+
+impl StageThroughput {
+    /// Features per second of work, leaving out the time spent waiting
+    pub fn features_per_second(&self) -> f64 {
+        let seconds = self.busy.as_secs_f64();
+        if seconds > 0.0 {
+            self.features as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How an ingest moved through the pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineStats {
+    /// Buffers the pipeline ran with
+    pub buffers: IngestBuffers,
+
+    /// Throughput of the parse, normalize and store stages, in that order
+    pub stages: Vec<StageThroughput>,
+
+    /// Batches written by the store stage
+    pub batches: usize,
+
+    /// Most features held by the pipeline at once
+    pub peak_features_in_flight: usize,
+
+    /// Time from the first read to the last stored batch
+    pub elapsed: Duration,
+}
+
+impl PipelineStats {
+    /// The slowest stage by work time, which set the pace of the others
+    pub fn bottleneck(&self) -> Option<&StageThroughput> {
+        self.stages.iter().max_by_key(|stage| stage.busy)
+    }
+}
+
+/// What the normalize stage learned about the file
+#[derive(Default)]
+struct Normalized {
+    /// Features read, with or without a geometry
+    read: usize,
+    geometry_type: Option<GeometryType>,
+    located_by_places: bool,
+    /// Features whose geometry had M ordinates
+    measured: usize,
+    errors: Vec<FeatureError>,
+    oversized: Vec<OversizedFeature>,
+    transform_errors: Vec<TransformErrors>,
+    preview: Option<DatasetPreview>,
+    throughput: Option<StageThroughput>,
+}
+
+/// What the store stage wrote
+struct Stored {
+    features: usize,
+    batches: usize,
+    throughput: StageThroughput,
+}
+
+impl IngestService {
+    /// Run an ingest through the parse, normalize and store stages
+    pub(super) async fn run_pipeline(&self, request: &IngestRequest) -> Result<IngestReport> {
+        let (reader, options, validation) = self.open(request).await?;
+        let buffers =
+            IngestBuffers::new(request.buffers.batch_size, request.buffers.channel_capacity);
+        let started = Instant::now();
+
+        let (sink, mut stream) = feature_channel(buffers);
+        let gauge = stream.gauge();
+        let (batch_tx, mut batch_rx) = mpsc::channel::<Vec<Feature>>(buffers.channel_capacity);
+
+        let parse = async move {
+            let read = match &request.geometry {
+                Some(geometry) => {
+                    match reader.read_with_geometry(&request.path, geometry.clone()).await {
+                        Ok(dataset) => sink.forward(dataset).await,
+                        Err(e) => Err(e),
+                    }
+                }
+                None => reader.read_streaming(&request.path, &options, sink).await,
+            };
+            read.map_err(ServiceError::Read)
+        };
+
+        let normalize_gauge = gauge.clone();
+        let normalize = async move {
+            let gauge = normalize_gauge;
+            let mut normalized = Normalized::default();
+            let Some(header) = stream.header().await else {
+                // The reader failed before its first feature and reports why
+                return Ok(normalized);
+            };
+            check_crs(request, header.crs)?;
+
+            let footprint = request
+                .places
+                .as_ref()
+                .and_then(|places| Some((places, places.footprint()?.to_geojson())));
+            let mut errors = FeatureErrors::new(
+                header.format_metadata.format_name.clone(),
+                request.options.read_policy,
+            );
+            let mut transforms = FeatureTransforms::new(request.transforms.clone());
+            let mut preview = preview_builder(&header.format_metadata);
+            let mut tiles = Vec::new();
+            let mut busy = Duration::ZERO;
+            let mut sent = 0;
+
+            while let Some(mut batch) = stream.next_batch().await {
+                let working = Instant::now();
+                let count = batch.len();
+                if let Some((places, footprint)) = &footprint {
+                    for feature in &mut batch {
+                        normalized.located_by_places |=
+                            locate_by_places(feature, footprint, places);
+                    }
+                }
+                if normalized.geometry_type.is_none() {
+                    normalized.geometry_type =
+                        detect_geometry_type(batch.iter().filter_map(|f| f.geometry.as_ref()));
+                }
+
+                let mut features = Vec::with_capacity(count);
+                for (offset, mut feature) in batch.into_iter().enumerate() {
+                    let index = normalized.read + offset;
+                    normalized.measured +=
+                        feature.geometry.as_ref().is_some_and(has_measures) as usize;
+                    if let Err(error) = transform_feature(&mut transforms, index, &mut feature) {
+                        errors.record(error).map_err(ServiceError::Read)?;
+                        continue;
+                    }
+                    match convert_feature(request, index, feature, header.crs) {
+                        Ok(Some(feature)) => features.push(feature),
+                        Ok(None) => {}
+                        Err(error) => errors.record(error).map_err(ServiceError::Read)?,
+                    }
+                }
+                normalized.read += count;
+
+                let limited =
+                    self.limit_features(features, request.feature_limits, &mut errors).await?;
+                gauge.release(count - limited.features.len());
+                gauge.hold(limited.tiles.len());
+                tiles.extend(limited.tiles);
+                normalized.oversized.extend(limited.oversized);
+
+                if let Some(quota) = &self.quota {
+                    let features = normalized.read - errors.len();
+                    quota
+                        .check(&UsageDelta {
+                            datasets: 1,
+                            features: features as i64,
+                            ..Default::default()
+                        })
+                        .await?;
+                }
+                busy += working.elapsed();
+
+                limited.features.iter().for_each(|feature| preview.add(feature));
+                if !limited.features.is_empty() {
+                    sent += limited.features.len();
+                    batch_tx.send(limited.features).await.map_err(|_| stopped())?;
+                }
+            }
+
+            // Extra tiles are numbered after the last feature of the file
+            let mut tiles = number_tiles(tiles, normalized.read as u64).peekable();
+            while tiles.peek().is_some() {
+                let batch: Vec<Feature> = tiles.by_ref().take(buffers.batch_size).collect();
+                batch.iter().for_each(|feature| preview.add(feature));
+                sent += batch.len();
+                batch_tx.send(batch).await.map_err(|_| stopped())?;
+            }
+
+            normalized.errors = errors.into_vec();
+            normalized.transform_errors = transforms.errors();
+            normalized.preview = Some(preview.finish());
+            normalized.throughput = Some(StageThroughput {
+                stage: "normalize",
+                features: sent,
+                busy,
+                waiting: started.elapsed().saturating_sub(busy),
+            });
+            Ok::<_, ServiceError>(normalized)
+        };
+
+        let store_gauge = gauge.clone();
+        let features_stored = AtomicUsize::new(0);
+        let stored_so_far = &features_stored;
+        let store = async move {
+            let mut stored = Stored {
+                features: 0,
+                batches: 0,
+                throughput: StageThroughput {
+                    stage: "store",
+                    features: 0,
+                    busy: Duration::ZERO,
+                    waiting: Duration::ZERO,
+                },
+            };
+            while let Some(batch) = batch_rx.recv().await {
+                let working = Instant::now();
+                if request.store_features {
+                    self.spatial_store.store_features(&batch).await?;
+                    stored.features += batch.len();
+                    stored_so_far.store(stored.features, Ordering::Relaxed);
+                    if let Some(progress) = &self.store_progress {
+                        progress(stored.features);
+                    }
+                }
+                store_gauge.release(batch.len());
+                stored.batches += 1;
+                stored.throughput.features += batch.len();
+                stored.throughput.busy += working.elapsed();
+            }
+            stored.throughput.waiting = started.elapsed().saturating_sub(stored.throughput.busy);
+            Ok::<_, ServiceError>(stored)
+        };
+
+        let (read, normalized, stored) = tokio::join!(parse, normalize, store);
+        let elapsed = started.elapsed();
+        let (format_dataset, mut normalized, stored) = match (read, normalized, stored) {
+            (Ok(read), Ok(normalized), Ok(stored)) => (read, normalized, stored),
+            (read, normalized, stored) => {
+                let error = first_cause([read.err(), normalized.err(), stored.err()]);
+                let features_stored = features_stored.load(Ordering::Relaxed);
+                if features_stored > 0 {
+                    tracing::warn!(
+                        features_stored,
+                        error = %error,
+                        "Ingest failed; features stored before the failure remain"
+                    );
+                }
+                return Err(error);
+            }
+        };
+
+        let reader_waited = gauge.reader_waited();
+        let mut stages = vec![StageThroughput {
+            stage: "parse",
+            features: gauge.read(),
+            busy: elapsed.saturating_sub(reader_waited),
+            waiting: reader_waited,
+        }];
+        stages.extend(normalized.throughput.take());
+        stages.push(stored.throughput);
+        let stats = PipelineStats {
+            buffers,
+            stages,
+            batches: stored.batches,
+            peak_features_in_flight: gauge.peak(),
+            elapsed,
+        };
+
+        self.finish_pipeline(
+            request,
+            format_dataset,
+            normalized,
+            stored.features,
+            validation.warnings,
+            stats,
+        )
+        .await
+    }
+
+    /// Describe and store the dataset once its features are stored
+    async fn finish_pipeline(
+        &self,
+        request: &IngestRequest,
+        format_dataset: FormatDataset,
+        normalized: Normalized,
+        features_stored: usize,
+        mut warnings: Vec<String>,
+        stats: PipelineStats,
+    ) -> Result<IngestReport> {
+        let crs = format_dataset.crs;
+        check_crs(request, crs)?;
+
+        let mut metadata: FormatMetadata = format_dataset.format_metadata;
+        if normalized.located_by_places {
+            if let Some(places) = &request.places {
+                metadata.spatial_association = Some(places_association(places));
+            }
+        }
+        log_read(&metadata, format_dataset.errors.len());
+
+        // Skipped and rejected features share one error budget, as in `prepare`
+        let mut feature_errors = FeatureErrors::resume(
+            metadata.format_name.clone(),
+            request.options.read_policy,
+            format_dataset.errors,
+        );
+        let rejected = normalized.errors.len();
+        for error in normalized.errors {
+            feature_errors.record(error).map_err(ServiceError::Read)?;
+        }
+
+        if normalized.measured > 0 {
+            warnings.push(measures_dropped(normalized.measured));
+        }
+        let (source, source_file) = match self.read_source(request, &mut warnings)? {
+            Some((content, file)) => (Some(content), Some(file)),
+            None => (None, None),
+        };
+        // A reader that failed before its first feature left no preview
+        let preview = normalized.preview.unwrap_or_else(|| preview_builder(&metadata).finish());
+        let mut dataset = describe_dataset(
+            request,
+            &metadata,
+            normalized.geometry_type.unwrap_or(GeometryType::Point),
+            normalized.read - rejected,
+            crs,
+            source_file,
+            preview,
+        );
+        let usage = UsageDelta::for_dataset(&dataset);
+        let dataset_id = self.store_dataset(&dataset, &[], source.as_deref(), &usage).await?;
+        dataset.id = dataset_id;
+
+        for stage in &stats.stages {
+            tracing::debug!(
+                stage = stage.stage,
+                features = stage.features,
+                busy_ms = stage.busy.as_millis() as u64,
+                features_per_second = stage.features_per_second(),
+                "Ingest stage throughput"
+            );
+        }
+        tracing::info!(
+            dataset_id = dataset_id.0,
+            features_stored,
+            batches = stats.batches,
+            peak_features_in_flight = stats.peak_features_in_flight,
+            "Successfully ingested dataset"
+        );
+
+        Ok(IngestReport {
+            dataset_id,
+            dataset,
+            features_stored,
+            warnings,
+            feature_errors: feature_errors.into_vec(),
+            oversized_features: normalized.oversized,
+            transform_errors: normalized.transform_errors,
+            usage,
+            format_metadata: metadata,
+            pipeline: Some(stats),
+        })
+    }
+}
+
+fn stopped() -> ServiceError {
+    ServiceError::Core(GeoragError::IngestStopped {
+        reason: "the store stage stopped".to_string(),
+    })
+}
+
+/// The error that stopped the pipeline
+///
+/// A stage whose neighbour failed stops with `IngestStopped`, so the first
+/// other error, in stage order, is the cause.
+fn first_cause(errors: [Option<ServiceError>; 3]) -> ServiceError {
+    let is_stopped = |error: &ServiceError| {
+        matches!(
+            error,
+            ServiceError::Read(GeoragError::IngestStopped { .. })
+                | ServiceError::Core(GeoragError::IngestStopped { .. })
+        )
+    };
+
+    let mut errors: Vec<ServiceError> = errors.into_iter().flatten().collect();
+    let cause = errors.iter().position(|error| !is_stopped(error)).unwrap_or(0);
+    errors.swap_remove(cause)
+}
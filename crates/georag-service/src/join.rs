@@ -0,0 +1,129 @@
+//! Spatial joins between stored datasets
+//!
+//! Stores that can evaluate a join themselves (PostgreSQL for `within` and
+//! `intersects`) run it in a single statement. Otherwise both datasets are
+//! loaded, joined in memory and the matched targets written back.
+
+use georag_core::error::GeoragError;
+use georag_core::geo::{JoinCounts, JoinOutcome, JoinPredicate, SpatialJoin};
+use georag_core::models::{DatasetId, Feature};
+use georag_store::ports::SpatialStore;
+use std::sync::Arc;
+
+use crate::error::{Result, ServiceError};
+
+/// Outcome of a completed join
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinReport {
+    /// Matched and missed target features
+    pub counts: JoinCounts,
+
+    /// Whether the store evaluated the join itself
+    pub pushed_down: bool,
+}
+
+/// Service for enriching one dataset's features with another's properties
+pub struct JoinService {
+    spatial_store: Arc<dyn SpatialStore>,
+}
+
+impl JoinService {
+    /// Create a join service over the spatial store
+    pub fn new(spatial_store: Arc<dyn SpatialStore>) -> Self {
+        Self { spatial_store }
+    }
+
+    /// Check join options before touching the store
+    pub fn validate(target: DatasetId, source: DatasetId, join: &SpatialJoin) -> Result<()> {
+        if target == source {
+            return Err(ServiceError::InvalidJoin(
+                "target and source must be different datasets".to_string(),
+            ));
+        }
+
+        if join.take.iter().all(|key| key.trim().is_empty()) {
+            return Err(ServiceError::InvalidJoin(
+                "at least one source property must be taken".to_string(),
+            ));
+        }
+
+        if let JoinPredicate::Nearest { max_distance: Some(distance) } = join.predicate {
+            if !distance.value.is_finite() || distance.value <= 0.0 {
+                return Err(ServiceError::InvalidJoin(
+                    "max distance must be greater than zero".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compute the join without writing anything
+    pub async fn preview(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<JoinOutcome> {
+        Self::validate(target, source, join)?;
+
+        let targets = self.load_features(target).await?;
+        let sources = self.load_features(source).await?;
+
+        Ok(join.apply(&targets, &sources))
+    }
+
+    /// Run the join and update the target features
+    pub async fn run(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<JoinReport> {
+        Self::validate(target, source, join)?;
+        self.load_dataset(target).await?;
+        self.load_dataset(source).await?;
+
+        if let Some(counts) = self.spatial_store.spatial_join(target, source, join).await? {
+            tracing::info!(
+                target = target.0,
+                source = source.0,
+                matched = counts.matched,
+                missed = counts.missed,
+                "Spatial join evaluated by the store"
+            );
+            return Ok(JoinReport { counts, pushed_down: true });
+        }
+
+        let outcome = self.preview(target, source, join).await?;
+        self.spatial_store.update_feature_properties(&outcome.updated).await?;
+
+        tracing::info!(
+            target = target.0,
+            source = source.0,
+            matched = outcome.counts.matched,
+            missed = outcome.counts.missed,
+            "Spatial join completed"
+        );
+
+        Ok(JoinReport {
+            counts: outcome.counts,
+            pushed_down: false,
+        })
+    }
+
+    async fn load_dataset(&self, id: DatasetId) -> Result<()> {
+        match self.spatial_store.get_dataset(id).await? {
+            Some(_) => Ok(()),
+            None => Err(GeoragError::DatasetNotFound { name: id.0.to_string() }.into()),
+        }
+    }
+
+    /// Features of a dataset in ID order, so matches do not depend on storage order
+    async fn load_features(&self, id: DatasetId) -> Result<Vec<Feature>> {
+        self.load_dataset(id).await?;
+        let mut features = self.spatial_store.get_features_for_dataset(id).await?;
+        features.sort_by_key(|f| f.id.0);
+        Ok(features)
+    }
+}
@@ -0,0 +1,72 @@
+//! Application services shared by the CLI and the REST API
+//!
+//! Both adapters call these services and keep only their own I/O (argument
+//! parsing, uploads, printing, HTTP responses) at the edges, so behavior such
+//! as validation, CRS checks or redaction is implemented once.
+
+pub mod area;
+pub mod audit;
+pub mod axis;
+pub mod bulk;
+pub mod clone;
+pub mod compact;
+pub mod convert;
+pub mod diff;
+pub mod error;
+pub mod events;
+pub mod gc;
+pub mod index_stats;
+pub mod ingest;
+pub mod join;
+pub mod operators;
+pub mod options;
+pub mod pages;
+pub mod presets;
+pub mod query;
+pub mod quota;
+pub mod remote;
+pub mod schema;
+pub mod upgrade;
+pub mod workspace;
+
+pub use area::{normalize_area_geometry, AreaService};
+pub use audit::{AuditService, MAX_NOTABLE_EVENTS};
+pub use axis::{AxisRepairPlan, AxisRepairReport, AxisRepairService};
+pub use bulk::{
+    BulkAction, BulkCandidate, BulkDatasetResult, BulkDatasetService, BulkPlan, BulkProgress,
+    BulkReport, DatasetFilter, NamePattern,
+};
+pub use clone::{
+    CloneCounts, ClonePhase, ClonePlan, CloneProgress, CloneReport, CloneVerification,
+    DatasetMapping, WorkspaceCloneService,
+};
+pub use compact::{CompactionPlan, CompactionProgress, CompactionReport, CompactionService};
+pub use convert::{
+    Conversion, ConvertFormat, ConvertOptions, ConvertService, ConvertedLayer, PropertyMapping,
+};
+pub use diff::{DatasetDiff, DiffApplyReport, DiffCounts, DiffService, FeatureChange};
+pub use error::{Result, ServiceError};
+pub use events::{
+    validate_consumer, EventLogService, EventPage, EventReplay, MAX_CONSUMER_NAME_LEN,
+    MAX_EVENT_WAIT,
+};
+pub use gc::{GcPlan, GcReport, GcService};
+pub use index_stats::{IndexStatsDrift, IndexStatsService, IndexStatsVerification};
+pub use ingest::{
+    max_features_in_flight, IngestReport, IngestRequest, IngestService, PipelineStats,
+    PreparedIngest, SourcePolicy, StageThroughput, StoreProgress,
+};
+pub use join::{JoinReport, JoinService};
+pub use operators::{apply_operators, parse_operators, Operator, ParsedOperator, ParsedQuery};
+pub use options::{geometry_output, parse_rerank};
+pub use pages::{
+    index_generation, plan_hash, PagedQuery, QueryCursor, QueryPages, ResultPage,
+    DEFAULT_MAX_PAGE_SIZE, DEFAULT_PAGE_TTL, NO_INDEX_GENERATION,
+};
+pub use presets::{select_preset, validate_preset, validate_presets, PresetMerge};
+pub use query::{ExampleSeed, QueryService};
+pub use quota::WorkspaceQuota;
+pub use remote::{is_remote, Download, DownloadPolicy};
+pub use schema::SchemaService;
+pub use upgrade::{UpgradeReport, UpgradeService, UpgradeStep, UPGRADE_STEPS};
+pub use workspace::{new_workspace_config, WorkspaceService, WorkspaceView};
@@ -0,0 +1,326 @@
+//! `key:value` operators in query text
+//!
+//! With operator parsing asked for, a query such as
+//! `drainage issues near:ubud within:2km dataset:reports since:2023` is split
+//! into the text that gets embedded and structured filters: `near:` names a
+//! saved area, `within:` buffers it, `dataset:` limits the datasets searched
+//! (repeat it for several) and `since:` keeps features dated from then on.
+//! Values with spaces are quoted, as in `near:"ubud centre"`.
+//!
+//! Tokens with other keys stay in the text as written. A malformed operator
+//! never fails the query: it stays in the text too, and the interpretation
+//! recorded in the plan says why it was ignored.
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use georag_core::config::parse_distance_unit;
+use georag_core::models::{
+    Crs, Distance, DistanceUnit, SavedArea, SpatialFilter, SpatialPredicate,
+};
+use georag_retrieval::{
+    AppliedOperator, IgnoredOperator, NamedAreaExpansion, QueryOperators, QueryPlan, TimeFilter,
+};
+
+/// Operator keys, as written before the colon
+pub const OPERATOR_KEYS: [&str; 4] = ["near", "within", "dataset", "since"];
+
+/// Filter named by one operator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operator {
+    /// Saved area to search in
+    Near(String),
+
+    /// Buffer around the `near:` area
+    Within(Distance),
+
+    /// Dataset to search
+    Dataset(String),
+
+    /// Earliest feature date kept
+    Since(DateTime<Utc>),
+}
+
+impl Operator {
+    fn key(&self) -> &'static str {
+        match self {
+            Operator::Near(_) => "near",
+            Operator::Within(_) => "within",
+            Operator::Dataset(_) => "dataset",
+            Operator::Since(_) => "since",
+        }
+    }
+}
+
+/// Operator and the token it was parsed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedOperator {
+    /// Token as written, e.g. `near:"ubud centre"`
+    pub token: String,
+
+    pub operator: Operator,
+}
+
+/// Query text split into its operators and the rest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// Query text as given
+    pub original: String,
+
+    /// Text without the parsed operators, whitespace collapsed
+    pub text: String,
+
+    /// Parsed operators, in the order written
+    pub operators: Vec<ParsedOperator>,
+
+    /// Operators that could not be parsed; they stay in `text`
+    pub ignored: Vec<IgnoredOperator>,
+}
+
+impl ParsedQuery {
+    /// Saved area named by the first `near:` operator
+    pub fn near(&self) -> Option<&str> {
+        self.operators.iter().find_map(|parsed| match &parsed.operator {
+            Operator::Near(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Split `key:value` operators out of query text
+///
+/// Keys are matched ignoring case. Never fails: unknown keys and malformed
+/// operators are left in the text.
+pub fn parse_operators(query: &str) -> ParsedQuery {
+    let mut text = Vec::new();
+    let mut operators = Vec::new();
+    let mut ignored = Vec::new();
+
+    for token in tokenize(query) {
+        match parse_token(&token) {
+            None => text.push(token),
+            Some(Ok(operator)) => operators.push(ParsedOperator { token, operator }),
+            Some(Err(reason)) => {
+                ignored.push(IgnoredOperator { token: token.clone(), reason });
+                text.push(token);
+            }
+        }
+    }
+
+    ParsedQuery {
+        original: query.to_string(),
+        text: text.join(" "),
+        operators,
+        ignored,
+    }
+}
+
+/// Turn parsed operators into filters of the plan
+///
+/// The plan's text becomes the text left once the operators are taken out.
+/// `place` is the saved area named by `near:`, already resolved, and
+/// `time_property` the feature property `since:` applies to; the property of
+/// the plan's time grouping is used when it is not given. Operators the plan
+/// cannot take are recorded as ignored: a `near:` when the plan already has
+/// a spatial filter or the area does not exist, a `within:` without a
+/// `near:`, a `since:` without a time property, and repeats of `near:`,
+/// `within:` or `since:`.
+pub fn apply_operators(
+    mut plan: QueryPlan,
+    parsed: ParsedQuery,
+    place: Option<&SavedArea>,
+    time_property: Option<&str>,
+) -> QueryPlan {
+    let time_property = time_property
+        .map(str::to_string)
+        .or_else(|| plan.group_by_time.as_ref().map(|g| g.property.clone()));
+    let has_spatial_filter = plan.spatial_filter.is_some();
+
+    let mut interpretation = QueryOperators {
+        original: parsed.original,
+        text: parsed.text,
+        applied: Vec::new(),
+        ignored: parsed.ignored,
+    };
+    let mut seen = Vec::new();
+    let mut area = None;
+    let mut within = None;
+
+    for ParsedOperator { token, operator } in parsed.operators {
+        let key = operator.key();
+        if key != "dataset" && seen.contains(&key) {
+            let reason = format!("repeats {}:, the first one is used", key);
+            interpretation.ignored.push(IgnoredOperator { token, reason });
+            continue;
+        }
+        seen.push(key);
+
+        match operator {
+            Operator::Near(name) => {
+                let reason = if has_spatial_filter {
+                    "the query already has a spatial filter".to_string()
+                } else if let Some(place) = place {
+                    area = Some((token, place));
+                    continue;
+                } else {
+                    format!("no saved area is named '{}'", name)
+                };
+                interpretation.ignored.push(IgnoredOperator { token, reason });
+            }
+            Operator::Within(distance) => within = Some((token, distance)),
+            Operator::Dataset(name) => {
+                interpretation.applied.push(AppliedOperator {
+                    token,
+                    filter: format!("dataset {}", name),
+                });
+                plan = plan.with_dataset(name);
+            }
+            Operator::Since(since) => match &time_property {
+                Some(property) => {
+                    interpretation.applied.push(AppliedOperator {
+                        token,
+                        filter: format!("{} on or after {}", property, since.format("%Y-%m-%d")),
+                    });
+                    plan = plan.with_time_filter(TimeFilter::new(property, since));
+                }
+                None => interpretation.ignored.push(IgnoredOperator {
+                    token,
+                    reason: "no time property is set for since:".to_string(),
+                }),
+            },
+        }
+    }
+
+    match (area, within) {
+        (Some((token, area)), within) => {
+            interpretation.applied.push(AppliedOperator {
+                token,
+                filter: format!("area {}", area.name),
+            });
+            if let Some((token, distance)) = &within {
+                interpretation.applied.push(AppliedOperator {
+                    token: token.clone(),
+                    filter: format!(
+                        "buffer of {} {:?} around area {}",
+                        distance.value, distance.unit, area.name
+                    ),
+                });
+            }
+            plan = plan
+                .with_spatial_filter(SpatialFilter {
+                    predicate: SpatialPredicate::Intersects,
+                    geometry: Some(area.geometry.clone()),
+                    distance: None,
+                    crs: Crs::wgs84(),
+                    buffer: within.map(|(_, distance)| distance),
+                    exclude: Vec::new(),
+                })
+                .with_named_area(NamedAreaExpansion {
+                    name: area.name.clone(),
+                    vertices: area.vertex_count(),
+                });
+        }
+        (None, Some((token, _))) => interpretation.ignored.push(IgnoredOperator {
+            token,
+            reason: "within: needs a near: area to measure from".to_string(),
+        }),
+        (None, None) => {}
+    }
+
+    plan.text_query = interpretation.text.clone();
+    plan.with_operators(interpretation)
+}
+
+/// Split on whitespace outside double quotes, keeping the quotes
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in query.chars() {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if c.is_whitespace() && !quoted {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Parse a token with an operator key; None for any other token
+fn parse_token(token: &str) -> Option<Result<Operator, String>> {
+    let (key, value) = token.split_once(':')?;
+    let key = key.to_lowercase();
+    if !OPERATOR_KEYS.contains(&key.as_str()) {
+        return None;
+    }
+
+    Some(unquote(value).and_then(|value| match key.as_str() {
+        "near" => Ok(Operator::Near(value)),
+        "within" => parse_within(&value).map(Operator::Within),
+        "dataset" => Ok(Operator::Dataset(value)),
+        _ => parse_since(&value).map(Operator::Since),
+    }))
+}
+
+/// Strip the quotes around a value, refusing empty and half-quoted ones
+fn unquote(value: &str) -> Result<String, String> {
+    let value = match value.strip_prefix('"') {
+        Some(rest) => {
+            rest.strip_suffix('"').ok_or_else(|| "the quote is not closed".to_string())?
+        }
+        None if value.contains('"') => return Err("quote the whole value".to_string()),
+        None => value,
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("the value is empty".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// Parse a distance such as `2km`, `500 m` or `300` (meters)
+fn parse_within(value: &str) -> Result<Distance, String> {
+    let (number, unit) = match value.find(|c: char| c.is_alphabetic()) {
+        Some(at) => value.split_at(at),
+        None => (value, ""),
+    };
+    let number: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a distance such as 2km", value))?;
+    if !number.is_finite() || number <= 0.0 {
+        return Err("the distance must be greater than zero".to_string());
+    }
+    let unit = match unit.trim() {
+        "" => DistanceUnit::Meters,
+        unit => parse_distance_unit(unit).map_err(|e| e.to_string())?,
+    };
+    Ok(Distance::new(number, unit))
+}
+
+/// Parse a start date: `YYYY`, `YYYY-MM`, `YYYY-MM-DD` or RFC 3339
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next().filter(|year| year.len() == 4);
+    let date = year.and_then(|year| {
+        let year = year.parse().ok()?;
+        let month = parts.next().map_or(Some(1), |m| m.parse().ok())?;
+        let day = parts.next().map_or(Some(1), |d| d.parse().ok())?;
+        NaiveDate::from_ymd_opt(year, month, day)
+    });
+
+    date.and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|start| Utc.from_utc_datetime(&start))
+        .ok_or_else(|| format!("'{}' is not a date such as 2023, 2023-06 or 2023-06-15", value))
+}
@@ -0,0 +1,48 @@
+//! Checks of the query options a request or a workspace preset sets
+//!
+//! API query requests and presets carry the rerank mode and geometry output
+//! as plain values. Both are parsed here, so a preset is accepted exactly
+//! when a request setting the same options would be.
+
+use georag_retrieval::{GeometryDetail, GeometryOutput, RerankMode};
+
+use crate::error::{Result, ServiceError};
+
+/// Parse a rerank mode: none, lexical or llm
+pub fn parse_rerank(mode: &str) -> Result<RerankMode> {
+    mode.parse::<RerankMode>().map_err(ServiceError::InvalidQuery)
+}
+
+/// Geometry output of a response, unset options taking their defaults
+///
+/// Precision must be at most [`GeometryOutput::MAX_PRECISION`] decimal places
+/// and the vertex budget at least [`GeometryOutput::MIN_VERTEX_BUDGET`].
+pub fn geometry_output(
+    detail: Option<&str>,
+    precision: Option<u32>,
+    vertex_budget: Option<usize>,
+) -> Result<GeometryOutput> {
+    let detail = match detail {
+        Some(detail) => detail.parse::<GeometryDetail>().map_err(ServiceError::InvalidQuery)?,
+        None => GeometryDetail::default(),
+    };
+
+    if let Some(precision) = precision {
+        if precision > GeometryOutput::MAX_PRECISION {
+            return Err(ServiceError::InvalidQuery(format!(
+                "coordinate_precision must be at most {}",
+                GeometryOutput::MAX_PRECISION
+            )));
+        }
+    }
+
+    let vertex_budget = vertex_budget.unwrap_or(GeometryOutput::DEFAULT_VERTEX_BUDGET);
+    if vertex_budget < GeometryOutput::MIN_VERTEX_BUDGET {
+        return Err(ServiceError::InvalidQuery(format!(
+            "vertex_budget must be at least {}",
+            GeometryOutput::MIN_VERTEX_BUDGET
+        )));
+    }
+
+    Ok(GeometryOutput { detail, precision, vertex_budget })
+}
@@ -0,0 +1,350 @@
+//! Cursor pagination over ranked query results
+//!
+//! A paged query runs the pipeline once for all of its `top_k` results and
+//! keeps the ranked list for a while; later pages are cut from that list
+//! instead of running the query again. A cursor names the list by the hash of
+//! the query plan and the index generation it was ranked against, and carries
+//! the rank offset of the page it asks for. Lists expire after the page TTL
+//! and are dropped as soon as the index generation changes, since their
+//! ranks no longer describe the index.
+
+use georag_core::models::{IndexState, WorkspaceId};
+use georag_retrieval::{GeometryOutput, PropertySelection, QueryPlan, QueryResult};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::{Result, ServiceError};
+
+/// Default time a ranked list is kept for its next pages
+pub const DEFAULT_PAGE_TTL: Duration = Duration::from_secs(300);
+
+/// Default largest page
+pub const DEFAULT_MAX_PAGE_SIZE: usize = 100;
+
+/// Ranked lists kept at once; the oldest is dropped to make room
+pub const MAX_CACHED_QUERIES: usize = 256;
+
+/// Generation of a workspace whose index was never built
+pub const NO_INDEX_GENERATION: &str = "none";
+
+/// Position in the ranked results of a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCursor {
+    /// Hash of the query plan and the scope it ran in
+    pub plan_hash: String,
+    /// Index generation the results were ranked against
+    pub generation: String,
+    /// Rank offset of the first result of the page
+    pub offset: usize,
+    /// Results per page
+    pub page_size: usize,
+}
+
+impl QueryCursor {
+    /// Opaque form handed to callers
+    pub fn encode(&self) -> String {
+        let plain =
+            format!("{}.{}.{}.{}", self.plan_hash, self.generation, self.offset, self.page_size);
+        plain.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Read a cursor given out by [`encode`](Self::encode)
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid =
+            || ServiceError::InvalidCursor("not a cursor returned by a query".to_string());
+        let cursor = cursor.trim();
+        if cursor.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| cursor.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        let plain = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        let parts: Vec<&str> = plain.split('.').collect();
+        let [plan_hash, generation, offset, page_size] = parts[..] else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            plan_hash: plan_hash.to_string(),
+            generation: generation.to_string(),
+            offset: offset.parse().map_err(|_| invalid())?,
+            page_size: page_size.parse().ok().filter(|size| *size > 0).ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// Generation of an index: every build starts a new one
+///
+/// A workspace whose index was never built has [`NO_INDEX_GENERATION`].
+pub fn index_generation(state: Option<&IndexState>) -> String {
+    match state {
+        Some(state) => format!("{}-{}", state.hash, state.built_at.timestamp_millis()),
+        None => NO_INDEX_GENERATION.to_string(),
+    }
+}
+
+/// Hash naming the ranked results of `plan` run in `scope`
+///
+/// The scope separates identical plans run by different workspaces or
+/// embedders. The caller's visibility is part of the plan, so callers seeing
+/// different datasets never share a ranked list.
+pub fn plan_hash(plan: &QueryPlan, scope: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(scope.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(plan).unwrap_or_default());
+    format!("{:x}", hasher.finalize())
+}
+
+/// A query run for paging: its plan, its full ranked result and the output
+/// options its pages are rendered with
+#[derive(Debug, Clone)]
+pub struct PagedQuery {
+    pub plan: QueryPlan,
+    pub result: QueryResult,
+    /// Embedder the query was embedded with
+    pub embedder_model: String,
+    pub geometry_output: GeometryOutput,
+    pub properties: PropertySelection,
+    /// Workspace the query ran in; only its requests can read the pages
+    pub workspace_id: Option<WorkspaceId>,
+}
+
+impl PagedQuery {
+    /// Paged query rendered with the default output options
+    pub fn new(plan: QueryPlan, result: QueryResult, embedder_model: impl Into<String>) -> Self {
+        Self {
+            plan,
+            result,
+            embedder_model: embedder_model.into(),
+            geometry_output: GeometryOutput::default(),
+            properties: PropertySelection::default(),
+            workspace_id: None,
+        }
+    }
+
+    /// Keep the pages for requests to this workspace
+    pub fn with_workspace(mut self, workspace_id: WorkspaceId) -> Self {
+        self.workspace_id = Some(workspace_id);
+        self
+    }
+
+    /// Render pages with this geometry detail and coordinate precision
+    pub fn with_geometry_output(mut self, output: GeometryOutput) -> Self {
+        self.geometry_output = output;
+        self
+    }
+
+    /// Keep only these property keys in rendered pages
+    pub fn with_property_selection(mut self, selection: PropertySelection) -> Self {
+        self.properties = selection;
+        self
+    }
+}
+
+/// One page of ranked results
+#[derive(Debug, Clone)]
+pub struct ResultPage {
+    /// The paged query, holding the results of every page
+    pub query: Arc<PagedQuery>,
+    /// The query result holding only this page's sources
+    pub result: QueryResult,
+    /// Rank offset of the first source of the page
+    pub offset: usize,
+    /// Sources ranked by the query across all pages
+    pub total: usize,
+    /// Cursor of the next page, when there is one
+    pub next: Option<QueryCursor>,
+}
+
+struct CachedQuery {
+    generation: String,
+    stored_at: Instant,
+    query: Arc<PagedQuery>,
+}
+
+/// Ranked results of paged queries, kept for their next pages
+pub struct QueryPages {
+    ttl: Duration,
+    max_page_size: usize,
+    entries: Mutex<HashMap<String, CachedQuery>>,
+}
+
+impl Default for QueryPages {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAGE_TTL)
+    }
+}
+
+impl QueryPages {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the largest page a query may ask for
+    pub fn with_max_page_size(mut self, max_page_size: usize) -> Self {
+        self.max_page_size = max_page_size.max(1);
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn max_page_size(&self) -> usize {
+        self.max_page_size
+    }
+
+    /// Check a requested page size against the limit
+    pub fn check_page_size(&self, page_size: usize) -> Result<()> {
+        if !(1..=self.max_page_size).contains(&page_size) {
+            return Err(ServiceError::InvalidQuery(format!(
+                "page_size must be between 1 and {}",
+                self.max_page_size
+            )));
+        }
+        Ok(())
+    }
+
+    /// First page of a query's results, keeping the rest for later pages
+    ///
+    /// Results fitting on one page are not kept and get no cursor.
+    pub fn first_page(
+        &self,
+        plan_hash: String,
+        generation: &str,
+        query: PagedQuery,
+        page_size: usize,
+    ) -> ResultPage {
+        let query = Arc::new(query);
+        let page = page_of(&query, &plan_hash, generation, 0, page_size);
+        if page.next.is_some() {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            self.evict(&mut entries);
+            entries.insert(
+                plan_hash,
+                CachedQuery {
+                    generation: generation.to_string(),
+                    stored_at: Instant::now(),
+                    query,
+                },
+            );
+        }
+        page
+    }
+
+    /// The page `cursor` points at, for a request to `workspace_id` given
+    /// the index's current generation
+    ///
+    /// Fails with [`ServiceError::CursorNotFound`] when the query ran in
+    /// another workspace, with [`ServiceError::CursorInvalidated`] once the
+    /// index changed and with [`ServiceError::CursorExpired`] once the results
+    /// were dropped.
+    pub fn page(
+        &self,
+        cursor: &QueryCursor,
+        generation: &str,
+        workspace_id: Option<WorkspaceId>,
+    ) -> Result<ResultPage> {
+        self.check_page_size(cursor.page_size)?;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        let Some(entry) = entries.get(&cursor.plan_hash) else {
+            return Err(if cursor.generation != generation {
+                ServiceError::CursorInvalidated
+            } else {
+                ServiceError::CursorExpired
+            });
+        };
+        // Checked first, so requests to another workspace never drop the results
+        if entry.query.workspace_id != workspace_id {
+            return Err(ServiceError::CursorNotFound);
+        }
+        if cursor.generation != generation || entry.generation != generation {
+            entries.remove(&cursor.plan_hash);
+            return Err(ServiceError::CursorInvalidated);
+        }
+        if entry.stored_at.elapsed() > self.ttl {
+            entries.remove(&cursor.plan_hash);
+            return Err(ServiceError::CursorExpired);
+        }
+        let query = entry.query.clone();
+        drop(entries);
+
+        let total = query.result.sources.len();
+        if cursor.offset > total {
+            return Err(ServiceError::InvalidCursor(format!(
+                "offset {} is past the {} results of the query",
+                cursor.offset, total
+            )));
+        }
+        Ok(page_of(&query, &cursor.plan_hash, generation, cursor.offset, cursor.page_size))
+    }
+
+    /// Ranked lists currently kept
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop expired lists, then the oldest while the cache is full
+    fn evict(&self, entries: &mut HashMap<String, CachedQuery>) {
+        entries.retain(|_, entry| entry.stored_at.elapsed() <= self.ttl);
+        while entries.len() >= MAX_CACHED_QUERIES {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(hash, _)| hash.clone());
+            match oldest {
+                Some(hash) => entries.remove(&hash),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Cut the page at `offset` out of the full ranked result
+fn page_of(
+    query: &Arc<PagedQuery>,
+    plan_hash: &str,
+    generation: &str,
+    offset: usize,
+    page_size: usize,
+) -> ResultPage {
+    let result = &query.result;
+    let total = result.sources.len();
+    let end = (offset + page_size).min(total);
+
+    let mut page = result.clone();
+    page.sources = result.sources[offset..end].to_vec();
+    page.semantic_scores = result
+        .semantic_scores
+        .as_ref()
+        .map(|scores| scores.get(offset..end.min(scores.len())).unwrap_or_default().to_vec());
+
+    let next = (end < total).then(|| QueryCursor {
+        plan_hash: plan_hash.to_string(),
+        generation: generation.to_string(),
+        offset: end,
+        page_size,
+    });
+    ResultPage {
+        query: query.clone(),
+        result: page,
+        offset,
+        total,
+        next,
+    }
+}
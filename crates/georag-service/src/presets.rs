@@ -0,0 +1,221 @@
+//! Workspace query presets: checking them and merging them into queries
+//!
+//! Presets live in the workspace settings under `query_presets`. A query
+//! takes the preset it names, or the workspace's `default_query_preset` when
+//! it names none, and every option the query sets itself wins over the
+//! preset's. The merge records which options came from the preset, so the
+//! explanation of the query can show them.
+
+use georag_core::config::WorkspaceSettings;
+use georag_core::models::{normalize_preset_name, QueryPreset};
+use georag_retrieval::{AppliedPreset, QueryPlan};
+use std::collections::BTreeMap;
+
+use crate::error::{Result, ServiceError};
+use crate::options::{geometry_output, parse_rerank};
+use crate::query::QueryService;
+
+/// Check a preset's options the way a query request setting them is checked
+pub fn validate_preset(preset: &QueryPreset) -> Result<()> {
+    let mut plan = QueryPlan::new("preset");
+    if let Some(top_k) = preset.top_k {
+        plan = plan.with_top_k(top_k);
+    }
+    if let Some(min_score) = preset.min_score {
+        plan = plan.with_min_score(min_score);
+    }
+    if let Some(mode) = &preset.rerank {
+        plan = plan.with_rerank(parse_rerank(mode)?);
+    }
+    if let Some(pool) = preset.rerank_pool {
+        plan = plan.with_rerank_pool(pool);
+    }
+    QueryService::validate(&plan)?;
+
+    geometry_output(
+        preset.geometry_detail.as_deref(),
+        preset.coordinate_precision,
+        preset.vertex_budget,
+    )?;
+    Ok(())
+}
+
+/// Check every preset of the settings, naming the first one that fails
+pub fn validate_presets(settings: &WorkspaceSettings) -> Result<()> {
+    for (name, preset) in settings.query_presets.iter().flatten() {
+        validate_preset(preset).map_err(|e| ServiceError::InvalidPreset {
+            name: name.clone(),
+            reason: match e {
+                ServiceError::InvalidQuery(reason) => reason,
+                e => e.to_string(),
+            },
+        })?;
+    }
+    Ok(())
+}
+
+/// Preset a query takes: the one it names, or else the workspace default
+///
+/// Returns `None` when the query names no preset and the workspace has no
+/// default. A named preset that does not exist fails with `PresetNotFound`.
+pub fn select_preset(
+    presets: Option<&BTreeMap<String, QueryPreset>>,
+    default: Option<&str>,
+    requested: Option<&str>,
+) -> Result<Option<PresetMerge>> {
+    let (name, is_default) = match (requested, default) {
+        (Some(name), _) => {
+            (normalize_preset_name(name).unwrap_or_else(|_| name.to_string()), false)
+        }
+        (None, Some(name)) => (name.to_string(), true),
+        (None, None) => return Ok(None),
+    };
+
+    let preset = presets
+        .and_then(|presets| presets.get(&name))
+        .ok_or_else(|| ServiceError::PresetNotFound { name: name.clone() })?;
+    Ok(Some(PresetMerge {
+        preset: preset.clone(),
+        applied: AppliedPreset {
+            name,
+            default: is_default,
+            applied: Vec::new(),
+            overridden: Vec::new(),
+        },
+    }))
+}
+
+/// A preset being merged into the options of a query
+#[derive(Debug, Clone)]
+pub struct PresetMerge {
+    /// Options of the preset
+    pub preset: QueryPreset,
+    applied: AppliedPreset,
+}
+
+impl PresetMerge {
+    /// Name of the preset
+    pub fn name(&self) -> &str {
+        &self.applied.name
+    }
+
+    /// Fill the query's `option` with the preset's `value` unless the query set it
+    pub fn fill<T>(&mut self, key: &str, option: &mut Option<T>, value: Option<T>) {
+        let Some(value) = value else {
+            return;
+        };
+        if option.is_some() {
+            self.applied.overridden.push(key.to_string());
+        } else {
+            *option = Some(value);
+            self.applied.applied.push(key.to_string());
+        }
+    }
+
+    /// Which options the query took from the preset and which it overrode
+    pub fn finish(self) -> AppliedPreset {
+        self.applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presets() -> BTreeMap<String, QueryPreset> {
+        let mobile = QueryPreset {
+            top_k: Some(5),
+            geometry_detail: Some("centroid".to_string()),
+            ..Default::default()
+        };
+        BTreeMap::from([("mobile".to_string(), mobile)])
+    }
+
+    #[test]
+    fn test_query_options_win_over_the_preset() {
+        let presets = presets();
+        let mut merge = select_preset(Some(&presets), None, Some("Mobile")).unwrap().unwrap();
+        let preset = merge.preset.clone();
+
+        let mut top_k = Some(12);
+        let mut geometry_detail = None;
+        let mut rerank: Option<String> = None;
+        merge.fill("top_k", &mut top_k, preset.top_k);
+        merge.fill("geometry_detail", &mut geometry_detail, preset.geometry_detail);
+        merge.fill("rerank", &mut rerank, preset.rerank);
+        assert_eq!((top_k, geometry_detail.as_deref(), rerank), (Some(12), Some("centroid"), None));
+
+        let applied = merge.finish();
+        assert_eq!(applied.name, "mobile");
+        assert!(!applied.default);
+        assert_eq!(applied.applied, ["geometry_detail"]);
+        assert_eq!(applied.overridden, ["top_k"]);
+    }
+
+    #[test]
+    fn test_default_preset_applies_when_none_is_named() {
+        let presets = presets();
+        let merge = select_preset(Some(&presets), Some("mobile"), None).unwrap().unwrap();
+        assert_eq!(merge.name(), "mobile");
+        assert!(merge.finish().default);
+
+        assert!(select_preset(Some(&presets), None, None).unwrap().is_none());
+        assert!(matches!(
+            select_preset(Some(&presets), Some("mobile"), Some("planners")),
+            Err(ServiceError::PresetNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_presets_are_checked_like_requests() {
+        assert!(validate_preset(&presets()["mobile"]).is_ok());
+
+        let invalid = [
+            QueryPreset {
+                min_score: Some(1.5),
+                ..Default::default()
+            },
+            QueryPreset {
+                rerank: Some("semantic".to_string()),
+                ..Default::default()
+            },
+            QueryPreset {
+                rerank: Some("lexical".to_string()),
+                rerank_pool: Some(0),
+                ..Default::default()
+            },
+            QueryPreset {
+                geometry_detail: Some("outline".to_string()),
+                ..Default::default()
+            },
+            QueryPreset {
+                vertex_budget: Some(3),
+                ..Default::default()
+            },
+            QueryPreset {
+                coordinate_precision: Some(20),
+                ..Default::default()
+            },
+        ];
+        for preset in invalid {
+            assert!(validate_preset(&preset).is_err(), "{}", preset);
+        }
+
+        let settings = WorkspaceSettings {
+            query_presets: Some(BTreeMap::from([(
+                "planners".to_string(),
+                QueryPreset {
+                    rerank: Some("semantic".to_string()),
+                    ..Default::default()
+                },
+            )])),
+            ..Default::default()
+        };
+        let err = validate_presets(&settings).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Invalid preset planners: Invalid rerank mode"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,651 @@
+//! Query execution shared by the CLI and the API
+//!
+//! Adapters turn their arguments into a `QueryPlan`; the service validates
+//! it, runs the retrieval pipeline and enriches the ranked sources with
+//! feature geometries for serialization. Redaction is applied here so every
+//! output of a query is masked the same way, and a query that returns nothing
+//! is diagnosed here so the CLI and the API explain it the same way.
+//! Static maps are rendered here too, fetching basemap tiles when a basemap
+//! is configured.
+
+use georag_core::error::GeoragError;
+use georag_core::geo::{FilterCache, GeometryLimits};
+use georag_core::llm::{Embedder, Generator};
+use georag_core::models::{
+    ChunkId, ConfidenceCutoffs, DatasetId, FeatureId, Geometry, IndexState, SourceUrlTemplate,
+};
+use georag_core::redaction::Redactor;
+use georag_retrieval::export;
+use georag_retrieval::rerank::MAX_RERANK_POOL;
+use georag_retrieval::{
+    Basemap, Diagnostic, DiagnosticKind, GeometryOutput, GroundingPolicy, MapFeature, MapOptions,
+    PropertySelection, QueryExample, QueryPlan, QueryResult, RerankMode, Reranker, ResultRow,
+    RetrievalPipeline, SeedMode, StaticMap, TileId,
+};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::error::{Result, ServiceError};
+
+/// Time allowed for each basemap tile request
+const TILE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a query by example resembles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExampleSeed {
+    /// Every indexed chunk of a stored feature
+    Feature {
+        dataset_id: DatasetId,
+        feature_id: FeatureId,
+    },
+
+    /// A single indexed chunk
+    Chunk(ChunkId),
+}
+
+/// Service for executing queries against the stores
+#[derive(Clone)]
+pub struct QueryService {
+    spatial_store: Arc<dyn SpatialStore>,
+    vector_store: Arc<dyn VectorStore>,
+    document_store: Arc<dyn DocumentStore>,
+    redactor: Redactor,
+    geometry_limits: GeometryLimits,
+    geometry_output: GeometryOutput,
+    properties: PropertySelection,
+    index_state: Option<IndexState>,
+    llm_reranker: Option<Arc<dyn Reranker>>,
+    basemap: Option<Basemap>,
+    filter_cache: Option<Arc<FilterCache>>,
+    source_urls: Option<SourceUrlTemplate>,
+    generator: Option<Arc<dyn Generator>>,
+    grounding: GroundingPolicy,
+    confidence_cutoffs: ConfidenceCutoffs,
+}
+
+impl QueryService {
+    /// Create a query service over the given stores
+    pub fn new(
+        spatial_store: Arc<dyn SpatialStore>,
+        vector_store: Arc<dyn VectorStore>,
+        document_store: Arc<dyn DocumentStore>,
+    ) -> Self {
+        Self {
+            spatial_store,
+            vector_store,
+            document_store,
+            redactor: Redactor::default(),
+            geometry_limits: GeometryLimits::default(),
+            geometry_output: GeometryOutput::default(),
+            properties: PropertySelection::default(),
+            index_state: None,
+            llm_reranker: None,
+            basemap: None,
+            filter_cache: None,
+            source_urls: None,
+            generator: None,
+            grounding: GroundingPolicy::default(),
+            confidence_cutoffs: ConfidenceCutoffs::default(),
+        }
+    }
+
+    /// Set the redaction rules applied to results and serialized outputs
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Set the size limits applied to the query filter geometry
+    pub fn with_geometry_limits(mut self, limits: GeometryLimits) -> Self {
+        self.geometry_limits = limits;
+        self
+    }
+
+    /// Set the geometry detail and coordinate precision of serialized results
+    pub fn with_geometry_output(mut self, output: GeometryOutput) -> Self {
+        self.geometry_output = output;
+        self
+    }
+
+    /// Set the property keys kept in serialized results
+    ///
+    /// Applied after redaction to rows, CSV columns and GeoJSON properties
+    /// alike.
+    pub fn with_property_selection(mut self, selection: PropertySelection) -> Self {
+        self.properties = selection;
+        self
+    }
+
+    /// Set the state of the index being queried
+    ///
+    /// Empty results are then also checked against the embedder the index was
+    /// built with, and the sources of semantic queries get a confidence from
+    /// the index's score baseline.
+    pub fn with_index_state(mut self, state: IndexState) -> Self {
+        self.index_state = Some(state);
+        self
+    }
+
+    /// Set the reranker serving plans that ask for `llm` reranking
+    pub fn with_llm_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.llm_reranker = Some(reranker);
+        self
+    }
+
+    /// Set the tile source drawn under static maps
+    ///
+    /// Without one, maps are rendered offline over a plain background.
+    pub fn with_basemap(mut self, basemap: Basemap) -> Self {
+        self.basemap = Some(basemap);
+        self
+    }
+
+    /// Share prepared spatial filters across queries
+    ///
+    /// Queries repeating a filter geometry, such as a saved area, then skip
+    /// preparing it again.
+    pub fn with_filter_cache(mut self, cache: Arc<FilterCache>) -> Self {
+        self.filter_cache = Some(cache);
+        self
+    }
+
+    /// Give every source a `source_url` filled from this template
+    pub fn with_source_url_template(mut self, template: SourceUrlTemplate) -> Self {
+        self.source_urls = Some(template);
+        self
+    }
+
+    /// Generate answers from the ranked excerpts with this generator
+    ///
+    /// Each sentence of a generated answer is checked against the sources;
+    /// unsupported ones are listed in the result's `ungrounded`.
+    pub fn with_generator(mut self, generator: Arc<dyn Generator>) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    /// Set the thresholds of the grounding check of generated answers
+    pub fn with_grounding(mut self, policy: GroundingPolicy) -> Self {
+        self.grounding = policy;
+        self
+    }
+
+    /// Set the percentiles sources must reach to be labelled high or medium confidence
+    pub fn with_confidence_cutoffs(mut self, cutoffs: ConfidenceCutoffs) -> Self {
+        self.confidence_cutoffs = cutoffs;
+        self
+    }
+
+    /// Check a plan for values the pipeline cannot use
+    pub fn validate(plan: &QueryPlan) -> Result<()> {
+        if let Some(min_score) = plan.min_score {
+            if !(0.0..=1.0).contains(&min_score) {
+                return Err(ServiceError::InvalidQuery(
+                    "min_score must be between 0.0 and 1.0".to_string(),
+                ));
+            }
+        }
+
+        if let Some(buffer) = plan.spatial_filter.as_ref().and_then(|f| f.buffer.as_ref()) {
+            if !buffer.value.is_finite() || buffer.value <= 0.0 {
+                return Err(ServiceError::InvalidQuery(
+                    "buffer distance must be greater than zero".to_string(),
+                ));
+            }
+        }
+
+        if plan.rerank != RerankMode::None && !(1..=MAX_RERANK_POOL).contains(&plan.rerank_pool) {
+            return Err(ServiceError::InvalidQuery(format!(
+                "rerank_pool must be between 1 and {}",
+                MAX_RERANK_POOL
+            )));
+        }
+
+        if let Some(grouping) = &plan.group_by_time {
+            if grouping.property.trim().is_empty() {
+                return Err(ServiceError::InvalidQuery(
+                    "group_by_time.property must not be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(filter) = &plan.time_filter {
+            if filter.property.trim().is_empty() {
+                return Err(ServiceError::InvalidQuery(
+                    "time_filter.property must not be empty".to_string(),
+                ));
+            }
+        }
+
+        if let Some(example) = &plan.example {
+            if example.seed_chunks.is_empty() {
+                return Err(ServiceError::ExampleNotIndexed { example: example.source.clone() });
+            }
+            if !plan.semantic_rerank {
+                return Err(ServiceError::InvalidQuery(
+                    "a query by example ranks by similarity and needs semantic_rerank".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve what a query by example resembles into its embedded chunks
+    ///
+    /// A feature contributes every chunk cut from it that is indexed and not
+    /// stale. The chunks' stored embeddings are searched with, so nothing is
+    /// embedded; a feature or chunk without one fails with
+    /// `ExampleNotIndexed`.
+    pub async fn example(&self, seed: ExampleSeed, mode: SeedMode) -> Result<QueryExample> {
+        let (source, chunk_ids) = match seed {
+            ExampleSeed::Feature { dataset_id, feature_id } => {
+                let dataset = self
+                    .spatial_store
+                    .get_dataset(dataset_id)
+                    .await?
+                    .ok_or(GeoragError::DatasetNotFound { name: dataset_id.0.to_string() })?;
+
+                // Feature IDs are store-wide, so look the feature up among the
+                // dataset's own features rather than by ID alone
+                let features = self.spatial_store.get_features_for_dataset(dataset_id).await?;
+                if !features.iter().any(|feature| feature.id == feature_id) {
+                    return Err(ServiceError::FeatureNotFound { dataset_id, feature_id });
+                }
+
+                let chunk_ids = self.document_store.list_chunk_ids().await?;
+                let chunk_ids: Vec<ChunkId> = self
+                    .document_store
+                    .get_chunks(&chunk_ids)
+                    .await?
+                    .into_iter()
+                    .filter(|chunk| chunk.spatial_ref == Some(feature_id) && !chunk.metadata.stale)
+                    .map(|chunk| chunk.id)
+                    .collect();
+                (format!("{}:{}", dataset.name, feature_id.0), chunk_ids)
+            }
+            ExampleSeed::Chunk(chunk_id) => {
+                if self.document_store.get_chunk(chunk_id).await?.is_none() {
+                    return Err(ServiceError::ChunkNotFound { id: chunk_id });
+                }
+                (format!("chunk {}", chunk_id.0), vec![chunk_id])
+            }
+        };
+
+        let mut seed_chunks = Vec::with_capacity(chunk_ids.len());
+        for chunk_id in chunk_ids {
+            if self.vector_store.get_embedding(chunk_id).await?.is_some() {
+                seed_chunks.push(chunk_id);
+            }
+        }
+        if seed_chunks.is_empty() {
+            return Err(ServiceError::ExampleNotIndexed { example: source });
+        }
+
+        Ok(QueryExample { source, seed_chunks, mode })
+    }
+
+    /// Validate and execute a plan with the given embedder
+    ///
+    /// A result without sources carries diagnostics of the likely causes.
+    pub async fn execute<E: Embedder>(&self, plan: &QueryPlan, embedder: E) -> Result<QueryResult> {
+        Self::validate(plan)?;
+        if let Some(state) = &self.index_state {
+            state.check_schema_version()?;
+        }
+        let embedder_model = embedder.model_name().to_string();
+
+        let pipeline = RetrievalPipeline::new(
+            self.spatial_store.clone(),
+            self.vector_store.clone(),
+            self.document_store.clone(),
+            embedder,
+        )
+        .with_redactor(self.redactor.clone())
+        .with_geometry_limits(self.geometry_limits)
+        .with_grounding(self.grounding);
+        let pipeline = match &self.llm_reranker {
+            Some(reranker) => pipeline.with_llm_reranker(reranker.clone()),
+            None => pipeline,
+        };
+        let pipeline = match &self.filter_cache {
+            Some(cache) => pipeline.with_filter_cache(cache.clone()),
+            None => pipeline,
+        };
+        let pipeline = match &self.source_urls {
+            Some(template) => pipeline.with_source_url_template(template.clone()),
+            None => pipeline,
+        };
+        let pipeline = match &self.generator {
+            Some(generator) => pipeline.with_generator(generator.clone()),
+            None => pipeline,
+        };
+        // The baseline only describes scores of the embedder the index was built with;
+        // a query by example is scored with the stored embeddings themselves
+        let baseline = self
+            .index_state
+            .as_ref()
+            .filter(|state| plan.example.is_some() || state.embedder == embedder_model)
+            .and_then(|state| state.score_baseline.clone());
+        let pipeline = match baseline {
+            Some(baseline) => pipeline.with_confidence(baseline, self.confidence_cutoffs),
+            None => pipeline,
+        };
+
+        let mut result = pipeline.execute(plan).await?;
+        if result.sources.is_empty() {
+            result.diagnostics = self.diagnose(plan, &result, &embedder_model).await?;
+        }
+        Ok(result)
+    }
+
+    /// Explain an empty result from the candidate counts and a few store checks
+    ///
+    /// Missing datasets, a mismatched embedder and an empty index are reported
+    /// first; otherwise the first phase that removed every candidate is named.
+    async fn diagnose(
+        &self,
+        plan: &QueryPlan,
+        result: &QueryResult,
+        embedder_model: &str,
+    ) -> Result<Vec<Diagnostic>> {
+        let counts = &result.candidates;
+        let mut diagnostics = Vec::new();
+
+        if self.spatial_store.list_datasets().await?.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::NoDatasets,
+                "The workspace has no datasets",
+                "Add one with 'georag add <file>' or POST /api/v1/ingest",
+            ));
+            return Ok(diagnostics);
+        }
+
+        // A query by example is not embedded, so the embedder cannot be at fault
+        let index_state = self.index_state.as_ref().filter(|_| plan.example.is_none());
+        if let Some(index) = index_state.filter(|i| i.embedder != embedder_model) {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::EmbedderMismatch,
+                format!(
+                    "The index was built with '{}' but the query was embedded with '{}'",
+                    index.embedder, embedder_model
+                ),
+                format!(
+                    "Query with '{}' or rebuild the index with 'georag build --force'",
+                    index.embedder
+                ),
+            ));
+        }
+
+        if counts.indexed_chunks == 0 {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::IndexEmpty,
+                "The index has no chunks",
+                "Run 'georag build' to index the workspace's datasets",
+            ));
+            return Ok(diagnostics);
+        }
+
+        let diagnostic = if counts.features_matched == Some(0) && counts.after_spatial == 0 {
+            let crs = plan.spatial_filter.as_ref().map(|f| f.crs.epsg).unwrap_or(4326);
+            Diagnostic::new(
+                DiagnosticKind::NoFeaturesInFilter,
+                "No features intersect the spatial filter",
+                format!(
+                    "Widen the bbox or filter geometry, and check its coordinates are EPSG:{} lon,lat",
+                    crs
+                ),
+            )
+        } else if counts.after_spatial == 0 {
+            Diagnostic::new(
+                DiagnosticKind::NoChunksInFilter,
+                format!(
+                    "{} features match the spatial filter but none of them has indexed chunks",
+                    counts.features_matched.unwrap_or_default()
+                ),
+                "Run 'georag build' after adding these features, or widen the bbox",
+            )
+        } else if counts.after_visibility == 0 && !plan.datasets.is_empty() {
+            Diagnostic::new(
+                DiagnosticKind::OutsideDatasets,
+                format!(
+                    "{} chunks are in scope but none belongs to the datasets {}",
+                    counts.after_spatial,
+                    plan.datasets.join(", ")
+                ),
+                "Check the dataset names with 'georag status', or search every dataset",
+            )
+        } else if counts.after_visibility == 0 {
+            Diagnostic::new(
+                DiagnosticKind::HiddenDatasets,
+                format!(
+                    "{} chunks are in scope but all belong to datasets hidden from this caller",
+                    counts.after_spatial
+                ),
+                "Use an API key whose tags allow these datasets",
+            )
+        } else if counts.after_text == 0 {
+            Diagnostic::new(
+                DiagnosticKind::TextFilter,
+                format!("The keyword filter removed all {} candidates", counts.after_visibility),
+                "Loosen the required or excluded keywords",
+            )
+        } else if counts.after_attributes == 0 {
+            Diagnostic::new(
+                DiagnosticKind::AttributeFilter,
+                format!("The attribute filters removed all {} candidates", counts.after_text),
+                "Check the property names and values with 'georag dataset sample'",
+            )
+        } else if counts.after_time == 0 {
+            let (property, since) = plan
+                .time_filter
+                .as_ref()
+                .map(|f| (f.property.as_str(), f.since.format("%Y-%m-%d").to_string()))
+                .unwrap_or_default();
+            Diagnostic::new(
+                DiagnosticKind::TimeFilter,
+                format!(
+                    "None of the {} candidates has a {} dated on or after {}",
+                    counts.after_attributes, property, since
+                ),
+                "Move the start date earlier, or check the property holds dates",
+            )
+        } else if result.filtered_by_threshold > 0 {
+            Diagnostic::new(
+                DiagnosticKind::BelowMinScore,
+                format!(
+                    "All {} ranked candidates scored below the minimum score of {:.2}",
+                    result.filtered_by_threshold,
+                    plan.min_score.unwrap_or_default()
+                ),
+                "Lower the minimum score, or explain the query to see the score distribution",
+            )
+        } else {
+            Diagnostic::new(
+                DiagnosticKind::NotInTopMatches,
+                format!(
+                    "{} candidates passed the filters but none is among the {} chunks most \
+                    similar to the query",
+                    counts.after_time, plan.top_k
+                ),
+                "Raise top_k or rephrase the query",
+            )
+        };
+        diagnostics.push(diagnostic);
+
+        Ok(diagnostics)
+    }
+
+    /// Feature geometry of each ranked source
+    pub async fn source_geometries(&self, result: &QueryResult) -> Vec<Option<Geometry>> {
+        export::source_geometries(&result.sources, self.spatial_store.as_ref()).await
+    }
+
+    /// Flat, redacted result rows in `CSV_COLUMNS` order, limited to the
+    /// selected properties
+    pub async fn to_rows(&self, result: &QueryResult) -> Vec<Map<String, Value>> {
+        let geometries = self.source_geometries(result).await;
+
+        result
+            .sources
+            .iter()
+            .zip(&geometries)
+            .map(|(source, geometry)| {
+                let mut row = ResultRow::from_source(source, geometry.as_ref());
+                row.lon = row.lon.map(|lon| self.geometry_output.trim(lon));
+                row.lat = row.lat.map(|lat| self.geometry_output.trim(lat));
+                let mut record = row.to_record();
+                self.redactor.redact_json_properties(&mut record);
+                self.properties.apply(&mut record);
+                record
+            })
+            .collect()
+    }
+
+    /// CSV of the result rows with the selected `CSV_COLUMNS`
+    pub async fn to_csv(&self, result: &QueryResult) -> String {
+        export::to_csv_columns(&self.to_rows(result).await, &self.properties.columns())
+    }
+
+    /// GeoJSON FeatureCollection with one redacted feature per source and the
+    /// attributions of their datasets
+    ///
+    /// Geometries are reduced and trimmed according to the geometry output,
+    /// and properties limited to the selected keys. Features simplified to the vertex budget carry a `dataset_id`, so the
+    /// full geometry can be fetched from the dataset's feature endpoint.
+    pub async fn to_geojson(&self, result: &QueryResult) -> Map<String, Value> {
+        let geometries = self.source_geometries(result).await;
+        let dataset_ids = if geometries.iter().flatten().any(|g| self.geometry_output.simplifies(g))
+        {
+            self.dataset_ids_by_path().await
+        } else {
+            HashMap::new()
+        };
+
+        let features: Vec<Value> = result
+            .sources
+            .iter()
+            .zip(geometries)
+            .map(|(source, geometry)| {
+                let mut properties =
+                    export::source_properties(source, geometry.as_ref(), &self.geometry_output);
+                if geometry.as_ref().is_some_and(|g| self.geometry_output.simplifies(g)) {
+                    if let Some(id) = dataset_ids.get(&source.document_path) {
+                        properties.insert("dataset_id".to_string(), Value::from(id.0));
+                    }
+                }
+                self.redactor.redact_json_properties(&mut properties);
+                self.properties.apply(&mut properties);
+                json!({
+                    "type": "Feature",
+                    "geometry": geometry
+                        .and_then(|g| self.geometry_output.apply(g))
+                        .map(|g| g.to_geojson()),
+                    "properties": properties,
+                })
+            })
+            .collect();
+
+        let mut collection = Map::new();
+        collection.insert("type".to_string(), Value::from("FeatureCollection"));
+        collection.insert("features".to_string(), Value::Array(features));
+        collection.insert("attributions".to_string(), json!(result.attributions));
+        collection
+    }
+
+    /// Dataset IDs keyed by dataset path, the path sources refer to
+    ///
+    /// Datasets that cannot be listed are left out.
+    async fn dataset_ids_by_path(&self) -> HashMap<String, DatasetId> {
+        let mut ids = HashMap::new();
+        let Ok(datasets) = self.spatial_store.list_datasets().await else {
+            return ids;
+        };
+        for meta in datasets {
+            if let Ok(Some(dataset)) = self.spatial_store.get_dataset(meta.id).await {
+                ids.insert(dataset.path.to_string_lossy().to_string(), dataset.id);
+            }
+        }
+        ids
+    }
+
+    /// PNG map of the result geometries, styled by score and fitted to their
+    /// extent, with the attributions of their datasets and of the basemap
+    ///
+    /// Geometries are reduced according to the geometry output. Basemap tiles
+    /// that cannot be fetched are left out, so a map is rendered even when
+    /// the tile server is unreachable.
+    pub async fn to_map_png(&self, result: &QueryResult, options: MapOptions) -> Result<Vec<u8>> {
+        let geometries = self.source_geometries(result).await;
+        let features = result
+            .sources
+            .iter()
+            .zip(geometries)
+            .filter_map(|(source, geometry)| {
+                Some(MapFeature {
+                    geometry: self.geometry_output.apply(geometry?)?,
+                    score: source.score,
+                })
+            })
+            .collect();
+
+        let mut attributions = result.attributions.clone();
+        attributions.extend(self.basemap.as_ref().and_then(|b| b.attribution.clone()));
+        let map = StaticMap::new(features, options).with_attributions(attributions);
+
+        let tiles = match &self.basemap {
+            Some(basemap) => fetch_tiles(basemap, map.tiles()).await,
+            None => Vec::new(),
+        };
+        Ok(map.render(&tiles)?)
+    }
+}
+
+/// Download basemap tiles, skipping those that fail
+async fn fetch_tiles(basemap: &Basemap, tiles: Vec<TileId>) -> Vec<(TileId, Vec<u8>)> {
+    let client = match reqwest::Client::builder()
+        .timeout(TILE_TIMEOUT)
+        .user_agent(concat!("georag/", env!("CARGO_PKG_VERSION")))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot create basemap client, rendering without tiles");
+            return Vec::new();
+        }
+    };
+
+    let mut requests = JoinSet::new();
+    for tile in tiles {
+        let client = client.clone();
+        let url = basemap.tile_url(tile);
+        requests.spawn(async move {
+            let response = client.get(&url).send().await.and_then(|r| r.error_for_status());
+            match response {
+                Ok(response) => match response.bytes().await {
+                    Ok(bytes) => Some((tile, bytes.to_vec())),
+                    Err(e) => {
+                        tracing::warn!(url = %url, error = %e, "Failed to read basemap tile");
+                        None
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!(url = %url, error = %e, "Failed to fetch basemap tile");
+                    None
+                }
+            }
+        });
+    }
+
+    let mut fetched = Vec::new();
+    while let Some(joined) = requests.join_next().await {
+        if let Ok(Some(tile)) = joined {
+            fetched.push(tile);
+        }
+    }
+    fetched
+}
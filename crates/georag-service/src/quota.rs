@@ -0,0 +1,67 @@
+//! Workspace quotas shared by ingest and index builds
+//!
+//! Usage lives in counters kept by the workspace store. Writers check the
+//! quotas against those counters before they store anything and record the
+//! change once the write succeeded; deleting data releases it again.
+
+use georag_core::models::{UsageDelta, WorkspaceId, WorkspaceQuotas, WorkspaceUsage};
+use georag_store::ports::WorkspaceStore;
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// Quotas and usage counters of one workspace
+#[derive(Clone)]
+pub struct WorkspaceQuota {
+    store: Arc<dyn WorkspaceStore>,
+    workspace_id: WorkspaceId,
+    quotas: WorkspaceQuotas,
+}
+
+impl WorkspaceQuota {
+    /// Enforce `quotas` on the workspace's counters in `store`
+    pub fn new(
+        store: Arc<dyn WorkspaceStore>,
+        workspace_id: WorkspaceId,
+        quotas: WorkspaceQuotas,
+    ) -> Self {
+        Self { store, workspace_id, quotas }
+    }
+
+    /// Workspace the counters belong to
+    pub fn workspace_id(&self) -> WorkspaceId {
+        self.workspace_id
+    }
+
+    /// Limits enforced on the workspace
+    pub fn quotas(&self) -> WorkspaceQuotas {
+        self.quotas
+    }
+
+    /// Current usage of the workspace
+    pub async fn usage(&self) -> Result<WorkspaceUsage> {
+        Ok(self.store.get_workspace_usage(self.workspace_id).await?)
+    }
+
+    /// Check that `delta` fits within the quotas, returning the current usage
+    ///
+    /// Fails with `QuotaExceeded` naming the first resource over its limit.
+    pub async fn check(&self, delta: &UsageDelta) -> Result<WorkspaceUsage> {
+        let usage = self.usage().await?;
+        self.quotas.check(&usage, delta)?;
+        Ok(usage)
+    }
+
+    /// Add a completed change to the usage counters
+    pub async fn record(&self, delta: &UsageDelta) -> Result<WorkspaceUsage> {
+        if delta.is_empty() {
+            return self.usage().await;
+        }
+        Ok(self.store.adjust_workspace_usage(self.workspace_id, delta).await?)
+    }
+
+    /// Give back usage recorded for data that was removed again
+    pub async fn release(&self, delta: &UsageDelta) -> Result<WorkspaceUsage> {
+        self.record(&delta.negated()).await
+    }
+}
@@ -0,0 +1,820 @@
+//! Datasets fetched from http(s) URLs
+//!
+//! Both adapters accept a URL wherever they accept a dataset file. The
+//! response is streamed into a temporary directory under a size limit; a
+//! connection that drops mid-body is resumed with a `Range` request, guarded
+//! by `If-Range` so a file that changed meanwhile is fetched again from the
+//! start. Format detection works on file extensions, so the download is
+//! named after the URL or `Content-Disposition` when that name has an
+//! extension the registry reads, and otherwise gets one derived from the
+//! `Content-Type` header or the first bytes of the file.
+//!
+//! The [`RemoteSource`] returned with the file keeps the URL and the
+//! response validators; [`IngestRequest::with_remote`] records it on the
+//! dataset, whose path becomes the URL rather than the temporary file.
+//!
+//! The [`DownloadPolicy`] decides which hosts may be fetched. Unless it
+//! allows private addresses, a host that is or resolves to a loopback,
+//! private, link-local or unspecified address is refused; the check runs on
+//! the addresses the connection is made to, for the requested URL and for
+//! every redirect.
+
+use chrono::Utc;
+use georag_core::formats::FormatRegistry;
+use georag_core::models::RemoteSource;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::header::{self, HeaderMap};
+use reqwest::{redirect, Client, Response, StatusCode, Url};
+use std::error::Error as StdError;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::error::{Result, ServiceError};
+use crate::ingest::IngestRequest;
+
+/// Largest file downloaded by default (512 MiB)
+pub const DEFAULT_MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Bytes at the start of a file looked at when sniffing its format
+const SNIFF_BYTES: usize = 4096;
+
+/// Name of the file the response is written to until its format is known
+const PARTIAL_NAME: &str = "download.part";
+
+/// Limits of a download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadPolicy {
+    /// Largest file downloaded, in bytes
+    pub max_bytes: u64,
+
+    /// Redirects followed before giving up
+    pub max_redirects: usize,
+
+    /// Further attempts after a dropped connection or a 429/5xx response
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for each further one
+    pub backoff: Duration,
+
+    /// Time allowed to connect, and between two reads of the body
+    pub timeout: Duration,
+
+    /// Hosts files may be downloaded from, `None` allowing any; an entry
+    /// `*.example.com` allows the subdomains of `example.com`
+    pub allowed_hosts: Option<Vec<String>>,
+
+    /// Whether hosts may be or resolve to loopback, private, link-local and
+    /// unspecified addresses
+    pub private_addresses: bool,
+}
+
+impl Default for DownloadPolicy {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_DOWNLOAD_BYTES,
+            max_redirects: 10,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            timeout: Duration::from_secs(30),
+            allowed_hosts: None,
+            private_addresses: false,
+        }
+    }
+}
+
+impl DownloadPolicy {
+    /// Set the largest file downloaded, in bytes
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Set the redirects followed before giving up
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Set the retries and the delay before the first one
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the hosts files may be downloaded from; `None` allows any
+    pub fn with_allowed_hosts(mut self, hosts: Option<Vec<String>>) -> Self {
+        self.allowed_hosts = hosts;
+        self
+    }
+
+    /// Set whether hosts may be or resolve to non-public addresses
+    pub fn with_private_addresses(mut self, allowed: bool) -> Self {
+        self.private_addresses = allowed;
+        self
+    }
+
+    /// Whether any URL may be downloaded at all
+    pub fn allows_downloads(&self) -> bool {
+        self.max_bytes > 0 && self.allowed_hosts.as_ref().is_none_or(|hosts| !hosts.is_empty())
+    }
+
+    /// Why `url` may not be fetched, judging by its host alone
+    ///
+    /// Host names are checked against the addresses they resolve to when the
+    /// connection is made.
+    fn refusal(&self, url: &Url) -> Option<String> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        if let Some(allowed) = &self.allowed_hosts {
+            if !allowed.iter().any(|pattern| host_matches(pattern, &host)) {
+                return Some(format!("{} is not an allowed host", host));
+            }
+        }
+        let ip = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok()?;
+        (!self.private_addresses && !is_public(ip))
+            .then(|| format!("{} is not a public address", ip))
+    }
+}
+
+/// Whether `host` matches an allowed host entry
+fn host_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+/// Whether `ip` is neither loopback, private, link-local nor unspecified
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified())
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(mapped) => is_public(IpAddr::V4(mapped)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// A host the policy does not allow, raised from the resolver or the
+/// redirect policy and found again in the source chain of the request error
+#[derive(Debug)]
+struct Refused(String);
+
+impl fmt::Display for Refused {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl StdError for Refused {}
+
+/// Resolver refusing names with any non-public address
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| !is_public(addr.ip())) {
+                let reason =
+                    format!("{} resolves to {}, which is not a public address", host, addr.ip());
+                return Err(Box::new(Refused(reason)) as Box<dyn StdError + Send + Sync>);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Refusal in the source chain of a request error
+fn refused(error: &reqwest::Error) -> Option<String> {
+    let mut source = error.source();
+    while let Some(e) = source {
+        if let Some(refused) = e.downcast_ref::<Refused>() {
+            return Some(refused.0.clone());
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// A downloaded file, deleted with its temporary directory when dropped
+#[derive(Debug)]
+pub struct Download {
+    /// The file, named with an extension the format registry reads when one
+    /// could be determined
+    pub path: PathBuf,
+
+    /// Where the file was downloaded from
+    pub source: RemoteSource,
+
+    _dir: TempDir,
+}
+
+impl Download {
+    /// Ingest request for the file, recording the URL on the dataset
+    pub fn request(&self) -> IngestRequest {
+        IngestRequest::new(&self.path).with_remote(self.source.clone())
+    }
+
+    /// File name of the download
+    pub fn file_name(&self) -> &str {
+        self.path.file_name().and_then(|s| s.to_str()).unwrap_or(PARTIAL_NAME)
+    }
+}
+
+/// Whether a dataset location is an http(s) URL rather than a file path
+pub fn is_remote(location: &str) -> bool {
+    let location = location.trim_start().to_ascii_lowercase();
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Headers of the response the file was written from
+#[derive(Debug, Default)]
+struct ResponseMeta {
+    final_url: Option<Url>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: Option<String>,
+    disposition_name: Option<String>,
+}
+
+impl ResponseMeta {
+    fn from_response(response: &Response) -> Self {
+        let headers = response.headers();
+        Self {
+            final_url: Some(response.url().clone()),
+            etag: header_value(headers, header::ETAG),
+            last_modified: header_value(headers, header::LAST_MODIFIED),
+            content_type: header_value(headers, header::CONTENT_TYPE),
+            disposition_name: header_value(headers, header::CONTENT_DISPOSITION)
+                .and_then(|value| disposition_file_name(&value)),
+        }
+    }
+
+    /// Validator sent with `If-Range`; weak ETags are not allowed there
+    fn validator(&self) -> Option<&str> {
+        self.etag
+            .as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+/// Why an attempt stopped
+enum Failure {
+    /// Worth another attempt, resuming where the file ends
+    Transient(String),
+    Fatal(ServiceError),
+}
+
+/// Download `url` into a temporary directory
+///
+/// Hosts the policy refuses, redirects beyond its limit, files over its size
+/// limit and responses other than 2xx, 429 and 5xx fail at once; the error
+/// names the URL the response came from. Dropped connections and 429/5xx responses are
+/// retried with backoff, resuming from the bytes already written.
+pub async fn fetch(
+    url: &str,
+    formats: &FormatRegistry,
+    policy: &DownloadPolicy,
+) -> Result<Download> {
+    let parsed = Url::parse(url.trim())
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .ok_or_else(|| ServiceError::InvalidUrl(url.to_string()))?;
+    let url = parsed.to_string();
+    if let Some(reason) = policy.refusal(&parsed) {
+        return Err(ServiceError::DownloadRefused { url, reason });
+    }
+
+    let client = client(policy).map_err(|e| download_error(&url, e))?;
+
+    let dir = TempDir::new().map_err(georag_core::error::GeoragError::from)?;
+    let partial = dir.path().join(PARTIAL_NAME);
+    let mut download = Progress::default();
+
+    let mut attempt = 0;
+    loop {
+        match download.attempt(&client, &parsed, &partial, policy).await {
+            Ok(()) => break,
+            Err(Failure::Transient(reason)) if attempt < policy.retries => {
+                let delay = policy.backoff * 2u32.saturating_pow(attempt);
+                attempt += 1;
+                tracing::warn!(
+                    url = %url,
+                    bytes = download.written,
+                    attempt,
+                    reason = %reason,
+                    "Download interrupted, retrying"
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(Failure::Transient(reason)) => {
+                return Err(ServiceError::Download {
+                    url,
+                    reason: format!("{} (gave up after {} retries)", reason, policy.retries),
+                })
+            }
+            Err(Failure::Fatal(e)) => return Err(e),
+        }
+    }
+
+    let head = read_head(&partial).await?;
+    let meta = download.meta;
+    let name = file_name(&parsed, &meta, &head, &formats.supported_formats());
+    let path = dir.path().join(&name);
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(georag_core::error::GeoragError::from)?;
+    tracing::info!(url = %url, bytes = download.written, file = %name, "Downloaded dataset");
+
+    let final_url = meta.final_url.map(|u| u.to_string()).filter(|u| *u != url);
+    Ok(Download {
+        path,
+        source: RemoteSource {
+            url,
+            final_url,
+            etag: meta.etag,
+            last_modified: meta.last_modified,
+            content_type: meta.content_type,
+            size: download.written,
+            fetched_at: Utc::now(),
+        },
+        _dir: dir,
+    })
+}
+
+/// HTTP client checking the host of every redirect, and unless the policy
+/// allows private addresses, the addresses of every connection
+fn client(policy: &DownloadPolicy) -> reqwest::Result<Client> {
+    let hops = policy.clone();
+    let mut client = Client::builder()
+        .redirect(redirect::Policy::custom(move |attempt| {
+            // The first of the previous URLs is the one requested
+            if attempt.previous().len() > hops.max_redirects {
+                return attempt.error(TooManyRedirects);
+            }
+            match hops.refusal(attempt.url()) {
+                Some(reason) => attempt.error(Refused(reason)),
+                None => attempt.follow(),
+            }
+        }))
+        .connect_timeout(policy.timeout)
+        .read_timeout(policy.timeout)
+        .user_agent(concat!("georag/", env!("CARGO_PKG_VERSION")));
+    if !policy.private_addresses {
+        // Through a proxy, the proxy's address would be the one checked
+        client = client.dns_resolver(PublicResolver).no_proxy();
+    }
+    client.build()
+}
+
+/// Bytes written so far and the headers they came with
+#[derive(Default)]
+struct Progress {
+    written: u64,
+    meta: ResponseMeta,
+}
+
+impl Progress {
+    /// Request the rest of the file and append it to `partial`
+    async fn attempt(
+        &mut self,
+        client: &Client,
+        url: &Url,
+        partial: &Path,
+        policy: &DownloadPolicy,
+    ) -> std::result::Result<(), Failure> {
+        let mut request = client.get(url.clone());
+        if self.written > 0 {
+            request = request.header(header::RANGE, format!("bytes={}-", self.written));
+            if let Some(validator) = self.meta.validator() {
+                request = request.header(header::IF_RANGE, validator);
+            }
+        }
+
+        let mut response = request.send().await.map_err(|e| match refused(&e) {
+            Some(reason) => {
+                Failure::Fatal(ServiceError::DownloadRefused { url: url.to_string(), reason })
+            }
+            None if e.is_redirect() => Failure::Fatal(ServiceError::Download {
+                url: url.to_string(),
+                reason: format!(
+                    "more than {} redirects, last at {}",
+                    policy.max_redirects,
+                    e.url().map_or_else(|| url.to_string(), Url::to_string)
+                ),
+            }),
+            None if e.is_builder() => Failure::Fatal(download_error(url.as_str(), e)),
+            None => Failure::Transient(e.to_string()),
+        })?;
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            return Err(Failure::Transient(format!("HTTP {} from {}", status, response.url())));
+        }
+        if !status.is_success() {
+            return Err(Failure::Fatal(ServiceError::Download {
+                url: url.to_string(),
+                reason: format!("HTTP {} from {}", status, response.url()),
+            }));
+        }
+
+        // A 200 answers a failed If-Range with the whole file, which restarts
+        // it; a range starting anywhere but the end of the file is refetched
+        let resumed = status == StatusCode::PARTIAL_CONTENT;
+        if resumed && !resumes_at(response.headers(), self.written) {
+            self.written = 0;
+            return Err(Failure::Transient(format!(
+                "{} resumed at the wrong offset",
+                response.url()
+            )));
+        }
+        if !resumed {
+            self.written = 0;
+            self.meta = ResponseMeta::from_response(&response);
+        }
+
+        let too_large = || {
+            Failure::Fatal(ServiceError::DownloadTooLarge {
+                url: url.to_string(),
+                limit: policy.max_bytes,
+            })
+        };
+        if response
+            .content_length()
+            .is_some_and(|len| self.written + len > policy.max_bytes)
+        {
+            return Err(too_large());
+        }
+
+        let mut file = if resumed {
+            tokio::fs::OpenOptions::new().append(true).open(partial).await
+        } else {
+            tokio::fs::File::create(partial).await
+        }
+        .map_err(|e| Failure::Fatal(georag_core::error::GeoragError::from(e).into()))?;
+
+        let outcome = loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    if self.written + chunk.len() as u64 > policy.max_bytes {
+                        break Err(too_large());
+                    }
+                    if let Err(e) = file.write_all(&chunk).await {
+                        break Err(Failure::Fatal(georag_core::error::GeoragError::from(e).into()));
+                    }
+                    self.written += chunk.len() as u64;
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(Failure::Transient(e.to_string())),
+            }
+        };
+
+        // The bytes counted as written must be on disk before a resume
+        file.flush()
+            .await
+            .map_err(|e| Failure::Fatal(georag_core::error::GeoragError::from(e).into()))?;
+        outcome
+    }
+}
+
+/// Redirect chain longer than the policy allows
+#[derive(Debug)]
+struct TooManyRedirects;
+
+impl fmt::Display for TooManyRedirects {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("too many redirects")
+    }
+}
+
+impl StdError for TooManyRedirects {}
+
+/// Whether a 206 response's `Content-Range` starts at `offset`
+fn resumes_at(headers: &HeaderMap, offset: u64) -> bool {
+    header_value(headers, header::CONTENT_RANGE)
+        .and_then(|range| {
+            let range = range.strip_prefix("bytes ")?;
+            range.split_once('-')?.0.trim().parse::<u64>().ok()
+        })
+        .is_some_and(|start| start == offset)
+}
+
+/// First bytes of the downloaded file
+async fn read_head(path: &Path) -> Result<Vec<u8>> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(georag_core::error::GeoragError::from)?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    file.take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await
+        .map_err(georag_core::error::GeoragError::from)?;
+    Ok(head)
+}
+
+fn header_value(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string())
+}
+
+fn download_error(url: &str, e: reqwest::Error) -> ServiceError {
+    ServiceError::Download {
+        url: url.to_string(),
+        reason: e.to_string(),
+    }
+}
+
+/// Name for the downloaded file
+///
+/// The first of the `Content-Disposition` name, the last segment of the
+/// final URL and that of the requested URL whose extension is supported is
+/// kept. Otherwise the first name found is given an extension from the
+/// content type or the content; with neither it keeps its own, and format
+/// detection reports it as unsupported.
+fn file_name(url: &Url, meta: &ResponseMeta, head: &[u8], supported: &[String]) -> String {
+    let supports = |ext: &str| supported.iter().any(|s| s.eq_ignore_ascii_case(ext));
+    let candidates: Vec<String> = [
+        meta.disposition_name.clone(),
+        meta.final_url.as_ref().and_then(last_segment),
+        last_segment(url),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if let Some(name) = candidates.iter().find(|name| extension(name).is_some_and(supports)) {
+        return name.clone();
+    }
+
+    let name = candidates.first().cloned().unwrap_or_else(|| "download".to_string());
+    let sniffed = meta
+        .content_type
+        .as_deref()
+        .and_then(content_type_extension)
+        .filter(|ext| supports(ext))
+        .or_else(|| sniff_extension(head).filter(|ext| supports(ext)));
+    match sniffed {
+        Some(ext) => format!("{}.{}", name, ext),
+        None => name,
+    }
+}
+
+fn extension(name: &str) -> Option<&str> {
+    Path::new(name).extension().and_then(|e| e.to_str())
+}
+
+/// Last non-empty path segment of a URL, percent-decoded
+fn last_segment(url: &Url) -> Option<String> {
+    let segment = url.path_segments()?.rev().find(|s| !s.is_empty())?;
+    Some(safe_name(&percent_decode(segment))).filter(|name| !name.is_empty())
+}
+
+/// File name given by a `Content-Disposition` header, preferring `filename*`
+fn disposition_file_name(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for param in value.split(';').skip(1) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename" => plain = Some(value.to_string()),
+            // RFC 5987: charset'language'percent-encoded-name
+            "filename*" => extended = value.splitn(3, '\'').nth(2).map(percent_decode),
+            _ => {}
+        }
+    }
+    extended.or(plain).map(|name| safe_name(&name)).filter(|name| !name.is_empty())
+}
+
+/// Keep a server-supplied name inside the download directory
+fn safe_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    name.trim_start_matches('.').trim().to_string()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Extension for a `Content-Type`, for the types that name a single format
+fn content_type_extension(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "application/geo+json" | "application/vnd.geo+json" | "application/json" => Some("geojson"),
+        "application/gpx+xml" => Some("gpx"),
+        "application/vnd.google-earth.kml+xml" => Some("kml"),
+        "application/pdf" => Some("pdf"),
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some("docx"),
+        _ => None,
+    }
+}
+
+/// Extension for the first bytes of a file
+fn sniff_extension(head: &[u8]) -> Option<&'static str> {
+    if head.starts_with(b"%PDF-") {
+        return Some("pdf");
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        let contains = |needle: &[u8]| head.windows(needle.len()).any(|w| w == needle);
+        return (contains(b"word/") || contains(b"[Content_Types].xml")).then_some("docx");
+    }
+
+    let text = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace())?;
+    match text[start] {
+        b'{' | b'[' => Some("geojson"),
+        b'<' => {
+            let text = String::from_utf8_lossy(text).to_ascii_lowercase();
+            if text.contains("<gpx") {
+                Some("gpx")
+            } else if text.contains("<kml") {
+                Some("kml")
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn supported() -> Vec<String> {
+        ["json", "geojson", "gpx", "kml", "pdf", "docx", "shp"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn meta(content_type: Option<&str>, disposition: Option<&str>) -> ResponseMeta {
+        ResponseMeta {
+            content_type: content_type.map(String::from),
+            disposition_name: disposition.and_then(disposition_file_name),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_remote() {
+        assert!(is_remote("https://example.com/roads.geojson"));
+        assert!(is_remote("HTTP://example.com/roads"));
+        assert!(!is_remote("data/roads.geojson"));
+        assert!(!is_remote("ftp://example.com/roads.geojson"));
+    }
+
+    #[test]
+    fn test_url_name_with_supported_extension_is_kept() {
+        let url = Url::parse("https://example.com/data/Port%20Parcels.geojson?v=2").unwrap();
+        let name = file_name(&url, &meta(Some("text/plain"), None), b"%PDF-1.7", &supported());
+        assert_eq!(name, "Port Parcels.geojson");
+    }
+
+    #[test]
+    fn test_missing_extension_comes_from_content_type_then_content() {
+        let url = Url::parse("https://example.com/api/export").unwrap();
+        let name = file_name(&url, &meta(Some("application/pdf"), None), b"{", &supported());
+        assert_eq!(name, "export.pdf");
+
+        let octet = meta(Some("application/octet-stream"), None);
+        assert_eq!(
+            file_name(&url, &octet, b"\xEF\xBB\xBF  {\"type\"", &supported()),
+            "export.geojson"
+        );
+        assert_eq!(
+            file_name(
+                &url,
+                &octet,
+                b"<?xml version=\"1.0\"?>\n<gpx version=\"1.1\">",
+                &supported()
+            ),
+            "export.gpx"
+        );
+        assert_eq!(file_name(&url, &octet, b"<kml xmlns=\"\">", &supported()), "export.kml");
+        assert_eq!(
+            file_name(&url, &octet, b"PK\x03\x04....[Content_Types].xml", &supported()),
+            "export.docx"
+        );
+
+        // Unknown content keeps the name, for format detection to reject
+        assert_eq!(file_name(&url, &octet, b"id,name\n1,a", &supported()), "export");
+        let root = Url::parse("https://example.com/").unwrap();
+        assert_eq!(file_name(&root, &octet, b"%PDF-1.4", &supported()), "download.pdf");
+    }
+
+    #[test]
+    fn test_content_disposition_names() {
+        let url = Url::parse("https://example.com/download?id=7").unwrap();
+        let named = meta(None, Some("attachment; filename=\"roads 2024.kml\""));
+        assert_eq!(file_name(&url, &named, b"", &supported()), "roads 2024.kml");
+
+        assert_eq!(
+            disposition_file_name("attachment; filename=\"a.pdf\"; filename*=UTF-8''caf%C3%A9.pdf"),
+            Some("café.pdf".to_string())
+        );
+        assert_eq!(
+            disposition_file_name("attachment; filename=\"../../etc/passwd\""),
+            Some("passwd".to_string())
+        );
+        assert_eq!(disposition_file_name("inline"), None);
+    }
+
+    #[test]
+    fn test_hosts_outside_the_policy_are_refused() {
+        let url = |u: &str| Url::parse(u).unwrap();
+        let policy = DownloadPolicy::default();
+        for private in [
+            "http://127.0.0.1/parks.geojson",
+            "http://10.1.2.3/parks.geojson",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:192.168.0.1]/",
+        ] {
+            assert!(policy.refusal(&url(private)).is_some(), "{}", private);
+        }
+        assert_eq!(policy.refusal(&url("https://data.example.com/parks")), None);
+        assert_eq!(policy.refusal(&url("http://8.8.8.8/parks")), None);
+        let local = policy.clone().with_private_addresses(true);
+        assert_eq!(local.refusal(&url("http://127.0.0.1/parks.geojson")), None);
+
+        let listed = policy.with_allowed_hosts(Some(vec![
+            "data.example.com".to_string(),
+            "*.cdn.example.org".to_string(),
+        ]));
+        assert_eq!(listed.refusal(&url("https://DATA.example.com/parks")), None);
+        assert_eq!(listed.refusal(&url("https://eu.cdn.example.org/parks")), None);
+        assert!(listed.refusal(&url("https://cdn.example.org/parks")).is_some());
+        assert!(listed.refusal(&url("https://evil-data.example.com/parks")).is_some());
+        assert!(!listed.clone().with_allowed_hosts(Some(Vec::new())).allows_downloads());
+    }
+
+    #[tokio::test]
+    async fn test_redirects_to_private_addresses_are_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut head = [0u8; 1024];
+                let n = socket.read(&mut head).await.unwrap_or(0);
+                let target = if String::from_utf8_lossy(&head[..n]).starts_with("GET /name") {
+                    format!("http://localhost:{}/admin", addr.port())
+                } else {
+                    format!("http://{}/admin", addr)
+                };
+                let reply = format!(
+                    "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\
+                     Connection: close\r\n\r\n",
+                    target
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+
+        // A connection to an IP literal skips the resolver, so only the check
+        // `fetch` makes first stops the request to the test server itself
+        let client = client(&DownloadPolicy::default()).unwrap();
+        let err = client.get(format!("http://{}/ip", addr)).send().await.unwrap_err();
+        assert_eq!(refused(&err), Some("127.0.0.1 is not a public address".to_string()));
+
+        let err = client.get(format!("http://{}/name", addr)).send().await.unwrap_err();
+        let reason = refused(&err).unwrap_or_default();
+        assert!(reason.starts_with("localhost resolves to"), "{:?}", err);
+    }
+}
@@ -0,0 +1,88 @@
+//! Inferred dataset schemas for data dictionaries
+//!
+//! The schema of a dataset is inferred from its stored features each time it
+//! is asked for, so it always describes what the store holds. Example values
+//! are masked by the same redaction rules as sampled features.
+
+use georag_core::models::{ChunkingSettings, DatasetId, DatasetMeta, DatasetSchema, SchemaBuilder};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::redaction::{Redactor, REDACTED_MARKER};
+use georag_store::ports::SpatialStore;
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::error::{Result, ServiceError};
+
+/// Service inferring the schemas of stored datasets
+pub struct SchemaService {
+    spatial_store: Arc<dyn SpatialStore>,
+    chunking: ChunkingSettings,
+    redactor: Redactor,
+}
+
+impl SchemaService {
+    /// Create a schema service reporting the default word-based chunking
+    pub fn new(spatial_store: Arc<dyn SpatialStore>) -> Self {
+        Self {
+            spatial_store,
+            chunking: ChunkingSettings::from(&ChunkGenerator::default()),
+            redactor: Redactor::default(),
+        }
+    }
+
+    /// Report these chunking settings with every schema
+    pub fn with_chunking(mut self, chunking: ChunkingSettings) -> Self {
+        self.chunking = chunking;
+        self
+    }
+
+    /// Redact example values with these rules
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Infer the schema of one dataset
+    pub async fn dataset_schema(&self, id: DatasetId) -> Result<DatasetSchema> {
+        let dataset = self
+            .spatial_store
+            .get_dataset(id)
+            .await?
+            .ok_or(ServiceError::DatasetNotFound { id })?;
+
+        let mut builder = SchemaBuilder::new();
+        for feature in self.spatial_store.get_features_for_dataset(id).await? {
+            builder.add(&feature);
+        }
+        let mut schema = builder.finish(&dataset, self.chunking.clone());
+        self.redact_examples(&mut schema);
+        Ok(schema)
+    }
+
+    /// Mask example values the way sampled features are masked
+    ///
+    /// Types are inferred from the stored values, so a redacted property
+    /// keeps its type and shows a single masked example.
+    fn redact_examples(&self, schema: &mut DatasetSchema) {
+        for property in &mut schema.properties {
+            if self.redactor.is_redacted_property(&property.key) {
+                property.examples = vec![Value::String(REDACTED_MARKER.to_string())];
+                continue;
+            }
+            for example in &mut property.examples {
+                if let Value::String(text) = example {
+                    *text = self.redactor.redact_text(text);
+                }
+            }
+        }
+    }
+
+    /// Infer the schemas of several datasets, in the order given
+    pub async fn schemas(&self, datasets: &[DatasetMeta]) -> Result<Vec<DatasetSchema>> {
+        let mut schemas = Vec::with_capacity(datasets.len());
+        for dataset in datasets {
+            schemas.push(self.dataset_schema(dataset.id).await?);
+        }
+        Ok(schemas)
+    }
+}
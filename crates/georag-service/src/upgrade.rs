@@ -0,0 +1,136 @@
+//! Index schema upgrades
+//!
+//! Every build writes the schema version of its index state, and loading an
+//! index from an older version refuses until it is upgraded. Each schema
+//! change registers a step from its previous version, like a migration, and
+//! an upgrade runs the steps between the found version and the current one
+//! in order. Indexes from a newer version are never touched.
+
+use georag_core::error::GeoragError;
+use georag_core::models::{IndexState, INDEX_SCHEMA_VERSION, UNVERSIONED_INDEX_SCHEMA};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::index_stats::IndexStatsService;
+
+/// What an upgrade step changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UpgradeAction {
+    /// Rebuild the per-dataset chunk and embedding counters
+    RecountIndexStats,
+}
+
+/// Step upgrading an index from one schema version to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpgradeStep {
+    /// Schema version the step upgrades from
+    pub from: u32,
+
+    /// What the step does, for plans and progress output
+    pub description: &'static str,
+
+    action: UpgradeAction,
+}
+
+impl UpgradeStep {
+    /// Schema version the step upgrades to
+    pub fn to(&self) -> u32 {
+        self.from + 1
+    }
+}
+
+/// Registered upgrade steps, one per schema version
+pub const UPGRADE_STEPS: &[UpgradeStep] = &[UpgradeStep {
+    from: UNVERSIONED_INDEX_SCHEMA,
+    description: "Count every dataset's chunks and embeddings into the index counters",
+    action: UpgradeAction::RecountIndexStats,
+}];
+
+/// Outcome of an upgrade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradeReport {
+    /// Schema version before the upgrade
+    pub from: u32,
+
+    /// Schema version after the upgrade
+    pub to: u32,
+
+    /// Steps that ran, in order
+    pub steps: Vec<UpgradeStep>,
+}
+
+/// Service upgrading an index to the current schema version
+pub struct UpgradeService {
+    index_stats: IndexStatsService,
+}
+
+impl UpgradeService {
+    /// Create a service over the workspace's document and vector stores
+    pub fn new(document_store: Arc<dyn DocumentStore>, vector_store: Arc<dyn VectorStore>) -> Self {
+        Self {
+            index_stats: IndexStatsService::new(document_store, vector_store),
+        }
+    }
+
+    /// Steps upgrading an index of `version` to the current schema
+    ///
+    /// Empty for a current index; an index from a newer version is refused.
+    pub fn pending(version: u32) -> Result<Vec<UpgradeStep>> {
+        if version > INDEX_SCHEMA_VERSION {
+            return Err(GeoragError::IndexSchemaTooNew {
+                found: version,
+                supported: INDEX_SCHEMA_VERSION,
+            }
+            .into());
+        }
+        Ok(UPGRADE_STEPS.iter().filter(|step| step.from >= version).copied().collect())
+    }
+
+    /// Run the pending steps and record the current version in `state`
+    ///
+    /// `on_step` is called before each step runs. The state's version is
+    /// bumped after each step, so a failed upgrade leaves it at the last
+    /// version reached and can be resumed.
+    pub async fn upgrade(
+        &self,
+        state: &mut IndexState,
+        mut on_step: impl FnMut(&UpgradeStep),
+    ) -> Result<UpgradeReport> {
+        let from = state.schema_version;
+        let steps = Self::pending(from)?;
+        for step in &steps {
+            on_step(step);
+            match step.action {
+                UpgradeAction::RecountIndexStats => {
+                    self.index_stats.recount().await?;
+                }
+            }
+            state.schema_version = step.to();
+        }
+
+        Ok(UpgradeReport { from, to: state.schema_version, steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_lead_from_unversioned_to_current_schema() {
+        let mut version = UNVERSIONED_INDEX_SCHEMA;
+        for step in UPGRADE_STEPS {
+            assert_eq!(step.from, version);
+            version = step.to();
+        }
+        assert_eq!(version, INDEX_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_pending_steps() {
+        assert_eq!(UpgradeService::pending(UNVERSIONED_INDEX_SCHEMA).unwrap(), UPGRADE_STEPS);
+        assert!(UpgradeService::pending(INDEX_SCHEMA_VERSION).unwrap().is_empty());
+        assert!(UpgradeService::pending(INDEX_SCHEMA_VERSION + 1).is_err());
+    }
+}
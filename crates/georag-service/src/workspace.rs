@@ -0,0 +1,349 @@
+//! Creating workspaces and changing their settings
+//!
+//! A workspace is created with a full set of settings, checked the same way
+//! `georag config check` checks a config file. The CRS, distance unit and
+//! validity mode become the workspace's configuration and every setting is
+//! stored. Later changes are checked again, and settings that stored data
+//! depends on are locked once there is such data: the CRS once features are
+//! stored and the embedder's dimensions once an index is built. Query
+//! presets are checked the way query requests with their options are.
+//! Setting, changing or removing the redaction rules is recorded as an
+//! audit event of the workspace.
+
+use chrono::Utc;
+use georag_core::config::{CloneProvenance, LayeredConfig, WorkspaceSettings};
+use georag_core::llm::factory::EmbedderSpec;
+use georag_core::models::{
+    normalize_preset_name, AuditEvent, AuditEventKind, IndexState, QueryPreset, WorkspaceConfig,
+    WorkspaceId, WorkspaceMeta,
+};
+use georag_core::redaction::RedactionConfig;
+use georag_store::ports::WorkspaceStore;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::audit::AuditService;
+use crate::error::{Result, ServiceError};
+use crate::presets::validate_presets;
+
+/// CRS of a workspace whose settings leave it unset (WGS 84)
+pub const DEFAULT_WORKSPACE_CRS: u32 = 4326;
+
+/// A workspace and the settings stored for it
+#[derive(Debug, Clone)]
+pub struct WorkspaceView {
+    pub meta: WorkspaceMeta,
+    pub settings: WorkspaceSettings,
+}
+
+impl WorkspaceView {
+    /// Configuration in effect: the stored settings over the defaults
+    pub fn effective(&self) -> LayeredConfig {
+        LayeredConfig::with_defaults().load_from_store(&self.settings)
+    }
+}
+
+/// Check the settings of a new workspace and derive its configuration
+///
+/// Unset CRS, distance unit and validity mode take their defaults.
+pub fn new_workspace_config(settings: &WorkspaceSettings) -> Result<WorkspaceConfig> {
+    settings.validate().map_err(ServiceError::InvalidSettings)?;
+    validate_presets(settings)?;
+    Ok(WorkspaceConfig {
+        crs: settings.crs.unwrap_or(DEFAULT_WORKSPACE_CRS),
+        distance_unit: settings.distance_unit.unwrap_or_default(),
+        geometry_validity: settings.geometry_validity.unwrap_or_default(),
+    })
+}
+
+/// Service creating workspaces and updating their settings
+pub struct WorkspaceService {
+    store: Arc<dyn WorkspaceStore>,
+    audit: Option<AuditService>,
+}
+
+impl WorkspaceService {
+    /// Create a workspace service over the workspace store
+    pub fn new(store: Arc<dyn WorkspaceStore>) -> Self {
+        Self { store, audit: None }
+    }
+
+    /// Record changes of the redaction rules with `audit`
+    pub fn with_audit(mut self, audit: AuditService) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Create a workspace with the given settings
+    ///
+    /// Names must be unique. The stored settings include the CRS, distance
+    /// unit and validity mode the workspace was created with, defaults
+    /// filled in.
+    pub async fn create(&self, name: &str, settings: &WorkspaceSettings) -> Result<WorkspaceView> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ServiceError::InvalidWorkspaceName(
+                "workspace name must not be empty".to_string(),
+            ));
+        }
+        let config = new_workspace_config(settings)?;
+
+        if self.store.list_workspaces().await?.iter().any(|w| w.name == name) {
+            return Err(ServiceError::WorkspaceExists { name: name.to_string() });
+        }
+
+        let id = self.store.create_workspace(name, &config).await?;
+        let settings = WorkspaceSettings {
+            crs: Some(config.crs),
+            distance_unit: Some(config.distance_unit),
+            geometry_validity: Some(config.geometry_validity),
+            ..settings.clone()
+        };
+        self.store.save_workspace_settings(id, &settings).await?;
+        self.record_redaction_change(id, None, settings.redaction.as_ref()).await;
+
+        self.get(id).await
+    }
+
+    /// Create a workspace named `name` with the settings of `source`
+    ///
+    /// The settings of the new workspace record which workspace it was
+    /// cloned from and when. Its data is copied separately, see
+    /// [`WorkspaceCloneService`](crate::clone::WorkspaceCloneService).
+    pub async fn create_clone(&self, source: WorkspaceId, name: &str) -> Result<WorkspaceView> {
+        let source = self.get(source).await?;
+        let settings = WorkspaceSettings {
+            cloned_from: Some(CloneProvenance {
+                workspace_id: source.meta.id.to_string(),
+                workspace: source.meta.name,
+                cloned_at: Utc::now(),
+            }),
+            ..source.settings
+        };
+        self.create(name, &settings).await
+    }
+
+    /// A workspace and its stored settings
+    ///
+    /// Workspaces without stored settings report the configuration they
+    /// were created with.
+    pub async fn get(&self, id: WorkspaceId) -> Result<WorkspaceView> {
+        let meta = self
+            .store
+            .get_workspace(id)
+            .await?
+            .ok_or(ServiceError::WorkspaceNotFound { id })?;
+        let settings = match self.store.get_workspace_settings(id).await? {
+            Some(settings) => settings,
+            None => WorkspaceSettings::from_workspace_config(&workspace_config(&meta)),
+        };
+        Ok(WorkspaceView { meta, settings })
+    }
+
+    /// Change the settings given in `patch`, keeping the others
+    ///
+    /// `index` is the workspace's built index, if any.
+    pub async fn update(
+        &self,
+        id: WorkspaceId,
+        patch: &WorkspaceSettings,
+        index: Option<&IndexState>,
+    ) -> Result<WorkspaceView> {
+        let current = self.get(id).await?;
+        let settings = current.settings.merged(patch)?;
+        self.apply(current, settings, index).await
+    }
+
+    /// Replace all stored settings; settings left out are unset
+    ///
+    /// `index` is the workspace's built index, if any.
+    pub async fn replace(
+        &self,
+        id: WorkspaceId,
+        settings: &WorkspaceSettings,
+        index: Option<&IndexState>,
+    ) -> Result<WorkspaceView> {
+        let current = self.get(id).await?;
+        self.apply(current, settings.clone(), index).await
+    }
+
+    /// Save a query preset, replacing any preset of the same name
+    ///
+    /// The name is normalized like an area name. Returns the normalized name
+    /// and the workspace with the preset saved.
+    pub async fn save_preset(
+        &self,
+        id: WorkspaceId,
+        name: &str,
+        preset: &QueryPreset,
+        index: Option<&IndexState>,
+    ) -> Result<(String, WorkspaceView)> {
+        let name = normalize_preset_name(name)
+            .map_err(|reason| ServiceError::InvalidPreset { name: name.to_string(), reason })?;
+        let current = self.get(id).await?;
+        let mut settings = current.settings.clone();
+        settings
+            .query_presets
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name.clone(), preset.clone());
+        let view = self.apply(current, settings, index).await?;
+        Ok((name, view))
+    }
+
+    /// Delete a query preset; a default preset stops being the default
+    pub async fn delete_preset(
+        &self,
+        id: WorkspaceId,
+        name: &str,
+        index: Option<&IndexState>,
+    ) -> Result<WorkspaceView> {
+        let name = normalize_preset_name(name).unwrap_or_else(|_| name.to_string());
+        let current = self.get(id).await?;
+        let mut settings = current.settings.clone();
+        if settings
+            .query_presets
+            .as_mut()
+            .and_then(|presets| presets.remove(&name))
+            .is_none()
+        {
+            return Err(ServiceError::PresetNotFound { name });
+        }
+        if settings.default_query_preset.as_deref() == Some(name.as_str()) {
+            settings.default_query_preset = None;
+        }
+        self.apply(current, settings, index).await
+    }
+
+    /// Make a query preset the default of queries naming none, or clear the default
+    pub async fn set_default_preset(
+        &self,
+        id: WorkspaceId,
+        name: Option<&str>,
+        index: Option<&IndexState>,
+    ) -> Result<WorkspaceView> {
+        let current = self.get(id).await?;
+        let mut settings = current.settings.clone();
+        settings.default_query_preset = match name {
+            Some(name) => {
+                let name = normalize_preset_name(name).unwrap_or_else(|_| name.to_string());
+                if !settings.query_presets.as_ref().is_some_and(|p| p.contains_key(&name)) {
+                    return Err(ServiceError::PresetNotFound { name });
+                }
+                Some(name)
+            }
+            None => None,
+        };
+        self.apply(current, settings, index).await
+    }
+
+    async fn apply(
+        &self,
+        current: WorkspaceView,
+        settings: WorkspaceSettings,
+        index: Option<&IndexState>,
+    ) -> Result<WorkspaceView> {
+        settings.validate().map_err(ServiceError::InvalidSettings)?;
+        validate_presets(&settings)?;
+
+        let id = current.meta.id;
+        let features = self.store.get_workspace_usage(id).await?.features;
+        check_locked_settings(&current.meta, &settings, features, index)?;
+
+        // Unset values keep what the workspace was created with
+        let config = WorkspaceConfig {
+            crs: settings.crs.unwrap_or(current.meta.crs),
+            distance_unit: settings.distance_unit.unwrap_or(current.meta.distance_unit),
+            geometry_validity: settings.geometry_validity.unwrap_or(current.meta.geometry_validity),
+        };
+        if config.crs != current.meta.crs
+            || config.distance_unit != current.meta.distance_unit
+            || config.geometry_validity != current.meta.geometry_validity
+        {
+            self.store.update_workspace_config(id, &config).await?;
+        }
+        self.store.save_workspace_settings(id, &settings).await?;
+        self.record_redaction_change(
+            id,
+            current.settings.redaction.as_ref(),
+            settings.redaction.as_ref(),
+        )
+        .await;
+
+        self.get(id).await
+    }
+
+    /// Record an audit event when the redaction rules differ from `before`
+    async fn record_redaction_change(
+        &self,
+        id: WorkspaceId,
+        before: Option<&RedactionConfig>,
+        after: Option<&RedactionConfig>,
+    ) {
+        let Some(audit) = &self.audit else {
+            return;
+        };
+        let empty = RedactionConfig::default();
+        let (before, after) = (before.unwrap_or(&empty), after.unwrap_or(&empty));
+        if before == after {
+            return;
+        }
+        let event = AuditEvent::new(AuditEventKind::RedactionChanged).with_details(format!(
+            "{} properties, {} patterns",
+            after.properties.len(),
+            after.patterns.len()
+        ));
+        audit.record(id, event).await;
+    }
+}
+
+/// Refuse settings that would no longer match the workspace's stored data
+///
+/// `features` is the number of features stored in the workspace and `index`
+/// its built index, if any.
+pub fn check_locked_settings(
+    workspace: &WorkspaceMeta,
+    settings: &WorkspaceSettings,
+    features: u64,
+    index: Option<&IndexState>,
+) -> Result<()> {
+    if let Some(crs) = settings.crs {
+        if crs != workspace.crs && features > 0 {
+            return Err(ServiceError::SettingLocked {
+                key: "crs".to_string(),
+                reason: format!(
+                    "the workspace stores {} features in EPSG:{}, which would not be \
+                     reprojected to EPSG:{}; create a new workspace with that CRS or \
+                     delete the datasets first",
+                    features, workspace.crs, crs
+                ),
+            });
+        }
+    }
+
+    if let (Some(embedder), Some(index)) = (&settings.embedder, index) {
+        let dimensions = EmbedderSpec::parse(embedder)
+            .map_err(ServiceError::InvalidSettings)?
+            .dimensions();
+        if dimensions != index.embedding_dim {
+            return Err(ServiceError::SettingLocked {
+                key: "embedder".to_string(),
+                reason: format!(
+                    "'{}' produces {}-dimensional embeddings but the index was built by '{}' \
+                     with {}; delete the index before switching to an embedder with other \
+                     dimensions",
+                    embedder, dimensions, index.embedder, index.embedding_dim
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn workspace_config(meta: &WorkspaceMeta) -> WorkspaceConfig {
+    WorkspaceConfig {
+        crs: meta.crs,
+        distance_unit: meta.distance_unit,
+        geometry_validity: meta.geometry_validity,
+    }
+}
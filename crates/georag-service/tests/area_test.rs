@@ -0,0 +1,126 @@
+//! Integration tests for the saved area service
+
+mod common;
+
+use georag_core::models::{Dataset, Feature, FeatureId, Geometry, GeometryType, WorkspaceId};
+use georag_service::{AreaService, ServiceError};
+use georag_store::memory::{MemoryAreaStore, MemorySpatialStore};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn dataset(name: &str) -> Dataset {
+    Dataset {
+        geometry_type: GeometryType::Polygon,
+        ..common::dataset(name)
+    }
+}
+
+fn service(store: Arc<MemorySpatialStore>) -> AreaService {
+    AreaService::new(Arc::new(MemoryAreaStore::new()), store)
+}
+
+#[tokio::test]
+async fn test_create_normalizes_name_and_geometry() {
+    let service = service(Arc::new(MemorySpatialStore::new()));
+    let workspace = WorkspaceId::new();
+
+    // Open ring with a repeated vertex, wrapped in a Feature
+    let geojson = json!({
+        "type": "Feature",
+        "properties": {},
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[106.80, -6.20], [106.85, -6.20], [106.85, -6.20], [106.85, -6.15], [106.80, -6.15]]]
+        }
+    });
+    let geometry = AreaService::parse_geometry(&geojson).unwrap();
+    let area = service.create(workspace, " Project-X ", &geometry).await.unwrap();
+
+    assert_eq!(area.name, "project-x");
+    assert_eq!(
+        area.geometry,
+        Geometry::polygon(vec![vec![
+            [106.80, -6.20],
+            [106.85, -6.20],
+            [106.85, -6.15],
+            [106.80, -6.15],
+            [106.80, -6.20],
+        ]])
+    );
+    assert_eq!(area.vertex_count(), 5);
+
+    let resolved = service.resolve(workspace, "PROJECT-X").await.unwrap();
+    assert_eq!(resolved.geometry, area.geometry);
+}
+
+#[tokio::test]
+async fn test_create_rejects_invalid_areas() {
+    let service = service(Arc::new(MemorySpatialStore::new()));
+    let workspace = WorkspaceId::new();
+    let square = Geometry::polygon(vec![vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]]);
+
+    let err = service.create(workspace, "bad name", &square).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidArea(_)));
+
+    let degenerate = Geometry::polygon(vec![vec![[0.0, 0.0], [1.0, 0.0], [1.0, 0.0]]]);
+    let err = service.create(workspace, "sliver", &degenerate).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidArea(_)));
+
+    let err = AreaService::parse_geometry(&json!({"type": "Circle", "radius": 3})).unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidArea(_)));
+
+    service.create(workspace, "site", &square).await.unwrap();
+    let err = service.create(workspace, "Site", &square).await.unwrap_err();
+    assert!(matches!(err, ServiceError::AreaExists { ref name } if name == "site"));
+}
+
+#[tokio::test]
+async fn test_create_from_feature_records_source() {
+    let store = Arc::new(MemorySpatialStore::new());
+    let id = store.store_dataset(&dataset("parcels")).await.unwrap();
+    let boundary = Geometry::polygon(vec![vec![[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 0.0]]]);
+    let feature = Feature::with_geometry(FeatureId(41), boundary.clone(), HashMap::new(), 4326);
+    store.store_features(std::slice::from_ref(&feature)).await.unwrap();
+    store.associate_features_with_dataset(id, vec![feature.id]);
+    let other = store.store_dataset(&dataset("roads")).await.unwrap();
+
+    let service = service(store);
+    let workspace = WorkspaceId::new();
+
+    let area = service
+        .create_from_feature(workspace, "parcel-41", id, FeatureId(41))
+        .await
+        .unwrap();
+    assert_eq!(area.geometry, boundary);
+    let source = area.source.unwrap();
+    assert_eq!((source.dataset_id, source.feature_id), (id, FeatureId(41)));
+
+    // The feature must belong to the named dataset
+    let err = service.create_from_feature(workspace, "elsewhere", other, FeatureId(41)).await;
+    assert!(matches!(err, Err(ServiceError::FeatureNotFound { .. })));
+}
+
+#[tokio::test]
+async fn test_list_and_delete() {
+    let service = service(Arc::new(MemorySpatialStore::new()));
+    let workspace = WorkspaceId::new();
+    service.create(workspace, "b", &Geometry::point(1.0, 1.0)).await.unwrap();
+    service.create(workspace, "a", &Geometry::point(0.0, 0.0)).await.unwrap();
+
+    let names: Vec<String> = service
+        .list(workspace)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|area| area.name)
+        .collect();
+    assert_eq!(names, vec!["a", "b"]);
+
+    service.delete(workspace, "A").await.unwrap();
+    let err = service.delete(workspace, "a").await.unwrap_err();
+    assert!(matches!(err, ServiceError::AreaNotFound { .. }));
+    let err = service.resolve(workspace, "a").await.unwrap_err();
+    assert!(matches!(err, ServiceError::AreaNotFound { .. }));
+}
@@ -0,0 +1,154 @@
+//! Integration tests for dataset credits in query results
+//!
+//! Three GeoJSON files are ingested: one declaring its attribution as a
+//! foreign member, one whose attribution is given at ingest, and one with
+//! none. A query over all of them lists each required attribution once.
+
+mod common;
+
+use common::Stores;
+use georag_core::error::Result;
+use georag_core::formats::FormatRegistry;
+use georag_core::llm::Embedder;
+use georag_core::models::Feature;
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{QueryPlan, QueryResult};
+use georag_service::{IngestRequest, IngestService};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Embeds every text the same, so each chunk matches every query
+struct ConstantEmbedder;
+
+impl Embedder for ConstantEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        "constant"
+    }
+}
+
+impl Stores {
+    /// Ingest a GeoJSON document, then chunk and index its features
+    async fn ingest(&self, path: &Path, document: serde_json::Value, request: IngestRequest) {
+        std::fs::write(path, document.to_string()).unwrap();
+        let service =
+            IngestService::new(self.spatial.clone(), Arc::new(FormatRegistry::with_defaults()));
+        let prepared = service.prepare(&request).await.unwrap();
+        let features: Vec<Feature> = prepared.features.clone();
+        let report = service.commit(prepared).await.unwrap();
+
+        let chunks = ChunkGenerator::default().generate_chunks(&report.dataset, &features);
+        self.index(&chunks, &ConstantEmbedder).await;
+    }
+
+    async fn query(&self) -> QueryResult {
+        self.service()
+            .execute(&QueryPlan::new("parks"), ConstantEmbedder)
+            .await
+            .unwrap()
+    }
+}
+
+fn collection(content: &str) -> serde_json::Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+            "properties": { "content": content }
+        }]
+    })
+}
+
+async fn setup(dir: &TempDir) -> Stores {
+    let stores = Stores::new();
+
+    let path = dir.path().join("osm.geojson");
+    let mut osm = collection("Parks mapped by volunteers");
+    osm["license"] = json!("ODbL-1.0");
+    osm["attribution"] = json!("  © OpenStreetMap contributors ");
+    stores.ingest(&path, osm, IngestRequest::new(&path)).await;
+
+    let path = dir.path().join("city.geojson");
+    let request = IngestRequest::new(&path)
+        .with_license("CC-BY-4.0")
+        .with_attribution("Jakarta Open Data");
+    stores.ingest(&path, collection("Parks maintained by the city"), request).await;
+
+    let path = dir.path().join("notes.geojson");
+    stores
+        .ingest(&path, collection("Parks noted on a walk"), IngestRequest::new(&path))
+        .await;
+
+    stores
+}
+
+#[tokio::test]
+async fn test_declared_and_given_credits_are_stored() {
+    let dir = TempDir::new().unwrap();
+    let stores = setup(&dir).await;
+
+    let mut credits: Vec<(String, Option<String>, Option<String>)> = Vec::new();
+    for meta in stores.spatial.list_datasets().await.unwrap() {
+        let dataset = stores.spatial.get_dataset(meta.id).await.unwrap().unwrap();
+        credits.push((dataset.name, dataset.license, dataset.attribution));
+    }
+    credits.sort();
+
+    assert_eq!(
+        credits,
+        vec![
+            (
+                "city".to_string(),
+                Some("CC-BY-4.0".to_string()),
+                Some("Jakarta Open Data".to_string())
+            ),
+            ("notes".to_string(), None, None),
+            (
+                "osm".to_string(),
+                Some("ODbL-1.0".to_string()),
+                Some("© OpenStreetMap contributors".to_string())
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_given_attribution_overrides_the_declared_one() {
+    let dir = TempDir::new().unwrap();
+    let stores = Stores::new();
+
+    let path = dir.path().join("osm.geojson");
+    let mut osm = collection("Parks mapped by volunteers");
+    osm["attribution"] = json!("© OpenStreetMap contributors");
+    let request = IngestRequest::new(&path).with_attribution("OSM via Jakarta Open Data");
+    stores.ingest(&path, osm, request).await;
+
+    assert_eq!(stores.query().await.attributions, vec!["OSM via Jakarta Open Data"]);
+}
+
+#[tokio::test]
+async fn test_query_lists_each_attribution_once() {
+    let dir = TempDir::new().unwrap();
+    let stores = setup(&dir).await;
+
+    let result = stores.query().await;
+    assert_eq!(result.sources.len(), 3);
+
+    let mut attributions = result.attributions.clone();
+    attributions.sort();
+    assert_eq!(attributions, vec!["Jakarta Open Data", "© OpenStreetMap contributors"]);
+
+    let collection = stores.service().to_geojson(&result).await;
+    assert_eq!(collection["attributions"], json!(result.attributions));
+}
@@ -0,0 +1,132 @@
+//! Integration tests for repairing features stored lat,lon
+//!
+//! A reference dataset fixes the workspace extent around Berlin. The dataset
+//! under repair holds one feature in the right order, one swapped and one
+//! outside the extent that cannot be told either way.
+
+mod common;
+
+use common::dataset;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, DatasetId, Embedding, Feature, FeatureId, Geometry,
+    TextChunk,
+};
+use georag_service::AxisRepairService;
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct Fixture {
+    spatial: Arc<MemorySpatialStore>,
+    documents: Arc<MemoryDocumentStore>,
+    vectors: Arc<MemoryVectorStore>,
+    dataset_id: DatasetId,
+}
+
+impl Fixture {
+    fn service(&self) -> AxisRepairService {
+        AxisRepairService::new(self.spatial.clone(), self.documents.clone(), self.vectors.clone())
+    }
+}
+
+fn point(id: u64, x: f64, y: f64) -> Feature {
+    Feature::with_geometry(FeatureId(id), Geometry::point(x, y), HashMap::new(), 4326)
+}
+
+fn chunk(id: u64, feature: u64) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: "Brandenburg Gate".to_string(),
+        source: ChunkSource {
+            document_path: "/data/sights.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: 16,
+        },
+        spatial_ref: Some(FeatureId(feature)),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 16,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+async fn setup() -> Fixture {
+    let spatial = Arc::new(MemorySpatialStore::new());
+
+    let reference = spatial.store_dataset(&dataset("districts")).await.unwrap();
+    spatial
+        .upsert_dataset_features(reference, &[point(1, 13.2, 52.4), point(2, 13.6, 52.6)])
+        .await
+        .unwrap();
+
+    // 10 is lon,lat, 11 is lat,lon, 12 lies outside the extent both ways
+    let dataset_id = spatial.store_dataset(&dataset("sights")).await.unwrap();
+    spatial
+        .upsert_dataset_features(
+            dataset_id,
+            &[point(10, 13.37, 52.51), point(11, 52.52, 13.40), point(12, 30.0, 30.0)],
+        )
+        .await
+        .unwrap();
+
+    let documents = Arc::new(MemoryDocumentStore::new());
+    documents.store_chunks(&[chunk(100, 10), chunk(101, 11)]).await.unwrap();
+
+    let vectors = Arc::new(MemoryVectorStore::new());
+    let embeddings: Vec<Embedding> = [100, 101]
+        .into_iter()
+        .map(|id| Embedding {
+            chunk_id: ChunkId(id),
+            vector: vec![1.0, 0.0],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    Fixture { spatial, documents, vectors, dataset_id }
+}
+
+#[tokio::test]
+async fn test_scan_flags_swapped_features_only() {
+    let fixture = setup().await;
+
+    let plan = fixture.service().scan(fixture.dataset_id).await.unwrap();
+
+    assert_eq!(plan.features_scanned, 3);
+    assert_eq!(plan.undecided, 1);
+    assert_eq!(plan.flips.len(), 1);
+    assert_eq!(plan.flips[0].id, FeatureId(11));
+    assert_eq!(plan.flips[0].geometry, Some(Geometry::point(13.40, 52.52)));
+
+    // Nothing is written by a scan
+    let stored = fixture.spatial.get_feature(FeatureId(11)).await.unwrap().unwrap();
+    assert_eq!(stored.geometry, Some(Geometry::point(52.52, 13.40)));
+}
+
+#[tokio::test]
+async fn test_apply_swaps_back_and_drops_chunks() {
+    let fixture = setup().await;
+    let service = fixture.service();
+
+    let plan = service.scan(fixture.dataset_id).await.unwrap();
+    let report = service.apply(&plan).await.unwrap();
+
+    assert_eq!(report.features_flipped, 1);
+    assert_eq!(report.chunks_invalidated, 1);
+
+    let repaired = fixture.spatial.get_feature(FeatureId(11)).await.unwrap().unwrap();
+    assert_eq!(repaired.geometry, Some(Geometry::point(13.40, 52.52)));
+    assert!(fixture.documents.get_chunk(ChunkId(101)).await.unwrap().is_none());
+    assert!(fixture.documents.get_chunk(ChunkId(100)).await.unwrap().is_some());
+    assert_eq!(fixture.vectors.list_embedding_ids().await.unwrap(), vec![ChunkId(100)]);
+
+    // The repaired dataset scans clean
+    assert!(service.scan(fixture.dataset_id).await.unwrap().is_empty());
+}
@@ -0,0 +1,269 @@
+//! Integration tests for bulk dataset deletion and archiving
+//!
+//! The workspace holds two test datasets, one added long ago and one just
+//! now, and an old `roads` dataset, each with a chunk and an embedding.
+
+use chrono::{Duration, Utc};
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    AuditEventKind, ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, DistanceUnit,
+    Embedding, GeometryType, SourceFile, TagVisibility, TextChunk, UsageDelta, ValidityMode,
+    WorkspaceConfig, WorkspaceQuotas, ARCHIVED_TAG,
+};
+use georag_service::{
+    AuditService, BulkAction, BulkDatasetService, DatasetFilter, NamePattern, ServiceError,
+    WorkspaceQuota,
+};
+use georag_store::memory::{
+    MemoryAuditStore, MemoryBlobStore, MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore,
+    MemoryWorkspaceStore,
+};
+use georag_store::ports::{
+    AuditStore, BlobStore, DocumentStore, SpatialStore, VectorStore, WorkspaceStore,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+struct Stores {
+    spatial: Arc<MemorySpatialStore>,
+    documents: Arc<MemoryDocumentStore>,
+    vectors: Arc<MemoryVectorStore>,
+    blobs: Arc<MemoryBlobStore>,
+    workspaces: Arc<MemoryWorkspaceStore>,
+    audit: Arc<MemoryAuditStore>,
+    quota: WorkspaceQuota,
+}
+
+impl Stores {
+    fn service(&self) -> BulkDatasetService {
+        BulkDatasetService::new(
+            self.spatial.clone(),
+            self.documents.clone(),
+            self.vectors.clone(),
+            self.blobs.clone(),
+            self.quota.clone(),
+            AuditService::new(self.audit.clone()),
+        )
+        .with_batch_size(2)
+    }
+
+    async fn dataset_names(&self) -> Vec<String> {
+        let datasets = self.spatial.list_datasets().await.unwrap();
+        datasets.into_iter().map(|d| d.name).collect()
+    }
+}
+
+fn dataset(name: &str, days_old: i64) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}.geojson", name)),
+        geometry_type: GeometryType::Point,
+        feature_count: 10,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: Some(SourceFile::new(format!("{}.geojson", name), 100)),
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now() - Duration::days(days_old),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn chunk(id: u64, name: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: format!("chunk of {}", name),
+        source: ChunkSource {
+            document_path: format!("/data/{}.geojson", name),
+            page: None,
+            offset: 0,
+            length: 0,
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 10,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+async fn setup() -> Stores {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+    let vectors = Arc::new(MemoryVectorStore::new());
+    let blobs = Arc::new(MemoryBlobStore::new());
+
+    let names = [("test_old", 400), ("test_new", 0), ("roads", 400)];
+    for (i, (name, days_old)) in names.into_iter().enumerate() {
+        let id = spatial.store_dataset(&dataset(name, days_old)).await.unwrap();
+        blobs.put_blob(id, &[0; 100]).await.unwrap();
+        documents.store_chunks(&[chunk(i as u64 + 1, name)]).await.unwrap();
+        vectors
+            .store_embeddings(&[Embedding {
+                chunk_id: ChunkId(i as u64 + 1),
+                vector: vec![0.5; 4],
+                spatial_metadata: None,
+                dataset_id: None,
+            }])
+            .await
+            .unwrap();
+    }
+
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let config = WorkspaceConfig {
+        crs: 4326,
+        distance_unit: DistanceUnit::Meters,
+        geometry_validity: ValidityMode::Lenient,
+    };
+    let workspace_id = workspaces.create_workspace("bulk", &config).await.unwrap();
+    let usage = UsageDelta {
+        datasets: 3,
+        features: 30,
+        chunks: 3,
+        blob_bytes: 300,
+    };
+    workspaces.adjust_workspace_usage(workspace_id, &usage).await.unwrap();
+    let quota = WorkspaceQuota::new(workspaces.clone(), workspace_id, WorkspaceQuotas::default());
+
+    Stores {
+        spatial,
+        documents,
+        vectors,
+        blobs,
+        workspaces,
+        audit: Arc::new(MemoryAuditStore::new()),
+        quota,
+    }
+}
+
+fn old_tests() -> DatasetFilter {
+    DatasetFilter::default()
+        .with_name(NamePattern::glob("test_*").unwrap())
+        .with_added_before(Utc::now() - Duration::days(30))
+}
+
+#[tokio::test]
+async fn test_plan_previews_without_changing_anything() {
+    let stores = setup().await;
+    let service = stores.service();
+
+    let plan = service
+        .plan(&old_tests(), BulkAction::Delete, &TagVisibility::All)
+        .await
+        .unwrap();
+    assert_eq!(plan.datasets_scanned, 3);
+    let names: Vec<&str> = plan.datasets.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, vec!["test_old"]);
+    assert_eq!(plan.feature_count(), 10);
+    assert_eq!(plan.source_bytes(), 100);
+    assert_eq!(stores.dataset_names().await.len(), 3);
+
+    let prefix = DatasetFilter::default().with_name(NamePattern::prefix("test"));
+    let plan = service.plan(&prefix, BulkAction::Delete, &TagVisibility::All).await.unwrap();
+    assert_eq!(plan.datasets.len(), 2);
+
+    // A filter without conditions would select everything
+    let err = service
+        .plan(&DatasetFilter::default(), BulkAction::Delete, &TagVisibility::All)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidFilter(_)));
+    assert!(matches!(NamePattern::regex("test_("), Err(ServiceError::InvalidFilter(_))));
+}
+
+#[tokio::test]
+async fn test_delete_cascades_and_continues_past_failures() {
+    let stores = setup().await;
+    let service = stores.service();
+    let filter = DatasetFilter::default().with_name(NamePattern::regex("^(test|roads)").unwrap());
+    let plan = service.plan(&filter, BulkAction::Delete, &TagVisibility::All).await.unwrap();
+    assert_eq!(plan.datasets.len(), 3);
+
+    // Removed between preview and confirmation
+    let roads = plan.datasets.iter().find(|d| d.name == "roads").unwrap().id;
+    stores.spatial.delete_dataset(roads).await.unwrap();
+
+    let mut progress = Vec::new();
+    let report = service.apply(&plan, |p| progress.push(p.done)).await;
+    assert_eq!(progress, vec![2, 3]);
+    assert_eq!((report.succeeded(), report.failed()), (2, 1));
+    let failed = report.results.iter().find(|r| !r.succeeded()).unwrap();
+    assert_eq!(failed.name, "roads");
+
+    assert!(stores.dataset_names().await.is_empty());
+    // The chunk of the failed dataset is left alone
+    assert_eq!(stores.documents.list_chunk_ids().await.unwrap(), vec![ChunkId(3)]);
+    assert_eq!(stores.vectors.list_embedding_ids().await.unwrap(), vec![ChunkId(3)]);
+    assert!(stores.blobs.get_blob(plan.datasets[1].id).await.unwrap().is_none());
+
+    let usage = stores
+        .workspaces
+        .get_workspace_usage(stores.quota.workspace_id())
+        .await
+        .unwrap();
+    assert_eq!(
+        (usage.datasets, usage.features, usage.chunks, usage.blob_bytes),
+        (1, 10, 1, 100)
+    );
+
+    let events = stores
+        .audit
+        .list_events(stores.quota.workspace_id(), Utc::now() - Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| e.kind == AuditEventKind::DatasetDeleted));
+}
+
+#[tokio::test]
+async fn test_archive_keeps_features_but_drops_chunks() {
+    let stores = setup().await;
+    let service = stores.service();
+    let plan = service
+        .plan(&old_tests(), BulkAction::Archive, &TagVisibility::All)
+        .await
+        .unwrap();
+    let report = service.apply(&plan, |_| {}).await;
+    assert_eq!(report.succeeded(), 1);
+    assert_eq!(report.results[0].chunks_removed, 1);
+
+    let archived = stores.spatial.get_dataset(plan.datasets[0].id).await.unwrap().unwrap();
+    assert_eq!(archived.tags, vec![ARCHIVED_TAG]);
+    assert!(stores.blobs.get_blob(archived.id).await.unwrap().is_some());
+    assert_eq!(stores.documents.list_chunk_ids().await.unwrap().len(), 2);
+
+    // Only the chunks are released; the dataset still takes up its storage
+    assert_eq!(report.released, UsageDelta { chunks: -1, ..Default::default() });
+
+    // Archived datasets are not archived twice, but can still be deleted
+    let again = service
+        .plan(&old_tests(), BulkAction::Archive, &TagVisibility::All)
+        .await
+        .unwrap();
+    assert!(again.is_empty());
+    let tagged = DatasetFilter::default().with_tag(" Archived ");
+    let delete = service.plan(&tagged, BulkAction::Delete, &TagVisibility::All).await.unwrap();
+    assert_eq!(delete.datasets.len(), 1);
+
+    // Keys restricted to other tags do not see archived datasets
+    let restricted = TagVisibility::only(["public"]);
+    let hidden = service.plan(&tagged, BulkAction::Delete, &restricted).await.unwrap();
+    assert!(hidden.is_empty());
+}
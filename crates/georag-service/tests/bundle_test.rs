@@ -0,0 +1,261 @@
+//! Integration tests for offline bundles
+//!
+//! A query whose spatial filter lies inside the bundle's bounding box must
+//! rank the same sources from the bundle as from the stores it was cut from.
+//! The spatial index saved in the bundle must answer like one rebuilt from
+//! the features, and is rebuilt when the features have changed.
+
+mod common;
+
+use chrono::Utc;
+use common::{chunk, dataset, KeywordEmbedder, Stores};
+use georag_core::error::GeoragError;
+use georag_core::models::{
+    Dataset, DatasetId, Distance, Feature, FeatureId, Geometry, IndexState, SpatialFilter,
+    SpatialPredicate, INDEX_SCHEMA_VERSION,
+};
+use georag_retrieval::QueryPlan;
+use georag_service::QueryService;
+use georag_store::bundle::{BundleStore, IndexOrigin, OfflineBundle, BUNDLE_FORMAT_VERSION};
+use georag_store::ports::{DocumentStore, SpatialStore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+const VOCABULARY: [&str; 4] = ["park", "bench", "market", "stall"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// Bounding box around central Jakarta
+const BBOX: [f64; 4] = [106.0, -7.0, 107.0, -6.0];
+
+fn index_state() -> IndexState {
+    IndexState {
+        schema_version: INDEX_SCHEMA_VERSION,
+        hash: "abc123".to_string(),
+        built_at: Utc::now(),
+        embedder: "keyword".to_string(),
+        chunk_count: 4,
+        embedding_dim: VOCABULARY.len(),
+        dataset_built_at: BTreeMap::new(),
+        contents: None,
+        last_build_diff: None,
+        tokenizer: None,
+        score_baseline: None,
+    }
+}
+
+/// Two places inside the bounding box, one outside it and a chunk without a place
+async fn setup() -> Stores {
+    let stores = Stores::new();
+
+    let features: Vec<Feature> = [(1, 106.8, -6.2), (2, 106.5, -6.5), (3, 110.4, -7.8)]
+        .into_iter()
+        .map(|(id, x, y)| {
+            Feature::with_geometry(FeatureId(id), Geometry::point(x, y), HashMap::new(), 4326)
+        })
+        .collect();
+    let dataset = Dataset {
+        feature_count: 4,
+        license: Some("CC-BY-4.0".to_string()),
+        attribution: Some("Jakarta Open Data".to_string()),
+        ..dataset("places")
+    };
+    let dataset_id = stores.spatial.store_dataset(&dataset).await.unwrap();
+    stores.spatial.store_features(&features).await.unwrap();
+    stores
+        .spatial
+        .associate_features_with_dataset(dataset_id, features.iter().map(|f| f.id).collect());
+
+    let chunks = vec![
+        chunk(1, "places", Some(1), "park bench"),
+        chunk(2, "places", Some(2), "market stall near the park"),
+        chunk(3, "places", Some(3), "park bench bench"),
+        chunk(4, "places", None, "park"),
+    ];
+    stores.index(&chunks, &embedder()).await;
+
+    stores
+}
+
+async fn export(stores: &Stores) -> OfflineBundle {
+    OfflineBundle::extract(
+        stores.spatial.as_ref(),
+        stores.vector.as_ref(),
+        stores.documents.as_ref(),
+        BBOX,
+        Some(index_state()),
+    )
+    .await
+    .unwrap()
+}
+
+/// A filter polygon inside the bundle's bounding box
+fn plan() -> QueryPlan {
+    let area = Geometry::polygon(vec![vec![
+        [106.2, -6.8],
+        [106.9, -6.8],
+        [106.9, -6.1],
+        [106.2, -6.1],
+        [106.2, -6.8],
+    ]]);
+    QueryPlan::new("park bench")
+        .with_spatial_filter(SpatialFilter::new(SpatialPredicate::Within).geometry(area))
+        .with_top_k(5)
+}
+
+#[tokio::test]
+async fn test_bundle_keeps_only_the_bounding_box() {
+    let bundle = export(&setup().await).await;
+
+    assert_eq!(bundle.format_version, BUNDLE_FORMAT_VERSION);
+    let mut feature_ids: Vec<u64> = bundle.features.iter().map(|f| f.id.0).collect();
+    feature_ids.sort();
+    assert_eq!(feature_ids, vec![1, 2]);
+
+    let mut chunk_ids: Vec<u64> = bundle.chunks.iter().map(|c| c.id.0).collect();
+    chunk_ids.sort();
+    assert_eq!(chunk_ids, vec![1, 2]);
+    assert_eq!(bundle.embeddings.len(), 2);
+
+    assert_eq!(bundle.datasets.len(), 1);
+    assert_eq!(bundle.datasets[0].dataset.feature_count, 2);
+    assert_eq!(bundle.attributions, vec!["Jakarta Open Data"]);
+    assert_eq!(bundle.index_state.as_ref().unwrap().chunk_count, 2);
+}
+
+#[tokio::test]
+async fn test_query_inside_bbox_matches_full_store() {
+    let stores = setup().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("jakarta.georag");
+    export(&stores).await.write_to(&path).unwrap();
+
+    let full = stores.service();
+    let bundle = Arc::new(BundleStore::open(&path).await.unwrap());
+    assert_eq!(bundle.index_state().unwrap().embedder, "keyword");
+    let offline = QueryService::new(bundle.clone(), bundle.clone(), bundle);
+
+    let expected = full.execute(&plan(), embedder()).await.unwrap();
+    let actual = offline.execute(&plan(), embedder()).await.unwrap();
+
+    let ranked = |sources: &[georag_retrieval::models::SourceReference]| -> Vec<(u64, f32)> {
+        sources.iter().map(|s| (s.chunk_id.0, s.score)).collect()
+    };
+    assert!(!expected.sources.is_empty());
+    assert_eq!(ranked(&actual.sources), ranked(&expected.sources));
+    assert_eq!(actual.attributions, vec!["Jakarta Open Data"]);
+    assert_eq!(actual.attributions, expected.attributions);
+}
+
+#[tokio::test]
+async fn test_bundle_store_is_read_only() {
+    let bundle = BundleStore::from_bundle(export(&setup().await).await).await.unwrap();
+
+    let err = bundle.store_chunks(&[chunk(9, "places", Some(1), "bench")]).await.unwrap_err();
+    assert!(matches!(err, GeoragError::ReadOnly { .. }), "got {:?}", err);
+
+    let err = bundle.delete_dataset(DatasetId(0)).await.unwrap_err();
+    assert!(matches!(err, GeoragError::ReadOnly { .. }), "got {:?}", err);
+    assert_eq!(bundle.list_datasets().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_other_format_versions_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("future.georag");
+
+    let mut bundle = export(&setup().await).await;
+    bundle.format_version = BUNDLE_FORMAT_VERSION + 1;
+    bundle.write_to(&path).unwrap();
+
+    let err = OfflineBundle::read_from(&path).unwrap_err();
+    assert!(matches!(err, GeoragError::FormatValidation { .. }), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn test_bundles_of_another_index_schema_are_refused() {
+    let mut bundle = export(&setup().await).await;
+    let store = BundleStore::from_bundle(bundle.clone()).await.unwrap();
+    assert_eq!(store.index_state().unwrap().schema_version, INDEX_SCHEMA_VERSION);
+
+    bundle.index_state.as_mut().unwrap().schema_version = INDEX_SCHEMA_VERSION - 1;
+    let err = BundleStore::from_bundle(bundle.clone()).await.err().unwrap();
+    assert!(matches!(err, GeoragError::IndexSchemaOutdated { .. }), "got {:?}", err);
+    assert!(err.to_string().contains("georag db upgrade"), "{}", err);
+
+    bundle.index_state.as_mut().unwrap().schema_version = INDEX_SCHEMA_VERSION + 1;
+    let err = BundleStore::from_bundle(bundle).await.err().unwrap();
+    assert!(matches!(err, GeoragError::IndexSchemaTooNew { .. }), "got {:?}", err);
+}
+
+/// Filters exercising every way the index narrows down features
+fn filters() -> Vec<SpatialFilter> {
+    let area = |min_x: f64, min_y: f64, max_x: f64, max_y: f64| {
+        Geometry::polygon(vec![vec![
+            [min_x, min_y],
+            [max_x, min_y],
+            [max_x, max_y],
+            [min_x, max_y],
+            [min_x, min_y],
+        ]])
+    };
+    vec![
+        SpatialFilter::new(SpatialPredicate::Within).geometry(area(106.2, -6.8, 106.9, -6.1)),
+        SpatialFilter::new(SpatialPredicate::Intersects).geometry(area(106.7, -6.3, 107.5, -5.5)),
+        SpatialFilter::new(SpatialPredicate::BoundingBox).geometry(area(106.0, -7.0, 106.6, -6.4)),
+        SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::point(106.8, -6.2)),
+        SpatialFilter::new(SpatialPredicate::DWithin)
+            .geometry(Geometry::point(106.85, -6.2))
+            .distance(Distance::kilometers(10.0)),
+        SpatialFilter::new(SpatialPredicate::Intersects),
+    ]
+}
+
+async fn matches(store: &dyn SpatialStore, filter: &SpatialFilter) -> Vec<u64> {
+    store.spatial_query(filter).await.unwrap().iter().map(|f| f.id.0).collect()
+}
+
+#[tokio::test]
+async fn test_saved_index_answers_like_a_rebuilt_one() {
+    let stores = setup().await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("jakarta.georag");
+    export(&stores).await.write_to(&path).unwrap();
+
+    let loaded = BundleStore::open(&path).await.unwrap();
+    assert_eq!(loaded.index_load().origin, IndexOrigin::Loaded);
+    assert_eq!(loaded.index_load().features, 2);
+
+    // A hash that no longer matches makes the store rebuild the index
+    let mut bundle = OfflineBundle::read_from(&path).unwrap();
+    bundle.spatial_index.content_hash = "stale".to_string();
+    let rebuilt = BundleStore::from_bundle(bundle).await.unwrap();
+    assert_eq!(rebuilt.index_load().origin, IndexOrigin::Rebuilt);
+
+    for filter in filters() {
+        let expected = matches(&rebuilt, &filter).await;
+        assert_eq!(matches(&loaded, &filter).await, expected, "{:?}", filter);
+
+        // Both answer like the full store, minus the feature outside the box
+        let mut full = matches(stores.spatial.as_ref(), &filter).await;
+        full.retain(|id| *id != 3);
+        assert_eq!(expected, full, "{:?}", filter);
+    }
+}
+
+#[tokio::test]
+async fn test_index_is_rebuilt_when_features_changed() {
+    let mut bundle = export(&setup().await).await;
+    let moved = bundle.features.iter_mut().find(|f| f.id == FeatureId(2)).unwrap();
+    moved.geometry = Some(Geometry::point(106.95, -6.05));
+
+    let store = BundleStore::from_bundle(bundle).await.unwrap();
+    assert_eq!(store.index_load().origin, IndexOrigin::Rebuilt);
+
+    // The saved envelope of the feature would miss it at its new place
+    let filter =
+        SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::point(106.95, -6.05));
+    assert_eq!(matches(&store, &filter).await, vec![2]);
+}
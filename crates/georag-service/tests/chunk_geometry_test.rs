@@ -0,0 +1,144 @@
+//! Integration tests for chunks located by the places they name
+//!
+//! A planning document discusses two sites. Ingested with a gazetteer of
+//! both, each chunk is located by the site its text names, so a filter around
+//! one site leaves out chunks about the other. A chunk naming no site falls
+//! back to the document's feature, which covers both.
+
+mod common;
+
+use common::{KeywordEmbedder, Stores};
+use georag_core::formats::FormatRegistry;
+use georag_core::geo::{Gazetteer, PLACES_PROPERTY};
+use georag_core::models::{FeatureId, Geometry, SpatialFilter, SpatialPredicate, TextChunk};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{QueryPlan, QueryResult, SpatialMatch};
+use georag_service::{IngestRequest, IngestService};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const VOCABULARY: [&str; 3] = ["drainage", "lights", "budget"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// One chunk per site and a closing chunk naming neither
+const PLAN_TEXT: &str =
+    "Alder culverts need drainage Birch lane needs lights overall budget stays flat";
+
+fn places() -> Gazetteer {
+    Gazetteer::from_geojson(&json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [0.0, 0.0] },
+                "properties": { "name": "Alder" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [1.0, 0.0] },
+                "properties": { "name": "Birch" }
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+impl Stores {
+    async fn query(&self, plan: &QueryPlan) -> QueryResult {
+        self.service().execute(plan, embedder()).await.unwrap()
+    }
+}
+
+/// Ingest the plan with the gazetteer, then chunk and index it
+async fn setup(dir: &TempDir) -> (Stores, Vec<TextChunk>) {
+    let path = dir.path().join("plan.geojson");
+    let plan = json!({
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "geometry": null, "properties": { "content": PLAN_TEXT } }
+        ]
+    });
+    std::fs::write(&path, plan.to_string()).unwrap();
+
+    let stores = Stores::new();
+    let service =
+        IngestService::new(stores.spatial.clone(), Arc::new(FormatRegistry::with_defaults()));
+    let report = service.ingest(&IngestRequest::new(&path).with_places(places())).await.unwrap();
+
+    let document = stores.spatial.get_feature(FeatureId(0)).await.unwrap().unwrap();
+    let chunks = ChunkGenerator::new(1, 4, 0)
+        .unwrap()
+        .generate_chunks(&report.dataset, &[document]);
+    stores.index(&chunks, &embedder()).await;
+
+    (stores, chunks)
+}
+
+fn around(x: f64) -> SpatialFilter {
+    let square = Geometry::polygon(vec![vec![
+        [x - 0.1, -0.1],
+        [x + 0.1, -0.1],
+        [x + 0.1, 0.1],
+        [x - 0.1, 0.1],
+        [x - 0.1, -0.1],
+    ]]);
+    SpatialFilter::new(SpatialPredicate::Intersects).geometry(square)
+}
+
+#[tokio::test]
+async fn test_document_keeps_places_and_covers_them() {
+    let dir = TempDir::new().unwrap();
+    let (stores, chunks) = setup(&dir).await;
+
+    let document = stores.spatial.get_feature(FeatureId(0)).await.unwrap().unwrap();
+    assert_eq!(document.geometry, places().footprint());
+    assert!(document.properties.contains_key(PLACES_PROPERTY));
+
+    let geometries: Vec<Option<Geometry>> = chunks.into_iter().map(|c| c.geometry).collect();
+    assert_eq!(
+        geometries,
+        vec![Some(Geometry::point(0.0, 0.0)), Some(Geometry::point(1.0, 0.0)), None]
+    );
+}
+
+#[tokio::test]
+async fn test_filter_around_one_site_skips_chunks_about_the_other() {
+    let dir = TempDir::new().unwrap();
+    let (stores, _) = setup(&dir).await;
+
+    let plan = QueryPlan::new("drainage lights budget").with_spatial_filter(around(0.0));
+    let result = stores.query(&plan).await;
+
+    let mut matches: Vec<(String, Option<SpatialMatch>)> = result
+        .sources
+        .iter()
+        .map(|s| (s.excerpt.split(' ').next().unwrap().to_string(), s.spatial_match))
+        .collect();
+    matches.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        matches,
+        vec![
+            ("Alder".to_string(), Some(SpatialMatch::Chunk)),
+            ("overall".to_string(), Some(SpatialMatch::Feature)),
+        ]
+    );
+
+    let explanation = stores.query(&plan.with_explain(true)).await.explanation.unwrap();
+    assert_eq!(explanation.spatial_phase.chunks_matched_by_geometry, 1);
+}
+
+#[tokio::test]
+async fn test_sources_without_spatial_filter_have_no_spatial_match() {
+    let dir = TempDir::new().unwrap();
+    let (stores, _) = setup(&dir).await;
+
+    let result = stores.query(&QueryPlan::new("drainage lights budget")).await;
+
+    assert_eq!(result.sources.len(), 3);
+    assert!(result.sources.iter().all(|s| s.spatial_match.is_none()));
+}
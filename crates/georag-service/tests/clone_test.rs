@@ -0,0 +1,255 @@
+//! Integration tests for cloning a workspace
+//!
+//! The source holds two datasets, `parks` with three features and `roads`
+//! with two, each with its source file and a chunk with an embedding, and a
+//! chunk of another workspace sharing its stores.
+
+use chrono::Utc;
+use georag_core::config::WorkspaceSettings;
+use georag_core::error::GeoragError;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, DatasetMeta, Embedding, Feature,
+    FeatureId, Geometry, GeometryType, SourceFile, TextChunk, WorkspaceQuotas,
+};
+use georag_service::{
+    ClonePhase, ServiceError, WorkspaceCloneService, WorkspaceQuota, WorkspaceService,
+};
+use georag_store::memory::{
+    MemoryBlobStore, MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore,
+    MemoryWorkspaceStore,
+};
+use georag_store::ports::{
+    BlobStore, DocumentStore, SpatialStore, VectorStore, WorkspaceStore, WorkspaceStores,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn stores() -> WorkspaceStores {
+    WorkspaceStores {
+        spatial: Arc::new(MemorySpatialStore::new()),
+        vector: Arc::new(MemoryVectorStore::new()),
+        document: Arc::new(MemoryDocumentStore::new()),
+        blob: Arc::new(MemoryBlobStore::new()),
+    }
+}
+
+fn dataset(name: &str, feature_count: usize) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}.geojson", name)),
+        geometry_type: GeometryType::Point,
+        feature_count,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: Some(SourceFile::new(format!("{}.geojson", name), 50)),
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: vec!["public".to_string()],
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(id: u64) -> Feature {
+    Feature {
+        id: FeatureId(id),
+        geometry: Some(Geometry::point(106.8, -6.2)),
+        properties: HashMap::from([("name".to_string(), serde_json::json!(id))]),
+        crs: 4326,
+        z: None,
+    }
+}
+
+fn chunk(id: u64, path: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: format!("chunk {}", id),
+        source: ChunkSource {
+            document_path: path.to_string(),
+            page: None,
+            offset: 0,
+            length: 0,
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 7,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+/// Fill the source stores, returning the datasets of the cloned workspace
+async fn fill(source: &WorkspaceStores) -> Vec<DatasetMeta> {
+    // A dataset deleted before the clone, so source IDs start at 2
+    let gone = source.spatial.store_dataset(&dataset("gone", 0)).await.unwrap();
+    source.spatial.delete_dataset(gone).await.unwrap();
+
+    let mut next_feature = 1;
+    for (i, (name, features)) in [("parks", 3), ("roads", 2)].into_iter().enumerate() {
+        let id = source.spatial.store_dataset(&dataset(name, features)).await.unwrap();
+        let batch: Vec<Feature> =
+            (next_feature..next_feature + features as u64).map(feature).collect();
+        next_feature += features as u64;
+        source.spatial.upsert_dataset_features(id, &batch).await.unwrap();
+        source.blob.put_blob(id, &[1; 50]).await.unwrap();
+
+        let chunk_id = i as u64 + 1;
+        source
+            .document
+            .store_chunks(&[chunk(chunk_id, &format!("/data/{}.geojson", name))])
+            .await
+            .unwrap();
+        source
+            .vector
+            .store_embeddings(&[Embedding {
+                chunk_id: ChunkId(chunk_id),
+                vector: vec![0.25; 4],
+                spatial_metadata: None,
+                dataset_id: None,
+            }])
+            .await
+            .unwrap();
+    }
+    // Built from a dataset of another workspace
+    source
+        .document
+        .store_chunks(&[chunk(9, "/elsewhere/rivers.shp")])
+        .await
+        .unwrap();
+
+    source.spatial.list_datasets().await.unwrap()
+}
+
+async fn quota(workspaces: &Arc<MemoryWorkspaceStore>, quotas: WorkspaceQuotas) -> WorkspaceQuota {
+    let service = WorkspaceService::new(workspaces.clone());
+    let view = service.create("experiment", &WorkspaceSettings::default()).await.unwrap();
+    WorkspaceQuota::new(workspaces.clone(), view.meta.id, quotas)
+}
+
+#[tokio::test]
+async fn test_clone_copies_everything_and_verifies_counts() {
+    let (source, destination) = (stores(), stores());
+    let datasets = fill(&source).await;
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let quota = quota(&workspaces, WorkspaceQuotas::default()).await;
+
+    let service = WorkspaceCloneService::new(source.clone(), destination.clone(), quota.clone())
+        .with_blobs(true)
+        .with_batch_size(2);
+    let plan = service.plan(&datasets).await.unwrap();
+    assert_eq!(plan.datasets.len(), 2);
+    assert_eq!(plan.chunk_ids, vec![ChunkId(1), ChunkId(2)]);
+
+    let mut progress = Vec::new();
+    let report = service.run(&plan, |p| progress.push((p.phase, p.done))).await.unwrap();
+    assert_eq!(
+        progress,
+        vec![(ClonePhase::Datasets, 1), (ClonePhase::Datasets, 2), (ClonePhase::Chunks, 2)]
+    );
+
+    assert!(report.verification.matches(), "{:?}", report.verification);
+    let counts = report.verification.destination;
+    assert_eq!(
+        (counts.datasets, counts.features, counts.chunks, counts.embeddings, counts.blobs),
+        (2, 5, 2, 2, 2)
+    );
+
+    // Datasets are renumbered; features and chunks keep their IDs
+    let mapping: Vec<(u64, u64)> =
+        report.datasets.iter().map(|m| (m.source.0, m.destination.0)).collect();
+    assert_eq!(mapping, vec![(2, 1), (3, 2)]);
+    let roads = destination.spatial.get_dataset(DatasetId(2)).await.unwrap().unwrap();
+    assert_eq!(roads.name, "roads");
+    assert_eq!(roads.tags, vec!["public"]);
+    assert!(destination.spatial.get_feature(FeatureId(5)).await.unwrap().is_some());
+    assert_eq!(destination.blob.get_blob(DatasetId(2)).await.unwrap(), Some(vec![1; 50]));
+    assert!(destination.vector.get_embedding(ChunkId(2)).await.unwrap().is_some());
+
+    let usage = quota.usage().await.unwrap();
+    assert_eq!((usage.datasets, usage.features, usage.chunks, usage.blob_bytes), (2, 5, 2, 100));
+
+    // The source is left as it was
+    assert_eq!(source.document.list_chunk_ids().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_clone_without_blobs_drops_source_files() {
+    let (source, destination) = (stores(), stores());
+    let datasets = fill(&source).await;
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let quota = quota(&workspaces, WorkspaceQuotas::default()).await;
+
+    let service = WorkspaceCloneService::new(source, destination.clone(), quota.clone());
+    let plan = service.plan(&datasets).await.unwrap();
+    let report = service.run(&plan, |_| {}).await.unwrap();
+    assert!(report.verification.matches());
+    assert_eq!(report.verification.destination.blobs, 0);
+
+    let parks = destination.spatial.get_dataset(DatasetId(1)).await.unwrap().unwrap();
+    assert!(parks.format.source.is_none());
+    assert!(destination.blob.get_blob(DatasetId(1)).await.unwrap().is_none());
+    assert_eq!(quota.usage().await.unwrap().blob_bytes, 0);
+}
+
+#[tokio::test]
+async fn test_clone_over_quota_copies_nothing() {
+    let (source, destination) = (stores(), stores());
+    let datasets = fill(&source).await;
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let quotas = WorkspaceQuotas {
+        max_features: Some(4),
+        ..Default::default()
+    };
+    let quota = quota(&workspaces, quotas).await;
+
+    let service = WorkspaceCloneService::new(source, destination.clone(), quota);
+    let plan = service.plan(&datasets).await.unwrap();
+    let err = service.run(&plan, |_| {}).await.unwrap_err();
+    assert!(
+        matches!(err, ServiceError::Core(GeoragError::QuotaExceeded { .. })),
+        "{:?}",
+        err
+    );
+    assert!(destination.spatial.list_datasets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_clone_settings_record_provenance() {
+    let workspaces = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(workspaces.clone());
+    let settings = WorkspaceSettings {
+        crs: Some(3857),
+        min_score: Some(0.4),
+        ..Default::default()
+    };
+    let source = service.create("jakarta", &settings).await.unwrap();
+
+    let clone = service.create_clone(source.meta.id, "jakarta-experiment").await.unwrap();
+    assert_eq!(clone.meta.crs, 3857);
+    assert_eq!(clone.settings.min_score, Some(0.4));
+    let provenance = clone.settings.cloned_from.unwrap();
+    assert_eq!(provenance.workspace, "jakarta");
+    assert_eq!(provenance.workspace_id, source.meta.id.to_string());
+
+    // Names stay unique
+    let err = service.create_clone(source.meta.id, "jakarta").await.unwrap_err();
+    assert!(matches!(err, ServiceError::WorkspaceExists { .. }));
+    assert_eq!(workspaces.list_workspaces().await.unwrap().len(), 2);
+}
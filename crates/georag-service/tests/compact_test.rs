@@ -0,0 +1,153 @@
+//! Integration tests for index compaction
+//!
+//! The stores hold one healthy chunk, one chunk whose feature was deleted and
+//! embeddings for chunks that no longer exist.
+
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Embedding, Feature, FeatureId, Geometry, TextChunk,
+};
+use georag_service::{CompactionProgress, CompactionService};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const DIMENSIONS: usize = 4;
+
+struct Stores {
+    spatial: Arc<MemorySpatialStore>,
+    documents: Arc<MemoryDocumentStore>,
+    vectors: Arc<MemoryVectorStore>,
+}
+
+impl Stores {
+    fn service(&self) -> CompactionService {
+        CompactionService::new(self.spatial.clone(), self.documents.clone(), self.vectors.clone())
+    }
+}
+
+fn chunk(id: u64, feature: Option<u64>) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: "0123456789".to_string(),
+        source: ChunkSource {
+            document_path: "/data/places.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: 10,
+        },
+        spatial_ref: feature.map(FeatureId),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 10,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+fn embedding(id: u64) -> Embedding {
+    Embedding {
+        chunk_id: ChunkId(id),
+        vector: vec![0.5; DIMENSIONS],
+        spatial_metadata: None,
+        dataset_id: None,
+    }
+}
+
+async fn setup() -> Stores {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let feature =
+        Feature::with_geometry(FeatureId(1), Geometry::point(106.8, -6.2), HashMap::new(), 4326);
+    spatial.store_features(&[feature]).await.unwrap();
+
+    let documents = Arc::new(MemoryDocumentStore::new());
+    // Chunk 1 is healthy, chunk 2 points at a deleted feature, chunk 3 has no feature
+    documents
+        .store_chunks(&[chunk(1, Some(1)), chunk(2, Some(2)), chunk(3, None)])
+        .await
+        .unwrap();
+
+    let vectors = Arc::new(MemoryVectorStore::new());
+    // Embeddings 10 and 11 belong to chunks that are gone
+    vectors
+        .store_embeddings(&[embedding(1), embedding(2), embedding(3), embedding(10), embedding(11)])
+        .await
+        .unwrap();
+
+    Stores { spatial, documents, vectors }
+}
+
+#[tokio::test]
+async fn test_scan_finds_orphans_without_deleting() {
+    let stores = setup().await;
+
+    let mut plan = stores.service().scan().await.unwrap();
+    plan.orphan_embeddings.sort_by_key(|id| id.0);
+
+    assert_eq!(plan.embeddings_scanned, 5);
+    assert_eq!(plan.chunks_scanned, 3);
+    assert_eq!(plan.orphan_embeddings, vec![ChunkId(10), ChunkId(11)]);
+    assert_eq!(plan.dangling_chunks, vec![ChunkId(2)]);
+    // Three vectors of four f32s plus the dangling chunk's text
+    assert_eq!(plan.reclaimable_bytes, 3 * 16 + 10);
+
+    assert_eq!(stores.vectors.list_embedding_ids().await.unwrap().len(), 5);
+    assert_eq!(stores.documents.list_chunk_ids().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_apply_deletes_in_batches() {
+    let stores = setup().await;
+    let service = stores.service().with_batch_size(1);
+
+    let plan = service.scan().await.unwrap();
+    let mut progress = Vec::new();
+    let report = service
+        .apply(&plan, |p: CompactionProgress| progress.push(p.deleted))
+        .await
+        .unwrap();
+
+    assert_eq!(report.embeddings_deleted, 2);
+    assert_eq!(report.chunks_deleted, 1);
+    assert_eq!(progress, vec![1, 2, 3]);
+
+    let mut embeddings = stores.vectors.list_embedding_ids().await.unwrap();
+    embeddings.sort_by_key(|id| id.0);
+    assert_eq!(embeddings, vec![ChunkId(1), ChunkId(3)]);
+    assert!(stores.documents.get_chunk(ChunkId(2)).await.unwrap().is_none());
+
+    // A second scan finds nothing left
+    assert!(service.scan().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_degenerate_chunks_are_purged_with_their_embeddings() {
+    let stores = setup().await;
+    let blank = TextChunk {
+        content: " ".to_string(),
+        ..chunk(4, Some(1))
+    };
+    let punctuation = TextChunk {
+        content: "-- .".to_string(),
+        ..chunk(5, Some(1))
+    };
+    stores.documents.store_chunks(&[blank, punctuation]).await.unwrap();
+    stores.vectors.store_embeddings(&[embedding(4), embedding(5)]).await.unwrap();
+    let service = stores.service();
+
+    let mut plan = service.scan().await.unwrap();
+    plan.degenerate_chunks.sort_by_key(|id| id.0);
+    assert_eq!(plan.degenerate_chunks, vec![ChunkId(4), ChunkId(5)]);
+    assert_eq!(plan.dangling_chunks, vec![ChunkId(2)]);
+
+    let report = service.apply(&plan, |_| {}).await.unwrap();
+    assert_eq!(report.degenerate_deleted, 2);
+    assert!(stores.documents.get_chunk(ChunkId(4)).await.unwrap().is_none());
+
+    let mut embeddings = stores.vectors.list_embedding_ids().await.unwrap();
+    embeddings.sort_by_key(|id| id.0);
+    assert_eq!(embeddings, vec![ChunkId(1), ChunkId(3)]);
+}
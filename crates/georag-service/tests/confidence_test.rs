@@ -0,0 +1,168 @@
+//! Integration tests for result confidence against the index's score baseline
+//!
+//! The index state carries a fixed baseline of the scores 0.00 to 1.00, so a
+//! source's percentile is its semantic score times 100. Three chunks sit at
+//! cosines 0.995, 0.97 and 0.6 from every query.
+
+use chrono::Utc;
+use georag_core::error::Result;
+use georag_core::llm::Embedder;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, ConfidenceCutoffs, ConfidenceLevel, Embedding, IndexState,
+    ScoreBaseline, TextChunk, INDEX_SCHEMA_VERSION,
+};
+use georag_retrieval::{QueryPlan, QueryResult};
+use georag_service::QueryService;
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Embedder placing every query on the first axis
+struct AxisEmbedder(&'static str);
+
+impl Embedder for AxisEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        self.0
+    }
+}
+
+const COSINES: [f32; 3] = [0.995, 0.97, 0.6];
+
+fn chunk(id: u64) -> TextChunk {
+    let content = format!("field report {}", id);
+    TextChunk {
+        id: ChunkId(id),
+        source: ChunkSource {
+            document_path: "/data/reports.txt".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        content,
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 3,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+fn index_state() -> IndexState {
+    let uniform: Vec<f32> = (0..=100).map(|i| i as f32 / 100.0).collect();
+    IndexState {
+        schema_version: INDEX_SCHEMA_VERSION,
+        hash: "4f1c2a9e8b7d6c5a".to_string(),
+        built_at: Utc::now(),
+        embedder: "axis".to_string(),
+        chunk_count: COSINES.len(),
+        embedding_dim: 2,
+        dataset_built_at: BTreeMap::new(),
+        contents: None,
+        last_build_diff: None,
+        tokenizer: None,
+        score_baseline: ScoreBaseline::from_scores(&uniform, 101),
+    }
+}
+
+async fn service() -> QueryService {
+    let vectors = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    let chunks: Vec<TextChunk> = (1..=COSINES.len() as u64).map(chunk).collect();
+    documents.store_chunks(&chunks).await.unwrap();
+    let embeddings: Vec<Embedding> = COSINES
+        .iter()
+        .zip(&chunks)
+        .map(|(cosine, chunk)| Embedding {
+            chunk_id: chunk.id,
+            vector: vec![*cosine, (1.0 - cosine * cosine).sqrt()],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    QueryService::new(Arc::new(MemorySpatialStore::new()), vectors, documents)
+        .with_index_state(index_state())
+}
+
+fn percentiles(result: &QueryResult) -> Vec<(f32, ConfidenceLevel)> {
+    result
+        .sources
+        .iter()
+        .map(|s| {
+            let confidence = s.confidence.unwrap();
+            ((confidence.percentile * 10.0).round() / 10.0, confidence.level)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sources_are_placed_in_the_baseline_and_labelled() {
+    let plan = QueryPlan::new("field reports").with_explain(true);
+    let result = service().await.execute(&plan, AxisEmbedder("axis")).await.unwrap();
+
+    assert_eq!(
+        percentiles(&result),
+        [
+            (99.5, ConfidenceLevel::High),
+            (97.0, ConfidenceLevel::Medium),
+            (60.0, ConfidenceLevel::Low)
+        ]
+    );
+
+    let explanation = result.explanation.unwrap();
+    let confidence = explanation.confidence.unwrap();
+    assert_eq!(confidence.baseline_samples, 101);
+    assert_eq!((confidence.high_cutoff, confidence.medium_cutoff), (99.0, 95.0));
+    assert!(confidence.caveat.contains("not how likely a result is to be correct"));
+
+    let detail = &explanation.ranking_details[1];
+    assert!((detail.percentile.unwrap() - 97.0).abs() < 0.1);
+    assert!(
+        detail.score_explanation.contains("percentile 97.0"),
+        "{}",
+        detail.score_explanation
+    );
+}
+
+#[tokio::test]
+async fn test_configured_cutoffs_relabel_sources() {
+    let cutoffs = ConfidenceCutoffs { high: 95.0, medium: 50.0 };
+    let service = service().await.with_confidence_cutoffs(cutoffs);
+    let result = service.execute(&QueryPlan::new("field reports"), AxisEmbedder("axis")).await;
+
+    let levels: Vec<ConfidenceLevel> =
+        percentiles(&result.unwrap()).into_iter().map(|(_, level)| level).collect();
+    assert_eq!(levels, [ConfidenceLevel::High, ConfidenceLevel::High, ConfidenceLevel::Medium]);
+}
+
+#[tokio::test]
+async fn test_no_confidence_without_a_comparable_baseline() {
+    let service = service().await;
+
+    // Scores of another embedder say nothing about the index's baseline
+    let plan = QueryPlan::new("field reports").with_explain(true);
+    let result = service.execute(&plan, AxisEmbedder("other")).await.unwrap();
+    assert_eq!(result.sources.len(), 3);
+    assert!(result.sources.iter().all(|s| s.confidence.is_none()));
+    assert!(result.explanation.unwrap().confidence.is_none());
+
+    // Positional scores are not similarities
+    let plan = QueryPlan::new("field reports").with_semantic_rerank(false);
+    let result = service.execute(&plan, AxisEmbedder("axis")).await.unwrap();
+    assert!(result.sources.iter().all(|s| s.confidence.is_none()));
+}
@@ -0,0 +1,306 @@
+//! Integration tests for converting dataset files between formats
+//!
+//! Each reader gets a fixture with a harbor and a market, in two layers for
+//! formats that have them. Every fixture whose reader is compiled in is
+//! converted to every output format and read back.
+
+use georag_core::formats::{FormatOptions, FormatRegistry, ReadPolicy};
+use georag_core::models::Geometry;
+use georag_service::{
+    ConvertFormat, ConvertOptions, ConvertService, PropertyMapping, ServiceError,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const GEOJSON: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {"type": "Feature", "id": "harbor", "geometry": {"type": "Point", "coordinates": [106.81, -6.1]},
+     "properties": {"name": "Harbor", "berths": 12}},
+    {"type": "Feature", "id": "market", "geometry": {"type": "LineString", "coordinates": [[106.8, -6.2], [106.82, -6.21]]},
+     "properties": {"name": "Market, old town", "open": true}}
+  ]
+}"#;
+
+const KML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+  <Document>
+    <Folder name="Harbor">
+      <name>Harbor</name>
+      <Placemark>
+        <name>Harbor</name>
+        <description>Ferry "Pier 1"</description>
+        <Point><coordinates>106.81,-6.1,0</coordinates></Point>
+      </Placemark>
+    </Folder>
+    <Folder name="Market">
+      <name>Market</name>
+      <Placemark>
+        <name>Market</name>
+        <LineString><coordinates>106.8,-6.2,0 106.82,-6.21,0</coordinates></LineString>
+      </Placemark>
+    </Folder>
+  </Document>
+</kml>"#;
+
+const GPX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test">
+  <wpt lat="-6.1" lon="106.81">
+    <ele>3.5</ele>
+    <name>Harbor</name>
+  </wpt>
+  <trk>
+    <name>Market</name>
+    <trkseg>
+      <trkpt lat="-6.2" lon="106.8"><ele>4.0</ele></trkpt>
+      <trkpt lat="-6.21" lon="106.82"><ele>4.5</ele></trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+/// Write the fixture of every registered reader, with the options to read it
+fn fixtures(dir: &Path, registry: &FormatRegistry) -> Vec<(PathBuf, FormatOptions)> {
+    let mut fixtures = Vec::new();
+    for (name, content) in [("places.geojson", GEOJSON), ("places.kml", KML), ("places.gpx", GPX)] {
+        let path = dir.join(name);
+        if registry.detect_format(&path).is_ok() {
+            fs::write(&path, content).unwrap();
+            fixtures.push((path, FormatOptions::new()));
+        }
+    }
+
+    let path = dir.join("places.shp");
+    if registry.detect_format(&path).is_ok() {
+        write_shapefile(&path);
+        fixtures.push((path, FormatOptions::new().with_option("crs", "4326")));
+    }
+    fixtures
+}
+
+fn write_shapefile(path: &Path) {
+    use shapefile::dbase::{FieldValue, Record, TableWriterBuilder};
+
+    let table = TableWriterBuilder::new().add_character_field("name".try_into().unwrap(), 50);
+    let mut writer = shapefile::Writer::from_path(path, table).unwrap();
+    for (name, x, y) in [("Harbor", 106.81, -6.1), ("Market", 106.8, -6.2)] {
+        let mut record = Record::default();
+        record.insert("name".to_string(), FieldValue::Character(Some(name.to_string())));
+        writer.write_shape_and_record(&shapefile::Point::new(x, y), &record).unwrap();
+    }
+}
+
+/// A feature as compared across a round trip
+#[derive(Debug, PartialEq)]
+struct Written {
+    id: String,
+    geometry: Option<Geometry>,
+    properties: HashMap<String, Value>,
+}
+
+/// Read a dataset file directly, as the conversion reads it
+async fn read(registry: &FormatRegistry, path: &Path, options: &FormatOptions) -> Vec<Written> {
+    let reader = registry.detect_format(path).unwrap();
+    let dataset = reader.read_with_options(path, options).await.unwrap();
+    dataset
+        .features
+        .into_iter()
+        .map(|feature| Written {
+            id: feature.id,
+            geometry: feature.geometry.as_ref().and_then(Geometry::from_geojson),
+            properties: feature.properties,
+        })
+        .collect()
+}
+
+/// Split CSV into rows keyed by the header, undoing RFC 4180 quoting
+fn parse_csv(csv: &str) -> Vec<HashMap<String, String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    let header = rows.remove(0);
+    rows.into_iter().map(|row| header.iter().cloned().zip(row).collect()).collect()
+}
+
+/// A property value as a CSV field holds it
+fn csv_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_every_reader_round_trips_through_every_writer() {
+    let dir = tempfile::tempdir().unwrap();
+    let registry = Arc::new(FormatRegistry::with_defaults());
+    let service = ConvertService::new(registry.clone());
+
+    for (path, read_options) in fixtures(dir.path(), &registry) {
+        let expected = read(&registry, &path, &read_options).await;
+        assert_eq!(expected.len(), 2, "{}", path.display());
+
+        for format in [ConvertFormat::GeoJson, ConvertFormat::Csv] {
+            let options = ConvertOptions::new(format).with_read_options(read_options.clone());
+            let conversion = service.convert(&path, &options).await.unwrap();
+            assert_eq!(conversion.crs, 4326);
+            assert_eq!(conversion.layers.len(), 1);
+            assert_eq!(conversion.feature_count(), 2);
+            let content = &conversion.layers[0].content;
+
+            match format {
+                ConvertFormat::GeoJson => {
+                    let output = dir.path().join("output.geojson");
+                    fs::write(&output, content).unwrap();
+                    let written = read(&registry, &output, &FormatOptions::new()).await;
+                    assert_eq!(written, expected, "{} as GeoJSON", path.display());
+                }
+                ConvertFormat::Csv => {
+                    let rows = parse_csv(content);
+                    assert_eq!(rows.len(), expected.len(), "{} as CSV", path.display());
+                    for (row, feature) in rows.iter().zip(&expected) {
+                        assert_eq!(row["id"], feature.id);
+                        let geometry: Value = serde_json::from_str(&row["geometry"]).unwrap();
+                        assert_eq!(Geometry::from_geojson(&geometry), feature.geometry);
+                        for (key, value) in &feature.properties {
+                            assert_eq!(row[key], csv_text(value), "{} of {}", key, path.display());
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_layers_are_split_into_one_output_each() {
+    let dir = tempfile::tempdir().unwrap();
+    let registry = Arc::new(FormatRegistry::with_defaults());
+    let service = ConvertService::new(registry.clone());
+
+    let written: Vec<PathBuf> =
+        fixtures(dir.path(), &registry).into_iter().map(|(path, _)| path).collect();
+    let layered = [("places.kml", ["Harbor", "Market"]), ("places.gpx", ["track", "waypoint"])];
+    for (name, layers) in layered {
+        let path = dir.path().join(name);
+        if !written.contains(&path) {
+            continue;
+        }
+
+        let options = ConvertOptions::new(ConvertFormat::GeoJson).with_split_layers(true);
+        let conversion = service.convert(&path, &options).await.unwrap();
+        let names: Vec<Option<&str>> =
+            conversion.layers.iter().map(|layer| layer.layer.as_deref()).collect();
+        assert_eq!(names, layers.map(Some).to_vec(), "{}", path.display());
+        assert!(conversion.layers.iter().all(|layer| layer.feature_count == 1));
+
+        // Together, the layers are one collection holding both features
+        let options = ConvertOptions::new(ConvertFormat::GeoJson);
+        let combined = service.convert(&path, &options).await.unwrap();
+        assert_eq!(combined.layers.len(), 1);
+        assert_eq!(combined.layers[0].layer, None);
+        assert_eq!(combined.feature_count(), 2);
+    }
+}
+
+#[tokio::test]
+async fn test_properties_are_mapped_and_geometries_simplified() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("trail.geojson");
+    let coordinates: Vec<[f64; 2]> = (0..50)
+        .map(|i| [106.8 + i as f64 * 0.001, -6.2 + (i % 2) as f64 * 0.0001])
+        .collect();
+    let trail = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "id": "trail",
+            "geometry": {"type": "LineString", "coordinates": coordinates},
+            "properties": {"name": "Coastal trail", "internal_code": "X1"}
+        }]
+    });
+    fs::write(&path, trail.to_string()).unwrap();
+
+    let service = ConvertService::new(Arc::new(FormatRegistry::with_defaults()));
+    let options = ConvertOptions::new(ConvertFormat::GeoJson)
+        .with_mapping(PropertyMapping::new().rename("name", "title").rename("internal_code", ""))
+        .with_max_vertices(Some(10));
+    let conversion = service.convert(&path, &options).await.unwrap();
+    assert_eq!(conversion.simplified, 1);
+
+    let output: Value = serde_json::from_str(&conversion.layers[0].content).unwrap();
+    let feature = &output["features"][0];
+    assert_eq!(feature["properties"], serde_json::json!({"title": "Coastal trail"}));
+    let geometry = Geometry::from_geojson(&feature["geometry"]).unwrap();
+    assert!(geometry.vertex_count() <= 10);
+}
+
+#[tokio::test]
+async fn test_failed_features_are_reported_by_index_and_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("places.geojson");
+    fs::write(&path, GEOJSON).unwrap();
+    let service = ConvertService::new(Arc::new(FormatRegistry::with_defaults()));
+
+    // A line cannot be simplified below two vertices
+    let options = ConvertOptions::new(ConvertFormat::Csv).with_max_vertices(Some(1));
+    let err = service.convert(&path, &options).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Read(_)), "{:?}", err);
+    assert!(err.to_string().contains("feature 1 (id market): too many vertices"), "{}", err);
+
+    // A lenient read skips the feature and reports it
+    let options =
+        options.with_read_options(FormatOptions::new().with_read_policy(ReadPolicy::lenient(5)));
+    let conversion = service.convert(&path, &options).await.unwrap();
+    assert_eq!(conversion.feature_count(), 1);
+    assert_eq!(conversion.feature_errors.len(), 1);
+    assert_eq!(conversion.feature_errors[0].index, 1);
+    assert_eq!(conversion.feature_errors[0].id.as_deref(), Some("market"));
+}
+
+#[tokio::test]
+async fn test_reprojected_output_names_its_crs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("places.geojson");
+    fs::write(&path, GEOJSON).unwrap();
+    let registry = Arc::new(FormatRegistry::with_defaults());
+    let service = ConvertService::new(registry.clone());
+
+    let options = ConvertOptions::new(ConvertFormat::GeoJson).with_crs(Some(3857));
+    let conversion = service.convert(&path, &options).await.unwrap();
+    assert_eq!((conversion.source_crs, conversion.crs), (4326, 3857));
+
+    let output = dir.path().join("mercator.geojson");
+    fs::write(&output, &conversion.layers[0].content).unwrap();
+    let reader = registry.detect_format(&output).unwrap();
+    let dataset = reader.read(&output).await.unwrap();
+    assert_eq!(dataset.crs, 3857);
+    let harbor = Geometry::from_geojson(dataset.features[0].geometry.as_ref().unwrap()).unwrap();
+    let Geometry::Point { coordinates: [x, _] } = harbor else {
+        panic!("expected a point, got {:?}", harbor);
+    };
+    // 106.81 degrees east on the spherical Mercator
+    assert!((x - 11_890_034.8).abs() < 1.0, "{}", x);
+}
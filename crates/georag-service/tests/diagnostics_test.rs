@@ -0,0 +1,205 @@
+//! Integration tests for the diagnostics of empty query results
+//!
+//! A park and a market feature, each with one indexed chunk, sit a few
+//! kilometres apart. Each test removes every candidate in a different way and
+//! checks the cause the query service reports.
+
+mod common;
+
+use chrono::Utc;
+use common::{chunk, dataset, KeywordEmbedder, Stores};
+use georag_core::models::{
+    Dataset, Feature, FeatureId, Geometry, IndexState, SpatialFilter, SpatialPredicate,
+    INDEX_SCHEMA_VERSION,
+};
+use georag_retrieval::models::TextFilter;
+use georag_retrieval::{AttributeFilter, DiagnosticKind, QueryPlan, QueryResult};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+const VOCABULARY: [&str; 4] = ["park", "bench", "market", "stall"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+impl Stores {
+    async fn query(&self, plan: &QueryPlan) -> QueryResult {
+        self.service().execute(plan, embedder()).await.unwrap()
+    }
+
+    async fn add_dataset(&self) {
+        let dataset = Dataset { feature_count: 2, ..dataset("places") };
+        self.spatial.store_dataset(&dataset).await.unwrap();
+    }
+
+    async fn add_place(&self, id: u64, x: f64, content: &str, category: &str) {
+        let mut properties = HashMap::new();
+        properties.insert("content".to_string(), json!(content));
+        properties.insert("category".to_string(), json!(category));
+        let feature =
+            Feature::with_geometry(FeatureId(id), Geometry::point(x, 0.0), properties, 4326);
+        self.spatial.store_features(&[feature]).await.unwrap();
+    }
+}
+
+/// A dataset with an indexed park at x=0 and an indexed market at x=0.05
+async fn setup() -> Stores {
+    let stores = Stores::new();
+    stores.add_dataset().await;
+    stores.add_place(1, 0.0, "park bench", "park").await;
+    stores.add_place(2, 0.05, "market stall", "market").await;
+    let chunks = vec![
+        chunk(1, "places", Some(1), "park bench"),
+        chunk(2, "places", Some(2), "market stall"),
+    ];
+    stores.index(&chunks, &embedder()).await;
+    stores
+}
+
+fn bbox(min_x: f64, max_x: f64) -> SpatialFilter {
+    let square = Geometry::polygon(vec![vec![
+        [min_x, -0.01],
+        [max_x, -0.01],
+        [max_x, 0.01],
+        [min_x, 0.01],
+        [min_x, -0.01],
+    ]]);
+    SpatialFilter::new(SpatialPredicate::Intersects).geometry(square)
+}
+
+fn kinds(result: &QueryResult) -> Vec<DiagnosticKind> {
+    result.diagnostics.iter().map(|d| d.kind).collect()
+}
+
+#[tokio::test]
+async fn test_results_carry_no_diagnostics() {
+    let stores = setup().await;
+    let result = stores.query(&QueryPlan::new("park bench")).await;
+
+    assert!(!result.sources.is_empty());
+    assert!(result.diagnostics.is_empty());
+    assert_eq!(result.candidates.indexed_chunks, 2);
+    assert_eq!(result.candidates.after_attributes, 2);
+}
+
+#[tokio::test]
+async fn test_workspace_without_datasets() {
+    let stores = Stores::new();
+    let result = stores.query(&QueryPlan::new("park bench")).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::NoDatasets]);
+    assert!(result.diagnostics[0].suggestion.contains("georag add"));
+}
+
+#[tokio::test]
+async fn test_datasets_without_index() {
+    let stores = Stores::new();
+    stores.add_dataset().await;
+    stores.add_place(1, 0.0, "park bench", "park").await;
+
+    let result = stores.query(&QueryPlan::new("park bench")).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::IndexEmpty]);
+    assert!(result.diagnostics[0].suggestion.contains("georag build"));
+}
+
+#[tokio::test]
+async fn test_bbox_without_features() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("park bench").with_spatial_filter(bbox(10.0, 11.0));
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::NoFeaturesInFilter]);
+    assert_eq!(result.candidates.features_matched, Some(0));
+    assert!(result.diagnostics[0].suggestion.contains("Widen the bbox"));
+}
+
+#[tokio::test]
+async fn test_features_in_bbox_without_chunks() {
+    let stores = setup().await;
+    stores.add_place(3, 0.5, "park bench", "park").await;
+    let plan = QueryPlan::new("park bench").with_spatial_filter(bbox(0.4, 0.6));
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::NoChunksInFilter]);
+    assert_eq!(result.candidates.features_matched, Some(1));
+    assert_eq!(result.candidates.after_spatial, 0);
+}
+
+#[tokio::test]
+async fn test_keyword_filter_removes_every_candidate() {
+    let stores = setup().await;
+    let mut filter = TextFilter::new();
+    filter.must_contain.push("fountain".to_string());
+    let plan = QueryPlan::new("park bench").with_text_filter(filter);
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::TextFilter]);
+}
+
+#[tokio::test]
+async fn test_attribute_filter_removes_every_candidate() {
+    let stores = setup().await;
+    let plan =
+        QueryPlan::new("park bench").with_attribute_filter(AttributeFilter::new("category", "zoo"));
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::AttributeFilter]);
+    assert_eq!(result.candidates.after_text, 2);
+    assert_eq!(result.candidates.after_attributes, 0);
+}
+
+#[tokio::test]
+async fn test_min_score_removes_every_candidate() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("bench")
+        .with_spatial_filter(bbox(0.04, 0.06))
+        .with_min_score(0.5);
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::BelowMinScore]);
+}
+
+#[tokio::test]
+async fn test_candidates_outside_top_k() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("park bench").with_spatial_filter(bbox(0.04, 0.06)).with_top_k(1);
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(kinds(&result), vec![DiagnosticKind::NotInTopMatches]);
+    assert!(result.diagnostics[0].suggestion.contains("top_k"));
+}
+
+#[tokio::test]
+async fn test_index_built_with_another_embedder() {
+    let stores = setup().await;
+    let index = IndexState {
+        schema_version: INDEX_SCHEMA_VERSION,
+        hash: "abc".to_string(),
+        built_at: Utc::now(),
+        embedder: "nomic-embed-text".to_string(),
+        chunk_count: 2,
+        embedding_dim: 768,
+        dataset_built_at: BTreeMap::new(),
+        contents: None,
+        last_build_diff: None,
+        tokenizer: None,
+        score_baseline: None,
+    };
+    let plan = QueryPlan::new("park bench").with_spatial_filter(bbox(10.0, 11.0));
+
+    let result = stores.service().with_index_state(index).execute(&plan, embedder()).await;
+
+    assert_eq!(
+        kinds(&result.unwrap()),
+        vec![DiagnosticKind::EmbedderMismatch, DiagnosticKind::NoFeaturesInFilter]
+    );
+}
@@ -0,0 +1,274 @@
+//! Integration tests for differential dataset updates
+//!
+//! A stored FeatureCollection is compared with a synthetic next version that
+//! contains one feature of each change class, matched both by key property
+//! and by content hash.
+
+use chrono::Utc;
+use georag_core::formats::FormatRegistry;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, DatasetPreview, Embedding, Feature,
+    FeatureId, GeometryType, TextChunk,
+};
+use georag_service::{DiffService, IngestRequest, IngestService};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const BEFORE: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.80, -6.20] },
+      "properties": { "id": "a", "name": "Monas" } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.82, -6.17] },
+      "properties": { "id": "b", "name": "Istiqlal" } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+      "properties": { "id": "c", "name": "Cathedral" } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.85, -6.21] },
+      "properties": { "id": "d", "name": "Old Station" } }
+  ]
+}"#;
+
+// a: unchanged, b: moved, c: renamed, d: removed, e: added
+const AFTER: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.80, -6.20] },
+      "properties": { "id": "a", "name": "Monas" } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.831, -6.170] },
+      "properties": { "id": "b", "name": "Istiqlal" } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.83, -6.18] },
+      "properties": { "id": "c", "name": "Jakarta Cathedral", "open": true } },
+    { "type": "Feature", "geometry": { "type": "Point", "coordinates": [106.86, -6.13] },
+      "properties": { "id": "e", "name": "Kota Tua" } }
+  ]
+}"#;
+
+struct Fixture {
+    dir: TempDir,
+    spatial: Arc<MemorySpatialStore>,
+    documents: Arc<MemoryDocumentStore>,
+    vectors: Arc<MemoryVectorStore>,
+    dataset_id: DatasetId,
+}
+
+impl Fixture {
+    fn service(&self) -> DiffService {
+        DiffService::new(self.spatial.clone(), self.documents.clone(), self.vectors.clone())
+    }
+
+    /// Read a FeatureCollection through the format registry
+    async fn read(&self, name: &str, content: &str) -> Vec<Feature> {
+        let path = self.dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        let ingest =
+            IngestService::new(self.spatial.clone(), Arc::new(FormatRegistry::with_defaults()));
+        let request = IngestRequest::new(path).with_store_features(false);
+        ingest.prepare(&request).await.unwrap().features
+    }
+}
+
+/// Store BEFORE as a dataset with one embedded chunk per feature
+async fn setup(before: &str) -> Fixture {
+    let dir = TempDir::new().unwrap();
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let dataset = Dataset {
+        id: DatasetId(0),
+        name: "landmarks".to_string(),
+        path: PathBuf::from("/data/landmarks.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 4,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    };
+    let dataset_id = spatial.store_dataset(&dataset).await.unwrap();
+
+    let fixture = Fixture {
+        dir,
+        spatial,
+        documents: Arc::new(MemoryDocumentStore::new()),
+        vectors: Arc::new(MemoryVectorStore::new()),
+        dataset_id,
+    };
+
+    let features = fixture.read("before.geojson", before).await;
+    fixture.spatial.upsert_dataset_features(dataset_id, &features).await.unwrap();
+
+    let chunks: Vec<TextChunk> = features
+        .iter()
+        .map(|feature| TextChunk {
+            id: ChunkId(feature.id.0),
+            content: feature.properties["name"].to_string(),
+            source: ChunkSource {
+                document_path: "/data/landmarks.geojson".to_string(),
+                page: None,
+                offset: 0,
+                length: 0,
+            },
+            spatial_ref: Some(feature.id),
+            geometry: None,
+            metadata: ChunkMetadata {
+                size: 0,
+                properties: Default::default(),
+                source_hash: None,
+                stale: false,
+                dataset_id: None,
+            },
+        })
+        .collect();
+    let embeddings: Vec<Embedding> = chunks
+        .iter()
+        .map(|chunk| Embedding {
+            chunk_id: chunk.id,
+            vector: vec![1.0, 0.0],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    fixture.documents.store_chunks(&chunks).await.unwrap();
+    fixture.vectors.store_embeddings(&embeddings).await.unwrap();
+
+    fixture
+}
+
+#[tokio::test]
+async fn test_diff_by_key_reports_each_change_class() {
+    let fixture = setup(BEFORE).await;
+    let incoming = fixture.read("after.geojson", AFTER).await;
+
+    let diff = fixture.service().diff(fixture.dataset_id, incoming, None).await.unwrap();
+    let counts = diff.counts();
+
+    assert_eq!(diff.key, "id");
+    assert_eq!(diff.matched_by_key, 3);
+    assert_eq!(counts.added, 1);
+    assert_eq!(counts.removed, 1);
+    assert_eq!(counts.geometry_changed, 1);
+    assert_eq!(counts.property_changed, 1);
+    assert_eq!(counts.unchanged, 1);
+
+    assert_eq!(diff.added[0].properties["id"], "e");
+    assert_eq!(diff.removed[0].properties["id"], "d");
+
+    let moved = diff.changed.iter().find(|c| c.geometry_changed).unwrap();
+    assert_eq!(moved.key.as_deref(), Some("b"));
+    assert!(moved.changed_properties.is_empty());
+
+    let renamed = diff.changed.iter().find(|c| !c.changed_properties.is_empty()).unwrap();
+    assert_eq!(renamed.key.as_deref(), Some("c"));
+    assert_eq!(renamed.changed_properties, vec!["name", "open"]);
+    assert_eq!(renamed.after.id, renamed.before.id);
+}
+
+#[tokio::test]
+async fn test_diff_writes_nothing() {
+    let fixture = setup(BEFORE).await;
+    let incoming = fixture.read("after.geojson", AFTER).await;
+
+    fixture.service().diff(fixture.dataset_id, incoming, None).await.unwrap();
+
+    let stored = fixture.spatial.get_features_for_dataset(fixture.dataset_id).await.unwrap();
+    assert_eq!(stored.len(), 4);
+    assert_eq!(fixture.documents.list_chunk_ids().await.unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_diff_falls_back_to_content_hash() {
+    let strip_ids = |content: &str| {
+        ["a", "b", "c", "d", "e"].iter().fold(content.to_string(), |text, id| {
+            text.replace(&format!("\"id\": \"{}\", ", id), "")
+        })
+    };
+    let fixture = setup(&strip_ids(BEFORE)).await;
+    let incoming = fixture.read("after.geojson", &strip_ids(AFTER)).await;
+
+    let diff = fixture.service().diff(fixture.dataset_id, incoming, None).await.unwrap();
+    let counts = diff.counts();
+
+    assert_eq!(diff.matched_by_key, 0);
+    assert_eq!(counts.unchanged, 1);
+    assert_eq!(counts.geometry_changed, 1);
+    assert_eq!(counts.property_changed, 1);
+    assert_eq!(counts.added, 1);
+    assert_eq!(counts.removed, 1);
+    assert_eq!(diff.removed[0].properties["name"], "Old Station");
+}
+
+#[tokio::test]
+async fn test_identical_file_has_no_changes() {
+    let fixture = setup(BEFORE).await;
+    let incoming = fixture.read("same.geojson", BEFORE).await;
+
+    let diff = fixture.service().diff(fixture.dataset_id, incoming, Some("id")).await.unwrap();
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.counts().unchanged, 4);
+}
+
+#[tokio::test]
+async fn test_apply_updates_features_and_invalidates_affected_chunks() {
+    let fixture = setup(BEFORE).await;
+    let incoming = fixture.read("after.geojson", AFTER).await;
+    let service = fixture.service();
+
+    let diff = service.diff(fixture.dataset_id, incoming, None).await.unwrap();
+    let report = service.apply(&diff).await.unwrap();
+
+    assert_eq!(report.features_upserted, 3);
+    assert_eq!(report.features_deleted, 1);
+    assert_eq!(report.chunks_invalidated, 3);
+
+    // Only the unchanged feature keeps its chunk and embedding
+    let chunks = fixture.documents.list_chunk_ids().await.unwrap();
+    assert_eq!(chunks, vec![ChunkId(0)]);
+    assert!(fixture.vectors.get_embedding(ChunkId(0)).await.unwrap().is_some());
+    assert!(fixture.vectors.get_embedding(ChunkId(1)).await.unwrap().is_none());
+
+    // The stored dataset now equals the incoming file
+    let again = fixture.read("after.geojson", AFTER).await;
+    let diff = service.diff(fixture.dataset_id, again, None).await.unwrap();
+    assert!(diff.is_empty());
+
+    let stored = fixture.spatial.get_features_for_dataset(fixture.dataset_id).await.unwrap();
+    let added = stored.iter().find(|f| f.properties["id"] == "e").unwrap();
+    assert_eq!(added.id, FeatureId(4));
+    assert!(fixture.spatial.get_feature(FeatureId(3)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_apply_regenerates_the_preview() {
+    let fixture = setup(BEFORE).await;
+    let incoming = fixture.read("after.geojson", AFTER).await;
+    let service = fixture.service();
+
+    let diff = service.diff(fixture.dataset_id, incoming, None).await.unwrap();
+    service.apply(&diff).await.unwrap();
+
+    let dataset = fixture.spatial.get_dataset(fixture.dataset_id).await.unwrap().unwrap();
+    let Some(DatasetPreview::Vector(preview)) = dataset.format.preview else {
+        panic!("expected a vector preview, got {:?}", dataset.format.preview);
+    };
+    // Kota Tua widened the extent north; the removed Old Station no longer counts
+    assert_eq!(preview.extent, Some([106.8, -6.2, 106.86, -6.13]));
+    assert_eq!(preview.geometry_types[0].count, 4);
+    assert!(preview.property_keys.contains(&"open".to_string()));
+}
@@ -0,0 +1,202 @@
+//! Integration tests for the change event log and its replay
+//!
+//! Events are recorded through the service into a memory log; reads wait
+//! for appends, refuse readers behind pruned events, and replaying the
+//! events of a workspace into a fresh spatial store rebuilds its datasets.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChangeEntity, ChangeEvent, ChangeOperation, ChunkId, Dataset, DatasetId, Feature, FeatureId,
+    Geometry, GeometryType, WorkspaceId,
+};
+use georag_service::{EventLogService, EventReplay, ServiceError};
+use georag_store::memory::{MemoryDocumentStore, MemoryEventLog, MemorySpatialStore};
+use georag_store::ports::{DocumentStore, SpatialStore};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn dataset(name: &str, feature_count: usize) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}.geojson", name)),
+        geometry_type: GeometryType::Point,
+        feature_count,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(id: u64) -> Feature {
+    Feature {
+        id: FeatureId(id),
+        geometry: Some(Geometry::point(106.8, -6.2)),
+        properties: HashMap::from([("name".to_string(), serde_json::json!(id))]),
+        crs: 4326,
+        z: None,
+    }
+}
+
+/// Store a dataset with features `ids` in `spatial`
+async fn add(spatial: &MemorySpatialStore, name: &str, ids: std::ops::Range<u64>) -> DatasetId {
+    let id = spatial.store_dataset(&dataset(name, ids.clone().count())).await.unwrap();
+    let features: Vec<Feature> = ids.map(feature).collect();
+    spatial.upsert_dataset_features(id, &features).await.unwrap();
+    id
+}
+
+fn chunk_event(workspace: WorkspaceId, id: u64) -> ChangeEvent {
+    ChangeEvent::new(workspace, ChangeEntity::Chunk, ChangeOperation::Upsert, vec![id])
+}
+
+#[tokio::test]
+async fn test_read_waits_for_an_append() {
+    let service = EventLogService::new(Arc::new(MemoryEventLog::new()));
+    let workspace = WorkspaceId::new();
+
+    let empty = service.read(0, 10, Duration::ZERO).await.unwrap();
+    assert!(empty.events.is_empty());
+    assert_eq!(empty.next_after_seq(), 0);
+
+    let reader = service.clone();
+    let read = tokio::spawn(async move { reader.read(0, 10, Duration::from_secs(30)).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    service.record(vec![chunk_event(workspace, 1), chunk_event(workspace, 2)]).await;
+
+    let page = tokio::time::timeout(Duration::from_secs(5), read)
+        .await
+        .expect("the append should end the wait")
+        .unwrap()
+        .unwrap();
+    let seqs: Vec<u64> = page.events.iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![1, 2]);
+    assert_eq!(page.next_after_seq(), 2);
+    assert_eq!(page.bounds.last, 2);
+}
+
+#[tokio::test]
+async fn test_readers_behind_pruned_events_are_refused() {
+    let service = EventLogService::new(Arc::new(MemoryEventLog::with_capacity(2)));
+    let workspace = WorkspaceId::new();
+    for id in 1..=3 {
+        service.record(vec![chunk_event(workspace, id)]).await;
+    }
+
+    let err = service.read(0, 10, Duration::ZERO).await.unwrap_err();
+    assert!(
+        matches!(err, ServiceError::EventsPruned { after_seq: 0, first: Some(2) }),
+        "{:?}",
+        err
+    );
+    let page = service.read(1, 10, Duration::ZERO).await.unwrap();
+    assert_eq!(page.events.len(), 2);
+
+    assert!(matches!(
+        service.read(1, 0, Duration::ZERO).await,
+        Err(ServiceError::InvalidEventRead(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_consumer_cursors() {
+    let service = EventLogService::new(Arc::new(MemoryEventLog::new()));
+    service.record(vec![chunk_event(WorkspaceId::new(), 1)]).await;
+
+    assert_eq!(service.cursor("indexer").await.unwrap(), 0);
+    service.acknowledge("indexer", 1).await.unwrap();
+    assert_eq!(service.cursor("indexer").await.unwrap(), 1);
+
+    assert!(matches!(
+        service.acknowledge("indexer", 2).await,
+        Err(ServiceError::InvalidEventRead(_))
+    ));
+    assert!(matches!(
+        service.cursor("no/slash").await,
+        Err(ServiceError::InvalidEventRead(_))
+    ));
+    assert!(matches!(service.cursor("").await, Err(ServiceError::InvalidEventRead(_))));
+}
+
+#[tokio::test]
+async fn test_replay_rebuilds_the_datasets_of_a_workspace() {
+    let service = EventLogService::new(Arc::new(MemoryEventLog::new()));
+    let (workspace, other) = (WorkspaceId::new(), WorkspaceId::new());
+    let source = MemorySpatialStore::new();
+
+    let parks = add(&source, "parks", 1..4).await;
+    service.record_dataset_added(workspace, &source, parks).await;
+    // More features than one event carries
+    let roads = add(&source, "roads", 10..1210).await;
+    service.record_dataset_added(workspace, &source, roads).await;
+    let lakes = add(&source, "lakes", 2000..2002).await;
+    service.record_dataset_added(other, &source, lakes).await;
+
+    source.set_dataset_tags(parks, &["public".to_string()]).await.unwrap();
+    let tagged = source.get_dataset(parks).await.unwrap().unwrap();
+    service.record_dataset_updated(workspace, &tagged).await;
+
+    let documents = MemoryDocumentStore::new();
+    service.record_chunks_built(workspace, &documents, &[ChunkId(1)]).await;
+
+    let events = service.read(0, 100, Duration::ZERO).await.unwrap().events;
+    // parks and its features, roads in four, lakes in two, the tags, the removed chunk
+    assert_eq!(events.len(), 2 + 4 + 2 + 1 + 1);
+    let target = Arc::new(MemorySpatialStore::new());
+    let mut replay = EventReplay::new(workspace, target.clone());
+    assert_eq!(replay.apply(&events).await.unwrap(), 7);
+    assert_eq!(replay.applied_seq(), events.last().unwrap().seq);
+
+    let datasets = target.list_datasets().await.unwrap();
+    let names: Vec<&str> = datasets.iter().map(|dataset| dataset.name.as_str()).collect();
+    assert_eq!(names, vec!["parks", "roads"]);
+    assert_eq!(datasets[0].tags, vec!["public".to_string()]);
+    let replayed_roads = replay.target_dataset(roads).unwrap();
+    assert_eq!(target.get_features_for_dataset(replayed_roads).await.unwrap().len(), 1200);
+
+    // Applying the same events again changes nothing
+    assert_eq!(replay.apply(&events).await.unwrap(), 0);
+    assert_eq!(target.list_datasets().await.unwrap().len(), 2);
+
+    service.record_dataset_deleted(workspace, roads).await;
+    let page = service.read(replay.applied_seq(), 100, Duration::ZERO).await.unwrap();
+    assert_eq!(replay.apply(&page.events).await.unwrap(), 1);
+    assert_eq!(target.list_datasets().await.unwrap().len(), 1);
+    assert!(target.get_features_for_dataset(replayed_roads).await.unwrap().is_empty());
+    assert_eq!(replay.target_dataset(roads), None);
+}
+
+#[tokio::test]
+async fn test_builds_record_removed_and_stored_chunks() {
+    let service = EventLogService::new(Arc::new(MemoryEventLog::new()));
+    let workspace = WorkspaceId::new();
+    let documents = MemoryDocumentStore::new();
+    assert!(documents.list_chunk_ids().await.unwrap().is_empty());
+
+    service
+        .record_chunks_built(workspace, &documents, &[ChunkId(4), ChunkId(5)])
+        .await;
+    let events = service.read(0, 10, Duration::ZERO).await.unwrap().events;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].entity, ChangeEntity::Chunk);
+    assert_eq!(events[0].operation, ChangeOperation::Delete);
+    assert_eq!(events[0].ids, vec![4, 5]);
+}
@@ -0,0 +1,255 @@
+//! Integration tests for the feature lookups of query responses
+//!
+//! A store counting its lookups serves six chunks over four features. The
+//! geometries of every ranked source are read in one batch, and through a
+//! feature cache a repeated query reads none.
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::geo::{JoinCounts, SampleStrategy, SpatialJoin};
+use georag_core::llm::Embedder;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, DatasetMeta, DatasetPreview,
+    Embedding, Feature, FeatureId, Geometry, SpatialFilter, TagVisibility, TextChunk,
+};
+use georag_retrieval::QueryPlan;
+use georag_service::QueryService;
+use georag_store::feature_cache::{CachedSpatialStore, FeatureCache, FeatureCacheStats};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Spatial store counting single and batched feature lookups
+#[derive(Default)]
+struct CountingStore {
+    inner: MemorySpatialStore,
+    single: AtomicUsize,
+    batched: AtomicUsize,
+}
+
+impl CountingStore {
+    fn lookups(&self) -> (usize, usize) {
+        (self.single.load(Ordering::SeqCst), self.batched.load(Ordering::SeqCst))
+    }
+}
+
+#[async_trait]
+impl SpatialStore for CountingStore {
+    async fn store_dataset(&self, dataset: &Dataset) -> Result<DatasetId> {
+        self.inner.store_dataset(dataset).await
+    }
+
+    async fn get_dataset(&self, id: DatasetId) -> Result<Option<Dataset>> {
+        self.inner.get_dataset(id).await
+    }
+
+    async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_datasets().await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_visible_datasets(visibility).await
+    }
+
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()> {
+        self.inner.set_dataset_tags(id, tags).await
+    }
+
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()> {
+        self.inner.set_dataset_preview(id, preview).await
+    }
+
+    async fn delete_dataset(&self, id: DatasetId) -> Result<()> {
+        self.inner.delete_dataset(id).await
+    }
+
+    async fn store_features(&self, features: &[Feature]) -> Result<()> {
+        self.inner.store_features(features).await
+    }
+
+    async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>> {
+        self.inner.spatial_query(filter).await
+    }
+
+    async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>> {
+        self.single.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_feature(id).await
+    }
+
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        self.batched.fetch_add(1, Ordering::SeqCst);
+        self.inner.get_features(ids).await
+    }
+
+    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
+        self.inner.get_features_for_dataset(dataset_id).await
+    }
+
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()> {
+        self.inner.update_feature_properties(features).await
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()> {
+        self.inner.upsert_dataset_features(dataset_id, features).await
+    }
+
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()> {
+        self.inner.delete_features(dataset_id, ids).await
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        self.inner.sample_features(dataset_id, n, strategy).await
+    }
+
+    async fn spatial_join(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        self.inner.spatial_join(target, source, join).await
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        geometry: &Geometry,
+        max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        self.inner.subdivide_geometry(geometry, max_vertices).await
+    }
+}
+
+/// Embedder placing every query on the first axis
+struct AxisEmbedder;
+
+impl Embedder for AxisEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        "axis"
+    }
+}
+
+fn chunk(id: u64, feature_id: Option<u64>) -> TextChunk {
+    let content = format!("parcel note {}", id);
+    TextChunk {
+        id: ChunkId(id),
+        source: ChunkSource {
+            document_path: "/data/parcels.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        content,
+        spatial_ref: feature_id.map(FeatureId),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 3,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+/// Four parcels; parcel 1 has two chunks and chunk 6 no parcel
+async fn fixture(spatial: Arc<dyn SpatialStore>) -> QueryService {
+    let vectors = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    let features: Vec<Feature> = (1..=4)
+        .map(|id| {
+            Feature::with_geometry(
+                FeatureId(id),
+                Geometry::point(id as f64, 0.0),
+                HashMap::new(),
+                4326,
+            )
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+
+    let chunks: Vec<TextChunk> = [(1, Some(1)), (2, Some(1)), (3, Some(2)), (4, Some(3))]
+        .into_iter()
+        .chain([(5, Some(4)), (6, None)])
+        .map(|(id, feature_id)| chunk(id, feature_id))
+        .collect();
+    documents.store_chunks(&chunks).await.unwrap();
+    let embeddings: Vec<Embedding> = chunks
+        .iter()
+        .map(|chunk| Embedding {
+            chunk_id: chunk.id,
+            vector: vec![1.0, chunk.id.0 as f32 * 0.1],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    QueryService::new(spatial, vectors, documents)
+}
+
+/// Run a query and serialize its results as GeoJSON
+async fn query_geojson(service: &QueryService) -> Vec<serde_json::Value> {
+    let result = service.execute(&QueryPlan::new("parcel notes"), AxisEmbedder).await.unwrap();
+    assert_eq!(result.sources.len(), 6);
+    let collection = service.to_geojson(&result).await;
+    collection["features"].as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_geometries_of_all_sources_are_read_in_one_batch() {
+    let store = Arc::new(CountingStore::default());
+    let service = fixture(store.clone()).await;
+
+    let features = query_geojson(&service).await;
+    assert_eq!(store.lookups(), (0, 1));
+
+    // Every source with a parcel got its geometry, the other none
+    let located = features.iter().filter(|f| !f["geometry"].is_null()).count();
+    assert_eq!(located, 5);
+}
+
+#[tokio::test]
+async fn test_repeated_query_reads_features_from_the_cache() {
+    let store = Arc::new(CountingStore::default());
+    let cache = Arc::new(FeatureCache::default());
+    let cached = Arc::new(CachedSpatialStore::new(store.clone(), cache.clone()));
+    let service = fixture(cached.clone()).await;
+
+    let first = query_geojson(&service).await;
+    assert_eq!(store.lookups(), (0, 1));
+    assert_eq!(cache.stats(), FeatureCacheStats { hits: 0, misses: 4 });
+
+    let second = query_geojson(&service).await;
+    assert_eq!(store.lookups(), (0, 1));
+    assert_eq!(cache.stats(), FeatureCacheStats { hits: 4, misses: 4 });
+    assert_eq!(first, second);
+
+    // Moving a parcel drops it from the cache, and only it is read again
+    let moved =
+        Feature::with_geometry(FeatureId(2), Geometry::point(20.0, 0.0), HashMap::new(), 4326);
+    cached.store_features(&[moved]).await.unwrap();
+    let third = query_geojson(&service).await;
+    assert_eq!(store.lookups(), (0, 2));
+    assert_eq!(cache.stats(), FeatureCacheStats { hits: 7, misses: 5 });
+    assert!(third.iter().any(|f| f["geometry"]["coordinates"][0] == 20.0));
+}
@@ -0,0 +1,141 @@
+//! Integration tests for garbage collection of outdated chunks
+//!
+//! Chunks of deleted features are removed with their embeddings, and chunks
+//! of features whose text changed are marked stale and skipped by retrieval.
+
+mod common;
+
+use common::{chunk, KeywordEmbedder, Stores};
+use georag_core::models::{ChunkId, Feature, FeatureId, Geometry, TextChunk};
+use georag_core::processing::chunk::{source_text_hash, ChunkGenerator};
+use georag_retrieval::QueryPlan;
+use georag_service::{GcPlan, GcService};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use serde_json::json;
+use std::collections::HashMap;
+
+const VOCABULARY: [&str; 4] = ["park", "bench", "market", "stall"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+impl Stores {
+    fn gc(&self) -> GcService {
+        GcService::new(self.spatial.clone(), self.documents.clone(), self.vector.clone())
+    }
+
+    async fn set_content(&self, id: u64, content: &str) {
+        let mut properties = HashMap::new();
+        properties.insert("content".to_string(), json!(content));
+        let feature =
+            Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.2), properties, 4326);
+        self.spatial.store_features(&[feature]).await.unwrap();
+    }
+
+    async fn chunk(&self, id: u64) -> TextChunk {
+        self.documents.get_chunk(ChunkId(id)).await.unwrap().unwrap()
+    }
+}
+
+fn feature_chunk(id: u64, feature: u64, content: &str) -> TextChunk {
+    let mut properties = HashMap::new();
+    properties.insert("content".to_string(), json!(content));
+    let source =
+        Feature::with_geometry(FeatureId(feature), Geometry::point(0.0, 0.0), properties, 4326);
+    let text = ChunkGenerator::default().feature_text(&source).unwrap();
+
+    let mut chunk = chunk(id, "places", Some(feature), content);
+    chunk.metadata.source_hash = Some(source_text_hash(&text));
+    chunk
+}
+
+/// Park and market features with one indexed chunk each, plus a chunk of a
+/// feature that has since been deleted
+async fn setup() -> Stores {
+    let stores = Stores::new();
+    stores.set_content(1, "park bench").await;
+    stores.set_content(2, "market stall").await;
+
+    let chunks = vec![
+        feature_chunk(1, 1, "park bench"),
+        feature_chunk(2, 2, "market stall"),
+        feature_chunk(3, 3, "park market"),
+    ];
+    stores.index(&chunks, &embedder()).await;
+
+    stores
+}
+
+#[tokio::test]
+async fn test_scan_finds_orphaned_and_stale_chunks() {
+    let stores = setup().await;
+    stores.set_content(1, "park closed for repairs").await;
+
+    let plan = stores.gc().scan().await.unwrap();
+
+    assert_eq!(
+        plan,
+        GcPlan {
+            chunks_scanned: 3,
+            orphaned_chunks: vec![ChunkId(3)],
+            stale_chunks: vec![ChunkId(1)],
+            already_stale: 0,
+        }
+    );
+
+    // Scanning alone changes nothing
+    assert_eq!(stores.documents.list_chunk_ids().await.unwrap().len(), 3);
+    assert!(!stores.chunk(1).await.metadata.stale);
+}
+
+#[tokio::test]
+async fn test_collect_deletes_orphans_and_marks_stale_chunks() {
+    let stores = setup().await;
+    stores.set_content(1, "park closed for repairs").await;
+
+    let report = stores.gc().collect().await.unwrap();
+
+    assert_eq!(report.chunks_deleted, 1);
+    assert_eq!(report.chunks_marked_stale, 1);
+    assert!(stores.documents.get_chunk(ChunkId(3)).await.unwrap().is_none());
+    assert!(stores.vector.get_embedding(ChunkId(3)).await.unwrap().is_none());
+    assert!(stores.chunk(1).await.metadata.stale);
+    assert!(!stores.chunk(2).await.metadata.stale);
+
+    // A second collection has nothing left to do
+    let plan = stores.gc().scan().await.unwrap();
+    assert!(plan.is_empty());
+    assert_eq!(plan.already_stale, 1);
+}
+
+#[tokio::test]
+async fn test_stale_chunks_are_left_out_of_retrieval() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("park bench").with_top_k(3);
+
+    let before = stores.service().execute(&plan, embedder()).await.unwrap();
+    assert!(before.sources.iter().any(|s| s.chunk_id == ChunkId(1)));
+
+    stores.set_content(1, "park closed for repairs").await;
+    stores.gc().collect().await.unwrap();
+
+    let after = stores.service().execute(&plan, embedder()).await.unwrap();
+    let ids: Vec<ChunkId> = after.sources.iter().map(|s| s.chunk_id).collect();
+    assert_eq!(ids, vec![ChunkId(2)]);
+}
+
+#[tokio::test]
+async fn test_chunks_without_hash_are_compared_by_content() {
+    let stores = setup().await;
+    let mut legacy = feature_chunk(4, 2, "market stall");
+    legacy.metadata.source_hash = None;
+    stores.documents.store_chunks(&[legacy]).await.unwrap();
+
+    let plan = stores.gc().scan().await.unwrap();
+    assert!(!plan.stale_chunks.contains(&ChunkId(4)));
+
+    stores.set_content(2, "flower market").await;
+    let plan = stores.gc().scan().await.unwrap();
+    assert_eq!(plan.stale_chunks, vec![ChunkId(2), ChunkId(4)]);
+}
@@ -0,0 +1,138 @@
+//! Integration tests for the grounding check of generated answers
+//!
+//! A generator that adds one sentence of its own to what the stations say
+//! stands in for a model making something up. That sentence must be flagged
+//! as ungrounded, marked or removed as the policy says, and scored in the
+//! explanation; the sentences taken from the sources must pass.
+
+use georag_core::error::Result;
+use georag_core::formats::FormatRegistry;
+use georag_core::geo::models::Crs;
+use georag_core::llm::{create_embedder, EmbedderOptions, Generator};
+use georag_retrieval::grounding::DEFAULT_MIN_SIMILARITY;
+use georag_retrieval::{GroundingPolicy, IndexBuilder, QueryPlan, QueryResult, UngroundedAction};
+use georag_service::{IngestRequest, IngestService, QueryService};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::SpatialStore;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const STATIONS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.80, -6.20] },
+      "properties": { "content": "Riverside barrier protects homes from river flood water" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.84, -6.22] },
+      "properties": { "content": "River flood warning station" }
+    }
+  ]
+}"#;
+
+/// The sentence no station supports
+const INVENTED: &str = "The barrier was built by Dutch engineers in 1923.";
+
+/// Restates the sources, then adds a claim of its own
+struct InventingGenerator;
+
+impl Generator for InventingGenerator {
+    fn generate(&self, _prompt: &str, _context: &[&str]) -> Result<String> {
+        Ok(format!(
+            "Riverside barrier protects homes from river flood water. \
+             A river flood warning station stands nearby. {}",
+            INVENTED
+        ))
+    }
+}
+
+/// Ingest the stations and build the index with `mock:768`
+async fn service(dir: &TempDir) -> QueryService {
+    let path = dir.path().join("stations.geojson");
+    std::fs::write(&path, STATIONS).unwrap();
+
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let vector = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    IngestService::new(spatial.clone(), Arc::new(FormatRegistry::with_defaults()))
+        .ingest(&IngestRequest::new(&path))
+        .await
+        .unwrap();
+
+    let embedder = create_embedder("mock:768", &EmbedderOptions::default()).unwrap();
+    let builder = IndexBuilder::new(
+        spatial.clone(),
+        vector.clone(),
+        documents.clone(),
+        embedder,
+        Crs::wgs84(),
+    );
+    let datasets = spatial.list_datasets().await.unwrap();
+    builder.full_rebuild(&datasets, true, |_| {}).await.unwrap();
+
+    QueryService::new(spatial, vector, documents).with_generator(Arc::new(InventingGenerator))
+}
+
+async fn query(service: &QueryService, explain: bool) -> QueryResult {
+    let plan = QueryPlan::new("river flood").with_explain(explain);
+    let embedder = create_embedder("mock:768", &EmbedderOptions::default()).unwrap();
+    service.execute(&plan, embedder).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_invented_sentence_is_flagged_and_scored() {
+    let dir = TempDir::new().unwrap();
+    let result = query(&service(&dir).await, true).await;
+
+    assert_eq!(result.ungrounded, vec![INVENTED]);
+    assert!(result.answer.ends_with(INVENTED), "{}", result.answer);
+
+    let grounding = result.explanation.unwrap().grounding.unwrap();
+    let grounded: Vec<bool> = grounding.iter().map(|s| s.grounded).collect();
+    assert_eq!(grounded, vec![true, true, false]);
+
+    // The restated source matches its excerpt closely
+    assert!(grounding[0].similarity > 0.9, "{:?}", grounding[0]);
+    assert_eq!(grounding[0].overlap, 1.0);
+    let barrier = result.sources.iter().find(|s| s.excerpt.starts_with("Riverside")).unwrap();
+    assert_eq!(grounding[0].chunk_id, Some(barrier.chunk_id));
+
+    let invented = &grounding[2];
+    assert_eq!(invented.sentence, INVENTED);
+    assert!(invented.similarity < DEFAULT_MIN_SIMILARITY, "{:?}", invented);
+    assert!(invented.overlap < 0.5, "{:?}", invented);
+}
+
+#[tokio::test]
+async fn test_ungrounded_sentences_are_annotated_or_stripped() {
+    let dir = TempDir::new().unwrap();
+    let service = service(&dir).await;
+
+    let annotate = GroundingPolicy::default().with_action(UngroundedAction::Annotate);
+    let result = query(&service.clone().with_grounding(annotate), false).await;
+    assert!(
+        result.answer.ends_with(&format!("{} [unsupported]", INVENTED)),
+        "{}",
+        result.answer
+    );
+    assert!(result.explanation.is_none());
+
+    let strip = GroundingPolicy::default().with_action(UngroundedAction::Strip);
+    let result = query(&service.clone().with_grounding(strip), false).await;
+    assert_eq!(
+        result.answer,
+        "Riverside barrier protects homes from river flood water. \
+         A river flood warning station stands nearby."
+    );
+    assert_eq!(result.ungrounded, vec![INVENTED]);
+
+    // Thresholds every sentence reaches flag nothing
+    let lenient = GroundingPolicy::default().with_min_similarity(0.0);
+    let result = query(&service.with_grounding(lenient), false).await;
+    assert!(result.ungrounded.is_empty());
+    assert!(!serde_json::to_string(&result).unwrap().contains("ungrounded"));
+}
@@ -0,0 +1,138 @@
+//! Integration tests for verifying and recounting the per-dataset index counters
+
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, DatasetId, DatasetIndexStats, Embedding, TextChunk,
+};
+use georag_service::IndexStatsService;
+use georag_store::memory::{MemoryDocumentStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn chunk(id: u64, dataset_id: Option<DatasetId>, content: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: "/data/harbour.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 1,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id,
+        },
+    }
+}
+
+/// Two datasets with embedded chunks, and a chunk from before the counters
+async fn fixture() -> (Arc<MemoryDocumentStore>, Arc<MemoryVectorStore>, IndexStatsService) {
+    let documents = Arc::new(MemoryDocumentStore::new());
+    let vectors = Arc::new(MemoryVectorStore::new());
+    documents
+        .store_chunks(&[
+            chunk(1, Some(DatasetId(1)), "crane"),
+            chunk(2, Some(DatasetId(1)), "quay"),
+            chunk(3, Some(DatasetId(2)), "market"),
+            chunk(4, None, "legacy"),
+        ])
+        .await
+        .unwrap();
+    let embeddings: Vec<Embedding> = [(1, 1), (2, 1), (3, 2)]
+        .iter()
+        .map(|(chunk_id, dataset_id)| Embedding {
+            chunk_id: ChunkId(*chunk_id),
+            vector: vec![1.0, 0.0],
+            spatial_metadata: None,
+            dataset_id: Some(DatasetId(*dataset_id)),
+        })
+        .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    let service = IndexStatsService::new(documents.clone(), vectors.clone());
+    (documents, vectors, service)
+}
+
+#[tokio::test]
+async fn test_stats_merge_chunk_and_embedding_counters() {
+    let (_, _, service) = fixture().await;
+    let stats = service.stats().await.unwrap();
+
+    assert_eq!(
+        stats[&DatasetId(1)],
+        DatasetIndexStats {
+            chunks: 2,
+            content_bytes: 9,
+            embeddings: 2
+        }
+    );
+    assert_eq!(
+        stats[&DatasetId(2)],
+        DatasetIndexStats {
+            chunks: 1,
+            content_bytes: 6,
+            embeddings: 1
+        }
+    );
+    assert_eq!(stats.len(), 2);
+}
+
+#[tokio::test]
+async fn test_deep_verify_finds_drifted_dataset_and_recount_repairs_it() {
+    let (documents, _, service) = fixture().await;
+
+    let verification = service.verify(true).await.unwrap();
+    assert!(verification.is_consistent());
+    assert_eq!(verification.unattributed_chunks, 1);
+
+    documents.set_index_stats(
+        DatasetId(2),
+        DatasetIndexStats {
+            chunks: 5,
+            content_bytes: 60,
+            embeddings: 0,
+        },
+    );
+    let verification = service.verify(true).await.unwrap();
+    assert_eq!(verification.drift.len(), 1);
+    let drift = verification.drift[0];
+    assert_eq!(drift.dataset_id, Some(DatasetId(2)));
+    assert_eq!(drift.counted.chunks, 5);
+    assert_eq!(
+        drift.actual,
+        DatasetIndexStats {
+            chunks: 1,
+            content_bytes: 6,
+            embeddings: 1
+        }
+    );
+
+    let recounted = service.recount().await.unwrap();
+    assert_eq!(recounted[&DatasetId(2)].chunks, 1);
+    assert!(service.verify(true).await.unwrap().is_consistent());
+}
+
+#[tokio::test]
+async fn test_quick_verify_compares_totals() {
+    let (documents, vectors, service) = fixture().await;
+    // The legacy chunk has no dataset, so the totals only match without it
+    documents.delete_chunks(&[ChunkId(4)]).await.unwrap();
+    assert!(service.verify(false).await.unwrap().is_consistent());
+
+    vectors.set_index_stats(DatasetId(1), DatasetIndexStats::default());
+    let verification = service.verify(false).await.unwrap();
+    assert!(!verification.deep);
+    assert_eq!(verification.drift.len(), 1);
+    assert_eq!(verification.drift[0].dataset_id, None);
+    assert_eq!(verification.drift[0].counted.embeddings, 1);
+    assert_eq!(verification.drift[0].actual.embeddings, 3);
+
+    service.recount().await.unwrap();
+    assert!(service.verify(false).await.unwrap().is_consistent());
+}
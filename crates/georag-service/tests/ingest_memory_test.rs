@@ -0,0 +1,238 @@
+//! Memory use of the streaming ingest pipeline
+//!
+//! A large synthetic GeoJSON file is ingested into a store that is slower
+//! than the reader and keeps nothing, so every feature the pipeline holds is
+//! one the reader got ahead with. A counting allocator records the peak heap
+//! growth during the ingest, which must stay below a fixed multiple of the
+//! batch size however many features the file has.
+//!
+//! This file holds a single test, since the allocator counts every thread.
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::formats::{FormatRegistry, IngestBuffers};
+use georag_core::geo::{JoinCounts, SampleStrategy, SpatialJoin};
+use georag_core::models::{
+    Dataset, DatasetId, DatasetMeta, DatasetPreview, Feature, FeatureId, Geometry, SpatialFilter,
+    TagVisibility,
+};
+use georag_service::{max_features_in_flight, IngestRequest, IngestService};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Features in the synthetic file
+const FEATURES: usize = 50_000;
+
+/// Peak heap growth allowed, in batches' worth of the file's JSON text
+const MEMORY_MULTIPLE: usize = 64;
+
+/// System allocator that tracks the bytes allocated and their peak
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grow(bytes: usize) {
+    let now = ALLOCATED.fetch_add(bytes, Ordering::Relaxed) + bytes;
+    PEAK.fetch_max(now, Ordering::Relaxed);
+}
+
+fn shrink(bytes: usize) {
+    ALLOCATED.fetch_sub(bytes, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() {
+            if new_size > layout.size() {
+                grow(new_size - layout.size());
+            } else {
+                shrink(layout.size() - new_size);
+            }
+        }
+        new
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Spatial store that takes a while to store features and then drops them
+struct SlowStore {
+    inner: MemorySpatialStore,
+}
+
+#[async_trait]
+impl SpatialStore for SlowStore {
+    async fn store_dataset(&self, dataset: &Dataset) -> Result<DatasetId> {
+        self.inner.store_dataset(dataset).await
+    }
+
+    async fn get_dataset(&self, id: DatasetId) -> Result<Option<Dataset>> {
+        self.inner.get_dataset(id).await
+    }
+
+    async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_datasets().await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_visible_datasets(visibility).await
+    }
+
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()> {
+        self.inner.set_dataset_tags(id, tags).await
+    }
+
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()> {
+        self.inner.set_dataset_preview(id, preview).await
+    }
+
+    async fn delete_dataset(&self, id: DatasetId) -> Result<()> {
+        self.inner.delete_dataset(id).await
+    }
+
+    async fn store_features(&self, _features: &[Feature]) -> Result<()> {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        Ok(())
+    }
+
+    async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>> {
+        self.inner.spatial_query(filter).await
+    }
+
+    async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>> {
+        self.inner.get_feature(id).await
+    }
+
+    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
+        self.inner.get_features_for_dataset(dataset_id).await
+    }
+
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()> {
+        self.inner.update_feature_properties(features).await
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()> {
+        self.inner.upsert_dataset_features(dataset_id, features).await
+    }
+
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()> {
+        self.inner.delete_features(dataset_id, ids).await
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        self.inner.sample_features(dataset_id, n, strategy).await
+    }
+
+    async fn spatial_join(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        self.inner.spatial_join(target, source, join).await
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        geometry: &Geometry,
+        max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        self.inner.subdivide_geometry(geometry, max_vertices).await
+    }
+}
+
+/// Write a FeatureCollection of `count` points, each with a paragraph of text
+fn write_collection(path: &Path, count: usize) {
+    let mut out = BufWriter::new(std::fs::File::create(path).unwrap());
+    write!(out, r#"{{"type":"FeatureCollection","features":["#).unwrap();
+    for i in 0..count {
+        if i > 0 {
+            write!(out, ",").unwrap();
+        }
+        let lon = 106.0 + (i % 1000) as f64 * 0.001;
+        let lat = -6.0 - (i / 1000) as f64 * 0.001;
+        write!(
+            out,
+            r#"{{"type":"Feature","geometry":{{"type":"Point","coordinates":[{lon},{lat}]}},"properties":{{"name":"Site {i}","content":"{}"}}}}"#,
+            "Survey notes on drainage, footpaths and street lighting. ".repeat(4)
+        )
+        .unwrap();
+    }
+    write!(out, "]}}").unwrap();
+    out.flush().unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_peak_memory_stays_within_a_multiple_of_the_batch_size() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sites.geojson");
+    write_collection(&path, FEATURES);
+    let bytes_per_feature = std::fs::metadata(&path).unwrap().len() as usize / FEATURES;
+
+    let buffers = IngestBuffers::new(100, 2);
+    let store = Arc::new(SlowStore { inner: MemorySpatialStore::new() });
+    let service = IngestService::new(store, Arc::new(FormatRegistry::with_defaults()));
+    let request = IngestRequest::new(&path).with_buffers(buffers);
+
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let report = service.ingest(&request).await.unwrap();
+    let growth = PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+
+    let limit = MEMORY_MULTIPLE * buffers.batch_size * bytes_per_feature;
+    assert!(
+        growth < limit,
+        "peak heap growth of {} bytes is over {} bytes ({} batches of {} features of {} bytes)",
+        growth,
+        limit,
+        MEMORY_MULTIPLE,
+        buffers.batch_size,
+        bytes_per_feature
+    );
+
+    // Holding the whole file would take far more than the limit
+    assert!(FEATURES * bytes_per_feature > 4 * limit);
+
+    assert_eq!(report.dataset.feature_count, FEATURES);
+    assert_eq!(report.features_stored, FEATURES);
+
+    let pipeline = report.pipeline.expect("ingest reports pipeline stats");
+    assert!(pipeline.peak_features_in_flight <= max_features_in_flight(buffers));
+    assert_eq!(pipeline.batches, FEATURES / buffers.batch_size);
+    let stages: Vec<(&str, usize)> =
+        pipeline.stages.iter().map(|stage| (stage.stage, stage.features)).collect();
+    assert_eq!(stages, vec![("parse", FEATURES), ("normalize", FEATURES), ("store", FEATURES)]);
+    assert!(pipeline.stages.iter().all(|stage| stage.features_per_second() > 0.0));
+}
@@ -0,0 +1,429 @@
+//! Integration tests for the shared ingest service
+//!
+//! These pin the behavior the CLI `add` command and the API ingest endpoint
+//! both rely on: naming, geometry type detection, feature storage, CRS checks,
+//! the handling of missing geometries and error classification.
+
+use georag_core::formats::{FeatureErrorKind, FormatRegistry, ReadPolicy};
+use georag_core::geo::ZCoordinates;
+use georag_core::models::{DatasetPreview, FeatureId, Geometry, GeometryType};
+use georag_service::{IngestRequest, IngestService, ServiceError, SourcePolicy};
+use georag_store::memory::{MemoryBlobStore, MemorySpatialStore};
+use georag_store::ports::{BlobStore, SpatialStore};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const PARKS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": null,
+      "properties": { "name": "Unmapped garden" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]] },
+      "properties": { "name": "Central Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+      "properties": { "name": "Pocket Park" }
+    }
+  ]
+}"#;
+
+fn write_file(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn service() -> (Arc<MemorySpatialStore>, IngestService) {
+    let store = Arc::new(MemorySpatialStore::new());
+    let service = IngestService::new(store.clone(), Arc::new(FormatRegistry::with_defaults()));
+    (store, service)
+}
+
+async fn ingest(request: IngestRequest) -> Result<georag_service::IngestReport, ServiceError> {
+    service().1.ingest(&request).await
+}
+
+fn parks(dir: &TempDir) -> PathBuf {
+    write_file(dir, "parks.geojson", PARKS)
+}
+
+#[tokio::test]
+async fn test_ingest_reports_dataset_and_stores_features() {
+    let dir = TempDir::new().unwrap();
+    let (store, service) = service();
+
+    let request = IngestRequest::new(parks(&dir)).with_tags(["Public", " public", "parks"]);
+    let report = service.ingest(&request).await.unwrap();
+
+    assert_eq!(report.dataset.name, "parks");
+    assert_eq!(report.dataset.geometry_type, GeometryType::Polygon);
+    assert_eq!(report.dataset.feature_count, 3);
+    assert_eq!(report.dataset.crs, 4326);
+    assert_eq!(report.dataset.format.format_name, "GeoJSON");
+    assert_eq!(report.dataset.tags, vec!["parks", "public"]);
+    assert_eq!(report.features_stored, 2);
+
+    let stored = store.get_dataset(report.dataset_id).await.unwrap().unwrap();
+    assert_eq!(stored.name, "parks");
+    assert_eq!(stored.tags, vec!["parks", "public"]);
+
+    // Features keep their position in the file; the one without geometry is
+    // dropped under the default strict read
+    assert!(store.get_feature(FeatureId(0)).await.unwrap().is_none());
+    assert!(store.get_feature(FeatureId(1)).await.unwrap().is_some());
+    assert!(store.get_feature(FeatureId(2)).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_ingest_uses_requested_name_and_can_skip_features() {
+    let dir = TempDir::new().unwrap();
+
+    let request = IngestRequest::new(parks(&dir))
+        .with_name("city-parks")
+        .with_store_features(false);
+    let report = ingest(request).await.unwrap();
+
+    assert_eq!(report.dataset.name, "city-parks");
+    assert_eq!(report.dataset.feature_count, 3);
+    assert_eq!(report.features_stored, 0);
+}
+
+#[tokio::test]
+async fn test_crs_mismatch_is_rejected_unless_allowed() {
+    let dir = TempDir::new().unwrap();
+    let path = parks(&dir);
+    let (_, service) = service();
+
+    let strict = IngestRequest::new(&path).with_workspace_crs(3857, false);
+    match service.prepare(&strict).await {
+        Err(ServiceError::CrsMismatch { dataset_crs, workspace_crs }) => {
+            assert_eq!((dataset_crs, workspace_crs), (4326, 3857));
+        }
+        other => panic!("expected CrsMismatch, got {:?}", other.map(|p| p.dataset)),
+    }
+
+    let forced = IngestRequest::new(&path).with_workspace_crs(3857, true);
+    let prepared = service.prepare(&forced).await.unwrap();
+    assert!(prepared.has_crs_mismatch());
+
+    let matching = IngestRequest::new(&path).with_workspace_crs(4326, false);
+    assert!(!service.prepare(&matching).await.unwrap().has_crs_mismatch());
+}
+
+#[tokio::test]
+async fn test_prepare_does_not_store() {
+    let dir = TempDir::new().unwrap();
+    let (store, service) = service();
+
+    service.prepare(&IngestRequest::new(parks(&dir))).await.unwrap();
+
+    assert!(store.list_datasets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_unsupported_format() {
+    let dir = TempDir::new().unwrap();
+    let path = write_file(&dir, "notes.xyz", "not a dataset");
+
+    let err = ingest(IngestRequest::new(path)).await.unwrap_err();
+    assert!(matches!(err, ServiceError::UnsupportedFormat(_)), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn test_options_the_format_does_not_accept_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    let (store, service) = service();
+
+    // GeoJSON has no reader options, so a GPX option is refused before the read
+    let request = IngestRequest::new(parks(&dir)).with_option("track_type", "tracks");
+    let err = service.ingest(&request).await.unwrap_err();
+
+    assert!(matches!(err, ServiceError::InvalidOptions(_)), "got {:?}", err);
+    assert!(err.to_string().contains("GeoJSON takes no options"), "{}", err);
+    assert!(store.list_datasets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_unreadable_file_is_not_stored() {
+    let dir = TempDir::new().unwrap();
+    let path = write_file(&dir, "broken.geojson", "{ not json");
+    let (store, service) = service();
+
+    let err = service.ingest(&IngestRequest::new(path)).await.unwrap_err();
+    assert!(
+        matches!(err, ServiceError::InvalidDataset(_) | ServiceError::Read(_)),
+        "got {:?}",
+        err
+    );
+    assert!(store.list_datasets().await.unwrap().is_empty());
+
+    // Without validation the reader's own error is reported
+    let request = IngestRequest::new(dir.path().join("broken.geojson")).with_validation(false);
+    let err = service.ingest(&request).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Read(_)), "got {:?}", err);
+    assert!(store.list_datasets().await.unwrap().is_empty());
+}
+
+const PARKS_WITH_CORRUPT_FEATURE: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+      "properties": { "name": "Pocket Park" }
+    },
+    {
+      "type": "Feature",
+      "id": "broken",
+      "geometry": { "type": "Point", "coordinates": ["east", "south"] },
+      "properties": { "name": "Broken Park" }
+    }
+  ]
+}"#;
+
+#[tokio::test]
+async fn test_lenient_ingest_reports_skipped_features() {
+    let dir = TempDir::new().unwrap();
+    let path = write_file(&dir, "parks.geojson", PARKS_WITH_CORRUPT_FEATURE);
+    let (store, service) = service();
+
+    // Strict is the default and stores nothing
+    let err = service.ingest(&IngestRequest::new(&path)).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Read(_)), "got {:?}", err);
+    assert!(store.list_datasets().await.unwrap().is_empty());
+
+    let request = IngestRequest::new(&path).with_read_policy(ReadPolicy::lenient(10));
+    let report = service.ingest(&request).await.unwrap();
+
+    assert_eq!(report.features_stored, 1);
+    assert_eq!(report.dataset.feature_count, 1);
+    assert_eq!(report.features_skipped(), 1);
+    assert_eq!(report.feature_errors[0].index, 1);
+    assert_eq!(report.feature_errors[0].id.as_deref(), Some("broken"));
+}
+
+#[tokio::test]
+async fn test_ingest_keeps_a_preview_of_the_features() {
+    let dir = TempDir::new().unwrap();
+    let (store, service) = service();
+    let request = IngestRequest::new(parks(&dir)).with_read_policy(ReadPolicy::lenient(10));
+
+    let report = service.ingest(&request).await.unwrap();
+    let Some(DatasetPreview::Vector(preview)) = &report.dataset.format.preview else {
+        panic!("expected a vector preview, got {:?}", report.dataset.format.preview);
+    };
+    assert_eq!(preview.extent, Some([0.0, -6.2, 106.8, 1.0]));
+    assert_eq!(preview.geometry_types.len(), 2);
+    assert_eq!(preview.without_geometry, 1);
+    assert_eq!(preview.property_keys, vec!["name"]);
+
+    // Listings carry the preview, and a prepared dataset gets the same one
+    let listed = store.list_datasets().await.unwrap();
+    assert_eq!(listed[0].preview, report.dataset.format.preview);
+    let prepared = service.prepare(&request).await.unwrap();
+    assert_eq!(prepared.dataset.format.preview, report.dataset.format.preview);
+}
+
+#[tokio::test]
+async fn test_ingest_keeps_original_file_in_blob_store() {
+    let dir = TempDir::new().unwrap();
+    let blobs = Arc::new(MemoryBlobStore::new());
+    let policy = SourcePolicy { hash: true, ..SourcePolicy::default() };
+    let service = service().1.with_blob_store(blobs.clone(), policy);
+
+    let request = IngestRequest::new(parks(&dir)).with_source_name("city parks.geojson");
+    let report = service.ingest(&request).await.unwrap();
+
+    let source = report.dataset.format.source.expect("source recorded");
+    assert_eq!(source.filename, "city parks.geojson");
+    assert_eq!(source.content_type, "application/geo+json");
+    assert_eq!(source.size, PARKS.len() as u64);
+    assert_eq!(source.sha256.as_ref().map(String::len), Some(64));
+
+    let content = blobs.get_blob(report.dataset_id).await.unwrap().unwrap();
+    assert_eq!(content, PARKS.as_bytes());
+}
+
+#[tokio::test]
+async fn test_ingest_skips_sources_over_the_size_limit() {
+    let dir = TempDir::new().unwrap();
+    let blobs = Arc::new(MemoryBlobStore::new());
+    let policy = SourcePolicy { max_bytes: 16, hash: false };
+    let service = service().1.with_blob_store(blobs.clone(), policy);
+
+    let report = service.ingest(&IngestRequest::new(parks(&dir))).await.unwrap();
+
+    assert!(report.dataset.format.source.is_none());
+    assert!(report.warnings.iter().any(|w| w.contains("source limit")));
+    assert!(blobs.get_blob(report.dataset_id).await.unwrap().is_none());
+}
+
+/// What happened to the only feature of a file
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Stored(Option<Geometry>),
+    Dropped,
+    Skipped(FeatureErrorKind),
+    Failed,
+}
+
+/// Ingest a one-feature file through `prepare` (dry runs, diffs) and
+/// through the pipeline (`ingest`), reporting what became of the feature
+async fn ingest_one(path: &Path, policy: ReadPolicy) -> (Outcome, Outcome) {
+    let (store, service) = service();
+    let request = IngestRequest::new(path).with_read_policy(policy);
+
+    let prepared = match service.prepare(&request).await {
+        Ok(prepared) => match (prepared.features.first(), prepared.feature_errors.first()) {
+            (Some(feature), _) => Outcome::Stored(feature.geometry.clone()),
+            (None, Some(error)) => Outcome::Skipped(error.kind),
+            (None, None) => Outcome::Dropped,
+        },
+        Err(_) => Outcome::Failed,
+    };
+    let ingested = match service.ingest(&request).await {
+        Ok(report) => {
+            match (store.get_feature(FeatureId(0)).await.unwrap(), report.feature_errors.first()) {
+                (Some(feature), _) => Outcome::Stored(feature.geometry),
+                (None, Some(error)) => Outcome::Skipped(error.kind),
+                (None, None) => Outcome::Dropped,
+            }
+        }
+        Err(_) => Outcome::Failed,
+    };
+    (prepared, ingested)
+}
+
+#[tokio::test]
+async fn test_missing_and_unusual_geometries_follow_one_policy() {
+    let dir = TempDir::new().unwrap();
+    let square = vec![vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 0.0]]];
+    let shifted = vec![vec![[2.0, 0.0], [3.0, 0.0], [3.0, 1.0], [2.0, 0.0]]];
+
+    // (name, geometry, outcome under a strict read, outcome under a lenient read)
+    let cases = vec![
+        ("null", json!(null), Outcome::Dropped, Outcome::Stored(None)),
+        (
+            "empty multipoint",
+            json!({"type": "MultiPoint", "coordinates": []}),
+            Outcome::Dropped,
+            Outcome::Stored(None),
+        ),
+        (
+            "3D point",
+            json!({"type": "Point", "coordinates": [106.8, -6.2, 30.0]}),
+            Outcome::Stored(Some(Geometry::point(106.8, -6.2))),
+            Outcome::Stored(Some(Geometry::point(106.8, -6.2))),
+        ),
+        (
+            "3D polygon",
+            json!({"type": "Polygon", "coordinates": [[[0, 0, 5], [1, 0, 5], [1, 1, 5], [0, 0, 5]]]}),
+            Outcome::Stored(Some(Geometry::polygon(square.clone()))),
+            Outcome::Stored(Some(Geometry::polygon(square.clone()))),
+        ),
+        (
+            "collection of polygons",
+            json!({"type": "GeometryCollection", "geometries": [
+                {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]]},
+                {"type": "Polygon", "coordinates": [[[2, 0], [3, 0], [3, 1], [2, 0]]]}
+            ]}),
+            Outcome::Stored(Some(Geometry::MultiPolygon {
+                coordinates: vec![square.clone(), shifted.clone()],
+            })),
+            Outcome::Stored(Some(Geometry::MultiPolygon {
+                coordinates: vec![square.clone(), shifted.clone()],
+            })),
+        ),
+        (
+            "mixed collection",
+            json!({"type": "GeometryCollection", "geometries": [
+                {"type": "Point", "coordinates": [0, 0]},
+                {"type": "LineString", "coordinates": [[0, 0], [1, 1]]}
+            ]}),
+            Outcome::Failed,
+            Outcome::Skipped(FeatureErrorKind::UnsupportedGeometry),
+        ),
+    ];
+
+    for (i, (name, geometry, strict, lenient)) in cases.into_iter().enumerate() {
+        let collection = json!({
+            "type": "FeatureCollection",
+            "features": [{"type": "Feature", "geometry": geometry, "properties": {"name": name}}]
+        });
+        let path = write_file(&dir, &format!("case-{}.geojson", i), &collection.to_string());
+
+        let (prepared, ingested) = ingest_one(&path, ReadPolicy::strict()).await;
+        assert_eq!(prepared, strict, "{} (strict, prepare)", name);
+        assert_eq!(ingested, strict, "{} (strict, ingest)", name);
+
+        let (prepared, ingested) = ingest_one(&path, ReadPolicy::lenient(10)).await;
+        assert_eq!(prepared, lenient, "{} (lenient, prepare)", name);
+        assert_eq!(ingested, lenient, "{} (lenient, ingest)", name);
+    }
+}
+
+#[tokio::test]
+async fn test_geometry_type_of_converted_collections() {
+    let dir = TempDir::new().unwrap();
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "geometry": null, "properties": {}},
+            {"type": "Feature", "geometry": {"type": "GeometryCollection", "geometries": [
+                {"type": "LineString", "coordinates": [[0, 0, 1], [1, 1, 1]]},
+                {"type": "LineString", "coordinates": [[2, 2, 1], [3, 3, 1]]}
+            ]}, "properties": {}}
+        ]
+    });
+    let path = write_file(&dir, "routes.geojson", &collection.to_string());
+
+    let report = ingest(IngestRequest::new(path)).await.unwrap();
+    assert_eq!(report.dataset.geometry_type, GeometryType::MultiLineString);
+}
+
+#[tokio::test]
+async fn test_z_is_kept_and_measures_are_dropped_with_a_warning() {
+    let dir = TempDir::new().unwrap();
+    let geojson = json!({
+        "type": "FeatureCollection",
+        "features": [{
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": [[106.80, -6.20, 4.5, 0.0], [106.82, -6.21, 5.0, 120.0]]
+            },
+            "properties": { "name": "Survey line" }
+        }]
+    });
+    let path = write_file(&dir, "survey.geojson", &geojson.to_string());
+    let (store, service) = service();
+
+    let prepared = service.prepare(&IngestRequest::new(&path)).await.unwrap();
+    let report = service.ingest(&IngestRequest::new(&path)).await.unwrap();
+
+    for warnings in [&prepared.warnings, &report.warnings] {
+        let measures: Vec<_> = warnings.iter().filter(|w| w.contains("M (measure)")).collect();
+        assert_eq!(measures.len(), 1, "{:?}", warnings);
+    }
+    assert_eq!(prepared.features[0].z_ordinates(), Some(&[4.5, 5.0][..]));
+
+    let stored = store.get_feature(FeatureId(0)).await.unwrap().unwrap();
+    assert_eq!(
+        stored.geometry,
+        Some(Geometry::line_string(vec![[106.80, -6.20], [106.82, -6.21]]))
+    );
+    assert_eq!(stored.z_ordinates(), Some(&[4.5, 5.0][..]));
+
+    let request = IngestRequest::new(&path).with_z_coordinates(ZCoordinates::Drop);
+    let prepared = service.prepare(&request).await.unwrap();
+    assert_eq!(prepared.features[0].z, None);
+}
@@ -0,0 +1,127 @@
+//! Integration tests for the shared join service
+//!
+//! The memory store declines push-down, so these exercise the in-memory join
+//! and the write-back of matched target features.
+
+mod common;
+
+use georag_core::geo::{JoinCounts, JoinPredicate, SpatialJoin};
+use georag_core::models::{Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType};
+use georag_service::{JoinService, ServiceError};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn dataset(name: &str, geometry_type: GeometryType) -> Dataset {
+    Dataset { geometry_type, ..common::dataset(name) }
+}
+
+fn feature(id: u64, geometry: Geometry, properties: serde_json::Value) -> Feature {
+    let properties: HashMap<String, serde_json::Value> =
+        serde_json::from_value(properties).unwrap();
+    Feature::with_geometry(FeatureId(id), geometry, properties, 4326)
+}
+
+async fn add(
+    store: &MemorySpatialStore,
+    name: &str,
+    geometry_type: GeometryType,
+    features: Vec<Feature>,
+) -> DatasetId {
+    let id = store.store_dataset(&dataset(name, geometry_type)).await.unwrap();
+    store.store_features(&features).await.unwrap();
+    store.associate_features_with_dataset(id, features.iter().map(|f| f.id).collect());
+    id
+}
+
+/// Two survey points, one inside the village polygon and one outside
+async fn setup() -> (Arc<MemorySpatialStore>, DatasetId, DatasetId) {
+    let store = Arc::new(MemorySpatialStore::new());
+
+    let points = add(
+        &store,
+        "points",
+        GeometryType::Point,
+        vec![
+            feature(1, Geometry::point(0.5, 0.5), json!({ "label": "well" })),
+            feature(2, Geometry::point(5.0, 5.0), json!({ "label": "gate" })),
+        ],
+    )
+    .await;
+
+    let villages = add(
+        &store,
+        "villages",
+        GeometryType::Polygon,
+        vec![feature(
+            10,
+            Geometry::polygon(vec![vec![
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [1.0, 1.0],
+                [0.0, 1.0],
+                [0.0, 0.0],
+            ]]),
+            json!({ "name": "Sukamaju", "kode": "3201", "area": 12 }),
+        )],
+    )
+    .await;
+
+    (store, points, villages)
+}
+
+fn join() -> SpatialJoin {
+    SpatialJoin::new(JoinPredicate::Within, vec!["name".to_string(), "kode".to_string()])
+        .with_prefix("villages_")
+}
+
+#[tokio::test]
+async fn test_run_writes_prefixed_properties() {
+    let (store, points, villages) = setup().await;
+    let service = JoinService::new(store.clone());
+
+    let report = service.run(points, villages, &join()).await.unwrap();
+    assert_eq!(report.counts, JoinCounts { matched: 1, missed: 1 });
+    assert!(!report.pushed_down);
+
+    let inside = store.get_feature(FeatureId(1)).await.unwrap().unwrap();
+    assert_eq!(inside.properties["label"], "well");
+    assert_eq!(inside.properties["villages_name"], "Sukamaju");
+    assert_eq!(inside.properties["villages_kode"], "3201");
+    assert!(!inside.properties.contains_key("villages_area"));
+
+    let outside = store.get_feature(FeatureId(2)).await.unwrap().unwrap();
+    assert_eq!(outside.properties.len(), 1);
+}
+
+#[tokio::test]
+async fn test_preview_does_not_write() {
+    let (store, points, villages) = setup().await;
+    let service = JoinService::new(store.clone());
+
+    let outcome = service.preview(points, villages, &join()).await.unwrap();
+    assert_eq!(outcome.counts, JoinCounts { matched: 1, missed: 1 });
+    assert_eq!(outcome.updated.len(), 1);
+    assert_eq!(outcome.updated[0].properties["villages_name"], "Sukamaju");
+
+    let stored = store.get_feature(FeatureId(1)).await.unwrap().unwrap();
+    assert!(!stored.properties.contains_key("villages_name"));
+}
+
+#[tokio::test]
+async fn test_invalid_joins_are_rejected() {
+    let (store, points, villages) = setup().await;
+    let service = JoinService::new(store);
+
+    let err = service.run(points, points, &join()).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidJoin(_)), "got {:?}", err);
+
+    let nothing = SpatialJoin::new(JoinPredicate::Within, vec![" ".to_string()]);
+    let err = service.preview(points, villages, &nothing).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidJoin(_)), "got {:?}", err);
+
+    let err = service.run(points, DatasetId(99), &join()).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Core(_)), "got {:?}", err);
+}
@@ -0,0 +1,164 @@
+//! Integration tests for merging overlapping chunks in query results
+//!
+//! One report is cut into three chunks overlapping by three words, the way
+//! ingest chunks long text. With merging on, the chunks that rank together
+//! come back as one source spanning their union.
+
+use chrono::Utc;
+use georag_core::llm::{Embedder, MockEmbedder};
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    Dataset, DatasetId, Embedding, Feature, FeatureId, Geometry, GeometryType, TextChunk,
+};
+use georag_core::processing::ChunkGenerator;
+use georag_retrieval::{QueryPlan, QueryResult};
+use georag_service::QueryService;
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const REPORT: &str = "the harbour market opens at dawn when fishing boats unload their \
+    catch and traders set up stalls along the quay";
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "reports".to_string(),
+        path: PathBuf::from("/data/reports.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 2,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(id: u64, content: &str) -> Feature {
+    let mut properties = HashMap::new();
+    properties.insert("content".to_string(), json!(content));
+    Feature::with_geometry(FeatureId(id), Geometry::point(106.8, -6.1), properties, 4326)
+}
+
+/// The report in words 0-7, 5-12 and 10-19, and a park note of one chunk
+///
+/// Chunks for which `indexed` is false are stored but not embedded.
+async fn setup(indexed: impl Fn(usize) -> bool) -> (QueryService, Vec<TextChunk>) {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let vector = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    let features = vec![feature(1, REPORT), feature(2, "quiet park with benches")];
+    spatial.store_features(&features).await.unwrap();
+
+    let chunks = ChunkGenerator::new(4, 8, 3).unwrap().generate_chunks(&dataset(), &features);
+    let offsets: Vec<usize> = chunks.iter().map(|c| c.source.offset).collect();
+    assert_eq!(offsets, vec![0, 28, 59, 0]);
+    documents.store_chunks(&chunks).await.unwrap();
+
+    let embedded: Vec<&TextChunk> =
+        chunks.iter().enumerate().filter(|(i, _)| indexed(*i)).map(|(_, c)| c).collect();
+    let texts: Vec<&str> = embedded.iter().map(|c| c.content.as_str()).collect();
+    let embeddings: Vec<Embedding> = MockEmbedder::default()
+        .embed(&texts)
+        .unwrap()
+        .into_iter()
+        .zip(&embedded)
+        .map(|(vector, chunk)| Embedding {
+            chunk_id: chunk.id,
+            vector,
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vector.store_embeddings(&embeddings).await.unwrap();
+
+    (QueryService::new(spatial, vector, documents), chunks)
+}
+
+async fn query(service: &QueryService, merge: bool) -> QueryResult {
+    let plan = QueryPlan::new("harbour market traders")
+        .with_top_k(10)
+        .with_explain(true)
+        .with_merge_overlapping(merge);
+    service.execute(&plan, MockEmbedder::default()).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_overlapping_chunks_are_merged() {
+    let (service, chunks) = setup(|_| true).await;
+    let result = query(&service, true).await;
+
+    assert_eq!(result.sources.len(), 2);
+    let report = result.sources.iter().find(|s| s.feature_id == Some(FeatureId(1))).unwrap();
+    assert_eq!(report.offset, Some(0));
+    assert_eq!(report.excerpt, REPORT.split_whitespace().collect::<Vec<_>>().join(" "));
+
+    // The merged source keeps the best score of its chunks
+    let unmerged = query(&service, false).await;
+    let best = unmerged
+        .sources
+        .iter()
+        .filter(|s| s.feature_id == Some(FeatureId(1)))
+        .map(|s| s.score)
+        .fold(f32::MIN, f32::max);
+    assert_eq!(report.score, best);
+    assert_eq!(result.semantic_scores.as_ref().unwrap().len(), 2);
+
+    let details = &result.explanation.as_ref().unwrap().ranking_details;
+    let detail = details.iter().find(|d| d.chunk_id == report.chunk_id).unwrap();
+    let mut members = detail.merged_chunks.clone();
+    members.push(report.chunk_id);
+    members.sort_by_key(|id| id.0);
+    let report_chunks: Vec<_> = chunks[..3].iter().map(|c| c.id).collect();
+    assert_eq!(members, report_chunks);
+    assert!(detail.score_explanation.contains("merged with 2 overlapping chunk(s)"));
+}
+
+#[tokio::test]
+async fn test_merging_is_off_by_default() {
+    let (service, _) = setup(|_| true).await;
+    let plan = QueryPlan::new("harbour market traders").with_top_k(10).with_explain(true);
+    let result = service.execute(&plan, MockEmbedder::default()).await.unwrap();
+
+    assert_eq!(result.sources.len(), 4);
+    let details = &result.explanation.as_ref().unwrap().ranking_details;
+    assert!(details.iter().all(|d| d.merged_chunks.is_empty()));
+}
+
+#[tokio::test]
+async fn test_chunks_with_a_gap_are_not_merged() {
+    // Without the middle chunk, words 0-7 and 10-19 do not overlap
+    let (service, chunks) = setup(|i| i != 1).await;
+    let result = query(&service, true).await;
+
+    assert_eq!(result.sources.len(), 3);
+    let mut report: Vec<_> = result
+        .sources
+        .iter()
+        .filter(|s| s.feature_id == Some(FeatureId(1)))
+        .map(|s| (s.offset, s.excerpt.as_str()))
+        .collect();
+    report.sort();
+    assert_eq!(
+        report,
+        vec![(Some(0), chunks[0].content.as_str()), (Some(59), chunks[2].content.as_str())]
+    );
+}
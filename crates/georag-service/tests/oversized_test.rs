@@ -0,0 +1,193 @@
+//! Integration tests for features over the ingest vertex limit
+//!
+//! Oversized features are rejected, simplified or subdivided at ingest. Tiles
+//! of a subdivided feature are stored as separate features, but queries
+//! report any tile as the original feature.
+
+mod common;
+
+use common::{KeywordEmbedder, Stores};
+use georag_core::formats::{FeatureErrorKind, FormatRegistry, ReadPolicy};
+use georag_core::geo::{FeatureLimits, OversizedAction, OversizedFeatures};
+use georag_core::models::{Feature, FeatureId, Geometry, SpatialFilter, SpatialPredicate};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::QueryPlan;
+use georag_service::{IngestReport, IngestRequest, IngestService, ServiceError};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const VOCABULARY: [&str; 2] = ["coast", "market"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// Vertices in the coastline ring, including the closing one
+const COAST_VERTICES: usize = 401;
+
+const LIMITS: FeatureLimits = FeatureLimits {
+    max_vertices: 64,
+    oversized: OversizedFeatures::Subdivide,
+};
+
+/// A detailed coastline polygon around (10, 0) followed by a market point
+fn write_coast(dir: &TempDir) -> PathBuf {
+    let mut ring: Vec<[f64; 2]> = (0..COAST_VERTICES - 1)
+        .map(|i| {
+            let angle = i as f64 / (COAST_VERTICES - 1) as f64 * std::f64::consts::TAU;
+            let radius = 5.0 + 0.1 * (i % 3) as f64;
+            [10.0 + radius * angle.cos(), radius * angle.sin()]
+        })
+        .collect();
+    ring.push(ring[0]);
+
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Polygon", "coordinates": [ring] },
+                "properties": { "name": "Rocky coast" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [50.0, 50.0] },
+                "properties": { "name": "Harbour market" }
+            }
+        ]
+    });
+
+    let path = dir.path().join("coast.geojson");
+    std::fs::write(&path, collection.to_string()).unwrap();
+    path
+}
+
+fn service(store: &Arc<MemorySpatialStore>) -> IngestService {
+    IngestService::new(store.clone(), Arc::new(FormatRegistry::with_defaults()))
+}
+
+async fn stored_features(store: &MemorySpatialStore, report: &IngestReport) -> Vec<Feature> {
+    let mut features = Vec::new();
+    for id in 0..report.features_stored as u64 {
+        features.push(store.get_feature(FeatureId(id)).await.unwrap().unwrap());
+    }
+    features
+}
+
+#[tokio::test]
+async fn test_oversized_feature_is_subdivided_and_reported() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+
+    let request = IngestRequest::new(write_coast(&dir)).with_feature_limits(LIMITS);
+    let report = service(&store).ingest(&request).await.unwrap();
+
+    assert_eq!(report.oversized_features.len(), 1);
+    let oversized = report.oversized_features[0];
+    assert_eq!(oversized.index, 0);
+    assert_eq!(oversized.vertices, COAST_VERTICES);
+    let OversizedAction::Subdivided { parts } = oversized.action else {
+        panic!("expected subdivision, got {:?}", oversized.action);
+    };
+    assert!(parts > 1);
+
+    // The dataset still counts the features of the file; the tiles are stored
+    assert_eq!(report.dataset.feature_count, 2);
+    assert_eq!(report.features_stored, 1 + parts);
+
+    let features = stored_features(&store, &report).await;
+    assert_eq!(features[0].part_of(), None);
+    assert_eq!(features[1].part_of(), None);
+    for tile in &features[2..] {
+        assert_eq!(tile.part_of(), Some(FeatureId(0)));
+        assert_eq!(tile.original_id(), FeatureId(0));
+        assert_eq!(tile.properties["name"], json!("Rocky coast"));
+    }
+    for feature in &features {
+        assert!(feature.geometry.as_ref().unwrap().vertex_count() <= LIMITS.max_vertices);
+    }
+}
+
+#[tokio::test]
+async fn test_query_matching_a_tile_reports_the_original_feature() {
+    let dir = TempDir::new().unwrap();
+    let stores = Stores::new();
+
+    let request = IngestRequest::new(write_coast(&dir)).with_feature_limits(LIMITS);
+    let report = service(&stores.spatial).ingest(&request).await.unwrap();
+
+    // Tiles get no chunks of their own
+    let features = stored_features(&stores.spatial, &report).await;
+    let chunks = ChunkGenerator::default().generate_chunks(&report.dataset, &features);
+    assert_eq!(chunks.len(), 2);
+    stores.index(&chunks, &embedder()).await;
+
+    // The east end of the coast lies in tiles other than the first
+    let east_end = Geometry::polygon(vec![vec![
+        [14.5, -0.5],
+        [16.0, -0.5],
+        [16.0, 0.5],
+        [14.5, 0.5],
+        [14.5, -0.5],
+    ]]);
+    let plan = QueryPlan::new("coast")
+        .with_spatial_filter(SpatialFilter::new(SpatialPredicate::Intersects).geometry(east_end));
+
+    let result = stores.service().execute(&plan, embedder()).await.unwrap();
+
+    let ids: Vec<Option<FeatureId>> = result.sources.iter().map(|s| s.feature_id).collect();
+    assert_eq!(ids, vec![Some(FeatureId(0))]);
+}
+
+#[tokio::test]
+async fn test_oversized_feature_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let path = write_coast(&dir);
+    let store = Arc::new(MemorySpatialStore::new());
+    let limits = FeatureLimits {
+        oversized: OversizedFeatures::Reject,
+        ..LIMITS
+    };
+
+    // A strict read fails on the oversized feature
+    let request = IngestRequest::new(&path).with_feature_limits(limits);
+    let err = service(&store).ingest(&request).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Read(_)), "got {:?}", err);
+
+    // A lenient read skips it like an unreadable feature
+    let request = request.with_read_policy(ReadPolicy::lenient(10));
+    let report = service(&store).ingest(&request).await.unwrap();
+
+    assert_eq!(report.features_stored, 1);
+    assert_eq!(report.dataset.feature_count, 1);
+    assert_eq!(report.feature_errors.len(), 1);
+    assert_eq!(report.feature_errors[0].index, 0);
+    assert_eq!(report.feature_errors[0].kind, FeatureErrorKind::TooComplex);
+    assert!(report.oversized_features.is_empty());
+}
+
+#[tokio::test]
+async fn test_oversized_feature_is_simplified() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+    let limits = FeatureLimits {
+        oversized: OversizedFeatures::Simplify,
+        ..LIMITS
+    };
+
+    let request = IngestRequest::new(write_coast(&dir)).with_feature_limits(limits);
+    let report = service(&store).ingest(&request).await.unwrap();
+
+    assert_eq!(report.features_stored, 2);
+    let coast = store.get_feature(FeatureId(0)).await.unwrap().unwrap();
+    let vertices = coast.geometry.unwrap().vertex_count();
+    assert!(vertices <= LIMITS.max_vertices);
+    assert_eq!(
+        report.oversized_features[0].action,
+        OversizedAction::Simplified { simplified_vertices: vertices }
+    );
+}
@@ -0,0 +1,143 @@
+//! End-to-end test of ingest, index build and query with the mock embedder
+//!
+//! Runs the same path as `georag add`, `georag build` and `georag query`
+//! against memory stores, without Ollama, and pins the ranked output.
+
+use georag_core::formats::FormatRegistry;
+use georag_core::geo::models::Crs;
+use georag_core::llm::{create_embedder, EmbedderOptions, Generator, MockGenerator};
+use georag_core::models::{Geometry, SpatialFilter, SpatialPredicate};
+use georag_retrieval::{IndexBuilder, QueryPlan, QueryResult};
+use georag_service::{IngestRequest, IngestService, QueryService};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::SpatialStore;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Three stations inside the study area and a better match outside it
+const STATIONS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.80, -6.20] },
+      "properties": { "content": "Riverside barrier protects homes from river flood water" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.82, -6.21] },
+      "properties": { "content": "Bakery selling fresh bread every morning" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.84, -6.22] },
+      "properties": { "content": "River flood warning station" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [110.40, -7.00] },
+      "properties": { "content": "River flood" }
+    }
+  ]
+}"#;
+
+struct Pipeline {
+    query: QueryService,
+    _dir: TempDir,
+}
+
+/// Ingest the stations and build the index with `mock:768`
+async fn build() -> Pipeline {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("stations.geojson");
+    std::fs::write(&path, STATIONS).unwrap();
+
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let vector = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    IngestService::new(spatial.clone(), Arc::new(FormatRegistry::with_defaults()))
+        .ingest(&IngestRequest::new(&path))
+        .await
+        .unwrap();
+
+    let embedder = create_embedder("mock:768", &EmbedderOptions::default()).unwrap();
+    let builder = IndexBuilder::new(
+        spatial.clone(),
+        vector.clone(),
+        documents.clone(),
+        embedder,
+        Crs::wgs84(),
+    );
+    let datasets = spatial.list_datasets().await.unwrap();
+    let result = builder.full_rebuild(&datasets, true, |_| {}).await.unwrap();
+    assert_eq!(result.chunk_count, 4);
+    assert_eq!(builder.create_index_state(&result).embedder, "mock:768");
+
+    Pipeline {
+        query: QueryService::new(spatial, vector, documents),
+        _dir: dir,
+    }
+}
+
+/// "river flood" within the study area around Jakarta
+async fn query(pipeline: &Pipeline) -> QueryResult {
+    let area = Geometry::polygon(vec![vec![
+        [106.7, -6.3],
+        [106.9, -6.3],
+        [106.9, -6.1],
+        [106.7, -6.1],
+        [106.7, -6.3],
+    ]]);
+    let plan = QueryPlan::new("river flood")
+        .with_spatial_filter(SpatialFilter::new(SpatialPredicate::Intersects).geometry(area));
+    let embedder = create_embedder("mock:768", &EmbedderOptions::default()).unwrap();
+    pipeline.query.execute(&plan, embedder).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_mock_pipeline_ranks_spatial_matches_by_similarity() {
+    let pipeline = build().await;
+    let result = query(&pipeline).await;
+
+    assert_eq!(result.spatial_matches, 3);
+    let excerpts: Vec<&str> = result.sources.iter().map(|s| s.excerpt.as_str()).collect();
+    assert_eq!(
+        excerpts,
+        [
+            "River flood warning station",
+            "Riverside barrier protects homes from river flood water",
+            "Bakery selling fresh bread every morning",
+        ]
+    );
+
+    let scores: Vec<f32> = result.sources.iter().map(|s| s.score).collect();
+    assert!(
+        scores.windows(2).all(|pair| pair[0] > pair[1]),
+        "scores not ranked: {:?}",
+        scores
+    );
+}
+
+#[tokio::test]
+async fn test_mock_pipeline_is_deterministic() {
+    let first = query(&build().await).await;
+    let second = query(&build().await).await;
+
+    let scores = |result: &QueryResult| result.sources.iter().map(|s| s.score).collect::<Vec<_>>();
+    assert_eq!(scores(&first), scores(&second));
+}
+
+#[tokio::test]
+async fn test_mock_generator_answers_from_ranked_excerpts() {
+    let result = query(&build().await).await;
+    let excerpts: Vec<&str> = result.sources.iter().take(2).map(|s| s.excerpt.as_str()).collect();
+
+    let answer = MockGenerator::new().generate("Where are flood defences?", &excerpts).unwrap();
+    assert_eq!(
+        answer,
+        "Answer to \"Where are flood defences?\" from 2 excerpt(s):\n\
+         [1] River flood warning station\n\
+         [2] Riverside barrier protects homes from river flood water"
+    );
+}
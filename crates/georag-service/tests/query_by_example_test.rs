@@ -0,0 +1,235 @@
+//! Integration tests for querying by example
+//!
+//! A parcel's two chunks seed the search with their stored embeddings. The
+//! embedder fails every call, so a query that embeds anything fails too.
+
+mod common;
+
+use common::dataset;
+use georag_core::error::{GeoragError, Result};
+use georag_core::llm::Embedder;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Crs, DatasetId, Embedding, Feature, FeatureId, Geometry,
+    SpatialFilter, SpatialPredicate, TextChunk,
+};
+use georag_retrieval::{QueryPlan, QueryResult, SeedMode};
+use georag_service::{ExampleSeed, QueryService, ServiceError};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Embedder that is never expected to run
+struct NoEmbedder;
+
+impl Embedder for NoEmbedder {
+    fn embed(&self, _texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Err(GeoragError::EmbedderUnavailable {
+            reason: "queries by example must not embed".to_string(),
+            remediation: "none".to_string(),
+        })
+    }
+
+    fn dimensions(&self) -> usize {
+        3
+    }
+
+    fn model_name(&self) -> &str {
+        "stored"
+    }
+}
+
+fn chunk(id: u64, feature_id: u64, dataset_id: DatasetId, content: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: "/data/parcels.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: Some(FeatureId(feature_id)),
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: content.split_whitespace().count(),
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: Some(dataset_id),
+        },
+    }
+}
+
+struct Fixture {
+    service: QueryService,
+    parcels: DatasetId,
+    roads: DatasetId,
+}
+
+/// Parcel 1 has two embedded chunks; parcel 2 is close to them, parcel 3 far
+/// away in both meaning and place. Parcel 4 has no chunks and parcel 5 only
+/// one without an embedding.
+async fn fixture() -> Fixture {
+    let spatial = Arc::new(MemorySpatialStore::new());
+    let vectors = Arc::new(MemoryVectorStore::new());
+    let documents = Arc::new(MemoryDocumentStore::new());
+
+    let parcels = spatial.store_dataset(&dataset("parcels")).await.unwrap();
+    let roads = spatial.store_dataset(&dataset("roads")).await.unwrap();
+    let features: Vec<Feature> = [(1, 0.0), (2, 0.1), (3, 5.0), (4, 0.2), (5, 0.3)]
+        .iter()
+        .map(|(id, x)| {
+            Feature::with_geometry(FeatureId(*id), Geometry::point(*x, 0.0), HashMap::new(), 4326)
+        })
+        .collect();
+    spatial.store_features(&features).await.unwrap();
+    spatial.associate_features_with_dataset(parcels, features.iter().map(|f| f.id).collect());
+
+    documents
+        .store_chunks(&[
+            chunk(1, 1, parcels, "orchard with irrigation"),
+            chunk(2, 1, parcels, "orchard by the canal"),
+            chunk(3, 2, parcels, "irrigated orchard"),
+            chunk(4, 3, parcels, "warehouse"),
+            chunk(5, 5, parcels, "vacant lot"),
+        ])
+        .await
+        .unwrap();
+    let embeddings: Vec<Embedding> = [
+        (1, [1.0, 0.0, 0.0]),
+        (2, [0.8, 0.6, 0.0]),
+        (3, [0.9, 0.3, 0.1]),
+        (4, [0.0, 0.1, 1.0]),
+    ]
+    .iter()
+    .map(|(id, vector)| Embedding {
+        chunk_id: ChunkId(*id),
+        vector: vector.to_vec(),
+        spatial_metadata: None,
+        dataset_id: Some(parcels),
+    })
+    .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    Fixture {
+        service: QueryService::new(spatial, vectors, documents),
+        parcels,
+        roads,
+    }
+}
+
+impl Fixture {
+    async fn query(&self, seed: ExampleSeed, mode: SeedMode, plan: QueryPlan) -> QueryResult {
+        let example = self.service.example(seed, mode).await.unwrap();
+        let plan = plan.with_example(example).with_explain(true);
+        self.service.execute(&plan, NoEmbedder).await.unwrap()
+    }
+
+    fn parcel(&self, id: u64) -> ExampleSeed {
+        ExampleSeed::Feature {
+            dataset_id: self.parcels,
+            feature_id: FeatureId(id),
+        }
+    }
+}
+
+fn chunk_ids(result: &QueryResult) -> Vec<u64> {
+    result.sources.iter().map(|s| s.chunk_id.0).collect()
+}
+
+#[tokio::test]
+async fn test_feature_chunks_seed_the_search_and_are_excluded() {
+    let fixture = fixture().await;
+
+    let example = fixture.service.example(fixture.parcel(1), SeedMode::Average).await.unwrap();
+    assert_eq!(example.source, "parcels:1");
+    assert_eq!(example.seed_chunks, vec![ChunkId(1), ChunkId(2)]);
+
+    for mode in [SeedMode::Average, SeedMode::Individual] {
+        let result = fixture.query(fixture.parcel(1), mode, QueryPlan::new("like parcels:1")).await;
+        assert_eq!(chunk_ids(&result), vec![3, 4], "{:?}", mode);
+
+        let semantic = result.explanation.unwrap().semantic_phase.unwrap();
+        let example = semantic.example.unwrap();
+        assert_eq!(example.source, "parcels:1");
+        assert_eq!(example.seed_chunks, 2);
+        assert_eq!(example.averaged, mode == SeedMode::Average);
+    }
+}
+
+#[tokio::test]
+async fn test_spatial_filter_applies_to_query_by_example() {
+    let fixture = fixture().await;
+    let near_parcel_3 = SpatialFilter {
+        predicate: SpatialPredicate::Intersects,
+        geometry: Some(Geometry::polygon(vec![vec![
+            [4.0, -1.0],
+            [6.0, -1.0],
+            [6.0, 1.0],
+            [4.0, 1.0],
+            [4.0, -1.0],
+        ]])),
+        distance: None,
+        crs: Crs::wgs84(),
+        buffer: None,
+        exclude: Vec::new(),
+    };
+
+    let plan = QueryPlan::new("like parcels:1").with_spatial_filter(near_parcel_3);
+    let result = fixture.query(fixture.parcel(1), SeedMode::Average, plan).await;
+    assert_eq!(chunk_ids(&result), vec![4]);
+}
+
+#[tokio::test]
+async fn test_single_chunk_seed() {
+    let fixture = fixture().await;
+    let result = fixture
+        .query(
+            ExampleSeed::Chunk(ChunkId(3)),
+            SeedMode::Average,
+            QueryPlan::new("like chunk 3"),
+        )
+        .await;
+
+    // Both chunks of parcel 1 are equally close to chunk 3
+    let mut closest = chunk_ids(&result)[..2].to_vec();
+    closest.sort();
+    assert_eq!(closest, [1, 2]);
+    assert!(!chunk_ids(&result).contains(&3));
+}
+
+#[tokio::test]
+async fn test_examples_without_embedded_chunks_are_rejected() {
+    let fixture = fixture().await;
+    let example = |seed| fixture.service.example(seed, SeedMode::Average);
+
+    // No chunks at all, and a chunk that was never embedded
+    for id in [4, 5] {
+        let err = example(fixture.parcel(id)).await.unwrap_err();
+        assert!(
+            matches!(err, ServiceError::ExampleNotIndexed { ref example } if *example == format!("parcels:{}", id))
+        );
+    }
+
+    // The feature must belong to the named dataset
+    let err = example(ExampleSeed::Feature {
+        dataset_id: fixture.roads,
+        feature_id: FeatureId(1),
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(err, ServiceError::FeatureNotFound { .. }));
+
+    let err = example(ExampleSeed::Chunk(ChunkId(99))).await.unwrap_err();
+    assert!(matches!(err, ServiceError::ChunkNotFound { id: ChunkId(99) }));
+
+    // A plan without seed chunks never reaches the pipeline
+    let plan = QueryPlan::new("like nothing").with_example(georag_retrieval::QueryExample {
+        source: "nothing".to_string(),
+        seed_chunks: Vec::new(),
+        mode: SeedMode::Average,
+    });
+    let err = fixture.service.execute(&plan, NoEmbedder).await.unwrap_err();
+    assert!(matches!(err, ServiceError::ExampleNotIndexed { .. }));
+}
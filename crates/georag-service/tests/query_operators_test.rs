@@ -0,0 +1,301 @@
+//! Integration tests for operators in query text
+//!
+//! A table of queries checks what the parser takes out of the text and what
+//! it leaves in. The operators are then applied to plans, and a query over a
+//! dataset of dated reports and a dataset of photos checks that `dataset:`
+//! and `since:` filter what the pipeline returns.
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::{chunk, dataset, KeywordEmbedder, Stores};
+use georag_core::models::{
+    Dataset, Distance, DistanceUnit, Feature, FeatureId, Geometry, SavedArea, SpatialFilter,
+    SpatialPredicate,
+};
+use georag_retrieval::{DiagnosticKind, QueryPlan, QueryResult, TimeGrouping, TimeInterval};
+use georag_service::{apply_operators, parse_operators, Operator};
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::HashMap;
+
+fn since(year: i32, month: u32, day: u32) -> Operator {
+    Operator::Since(Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap())
+}
+
+#[test]
+fn test_parser_table() {
+    // Query, text left, operators parsed, tokens ignored (and left in the text)
+    let cases: Vec<(&str, &str, Vec<Operator>, Vec<&str>)> = vec![
+        (
+            "drainage issues near:ubud within:2km dataset:reports since:2023",
+            "drainage issues",
+            vec![
+                Operator::Near("ubud".to_string()),
+                Operator::Within(Distance::kilometers(2.0)),
+                Operator::Dataset("reports".to_string()),
+                since(2023, 1, 1),
+            ],
+            vec![],
+        ),
+        (
+            r#"flooding near:"ubud centre" within:"1.5 mi" after rain"#,
+            "flooding after rain",
+            vec![
+                Operator::Near("ubud centre".to_string()),
+                Operator::Within(Distance::new(1.5, DistanceUnit::Miles)),
+            ],
+            vec![],
+        ),
+        (
+            r#"trees dataset:"city parks" dataset:streets"#,
+            "trees",
+            vec![
+                Operator::Dataset("city parks".to_string()),
+                Operator::Dataset("streets".to_string()),
+            ],
+            vec![],
+        ),
+        (
+            "NEAR:Ubud Since:2023-06 drains",
+            "drains",
+            vec![Operator::Near("Ubud".to_string()), since(2023, 6, 1)],
+            vec![],
+        ),
+        (
+            "drains since:2023-06-15T08:00:00Z within:300",
+            "drains",
+            vec![
+                Operator::Since(Utc.with_ymd_and_hms(2023, 6, 15, 8, 0, 0).unwrap()),
+                Operator::Within(Distance::meters(300.0)),
+            ],
+            vec![],
+        ),
+        // Other keys, URLs and times are not operators
+        (
+            "road see:https://example.com/a at 10:30 foo:bar",
+            "road see:https://example.com/a at 10:30 foo:bar",
+            vec![],
+            vec![],
+        ),
+        // A quoted phrase is text, even when it looks like an operator
+        (r#""near:ubud" drains"#, r#""near:ubud" drains"#, vec![], vec![]),
+        ("  spaced   out  ", "spaced out", vec![], vec![]),
+        ("", "", vec![], vec![]),
+        // Malformed operators stay in the text
+        ("near: drains", "near: drains", vec![], vec!["near:"]),
+        ("drains within:far", "drains within:far", vec![], vec!["within:far"]),
+        ("drains within:-2km", "drains within:-2km", vec![], vec!["within:-2km"]),
+        (
+            "drains within:3leagues",
+            "drains within:3leagues",
+            vec![],
+            vec!["within:3leagues"],
+        ),
+        ("drains since:2023-13", "drains since:2023-13", vec![], vec!["since:2023-13"]),
+        (
+            "drains since:yesterday",
+            "drains since:yesterday",
+            vec![],
+            vec!["since:yesterday"],
+        ),
+        ("drains dataset:\"\"", "drains dataset:\"\"", vec![], vec!["dataset:\"\""]),
+        (r#"drains near:ub"ud"#, r#"drains near:ub"ud"#, vec![], vec![r#"near:ub"ud"#]),
+        (
+            r#"drains near:"ubud centre"#,
+            r#"drains near:"ubud centre"#,
+            vec![],
+            vec![r#"near:"ubud centre"#],
+        ),
+    ];
+
+    for (query, text, operators, ignored) in cases {
+        let parsed = parse_operators(query);
+        assert_eq!(parsed.original, query);
+        assert_eq!(parsed.text, text, "text of {:?}", query);
+        let parsed_operators: Vec<Operator> =
+            parsed.operators.iter().map(|p| p.operator.clone()).collect();
+        assert_eq!(parsed_operators, operators, "operators of {:?}", query);
+        let ignored_tokens: Vec<&str> = parsed.ignored.iter().map(|i| i.token.as_str()).collect();
+        assert_eq!(ignored_tokens, ignored, "ignored tokens of {:?}", query);
+        assert!(parsed.ignored.iter().all(|i| !i.reason.is_empty()));
+    }
+}
+
+#[test]
+fn test_tokens_are_kept_as_written() {
+    let parsed = parse_operators(r#"flooding near:"ubud centre" within:2KM"#);
+    let tokens: Vec<&str> = parsed.operators.iter().map(|p| p.token.as_str()).collect();
+    assert_eq!(tokens, vec![r#"near:"ubud centre""#, "within:2KM"]);
+    assert_eq!(parsed.near(), Some("ubud centre"));
+}
+
+fn ubud() -> SavedArea {
+    let square = Geometry::polygon(vec![vec![
+        [115.25, -8.52],
+        [115.27, -8.52],
+        [115.27, -8.50],
+        [115.25, -8.50],
+        [115.25, -8.52],
+    ]]);
+    SavedArea::new("ubud", square)
+}
+
+#[test]
+fn test_operators_become_filters() {
+    let query = "drainage issues near:ubud within:2km dataset:reports since:2023";
+    let area = ubud();
+    let plan = apply_operators(
+        QueryPlan::new(query),
+        parse_operators(query),
+        Some(&area),
+        Some("reported_at"),
+    );
+
+    assert_eq!(plan.text_query, "drainage issues");
+    let filter = plan.spatial_filter.as_ref().unwrap();
+    assert_eq!(filter.geometry, Some(area.geometry.clone()));
+    assert_eq!(filter.buffer, Some(Distance::kilometers(2.0)));
+    assert_eq!(plan.named_area.as_ref().unwrap().name, "ubud");
+    assert_eq!(plan.datasets, vec!["reports".to_string()]);
+    let time_filter = plan.time_filter.as_ref().unwrap();
+    assert_eq!(time_filter.property, "reported_at");
+    assert_eq!(time_filter.since, Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap());
+
+    let operators = plan.operators.as_ref().unwrap();
+    assert_eq!(operators.original, query);
+    assert_eq!(operators.text, "drainage issues");
+    assert_eq!(operators.applied.len(), 4);
+    assert!(operators.ignored.is_empty());
+    let filters: Vec<&str> = operators.applied.iter().map(|a| a.filter.as_str()).collect();
+    assert!(filters.contains(&"area ubud"), "{:?}", filters);
+    assert!(filters.contains(&"reported_at on or after 2023-01-01"), "{:?}", filters);
+}
+
+#[test]
+fn test_operators_the_plan_cannot_take_are_ignored() {
+    // Unknown area: near: and the within: measured from it are ignored
+    let query = "drains near:nowhere within:2km since:2023";
+    let plan = apply_operators(QueryPlan::new(query), parse_operators(query), None, None);
+    assert_eq!(plan.text_query, "drains");
+    assert!(plan.spatial_filter.is_none());
+    assert!(plan.time_filter.is_none());
+    let operators = plan.operators.unwrap();
+    assert!(operators.applied.is_empty());
+    let reasons: Vec<&str> = operators.ignored.iter().map(|i| i.reason.as_str()).collect();
+    assert_eq!(reasons.len(), 3, "{:?}", reasons);
+    assert!(reasons.iter().any(|r| r.contains("no saved area is named 'nowhere'")));
+    assert!(reasons.iter().any(|r| r.contains("within: needs a near: area")));
+    assert!(reasons.iter().any(|r| r.contains("no time property")));
+
+    // An explicit spatial filter wins over near:
+    let query = "drains near:ubud near:kuta";
+    let area = ubud();
+    let explicit = QueryPlan::new(query).with_spatial_filter(
+        SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::point(115.0, -8.0)),
+    );
+    let plan = apply_operators(explicit, parse_operators(query), Some(&area), None);
+    assert_eq!(plan.spatial_filter.unwrap().geometry, Some(Geometry::point(115.0, -8.0)));
+    let ignored = plan.operators.unwrap().ignored;
+    assert_eq!(ignored[0].token, "near:ubud");
+    assert!(ignored[0].reason.contains("already has a spatial filter"));
+    assert_eq!(ignored[1].token, "near:kuta");
+    assert!(ignored[1].reason.contains("repeats near:"));
+
+    // since: falls back to the time grouping's property
+    let query = "drains since:2024-02";
+    let grouped = QueryPlan::new(query)
+        .with_group_by_time(TimeGrouping::new("reported_at", TimeInterval::Month));
+    let plan = apply_operators(grouped, parse_operators(query), None, None);
+    assert_eq!(plan.time_filter.unwrap().property, "reported_at");
+}
+
+const VOCABULARY: [&str; 3] = ["drain", "blocked", "photo"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+impl Stores {
+    async fn query(&self, query: &str) -> QueryResult {
+        let plan = apply_operators(
+            QueryPlan::new(query).with_explain(true),
+            parse_operators(query),
+            None,
+            Some("reported_at"),
+        );
+        self.service().execute(&plan, embedder()).await.unwrap()
+    }
+
+    /// Store a feature of dataset `name` with one indexed chunk
+    async fn add(&self, id: u64, name: &str, content: &str, reported_at: &str) {
+        let mut properties = HashMap::new();
+        properties.insert("content".to_string(), json!(content));
+        properties.insert("reported_at".to_string(), json!(reported_at));
+        let feature =
+            Feature::with_geometry(FeatureId(id), Geometry::point(115.26, -8.51), properties, 4326);
+        self.spatial.store_features(&[feature]).await.unwrap();
+        self.index(&[chunk(id, name, Some(id), content)], &embedder()).await;
+    }
+}
+
+/// Reports from 2022 and 2023, and a photo from 2023
+async fn setup() -> Stores {
+    let stores = Stores::new();
+    for name in ["reports", "photos"] {
+        let dataset = Dataset { feature_count: 2, ..dataset(name) };
+        stores.spatial.store_dataset(&dataset).await.unwrap();
+    }
+    stores.add(1, "reports", "drain blocked", "2022-11-03").await;
+    stores.add(2, "reports", "drain blocked again", "2023-04-18").await;
+    stores.add(3, "photos", "photo of a drain", "2023-05-01").await;
+    stores
+}
+
+fn excerpts(result: &QueryResult) -> Vec<&str> {
+    result.sources.iter().map(|s| s.excerpt.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_dataset_and_since_filter_the_results() {
+    let stores = setup().await;
+
+    let result = stores.query("drain").await;
+    assert_eq!(result.sources.len(), 3);
+
+    let result = stores.query("drain dataset:reports").await;
+    let mut found = excerpts(&result);
+    found.sort();
+    assert_eq!(found, vec!["drain blocked", "drain blocked again"]);
+
+    let result = stores.query("drain dataset:REPORTS since:2023").await;
+    assert_eq!(excerpts(&result), vec!["drain blocked again"]);
+    assert_eq!(result.candidates.after_visibility, 2);
+    assert_eq!(result.candidates.after_time, 1);
+
+    // Datasets add up
+    let result = stores.query("drain since:2023 dataset:reports dataset:photos").await;
+    assert_eq!(result.sources.len(), 2);
+
+    // The interpretation is echoed in the explanation
+    let operators = result.explanation.unwrap().operators.unwrap();
+    assert_eq!(operators.text, "drain");
+    assert_eq!(operators.applied.len(), 3);
+}
+
+#[tokio::test]
+async fn test_empty_results_name_the_operator_filter() {
+    let stores = setup().await;
+
+    let result = stores.query("drain dataset:minutes").await;
+    assert!(result.sources.is_empty());
+    assert_eq!(result.diagnostics[0].kind, DiagnosticKind::OutsideDatasets);
+    assert!(result.diagnostics[0].message.contains("minutes"));
+
+    let result = stores.query("drain since:2024").await;
+    assert!(result.sources.is_empty());
+    assert_eq!(result.diagnostics[0].kind, DiagnosticKind::TimeFilter);
+    assert!(result.diagnostics[0]
+        .message
+        .contains("reported_at dated on or after 2024-01-01"));
+}
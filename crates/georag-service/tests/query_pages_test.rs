@@ -0,0 +1,167 @@
+//! Integration tests for cursor pagination over ranked query results
+//!
+//! Pages are cut from the ranked list a query produced once. A cursor stops
+//! working once its list expires or the index it was ranked against changes,
+//! each with its own error so callers know to run the query again, and it
+//! never works in another workspace.
+
+use georag_core::models::{ChunkId, WorkspaceId};
+use georag_retrieval::{QueryPlan, QueryResult, SourceReference};
+use georag_service::{plan_hash, PagedQuery, QueryCursor, QueryPages, ServiceError};
+use std::time::Duration;
+
+fn ranked(n: usize) -> QueryResult {
+    let sources = (0..n)
+        .map(|i| SourceReference {
+            chunk_id: ChunkId(i as u64),
+            feature_id: None,
+            document_path: format!("doc-{}.txt", i),
+            page: None,
+            offset: None,
+            excerpt: format!("result {}", i),
+            score: 1.0 - i as f32 / 100.0,
+            spatial_match: None,
+            source_url: None,
+            confidence: None,
+        })
+        .collect();
+    QueryResult {
+        answer: String::new(),
+        sources,
+        spatial_matches: n,
+        semantic_scores: Some((0..n).map(|i| 1.0 - i as f32 / 100.0).collect()),
+        explanation: None,
+        filtered_by_threshold: 0,
+        time_groups: None,
+        candidates: Default::default(),
+        diagnostics: Vec::new(),
+        attributions: Vec::new(),
+        ungrounded: Vec::new(),
+    }
+}
+
+fn paged(n: usize) -> (String, PagedQuery) {
+    let plan = QueryPlan::new("harbour cranes").with_top_k(n);
+    (plan_hash(&plan, "workspace-a"), PagedQuery::new(plan, ranked(n), "mock:32"))
+}
+
+fn excerpts(page: &QueryResult) -> Vec<String> {
+    page.sources.iter().map(|s| s.excerpt.clone()).collect()
+}
+
+#[test]
+fn test_pages_walk_the_ranked_results_in_order() {
+    let pages = QueryPages::default();
+    let (hash, query) = paged(25);
+
+    let first = pages.first_page(hash, "gen-1", query, 10);
+    assert_eq!((first.offset, first.total, first.result.sources.len()), (0, 25, 10));
+    assert_eq!(first.result.semantic_scores.as_ref().unwrap().len(), 10);
+
+    let mut seen = excerpts(&first.result);
+    let mut next = first.next;
+    while let Some(cursor) = next {
+        // Cursors survive the round trip through their opaque form
+        let cursor = QueryCursor::decode(&cursor.encode()).unwrap();
+        let page = pages.page(&cursor, "gen-1", None).unwrap();
+        assert_eq!(page.offset, seen.len());
+        seen.extend(excerpts(&page.result));
+        next = page.next;
+    }
+
+    let expected: Vec<String> = (0..25).map(|i| format!("result {}", i)).collect();
+    assert_eq!(seen, expected);
+}
+
+#[test]
+fn test_single_page_results_get_no_cursor() {
+    let pages = QueryPages::default();
+    let (hash, query) = paged(4);
+
+    let page = pages.first_page(hash, "gen-1", query, 10);
+    assert!(page.next.is_none());
+    assert_eq!(page.result.sources.len(), 4);
+    assert!(pages.is_empty());
+}
+
+#[test]
+fn test_cursor_expires_after_the_ttl() {
+    let pages = QueryPages::new(Duration::from_millis(50));
+    let (hash, query) = paged(30);
+    let cursor = pages.first_page(hash, "gen-1", query, 10).next.unwrap();
+    assert!(pages.page(&cursor, "gen-1", None).is_ok());
+
+    std::thread::sleep(Duration::from_millis(80));
+    assert!(matches!(pages.page(&cursor, "gen-1", None), Err(ServiceError::CursorExpired)));
+    assert!(pages.is_empty());
+}
+
+#[test]
+fn test_index_change_invalidates_cursors() {
+    let pages = QueryPages::default();
+    let (hash, query) = paged(30);
+    let cursor = pages.first_page(hash, "gen-1", query, 10).next.unwrap();
+
+    assert!(matches!(
+        pages.page(&cursor, "gen-2", None),
+        Err(ServiceError::CursorInvalidated)
+    ));
+    // The ranked list is dropped, so the old generation cannot come back to it
+    assert!(pages.is_empty());
+}
+
+#[test]
+fn test_cursors_only_work_in_their_workspace() {
+    let pages = QueryPages::default();
+    let (workspace, other) = (WorkspaceId::new(), WorkspaceId::new());
+    let (hash, query) = paged(30);
+    let cursor = pages
+        .first_page(hash, "gen-1", query.with_workspace(workspace), 10)
+        .next
+        .unwrap();
+
+    assert!(matches!(
+        pages.page(&cursor, "gen-1", Some(other)),
+        Err(ServiceError::CursorNotFound)
+    ));
+    assert!(matches!(pages.page(&cursor, "gen-2", None), Err(ServiceError::CursorNotFound)));
+    // Requests to other workspaces leave the ranked list in place
+    assert!(pages.page(&cursor, "gen-1", Some(workspace)).is_ok());
+}
+
+#[test]
+fn test_page_size_limits_and_malformed_cursors() {
+    let pages = QueryPages::default().with_max_page_size(20);
+    assert!(pages.check_page_size(20).is_ok());
+    assert!(matches!(pages.check_page_size(0), Err(ServiceError::InvalidQuery(_))));
+    assert!(matches!(pages.check_page_size(21), Err(ServiceError::InvalidQuery(_))));
+
+    for cursor in ["", "zz", "abc", &hex("only.three.parts"), &hex("a.b.x.10"), &hex("a.b.0.0")] {
+        assert!(
+            matches!(QueryCursor::decode(cursor), Err(ServiceError::InvalidCursor(_))),
+            "{:?} was accepted",
+            cursor
+        );
+    }
+
+    // A cursor for a query that was never paged has nothing to return
+    let cursor = QueryCursor {
+        plan_hash: "unknown".to_string(),
+        generation: "gen-1".to_string(),
+        offset: 10,
+        page_size: 10,
+    };
+    assert!(matches!(pages.page(&cursor, "gen-1", None), Err(ServiceError::CursorExpired)));
+}
+
+#[test]
+fn test_plan_hash_separates_scopes_and_plans() {
+    let plan = QueryPlan::new("harbour cranes");
+    assert_eq!(plan_hash(&plan, "a"), plan_hash(&plan.clone(), "a"));
+    assert_ne!(plan_hash(&plan, "a"), plan_hash(&plan, "b"));
+    assert_ne!(plan_hash(&plan, "a"), plan_hash(&plan.clone().with_top_k(50), "a"));
+}
+
+fn hex(plain: &str) -> String {
+    plain.bytes().map(|b| format!("{:02x}", b)).collect()
+}
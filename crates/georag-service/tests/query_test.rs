@@ -0,0 +1,233 @@
+//! Integration tests for the shared query service
+//!
+//! The CLI `--format` output and the API query responses are both rendered
+//! from `to_rows`, `to_geojson` and `to_map_png`, so these pin their shape and
+//! redaction.
+
+mod common;
+
+use common::{chunk, dataset, KeywordEmbedder, Stores};
+use georag_core::geo::models::Crs;
+use georag_core::models::{Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType};
+use georag_core::redaction::{RedactionConfig, Redactor};
+use georag_retrieval::export::to_csv;
+use georag_retrieval::{
+    Basemap, GeometryDetail, GeometryOutput, MapOptions, QueryPlan, QueryResult,
+};
+use georag_service::{QueryService, ServiceError};
+use georag_store::ports::SpatialStore;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const VOCABULARY: [&str; 4] = ["park", "bench", "market", "stall"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// A park chunk attached to a point feature and a market chunk without one
+async fn setup() -> QueryService {
+    let stores = Stores::new();
+
+    let feature = Feature::with_geometry(
+        FeatureId(7),
+        Geometry::point(106.8, -6.2),
+        HashMap::new(),
+        Crs::wgs84().epsg,
+    );
+    stores.spatial.store_features(&[feature]).await.unwrap();
+
+    let chunks = vec![
+        chunk(1, "parks", Some(7), "park bench call 555-0100"),
+        chunk(2, "parks", None, "market stall"),
+    ];
+    stores.index(&chunks, &embedder()).await;
+
+    let redactor = Redactor::new(&RedactionConfig {
+        properties: Vec::new(),
+        patterns: vec![r"\d{3}-\d{4}".to_string()],
+    })
+    .unwrap();
+
+    stores.service().with_redactor(redactor)
+}
+
+fn plan() -> QueryPlan {
+    QueryPlan::new("park bench").with_top_k(1)
+}
+
+#[tokio::test]
+async fn test_rows_are_located_and_redacted() {
+    let service = setup().await;
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let rows = service.to_rows(&result).await;
+    assert_eq!(rows.len(), 1);
+
+    let mut row = rows[0].clone();
+    assert!(row.remove("score").unwrap().is_number());
+    assert_eq!(
+        Value::Object(row),
+        json!({
+            "excerpt": "park bench call [REDACTED]",
+            "document_path": "/data/parks.geojson",
+            "chunk_id": 1,
+            "feature_id": 7,
+            "lon": 106.8,
+            "lat": -6.2,
+        })
+    );
+
+    let csv = to_csv(&rows);
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(lines[0], "score,excerpt,document_path,chunk_id,feature_id,lon,lat");
+    assert!(lines[1].ends_with(",park bench call [REDACTED],/data/parks.geojson,1,7,106.8,-6.2"));
+}
+
+#[tokio::test]
+async fn test_geojson_has_one_feature_per_source() {
+    let service = setup().await;
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let collection = service.to_geojson(&result).await;
+    assert_eq!(collection.len(), 2);
+    assert_eq!(collection["type"], "FeatureCollection");
+
+    let features = collection["features"].as_array().unwrap();
+    assert_eq!(features.len(), 1);
+
+    let feature = &features[0];
+    assert_eq!(feature["type"], "Feature");
+    assert_eq!(feature["geometry"], json!({ "type": "Point", "coordinates": [106.8, -6.2] }));
+    assert_eq!(feature["properties"]["excerpt"], "park bench call [REDACTED]");
+    assert_eq!(feature["properties"]["feature_id"], 7);
+    assert_eq!(feature["properties"]["chunk_id"], 1);
+}
+
+#[tokio::test]
+async fn test_geometry_output_controls_payload() {
+    let service = setup().await;
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let trimmed = service.clone().with_geometry_output(GeometryOutput {
+        detail: GeometryDetail::Centroid,
+        precision: Some(0),
+        ..Default::default()
+    });
+    let collection = trimmed.to_geojson(&result).await;
+    assert_eq!(
+        collection["features"][0]["geometry"],
+        json!({ "type": "Point", "coordinates": [107.0, -6.0] })
+    );
+    let rows = trimmed.to_rows(&result).await;
+    assert_eq!((rows[0]["lon"].clone(), rows[0]["lat"].clone()), (json!(107.0), json!(-6.0)));
+
+    let without = service.with_geometry_output(GeometryOutput {
+        detail: GeometryDetail::None,
+        precision: None,
+        ..Default::default()
+    });
+    let collection = without.to_geojson(&result).await;
+    assert_eq!(collection["features"][0]["geometry"], Value::Null);
+}
+
+/// The parks dataset with one park outlined by a 100,000-vertex polygon
+async fn detailed_park() -> (QueryService, DatasetId) {
+    let stores = Stores::new();
+
+    let dataset = Dataset {
+        geometry_type: GeometryType::Polygon,
+        feature_count: 1,
+        ..dataset("parks")
+    };
+    let dataset_id = stores.spatial.store_dataset(&dataset).await.unwrap();
+
+    let n = 100_000;
+    let mut ring: Vec<[f64; 2]> = (0..n)
+        .map(|i| {
+            let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+            let radius = 0.01 * (1.0 + 0.001 * (i as f64 * 0.37).sin());
+            [106.8 + radius * angle.cos(), -6.2 + radius * angle.sin()]
+        })
+        .collect();
+    ring.push(ring[0]);
+    let feature =
+        Feature::with_geometry(FeatureId(9), Geometry::polygon(vec![ring]), HashMap::new(), 4326);
+    stores.spatial.upsert_dataset_features(dataset_id, &[feature]).await.unwrap();
+    stores.index(&[chunk(1, "parks", Some(9), "park bench")], &embedder()).await;
+
+    (stores.service(), dataset_id)
+}
+
+#[tokio::test]
+async fn test_adaptive_geometry_points_to_the_full_feature() {
+    let (service, dataset_id) = detailed_park().await;
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let adaptive = service.clone().with_geometry_output(GeometryOutput {
+        detail: GeometryDetail::Adaptive,
+        vertex_budget: 500,
+        ..Default::default()
+    });
+    let collection = adaptive.to_geojson(&result).await;
+    let feature = &collection["features"][0];
+    let ring = feature["geometry"]["coordinates"][0].as_array().unwrap();
+    assert!(ring.len() <= 500 && ring.len() > 100, "{} vertices", ring.len());
+    assert_eq!(ring.first(), ring.last());
+    assert_eq!(feature["properties"]["geometry_simplified"], true);
+    assert_eq!(feature["properties"]["original_vertex_count"], 100_001);
+    assert_eq!(feature["properties"]["dataset_id"], dataset_id.0);
+    assert_eq!(feature["properties"]["feature_id"], 9);
+
+    // A budget above the vertex count keeps the geometry whole
+    let whole = service.with_geometry_output(GeometryOutput {
+        detail: GeometryDetail::Adaptive,
+        vertex_budget: 200_000,
+        ..Default::default()
+    });
+    let collection = whole.to_geojson(&result).await;
+    let feature = &collection["features"][0];
+    assert_eq!(feature["geometry"]["coordinates"][0].as_array().unwrap().len(), 100_001);
+    assert_eq!(feature["properties"]["geometry_simplified"], false);
+    assert!(feature["properties"].get("dataset_id").is_none());
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+#[tokio::test]
+async fn test_map_is_rendered_offline() {
+    let service = setup().await;
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let png = service.to_map_png(&result, MapOptions::default()).await.unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+
+    // Nothing to plot still gives an image
+    let empty = QueryResult::new("", Vec::new(), 0);
+    let png = service.to_map_png(&empty, MapOptions::default()).await.unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_unreachable_basemap_still_renders() {
+    let service = setup().await.with_basemap(
+        Basemap::new("http://127.0.0.1:9/{z}/{x}/{y}.png").with_attribution("Test tiles"),
+    );
+    let result = service.execute(&plan(), embedder()).await.unwrap();
+
+    let png = service.to_map_png(&result, MapOptions::new(256, 256).unwrap()).await.unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_invalid_plans_are_rejected_before_execution() {
+    let service = setup().await;
+
+    let err = service.execute(&plan().with_min_score(1.5), embedder()).await.unwrap_err();
+    match err {
+        ServiceError::InvalidQuery(message) => {
+            assert_eq!(message, "min_score must be between 0.0 and 1.0")
+        }
+        other => panic!("expected InvalidQuery, got {:?}", other),
+    }
+}
@@ -0,0 +1,343 @@
+//! Integration tests for datasets added from http(s) URLs
+//!
+//! A small HTTP server on a local port stands in for the remote host. It
+//! drops a connection mid-body to check the download resumes with a range
+//! request, serves a file under a URL without an extension, and answers
+//! with redirect loops, errors and oversized files. Being on a loopback
+//! address, it is reached under a policy allowing private addresses, except
+//! to check the default policy refuses it.
+
+use georag_core::formats::FormatRegistry;
+use georag_service::remote::fetch;
+use georag_service::{DownloadPolicy, IngestService, ServiceError};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const PARKS: &str = r#"{
+  "type": "FeatureCollection",
+  "features": [
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+      "properties": { "name": "Pocket Park" }
+    },
+    {
+      "type": "Feature",
+      "geometry": { "type": "Point", "coordinates": [106.9, -6.3] },
+      "properties": { "name": "Taman Suropati" }
+    }
+  ]
+}"#;
+
+const LAST_MODIFIED: &str = "Tue, 01 Oct 2024 08:00:00 GMT";
+
+/// A request as the server saw it
+#[derive(Debug, Clone)]
+struct Request {
+    path: String,
+    range: Option<String>,
+    if_range: Option<String>,
+}
+
+/// A response; with `cut_at` the connection closes after that many body bytes
+struct Reply {
+    status: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    cut_at: Option<usize>,
+}
+
+impl Reply {
+    fn new(status: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            cut_at: None,
+        }
+    }
+
+    fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+type Handler = dyn Fn(&Request) -> Reply + Send + Sync;
+
+/// Serve `handler` on a local port, returning the base URL and the requests seen
+async fn serve(
+    handler: impl Fn(&Request) -> Reply + Send + Sync + 'static,
+) -> (String, Arc<Mutex<Vec<Request>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let handler: Arc<Handler> = Arc::new(handler);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let log = seen.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let handler = handler.clone();
+            let log = log.clone();
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let head = String::from_utf8_lossy(&head).into_owned();
+                let mut lines = head.lines();
+                let path = lines.next().unwrap().split(' ').nth(1).unwrap().to_string();
+                let header = |name: &str| {
+                    head.lines().skip(1).find_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        key.eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+                    })
+                };
+                let request = Request {
+                    path,
+                    range: header("range"),
+                    if_range: header("if-range"),
+                };
+                log.lock().unwrap().push(request.clone());
+
+                let reply = handler(&request);
+                let mut response = format!(
+                    "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n",
+                    reply.status,
+                    reply.body.len()
+                );
+                for (name, value) in &reply.headers {
+                    response.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                response.push_str("\r\n");
+
+                let body = &reply.body[..reply.cut_at.unwrap_or(reply.body.len())];
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.write_all(body).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+
+    (base, seen)
+}
+
+fn policy() -> DownloadPolicy {
+    DownloadPolicy::default()
+        .with_retries(2, Duration::from_millis(10))
+        .with_private_addresses(true)
+}
+
+#[tokio::test]
+async fn test_dropped_download_resumes_and_is_ingested_with_its_url() {
+    let requests = AtomicUsize::new(0);
+    let (base, seen) = serve(move |request| {
+        let body = PARKS.as_bytes();
+        let half = body.len() / 2;
+        match (requests.fetch_add(1, Ordering::SeqCst), &request.range) {
+            // The first response breaks off halfway through the body
+            (0, None) => {
+                let mut reply = Reply::new("200 OK", body)
+                    .header("Content-Type", "application/octet-stream")
+                    .header("ETag", "\"parks-v1\"")
+                    .header("Last-Modified", LAST_MODIFIED);
+                reply.cut_at = Some(half);
+                reply
+            }
+            (_, Some(range)) => {
+                let start: usize = range["bytes=".len()..].trim_end_matches('-').parse().unwrap();
+                Reply::new("206 Partial Content", &body[start..])
+                    .header("Content-Type", "application/octet-stream")
+                    .header("ETag", "\"parks-v1\"")
+                    .header(
+                        "Content-Range",
+                        format!("bytes {}-{}/{}", start, body.len() - 1, body.len()),
+                    )
+            }
+            _ => Reply::new("500 Internal Server Error", "unexpected request"),
+        }
+    })
+    .await;
+
+    // No extension in the URL: the content says GeoJSON
+    let url = format!("{}/exports/parks", base);
+    let formats = FormatRegistry::with_defaults();
+    let download = fetch(&url, &formats, &policy()).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&download.path).unwrap(), PARKS);
+    assert_eq!(download.file_name(), "parks.geojson");
+
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[1].range.as_deref(), Some(&*format!("bytes={}-", PARKS.len() / 2)));
+    assert_eq!(seen[1].if_range.as_deref(), Some("\"parks-v1\""));
+
+    let store = Arc::new(MemorySpatialStore::new());
+    let service = IngestService::new(store.clone(), Arc::new(formats));
+    let report = service.ingest(&download.request()).await.unwrap();
+    assert_eq!(report.dataset.name, "parks");
+    assert_eq!(report.features_stored, 2);
+    assert_eq!(report.dataset.path, PathBuf::from(&url));
+
+    let stored = store.get_dataset(report.dataset_id).await.unwrap().unwrap();
+    assert_eq!(stored.path, PathBuf::from(&url));
+    let remote = stored.format.remote.unwrap();
+    assert_eq!(remote.url, url);
+    assert_eq!(remote.etag.as_deref(), Some("\"parks-v1\""));
+    assert_eq!(remote.last_modified.as_deref(), Some(LAST_MODIFIED));
+    assert_eq!(remote.size, PARKS.len() as u64);
+    assert_eq!(remote.final_url, None);
+
+    // The temporary copy goes away with the download
+    let path = download.path.clone();
+    drop(download);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_changed_file_is_fetched_again_from_the_start() {
+    let requests = AtomicUsize::new(0);
+    let (base, _) = serve(move |_| {
+        let version = requests.fetch_add(1, Ordering::SeqCst);
+        let body = PARKS.replace("Pocket Park", &format!("Pocket Park v{}", version));
+        let mut reply = Reply::new("200 OK", body)
+            .header("Content-Type", "application/geo+json")
+            .header("ETag", format!("\"v{}\"", version));
+        if version == 0 {
+            reply.cut_at = Some(40);
+        }
+        reply
+    })
+    .await;
+
+    // The server ignores the stale If-Range and sends the whole new file
+    let download = fetch(&format!("{}/parks", base), &FormatRegistry::with_defaults(), &policy())
+        .await
+        .unwrap();
+    let content = std::fs::read_to_string(&download.path).unwrap();
+    assert!(content.starts_with("{\n  \"type\""));
+    assert!(content.contains("Pocket Park v1"));
+    assert_eq!(download.source.etag.as_deref(), Some("\"v1\""));
+    assert_eq!(download.file_name(), "parks.geojson");
+}
+
+#[tokio::test]
+async fn test_error_status_names_the_final_url() {
+    let (base, _) = serve(|request| match request.path.as_str() {
+        "/data/parks.geojson" => {
+            Reply::new("301 Moved Permanently", "").header("Location", "/archive/parks.geojson")
+        }
+        _ => Reply::new("404 Not Found", "gone"),
+    })
+    .await;
+
+    let url = format!("{}/data/parks.geojson", base);
+    let err = fetch(&url, &FormatRegistry::with_defaults(), &policy()).await.unwrap_err();
+    assert!(matches!(err, ServiceError::Download { .. }), "{:?}", err);
+    let message = err.to_string();
+    assert!(message.contains("404"), "{}", message);
+    assert!(message.contains(&format!("{}/archive/parks.geojson", base)), "{}", message);
+}
+
+#[tokio::test]
+async fn test_redirect_chains_are_bounded() {
+    let (base, seen) = serve(|request| {
+        let hop: usize = request.path.trim_start_matches("/hop/").parse().unwrap();
+        Reply::new("302 Found", "").header("Location", format!("/hop/{}", hop + 1))
+    })
+    .await;
+
+    let policy = policy().with_max_redirects(3);
+    let err = fetch(&format!("{}/hop/0", base), &FormatRegistry::with_defaults(), &policy)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("more than 3 redirects"), "{}", err);
+
+    // A redirect loop is not retried
+    assert_eq!(seen.lock().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_downloads_over_the_size_limit_are_refused() {
+    let (base, _) = serve(|_| Reply::new("200 OK", PARKS)).await;
+
+    let policy = policy().with_max_bytes(64);
+    let err = fetch(&format!("{}/parks.geojson", base), &FormatRegistry::with_defaults(), &policy)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ServiceError::DownloadTooLarge { limit: 64, .. }), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_transient_failures_give_up_after_the_retries() {
+    let (base, seen) = serve(|_| Reply::new("503 Service Unavailable", "busy")).await;
+
+    let err =
+        fetch(&format!("{}/parks.geojson", base), &FormatRegistry::with_defaults(), &policy())
+            .await
+            .unwrap_err();
+    assert!(err.to_string().contains("503"), "{}", err);
+    assert_eq!(seen.lock().unwrap().len(), 3);
+
+    let err = fetch("ftp://example.com/parks.geojson", &FormatRegistry::with_defaults(), &policy())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidUrl(_)));
+}
+
+#[tokio::test]
+async fn test_private_addresses_are_refused_by_default() {
+    let (base, seen) = serve(|_| Reply::new("200 OK", PARKS)).await;
+    let port = base.rsplit(':').next().unwrap();
+    let formats = FormatRegistry::with_defaults();
+
+    for url in [
+        format!("{}/parks.geojson", base),
+        format!("http://localhost:{}/parks.geojson", port),
+    ] {
+        let err = fetch(&url, &formats, &DownloadPolicy::default()).await.unwrap_err();
+        assert!(matches!(err, ServiceError::DownloadRefused { .. }), "{:?}", err);
+    }
+    assert!(seen.lock().unwrap().is_empty());
+
+    // Hosts outside the allowed ones are refused even when private addresses are not
+    let listed = policy().with_allowed_hosts(Some(vec!["data.example.com".to_string()]));
+    let err = fetch(&format!("{}/parks.geojson", base), &formats, &listed).await.unwrap_err();
+    assert!(err.to_string().contains("not an allowed host"), "{}", err);
+    assert!(seen.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_redirects_to_hosts_outside_the_allowed_ones_are_refused() {
+    let base = Arc::new(OnceLock::<String>::new());
+    let target = base.clone();
+    let (served, seen) = serve(move |request| match request.path.as_str() {
+        "/parks.geojson" => Reply::new("302 Found", "")
+            .header("Location", format!("{}/internal", target.get().unwrap())),
+        _ => Reply::new("200 OK", PARKS),
+    })
+    .await;
+    base.set(served.clone()).unwrap();
+
+    // Reached as localhost, then redirected to the 127.0.0.1 it resolves to
+    let port = served.rsplit(':').next().unwrap();
+    let listed = policy().with_allowed_hosts(Some(vec!["localhost".to_string()]));
+    let url = format!("http://localhost:{}/parks.geojson", port);
+    let err = fetch(&url, &FormatRegistry::with_defaults(), &listed).await.unwrap_err();
+    assert!(matches!(err, ServiceError::DownloadRefused { .. }), "{:?}", err);
+    assert!(err.to_string().contains("127.0.0.1 is not an allowed host"), "{}", err);
+
+    let paths: Vec<String> = seen.lock().unwrap().iter().map(|r| r.path.clone()).collect();
+    assert_eq!(paths, ["/parks.geojson"]);
+}
@@ -0,0 +1,172 @@
+//! Integration tests for second-pass reranking
+//!
+//! Three indexed chunks are embedded over a vocabulary that does not know
+//! the word "fountain", so embedding similarity ranks the passage about the
+//! fountain last. BM25 over the pool sees the word and moves it first.
+
+mod common;
+
+use async_trait::async_trait;
+use common::{chunk, KeywordEmbedder, Stores};
+use georag_core::error::Result;
+use georag_core::models::{Feature, FeatureId, Geometry, TextChunk};
+use georag_retrieval::{QueryPlan, QueryResult, RerankCandidate, RerankMode, Reranker};
+use georag_service::ServiceError;
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const VOCABULARY: [&str; 2] = ["park", "bench"];
+
+fn embedder() -> KeywordEmbedder {
+    KeywordEmbedder::new(&VOCABULARY)
+}
+
+/// Reranker reversing the first-pass order
+struct ReverseReranker;
+
+#[async_trait]
+impl Reranker for ReverseReranker {
+    fn name(&self) -> &str {
+        "reverse"
+    }
+
+    async fn rerank(&self, _query: &str, candidates: Vec<RerankCandidate>) -> Result<Vec<f32>> {
+        let count = candidates.len() as f32;
+        Ok((0..candidates.len()).map(|index| (index + 1) as f32 / count).collect())
+    }
+}
+
+/// Chunk texts, ranked 3, 1, 2 by embedding similarity to "fountain bench"
+const TEXTS: [(u64, &str); 3] = [
+    (1, "park park bench"),
+    (2, "fountain near the park"),
+    (3, "bench bench bench park"),
+];
+
+impl Stores {
+    async fn query(&self, plan: &QueryPlan) -> QueryResult {
+        self.service().execute(plan, embedder()).await.unwrap()
+    }
+}
+
+async fn setup() -> Stores {
+    let stores = Stores::new();
+
+    for (id, content) in TEXTS {
+        let mut properties = HashMap::new();
+        properties.insert("content".to_string(), json!(content));
+        let feature = Feature::with_geometry(
+            FeatureId(id),
+            Geometry::point(id as f64, 0.0),
+            properties,
+            4326,
+        );
+        stores.spatial.store_features(&[feature]).await.unwrap();
+    }
+    let chunks: Vec<TextChunk> = TEXTS
+        .into_iter()
+        .map(|(id, content)| chunk(id, "park", Some(id), content))
+        .collect();
+    stores.index(&chunks, &embedder()).await;
+
+    stores
+}
+
+fn excerpts(result: &QueryResult) -> Vec<&str> {
+    result.sources.iter().map(|s| s.excerpt.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_lexical_rerank_promotes_passage_with_query_terms() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("fountain bench").with_top_k(1).with_explain(true);
+
+    let first_pass = stores.query(&plan).await;
+    assert_eq!(excerpts(&first_pass), vec!["bench bench bench park"]);
+    assert!(first_pass.explanation.unwrap().rerank_phase.is_none());
+
+    let reranked = stores.query(&plan.with_rerank(RerankMode::Lexical)).await;
+    assert_eq!(excerpts(&reranked), vec!["fountain near the park"]);
+
+    let explanation = reranked.explanation.unwrap();
+    let phase = explanation.rerank_phase.unwrap();
+    assert_eq!(phase.reranker, "bm25");
+    assert_eq!(phase.candidates_reranked, 3);
+    assert!(explanation.timings.rerank.is_some());
+
+    let detail = &explanation.ranking_details[0];
+    assert_eq!(detail.rank, 1);
+    assert_eq!(detail.pre_rerank_rank, Some(3));
+    assert_eq!(detail.semantic_score, Some(0.0));
+    assert_eq!(detail.final_score, 1.0);
+}
+
+#[tokio::test]
+async fn test_pool_limits_the_candidates_reranked() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("fountain bench")
+        .with_top_k(1)
+        .with_rerank(RerankMode::Lexical)
+        .with_rerank_pool(2)
+        .with_explain(true);
+
+    let result = stores.query(&plan).await;
+
+    assert_eq!(excerpts(&result), vec!["bench bench bench park"]);
+    assert_eq!(result.explanation.unwrap().rerank_phase.unwrap().candidates_reranked, 2);
+}
+
+#[tokio::test]
+async fn test_llm_rerank_uses_the_configured_reranker() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("fountain bench").with_rerank(RerankMode::Llm).with_explain(true);
+
+    let result = stores
+        .service()
+        .with_llm_reranker(Arc::new(ReverseReranker))
+        .execute(&plan, embedder())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        excerpts(&result),
+        vec!["fountain near the park", "park park bench", "bench bench bench park"]
+    );
+    let explanation = result.explanation.unwrap();
+    assert_eq!(explanation.rerank_phase.unwrap().reranker, "reverse");
+    let ranks: Vec<(usize, Option<usize>)> = explanation
+        .ranking_details
+        .iter()
+        .map(|d| (d.rank, d.pre_rerank_rank))
+        .collect();
+    assert_eq!(ranks, vec![(1, Some(3)), (2, Some(2)), (3, Some(1))]);
+}
+
+#[tokio::test]
+async fn test_llm_rerank_without_a_reranker_fails() {
+    let stores = setup().await;
+    let plan = QueryPlan::new("fountain bench").with_rerank(RerankMode::Llm);
+
+    let error = stores.service().execute(&plan, embedder()).await.unwrap_err();
+
+    assert!(error.to_string().contains("rerank.model"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_rerank_pool_is_validated() {
+    let stores = setup().await;
+
+    for pool in [0, 501] {
+        let plan = QueryPlan::new("fountain bench")
+            .with_rerank(RerankMode::Lexical)
+            .with_rerank_pool(pool);
+        let result = stores.service().execute(&plan, embedder()).await;
+        assert!(matches!(result, Err(ServiceError::InvalidQuery(_))), "pool {}", pool);
+    }
+
+    // The pool is ignored without reranking
+    let plan = QueryPlan::new("fountain bench").with_rerank_pool(0);
+    assert_eq!(stores.query(&plan).await.sources.len(), 3);
+}
@@ -0,0 +1,172 @@
+//! Integration tests for inferred dataset schemas
+//!
+//! The Markdown data dictionary of a small fixture workspace is compared with
+//! a snapshot in `tests/snapshots`; update the snapshot when the rendering
+//! changes on purpose.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    data_dictionary, ChunkingSettings, Dataset, DatasetId, Feature, FeatureId, Geometry,
+    GeometryType,
+};
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_core::redaction::{RedactionConfig, Redactor};
+use georag_service::{SchemaService, ServiceError};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const DATA_DICTIONARY: &str = include_str!("snapshots/data_dictionary.md");
+
+fn dataset(name: &str, format: &str, geometry_type: GeometryType) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}", name)),
+        geometry_type,
+        feature_count: 0,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: format.to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn feature(id: u64, geometry: Option<Geometry>, properties: Value) -> Feature {
+    let properties: HashMap<String, Value> = serde_json::from_value(properties).unwrap();
+    match geometry {
+        Some(geometry) => Feature::with_geometry(FeatureId(id), geometry, properties, 4326),
+        None => Feature::without_geometry(FeatureId(id), properties, 4326),
+    }
+}
+
+async fn add(store: &MemorySpatialStore, dataset: Dataset, features: Vec<Feature>) -> DatasetId {
+    let id = store.store_dataset(&dataset).await.unwrap();
+    store.store_features(&features).await.unwrap();
+    store.associate_features_with_dataset(id, features.iter().map(|f| f.id).collect());
+    id
+}
+
+/// Harbour cranes with mixed value types, and a document without geometry
+async fn fixture() -> (Arc<MemorySpatialStore>, DatasetId, DatasetId) {
+    let store = Arc::new(MemorySpatialStore::new());
+    let cranes = add(
+        &store,
+        dataset("harbour_cranes", "GeoJSON", GeometryType::Mixed),
+        vec![
+            feature(
+                1,
+                Some(Geometry::point(106.80, -6.10)),
+                json!({ "name": "Crane A", "capacity_t": 40, "operator": "Pelindo" }),
+            ),
+            feature(
+                2,
+                Some(Geometry::point(106.81, -6.10)),
+                json!({ "name": "Crane B", "capacity_t": 62.5, "operator": null }),
+            ),
+            feature(
+                3,
+                Some(Geometry::line_string(vec![[106.80, -6.11], [106.82, -6.11]])),
+                json!({ "name": "Crane | C", "capacity_t": 40, "_source_row": 3 }),
+            ),
+        ],
+    )
+    .await;
+    let notes = add(
+        &store,
+        dataset("survey_notes", "PDF", GeometryType::Point),
+        vec![
+            feature(
+                10,
+                None,
+                json!({
+                    "content": "Tide gauge readings along the eastern breakwater were taken every hour",
+                    "page": 1
+                }),
+            ),
+            feature(11, None, json!({ "content": "Short note", "page": 2 })),
+        ],
+    )
+    .await;
+    (store, cranes, notes)
+}
+
+fn service(store: Arc<MemorySpatialStore>) -> SchemaService {
+    let generator = ChunkGenerator::default().with_properties(["name"]);
+    SchemaService::new(store).with_chunking(ChunkingSettings::from(&generator))
+}
+
+#[tokio::test]
+async fn test_data_dictionary_matches_snapshot() {
+    let (store, _, _) = fixture().await;
+    let mut datasets = store.list_datasets().await.unwrap();
+    datasets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let schemas = service(store).schemas(&datasets).await.unwrap();
+    assert_eq!(data_dictionary(&schemas), DATA_DICTIONARY);
+}
+
+#[tokio::test]
+async fn test_json_schema_lists_types_and_required_keys() {
+    let (store, cranes, _) = fixture().await;
+    let schema = service(store).dataset_schema(cranes).await.unwrap().to_json_schema();
+
+    let properties = &schema["properties"]["properties"];
+    assert_eq!(properties["required"], json!(["capacity_t", "name"]));
+    // Integers and decimals of one key are numbers
+    assert_eq!(properties["properties"]["capacity_t"]["type"], "number");
+    assert_eq!(properties["properties"]["operator"]["type"], json!(["string", "null"]));
+    assert_eq!(properties["properties"]["operator"]["examples"], json!(["Pelindo"]));
+    // Internal keys are not part of the schema
+    assert!(properties["properties"].get("_source_row").is_none());
+
+    assert_eq!(
+        schema["properties"]["geometry"]["properties"]["type"]["enum"],
+        json!(["Point", "LineString"])
+    );
+    assert_eq!(schema["x-georag"]["crs"], 4326);
+    assert_eq!(schema["x-georag"]["chunking"]["chunk_properties"], json!(["name"]));
+}
+
+#[tokio::test]
+async fn test_redacted_properties_keep_their_type() {
+    let (store, cranes, _) = fixture().await;
+    let rules = RedactionConfig {
+        properties: vec!["operator".to_string()],
+        ..Default::default()
+    };
+    let schema = service(store)
+        .with_redactor(Redactor::new(&rules).unwrap())
+        .dataset_schema(cranes)
+        .await
+        .unwrap();
+
+    let operator = schema.properties.iter().find(|p| p.key == "operator").unwrap();
+    assert_eq!(operator.examples, vec![json!("[REDACTED]")]);
+    assert_eq!(operator.present, 2);
+}
+
+#[tokio::test]
+async fn test_unknown_dataset_is_not_found() {
+    let (store, _, _) = fixture().await;
+    let result = service(store).dataset_schema(DatasetId(999)).await;
+    assert!(matches!(result, Err(ServiceError::DatasetNotFound { .. })));
+}
@@ -0,0 +1,127 @@
+//! Integration tests for source URLs in query results
+//!
+//! Two GeoJSON files whose names hold spaces and non-ASCII letters are
+//! ingested. With a source URL template every source links to its document,
+//! each part encoded for the URL; without one the field is left out.
+
+mod common;
+
+use common::Stores;
+use georag_core::config::parse_source_url_template;
+use georag_core::error::Result;
+use georag_core::formats::FormatRegistry;
+use georag_core::llm::Embedder;
+use georag_core::models::Feature;
+use georag_core::processing::chunk::ChunkGenerator;
+use georag_retrieval::{QueryPlan, QueryResult};
+use georag_service::{IngestRequest, IngestService, QueryService};
+use serde_json::json;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// Embeds every text the same, so each chunk matches every query
+struct ConstantEmbedder;
+
+impl Embedder for ConstantEmbedder {
+    fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|_| vec![1.0, 0.0]).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn model_name(&self) -> &str {
+        "constant"
+    }
+}
+
+impl Stores {
+    /// Ingest one GeoJSON point per file, then chunk and index the features
+    async fn setup(dir: &TempDir, files: &[(&str, &str)]) -> Self {
+        let stores = Self::new();
+
+        for (file_name, content) in files {
+            let path = dir.path().join(file_name);
+            let document = json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [106.8, -6.2] },
+                    "properties": { "content": content }
+                }]
+            });
+            std::fs::write(&path, document.to_string()).unwrap();
+
+            let service = IngestService::new(
+                stores.spatial.clone(),
+                Arc::new(FormatRegistry::with_defaults()),
+            );
+            let prepared = service.prepare(&IngestRequest::new(&path)).await.unwrap();
+            let features: Vec<Feature> = prepared.features.clone();
+            let report = service.commit(prepared).await.unwrap();
+
+            let chunks = ChunkGenerator::default().generate_chunks(&report.dataset, &features);
+            stores.index(&chunks, &ConstantEmbedder).await;
+        }
+
+        stores
+    }
+
+    async fn query(&self, service: QueryService) -> QueryResult {
+        service.execute(&QueryPlan::new("harbour"), ConstantEmbedder).await.unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_source_urls_encode_spaces_and_non_ascii_names() {
+    let dir = TempDir::new().unwrap();
+    let stores = Stores::setup(
+        &dir,
+        &[
+            ("Port Report 2023.geojson", "Harbour works in the north"),
+            ("Pelabuhan Tanjung Priok — café.geojson", "Harbour café by the quay"),
+        ],
+    )
+    .await;
+
+    let template =
+        parse_source_url_template("https://docs.example.com/{dataset}/{document_path}?at={offset}")
+            .unwrap();
+    let service = stores.service().with_source_url_template(template);
+    let result = stores.query(service.clone()).await;
+    assert_eq!(result.sources.len(), 2);
+
+    let mut urls: Vec<String> =
+        result.sources.iter().map(|s| s.source_url.clone().unwrap()).collect();
+    urls.sort();
+    assert_eq!(
+        urls,
+        vec![
+            "https://docs.example.com/pelabuhan-tanjung-priok-caf%C3%A9/\
+             Pelabuhan%20Tanjung%20Priok%20%E2%80%94%20caf%C3%A9.geojson?at=0",
+            "https://docs.example.com/port-report-2023/Port%20Report%202023.geojson?at=0",
+        ]
+    );
+
+    // GeoJSON results carry the same links
+    let collection = service.to_geojson(&result).await;
+    for (feature, source) in collection["features"].as_array().unwrap().iter().zip(&result.sources)
+    {
+        assert_eq!(feature["properties"]["source_url"], json!(source.source_url));
+    }
+}
+
+#[tokio::test]
+async fn test_source_url_is_omitted_without_template() {
+    let dir = TempDir::new().unwrap();
+    let stores = Stores::setup(&dir, &[("Port Report 2023.geojson", "Harbour works")]).await;
+
+    let result = stores.query(stores.service()).await;
+    assert_eq!(result.sources.len(), 1);
+    assert!(result.sources[0].source_url.is_none());
+
+    let collection = stores.service().to_geojson(&result).await;
+    assert!(collection["features"][0]["properties"].get("source_url").is_none());
+    assert!(!serde_json::to_string(&result).unwrap().contains("source_url"));
+}
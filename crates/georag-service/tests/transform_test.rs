@@ -0,0 +1,173 @@
+//! Integration tests for workspace feature transforms at ingest
+//!
+//! A chain of transforms cleans up parcel IDs, derives a label, renames and
+//! drops properties and records each parcel's centroid. One parcel lacks a
+//! property the label needs, so the template transform fails on it alone.
+
+use georag_core::config::WorkspaceSettings;
+use georag_core::formats::{FeatureErrorKind, FeatureTransform, FormatRegistry, ReadPolicy};
+use georag_core::models::FeatureId;
+use georag_service::{IngestRequest, IngestService, ServiceError};
+use georag_store::memory::MemorySpatialStore;
+use georag_store::ports::SpatialStore;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const TRANSFORMS: &str = r#"
+[[feature_transforms]]
+type = "regex_replace"
+key = "parcel"
+pattern = "^PRC-0*"
+replacement = ""
+
+[[feature_transforms]]
+type = "template"
+key = "label"
+template = "{zone}-{class}"
+
+[[feature_transforms]]
+type = "rename"
+from = "zone"
+to = "district"
+
+[[feature_transforms]]
+type = "drop_key"
+key = "class"
+
+[[feature_transforms]]
+type = "compute_centroid"
+key = "centroid"
+"#;
+
+/// The transforms as the workspace settings parse them
+fn transforms() -> Vec<FeatureTransform> {
+    WorkspaceSettings::from_toml(TRANSFORMS).unwrap().feature_transforms.unwrap()
+}
+
+/// Four parcels; the third has no `class`
+fn write_parcels(dir: &TempDir) -> PathBuf {
+    let parcel = |x: f64, properties: serde_json::Value| {
+        json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[x, 0.0], [x + 2.0, 0.0], [x + 2.0, 2.0], [x, 2.0], [x, 0.0]]]
+            },
+            "properties": properties
+        })
+    };
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": [
+            parcel(0.0, json!({ "parcel": "PRC-0017", "zone": "R1", "class": "house" })),
+            parcel(10.0, json!({ "parcel": "PRC-0204", "zone": "C2", "class": "shop" })),
+            parcel(20.0, json!({ "parcel": "PRC-0311", "zone": "R1" })),
+            parcel(30.0, json!({ "parcel": "PRC-1000", "zone": "I1", "class": "depot" })),
+        ]
+    });
+
+    let path = dir.path().join("parcels.geojson");
+    std::fs::write(&path, collection.to_string()).unwrap();
+    path
+}
+
+fn service(store: &Arc<MemorySpatialStore>) -> IngestService {
+    IngestService::new(store.clone(), Arc::new(FormatRegistry::with_defaults()))
+}
+
+#[tokio::test]
+async fn test_lenient_ingest_skips_only_the_feature_a_transform_fails_on() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+    let request = IngestRequest::new(write_parcels(&dir))
+        .with_read_policy(ReadPolicy::lenient(10))
+        .with_transforms(transforms());
+
+    let report = service(&store).ingest(&request).await.unwrap();
+
+    assert_eq!(report.features_stored, 3);
+    assert_eq!(report.dataset.feature_count, 3);
+    assert_eq!(report.feature_errors.len(), 1);
+    let error = &report.feature_errors[0];
+    assert_eq!(error.index, 2);
+    assert_eq!(error.kind, FeatureErrorKind::TransformFailed);
+    assert_eq!(error.message, "transform 1 (template): property 'class' is missing");
+
+    let counts: Vec<usize> = report.transform_errors.iter().map(|t| t.errors).collect();
+    assert_eq!(counts, [0, 1, 0, 0, 0]);
+    assert_eq!(report.transform_errors[1].transform, "template label = \"{zone}-{class}\"");
+
+    let first = store.get_feature(FeatureId(0)).await.unwrap().unwrap();
+    assert_eq!(
+        serde_json::to_value(&first.properties).unwrap(),
+        json!({ "parcel": "17", "district": "R1", "label": "R1-house", "centroid": [1.0, 1.0] })
+    );
+    let last = store.get_feature(FeatureId(3)).await.unwrap().unwrap();
+    assert_eq!(last.properties["parcel"], json!("1000"));
+    assert_eq!(last.properties["label"], json!("I1-depot"));
+    assert!(store.get_feature(FeatureId(2)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_strict_ingest_fails_on_the_first_transform_failure() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+    let request = IngestRequest::new(write_parcels(&dir))
+        .with_read_policy(ReadPolicy::strict())
+        .with_transforms(transforms());
+
+    let err = service(&store).ingest(&request).await.unwrap_err();
+    let ServiceError::Read(error) = err else {
+        panic!("expected a read error, got {:?}", err);
+    };
+    assert!(error.to_string().contains("property 'class' is missing"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_prepare_previews_features_before_and_after() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+    let request = IngestRequest::new(write_parcels(&dir))
+        .with_read_policy(ReadPolicy::lenient(10))
+        .with_transforms(transforms());
+
+    let prepared = service(&store).prepare(&request).await.unwrap();
+
+    // The dry run counts the same failures as the streaming ingest
+    assert_eq!(prepared.features.len(), 3);
+    let counts: Vec<usize> = prepared.transform_errors.iter().map(|t| t.errors).collect();
+    assert_eq!(counts, [0, 1, 0, 0, 0]);
+
+    let preview = &prepared.transform_preview;
+    assert_eq!(preview.iter().map(|p| p.index).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(preview[0].before["parcel"], json!("PRC-0017"));
+    assert_eq!(preview[0].before["class"], json!("house"));
+    let after = preview[0].after.as_ref().unwrap();
+    assert_eq!(after["parcel"], json!("17"));
+    assert_eq!(after["label"], json!("R1-house"));
+    assert!(!after.contains_key("class"));
+    assert!(preview[0].error.is_none());
+
+    assert!(preview[2].after.is_none());
+    assert_eq!(
+        preview[2].error.as_deref(),
+        Some("transform 1 (template): property 'class' is missing")
+    );
+}
+
+#[tokio::test]
+async fn test_ingest_without_transforms_reports_none() {
+    let dir = TempDir::new().unwrap();
+    let store = Arc::new(MemorySpatialStore::new());
+    let request = IngestRequest::new(write_parcels(&dir));
+
+    let prepared = service(&store).prepare(&request).await.unwrap();
+    assert!(prepared.transform_errors.is_empty());
+    assert!(prepared.transform_preview.is_empty());
+
+    let report = service(&store).ingest(&request).await.unwrap();
+    assert_eq!(report.features_stored, 4);
+    assert!(report.transform_errors.is_empty());
+}
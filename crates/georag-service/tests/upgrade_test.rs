@@ -0,0 +1,115 @@
+//! Integration tests for upgrading an index to the current schema version
+
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, DatasetId, DatasetIndexStats, Embedding, IndexState,
+    TextChunk, INDEX_SCHEMA_VERSION, UNVERSIONED_INDEX_SCHEMA,
+};
+use georag_service::{IndexStatsService, UpgradeService, UPGRADE_STEPS};
+use georag_store::memory::{MemoryDocumentStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Index state as builds wrote it before schema versions were recorded
+const UNVERSIONED_STATE: &str = r#"{
+    "hash": "4f1c2a9e8b7d6c5a",
+    "built_at": "2025-03-14T09:26:53Z",
+    "embedder": "ollama:nomic-embed-text",
+    "chunk_count": 2,
+    "embedding_dim": 2
+}"#;
+
+fn chunk(id: u64, content: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: "/data/harbour.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 1,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: Some(DatasetId(1)),
+        },
+    }
+}
+
+async fn stores() -> (Arc<MemoryDocumentStore>, Arc<MemoryVectorStore>) {
+    let documents = Arc::new(MemoryDocumentStore::new());
+    let vectors = Arc::new(MemoryVectorStore::new());
+    documents.store_chunks(&[chunk(1, "crane"), chunk(2, "quay")]).await.unwrap();
+    vectors
+        .store_embeddings(&[Embedding {
+            chunk_id: ChunkId(1),
+            vector: vec![1.0, 0.0],
+            spatial_metadata: None,
+            dataset_id: Some(DatasetId(1)),
+        }])
+        .await
+        .unwrap();
+    (documents, vectors)
+}
+
+#[tokio::test]
+async fn test_unversioned_index_is_upgraded_step_by_step() {
+    let mut state: IndexState = serde_json::from_str(UNVERSIONED_STATE).unwrap();
+    assert_eq!(state.schema_version, UNVERSIONED_INDEX_SCHEMA);
+    assert!(state.check_schema_version().is_err());
+
+    let (documents, vectors) = stores().await;
+    let service = UpgradeService::new(documents.clone(), vectors.clone());
+    let mut ran = Vec::new();
+    let report = service.upgrade(&mut state, |step| ran.push(step.from)).await.unwrap();
+
+    assert_eq!(report.from, UNVERSIONED_INDEX_SCHEMA);
+    assert_eq!(report.to, INDEX_SCHEMA_VERSION);
+    assert_eq!(report.steps, UPGRADE_STEPS);
+    assert_eq!(ran, UPGRADE_STEPS.iter().map(|step| step.from).collect::<Vec<_>>());
+    assert_eq!(state.schema_version, INDEX_SCHEMA_VERSION);
+    state.check_schema_version().unwrap();
+    assert_eq!(state.hash, "4f1c2a9e8b7d6c5a");
+
+    // The counters were rebuilt from the stored chunks and embeddings
+    let stats = IndexStatsService::new(documents, vectors).stats().await.unwrap();
+    assert_eq!(
+        stats[&DatasetId(1)],
+        DatasetIndexStats {
+            chunks: 2,
+            content_bytes: 9,
+            embeddings: 1
+        }
+    );
+
+    // Upgrading the upgraded state again runs nothing
+    let report = service.upgrade(&mut state, |_| panic!("no step should run")).await.unwrap();
+    assert!(report.steps.is_empty());
+    assert_eq!(report.to, INDEX_SCHEMA_VERSION);
+}
+
+#[tokio::test]
+async fn test_newer_index_is_refused_untouched() {
+    let mut state: IndexState = serde_json::from_str(UNVERSIONED_STATE).unwrap();
+    state.schema_version = INDEX_SCHEMA_VERSION + 1;
+
+    let (documents, vectors) = stores().await;
+    let service = UpgradeService::new(documents, vectors);
+    let err = service.upgrade(&mut state, |_| panic!("no step should run")).await.unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Index schema version {} is newer than version {}, the newest this build reads. \
+             Upgrade georag to open it",
+            INDEX_SCHEMA_VERSION + 1,
+            INDEX_SCHEMA_VERSION
+        )
+    );
+    assert_eq!(state.schema_version, INDEX_SCHEMA_VERSION + 1);
+}
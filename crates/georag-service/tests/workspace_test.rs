@@ -0,0 +1,222 @@
+//! Integration tests for the workspace service
+
+use chrono::Utc;
+use georag_core::config::WorkspaceSettings;
+use georag_core::models::{
+    DistanceUnit, IndexState, QueryPreset, UsageDelta, ValidityMode, INDEX_SCHEMA_VERSION,
+};
+use georag_service::{ServiceError, WorkspaceService};
+use georag_store::memory::MemoryWorkspaceStore;
+use georag_store::ports::WorkspaceStore;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+fn index(embedder: &str, embedding_dim: usize) -> IndexState {
+    IndexState {
+        schema_version: INDEX_SCHEMA_VERSION,
+        hash: "abc".to_string(),
+        built_at: Utc::now(),
+        embedder: embedder.to_string(),
+        chunk_count: 10,
+        embedding_dim,
+        dataset_built_at: BTreeMap::new(),
+        contents: None,
+        last_build_diff: None,
+        tokenizer: None,
+        score_baseline: None,
+    }
+}
+
+#[tokio::test]
+async fn test_create_stores_settings_with_defaults_filled_in() {
+    let store = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(store.clone());
+
+    let settings = WorkspaceSettings {
+        crs: Some(3857),
+        min_score: Some(0.4),
+        max_datasets: Some(5),
+        ..Default::default()
+    };
+    let view = service.create(" city ", &settings).await.unwrap();
+    assert_eq!(view.meta.name, "city");
+    assert_eq!(view.meta.crs, 3857);
+    assert_eq!(view.settings.distance_unit, Some(DistanceUnit::Meters));
+    assert_eq!(view.settings.geometry_validity, Some(ValidityMode::Lenient));
+    assert_eq!(view.settings.max_datasets, Some(5));
+    assert_eq!(store.get_workspace_settings(view.meta.id).await.unwrap(), Some(view.settings));
+
+    let effective = service.get(view.meta.id).await.unwrap().effective();
+    assert_eq!(effective.crs.value, 3857);
+    assert_eq!(effective.min_score.value, Some(0.4));
+
+    let err = service.create("city", &WorkspaceSettings::default()).await.unwrap_err();
+    assert!(matches!(err, ServiceError::WorkspaceExists { ref name } if name == "city"));
+
+    let err = service.create("  ", &WorkspaceSettings::default()).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidWorkspaceName(_)));
+}
+
+#[tokio::test]
+async fn test_invalid_settings_create_nothing() {
+    let store = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(store.clone());
+
+    for settings in [
+        WorkspaceSettings {
+            min_score: Some(1.5),
+            ..Default::default()
+        },
+        WorkspaceSettings {
+            embedder: Some("mock:0".to_string()),
+            ..Default::default()
+        },
+        WorkspaceSettings {
+            default_radius: Some(-1.0),
+            ..Default::default()
+        },
+    ] {
+        let err = service.create("city", &settings).await.unwrap_err();
+        assert!(matches!(err, ServiceError::InvalidSettings(_)), "{:?}", err);
+    }
+    assert!(store.list_workspaces().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_update_changes_only_the_given_settings() {
+    let store = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(store.clone());
+    let settings = WorkspaceSettings {
+        min_score: Some(0.4),
+        ..Default::default()
+    };
+    let id = service.create("city", &settings).await.unwrap().meta.id;
+
+    // Without data, the CRS can still change
+    let patch = WorkspaceSettings {
+        crs: Some(32748),
+        distance_unit: Some(DistanceUnit::Kilometers),
+        ..Default::default()
+    };
+    let view = service.update(id, &patch, None).await.unwrap();
+    assert_eq!(view.meta.crs, 32748);
+    assert_eq!(view.meta.distance_unit, DistanceUnit::Kilometers);
+    assert_eq!(view.settings.min_score, Some(0.4));
+    assert_eq!(store.get_workspace(id).await.unwrap().unwrap().crs, 32748);
+
+    let patch = WorkspaceSettings {
+        min_score: Some(2.0),
+        ..Default::default()
+    };
+    let err = service.update(id, &patch, None).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidSettings(_)));
+    assert_eq!(service.get(id).await.unwrap().settings.min_score, Some(0.4));
+}
+
+#[tokio::test]
+async fn test_crs_is_locked_once_features_are_stored() {
+    let store = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(store.clone());
+    let id = service.create("city", &WorkspaceSettings::default()).await.unwrap().meta.id;
+    store
+        .adjust_workspace_usage(
+            id,
+            &UsageDelta {
+                datasets: 1,
+                features: 12,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let patch = WorkspaceSettings { crs: Some(3857), ..Default::default() };
+    let err = service.update(id, &patch, None).await.unwrap_err();
+    match err {
+        ServiceError::SettingLocked { key, reason } => {
+            assert_eq!(key, "crs");
+            assert!(reason.contains("12 features in EPSG:4326"), "{}", reason);
+        }
+        other => panic!("expected a locked setting, got {:?}", other),
+    }
+    assert_eq!(store.get_workspace(id).await.unwrap().unwrap().crs, 4326);
+
+    // Replacing the settings is checked the same way
+    let err = service.replace(id, &patch, None).await.unwrap_err();
+    assert!(matches!(err, ServiceError::SettingLocked { .. }));
+
+    // Restating the current CRS, or changing other settings, is fine
+    let patch = WorkspaceSettings {
+        crs: Some(4326),
+        min_score: Some(0.3),
+        ..Default::default()
+    };
+    assert!(service.update(id, &patch, None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_embedder_dimensions_are_locked_once_an_index_exists() {
+    let store = Arc::new(MemoryWorkspaceStore::new());
+    let service = WorkspaceService::new(store.clone());
+    let id = service.create("city", &WorkspaceSettings::default()).await.unwrap().meta.id;
+    let built = index("ollama:nomic-embed-text", 768);
+
+    let patch = WorkspaceSettings {
+        embedder: Some("ollama:mxbai-embed-large".to_string()),
+        ..Default::default()
+    };
+    let err = service.update(id, &patch, Some(&built)).await.unwrap_err();
+    match err {
+        ServiceError::SettingLocked { key, reason } => {
+            assert_eq!(key, "embedder");
+            assert!(reason.contains("1024-dimensional"), "{}", reason);
+        }
+        other => panic!("expected a locked setting, got {:?}", other),
+    }
+
+    // Another embedder with the index's dimensions, or no index at all
+    let patch = WorkspaceSettings {
+        embedder: Some("mock:768".to_string()),
+        ..Default::default()
+    };
+    assert!(service.update(id, &patch, Some(&built)).await.is_ok());
+    let patch = WorkspaceSettings {
+        embedder: Some("mock:32".to_string()),
+        ..Default::default()
+    };
+    assert!(service.update(id, &patch, None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_presets_are_saved_checked_and_deleted() {
+    let service = WorkspaceService::new(Arc::new(MemoryWorkspaceStore::new()));
+    let id = service.create("city", &WorkspaceSettings::default()).await.unwrap().meta.id;
+
+    let planners = QueryPreset {
+        top_k: Some(25),
+        rerank: Some("lexical".to_string()),
+        ..Default::default()
+    };
+    let (name, _) = service.save_preset(id, " Planners ", &planners, None).await.unwrap();
+    assert_eq!(name, "planners");
+    let view = service.set_default_preset(id, Some("planners"), None).await.unwrap();
+    assert_eq!(view.settings.query_presets.unwrap()["planners"], planners);
+    assert_eq!(view.effective().default_query_preset.value.as_deref(), Some("planners"));
+
+    // Options are checked as in a query request, and nothing is saved when they fail
+    let invalid = QueryPreset {
+        geometry_detail: Some("outline".to_string()),
+        ..Default::default()
+    };
+    let err = service.save_preset(id, "mobile", &invalid, None).await.unwrap_err();
+    assert!(matches!(err, ServiceError::InvalidPreset { ref name, .. } if name == "mobile"));
+    let err = service.set_default_preset(id, Some("mobile"), None).await.unwrap_err();
+    assert!(matches!(err, ServiceError::PresetNotFound { .. }));
+
+    // Deleting the default preset clears the default
+    let view = service.delete_preset(id, "planners", None).await.unwrap();
+    assert_eq!(view.settings.query_presets, Some(BTreeMap::new()));
+    assert_eq!(view.settings.default_query_preset, None);
+    let err = service.delete_preset(id, "planners", None).await.unwrap_err();
+    assert!(matches!(err, ServiceError::PresetNotFound { .. }));
+}
@@ -0,0 +1,5 @@
+-- Access tags on datasets; untagged datasets are visible to every caller
+ALTER TABLE datasets ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+-- GIN index for tag containment filters (tags <@ allowed)
+CREATE INDEX idx_datasets_tags ON datasets USING GIN(tags);
@@ -0,0 +1,7 @@
+-- Checkpoint of the index build in progress; at most one row
+CREATE TABLE build_checkpoints (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    generation UUID NOT NULL,
+    checkpoint JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+-- Workspace settings shared by the CLI and the API; config.toml mirrors them
+CREATE TABLE workspace_settings (
+    workspace_id UUID PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    settings JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+-- Original files of datasets, removed along with their dataset
+CREATE TABLE dataset_blobs (
+    dataset_id UUID PRIMARY KEY REFERENCES datasets(id) ON DELETE CASCADE,
+    content BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
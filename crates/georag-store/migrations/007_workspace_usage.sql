@@ -0,0 +1,24 @@
+-- Usage counters checked against workspace quotas, maintained incrementally
+CREATE TABLE workspace_usage (
+    workspace_id UUID PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+    datasets BIGINT NOT NULL DEFAULT 0,
+    features BIGINT NOT NULL DEFAULT 0,
+    chunks BIGINT NOT NULL DEFAULT 0,
+    blob_bytes BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Start from what existing workspaces already hold
+INSERT INTO workspace_usage (workspace_id, datasets, features, chunks, blob_bytes)
+SELECT
+    w.id,
+    (SELECT COUNT(*) FROM datasets d WHERE d.workspace_id = w.id),
+    (SELECT COALESCE(SUM(d.feature_count), 0) FROM datasets d WHERE d.workspace_id = w.id),
+    (SELECT COUNT(*) FROM chunks c
+        JOIN documents doc ON doc.id = c.document_id
+        JOIN datasets d ON d.id = doc.dataset_id
+        WHERE d.workspace_id = w.id),
+    (SELECT COALESCE(SUM(OCTET_LENGTH(b.content)), 0) FROM dataset_blobs b
+        JOIN datasets d ON d.id = b.dataset_id
+        WHERE d.workspace_id = w.id)
+FROM workspaces w;
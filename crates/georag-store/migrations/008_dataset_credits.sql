@@ -0,0 +1,3 @@
+-- License and attribution of datasets, carried into query results
+ALTER TABLE datasets ADD COLUMN license TEXT;
+ALTER TABLE datasets ADD COLUMN attribution TEXT;
@@ -0,0 +1,10 @@
+-- Named geometries saved per workspace and reusable as query filters
+CREATE TABLE saved_areas (
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    geometry JSONB NOT NULL,
+    source_dataset_id UUID,
+    source_feature_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (workspace_id, name)
+);
@@ -0,0 +1,2 @@
+-- Features whose geometry is missing are kept without one under lenient validity
+ALTER TABLE features ALTER COLUMN geometry DROP NOT NULL;
@@ -0,0 +1,4 @@
+-- Features keep the Z ordinates of their source: the column takes 2D and 3D
+-- geometries alike, still in EPSG:4326. Predicates and indexes stay 2D.
+ALTER TABLE features ALTER COLUMN geometry TYPE GEOMETRY USING geometry;
+ALTER TABLE features ADD CONSTRAINT features_geometry_srid CHECK (ST_SRID(geometry) = 4326);
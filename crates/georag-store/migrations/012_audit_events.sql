@@ -0,0 +1,15 @@
+-- What happened in each workspace, aggregated into activity timelines
+CREATE TABLE audit_events (
+    id BIGSERIAL PRIMARY KEY,
+    workspace_id UUID NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    details TEXT
+);
+
+-- Timelines count a workspace's events over a recent range
+CREATE INDEX idx_audit_events_workspace_time ON audit_events (workspace_id, occurred_at);
+
+-- Failed builds and quota refusals are listed one by one, newest first
+CREATE INDEX idx_audit_events_notable ON audit_events (workspace_id, occurred_at DESC)
+    WHERE kind IN ('build_failed', 'quota_exceeded');
@@ -0,0 +1,85 @@
+-- Chunk and embedding counters per dataset, kept by triggers in the
+-- transaction of every write so status and usage never scan the chunks
+CREATE TABLE dataset_index_stats (
+    dataset_id BIGINT PRIMARY KEY,
+    chunks BIGINT NOT NULL DEFAULT 0,
+    content_bytes BIGINT NOT NULL DEFAULT 0,
+    embeddings BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Dataset of each embedding, so deletions cascading from chunks are counted too
+ALTER TABLE embeddings ADD COLUMN dataset_id BIGINT;
+
+UPDATE embeddings e
+SET dataset_id = (c.metadata->>'dataset_id')::BIGINT
+FROM chunks c
+WHERE c.id = e.chunk_id;
+
+CREATE FUNCTION adjust_dataset_index_stats(
+    target BIGINT,
+    chunk_change BIGINT,
+    byte_change BIGINT,
+    embedding_change BIGINT
+) RETURNS VOID AS $$
+BEGIN
+    IF target IS NULL THEN
+        RETURN;
+    END IF;
+    INSERT INTO dataset_index_stats (dataset_id, chunks, content_bytes, embeddings)
+    VALUES (target, GREATEST(chunk_change, 0), GREATEST(byte_change, 0), GREATEST(embedding_change, 0))
+    ON CONFLICT (dataset_id) DO UPDATE
+    SET chunks = GREATEST(dataset_index_stats.chunks + chunk_change, 0),
+        content_bytes = GREATEST(dataset_index_stats.content_bytes + byte_change, 0),
+        embeddings = GREATEST(dataset_index_stats.embeddings + embedding_change, 0),
+        updated_at = NOW();
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION count_chunk_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM adjust_dataset_index_stats(
+            (OLD.metadata->>'dataset_id')::BIGINT, -1, -OCTET_LENGTH(OLD.content), 0);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM adjust_dataset_index_stats(
+            (NEW.metadata->>'dataset_id')::BIGINT, 1, OCTET_LENGTH(NEW.content), 0);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER chunks_index_stats
+AFTER INSERT OR UPDATE OF content, metadata OR DELETE ON chunks
+FOR EACH ROW EXECUTE FUNCTION count_chunk_change();
+
+CREATE FUNCTION count_embedding_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM adjust_dataset_index_stats(OLD.dataset_id, 0, 0, -1);
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM adjust_dataset_index_stats(NEW.dataset_id, 0, 0, 1);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER embeddings_index_stats
+AFTER INSERT OR UPDATE OF dataset_id OR DELETE ON embeddings
+FOR EACH ROW EXECUTE FUNCTION count_embedding_change();
+
+-- Start from what the stores already hold
+INSERT INTO dataset_index_stats (dataset_id, chunks, content_bytes)
+SELECT (metadata->>'dataset_id')::BIGINT, COUNT(*), SUM(OCTET_LENGTH(content))
+FROM chunks
+WHERE metadata->>'dataset_id' IS NOT NULL
+GROUP BY 1;
+
+INSERT INTO dataset_index_stats (dataset_id, embeddings)
+SELECT dataset_id, COUNT(*)
+FROM embeddings
+WHERE dataset_id IS NOT NULL
+GROUP BY 1
+ON CONFLICT (dataset_id) DO UPDATE SET embeddings = EXCLUDED.embeddings;
@@ -0,0 +1,32 @@
+-- Ordered changes of every workspace, read by downstream consumers.
+-- Events outlive their workspace: removing them would leave gaps in the log.
+CREATE TABLE change_events (
+    seq BIGINT PRIMARY KEY,
+    workspace_id UUID NOT NULL,
+    entity TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    dataset_id BIGINT,
+    ids BIGINT[] NOT NULL DEFAULT '{}',
+    payload JSONB,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Pruning by age finds the newest event older than the retention
+CREATE INDEX idx_change_events_time ON change_events (occurred_at);
+
+-- Last sequence number handed out. Appends update this row in their
+-- transaction, so a rolled back append takes no numbers and appends commit
+-- in sequence order, unlike with a BIGSERIAL.
+CREATE TABLE change_event_sequence (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    last_seq BIGINT NOT NULL
+);
+
+INSERT INTO change_event_sequence (last_seq) VALUES (0);
+
+-- Sequence number each consumer last acknowledged
+CREATE TABLE change_event_cursors (
+    consumer TEXT PRIMARY KEY,
+    seq BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- Redaction rule changes are listed one by one too
+DROP INDEX idx_audit_events_notable;
+
+CREATE INDEX idx_audit_events_notable ON audit_events (workspace_id, occurred_at DESC)
+    WHERE kind IN ('build_failed', 'quota_exceeded', 'redaction_changed');
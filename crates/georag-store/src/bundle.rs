@@ -0,0 +1,602 @@
+//! Offline bundles of a bounding box
+//!
+//! A bundle is a single JSON file holding everything a query inside its
+//! bounding box needs: the features intersecting the box, the chunks that
+//! reference those features, their embeddings and the index state. Bundles
+//! are opened with [`BundleStore`], which serves them from the memory adapters
+//! and rejects every write.
+//!
+//! The R-tree over the features is saved too, as the envelope of every
+//! feature, so opening a large bundle bulk-loads it from the saved envelopes
+//! instead of computing them again. It is rebuilt when the features no
+//! longer hash to the value it was saved with.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use georag_core::error::{GeoragError, Result};
+use georag_core::geo::{
+    IndexEntry, IndexedGeometry, JoinCounts, PreparedFilter, SampleStrategy, SpatialIndex,
+    SpatialJoin,
+};
+use georag_core::models::{
+    distinct_attributions, ChunkId, Dataset, DatasetId, DatasetIndexStats, DatasetMeta,
+    DatasetPreview, Embedding, Feature, FeatureId, Geometry, IndexState, ScoredResult,
+    SpatialFilter, SpatialPredicate, TagVisibility, TextChunk,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use crate::ports::{DocumentStore, SpatialStore, VectorStore};
+
+/// Version of the bundle file layout written by this build
+///
+/// Bump this whenever a field is added, removed or changes meaning; bundles
+/// with a different version are rejected instead of being misread.
+pub const BUNDLE_FORMAT_VERSION: u32 = 3;
+
+/// Query-ready subset of the stores for offline use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineBundle {
+    /// Layout version, see [`BUNDLE_FORMAT_VERSION`]
+    pub format_version: u32,
+
+    /// When the bundle was exported
+    pub created_at: DateTime<Utc>,
+
+    /// Bounding box the bundle covers: `[min_x, min_y, max_x, max_y]`
+    pub bbox: [f64; 4],
+
+    /// Index state of the source, with the chunk count of the bundle
+    pub index_state: Option<IndexState>,
+
+    /// Datasets with at least one feature in the bounding box
+    pub datasets: Vec<BundleDataset>,
+
+    /// Distinct attribution lines of the datasets, which the licenses of
+    /// the data may require wherever the bundle is used
+    pub attributions: Vec<String>,
+
+    /// Features intersecting the bounding box
+    pub features: Vec<Feature>,
+
+    /// Chunks whose spatial reference is one of the features
+    pub chunks: Vec<TextChunk>,
+
+    /// Embeddings of the chunks
+    pub embeddings: Vec<Embedding>,
+
+    /// Spatial index over the features
+    pub spatial_index: BundleIndex,
+}
+
+/// A dataset and the IDs of its features kept in the bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleDataset {
+    pub dataset: Dataset,
+    pub feature_ids: Vec<FeatureId>,
+}
+
+/// Spatial index of a bundle, saved as the envelope of every feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleIndex {
+    /// Hash of the IDs and geometries of the features the index was built from
+    pub content_hash: String,
+
+    /// Feature IDs and envelopes, sorted by ID
+    pub entries: Vec<IndexEntry>,
+}
+
+impl BundleIndex {
+    /// Index the features of a bundle
+    pub fn build(features: &[Feature]) -> Self {
+        let located = located_features(features);
+        let index = rebuild_index(&located);
+        Self {
+            content_hash: content_hash(&located),
+            entries: index.entries(),
+        }
+    }
+}
+
+/// How a bundle's spatial index was made ready when the bundle was opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexOrigin {
+    /// Bulk-loaded from the envelopes saved in the bundle
+    Loaded,
+
+    /// Built from the features, because the saved index no longer matches them
+    Rebuilt,
+}
+
+impl IndexOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Loaded => "loaded",
+            Self::Rebuilt => "rebuilt",
+        }
+    }
+}
+
+/// Where a bundle's spatial index came from and how long it took
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexLoad {
+    pub origin: IndexOrigin,
+
+    /// Time spent checking the saved index and loading or rebuilding it
+    pub elapsed: Duration,
+
+    /// Number of features in the index
+    pub features: usize,
+}
+
+/// Only the version is read first, so a newer layout fails with a clear error
+#[derive(Deserialize)]
+struct BundleHeader {
+    format_version: u32,
+}
+
+impl OfflineBundle {
+    /// Copy the part of the stores inside a bounding box
+    ///
+    /// Features are selected with the `Intersects` predicate. Chunks without a
+    /// spatial reference are left out, since they cannot be placed in the box.
+    pub async fn extract(
+        spatial: &dyn SpatialStore,
+        vector: &dyn VectorStore,
+        document: &dyn DocumentStore,
+        bbox: [f64; 4],
+        index_state: Option<IndexState>,
+    ) -> Result<Self> {
+        let filter = SpatialFilter::new(SpatialPredicate::Intersects).geometry(bbox_polygon(&bbox));
+        let features = spatial.spatial_query(&filter).await?;
+        let feature_ids: HashSet<FeatureId> = features.iter().map(|f| f.id).collect();
+
+        let mut datasets = Vec::new();
+        for meta in spatial.list_datasets().await? {
+            let kept: Vec<FeatureId> = spatial
+                .get_features_for_dataset(meta.id)
+                .await?
+                .into_iter()
+                .map(|f| f.id)
+                .filter(|id| feature_ids.contains(id))
+                .collect();
+            if kept.is_empty() {
+                continue;
+            }
+            if let Some(mut dataset) = spatial.get_dataset(meta.id).await? {
+                dataset.feature_count = kept.len();
+                datasets.push(BundleDataset { dataset, feature_ids: kept });
+            }
+        }
+
+        let chunk_ids = document.list_chunk_ids().await?;
+        let chunks: Vec<TextChunk> = document
+            .get_chunks(&chunk_ids)
+            .await?
+            .into_iter()
+            .filter(|chunk| chunk.spatial_ref.is_some_and(|id| feature_ids.contains(&id)))
+            .collect();
+
+        let mut embeddings = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            if let Some(embedding) = vector.get_embedding(chunk.id).await? {
+                embeddings.push(embedding);
+            }
+        }
+
+        let spatial_index = BundleIndex::build(&features);
+        let index_state =
+            index_state.map(|state| IndexState { chunk_count: chunks.len(), ..state });
+        let attributions =
+            distinct_attributions(datasets.iter().filter_map(|d| d.dataset.attribution.as_deref()));
+
+        Ok(Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            created_at: Utc::now(),
+            bbox,
+            index_state,
+            datasets,
+            attributions,
+            features,
+            chunks,
+            embeddings,
+            spatial_index,
+        })
+    }
+
+    /// Write the bundle to a file
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let json =
+            serde_json::to_vec(self).map_err(|e| GeoragError::Serialization(e.to_string()))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Read a bundle from a file, rejecting other format versions
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let content = fs::read(path)?;
+        let invalid = |reason: String| GeoragError::FormatValidation {
+            format: "offline bundle".to_string(),
+            reason,
+        };
+
+        let header: BundleHeader = serde_json::from_slice(&content)
+            .map_err(|e| invalid(format!("{} is not a bundle: {}", path.display(), e)))?;
+        if header.format_version != BUNDLE_FORMAT_VERSION {
+            return Err(invalid(format!(
+                "{} has format version {}, this build reads version {}. \
+                Export the bundle again with this version of georag",
+                path.display(),
+                header.format_version,
+                BUNDLE_FORMAT_VERSION
+            )));
+        }
+
+        serde_json::from_slice(&content).map_err(|e| GeoragError::Serialization(e.to_string()))
+    }
+}
+
+/// Features with a geometry, sorted by ID as their index entries are
+fn located_features(features: &[Feature]) -> Vec<(&Feature, &Geometry)> {
+    let mut located: Vec<(&Feature, &Geometry)> =
+        features.iter().filter_map(|f| f.geometry.as_ref().map(|g| (f, g))).collect();
+    located.sort_by_key(|(feature, _)| feature.id.0);
+    located
+}
+
+fn rebuild_index(located: &[(&Feature, &Geometry)]) -> SpatialIndex {
+    SpatialIndex::from_geometries(
+        located
+            .iter()
+            .map(|(feature, geometry)| (feature.id.0 as usize, (*geometry).clone()))
+            .collect(),
+    )
+}
+
+/// Bulk-load the saved index, or `None` if it does not list the features
+fn load_index(located: &[(&Feature, &Geometry)], saved: &[IndexEntry]) -> Option<SpatialIndex> {
+    if saved.len() != located.len() {
+        return None;
+    }
+    let indexed = located
+        .iter()
+        .zip(saved)
+        .map(|((feature, geometry), entry)| {
+            (entry.id == feature.id.0 as usize).then(|| {
+                IndexedGeometry::with_envelope(entry.id, (*geometry).clone(), entry.envelope)
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(SpatialIndex::from_indexed(indexed))
+}
+
+/// SHA-256 over the ID and coordinates of every located feature
+fn content_hash(located: &[(&Feature, &Geometry)]) -> String {
+    fn part(hasher: &mut Sha256, coordinates: &[[f64; 2]]) {
+        hasher.update((coordinates.len() as u64).to_le_bytes());
+        for [x, y] in coordinates {
+            hasher.update(x.to_le_bytes());
+            hasher.update(y.to_le_bytes());
+        }
+    }
+
+    fn parts(hasher: &mut Sha256, parts: &[Vec<[f64; 2]>]) {
+        hasher.update((parts.len() as u64).to_le_bytes());
+        for coordinates in parts {
+            part(hasher, coordinates);
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    for (feature, geometry) in located {
+        hasher.update(feature.id.0.to_le_bytes());
+        // The geometry type is hashed too, so a line and a ring of the same
+        // coordinates differ
+        hasher.update([geometry.geometry_type() as u8]);
+        match geometry {
+            Geometry::Point { coordinates } => part(&mut hasher, &[*coordinates]),
+            Geometry::LineString { coordinates } | Geometry::MultiPoint { coordinates } => {
+                part(&mut hasher, coordinates)
+            }
+            Geometry::Polygon { coordinates } | Geometry::MultiLineString { coordinates } => {
+                parts(&mut hasher, coordinates)
+            }
+            Geometry::MultiPolygon { coordinates } => {
+                hasher.update((coordinates.len() as u64).to_le_bytes());
+                for polygon in coordinates {
+                    parts(&mut hasher, polygon);
+                }
+            }
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Closed polygon covering a bounding box
+fn bbox_polygon(bbox: &[f64; 4]) -> Geometry {
+    let [min_x, min_y, max_x, max_y] = *bbox;
+    Geometry::polygon(vec![vec![
+        [min_x, min_y],
+        [max_x, min_y],
+        [max_x, max_y],
+        [min_x, max_y],
+        [min_x, min_y],
+    ]])
+}
+
+/// Read-only store serving an [`OfflineBundle`]
+///
+/// Implements the spatial, vector and document ports over the memory
+/// adapters. Every write returns [`GeoragError::ReadOnly`].
+#[derive(Debug, Clone)]
+pub struct BundleStore {
+    spatial: MemorySpatialStore,
+    vector: MemoryVectorStore,
+    document: MemoryDocumentStore,
+    index: Arc<SpatialIndex>,
+    index_load: IndexLoad,
+    bbox: [f64; 4],
+    index_state: Option<IndexState>,
+}
+
+impl BundleStore {
+    /// Open a bundle file
+    pub async fn open(path: &Path) -> Result<Self> {
+        Self::from_bundle(OfflineBundle::read_from(path)?).await
+    }
+
+    /// Load a bundle into memory
+    ///
+    /// The saved spatial index is used when the features still hash to its
+    /// content hash; otherwise the index is rebuilt from the features. A
+    /// bundle exported from an index of another schema version is refused,
+    /// since the store is read-only and cannot run the upgrade steps; upgrade
+    /// the workspace and export it again.
+    pub async fn from_bundle(bundle: OfflineBundle) -> Result<Self> {
+        if let Some(state) = &bundle.index_state {
+            state.check_schema_version()?;
+        }
+        let spatial = MemorySpatialStore::new();
+        let vector = MemoryVectorStore::new();
+        let document = MemoryDocumentStore::new();
+
+        let started = Instant::now();
+        let located = located_features(&bundle.features);
+        let loaded = (content_hash(&located) == bundle.spatial_index.content_hash)
+            .then(|| load_index(&located, &bundle.spatial_index.entries))
+            .flatten();
+        let (index, origin) = match loaded {
+            Some(index) => (index, IndexOrigin::Loaded),
+            None => (rebuild_index(&located), IndexOrigin::Rebuilt),
+        };
+        let index_load = IndexLoad {
+            origin,
+            elapsed: started.elapsed(),
+            features: index.len(),
+        };
+
+        spatial.store_features(&bundle.features).await?;
+        for BundleDataset { dataset, feature_ids } in bundle.datasets {
+            spatial.associate_features_with_dataset(dataset.id, feature_ids);
+            spatial.insert_dataset(dataset);
+        }
+        document.store_chunks(&bundle.chunks).await?;
+        vector.store_embeddings(&bundle.embeddings).await?;
+
+        Ok(Self {
+            spatial,
+            vector,
+            document,
+            index: Arc::new(index),
+            index_load,
+            bbox: bundle.bbox,
+            index_state: bundle.index_state,
+        })
+    }
+
+    /// Bounding box the bundle covers
+    pub fn bbox(&self) -> [f64; 4] {
+        self.bbox
+    }
+
+    /// Index state the bundle was exported with
+    pub fn index_state(&self) -> Option<&IndexState> {
+        self.index_state.as_ref()
+    }
+
+    /// Whether the spatial index was loaded or rebuilt, and how long it took
+    pub fn index_load(&self) -> IndexLoad {
+        self.index_load
+    }
+}
+
+fn read_only<T>(operation: &str) -> Result<T> {
+    Err(GeoragError::ReadOnly { operation: operation.to_string() })
+}
+
+#[async_trait]
+impl SpatialStore for BundleStore {
+    async fn store_dataset(&self, _dataset: &Dataset) -> Result<DatasetId> {
+        read_only("store a dataset")
+    }
+
+    async fn get_dataset(&self, id: DatasetId) -> Result<Option<Dataset>> {
+        self.spatial.get_dataset(id).await
+    }
+
+    async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.spatial.list_datasets().await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
+        self.spatial.list_visible_datasets(visibility).await
+    }
+
+    async fn set_dataset_tags(&self, _id: DatasetId, _tags: &[String]) -> Result<()> {
+        read_only("change dataset tags")
+    }
+
+    async fn set_dataset_preview(&self, _id: DatasetId, _preview: &DatasetPreview) -> Result<()> {
+        read_only("change a dataset preview")
+    }
+
+    async fn delete_dataset(&self, _id: DatasetId) -> Result<()> {
+        read_only("delete a dataset")
+    }
+
+    async fn store_features(&self, _features: &[Feature]) -> Result<()> {
+        read_only("store features")
+    }
+
+    async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>> {
+        self.spatial_query_prepared(&PreparedFilter::new(filter)).await
+    }
+
+    async fn spatial_query_prepared(&self, prepared: &PreparedFilter) -> Result<Vec<Feature>> {
+        // Without a filter geometry every feature matches, located or not
+        if prepared.filter().geometry.is_none() {
+            return self.spatial.spatial_query_prepared(prepared).await;
+        }
+        let ids = self.index.query_prepared(prepared);
+        Ok(self.spatial.features_by_id(ids.into_iter().map(|id| FeatureId(id as u64))))
+    }
+
+    async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>> {
+        self.spatial.get_feature(id).await
+    }
+
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        self.spatial.get_features(ids).await
+    }
+
+    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
+        self.spatial.get_features_for_dataset(dataset_id).await
+    }
+
+    async fn update_feature_properties(&self, _features: &[Feature]) -> Result<()> {
+        read_only("update feature properties")
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        _dataset_id: DatasetId,
+        _features: &[Feature],
+    ) -> Result<()> {
+        read_only("update dataset features")
+    }
+
+    async fn delete_features(&self, _dataset_id: DatasetId, _ids: &[FeatureId]) -> Result<()> {
+        read_only("delete features")
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        self.spatial.sample_features(dataset_id, n, strategy).await
+    }
+
+    async fn spatial_join(
+        &self,
+        _target: DatasetId,
+        _source: DatasetId,
+        _join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        read_only("run a spatial join")
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        _geometry: &Geometry,
+        _max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl VectorStore for BundleStore {
+    async fn store_embeddings(&self, _embeddings: &[Embedding]) -> Result<()> {
+        read_only("store embeddings")
+    }
+
+    async fn similarity_search(
+        &self,
+        query: &[f32],
+        k: usize,
+        threshold: Option<f32>,
+    ) -> Result<Vec<ScoredResult>> {
+        self.vector.similarity_search(query, k, threshold).await
+    }
+
+    async fn get_embedding(&self, chunk_id: ChunkId) -> Result<Option<Embedding>> {
+        self.vector.get_embedding(chunk_id).await
+    }
+
+    async fn delete_embeddings(&self, _chunk_ids: &[ChunkId]) -> Result<()> {
+        read_only("delete embeddings")
+    }
+
+    async fn dimensions(&self) -> Result<usize> {
+        self.vector.dimensions().await
+    }
+
+    async fn list_embedding_ids(&self) -> Result<Vec<ChunkId>> {
+        self.vector.list_embedding_ids().await
+    }
+
+    async fn compact(&self) -> Result<()> {
+        read_only("compact embeddings")
+    }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        self.vector.dataset_index_stats().await
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        read_only("recount index stats")
+    }
+}
+
+#[async_trait]
+impl DocumentStore for BundleStore {
+    async fn store_chunks(&self, _chunks: &[TextChunk]) -> Result<()> {
+        read_only("store chunks")
+    }
+
+    async fn get_chunks(&self, ids: &[ChunkId]) -> Result<Vec<TextChunk>> {
+        self.document.get_chunks(ids).await
+    }
+
+    async fn get_chunk(&self, id: ChunkId) -> Result<Option<TextChunk>> {
+        self.document.get_chunk(id).await
+    }
+
+    async fn delete_chunks(&self, _ids: &[ChunkId]) -> Result<()> {
+        read_only("delete chunks")
+    }
+
+    async fn mark_chunks_stale(&self, _ids: &[ChunkId]) -> Result<()> {
+        read_only("mark chunks stale")
+    }
+
+    async fn list_chunk_ids(&self) -> Result<Vec<ChunkId>> {
+        self.document.list_chunk_ids().await
+    }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        self.document.dataset_index_stats().await
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        read_only("recount index stats")
+    }
+}
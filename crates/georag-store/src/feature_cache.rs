@@ -0,0 +1,389 @@
+//! Read-through cache of stored features
+//!
+//! Query responses look up the feature of every ranked source for its
+//! geometry, and popular features are looked up query after query.
+//! [`CachedSpatialStore`] answers `get_feature` and `get_features` from a
+//! [`FeatureCache`] and forwards only the misses to the store it wraps, in
+//! one batch. Every write through it invalidates the features it touches.
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::geo::{JoinCounts, PreparedFilter, SampleStrategy, SpatialJoin};
+use georag_core::models::{
+    Dataset, DatasetId, DatasetMeta, DatasetPreview, Feature, FeatureId, Geometry, SpatialFilter,
+    TagVisibility,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::ports::{distinct_feature_ids, SpatialStore};
+
+/// Features kept by default
+pub const DEFAULT_FEATURE_CACHE_SIZE: usize = 1024;
+
+/// Least recently used cache of features, keyed by feature ID
+pub struct FeatureCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    features: HashMap<FeatureId, (Arc<Feature>, u64)>,
+    /// Use counter; an entry's stamp is the count at its last use
+    clock: u64,
+    /// Bumped by every invalidation, so a lookup that raced a write does
+    /// not cache what it read before the write
+    generation: u64,
+    stats: FeatureCacheStats,
+}
+
+/// Feature lookups served by a [`FeatureCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeatureCacheStats {
+    /// Features answered from the cache
+    pub hits: u64,
+    /// Features read from the store
+    pub misses: u64,
+}
+
+impl FeatureCacheStats {
+    /// Share of feature lookups answered from the cache, 0 before any lookup
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+/// Features found in a [`FeatureCache`] and the IDs to read from the store
+struct Lookup {
+    found: Vec<Feature>,
+    missing: Vec<FeatureId>,
+    generation: u64,
+}
+
+impl FeatureCache {
+    /// Create a cache keeping up to `capacity` features
+    ///
+    /// A capacity of zero disables caching: every lookup reads the store.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// Split distinct `ids` into cached features and IDs still to be read
+    fn lookup(&self, ids: &[FeatureId]) -> Lookup {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for id in ids {
+            match entries.features.get_mut(id) {
+                Some((feature, used)) => {
+                    *used = clock;
+                    found.push(feature.as_ref().clone());
+                }
+                None => missing.push(*id),
+            }
+        }
+        entries.stats.hits += found.len() as u64;
+        entries.stats.misses += missing.len() as u64;
+
+        Lookup {
+            found,
+            missing,
+            generation: entries.generation,
+        }
+    }
+
+    /// Keep features read at `generation`, unless they were invalidated since
+    fn insert(&self, features: &[Feature], generation: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation {
+            return;
+        }
+        let clock = entries.clock;
+        for feature in features {
+            if entries.features.len() >= self.capacity
+                && !entries.features.contains_key(&feature.id)
+            {
+                let oldest =
+                    entries.features.iter().min_by_key(|(_, (_, used))| *used).map(|(id, _)| *id);
+                if let Some(oldest) = oldest {
+                    entries.features.remove(&oldest);
+                }
+            }
+            entries.features.insert(feature.id, (Arc::new(feature.clone()), clock));
+        }
+    }
+
+    /// Drop the given features, so their next lookup reads the store
+    pub fn invalidate(&self, ids: impl IntoIterator<Item = FeatureId>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        for id in ids {
+            entries.features.remove(&id);
+        }
+    }
+
+    /// Drop every feature
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.features.clear();
+    }
+
+    /// Number of features held
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().features.len()
+    }
+
+    /// Check if no feature is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hits and misses since the cache was created
+    pub fn stats(&self) -> FeatureCacheStats {
+        self.entries.lock().unwrap().stats
+    }
+}
+
+impl Default for FeatureCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_FEATURE_CACHE_SIZE)
+    }
+}
+
+/// Spatial store answering feature lookups from a [`FeatureCache`]
+///
+/// Writes are forwarded and then invalidate the features they touch, whether
+/// they succeeded or not. Deleting a dataset or joining inside the store
+/// clears the whole cache, since the features they change are not known here.
+pub struct CachedSpatialStore {
+    inner: Arc<dyn SpatialStore>,
+    cache: Arc<FeatureCache>,
+}
+
+impl CachedSpatialStore {
+    /// Wrap `inner`, caching its features in `cache`
+    pub fn new(inner: Arc<dyn SpatialStore>, cache: Arc<FeatureCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    /// The cache features are kept in
+    pub fn cache(&self) -> &Arc<FeatureCache> {
+        &self.cache
+    }
+
+    fn invalidate(&self, features: &[Feature]) {
+        self.cache.invalidate(features.iter().map(|f| f.id));
+    }
+}
+
+#[async_trait]
+impl SpatialStore for CachedSpatialStore {
+    async fn store_dataset(&self, dataset: &Dataset) -> Result<DatasetId> {
+        self.inner.store_dataset(dataset).await
+    }
+
+    async fn get_dataset(&self, id: DatasetId) -> Result<Option<Dataset>> {
+        self.inner.get_dataset(id).await
+    }
+
+    async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_datasets().await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
+        self.inner.list_visible_datasets(visibility).await
+    }
+
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()> {
+        self.inner.set_dataset_tags(id, tags).await
+    }
+
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()> {
+        self.inner.set_dataset_preview(id, preview).await
+    }
+
+    async fn delete_dataset(&self, id: DatasetId) -> Result<()> {
+        let result = self.inner.delete_dataset(id).await;
+        self.cache.clear();
+        result
+    }
+
+    async fn store_features(&self, features: &[Feature]) -> Result<()> {
+        let result = self.inner.store_features(features).await;
+        self.invalidate(features);
+        result
+    }
+
+    async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>> {
+        self.inner.spatial_query(filter).await
+    }
+
+    async fn spatial_query_prepared(&self, prepared: &PreparedFilter) -> Result<Vec<Feature>> {
+        self.inner.spatial_query_prepared(prepared).await
+    }
+
+    async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>> {
+        Ok(self.get_features(&[id]).await?.pop())
+    }
+
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        let Lookup { mut found, missing, generation } =
+            self.cache.lookup(&distinct_feature_ids(ids));
+        if !missing.is_empty() {
+            let fetched = self.inner.get_features(&missing).await?;
+            self.cache.insert(&fetched, generation);
+            found.extend(fetched);
+        }
+        found.sort_by_key(|f| f.id.0);
+        Ok(found)
+    }
+
+    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
+        self.inner.get_features_for_dataset(dataset_id).await
+    }
+
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()> {
+        let result = self.inner.update_feature_properties(features).await;
+        self.invalidate(features);
+        result
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()> {
+        let result = self.inner.upsert_dataset_features(dataset_id, features).await;
+        self.invalidate(features);
+        result
+    }
+
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()> {
+        let result = self.inner.delete_features(dataset_id, ids).await;
+        self.cache.invalidate(ids.iter().copied());
+        result
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        self.inner.sample_features(dataset_id, n, strategy).await
+    }
+
+    async fn spatial_join(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        let result = self.inner.spatial_join(target, source, join).await;
+        self.cache.clear();
+        result
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        geometry: &Geometry,
+        max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        self.inner.subdivide_geometry(geometry, max_vertices).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemorySpatialStore;
+    use std::collections::HashMap;
+
+    fn feature(id: u64, name: &str) -> Feature {
+        let properties = HashMap::from([("name".to_string(), serde_json::json!(name))]);
+        Feature::with_geometry(FeatureId(id), Geometry::point(id as f64, 0.0), properties, 4326)
+    }
+
+    async fn cached(capacity: usize) -> (Arc<MemorySpatialStore>, CachedSpatialStore) {
+        let inner = Arc::new(MemorySpatialStore::new());
+        inner
+            .store_features(&[feature(1, "quay"), feature(2, "crane"), feature(3, "market")])
+            .await
+            .unwrap();
+        let store = CachedSpatialStore::new(inner.clone(), Arc::new(FeatureCache::new(capacity)));
+        (inner, store)
+    }
+
+    fn ids(features: &[Feature]) -> Vec<u64> {
+        features.iter().map(|f| f.id.0).collect()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_lookups_are_answered_from_the_cache() {
+        let (_, store) = cached(8).await;
+
+        let first = store.get_features(&[FeatureId(2), FeatureId(9), FeatureId(1)]).await.unwrap();
+        assert_eq!(ids(&first), [1, 2]);
+        assert_eq!(store.cache().stats(), FeatureCacheStats { hits: 0, misses: 3 });
+
+        // Duplicates are looked up once; the absent ID is not cached
+        let second = store.get_features(&[FeatureId(1), FeatureId(1), FeatureId(9)]).await.unwrap();
+        assert_eq!(ids(&second), [1]);
+        assert_eq!(store.cache().stats(), FeatureCacheStats { hits: 1, misses: 4 });
+        assert_eq!(store.cache().stats().hit_rate(), 0.2);
+        assert_eq!(store.cache().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_writes_invalidate_the_features_they_touch() {
+        let (_, store) = cached(8).await;
+        store.get_features(&[FeatureId(1), FeatureId(2)]).await.unwrap();
+
+        store.update_feature_properties(&[feature(1, "pier")]).await.unwrap();
+        assert_eq!(store.cache().len(), 1);
+        let updated = store.get_feature(FeatureId(1)).await.unwrap().unwrap();
+        assert_eq!(updated.properties["name"], serde_json::json!("pier"));
+
+        store.delete_dataset(DatasetId(7)).await.unwrap();
+        assert!(store.cache().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_feature_is_evicted() {
+        let (inner, store) = cached(2).await;
+        store.get_features(&[FeatureId(1), FeatureId(2)]).await.unwrap();
+        store.get_feature(FeatureId(1)).await.unwrap();
+        store.get_feature(FeatureId(3)).await.unwrap();
+        assert_eq!(store.cache().len(), 2);
+
+        // Feature 2 was evicted, so a change behind the cache's back shows
+        inner.store_features(&[feature(2, "gantry")]).await.unwrap();
+        let reread = store.get_feature(FeatureId(2)).await.unwrap().unwrap();
+        assert_eq!(reread.properties["name"], serde_json::json!("gantry"));
+    }
+
+    #[test]
+    fn test_lookup_racing_an_invalidation_is_not_cached() {
+        let cache = FeatureCache::new(8);
+        let lookup = cache.lookup(&[FeatureId(1)]);
+        cache.invalidate([FeatureId(1)]);
+        cache.insert(&[feature(1, "quay")], lookup.generation);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+//! Filesystem implementation of the blob store
+//!
+//! Each blob is a file named after its dataset ID inside one directory,
+//! `.georag/blobs` in a CLI workspace. Files are written to a temporary name
+//! first and renamed into place, so a crash never leaves a truncated blob.
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::models::DatasetId;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::ports::BlobStore;
+
+/// Blob store keeping one file per dataset in a directory
+#[derive(Debug, Clone)]
+pub struct FilesystemBlobStore {
+    root: PathBuf,
+}
+
+impl FilesystemBlobStore {
+    /// Create a blob store in `root`; the directory is created on first write
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory the blobs are kept in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn blob_path(&self, dataset_id: DatasetId) -> PathBuf {
+        self.root.join(dataset_id.0.to_string())
+    }
+}
+
+#[async_trait]
+impl BlobStore for FilesystemBlobStore {
+    async fn put_blob(&self, dataset_id: DatasetId, content: &[u8]) -> Result<()> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        let path = self.blob_path(dataset_id);
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, content).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn get_blob(&self, dataset_id: DatasetId) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.blob_path(dataset_id)).await {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete_blob(&self, dataset_id: DatasetId) -> Result<()> {
+        match tokio::fs::remove_file(self.blob_path(dataset_id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
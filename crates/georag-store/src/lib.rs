@@ -1,3 +1,7 @@
+pub mod bundle;
+pub mod feature_cache;
+pub mod filesystem;
 pub mod memory;
 pub mod ports;
 pub mod postgres;
+pub mod slots;
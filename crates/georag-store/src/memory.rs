@@ -5,17 +5,28 @@
 //! an unrecoverable state. For production workloads, use the PostgreSQL backend.
 
 use async_trait::async_trait;
-use chrono::Utc;
-use georag_core::error::Result;
+use chrono::{DateTime, Duration, Utc};
+use georag_core::config::WorkspaceSettings;
+use georag_core::error::{GeoragError, Result};
+use georag_core::geo::{sample_features, JoinCounts, PreparedFilter, SampleStrategy, SpatialJoin};
 use georag_core::models::{
-    ChunkId, Dataset, DatasetId, DatasetMeta, Embedding, Feature, FeatureId, ScoredResult,
-    SpatialFilter, TextChunk, WorkspaceConfig, WorkspaceId, WorkspaceMeta,
+    sort_datasets, AuditEvent, BuildCheckpoint, ChangeEvent, ChunkId, Dataset, DatasetId,
+    DatasetIndexStats, DatasetMeta, DatasetPreview, DatasetSort, Embedding, EventLogBounds,
+    EventRetention, Feature, FeatureId, Geometry, SavedArea, ScoredResult, SortOrder,
+    SpatialFilter, TagVisibility, TextChunk, UsageDelta, WorkspaceConfig, WorkspaceId,
+    WorkspaceMeta, WorkspaceUsage, DEFAULT_EVENT_RETENTION_EVENTS, MAX_TIMELINE_DAYS,
 };
-use std::collections::HashMap;
+use georag_core::resources::{ResourceGuard, ResourceKind};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::ErrorKind;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
+use crate::filesystem::FilesystemBlobStore;
 use crate::ports::{
-    DocumentStore, SpatialStore, Transaction, Transactional, VectorStore, WorkspaceStore,
+    distinct_feature_ids, AreaStore, AuditStore, BlobStore, CheckpointStore, DocumentStore,
+    EventLogStore, SlotStore, SpatialStore, Transaction, Transactional, VectorStore,
+    WorkspaceStore, WorkspaceStoreProvider, WorkspaceStores,
 };
 
 /// In-memory implementation of SpatialStore
@@ -43,6 +54,23 @@ impl MemorySpatialStore {
         dataset_features.entry(dataset_id).or_default().extend(feature_ids);
     }
 
+    /// Insert a dataset under its existing ID, e.g. when loading a bundle
+    pub(crate) fn insert_dataset(&self, dataset: Dataset) {
+        let mut datasets = self.datasets.write().unwrap();
+        let mut next_id = self.next_id.write().unwrap();
+        *next_id = (*next_id).max(dataset.id.0 + 1);
+        datasets.insert(dataset.id, dataset);
+    }
+
+    /// Features with the given IDs, sorted by ID; unknown IDs are skipped
+    pub(crate) fn features_by_id(&self, ids: impl IntoIterator<Item = FeatureId>) -> Vec<Feature> {
+        let features = self.features.read().unwrap();
+        let mut found: Vec<Feature> =
+            ids.into_iter().filter_map(|id| features.get(&id).cloned()).collect();
+        found.sort_by_key(|f| f.id.0);
+        found
+    }
+
     /// Create a snapshot of the current state for transaction support
     fn create_snapshot(&self) -> MemoryStoreSnapshot {
         MemoryStoreSnapshot {
@@ -128,9 +156,14 @@ impl SpatialStore for MemorySpatialStore {
     }
 
     async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.list_visible_datasets(&TagVisibility::All).await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
         let datasets = self.datasets.read().unwrap();
-        Ok(datasets
+        let mut metas: Vec<DatasetMeta> = datasets
             .values()
+            .filter(|d| visibility.allows(&d.tags))
             .map(|d| DatasetMeta {
                 id: d.id,
                 name: d.name.clone(),
@@ -138,8 +171,32 @@ impl SpatialStore for MemorySpatialStore {
                 feature_count: d.feature_count,
                 crs: d.crs,
                 added_at: d.added_at,
+                tags: d.tags.clone(),
+                license: d.license.clone(),
+                attribution: d.attribution.clone(),
+                preview: d.format.preview.clone(),
             })
-            .collect())
+            .collect();
+        sort_datasets(&mut metas, DatasetSort::Name, SortOrder::Asc);
+        Ok(metas)
+    }
+
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()> {
+        let mut datasets = self.datasets.write().unwrap();
+        let dataset = datasets
+            .get_mut(&id)
+            .ok_or_else(|| GeoragError::DatasetNotFound { name: id.0.to_string() })?;
+        dataset.tags = tags.to_vec();
+        Ok(())
+    }
+
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()> {
+        let mut datasets = self.datasets.write().unwrap();
+        let dataset = datasets
+            .get_mut(&id)
+            .ok_or_else(|| GeoragError::DatasetNotFound { name: id.0.to_string() })?;
+        dataset.format.preview = Some(preview.clone());
+        Ok(())
     }
 
     async fn delete_dataset(&self, id: DatasetId) -> Result<()> {
@@ -157,14 +214,18 @@ impl SpatialStore for MemorySpatialStore {
     }
 
     async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>> {
+        self.spatial_query_prepared(&PreparedFilter::new(filter)).await
+    }
+
+    async fn spatial_query_prepared(&self, prepared: &PreparedFilter) -> Result<Vec<Feature>> {
         let features = self.features.read().unwrap();
 
-        Ok(features
+        let mut matched: Vec<Feature> = features
             .values()
             .filter(|feature| {
-                // If no filter geometry, include all features
-                if filter.geometry.is_none() {
-                    return true;
+                // If no filter geometry, include all features outside the exclusions
+                if prepared.filter().geometry.is_none() {
+                    return feature.geometry.as_ref().is_none_or(|g| !prepared.excludes(g));
                 }
 
                 // Get feature geometry
@@ -172,11 +233,13 @@ impl SpatialStore for MemorySpatialStore {
                     return false; // No geometry, can't match spatial filter
                 };
 
-                // Apply spatial filter directly (types are now unified!)
-                georag_core::geo::spatial::evaluate_spatial_filter(feature_geom, filter)
+                // Filter geometry is prepared once for all candidates
+                prepared.evaluate(feature_geom)
             })
             .cloned()
-            .collect())
+            .collect();
+        matched.sort_by_key(|f| f.id.0);
+        Ok(matched)
     }
 
     async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>> {
@@ -184,6 +247,10 @@ impl SpatialStore for MemorySpatialStore {
         Ok(features.get(&id).cloned())
     }
 
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        Ok(self.features_by_id(distinct_feature_ids(ids)))
+    }
+
     async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
         let dataset_features = self.dataset_features.read().unwrap();
         let features = self.features.read().unwrap();
@@ -191,16 +258,135 @@ impl SpatialStore for MemorySpatialStore {
         let feature_ids = dataset_features.get(&dataset_id);
 
         match feature_ids {
-            Some(ids) => Ok(ids.iter().filter_map(|id| features.get(id).cloned()).collect()),
+            Some(ids) => {
+                let mut members: Vec<Feature> =
+                    ids.iter().filter_map(|id| features.get(id).cloned()).collect();
+                members.sort_by_key(|f| f.id.0);
+                Ok(members)
+            }
             None => Ok(Vec::new()),
         }
     }
+
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()> {
+        let mut store = self.features.write().unwrap();
+        for feature in features {
+            if let Some(existing) = store.get_mut(&feature.id) {
+                existing.properties = feature.properties.clone();
+            }
+        }
+        Ok(())
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()> {
+        let mut store = self.features.write().unwrap();
+        let mut dataset_features = self.dataset_features.write().unwrap();
+        let members = dataset_features.entry(dataset_id).or_default();
+
+        for feature in features {
+            if !members.contains(&feature.id) {
+                members.push(feature.id);
+            }
+            store.insert(feature.id, feature.clone());
+        }
+        Ok(())
+    }
+
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()> {
+        let mut store = self.features.write().unwrap();
+        let mut dataset_features = self.dataset_features.write().unwrap();
+
+        if let Some(members) = dataset_features.get_mut(&dataset_id) {
+            members.retain(|id| !ids.contains(id));
+        }
+        for id in ids {
+            store.remove(id);
+        }
+        Ok(())
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        let features = self.get_features_for_dataset(dataset_id).await?;
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Ok(sample_features(&features, n, strategy, seed))
+    }
+
+    async fn spatial_join(
+        &self,
+        _target: DatasetId,
+        _source: DatasetId,
+        _join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        // Joins are computed by the caller over the loaded features
+        Ok(None)
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        _geometry: &Geometry,
+        _max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        // Geometries are cut by the caller
+        Ok(None)
+    }
+}
+
+/// Index counters by dataset
+type IndexStats = BTreeMap<DatasetId, DatasetIndexStats>;
+
+/// Count a chunk into or, unless `added`, out of its dataset's counters
+fn count_chunk(stats: &mut IndexStats, chunk: &TextChunk, added: bool) {
+    let Some(dataset_id) = chunk.metadata.dataset_id else {
+        return;
+    };
+    let counters = stats.entry(dataset_id).or_default();
+    let bytes = chunk.content.len() as u64;
+    if added {
+        counters.chunks += 1;
+        counters.content_bytes += bytes;
+    } else {
+        counters.chunks = counters.chunks.saturating_sub(1);
+        counters.content_bytes = counters.content_bytes.saturating_sub(bytes);
+    }
+    if counters.is_empty() {
+        stats.remove(&dataset_id);
+    }
+}
+
+/// Count an embedding into or, unless `added`, out of its dataset's counters
+fn count_embedding(stats: &mut IndexStats, embedding: &Embedding, added: bool) {
+    let Some(dataset_id) = embedding.dataset_id else {
+        return;
+    };
+    let counters = stats.entry(dataset_id).or_default();
+    if added {
+        counters.embeddings += 1;
+    } else {
+        counters.embeddings = counters.embeddings.saturating_sub(1);
+    }
+    if counters.is_empty() {
+        stats.remove(&dataset_id);
+    }
 }
 
 /// In-memory implementation of VectorStore
 #[derive(Debug, Clone, Default)]
 pub struct MemoryVectorStore {
     embeddings: Arc<RwLock<HashMap<ChunkId, Embedding>>>,
+    /// Updated while `embeddings` is locked for writing
+    stats: Arc<RwLock<IndexStats>>,
 }
 
 impl MemoryVectorStore {
@@ -209,6 +395,13 @@ impl MemoryVectorStore {
         Self::default()
     }
 
+    /// Overwrite the counters of a dataset without touching its embeddings
+    ///
+    /// Lets tests simulate counters that drifted from the data.
+    pub fn set_index_stats(&self, dataset_id: DatasetId, stats: DatasetIndexStats) {
+        self.stats.write().unwrap().insert(dataset_id, stats);
+    }
+
     /// Calculate cosine similarity between two vectors
     fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
@@ -231,8 +424,12 @@ impl MemoryVectorStore {
 impl VectorStore for MemoryVectorStore {
     async fn store_embeddings(&self, embeddings: &[Embedding]) -> Result<()> {
         let mut store = self.embeddings.write().unwrap();
+        let mut stats = self.stats.write().unwrap();
         for embedding in embeddings {
-            store.insert(embedding.chunk_id, embedding.clone());
+            if let Some(replaced) = store.insert(embedding.chunk_id, embedding.clone()) {
+                count_embedding(&mut stats, &replaced, false);
+            }
+            count_embedding(&mut stats, embedding, true);
         }
         Ok(())
     }
@@ -262,8 +459,8 @@ impl VectorStore for MemoryVectorStore {
             results.retain(|r| r.score >= threshold);
         }
 
-        // Sort by score descending
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        // Sort by score descending, ties by chunk ID
+        results.sort_by(ScoredResult::rank_cmp);
 
         // Take top k
         results.truncate(k);
@@ -278,8 +475,11 @@ impl VectorStore for MemoryVectorStore {
 
     async fn delete_embeddings(&self, chunk_ids: &[ChunkId]) -> Result<()> {
         let mut embeddings = self.embeddings.write().unwrap();
+        let mut stats = self.stats.write().unwrap();
         for chunk_id in chunk_ids {
-            embeddings.remove(chunk_id);
+            if let Some(removed) = embeddings.remove(chunk_id) {
+                count_embedding(&mut stats, &removed, false);
+            }
         }
         Ok(())
     }
@@ -288,12 +488,41 @@ impl VectorStore for MemoryVectorStore {
         let embeddings = self.embeddings.read().unwrap();
         Ok(embeddings.values().next().map(|e| e.vector.len()).unwrap_or(0))
     }
+
+    async fn list_embedding_ids(&self) -> Result<Vec<ChunkId>> {
+        let embeddings = self.embeddings.read().unwrap();
+        let mut ids: Vec<ChunkId> = embeddings.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        Ok(ids)
+    }
+
+    async fn compact(&self) -> Result<()> {
+        // Drop the capacity left behind by removed entries
+        self.embeddings.write().unwrap().shrink_to_fit();
+        Ok(())
+    }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        Ok(self.stats.read().unwrap().clone())
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let embeddings = self.embeddings.read().unwrap();
+        let mut recounted = IndexStats::new();
+        for embedding in embeddings.values() {
+            count_embedding(&mut recounted, embedding, true);
+        }
+        *self.stats.write().unwrap() = recounted.clone();
+        Ok(recounted)
+    }
 }
 
 /// In-memory implementation of DocumentStore
 #[derive(Debug, Clone, Default)]
 pub struct MemoryDocumentStore {
     chunks: Arc<RwLock<HashMap<ChunkId, TextChunk>>>,
+    /// Updated while `chunks` is locked for writing
+    stats: Arc<RwLock<IndexStats>>,
 }
 
 impl MemoryDocumentStore {
@@ -301,14 +530,25 @@ impl MemoryDocumentStore {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Overwrite the counters of a dataset without touching its chunks
+    ///
+    /// Lets tests simulate counters that drifted from the data.
+    pub fn set_index_stats(&self, dataset_id: DatasetId, stats: DatasetIndexStats) {
+        self.stats.write().unwrap().insert(dataset_id, stats);
+    }
 }
 
 #[async_trait]
 impl DocumentStore for MemoryDocumentStore {
     async fn store_chunks(&self, chunks: &[TextChunk]) -> Result<()> {
         let mut store = self.chunks.write().unwrap();
+        let mut stats = self.stats.write().unwrap();
         for chunk in chunks {
-            store.insert(chunk.id, chunk.clone());
+            if let Some(replaced) = store.insert(chunk.id, chunk.clone()) {
+                count_chunk(&mut stats, &replaced, false);
+            }
+            count_chunk(&mut stats, chunk, true);
         }
         Ok(())
     }
@@ -324,16 +564,45 @@ impl DocumentStore for MemoryDocumentStore {
     }
 
     async fn delete_chunks(&self, ids: &[ChunkId]) -> Result<()> {
+        let mut chunks = self.chunks.write().unwrap();
+        let mut stats = self.stats.write().unwrap();
+        for id in ids {
+            if let Some(removed) = chunks.remove(id) {
+                count_chunk(&mut stats, &removed, false);
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_chunks_stale(&self, ids: &[ChunkId]) -> Result<()> {
         let mut chunks = self.chunks.write().unwrap();
         for id in ids {
-            chunks.remove(id);
+            if let Some(chunk) = chunks.get_mut(id) {
+                chunk.metadata.stale = true;
+            }
         }
         Ok(())
     }
 
     async fn list_chunk_ids(&self) -> Result<Vec<ChunkId>> {
         let chunks = self.chunks.read().unwrap();
-        Ok(chunks.keys().copied().collect())
+        let mut ids: Vec<ChunkId> = chunks.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        Ok(ids)
+    }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        Ok(self.stats.read().unwrap().clone())
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let chunks = self.chunks.read().unwrap();
+        let mut recounted = IndexStats::new();
+        for chunk in chunks.values() {
+            count_chunk(&mut recounted, chunk, true);
+        }
+        *self.stats.write().unwrap() = recounted.clone();
+        Ok(recounted)
     }
 }
 
@@ -352,6 +621,8 @@ struct StoredWorkspace {
 pub struct MemoryWorkspaceStore {
     workspaces: Arc<RwLock<HashMap<WorkspaceId, StoredWorkspace>>>,
     workspace_datasets: Arc<RwLock<HashMap<WorkspaceId, HashMap<DatasetId, DatasetMeta>>>>,
+    settings: Arc<RwLock<HashMap<WorkspaceId, WorkspaceSettings>>>,
+    usage: Arc<RwLock<HashMap<WorkspaceId, WorkspaceUsage>>>,
 }
 
 impl MemoryWorkspaceStore {
@@ -397,7 +668,24 @@ impl WorkspaceStore for MemoryWorkspaceStore {
 
     async fn list_workspaces(&self) -> Result<Vec<WorkspaceMeta>> {
         let workspaces = self.workspaces.read().unwrap();
-        Ok(workspaces.values().map(|w| w.meta.clone()).collect())
+        let mut metas: Vec<WorkspaceMeta> = workspaces.values().map(|w| w.meta.clone()).collect();
+        metas.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(metas)
+    }
+
+    async fn update_workspace_config(
+        &self,
+        id: WorkspaceId,
+        config: &WorkspaceConfig,
+    ) -> Result<()> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        if let Some(workspace) = workspaces.get_mut(&id) {
+            workspace.meta.crs = config.crs;
+            workspace.meta.distance_unit = config.distance_unit;
+            workspace.meta.geometry_validity = config.geometry_validity;
+            workspace.config = config.clone();
+        }
+        Ok(())
     }
 
     async fn delete_workspace(&self, id: WorkspaceId) -> Result<()> {
@@ -406,6 +694,8 @@ impl WorkspaceStore for MemoryWorkspaceStore {
 
         workspaces.remove(&id);
         ws_datasets.remove(&id);
+        self.settings.write().unwrap().remove(&id);
+        self.usage.write().unwrap().remove(&id);
         Ok(())
     }
 
@@ -414,10 +704,12 @@ impl WorkspaceStore for MemoryWorkspaceStore {
         workspace_id: WorkspaceId,
     ) -> Result<Vec<DatasetMeta>> {
         let ws_datasets = self.workspace_datasets.read().unwrap();
-        Ok(ws_datasets
+        let mut metas: Vec<DatasetMeta> = ws_datasets
             .get(&workspace_id)
             .map(|datasets| datasets.values().cloned().collect())
-            .unwrap_or_default())
+            .unwrap_or_default();
+        sort_datasets(&mut metas, DatasetSort::Name, SortOrder::Asc);
+        Ok(metas)
     }
 
     async fn delete_dataset_in_workspace(
@@ -431,6 +723,401 @@ impl WorkspaceStore for MemoryWorkspaceStore {
         }
         Ok(())
     }
+
+    async fn get_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Option<WorkspaceSettings>> {
+        Ok(self.settings.read().unwrap().get(&workspace_id).cloned())
+    }
+
+    async fn save_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+        settings: &WorkspaceSettings,
+    ) -> Result<()> {
+        self.settings.write().unwrap().insert(workspace_id, settings.clone());
+        Ok(())
+    }
+
+    async fn get_workspace_usage(&self, workspace_id: WorkspaceId) -> Result<WorkspaceUsage> {
+        Ok(self.usage.read().unwrap().get(&workspace_id).copied().unwrap_or_default())
+    }
+
+    async fn adjust_workspace_usage(
+        &self,
+        workspace_id: WorkspaceId,
+        delta: &UsageDelta,
+    ) -> Result<WorkspaceUsage> {
+        let mut usage = self.usage.write().unwrap();
+        let counters = usage.entry(workspace_id).or_default();
+        counters.apply(delta);
+        Ok(*counters)
+    }
+}
+
+/// In-memory implementation of CheckpointStore
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCheckpointStore {
+    checkpoint: Arc<RwLock<Option<BuildCheckpoint>>>,
+}
+
+impl MemoryCheckpointStore {
+    /// Create a new in-memory checkpoint store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn save_checkpoint(&self, checkpoint: &BuildCheckpoint) -> Result<()> {
+        *self.checkpoint.write().unwrap() = Some(checkpoint.clone());
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self) -> Result<Option<BuildCheckpoint>> {
+        Ok(self.checkpoint.read().unwrap().clone())
+    }
+
+    async fn clear_checkpoint(&self) -> Result<()> {
+        *self.checkpoint.write().unwrap() = None;
+        Ok(())
+    }
+}
+
+/// In-memory implementation of BlobStore
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBlobStore {
+    blobs: Arc<RwLock<HashMap<DatasetId, Vec<u8>>>>,
+}
+
+impl MemoryBlobStore {
+    /// Create a new in-memory blob store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlobStore for MemoryBlobStore {
+    async fn put_blob(&self, dataset_id: DatasetId, content: &[u8]) -> Result<()> {
+        self.blobs.write().unwrap().insert(dataset_id, content.to_vec());
+        Ok(())
+    }
+
+    async fn get_blob(&self, dataset_id: DatasetId) -> Result<Option<Vec<u8>>> {
+        Ok(self.blobs.read().unwrap().get(&dataset_id).cloned())
+    }
+
+    async fn delete_blob(&self, dataset_id: DatasetId) -> Result<()> {
+        self.blobs.write().unwrap().remove(&dataset_id);
+        Ok(())
+    }
+}
+
+/// In-memory implementation of AreaStore
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAreaStore {
+    areas: Arc<RwLock<HashMap<(WorkspaceId, String), SavedArea>>>,
+}
+
+impl MemoryAreaStore {
+    /// Create a new in-memory area store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AreaStore for MemoryAreaStore {
+    async fn create_area(&self, workspace_id: WorkspaceId, area: &SavedArea) -> Result<bool> {
+        let mut areas = self.areas.write().unwrap();
+        let key = (workspace_id, area.name.clone());
+        if areas.contains_key(&key) {
+            return Ok(false);
+        }
+        areas.insert(key, area.clone());
+        Ok(true)
+    }
+
+    async fn get_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<Option<SavedArea>> {
+        Ok(self.areas.read().unwrap().get(&(workspace_id, name.to_string())).cloned())
+    }
+
+    async fn list_areas(&self, workspace_id: WorkspaceId) -> Result<Vec<SavedArea>> {
+        let mut areas: Vec<SavedArea> = self
+            .areas
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|((workspace, _), _)| *workspace == workspace_id)
+            .map(|(_, area)| area.clone())
+            .collect();
+        areas.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(areas)
+    }
+
+    async fn delete_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<bool> {
+        Ok(self.areas.write().unwrap().remove(&(workspace_id, name.to_string())).is_some())
+    }
+}
+
+/// In-memory implementation of AuditStore
+///
+/// Events older than the longest timeline are dropped as new ones arrive,
+/// and counts are aggregated in memory by the port's default methods.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryAuditStore {
+    events: Arc<RwLock<HashMap<WorkspaceId, Vec<AuditEvent>>>>,
+}
+
+impl MemoryAuditStore {
+    /// Create a new in-memory audit store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AuditStore for MemoryAuditStore {
+    async fn record_event(&self, workspace_id: WorkspaceId, event: &AuditEvent) -> Result<()> {
+        let oldest = Utc::now() - Duration::days(MAX_TIMELINE_DAYS);
+        let mut events = self.events.write().unwrap();
+        let events = events.entry(workspace_id).or_default();
+        events.retain(|event| event.at >= oldest);
+        events.push(event.clone());
+        Ok(())
+    }
+
+    async fn list_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AuditEvent>> {
+        let mut events: Vec<AuditEvent> = self
+            .events
+            .read()
+            .unwrap()
+            .get(&workspace_id)
+            .map(|events| events.iter().filter(|event| event.at >= since).cloned().collect())
+            .unwrap_or_default();
+        events.sort_by_key(|event| event.at);
+        Ok(events)
+    }
+}
+
+/// In-memory implementation of EventLogStore
+///
+/// A ring buffer: once it holds `capacity` events, each append drops the
+/// oldest. Clones share the log, so one log can stand in for a shared backend.
+#[derive(Debug, Clone)]
+pub struct MemoryEventLog {
+    log: Arc<RwLock<EventLog>>,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct EventLog {
+    events: VecDeque<ChangeEvent>,
+    last_seq: u64,
+    cursors: HashMap<String, u64>,
+}
+
+impl EventLog {
+    /// Drop the `count` oldest events
+    fn drop_oldest(&mut self, count: usize) -> u64 {
+        let count = count.min(self.events.len());
+        self.events.drain(..count);
+        count as u64
+    }
+}
+
+impl MemoryEventLog {
+    /// Create a new in-memory event log keeping the default number of events
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_RETENTION_EVENTS)
+    }
+
+    /// Create a new in-memory event log keeping at most `capacity` events
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            log: Arc::new(RwLock::new(EventLog::default())),
+            capacity: capacity.max(1),
+        }
+    }
+}
+
+impl Default for MemoryEventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventLogStore for MemoryEventLog {
+    async fn append_events(&self, events: &[ChangeEvent]) -> Result<u64> {
+        let mut log = self.log.write().unwrap();
+        for event in events {
+            log.last_seq += 1;
+            let mut event = event.clone();
+            event.seq = log.last_seq;
+            log.events.push_back(event);
+        }
+        let excess = log.events.len().saturating_sub(self.capacity);
+        log.drop_oldest(excess);
+        Ok(log.last_seq)
+    }
+
+    async fn events_after(&self, after_seq: u64, limit: usize) -> Result<Vec<ChangeEvent>> {
+        let log = self.log.read().unwrap();
+        // Sequence numbers are consecutive, so the position follows from the first
+        let skip = match log.events.front() {
+            Some(first) => after_seq.saturating_sub(first.seq - 1),
+            None => return Ok(Vec::new()),
+        };
+        Ok(log.events.iter().skip(skip as usize).take(limit).cloned().collect())
+    }
+
+    async fn event_bounds(&self) -> Result<EventLogBounds> {
+        let log = self.log.read().unwrap();
+        Ok(EventLogBounds {
+            first: log.events.front().map(|event| event.seq),
+            last: log.last_seq,
+        })
+    }
+
+    async fn prune_events(&self, retention: &EventRetention) -> Result<u64> {
+        let oldest = retention.oldest(Utc::now());
+        let mut log = self.log.write().unwrap();
+        // Through the newest event past its age, like the PostgreSQL store
+        let aged = oldest.map_or(0, |oldest| {
+            log.events
+                .iter()
+                .rposition(|event| event.at < oldest)
+                .map_or(0, |last| last + 1)
+        });
+        let excess = log.events.len().saturating_sub(retention.max_events);
+        Ok(log.drop_oldest(aged.max(excess)))
+    }
+
+    async fn get_event_cursor(&self, consumer: &str) -> Result<Option<u64>> {
+        Ok(self.log.read().unwrap().cursors.get(consumer).copied())
+    }
+
+    async fn set_event_cursor(&self, consumer: &str, seq: u64) -> Result<()> {
+        self.log.write().unwrap().cursors.insert(consumer.to_string(), seq);
+        Ok(())
+    }
+}
+
+/// In-memory implementation of WorkspaceStoreProvider
+///
+/// Each workspace gets its own memory stores. With a blob directory, each
+/// workspace keeps its original files on disk instead, in a directory named
+/// after its ID under the blob directory.
+#[derive(Clone, Default)]
+pub struct MemoryStoreProvider {
+    workspaces: Arc<RwLock<HashMap<WorkspaceId, WorkspaceStores>>>,
+    blob_dir: Option<PathBuf>,
+}
+
+impl MemoryStoreProvider {
+    /// Create a new in-memory store provider
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the original files of each workspace in its own directory under `dir`
+    pub fn with_blob_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.blob_dir = Some(dir.into());
+        self
+    }
+
+    fn blob_store(&self, workspace_id: WorkspaceId) -> Arc<dyn BlobStore> {
+        match &self.blob_dir {
+            Some(dir) => Arc::new(FilesystemBlobStore::new(dir.join(workspace_id.to_string()))),
+            None => Arc::new(MemoryBlobStore::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl WorkspaceStoreProvider for MemoryStoreProvider {
+    async fn open_workspace(&self, workspace_id: WorkspaceId) -> Result<WorkspaceStores> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let stores = workspaces.entry(workspace_id).or_insert_with(|| WorkspaceStores {
+            spatial: Arc::new(MemorySpatialStore::new()),
+            vector: Arc::new(MemoryVectorStore::new()),
+            document: Arc::new(MemoryDocumentStore::new()),
+            blob: self.blob_store(workspace_id),
+        });
+        Ok(stores.clone())
+    }
+
+    async fn drop_workspace(&self, workspace_id: WorkspaceId) -> Result<()> {
+        self.workspaces.write().unwrap().remove(&workspace_id);
+        if let Some(dir) = &self.blob_dir {
+            match tokio::fs::remove_dir_all(dir.join(workspace_id.to_string())).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// In-memory implementation of SlotStore
+///
+/// Shares slots between the holders of clones of one store, e.g. to stand
+/// in for a shared backend in tests.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySlotStore {
+    held: Arc<RwLock<HashMap<ResourceKind, usize>>>,
+}
+
+impl MemorySlotStore {
+    /// Create a new in-memory slot store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of slots of `kind` held
+    pub fn held(&self, kind: ResourceKind) -> usize {
+        self.held.read().unwrap().get(&kind).copied().unwrap_or(0)
+    }
+}
+
+/// Slot of a memory slot store, released on drop
+struct MemorySlot {
+    held: Arc<RwLock<HashMap<ResourceKind, usize>>>,
+    kind: ResourceKind,
+}
+
+impl Drop for MemorySlot {
+    fn drop(&mut self) {
+        if let Some(count) = self.held.write().unwrap().get_mut(&self.kind) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+#[async_trait]
+impl SlotStore for MemorySlotStore {
+    async fn try_acquire_slot(
+        &self,
+        kind: ResourceKind,
+        limit: usize,
+    ) -> Result<Option<ResourceGuard>> {
+        let mut held = self.held.write().unwrap();
+        let count = held.entry(kind).or_insert(0);
+        if *count >= limit {
+            return Ok(None);
+        }
+        *count += 1;
+        Ok(Some(ResourceGuard::new(MemorySlot { held: self.held.clone(), kind })))
+    }
 }
 
 #[cfg(test)]
@@ -455,8 +1142,15 @@ mod tests {
                 paragraph_count: None,
                 extraction_method: None,
                 spatial_association: None,
+                axis_order: None,
+                source: None,
+                preview: None,
+                remote: None,
             },
             added_at: Utc::now(),
+            tags: Vec::new(),
+            license: None,
+            attribution: None,
         }
     }
 
@@ -530,4 +1224,124 @@ mod tests {
         let id = store.store_dataset(&next_dataset).await.unwrap();
         assert_eq!(id.0, 1); // Should be 1, not 3
     }
+
+    #[tokio::test]
+    async fn test_list_visible_datasets_filters_by_tags() {
+        let store = MemorySpatialStore::new();
+
+        let public = store.store_dataset(&create_test_dataset("public")).await.unwrap();
+        let restricted = store.store_dataset(&create_test_dataset("restricted")).await.unwrap();
+        store.store_dataset(&create_test_dataset("untagged")).await.unwrap();
+
+        store.set_dataset_tags(public, &["public".to_string()]).await.unwrap();
+        store.set_dataset_tags(restricted, &["restricted".to_string()]).await.unwrap();
+
+        let mut visible: Vec<String> = store
+            .list_visible_datasets(&TagVisibility::only(["public"]))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        visible.sort();
+        assert_eq!(visible, vec!["public", "untagged"]);
+
+        assert_eq!(store.list_datasets().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_sample_features_stays_within_dataset() {
+        let store = MemorySpatialStore::new();
+        let id = store.store_dataset(&create_test_dataset("points")).await.unwrap();
+        let features: Vec<Feature> = (0..30)
+            .map(|i| {
+                Feature::with_geometry(
+                    FeatureId(i),
+                    georag_core::models::Geometry::point(i as f64, 0.0),
+                    HashMap::new(),
+                    4326,
+                )
+            })
+            .collect();
+        store.upsert_dataset_features(id, &features).await.unwrap();
+
+        for strategy in [SampleStrategy::Random, SampleStrategy::Spatial] {
+            let sample = store.sample_features(id, 8, strategy).await.unwrap();
+            assert_eq!(sample.len(), 8);
+        }
+        assert_eq!(store.sample_features(id, 100, SampleStrategy::Random).await.unwrap().len(), 30);
+        assert!(store
+            .sample_features(DatasetId(9), 5, SampleStrategy::Spatial)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_set_tags_on_missing_dataset_fails() {
+        let store = MemorySpatialStore::new();
+        assert!(store.set_dataset_tags(DatasetId(42), &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_settings_roundtrip() {
+        use georag_core::models::workspace::{DistanceUnit, ValidityMode};
+
+        let store = MemoryWorkspaceStore::new();
+        let config = WorkspaceConfig {
+            crs: 4326,
+            distance_unit: DistanceUnit::Meters,
+            geometry_validity: ValidityMode::Lenient,
+        };
+        let id = store.create_workspace("city", &config).await.unwrap();
+        assert!(store.get_workspace_settings(id).await.unwrap().is_none());
+
+        let settings = WorkspaceSettings {
+            embedder: Some("ollama:mxbai-embed-large".to_string()),
+            ..WorkspaceSettings::from_workspace_config(&config)
+        };
+        store.save_workspace_settings(id, &settings).await.unwrap();
+        assert_eq!(store.get_workspace_settings(id).await.unwrap(), Some(settings));
+
+        store.delete_workspace(id).await.unwrap();
+        assert!(store.get_workspace_settings(id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_provider_keeps_workspaces_apart() {
+        let provider = MemoryStoreProvider::new();
+        let (a, b) = (WorkspaceId::new(), WorkspaceId::new());
+
+        let stores = provider.open_workspace(a).await.unwrap();
+        stores.spatial.store_dataset(&create_test_dataset("roads")).await.unwrap();
+
+        // The same workspace gets the same stores back; another starts empty
+        let reopened = provider.open_workspace(a).await.unwrap();
+        assert_eq!(reopened.spatial.list_datasets().await.unwrap().len(), 1);
+        let other = provider.open_workspace(b).await.unwrap();
+        assert!(other.spatial.list_datasets().await.unwrap().is_empty());
+
+        provider.drop_workspace(a).await.unwrap();
+        let recreated = provider.open_workspace(a).await.unwrap();
+        assert!(recreated.spatial.list_datasets().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_provider_keeps_blobs_of_each_workspace_in_its_own_directory() {
+        let dir =
+            std::env::temp_dir().join(format!("georag-provider-blobs-{}", WorkspaceId::new()));
+        let provider = MemoryStoreProvider::new().with_blob_dir(&dir);
+        let (a, b) = (WorkspaceId::new(), WorkspaceId::new());
+
+        let stores = provider.open_workspace(a).await.unwrap();
+        stores.blob.put_blob(DatasetId(1), b"roads").await.unwrap();
+        assert_eq!(std::fs::read(dir.join(a.to_string()).join("1")).unwrap(), b"roads");
+
+        let other = provider.open_workspace(b).await.unwrap();
+        assert!(other.blob.get_blob(DatasetId(1)).await.unwrap().is_none());
+
+        provider.drop_workspace(a).await.unwrap();
+        assert!(!dir.join(a.to_string()).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,9 +1,19 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use georag_core::config::WorkspaceSettings;
 use georag_core::error::Result;
+use georag_core::geo::{JoinCounts, PreparedFilter, SampleStrategy, SpatialJoin};
 use georag_core::models::{
-    ChunkId, Dataset, DatasetId, DatasetMeta, Embedding, Feature, FeatureId, ScoredResult,
-    SpatialFilter, TextChunk, WorkspaceConfig, WorkspaceId, WorkspaceMeta,
+    count_events, AuditEvent, BuildCheckpoint, ChangeEvent, ChunkId, Dataset, DatasetId,
+    DatasetIndexStats, DatasetMeta, DatasetPreview, Embedding, EventCount, EventLogBounds,
+    EventRetention, Feature, FeatureId, Geometry, SavedArea, ScoredResult, SpatialFilter,
+    TagVisibility, TextChunk, TimelineGranularity, UsageDelta, WorkspaceConfig, WorkspaceId,
+    WorkspaceMeta, WorkspaceUsage,
 };
+use georag_core::resources::{ResourceGuard, ResourceKind};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Port for workspace management operations
 #[async_trait]
@@ -14,13 +24,20 @@ pub trait WorkspaceStore: Send + Sync {
     /// Get workspace by ID
     async fn get_workspace(&self, id: WorkspaceId) -> Result<Option<WorkspaceMeta>>;
 
-    /// List all workspaces
+    /// List all workspaces, sorted by name
     async fn list_workspaces(&self) -> Result<Vec<WorkspaceMeta>>;
 
+    /// Replace the CRS, distance unit and validity mode of a workspace
+    async fn update_workspace_config(
+        &self,
+        id: WorkspaceId,
+        config: &WorkspaceConfig,
+    ) -> Result<()>;
+
     /// Delete a workspace and all its data
     async fn delete_workspace(&self, id: WorkspaceId) -> Result<()>;
 
-    /// List datasets for a specific workspace
+    /// List datasets for a specific workspace, sorted by name and then ID
     async fn list_datasets_for_workspace(
         &self,
         workspace_id: WorkspaceId,
@@ -32,6 +49,32 @@ pub trait WorkspaceStore: Send + Sync {
         workspace_id: WorkspaceId,
         dataset_id: DatasetId,
     ) -> Result<()>;
+
+    /// Load the settings stored for a workspace, if any were saved
+    async fn get_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Option<WorkspaceSettings>>;
+
+    /// Replace the settings stored for a workspace
+    async fn save_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+        settings: &WorkspaceSettings,
+    ) -> Result<()>;
+
+    /// Usage counted against the workspace's quotas (zero when nothing was recorded)
+    async fn get_workspace_usage(&self, workspace_id: WorkspaceId) -> Result<WorkspaceUsage>;
+
+    /// Add a change to the workspace's usage counters and return the new usage
+    ///
+    /// Counters are updated in place rather than recomputed from the data;
+    /// they never drop below zero.
+    async fn adjust_workspace_usage(
+        &self,
+        workspace_id: WorkspaceId,
+        delta: &UsageDelta,
+    ) -> Result<WorkspaceUsage>;
 }
 
 /// Port for spatial data storage operations
@@ -43,23 +86,112 @@ pub trait SpatialStore: Send + Sync {
     /// Retrieve a dataset by ID
     async fn get_dataset(&self, id: DatasetId) -> Result<Option<Dataset>>;
 
-    /// List all dataset metadata
+    /// List all dataset metadata, sorted by name and then ID
     async fn list_datasets(&self) -> Result<Vec<DatasetMeta>>;
 
+    /// List metadata of the datasets visible with the given access tags, sorted by name and then ID
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>>;
+
+    /// Replace the access tags of a dataset
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()>;
+
+    /// Replace the content preview kept in a dataset's metadata
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()>;
+
     /// Delete a dataset
     async fn delete_dataset(&self, id: DatasetId) -> Result<()>;
 
     /// Store spatial features
     async fn store_features(&self, features: &[Feature]) -> Result<()>;
 
-    /// Query features using spatial filter
+    /// Query features using spatial filter, sorted by feature ID
     async fn spatial_query(&self, filter: &SpatialFilter) -> Result<Vec<Feature>>;
 
+    /// Query features using a spatial filter prepared beforehand, sorted by feature ID
+    ///
+    /// Stores evaluating filters in process use the prepared filter instead
+    /// of preparing it again; others run `spatial_query` with its filter.
+    async fn spatial_query_prepared(&self, prepared: &PreparedFilter) -> Result<Vec<Feature>> {
+        self.spatial_query(prepared.filter()).await
+    }
+
     /// Get a specific feature by ID
     async fn get_feature(&self, id: FeatureId) -> Result<Option<Feature>>;
 
-    /// Get all features for a specific dataset
+    /// Get the features with the given IDs, sorted by feature ID
+    ///
+    /// IDs without a stored feature are left out. Stores that can fetch
+    /// several features in one round trip override the default, which looks
+    /// them up one at a time.
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        let mut features = Vec::with_capacity(ids.len());
+        for id in distinct_feature_ids(ids) {
+            if let Some(feature) = self.get_feature(id).await? {
+                features.push(feature);
+            }
+        }
+        Ok(features)
+    }
+
+    /// Get all features for a specific dataset, sorted by feature ID
     async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>>;
+
+    /// Replace the properties of existing features, matched by ID
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()>;
+
+    /// Insert or replace features as members of a dataset
+    ///
+    /// Features with an existing ID have their geometry and properties
+    /// replaced; new IDs are added to the dataset.
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()>;
+
+    /// Delete features of a dataset by ID
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()>;
+
+    /// Pick up to `n` features of a dataset for previewing
+    ///
+    /// Samples differ between calls. Implementations must not read every
+    /// feature of a very large dataset to draw a small sample.
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>>;
+
+    /// Run a spatial join inside the store, copying source properties onto targets
+    ///
+    /// Returns `None` when the store cannot evaluate the join itself; callers
+    /// then compute it with [`SpatialJoin::apply`] and write the result back
+    /// with `update_feature_properties`.
+    async fn spatial_join(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>>;
+
+    /// Cut a geometry into parts of at most `max_vertices` vertices inside the store
+    ///
+    /// Returns `None` when the store has no subdivision of its own; callers
+    /// then use [`subdivide`](georag_core::geo::subdivide).
+    async fn subdivide_geometry(
+        &self,
+        geometry: &Geometry,
+        max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>>;
+}
+
+/// Feature IDs sorted and without duplicates, as batched lookups take them
+pub fn distinct_feature_ids(ids: &[FeatureId]) -> Vec<FeatureId> {
+    let mut ids = ids.to_vec();
+    ids.sort_by_key(|id| id.0);
+    ids.dedup();
+    ids
 }
 
 /// Port for vector storage and similarity search
@@ -69,7 +201,9 @@ pub trait VectorStore: Send + Sync {
     async fn store_embeddings(&self, embeddings: &[Embedding]) -> Result<()>;
 
     /// Perform similarity search
-    /// Returns the top k most similar embeddings to the query vector
+    /// Returns the top k most similar embeddings to the query vector, highest
+    /// score first and equal scores by ascending chunk ID (see
+    /// [`ScoredResult::rank_cmp`]), so ties are cut off the same way every time.
     /// If threshold is provided, only returns results with similarity >= threshold
     async fn similarity_search(
         &self,
@@ -86,6 +220,21 @@ pub trait VectorStore: Send + Sync {
 
     /// Get the dimensionality of stored vectors
     async fn dimensions(&self) -> Result<usize>;
+
+    /// List the chunk IDs of all stored embeddings, in ascending order
+    async fn list_embedding_ids(&self) -> Result<Vec<ChunkId>>;
+
+    /// Release storage held by deleted embeddings
+    async fn compact(&self) -> Result<()>;
+
+    /// Embedding counters of every dataset with embeddings
+    ///
+    /// Only `embeddings` is set; chunks are counted by the document store.
+    /// Counters are kept as embeddings are written, so this never scans.
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>>;
+
+    /// Rebuild the embedding counters from the stored embeddings and return them
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>>;
 }
 
 /// Port for document chunk storage
@@ -94,7 +243,7 @@ pub trait DocumentStore: Send + Sync {
     /// Store text chunks
     async fn store_chunks(&self, chunks: &[TextChunk]) -> Result<()>;
 
-    /// Retrieve chunks by IDs
+    /// Retrieve chunks by IDs, in the order the IDs are given
     async fn get_chunks(&self, ids: &[ChunkId]) -> Result<Vec<TextChunk>>;
 
     /// Get a single chunk by ID
@@ -103,8 +252,203 @@ pub trait DocumentStore: Send + Sync {
     /// Delete chunks by IDs
     async fn delete_chunks(&self, ids: &[ChunkId]) -> Result<()>;
 
-    /// List all chunk IDs
+    /// Flag chunks as stale so retrieval leaves them out, see `ChunkMetadata::stale`
+    async fn mark_chunks_stale(&self, ids: &[ChunkId]) -> Result<()>;
+
+    /// List all chunk IDs, in ascending order
     async fn list_chunk_ids(&self) -> Result<Vec<ChunkId>>;
+
+    /// Chunk counters of every dataset with chunks
+    ///
+    /// Only `chunks` and `content_bytes` are set; embeddings are counted by
+    /// the vector store. Counters are kept as chunks are written, so this
+    /// never scans.
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>>;
+
+    /// Rebuild the chunk counters from the stored chunks and return them
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>>;
+}
+
+/// Port for index build checkpoints
+///
+/// Holds at most one checkpoint: the one of the build in progress.
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Save the checkpoint, replacing any previous one
+    async fn save_checkpoint(&self, checkpoint: &BuildCheckpoint) -> Result<()>;
+
+    /// Load the checkpoint of an interrupted build
+    async fn load_checkpoint(&self) -> Result<Option<BuildCheckpoint>>;
+
+    /// Remove the checkpoint once the build completes
+    async fn clear_checkpoint(&self) -> Result<()>;
+}
+
+/// Port for the original files of datasets
+///
+/// Holds at most one blob per dataset, addressed by dataset ID.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Store the original file of a dataset, replacing any previous one
+    async fn put_blob(&self, dataset_id: DatasetId, content: &[u8]) -> Result<()>;
+
+    /// Load the original file of a dataset, if one is stored
+    async fn get_blob(&self, dataset_id: DatasetId) -> Result<Option<Vec<u8>>>;
+
+    /// Remove the original file of a dataset; missing blobs are not an error
+    async fn delete_blob(&self, dataset_id: DatasetId) -> Result<()>;
+}
+
+/// Port for saved areas: named geometries reusable as query filters
+///
+/// Area names are unique within a workspace and stored already normalized.
+#[async_trait]
+pub trait AreaStore: Send + Sync {
+    /// Save a new area; returns false, storing nothing, when the name is taken
+    async fn create_area(&self, workspace_id: WorkspaceId, area: &SavedArea) -> Result<bool>;
+
+    /// Load an area by name
+    async fn get_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<Option<SavedArea>>;
+
+    /// List the areas of a workspace, sorted by name
+    async fn list_areas(&self, workspace_id: WorkspaceId) -> Result<Vec<SavedArea>>;
+
+    /// Remove an area; returns whether one was removed
+    async fn delete_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<bool>;
+}
+
+/// Port for the audit events of workspaces: what was added, built and queried
+///
+/// Stores only need to record and list events; those that can aggregate
+/// them without listing (e.g. with SQL) override the counting methods.
+#[async_trait]
+pub trait AuditStore: Send + Sync {
+    /// Record an event of a workspace
+    async fn record_event(&self, workspace_id: WorkspaceId, event: &AuditEvent) -> Result<()>;
+
+    /// Events of a workspace at or after `since`, oldest first
+    async fn list_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AuditEvent>>;
+
+    /// Events at or after `since` counted per bucket and kind, sorted by bucket and then kind
+    async fn count_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+        granularity: TimelineGranularity,
+    ) -> Result<Vec<EventCount>> {
+        Ok(count_events(&self.list_events(workspace_id, since).await?, granularity))
+    }
+
+    /// Notable events at or after `since`, newest first and at most `limit`
+    async fn notable_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        let mut events = self.list_events(workspace_id, since).await?;
+        events.retain(|event| event.kind.is_notable());
+        events.reverse();
+        events.truncate(limit);
+        Ok(events)
+    }
+}
+
+/// Port for the ordered log of changes read by downstream consumers
+///
+/// Appended events get consecutive sequence numbers, starting at 1, in the
+/// order their appends complete; a failed append takes none. Pruning drops
+/// the oldest events only, so the events kept never have gaps.
+#[async_trait]
+pub trait EventLogStore: Send + Sync {
+    /// Append events in order, returning the sequence number of the last one
+    ///
+    /// The events get consecutive sequence numbers, whatever `seq` they carry.
+    async fn append_events(&self, events: &[ChangeEvent]) -> Result<u64>;
+
+    /// Events after `after_seq`, oldest first and at most `limit`
+    async fn events_after(&self, after_seq: u64, limit: usize) -> Result<Vec<ChangeEvent>>;
+
+    /// Oldest event kept and newest event appended
+    async fn event_bounds(&self) -> Result<EventLogBounds>;
+
+    /// Drop the oldest events beyond the retention, returning how many were dropped
+    async fn prune_events(&self, retention: &EventRetention) -> Result<u64>;
+
+    /// Sequence number a consumer last acknowledged
+    async fn get_event_cursor(&self, consumer: &str) -> Result<Option<u64>>;
+
+    /// Record the sequence number a consumer acknowledged
+    async fn set_event_cursor(&self, consumer: &str, seq: u64) -> Result<()>;
+}
+
+/// Stores holding the data of one workspace
+#[derive(Clone)]
+pub struct WorkspaceStores {
+    pub spatial: Arc<dyn SpatialStore>,
+    pub vector: Arc<dyn VectorStore>,
+    pub document: Arc<dyn DocumentStore>,
+    pub blob: Arc<dyn BlobStore>,
+}
+
+/// Port for stores that keep each workspace's data apart
+///
+/// Datasets, chunks, embeddings and original files stored through one
+/// workspace's stores are never visible through another's.
+#[async_trait]
+pub trait WorkspaceStoreProvider: Send + Sync {
+    /// Stores of a workspace, created empty on first use
+    async fn open_workspace(&self, workspace_id: WorkspaceId) -> Result<WorkspaceStores>;
+
+    /// Drop a workspace's stores and everything in them
+    async fn drop_workspace(&self, workspace_id: WorkspaceId) -> Result<()>;
+}
+
+/// Port for backends whose reachability can be checked
+///
+/// Checked periodically by the API server, which stops writing to a backend
+/// that fails its checks until they pass again.
+#[async_trait]
+pub trait HealthProbe: Send + Sync {
+    /// Name of the backend, e.g. `postgres`
+    fn backend(&self) -> &str;
+
+    /// Fail when the backend cannot serve requests
+    async fn health_check(&self) -> Result<()>;
+}
+
+/// Port for slots of the instance-wide limits shared by processes
+///
+/// Lets the API server and CLI runs against one backend cap concurrent
+/// operations together. A slot is released when its guard is dropped, also
+/// when the process holding it dies.
+#[async_trait]
+pub trait SlotStore: Send + Sync {
+    /// Take one of `limit` slots of `kind`, or `None` while all are held
+    async fn try_acquire_slot(
+        &self,
+        kind: ResourceKind,
+        limit: usize,
+    ) -> Result<Option<ResourceGuard>>;
+
+    /// Wait for one of `limit` slots of `kind`, checking again every `poll`
+    async fn acquire_slot(
+        &self,
+        kind: ResourceKind,
+        limit: usize,
+        poll: Duration,
+    ) -> Result<ResourceGuard> {
+        loop {
+            if let Some(guard) = self.try_acquire_slot(kind, limit).await? {
+                return Ok(guard);
+            }
+            tokio::time::sleep(poll).await;
+        }
+    }
 }
 
 /// Transaction handler
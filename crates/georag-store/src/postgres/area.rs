@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::{AreaSource, DatasetId, FeatureId, SavedArea, WorkspaceId};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use uuid::Uuid;
+
+use super::PostgresStore;
+use crate::ports::AreaStore;
+
+#[async_trait]
+impl AreaStore for PostgresStore {
+    async fn create_area(&self, workspace_id: WorkspaceId, area: &SavedArea) -> Result<bool> {
+        let geometry = serde_json::to_value(&area.geometry).map_err(|e| {
+            GeoragError::Serialization(format!("Failed to serialize area geometry: {}", e))
+        })?;
+        let (dataset_uuid, feature_uuid) = match area.source {
+            Some(source) => (
+                Some(Uuid::from_u128(source.dataset_id.0 as u128)),
+                Some(Uuid::from_u128(source.feature_id.0 as u128)),
+            ),
+            None => (None, None),
+        };
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO saved_areas
+                (workspace_id, name, geometry, source_dataset_id, source_feature_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (workspace_id, name) DO NOTHING
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(&area.name)
+        .bind(geometry)
+        .bind(dataset_uuid)
+        .bind(feature_uuid)
+        .bind(area.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to store area: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<Option<SavedArea>> {
+        let row = sqlx::query(
+            r#"
+            SELECT name, geometry, source_dataset_id, source_feature_id, created_at
+            FROM saved_areas
+            WHERE workspace_id = $1 AND name = $2
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to load area: {}", e)))?;
+
+        row.map(|row| area_from_row(&row)).transpose()
+    }
+
+    async fn list_areas(&self, workspace_id: WorkspaceId) -> Result<Vec<SavedArea>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT name, geometry, source_dataset_id, source_feature_id, created_at
+            FROM saved_areas
+            WHERE workspace_id = $1
+            ORDER BY name
+            "#,
+        )
+        .bind(workspace_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to list areas: {}", e)))?;
+
+        rows.iter().map(area_from_row).collect()
+    }
+
+    async fn delete_area(&self, workspace_id: WorkspaceId, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM saved_areas WHERE workspace_id = $1 AND name = $2")
+            .bind(workspace_id.0)
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to delete area: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn area_from_row(row: &PgRow) -> Result<SavedArea> {
+    let geometry: serde_json::Value = row.get("geometry");
+    let geometry = serde_json::from_value(geometry)
+        .map_err(|e| GeoragError::Serialization(format!("Failed to parse area geometry: {}", e)))?;
+    let dataset_uuid: Option<Uuid> = row.get("source_dataset_id");
+    let feature_uuid: Option<Uuid> = row.get("source_feature_id");
+    let source = match (dataset_uuid, feature_uuid) {
+        (Some(dataset), Some(feature)) => Some(AreaSource {
+            dataset_id: DatasetId(dataset.as_u128() as u64),
+            feature_id: FeatureId(feature.as_u128() as u64),
+        }),
+        _ => None,
+    };
+    let created_at: DateTime<Utc> = row.get("created_at");
+
+    Ok(SavedArea {
+        name: row.get("name"),
+        geometry,
+        source,
+        created_at,
+    })
+}
@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::{
+    AuditEvent, AuditEventKind, EventCount, TimelineGranularity, WorkspaceId,
+};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+
+use super::PostgresStore;
+use crate::ports::AuditStore;
+
+/// Kinds listed by `notable_events`, matching the partial index on the table
+fn notable_kinds() -> Vec<String> {
+    AuditEventKind::ALL
+        .into_iter()
+        .filter(AuditEventKind::is_notable)
+        .map(|kind| kind.as_str().to_string())
+        .collect()
+}
+
+#[async_trait]
+impl AuditStore for PostgresStore {
+    async fn record_event(&self, workspace_id: WorkspaceId, event: &AuditEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_events (workspace_id, kind, occurred_at, details)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(event.kind.as_str())
+        .bind(event.at)
+        .bind(&event.details)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to record audit event: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn list_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AuditEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT kind, occurred_at, details
+            FROM audit_events
+            WHERE workspace_id = $1 AND occurred_at >= $2
+            ORDER BY occurred_at, id
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to list audit events: {}", e)))?;
+
+        rows.iter().map(event_from_row).collect()
+    }
+
+    async fn count_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+        granularity: TimelineGranularity,
+    ) -> Result<Vec<EventCount>> {
+        // Buckets start at UTC boundaries, like TimelineGranularity::bucket_start
+        let rows = sqlx::query(
+            r#"
+            SELECT date_trunc($3, occurred_at AT TIME ZONE 'UTC') AS bucket, kind, COUNT(*) AS count
+            FROM audit_events
+            WHERE workspace_id = $1 AND occurred_at >= $2
+            GROUP BY bucket, kind
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(since)
+        .bind(granularity.as_str())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to count audit events: {}", e)))?;
+
+        let mut counts = rows
+            .iter()
+            .map(|row| {
+                let bucket: NaiveDateTime = row.get("bucket");
+                let count: i64 = row.get("count");
+                Ok(EventCount {
+                    bucket: bucket.and_utc(),
+                    kind: parse_kind(row.get("kind"))?,
+                    count: count as u64,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        counts.sort_by_key(|count| (count.bucket, count.kind));
+        Ok(counts)
+    }
+
+    async fn notable_events(
+        &self,
+        workspace_id: WorkspaceId,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<AuditEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT kind, occurred_at, details
+            FROM audit_events
+            WHERE workspace_id = $1 AND occurred_at >= $2 AND kind = ANY($3)
+            ORDER BY occurred_at DESC, id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(since)
+        .bind(notable_kinds())
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to list notable audit events: {}", e))
+        })?;
+
+        rows.iter().map(event_from_row).collect()
+    }
+}
+
+fn parse_kind(kind: &str) -> Result<AuditEventKind> {
+    kind.parse().map_err(GeoragError::Serialization)
+}
+
+fn event_from_row(row: &PgRow) -> Result<AuditEvent> {
+    Ok(AuditEvent {
+        kind: parse_kind(row.get("kind"))?,
+        at: row.get("occurred_at"),
+        details: row.get("details"),
+    })
+}
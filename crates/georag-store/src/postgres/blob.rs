@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::DatasetId;
+use uuid::Uuid;
+
+use super::PostgresStore;
+use crate::ports::BlobStore;
+
+#[async_trait]
+impl BlobStore for PostgresStore {
+    async fn put_blob(&self, dataset_id: DatasetId, content: &[u8]) -> Result<()> {
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+
+        sqlx::query(
+            r#"
+            INSERT INTO dataset_blobs (dataset_id, content, created_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (dataset_id) DO UPDATE
+            SET content = EXCLUDED.content,
+                created_at = EXCLUDED.created_at
+            "#,
+        )
+        .bind(dataset_uuid)
+        .bind(content)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to store blob: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn get_blob(&self, dataset_id: DatasetId) -> Result<Option<Vec<u8>>> {
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+
+        sqlx::query_scalar("SELECT content FROM dataset_blobs WHERE dataset_id = $1")
+            .bind(dataset_uuid)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to load blob: {}", e)))
+    }
+
+    async fn delete_blob(&self, dataset_id: DatasetId) -> Result<()> {
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+
+        sqlx::query("DELETE FROM dataset_blobs WHERE dataset_id = $1")
+            .bind(dataset_uuid)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to delete blob: {}", e)))?;
+
+        Ok(())
+    }
+}
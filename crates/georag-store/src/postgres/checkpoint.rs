@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::BuildCheckpoint;
+
+use super::PostgresStore;
+use crate::ports::CheckpointStore;
+
+#[async_trait]
+impl CheckpointStore for PostgresStore {
+    async fn save_checkpoint(&self, checkpoint: &BuildCheckpoint) -> Result<()> {
+        let json = serde_json::to_value(checkpoint).map_err(|e| {
+            GeoragError::Serialization(format!("Failed to serialize checkpoint: {}", e))
+        })?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO build_checkpoints (id, generation, checkpoint, updated_at)
+            VALUES (TRUE, $1, $2, NOW())
+            ON CONFLICT (id) DO UPDATE
+            SET generation = EXCLUDED.generation,
+                checkpoint = EXCLUDED.checkpoint,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(checkpoint.generation)
+        .bind(json)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to save checkpoint: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self) -> Result<Option<BuildCheckpoint>> {
+        let json: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT checkpoint FROM build_checkpoints WHERE id")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to load checkpoint: {}", e))
+                })?;
+
+        json.map(|json| {
+            serde_json::from_value(json).map_err(|e| {
+                GeoragError::Serialization(format!("Failed to parse checkpoint: {}", e))
+            })
+        })
+        .transpose()
+    }
+
+    async fn clear_checkpoint(&self) -> Result<()> {
+        sqlx::query("DELETE FROM build_checkpoints")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                GeoragError::Serialization(format!("Failed to clear checkpoint: {}", e))
+            })?;
+
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use georag_core::error::{GeoragError, Result};
-use georag_core::models::{ChunkId, FeatureId, TextChunk};
+use georag_core::geo::{from_wkb, to_wkb};
+use georag_core::models::{ChunkId, DatasetId, DatasetIndexStats, FeatureId, TextChunk};
 use sqlx::Row;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 use super::PostgresStore;
@@ -114,23 +116,26 @@ impl DocumentStore for PostgresStore {
 
             // Handle spatial reference
             let spatial_ref_uuid = chunk.spatial_ref.map(|fid| Uuid::from_u128(fid.0 as u128));
+            let geometry_wkb = chunk.geometry.as_ref().map(to_wkb);
 
             // For now, we'll use the chunk index from the loop if not available in metadata
             // In a real implementation, this would come from the chunk's source information
             let chunk_index = idx as i32;
+            // Offsets are characters of the normalized text the chunk was cut from
             let start_offset = chunk.source.offset as i32;
-            let end_offset = (chunk.source.offset + chunk.content.len()) as i32;
+            let end_offset = (chunk.source.offset + chunk.source.length) as i32;
 
             sqlx::query(
                 r#"
-                INSERT INTO chunks (id, document_id, chunk_index, content, start_offset, end_offset, spatial_ref, metadata)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                INSERT INTO chunks (id, document_id, chunk_index, content, start_offset, end_offset, spatial_ref, metadata, geometry)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, ST_GeomFromWKB($9, 4326))
                 ON CONFLICT (document_id, chunk_index) DO UPDATE
                 SET content = EXCLUDED.content,
                     start_offset = EXCLUDED.start_offset,
                     end_offset = EXCLUDED.end_offset,
                     spatial_ref = EXCLUDED.spatial_ref,
-                    metadata = EXCLUDED.metadata
+                    metadata = EXCLUDED.metadata,
+                    geometry = EXCLUDED.geometry
                 "#
             )
             .bind(chunk_uuid)
@@ -141,6 +146,7 @@ impl DocumentStore for PostgresStore {
             .bind(end_offset)
             .bind(spatial_ref_uuid)
             .bind(metadata_json)
+            .bind(geometry_wkb)
             .execute(&mut *tx)
             .await
             .map_err(|e| GeoragError::Serialization(format!("Failed to store chunk: {}", e)))?;
@@ -169,11 +175,13 @@ impl DocumentStore for PostgresStore {
                 c.start_offset,
                 c.end_offset,
                 c.spatial_ref,
+                ST_AsBinary(c.geometry) AS geometry,
                 c.metadata,
                 d.source_path
             FROM chunks c
             JOIN documents d ON c.document_id = d.id
             WHERE c.id = ANY($1)
+            ORDER BY array_position($1, c.id)
             "#,
         )
         .bind(&uuids)
@@ -189,17 +197,23 @@ impl DocumentStore for PostgresStore {
 
                 let spatial_ref_uuid: Option<Uuid> = row.get("spatial_ref");
                 let spatial_ref = spatial_ref_uuid.map(|uuid| FeatureId(uuid.as_u128() as u64));
+                let geometry_wkb: Option<Vec<u8>> = row.get("geometry");
+                let geometry = geometry_wkb.and_then(|wkb| from_wkb(&wkb).ok());
 
                 let metadata_json: serde_json::Value = row.get("metadata");
                 let metadata = serde_json::from_value(metadata_json).unwrap_or_else(|_| {
                     georag_core::models::document::ChunkMetadata {
                         size: 0,
                         properties: std::collections::HashMap::new(),
+                        source_hash: None,
+                        stale: false,
+                        dataset_id: None,
                     }
                 });
 
                 let document_path: String = row.get("source_path");
                 let start_offset: i32 = row.get("start_offset");
+                let end_offset: i32 = row.get("end_offset");
 
                 TextChunk {
                     id,
@@ -208,8 +222,10 @@ impl DocumentStore for PostgresStore {
                         document_path,
                         page: None,
                         offset: start_offset as usize,
+                        length: (end_offset - start_offset).max(0) as usize,
                     },
                     spatial_ref,
+                    geometry,
                     metadata,
                 }
             })
@@ -241,8 +257,30 @@ impl DocumentStore for PostgresStore {
         Ok(())
     }
 
+    async fn mark_chunks_stale(&self, ids: &[ChunkId]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let uuids: Vec<Uuid> = ids.iter().map(|id| Uuid::from_u128(id.0 as u128)).collect();
+
+        sqlx::query(
+            r#"
+            UPDATE chunks
+            SET metadata = jsonb_set(COALESCE(metadata, '{}'::jsonb), '{stale}', 'true'::jsonb)
+            WHERE id = ANY($1)
+            "#,
+        )
+        .bind(&uuids)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to mark chunks stale: {}", e)))?;
+
+        Ok(())
+    }
+
     async fn list_chunk_ids(&self) -> Result<Vec<ChunkId>> {
-        let rows = sqlx::query("SELECT id FROM chunks ORDER BY created_at")
+        let rows = sqlx::query("SELECT id FROM chunks ORDER BY id")
             .fetch_all(&self.pool)
             .await
             .map_err(|e| GeoragError::Serialization(format!("Failed to list chunk IDs: {}", e)))?;
@@ -257,4 +295,74 @@ impl DocumentStore for PostgresStore {
 
         Ok(ids)
     }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT dataset_id, chunks, content_bytes
+            FROM dataset_index_stats
+            WHERE chunks > 0
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to get index stats: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let dataset_id: i64 = row.get("dataset_id");
+                let chunks: i64 = row.get("chunks");
+                let content_bytes: i64 = row.get("content_bytes");
+                let stats = DatasetIndexStats {
+                    chunks: chunks as u64,
+                    content_bytes: content_bytes as u64,
+                    embeddings: 0,
+                };
+                (DatasetId(dataset_id as u64), stats)
+            })
+            .collect())
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
+        })?;
+
+        // Hold off chunk writes so the recount is exact when it commits
+        sqlx::query("LOCK TABLE chunks IN SHARE MODE")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to lock chunks: {}", e)))?;
+
+        sqlx::query(
+            "UPDATE dataset_index_stats SET chunks = 0, content_bytes = 0, updated_at = NOW()",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to reset index stats: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO dataset_index_stats (dataset_id, chunks, content_bytes)
+            SELECT (metadata->>'dataset_id')::BIGINT, COUNT(*), SUM(OCTET_LENGTH(content))
+            FROM chunks
+            WHERE metadata->>'dataset_id' IS NOT NULL
+            GROUP BY 1
+            ON CONFLICT (dataset_id) DO UPDATE
+            SET chunks = EXCLUDED.chunks,
+                content_bytes = EXCLUDED.content_bytes,
+                updated_at = NOW()
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to recount chunks: {}", e)))?;
+
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        DocumentStore::dataset_index_stats(self).await
+    }
 }
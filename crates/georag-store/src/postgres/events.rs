@@ -0,0 +1,172 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use georag_core::error::{GeoragError, Result};
+use georag_core::models::{ChangeEvent, DatasetId, EventLogBounds, EventRetention, WorkspaceId};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+
+use super::PostgresStore;
+use crate::ports::EventLogStore;
+
+#[async_trait]
+impl EventLogStore for PostgresStore {
+    async fn append_events(&self, events: &[ChangeEvent]) -> Result<u64> {
+        if events.is_empty() {
+            return Ok(self.event_bounds().await?.last);
+        }
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
+        })?;
+
+        // Locks the row until commit, so later appends get later numbers and commit after
+        let last: i64 = sqlx::query_scalar(
+            "UPDATE change_event_sequence SET last_seq = last_seq + $1 RETURNING last_seq",
+        )
+        .bind(events.len() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to take event sequence numbers: {}", e))
+        })?;
+
+        let first = last - events.len() as i64 + 1;
+        for (seq, event) in (first..).zip(events) {
+            let ids: Vec<i64> = event.ids.iter().map(|&id| id as i64).collect();
+            sqlx::query(
+                r#"
+                INSERT INTO change_events
+                    (seq, workspace_id, entity, operation, dataset_id, ids, payload, occurred_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(seq)
+            .bind(event.workspace_id.0)
+            .bind(event.entity.as_str())
+            .bind(event.operation.as_str())
+            .bind(event.dataset_id.map(|id| id.0 as i64))
+            .bind(ids)
+            .bind(&event.payload)
+            .bind(event.at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                GeoragError::Serialization(format!("Failed to append change event: {}", e))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(last as u64)
+    }
+
+    async fn events_after(&self, after_seq: u64, limit: usize) -> Result<Vec<ChangeEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT seq, workspace_id, entity, operation, dataset_id, ids, payload, occurred_at
+            FROM change_events
+            WHERE seq > $1
+            ORDER BY seq
+            LIMIT $2
+            "#,
+        )
+        .bind(after_seq as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to list change events: {}", e)))?;
+
+        rows.iter().map(event_from_row).collect()
+    }
+
+    async fn event_bounds(&self) -> Result<EventLogBounds> {
+        let row = sqlx::query(
+            r#"
+            SELECT (SELECT MIN(seq) FROM change_events) AS first, last_seq
+            FROM change_event_sequence
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to read event log bounds: {}", e))
+        })?;
+
+        let first: Option<i64> = row.get("first");
+        let last: i64 = row.get("last_seq");
+        Ok(EventLogBounds {
+            first: first.map(|seq| seq as u64),
+            last: last as u64,
+        })
+    }
+
+    async fn prune_events(&self, retention: &EventRetention) -> Result<u64> {
+        // Everything up to one sequence number goes, so the rest has no gaps
+        // even where concurrent appends stored their times out of order
+        let result = sqlx::query(
+            r#"
+            DELETE FROM change_events
+            WHERE seq <= GREATEST(
+                (SELECT last_seq FROM change_event_sequence) - $1,
+                COALESCE((SELECT MAX(seq) FROM change_events WHERE occurred_at < $2), 0)
+            )
+            "#,
+        )
+        .bind(retention.max_events as i64)
+        .bind(retention.oldest(Utc::now()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to prune change events: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn get_event_cursor(&self, consumer: &str) -> Result<Option<u64>> {
+        let seq: Option<i64> =
+            sqlx::query_scalar("SELECT seq FROM change_event_cursors WHERE consumer = $1")
+                .bind(consumer)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to read event cursor: {}", e))
+                })?;
+
+        Ok(seq.map(|seq| seq as u64))
+    }
+
+    async fn set_event_cursor(&self, consumer: &str, seq: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO change_event_cursors (consumer, seq)
+            VALUES ($1, $2)
+            ON CONFLICT (consumer) DO UPDATE
+            SET seq = EXCLUDED.seq, updated_at = NOW()
+            "#,
+        )
+        .bind(consumer)
+        .bind(seq as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to store event cursor: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+fn event_from_row(row: &PgRow) -> Result<ChangeEvent> {
+    let seq: i64 = row.get("seq");
+    let dataset_id: Option<i64> = row.get("dataset_id");
+    let ids: Vec<i64> = row.get("ids");
+    Ok(ChangeEvent {
+        seq: seq as u64,
+        workspace_id: WorkspaceId(row.get("workspace_id")),
+        at: row.get("occurred_at"),
+        entity: row.get::<&str, _>("entity").parse().map_err(GeoragError::Serialization)?,
+        operation: row.get::<&str, _>("operation").parse().map_err(GeoragError::Serialization)?,
+        dataset_id: dataset_id.map(|id| DatasetId(id as u64)),
+        ids: ids.into_iter().map(|id| id as u64).collect(),
+        payload: row.get("payload"),
+    })
+}
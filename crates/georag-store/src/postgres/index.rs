@@ -241,8 +241,8 @@ async fn get_georag_tables(pool: &PgPool) -> Result<Vec<String>> {
         FROM pg_tables
         WHERE schemaname = 'public'
         AND tablename IN (
-            'workspaces', 'datasets', 'features', 'documents',
-            'chunks', 'embeddings', 'index_builds'
+            'workspaces', 'workspace_settings', 'workspace_usage', 'datasets', 'dataset_blobs', 'features',
+            'documents', 'chunks', 'embeddings', 'index_builds'
         )
         ORDER BY tablename
     "#;
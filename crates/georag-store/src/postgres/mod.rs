@@ -1,7 +1,13 @@
+pub mod area;
+pub mod audit;
+pub mod blob;
+pub mod checkpoint;
 pub mod config;
 pub mod document;
+pub mod events;
 pub mod index;
 pub mod migrations;
+pub mod slots;
 pub mod spatial;
 pub mod transaction;
 pub mod vector;
@@ -12,10 +18,13 @@ pub use index::{IndexStats, RebuildResult, VacuumResult};
 pub use migrations::{MigrationError, MigrationManager, MigrationStatus};
 pub use transaction::{Transaction, TransactionManager};
 
+use async_trait::async_trait;
 use georag_core::error::{GeoragError, Result};
 use sqlx::{postgres::PgPoolOptions, PgPool};
 use std::time::Duration;
 
+use crate::ports::HealthProbe;
+
 /// PostgreSQL storage adapter
 pub struct PostgresStore {
     pool: PgPool,
@@ -166,3 +175,14 @@ impl PostgresStore {
         index::vacuum_analyze(&self.pool, table_name, analyze, full).await
     }
 }
+
+#[async_trait]
+impl HealthProbe for PostgresStore {
+    fn backend(&self) -> &str {
+        "postgres"
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        PostgresStore::health_check(self).await
+    }
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use georag_core::error::{GeoragError, Result};
+use georag_core::resources::{ResourceGuard, ResourceKind};
+
+use super::PostgresStore;
+use crate::ports::SlotStore;
+
+/// First key of the advisory locks standing for slots, one per resource kind
+///
+/// The second key is the slot number, so slot `n` of a kind is the lock
+/// `(SLOT_LOCK_NAMESPACE + kind, n)`.
+const SLOT_LOCK_NAMESPACE: i32 = 0x4752_5300;
+
+fn lock_class(kind: ResourceKind) -> i32 {
+    let offset = match kind {
+        ResourceKind::Ingest => 0,
+        ResourceKind::Build => 1,
+        ResourceKind::EmbeddingBatch => 2,
+        ResourceKind::Export => 3,
+    };
+    SLOT_LOCK_NAMESPACE + offset
+}
+
+/// Slots are transaction-level advisory locks, each held by an open
+/// transaction on its own connection
+///
+/// Dropping the guard rolls the transaction back, which releases the lock;
+/// so does the connection closing when its process dies.
+#[async_trait]
+impl SlotStore for PostgresStore {
+    async fn try_acquire_slot(
+        &self,
+        kind: ResourceKind,
+        limit: usize,
+    ) -> Result<Option<ResourceGuard>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin slot transaction: {}", e))
+        })?;
+
+        for slot in 0..limit.min(i32::MAX as usize) as i32 {
+            let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1, $2)")
+                .bind(lock_class(kind))
+                .bind(slot)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to take {} slot: {}", kind, e))
+                })?;
+            if locked {
+                return Ok(Some(ResourceGuard::new(tx)));
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -1,14 +1,28 @@
 use async_trait::async_trait;
 use georag_core::error::{GeoragError, Result};
+use georag_core::geo::{
+    from_wkb, from_wkb_z, to_wkb, to_wkb_z, JoinCounts, JoinPredicate, SampleStrategy, SpatialJoin,
+};
 use georag_core::models::{
-    Dataset, DatasetId, DatasetMeta, Feature, FeatureId, Geometry, GeometryType, SpatialFilter,
-    SpatialPredicate,
+    Dataset, DatasetId, DatasetMeta, DatasetPreview, Feature, FeatureId, Geometry, GeometryType,
+    SpatialFilter, SpatialPredicate, TagVisibility,
 };
+use sqlx::postgres::PgRow;
 use sqlx::Row;
 use uuid::Uuid;
 
 use super::PostgresStore;
-use crate::ports::SpatialStore;
+use crate::ports::{distinct_feature_ids, SpatialStore};
+
+/// Estimated size of the features table above which samples read a
+/// TABLESAMPLE of the table instead of scanning every row
+const SAMPLE_SCAN_LIMIT: f64 = 200_000.0;
+
+/// Feature listing order: original ID, numerically for decimal IDs, then internal ID
+///
+/// Decimal IDs of equal length compare as text in numeric order, so the
+/// store lists features the way the memory store orders their IDs.
+const FEATURE_ORDER: &str = "length(feature_id), feature_id, id";
 
 #[async_trait]
 impl SpatialStore for PostgresStore {
@@ -55,18 +69,25 @@ impl SpatialStore for PostgresStore {
             GeometryType::GeometryCollection | GeometryType::Mixed => "GeometryCollection",
         };
 
+        let metadata = serde_json::to_value(&dataset.format).map_err(|e| {
+            GeoragError::Serialization(format!("Failed to serialize dataset metadata: {}", e))
+        })?;
+
         // Insert dataset
         sqlx::query(
             r#"
-            INSERT INTO datasets (id, workspace_id, name, source_path, format, crs, geometry_type, feature_count, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            INSERT INTO datasets (id, workspace_id, name, source_path, format, crs, geometry_type, feature_count, metadata, tags, license, attribution)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             ON CONFLICT (workspace_id, name) DO UPDATE
             SET source_path = EXCLUDED.source_path,
                 format = EXCLUDED.format,
                 crs = EXCLUDED.crs,
                 geometry_type = EXCLUDED.geometry_type,
                 feature_count = EXCLUDED.feature_count,
-                metadata = EXCLUDED.metadata
+                metadata = EXCLUDED.metadata,
+                tags = EXCLUDED.tags,
+                license = EXCLUDED.license,
+                attribution = EXCLUDED.attribution
             "#
         )
         .bind(dataset_uuid)
@@ -77,7 +98,10 @@ impl SpatialStore for PostgresStore {
         .bind(format!("EPSG:{}", dataset.crs))
         .bind(geometry_type_str)
         .bind(dataset.feature_count as i32)
-        .bind(serde_json::json!({}))
+        .bind(metadata)
+        .bind(&dataset.tags)
+        .bind(&dataset.license)
+        .bind(&dataset.attribution)
         .execute(&self.pool)
         .await
         .map_err(|e| GeoragError::Serialization(format!("Failed to store dataset: {}", e)))?;
@@ -90,7 +114,8 @@ impl SpatialStore for PostgresStore {
 
         let row = sqlx::query(
             r#"
-            SELECT id, name, source_path, crs, geometry_type, feature_count, created_at
+            SELECT id, name, source_path, crs, geometry_type, feature_count, metadata, created_at,
+                   tags, license, attribution
             FROM datasets
             WHERE id = $1
             "#,
@@ -119,6 +144,24 @@ impl SpatialStore for PostgresStore {
                     _ => GeometryType::GeometryCollection,
                 };
 
+                // Datasets stored before metadata was kept have an empty object
+                let metadata: serde_json::Value = row.get("metadata");
+                let format = serde_json::from_value(metadata).unwrap_or_else(|_| {
+                    georag_core::models::dataset::FormatMetadata {
+                        format_name: "GeoJSON".to_string(),
+                        format_version: None,
+                        layer_name: None,
+                        page_count: None,
+                        paragraph_count: None,
+                        extraction_method: None,
+                        spatial_association: None,
+                        axis_order: None,
+                        source: None,
+                        preview: None,
+                        remote: None,
+                    }
+                });
+
                 let dataset = Dataset {
                     id,
                     name: row.get("name"),
@@ -126,16 +169,11 @@ impl SpatialStore for PostgresStore {
                     geometry_type,
                     feature_count: row.get::<i32, _>("feature_count") as usize,
                     crs,
-                    format: georag_core::models::dataset::FormatMetadata {
-                        format_name: "GeoJSON".to_string(), // Default for now
-                        format_version: None,
-                        layer_name: None,
-                        page_count: None,
-                        paragraph_count: None,
-                        extraction_method: None,
-                        spatial_association: None,
-                    },
+                    format,
                     added_at: row.get("created_at"),
+                    tags: row.get("tags"),
+                    license: row.get("license"),
+                    attribution: row.get("attribution"),
                 };
 
                 Ok(Some(dataset))
@@ -145,13 +183,26 @@ impl SpatialStore for PostgresStore {
     }
 
     async fn list_datasets(&self) -> Result<Vec<DatasetMeta>> {
+        self.list_visible_datasets(&TagVisibility::All).await
+    }
+
+    async fn list_visible_datasets(&self, visibility: &TagVisibility) -> Result<Vec<DatasetMeta>> {
+        // NULL means unrestricted; otherwise every dataset tag must be allowed
+        let allowed: Option<Vec<String>> = match visibility {
+            TagVisibility::All => None,
+            TagVisibility::Only(tags) => Some(tags.iter().cloned().collect()),
+        };
+
         let rows = sqlx::query(
             r#"
-            SELECT id, name, crs, geometry_type, feature_count, created_at
+            SELECT id, name, crs, geometry_type, feature_count, created_at, tags, license,
+                   attribution, metadata->'preview' AS preview
             FROM datasets
-            ORDER BY created_at DESC
+            WHERE $1::TEXT[] IS NULL OR tags <@ $1::TEXT[]
+            ORDER BY name, id
             "#,
         )
+        .bind(allowed)
         .fetch_all(&self.pool)
         .await
         .map_err(|e| GeoragError::Serialization(format!("Failed to list datasets: {}", e)))?;
@@ -186,6 +237,10 @@ impl SpatialStore for PostgresStore {
                     feature_count: row.get::<i32, _>("feature_count") as usize,
                     crs,
                     added_at: row.get("created_at"),
+                    tags: row.get("tags"),
+                    license: row.get("license"),
+                    attribution: row.get("attribution"),
+                    preview: preview_from_row(&row),
                 }
             })
             .collect();
@@ -193,6 +248,50 @@ impl SpatialStore for PostgresStore {
         Ok(datasets)
     }
 
+    async fn set_dataset_tags(&self, id: DatasetId, tags: &[String]) -> Result<()> {
+        let dataset_uuid = Uuid::from_u128(id.0 as u128);
+
+        let result = sqlx::query("UPDATE datasets SET tags = $2 WHERE id = $1")
+            .bind(dataset_uuid)
+            .bind(tags)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                GeoragError::Serialization(format!("Failed to update dataset tags: {}", e))
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(GeoragError::DatasetNotFound { name: id.0.to_string() });
+        }
+
+        Ok(())
+    }
+
+    async fn set_dataset_preview(&self, id: DatasetId, preview: &DatasetPreview) -> Result<()> {
+        let dataset_uuid = Uuid::from_u128(id.0 as u128);
+        let preview = serde_json::to_value(preview).map_err(|e| {
+            GeoragError::Serialization(format!("Failed to serialize dataset preview: {}", e))
+        })?;
+
+        // Datasets stored before metadata was kept have an empty object, which gains the key
+        let result = sqlx::query(
+            "UPDATE datasets SET metadata = jsonb_set(metadata, '{preview}', $2) WHERE id = $1",
+        )
+        .bind(dataset_uuid)
+        .bind(preview)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to update dataset preview: {}", e))
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(GeoragError::DatasetNotFound { name: id.0.to_string() });
+        }
+
+        Ok(())
+    }
+
     async fn delete_dataset(&self, id: DatasetId) -> Result<()> {
         let dataset_uuid = Uuid::from_u128(id.0 as u128);
 
@@ -277,10 +376,9 @@ impl SpatialStore for PostgresStore {
         for feature in features {
             let feature_uuid = Uuid::from_u128(feature.id.0 as u128);
 
-            // Convert geometry to GeoJSON string
-            let geometry_json = serde_json::to_string(&feature.geometry).map_err(|e| {
-                GeoragError::Serialization(format!("Failed to serialize geometry: {}", e))
-            })?;
+            // WKB keeps every bit of the coordinates, unlike GeoJSON text, and Z
+            let geometry_wkb =
+                feature.geometry.as_ref().map(|g| to_wkb_z(g, feature.z_ordinates()));
 
             // Convert properties to JSONB
             let properties_json = serde_json::to_value(&feature.properties).map_err(|e| {
@@ -290,7 +388,7 @@ impl SpatialStore for PostgresStore {
             sqlx::query(
                 r#"
                 INSERT INTO features (id, dataset_id, feature_id, geometry, properties)
-                VALUES ($1, $2, $3, ST_GeomFromGeoJSON($4), $5)
+                VALUES ($1, $2, $3, ST_GeomFromWKB($4, 4326), $5)
                 ON CONFLICT (dataset_id, feature_id) DO UPDATE
                 SET geometry = EXCLUDED.geometry,
                     properties = EXCLUDED.properties
@@ -299,7 +397,7 @@ impl SpatialStore for PostgresStore {
             .bind(feature_uuid)
             .bind(dataset_id)
             .bind(feature.id.0.to_string())
-            .bind(geometry_json)
+            .bind(geometry_wkb)
             .bind(properties_json)
             .execute(&mut *tx)
             .await
@@ -317,17 +415,29 @@ impl SpatialStore for PostgresStore {
         // Build the WHERE clause based on the spatial predicate
         let (where_clause, needs_geometry, needs_distance) = match filter.predicate {
             SpatialPredicate::Within => {
-                ("ST_Within(geometry, ST_GeomFromGeoJSON($1))", true, false)
+                ("ST_Within(geometry, ST_GeomFromWKB($1, 4326))", true, false)
             }
             SpatialPredicate::Intersects => {
-                ("ST_Intersects(geometry, ST_GeomFromGeoJSON($1))", true, false)
+                ("ST_Intersects(geometry, ST_GeomFromWKB($1, 4326))", true, false)
             }
             SpatialPredicate::Contains => {
-                ("ST_Contains(geometry, ST_GeomFromGeoJSON($1))", true, false)
+                ("ST_Contains(geometry, ST_GeomFromWKB($1, 4326))", true, false)
             }
-            SpatialPredicate::BoundingBox => ("geometry && ST_GeomFromGeoJSON($1)", true, false),
+            SpatialPredicate::CoveredBy => {
+                ("ST_CoveredBy(geometry, ST_GeomFromWKB($1, 4326))", true, false)
+            }
+            SpatialPredicate::Touches => {
+                ("ST_Touches(geometry, ST_GeomFromWKB($1, 4326))", true, false)
+            }
+            SpatialPredicate::Crosses => {
+                ("ST_Crosses(geometry, ST_GeomFromWKB($1, 4326))", true, false)
+            }
+            SpatialPredicate::Overlaps => {
+                ("ST_Overlaps(geometry, ST_GeomFromWKB($1, 4326))", true, false)
+            }
+            SpatialPredicate::BoundingBox => ("geometry && ST_GeomFromWKB($1, 4326)", true, false),
             SpatialPredicate::DWithin => (
-                "ST_DWithin(geometry::geography, ST_GeomFromGeoJSON($1)::geography, $2)",
+                "ST_DWithin(geometry::geography, ST_GeomFromWKB($1, 4326)::geography, $2)",
                 true,
                 true,
             ),
@@ -347,27 +457,41 @@ impl SpatialStore for PostgresStore {
             ));
         }
 
-        let geometry_json =
-            serde_json::to_string(filter.geometry.as_ref().unwrap()).map_err(|e| {
-                GeoragError::Serialization(format!("Failed to serialize geometry: {}", e))
-            })?;
+        let geometry_wkb = to_wkb(filter.geometry.as_ref().unwrap());
+
+        // Exclusion areas follow the filter's own parameters
+        let first_exclusion = if needs_distance { 3 } else { 2 };
+        let exclusions: String = (0..filter.exclude.len())
+            .map(|i| {
+                format!(
+                    " AND NOT ST_Intersects(geometry, ST_GeomFromWKB(${}, 4326))",
+                    first_exclusion + i
+                )
+            })
+            .collect();
 
         let query_str = format!(
             r#"
-            SELECT id, feature_id, ST_AsGeoJSON(geometry) as geometry, properties
+            SELECT id, feature_id, ST_AsBinary(geometry) AS geometry, properties
             FROM features
-            WHERE {}
+            WHERE {}{}
+            ORDER BY {}
             "#,
-            where_clause
+            where_clause, exclusions, FEATURE_ORDER
         );
 
-        let rows = sqlx::query(&query_str)
-            .bind(geometry_json)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| {
-                GeoragError::Serialization(format!("Failed to execute spatial query: {}", e))
-            })?;
+        let mut query = sqlx::query(&query_str).bind(geometry_wkb);
+        // ST_DWithin on geography measures in meters
+        if let (true, Some(distance)) = (needs_distance, &filter.distance) {
+            query = query.bind(distance.to_meters());
+        }
+        for area in &filter.exclude {
+            query = query.bind(to_wkb(area));
+        }
+
+        let rows = query.fetch_all(&self.pool).await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to execute spatial query: {}", e))
+        })?;
 
         let features = rows
             .into_iter()
@@ -375,10 +499,11 @@ impl SpatialStore for PostgresStore {
                 let uuid: Uuid = row.get("id");
                 let id = FeatureId(uuid.as_u128() as u64);
 
-                let geometry_str: String = row.get("geometry");
-                let geometry_json: serde_json::Value =
-                    serde_json::from_str(&geometry_str).unwrap_or(serde_json::json!({}));
-                let geometry = Geometry::from_geojson(&geometry_json);
+                let geometry_wkb: Vec<u8> = row.get("geometry");
+                let (geometry, z) = match from_wkb_z(&geometry_wkb) {
+                    Ok((geometry, z)) => (Some(geometry), z),
+                    Err(_) => (None, None),
+                };
 
                 let properties: serde_json::Value = row.get("properties");
                 let properties_map = properties
@@ -391,6 +516,7 @@ impl SpatialStore for PostgresStore {
                     geometry,
                     properties: properties_map,
                     crs: filter.crs.epsg,
+                    z,
                 }
             })
             .collect();
@@ -403,7 +529,7 @@ impl SpatialStore for PostgresStore {
 
         let row = sqlx::query(
             r#"
-            SELECT id, feature_id, ST_AsGeoJSON(geometry) as geometry, properties
+            SELECT id, feature_id, ST_AsBinary(geometry) AS geometry, properties
             FROM features
             WHERE id = $1
             "#,
@@ -415,12 +541,11 @@ impl SpatialStore for PostgresStore {
 
         match row {
             Some(row) => {
-                let geometry_str: String = row.get("geometry");
-                let geometry_json: serde_json::Value = serde_json::from_str(&geometry_str)
-                    .map_err(|e| {
-                        GeoragError::Serialization(format!("Failed to parse geometry: {}", e))
-                    })?;
-                let geometry = Geometry::from_geojson(&geometry_json);
+                let geometry_wkb: Option<Vec<u8>> = row.get("geometry");
+                let (geometry, z) = match geometry_wkb.map(|wkb| from_wkb_z(&wkb)).transpose()? {
+                    Some((geometry, z)) => (Some(geometry), z),
+                    None => (None, None),
+                };
 
                 let properties: serde_json::Value = row.get("properties");
                 let properties_map = properties
@@ -433,55 +558,380 @@ impl SpatialStore for PostgresStore {
                     geometry,
                     properties: properties_map,
                     crs: 4326, // Default CRS
+                    z,
                 }))
             }
             None => Ok(None),
         }
     }
 
-    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
-        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+    async fn get_features(&self, ids: &[FeatureId]) -> Result<Vec<Feature>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
 
+        let uuids: Vec<Uuid> = distinct_feature_ids(ids)
+            .iter()
+            .map(|id| Uuid::from_u128(id.0 as u128))
+            .collect();
         let rows = sqlx::query(
             r#"
-            SELECT id, feature_id, ST_AsGeoJSON(geometry) as geometry, properties
+            SELECT id, feature_id, ST_AsBinary(geometry) AS geometry, properties
             FROM features
-            WHERE dataset_id = $1
+            WHERE id = ANY($1)
             "#,
         )
-        .bind(dataset_uuid)
+        .bind(&uuids)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| {
-            GeoragError::Serialization(format!("Failed to get features for dataset: {}", e))
+        .map_err(|e| GeoragError::Serialization(format!("Failed to get features: {}", e)))?;
+
+        let mut features: Vec<Feature> = rows.into_iter().map(feature_from_row).collect();
+        features.sort_by_key(|f| f.id.0);
+        Ok(features)
+    }
+
+    async fn get_features_for_dataset(&self, dataset_id: DatasetId) -> Result<Vec<Feature>> {
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+
+        let query_str = format!(
+            r#"
+            SELECT id, feature_id, ST_AsBinary(geometry) AS geometry, properties
+            FROM features
+            WHERE dataset_id = $1
+            ORDER BY {}
+            "#,
+            FEATURE_ORDER
+        );
+        let rows = sqlx::query(&query_str).bind(dataset_uuid).fetch_all(&self.pool).await.map_err(
+            |e| GeoragError::Serialization(format!("Failed to get features for dataset: {}", e)),
+        )?;
+
+        Ok(rows.into_iter().map(feature_from_row).collect())
+    }
+
+    async fn update_feature_properties(&self, features: &[Feature]) -> Result<()> {
+        if features.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
         })?;
 
-        let features = rows
-            .into_iter()
-            .map(|row| {
-                let uuid: Uuid = row.get("id");
-                let id = FeatureId(uuid.as_u128() as u64);
+        for feature in features {
+            let feature_uuid = Uuid::from_u128(feature.id.0 as u128);
+            let properties_json = serde_json::to_value(&feature.properties).map_err(|e| {
+                GeoragError::Serialization(format!("Failed to serialize properties: {}", e))
+            })?;
 
-                let geometry_str: String = row.get("geometry");
-                let geometry_json: serde_json::Value =
-                    serde_json::from_str(&geometry_str).unwrap_or(serde_json::json!({}));
-                let geometry = Geometry::from_geojson(&geometry_json);
+            sqlx::query("UPDATE features SET properties = $2 WHERE id = $1")
+                .bind(feature_uuid)
+                .bind(properties_json)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to update feature: {}", e))
+                })?;
+        }
 
-                let properties: serde_json::Value = row.get("properties");
-                let properties_map = properties
-                    .as_object()
-                    .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
-                    .unwrap_or_default();
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
 
-                Feature {
-                    id,
-                    geometry,
-                    properties: properties_map,
-                    crs: 4326, // Default CRS
-                }
-            })
-            .collect();
+        Ok(())
+    }
+
+    async fn upsert_dataset_features(
+        &self,
+        dataset_id: DatasetId,
+        features: &[Feature],
+    ) -> Result<()> {
+        if features.is_empty() {
+            return Ok(());
+        }
+
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
+        })?;
+
+        for feature in features {
+            let feature_uuid = Uuid::from_u128(feature.id.0 as u128);
+            let geometry_wkb =
+                feature.geometry.as_ref().map(|g| to_wkb_z(g, feature.z_ordinates()));
+            let properties_json = serde_json::to_value(&feature.properties).map_err(|e| {
+                GeoragError::Serialization(format!("Failed to serialize properties: {}", e))
+            })?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO features (id, dataset_id, feature_id, geometry, properties)
+                VALUES ($1, $2, $3, ST_GeomFromWKB($4, 4326), $5)
+                ON CONFLICT (id) DO UPDATE
+                SET dataset_id = EXCLUDED.dataset_id,
+                    geometry = EXCLUDED.geometry,
+                    properties = EXCLUDED.properties
+                "#,
+            )
+            .bind(feature_uuid)
+            .bind(dataset_uuid)
+            .bind(feature.id.0.to_string())
+            .bind(geometry_wkb)
+            .bind(properties_json)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to upsert feature: {}", e)))?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    async fn delete_features(&self, dataset_id: DatasetId, ids: &[FeatureId]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+        let feature_uuids: Vec<Uuid> = ids.iter().map(|id| Uuid::from_u128(id.0 as u128)).collect();
+
+        sqlx::query("DELETE FROM features WHERE dataset_id = $1 AND id = ANY($2)")
+            .bind(dataset_uuid)
+            .bind(&feature_uuids)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to delete features: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn sample_features(
+        &self,
+        dataset_id: DatasetId,
+        n: usize,
+        strategy: SampleStrategy,
+    ) -> Result<Vec<Feature>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let dataset_uuid = Uuid::from_u128(dataset_id.0 as u128);
+
+        // On huge tables read a block sample sized to roughly SAMPLE_SCAN_LIMIT rows
+        let estimated: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(reltuples), 0)::float8 FROM pg_class WHERE relname = 'features'",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to estimate features: {}", e)))?;
+        let source = if estimated > SAMPLE_SCAN_LIMIT {
+            format!("features TABLESAMPLE SYSTEM ({:.4})", 100.0 * SAMPLE_SCAN_LIMIT / estimated)
+        } else {
+            "features".to_string()
+        };
+
+        let mut features: Vec<Feature> = match strategy {
+            SampleStrategy::Random => Vec::new(),
+            SampleStrategy::Spatial => {
+                // One random feature per cell of a k x k grid over the extent
+                let k = (n as f64).sqrt().ceil();
+                let sql = format!(
+                    r#"
+                    WITH sampled AS (
+                        SELECT id, geometry, properties,
+                               ST_Centroid(ST_Envelope(geometry)) AS center
+                        FROM {source}
+                        WHERE dataset_id = $1 AND geometry IS NOT NULL
+                    ),
+                    bounds AS (
+                        SELECT ST_XMin(box) AS xmin, ST_YMin(box) AS ymin,
+                               GREATEST(ST_XMax(box) - ST_XMin(box), 1e-12) / $2 AS width,
+                               GREATEST(ST_YMax(box) - ST_YMin(box), 1e-12) / $2 AS height
+                        FROM (SELECT ST_Extent(center)::geometry AS box FROM sampled) AS e
+                    ),
+                    cells AS (
+                        SELECT DISTINCT ON (cx, cy) s.id, s.geometry, s.properties
+                        FROM sampled s
+                        CROSS JOIN bounds b
+                        CROSS JOIN LATERAL (
+                            SELECT LEAST(floor((ST_X(s.center) - b.xmin) / b.width), $2 - 1) AS cx,
+                                   LEAST(floor((ST_Y(s.center) - b.ymin) / b.height), $2 - 1) AS cy
+                        ) AS g
+                        ORDER BY cx, cy, random()
+                    )
+                    SELECT id, ST_AsBinary(geometry) AS geometry, properties
+                    FROM cells
+                    ORDER BY random()
+                    LIMIT $3
+                    "#
+                );
+
+                sqlx::query(&sql)
+                    .bind(dataset_uuid)
+                    .bind(k)
+                    .bind(n as i64)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(|e| {
+                        GeoragError::Serialization(format!("Failed to sample features: {}", e))
+                    })?
+                    .into_iter()
+                    .map(feature_from_row)
+                    .collect()
+            }
+        };
+
+        // Random sampling, and topping up grids with empty cells
+        if features.len() < n {
+            let taken: Vec<Uuid> =
+                features.iter().map(|f| Uuid::from_u128(f.id.0 as u128)).collect();
+            let sql = format!(
+                r#"
+                SELECT id, ST_AsBinary(geometry) AS geometry, properties
+                FROM {source}
+                WHERE dataset_id = $1 AND NOT (id = ANY($2))
+                ORDER BY random()
+                LIMIT $3
+                "#
+            );
+            let rows = sqlx::query(&sql)
+                .bind(dataset_uuid)
+                .bind(&taken)
+                .bind((n - features.len()) as i64)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to sample features: {}", e))
+                })?;
+            features.extend(rows.into_iter().map(feature_from_row));
+        }
 
         Ok(features)
     }
+
+    async fn spatial_join(
+        &self,
+        target: DatasetId,
+        source: DatasetId,
+        join: &SpatialJoin,
+    ) -> Result<Option<JoinCounts>> {
+        // Nearest joins are computed by the caller over the loaded features
+        let predicate = match join.predicate {
+            JoinPredicate::Within => "ST_Within(t.geometry, s.geometry)",
+            JoinPredicate::Intersects => "ST_Intersects(t.geometry, s.geometry)",
+            JoinPredicate::Nearest { .. } => return Ok(None),
+        };
+
+        let target_uuid = Uuid::from_u128(target.0 as u128);
+        let source_uuid = Uuid::from_u128(source.0 as u128);
+
+        // Each target takes the properties of its first matching source, the
+        // same rule the in-memory join applies
+        let sql = format!(
+            r#"
+            UPDATE features AS f
+            SET properties = COALESCE(f.properties, '{{}}'::jsonb) || COALESCE((
+                SELECT jsonb_object_agg($4 || p.key, p.value)
+                FROM jsonb_each(m.properties) AS p
+                WHERE p.key = ANY($3)
+            ), '{{}}'::jsonb)
+            FROM (
+                SELECT DISTINCT ON (t.id) t.id AS target_id, s.properties
+                FROM features t
+                JOIN features s ON {predicate}
+                WHERE t.dataset_id = $1 AND s.dataset_id = $2
+                ORDER BY t.id, s.id
+            ) AS m
+            WHERE f.id = m.target_id
+            "#
+        );
+
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
+        })?;
+
+        let matched = sqlx::query(&sql)
+            .bind(target_uuid)
+            .bind(source_uuid)
+            .bind(&join.take)
+            .bind(&join.prefix)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to run spatial join: {}", e)))?
+            .rows_affected() as usize;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM features WHERE dataset_id = $1")
+            .bind(target_uuid)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to count features: {}", e)))?;
+
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        Ok(Some(JoinCounts {
+            matched,
+            missed: (total as usize).saturating_sub(matched),
+        }))
+    }
+
+    async fn subdivide_geometry(
+        &self,
+        geometry: &Geometry,
+        max_vertices: usize,
+    ) -> Result<Option<Vec<Geometry>>> {
+        // ST_Subdivide needs at least 5 vertices per part
+        let max_vertices = i32::try_from(max_vertices.max(5)).unwrap_or(i32::MAX);
+
+        let rows: Vec<Vec<u8>> =
+            sqlx::query_scalar("SELECT ST_AsBinary(ST_Subdivide(ST_GeomFromWKB($1, 4326), $2))")
+                .bind(to_wkb(geometry))
+                .bind(max_vertices)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to subdivide geometry: {}", e))
+                })?;
+
+        let parts = rows.iter().map(|wkb| from_wkb(wkb)).collect::<Result<Vec<_>>>()?;
+        Ok(Some(parts))
+    }
+}
+
+/// Build a feature from a row selecting `id`, `geometry` as WKB and `properties`
+/// Dataset preview from a `preview` column holding `metadata->'preview'`
+///
+/// Datasets ingested before previews were kept have none.
+pub(super) fn preview_from_row(row: &PgRow) -> Option<DatasetPreview> {
+    let value: Option<serde_json::Value> = row.get("preview");
+    value.and_then(|value| serde_json::from_value(value).ok())
+}
+
+fn feature_from_row(row: PgRow) -> Feature {
+    let uuid: Uuid = row.get("id");
+    let id = FeatureId(uuid.as_u128() as u64);
+
+    let geometry_wkb: Option<Vec<u8>> = row.get("geometry");
+    let (geometry, z) = match geometry_wkb.and_then(|wkb| from_wkb_z(&wkb).ok()) {
+        Some((geometry, z)) => (Some(geometry), z),
+        None => (None, None),
+    };
+
+    let properties: serde_json::Value = row.get("properties");
+    let properties = properties
+        .as_object()
+        .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default();
+
+    Feature {
+        id,
+        geometry,
+        properties,
+        crs: 4326, // Default CRS
+        z,
+    }
 }
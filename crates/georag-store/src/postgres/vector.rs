@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use georag_core::error::{GeoragError, Result};
-use georag_core::models::{ChunkId, Embedding, ScoredResult};
+use georag_core::models::{ChunkId, DatasetId, DatasetIndexStats, Embedding, ScoredResult};
 use sqlx::Row;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 use super::PostgresStore;
@@ -112,13 +113,18 @@ impl VectorStore for PostgresStore {
             // We'll use a default model name if not specified
             let model_name = "default";
 
+            // Embeddings without a dataset take the one of their chunk
             sqlx::query(
                 r#"
-                INSERT INTO embeddings (id, chunk_id, model, dimensions, vector)
-                VALUES ($1, $2, $3, $4, $5::vector)
+                INSERT INTO embeddings (id, chunk_id, model, dimensions, vector, dataset_id)
+                VALUES (
+                    $1, $2, $3, $4, $5::vector,
+                    COALESCE($6, (SELECT (metadata->>'dataset_id')::BIGINT FROM chunks WHERE id = $2))
+                )
                 ON CONFLICT (chunk_id, model) DO UPDATE
                 SET vector = EXCLUDED.vector,
-                    dimensions = EXCLUDED.dimensions
+                    dimensions = EXCLUDED.dimensions,
+                    dataset_id = EXCLUDED.dataset_id
                 "#,
             )
             .bind(embedding_uuid)
@@ -126,6 +132,7 @@ impl VectorStore for PostgresStore {
             .bind(model_name)
             .bind(dimensions)
             .bind(vector_str)
+            .bind(embedding.dataset_id.map(|id| id.0 as i64))
             .execute(&mut *tx)
             .await
             .map_err(|e| GeoragError::Serialization(format!("Failed to store embedding: {}", e)))?;
@@ -161,7 +168,7 @@ impl VectorStore for PostgresStore {
                     1 - (e.vector <=> $1::vector) as similarity
                 FROM embeddings e
                 WHERE 1 - (e.vector <=> $1::vector) >= $3
-                ORDER BY e.vector <=> $1::vector
+                ORDER BY e.vector <=> $1::vector, e.chunk_id
                 LIMIT $2
                 "#
             .to_string()
@@ -171,7 +178,7 @@ impl VectorStore for PostgresStore {
                 e.chunk_id,
                 1 - (e.vector <=> $1::vector) as similarity
             FROM embeddings e
-            ORDER BY e.vector <=> $1::vector
+            ORDER BY e.vector <=> $1::vector, e.chunk_id
             LIMIT $2
             "#
             .to_string()
@@ -187,7 +194,7 @@ impl VectorStore for PostgresStore {
             GeoragError::Serialization(format!("Failed to execute similarity search: {}", e))
         })?;
 
-        let results = rows
+        let mut results: Vec<ScoredResult> = rows
             .into_iter()
             .map(|row| {
                 let chunk_uuid: Uuid = row.get("chunk_id");
@@ -202,6 +209,9 @@ impl VectorStore for PostgresStore {
             })
             .collect();
 
+        // Distances that differ slightly can round to the same score
+        results.sort_by(ScoredResult::rank_cmp);
+
         Ok(results)
     }
 
@@ -210,7 +220,7 @@ impl VectorStore for PostgresStore {
 
         let row = sqlx::query(
             r#"
-            SELECT chunk_id, vector::text as vector_text
+            SELECT chunk_id, vector::text as vector_text, dataset_id
             FROM embeddings
             WHERE chunk_id = $1
             LIMIT 1
@@ -230,7 +240,13 @@ impl VectorStore for PostgresStore {
                     GeoragError::Serialization(format!("Failed to parse vector: {}", e))
                 })?;
 
-                Ok(Some(Embedding { chunk_id, vector, spatial_metadata: None }))
+                let dataset_id: Option<i64> = row.get("dataset_id");
+                Ok(Some(Embedding {
+                    chunk_id,
+                    vector,
+                    spatial_metadata: None,
+                    dataset_id: dataset_id.map(|id| DatasetId(id as u64)),
+                }))
             }
             None => Ok(None),
         }
@@ -272,6 +288,97 @@ impl VectorStore for PostgresStore {
             None => Ok(0), // No embeddings stored yet
         }
     }
+
+    async fn list_embedding_ids(&self) -> Result<Vec<ChunkId>> {
+        let rows = sqlx::query("SELECT chunk_id FROM embeddings ORDER BY chunk_id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to list embeddings: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let uuid: Uuid = row.get("chunk_id");
+                ChunkId(uuid.as_u128() as u64)
+            })
+            .collect())
+    }
+
+    async fn compact(&self) -> Result<()> {
+        // VACUUM cannot run inside a transaction, so it goes straight to the pool
+        sqlx::query("VACUUM embeddings").execute(&self.pool).await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to vacuum embeddings: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    async fn dataset_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT dataset_id, embeddings
+            FROM dataset_index_stats
+            WHERE embeddings > 0
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to get index stats: {}", e)))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let dataset_id: i64 = row.get("dataset_id");
+                let embeddings: i64 = row.get("embeddings");
+                let stats = DatasetIndexStats {
+                    embeddings: embeddings as u64,
+                    ..Default::default()
+                };
+                (DatasetId(dataset_id as u64), stats)
+            })
+            .collect())
+    }
+
+    async fn recount_index_stats(&self) -> Result<BTreeMap<DatasetId, DatasetIndexStats>> {
+        let mut tx = self.pool.begin().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to begin transaction: {}", e))
+        })?;
+
+        // Hold off embedding writes so the recount is exact when it commits
+        sqlx::query("LOCK TABLE embeddings IN SHARE MODE")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| GeoragError::Serialization(format!("Failed to lock embeddings: {}", e)))?;
+
+        sqlx::query("UPDATE dataset_index_stats SET embeddings = 0, updated_at = NOW()")
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                GeoragError::Serialization(format!("Failed to reset index stats: {}", e))
+            })?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO dataset_index_stats (dataset_id, embeddings)
+            SELECT dataset_id, COUNT(*)
+            FROM embeddings
+            WHERE dataset_id IS NOT NULL
+            GROUP BY 1
+            ON CONFLICT (dataset_id) DO UPDATE
+            SET embeddings = EXCLUDED.embeddings,
+                updated_at = NOW()
+            "#,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to recount embeddings: {}", e)))?;
+
+        tx.commit().await.map_err(|e| {
+            GeoragError::Serialization(format!("Failed to commit transaction: {}", e))
+        })?;
+
+        VectorStore::dataset_index_stats(self).await
+    }
 }
 
 /// Parse pgvector format string "[1.0,2.0,3.0]" to Vec<f32>
@@ -1,12 +1,16 @@
 use async_trait::async_trait;
+use georag_core::config::WorkspaceSettings;
 use georag_core::error::{GeoragError, Result};
 use georag_core::models::workspace::{DistanceUnit, ValidityMode};
 use georag_core::models::{
-    DatasetId, DatasetMeta, GeometryType, WorkspaceConfig, WorkspaceId, WorkspaceMeta,
+    DatasetId, DatasetMeta, GeometryType, UsageDelta, WorkspaceConfig, WorkspaceId, WorkspaceMeta,
+    WorkspaceUsage,
 };
+use sqlx::postgres::PgRow;
 use sqlx::Row;
 use uuid::Uuid;
 
+use super::spatial::preview_from_row;
 use super::PostgresStore;
 use crate::ports::WorkspaceStore;
 
@@ -92,7 +96,7 @@ impl WorkspaceStore for PostgresStore {
             r#"
             SELECT id, name, crs, distance_unit, geometry_validity, created_at
             FROM workspaces
-            ORDER BY created_at DESC
+            ORDER BY name
             "#,
         )
         .fetch_all(&self.pool)
@@ -139,6 +143,29 @@ impl WorkspaceStore for PostgresStore {
         Ok(workspaces)
     }
 
+    async fn update_workspace_config(
+        &self,
+        id: WorkspaceId,
+        config: &WorkspaceConfig,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE workspaces
+            SET crs = $2, distance_unit = $3, geometry_validity = $4
+            WHERE id = $1
+            "#,
+        )
+        .bind(id.0)
+        .bind(format!("EPSG:{}", config.crs))
+        .bind(format!("{:?}", config.distance_unit))
+        .bind(format!("{:?}", config.geometry_validity))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoragError::Serialization(format!("Failed to update workspace: {}", e)))?;
+
+        Ok(())
+    }
+
     async fn delete_workspace(&self, id: WorkspaceId) -> Result<()> {
         sqlx::query("DELETE FROM workspaces WHERE id = $1")
             .bind(id.0)
@@ -157,10 +184,11 @@ impl WorkspaceStore for PostgresStore {
     ) -> Result<Vec<DatasetMeta>> {
         let rows = sqlx::query(
             r#"
-            SELECT id, name, crs, geometry_type, feature_count, created_at
+            SELECT id, name, crs, geometry_type, feature_count, created_at, tags, license,
+                   attribution, metadata->'preview' AS preview
             FROM datasets
             WHERE workspace_id = $1
-            ORDER BY created_at DESC
+            ORDER BY name, id
             "#,
         )
         .bind(workspace_id.0)
@@ -200,6 +228,10 @@ impl WorkspaceStore for PostgresStore {
                     feature_count: row.get::<i32, _>("feature_count") as usize,
                     crs,
                     added_at: row.get("created_at"),
+                    tags: row.get("tags"),
+                    license: row.get("license"),
+                    attribution: row.get("attribution"),
+                    preview: preview_from_row(&row),
                 }
             })
             .collect();
@@ -223,4 +255,115 @@ impl WorkspaceStore for PostgresStore {
 
         Ok(())
     }
+
+    async fn get_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Option<WorkspaceSettings>> {
+        let json: Option<serde_json::Value> =
+            sqlx::query_scalar("SELECT settings FROM workspace_settings WHERE workspace_id = $1")
+                .bind(workspace_id.0)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    GeoragError::Serialization(format!("Failed to load workspace settings: {}", e))
+                })?;
+
+        json.map(|json| {
+            serde_json::from_value(json).map_err(|e| {
+                GeoragError::Serialization(format!("Failed to parse workspace settings: {}", e))
+            })
+        })
+        .transpose()
+    }
+
+    async fn save_workspace_settings(
+        &self,
+        workspace_id: WorkspaceId,
+        settings: &WorkspaceSettings,
+    ) -> Result<()> {
+        let json = serde_json::to_value(settings).map_err(|e| {
+            GeoragError::Serialization(format!("Failed to serialize workspace settings: {}", e))
+        })?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO workspace_settings (workspace_id, settings, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (workspace_id) DO UPDATE
+            SET settings = EXCLUDED.settings,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(json)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to save workspace settings: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    async fn get_workspace_usage(&self, workspace_id: WorkspaceId) -> Result<WorkspaceUsage> {
+        let row = sqlx::query(
+            r#"
+            SELECT datasets, features, chunks, blob_bytes
+            FROM workspace_usage
+            WHERE workspace_id = $1
+            "#,
+        )
+        .bind(workspace_id.0)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to load workspace usage: {}", e))
+        })?;
+
+        Ok(row.map(|row| usage_from_row(&row)).unwrap_or_default())
+    }
+
+    async fn adjust_workspace_usage(
+        &self,
+        workspace_id: WorkspaceId,
+        delta: &UsageDelta,
+    ) -> Result<WorkspaceUsage> {
+        // One statement, so concurrent adjustments never lose an update
+        let row = sqlx::query(
+            r#"
+            INSERT INTO workspace_usage (workspace_id, datasets, features, chunks, blob_bytes)
+            VALUES ($1, GREATEST($2, 0), GREATEST($3, 0), GREATEST($4, 0), GREATEST($5, 0))
+            ON CONFLICT (workspace_id) DO UPDATE
+            SET datasets = GREATEST(workspace_usage.datasets + $2, 0),
+                features = GREATEST(workspace_usage.features + $3, 0),
+                chunks = GREATEST(workspace_usage.chunks + $4, 0),
+                blob_bytes = GREATEST(workspace_usage.blob_bytes + $5, 0),
+                updated_at = NOW()
+            RETURNING datasets, features, chunks, blob_bytes
+            "#,
+        )
+        .bind(workspace_id.0)
+        .bind(delta.datasets)
+        .bind(delta.features)
+        .bind(delta.chunks)
+        .bind(delta.blob_bytes)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            GeoragError::Serialization(format!("Failed to update workspace usage: {}", e))
+        })?;
+
+        Ok(usage_from_row(&row))
+    }
+}
+
+fn usage_from_row(row: &PgRow) -> WorkspaceUsage {
+    let count = |column: &str| row.get::<i64, _>(column).max(0) as u64;
+    WorkspaceUsage {
+        datasets: count("datasets"),
+        features: count("features"),
+        chunks: count("chunks"),
+        blob_bytes: count("blob_bytes"),
+    }
 }
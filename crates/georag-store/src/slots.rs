@@ -0,0 +1,44 @@
+//! Instance-wide limits enforced through a shared slot store
+
+use async_trait::async_trait;
+use georag_core::error::Result;
+use georag_core::resources::{ResourceGate, ResourceGuard, ResourceKind, ResourceLimits};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::ports::SlotStore;
+
+/// How often a slot is tried again while all are held
+pub const SLOT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Gate taking slots of a store shared with other processes
+///
+/// Used by processes with no queue of their own, such as CLI runs, which
+/// wait for a slot however long it takes.
+#[derive(Clone)]
+pub struct SharedSlots {
+    store: Arc<dyn SlotStore>,
+    limits: ResourceLimits,
+}
+
+impl SharedSlots {
+    pub fn new(store: Arc<dyn SlotStore>, limits: ResourceLimits) -> Self {
+        Self { store, limits }
+    }
+
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// Take a slot of `kind` if one is free
+    pub async fn try_acquire(&self, kind: ResourceKind) -> Result<Option<ResourceGuard>> {
+        self.store.try_acquire_slot(kind, self.limits.limit(kind)).await
+    }
+}
+
+#[async_trait]
+impl ResourceGate for SharedSlots {
+    async fn acquire(&self, kind: ResourceKind) -> Result<ResourceGuard> {
+        self.store.acquire_slot(kind, self.limits.limit(kind), SLOT_POLL_INTERVAL).await
+    }
+}
@@ -0,0 +1,102 @@
+//! Area store conformance across implementations
+//!
+//! Every area store keeps one area per name within a workspace: creating a
+//! taken name stores nothing and reports it, listings are sorted by name,
+//! other workspaces do not see the area, and deleting reports whether an
+//! area was removed.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use georag_core::models::{
+    AreaSource, DatasetId, DistanceUnit, FeatureId, Geometry, SavedArea, ValidityMode,
+    WorkspaceConfig, WorkspaceId,
+};
+use georag_store::memory::MemoryAreaStore;
+use georag_store::ports::AreaStore;
+
+fn boundary() -> Geometry {
+    Geometry::polygon(vec![vec![
+        [106.80, -6.20],
+        [106.85, -6.20],
+        [106.85, -6.15],
+        [106.80, -6.15],
+        [106.80, -6.20],
+    ]])
+}
+
+async fn check_area_roundtrip(store: &dyn AreaStore, workspace: WorkspaceId, other: WorkspaceId) {
+    assert!(store.get_area(workspace, "project-x").await.unwrap().is_none());
+
+    let source = AreaSource {
+        dataset_id: DatasetId(3),
+        feature_id: FeatureId(11),
+    };
+    let area = SavedArea::new("project-x", boundary()).with_source(source);
+    assert!(store.create_area(workspace, &area).await.unwrap());
+    assert!(store
+        .create_area(workspace, &SavedArea::new("harbour", boundary()))
+        .await
+        .unwrap());
+
+    // A taken name keeps the first area
+    let replacement = SavedArea::new("project-x", Geometry::point(0.0, 0.0));
+    assert!(!store.create_area(workspace, &replacement).await.unwrap());
+
+    let stored = store.get_area(workspace, "project-x").await.unwrap().unwrap();
+    assert_eq!(stored.geometry, boundary());
+    assert_eq!(stored.source, Some(source));
+    assert_eq!(stored.vertex_count(), 5);
+
+    let names: Vec<String> =
+        store.list_areas(workspace).await.unwrap().into_iter().map(|a| a.name).collect();
+    assert_eq!(names, vec!["harbour", "project-x"]);
+
+    // Names are scoped to the workspace
+    assert!(store.get_area(other, "project-x").await.unwrap().is_none());
+    assert!(store.list_areas(other).await.unwrap().is_empty());
+
+    assert!(store.delete_area(workspace, "project-x").await.unwrap());
+    assert!(!store.delete_area(workspace, "project-x").await.unwrap());
+    assert!(store.get_area(workspace, "project-x").await.unwrap().is_none());
+    assert_eq!(store.list_areas(workspace).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_memory_area_store() {
+    check_area_roundtrip(&MemoryAreaStore::new(), WorkspaceId::new(), WorkspaceId::new()).await;
+}
+
+#[tokio::test]
+async fn test_postgres_area_store() {
+    use georag_store::ports::WorkspaceStore;
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL areas");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Areas belong to a workspace row
+    let config = WorkspaceConfig {
+        crs: 4326,
+        distance_unit: DistanceUnit::Meters,
+        geometry_validity: ValidityMode::Lenient,
+    };
+    let suffix = chrono::Utc::now().timestamp_micros();
+    let workspace = store.create_workspace(&format!("areas-{}", suffix), &config).await.unwrap();
+    let other = store
+        .create_workspace(&format!("areas-other-{}", suffix), &config)
+        .await
+        .unwrap();
+
+    check_area_roundtrip(&store, workspace, other).await;
+
+    // Deleting the workspace removes its areas
+    store.delete_workspace(workspace).await.unwrap();
+    assert!(store.list_areas(workspace).await.unwrap().is_empty());
+    store.delete_workspace(other).await.unwrap();
+}
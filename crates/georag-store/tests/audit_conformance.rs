@@ -0,0 +1,100 @@
+//! Audit store conformance across implementations
+//!
+//! Every audit store lists a workspace's events since a point in time,
+//! oldest first, counts them in the same UTC buckets whether it aggregates
+//! itself or falls back to counting listed events, and lists notable
+//! events newest first. Other workspaces do not see the events.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::{DateTime, Duration, Utc};
+use georag_core::models::{
+    count_events, AuditEvent, AuditEventKind, DistanceUnit, TimelineGranularity, ValidityMode,
+    WorkspaceConfig, WorkspaceId,
+};
+use georag_store::memory::MemoryAuditStore;
+use georag_store::ports::AuditStore;
+
+fn event(kind: AuditEventKind, at: DateTime<Utc>) -> AuditEvent {
+    AuditEvent { kind, at, details: None }
+}
+
+async fn check_audit_events(store: &dyn AuditStore, workspace: WorkspaceId, other: WorkspaceId) {
+    // Whole seconds, so stores keeping microseconds return the same times
+    let now = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+    let events = [
+        event(AuditEventKind::DatasetAdded, now - Duration::days(3)),
+        event(AuditEventKind::Query, now - Duration::days(2)),
+        event(AuditEventKind::Query, now - Duration::days(2) + Duration::minutes(5)),
+        event(AuditEventKind::BuildFailed, now - Duration::days(1))
+            .with_details("Embedder unavailable"),
+        event(AuditEventKind::QuotaExceeded, now - Duration::hours(1)),
+    ];
+    for event in &events {
+        store.record_event(workspace, event).await.unwrap();
+    }
+    store.record_event(other, &event(AuditEventKind::Query, now)).await.unwrap();
+
+    let since = now - Duration::days(2) - Duration::hours(1);
+    let listed = store.list_events(workspace, since).await.unwrap();
+    assert_eq!(listed, events[1..].to_vec());
+
+    for granularity in
+        [TimelineGranularity::Hour, TimelineGranularity::Day, TimelineGranularity::Week]
+    {
+        let counts = store.count_events(workspace, since, granularity).await.unwrap();
+        assert_eq!(counts, count_events(&listed, granularity), "{}", granularity);
+    }
+
+    let notable = store.notable_events(workspace, since, 10).await.unwrap();
+    let kinds: Vec<AuditEventKind> = notable.iter().map(|event| event.kind).collect();
+    assert_eq!(kinds, vec![AuditEventKind::QuotaExceeded, AuditEventKind::BuildFailed]);
+    assert_eq!(notable[1].details.as_deref(), Some("Embedder unavailable"));
+    assert_eq!(store.notable_events(workspace, since, 1).await.unwrap().len(), 1);
+
+    assert_eq!(store.list_events(other, since).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_memory_audit_store() {
+    check_audit_events(&MemoryAuditStore::new(), WorkspaceId::new(), WorkspaceId::new()).await;
+}
+
+#[tokio::test]
+async fn test_postgres_audit_store() {
+    use georag_store::ports::WorkspaceStore;
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL audit events");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Events belong to a workspace row
+    let config = WorkspaceConfig {
+        crs: 4326,
+        distance_unit: DistanceUnit::Meters,
+        geometry_validity: ValidityMode::Lenient,
+    };
+    let suffix = Utc::now().timestamp_micros();
+    let workspace = store.create_workspace(&format!("audit-{}", suffix), &config).await.unwrap();
+    let other = store
+        .create_workspace(&format!("audit-other-{}", suffix), &config)
+        .await
+        .unwrap();
+
+    check_audit_events(&store, workspace, other).await;
+
+    // Deleting the workspace removes its events
+    store.delete_workspace(workspace).await.unwrap();
+    assert!(store
+        .list_events(workspace, Utc::now() - Duration::days(7))
+        .await
+        .unwrap()
+        .is_empty());
+    store.delete_workspace(other).await.unwrap();
+}
@@ -0,0 +1,95 @@
+//! Blob store conformance across implementations
+//!
+//! Every blob store keeps one blob per dataset: writes replace the previous
+//! content, reads of a missing blob return `None`, and deleting a missing
+//! blob succeeds.
+//!
+//! The memory and filesystem stores always run. The PostgreSQL store runs
+//! when `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{Dataset, DatasetId, GeometryType};
+use georag_store::filesystem::FilesystemBlobStore;
+use georag_store::memory::MemoryBlobStore;
+use georag_store::ports::{BlobStore, SpatialStore};
+use std::path::PathBuf;
+
+async fn check_blob_roundtrip(store: &dyn BlobStore, id: DatasetId) {
+    assert!(store.get_blob(id).await.unwrap().is_none());
+
+    store.put_blob(id, b"first").await.unwrap();
+    store.put_blob(id, b"{\"type\":\"FeatureCollection\"}").await.unwrap();
+    let content = store.get_blob(id).await.unwrap().unwrap();
+    assert_eq!(content, b"{\"type\":\"FeatureCollection\"}");
+
+    store.delete_blob(id).await.unwrap();
+    assert!(store.get_blob(id).await.unwrap().is_none());
+    store.delete_blob(id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_blob_store() {
+    check_blob_roundtrip(&MemoryBlobStore::new(), DatasetId(7)).await;
+}
+
+#[tokio::test]
+async fn test_filesystem_blob_store() {
+    let root = std::env::temp_dir().join(format!("georag-blobs-{}", std::process::id()));
+    let store = FilesystemBlobStore::new(&root);
+
+    check_blob_roundtrip(&store, DatasetId(7)).await;
+    store.put_blob(DatasetId(8), b"kept").await.unwrap();
+    assert!(root.join("8").exists());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_blob_store() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL blobs");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Blobs belong to a dataset row
+    let id = DatasetId(Utc::now().timestamp_micros() as u64);
+    let dataset = Dataset {
+        id,
+        name: format!("blobs-{}", id.0),
+        path: PathBuf::from("/data/blobs.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 0,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    };
+    let id = store.store_dataset(&dataset).await.unwrap();
+
+    check_blob_roundtrip(&store, id).await;
+
+    // Deleting the dataset removes its blob
+    store.put_blob(id, b"kept").await.unwrap();
+    store.delete_dataset(id).await.unwrap();
+    assert!(store.get_blob(id).await.unwrap().is_none());
+}
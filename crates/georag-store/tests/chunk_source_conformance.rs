@@ -0,0 +1,104 @@
+//! Chunk source conformance across stores
+//!
+//! A chunk's offset and length are characters of the normalized text it was
+//! cut from, and they must come back unchanged from every `DocumentStore`:
+//! overlapping chunks are merged in query results by comparing their ranges.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, Dataset, DatasetId, Feature, FeatureId, Geometry, GeometryType,
+};
+use georag_core::processing::ChunkGenerator;
+use georag_store::memory::MemoryDocumentStore;
+use georag_store::ports::DocumentStore;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn dataset() -> Dataset {
+    Dataset {
+        id: DatasetId(1),
+        name: "reports".to_string(),
+        path: PathBuf::from("/data/reports.geojson"),
+        geometry_type: GeometryType::Point,
+        feature_count: 1,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+/// Store overlapping chunks of one feature and check their character ranges
+async fn check_char_offsets(store: &dyn DocumentStore, base: u64) {
+    // Two bytes for the accent, so byte and character offsets differ
+    let text = (0..20).map(|i| format!("pél{}", i)).collect::<Vec<_>>().join(" ");
+    let mut properties = HashMap::new();
+    properties.insert("content".to_string(), serde_json::Value::String(text.clone()));
+    let feature =
+        Feature::with_geometry(FeatureId(1), Geometry::point(106.8, -6.2), properties, 4326);
+
+    let generator = ChunkGenerator::new(4, 8, 3).unwrap();
+    let mut chunks = generator.generate_chunks(&dataset(), &[feature]);
+    for (i, chunk) in chunks.iter_mut().enumerate() {
+        // Keep this run's chunks apart, and free of features the store lacks
+        chunk.id = ChunkId(base + i as u64);
+        chunk.spatial_ref = None;
+    }
+    store.store_chunks(&chunks).await.unwrap();
+
+    let ids: Vec<ChunkId> = chunks.iter().map(|c| c.id).collect();
+    let stored = store.get_chunks(&ids).await.unwrap();
+    let ranges: Vec<(usize, usize)> =
+        stored.iter().map(|c| (c.source.offset, c.source.length)).collect();
+    assert_eq!(ranges, vec![(0, 39), (25, 42), (50, 59)]);
+
+    // Each chunk is the slice of the normalized text at its range
+    let normalized: Vec<char> = generator.normalized_text(&text).chars().collect();
+    for chunk in &stored {
+        let slice: String = normalized
+            [chunk.source.offset..chunk.source.offset + chunk.source.length]
+            .iter()
+            .collect();
+        assert_eq!(chunk.content, slice);
+    }
+}
+
+#[tokio::test]
+async fn test_memory_store_keeps_char_offsets() {
+    check_char_offsets(&MemoryDocumentStore::new(), 0).await;
+}
+
+#[tokio::test]
+async fn test_postgres_store_keeps_char_offsets() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL chunk source conformance");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's rows apart from anything already in the database
+    let base = (Utc::now().timestamp_micros() as u64) << 8;
+    check_char_offsets(&store, base).await;
+}
@@ -0,0 +1,114 @@
+//! Event log store conformance across implementations
+//!
+//! Every event log numbers appended events consecutively after the newest
+//! one, reads them back in order after any sequence number, prunes only the
+//! oldest ones, and keeps the cursors of consumers. The log is shared by all
+//! workspaces, so the checks only rely on sequence numbers relative to the
+//! newest event when they start.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::{Duration, Utc};
+use georag_core::models::{
+    ChangeEntity, ChangeEvent, ChangeOperation, DatasetId, EventRetention, WorkspaceId,
+};
+use georag_store::memory::MemoryEventLog;
+use georag_store::ports::EventLogStore;
+use serde_json::json;
+
+fn event(workspace: WorkspaceId, id: u64) -> ChangeEvent {
+    ChangeEvent::new(workspace, ChangeEntity::Feature, ChangeOperation::Upsert, vec![id, id + 1])
+        .with_dataset(DatasetId(7))
+        .with_payload(json!([{ "id": id }]))
+}
+
+async fn check_event_log(store: &dyn EventLogStore) {
+    let workspace = WorkspaceId::new();
+    let start = store.event_bounds().await.unwrap().last;
+
+    let last = store.append_events(&[event(workspace, 1), event(workspace, 3)]).await.unwrap();
+    assert_eq!(last, start + 2);
+    let last = store.append_events(&[event(workspace, 5)]).await.unwrap();
+    assert_eq!(last, start + 3);
+    assert_eq!(store.append_events(&[]).await.unwrap(), start + 3);
+
+    let events = store.events_after(start, 10).await.unwrap();
+    let seqs: Vec<u64> = events.iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![start + 1, start + 2, start + 3]);
+    assert_eq!(events[1].workspace_id, workspace);
+    assert_eq!(events[1].entity, ChangeEntity::Feature);
+    assert_eq!(events[1].operation, ChangeOperation::Upsert);
+    assert_eq!(events[1].dataset_id, Some(DatasetId(7)));
+    assert_eq!(events[1].ids, vec![3, 4]);
+    assert_eq!(events[1].payload, Some(json!([{ "id": 3 }])));
+
+    let page = store.events_after(start + 1, 1).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].seq, start + 2);
+    assert!(store.events_after(start + 3, 10).await.unwrap().is_empty());
+
+    // Keeping two events drops everything before them, and nothing more on a second prune
+    let retention = EventRetention { max_events: 2, max_age: None };
+    assert!(store.prune_events(&retention).await.unwrap() >= 1);
+    assert_eq!(store.prune_events(&retention).await.unwrap(), 0);
+    let bounds = store.event_bounds().await.unwrap();
+    assert_eq!(bounds.first, Some(start + 2));
+    assert_eq!(bounds.last, start + 3);
+    assert!(bounds.is_pruned(start));
+    assert!(!bounds.is_pruned(start + 1));
+    assert_eq!(store.events_after(start, 10).await.unwrap()[0].seq, start + 2);
+
+    // Old events go by age even when fewer than the most kept
+    let mut old = event(workspace, 9);
+    old.at = Utc::now() - Duration::days(30);
+    let last = store.append_events(&[old]).await.unwrap();
+    let retention = EventRetention {
+        max_events: 100,
+        max_age: Some(Duration::days(7)),
+    };
+    store.prune_events(&retention).await.unwrap();
+    let bounds = store.event_bounds().await.unwrap();
+    assert_eq!(bounds.first, None);
+    assert_eq!(bounds.last, last, "pruning keeps the numbering");
+
+    let consumer = format!("conformance-{}", workspace);
+    assert_eq!(store.get_event_cursor(&consumer).await.unwrap(), None);
+    store.set_event_cursor(&consumer, start + 2).await.unwrap();
+    store.set_event_cursor(&consumer, start + 3).await.unwrap();
+    assert_eq!(store.get_event_cursor(&consumer).await.unwrap(), Some(start + 3));
+}
+
+#[tokio::test]
+async fn test_memory_event_log() {
+    check_event_log(&MemoryEventLog::new()).await;
+}
+
+#[tokio::test]
+async fn test_memory_event_log_drops_the_oldest_events_past_its_capacity() {
+    let store = MemoryEventLog::with_capacity(3);
+    let workspace = WorkspaceId::new();
+    let events: Vec<ChangeEvent> = (0..5).map(|id| event(workspace, id)).collect();
+    assert_eq!(store.append_events(&events).await.unwrap(), 5);
+
+    let bounds = store.event_bounds().await.unwrap();
+    assert_eq!(bounds.first, Some(3));
+    assert_eq!(bounds.last, 5);
+    let seqs: Vec<u64> =
+        store.events_after(0, 10).await.unwrap().iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_postgres_event_log() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL event log");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+    check_event_log(&store).await;
+}
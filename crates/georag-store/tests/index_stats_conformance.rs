@@ -0,0 +1,202 @@
+//! Per-dataset index counter conformance across stores
+//!
+//! Counters follow every chunk and embedding write, replacement and deletion
+//! without a scan. Each test then desynchronizes them on purpose and checks
+//! that a recount restores what the stores hold.
+//!
+//! The memory stores always run. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::Utc;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, DatasetId, DatasetIndexStats, Embedding, TextChunk,
+};
+use georag_store::memory::{MemoryDocumentStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, VectorStore};
+use std::collections::{BTreeMap, HashMap};
+
+fn chunk(id: u64, dataset_id: DatasetId, content: &str) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: content.to_string(),
+        source: ChunkSource {
+            document_path: "/data/counters.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: content.chars().count(),
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: content.split_whitespace().count(),
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: Some(dataset_id),
+        },
+    }
+}
+
+fn embedding(id: u64, dataset_id: DatasetId) -> Embedding {
+    Embedding {
+        chunk_id: ChunkId(id),
+        vector: vec![0.6, 0.8, 0.0],
+        spatial_metadata: None,
+        dataset_id: Some(dataset_id),
+    }
+}
+
+fn stats(chunks: u64, content_bytes: u64, embeddings: u64) -> DatasetIndexStats {
+    DatasetIndexStats { chunks, content_bytes, embeddings }
+}
+
+/// Counters of both stores for the given datasets only
+///
+/// A shared database holds the datasets of other runs as well.
+async fn counters(
+    documents: &dyn DocumentStore,
+    vectors: &dyn VectorStore,
+    own: &[DatasetId],
+) -> BTreeMap<DatasetId, DatasetIndexStats> {
+    let mut counters: BTreeMap<DatasetId, DatasetIndexStats> = BTreeMap::new();
+    let chunks = documents.dataset_index_stats().await.unwrap();
+    let embeddings = vectors.dataset_index_stats().await.unwrap();
+    for (dataset_id, stats) in chunks.iter().chain(embeddings.iter()) {
+        if own.contains(dataset_id) {
+            counters.entry(*dataset_id).or_default().add(stats);
+        }
+    }
+    counters
+}
+
+/// Write, replace and delete chunks and embeddings of two datasets,
+/// checking the counters after each step
+///
+/// Leaves two chunks of `harbour`, one of them embedded.
+async fn check_counters(
+    documents: &dyn DocumentStore,
+    vectors: &dyn VectorStore,
+    base: u64,
+    harbour: DatasetId,
+    market: DatasetId,
+) {
+    let own = [harbour, market];
+    documents
+        .store_chunks(&[
+            chunk(base + 1, harbour, "crane"),
+            chunk(base + 2, harbour, "quay wall"),
+            chunk(base + 3, harbour, "pélican"),
+            chunk(base + 4, market, "fish stalls"),
+            chunk(base + 5, market, "spice"),
+        ])
+        .await
+        .unwrap();
+    vectors
+        .store_embeddings(&[
+            embedding(base + 1, harbour),
+            embedding(base + 2, harbour),
+            embedding(base + 4, market),
+        ])
+        .await
+        .unwrap();
+
+    // Content is counted in bytes, so "pélican" counts 8
+    let expected = BTreeMap::from([(harbour, stats(3, 22, 2)), (market, stats(2, 16, 1))]);
+    assert_eq!(counters(documents, vectors, &own).await, expected);
+
+    // Replacing a chunk or an embedding counts it once
+    documents.store_chunks(&[chunk(base + 2, harbour, "quay")]).await.unwrap();
+    vectors.store_embeddings(&[embedding(base + 2, harbour)]).await.unwrap();
+    let expected = BTreeMap::from([(harbour, stats(3, 17, 2)), (market, stats(2, 16, 1))]);
+    assert_eq!(counters(documents, vectors, &own).await, expected);
+
+    // Deleting every chunk of a dataset drops it from the counters
+    vectors
+        .delete_embeddings(&[ChunkId(base + 1), ChunkId(base + 4)])
+        .await
+        .unwrap();
+    documents
+        .delete_chunks(&[ChunkId(base + 1), ChunkId(base + 4), ChunkId(base + 5)])
+        .await
+        .unwrap();
+    let expected = BTreeMap::from([(harbour, stats(2, 12, 1))]);
+    assert_eq!(counters(documents, vectors, &own).await, expected);
+}
+
+/// Recount both stores and check the counters match the data again
+async fn check_recount(
+    documents: &dyn DocumentStore,
+    vectors: &dyn VectorStore,
+    harbour: DatasetId,
+    market: DatasetId,
+) {
+    let own = [harbour, market];
+    documents.recount_index_stats().await.unwrap();
+    vectors.recount_index_stats().await.unwrap();
+    let expected = BTreeMap::from([(harbour, stats(2, 12, 1))]);
+    assert_eq!(counters(documents, vectors, &own).await, expected);
+}
+
+#[tokio::test]
+async fn test_memory_index_stats() {
+    let documents = MemoryDocumentStore::new();
+    let vectors = MemoryVectorStore::new();
+    let (harbour, market) = (DatasetId(1), DatasetId(2));
+    check_counters(&documents, &vectors, 0, harbour, market).await;
+
+    // Drift in both directions, including a dataset without data
+    documents.set_index_stats(harbour, stats(7, 3, 0));
+    vectors.set_index_stats(harbour, stats(0, 0, 0));
+    documents.set_index_stats(market, stats(4, 40, 0));
+    vectors.set_index_stats(market, stats(0, 0, 9));
+    assert_ne!(
+        counters(&documents, &vectors, &[harbour, market]).await,
+        BTreeMap::from([(harbour, stats(2, 12, 1))])
+    );
+
+    check_recount(&documents, &vectors, harbour, market).await;
+}
+
+#[tokio::test]
+async fn test_postgres_index_stats() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL index stats");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's rows and datasets apart from anything already in the database
+    let base = (Utc::now().timestamp_micros() as u64) << 8;
+    let (harbour, market) = (DatasetId(base + 1), DatasetId(base + 2));
+    check_counters(&store, &store, base, harbour, market).await;
+
+    // Drift the counters behind the triggers' back
+    sqlx::query(
+        r#"
+        INSERT INTO dataset_index_stats (dataset_id, chunks, content_bytes, embeddings)
+        VALUES ($1, 7, 3, 0), ($2, 4, 40, 9)
+        ON CONFLICT (dataset_id) DO UPDATE
+        SET chunks = EXCLUDED.chunks,
+            content_bytes = EXCLUDED.content_bytes,
+            embeddings = EXCLUDED.embeddings
+        "#,
+    )
+    .bind(harbour.0 as i64)
+    .bind(market.0 as i64)
+    .execute(store.pool())
+    .await
+    .unwrap();
+    assert_ne!(
+        counters(&store, &store, &[harbour, market]).await,
+        BTreeMap::from([(harbour, stats(2, 12, 1))])
+    );
+
+    check_recount(&store, &store, harbour, market).await;
+
+    let ids: Vec<ChunkId> = [2, 3].iter().map(|id| ChunkId(base + id)).collect();
+    DocumentStore::delete_chunks(&store, &ids).await.unwrap();
+}
@@ -0,0 +1,203 @@
+//! Listing order conformance across stores
+//!
+//! Every listing has a defined order so pagination and diffs are stable:
+//! datasets by name then ID, features by ID, chunk and embedding IDs
+//! ascending, and similarity results by score then chunk ID. Fixtures are
+//! written out of order, and feature IDs of different lengths catch stores
+//! that sort decimal IDs as text.
+//!
+//! The memory stores always run. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::Utc;
+use georag_core::models::dataset::FormatMetadata;
+use georag_core::models::{
+    ChunkId, ChunkMetadata, ChunkSource, Dataset, DatasetId, Embedding, Feature, FeatureId,
+    Geometry, GeometryType, SpatialFilter, SpatialPredicate, TextChunk,
+};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::{DocumentStore, SpatialStore, VectorStore};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Feature and chunk IDs relative to the run's base, deliberately unsorted
+const IDS: [u64; 5] = [100, 9, 10, 2, 11];
+
+fn dataset(name: &str) -> Dataset {
+    Dataset {
+        id: DatasetId(0),
+        name: name.to_string(),
+        path: PathBuf::from(format!("/data/{}.geojson", name)),
+        geometry_type: GeometryType::Point,
+        feature_count: 0,
+        crs: 4326,
+        format: FormatMetadata {
+            format_name: "GeoJSON".to_string(),
+            format_version: None,
+            layer_name: None,
+            page_count: None,
+            paragraph_count: None,
+            extraction_method: None,
+            spatial_association: None,
+            axis_order: None,
+            source: None,
+            preview: None,
+            remote: None,
+        },
+        added_at: Utc::now(),
+        tags: Vec::new(),
+        license: None,
+        attribution: None,
+    }
+}
+
+fn chunk(id: u64) -> TextChunk {
+    TextChunk {
+        id: ChunkId(id),
+        content: format!("chunk {}", id),
+        source: ChunkSource {
+            document_path: "/data/ordering.geojson".to_string(),
+            page: None,
+            offset: 0,
+            length: 0,
+        },
+        spatial_ref: None,
+        geometry: None,
+        metadata: ChunkMetadata {
+            size: 8,
+            properties: HashMap::new(),
+            source_hash: None,
+            stale: false,
+            dataset_id: None,
+        },
+    }
+}
+
+fn sorted(mut ids: Vec<u64>) -> Vec<u64> {
+    ids.sort();
+    ids
+}
+
+fn sorted_names(names: &[String]) -> Vec<String> {
+    let mut names = names.to_vec();
+    names.sort();
+    names
+}
+
+/// Keep the IDs written by this run, as offsets from `base`, in listed order
+fn own_ids(listed: impl IntoIterator<Item = u64>, base: u64) -> Vec<u64> {
+    listed
+        .into_iter()
+        .map(|id| id.wrapping_sub(base))
+        .filter(|id| IDS.contains(id))
+        .collect()
+}
+
+async fn check_spatial_order(store: &dyn SpatialStore, base: u64, run: &str) {
+    // Datasets are added in reverse name order
+    let names: Vec<String> = ["c", "b", "a"].iter().map(|n| format!("{}-{}", run, n)).collect();
+    let mut dataset_id = None;
+    for name in &names {
+        dataset_id = Some(store.store_dataset(&dataset(name)).await.unwrap());
+    }
+
+    let listed: Vec<String> = store
+        .list_datasets()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|d| d.name)
+        .filter(|name| names.contains(name))
+        .collect();
+    assert_eq!(listed, sorted_names(&names));
+
+    let features: Vec<Feature> = IDS
+        .iter()
+        .map(|id| {
+            let geometry = Geometry::point(*id as f64 / 1000.0, 0.5);
+            Feature::with_geometry(FeatureId(base + id), geometry, HashMap::new(), 4326)
+        })
+        .collect();
+    let dataset_id = dataset_id.unwrap();
+    store.upsert_dataset_features(dataset_id, &features).await.unwrap();
+
+    let expected = sorted(IDS.to_vec());
+    let members = store.get_features_for_dataset(dataset_id).await.unwrap();
+    assert_eq!(own_ids(members.iter().map(|f| f.id.0), base), expected);
+
+    let square =
+        Geometry::polygon(vec![vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]]);
+    let filter = SpatialFilter::new(SpatialPredicate::Intersects).geometry(square);
+    let matched = store.spatial_query(&filter).await.unwrap();
+    assert_eq!(own_ids(matched.iter().map(|f| f.id.0), base), expected);
+}
+
+async fn check_document_order(documents: &dyn DocumentStore, vectors: &dyn VectorStore, base: u64) {
+    let chunks: Vec<TextChunk> = IDS.iter().map(|id| chunk(base + id)).collect();
+    documents.store_chunks(&chunks).await.unwrap();
+
+    let expected = sorted(IDS.to_vec());
+    let listed = documents.list_chunk_ids().await.unwrap();
+    assert_eq!(own_ids(listed.iter().map(|id| id.0), base), expected);
+
+    // Chunks come back in the order they were asked for
+    let requested: Vec<ChunkId> = IDS.iter().rev().map(|id| ChunkId(base + id)).collect();
+    let fetched = documents.get_chunks(&requested).await.unwrap();
+    let fetched: Vec<ChunkId> = fetched.into_iter().map(|c| c.id).collect();
+    assert_eq!(fetched, requested);
+
+    // Identical vectors tie on score, so chunk IDs decide the order
+    let embeddings: Vec<Embedding> = IDS
+        .iter()
+        .map(|id| Embedding {
+            chunk_id: ChunkId(base + id),
+            vector: vec![0.6, 0.8, 0.0],
+            spatial_metadata: None,
+            dataset_id: None,
+        })
+        .collect();
+    vectors.store_embeddings(&embeddings).await.unwrap();
+
+    let listed = vectors.list_embedding_ids().await.unwrap();
+    assert_eq!(own_ids(listed.iter().map(|id| id.0), base), expected);
+
+    let results = vectors.similarity_search(&[0.6, 0.8, 0.0], 3, None).await.unwrap();
+    let ranked = own_ids(results.iter().map(|r| r.chunk_id.0), base);
+    assert_eq!(ranked, expected[..ranked.len()].to_vec());
+}
+
+#[tokio::test]
+async fn test_memory_store_ordering() {
+    check_spatial_order(&MemorySpatialStore::new(), 0, "memory").await;
+    check_document_order(&MemoryDocumentStore::new(), &MemoryVectorStore::new(), 0).await;
+}
+
+#[tokio::test]
+async fn test_memory_similarity_ties_keep_lowest_ids() {
+    let vectors = MemoryVectorStore::new();
+    check_document_order(&MemoryDocumentStore::new(), &vectors, 0).await;
+
+    // The top two of five tied results are always the two lowest IDs
+    let results = vectors.similarity_search(&[0.6, 0.8, 0.0], 2, None).await.unwrap();
+    let ids: Vec<u64> = results.iter().map(|r| r.chunk_id.0).collect();
+    assert_eq!(ids, vec![2, 9]);
+}
+
+#[tokio::test]
+async fn test_postgres_store_ordering() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL ordering");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's rows apart from anything already in the database
+    let base = (Utc::now().timestamp_micros() as u64) << 8;
+    let run = format!("ordering-{}", base);
+    check_spatial_order(&store, base, &run).await;
+    check_document_order(&store, &store, base).await;
+}
@@ -0,0 +1,159 @@
+//! Coordinate precision conformance across stores
+//!
+//! A coordinate stored in any `SpatialStore` must come back bit for bit, so
+//! survey data keeps its millimeters. The PostgreSQL store exchanges
+//! geometries as WKB for this; GeoJSON text from `ST_AsGeoJSON` would round
+//! to 9 decimal places.
+//!
+//! The memory and bundle stores always run. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use georag_core::models::{Feature, FeatureId, Geometry, SpatialFilter, SpatialPredicate};
+use georag_store::bundle::{BundleStore, OfflineBundle};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::SpatialStore;
+use proptest::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// RTK fixes with more significant digits than decimal formatting keeps
+fn survey_points() -> Vec<[f64; 2]> {
+    vec![
+        [106.827_153_012_345_67, -6.175_392_123_456_789],
+        [106.827_153_012_345_68, -6.175_392_123_456_788],
+        [-122.419_415_527_343_75, 37.774_929_046_630_86],
+        [0.000_000_001_234_567_8, -0.000_000_009_876_543_2],
+        [179.999_999_999_999_97, -89.999_999_999_999_99],
+    ]
+}
+
+fn features(base: u64, points: &[[f64; 2]]) -> Vec<Feature> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, [x, y])| {
+            Feature::with_geometry(
+                FeatureId(base + i as u64),
+                Geometry::point(*x, *y),
+                HashMap::new(),
+                4326,
+            )
+        })
+        .collect()
+}
+
+/// Store points with IDs offset by `base` and read each back by ID
+async fn check_round_trip(store: &dyn SpatialStore, base: u64, points: &[[f64; 2]]) {
+    store.store_features(&features(base, points)).await.unwrap();
+
+    for (i, [x, y]) in points.iter().enumerate() {
+        let feature = store.get_feature(FeatureId(base + i as u64)).await.unwrap().unwrap();
+        let Some(Geometry::Point { coordinates }) = feature.geometry else {
+            panic!("feature {} lost its point", i);
+        };
+        assert_eq!(
+            [coordinates[0].to_bits(), coordinates[1].to_bits()],
+            [x.to_bits(), y.to_bits()],
+            "stored {:?}, read {:?}",
+            [x, y],
+            coordinates
+        );
+    }
+}
+
+/// Round-trip points through a bundle file written from a memory store
+async fn check_bundle_round_trip(points: &[[f64; 2]]) {
+    let spatial = MemorySpatialStore::new();
+    spatial.store_features(&features(0, points)).await.unwrap();
+
+    let bundle = OfflineBundle::extract(
+        &spatial,
+        &MemoryVectorStore::new(),
+        &MemoryDocumentStore::new(),
+        [-180.0, -90.0, 180.0, 90.0],
+        None,
+    )
+    .await
+    .unwrap();
+
+    // Tests run in parallel, so every bundle gets its own file
+    static BUNDLES: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "georag-precision-{}-{}.json",
+        std::process::id(),
+        BUNDLES.fetch_add(1, Ordering::Relaxed)
+    ));
+    bundle.write_to(&path).unwrap();
+    let store = BundleStore::open(&path).await;
+    std::fs::remove_file(&path).unwrap();
+
+    let store = store.unwrap();
+    for (i, [x, y]) in points.iter().enumerate() {
+        let feature = store.get_feature(FeatureId(i as u64)).await.unwrap().unwrap();
+        let Some(Geometry::Point { coordinates }) = feature.geometry else {
+            panic!("feature {} lost its point", i);
+        };
+        assert_eq!(coordinates[0].to_bits(), x.to_bits());
+        assert_eq!(coordinates[1].to_bits(), y.to_bits());
+    }
+}
+
+#[tokio::test]
+async fn test_memory_store_keeps_survey_precision() {
+    check_round_trip(&MemorySpatialStore::new(), 0, &survey_points()).await;
+}
+
+#[tokio::test]
+async fn test_bundle_keeps_survey_precision() {
+    check_bundle_round_trip(&survey_points()).await;
+}
+
+proptest! {
+    #[test]
+    fn bundle_round_trip(
+        points in prop::collection::vec((-180.0..180.0f64, -90.0..90.0f64), 1..16)
+    ) {
+        let points: Vec<[f64; 2]> = points.into_iter().map(|(x, y)| [x, y]).collect();
+        tokio::runtime::Runtime::new().unwrap().block_on(check_bundle_round_trip(&points));
+    }
+}
+
+#[tokio::test]
+async fn test_postgres_store_keeps_survey_precision() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL precision conformance");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's features apart from anything already in the database
+    let base = (chrono::Utc::now().timestamp_micros() as u64) << 8;
+    let points = survey_points();
+    check_round_trip(&store, base, &points).await;
+
+    // The filter geometry travels at full precision too: a box 1e-12 degrees
+    // wide ending at the first fix finds it but not the second, 1e-14 degrees east
+    let [x, y] = points[0];
+    let d = 1e-12;
+    let filter =
+        SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::polygon(vec![vec![
+            [x - d, y - d],
+            [x, y - d],
+            [x, y + d],
+            [x - d, y + d],
+            [x - d, y - d],
+        ]]));
+    let matched: Vec<u64> = store
+        .spatial_query(&filter)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|f| f.id.0.wrapping_sub(base))
+        .filter(|id| *id < points.len() as u64)
+        .collect();
+    assert_eq!(matched, vec![0]);
+}
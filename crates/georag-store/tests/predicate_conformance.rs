@@ -0,0 +1,204 @@
+//! Spatial predicate conformance across stores
+//!
+//! Every `SpatialStore` must give the same answer for every predicate. The
+//! fixtures are deliberately asymmetric (a large polygon, points inside, on
+//! and outside its boundary, lines touching and crossing it, and polygons
+//! sharing an edge with it or overlapping it) so a predicate evaluated with
+//! its arguments swapped, or with different boundary rules, fails here.
+//!
+//! Queries with a filter prepared beforehand, taken from a cache shared by
+//! the queries as the API does, must answer the same as unprepared ones.
+//!
+//! The memory store and a bundle cut from it, which answers from its saved
+//! spatial index, always run. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use georag_core::geo::FilterCache;
+use georag_core::models::{Feature, FeatureId, Geometry, SpatialFilter, SpatialPredicate};
+use georag_store::bundle::{BundleStore, IndexOrigin, OfflineBundle};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::SpatialStore;
+use std::collections::{BTreeSet, HashMap};
+
+const SQUARE: u64 = 1;
+const INSIDE: u64 = 2;
+const ON_EDGE: u64 = 3;
+const OUTSIDE: u64 = 4;
+const LINE_TOUCHING: u64 = 5;
+const LINE_ON_EDGE: u64 = 6;
+const ADJACENT: u64 = 7;
+const CROSSING: u64 = 8;
+const OVERLAPPING: u64 = 9;
+
+fn square() -> Geometry {
+    rectangle(0.0, 0.0, 10.0, 10.0)
+}
+
+fn rectangle(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Geometry {
+    Geometry::polygon(vec![vec![
+        [min_x, min_y],
+        [max_x, min_y],
+        [max_x, max_y],
+        [min_x, max_y],
+        [min_x, min_y],
+    ]])
+}
+
+fn crossing() -> Geometry {
+    Geometry::line_string(vec![[-5.0, 3.0], [15.0, 3.0]])
+}
+
+fn fixtures() -> Vec<(u64, Geometry)> {
+    vec![
+        (SQUARE, square()),
+        (INSIDE, Geometry::point(5.0, 5.0)),
+        (ON_EDGE, Geometry::point(10.0, 5.0)),
+        (OUTSIDE, Geometry::point(20.0, 20.0)),
+        // Runs from the boundary into the interior
+        (LINE_TOUCHING, Geometry::line_string(vec![[0.0, 5.0], [5.0, 5.0]])),
+        // Lies entirely on the boundary
+        (LINE_ON_EDGE, Geometry::line_string(vec![[0.0, 0.0], [10.0, 0.0]])),
+        // Shares the square's east edge
+        (ADJACENT, rectangle(10.0, 0.0, 20.0, 10.0)),
+        // Runs through the square and out of both sides
+        (CROSSING, crossing()),
+        // Covers the square's north-east corner
+        (OVERLAPPING, rectangle(6.0, 6.0, 16.0, 16.0)),
+    ]
+}
+
+/// Filter geometry, predicate and the fixtures expected to match
+fn cases() -> Vec<(Geometry, SpatialPredicate, Vec<u64>)> {
+    use SpatialPredicate::*;
+
+    let inside = Geometry::point(5.0, 5.0);
+    let on_edge = Geometry::point(10.0, 5.0);
+
+    vec![
+        // Features inside the square
+        (square(), Within, vec![SQUARE, INSIDE, LINE_TOUCHING]),
+        (square(), CoveredBy, vec![SQUARE, INSIDE, ON_EDGE, LINE_TOUCHING, LINE_ON_EDGE]),
+        (
+            square(),
+            Intersects,
+            vec![
+                SQUARE,
+                INSIDE,
+                ON_EDGE,
+                LINE_TOUCHING,
+                LINE_ON_EDGE,
+                ADJACENT,
+                CROSSING,
+                OVERLAPPING,
+            ],
+        ),
+        (square(), Contains, vec![SQUARE]),
+        // Features meeting the square only on its boundary
+        (square(), Touches, vec![ON_EDGE, LINE_ON_EDGE, ADJACENT]),
+        // Features passing through the square's interior and out again
+        (square(), Crosses, vec![CROSSING]),
+        (square(), Overlaps, vec![OVERLAPPING]),
+        // Features a line runs through; the line itself intersects but does not cross
+        (crossing(), Crosses, vec![SQUARE, ADJACENT]),
+        (crossing(), Intersects, vec![SQUARE, ADJACENT, CROSSING]),
+        // Features around a point in the interior
+        (inside.clone(), Within, vec![INSIDE]),
+        (inside.clone(), Contains, vec![SQUARE, INSIDE]),
+        (inside, Intersects, vec![SQUARE, INSIDE, LINE_TOUCHING]),
+        // Features around a point on the boundary
+        (on_edge.clone(), Contains, vec![ON_EDGE]),
+        (on_edge.clone(), CoveredBy, vec![ON_EDGE]),
+        (on_edge.clone(), Touches, vec![SQUARE, ADJACENT]),
+        (on_edge, Intersects, vec![SQUARE, ON_EDGE, ADJACENT]),
+    ]
+}
+
+/// Store the fixtures with IDs offset by `base` and check every case
+async fn check_conformance(store: &dyn SpatialStore, base: u64) {
+    let features: Vec<Feature> = fixtures()
+        .into_iter()
+        .map(|(id, geometry)| {
+            Feature::with_geometry(FeatureId(base + id), geometry, HashMap::new(), 4326)
+        })
+        .collect();
+    store.store_features(&features).await.unwrap();
+    check_cases(store, base).await;
+}
+
+/// Check every case against fixtures stored with IDs offset by `base`
+async fn check_cases(store: &dyn SpatialStore, base: u64) {
+    let own = |features: Vec<Feature>| -> BTreeSet<u64> {
+        features
+            .into_iter()
+            .map(|f| f.id.0.wrapping_sub(base))
+            .filter(|id| (SQUARE..=OVERLAPPING).contains(id))
+            .collect()
+    };
+
+    let cache = FilterCache::default();
+    for (geometry, predicate, expected) in cases() {
+        let filter = SpatialFilter::new(predicate).geometry(geometry.clone());
+        let matched = own(store.spatial_query(&filter).await.unwrap());
+
+        assert_eq!(
+            matched,
+            expected.into_iter().collect::<BTreeSet<u64>>(),
+            "{:?} against {:?}",
+            predicate,
+            geometry
+        );
+
+        // Prepared once, then served from the cache
+        for _ in 0..2 {
+            let prepared = cache.get_or_prepare(&filter);
+            let prepared_matched = own(store.spatial_query_prepared(&prepared).await.unwrap());
+            assert_eq!(
+                prepared_matched, matched,
+                "prepared {:?} against {:?}",
+                predicate, geometry
+            );
+        }
+    }
+    assert_eq!(cache.stats().misses, cases().len() as u64);
+}
+
+#[tokio::test]
+async fn test_memory_store_conformance() {
+    check_conformance(&MemorySpatialStore::new(), 0).await;
+}
+
+#[tokio::test]
+async fn test_bundle_store_conformance() {
+    let memory = MemorySpatialStore::new();
+    check_conformance(&memory, 0).await;
+
+    let bundle = OfflineBundle::extract(
+        &memory,
+        &MemoryVectorStore::new(),
+        &MemoryDocumentStore::new(),
+        [-100.0, -100.0, 100.0, 100.0],
+        None,
+    )
+    .await
+    .unwrap();
+    let store = BundleStore::from_bundle(bundle).await.unwrap();
+    assert_eq!(store.index_load().origin, IndexOrigin::Loaded);
+    check_cases(&store, 0).await;
+}
+
+#[tokio::test]
+async fn test_postgres_store_conformance() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL conformance");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's features apart from anything already in the database
+    let base = (chrono::Utc::now().timestamp_micros() as u64) << 8;
+    check_conformance(&store, base).await;
+}
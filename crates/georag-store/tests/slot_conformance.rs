@@ -0,0 +1,57 @@
+//! Slot store conformance across implementations
+//!
+//! A slot store hands out at most `limit` slots of each resource kind,
+//! counts kinds apart, and frees a slot when its guard is dropped.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database; its slots are
+//! taken through two stores, as two processes sharing the database would.
+
+use georag_core::resources::ResourceKind;
+use georag_store::memory::MemorySlotStore;
+use georag_store::ports::SlotStore;
+use std::time::Duration;
+
+async fn check_slots(first: &dyn SlotStore, second: &dyn SlotStore) {
+    let kind = ResourceKind::Export;
+    let a = first.try_acquire_slot(kind, 2).await.unwrap();
+    let b = second.try_acquire_slot(kind, 2).await.unwrap();
+    assert!(a.is_some() && b.is_some());
+    assert!(first.try_acquire_slot(kind, 2).await.unwrap().is_none());
+    assert!(second.try_acquire_slot(kind, 2).await.unwrap().is_none());
+
+    // Other kinds have slots of their own
+    assert!(first.try_acquire_slot(ResourceKind::Build, 1).await.unwrap().is_some());
+
+    drop(a);
+    let c = tokio::time::timeout(
+        Duration::from_secs(5),
+        second.acquire_slot(kind, 2, Duration::from_millis(20)),
+    )
+    .await
+    .expect("a freed slot was not handed out again")
+    .unwrap();
+    assert!(first.try_acquire_slot(kind, 2).await.unwrap().is_none());
+    drop((b, c));
+}
+
+#[tokio::test]
+async fn test_memory_slot_store() {
+    let store = MemorySlotStore::new();
+    check_slots(&store, &store.clone()).await;
+    assert_eq!(store.held(ResourceKind::Export), 0);
+}
+
+#[tokio::test]
+async fn test_postgres_slot_store() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL slots");
+        return;
+    };
+
+    let first = PostgresStore::new(PostgresConfig::new(url.clone()).unwrap()).await.unwrap();
+    let second = PostgresStore::new(PostgresConfig::new(url).unwrap()).await.unwrap();
+    check_slots(&first, &second).await;
+}
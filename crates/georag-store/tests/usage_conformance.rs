@@ -0,0 +1,77 @@
+//! Workspace usage counters across implementations
+//!
+//! Usage starts at zero, adjustments add up, negative adjustments release
+//! usage without going below zero, and counters are kept per workspace.
+//!
+//! The memory store always runs. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use chrono::Utc;
+use georag_core::models::workspace::{DistanceUnit, ValidityMode};
+use georag_core::models::{UsageDelta, WorkspaceConfig, WorkspaceUsage};
+use georag_store::memory::MemoryWorkspaceStore;
+use georag_store::ports::WorkspaceStore;
+
+async fn check_usage_counters(store: &dyn WorkspaceStore, prefix: &str) {
+    let config = WorkspaceConfig {
+        crs: 4326,
+        distance_unit: DistanceUnit::Meters,
+        geometry_validity: ValidityMode::Lenient,
+    };
+    let id = store.create_workspace(&format!("{}-a", prefix), &config).await.unwrap();
+    let other = store.create_workspace(&format!("{}-b", prefix), &config).await.unwrap();
+
+    assert_eq!(store.get_workspace_usage(id).await.unwrap(), WorkspaceUsage::default());
+
+    let added = UsageDelta {
+        datasets: 1,
+        features: 120,
+        chunks: 0,
+        blob_bytes: 2048,
+    };
+    store.adjust_workspace_usage(id, &added).await.unwrap();
+    let usage = store
+        .adjust_workspace_usage(id, &UsageDelta { chunks: 40, ..Default::default() })
+        .await
+        .unwrap();
+    let expected = WorkspaceUsage {
+        datasets: 1,
+        features: 120,
+        chunks: 40,
+        blob_bytes: 2048,
+    };
+    assert_eq!(usage, expected);
+    assert_eq!(store.get_workspace_usage(id).await.unwrap(), expected);
+    assert_eq!(store.get_workspace_usage(other).await.unwrap(), WorkspaceUsage::default());
+
+    // Releasing more than is used stops at zero
+    let usage = store
+        .adjust_workspace_usage(id, &UsageDelta { chunks: -100, ..added.negated() })
+        .await
+        .unwrap();
+    assert_eq!(usage, WorkspaceUsage::default());
+
+    store.delete_workspace(id).await.unwrap();
+    store.delete_workspace(other).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_workspace_usage() {
+    check_usage_counters(&MemoryWorkspaceStore::new(), "usage").await;
+}
+
+#[tokio::test]
+async fn test_postgres_workspace_usage() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL usage");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Workspace names are unique, so runs against one database must not collide
+    check_usage_counters(&store, &format!("usage-{}", Utc::now().timestamp_micros())).await;
+}
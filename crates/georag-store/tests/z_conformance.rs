@@ -0,0 +1,135 @@
+//! Z coordinate conformance across stores
+//!
+//! A feature with a Z ordinate per vertex must come back from any
+//! `SpatialStore` with the same Z, for every geometry type. Predicates stay
+//! two-dimensional: a flat filter finds features at any elevation.
+//!
+//! The memory and bundle stores always run. The PostgreSQL store runs when
+//! `GEORAG_TEST_DATABASE_URL` points at a PostGIS database.
+
+use georag_core::models::{Feature, FeatureId, Geometry, SpatialFilter, SpatialPredicate};
+use georag_store::bundle::{BundleStore, OfflineBundle};
+use georag_store::memory::{MemoryDocumentStore, MemorySpatialStore, MemoryVectorStore};
+use georag_store::ports::SpatialStore;
+use std::collections::HashMap;
+
+/// One feature of every geometry type with IDs offset by `base`, Z rising per vertex
+fn features(base: u64) -> Vec<Feature> {
+    let ring = vec![[106.80, -6.20], [106.81, -6.20], [106.81, -6.19], [106.80, -6.20]];
+    let geometries = [
+        Geometry::point(106.80, -6.20),
+        Geometry::line_string(vec![[106.80, -6.20], [106.82, -6.21]]),
+        Geometry::polygon(vec![ring.clone()]),
+        Geometry::MultiPoint {
+            coordinates: vec![[106.80, -6.20], [106.83, -6.22]],
+        },
+        Geometry::MultiLineString {
+            coordinates: vec![
+                vec![[106.80, -6.20], [106.81, -6.21]],
+                vec![[106.82, -6.22], [106.83, -6.23]],
+            ],
+        },
+        Geometry::MultiPolygon {
+            coordinates: vec![vec![ring.clone()], vec![ring]],
+        },
+    ];
+
+    geometries
+        .into_iter()
+        .enumerate()
+        .map(|(i, geometry)| {
+            let z = (0..geometry.vertex_count()).map(|v| v as f64 * 0.25 - 12.5).collect();
+            Feature::with_geometry(FeatureId(base + i as u64), geometry, HashMap::new(), 4326)
+                .with_z(Some(z))
+        })
+        .collect()
+}
+
+fn check_same(read: &Feature, stored: &Feature) {
+    assert_eq!(read.geometry, stored.geometry, "feature {}", stored.id.0);
+    assert_eq!(read.z_ordinates(), stored.z_ordinates(), "feature {}", stored.id.0);
+}
+
+/// A flat box around all the features
+fn flat_filter() -> SpatialFilter {
+    SpatialFilter::new(SpatialPredicate::Intersects).geometry(Geometry::polygon(vec![vec![
+        [106.7, -6.3],
+        [106.9, -6.3],
+        [106.9, -6.1],
+        [106.7, -6.1],
+        [106.7, -6.3],
+    ]]))
+}
+
+/// Store the features, read each back by ID and find them all with a flat filter
+async fn check_round_trip(store: &dyn SpatialStore, base: u64) {
+    let features = features(base);
+    store.store_features(&features).await.unwrap();
+
+    for feature in &features {
+        let read = store.get_feature(feature.id).await.unwrap().unwrap();
+        check_same(&read, feature);
+    }
+
+    let found: HashMap<u64, Feature> = store
+        .spatial_query(&flat_filter())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|f| (f.id.0, f))
+        .collect();
+    for feature in &features {
+        check_same(&found[&feature.id.0], feature);
+    }
+}
+
+#[tokio::test]
+async fn test_memory_store_keeps_z() {
+    check_round_trip(&MemorySpatialStore::new(), 0).await;
+}
+
+#[tokio::test]
+async fn test_bundle_keeps_z() {
+    let spatial = MemorySpatialStore::new();
+    let features = features(0);
+    spatial.store_features(&features).await.unwrap();
+
+    let bundle = OfflineBundle::extract(
+        &spatial,
+        &MemoryVectorStore::new(),
+        &MemoryDocumentStore::new(),
+        [-180.0, -90.0, 180.0, 90.0],
+        None,
+    )
+    .await
+    .unwrap();
+
+    let path = std::env::temp_dir().join(format!("georag-z-{}.json", std::process::id()));
+    bundle.write_to(&path).unwrap();
+    let store = BundleStore::open(&path).await;
+    std::fs::remove_file(&path).unwrap();
+
+    let store = store.unwrap();
+    for feature in &features {
+        let read = store.get_feature(feature.id).await.unwrap().unwrap();
+        check_same(&read, feature);
+    }
+    assert_eq!(store.spatial_query(&flat_filter()).await.unwrap().len(), features.len());
+}
+
+#[tokio::test]
+async fn test_postgres_store_keeps_z() {
+    use georag_store::postgres::{PostgresConfig, PostgresStore};
+
+    let Ok(url) = std::env::var("GEORAG_TEST_DATABASE_URL") else {
+        eprintln!("GEORAG_TEST_DATABASE_URL not set, skipping PostgreSQL Z conformance");
+        return;
+    };
+
+    let config = PostgresConfig::new(url).unwrap();
+    let store = PostgresStore::with_migrations(config).await.unwrap();
+
+    // Keep this run's features apart from anything already in the database
+    let base = (chrono::Utc::now().timestamp_micros() as u64) << 8;
+    check_round_trip(&store, base).await;
+}